@@ -0,0 +1,47 @@
+package io.github.rtthw.xmpkit
+
+/**
+ * Native methods of libxmpkit, built with the `jni` feature.
+ *
+ * Objects are passed as handles; a handle of 0 is a closed object.
+ */
+internal object Native {
+    init {
+        System.loadLibrary("xmpkit")
+    }
+
+    @JvmStatic external fun registerNamespace(uri: String, prefix: String)
+
+    @JvmStatic external fun metaNew(): Long
+    @JvmStatic external fun metaParse(xmpPacket: String): Long
+    @JvmStatic external fun metaFree(handle: Long)
+    @JvmStatic external fun metaGetProperty(handle: Long, namespace: String, path: String): String?
+    @JvmStatic external fun metaSetProperty(handle: Long, namespace: String, path: String, value: String)
+    @JvmStatic external fun metaHasProperty(handle: Long, namespace: String, path: String): Boolean
+    @JvmStatic external fun metaDeleteProperty(handle: Long, namespace: String, path: String)
+    @JvmStatic external fun metaGetLocalizedText(
+        handle: Long,
+        namespace: String,
+        path: String,
+        genericLang: String,
+        specificLang: String,
+    ): String?
+    @JvmStatic external fun metaSetLocalizedText(
+        handle: Long,
+        namespace: String,
+        path: String,
+        genericLang: String,
+        specificLang: String,
+        value: String,
+    )
+    @JvmStatic external fun metaSerialize(handle: Long): String
+    @JvmStatic external fun metaSerializePacket(handle: Long): String
+
+    @JvmStatic external fun fileNew(): Long
+    @JvmStatic external fun fileFree(handle: Long)
+    @JvmStatic external fun fileFromBytes(handle: Long, data: ByteArray, flags: Int)
+    @JvmStatic external fun fileGetXmp(handle: Long): Long
+    @JvmStatic external fun filePutXmp(handle: Long, meta: Long)
+    @JvmStatic external fun fileRemoveXmp(handle: Long)
+    @JvmStatic external fun fileWriteToBytes(handle: Long): ByteArray
+}
@@ -0,0 +1,46 @@
+package io.github.rtthw.xmpkit
+
+/**
+ * Options for reading XMP metadata from files.
+ *
+ * ```kotlin
+ * val file = XmpFile.fromBytes(bytes, ReadOptions().onlyXmp().usePacketScanning())
+ * ```
+ */
+class ReadOptions {
+    internal var flags = 0
+        private set
+
+    /** Only the XMP is wanted (allows optimizations). */
+    fun onlyXmp() = set(ONLY_XMP)
+
+    /** Be strict about only attempting to use the designated file handler. */
+    fun strict() = set(STRICT)
+
+    /** Require the use of a smart handler. */
+    fun useSmartHandler() = set(USE_SMART_HANDLER)
+
+    /** Force packet scanning (do not use smart handler). */
+    fun usePacketScanning() = set(USE_PACKET_SCANNING)
+
+    /** Only packet scan files "known" to need scanning. */
+    fun limitedScanning() = set(LIMITED_SCANNING)
+
+    /** Attempt to repair a damaged file. */
+    fun repairFile() = set(REPAIR_FILE)
+
+    private fun set(flag: Int): ReadOptions {
+        flags = flags or flag
+        return this
+    }
+
+    private companion object {
+        // Decoded by src/jni/file.rs
+        const val ONLY_XMP = 0x1
+        const val STRICT = 0x2
+        const val USE_SMART_HANDLER = 0x4
+        const val USE_PACKET_SCANNING = 0x8
+        const val LIMITED_SCANNING = 0x10
+        const val REPAIR_FILE = 0x20
+    }
+}
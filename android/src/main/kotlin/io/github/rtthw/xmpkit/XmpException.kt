@@ -0,0 +1,47 @@
+package io.github.rtthw.xmpkit
+
+/**
+ * Error of an XMP operation, with one subclass per kind of error.
+ *
+ * ```kotlin
+ * try {
+ *     XmpMeta.parse(packet)
+ * } catch (e: XmpException.ParseError) {
+ *     Log.w(TAG, "Invalid XMP: ${e.message}")
+ * }
+ * ```
+ */
+sealed class XmpException(message: String) : Exception(message) {
+    /** An argument is invalid */
+    class BadParam(message: String) : XmpException(message)
+
+    /** A value is invalid */
+    class BadValue(message: String) : XmpException(message)
+
+    /** Unknown namespace or invalid schema */
+    class BadSchema(message: String) : XmpException(message)
+
+    /** Invalid property path */
+    class BadXPath(message: String) : XmpException(message)
+
+    /** The XMP or the file could not be parsed */
+    class ParseError(message: String) : XmpException(message)
+
+    /** The XMP could not be serialized */
+    class SerializationError(message: String) : XmpException(message)
+
+    /** Reading or writing data failed */
+    class IoError(message: String) : XmpException(message)
+
+    /** Internal error */
+    class InternalError(message: String) : XmpException(message)
+
+    /** The requested data was not found */
+    class NotFound(message: String) : XmpException(message)
+
+    /** The operation is not supported, for example for the file format */
+    class NotSupported(message: String) : XmpException(message)
+
+    /** The operation was cancelled */
+    class Cancelled(message: String) : XmpException(message)
+}
@@ -0,0 +1,67 @@
+package io.github.rtthw.xmpkit
+
+/**
+ * File whose XMP metadata is read and written, mirroring `XmpFile` in Rust.
+ *
+ * Files are read from and written to byte arrays, for example from the
+ * streams of a `ContentResolver`:
+ *
+ * ```kotlin
+ * val bytes = resolver.openInputStream(uri)!!.use { it.readBytes() }
+ * XmpFile.fromBytes(bytes).use { file ->
+ *     val meta = file.getXmp() ?: XmpMeta()
+ *     meta.setProperty(XmpMeta.NS_XMP, "Rating", "5")
+ *     file.putXmp(meta)
+ *     resolver.openOutputStream(uri, "wt")!!.use { it.write(file.writeToBytes()) }
+ * }
+ * ```
+ */
+class XmpFile : AutoCloseable {
+    private var handle = Native.fileNew()
+
+    /** Load the metadata of a file. */
+    @Synchronized
+    @JvmOverloads
+    fun loadBytes(data: ByteArray, options: ReadOptions = ReadOptions()) =
+        Native.fileFromBytes(handle, data, options.flags)
+
+    /** Get the metadata, or null if the file has none. */
+    @Synchronized
+    fun getXmp(): XmpMeta? = Native.fileGetXmp(handle).takeIf { it != 0L }?.let(::XmpMeta)
+
+    /** Replace the metadata, which can still be used and closed. */
+    @Synchronized
+    fun putXmp(meta: XmpMeta) = synchronized(meta) { Native.filePutXmp(handle, meta.handle) }
+
+    /** Remove the metadata from the file written by [writeToBytes]. */
+    @Synchronized
+    fun removeXmp() = Native.fileRemoveXmp(handle)
+
+    /** Write the file with its metadata. */
+    @Synchronized
+    fun writeToBytes(): ByteArray = Native.fileWriteToBytes(handle)
+
+    /** Free the native memory; the file can't be used afterwards. */
+    @Synchronized
+    override fun close() {
+        Native.fileFree(handle)
+        handle = 0
+    }
+
+    protected fun finalize() = close()
+
+    companion object {
+        /** Load the metadata of a file. */
+        @JvmStatic
+        @JvmOverloads
+        fun fromBytes(data: ByteArray, options: ReadOptions = ReadOptions()) =
+            XmpFile().apply {
+                try {
+                    loadBytes(data, options)
+                } catch (e: XmpException) {
+                    close()
+                    throw e
+                }
+            }
+    }
+}
@@ -0,0 +1,98 @@
+package io.github.rtthw.xmpkit
+
+/**
+ * XMP metadata, mirroring `XmpMeta` in Rust.
+ *
+ * Close it to free its native memory, or let the garbage collector do it.
+ * The methods are synchronized, so that a metadata object can be shared by
+ * threads.
+ *
+ * ```kotlin
+ * XmpMeta().use { meta ->
+ *     meta.setProperty(XmpMeta.NS_XMP, "CreatorTool", "MyApp")
+ *     val packet = meta.serializePacket()
+ * }
+ * ```
+ */
+class XmpMeta internal constructor(handle: Long) : AutoCloseable {
+    internal var handle = handle
+        private set
+
+    /** Create empty metadata. */
+    constructor() : this(Native.metaNew())
+
+    /** Get the value of a simple property, or null if it doesn't exist. */
+    @Synchronized
+    fun getProperty(namespace: String, path: String): String? =
+        Native.metaGetProperty(handle, namespace, path)
+
+    /** Set the value of a simple property, creating it if needed. */
+    @Synchronized
+    fun setProperty(namespace: String, path: String, value: String) =
+        Native.metaSetProperty(handle, namespace, path, value)
+
+    /** Check if a property exists. */
+    @Synchronized
+    fun hasProperty(namespace: String, path: String): Boolean =
+        Native.metaHasProperty(handle, namespace, path)
+
+    /** Delete a property and its children. */
+    @Synchronized
+    fun deleteProperty(namespace: String, path: String) =
+        Native.metaDeleteProperty(handle, namespace, path)
+
+    /**
+     * Get a localized text in the language that best matches [specificLang]
+     * (such as `en-US`) or [genericLang] (such as `en`), or null.
+     */
+    @Synchronized
+    fun getLocalizedText(
+        namespace: String,
+        path: String,
+        genericLang: String,
+        specificLang: String,
+    ): String? = Native.metaGetLocalizedText(handle, namespace, path, genericLang, specificLang)
+
+    /** Set a localized text, creating the language alternative if needed. */
+    @Synchronized
+    fun setLocalizedText(
+        namespace: String,
+        path: String,
+        genericLang: String,
+        specificLang: String,
+        value: String,
+    ) = Native.metaSetLocalizedText(handle, namespace, path, genericLang, specificLang, value)
+
+    /** Serialize to RDF/XML. */
+    @Synchronized
+    fun serialize(): String = Native.metaSerialize(handle)
+
+    /** Serialize to an XMP packet. */
+    @Synchronized
+    fun serializePacket(): String = Native.metaSerializePacket(handle)
+
+    /** Free the native memory; the metadata can't be used afterwards. */
+    @Synchronized
+    override fun close() {
+        Native.metaFree(handle)
+        handle = 0
+    }
+
+    protected fun finalize() = close()
+
+    companion object {
+        /** Dublin Core namespace */
+        const val NS_DC = "http://purl.org/dc/elements/1.1/"
+
+        /** XMP basic namespace */
+        const val NS_XMP = "http://ns.adobe.com/xap/1.0/"
+
+        /** Parse an XMP packet or RDF/XML document. */
+        @JvmStatic
+        fun parse(xmpPacket: String) = XmpMeta(Native.metaParse(xmpPacket))
+
+        /** Register a namespace URI with a preferred prefix. */
+        @JvmStatic
+        fun registerNamespace(uri: String, prefix: String) = Native.registerNamespace(uri, prefix)
+    }
+}
@@ -1,5 +1,6 @@
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
 use std::hint::black_box;
+use std::io::BufReader;
 use xmpkit::XmpMeta;
 
 // Simple XMP packet with minimal properties
@@ -110,6 +111,14 @@ fn bench_parse_complex(c: &mut Criterion) {
     });
 }
 
+fn bench_parse_borrowed(c: &mut Criterion) {
+    c.bench_function("parse_borrowed_complex", |b| {
+        b.iter(|| {
+            let _meta = XmpMeta::parse_borrowed(black_box(COMPLEX_XMP)).unwrap();
+        });
+    });
+}
+
 fn bench_parse_from_str_trait(c: &mut Criterion) {
     c.bench_function("parse_from_str_trait", |b| {
         b.iter(|| {
@@ -214,6 +223,67 @@ fn bench_parse_rdf_only(c: &mut Criterion) {
     });
 }
 
+/// Build a multi-megabyte packet, like the face regions and develop
+/// settings some cameras and editors write
+fn huge_xmp(regions: usize) -> String {
+    let mut xmp = String::from(
+        r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+         xmlns:mwg-rs="http://www.metadataworkinggroup.com/schemas/regions/"
+         xmlns:stArea="http://ns.adobe.com/xmp/sType/Area#">
+  <rdf:Description rdf:about="">
+    <mwg-rs:Regions rdf:parseType="Resource">
+      <mwg-rs:RegionList>
+        <rdf:Bag>
+"#,
+    );
+    for i in 0..regions {
+        xmp.push_str(&format!(
+            r#"          <rdf:li rdf:parseType="Resource">
+            <mwg-rs:Name>Person {i}</mwg-rs:Name>
+            <mwg-rs:Type>Face</mwg-rs:Type>
+            <mwg-rs:Area stArea:x="0.{i}" stArea:y="0.5" stArea:w="0.1" stArea:h="0.1" stArea:unit="normalized"/>
+          </rdf:li>
+"#
+        ));
+    }
+    xmp.push_str(
+        r#"        </rdf:Bag>
+      </mwg-rs:RegionList>
+    </mwg-rs:Regions>
+  </rdf:Description>
+</rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#,
+    );
+    xmp
+}
+
+fn bench_parse_huge(c: &mut Criterion) {
+    let xmp = huge_xmp(10_000);
+    let mut group = c.benchmark_group("parse_huge");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(xmp.len() as u64));
+    group.bench_function("parse", |b| {
+        b.iter(|| {
+            let _meta = XmpMeta::parse(black_box(&xmp)).unwrap();
+        });
+    });
+    group.bench_function("parse_from_reader", |b| {
+        b.iter(|| {
+            let reader = BufReader::new(black_box(xmp.as_bytes()));
+            let _meta = XmpMeta::parse_from_reader(reader).unwrap();
+        });
+    });
+    group.bench_function("parse_borrowed", |b| {
+        b.iter(|| {
+            let _meta = XmpMeta::parse_borrowed(black_box(&xmp)).unwrap();
+        });
+    });
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_parse_simple,
@@ -221,6 +291,8 @@ criterion_group!(
     bench_parse_complex,
     bench_parse_large,
     bench_parse_rdf_only,
-    bench_parse_from_str_trait
+    bench_parse_from_str_trait,
+    bench_parse_borrowed,
+    bench_parse_huge
 );
 criterion_main!(benches);
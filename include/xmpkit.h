@@ -0,0 +1,107 @@
+/*
+ * C API of xmpkit, built with the `capi` feature.
+ *
+ * Functions that can fail return XMP_OK or an XMP_ERR_* code, with the
+ * numbers of the Adobe XMP Toolkit's XMP_Error codes; xmp_last_error()
+ * returns the message of the last error of the calling thread.
+ *
+ * Strings passed in are borrowed, NUL-terminated UTF-8. Strings and byte
+ * buffers returned through out parameters belong to the caller, who frees
+ * them with xmp_string_free() and xmp_bytes_free(); out parameters are
+ * only written on success. Null pointers are rejected with
+ * XMP_ERR_BAD_PARAM, except by the _free functions, which ignore them.
+ *
+ * Keep in sync with src/capi.
+ */
+
+#ifndef XMPKIT_H
+#define XMPKIT_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Error codes */
+#define XMP_OK 0
+#define XMP_ERR_NOT_FOUND 2
+#define XMP_ERR_BAD_PARAM 4
+#define XMP_ERR_BAD_VALUE 5
+#define XMP_ERR_UNIMPLEMENTED 8
+#define XMP_ERR_INTERNAL 9
+#define XMP_ERR_CANCELLED 16
+#define XMP_ERR_BAD_SCHEMA 101
+#define XMP_ERR_BAD_XPATH 102
+#define XMP_ERR_BAD_PARSE 106
+#define XMP_ERR_BAD_SERIALIZE 107
+#define XMP_ERR_IO 114
+
+/* Flags of xmp_file_open() and xmp_file_open_bytes() */
+#define XMP_OPEN_FOR_READ 0x1
+#define XMP_OPEN_FOR_UPDATE 0x2
+#define XMP_OPEN_ONLY_XMP 0x4
+#define XMP_OPEN_STRICTLY 0x10
+#define XMP_OPEN_USE_SMART_HANDLER 0x20
+#define XMP_OPEN_USE_PACKET_SCANNING 0x40
+#define XMP_OPEN_LIMITED_SCANNING 0x80
+#define XMP_OPEN_REPAIR_FILE 0x100
+
+/* Flags of xmp_file_save() */
+#define XMP_SAVE_ATOMIC 0x1
+#define XMP_SAVE_PREFER_IN_PLACE 0x2
+#define XMP_SAVE_KEEP_MODIFICATION_TIME 0x4
+#define XMP_SAVE_STRIP_ALL_METADATA 0x8
+
+/* XMP metadata */
+typedef struct XmpMeta XmpMeta;
+
+/* File whose metadata is read and written */
+typedef struct XmpFile XmpFile;
+
+/* General */
+const char *xmp_version(void);
+const char *xmp_last_error(void);
+void xmp_string_free(char *string);
+void xmp_bytes_free(uint8_t *data, size_t len);
+int xmp_register_namespace(const char *uri, const char *prefix);
+
+/* Metadata */
+XmpMeta *xmp_meta_new(void);
+int xmp_meta_parse(const char *xml, XmpMeta **meta);
+void xmp_meta_free(XmpMeta *meta);
+int xmp_meta_get_property(const XmpMeta *meta, const char *namespace_, const char *path,
+                          char **value);
+int xmp_meta_set_property(XmpMeta *meta, const char *namespace_, const char *path,
+                          const char *value);
+int xmp_meta_delete_property(XmpMeta *meta, const char *namespace_, const char *path);
+int xmp_meta_count_array_items(const XmpMeta *meta, const char *namespace_, const char *path,
+                               size_t *count);
+int xmp_meta_append_array_item(XmpMeta *meta, const char *namespace_, const char *path,
+                               const char *value);
+int xmp_meta_get_localized_text(const XmpMeta *meta, const char *namespace_, const char *path,
+                                const char *generic_lang, const char *specific_lang,
+                                char **actual_lang, char **value);
+int xmp_meta_set_localized_text(XmpMeta *meta, const char *namespace_, const char *path,
+                                const char *generic_lang, const char *specific_lang,
+                                const char *value);
+int xmp_meta_serialize(const XmpMeta *meta, char **xml);
+
+/* Files */
+XmpFile *xmp_file_new(void);
+void xmp_file_free(XmpFile *file);
+int xmp_file_open(XmpFile *file, const char *path, uint32_t flags);
+int xmp_file_open_bytes(XmpFile *file, const uint8_t *data, size_t len, uint32_t flags);
+int xmp_file_get_xmp(const XmpFile *file, XmpMeta **meta);
+int xmp_file_put_xmp(XmpFile *file, const XmpMeta *meta);
+int xmp_file_remove_xmp(XmpFile *file);
+int xmp_file_save(const XmpFile *file, const char *path, uint32_t flags);
+int xmp_file_write_bytes(const XmpFile *file, uint8_t **data, size_t *len);
+int xmp_file_close(XmpFile *file);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* XMPKIT_H */
@@ -0,0 +1,352 @@
+//! Command line tool to read and edit the XMP metadata of files
+//!
+//! Built with the `cli` feature, e.g. `cargo install xmpkit --features cli`:
+//!
+//! ```text
+//! xmpkit get photo.jpg dc:title
+//! xmpkit set --recursive photos xmp:Rating 5
+//! xmpkit dump --json photo.jpg
+//! xmpkit strip 'photos/*.jpg'
+//! xmpkit copy --from a.jpg --to b.png
+//! ```
+//!
+//! Files can be given as paths, directories, or glob patterns on the file
+//! name (`*` and `?`); directories are searched recursively with
+//! `--recursive`.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use xmpkit::files::batch::{list_files, BatchOptions};
+use xmpkit::{
+    get_global_namespace_prefix, get_global_namespace_uri, IterOptions, ReadOptions, SaveOptions,
+    XmpError, XmpFile, XmpMeta, XmpResult, XmpValue,
+};
+
+fn main() -> ExitCode {
+    let matches = cli().get_matches();
+    let (command, args) = matches.subcommand().expect("a subcommand is required");
+    let files = match find_files(args) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("xmpkit: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let source = match command {
+        "copy" => match read_source(args) {
+            Ok(meta) => Some(meta),
+            Err(e) => {
+                eprintln!("xmpkit: {}", e);
+                return ExitCode::FAILURE;
+            }
+        },
+        _ => None,
+    };
+
+    let mut success = true;
+    for path in &files {
+        let result = match command {
+            "get" => get(path, args, files.len() > 1),
+            "set" => set(path, args),
+            "dump" => dump(path, args, files.len() > 1),
+            "strip" => strip(path, args),
+            "copy" => copy(path, source.as_ref().expect("the source is read for copy")),
+            _ => unreachable!("unknown subcommand {}", command),
+        };
+        if let Err(e) = result {
+            eprintln!("xmpkit: {}: {}", path.display(), e);
+            success = false;
+        }
+    }
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Define the command line
+fn cli() -> Command {
+    let file = |name: &'static str, flag: bool| {
+        let arg = Arg::new(name)
+            .value_name("FILE")
+            .required(true)
+            .help("File, directory or glob pattern such as 'photos/*.jpg'");
+        if flag {
+            arg.long(name)
+        } else {
+            arg
+        }
+    };
+    let property = || {
+        Arg::new("property")
+            .required(true)
+            .help("Property, such as dc:title or xmp:Rating")
+    };
+    let lang = || {
+        Arg::new("lang")
+            .long("lang")
+            .value_name("LANG")
+            .help("Language of a localized text, such as x-default or en-US")
+    };
+    let recursive = || {
+        Arg::new("recursive")
+            .short('r')
+            .long("recursive")
+            .action(ArgAction::SetTrue)
+            .help("Process the files of subdirectories too")
+    };
+
+    Command::new("xmpkit")
+        .about("Read and edit the XMP metadata of files")
+        .version(env!("CARGO_PKG_VERSION"))
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("get")
+                .about("Print the value of a property")
+                .arg(file("file", false))
+                .arg(property())
+                .arg(lang())
+                .arg(recursive()),
+        )
+        .subcommand(
+            Command::new("set")
+                .about("Set the value of a property")
+                .arg(file("file", false))
+                .arg(property())
+                .arg(Arg::new("value").required(true).help("The new value"))
+                .arg(lang())
+                .arg(recursive()),
+        )
+        .subcommand(
+            Command::new("dump")
+                .about("Print all the XMP metadata")
+                .arg(file("file", false))
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .action(ArgAction::SetTrue)
+                        .help("Print JSON instead of RDF/XML"),
+                )
+                .arg(recursive()),
+        )
+        .subcommand(
+            Command::new("strip")
+                .about("Remove the XMP metadata")
+                .arg(file("file", false))
+                .arg(
+                    Arg::new("all")
+                        .long("all")
+                        .action(ArgAction::SetTrue)
+                        .help("Also remove the native metadata, such as Exif and IPTC"),
+                )
+                .arg(recursive()),
+        )
+        .subcommand(
+            Command::new("copy")
+                .about("Copy the XMP metadata of a file to other files")
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .value_name("FILE")
+                        .required(true)
+                        .help("File to copy the metadata from"),
+                )
+                .arg(file("to", true))
+                .arg(recursive()),
+        )
+}
+
+/// Find the files to process, from the `file` or `to` argument
+fn find_files(args: &ArgMatches) -> XmpResult<Vec<PathBuf>> {
+    let name = if args.try_contains_id("to").unwrap_or(false) {
+        "to"
+    } else {
+        "file"
+    };
+    let pattern = args.get_one::<String>(name).expect("the file is required");
+    let mut options = BatchOptions::default();
+    if args.get_flag("recursive") {
+        options = options.recursive();
+    }
+
+    let path = Path::new(pattern);
+    if path.is_dir() {
+        return list_files(path, &options);
+    }
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if file_name.contains(['*', '?']) {
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        return list_files(dir, &options.include(file_name));
+    }
+    Ok(vec![path.to_path_buf()])
+}
+
+/// Split a property such as `dc:title` into a namespace URI and a path
+fn parse_property(args: &ArgMatches) -> XmpResult<(String, &str)> {
+    let property = args
+        .get_one::<String>("property")
+        .expect("the property is required");
+    let (prefix, path) = property.split_once(':').ok_or_else(|| {
+        XmpError::BadParam(format!(
+            "Expected a property with a prefix, such as dc:title, not {}",
+            property
+        ))
+    })?;
+    let uri = get_global_namespace_uri(prefix)
+        .ok_or_else(|| XmpError::BadSchema(format!("Unknown namespace prefix {}", prefix)))?;
+    Ok((uri, path))
+}
+
+/// Open a file and get its metadata
+fn open(path: &Path) -> XmpResult<(XmpFile, Option<XmpMeta>)> {
+    let mut file = XmpFile::new();
+    file.open_with(path, ReadOptions::default())?;
+    let meta = file.get_xmp().cloned();
+    Ok((file, meta))
+}
+
+/// Error for a file without metadata
+fn no_xmp() -> XmpError {
+    XmpError::NotFound("No XMP metadata".to_string())
+}
+
+/// Print the value of a property
+///
+/// Arrays and structures are printed with one line per item or field.
+fn get(path: &Path, args: &ArgMatches, many: bool) -> XmpResult<()> {
+    let (uri, property) = parse_property(args)?;
+    let (_, meta) = open(path)?;
+    let meta = meta.ok_or_else(no_xmp)?;
+
+    let lines = match args.get_one::<String>("lang") {
+        Some(lang) => meta
+            .get_localized_text(&uri, property, "", lang)
+            .map(|(value, _)| vec![value])
+            .unwrap_or_default(),
+        None => property_lines(&meta, &uri, property),
+    };
+    if lines.is_empty() {
+        return Err(XmpError::NotFound(format!("No {} property", property)));
+    }
+    if many {
+        println!("{}:", path.display());
+    }
+    let pad = if many { "  " } else { "" };
+    for line in lines {
+        println!("{}{}", pad, line);
+    }
+    Ok(())
+}
+
+/// Get the values of a property and of its items and fields
+fn property_lines(meta: &XmpMeta, uri: &str, property: &str) -> Vec<String> {
+    let Some(prefix) = get_global_namespace_prefix(uri) else {
+        return Vec::new();
+    };
+    let name = format!("{}:{}", prefix, property);
+    let options = IterOptions::default()
+        .namespace(uri)
+        .just_leaf_nodes()
+        .omit_qualifiers();
+    meta.iter_with(options)
+        .filter_map(|item| {
+            let nested = item.path.strip_prefix(&name)?;
+            if !(nested.is_empty() || nested.starts_with(['[', '/'])) {
+                return None;
+            }
+            let value = item.value?;
+            // Items of arrays of simple values are printed alone
+            let is_item = nested.starts_with('[') && nested.ends_with(']') && !nested.contains('/');
+            Some(if nested.is_empty() || is_item {
+                value
+            } else {
+                format!("{}: {}", nested.trim_start_matches('/'), value)
+            })
+        })
+        .collect()
+}
+
+/// Set the value of a property
+///
+/// Localized texts are set in the default language unless `--lang` is
+/// given, so that setting `dc:title` keeps it a language alternative.
+fn set(path: &Path, args: &ArgMatches) -> XmpResult<()> {
+    let (uri, property) = parse_property(args)?;
+    let value = args
+        .get_one::<String>("value")
+        .expect("the value is required");
+    let (mut file, meta) = open(path)?;
+    let mut meta = meta.unwrap_or_default();
+
+    let lang = args
+        .get_one::<String>("lang")
+        .map(String::as_str)
+        .or_else(|| {
+            meta.get_localized_text(&uri, property, "", "x-default")
+                .map(|_| "x-default")
+        });
+    match lang {
+        Some(lang) => meta.set_localized_text(&uri, property, "", lang, value)?,
+        None => meta.set_property(&uri, property, XmpValue::String(value.clone()))?,
+    }
+    file.put_xmp(meta);
+    save(&file, path, SaveOptions::default())
+}
+
+/// Print all the metadata of a file
+fn dump(path: &Path, args: &ArgMatches, many: bool) -> XmpResult<()> {
+    let (_, meta) = open(path)?;
+    let meta = meta.ok_or_else(no_xmp)?;
+    let text = if args.get_flag("json") {
+        meta.to_json()?
+    } else {
+        meta.serialize()?
+    };
+    if many {
+        println!("==> {} <==", path.display());
+    }
+    println!("{}", text.trim_end());
+    Ok(())
+}
+
+/// Remove the metadata of a file
+fn strip(path: &Path, args: &ArgMatches) -> XmpResult<()> {
+    let (mut file, _) = open(path)?;
+    file.remove_xmp();
+    let mut options = SaveOptions::default();
+    if args.get_flag("all") {
+        options = options.strip_all_metadata();
+    }
+    save(&file, path, options)
+}
+
+/// Read the metadata of the `--from` file
+fn read_source(args: &ArgMatches) -> XmpResult<XmpMeta> {
+    let from = Path::new(args.get_one::<String>("from").expect("from is required"));
+    let (_, meta) = open(from)?;
+    meta.ok_or_else(|| XmpError::NotFound(format!("No XMP metadata in {}", from.display())))
+}
+
+/// Replace the metadata of a file
+fn copy(path: &Path, source: &XmpMeta) -> XmpResult<()> {
+    let (mut file, _) = open(path)?;
+    file.put_xmp(source.clone());
+    save(&file, path, SaveOptions::default())
+}
+
+/// Save a file through a temporary file, so that it is left untouched if
+/// it can't be written
+fn save(file: &XmpFile, path: &Path, options: SaveOptions) -> XmpResult<()> {
+    file.save_with(path, options.atomic())?;
+    Ok(())
+}
@@ -0,0 +1,118 @@
+//! Error codes of the C API
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CString};
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::core::error::{XmpError, XmpResult};
+
+/// The call succeeded
+pub const XMP_OK: c_int = 0;
+/// The property, metadata or file was not found (`kXMPErr_Unavailable`)
+pub const XMP_ERR_NOT_FOUND: c_int = 2;
+/// An argument is invalid, such as a null pointer (`kXMPErr_BadParam`)
+pub const XMP_ERR_BAD_PARAM: c_int = 4;
+/// A value is invalid (`kXMPErr_BadValue`)
+pub const XMP_ERR_BAD_VALUE: c_int = 5;
+/// The operation is not supported (`kXMPErr_Unimplemented`)
+pub const XMP_ERR_UNIMPLEMENTED: c_int = 8;
+/// Internal error, including panics (`kXMPErr_InternalFailure`)
+pub const XMP_ERR_INTERNAL: c_int = 9;
+/// The operation was cancelled (`kXMPErr_ProgressAbort`)
+pub const XMP_ERR_CANCELLED: c_int = 16;
+/// Unknown namespace or invalid schema (`kXMPErr_BadSchema`)
+pub const XMP_ERR_BAD_SCHEMA: c_int = 101;
+/// Invalid property path (`kXMPErr_BadXPath`)
+pub const XMP_ERR_BAD_XPATH: c_int = 102;
+/// The XMP could not be parsed (`kXMPErr_BadParse`)
+pub const XMP_ERR_BAD_PARSE: c_int = 106;
+/// The XMP could not be serialized (`kXMPErr_BadSerialize`)
+pub const XMP_ERR_BAD_SERIALIZE: c_int = 107;
+/// A file could not be read or written (`kXMPErr_ReadError`)
+pub const XMP_ERR_IO: c_int = 114;
+
+thread_local! {
+    /// Message of the last error of the thread
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Get the message of the last error of the calling thread
+///
+/// Returns null if no call failed on the thread yet. The string stays
+/// valid until the next failing call on the thread, and must not be freed.
+#[no_mangle]
+pub extern "C" fn xmp_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Get the code of an error
+fn error_code(error: &XmpError) -> c_int {
+    match error {
+        XmpError::BadParam(_) => XMP_ERR_BAD_PARAM,
+        XmpError::BadValue(_) => XMP_ERR_BAD_VALUE,
+        XmpError::BadSchema(_) => XMP_ERR_BAD_SCHEMA,
+        XmpError::BadXPath(_) => XMP_ERR_BAD_XPATH,
+        XmpError::ParseError(_) => XMP_ERR_BAD_PARSE,
+        XmpError::SerializationError(_) => XMP_ERR_BAD_SERIALIZE,
+        XmpError::IoError(_) => XMP_ERR_IO,
+        XmpError::InternalError(_) => XMP_ERR_INTERNAL,
+        XmpError::NotFound(_) => XMP_ERR_NOT_FOUND,
+        XmpError::NotSupported(_) => XMP_ERR_UNIMPLEMENTED,
+        XmpError::Cancelled => XMP_ERR_CANCELLED,
+    }
+}
+
+/// Record the message of the last error of the thread
+fn set_last_error(message: String) {
+    // Messages can't contain NUL characters, which would end them early
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Run the body of a C function, turning its result into an error code
+pub(super) fn call(f: impl FnOnce() -> XmpResult<()>) -> c_int {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => XMP_OK,
+        Ok(Err(error)) => {
+            set_last_error(error.to_string());
+            error_code(&error)
+        }
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            set_last_error(format!("Internal error: {}", message));
+            XMP_ERR_INTERNAL
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_call_reports_errors() {
+        assert_eq!(call(|| Ok(())), XMP_OK);
+        assert_eq!(
+            call(|| Err(XmpError::NotFound("No XMP metadata".to_string()))),
+            XMP_ERR_NOT_FOUND
+        );
+        let message = unsafe { CStr::from_ptr(xmp_last_error()) };
+        assert_eq!(
+            message.to_str().unwrap(),
+            "Resource not found: No XMP metadata"
+        );
+
+        assert_eq!(call(|| panic!("broken")), XMP_ERR_INTERNAL);
+        let message = unsafe { CStr::from_ptr(xmp_last_error()) };
+        assert_eq!(message.to_str().unwrap(), "Internal error: broken");
+    }
+}
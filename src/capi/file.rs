@@ -0,0 +1,350 @@
+//! C API for files
+
+use std::ffi::{c_char, c_int};
+
+use super::{call, mut_arg, out_arg, ref_arg, str_arg};
+use crate::core::error::{XmpError, XmpResult};
+use crate::{ReadOptions, SaveOptions, XmpFile, XmpMeta};
+
+/// Open the file for reading only (`kXMPFiles_OpenForRead`)
+pub const XMP_OPEN_FOR_READ: u32 = 0x1;
+/// Open the file for update, written by [`xmp_file_close`]
+/// (`kXMPFiles_OpenForUpdate`)
+pub const XMP_OPEN_FOR_UPDATE: u32 = 0x2;
+/// Only the XMP is needed, not the native metadata
+/// (`kXMPFiles_OpenOnlyXMP`)
+pub const XMP_OPEN_ONLY_XMP: u32 = 0x4;
+/// Fail if the format has no handler (`kXMPFiles_OpenStrictly`)
+pub const XMP_OPEN_STRICTLY: u32 = 0x10;
+/// Require a format handler, without packet scanning
+/// (`kXMPFiles_OpenUseSmartHandler`)
+pub const XMP_OPEN_USE_SMART_HANDLER: u32 = 0x20;
+/// Scan the file for a packet (`kXMPFiles_OpenUsePacketScanning`)
+pub const XMP_OPEN_USE_PACKET_SCANNING: u32 = 0x40;
+/// Only scan the start and end of the file
+/// (`kXMPFiles_OpenLimitedScanning`)
+pub const XMP_OPEN_LIMITED_SCANNING: u32 = 0x80;
+/// Repair damaged files (`kXMPFiles_OpenRepairFile`)
+pub const XMP_OPEN_REPAIR_FILE: u32 = 0x100;
+
+/// Write a new file and rename it over the target, so that the target is
+/// left untouched if saving fails (`kXMPFiles_UpdateSafely`)
+pub const XMP_SAVE_ATOMIC: u32 = 0x1;
+/// Update the packet in place when it fits
+pub const XMP_SAVE_PREFER_IN_PLACE: u32 = 0x2;
+/// Keep the modification time of the target
+pub const XMP_SAVE_KEEP_MODIFICATION_TIME: u32 = 0x4;
+/// Also remove the native metadata, such as Exif and IPTC
+pub const XMP_SAVE_STRIP_ALL_METADATA: u32 = 0x8;
+
+/// A flag and the builder method of the options it sets
+type FlagBuilder<T> = (u32, fn(T) -> T);
+
+/// Convert open flags to read options
+fn read_options(flags: u32) -> XmpResult<ReadOptions> {
+    let builders: [FlagBuilder<ReadOptions>; 8] = [
+        (XMP_OPEN_FOR_READ, ReadOptions::for_read),
+        (XMP_OPEN_FOR_UPDATE, ReadOptions::for_update),
+        (XMP_OPEN_ONLY_XMP, ReadOptions::only_xmp),
+        (XMP_OPEN_STRICTLY, ReadOptions::strict),
+        (XMP_OPEN_USE_SMART_HANDLER, ReadOptions::use_smart_handler),
+        (
+            XMP_OPEN_USE_PACKET_SCANNING,
+            ReadOptions::use_packet_scanning,
+        ),
+        (XMP_OPEN_LIMITED_SCANNING, ReadOptions::limited_scanning),
+        (XMP_OPEN_REPAIR_FILE, ReadOptions::repair_file),
+    ];
+    apply_flags(flags, ReadOptions::default(), &builders)
+}
+
+/// Convert save flags to save options
+fn save_options(flags: u32) -> XmpResult<SaveOptions> {
+    let builders: [FlagBuilder<SaveOptions>; 4] = [
+        (XMP_SAVE_ATOMIC, SaveOptions::atomic),
+        (XMP_SAVE_PREFER_IN_PLACE, SaveOptions::prefer_in_place),
+        (
+            XMP_SAVE_KEEP_MODIFICATION_TIME,
+            SaveOptions::keep_modification_time,
+        ),
+        (XMP_SAVE_STRIP_ALL_METADATA, SaveOptions::strip_all_metadata),
+    ];
+    apply_flags(flags, SaveOptions::default(), &builders)
+}
+
+/// Apply the builder of each flag set in `flags`
+///
+/// Unknown flags are rejected, so that they can be given a meaning later.
+fn apply_flags<T>(flags: u32, mut options: T, builders: &[FlagBuilder<T>]) -> XmpResult<T> {
+    let mut known = 0;
+    for &(flag, builder) in builders {
+        known |= flag;
+        if flags & flag != 0 {
+            options = builder(options);
+        }
+    }
+    if flags & !known != 0 {
+        return Err(XmpError::BadParam(format!(
+            "Unknown flags 0x{:x}",
+            flags & !known
+        )));
+    }
+    Ok(options)
+}
+
+/// Create a file object, to open with [`xmp_file_open`] or
+/// [`xmp_file_open_bytes`]
+///
+/// Free it with [`xmp_file_free`].
+#[no_mangle]
+pub extern "C" fn xmp_file_new() -> *mut XmpFile {
+    Box::into_raw(Box::new(XmpFile::new()))
+}
+
+/// Free a file object, without writing it
+///
+/// Call [`xmp_file_close`] first to write a file opened for update.
+///
+/// # Safety
+///
+/// `file` must be null or a file object created by the C API that was not
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_file_free(file: *mut XmpFile) {
+    if !file.is_null() {
+        drop(Box::from_raw(file));
+    }
+}
+
+/// Open a file and read its metadata
+///
+/// `flags` is a combination of the `XMP_OPEN_*` flags, or 0.
+///
+/// # Safety
+///
+/// `file` must be a valid file object and `path` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_file_open(
+    file: *mut XmpFile,
+    path: *const c_char,
+    flags: u32,
+) -> c_int {
+    call(|| {
+        let options = read_options(flags)?;
+        mut_arg(file, "file")?.open_with(str_arg(path, "path")?, options)
+    })
+}
+
+/// Read the metadata of a file in memory
+///
+/// The data is copied, and can be freed after the call. `flags` is a
+/// combination of the `XMP_OPEN_*` flags, or 0.
+///
+/// # Safety
+///
+/// `file` must be a valid file object and `data` point to `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_file_open_bytes(
+    file: *mut XmpFile,
+    data: *const u8,
+    len: usize,
+    flags: u32,
+) -> c_int {
+    call(|| {
+        let options = read_options(flags)?;
+        let data = if len == 0 {
+            &[]
+        } else {
+            std::slice::from_raw_parts(ref_arg(data, "data")?, len)
+        };
+        mut_arg(file, "file")?.from_bytes_with(data, options)
+    })
+}
+
+/// Get the metadata of an open file
+///
+/// On success, `*meta` is set to new metadata to free with
+/// [`xmp_meta_free`](super::xmp_meta_free). Like clones of `XmpMeta` in
+/// Rust, it shares its properties with the metadata of the file, so that
+/// changes to it are saved with the file. Returns `XMP_ERR_NOT_FOUND` if the file has no metadata.
+///
+/// # Safety
+///
+/// `file` must be a valid file object and `meta` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_file_get_xmp(file: *const XmpFile, meta: *mut *mut XmpMeta) -> c_int {
+    call(|| {
+        let out = out_arg(meta, "meta")?;
+        let xmp = ref_arg(file, "file")?
+            .get_xmp()
+            .ok_or_else(|| XmpError::NotFound("No XMP metadata".to_string()))?;
+        *out = Box::into_raw(Box::new(xmp.clone()));
+        Ok(())
+    })
+}
+
+/// Replace the metadata of an open file
+///
+/// The metadata stays owned by the caller, and is not copied: later
+/// changes to it are saved too.
+///
+/// # Safety
+///
+/// `file` must be a valid file object and `meta` valid metadata.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_file_put_xmp(file: *mut XmpFile, meta: *const XmpMeta) -> c_int {
+    call(|| {
+        let meta = ref_arg(meta, "meta")?.clone();
+        mut_arg(file, "file")?.put_xmp(meta);
+        Ok(())
+    })
+}
+
+/// Remove the metadata of an open file, when it is saved
+///
+/// # Safety
+///
+/// `file` must be a valid file object.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_file_remove_xmp(file: *mut XmpFile) -> c_int {
+    call(|| {
+        mut_arg(file, "file")?.remove_xmp();
+        Ok(())
+    })
+}
+
+/// Save an open file with its metadata to `path`
+///
+/// `flags` is a combination of the `XMP_SAVE_*` flags, or 0.
+///
+/// # Safety
+///
+/// `file` must be a valid file object and `path` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_file_save(
+    file: *const XmpFile,
+    path: *const c_char,
+    flags: u32,
+) -> c_int {
+    call(|| {
+        let options = save_options(flags)?;
+        ref_arg(file, "file")?.save_with(str_arg(path, "path")?, options)?;
+        Ok(())
+    })
+}
+
+/// Write an open file with its metadata to memory
+///
+/// On success, `*data` is set to a buffer of `*len` bytes to free with
+/// [`xmp_bytes_free`](super::xmp_bytes_free).
+///
+/// # Safety
+///
+/// `file` must be a valid file object, and `data` and `len` valid
+/// pointers.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_file_write_bytes(
+    file: *const XmpFile,
+    data: *mut *mut u8,
+    len: *mut usize,
+) -> c_int {
+    call(|| {
+        let (out_data, out_len) = (out_arg(data, "data")?, out_arg(len, "len")?);
+        let bytes = ref_arg(file, "file")?.write_to_bytes()?.into_boxed_slice();
+        *out_len = bytes.len();
+        *out_data = Box::into_raw(bytes).cast();
+        Ok(())
+    })
+}
+
+/// Close an open file, writing it if it was opened for update
+///
+/// The file object can be opened again, and must still be freed with
+/// [`xmp_file_free`].
+///
+/// # Safety
+///
+/// `file` must be a valid file object.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_file_close(file: *mut XmpFile) -> c_int {
+    call(|| mut_arg(file, "file")?.try_close())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capi::{
+        xmp_bytes_free, xmp_meta_free, xmp_meta_get_property, xmp_meta_new, xmp_meta_set_property,
+        xmp_string_free, XMP_ERR_BAD_PARAM, XMP_ERR_NOT_FOUND, XMP_OK,
+    };
+    use std::ffi::{CStr, CString};
+    use std::ptr;
+
+    /// Smallest JPEG, without metadata
+    const JPEG: &[u8] = &[
+        0xFF, 0xD8, 0xFF, 0xDB, 0x00, 0x43, 0x00, 0x08, 0x06, 0x06, 0x07, 0x06, 0x05, 0x08, 0x07,
+        0x07, 0x07, 0x09, 0x09, 0x08, 0x0A, 0x0C, 0x14, 0x0D, 0x0C, 0x0B, 0x0B, 0x0C, 0x19, 0x12,
+        0x13, 0x0F, 0x14, 0x1D, 0x1A, 0x1F, 0x1E, 0x1D, 0x1A, 0x1C, 0x1C, 0x20, 0x24, 0x2E, 0x27,
+        0x20, 0x22, 0x2C, 0x23, 0x1C, 0x1C, 0x28, 0x37, 0x29, 0x2C, 0x30, 0x31, 0x34, 0x34, 0x34,
+        0x1F, 0x27, 0x39, 0x3D, 0x38, 0x32, 0x3C, 0x2E, 0x33, 0x34, 0x32, 0xFF, 0xD9,
+    ];
+
+    const XMP: &CStr = c"http://ns.adobe.com/xap/1.0/";
+
+    #[test]
+    fn test_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = CString::new(dir.path().join("out.jpg").to_str().unwrap()).unwrap();
+        let tool = c"CreatorTool".as_ptr();
+        unsafe {
+            let file = xmp_file_new();
+            assert_eq!(
+                xmp_file_open_bytes(file, JPEG.as_ptr(), JPEG.len(), 0),
+                XMP_OK
+            );
+            let mut meta = ptr::null_mut();
+            assert_eq!(xmp_file_get_xmp(file, &mut meta), XMP_ERR_NOT_FOUND);
+
+            let meta = xmp_meta_new();
+            assert_eq!(
+                xmp_meta_set_property(meta, XMP.as_ptr(), tool, c"C".as_ptr()),
+                XMP_OK
+            );
+            assert_eq!(xmp_file_put_xmp(file, meta), XMP_OK);
+            xmp_meta_free(meta);
+            assert_eq!(xmp_file_save(file, path.as_ptr(), XMP_SAVE_ATOMIC), XMP_OK);
+
+            let (mut data, mut len) = (ptr::null_mut(), 0);
+            assert_eq!(xmp_file_write_bytes(file, &mut data, &mut len), XMP_OK);
+            assert!(len > JPEG.len());
+            assert_eq!(
+                std::fs::read(dir.path().join("out.jpg")).unwrap(),
+                std::slice::from_raw_parts(data, len)
+            );
+            xmp_bytes_free(data, len);
+            assert_eq!(xmp_file_close(file), XMP_OK);
+
+            assert_eq!(
+                xmp_file_open(file, path.as_ptr(), XMP_OPEN_FOR_READ),
+                XMP_OK
+            );
+            let mut meta = ptr::null_mut();
+            assert_eq!(xmp_file_get_xmp(file, &mut meta), XMP_OK);
+            let mut value = ptr::null_mut();
+            assert_eq!(
+                xmp_meta_get_property(meta, XMP.as_ptr(), tool, &mut value),
+                XMP_OK
+            );
+            assert_eq!(CStr::from_ptr(value).to_str().unwrap(), "C");
+            xmp_string_free(value);
+            xmp_meta_free(meta);
+
+            assert_eq!(
+                xmp_file_open(file, path.as_ptr(), 0x8000),
+                XMP_ERR_BAD_PARAM
+            );
+            assert_eq!(xmp_file_open(file, ptr::null(), 0), XMP_ERR_BAD_PARAM);
+            xmp_file_free(file);
+        }
+    }
+}
@@ -0,0 +1,373 @@
+//! C API for XMP metadata
+
+use std::ffi::{c_char, c_int};
+
+use super::{call, into_c_string, mut_arg, out_arg, ref_arg, str_arg};
+use crate::core::error::XmpError;
+use crate::{XmpMeta, XmpValue};
+
+/// Convert a property value to the string returned to C
+fn value_string(value: XmpValue) -> String {
+    match value {
+        XmpValue::String(s) | XmpValue::Uri(s) => s,
+        XmpValue::Integer(i) => i.to_string(),
+        XmpValue::Boolean(b) => b.to_string(),
+        XmpValue::DateTime(d) => d.format(),
+        _ => format!("{:?}", value),
+    }
+}
+
+/// Create empty metadata
+///
+/// Free it with [`xmp_meta_free`].
+#[no_mangle]
+pub extern "C" fn xmp_meta_new() -> *mut XmpMeta {
+    Box::into_raw(Box::default())
+}
+
+/// Parse an XMP packet or RDF/XML document
+///
+/// On success, `*meta` is set to new metadata to free with
+/// [`xmp_meta_free`].
+///
+/// # Safety
+///
+/// `xml` must be a NUL-terminated string and `meta` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_meta_parse(xml: *const c_char, meta: *mut *mut XmpMeta) -> c_int {
+    call(|| {
+        let out = out_arg(meta, "meta")?;
+        let parsed = XmpMeta::parse(str_arg(xml, "xml")?)?;
+        *out = Box::into_raw(Box::new(parsed));
+        Ok(())
+    })
+}
+
+/// Free metadata
+///
+/// # Safety
+///
+/// `meta` must be null or metadata created by the C API that was not freed
+/// yet.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_meta_free(meta: *mut XmpMeta) {
+    if !meta.is_null() {
+        drop(Box::from_raw(meta));
+    }
+}
+
+/// Get the value of a simple property
+///
+/// On success, `*value` is set to a string to free with
+/// [`xmp_string_free`](super::xmp_string_free). Returns
+/// `XMP_ERR_NOT_FOUND` if the property doesn't exist or is not simple.
+///
+/// # Safety
+///
+/// `meta` must be valid metadata, `namespace` and `path` NUL-terminated
+/// strings, and `value` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_meta_get_property(
+    meta: *const XmpMeta,
+    namespace: *const c_char,
+    path: *const c_char,
+    value: *mut *mut c_char,
+) -> c_int {
+    call(|| {
+        let out = out_arg(value, "value")?;
+        let path = str_arg(path, "path")?;
+        let property = ref_arg(meta, "meta")?
+            .get_property(str_arg(namespace, "namespace")?, path)
+            .ok_or_else(|| XmpError::NotFound(format!("No {} property", path)))?;
+        *out = into_c_string(value_string(property))?;
+        Ok(())
+    })
+}
+
+/// Set the value of a simple property, creating it if needed
+///
+/// # Safety
+///
+/// `meta` must be valid metadata, and `namespace`, `path` and `value`
+/// NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_meta_set_property(
+    meta: *mut XmpMeta,
+    namespace: *const c_char,
+    path: *const c_char,
+    value: *const c_char,
+) -> c_int {
+    call(|| {
+        let value = XmpValue::String(str_arg(value, "value")?.to_string());
+        mut_arg(meta, "meta")?.set_property(
+            str_arg(namespace, "namespace")?,
+            str_arg(path, "path")?,
+            value,
+        )
+    })
+}
+
+/// Delete a property and its children
+///
+/// Deleting a property that doesn't exist succeeds.
+///
+/// # Safety
+///
+/// `meta` must be valid metadata, and `namespace` and `path`
+/// NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_meta_delete_property(
+    meta: *mut XmpMeta,
+    namespace: *const c_char,
+    path: *const c_char,
+) -> c_int {
+    call(|| {
+        mut_arg(meta, "meta")?
+            .delete_property(str_arg(namespace, "namespace")?, str_arg(path, "path")?)
+    })
+}
+
+/// Count the items of an array property
+///
+/// `*count` is set to 0 if the property doesn't exist.
+///
+/// # Safety
+///
+/// `meta` must be valid metadata, `namespace` and `path` NUL-terminated
+/// strings, and `count` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_meta_count_array_items(
+    meta: *const XmpMeta,
+    namespace: *const c_char,
+    path: *const c_char,
+    count: *mut usize,
+) -> c_int {
+    call(|| {
+        let out = out_arg(count, "count")?;
+        *out = ref_arg(meta, "meta")?
+            .count_array_items(str_arg(namespace, "namespace")?, str_arg(path, "path")?);
+        Ok(())
+    })
+}
+
+/// Append an item to an array property, creating an unordered array if
+/// needed
+///
+/// # Safety
+///
+/// `meta` must be valid metadata, and `namespace`, `path` and `value`
+/// NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_meta_append_array_item(
+    meta: *mut XmpMeta,
+    namespace: *const c_char,
+    path: *const c_char,
+    value: *const c_char,
+) -> c_int {
+    call(|| {
+        let value = XmpValue::String(str_arg(value, "value")?.to_string());
+        mut_arg(meta, "meta")?.append_array_item(
+            str_arg(namespace, "namespace")?,
+            str_arg(path, "path")?,
+            value,
+        )
+    })
+}
+
+/// Get a localized text, in the language that best matches
+/// `specific_lang` (such as `en-US`) or `generic_lang` (such as `en`, or
+/// an empty string)
+///
+/// On success, `*value` is set to the text and, unless `actual_lang` is
+/// null, `*actual_lang` to its language; both are strings to free with
+/// [`xmp_string_free`](super::xmp_string_free). Returns
+/// `XMP_ERR_NOT_FOUND` if the property has no text.
+///
+/// # Safety
+///
+/// `meta` must be valid metadata, `namespace`, `path`, `generic_lang` and
+/// `specific_lang` NUL-terminated strings, `value` a valid pointer, and
+/// `actual_lang` null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_meta_get_localized_text(
+    meta: *const XmpMeta,
+    namespace: *const c_char,
+    path: *const c_char,
+    generic_lang: *const c_char,
+    specific_lang: *const c_char,
+    actual_lang: *mut *mut c_char,
+    value: *mut *mut c_char,
+) -> c_int {
+    call(|| {
+        let out = out_arg(value, "value")?;
+        let path = str_arg(path, "path")?;
+        let (text, lang) = ref_arg(meta, "meta")?
+            .get_localized_text(
+                str_arg(namespace, "namespace")?,
+                path,
+                str_arg(generic_lang, "generic_lang")?,
+                str_arg(specific_lang, "specific_lang")?,
+            )
+            .ok_or_else(|| XmpError::NotFound(format!("No text for {}", path)))?;
+        let text = into_c_string(text)?;
+        if let Some(out_lang) = actual_lang.as_mut() {
+            match into_c_string(lang) {
+                Ok(lang) => *out_lang = lang,
+                Err(e) => {
+                    super::xmp_string_free(text);
+                    return Err(e);
+                }
+            }
+        }
+        *out = text;
+        Ok(())
+    })
+}
+
+/// Set a localized text, creating the language alternative if needed
+///
+/// # Safety
+///
+/// `meta` must be valid metadata, and `namespace`, `path`,
+/// `generic_lang`, `specific_lang` and `value` NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_meta_set_localized_text(
+    meta: *mut XmpMeta,
+    namespace: *const c_char,
+    path: *const c_char,
+    generic_lang: *const c_char,
+    specific_lang: *const c_char,
+    value: *const c_char,
+) -> c_int {
+    call(|| {
+        mut_arg(meta, "meta")?.set_localized_text(
+            str_arg(namespace, "namespace")?,
+            str_arg(path, "path")?,
+            str_arg(generic_lang, "generic_lang")?,
+            str_arg(specific_lang, "specific_lang")?,
+            str_arg(value, "value")?,
+        )
+    })
+}
+
+/// Serialize metadata as an XMP packet
+///
+/// On success, `*xml` is set to a string to free with
+/// [`xmp_string_free`](super::xmp_string_free).
+///
+/// # Safety
+///
+/// `meta` must be valid metadata and `xml` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_meta_serialize(meta: *const XmpMeta, xml: *mut *mut c_char) -> c_int {
+    call(|| {
+        let out = out_arg(xml, "xml")?;
+        *out = into_c_string(ref_arg(meta, "meta")?.serialize_packet()?)?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capi::{xmp_string_free, XMP_ERR_BAD_PARSE, XMP_ERR_NOT_FOUND, XMP_OK};
+    use std::ffi::CStr;
+    use std::ptr;
+
+    const XMP: &CStr = c"http://ns.adobe.com/xap/1.0/";
+    const DC: &CStr = c"http://purl.org/dc/elements/1.1/";
+
+    /// Take a string returned by the C API
+    unsafe fn take(string: *mut c_char) -> String {
+        let copy = CStr::from_ptr(string).to_str().unwrap().to_string();
+        xmp_string_free(string);
+        copy
+    }
+
+    #[test]
+    fn test_meta_round_trip() {
+        unsafe {
+            let meta = xmp_meta_new();
+            let mut value = ptr::null_mut();
+            assert_eq!(
+                xmp_meta_get_property(meta, XMP.as_ptr(), c"CreatorTool".as_ptr(), &mut value),
+                XMP_ERR_NOT_FOUND
+            );
+            assert!(value.is_null());
+
+            let (tool, title) = (c"CreatorTool".as_ptr(), c"title".as_ptr());
+            assert_eq!(
+                xmp_meta_set_property(meta, XMP.as_ptr(), tool, c"C".as_ptr()),
+                XMP_OK
+            );
+            assert_eq!(
+                xmp_meta_set_localized_text(
+                    meta,
+                    DC.as_ptr(),
+                    title,
+                    c"".as_ptr(),
+                    c"x-default".as_ptr(),
+                    c"Titre".as_ptr()
+                ),
+                XMP_OK
+            );
+            for keyword in [c"one", c"two"] {
+                let subject = c"subject".as_ptr();
+                assert_eq!(
+                    xmp_meta_append_array_item(meta, DC.as_ptr(), subject, keyword.as_ptr()),
+                    XMP_OK
+                );
+            }
+
+            let mut xml = ptr::null_mut();
+            assert_eq!(xmp_meta_serialize(meta, &mut xml), XMP_OK);
+            let xml = std::ffi::CString::new(take(xml)).unwrap();
+            xmp_meta_free(meta);
+
+            let mut meta = ptr::null_mut();
+            assert_eq!(xmp_meta_parse(xml.as_ptr(), &mut meta), XMP_OK);
+            assert_eq!(
+                xmp_meta_get_property(meta, XMP.as_ptr(), tool, &mut value),
+                XMP_OK
+            );
+            assert_eq!(take(value), "C");
+            let mut lang = ptr::null_mut();
+            assert_eq!(
+                xmp_meta_get_localized_text(
+                    meta,
+                    DC.as_ptr(),
+                    title,
+                    c"en".as_ptr(),
+                    c"en-US".as_ptr(),
+                    &mut lang,
+                    &mut value
+                ),
+                XMP_OK
+            );
+            assert_eq!(
+                (take(value), take(lang)),
+                ("Titre".to_string(), "x-default".to_string())
+            );
+            let mut count = 0;
+            assert_eq!(
+                xmp_meta_count_array_items(meta, DC.as_ptr(), c"subject".as_ptr(), &mut count),
+                XMP_OK
+            );
+            assert_eq!(count, 2);
+
+            assert_eq!(xmp_meta_delete_property(meta, XMP.as_ptr(), tool), XMP_OK);
+            assert_eq!(
+                xmp_meta_get_property(meta, XMP.as_ptr(), tool, &mut value),
+                XMP_ERR_NOT_FOUND
+            );
+            xmp_meta_free(meta);
+
+            let mut meta = ptr::null_mut();
+            assert_eq!(
+                xmp_meta_parse(c"<x:xmpmeta".as_ptr(), &mut meta),
+                XMP_ERR_BAD_PARSE
+            );
+            assert!(meta.is_null());
+        }
+    }
+}
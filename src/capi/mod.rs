@@ -0,0 +1,234 @@
+//! C API for xmpkit
+//!
+//! This module exports `extern "C"` functions mirroring the Adobe XMP
+//! Toolkit, so that C, C++, Swift and Objective-C projects can link xmpkit
+//! instead of the Adobe SDK. Enable the `capi` feature and link the
+//! `cdylib` built by cargo (`libxmpkit.so`, `libxmpkit.dylib` or
+//! `xmpkit.dll`); the declarations are in `include/xmpkit.h`.
+//!
+//! ```c
+//! #include "xmpkit.h"
+//!
+//! XmpFile *file = xmp_file_new();
+//! XmpMeta *meta = NULL;
+//! char *title = NULL;
+//! if (xmp_file_open(file, "photo.jpg", XMP_OPEN_FOR_READ) == XMP_OK &&
+//!     xmp_file_get_xmp(file, &meta) == XMP_OK &&
+//!     xmp_meta_get_property(meta, "http://ns.adobe.com/xap/1.0/",
+//!                           "CreatorTool", &title) == XMP_OK) {
+//!     printf("%s\n", title);
+//!     xmp_string_free(title);
+//! } else {
+//!     fprintf(stderr, "%s\n", xmp_last_error());
+//! }
+//! xmp_meta_free(meta);
+//! xmp_file_free(file);
+//! ```
+//!
+//! # Errors
+//!
+//! Functions that can fail return an `int` error code: `XMP_OK` (0) on
+//! success, otherwise one of the `XMP_ERR_*` codes, which have the numbers
+//! of the Adobe toolkit's `XMP_Error` codes. The message of the last error
+//! of the calling thread is returned by [`xmp_last_error`]. Panics are
+//! caught and reported as `XMP_ERR_INTERNAL`.
+//!
+//! # Pointers
+//!
+//! - Strings passed in are borrowed, NUL-terminated UTF-8; they are only
+//!   used during the call.
+//! - Strings and byte buffers returned through out parameters belong to
+//!   the caller, who frees them with [`xmp_string_free`] and
+//!   [`xmp_bytes_free`]. The out parameters are only written on success.
+//! - `XmpMeta` and `XmpFile` objects are created by the `_new`, `_parse`
+//!   and `_get_xmp` functions, and freed with their `_free` function.
+//!   They can be used from any thread, but not from several threads at
+//!   once.
+//! - Null pointers are rejected with `XMP_ERR_BAD_PARAM`, except by the
+//!   `_free` functions, which ignore them.
+
+mod error;
+mod file;
+mod meta;
+
+use std::ffi::{c_char, c_int, CStr, CString};
+
+use crate::core::error::{XmpError, XmpResult};
+use error::call;
+
+pub use error::*;
+pub use file::*;
+pub use meta::*;
+
+/// Get the version of xmpkit, as a static string
+#[no_mangle]
+pub extern "C" fn xmp_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Free a string returned by the C API
+///
+/// # Safety
+///
+/// `string` must be null or a string returned by the C API that was not
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Free a byte buffer returned by the C API
+///
+/// # Safety
+///
+/// `data` must be null or a buffer returned by the C API that was not
+/// freed yet, and `len` its length.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_bytes_free(data: *mut u8, len: usize) {
+    if !data.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(data, len)));
+    }
+}
+
+/// Register a namespace URI with a preferred prefix
+///
+/// # Safety
+///
+/// `uri` and `prefix` must be NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn xmp_register_namespace(
+    uri: *const c_char,
+    prefix: *const c_char,
+) -> c_int {
+    call(|| crate::register_namespace(str_arg(uri, "uri")?, str_arg(prefix, "prefix")?))
+}
+
+/// Borrow a string argument
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> XmpResult<&'a str> {
+    if ptr.is_null() {
+        return Err(null_arg(name));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| XmpError::BadParam(format!("{} is not valid UTF-8", name)))
+}
+
+/// Borrow an object argument
+unsafe fn ref_arg<'a, T>(ptr: *const T, name: &str) -> XmpResult<&'a T> {
+    ptr.as_ref().ok_or_else(|| null_arg(name))
+}
+
+/// Borrow a mutable object argument
+unsafe fn mut_arg<'a, T>(ptr: *mut T, name: &str) -> XmpResult<&'a mut T> {
+    ptr.as_mut().ok_or_else(|| null_arg(name))
+}
+
+/// Check an out parameter, before computing the value written to it
+unsafe fn out_arg<'a, T>(ptr: *mut T, name: &str) -> XmpResult<&'a mut T> {
+    ptr.as_mut().ok_or_else(|| null_arg(name))
+}
+
+/// Error for a null pointer argument
+fn null_arg(name: &str) -> XmpError {
+    XmpError::BadParam(format!("{} is null", name))
+}
+
+/// Move a string to the caller
+fn into_c_string(string: String) -> XmpResult<*mut c_char> {
+    CString::new(string)
+        .map(CString::into_raw)
+        .map_err(|_| XmpError::BadValue("The string contains a NUL character".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Names of the functions exported by the C API
+    fn exported_functions() -> Vec<&'static str> {
+        [
+            include_str!("mod.rs"),
+            include_str!("error.rs"),
+            include_str!("meta.rs"),
+            include_str!("file.rs"),
+        ]
+        .iter()
+        .flat_map(|source| source.split("extern \"C\" fn ").skip(1))
+        .filter_map(|rest| rest.split('(').next())
+        .collect()
+    }
+
+    #[test]
+    fn test_header_declares_exports() {
+        let header = include_str!("../../include/xmpkit.h");
+        let functions = exported_functions();
+        assert!(functions.len() > 20);
+        for function in functions {
+            assert!(
+                header.contains(&format!(" {}(", function))
+                    || header.contains(&format!("*{}(", function)),
+                "{} is not declared in xmpkit.h",
+                function
+            );
+        }
+        let codes = [
+            ("XMP_OK", XMP_OK),
+            ("XMP_ERR_NOT_FOUND", XMP_ERR_NOT_FOUND),
+            ("XMP_ERR_BAD_PARAM", XMP_ERR_BAD_PARAM),
+            ("XMP_ERR_BAD_VALUE", XMP_ERR_BAD_VALUE),
+            ("XMP_ERR_UNIMPLEMENTED", XMP_ERR_UNIMPLEMENTED),
+            ("XMP_ERR_INTERNAL", XMP_ERR_INTERNAL),
+            ("XMP_ERR_CANCELLED", XMP_ERR_CANCELLED),
+            ("XMP_ERR_BAD_SCHEMA", XMP_ERR_BAD_SCHEMA),
+            ("XMP_ERR_BAD_XPATH", XMP_ERR_BAD_XPATH),
+            ("XMP_ERR_BAD_PARSE", XMP_ERR_BAD_PARSE),
+            ("XMP_ERR_BAD_SERIALIZE", XMP_ERR_BAD_SERIALIZE),
+            ("XMP_ERR_IO", XMP_ERR_IO),
+        ];
+        for (name, code) in codes {
+            assert!(header.contains(&format!("#define {} {}\n", name, code)));
+        }
+        let flags = [
+            ("XMP_OPEN_FOR_READ", XMP_OPEN_FOR_READ),
+            ("XMP_OPEN_FOR_UPDATE", XMP_OPEN_FOR_UPDATE),
+            ("XMP_OPEN_ONLY_XMP", XMP_OPEN_ONLY_XMP),
+            ("XMP_OPEN_STRICTLY", XMP_OPEN_STRICTLY),
+            ("XMP_OPEN_USE_SMART_HANDLER", XMP_OPEN_USE_SMART_HANDLER),
+            ("XMP_OPEN_USE_PACKET_SCANNING", XMP_OPEN_USE_PACKET_SCANNING),
+            ("XMP_OPEN_LIMITED_SCANNING", XMP_OPEN_LIMITED_SCANNING),
+            ("XMP_OPEN_REPAIR_FILE", XMP_OPEN_REPAIR_FILE),
+            ("XMP_SAVE_ATOMIC", XMP_SAVE_ATOMIC),
+            ("XMP_SAVE_PREFER_IN_PLACE", XMP_SAVE_PREFER_IN_PLACE),
+            (
+                "XMP_SAVE_KEEP_MODIFICATION_TIME",
+                XMP_SAVE_KEEP_MODIFICATION_TIME,
+            ),
+            ("XMP_SAVE_STRIP_ALL_METADATA", XMP_SAVE_STRIP_ALL_METADATA),
+        ];
+        for (name, flag) in flags {
+            assert!(header.contains(&format!("#define {} 0x{:x}\n", name, flag)));
+        }
+    }
+
+    #[test]
+    fn test_version_and_namespace() {
+        let version = unsafe { CStr::from_ptr(xmp_version()) };
+        assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+
+        let uri = c"http://example.com/capi/";
+        unsafe {
+            assert_eq!(
+                xmp_register_namespace(uri.as_ptr(), c"capi".as_ptr()),
+                XMP_OK
+            );
+            assert_eq!(
+                xmp_register_namespace(uri.as_ptr(), std::ptr::null()),
+                XMP_ERR_BAD_PARAM
+            );
+        }
+        let message = unsafe { CStr::from_ptr(xmp_last_error()) };
+        assert_eq!(message.to_str().unwrap(), "Bad parameter: prefix is null");
+    }
+}
@@ -0,0 +1,480 @@
+//! Property aliases
+//!
+//! An alias is a property name that stands for another, actual property,
+//! such as `pdf:Title` for the `x-default` item of `dc:title`. The standard
+//! aliases of the XMP Specification are built in; others can be registered
+//! with [`register_alias`] or with a custom [`XmpSchema`].
+//!
+//! [`XmpMeta`](crate::XmpMeta) resolves aliases transparently: getting or
+//! setting an alias reads or writes the actual property, and aliases found
+//! in parsed packets are moved to their actual properties. The serializer
+//! therefore never writes aliases.
+//!
+//! # Example
+//!
+//! ```rust
+//! use xmpkit::{ns, XmpMeta};
+//!
+//! let meta = XmpMeta::parse(r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+//!   <rdf:Description rdf:about="" xmlns:pdf="http://ns.adobe.com/pdf/1.3/" pdf:Title="Report"/>
+//! </rdf:RDF>"#)
+//! .unwrap();
+//!
+//! assert_eq!(
+//!     meta.get_localized_text(ns::DC, "title", "", "x-default"),
+//!     Some(("Report".to_string(), "x-default".to_string()))
+//! );
+//! assert_eq!(meta.get_property(ns::PDF, "Title"), Some("Report".into()));
+//! ```
+//!
+//! [`XmpSchema`]: crate::core::validate::XmpSchema
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::namespace::{self, ns};
+use crate::core::node::{ArrayNode, ArrayType, Node, StructureNode};
+use crate::core::path::PathStep;
+use crate::core::validate::ValueForm;
+use crate::types::qualifier::Qualifier;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+/// The actual property an alias stands for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmpAlias {
+    pub(crate) namespace: String,
+    pub(crate) property: String,
+    pub(crate) form: ValueForm,
+}
+
+impl XmpAlias {
+    /// Namespace URI of the actual property
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Name of the actual top-level property
+    pub fn property(&self) -> &str {
+        &self.property
+    }
+
+    /// Form of the actual property
+    ///
+    /// `Simple` if the alias stands for the whole actual property, or the
+    /// array form if it stands for the first item (`Seq` and `Alt`) or the
+    /// `x-default` item (`LangAlt`).
+    pub fn form(&self) -> ValueForm {
+        self.form
+    }
+
+    /// XMP path of the actual node, relative to [`namespace`](Self::namespace)
+    pub fn path(&self) -> String {
+        match self.form {
+            ValueForm::LangAlt => format!("{}[?xml:lang=\"x-default\"]", self.property),
+            ValueForm::Simple => self.property.clone(),
+            _ => format!("{}[1]", self.property),
+        }
+    }
+
+    /// Path steps of the actual node
+    pub(crate) fn steps(&self) -> Vec<PathStep> {
+        let mut steps = vec![PathStep::Field(self.key())];
+        match self.form {
+            ValueForm::LangAlt => steps.push(PathStep::QualifierSelector {
+                namespace: ns::XML.to_string(),
+                name: "lang".to_string(),
+                value: "x-default".to_string(),
+            }),
+            ValueForm::Simple => {}
+            _ => steps.push(PathStep::Index(0)),
+        }
+        steps
+    }
+
+    /// Key of the actual property in the root structure
+    pub(crate) fn key(&self) -> String {
+        format!("{}:{}", self.namespace, self.property)
+    }
+
+    /// Type of the actual array, if the alias stands for an item
+    pub(crate) fn array_type(&self) -> Option<ArrayType> {
+        match self.form {
+            ValueForm::Seq => Some(ArrayType::Ordered),
+            ValueForm::Alt | ValueForm::LangAlt => Some(ArrayType::Alternative),
+            _ => None,
+        }
+    }
+}
+
+/// The standard aliases: alias namespace, alias name, actual namespace,
+/// actual name and form
+const STANDARD_ALIASES: &[(&str, &str, &str, &str, ValueForm)] = {
+    use ValueForm::*;
+    &[
+        (ns::XMP, "Author", ns::DC, "creator", Seq),
+        (ns::XMP, "Authors", ns::DC, "creator", Simple),
+        (ns::XMP, "Description", ns::DC, "description", Simple),
+        (ns::XMP, "Format", ns::DC, "format", Simple),
+        (ns::XMP, "Keywords", ns::DC, "subject", Simple),
+        (ns::XMP, "Locale", ns::DC, "language", Simple),
+        (ns::XMP, "Title", ns::DC, "title", Simple),
+        (ns::XMP_RIGHTS, "Copyright", ns::DC, "rights", Simple),
+        (ns::PDF, "Author", ns::DC, "creator", Seq),
+        (ns::PDF, "BaseURL", ns::XMP, "BaseURL", Simple),
+        (ns::PDF, "CreationDate", ns::XMP, "CreateDate", Simple),
+        (ns::PDF, "Creator", ns::XMP, "CreatorTool", Simple),
+        (ns::PDF, "ModDate", ns::XMP, "ModifyDate", Simple),
+        (ns::PDF, "Subject", ns::DC, "description", LangAlt),
+        (ns::PDF, "Title", ns::DC, "title", LangAlt),
+        (ns::PHOTOSHOP, "Author", ns::DC, "creator", Seq),
+        (ns::PHOTOSHOP, "Caption", ns::DC, "description", LangAlt),
+        (ns::PHOTOSHOP, "Copyright", ns::DC, "rights", LangAlt),
+        (ns::PHOTOSHOP, "Keywords", ns::DC, "subject", Simple),
+        (ns::PHOTOSHOP, "Marked", ns::XMP_RIGHTS, "Marked", Simple),
+        (ns::PHOTOSHOP, "Title", ns::DC, "title", LangAlt),
+        (
+            ns::PHOTOSHOP,
+            "WebStatement",
+            ns::XMP_RIGHTS,
+            "WebStatement",
+            Simple,
+        ),
+        (ns::TIFF, "Artist", ns::DC, "creator", Seq),
+        (ns::TIFF, "Copyright", ns::DC, "rights", Simple),
+        (ns::TIFF, "DateTime", ns::XMP, "ModifyDate", Simple),
+        (ns::TIFF, "ImageDescription", ns::DC, "description", Simple),
+        (ns::TIFF, "Software", ns::XMP, "CreatorTool", Simple),
+    ]
+};
+
+/// Aliases keyed by alias namespace URI and alias name
+type AliasMap = HashMap<String, HashMap<String, XmpAlias>>;
+
+/// Global alias registry, initialized with the standard aliases
+static GLOBAL_ALIASES: OnceLock<RwLock<AliasMap>> = OnceLock::new();
+
+fn get_global_aliases() -> &'static RwLock<AliasMap> {
+    GLOBAL_ALIASES.get_or_init(|| {
+        let mut map = AliasMap::new();
+        for (alias_ns, alias_name, actual_ns, actual_name, form) in STANDARD_ALIASES {
+            map.entry(alias_ns.to_string()).or_default().insert(
+                alias_name.to_string(),
+                XmpAlias {
+                    namespace: actual_ns.to_string(),
+                    property: actual_name.to_string(),
+                    form: *form,
+                },
+            );
+        }
+        RwLock::new(map)
+    })
+}
+
+/// Resolve a namespace URI or globally registered prefix
+fn resolve_namespace(namespace: &str) -> XmpResult<String> {
+    if namespace::is_namespace_registered(namespace) {
+        return Ok(namespace.to_string());
+    }
+    namespace::get_global_namespace_uri(namespace)
+        .ok_or_else(|| XmpError::BadSchema(format!("Unregistered namespace '{}'", namespace)))
+}
+
+/// Register an alias
+///
+/// `form` is `Simple` for an alias of the whole actual property, or the
+/// array form of the actual property for an alias of its first item (`Seq`
+/// and `Alt`) or of its `x-default` item (`LangAlt`). Both namespaces must
+/// be registered, as URIs or prefixes.
+///
+/// Registering the same alias again is allowed. It is an error to redefine
+/// an alias, to alias an alias, or to make an actual property an alias.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::core::alias::{register_alias, resolve_alias};
+/// use xmpkit::{ns, register_namespace, ValueForm};
+///
+/// register_namespace("http://example.com/legacy/", "legacy").unwrap();
+/// register_alias("legacy", "Headline", ns::DC, "title", ValueForm::LangAlt).unwrap();
+///
+/// let alias = resolve_alias("legacy", "Headline").unwrap();
+/// assert_eq!(alias.path(), r#"title[?xml:lang="x-default"]"#);
+/// ```
+pub fn register_alias(
+    alias_ns: &str,
+    alias_prop: &str,
+    actual_ns: &str,
+    actual_prop: &str,
+    form: ValueForm,
+) -> XmpResult<()> {
+    if alias_prop.is_empty() || actual_prop.is_empty() {
+        return Err(XmpError::BadParam(
+            "Alias and actual property names are required".to_string(),
+        ));
+    }
+    if matches!(form, ValueForm::Bag | ValueForm::Struct) {
+        return Err(XmpError::BadParam(format!(
+            "An alias can't stand for an item of a {}",
+            form
+        )));
+    }
+    let alias_ns = resolve_namespace(alias_ns)?;
+    let actual = XmpAlias {
+        namespace: resolve_namespace(actual_ns)?,
+        property: actual_prop.to_string(),
+        form,
+    };
+
+    // RwLock::write() only fails if the lock is poisoned
+    let mut guard = get_global_aliases()
+        .write()
+        .expect("Alias registry lock poisoned");
+    if let Some(existing) = guard.get(&alias_ns).and_then(|m| m.get(alias_prop)) {
+        if *existing == actual {
+            return Ok(());
+        }
+        return Err(XmpError::BadParam(format!(
+            "Alias '{}:{}' is already registered for '{}'",
+            alias_ns,
+            alias_prop,
+            existing.key()
+        )));
+    }
+    if guard
+        .get(&actual.namespace)
+        .is_some_and(|m| m.contains_key(actual_prop))
+    {
+        return Err(XmpError::BadParam(format!(
+            "Actual property '{}' is an alias",
+            actual.key()
+        )));
+    }
+    if guard
+        .values()
+        .flat_map(|m| m.values())
+        .any(|a| a.namespace == alias_ns && a.property == alias_prop)
+    {
+        return Err(XmpError::BadParam(format!(
+            "Alias '{}:{}' is an actual property",
+            alias_ns, alias_prop
+        )));
+    }
+
+    guard
+        .entry(alias_ns)
+        .or_default()
+        .insert(alias_prop.to_string(), actual);
+    Ok(())
+}
+
+/// Resolve an alias to the actual property it stands for
+///
+/// `namespace` is a namespace URI or a registered prefix. Returns `None` if
+/// the property is not an alias.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::core::alias::resolve_alias;
+/// use xmpkit::{ns, ValueForm};
+///
+/// let alias = resolve_alias(ns::XMP, "Author").unwrap();
+/// assert_eq!(alias.namespace(), ns::DC);
+/// assert_eq!(alias.property(), "creator");
+/// assert_eq!(alias.form(), ValueForm::Seq);
+/// assert_eq!(alias.path(), "creator[1]");
+///
+/// assert!(resolve_alias("pdf", "Producer").is_none());
+/// ```
+pub fn resolve_alias(namespace: &str, property: &str) -> Option<XmpAlias> {
+    lookup(&resolve_namespace(namespace).ok()?, property)
+}
+
+/// Resolve an alias by namespace URI
+pub(crate) fn lookup(ns_uri: &str, name: &str) -> Option<XmpAlias> {
+    let guard = get_global_aliases()
+        .read()
+        .expect("Alias registry lock poisoned");
+    guard.get(ns_uri)?.get(name).cloned()
+}
+
+/// Get the aliases of a namespace, as alias names and actual properties
+pub(crate) fn aliases_of(ns_uri: &str) -> Vec<(String, XmpAlias)> {
+    let guard = get_global_aliases()
+        .read()
+        .expect("Alias registry lock poisoned");
+    guard
+        .get(ns_uri)
+        .map(|m| {
+            m.iter()
+                .map(|(name, a)| (name.clone(), a.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Get the array type implied for an actual property by the aliases of its
+/// items, if any
+pub(crate) fn actual_array_type(ns_uri: &str, name: &str) -> Option<ArrayType> {
+    let guard = get_global_aliases()
+        .read()
+        .expect("Alias registry lock poisoned");
+    guard
+        .values()
+        .flat_map(|m| m.values())
+        .filter(|a| a.namespace == ns_uri && a.property == name)
+        .find_map(XmpAlias::array_type)
+}
+
+/// Move the aliases of a parsed tree to their actual properties
+///
+/// An alias whose actual property exists is dropped, unless it stands for
+/// the `x-default` item and the actual array has none. This follows the
+/// non-strict handling of the Adobe XMP Toolkit. With `strict`, an alias
+/// whose value differs from the actual property is an error instead.
+pub(crate) fn move_explicit_aliases(root: &mut StructureNode, strict: bool) -> XmpResult<()> {
+    let aliases: Vec<(String, XmpAlias)> = root
+        .fields
+        .keys()
+        .filter_map(|key| {
+            let (ns_uri, name) = key.rsplit_once(':')?;
+            Some((key.clone(), lookup(ns_uri, name)?))
+        })
+        .collect();
+
+    for (key, alias) in aliases {
+        let Some(node) = root.remove_field(&key) else {
+            continue;
+        };
+        let actual_key = alias.key();
+        let array_type = alias.array_type().filter(|_| !node.is_array());
+        let matches = match (array_type, root.get_field_mut(&actual_key)) {
+            (None, None) => {
+                root.set_field(actual_key, node);
+                true
+            }
+            (Some(array_type), None) => {
+                let mut array = ArrayNode::new(array_type);
+                array.append(alias_item(node, alias.form));
+                root.set_field(actual_key, Node::Array(array));
+                true
+            }
+            (Some(_), Some(Node::Array(array))) => {
+                if array.is_empty() {
+                    array.append(alias_item(node, alias.form));
+                    true
+                } else if alias.form == ValueForm::LangAlt {
+                    match array.items.iter().find(|item| is_x_default(item)) {
+                        Some(item) => same_value(item, &node),
+                        None => {
+                            array.items.insert(0, alias_item(node, alias.form));
+                            true
+                        }
+                    }
+                } else {
+                    same_value(&array.items[0], &node)
+                }
+            }
+            (None, Some(actual)) => same_value(actual, &node),
+            (Some(_), Some(_)) => false,
+        };
+        if strict && !matches {
+            return Err(XmpError::ParseError(format!(
+                "Alias {} and actual property {} have different values",
+                key,
+                alias.key()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Check if an alias has the value of its actual property
+///
+/// Simple values are compared without their qualifiers.
+fn same_value(actual: &Node, alias: &Node) -> bool {
+    match (actual, alias) {
+        (Node::Simple(actual), Node::Simple(alias)) => actual.value == alias.value,
+        _ => actual == alias,
+    }
+}
+
+/// Turn the value of an alias into an item of the actual array
+fn alias_item(mut node: Node, form: ValueForm) -> Node {
+    let has_lang = node
+        .qualifiers()
+        .iter()
+        .any(|q| q.namespace == ns::XML && q.name == "lang");
+    if form == ValueForm::LangAlt && !has_lang {
+        node.qualifiers_mut()
+            .insert(0, Qualifier::new(ns::XML, "lang", "x-default"));
+    }
+    node
+}
+
+fn is_x_default(item: &Node) -> bool {
+    item.qualifiers()
+        .iter()
+        .any(|q| q.namespace == ns::XML && q.name == "lang" && q.value == "x-default")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_aliases() {
+        let alias = resolve_alias(ns::PDF, "Title").unwrap();
+        assert_eq!(alias.key(), format!("{}:title", ns::DC));
+        assert_eq!(alias.form(), ValueForm::LangAlt);
+        assert_eq!(
+            resolve_alias("tiff", "Software").unwrap().path(),
+            "CreatorTool"
+        );
+        assert!(resolve_alias(ns::DC, "title").is_none());
+        assert!(resolve_alias("unknown", "Title").is_none());
+        assert_eq!(
+            actual_array_type(ns::DC, "creator"),
+            Some(ArrayType::Ordered)
+        );
+        assert_eq!(actual_array_type(ns::DC, "subject"), None);
+    }
+
+    #[test]
+    fn test_register_alias() {
+        const NS: &str = "http://example.com/alias-test/";
+        namespace::register_namespace(NS, "atest").unwrap();
+
+        register_alias(NS, "Writer", ns::DC, "creator", ValueForm::Seq).unwrap();
+        // Registering the same alias again is a no-op
+        register_alias("atest", "Writer", "dc", "creator", ValueForm::Seq).unwrap();
+        assert_eq!(
+            lookup(NS, "Writer").map(|a| a.path()),
+            Some("creator[1]".to_string())
+        );
+
+        // Redefining an alias
+        assert!(matches!(
+            register_alias(NS, "Writer", ns::DC, "contributor", ValueForm::Seq),
+            Err(XmpError::BadParam(_))
+        ));
+        // Aliasing an alias
+        assert!(matches!(
+            register_alias(NS, "Name", ns::PDF, "Title", ValueForm::Simple),
+            Err(XmpError::BadParam(_))
+        ));
+        // Making an actual property an alias
+        assert!(matches!(
+            register_alias(ns::DC, "creator", NS, "Creators", ValueForm::Simple),
+            Err(XmpError::BadParam(_))
+        ));
+        assert!(matches!(
+            register_alias(NS, "Tag", ns::DC, "subject", ValueForm::Bag),
+            Err(XmpError::BadParam(_))
+        ));
+        assert!(matches!(
+            register_alias("nope", "Tag", ns::DC, "subject", ValueForm::Simple),
+            Err(XmpError::BadSchema(_))
+        ));
+    }
+}
@@ -0,0 +1,743 @@
+//! Read-only XMP metadata borrowed from a packet
+//!
+//! [`XmpMeta::parse_borrowed`] parses a packet into an [`XmpMetaRef`],
+//! whose values ([`XmpValueRef`]) borrow their text from the packet instead
+//! of copying it. This makes reading a few properties from many packets
+//! much cheaper than building an [`XmpMeta`] for each of them.
+//!
+//! The view keeps the properties in the order of the packet, and supports
+//! the RDF forms of the regular parser. It differs from [`XmpMeta`] in a
+//! few ways:
+//! - qualifiers other than `xml:lang` are not kept; the `rdf:value` of a
+//!   qualified value is its value
+//! - aliases are not resolved
+//! - the parsing is strict: there is no lenient mode
+//!
+//! [`XmpMeta`]: crate::core::metadata::XmpMeta
+//! [`XmpMeta::parse_borrowed`]: crate::core::metadata::XmpMeta::parse_borrowed
+
+use std::borrow::Cow;
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::namespace::{get_global_namespace_uri, ns};
+use crate::core::parser::ParseOptions;
+use crate::types::value_ref::{XmpPropertyRef, XmpValueRef};
+use quick_xml::escape::{resolve_predefined_entity, unescape};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+/// Read-only XMP metadata borrowed from a packet
+///
+/// Created by [`XmpMeta::parse_borrowed`]; see the [module
+/// documentation](self).
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::{ns, XmpMeta};
+///
+/// let xml = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+///   <rdf:Description xmlns:dc="http://purl.org/dc/elements/1.1/"
+///       xmlns:xmp="http://ns.adobe.com/xap/1.0/" xmp:Rating="4">
+///     <dc:title><rdf:Alt><rdf:li xml:lang="x-default">Sunset</rdf:li></rdf:Alt></dc:title>
+///   </rdf:Description>
+/// </rdf:RDF>"#;
+/// let meta = XmpMeta::parse_borrowed(xml).unwrap();
+/// assert_eq!(meta.get_property(ns::XMP, "Rating").and_then(|v| v.as_int()), Some(4));
+/// assert_eq!(
+///     meta.get_localized_text(ns::DC, "title", "en"),
+///     Some(("Sunset", "x-default"))
+/// );
+/// ```
+///
+/// [`XmpMeta::parse_borrowed`]: crate::core::metadata::XmpMeta::parse_borrowed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XmpMetaRef<'a> {
+    /// Top-level properties, in the order of the packet
+    properties: Vec<XmpPropertyRef<'a>>,
+    /// Namespace prefixes declared in the packet, and their URIs
+    namespaces: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    /// The `rdf:about` of the `rdf:Description` elements
+    about: Option<Cow<'a, str>>,
+}
+
+impl<'a> XmpMetaRef<'a> {
+    /// Parse a packet with options
+    pub(crate) fn parse(xml: &'a str, options: &ParseOptions) -> XmpResult<Self> {
+        if let Some(max) = options.max_packet_size {
+            if xml.len() > max {
+                return Err(XmpError::ParseError(format!(
+                    "XMP packet of {} bytes exceeds the limit of {} bytes",
+                    xml.len(),
+                    max
+                )));
+            }
+        }
+        BorrowedParser {
+            input: xml,
+            reader: Reader::from_str(xml),
+            options: *options,
+            scopes: Vec::new(),
+            depth: 0,
+            node_count: 0,
+            meta: XmpMetaRef::default(),
+        }
+        .parse()
+    }
+
+    /// Get the top-level properties, in the order of the packet
+    pub fn properties(&self) -> &[XmpPropertyRef<'a>] {
+        &self.properties
+    }
+
+    /// Get the `rdf:about` URI of the metadata
+    pub fn about(&self) -> Option<&str> {
+        self.about.as_deref()
+    }
+
+    /// Get a top-level property
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace URI or prefix
+    /// * `name` - The property name (e.g., "title")
+    pub fn get_property(&self, namespace: &str, name: &str) -> Option<&XmpValueRef<'a>> {
+        let uri = self.resolve_namespace_uri(namespace)?;
+        self.properties
+            .iter()
+            .find(|property| property.namespace == uri.as_ref() && property.name == name)
+            .map(|property| &property.value)
+    }
+
+    /// Get an array item by index
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace URI or prefix
+    /// * `name` - The array property name (e.g., "creator")
+    /// * `index` - The array index (0-based)
+    pub fn get_array_item(
+        &self,
+        namespace: &str,
+        name: &str,
+        index: usize,
+    ) -> Option<&XmpValueRef<'a>> {
+        self.get_property(namespace, name)?.as_array()?.get(index)
+    }
+
+    /// Get a field of a structure property
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace URI or prefix of the structure
+    /// * `struct_name` - The structure property name
+    /// * `field_namespace` - The namespace URI or prefix of the field
+    /// * `field_name` - The field name
+    pub fn get_struct_field(
+        &self,
+        namespace: &str,
+        struct_name: &str,
+        field_namespace: &str,
+        field_name: &str,
+    ) -> Option<&XmpValueRef<'a>> {
+        let field_uri = self.resolve_namespace_uri(field_namespace)?;
+        self.get_property(namespace, struct_name)?
+            .field(&field_uri, field_name)
+    }
+
+    /// Get the text of a language alternative for a language
+    ///
+    /// Chooses the item in `lang` (compared ignoring case), or else the
+    /// "x-default" item, or else the first item. Returns the text and the
+    /// language of the item.
+    pub fn get_localized_text(
+        &self,
+        namespace: &str,
+        name: &str,
+        lang: &str,
+    ) -> Option<(&str, &str)> {
+        let items = self.get_property(namespace, name)?.as_array()?;
+        let find = |wanted: &str| {
+            items
+                .iter()
+                .find(|item| item.lang().is_some_and(|l| l.eq_ignore_ascii_case(wanted)))
+        };
+        let item = find(lang).or_else(|| find("x-default")).or(items.first())?;
+        Some((item.as_str()?, item.lang().unwrap_or("x-default")))
+    }
+
+    /// Resolve a namespace URI or prefix to the URI
+    ///
+    /// Prefixes declared in the packet take precedence over the registered
+    /// ones.
+    fn resolve_namespace_uri<'s>(&'s self, namespace: &'s str) -> Option<Cow<'s, str>> {
+        if namespace.starts_with("http://") {
+            return Some(Cow::Borrowed(namespace));
+        }
+        match self
+            .namespaces
+            .iter()
+            .find(|(prefix, _)| prefix == namespace)
+        {
+            Some((_, uri)) => Some(Cow::Borrowed(uri)),
+            None => get_global_namespace_uri(namespace).map(Cow::Owned),
+        }
+    }
+}
+
+/// Attributes of an element: qualified names and unescaped values
+type Attributes<'a> = Vec<(&'a str, Cow<'a, str>)>;
+
+/// A parser of packets into [`XmpMetaRef`]s
+struct BorrowedParser<'a> {
+    input: &'a str,
+    reader: Reader<&'a [u8]>,
+    options: ParseOptions,
+    /// Namespace declarations in scope, innermost last
+    scopes: Vec<(&'a str, Cow<'a, str>)>,
+    /// Nesting depth of the elements being parsed
+    depth: usize,
+    /// Number of properties and array items
+    node_count: usize,
+    meta: XmpMetaRef<'a>,
+}
+
+impl<'a> BorrowedParser<'a> {
+    fn parse(mut self) -> XmpResult<XmpMetaRef<'a>> {
+        let mut open = Vec::new();
+        let mut properties = Vec::new();
+        loop {
+            match self.next_event()? {
+                Event::Start(e) => {
+                    let scope = self.scopes.len();
+                    let attrs = self.attributes(&e)?;
+                    let name = self.slice(e.name().into_inner())?;
+                    if is_description_element(name) {
+                        self.check_xmpmeta(&open)?;
+                        self.check_about(&attrs);
+                        self.add_description_attributes(&attrs, &mut properties)?;
+                        self.parse_properties(&mut properties)?;
+                        self.scopes.truncate(scope);
+                    } else {
+                        self.enter()?;
+                        open.push((name, scope));
+                    }
+                }
+                Event::Empty(e) => {
+                    let scope = self.scopes.len();
+                    let attrs = self.attributes(&e)?;
+                    if is_description_element(self.slice(e.name().into_inner())?) {
+                        self.check_xmpmeta(&open)?;
+                        self.check_about(&attrs);
+                        self.add_description_attributes(&attrs, &mut properties)?;
+                    }
+                    self.scopes.truncate(scope);
+                }
+                Event::End(_) => {
+                    if let Some((_, scope)) = open.pop() {
+                        self.scopes.truncate(scope);
+                        self.depth -= 1;
+                    }
+                }
+                Event::PI(pi) if pi.starts_with(b"xpacket end") => break,
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        self.meta.properties = properties;
+        Ok(self.meta)
+    }
+
+    fn next_event(&mut self) -> XmpResult<Event<'a>> {
+        self.reader.read_event().map_err(xml_error)
+    }
+
+    /// Check that an `rdf:Description` is inside `x:xmpmeta`, if required
+    fn check_xmpmeta(&self, open: &[(&'a str, usize)]) -> XmpResult<()> {
+        if self.options.require_xmpmeta_element
+            && !open.iter().any(|(name, _)| is_xmpmeta_element(name))
+        {
+            return Err(XmpError::ParseError(
+                "rdf:Description outside of an x:xmpmeta element".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Record the `rdf:about` of a top-level `rdf:Description`
+    fn check_about(&mut self, attrs: &Attributes<'a>) {
+        let about = attrs
+            .iter()
+            .find(|(name, _)| *name == "rdf:about" || *name == "about");
+        if let Some((_, about)) = about {
+            if self.meta.about.as_deref().is_none_or(str::is_empty) {
+                self.meta.about = Some(about.clone());
+            }
+        }
+    }
+
+    /// Start parsing the content of an element
+    fn enter(&mut self) -> XmpResult<()> {
+        let max = self.options.depth_limit();
+        if self.depth >= max {
+            return Err(XmpError::ParseError(format!(
+                "Element nesting exceeds the limit of {} levels",
+                max
+            )));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Count a property or array item against the node limit
+    fn count_node(&mut self) -> XmpResult<()> {
+        self.node_count += 1;
+        match self.options.max_node_count {
+            Some(max) if self.node_count > max => Err(XmpError::ParseError(format!(
+                "XMP packet has more than {} properties and array items",
+                max
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Get the part of the packet that `bytes` is a slice of
+    ///
+    /// The events of a reader created from a `&str` borrow from it, so
+    /// their contents can be given the lifetime of the packet.
+    fn slice(&self, bytes: &[u8]) -> XmpResult<&'a str> {
+        let start = (bytes.as_ptr() as usize).wrapping_sub(self.input.as_ptr() as usize);
+        start
+            .checked_add(bytes.len())
+            .and_then(|end| self.input.get(start..end))
+            .ok_or_else(|| XmpError::InternalError("XML event outside of the packet".to_string()))
+    }
+
+    /// Collect the attributes of an element and register its namespace
+    /// declarations
+    fn attributes(&mut self, e: &BytesStart<'a>) -> XmpResult<Attributes<'a>> {
+        let mut attrs = Vec::new();
+        for attr in e.attributes() {
+            let attr = attr.map_err(xml_error)?;
+            let name = self.slice(attr.key.into_inner())?;
+            let value = unescape(self.slice(&attr.value)?).map_err(xml_error)?;
+            if let Some(prefix) = name.strip_prefix("xmlns:") {
+                if !self.meta.namespaces.iter().any(|(p, _)| p == prefix) {
+                    self.meta
+                        .namespaces
+                        .push((Cow::Borrowed(prefix), value.clone()));
+                }
+                self.scopes.push((prefix, value.clone()));
+            }
+            attrs.push((name, value));
+        }
+        Ok(attrs)
+    }
+
+    /// Resolve a qualified name to its namespace URI and local name
+    fn resolve_name(&self, name: &'a str) -> Option<(Cow<'a, str>, &'a str)> {
+        let (prefix, local) = name.split_once(':')?;
+        if prefix == "xml" {
+            return Some((Cow::Borrowed(ns::XML), local));
+        }
+        let (_, uri) = self.scopes.iter().rev().find(|(p, _)| *p == prefix)?;
+        Some((uri.clone(), local))
+    }
+
+    /// Add the attributes of an `rdf:Description` as properties
+    fn add_description_attributes(
+        &mut self,
+        attrs: &Attributes<'a>,
+        target: &mut Vec<XmpPropertyRef<'a>>,
+    ) -> XmpResult<()> {
+        for (name, value) in attrs {
+            if is_namespace_declaration(name)
+                || name.starts_with("rdf:")
+                || name.starts_with("xml:")
+                || *name == "about"
+            {
+                continue;
+            }
+            if let Some((namespace, local)) = self.resolve_name(name) {
+                self.count_node()?;
+                set_field(target, namespace, local, XmpValueRef::String(value.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse property elements until the end of the enclosing element
+    fn parse_properties(&mut self, target: &mut Vec<XmpPropertyRef<'a>>) -> XmpResult<()> {
+        self.enter()?;
+        loop {
+            match self.next_event()? {
+                Event::Start(e) => self.parse_property(e, false, target)?,
+                Event::Empty(e) => self.parse_property(e, true, target)?,
+                Event::End(_) => break,
+                Event::Eof => return Err(unexpected_eof()),
+                _ => {}
+            }
+        }
+        self.depth -= 1;
+        Ok(())
+    }
+
+    /// Parse a property element into a field of `target`
+    ///
+    /// Elements with unknown prefixes are skipped.
+    fn parse_property(
+        &mut self,
+        e: BytesStart<'a>,
+        is_empty: bool,
+        target: &mut Vec<XmpPropertyRef<'a>>,
+    ) -> XmpResult<()> {
+        let scope = self.scopes.len();
+        let attrs = self.attributes(&e)?;
+        match self.resolve_name(self.slice(e.name().into_inner())?) {
+            Some((namespace, local)) => {
+                let value = self.parse_value(&attrs, is_empty)?;
+                set_field(target, namespace, local, value);
+            }
+            None if !is_empty => {
+                self.reader.read_to_end(e.name()).map_err(xml_error)?;
+            }
+            None => {}
+        }
+        self.scopes.truncate(scope);
+        Ok(())
+    }
+
+    /// Parse the value of a property element or `rdf:li` array item
+    fn parse_value(
+        &mut self,
+        attrs: &Attributes<'a>,
+        is_empty: bool,
+    ) -> XmpResult<XmpValueRef<'a>> {
+        self.count_node()?;
+        let mut lang = None;
+        let mut resource = None;
+        let mut parse_resource = false;
+        let mut fields = Vec::new();
+        for (name, value) in attrs {
+            match *name {
+                "xml:lang" => lang = Some(value.clone()),
+                "rdf:parseType" => parse_resource = value == "Resource",
+                "rdf:resource" => resource = Some(value.clone()),
+                name if is_namespace_declaration(name) => {}
+                // rdf:about, rdf:ID, rdf:nodeID, rdf:datatype, ...
+                name if name.starts_with("rdf:") && name != "rdf:value" => {}
+                name => {
+                    if let Some((namespace, local)) = self.resolve_name(name) {
+                        self.count_node()?;
+                        set_field(
+                            &mut fields,
+                            namespace,
+                            local,
+                            XmpValueRef::String(value.clone()),
+                        );
+                    }
+                }
+            }
+        }
+
+        let value = if is_empty {
+            if let Some(uri) = resource {
+                XmpValueRef::String(uri)
+            } else if parse_resource || !fields.is_empty() {
+                XmpValueRef::Structure(fields)
+            } else {
+                XmpValueRef::String(Cow::Borrowed(""))
+            }
+        } else if parse_resource {
+            self.parse_properties(&mut fields)?;
+            XmpValueRef::Structure(fields)
+        } else {
+            self.parse_content(fields)?
+        };
+
+        // A structure with an rdf:value field is a qualified value
+        let value = match value {
+            XmpValueRef::Structure(mut fields) => {
+                match fields
+                    .iter()
+                    .position(|field| field.namespace == ns::RDF && field.name == "value")
+                {
+                    Some(index) => fields.swap_remove(index).value,
+                    None => XmpValueRef::Structure(fields),
+                }
+            }
+            value => value,
+        };
+        Ok(match (lang, value) {
+            (Some(lang), XmpValueRef::String(value)) => XmpValueRef::LangString { lang, value },
+            (_, value) => value,
+        })
+    }
+
+    /// Parse the content of a non-empty property element
+    fn parse_content(&mut self, mut fields: Vec<XmpPropertyRef<'a>>) -> XmpResult<XmpValueRef<'a>> {
+        let mut text: Option<Cow<'a, str>> = None;
+        let mut child = None;
+        self.enter()?;
+        loop {
+            match self.next_event()? {
+                Event::Text(e) => {
+                    let raw = self.slice(&e)?;
+                    let content = if raw.contains('\r') {
+                        Cow::Owned(raw.replace("\r\n", "\n").replace('\r', "\n"))
+                    } else {
+                        Cow::Borrowed(raw)
+                    };
+                    append_text(&mut text, content);
+                }
+                Event::CData(e) => append_text(&mut text, Cow::Borrowed(self.slice(&e)?)),
+                Event::GeneralRef(e) => {
+                    let entity = e.decode().map_err(xml_error)?;
+                    let resolved = match e.resolve_char_ref().map_err(xml_error)? {
+                        Some(ch) => ch.to_string(),
+                        None => resolve_predefined_entity(&entity)
+                            .ok_or_else(|| {
+                                XmpError::ParseError(format!(
+                                    "Unknown entity reference '&{};'",
+                                    entity
+                                ))
+                            })?
+                            .to_string(),
+                    };
+                    append_text(&mut text, Cow::Owned(resolved));
+                }
+                Event::Start(e) => {
+                    let name = self.slice(e.name().into_inner())?;
+                    if array_element(name) {
+                        let scope = self.scopes.len();
+                        self.attributes(&e)?;
+                        child = Some(XmpValueRef::Array(self.parse_items()?));
+                        self.scopes.truncate(scope);
+                    } else if is_description_element(name) {
+                        let scope = self.scopes.len();
+                        let attrs = self.attributes(&e)?;
+                        let mut structure = Vec::new();
+                        self.add_description_attributes(&attrs, &mut structure)?;
+                        self.parse_properties(&mut structure)?;
+                        child = Some(XmpValueRef::Structure(structure));
+                        self.scopes.truncate(scope);
+                    } else {
+                        // Field element without an enclosing rdf:Description
+                        self.parse_property(e, false, &mut fields)?;
+                    }
+                }
+                Event::Empty(e) => {
+                    let name = self.slice(e.name().into_inner())?;
+                    if array_element(name) {
+                        child = Some(XmpValueRef::Array(Vec::new()));
+                    } else if is_description_element(name) {
+                        let scope = self.scopes.len();
+                        let attrs = self.attributes(&e)?;
+                        let mut structure = Vec::new();
+                        self.add_description_attributes(&attrs, &mut structure)?;
+                        child = Some(XmpValueRef::Structure(structure));
+                        self.scopes.truncate(scope);
+                    } else {
+                        self.parse_property(e, true, &mut fields)?;
+                    }
+                }
+                Event::End(_) => break,
+                Event::Eof => return Err(unexpected_eof()),
+                _ => {}
+            }
+        }
+        self.depth -= 1;
+
+        Ok(if let Some(child) = child {
+            child
+        } else if !fields.is_empty() {
+            XmpValueRef::Structure(fields)
+        } else {
+            XmpValueRef::String(text.unwrap_or_default())
+        })
+    }
+
+    /// Parse `rdf:li` items until the end of the array container
+    fn parse_items(&mut self) -> XmpResult<Vec<XmpValueRef<'a>>> {
+        let mut items = Vec::new();
+        self.enter()?;
+        loop {
+            let (e, is_empty) = match self.next_event()? {
+                Event::Start(e) => (e, false),
+                Event::Empty(e) => (e, true),
+                Event::End(_) => break,
+                Event::Eof => return Err(unexpected_eof()),
+                _ => continue,
+            };
+            if !is_li_element(self.slice(e.name().into_inner())?) {
+                if !is_empty {
+                    self.reader.read_to_end(e.name()).map_err(xml_error)?;
+                }
+                continue;
+            }
+            let scope = self.scopes.len();
+            let attrs = self.attributes(&e)?;
+            items.push(self.parse_value(&attrs, is_empty)?);
+            self.scopes.truncate(scope);
+        }
+        self.depth -= 1;
+        Ok(items)
+    }
+}
+
+/// Set a field, replacing a field with the same name
+fn set_field<'a>(
+    fields: &mut Vec<XmpPropertyRef<'a>>,
+    namespace: Cow<'a, str>,
+    name: &'a str,
+    value: XmpValueRef<'a>,
+) {
+    match fields
+        .iter_mut()
+        .find(|field| field.namespace == namespace && field.name == name)
+    {
+        Some(field) => field.value = value,
+        None => fields.push(XmpPropertyRef {
+            namespace,
+            name: Cow::Borrowed(name),
+            value,
+        }),
+    }
+}
+
+/// Append a piece of text content, copying only when there are several
+fn append_text<'a>(text: &mut Option<Cow<'a, str>>, content: Cow<'a, str>) {
+    match text {
+        None => *text = Some(content),
+        Some(text) => text.to_mut().push_str(&content),
+    }
+}
+
+fn is_namespace_declaration(name: &str) -> bool {
+    name == "xmlns" || name.starts_with("xmlns:")
+}
+
+fn is_description_element(name: &str) -> bool {
+    name == "Description" || name.ends_with(":Description")
+}
+
+fn is_li_element(name: &str) -> bool {
+    name == "li" || name.ends_with(":li")
+}
+
+fn array_element(name: &str) -> bool {
+    matches!(
+        name.rsplit(':').next().unwrap_or(name),
+        "Seq" | "Bag" | "Alt"
+    )
+}
+
+fn is_xmpmeta_element(name: &str) -> bool {
+    let local = name.rsplit(':').next().unwrap_or(name);
+    local == "xmpmeta" || local == "xapmeta"
+}
+
+fn unexpected_eof() -> XmpError {
+    XmpError::ParseError("Unexpected end of RDF content".to_string())
+}
+
+fn xml_error(e: impl std::fmt::Display) -> XmpError {
+    XmpError::ParseError(format!("XML parsing error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::metadata::XmpMeta;
+
+    const PACKET: &str = r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+  <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+    <rdf:Description rdf:about="" xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+        xmlns:dc="http://purl.org/dc/elements/1.1/" xmp:Label="Red">
+      <dc:creator><rdf:Seq><rdf:li>Ann</rdf:li><rdf:li>Bob &amp; Co</rdf:li></rdf:Seq></dc:creator>
+      <dc:title>
+        <rdf:Alt>
+          <rdf:li xml:lang="x-default">Title</rdf:li>
+          <rdf:li xml:lang="de-DE">Titel</rdf:li>
+        </rdf:Alt>
+      </dc:title>
+      <xmp:Flash xmlns:exif="http://ns.adobe.com/exif/1.0/" rdf:parseType="Resource">
+        <exif:Fired>True</exif:Fired>
+      </xmp:Flash>
+      <xmp:Nick><rdf:value>nick</rdf:value><xmp:Label>qualifier</xmp:Label></xmp:Nick>
+    </rdf:Description>
+  </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#;
+
+    #[test]
+    fn test_parse_borrowed() {
+        let meta = XmpMeta::parse_borrowed(PACKET).unwrap();
+        assert_eq!(meta.about(), Some(""));
+        assert_eq!(meta.properties().len(), 5);
+
+        let label = meta.get_property("xmp", "Label").unwrap();
+        assert!(matches!(label, XmpValueRef::String(Cow::Borrowed("Red"))));
+        let creator = meta.get_array_item(ns::DC, "creator", 1).unwrap();
+        assert!(matches!(creator, XmpValueRef::String(Cow::Owned(_))));
+        assert_eq!(creator.as_str(), Some("Bob & Co"));
+        assert_eq!(
+            meta.get_localized_text(ns::DC, "title", "DE-de"),
+            Some(("Titel", "de-DE"))
+        );
+        assert_eq!(
+            meta.get_struct_field(ns::XMP, "Flash", ns::EXIF, "Fired")
+                .and_then(XmpValueRef::as_bool),
+            Some(true)
+        );
+        assert_eq!(
+            meta.get_property(ns::XMP, "Nick")
+                .and_then(XmpValueRef::as_str),
+            Some("nick")
+        );
+        assert_eq!(meta.get_property(ns::XMP, "Missing"), None);
+
+        let packet = PACKET.replace("begin=\"\"", "begin=\"\u{FEFF}\"");
+        let meta = XmpMeta::parse_borrowed(&packet).unwrap();
+        assert_eq!(meta.properties().len(), 5);
+
+        // The same properties as the regular parser
+        let owned = XmpMeta::parse(PACKET).unwrap();
+        for property in meta.properties() {
+            assert!(owned.does_property_exist(&property.namespace, &property.name));
+        }
+    }
+
+    #[test]
+    fn test_parse_borrowed_options() {
+        let options = ParseOptions::default().max_depth(3);
+        assert!(XmpMeta::parse_borrowed_with_options(PACKET, &options).is_err());
+        let options = ParseOptions::default().max_node_count(4);
+        assert!(XmpMeta::parse_borrowed_with_options(PACKET, &options).is_err());
+        let options = ParseOptions::default().require_xmpmeta_element();
+        assert!(XmpMeta::parse_borrowed_with_options(PACKET, &options).is_ok());
+
+        let xml = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+            <rdf:Description/></rdf:RDF>"#;
+        assert!(XmpMeta::parse_borrowed_with_options(xml, &options).is_err());
+        assert!(XmpMeta::parse_borrowed("<rdf:RDF><rdf:Description>").is_err());
+    }
+
+    #[test]
+    fn test_parse_borrowed_deep_nesting() {
+        let levels = 10_000;
+        let xml = format!(
+            r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+         xmlns:ns="http://example.com/ns/">
+  <rdf:Description rdf:about="">{}x{}</rdf:Description>
+</rdf:RDF>"#,
+            r#"<ns:a rdf:parseType="Resource">"#.repeat(levels),
+            "</ns:a>".repeat(levels)
+        );
+        assert!(matches!(
+            XmpMeta::parse_borrowed(&xml),
+            Err(XmpError::ParseError(_))
+        ));
+    }
+}
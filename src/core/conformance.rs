@@ -0,0 +1,582 @@
+//! PDF/A conformance of XMP metadata
+//!
+//! The XMP of a PDF/A document (ISO 19005) must satisfy requirements beyond
+//! those of the XMP Specification:
+//! - the packet header has no `bytes` or `encoding` attribute
+//! - `pdfaid:part` and `pdfaid:conformance` identify the PDF/A version
+//!   (for PDF/A-4, `pdfaid:rev` replaces the conformance level)
+//! - the properties of schemas other than those predefined by the XMP
+//!   Specification are described by extension schemas in
+//!   `pdfaExtension:schemas`
+//!
+//! [`check_pdfa`] reports the requirements that aren't met, and
+//! [`add_pdfa_extension_schemas`] writes the extension schemas of the custom
+//! schemas registered with
+//! [`register_schema`](crate::core::validate::register_schema).
+//!
+//! # Example
+//!
+//! ```rust
+//! use xmpkit::core::conformance::{add_pdfa_extension_schemas, check_pdfa, PdfaIssue};
+//! use xmpkit::core::validate::{register_schema, ValueForm, ValueType, XmpSchema};
+//! use xmpkit::{ns, XmpMeta};
+//!
+//! const NS: &str = "http://example.com/ns/archive/1.0/";
+//! register_schema(
+//!     XmpSchema::new(NS, "archive").property("Box", ValueForm::Simple, ValueType::Integer),
+//! )
+//! .unwrap();
+//!
+//! let mut meta = XmpMeta::new();
+//! meta.set_property(ns::PDFA, "part", 2.into()).unwrap();
+//! meta.set_property(ns::PDFA, "conformance", "B".into()).unwrap();
+//! meta.set_property(NS, "Box", 12.into()).unwrap();
+//! let packet = meta.serialize_packet().unwrap();
+//! assert_eq!(
+//!     check_pdfa(&meta, &packet),
+//!     vec![PdfaIssue::UndescribedSchema { namespace: NS.to_string() }]
+//! );
+//!
+//! add_pdfa_extension_schemas(&mut meta).unwrap();
+//! let packet = meta.serialize_packet().unwrap();
+//! assert!(check_pdfa(&meta, &packet).is_empty());
+//! ```
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::{IterOptions, XmpMeta};
+use crate::core::namespace::ns;
+use crate::core::node::{ArrayNode, ArrayType, Node, StructureNode};
+use crate::core::validate::{self, SchemaProperty, ValueForm, ValueType};
+use crate::types::value::XmpValue;
+use std::fmt;
+
+/// Schemas that PDF/A doesn't require extension schemas for: those of the
+/// XMP Specification, and the PDF/A schemas themselves
+const PREDEFINED_SCHEMAS: &[&str] = &[
+    ns::DC,
+    ns::XMP,
+    ns::XMP_RIGHTS,
+    ns::XMP_MM,
+    ns::XMP_BJ,
+    ns::XMP_PAGED,
+    ns::XMP_DM,
+    ns::PDF,
+    ns::PHOTOSHOP,
+    ns::CAMERA_RAW,
+    ns::TIFF,
+    ns::EXIF,
+    ns::EXIF_AUX,
+    ns::PDFA,
+    ns::PDFA_EXTENSION,
+];
+
+/// A PDF/A requirement that the metadata doesn't meet
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PdfaIssue {
+    /// The packet can't be parsed
+    MalformedPacket {
+        /// The parse error
+        message: String,
+    },
+    /// The packet header has a `bytes` or `encoding` attribute
+    ForbiddenHeaderAttribute {
+        /// Name of the attribute
+        attribute: String,
+    },
+    /// A `pdfaid:` identification property is missing or invalid
+    InvalidIdentification {
+        /// XMP path of the property (e.g. `pdfaid:part`)
+        path: String,
+        /// The invalid value; `None` if the property is missing
+        value: Option<String>,
+    },
+    /// An extension schema, or one of its property descriptions, lacks a
+    /// required field
+    IncompleteExtensionSchema {
+        /// XMP path of the description (e.g. `pdfaExtension:schemas[1]`)
+        path: String,
+        /// Name of the missing field (e.g. `pdfaSchema:prefix`)
+        field: String,
+    },
+    /// Properties of a schema that isn't predefined are used, but there is
+    /// no extension schema for it
+    UndescribedSchema {
+        /// Namespace URI of the schema
+        namespace: String,
+    },
+    /// A property is not described by the extension schema of its namespace
+    UndescribedProperty {
+        /// XMP path of the property
+        path: String,
+    },
+}
+
+impl fmt::Display for PdfaIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PdfaIssue::MalformedPacket { message } => write!(f, "malformed packet: {}", message),
+            PdfaIssue::ForbiddenHeaderAttribute { attribute } => {
+                write!(f, "packet header has a '{}' attribute", attribute)
+            }
+            PdfaIssue::InvalidIdentification { path, value: None } => {
+                write!(f, "{}: missing", path)
+            }
+            PdfaIssue::InvalidIdentification {
+                path,
+                value: Some(value),
+            } => write!(f, "{}: invalid value {:?}", path, value),
+            PdfaIssue::IncompleteExtensionSchema { path, field } => {
+                write!(f, "{}: missing {}", path, field)
+            }
+            PdfaIssue::UndescribedSchema { namespace } => {
+                write!(f, "no extension schema for '{}'", namespace)
+            }
+            PdfaIssue::UndescribedProperty { path } => {
+                write!(f, "{}: not described by an extension schema", path)
+            }
+        }
+    }
+}
+
+/// Check the metadata and the packet that embeds it against the XMP
+/// requirements of PDF/A
+///
+/// The packet is checked for the header attributes and parsed; the other
+/// requirements are checked on `meta`. Only top-level properties are
+/// checked against the extension schemas, not the fields of structures.
+///
+/// Returns the issues found, in the order of the list of the module
+/// documentation; an empty list if the metadata conforms.
+pub fn check_pdfa(meta: &XmpMeta, packet: &str) -> Vec<PdfaIssue> {
+    let mut issues = Vec::new();
+
+    if let Some(start) = packet.find("<?xpacket begin") {
+        let header = &packet[start..];
+        let header = &header[..header.find("?>").unwrap_or(header.len())];
+        for attribute in ["bytes", "encoding"] {
+            if header.contains(&format!(" {}=", attribute)) {
+                issues.push(PdfaIssue::ForbiddenHeaderAttribute {
+                    attribute: attribute.to_string(),
+                });
+            }
+        }
+    }
+    if let Err(error) = XmpMeta::parse(packet) {
+        issues.push(PdfaIssue::MalformedPacket {
+            message: error.to_string(),
+        });
+    }
+
+    check_identification(meta, &mut issues);
+
+    let described = described_schemas(meta, &mut issues);
+    let mut undescribed: Vec<String> = Vec::new();
+    for item in meta.iter_with(IterOptions::default().just_children()) {
+        if PREDEFINED_SCHEMAS.contains(&item.schema_ns.as_str()) {
+            continue;
+        }
+        let name = item.path.rsplit(':').next().unwrap_or_default();
+        match described.iter().find(|(uri, _)| *uri == item.schema_ns) {
+            Some((_, properties)) if !properties.iter().any(|p| p == name) => {
+                issues.push(PdfaIssue::UndescribedProperty { path: item.path });
+            }
+            Some(_) => {}
+            None if !undescribed.contains(&item.schema_ns) => {
+                undescribed.push(item.schema_ns);
+            }
+            None => {}
+        }
+    }
+    issues.extend(
+        undescribed
+            .into_iter()
+            .map(|namespace| PdfaIssue::UndescribedSchema { namespace }),
+    );
+    issues
+}
+
+/// Add extension schemas for the registered custom schemas whose properties
+/// are used
+///
+/// Each schema is described with all its properties, as `external`
+/// properties whose description is their name. Schemas that already have an
+/// extension schema are left as they are.
+///
+/// Returns the namespaces of the properties that still need an extension
+/// schema because their schema isn't registered.
+///
+/// Fails with `XmpError::NotSupported`, without changing anything, if a
+/// schema has structure properties: [`XmpSchema`](validate::XmpSchema)
+/// doesn't describe their fields, which PDF/A requires.
+pub fn add_pdfa_extension_schemas(meta: &mut XmpMeta) -> XmpResult<Vec<String>> {
+    let described: Vec<String> = described_schemas(meta, &mut Vec::new())
+        .into_iter()
+        .map(|(uri, _)| uri)
+        .collect();
+    let mut namespaces: Vec<String> = Vec::new();
+    for item in meta.iter_with(IterOptions::default().just_children()) {
+        let uri = item.schema_ns;
+        if !PREDEFINED_SCHEMAS.contains(&uri.as_str())
+            && !described.contains(&uri)
+            && !namespaces.contains(&uri)
+        {
+            namespaces.push(uri);
+        }
+    }
+
+    let mut unregistered = Vec::new();
+    let mut schemas = Vec::new();
+    for uri in namespaces {
+        match validate::get_registered_schema(&uri) {
+            Some(schema) => schemas.push(extension_schema(&schema)?),
+            None => unregistered.push(uri),
+        }
+    }
+    for schema in schemas {
+        meta.append_array_node(
+            ns::PDFA_EXTENSION,
+            "schemas",
+            Some(ArrayType::Unordered),
+            schema,
+        )?;
+    }
+    Ok(unregistered)
+}
+
+/// Check `pdfaid:part` and the conformance level or revision of the part
+fn check_identification(meta: &XmpMeta, issues: &mut Vec<PdfaIssue>) {
+    let mut invalid = |name: &str, value: Option<String>| {
+        issues.push(PdfaIssue::InvalidIdentification {
+            path: format!("{}:{}", ns::PDFA_PREFIX, name),
+            value,
+        });
+    };
+
+    let Some(part) = text(meta, ns::PDFA, "part") else {
+        return invalid("part", None);
+    };
+    let conformance = text(meta, ns::PDFA, "conformance");
+    let (levels, required): (&[&str], bool) = match part.trim() {
+        "1" => (&["A", "B"], true),
+        "2" | "3" => (&["A", "B", "U"], true),
+        "4" => (&["E", "F"], false),
+        _ => return invalid("part", Some(part)),
+    };
+    match conformance {
+        Some(level) if !levels.contains(&level.as_str()) => invalid("conformance", Some(level)),
+        None if required => invalid("conformance", None),
+        _ => {}
+    }
+
+    if part.trim() == "4" {
+        match text(meta, ns::PDFA, "rev") {
+            Some(rev) if rev.len() != 4 || !rev.chars().all(|c| c.is_ascii_digit()) => {
+                invalid("rev", Some(rev))
+            }
+            None => invalid("rev", None),
+            _ => {}
+        }
+    }
+}
+
+/// Read the namespaces of the extension schemas and the names of their
+/// properties, reporting the fields missing from their descriptions
+fn described_schemas(meta: &XmpMeta, issues: &mut Vec<PdfaIssue>) -> Vec<(String, Vec<String>)> {
+    let mut incomplete = |path: &str, field: &str| {
+        issues.push(PdfaIssue::IncompleteExtensionSchema {
+            path: format!("{}:{}", ns::PDFA_EXTENSION_PREFIX, path),
+            field: field.to_string(),
+        });
+    };
+
+    let mut described = Vec::new();
+    for i in 1..=item_count(meta, ns::PDFA_EXTENSION, "schemas") {
+        let schema = format!("schemas[{}]", i);
+        let field = |name: &str| {
+            text(
+                meta,
+                ns::PDFA_EXTENSION,
+                &format!("{}/{}:{}", schema, ns::PDFA_SCHEMA_PREFIX, name),
+            )
+        };
+        for name in ["namespaceURI", "prefix"] {
+            if field(name).is_none() {
+                incomplete(&schema, &format!("{}:{}", ns::PDFA_SCHEMA_PREFIX, name));
+            }
+        }
+
+        let list = format!("{}/{}:property", schema, ns::PDFA_SCHEMA_PREFIX);
+        let mut properties = Vec::new();
+        for j in 1..=item_count(meta, ns::PDFA_EXTENSION, &list) {
+            let property = format!("{}[{}]", list, j);
+            for name in ["name", "valueType", "category", "description"] {
+                let path = format!("{}/{}:{}", property, ns::PDFA_PROPERTY_PREFIX, name);
+                match text(meta, ns::PDFA_EXTENSION, &path) {
+                    Some(value) if name == "name" => properties.push(value),
+                    Some(_) => {}
+                    None => {
+                        incomplete(&property, &format!("{}:{}", ns::PDFA_PROPERTY_PREFIX, name))
+                    }
+                }
+            }
+        }
+        if let Some(uri) = field("namespaceURI") {
+            described.push((uri, properties));
+        }
+    }
+    described
+}
+
+/// Create the extension schema of a custom schema
+fn extension_schema(schema: &validate::XmpSchema) -> XmpResult<Node> {
+    let mut properties = ArrayNode::new(ArrayType::Ordered);
+    for property in schema.properties() {
+        let mut node = StructureNode::new();
+        set_field(&mut node, ns::PDFA_PROPERTY, "name", property.name());
+        set_field(
+            &mut node,
+            ns::PDFA_PROPERTY,
+            "valueType",
+            &value_type(schema, property)?,
+        );
+        set_field(&mut node, ns::PDFA_PROPERTY, "category", "external");
+        set_field(&mut node, ns::PDFA_PROPERTY, "description", property.name());
+        properties.append(Node::Structure(node));
+    }
+
+    let mut node = StructureNode::new();
+    set_field(
+        &mut node,
+        ns::PDFA_SCHEMA,
+        "schema",
+        &format!("{} schema", schema.prefix()),
+    );
+    set_field(&mut node, ns::PDFA_SCHEMA, "namespaceURI", schema.uri());
+    set_field(&mut node, ns::PDFA_SCHEMA, "prefix", schema.prefix());
+    node.set_field(
+        format!("{}:property", ns::PDFA_SCHEMA),
+        Node::Array(properties),
+    );
+    Ok(Node::Structure(node))
+}
+
+/// Get the PDF/A value type of a property (e.g. `bag Text`)
+fn value_type(schema: &validate::XmpSchema, property: &SchemaProperty) -> XmpResult<String> {
+    let item = match property.value_type() {
+        _ if !property.choices().is_empty() => "Closed Choice of Text",
+        ValueType::Text => "Text",
+        ValueType::Integer => "Integer",
+        ValueType::Real => "Real",
+        ValueType::Boolean => "Boolean",
+        ValueType::Rational => "Rational",
+        ValueType::Uri => "URI",
+        ValueType::Date => "Date",
+        ValueType::Struct => "",
+    };
+    if item.is_empty() || property.form() == ValueForm::Struct {
+        return Err(XmpError::NotSupported(format!(
+            "Can't describe the fields of the structure {}:{}",
+            schema.prefix(),
+            property.name()
+        )));
+    }
+    Ok(match property.form() {
+        ValueForm::Bag => format!("bag {}", item),
+        ValueForm::Seq => format!("seq {}", item),
+        ValueForm::Alt => format!("alt {}", item),
+        ValueForm::LangAlt => "Lang Alt".to_string(),
+        _ => item.to_string(),
+    })
+}
+
+/// Set a simple field of a structure
+fn set_field(node: &mut StructureNode, namespace: &str, name: &str, value: &str) {
+    node.set_field(format!("{}:{}", namespace, name), Node::simple(value));
+}
+
+/// Get a non-empty text value
+fn text(meta: &XmpMeta, namespace: &str, path: &str) -> Option<String> {
+    match meta.get_property_by_path(namespace, path)? {
+        XmpValue::String(s) | XmpValue::Uri(s) if !s.is_empty() => Some(s),
+        XmpValue::Integer(i) => Some(i.to_string()),
+        _ => None,
+    }
+}
+
+/// Count the items of an array path
+fn item_count(meta: &XmpMeta, namespace: &str, path: &str) -> usize {
+    (1..)
+        .take_while(|i| {
+            meta.get_property_by_path(namespace, &format!("{}[{}]", path, i))
+                .is_some()
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::register_namespace;
+    use crate::core::validate::{register_schema, XmpSchema};
+
+    const NS: &str = "http://example.com/ns/conformance/1.0/";
+    const OTHER_NS: &str = "http://example.com/ns/conformance-other/1.0/";
+
+    fn register() {
+        register_schema(
+            XmpSchema::new(NS, "conf")
+                .property("Box", ValueForm::Simple, ValueType::Integer)
+                .property("Keywords", ValueForm::Bag, ValueType::Text)
+                .property("Caption", ValueForm::LangAlt, ValueType::Text)
+                .choice("Status", ValueForm::Simple, &["draft", "final"]),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_check_pdfa() {
+        register();
+        let mut meta = XmpMeta::new();
+        meta.set_property(NS, "Box", 4.into()).unwrap();
+        register_namespace(OTHER_NS, "other").unwrap();
+        meta.set_property(OTHER_NS, "Note", "x".into()).unwrap();
+        let packet = r#"<?xpacket begin="" bytes="1024" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+  <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+    <rdf:Description rdf:about="">
+  </rdf:RDF>
+</x:xmpmeta>"#;
+
+        let issues = check_pdfa(&meta, packet);
+        assert_eq!(
+            issues[0],
+            PdfaIssue::ForbiddenHeaderAttribute {
+                attribute: "bytes".to_string()
+            }
+        );
+        assert!(matches!(issues[1], PdfaIssue::MalformedPacket { .. }));
+        assert_eq!(
+            issues[2],
+            PdfaIssue::InvalidIdentification {
+                path: "pdfaid:part".to_string(),
+                value: None
+            }
+        );
+        assert_eq!(
+            issues[3..],
+            [
+                PdfaIssue::UndescribedSchema {
+                    namespace: OTHER_NS.to_string()
+                },
+                PdfaIssue::UndescribedSchema {
+                    namespace: NS.to_string()
+                },
+            ]
+        );
+
+        meta.set_property(ns::PDFA, "part", 1.into()).unwrap();
+        meta.set_property(ns::PDFA, "conformance", "U".into())
+            .unwrap();
+        let unregistered = add_pdfa_extension_schemas(&mut meta).unwrap();
+        assert_eq!(unregistered, vec![OTHER_NS.to_string()]);
+        // An extension schema that lacks a property
+        meta.set_property(NS, "Status", "final".into()).unwrap();
+        meta.set_property(NS, "Extra", "1".into()).unwrap();
+        meta.set_property_by_path(
+            ns::PDFA_EXTENSION,
+            "schemas[1]/pdfaSchema:property[1]/pdfaProperty:category",
+            "".into(),
+        )
+        .unwrap();
+
+        let packet = meta.serialize_packet().unwrap();
+        assert_eq!(
+            check_pdfa(&meta, &packet),
+            vec![
+                PdfaIssue::InvalidIdentification {
+                    path: "pdfaid:conformance".to_string(),
+                    value: Some("U".to_string())
+                },
+                PdfaIssue::IncompleteExtensionSchema {
+                    path: "pdfaExtension:schemas[1]/pdfaSchema:property[1]".to_string(),
+                    field: "pdfaProperty:category".to_string()
+                },
+                PdfaIssue::UndescribedProperty {
+                    path: "conf:Extra".to_string()
+                },
+                PdfaIssue::UndescribedSchema {
+                    namespace: OTHER_NS.to_string()
+                },
+            ]
+        );
+
+        meta.set_property(ns::PDFA, "part", 4.into()).unwrap();
+        meta.delete_property(ns::PDFA, "conformance").unwrap();
+        meta.set_property(ns::PDFA, "rev", "20".into()).unwrap();
+        let issues = check_pdfa(&meta, &packet);
+        assert_eq!(
+            issues[0],
+            PdfaIssue::InvalidIdentification {
+                path: "pdfaid:rev".to_string(),
+                value: Some("20".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn test_add_pdfa_extension_schemas() {
+        register();
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::PDFA, "part", 3.into()).unwrap();
+        meta.set_property(ns::PDFA, "conformance", "A".into())
+            .unwrap();
+        meta.set_property(ns::DC, "format", "application/pdf".into())
+            .unwrap();
+        meta.set_property(NS, "Box", 4.into()).unwrap();
+        meta.append_array_item(NS, "Keywords", "tax".into())
+            .unwrap();
+        meta.set_localized_text(NS, "Caption", "", "x-default", "Receipts")
+            .unwrap();
+
+        assert!(add_pdfa_extension_schemas(&mut meta).unwrap().is_empty());
+        // A schema is described only once
+        add_pdfa_extension_schemas(&mut meta).unwrap();
+        assert_eq!(meta.count_array_items(ns::PDFA_EXTENSION, "schemas"), 1);
+
+        let packet = meta.serialize_packet().unwrap();
+        assert!(packet.contains("<pdfaExtension:schemas>"));
+        let parsed = XmpMeta::parse(&packet).unwrap();
+        assert!(check_pdfa(&parsed, &packet).is_empty());
+        let types: Vec<String> = (1..=4)
+            .map(|i| {
+                let path = format!(
+                    "schemas[1]/pdfaSchema:property[{}]/pdfaProperty:valueType",
+                    i
+                );
+                text(&parsed, ns::PDFA_EXTENSION, &path).unwrap()
+            })
+            .collect();
+        assert_eq!(
+            types,
+            ["Integer", "bag Text", "Lang Alt", "Closed Choice of Text"]
+        );
+
+        // Structures can't be described
+        let struct_ns = "http://example.com/ns/conformance-struct/1.0/";
+        register_schema(XmpSchema::new(struct_ns, "confs").property(
+            "Location",
+            ValueForm::Struct,
+            ValueType::Struct,
+        ))
+        .unwrap();
+        let mut meta = XmpMeta::new();
+        meta.set_struct_field(struct_ns, "Location", struct_ns, "City", "Paris".into())
+            .unwrap();
+        assert!(matches!(
+            add_pdfa_extension_schemas(&mut meta),
+            Err(XmpError::NotSupported(_))
+        ));
+        assert!(!meta.does_property_exist(ns::PDFA_EXTENSION, "schemas"));
+    }
+}
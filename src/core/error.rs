@@ -46,6 +46,10 @@ pub enum XmpError {
     /// Operation not supported
     #[error("Operation not supported: {0}")]
     NotSupported(String),
+
+    /// Operation cancelled by a progress callback
+    #[error("Operation cancelled")]
+    Cancelled,
 }
 
 /// Result type alias for XMP operations
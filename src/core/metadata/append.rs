@@ -0,0 +1,535 @@
+//! Merging one XMP tree into another
+//!
+//! This is a port of `AppendProperties` and `ApplyTemplate` from the Adobe
+//! XMP Toolkit. Source properties that don't exist in the destination are
+//! copied. Existing structures are merged field by field, localized text
+//! arrays gain the missing languages and other arrays gain the items they
+//! don't already contain. Existing simple values are kept unless
+//! [`AppendOptions::replace_old_values`] is set.
+//!
+//! [`XmpMeta::apply_template`] merges the same way, with each step
+//! (clearing, adding and replacing) chosen by [`TemplateOptions`].
+
+use super::XmpMeta;
+use crate::core::error::XmpResult;
+use crate::core::namespace::ns;
+use crate::core::node::{ArrayType, Node, StructureNode};
+
+/// Options to control [`XmpMeta::append_from`]
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::{ns, AppendOptions, XmpMeta, XmpValue};
+///
+/// let mut template = XmpMeta::new();
+/// template.set_property(ns::XMP_RIGHTS, "Marked", XmpValue::Boolean(true)).unwrap();
+///
+/// let mut meta = XmpMeta::new();
+/// meta.append_from(&template, AppendOptions::default()).unwrap();
+/// assert_eq!(meta.get_property_bool(ns::XMP_RIGHTS, "Marked"), Some(true));
+/// ```
+#[derive(Default, Clone, Copy, Debug)]
+pub struct AppendOptions {
+    /// Replace existing values with the source values
+    pub(crate) replace_old_values: bool,
+    /// Delete destination values when the source value is empty
+    pub(crate) delete_empty_values: bool,
+    /// Include internal properties (not only external ones)
+    pub(crate) include_internal: bool,
+}
+
+impl AppendOptions {
+    /// Replace existing values with the source values.
+    ///
+    /// By default existing simple values are kept and only composite
+    /// values are merged.
+    pub fn replace_old_values(mut self) -> Self {
+        self.replace_old_values = true;
+        self
+    }
+
+    /// Delete destination values when the source value is empty.
+    ///
+    /// A simple value is empty if its string is empty; an array or
+    /// structure is empty if it has no items or fields.
+    pub fn delete_empty_values(mut self) -> Self {
+        self.delete_empty_values = true;
+        self
+    }
+
+    /// Include internal properties.
+    ///
+    /// Internal properties are maintained by applications and file handlers
+    /// (such as `xmp:ModifyDate`, `xmpMM:*` or most of `exif:*`). By default
+    /// only external properties, which users edit, are appended.
+    pub fn include_internal(mut self) -> Self {
+        self.include_internal = true;
+        self
+    }
+}
+
+/// Options to control [`XmpMeta::apply_template`]
+///
+/// Equivalent to the `kXMPTemplate_*` options of the Adobe XMP Toolkit.
+/// Without any option, applying a template does nothing.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::{ns, TemplateOptions, XmpMeta, XmpValue};
+///
+/// let mut template = XmpMeta::new();
+/// template.set_property(ns::DC, "rights", XmpValue::String("(c) Studio".into())).unwrap();
+///
+/// let mut meta = XmpMeta::new();
+/// meta.set_property(ns::DC, "source", XmpValue::String("Scan".into())).unwrap();
+/// meta.apply_template(
+///     &template,
+///     TemplateOptions::default().clear_unnamed_properties().add_new_properties(),
+/// )
+/// .unwrap();
+/// assert!(meta.has_property(ns::DC, "rights"));
+/// assert!(!meta.has_property(ns::DC, "source"));
+/// ```
+#[derive(Default, Clone, Copy, Debug)]
+pub struct TemplateOptions {
+    /// Delete the properties that aren't in the template
+    pub(crate) clear_unnamed_properties: bool,
+    /// Add the template properties that don't exist yet
+    pub(crate) add_new_properties: bool,
+    /// Replace existing properties with the template ones
+    pub(crate) replace_existing_properties: bool,
+    /// Delete existing properties whose template value is empty
+    pub(crate) replace_with_delete_empty: bool,
+    /// Include internal properties (not only external ones)
+    pub(crate) include_internal_properties: bool,
+}
+
+impl TemplateOptions {
+    /// Delete the properties that aren't in the template.
+    pub fn clear_unnamed_properties(mut self) -> Self {
+        self.clear_unnamed_properties = true;
+        self
+    }
+
+    /// Add the template properties that don't exist yet.
+    ///
+    /// Existing structures and arrays gain the template fields and items
+    /// they lack, unless [`Self::replace_existing_properties`] is set.
+    pub fn add_new_properties(mut self) -> Self {
+        self.add_new_properties = true;
+        self
+    }
+
+    /// Replace existing properties with the template ones.
+    pub fn replace_existing_properties(mut self) -> Self {
+        self.replace_existing_properties = true;
+        self
+    }
+
+    /// Replace existing properties, and delete those whose template value
+    /// is empty.
+    ///
+    /// Implies [`Self::replace_existing_properties`]. Empty values are not
+    /// deleted together with [`Self::clear_unnamed_properties`], which
+    /// keeps the empty template values instead.
+    pub fn replace_with_delete_empty(mut self) -> Self {
+        self.replace_with_delete_empty = true;
+        self
+    }
+
+    /// Include internal properties.
+    ///
+    /// By default only external properties, which users edit, are cleared,
+    /// added or replaced (see [`AppendOptions::include_internal`]).
+    pub fn include_internal_properties(mut self) -> Self {
+        self.include_internal_properties = true;
+        self
+    }
+}
+
+impl XmpMeta {
+    /// Apply a template to this XMP object
+    ///
+    /// This is a port of `ApplyTemplate` from the Adobe XMP Toolkit, used
+    /// to stamp the same properties (such as copyright and creator) on
+    /// many files. The properties that aren't in the template are cleared
+    /// first, then the template properties are added or replaced, as chosen
+    /// by `options`. Namespaces registered in `template` are registered
+    /// here too.
+    pub fn apply_template(
+        &mut self,
+        template: &XmpMeta,
+        options: TemplateOptions,
+    ) -> XmpResult<()> {
+        let replace = options.replace_existing_properties || options.replace_with_delete_empty;
+        let merge = AppendOptions {
+            replace_old_values: replace,
+            delete_empty_values: options.replace_with_delete_empty
+                && !options.clear_unnamed_properties,
+            include_internal: options.include_internal_properties,
+        };
+
+        // Copy the template first: it may share its root with `self`
+        let source = root_read!(template.root).clone();
+        self.register_namespaces_of(template);
+
+        let mut root = root_write!(self.root);
+        if options.clear_unnamed_properties {
+            root.fields.retain(|key, _| {
+                let Some((ns_uri, name)) = key.rsplit_once(':') else {
+                    return true;
+                };
+                source.fields.contains_key(key)
+                    || (!merge.include_internal && is_internal_property(ns_uri, name))
+            });
+        }
+        if !options.add_new_properties && !replace {
+            return Ok(());
+        }
+        for (key, node) in &source.fields {
+            let Some((ns_uri, name)) = key.rsplit_once(':') else {
+                continue;
+            };
+            if !merge.include_internal && is_internal_property(ns_uri, name) {
+                continue;
+            }
+            // Only existing properties are replaced without add_new_properties
+            if options.add_new_properties || root.get_field(key).is_some() {
+                append_subtree(&mut root, key, node, &merge);
+            }
+        }
+        Ok(())
+    }
+
+    /// Append the properties of another XMP object to this one
+    ///
+    /// See the [module documentation](self) for how existing values are
+    /// merged. Namespaces registered in `other` are registered here too.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The XMP object to copy properties from
+    /// * `options` - Options to control merging
+    pub fn append_from(&mut self, other: &XmpMeta, options: AppendOptions) -> XmpResult<()> {
+        // Copy the source first: `other` may share its root with `self`
+        let source = root_read!(other.root).clone();
+        self.register_namespaces_of(other);
+
+        let mut root = root_write!(self.root);
+        for (key, node) in &source.fields {
+            let Some((ns_uri, name)) = key.rsplit_once(':') else {
+                continue;
+            };
+            if options.include_internal || !is_internal_property(ns_uri, name) {
+                append_subtree(&mut root, key, node, &options);
+            }
+        }
+        Ok(())
+    }
+
+    /// Register the namespaces of `other` that aren't registered here
+    pub(super) fn register_namespaces_of(&mut self, other: &XmpMeta) {
+        for (uri, prefix) in other.namespaces.get_all_namespaces() {
+            if !self.namespaces.has_uri(&uri) {
+                let _ = self.namespaces.register(&uri, &prefix);
+            }
+        }
+    }
+}
+
+/// Append `source` as the field `key` of `dest`
+fn append_subtree(dest: &mut StructureNode, key: &str, source: &Node, options: &AppendOptions) {
+    if options.delete_empty_values && is_empty_value(source) {
+        dest.remove_field(key);
+        return;
+    }
+
+    let Some(dest_node) = dest.get_field_mut(key) else {
+        dest.set_field(key, clone_node(source, options));
+        return;
+    };
+
+    if options.replace_old_values {
+        *dest_node = clone_node(source, options);
+        return;
+    }
+
+    match (dest_node, source) {
+        (Node::Structure(dest_struct), Node::Structure(source_struct)) => {
+            for (field_key, field) in &source_struct.fields {
+                append_subtree(dest_struct, field_key, field, options);
+            }
+            if options.delete_empty_values && dest_struct.fields.is_empty() {
+                dest.remove_field(key);
+            }
+        }
+        (Node::Array(dest_array), Node::Array(source_array))
+            if dest_array.array_type == source_array.array_type =>
+        {
+            let is_alt_text = source_array.array_type == ArrayType::Alternative
+                && source_array
+                    .items
+                    .iter()
+                    .any(|item| item_lang(item).is_some());
+
+            for item in &source_array.items {
+                if options.delete_empty_values && is_empty_value(item) {
+                    continue;
+                }
+
+                if is_alt_text {
+                    let Some(lang) = item_lang(item) else {
+                        continue;
+                    };
+                    let exists = dest_array
+                        .items
+                        .iter()
+                        .any(|d| item_lang(d).is_some_and(|l| l.eq_ignore_ascii_case(lang)));
+                    if exists {
+                        continue;
+                    }
+                    if lang == "x-default" {
+                        dest_array.items.insert(0, item.clone());
+                    } else {
+                        dest_array.items.push(item.clone());
+                    }
+                } else if !dest_array.items.contains(item) {
+                    dest_array.items.push(item.clone());
+                }
+            }
+        }
+        // Existing simple values are kept, and values of a different form
+        // are left alone
+        _ => {}
+    }
+}
+
+/// Clone a node, dropping empty descendants if `delete_empty_values` is set
+fn clone_node(node: &Node, options: &AppendOptions) -> Node {
+    if !options.delete_empty_values {
+        return node.clone();
+    }
+
+    let mut node = node.clone();
+    match &mut node {
+        Node::Simple(_) => {}
+        Node::Array(array) => array.items.retain(|item| !is_empty_value(item)),
+        Node::Structure(structure) => structure.fields.retain(|_, field| !is_empty_value(field)),
+    }
+    node
+}
+
+fn is_empty_value(node: &Node) -> bool {
+    match node {
+        Node::Simple(simple) => simple.value.is_empty(),
+        Node::Array(array) => array.is_empty(),
+        Node::Structure(structure) => structure.fields.is_empty(),
+    }
+}
+
+fn item_lang(node: &Node) -> Option<&str> {
+    node.qualifiers()
+        .iter()
+        .find(|q| q.namespace == ns::XML && q.name == "lang")
+        .map(|q| q.value.as_str())
+}
+
+/// Check if a top-level property is internal, following the Adobe XMP Toolkit
+pub(super) fn is_internal_property(ns_uri: &str, name: &str) -> bool {
+    match ns_uri {
+        ns::DC => matches!(name, "format" | "language"),
+        ns::XMP => matches!(
+            name,
+            "BaseURL" | "CreatorTool" | "Format" | "Locale" | "MetadataDate" | "ModifyDate"
+        ),
+        ns::PDF => matches!(
+            name,
+            "BaseURL" | "Creator" | "ModDate" | "PDFVersion" | "Producer"
+        ),
+        ns::TIFF => !matches!(name, "ImageDescription" | "Artist" | "Copyright"),
+        ns::EXIF => name != "UserComment",
+        ns::PHOTOSHOP => matches!(name, "ICCProfile" | "TextLayers"),
+        ns::EXIF_AUX | ns::CAMERA_RAW | ns::XMP_MM => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::value::XmpValue;
+
+    fn template() -> XmpMeta {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "rights", XmpValue::String("(c) Template".into()))
+            .unwrap();
+        meta.set_property(ns::XMP, "CreatorTool", XmpValue::String("Template".into()))
+            .unwrap();
+        meta.append_array_item(ns::DC, "subject", "common".into())
+            .unwrap();
+        meta.append_array_item(ns::DC, "subject", "shared".into())
+            .unwrap();
+        meta.set_localized_text(ns::DC, "title", "", "x-default", "Template")
+            .unwrap();
+        meta.set_localized_text(ns::DC, "title", "", "fr", "Modele")
+            .unwrap();
+        meta.set_property(ns::PHOTOSHOP, "City", XmpValue::String(String::new()))
+            .unwrap();
+        meta
+    }
+
+    fn image() -> XmpMeta {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "rights", XmpValue::String("(c) Image".into()))
+            .unwrap();
+        meta.append_array_item(ns::DC, "subject", "shared".into())
+            .unwrap();
+        meta.set_localized_text(ns::DC, "title", "", "en", "Image")
+            .unwrap();
+        meta.set_property(ns::PHOTOSHOP, "City", XmpValue::String("Paris".into()))
+            .unwrap();
+        meta
+    }
+
+    #[test]
+    fn test_append_merge() {
+        let mut meta = image();
+        meta.append_from(&template(), AppendOptions::default())
+            .unwrap();
+
+        // Existing simple values are kept, internal properties skipped
+        assert_eq!(
+            meta.get_property(ns::DC, "rights"),
+            Some(XmpValue::String("(c) Image".to_string()))
+        );
+        assert!(!meta.has_property(ns::XMP, "CreatorTool"));
+        assert_eq!(
+            meta.get_property(ns::PHOTOSHOP, "City"),
+            Some(XmpValue::String("Paris".to_string()))
+        );
+
+        // Arrays gain missing items
+        assert_eq!(meta.count_array_items(ns::DC, "subject"), 2);
+        assert_eq!(
+            meta.get_array_item(ns::DC, "subject", 1),
+            Some(XmpValue::String("common".to_string()))
+        );
+
+        // Localized text gains missing languages, x-default first
+        assert_eq!(meta.count_array_items(ns::DC, "title"), 3);
+        assert_eq!(
+            meta.get_localized_text(ns::DC, "title", "", "fr")
+                .unwrap()
+                .0,
+            "Modele"
+        );
+        assert_eq!(
+            meta.get_property_by_path(ns::DC, "title[1]/?xml:lang"),
+            Some(XmpValue::String("x-default".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_append_options() {
+        let mut meta = image();
+        let options = AppendOptions::default()
+            .replace_old_values()
+            .delete_empty_values()
+            .include_internal();
+        meta.append_from(&template(), options).unwrap();
+
+        assert_eq!(
+            meta.get_property(ns::DC, "rights"),
+            Some(XmpValue::String("(c) Template".to_string()))
+        );
+        assert!(meta.has_property(ns::XMP, "CreatorTool"));
+        assert!(!meta.has_property(ns::PHOTOSHOP, "City"));
+        assert_eq!(meta.count_array_items(ns::DC, "subject"), 2);
+        assert_eq!(meta.count_array_items(ns::DC, "title"), 2);
+        assert_eq!(
+            meta.get_localized_text(ns::DC, "title", "", "fr")
+                .unwrap()
+                .0,
+            "Modele"
+        );
+    }
+
+    #[test]
+    fn test_apply_template() {
+        // Adding keeps existing values, like append_from
+        let mut meta = image();
+        meta.apply_template(&template(), TemplateOptions::default().add_new_properties())
+            .unwrap();
+        assert_eq!(
+            meta.get_property(ns::DC, "rights"),
+            Some(XmpValue::String("(c) Image".to_string()))
+        );
+        assert_eq!(meta.count_array_items(ns::DC, "subject"), 2);
+        assert!(!meta.has_property(ns::XMP, "CreatorTool"));
+
+        // Replacing alone doesn't add new properties
+        let mut meta = image();
+        meta.apply_template(
+            &template(),
+            TemplateOptions::default().replace_existing_properties(),
+        )
+        .unwrap();
+        assert_eq!(
+            meta.get_property(ns::DC, "rights"),
+            Some(XmpValue::String("(c) Template".to_string()))
+        );
+        assert_eq!(meta.count_array_items(ns::DC, "title"), 2);
+        assert_eq!(
+            meta.get_property(ns::PHOTOSHOP, "City"),
+            Some(XmpValue::String(String::new()))
+        );
+
+        let mut meta = image();
+        meta.apply_template(
+            &template(),
+            TemplateOptions::default().replace_with_delete_empty(),
+        )
+        .unwrap();
+        assert!(!meta.has_property(ns::PHOTOSHOP, "City"));
+        assert!(!meta.has_property(ns::XMP, "CreatorTool"));
+
+        // Clearing keeps internal properties and the empty template values
+        let mut meta = image();
+        meta.set_property(ns::DC, "source", XmpValue::String("Scan".into()))
+            .unwrap();
+        meta.set_property(ns::XMP, "ModifyDate", XmpValue::String("2024".into()))
+            .unwrap();
+        let options = TemplateOptions::default()
+            .clear_unnamed_properties()
+            .add_new_properties()
+            .replace_with_delete_empty();
+        meta.apply_template(&template(), options).unwrap();
+        assert!(!meta.has_property(ns::DC, "source"));
+        assert!(meta.has_property(ns::XMP, "ModifyDate"));
+        assert_eq!(
+            meta.get_property(ns::PHOTOSHOP, "City"),
+            Some(XmpValue::String(String::new()))
+        );
+
+        meta.apply_template(
+            &XmpMeta::new(),
+            TemplateOptions::default()
+                .clear_unnamed_properties()
+                .include_internal_properties(),
+        )
+        .unwrap();
+        assert!(!meta.has_property(ns::XMP, "ModifyDate"));
+        assert!(!meta.has_property(ns::DC, "rights"));
+    }
+
+    #[test]
+    fn test_append_from_clone() {
+        // Clones share their root; appending must not deadlock
+        let mut meta = image();
+        let copy = meta.clone();
+        meta.append_from(&copy, AppendOptions::default()).unwrap();
+        assert_eq!(meta.count_array_items(ns::DC, "subject"), 1);
+    }
+}
@@ -0,0 +1,509 @@
+//! Catenating array items into a string and separating them back
+//!
+//! This is a port of `CatenateArrayItems` and `SeparateArrayItems` from the
+//! Adobe XMP Toolkit, so that values like `"red; green; blue"` can be
+//! edited as a single string. The two operations are inverses: items that
+//! contain separators are quoted when catenated, doubling the quotes inside
+//! them.
+//!
+//! When separating, items are separated by semicolons, controls, commas
+//! (unless [`SeparateOptions::allow_commas`] is set), and spaces that are
+//! leading, trailing, repeated or next to another separator. A single space
+//! between words is part of the item. Quotes may be any of the quotation
+//! marks of Unicode, such as `"`, `«»` or `「」`.
+
+use super::XmpMeta;
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::node::{ArrayNode, ArrayType, Node};
+use crate::core::validate;
+
+/// Options to control [`XmpMeta::catenate_array_items`] and
+/// [`XmpMeta::separate_array_items`]
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::{core::SeparateOptions, ns, XmpMeta};
+///
+/// let mut meta = XmpMeta::new();
+/// meta.separate_array_items(ns::DC, "subject", "red; green, blue", SeparateOptions::default())
+///     .unwrap();
+/// assert_eq!(meta.count_array_items(ns::DC, "subject"), 3);
+///
+/// let keywords = meta
+///     .catenate_array_items(ns::DC, "subject", "; ", "\"", SeparateOptions::default())
+///     .unwrap();
+/// assert_eq!(keywords, "red; green; blue");
+/// ```
+#[derive(Default, Clone, Copy, Debug)]
+pub struct SeparateOptions {
+    /// Form of the array created by `separate_array_items`
+    pub(crate) array_type: Option<ArrayType>,
+    /// Commas are part of the items
+    pub(crate) allow_commas: bool,
+}
+
+impl SeparateOptions {
+    /// Create the array with this form when separating.
+    ///
+    /// By default an existing array keeps its form and a new one is an
+    /// `rdf:Bag` (or has the form defined by a registered schema). If the
+    /// array exists with a different form, separating fails.
+    pub fn array_type(mut self, array_type: ArrayType) -> Self {
+        self.array_type = Some(array_type);
+        self
+    }
+
+    /// Treat commas as part of the items rather than as separators.
+    pub fn allow_commas(mut self) -> Self {
+        self.allow_commas = true;
+        self
+    }
+}
+
+/// Kind of a character for separating items
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharKind {
+    Normal,
+    Space,
+    Comma,
+    Semicolon,
+    Quote,
+    Control,
+}
+
+fn classify(c: char) -> CharKind {
+    match c {
+        ' ' | '\u{3000}' | '\u{303F}' | '\u{2000}'..='\u{200B}' => CharKind::Space,
+        ',' | '\u{FF0C}' | '\u{FF64}' | '\u{FE50}' | '\u{FE51}' | '\u{3001}' | '\u{060C}'
+        | '\u{055D}' => CharKind::Comma,
+        ';' | '\u{FF1B}' | '\u{FE54}' | '\u{061B}' | '\u{037E}' => CharKind::Semicolon,
+        '"'
+        | '\u{00AB}'
+        | '\u{00BB}'
+        | '\u{2015}'
+        | '\u{2018}'..='\u{201F}'
+        | '\u{2039}'
+        | '\u{203A}'
+        | '\u{300C}'..='\u{300F}'
+        | '\u{301D}'..='\u{301F}' => CharKind::Quote,
+        '\u{0000}'..='\u{001F}' | '\u{007F}' | '\u{2028}' | '\u{2029}' => CharKind::Control,
+        _ => CharKind::Normal,
+    }
+}
+
+/// Get the closing quote of an opening quote
+fn closing_quote(open: char) -> Option<char> {
+    Some(match open {
+        '"' => '"',
+        '\u{00AB}' => '\u{00BB}',
+        '\u{00BB}' => '\u{00AB}',
+        '\u{2015}' => '\u{2015}',
+        '\u{2018}' => '\u{2019}',
+        '\u{201A}' => '\u{201B}',
+        '\u{201C}' => '\u{201D}',
+        '\u{201E}' => '\u{201F}',
+        '\u{2039}' => '\u{203A}',
+        '\u{203A}' => '\u{2039}',
+        '\u{300C}' => '\u{300D}',
+        '\u{300E}' => '\u{300F}',
+        '\u{301D}' => '\u{301F}',
+        _ => return None,
+    })
+}
+
+/// Check if `c` closes a value opened with `open`
+fn is_closing_quote(c: char, open: char, close: char) -> bool {
+    c == close || (open == '\u{301D}' && c == '\u{301E}')
+}
+
+/// Check if `c` is one of the quotes surrounding a value
+fn is_surrounding_quote(c: char, open: char, close: char) -> bool {
+    c == open || is_closing_quote(c, open, close)
+}
+
+/// Quote an item if it contains separators, doubling the surrounding
+/// quotes inside it
+fn apply_quotes(item: &str, open: char, close: char, allow_commas: bool) -> String {
+    let mut chars = item.chars().peekable();
+    let mut needs_quotes = matches!(
+        item.chars().next().map(classify),
+        Some(CharKind::Quote | CharKind::Space)
+    ) || item.ends_with(|c| classify(c) == CharKind::Space);
+    let mut prev_space = false;
+    while let Some(c) = chars.next() {
+        if needs_quotes {
+            break;
+        }
+        match classify(c) {
+            CharKind::Space => {
+                // Multiple spaces, or a space next to another separator
+                needs_quotes = prev_space
+                    || chars.peek().is_some_and(|&next| {
+                        matches!(classify(next), CharKind::Semicolon | CharKind::Control)
+                            || (classify(next) == CharKind::Comma && !allow_commas)
+                    });
+                prev_space = true;
+            }
+            CharKind::Semicolon | CharKind::Control => needs_quotes = true,
+            CharKind::Comma => needs_quotes = !allow_commas,
+            CharKind::Normal | CharKind::Quote => prev_space = false,
+        }
+    }
+    if !needs_quotes {
+        return item.to_string();
+    }
+
+    let mut quoted = String::with_capacity(item.len() + 2);
+    quoted.push(open);
+    for c in item.chars() {
+        if is_surrounding_quote(c, open, close) {
+            quoted.push(c);
+        }
+        quoted.push(c);
+    }
+    quoted.push(close);
+    quoted
+}
+
+/// Split a string into items
+fn split_items(text: &str, allow_commas: bool) -> Vec<String> {
+    let is_item_char = |kind: CharKind| {
+        matches!(kind, CharKind::Normal | CharKind::Quote)
+            || (kind == CharKind::Comma && allow_commas)
+    };
+    let chars: Vec<char> = text.chars().collect();
+    let mut items = Vec::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        // Skip separators (including commas, which can't start an item)
+        while pos < chars.len()
+            && !matches!(classify(chars[pos]), CharKind::Normal | CharKind::Quote)
+        {
+            pos += 1;
+        }
+        if pos >= chars.len() {
+            break;
+        }
+
+        let mut item = String::new();
+        let open = chars[pos];
+        match closing_quote(open).filter(|_| classify(open) == CharKind::Quote) {
+            None => {
+                // Unquoted: up to the next separator
+                while pos < chars.len() {
+                    let kind = classify(chars[pos]);
+                    if is_item_char(kind) {
+                        item.push(chars[pos]);
+                        pos += 1;
+                        continue;
+                    }
+                    // A single space between item characters is kept
+                    let next = chars.get(pos + 1).copied().map(classify);
+                    if kind != CharKind::Space || !next.is_some_and(is_item_char) {
+                        break;
+                    }
+                    item.push(chars[pos]);
+                    pos += 1;
+                }
+            }
+            Some(close) => {
+                // Quoted: undouble the surrounding quotes inside
+                pos += 1;
+                while pos < chars.len() {
+                    let c = chars[pos];
+                    pos += 1;
+                    if !is_surrounding_quote(c, open, close) {
+                        item.push(c);
+                    } else if chars.get(pos) == Some(&c) {
+                        item.push(c);
+                        pos += 1;
+                    } else if !is_closing_quote(c, open, close) {
+                        item.push(c);
+                    } else {
+                        break;
+                    }
+                }
+            }
+        }
+        items.push(item);
+    }
+    items
+}
+
+impl XmpMeta {
+    /// Catenate the items of an array into a single string
+    ///
+    /// Items that contain separators are quoted, see the
+    /// [module documentation](self). Returns an empty string if the array
+    /// doesn't exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace URI or prefix
+    /// * `array_name` - The array property name
+    /// * `separator` - The separator: one semicolon and any number of
+    ///   spaces (`"; "` if empty)
+    /// * `quotes` - The opening quote, optionally followed by the closing
+    ///   quote (`"\""` if empty)
+    /// * `options` - Options to control quoting
+    ///
+    /// Fails with `XmpError::BadParam` if the array is an alternative array,
+    /// has structured items, or if the separator or quotes are invalid.
+    pub fn catenate_array_items(
+        &self,
+        namespace: &str,
+        array_name: &str,
+        separator: &str,
+        quotes: &str,
+        options: SeparateOptions,
+    ) -> XmpResult<String> {
+        let separator = if separator.is_empty() {
+            "; "
+        } else {
+            separator
+        };
+        let semicolons = separator
+            .chars()
+            .filter(|&c| classify(c) == CharKind::Semicolon)
+            .count();
+        if semicolons != 1
+            || separator
+                .chars()
+                .any(|c| !matches!(classify(c), CharKind::Semicolon | CharKind::Space))
+        {
+            return Err(XmpError::BadParam(
+                "Separator must have one semicolon and only spaces otherwise".to_string(),
+            ));
+        }
+
+        let mut quote_chars = if quotes.is_empty() { "\"" } else { quotes }.chars();
+        let open = quote_chars.next().unwrap_or('"');
+        let close = closing_quote(open)
+            .filter(|_| classify(open) == CharKind::Quote)
+            .ok_or_else(|| XmpError::BadParam("Invalid quoting character".to_string()))?;
+        match (quote_chars.next(), quote_chars.next()) {
+            (None, None) => {}
+            (Some(c), None) if c == close => {}
+            _ => return Err(XmpError::BadParam("Mismatched quote pair".to_string())),
+        }
+
+        let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+        let root = root_read!(self.root);
+        let Some(node) = root.get_field(&format!("{}:{}", ns_uri, array_name)) else {
+            return Ok(String::new());
+        };
+        let array = match node {
+            Node::Array(array) if array.array_type != ArrayType::Alternative => array,
+            _ => {
+                return Err(XmpError::BadParam(
+                    "Named property must be a non-alternative array".to_string(),
+                ))
+            }
+        };
+
+        let items = array
+            .items
+            .iter()
+            .map(|item| match item {
+                Node::Simple(simple) => Ok(apply_quotes(
+                    &simple.value,
+                    open,
+                    close,
+                    options.allow_commas,
+                )),
+                _ => Err(XmpError::BadParam("Array items must be simple".to_string())),
+            })
+            .collect::<XmpResult<Vec<_>>>()?;
+        Ok(items.join(separator))
+    }
+
+    /// Replace the items of an array with the items separated from a string
+    ///
+    /// Existing items whose value is separated again are kept with their
+    /// qualifiers. The array is created if it doesn't exist. See the
+    /// [module documentation](self) for the separators and quoting.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace URI or prefix
+    /// * `array_name` - The array property name
+    /// * `text` - The string to separate
+    /// * `options` - Options for the array form and commas
+    pub fn separate_array_items(
+        &mut self,
+        namespace: &str,
+        array_name: &str,
+        text: &str,
+        options: SeparateOptions,
+    ) -> XmpResult<()> {
+        let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+        let items = split_items(text, options.allow_commas);
+        for item in &items {
+            validate::check_custom_property(&ns_uri, array_name, None, Some(item))?;
+        }
+
+        let full_path = format!("{}:{}", ns_uri, array_name);
+        let mut root = root_write!(self.root);
+        let old_array = match root.remove_field(&full_path) {
+            None => None,
+            Some(Node::Array(array)) => Some(array),
+            Some(node) => {
+                root.set_field(full_path, node);
+                return Err(XmpError::BadParam(format!(
+                    "Property '{}:{}' is not an array",
+                    ns_uri, array_name
+                )));
+            }
+        };
+
+        let array_type = match (&old_array, options.array_type) {
+            (Some(old), Some(array_type)) if old.array_type != array_type => {
+                let existing = old.array_type;
+                if let Some(old) = old_array {
+                    root.set_field(full_path, Node::Array(old));
+                }
+                return Err(XmpError::BadValue(format!(
+                    "Mismatch of existing and specified array form for '{}:{}': existing rdf:{}, specified rdf:{}",
+                    ns_uri,
+                    array_name,
+                    existing.rdf_type(),
+                    array_type.rdf_type()
+                )));
+            }
+            (Some(old), _) => old.array_type,
+            (None, Some(array_type)) => array_type,
+            (None, None) => {
+                validate::custom_array_type(&ns_uri, array_name).unwrap_or(ArrayType::Unordered)
+            }
+        };
+
+        let mut old_items: Vec<Option<Node>> = old_array
+            .map(|array| array.items.into_iter().map(Some).collect())
+            .unwrap_or_default();
+        let mut array = ArrayNode::new(array_type);
+        for item in items {
+            // Keep a matching old item, which may have qualifiers
+            let old = old_items.iter_mut().find(|old| {
+                old.as_ref()
+                    .and_then(Node::as_simple)
+                    .is_some_and(|simple| simple.value == item)
+            });
+            match old.and_then(Option::take) {
+                Some(old) => array.append(old),
+                None => array.append(Node::simple(item)),
+            }
+        }
+        root.set_field(full_path, Node::Array(array));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+
+    #[test]
+    fn test_split_items() {
+        assert_eq!(
+            split_items("red; green, blue;;  sky blue ", false),
+            vec!["red", "green", "blue", "sky blue"]
+        );
+        assert_eq!(split_items("1,000; 2,5", true), vec!["1,000", "2,5"]);
+        assert_eq!(
+            split_items(r#""a; b"; "say ""hi"""; «x»"#, false),
+            vec!["a; b", r#"say "hi""#, "x"]
+        );
+        // Unterminated quotes end with the string
+        assert_eq!(split_items("\"open", false), vec!["open"]);
+        assert!(split_items(" ; , ", false).is_empty());
+    }
+
+    #[test]
+    fn test_apply_quotes() {
+        assert_eq!(apply_quotes("sky blue", '"', '"', false), "sky blue");
+        assert_eq!(apply_quotes("a; b", '"', '"', false), "\"a; b\"");
+        assert_eq!(apply_quotes("1,000", '"', '"', false), "\"1,000\"");
+        assert_eq!(apply_quotes("1,000", '"', '"', true), "1,000");
+        assert_eq!(
+            apply_quotes("two  spaces", '"', '"', false),
+            "\"two  spaces\""
+        );
+        assert_eq!(
+            apply_quotes("\"hi\" there", '"', '"', false),
+            "\"\"\"hi\"\" there\""
+        );
+        assert_eq!(apply_quotes("a;b", '«', '»', false), "«a;b»");
+    }
+
+    #[test]
+    fn test_catenate_separate() {
+        let mut meta = XmpMeta::new();
+        let options = SeparateOptions::default();
+        assert_eq!(
+            meta.catenate_array_items(ns::DC, "subject", "", "", options)
+                .unwrap(),
+            ""
+        );
+
+        let items = ["red", "sky blue", "a; b", "1,000", "\"quoted\""];
+        for item in items {
+            meta.append_array_item_with(ns::DC, "subject", ArrayType::Unordered, item.into())
+                .unwrap();
+        }
+        let text = meta
+            .catenate_array_items(ns::DC, "subject", ";  ", "", options)
+            .unwrap();
+        assert_eq!(text, r#"red;  sky blue;  "a; b";  "1,000";  """quoted""""#);
+
+        let mut copy = XmpMeta::new();
+        copy.separate_array_items(ns::DC, "subject", &text, options)
+            .unwrap();
+        for (index, item) in items.iter().enumerate() {
+            assert_eq!(
+                copy.get_array_item(ns::DC, "subject", index),
+                Some((*item).into())
+            );
+        }
+        assert!(copy.serialize().unwrap().contains("<rdf:Bag>"));
+
+        // Matching items keep their qualifiers
+        copy.set_qualifier(ns::DC, "subject[1]", ns::XMP, "Label", "kept".into())
+            .unwrap();
+        copy.separate_array_items(ns::DC, "subject", "new; red", options)
+            .unwrap();
+        assert_eq!(copy.count_array_items(ns::DC, "subject"), 2);
+        assert_eq!(
+            copy.get_qualifier(ns::DC, "subject[2]", ns::XMP, "Label"),
+            Some("kept".into())
+        );
+
+        assert!(meta
+            .catenate_array_items(ns::DC, "subject", ", ", "", options)
+            .is_err());
+        assert!(meta
+            .catenate_array_items(ns::DC, "subject", "; ", "x", options)
+            .is_err());
+        assert!(meta
+            .catenate_array_items(ns::DC, "subject", "; ", "«»", options)
+            .is_ok());
+        assert!(meta
+            .separate_array_items(
+                ns::DC,
+                "subject",
+                "x",
+                SeparateOptions::default().array_type(ArrayType::Ordered)
+            )
+            .is_err());
+        assert_eq!(meta.count_array_items(ns::DC, "subject"), 5);
+
+        meta.set_localized_text(ns::DC, "title", "", "x-default", "Title")
+            .unwrap();
+        assert!(matches!(
+            meta.catenate_array_items(ns::DC, "title", "; ", "", options),
+            Err(XmpError::BadParam(_))
+        ));
+    }
+}
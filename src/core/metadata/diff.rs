@@ -0,0 +1,303 @@
+//! Structured comparison of two XMP trees
+//!
+//! [`XmpMeta::diff`] compares the leaf nodes of two metadata objects (simple
+//! values, qualifiers and empty arrays or structures) by XMP path and reports
+//! which were added, removed or changed. [`XmpMeta::changes`] compares an
+//! object with itself as it was parsed, to tell if it needs saving.
+
+use super::iter::{IterOptions, XmpIterItem};
+use super::node::{new_root_node, root_baseline, root_reset_baseline};
+use super::XmpMeta;
+use std::collections::HashMap;
+
+/// A single difference between two XMP trees
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiffEntry {
+    /// Namespace URI of the top-level property containing the node
+    pub schema_ns: String,
+    /// XMP path of the node (e.g. `dc:subject[2]`)
+    pub path: String,
+    /// Value in the original tree; `None` if added or not a simple value
+    pub old_value: Option<String>,
+    /// Value in the new tree; `None` if removed or not a simple value
+    pub new_value: Option<String>,
+}
+
+/// Differences between two XMP trees, as returned by [`XmpMeta::diff`]
+///
+/// Entries are ordered by namespace URI and path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct XmpDiff {
+    /// Nodes that exist only in the new tree
+    pub added: Vec<DiffEntry>,
+    /// Nodes that exist only in the original tree
+    pub removed: Vec<DiffEntry>,
+    /// Nodes whose value or kind differs
+    pub changed: Vec<DiffEntry>,
+}
+
+impl XmpDiff {
+    /// Check if the two trees are equal
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Get the total number of differences
+    pub fn len(&self) -> usize {
+        self.added.len() + self.removed.len() + self.changed.len()
+    }
+
+    /// Serialize the diff to a JSON string
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{ns, XmpMeta, XmpValue};
+    ///
+    /// let old = XmpMeta::new();
+    /// let mut new = XmpMeta::new();
+    /// new.set_property(ns::XMP, "Rating", XmpValue::Integer(5)).unwrap();
+    ///
+    /// let json = old.diff(&new).to_json().unwrap();
+    /// assert!(json.contains("\"path\":\"xmp:Rating\""));
+    /// ```
+    #[cfg(feature = "serde_json")]
+    pub fn to_json(&self) -> crate::core::error::XmpResult<String> {
+        serde_json::to_string(self)
+            .map_err(|e| crate::core::error::XmpError::SerializationError(e.to_string()))
+    }
+}
+
+impl XmpMeta {
+    /// Compare this XMP object (the original) with another (the new version)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{ns, XmpMeta, XmpValue};
+    ///
+    /// let mut old = XmpMeta::new();
+    /// old.set_property(ns::XMP, "CreatorTool", XmpValue::String("A".into())).unwrap();
+    ///
+    /// let mut new = XmpMeta::new();
+    /// new.set_property(ns::XMP, "CreatorTool", XmpValue::String("B".into())).unwrap();
+    ///
+    /// let diff = old.diff(&new);
+    /// assert_eq!(diff.changed[0].path, "xmp:CreatorTool");
+    /// assert_eq!(diff.changed[0].new_value.as_deref(), Some("B"));
+    /// ```
+    pub fn diff(&self, other: &XmpMeta) -> XmpDiff {
+        let options = IterOptions::default().just_leaf_nodes();
+        let old: Vec<XmpIterItem> = self.iter_with(options.clone()).collect();
+        let new: Vec<XmpIterItem> = other.iter_with(options).collect();
+
+        let old_by_path: HashMap<&str, &XmpIterItem> =
+            old.iter().map(|item| (item.path.as_str(), item)).collect();
+        let new_by_path: HashMap<&str, &XmpIterItem> =
+            new.iter().map(|item| (item.path.as_str(), item)).collect();
+
+        let mut diff = XmpDiff::default();
+        for item in &old {
+            match new_by_path.get(item.path.as_str()) {
+                None => diff.removed.push(entry(item, item.value.clone(), None)),
+                Some(new_item)
+                    if new_item.value != item.value || new_item.options != item.options =>
+                {
+                    diff.changed
+                        .push(entry(item, item.value.clone(), new_item.value.clone()));
+                }
+                Some(_) => {}
+            }
+        }
+        for item in &new {
+            if !old_by_path.contains_key(item.path.as_str()) {
+                diff.added.push(entry(item, None, item.value.clone()));
+            }
+        }
+        diff
+    }
+}
+
+impl XmpMeta {
+    /// Get the changes made since the metadata was parsed or created
+    ///
+    /// Compares the properties with a copy of them taken before the first
+    /// change, so a value set back to what it was is not a change. The
+    /// about URI is not compared. Clones share their properties, so they
+    /// share their changes too.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{ns, XmpMeta, XmpValue};
+    ///
+    /// let mut meta = XmpMeta::new();
+    /// assert!(!meta.is_dirty());
+    ///
+    /// meta.set_property(ns::XMP, "Rating", XmpValue::Integer(5)).unwrap();
+    /// assert!(meta.is_dirty());
+    /// assert_eq!(meta.changed_paths(), ["xmp:Rating"]);
+    ///
+    /// meta.mark_clean();
+    /// assert!(!meta.is_dirty());
+    /// ```
+    pub fn changes(&self) -> XmpDiff {
+        let Some(baseline) = root_baseline(&self.root) else {
+            return XmpDiff::default();
+        };
+        let original = XmpMeta {
+            root: new_root_node(baseline),
+            namespaces: self.namespaces.clone(),
+            about_uri: None,
+            auto_ids: false,
+        };
+        original.diff(self)
+    }
+
+    /// Check if the properties changed since the metadata was parsed or
+    /// created (see [`XmpMeta::changes`])
+    pub fn is_dirty(&self) -> bool {
+        !self.changes().is_empty()
+    }
+
+    /// Get the XMP paths of the nodes that changed since the metadata was
+    /// parsed or created (see [`XmpMeta::changes`])
+    ///
+    /// The paths are those of leaf nodes, such as `dc:subject[2]`, sorted
+    /// by namespace URI and path.
+    pub fn changed_paths(&self) -> Vec<String> {
+        let changes = self.changes();
+        let mut entries: Vec<_> = changes
+            .added
+            .into_iter()
+            .chain(changes.removed)
+            .chain(changes.changed)
+            .map(|entry| (entry.schema_ns, entry.path))
+            .collect();
+        entries.sort();
+        entries.dedup();
+        entries.into_iter().map(|(_, path)| path).collect()
+    }
+
+    /// Make the current properties the ones [`XmpMeta::changes`] compares
+    /// with, such as after saving them
+    pub fn mark_clean(&mut self) {
+        self.clear_changes();
+    }
+
+    /// Forget the changes of the properties, which clones share
+    pub(crate) fn clear_changes(&self) {
+        root_reset_baseline(&self.root);
+    }
+
+    /// Check if `other` is a clone sharing the properties of this object
+    #[cfg(feature = "files")]
+    pub(crate) fn shares_root(&self, other: &XmpMeta) -> bool {
+        std::ptr::eq(&*self.root, &*other.root)
+    }
+}
+
+fn entry(item: &XmpIterItem, old_value: Option<String>, new_value: Option<String>) -> DiffEntry {
+    DiffEntry {
+        schema_ns: item.schema_ns.clone(),
+        path: item.path.clone(),
+        old_value,
+        new_value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+
+    #[test]
+    fn test_diff() {
+        let mut old = XmpMeta::new();
+        old.set_property(ns::XMP, "CreatorTool", XmpValue::String("Editor 1".into()))
+            .unwrap();
+        old.set_property(ns::PHOTOSHOP, "City", XmpValue::String("Paris".into()))
+            .unwrap();
+        old.append_array_item(ns::DC, "subject", "a".into())
+            .unwrap();
+
+        let mut new = XmpMeta::new();
+        new.set_property(ns::XMP, "CreatorTool", XmpValue::String("Editor 2".into()))
+            .unwrap();
+        new.append_array_item(ns::DC, "subject", "a".into())
+            .unwrap();
+        new.append_array_item(ns::DC, "subject", "b".into())
+            .unwrap();
+
+        assert!(old.diff(&old).is_empty());
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.len(), 3);
+        assert_eq!(
+            diff.added,
+            [DiffEntry {
+                schema_ns: ns::DC.to_string(),
+                path: "dc:subject[2]".to_string(),
+                old_value: None,
+                new_value: Some("b".to_string()),
+            }]
+        );
+        assert_eq!(diff.removed[0].path, "photoshop:City");
+        assert_eq!(diff.removed[0].old_value.as_deref(), Some("Paris"));
+        assert_eq!(diff.changed[0].path, "xmp:CreatorTool");
+        assert_eq!(diff.changed[0].old_value.as_deref(), Some("Editor 1"));
+        assert_eq!(diff.changed[0].new_value.as_deref(), Some("Editor 2"));
+
+        // Reversed comparison swaps added and removed
+        let reversed = new.diff(&old);
+        assert_eq!(reversed.removed[0].path, "dc:subject[2]");
+        assert_eq!(reversed.added[0].path, "photoshop:City");
+    }
+
+    #[test]
+    fn test_changes() {
+        let packet = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+            <rdf:Description rdf:about="" xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+                xmp:CreatorTool="Editor" xmp:Rating="3"/>
+        </rdf:RDF>"#;
+        let mut meta = XmpMeta::parse(packet).unwrap();
+        assert!(!meta.is_dirty());
+
+        // Setting a value back to what it was is not a change
+        meta.set_property(ns::XMP, "Rating", XmpValue::Integer(4))
+            .unwrap();
+        meta.set_property(ns::XMP, "Rating", XmpValue::String("3".into()))
+            .unwrap();
+        assert!(!meta.is_dirty());
+
+        let mut copy = meta.clone();
+        copy.delete_property(ns::XMP, "CreatorTool").unwrap();
+        copy.append_array_item(ns::DC, "subject", "a".into())
+            .unwrap();
+        assert_eq!(meta.changed_paths(), ["xmp:CreatorTool", "dc:subject[1]"]);
+        assert_eq!(
+            meta.changes().removed[0].old_value.as_deref(),
+            Some("Editor")
+        );
+
+        meta.mark_clean();
+        assert!(!copy.is_dirty());
+        assert!(meta.changed_paths().is_empty());
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn test_diff_json() {
+        let old = XmpMeta::new();
+        let mut new = XmpMeta::new();
+        new.set_property(ns::XMP, "Rating", XmpValue::Integer(3))
+            .unwrap();
+
+        let diff = old.diff(&new);
+        let parsed: XmpDiff = serde_json::from_str(&diff.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, diff);
+    }
+}
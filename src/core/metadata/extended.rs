@@ -0,0 +1,174 @@
+//! Splitting XMP into standard and extended packets
+//!
+//! This is a port of `PackageForJPEG` from the Adobe XMP Toolkit. When the
+//! packet doesn't fit a size limit (such as the 64KB of a JPEG APP1
+//! segment), properties are moved to an extended serialization: first the
+//! Camera Raw settings, then `photoshop:History`, then the largest
+//! remaining properties until the standard packet fits. The standard
+//! packet links to the extension with `xmpNote:HasExtendedXMP`, whose value
+//! is the MD5 digest of the extended serialization.
+
+use super::XmpMeta;
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::namespace::ns;
+use crate::core::node::{Node, StructureNode};
+use crate::core::serializer::SerializeOptions;
+use crate::utils::md5;
+
+/// Name of the property linking the standard packet to the extension
+pub(crate) const HAS_EXTENDED_XMP: &str = "HasExtendedXMP";
+
+/// Value used to size the standard packet before the digest is known
+const PLACEHOLDER_DIGEST: &str = "123456789-123456789-123456789-12";
+
+/// XMP split into a standard packet and an optional extension
+#[derive(Debug, Clone)]
+pub(crate) struct SplitPacket {
+    /// Standard packet, no larger than the requested limit
+    pub(crate) standard: String,
+    /// Extended serialization and its digest (32 uppercase hex digits)
+    pub(crate) extended: Option<(String, String)>,
+}
+
+impl XmpMeta {
+    /// Serialize as a packet of at most `limit` bytes, moving properties to
+    /// an extended serialization if needed
+    ///
+    /// See the [module documentation](self) for which properties are moved.
+    /// Fails with `XmpError::BadValue` if the packet can't be reduced enough.
+    pub(crate) fn split_packet(&self, limit: usize) -> XmpResult<SplitPacket> {
+        let serializer = self.serializer(false);
+        let options = SerializeOptions::compact_unpadded();
+        let has_extended_key = format!("{}:{}", ns::XMP_NOTE, HAS_EXTENDED_XMP);
+
+        let mut standard = root_read!(self.root).clone();
+        // A link to a previous extension is stale
+        standard.remove_field(&has_extended_key);
+        let packet = serializer.serialize_packet_with(&standard, &options)?;
+        if packet.len() <= limit {
+            return Ok(SplitPacket {
+                standard: packet,
+                extended: None,
+            });
+        }
+
+        standard.set_field(&has_extended_key, Node::simple(PLACEHOLDER_DIGEST));
+        let mut extended = StructureNode::new();
+        let fits = |standard: &StructureNode| -> XmpResult<bool> {
+            Ok(serializer.serialize_packet_with(standard, &options)?.len() <= limit)
+        };
+
+        // Camera Raw settings, then the Photoshop history
+        let history_key = format!("{}:History", ns::PHOTOSHOP);
+        let mut keys: Vec<String> = standard
+            .fields
+            .keys()
+            .filter(|key| **key != has_extended_key)
+            .cloned()
+            .collect();
+        keys.sort();
+        let (camera_raw, rest): (Vec<String>, Vec<String>) = keys.into_iter().partition(|key| {
+            key.rsplit_once(':')
+                .is_some_and(|(ns_uri, _)| ns_uri == ns::CAMERA_RAW)
+        });
+        let mut fitted = false;
+        for stage in [camera_raw, vec![history_key.clone()]] {
+            for key in &stage {
+                move_field(&mut standard, &mut extended, key);
+            }
+            if fits(&standard)? {
+                fitted = true;
+                break;
+            }
+        }
+
+        // Then the largest properties
+        if !fitted {
+            let mut by_size = Vec::new();
+            for key in rest.into_iter().filter(|key| *key != history_key) {
+                if let Some(node) = standard.get_field(&key) {
+                    let mut single = StructureNode::new();
+                    single.set_field(key.clone(), node.clone());
+                    let size = serializer.serialize_packet_with(&single, &options)?.len();
+                    by_size.push((size, key));
+                }
+            }
+            by_size.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+            for (_, key) in by_size {
+                move_field(&mut standard, &mut extended, &key);
+                if fits(&standard)? {
+                    fitted = true;
+                    break;
+                }
+            }
+        }
+        if !fitted {
+            return Err(XmpError::BadValue(format!(
+                "Can't reduce XMP to {} bytes",
+                limit
+            )));
+        }
+
+        let extended = serializer.serialize_packet_with(
+            &extended,
+            &SerializeOptions::default()
+                .use_compact_format()
+                .omit_packet_wrapper(),
+        )?;
+        let digest = md5::hex_digest(extended.as_bytes());
+        standard.set_field(&has_extended_key, Node::simple(digest.clone()));
+        Ok(SplitPacket {
+            standard: serializer.serialize_packet_with(&standard, &options)?,
+            extended: Some((extended, digest)),
+        })
+    }
+}
+
+/// Move the top-level property `key` from `from` to `to`
+fn move_field(from: &mut StructureNode, to: &mut StructureNode, key: &str) {
+    if let Some(node) = from.remove_field(key) {
+        to.set_field(key, node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::value::XmpValue;
+
+    #[test]
+    fn test_split_packet() {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String("Title".to_string()))
+            .unwrap();
+        let split = meta.split_packet(4096).unwrap();
+        assert!(split.extended.is_none());
+        assert!(!split.standard.contains(HAS_EXTENDED_XMP));
+
+        meta.set_property(
+            ns::CAMERA_RAW,
+            "Exposure",
+            XmpValue::String("+0.50".to_string()),
+        )
+        .unwrap();
+        meta.set_property(ns::DC, "description", XmpValue::String("d".repeat(3000)))
+            .unwrap();
+        meta.set_property(ns::DC, "source", XmpValue::String("s".repeat(2000)))
+            .unwrap();
+        let split = meta.split_packet(4096).unwrap();
+        assert!(split.standard.len() <= 4096);
+        let (extended, digest) = split.extended.unwrap();
+        assert_eq!(digest, md5::hex_digest(extended.as_bytes()));
+        assert!(split
+            .standard
+            .contains(&format!("xmpNote:HasExtendedXMP=\"{}\"", digest)));
+
+        // Camera Raw settings go first, then the largest properties
+        assert!(extended.contains("crs:Exposure"));
+        assert!(extended.contains("dc:description"));
+        assert!(split.standard.contains("dc:source"));
+        assert!(split.standard.contains("dc:title"));
+
+        assert!(matches!(meta.split_packet(100), Err(XmpError::BadValue(_))));
+    }
+}
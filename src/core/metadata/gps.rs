@@ -0,0 +1,194 @@
+//! GPS position (`exif:GPSLatitude`, `exif:GPSLongitude`, `exif:GPSAltitude`)
+//!
+//! The position of an image is spread over several EXIF properties: the
+//! coordinates are text in the "DDD,MM.mmK" form, and the altitude is a
+//! rational number of meters with an `exif:GPSAltitudeRef` telling if it is
+//! below sea level. [`XmpMeta::set_gps`] and [`XmpMeta::get_gps`] keep them
+//! consistent, with the conversions of [`crate::utils::gps`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use xmpkit::{ns, XmpMeta};
+//!
+//! let mut meta = XmpMeta::new();
+//! meta.set_gps(-33.8688, 151.2093, Some(-3.5)).unwrap();
+//! assert_eq!(
+//!     meta.get_property(ns::EXIF, "GPSLatitude").unwrap().as_str(),
+//!     Some("33,52.128S")
+//! );
+//!
+//! let position = meta.get_gps().unwrap();
+//! assert_eq!((position.latitude, position.longitude), (-33.8688, 151.2093));
+//! assert_eq!(position.altitude, Some(-3.5));
+//! ```
+
+use super::XmpMeta;
+use crate::core::error::XmpResult;
+use crate::core::namespace::ns;
+use crate::types::value::XmpValue;
+use crate::utils::gps::{
+    format_altitude, format_coordinate, parse_altitude, parse_coordinate, GpsAxis,
+};
+
+/// Version of the GPS properties written by [`XmpMeta::set_gps`]
+const GPS_VERSION: &str = "2.2.0.0";
+
+/// A GPS position in decimal degrees
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GpsPosition {
+    /// Latitude in degrees, negative to the south
+    pub latitude: f64,
+    /// Longitude in degrees, negative to the west
+    pub longitude: f64,
+    /// Altitude in meters, negative below sea level
+    pub altitude: Option<f64>,
+}
+
+impl XmpMeta {
+    /// Get the GPS position of `exif:GPSLatitude`, `exif:GPSLongitude` and
+    /// `exif:GPSAltitude`
+    ///
+    /// Returns `None` if a coordinate is missing, malformed or has the
+    /// direction of the other axis. A malformed altitude is ignored.
+    pub fn get_gps(&self) -> Option<GpsPosition> {
+        let text = |name: &str| match self.get_property(ns::EXIF, name)? {
+            XmpValue::String(s) => Some(s),
+            _ => None,
+        };
+        let coordinate = |name: &str, axis: GpsAxis| match parse_coordinate(&text(name)?) {
+            Ok((degrees, parsed)) if parsed == axis => Some(degrees),
+            _ => None,
+        };
+        let latitude = coordinate("GPSLatitude", GpsAxis::Latitude)?;
+        let longitude = coordinate("GPSLongitude", GpsAxis::Longitude)?;
+        let altitude = text("GPSAltitude")
+            .and_then(|altitude| parse_altitude(&altitude, text("GPSAltitudeRef").as_deref()).ok());
+        Some(GpsPosition {
+            latitude,
+            longitude,
+            altitude,
+        })
+    }
+
+    /// Set the GPS position
+    ///
+    /// Sets `exif:GPSLatitude` and `exif:GPSLongitude`, and
+    /// `exif:GPSAltitude` with `exif:GPSAltitudeRef` if `altitude` is
+    /// given, removing them otherwise. `exif:GPSVersionID` is set if
+    /// missing.
+    ///
+    /// # Arguments
+    ///
+    /// * `latitude` - Latitude in degrees, negative to the south
+    /// * `longitude` - Longitude in degrees, negative to the west
+    /// * `altitude` - Altitude in meters, negative below sea level
+    ///
+    /// Fails with `XmpError::BadValue`, without changing anything, if a
+    /// value is out of range.
+    pub fn set_gps(
+        &mut self,
+        latitude: f64,
+        longitude: f64,
+        altitude: Option<f64>,
+    ) -> XmpResult<()> {
+        let latitude = format_coordinate(latitude, GpsAxis::Latitude)?;
+        let longitude = format_coordinate(longitude, GpsAxis::Longitude)?;
+        let altitude = altitude.map(format_altitude).transpose()?;
+
+        self.set_property(ns::EXIF, "GPSLatitude", latitude.into())?;
+        self.set_property(ns::EXIF, "GPSLongitude", longitude.into())?;
+        match altitude {
+            Some((altitude, reference)) => {
+                self.set_property(ns::EXIF, "GPSAltitude", altitude.into())?;
+                self.set_property(ns::EXIF, "GPSAltitudeRef", reference.into())?;
+            }
+            None => {
+                self.delete_property(ns::EXIF, "GPSAltitude")?;
+                self.delete_property(ns::EXIF, "GPSAltitudeRef")?;
+            }
+        }
+        if !self.does_property_exist(ns::EXIF, "GPSVersionID") {
+            self.set_property(ns::EXIF, "GPSVersionID", GPS_VERSION.into())?;
+        }
+        Ok(())
+    }
+
+    /// Remove the GPS position set by [`set_gps`](Self::set_gps)
+    pub fn delete_gps(&mut self) -> XmpResult<()> {
+        for name in [
+            "GPSLatitude",
+            "GPSLongitude",
+            "GPSAltitude",
+            "GPSAltitudeRef",
+        ] {
+            self.delete_property(ns::EXIF, name)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gps_round_trip() {
+        let mut meta = XmpMeta::new();
+        assert_eq!(meta.get_gps(), None);
+
+        meta.set_gps(48.858_37, 2.294_48, Some(35.0)).unwrap();
+        let packet = meta.serialize_packet().unwrap();
+        assert!(packet.contains("48,51.5022N"));
+        assert!(packet.contains("2,17.6688E"));
+        let parsed = XmpMeta::parse(&packet).unwrap();
+        let position = parsed.get_gps().unwrap();
+        assert!((position.latitude - 48.858_37).abs() < 1e-9);
+        assert!((position.longitude - 2.294_48).abs() < 1e-9);
+        assert_eq!(position.altitude, Some(35.0));
+        assert_eq!(
+            parsed
+                .get_property(ns::EXIF, "GPSVersionID")
+                .unwrap()
+                .as_str(),
+            Some("2.2.0.0")
+        );
+
+        // Without an altitude, the old one is removed
+        meta.set_gps(-1.5, -2.5, None).unwrap();
+        assert!(!meta.does_property_exist(ns::EXIF, "GPSAltitudeRef"));
+        assert_eq!(
+            meta.get_gps(),
+            Some(GpsPosition {
+                latitude: -1.5,
+                longitude: -2.5,
+                altitude: None,
+            })
+        );
+
+        // Invalid values change nothing
+        assert!(meta.set_gps(0.0, 200.0, Some(1.0)).is_err());
+        assert!(meta.set_gps(0.0, 0.0, Some(f64::NAN)).is_err());
+        assert_eq!(meta.get_gps().unwrap().latitude, -1.5);
+
+        meta.set_property(ns::EXIF, "GPSLatitude", "2,30W".into())
+            .unwrap();
+        assert_eq!(meta.get_gps(), None);
+
+        meta.delete_gps().unwrap();
+        assert!(!meta.does_property_exist(ns::EXIF, "GPSLongitude"));
+    }
+
+    #[test]
+    fn test_gps_from_exif_form() {
+        let xml = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+            <rdf:Description rdf:about="" xmlns:exif="http://ns.adobe.com/exif/1.0/"
+                exif:GPSLatitude="40,26,46N" exif:GPSLongitude="79,58,56W"
+                exif:GPSAltitude="bad" exif:GPSAltitudeRef="0"/>
+        </rdf:RDF>"#;
+        let position = XmpMeta::parse(xml).unwrap().get_gps().unwrap();
+        assert!((position.latitude - 40.446_111).abs() < 1e-6);
+        assert!((position.longitude + 79.982_222).abs() < 1e-6);
+        assert_eq!(position.altitude, None);
+    }
+}
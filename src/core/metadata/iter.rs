@@ -0,0 +1,407 @@
+//! Iteration over the XMP data tree
+//!
+//! [`XmpMeta::iter`] and [`XmpMeta::iter_with`] visit every node of the
+//! metadata tree in document order: each property is reported before its
+//! qualifiers, and qualifiers come before array items and struct fields.
+//! Top-level properties are grouped by namespace URI and sorted by name so
+//! the order is stable between runs.
+
+use super::XmpMeta;
+use crate::core::namespace::get_global_namespace_prefix;
+use crate::core::node::{ArrayType, Node};
+use crate::types::qualifier::Qualifier;
+
+/// Options to control iteration over XMP metadata
+///
+/// Equivalent to the `kXMP_Iter*` options of the Adobe XMP Toolkit.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::{ns, IterOptions, XmpMeta};
+///
+/// let meta = XmpMeta::new();
+/// let options = IterOptions::default().namespace(ns::DC).just_leaf_nodes();
+/// for item in meta.iter_with(options) {
+///     println!("{} = {:?}", item.path, item.value);
+/// }
+/// ```
+#[derive(Default, Clone, Debug)]
+pub struct IterOptions {
+    /// Only visit top-level properties
+    pub(crate) just_children: bool,
+    /// Only visit nodes without children
+    pub(crate) just_leaf_nodes: bool,
+    /// Report only the last path component of each node
+    pub(crate) just_leaf_name: bool,
+    /// Do not visit qualifiers
+    pub(crate) omit_qualifiers: bool,
+    /// Only visit properties in this namespace (URI or prefix)
+    pub(crate) namespace: Option<String>,
+}
+
+impl IterOptions {
+    /// Only visit the top-level properties, not their items, fields or qualifiers.
+    pub fn just_children(mut self) -> Self {
+        self.just_children = true;
+        self
+    }
+
+    /// Only visit leaf nodes (simple values and qualifiers).
+    pub fn just_leaf_nodes(mut self) -> Self {
+        self.just_leaf_nodes = true;
+        self
+    }
+
+    /// Report only the last component of each path (e.g. `[1]` or `?xml:lang`).
+    pub fn just_leaf_name(mut self) -> Self {
+        self.just_leaf_name = true;
+        self
+    }
+
+    /// Do not visit qualifiers.
+    pub fn omit_qualifiers(mut self) -> Self {
+        self.omit_qualifiers = true;
+        self
+    }
+
+    /// Only visit properties in the given namespace (URI or prefix).
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+}
+
+/// Describes the kind of node reported by [`XmpIterator`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PropertyOptions {
+    /// The node is a structure
+    pub is_struct: bool,
+    /// The form of the array, if the node is an array
+    pub array_type: Option<ArrayType>,
+    /// The node is a qualifier of its parent
+    pub is_qualifier: bool,
+    /// The node has qualifiers
+    pub has_qualifiers: bool,
+    /// The node has an `xml:lang` qualifier
+    pub has_lang: bool,
+    /// The node is a simple value that is a URI
+    pub is_uri: bool,
+}
+
+impl PropertyOptions {
+    /// Check if the node is an array
+    pub fn is_array(&self) -> bool {
+        self.array_type.is_some()
+    }
+
+    /// Check if the node is a simple value
+    pub fn is_simple(&self) -> bool {
+        !self.is_struct && self.array_type.is_none()
+    }
+}
+
+/// A node visited by [`XmpIterator`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmpIterItem {
+    /// Namespace URI of the top-level property containing the node
+    pub schema_ns: String,
+    /// XMP path of the node (e.g. `dc:title[1]/?xml:lang`)
+    pub path: String,
+    /// Value of the node; `None` for arrays and structures
+    pub value: Option<String>,
+    /// Kind of the node
+    pub options: PropertyOptions,
+}
+
+/// Iterator over the nodes of an [`XmpMeta`] tree
+///
+/// The iterator works on a snapshot taken when it is created, so the
+/// metadata can be modified while iterating.
+#[derive(Debug, Clone)]
+pub struct XmpIterator {
+    items: Vec<(usize, XmpIterItem)>,
+    pos: usize,
+}
+
+impl XmpIterator {
+    /// Skip the children and qualifiers of the node returned last.
+    pub fn skip_subtree(&mut self) {
+        let Some((depth, _)) = self.pos.checked_sub(1).and_then(|i| self.items.get(i)) else {
+            return;
+        };
+        let depth = *depth;
+        while self.items.get(self.pos).is_some_and(|(d, _)| *d > depth) {
+            self.pos += 1;
+        }
+    }
+}
+
+impl Iterator for XmpIterator {
+    type Item = XmpIterItem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (_, item) = self.items.get(self.pos)?;
+        self.pos += 1;
+        Some(item.clone())
+    }
+}
+
+impl XmpMeta {
+    /// Iterate over every node of the metadata tree
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{ns, XmpMeta};
+    ///
+    /// let mut meta = XmpMeta::new();
+    /// meta.append_array_item(ns::DC, "subject", "travel".into()).unwrap();
+    ///
+    /// let paths: Vec<_> = meta.iter().map(|item| item.path).collect();
+    /// assert_eq!(paths, ["dc:subject", "dc:subject[1]"]);
+    /// ```
+    pub fn iter(&self) -> XmpIterator {
+        self.iter_with(IterOptions::default())
+    }
+
+    /// Iterate over the metadata tree with options
+    ///
+    /// An unknown namespace filter yields no items.
+    pub fn iter_with(&self, options: IterOptions) -> XmpIterator {
+        XmpIterator {
+            items: self.collect_iter_items(&options).unwrap_or_default(),
+            pos: 0,
+        }
+    }
+
+    fn collect_iter_items(&self, options: &IterOptions) -> Option<Vec<(usize, XmpIterItem)>> {
+        let filter = match &options.namespace {
+            Some(namespace) => Some(self.resolve_namespace_uri(namespace)?),
+            None => None,
+        };
+
+        let root = root_read_opt!(self.root);
+        let mut properties: Vec<_> = root
+            .fields
+            .iter()
+            .filter_map(|(key, node)| {
+                let (ns_uri, name) = key.rsplit_once(':')?;
+                Some((ns_uri, name, key, node))
+            })
+            .filter(|(ns_uri, ..)| filter.as_deref().is_none_or(|f| f == *ns_uri))
+            .collect();
+        properties.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+        let mut collector = Collector {
+            meta: self,
+            options,
+            items: Vec::new(),
+        };
+        for (ns_uri, _, key, node) in properties {
+            let path = collector.key_name(key);
+            collector.visit(ns_uri, &path, &path, node, 0);
+        }
+        Some(collector.items)
+    }
+}
+
+struct Collector<'a> {
+    meta: &'a XmpMeta,
+    options: &'a IterOptions,
+    items: Vec<(usize, XmpIterItem)>,
+}
+
+impl Collector<'_> {
+    fn visit(&mut self, schema_ns: &str, path: &str, leaf: &str, node: &Node, depth: usize) {
+        let qualifiers = node.qualifiers();
+        let options = PropertyOptions {
+            is_struct: node.is_structure(),
+            array_type: node.as_array().map(|a| a.array_type),
+            is_qualifier: false,
+            has_qualifiers: !qualifiers.is_empty(),
+            has_lang: qualifiers.iter().any(is_lang),
+            is_uri: node.as_simple().is_some_and(|s| s.is_uri),
+        };
+        let value = node.as_simple().map(|s| s.value.clone());
+
+        let is_leaf = match node {
+            Node::Simple(_) => true,
+            Node::Array(a) => a.is_empty(),
+            Node::Structure(s) => s.fields.is_empty(),
+        };
+        if !self.options.just_leaf_nodes || is_leaf {
+            self.push(depth, schema_ns, path, leaf, value, options);
+        }
+        if self.options.just_children {
+            return;
+        }
+
+        if !self.options.omit_qualifiers {
+            for qualifier in qualifiers {
+                let name = format!("?{}", self.name(&qualifier.namespace, &qualifier.name));
+                let options = PropertyOptions {
+                    is_qualifier: true,
+                    ..Default::default()
+                };
+                let value = Some(qualifier.value.clone());
+                let qual_path = format!("{}/{}", path, name);
+                self.push(depth + 1, schema_ns, &qual_path, &name, value, options);
+            }
+        }
+
+        match node {
+            Node::Simple(_) => {}
+            Node::Array(array) => {
+                for (i, item) in array.items.iter().enumerate() {
+                    let name = format!("[{}]", i + 1);
+                    let item_path = format!("{}{}", path, name);
+                    self.visit(schema_ns, &item_path, &name, item, depth + 1);
+                }
+            }
+            Node::Structure(structure) => {
+                let mut fields: Vec<_> = structure
+                    .fields
+                    .iter()
+                    .map(|(key, node)| (self.key_name(key), node))
+                    .collect();
+                fields.sort_by(|a, b| a.0.cmp(&b.0));
+                for (name, field) in fields {
+                    let field_path = format!("{}/{}", path, name);
+                    self.visit(schema_ns, &field_path, &name, field, depth + 1);
+                }
+            }
+        }
+    }
+
+    fn push(
+        &mut self,
+        depth: usize,
+        schema_ns: &str,
+        path: &str,
+        leaf: &str,
+        value: Option<String>,
+        options: PropertyOptions,
+    ) {
+        let path = if self.options.just_leaf_name {
+            leaf
+        } else {
+            path
+        };
+        self.items.push((
+            depth,
+            XmpIterItem {
+                schema_ns: schema_ns.to_string(),
+                path: path.to_string(),
+                value,
+                options,
+            },
+        ));
+    }
+
+    /// Convert an internal "ns_uri:name" key to "prefix:name"
+    fn key_name(&self, key: &str) -> String {
+        match key.rsplit_once(':') {
+            Some((ns_uri, name)) => self.name(ns_uri, name),
+            None => key.to_string(),
+        }
+    }
+
+    fn name(&self, ns_uri: &str, name: &str) -> String {
+        let prefix = self
+            .meta
+            .namespaces
+            .get_prefix(ns_uri)
+            .map(str::to_string)
+            .or_else(|| get_global_namespace_prefix(ns_uri));
+        match prefix {
+            Some(prefix) => format!("{}:{}", prefix, name),
+            None => format!("{}:{}", ns_uri, name),
+        }
+    }
+}
+
+fn is_lang(qualifier: &Qualifier) -> bool {
+    qualifier.namespace == crate::core::namespace::ns::XML && qualifier.name == "lang"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+
+    fn sample() -> XmpMeta {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "CreatorTool", XmpValue::String("Tool".into()))
+            .unwrap();
+        meta.set_localized_text(ns::DC, "title", "", "x-default", "Title")
+            .unwrap();
+        meta.append_array_item(ns::DC, "subject", "a".into())
+            .unwrap();
+        meta.set_struct_field(ns::EXIF, "Flash", ns::EXIF, "Fired", "True".into())
+            .unwrap();
+        meta
+    }
+
+    #[test]
+    fn test_iter_all() {
+        let meta = sample();
+        let paths: Vec<_> = meta.iter().map(|i| i.path).collect();
+        assert_eq!(
+            paths,
+            [
+                // Namespaces are ordered by URI
+                "exif:Flash",
+                "exif:Flash/exif:Fired",
+                "xmp:CreatorTool",
+                "dc:subject",
+                "dc:subject[1]",
+                "dc:title",
+                "dc:title[1]",
+                "dc:title[1]/?xml:lang",
+            ]
+        );
+
+        let title = meta.iter().find(|i| i.path == "dc:title[1]").unwrap();
+        assert_eq!(title.schema_ns, ns::DC);
+        assert_eq!(title.value.as_deref(), Some("Title"));
+        assert!(title.options.has_lang && title.options.is_simple());
+
+        let flash = meta.iter().find(|i| i.path == "exif:Flash").unwrap();
+        assert!(flash.options.is_struct && flash.value.is_none());
+    }
+
+    #[test]
+    fn test_iter_options() {
+        let meta = sample();
+
+        let paths = |options: IterOptions| -> Vec<String> {
+            meta.iter_with(options).map(|i| i.path).collect()
+        };
+
+        assert_eq!(
+            paths(IterOptions::default().namespace("dc").just_children()),
+            ["dc:subject", "dc:title"]
+        );
+        assert_eq!(
+            paths(IterOptions::default().namespace(ns::DC).just_leaf_nodes()),
+            ["dc:subject[1]", "dc:title[1]", "dc:title[1]/?xml:lang"]
+        );
+        assert_eq!(
+            paths(
+                IterOptions::default()
+                    .namespace(ns::DC)
+                    .omit_qualifiers()
+                    .just_leaf_name()
+            ),
+            ["dc:subject", "[1]", "dc:title", "[1]"]
+        );
+        assert!(paths(IterOptions::default().namespace("nope")).is_empty());
+
+        let mut iter = meta.iter_with(IterOptions::default().namespace(ns::DC));
+        assert_eq!(iter.next().unwrap().path, "dc:subject");
+        iter.skip_subtree();
+        assert_eq!(iter.next().unwrap().path, "dc:title");
+    }
+}
@@ -0,0 +1,558 @@
+//! JSON representation of XMP trees
+//!
+//! [`XmpMeta::to_json`] produces a lossless representation, keeping array
+//! forms, qualifiers and namespaces, that [`XmpMeta::from_json`] reads
+//! back:
+//!
+//! ```json
+//! {
+//!   "namespaces": { "dc": "http://purl.org/dc/elements/1.1/" },
+//!   "properties": {
+//!     "dc:format": { "value": "image/jpeg" },
+//!     "dc:title": {
+//!       "array": "Alt",
+//!       "items": [{ "value": "Title", "qualifiers": { "xml:lang": "x-default" } }]
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! Structures are `{ "fields": { ... } }` and URI values (`rdf:resource`
+//! in RDF/XML) are `{ "uri": "..." }`. `"about"` holds the `rdf:about` URI,
+//! if any.
+//!
+//! With [`JsonOptions::simple`], properties are plain JSON values instead:
+//! strings for simple values (and the default item of language
+//! alternatives), arrays and objects, without qualifiers or namespaces:
+//!
+//! ```json
+//! { "dc:format": "image/jpeg", "dc:title": "Title" }
+//! ```
+//!
+//! When reading the simple form, prefixes must be registered; arrays get
+//! the form defined by the built-in and registered schemas (`rdf:Seq`
+//! otherwise), and numbers and booleans become text.
+
+use super::node::new_root_node;
+use super::{item_lang, lang_item, XmpMeta};
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::namespace::{get_global_namespace_prefix, NamespaceMap};
+use crate::core::node::{ArrayNode, ArrayType, Node, StructureNode};
+use crate::core::validate::{self, ValueForm};
+use crate::types::qualifier::Qualifier;
+use crate::utils::convert;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// Options for JSON export and import
+#[derive(Default, Clone, Copy, Debug)]
+pub struct JsonOptions {
+    /// Use the lossy simple form
+    pub(crate) simple: bool,
+    /// Indent the output
+    pub(crate) pretty: bool,
+}
+
+impl JsonOptions {
+    /// Create default options (lossless, compact output)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use the lossy simple form: plain JSON values, without qualifiers,
+    /// array forms or namespaces.
+    pub fn simple(mut self) -> Self {
+        self.simple = true;
+        self
+    }
+
+    /// Indent the output.
+    pub fn pretty(mut self) -> Self {
+        self.pretty = true;
+        self
+    }
+}
+
+impl XmpMeta {
+    /// Export to lossless JSON
+    ///
+    /// See the [module documentation](self) for the representation.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{ns, XmpMeta};
+    ///
+    /// let mut meta = XmpMeta::new();
+    /// meta.set_localized_text(ns::DC, "title", "", "x-default", "Title")
+    ///     .unwrap();
+    /// let json = meta.to_json().unwrap();
+    /// assert_eq!(XmpMeta::from_json(&json).unwrap().to_json().unwrap(), json);
+    /// ```
+    pub fn to_json(&self) -> XmpResult<String> {
+        self.to_json_with(&JsonOptions::default())
+    }
+
+    /// Export to JSON with options
+    pub fn to_json_with(&self, options: &JsonOptions) -> XmpResult<String> {
+        let root = root_read!(self.root);
+        let mut names = Names::new(&self.namespaces);
+        let value = if options.simple {
+            Value::Object(simple_fields(&root, &mut names))
+        } else {
+            let properties = lossless_fields(&root, &mut names);
+            let mut object = Map::new();
+            if let Some(about) = &self.about_uri {
+                object.insert("about".to_string(), Value::String(about.clone()));
+            }
+            let namespaces = names
+                .prefixes
+                .into_iter()
+                .map(|(prefix, uri)| (prefix, Value::String(uri)))
+                .collect();
+            object.insert("namespaces".to_string(), Value::Object(namespaces));
+            object.insert("properties".to_string(), Value::Object(properties));
+            Value::Object(object)
+        };
+
+        let json = if options.pretty {
+            serde_json::to_string_pretty(&value)
+        } else {
+            serde_json::to_string(&value)
+        };
+        json.map_err(|e| XmpError::SerializationError(e.to_string()))
+    }
+
+    /// Import from lossless JSON, as produced by [`XmpMeta::to_json`]
+    pub fn from_json(s: &str) -> XmpResult<Self> {
+        Self::from_json_with(s, &JsonOptions::default())
+    }
+
+    /// Import from JSON with options
+    ///
+    /// With [`JsonOptions::simple`], `s` must be in the simple form.
+    pub fn from_json_with(s: &str, options: &JsonOptions) -> XmpResult<Self> {
+        let value: Value =
+            serde_json::from_str(s).map_err(|e| XmpError::ParseError(e.to_string()))?;
+        let object = as_object(&value, "document")?;
+        let mut meta = XmpMeta::new();
+
+        let root = if options.simple {
+            let mut root = StructureNode::new();
+            for (key, value) in object {
+                let (ns_uri, name) = meta.resolve_key(&HashMap::new(), key)?;
+                let form = validate::known_form(&ns_uri, name);
+                if let Some(node) = simple_node(&meta, value, form)? {
+                    root.set_field(format!("{}:{}", ns_uri, name), node);
+                }
+            }
+            root
+        } else {
+            let mut prefixes = HashMap::new();
+            if let Some(namespaces) = object.get("namespaces") {
+                for (prefix, uri) in as_object(namespaces, "namespaces")? {
+                    let uri = as_str(uri, "namespace URI")?;
+                    prefixes.insert(prefix.clone(), uri.to_string());
+                    // Prefixes already used for other namespaces are only
+                    // valid in the document
+                    let _ = meta.namespaces.register(uri, prefix);
+                }
+            }
+            if let Some(about) = object.get("about") {
+                meta.about_uri = Some(as_str(about, "about URI")?.to_string());
+            }
+            match object.get("properties") {
+                Some(properties) => meta.lossless_structure(&prefixes, properties)?,
+                None => StructureNode::new(),
+            }
+        };
+        meta.root = new_root_node(root);
+        Ok(meta)
+    }
+
+    /// Resolve a "prefix:name" key to the namespace URI and the name
+    pub(super) fn resolve_key<'k>(
+        &self,
+        prefixes: &HashMap<String, String>,
+        key: &'k str,
+    ) -> XmpResult<(String, &'k str)> {
+        let (prefix, name) = key
+            .split_once(':')
+            .ok_or_else(|| XmpError::ParseError(format!("Missing prefix in '{}'", key)))?;
+        let ns_uri = match prefixes.get(prefix) {
+            Some(uri) => uri.clone(),
+            None => self.resolve_namespace_uri_or_error(prefix)?,
+        };
+        Ok((ns_uri, name))
+    }
+
+    fn lossless_structure(
+        &self,
+        prefixes: &HashMap<String, String>,
+        value: &Value,
+    ) -> XmpResult<StructureNode> {
+        let mut structure = StructureNode::new();
+        for (key, value) in as_object(value, "fields")? {
+            let (ns_uri, name) = self.resolve_key(prefixes, key)?;
+            let node = self.lossless_node(prefixes, value)?;
+            structure.set_field(format!("{}:{}", ns_uri, name), node);
+        }
+        Ok(structure)
+    }
+
+    fn lossless_node(&self, prefixes: &HashMap<String, String>, value: &Value) -> XmpResult<Node> {
+        let object = as_object(value, "node")?;
+        let mut node = if let Some(value) = object.get("value") {
+            Node::simple(as_str(value, "value")?)
+        } else if let Some(uri) = object.get("uri") {
+            Node::uri(as_str(uri, "uri")?)
+        } else if let Some(form) = object.get("array") {
+            let array_type = match as_str(form, "array form")? {
+                "Seq" => ArrayType::Ordered,
+                "Bag" => ArrayType::Unordered,
+                "Alt" => ArrayType::Alternative,
+                other => {
+                    return Err(XmpError::ParseError(format!(
+                        "Unknown array form '{}'",
+                        other
+                    )))
+                }
+            };
+            let mut array = ArrayNode::new(array_type);
+            if let Some(items) = object.get("items") {
+                let items = items.as_array().ok_or_else(|| {
+                    XmpError::ParseError("Array items must be a list".to_string())
+                })?;
+                for item in items {
+                    array.append(self.lossless_node(prefixes, item)?);
+                }
+            }
+            Node::Array(array)
+        } else if let Some(fields) = object.get("fields") {
+            Node::Structure(self.lossless_structure(prefixes, fields)?)
+        } else {
+            return Err(XmpError::ParseError(
+                "Node must have a value, an array form or fields".to_string(),
+            ));
+        };
+
+        if let Some(qualifiers) = object.get("qualifiers") {
+            for (key, value) in as_object(qualifiers, "qualifiers")? {
+                let (ns_uri, name) = self.resolve_key(prefixes, key)?;
+                node.qualifiers_mut().push(Qualifier::new(
+                    ns_uri,
+                    name,
+                    as_str(value, "qualifier value")?,
+                ));
+            }
+        }
+        Ok(node)
+    }
+}
+
+pub(super) fn as_object<'v>(value: &'v Value, what: &str) -> XmpResult<&'v Map<String, Value>> {
+    value
+        .as_object()
+        .ok_or_else(|| XmpError::ParseError(format!("Expected an object for {}", what)))
+}
+
+pub(super) fn as_str<'v>(value: &'v Value, what: &str) -> XmpResult<&'v str> {
+    value
+        .as_str()
+        .ok_or_else(|| XmpError::ParseError(format!("Expected a string for {}", what)))
+}
+
+/// Prefixed names, with the prefixes used so far
+pub(super) struct Names<'a> {
+    namespaces: &'a NamespaceMap,
+    /// Prefix to URI
+    pub(super) prefixes: BTreeMap<String, String>,
+    /// URI to prefix
+    uris: HashMap<String, String>,
+}
+
+impl<'a> Names<'a> {
+    pub(super) fn new(namespaces: &'a NamespaceMap) -> Self {
+        Self {
+            namespaces,
+            prefixes: BTreeMap::new(),
+            uris: HashMap::new(),
+        }
+    }
+
+    /// Get the "prefix:name" form of a name, generating prefixes for
+    /// unregistered namespaces
+    pub(super) fn name(&mut self, ns_uri: &str, name: &str) -> String {
+        if let Some(prefix) = self.uris.get(ns_uri) {
+            return format!("{}:{}", prefix, name);
+        }
+        let registered = self
+            .namespaces
+            .get_prefix(ns_uri)
+            .map(str::to_string)
+            .or_else(|| get_global_namespace_prefix(ns_uri))
+            .filter(|prefix| !self.prefixes.contains_key(prefix));
+        let prefix = registered.unwrap_or_else(|| {
+            (1..)
+                .map(|n| format!("ns{}", n))
+                .find(|prefix| !self.prefixes.contains_key(prefix))
+                .unwrap_or_default()
+        });
+        self.prefixes.insert(prefix.clone(), ns_uri.to_string());
+        self.uris.insert(ns_uri.to_string(), prefix.clone());
+        format!("{}:{}", prefix, name)
+    }
+
+    /// Get the "prefix:name" form of an internal "ns_uri:name" key
+    pub(super) fn key(&mut self, key: &str) -> String {
+        match key.rsplit_once(':') {
+            Some((ns_uri, name)) => self.name(ns_uri, name),
+            None => key.to_string(),
+        }
+    }
+}
+
+/// Get the fields of a structure, sorted by key
+pub(super) fn sorted_fields(structure: &StructureNode) -> Vec<(&String, &Node)> {
+    let mut fields: Vec<_> = structure.fields.iter().collect();
+    fields.sort_by_key(|(key, _)| *key);
+    fields
+}
+
+fn lossless_fields(structure: &StructureNode, names: &mut Names) -> Map<String, Value> {
+    sorted_fields(structure)
+        .into_iter()
+        .map(|(key, node)| (names.key(key), lossless_value(node, names)))
+        .collect()
+}
+
+fn lossless_value(node: &Node, names: &mut Names) -> Value {
+    let mut object = Map::new();
+    match node {
+        Node::Simple(simple) => {
+            let key = if simple.is_uri { "uri" } else { "value" };
+            object.insert(key.to_string(), Value::String(simple.value.clone()));
+        }
+        Node::Array(array) => {
+            object.insert(
+                "array".to_string(),
+                Value::String(array.array_type.rdf_type().to_string()),
+            );
+            let items = array
+                .items
+                .iter()
+                .map(|item| lossless_value(item, names))
+                .collect();
+            object.insert("items".to_string(), Value::Array(items));
+        }
+        Node::Structure(structure) => {
+            object.insert(
+                "fields".to_string(),
+                Value::Object(lossless_fields(structure, names)),
+            );
+        }
+    }
+
+    if !node.qualifiers().is_empty() {
+        let qualifiers = node
+            .qualifiers()
+            .iter()
+            .map(|q| {
+                (
+                    names.name(&q.namespace, &q.name),
+                    Value::String(q.value.clone()),
+                )
+            })
+            .collect();
+        object.insert("qualifiers".to_string(), Value::Object(qualifiers));
+    }
+    Value::Object(object)
+}
+
+fn simple_fields(structure: &StructureNode, names: &mut Names) -> Map<String, Value> {
+    sorted_fields(structure)
+        .into_iter()
+        .map(|(key, node)| (names.key(key), simple_value(node, names)))
+        .collect()
+}
+
+fn simple_value(node: &Node, names: &mut Names) -> Value {
+    match node {
+        Node::Simple(simple) => Value::String(simple.value.clone()),
+        Node::Array(array) => {
+            let is_lang_alt = array.array_type == ArrayType::Alternative
+                && !array.is_empty()
+                && array.items.iter().all(|item| item_lang(item).is_some());
+            if is_lang_alt {
+                let item = array
+                    .items
+                    .iter()
+                    .find(|item| item_lang(item) == Some("x-default"))
+                    .unwrap_or(&array.items[0]);
+                return simple_value(item, names);
+            }
+            Value::Array(
+                array
+                    .items
+                    .iter()
+                    .map(|item| simple_value(item, names))
+                    .collect(),
+            )
+        }
+        Node::Structure(structure) => Value::Object(simple_fields(structure, names)),
+    }
+}
+
+/// Convert a value of the simple form to a node, using the schema form of
+/// top-level properties
+fn simple_node(meta: &XmpMeta, value: &Value, form: Option<ValueForm>) -> XmpResult<Option<Node>> {
+    let node = match value {
+        Value::Null => return Ok(None),
+        Value::Bool(b) => Node::simple(convert::convert_from_bool(*b)),
+        Value::Number(n) => Node::simple(n.to_string()),
+        Value::String(s) if form == Some(ValueForm::LangAlt) => {
+            let mut array = ArrayNode::new(ArrayType::Alternative);
+            array.append(lang_item("x-default", s));
+            Node::Array(array)
+        }
+        Value::String(s) => Node::simple(s.as_str()),
+        Value::Array(items) => {
+            let array_type = match form {
+                Some(ValueForm::Bag) => ArrayType::Unordered,
+                Some(ValueForm::Alt | ValueForm::LangAlt) => ArrayType::Alternative,
+                _ => ArrayType::Ordered,
+            };
+            let mut array = ArrayNode::new(array_type);
+            for item in items {
+                if let Some(item) = simple_node(meta, item, None)? {
+                    array.append(item);
+                }
+            }
+            Node::Array(array)
+        }
+        Value::Object(fields) => {
+            let mut structure = StructureNode::new();
+            for (key, value) in fields {
+                let (ns_uri, name) = meta.resolve_key(&HashMap::new(), key)?;
+                if let Some(node) = simple_node(meta, value, None)? {
+                    structure.set_field(format!("{}:{}", ns_uri, name), node);
+                }
+            }
+            Node::Structure(structure)
+        }
+    };
+    Ok(Some(node))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+
+    const PACKET: &str = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+<rdf:Description rdf:about=""
+    xmlns:dc="http://purl.org/dc/elements/1.1/"
+    xmlns:exif="http://ns.adobe.com/exif/1.0/"
+    xmlns:my="http://example.com/ns/json-test/"
+    dc:format="image/jpeg"
+    my:Custom="value">
+  <dc:title><rdf:Alt><rdf:li xml:lang="x-default">Title</rdf:li><rdf:li xml:lang="fr">Titre</rdf:li></rdf:Alt></dc:title>
+  <dc:subject><rdf:Bag><rdf:li>sky</rdf:li><rdf:li>sea</rdf:li></rdf:Bag></dc:subject>
+  <exif:Flash rdf:parseType="Resource"><exif:Fired>False</exif:Fired></exif:Flash>
+</rdf:Description>
+</rdf:RDF>
+</x:xmpmeta>"#;
+
+    #[test]
+    fn test_lossless_round_trip() {
+        let meta = XmpMeta::parse(PACKET).unwrap();
+        let json = meta.to_json().unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value["namespaces"]["my"],
+            "http://example.com/ns/json-test/"
+        );
+        assert_eq!(value["properties"]["my:Custom"]["value"], "value");
+        assert_eq!(value["properties"]["dc:subject"]["array"], "Bag");
+        assert_eq!(
+            value["properties"]["dc:title"]["items"][1]["qualifiers"]["xml:lang"],
+            "fr"
+        );
+        assert_eq!(
+            value["properties"]["exif:Flash"]["fields"]["exif:Fired"]["value"],
+            "False"
+        );
+
+        let read = XmpMeta::from_json(&json).unwrap();
+        assert_eq!(read.to_json().unwrap(), json);
+        assert_eq!(
+            read.get_localized_text(ns::DC, "title", "fr", "fr"),
+            Some(("Titre".to_string(), "fr".to_string()))
+        );
+
+        assert!(matches!(
+            XmpMeta::from_json(r#"{"properties":{"zz:Unknown":{"value":"x"}}}"#),
+            Err(XmpError::BadSchema(_))
+        ));
+        assert!(matches!(
+            XmpMeta::from_json(r#"{"properties":{"dc:format":{}}}"#),
+            Err(XmpError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_document_prefixes() {
+        // A registered namespace under another prefix, and an unregistered one
+        let meta = XmpMeta::parse(
+            r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+<rdf:Description rdf:about=""
+    xmlns:d="http://purl.org/dc/elements/1.1/"
+    xmlns:cam="http://example.com/ns/json-prefixes/"
+    d:format="image/jpeg"
+    cam:Model="X100"/>
+</rdf:RDF>"#,
+        )
+        .unwrap();
+        let json = meta.to_json().unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+
+        // The registered prefix is used for the registered namespace
+        assert_eq!(value["properties"]["dc:format"]["value"], "image/jpeg");
+        assert_eq!(value["namespaces"]["dc"], ns::DC);
+        assert!(value["namespaces"].get("d").is_none());
+        // The document prefix is kept for the unregistered one
+        assert_eq!(value["properties"]["cam:Model"]["value"], "X100");
+        assert_eq!(
+            value["namespaces"]["cam"],
+            "http://example.com/ns/json-prefixes/"
+        );
+        assert!(value["namespaces"].get("ns1").is_none());
+    }
+
+    #[test]
+    fn test_simple_mode() {
+        let meta = XmpMeta::parse(PACKET).unwrap();
+        let simple = JsonOptions::new().simple();
+        let json = meta.to_json_with(&simple).unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["dc:title"], "Title");
+        assert_eq!(value["dc:subject"], serde_json::json!(["sky", "sea"]));
+        assert_eq!(value["exif:Flash"]["exif:Fired"], "False");
+        assert_eq!(value["dc:format"], "image/jpeg");
+
+        let read = XmpMeta::from_json_with(
+            r#"{"dc:title": "Title", "dc:subject": ["sky"], "xmp:Rating": 4, "dc:source": null}"#,
+            &simple,
+        )
+        .unwrap();
+        assert_eq!(
+            read.get_localized_text(ns::DC, "title", "", "x-default"),
+            Some(("Title".to_string(), "x-default".to_string()))
+        );
+        assert_eq!(read.count_array_items(ns::DC, "subject"), 1);
+        assert!(read.serialize().unwrap().contains("<rdf:Bag>"));
+        assert_eq!(read.get_property(ns::XMP, "Rating"), Some("4".into()));
+        assert!(!read.has_property(ns::DC, "source"));
+    }
+}
@@ -0,0 +1,424 @@
+//! JSON-LD representation of XMP
+//!
+//! XMP is RDF, so [`XmpMeta::to_jsonld`] writes the metadata as a JSON-LD
+//! node object and [`XmpMeta::from_jsonld`] reads it back:
+//! - `@context` maps the prefixes to the namespace URIs, and `@id` is the
+//!   `rdf:about` URI
+//! - simple values are strings, or value objects with `@language` for
+//!   `xml:lang` qualified text
+//! - URI values (`rdf:resource` in RDF/XML) are node references with only
+//!   an `@id`
+//! - arrays are nodes typed `rdf:Bag`, `rdf:Seq` or `rdf:Alt` whose items
+//!   are the `rdf:_1`, `rdf:_2`, ... membership properties
+//! - structures are nested node objects
+//! - other qualifiers are properties of a node holding the value in
+//!   `rdf:value`, as in RDF/XML
+//!
+//! ```json
+//! {
+//!   "@context": {
+//!     "dc": "http://purl.org/dc/elements/1.1/",
+//!     "rdf": "http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+//!   },
+//!   "@id": "",
+//!   "dc:format": "image/jpeg",
+//!   "dc:title": {
+//!     "@type": "rdf:Alt",
+//!     "rdf:_1": { "@value": "Title", "@language": "x-default" }
+//!   }
+//! }
+//! ```
+//!
+//! When reading, full IRIs can be used instead of compact ones, a single
+//! node in `@graph` is accepted, and JSON arrays and `@list` objects are
+//! read as `rdf:Bag` and `rdf:Seq` arrays respectively.
+
+use super::json::{as_object, as_str, sorted_fields, Names};
+use super::node::new_root_node;
+use super::XmpMeta;
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::namespace::ns;
+use crate::core::node::{ArrayNode, ArrayType, Node, StructureNode};
+use crate::types::qualifier::Qualifier;
+use crate::utils::convert;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+impl XmpMeta {
+    /// Serialize to JSON-LD
+    ///
+    /// See the [module documentation](self) for the representation.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{ns, XmpMeta, XmpValue};
+    ///
+    /// let mut meta = XmpMeta::new();
+    /// meta.set_property(ns::XMP, "CreatorTool", XmpValue::String("MyApp".into()))
+    ///     .unwrap();
+    /// let jsonld = meta.to_jsonld().unwrap();
+    /// assert!(jsonld.contains("\"xmp:CreatorTool\":\"MyApp\""));
+    ///
+    /// let read = XmpMeta::from_jsonld(&jsonld).unwrap();
+    /// assert_eq!(read.get_property(ns::XMP, "CreatorTool"), Some("MyApp".into()));
+    /// ```
+    pub fn to_jsonld(&self) -> XmpResult<String> {
+        let root = root_read!(self.root);
+        let mut names = Names::new(&self.namespaces);
+        let mut object = Map::new();
+        for (key, node) in sorted_fields(&root) {
+            object.insert(names.key(key), node_value(node, &mut names));
+        }
+        object.insert(
+            "@id".to_string(),
+            Value::String(self.about_uri.clone().unwrap_or_default()),
+        );
+        let context = names
+            .prefixes
+            .into_iter()
+            .map(|(prefix, uri)| (prefix, Value::String(uri)))
+            .collect();
+        object.insert("@context".to_string(), Value::Object(context));
+
+        serde_json::to_string(&Value::Object(object))
+            .map_err(|e| XmpError::SerializationError(e.to_string()))
+    }
+
+    /// Parse from JSON-LD, as produced by [`XmpMeta::to_jsonld`]
+    ///
+    /// Compact IRIs must use a prefix of the `@context` or a registered
+    /// prefix. Remote contexts are not supported.
+    pub fn from_jsonld(s: &str) -> XmpResult<Self> {
+        let value: Value =
+            serde_json::from_str(s).map_err(|e| XmpError::ParseError(e.to_string()))?;
+        let mut object = as_object(&value, "document")?;
+        if let Some(graph) = object.get("@graph") {
+            match graph.as_array().map(Vec::as_slice) {
+                Some([node]) => object = as_object(node, "graph node")?,
+                _ => {
+                    return Err(XmpError::NotSupported(
+                        "@graph must hold a single node".to_string(),
+                    ))
+                }
+            }
+        }
+
+        let mut meta = XmpMeta::new();
+        let mut prefixes = HashMap::new();
+        let context = object.get("@context").or_else(|| value.get("@context"));
+        match context {
+            None => {}
+            Some(Value::Object(context)) => {
+                for (prefix, uri) in context {
+                    // Terms other than prefix definitions are ignored
+                    let Some(uri) = uri.as_str() else {
+                        continue;
+                    };
+                    if prefix.starts_with('@') {
+                        continue;
+                    }
+                    prefixes.insert(prefix.clone(), uri.to_string());
+                    let _ = meta.namespaces.register(uri, prefix);
+                }
+            }
+            Some(_) => {
+                return Err(XmpError::NotSupported(
+                    "Only inline @context objects are supported".to_string(),
+                ))
+            }
+        }
+        if let Some(id) = object.get("@id") {
+            let id = as_str(id, "@id")?;
+            if !id.is_empty() {
+                meta.about_uri = Some(id.to_string());
+            }
+        }
+
+        let reader = Reader {
+            meta: &meta,
+            prefixes: &prefixes,
+        };
+        let root = reader.structure(object)?;
+        meta.root = new_root_node(root);
+        Ok(meta)
+    }
+}
+
+/// Convert a node to a JSON-LD value
+fn node_value(node: &Node, names: &mut Names) -> Value {
+    let (lang, qualifiers): (Vec<&Qualifier>, Vec<&Qualifier>) = node
+        .qualifiers()
+        .iter()
+        .partition(|q| q.namespace == ns::XML && q.name == "lang" && node.is_simple());
+
+    let value = match node {
+        Node::Simple(simple) => match lang.first() {
+            Some(lang) => {
+                let mut object = Map::new();
+                object.insert("@value".to_string(), Value::String(simple.value.clone()));
+                object.insert("@language".to_string(), Value::String(lang.value.clone()));
+                Value::Object(object)
+            }
+            None if simple.is_uri => {
+                let mut object = Map::new();
+                object.insert("@id".to_string(), Value::String(simple.value.clone()));
+                Value::Object(object)
+            }
+            None => Value::String(simple.value.clone()),
+        },
+        Node::Array(array) => {
+            let mut object = Map::new();
+            object.insert(
+                "@type".to_string(),
+                Value::String(names.name(ns::RDF, array.array_type.rdf_type())),
+            );
+            for (index, item) in array.items.iter().enumerate() {
+                let key = names.name(ns::RDF, &format!("_{}", index + 1));
+                object.insert(key, node_value(item, names));
+            }
+            Value::Object(object)
+        }
+        Node::Structure(structure) => {
+            let mut object = Map::new();
+            for (key, field) in sorted_fields(structure) {
+                object.insert(names.key(key), node_value(field, names));
+            }
+            Value::Object(object)
+        }
+    };
+
+    if qualifiers.is_empty() {
+        return value;
+    }
+    let mut object = Map::new();
+    object.insert(names.name(ns::RDF, "value"), value);
+    for q in qualifiers {
+        object.insert(
+            names.name(&q.namespace, &q.name),
+            Value::String(q.value.clone()),
+        );
+    }
+    Value::Object(object)
+}
+
+/// Reader of JSON-LD node objects
+struct Reader<'a> {
+    meta: &'a XmpMeta,
+    /// Prefixes of the `@context`
+    prefixes: &'a HashMap<String, String>,
+}
+
+impl Reader<'_> {
+    /// Resolve a compact or full IRI to the namespace URI and the name
+    fn resolve(&self, key: &str) -> XmpResult<(String, String)> {
+        if key.starts_with("http://") || key.starts_with("https://") {
+            let split = key.rfind(['#', '/']).map_or(key.len(), |i| i + 1);
+            return Ok((key[..split].to_string(), key[split..].to_string()));
+        }
+        let (ns_uri, name) = self.meta.resolve_key(self.prefixes, key)?;
+        Ok((ns_uri, name.to_string()))
+    }
+
+    /// Get the properties of a node object, skipping keywords
+    fn properties<'v>(
+        &self,
+        object: &'v Map<String, Value>,
+    ) -> XmpResult<Vec<(String, String, &'v Value)>> {
+        object
+            .iter()
+            .filter(|(key, _)| !key.starts_with('@'))
+            .map(|(key, value)| {
+                let (ns_uri, name) = self.resolve(key)?;
+                Ok((ns_uri, name, value))
+            })
+            .collect()
+    }
+
+    fn structure(&self, object: &Map<String, Value>) -> XmpResult<StructureNode> {
+        let mut structure = StructureNode::new();
+        for (ns_uri, name, value) in self.properties(object)? {
+            if let Some(node) = self.node(value)? {
+                structure.set_field(format!("{}:{}", ns_uri, name), node);
+            }
+        }
+        Ok(structure)
+    }
+
+    fn node(&self, value: &Value) -> XmpResult<Option<Node>> {
+        let object = match value {
+            Value::Null => return Ok(None),
+            Value::Bool(b) => return Ok(Some(Node::simple(convert::convert_from_bool(*b)))),
+            Value::Number(n) => return Ok(Some(Node::simple(n.to_string()))),
+            Value::String(s) => return Ok(Some(Node::simple(s.as_str()))),
+            Value::Array(items) => return self.array(ArrayType::Unordered, items.iter()).map(Some),
+            Value::Object(object) => object,
+        };
+
+        if let Some(value) = object.get("@value") {
+            let Some(mut node) = self.node(value)? else {
+                return Ok(None);
+            };
+            if let Some(lang) = object.get("@language") {
+                node.qualifiers_mut().push(Qualifier::new(
+                    ns::XML,
+                    "lang",
+                    as_str(lang, "@language")?,
+                ));
+            }
+            return Ok(Some(node));
+        }
+        // A reference to a resource
+        if let (Some(id), 1) = (object.get("@id"), object.len()) {
+            return Ok(Some(Node::uri(as_str(id, "@id")?)));
+        }
+        if let Some(list) = object.get("@list") {
+            let items = list
+                .as_array()
+                .ok_or_else(|| XmpError::ParseError("@list must be an array".to_string()))?;
+            return self.array(ArrayType::Ordered, items.iter()).map(Some);
+        }
+
+        let properties = self.properties(object)?;
+        let is_rdf = |(ns_uri, name, _): &&(String, String, &Value), expected: &str| {
+            ns_uri == ns::RDF && name == expected
+        };
+
+        // Qualified value
+        if let Some((_, _, value)) = properties.iter().find(|p| is_rdf(p, "value")) {
+            let Some(mut node) = self.node(value)? else {
+                return Ok(None);
+            };
+            for (ns_uri, name, value) in properties.iter().filter(|p| !is_rdf(p, "value")) {
+                let qualifier = match self.node(value)? {
+                    Some(Node::Simple(simple)) => simple.value,
+                    _ => {
+                        return Err(XmpError::ParseError(format!(
+                            "Qualifier '{}' must be a simple value",
+                            name
+                        )))
+                    }
+                };
+                node.qualifiers_mut().push(Qualifier::new(
+                    ns_uri.as_str(),
+                    name.as_str(),
+                    qualifier,
+                ));
+            }
+            return Ok(Some(node));
+        }
+
+        // Array
+        let array_type = match object.get("@type").and_then(Value::as_str) {
+            Some(rdf_type) => match self.resolve(rdf_type)? {
+                (ns_uri, name) if ns_uri == ns::RDF => match name.as_str() {
+                    "Bag" => Some(ArrayType::Unordered),
+                    "Seq" => Some(ArrayType::Ordered),
+                    "Alt" => Some(ArrayType::Alternative),
+                    _ => None,
+                },
+                _ => None,
+            },
+            None => None,
+        };
+        if let Some(array_type) = array_type {
+            let mut items: Vec<(usize, &Value)> = properties
+                .iter()
+                .filter(|(ns_uri, _, _)| ns_uri == ns::RDF)
+                .filter_map(|(_, name, value)| {
+                    Some((name.strip_prefix('_')?.parse().ok()?, *value))
+                })
+                .collect();
+            items.sort_by_key(|(index, _)| *index);
+            return self
+                .array(array_type, items.into_iter().map(|(_, value)| value))
+                .map(Some);
+        }
+
+        Ok(Some(Node::Structure(self.structure(object)?)))
+    }
+
+    fn array<'v>(
+        &self,
+        array_type: ArrayType,
+        items: impl Iterator<Item = &'v Value>,
+    ) -> XmpResult<Node> {
+        let mut array = ArrayNode::new(array_type);
+        for item in items {
+            if let Some(item) = self.node(item)? {
+                array.append(item);
+            }
+        }
+        Ok(Node::Array(array))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACKET: &str = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+<rdf:Description rdf:about=""
+    xmlns:dc="http://purl.org/dc/elements/1.1/"
+    xmlns:exif="http://ns.adobe.com/exif/1.0/"
+    xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+    dc:format="image/jpeg">
+  <dc:title><rdf:Alt><rdf:li xml:lang="x-default">Title</rdf:li><rdf:li xml:lang="fr">Titre</rdf:li></rdf:Alt></dc:title>
+  <dc:creator><rdf:Seq><rdf:li>A</rdf:li><rdf:li>B</rdf:li></rdf:Seq></dc:creator>
+  <exif:Flash rdf:parseType="Resource"><exif:Fired>False</exif:Fired></exif:Flash>
+  <xmp:CreatorTool rdf:parseType="Resource"><rdf:value>App</rdf:value><xmp:Nickname>app</xmp:Nickname></xmp:CreatorTool>
+</rdf:Description>
+</rdf:RDF>
+</x:xmpmeta>"#;
+
+    #[test]
+    fn test_round_trip() {
+        let mut meta = XmpMeta::parse(PACKET).unwrap();
+        meta.set_about_uri("uuid:1234");
+        let jsonld = meta.to_jsonld().unwrap();
+        let value: Value = serde_json::from_str(&jsonld).unwrap();
+        assert_eq!(value["@context"]["rdf"], ns::RDF);
+        assert_eq!(value["dc:format"], "image/jpeg");
+        assert_eq!(value["dc:creator"]["@type"], "rdf:Seq");
+        assert_eq!(value["dc:creator"]["rdf:_2"], "B");
+        assert_eq!(value["dc:title"]["rdf:_2"]["@language"], "fr");
+        assert_eq!(value["exif:Flash"]["exif:Fired"], "False");
+        assert_eq!(value["xmp:CreatorTool"]["rdf:value"], "App");
+        assert_eq!(value["xmp:CreatorTool"]["xmp:Nickname"], "app");
+
+        let read = XmpMeta::from_jsonld(&jsonld).unwrap();
+        assert_eq!(read.to_jsonld().unwrap(), jsonld);
+        assert!(meta.diff(&read).is_empty());
+    }
+
+    #[test]
+    fn test_linked_data_input() {
+        let read = XmpMeta::from_jsonld(
+            r#"{
+              "@context": {"dc": "http://purl.org/dc/elements/1.1/", "@vocab": "http://schema.org/"},
+              "@graph": [{
+                "@id": "https://example.com/photo.jpg",
+                "dc:subject": ["sky", "sea"],
+                "http://purl.org/dc/elements/1.1/creator": {"@list": ["A", "B"]},
+                "dc:description": {"@value": "Photo", "@language": "en"},
+                "http://ns.adobe.com/xap/1.0/Rating": 4
+              }]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(read.about_uri(), Some("https://example.com/photo.jpg"));
+        assert_eq!(read.count_array_items(ns::DC, "subject"), 2);
+        assert_eq!(read.get_array_item(ns::DC, "creator", 1), Some("B".into()));
+        assert_eq!(read.get_property(ns::XMP, "Rating"), Some("4".into()));
+        assert!(read
+            .to_jsonld()
+            .unwrap()
+            .contains(r#""dc:description":{"@language":"en","@value":"Photo"}"#));
+
+        assert!(matches!(
+            XmpMeta::from_jsonld(r#"{"@context": "https://schema.org/"}"#),
+            Err(XmpError::NotSupported(_))
+        ));
+    }
+}
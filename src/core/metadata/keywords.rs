@@ -0,0 +1,235 @@
+//! Keywords (`dc:subject`, `lr:hierarchicalSubject`)
+//!
+//! `dc:subject` is a bag of flat keywords. Lightroom also keeps the
+//! keyword hierarchy in `lr:hierarchicalSubject`, where each item is a
+//! path from a top-level keyword, with the levels separated by `|`, as in
+//! `"Places|France|Paris"`. The helpers here keep both in step: a
+//! hierarchical keyword is added with its leaf ("Paris") in `dc:subject`,
+//! and removing a keyword removes it from both.
+//!
+//! # Example
+//!
+//! ```rust
+//! use xmpkit::XmpMeta;
+//!
+//! let mut meta = XmpMeta::new();
+//! meta.add_keyword("sunset").unwrap();
+//! meta.add_keyword("Places|France|Paris").unwrap();
+//! assert_eq!(meta.keywords(), ["sunset", "Paris"]);
+//! assert_eq!(meta.hierarchical_keywords(), ["Places|France|Paris"]);
+//!
+//! meta.remove_keyword("Places|France").unwrap();
+//! assert_eq!(meta.keywords(), ["sunset"]);
+//! ```
+
+use super::XmpMeta;
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::namespace::ns;
+use crate::core::node::ArrayType;
+use crate::types::value::XmpValue;
+
+/// Separator of the levels of a hierarchical keyword
+const LEVEL_SEPARATOR: char = '|';
+
+impl XmpMeta {
+    /// Get the keywords of `dc:subject`
+    pub fn keywords(&self) -> Vec<String> {
+        self.text_items(ns::DC, "subject")
+    }
+
+    /// Get the hierarchical keywords of `lr:hierarchicalSubject`, with the
+    /// levels separated by `|`
+    pub fn hierarchical_keywords(&self) -> Vec<String> {
+        self.text_items(ns::LIGHTROOM, "hierarchicalSubject")
+    }
+
+    /// Add a keyword, if it is not there yet
+    ///
+    /// A keyword with levels separated by `|`, such as
+    /// `"Places|France|Paris"`, is added to `lr:hierarchicalSubject`, and
+    /// its last level to `dc:subject`. A flat keyword is added to
+    /// `dc:subject`, and to `lr:hierarchicalSubject` if the metadata has
+    /// one, so that Lightroom doesn't drop it. Spaces around the levels are
+    /// removed.
+    ///
+    /// Fails with `XmpError::BadParam` if the keyword or one of its levels
+    /// is empty.
+    pub fn add_keyword(&mut self, keyword: &str) -> XmpResult<()> {
+        let levels = keyword_levels(keyword)?;
+        let leaf = levels[levels.len() - 1];
+        if !self.keywords().iter().any(|k| k == leaf) {
+            self.append_array_item_with(ns::DC, "subject", ArrayType::Unordered, leaf.into())?;
+        }
+
+        if levels.len() > 1 || self.does_property_exist(ns::LIGHTROOM, "hierarchicalSubject") {
+            let path = levels.join("|");
+            if !self.hierarchical_keywords().contains(&path) {
+                self.append_array_item_with(
+                    ns::LIGHTROOM,
+                    "hierarchicalSubject",
+                    ArrayType::Unordered,
+                    path.into(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove a keyword
+    ///
+    /// A hierarchical keyword is removed from `lr:hierarchicalSubject`
+    /// together with the keywords below it, and their last levels from
+    /// `dc:subject` unless another hierarchical keyword ends with them. A flat
+    /// keyword is removed from `dc:subject`, along with the hierarchical
+    /// keywords that end with it. Arrays left empty are removed.
+    ///
+    /// Returns `true` if a keyword was removed.
+    ///
+    /// Fails with `XmpError::BadParam` if the keyword or one of its levels
+    /// is empty.
+    pub fn remove_keyword(&mut self, keyword: &str) -> XmpResult<bool> {
+        let levels = keyword_levels(keyword)?;
+        let path = levels.join("|");
+        let hierarchical = self.hierarchical_keywords();
+
+        let removed_paths: Vec<&String> = if levels.len() > 1 {
+            let prefix = format!("{}{}", path, LEVEL_SEPARATOR);
+            hierarchical
+                .iter()
+                .filter(|k| **k == path || k.starts_with(&prefix))
+                .collect()
+        } else {
+            hierarchical
+                .iter()
+                .filter(|k| hierarchy_leaf(k) == path)
+                .collect()
+        };
+        let kept_leaves: Vec<&str> = hierarchical
+            .iter()
+            .filter(|k| !removed_paths.contains(k))
+            .map(|k| hierarchy_leaf(k))
+            .collect();
+        let mut removed_leaves: Vec<&str> = removed_paths
+            .iter()
+            .map(|k| hierarchy_leaf(k))
+            .filter(|leaf| !kept_leaves.contains(leaf))
+            .collect();
+        if levels.len() == 1 {
+            removed_leaves.push(&path);
+        }
+
+        let removed_hierarchy =
+            self.remove_text_items(ns::LIGHTROOM, "hierarchicalSubject", |k| {
+                removed_paths.iter().any(|path| *path == k)
+            })?;
+        let removed_subject =
+            self.remove_text_items(ns::DC, "subject", |k| removed_leaves.contains(&k))?;
+        Ok(removed_hierarchy || removed_subject)
+    }
+
+    /// Get the text items of an array property
+    fn text_items(&self, namespace: &str, name: &str) -> Vec<String> {
+        let count = self.get_array_size(namespace, name).unwrap_or(0);
+        (0..count)
+            .filter_map(|i| match self.get_array_item(namespace, name, i)? {
+                XmpValue::String(s) => Some(s),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Remove the text items of an array property matching `remove`, and
+    /// the property if no items are left
+    ///
+    /// Returns `true` if an item was removed.
+    fn remove_text_items(
+        &mut self,
+        namespace: &str,
+        name: &str,
+        remove: impl Fn(&str) -> bool,
+    ) -> XmpResult<bool> {
+        let count = self.get_array_size(namespace, name).unwrap_or(0);
+        let mut removed = false;
+        for i in (0..count).rev() {
+            if let Some(XmpValue::String(item)) = self.get_array_item(namespace, name, i) {
+                if remove(&item) {
+                    self.delete_array_item(namespace, name, i)?;
+                    removed = true;
+                }
+            }
+        }
+        if removed && self.get_array_size(namespace, name) == Some(0) {
+            self.delete_property(namespace, name)?;
+        }
+        Ok(removed)
+    }
+}
+
+/// Split a keyword into its trimmed levels
+fn keyword_levels(keyword: &str) -> XmpResult<Vec<&str>> {
+    let levels: Vec<&str> = keyword.split(LEVEL_SEPARATOR).map(str::trim).collect();
+    if levels.iter().any(|level| level.is_empty()) {
+        return Err(XmpError::BadParam(format!("Invalid keyword '{}'", keyword)));
+    }
+    Ok(levels)
+}
+
+/// Get the last level of a hierarchical keyword
+fn hierarchy_leaf(keyword: &str) -> &str {
+    keyword
+        .rsplit(LEVEL_SEPARATOR)
+        .next()
+        .unwrap_or(keyword)
+        .trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keywords() {
+        let mut meta = XmpMeta::new();
+        assert!(meta.keywords().is_empty());
+
+        meta.add_keyword("beach").unwrap();
+        meta.add_keyword("beach").unwrap();
+        assert_eq!(meta.keywords(), ["beach"]);
+        assert!(!meta.does_property_exist(ns::LIGHTROOM, "hierarchicalSubject"));
+        assert!(meta.add_keyword("").is_err());
+        assert!(meta.add_keyword("Places||Paris").is_err());
+
+        meta.add_keyword(" Places | France | Paris ").unwrap();
+        meta.add_keyword("Places|France|Lyon").unwrap();
+        meta.add_keyword("People|Paris").unwrap();
+        meta.add_keyword("sunset").unwrap();
+        assert_eq!(meta.keywords(), ["beach", "Paris", "Lyon", "sunset"]);
+        assert_eq!(
+            meta.hierarchical_keywords(),
+            [
+                "Places|France|Paris",
+                "Places|France|Lyon",
+                "People|Paris",
+                "sunset"
+            ]
+        );
+
+        let packet = meta.serialize_packet().unwrap();
+        assert!(packet.contains("<lr:hierarchicalSubject>"));
+        let mut meta = XmpMeta::parse(&packet).unwrap();
+
+        // "Paris" is still the leaf of People|Paris
+        assert!(meta.remove_keyword("Places|France").unwrap());
+        assert_eq!(meta.keywords(), ["beach", "Paris", "sunset"]);
+        assert_eq!(meta.hierarchical_keywords(), ["People|Paris", "sunset"]);
+
+        assert!(meta.remove_keyword("Paris").unwrap());
+        assert!(meta.remove_keyword("sunset").unwrap());
+        assert!(!meta.remove_keyword("sunset").unwrap());
+        assert_eq!(meta.keywords(), ["beach"]);
+        assert!(!meta.does_property_exist(ns::LIGHTROOM, "hierarchicalSubject"));
+
+        assert!(meta.remove_keyword("beach").unwrap());
+        assert!(!meta.does_property_exist(ns::DC, "subject"));
+    }
+}
@@ -2,19 +2,64 @@
 //!
 //! This module provides the main XmpMeta struct for working with XMP metadata.
 
+use crate::core::alias;
+use crate::core::borrowed::XmpMetaRef;
 use crate::core::error::{XmpError, XmpResult};
+use crate::core::mm;
 use crate::core::namespace::NamespaceMap;
-use crate::core::node::{Node, StructureNode};
-use crate::core::parser::XmpParser;
-use crate::core::serializer::XmpSerializer;
+use crate::core::node::{ArrayNode, ArrayType, Node, SimpleNode, StructureNode};
+use crate::core::parser::{ParseOptions, ParseWarning, XmpParser};
+use crate::core::path::{self, PathStep};
+use crate::core::serializer::SerializeOptions;
+use crate::core::validate::{self, ValueForm};
+use crate::types::qualifier::Qualifier;
 use crate::types::value::XmpValue;
+use crate::utils::encoding::PacketEncoding;
+use crate::utils::{convert, guid};
+use std::borrow::Cow;
+use std::io::BufRead;
 use std::str::FromStr;
 
 mod node;
 #[macro_use]
 mod macros;
-
+mod append;
+mod catenate;
+mod diff;
+#[cfg(feature = "jpeg")]
+mod extended;
+mod gps;
+mod iter;
+#[cfg(feature = "serde_json")]
+mod json;
+#[cfg(feature = "jsonld")]
+mod jsonld;
+mod keywords;
+mod namespaces;
+mod rating;
+mod regions;
+#[cfg(feature = "serde")]
+mod schema_serde;
+mod snapshot;
+mod subtree;
+mod thumbnail;
+
+pub use append::{AppendOptions, TemplateOptions};
+pub use catenate::SeparateOptions;
+pub use diff::{DiffEntry, XmpDiff};
+#[cfg(feature = "jpeg")]
+pub(crate) use extended::HAS_EXTENDED_XMP;
+pub use gps::GpsPosition;
+pub use iter::{IterOptions, PropertyOptions, XmpIterItem, XmpIterator};
+#[cfg(feature = "serde_json")]
+pub use json::JsonOptions;
 use node::{new_root_node, root_read_with, RootNode};
+pub use regions::{Dimensions, Region, RegionArea, RegionInfo, RegionType};
+#[cfg(feature = "serde")]
+pub use schema_serde::{Alt, Bag, Seq};
+pub use snapshot::XmpSnapshot;
+pub use subtree::RemoveOptions;
+pub use thumbnail::Thumbnail;
 
 /// Main structure for working with XMP metadata
 #[derive(Debug, Clone)]
@@ -25,6 +70,8 @@ pub struct XmpMeta {
     namespaces: NamespaceMap,
     /// About URI (typically empty string for main metadata)
     about_uri: Option<String>,
+    /// Generate identifiers for empty `xmpMM` ID values
+    auto_ids: bool,
 }
 
 impl XmpMeta {
@@ -34,9 +81,35 @@ impl XmpMeta {
             root: new_root_node(StructureNode::new()),
             namespaces: NamespaceMap::new(),
             about_uri: None,
+            auto_ids: false,
         }
     }
 
+    /// Generate identifiers for empty `xmpMM` ID values set with
+    /// [`set_property`](Self::set_property)
+    ///
+    /// This matches Adobe applications, which never write empty IDs. See
+    /// [`utils::guid`](crate::utils::guid) for the format of the identifiers.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{ns, XmpMeta};
+    ///
+    /// let mut meta = XmpMeta::new();
+    /// meta.set_auto_ids(true);
+    /// meta.set_property(ns::XMP_MM, "InstanceID", "".into()).unwrap();
+    /// assert!(meta.instance_id().unwrap().starts_with("xmp.iid:"));
+    /// ```
+    pub fn set_auto_ids(&mut self, enabled: bool) {
+        self.auto_ids = enabled;
+    }
+
+    /// Check if identifiers are generated for empty `xmpMM` ID values
+    pub fn auto_ids(&self) -> bool {
+        self.auto_ids
+    }
+
     /// Resolve namespace URI from namespace parameter (URI or prefix)
     ///
     /// Returns the URI if namespace is already a URI, or resolves the prefix to URI.
@@ -93,19 +166,195 @@ impl XmpMeta {
         }
     }
 
+    /// Create metadata from the properties of the first resource of a parse
+    fn from_parsed(root: StructureNode, parser: &XmpParser) -> Self {
+        Self {
+            root: new_root_node(root),
+            namespaces: namespaces::document_namespaces(parser.namespaces()),
+            about_uri: parser
+                .about()
+                .filter(|about| !about.is_empty())
+                .map(str::to_string),
+            auto_ids: false,
+        }
+    }
+
     /// Parse XMP metadata from a string
     ///
     /// The string should contain a complete XMP Packet (with or without
     /// the `<?xpacket>` wrapper).
     pub fn parse(s: &str) -> XmpResult<Self> {
+        Self::parse_with_options(s, &ParseOptions::default())
+    }
+
+    /// Parse XMP metadata from a string with options
+    ///
+    /// See [`ParseOptions`] for the available options. Use the limits of
+    /// [`ParseOptions`] when parsing untrusted input.
+    pub fn parse_with_options(s: &str, options: &ParseOptions) -> XmpResult<Self> {
         let mut parser = XmpParser::new();
-        let root_node = parser.parse_packet(s)?;
+        let mut root_node = parser.parse_packet_with(s, options)?;
+        alias::move_explicit_aliases(&mut root_node, options.strict_aliasing)?;
 
-        Ok(Self {
-            root: new_root_node(root_node),
-            namespaces: NamespaceMap::new(),
-            about_uri: None,
-        })
+        Ok(Self::from_parsed(root_node, &parser))
+    }
+
+    /// Parse the metadata of each resource described by a string
+    ///
+    /// With [`DescriptionMerge::Separate`](crate::DescriptionMerge::Separate),
+    /// `rdf:Description` elements with different `rdf:about` values give
+    /// different metadata objects, in document order. Otherwise, the only object is the one returned by
+    /// [`parse_with_options`](Self::parse_with_options).
+    pub fn parse_resources(s: &str, options: &ParseOptions) -> XmpResult<Vec<Self>> {
+        let mut parser = XmpParser::new();
+        let mut root_node = parser.parse_packet_with(s, options)?;
+        alias::move_explicit_aliases(&mut root_node, options.strict_aliasing)?;
+
+        let mut resources = vec![Self::from_parsed(root_node, &parser)];
+        for (about, mut root_node) in parser.take_resources() {
+            alias::move_explicit_aliases(&mut root_node, options.strict_aliasing)?;
+            let mut meta = Self::from_parsed(root_node, &parser);
+            meta.about_uri = Some(about);
+            resources.push(meta);
+        }
+        Ok(resources)
+    }
+
+    /// Parse XMP metadata into a read-only view that borrows from `s`
+    ///
+    /// The values of the view borrow their text from the packet instead of
+    /// copying it, which avoids an allocation per property when only reading
+    /// metadata. See [`XmpMetaRef`] for the differences with `XmpMeta`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{ns, XmpMeta};
+    ///
+    /// let xml = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+    ///   <rdf:Description xmlns:xmp="http://ns.adobe.com/xap/1.0/" xmp:Label="Red"/>
+    /// </rdf:RDF>"#;
+    /// let meta = XmpMeta::parse_borrowed(xml).unwrap();
+    /// let label = meta.get_property(ns::XMP, "Label").and_then(|v| v.as_str());
+    /// assert_eq!(label, Some("Red"));
+    /// ```
+    pub fn parse_borrowed(s: &str) -> XmpResult<XmpMetaRef<'_>> {
+        XmpMetaRef::parse(s, &ParseOptions::default())
+    }
+
+    /// Parse XMP metadata into a borrowed view with options
+    ///
+    /// See [`parse_borrowed`](Self::parse_borrowed) and [`ParseOptions`].
+    /// `strict_aliasing` has no effect, since the view does not resolve
+    /// aliases.
+    pub fn parse_borrowed_with_options<'a>(
+        s: &'a str,
+        options: &ParseOptions,
+    ) -> XmpResult<XmpMetaRef<'a>> {
+        XmpMetaRef::parse(s, options)
+    }
+
+    /// Parse XMP metadata from a reader
+    ///
+    /// The XML is parsed as it is read, which keeps the memory use low for
+    /// large packets: only the parsed metadata is kept, not the packet. The
+    /// packet must be UTF-8; see [`XmpParser::parse_reader`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::io::BufReader;
+    /// use xmpkit::{ns, XmpMeta, XmpValue};
+    ///
+    /// let xml = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+    ///   <rdf:Description xmlns:xmp="http://ns.adobe.com/xap/1.0/" xmp:Label="Red"/>
+    /// </rdf:RDF>"#;
+    /// let meta = XmpMeta::parse_from_reader(BufReader::new(xml.as_bytes())).unwrap();
+    /// assert_eq!(
+    ///     meta.get_property(ns::XMP, "Label"),
+    ///     Some(XmpValue::String("Red".to_string()))
+    /// );
+    /// ```
+    pub fn parse_from_reader<R: BufRead>(reader: R) -> XmpResult<Self> {
+        Self::parse_from_reader_with_options(reader, &ParseOptions::default())
+    }
+
+    /// Parse XMP metadata from a reader with options
+    ///
+    /// See [`parse_from_reader`](Self::parse_from_reader) and
+    /// [`ParseOptions`].
+    pub fn parse_from_reader_with_options<R: BufRead>(
+        reader: R,
+        options: &ParseOptions,
+    ) -> XmpResult<Self> {
+        let mut parser = XmpParser::new();
+        let mut root_node = parser.parse_reader(reader, options)?;
+        alias::move_explicit_aliases(&mut root_node, options.strict_aliasing)?;
+
+        Ok(Self::from_parsed(root_node, &parser))
+    }
+
+    /// Parse XMP metadata from bytes in any encoding
+    ///
+    /// UTF-8, UTF-16 and UTF-32 are recognized, with or without a byte
+    /// order mark (see [`PacketEncoding::detect`]). Trailing zero bytes are
+    /// ignored.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::utils::encoding::PacketEncoding;
+    /// use xmpkit::{ns, XmpMeta, XmpValue};
+    ///
+    /// let mut meta = XmpMeta::new();
+    /// meta.set_property(ns::XMP, "CreatorTool", "MyApp".into()).unwrap();
+    /// let packet = PacketEncoding::Utf16Le.encode(&meta.serialize_packet().unwrap());
+    ///
+    /// let parsed = XmpMeta::parse_bytes(&packet).unwrap();
+    /// assert_eq!(
+    ///     parsed.get_property(ns::XMP, "CreatorTool"),
+    ///     Some(XmpValue::String("MyApp".to_string()))
+    /// );
+    /// ```
+    pub fn parse_bytes(data: &[u8]) -> XmpResult<Self> {
+        Self::parse(&PacketEncoding::decode_detected(data)?)
+    }
+
+    /// Parse XMP metadata from a string, recovering from malformed XML
+    ///
+    /// Unlike [`parse`](Self::parse), problems such as unclosed tags, bad
+    /// entity references, control characters and wrong namespace URIs are
+    /// recovered from, and returned as warnings. See the
+    /// [parser documentation](crate::core::parser) for the details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{ns, XmpMeta, XmpValue};
+    ///
+    /// // Unclosed rdf:RDF, unescaped '&', control character and a missing
+    /// // '/' at the end of the namespace URI
+    /// let xml = "<rdf:RDF xmlns:rdf='http://www.w3.org/1999/02/22-rdf-syntax-ns#'>
+    ///     <rdf:Description xmlns:xmp='http://ns.adobe.com/xap/1.0'>
+    ///         <xmp:CreatorTool>Tom & Jerry\u{1}</xmp:CreatorTool>
+    ///     </rdf:Description>";
+    /// assert!(XmpMeta::parse(xml).is_err());
+    ///
+    /// let (meta, warnings) = XmpMeta::parse_lenient(xml).unwrap();
+    /// assert_eq!(
+    ///     meta.get_property(ns::XMP, "CreatorTool"),
+    ///     Some(XmpValue::String("Tom & Jerry".to_string()))
+    /// );
+    /// assert!(!warnings.is_empty());
+    /// ```
+    pub fn parse_lenient(s: &str) -> XmpResult<(Self, Vec<ParseWarning>)> {
+        let mut parser = XmpParser::new();
+        parser.set_lenient(true);
+        let mut root_node = parser.parse_packet(s)?;
+        alias::move_explicit_aliases(&mut root_node, false)?;
+
+        let meta = Self::from_parsed(root_node, &parser);
+        Ok((meta, parser.take_warnings()))
     }
 
     /// Check if a property exists
@@ -115,13 +364,37 @@ impl XmpMeta {
     /// * `namespace` - The namespace URI or prefix
     /// * `path` - The property path
     pub fn has_property(&self, namespace: &str, path: &str) -> bool {
-        let ns_uri = match self.resolve_namespace_uri(namespace) {
-            Some(uri) => uri,
-            None => return false,
+        self.does_property_exist(namespace, path)
+    }
+
+    /// Check if the node addressed by an XMP path exists
+    ///
+    /// `path` may be a top-level property name or any XMP path, such as
+    /// "creator\[2\]", "Flash/exif:Fired" or "title\[1\]/?xml:lang".
+    /// Invalid paths and unknown namespaces are reported as not existing.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace URI or prefix
+    /// * `path` - The XMP path
+    pub fn does_property_exist(&self, namespace: &str, path: &str) -> bool {
+        let Some(ns_uri) = self.resolve_namespace_uri(namespace) else {
+            return false;
+        };
+        let Ok(steps) = self.expand_path(&ns_uri, path) else {
+            return false;
         };
 
-        let full_path = format!("{}:{}", ns_uri, path);
-        root_read_with(&self.root, |root| root.has_field(&full_path))
+        root_read_with(&self.root, |root| match steps.split_last() {
+            Some((PathStep::Qualifier { namespace, name }, node_steps)) => {
+                find_node(root, node_steps).is_some_and(|node| {
+                    node.qualifiers()
+                        .iter()
+                        .any(|q| q.namespace == *namespace && q.name == *name)
+                })
+            }
+            _ => find_node(root, &steps).is_some(),
+        })
     }
 
     /// Get a property value
@@ -133,6 +406,10 @@ impl XmpMeta {
     pub fn get_property(&self, namespace: &str, path: &str) -> Option<XmpValue> {
         // First, try direct lookup with the provided namespace
         let ns_uri = self.resolve_namespace_uri(namespace)?;
+        if let Some(alias) = alias::lookup(&ns_uri, path).filter(|a| a.form != ValueForm::Simple) {
+            return self.get_property_by_path(&alias.namespace, &alias.path());
+        }
+        let (ns_uri, path) = resolve_alias_name(ns_uri, path, false);
         let full_path = format!("{}:{}", ns_uri, path);
 
         let root = root_read_opt!(self.root);
@@ -140,7 +417,7 @@ impl XmpMeta {
 
         // Handle simple node
         if let Some(simple_node) = node.as_simple() {
-            return Some(XmpValue::String(simple_node.value.clone()));
+            return Some(simple_value(simple_node));
         }
 
         // Handle structure node: return empty string
@@ -155,6 +432,10 @@ impl XmpMeta {
 
     /// Set a property value
     ///
+    /// With [`set_auto_ids`](Self::set_auto_ids), an empty
+    /// `xmpMM:DocumentID`, `xmpMM:OriginalDocumentID` or `xmpMM:InstanceID`
+    /// is replaced by a new identifier.
+    ///
     /// # Arguments
     ///
     /// * `namespace` - The namespace URI or prefix
@@ -162,38 +443,145 @@ impl XmpMeta {
     /// * `value` - The value to set
     pub fn set_property(&mut self, namespace: &str, path: &str, value: XmpValue) -> XmpResult<()> {
         let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+        if let Some(alias) = alias::lookup(&ns_uri, path).filter(|a| a.form != ValueForm::Simple) {
+            return self.set_property_by_path(&alias.namespace, &alias.path(), value);
+        }
+        let (ns_uri, path) = resolve_alias_name(ns_uri, path, false);
 
         let full_path = format!("{}:{}", ns_uri, path);
         let node = match value {
+            XmpValue::String(s) if s.is_empty() && self.auto_ids => {
+                match mm::id_kind(&ns_uri, &path) {
+                    Some(kind) => Node::simple(guid::new_xmp_id(kind)),
+                    None => Node::simple(s),
+                }
+            }
             XmpValue::String(s) => Node::simple(s),
             XmpValue::Integer(i) => Node::simple(i.to_string()),
-            XmpValue::Boolean(b) => Node::simple(if b { "True" } else { "False" }),
-            XmpValue::DateTime(dt) => Node::simple(dt),
+            XmpValue::Boolean(b) => Node::simple(convert::convert_from_bool(b)),
+            XmpValue::DateTime(dt) => Node::simple(dt.format()),
+            XmpValue::Uri(uri) => Node::uri(uri),
             _ => {
                 return Err(XmpError::NotSupported(
                     "Complex types not yet supported".to_string(),
                 ))
             }
         };
+        validate::check_custom_property(
+            &ns_uri,
+            &path,
+            Some(ValueForm::Simple),
+            node.as_simple().map(|node| node.value.as_str()),
+        )?;
 
         root_write!(self.root).set_field(full_path, node);
         Ok(())
     }
 
+    /// Set a top-level property to a node tree, replacing the existing one
+    ///
+    /// Used by the schema accessors to write structures and arrays of
+    /// structures, which the setters can't create.
+    pub(crate) fn set_property_node(
+        &mut self,
+        namespace: &str,
+        name: &str,
+        node: Node,
+    ) -> XmpResult<()> {
+        let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+        root_write!(self.root).set_field(format!("{}:{}", ns_uri, name), node);
+        Ok(())
+    }
+
     /// Delete a property
     ///
+    /// `path` may be a top-level property name or any XMP path, so array
+    /// items, struct fields and qualifiers can be deleted as well. Deleting
+    /// a node that does not exist is not an error.
+    ///
     /// # Arguments
     ///
     /// * `namespace` - The namespace URI or prefix
-    /// * `path` - The property path
+    /// * `path` - The XMP path (e.g., "CreatorTool" or "subject\[2\]")
     pub fn delete_property(&mut self, namespace: &str, path: &str) -> XmpResult<()> {
         let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+        let steps = self.expand_path(&ns_uri, path)?;
 
-        let full_path = format!("{}:{}", ns_uri, path);
-        root_write!(self.root).remove_field(&full_path);
+        let mut root = root_write!(self.root);
+        let Some((last, parent_steps)) = steps.split_last() else {
+            return Ok(());
+        };
+
+        if let PathStep::Qualifier { namespace, name } = last {
+            if let Some(node) = find_node_mut(&mut root, parent_steps) {
+                node.qualifiers_mut()
+                    .retain(|q| !(q.namespace == *namespace && q.name == *name));
+            }
+            return Ok(());
+        }
+
+        if parent_steps.is_empty() {
+            if let PathStep::Field(key) = last {
+                root.remove_field(key);
+            }
+            return Ok(());
+        }
+
+        match (find_node_mut(&mut root, parent_steps), last) {
+            (Some(Node::Structure(parent)), PathStep::Field(key)) => {
+                parent.remove_field(key);
+            }
+            (Some(Node::Array(parent)), step) => {
+                if let Some(index) = select_array_item(parent, step) {
+                    parent.remove(index)?;
+                }
+            }
+            _ => {}
+        }
         Ok(())
     }
 
+    /// Delete all top-level properties of a namespace whose names match a pattern
+    ///
+    /// The pattern is matched against the local property name (without
+    /// prefix). `*` matches any sequence of characters and `?` matches a
+    /// single character, so `"*"` removes every property of the namespace.
+    ///
+    /// Returns the number of properties deleted.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace URI or prefix
+    /// * `pattern` - The name pattern (e.g., "*" or "Color*")
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{ns, XmpMeta, XmpValue};
+    ///
+    /// let mut meta = XmpMeta::new();
+    /// meta.set_property(ns::PHOTOSHOP, "City", XmpValue::String("Paris".into())).unwrap();
+    /// meta.set_property(ns::PHOTOSHOP, "Country", XmpValue::String("France".into())).unwrap();
+    ///
+    /// assert_eq!(meta.delete_properties_matching(ns::PHOTOSHOP, "*").unwrap(), 2);
+    /// assert!(!meta.has_property(ns::PHOTOSHOP, "City"));
+    /// ```
+    pub fn delete_properties_matching(
+        &mut self,
+        namespace: &str,
+        pattern: &str,
+    ) -> XmpResult<usize> {
+        let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+
+        let mut root = root_write!(self.root);
+        let before = root.fields.len();
+        root.fields.retain(|key, _| match key.rsplit_once(':') {
+            Some((key_ns, name)) => key_ns != ns_uri || !glob_match(pattern, name),
+            None => true,
+        });
+        Ok(before - root.fields.len())
+    }
+
     /// Get the about URI
     pub fn about_uri(&self) -> Option<&str> {
         self.about_uri.as_deref()
@@ -204,20 +592,69 @@ impl XmpMeta {
         self.about_uri = Some(uri.into());
     }
 
+    /// Get the name of the metadata: the `rdf:about` value of its
+    /// `rdf:Description` elements
+    ///
+    /// Empty if the metadata has no about URI. Same as
+    /// [`about_uri`](Self::about_uri), following the Adobe XMP Toolkit.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::XmpMeta;
+    ///
+    /// let xml = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+    ///   <rdf:Description rdf:about="uuid:1234"/>
+    /// </rdf:RDF>"#;
+    /// let mut meta = XmpMeta::parse(xml).unwrap();
+    /// assert_eq!(meta.name(), "uuid:1234");
+    ///
+    /// meta.set_name("uuid:5678");
+    /// assert!(meta.serialize().unwrap().contains(r#"rdf:about="uuid:5678""#));
+    /// ```
+    pub fn name(&self) -> &str {
+        self.about_uri.as_deref().unwrap_or("")
+    }
+
+    /// Set the name of the metadata, written as the `rdf:about` value
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.set_about_uri(name);
+    }
+
     /// Serialize to RDF/XML string
     pub fn serialize(&self) -> XmpResult<String> {
-        let serializer = XmpSerializer::new();
+        let serializer = self.serializer(false);
         let root = root_read!(self.root);
         serializer.serialize_rdf(&root)
     }
 
     /// Serialize to XMP Packet format
     pub fn serialize_packet(&self) -> XmpResult<String> {
-        let serializer = XmpSerializer::new();
+        let serializer = self.serializer(false);
         let root = root_read!(self.root);
         serializer.serialize_packet(&root)
     }
 
+    /// Serialize to XMP Packet format with options
+    ///
+    /// See [`SerializeOptions`] for the available options. Use
+    /// [`SerializeOptions::exact_packet_length`] to produce a packet that can
+    /// replace an existing one in place.
+    pub fn serialize_with_options(&self, options: &SerializeOptions) -> XmpResult<String> {
+        let serializer = self.serializer(options.canonical_prefixes);
+        let root = root_read!(self.root);
+        serializer.serialize_packet_with(&root, options)
+    }
+
+    /// Serialize to an XMP Packet in the encoding of the options
+    ///
+    /// See [`SerializeOptions::encoding`]. With the default options, this
+    /// is the UTF-8 packet of [`serialize_with_options`](Self::serialize_with_options).
+    pub fn serialize_to_bytes(&self, options: &SerializeOptions) -> XmpResult<Vec<u8>> {
+        let packet = self.serialize_with_options(options)?;
+        Ok(options.encoding.encode(&packet))
+    }
+
     /// Get an array item by index
     ///
     /// # Arguments
@@ -227,6 +664,7 @@ impl XmpMeta {
     /// * `index` - The array index (0-based)
     pub fn get_array_item(&self, namespace: &str, path: &str, index: usize) -> Option<XmpValue> {
         let ns_uri = self.resolve_namespace_uri(namespace)?;
+        let (ns_uri, path) = resolve_alias_name(ns_uri, path, false);
 
         let full_path = format!("{}:{}", ns_uri, path);
         let root = root_read_opt!(self.root);
@@ -234,7 +672,7 @@ impl XmpMeta {
             .and_then(|node| node.as_array())
             .and_then(|array| array.get(index))
             .and_then(|item| item.as_simple())
-            .map(|n| XmpValue::String(n.value.clone()))
+            .map(simple_value)
     }
 
     /// Get the size of an array property
@@ -245,6 +683,7 @@ impl XmpMeta {
     /// * `path` - The array property path
     pub fn get_array_size(&self, namespace: &str, path: &str) -> Option<usize> {
         let ns_uri = self.resolve_namespace_uri(namespace)?;
+        let (ns_uri, path) = resolve_alias_name(ns_uri, path, false);
 
         let full_path = format!("{}:{}", ns_uri, path);
         let root = root_read_opt!(self.root);
@@ -255,6 +694,10 @@ impl XmpMeta {
 
     /// Append an item to an array property
     ///
+    /// If the array doesn't exist, a new ordered array (`rdf:Seq`) is created.
+    /// Use [`append_array_item_with`](Self::append_array_item_with) to choose
+    /// the array form.
+    ///
     /// # Arguments
     ///
     /// * `namespace` - The namespace URI or prefix
@@ -265,32 +708,174 @@ impl XmpMeta {
         namespace: &str,
         path: &str,
         value: XmpValue,
+    ) -> XmpResult<()> {
+        self.append_array_node(namespace, path, None, value_to_node(value)?)
+    }
+
+    /// Append an item to an array property, creating the array with the given form
+    ///
+    /// If the array doesn't exist, it is created as `array_type`
+    /// (`rdf:Seq`, `rdf:Bag` or `rdf:Alt`). If it already exists with a
+    /// different form, an error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace URI or prefix
+    /// * `path` - The array property path
+    /// * `array_type` - The array form to use when creating the array
+    /// * `value` - The value to append
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{core::ArrayType, ns, XmpMeta, XmpValue};
+    ///
+    /// let mut meta = XmpMeta::new();
+    /// meta.append_array_item_with(ns::DC, "subject", ArrayType::Unordered, "sunset".into())
+    ///     .unwrap();
+    /// meta.append_array_item_with(ns::DC, "subject", ArrayType::Unordered, "beach".into())
+    ///     .unwrap();
+    /// assert_eq!(meta.count_array_items(ns::DC, "subject"), 2);
+    /// ```
+    pub fn append_array_item_with(
+        &mut self,
+        namespace: &str,
+        path: &str,
+        array_type: ArrayType,
+        value: XmpValue,
+    ) -> XmpResult<()> {
+        self.append_array_node(namespace, path, Some(array_type), value_to_node(value)?)
+    }
+
+    /// Append a node to an array property, creating the array with the
+    /// given form (or the form of its custom schema, or `rdf:Seq`)
+    ///
+    /// Used by the schema accessors to append structures.
+    pub(crate) fn append_array_node(
+        &mut self,
+        namespace: &str,
+        path: &str,
+        array_type: Option<ArrayType>,
+        item_node: Node,
     ) -> XmpResult<()> {
         let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+        let (ns_uri, path) = resolve_alias_name(ns_uri, path, false);
 
         let full_path = format!("{}:{}", ns_uri, path);
+        let array_type = array_type.or_else(|| validate::custom_array_type(&ns_uri, &path));
+        validate::check_custom_property(
+            &ns_uri,
+            &path,
+            Some(array_form(array_type.unwrap_or(ArrayType::Ordered))),
+            item_node.as_simple().map(|node| node.value.as_str()),
+        )?;
         let mut root = root_write!(self.root);
 
-        // Get or create array node
-        let array_node = root
+        match root.get_field_mut(&full_path) {
+            Some(Node::Array(array)) => {
+                if let Some(array_type) = array_type {
+                    if array.array_type != array_type {
+                        return Err(XmpError::BadValue(format!(
+                            "Mismatch of existing and specified array form for '{}:{}': existing rdf:{}, specified rdf:{}",
+                            ns_uri,
+                            path,
+                            array.array_type.rdf_type(),
+                            array_type.rdf_type()
+                        )));
+                    }
+                }
+                array.append(item_node);
+            }
+            Some(_) => {
+                return Err(XmpError::BadValue(format!(
+                    "Property '{}:{}' exists but is not an array. Use get_property() or get_struct_field() instead.",
+                    ns_uri, path
+                )));
+            }
+            None => {
+                // Create new array (default to Ordered)
+                let mut array = ArrayNode::new(array_type.unwrap_or(ArrayType::Ordered));
+                array.append(item_node);
+                root.set_field(full_path, Node::Array(array));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replace an item in an array property
+    ///
+    /// Qualifiers on the existing item (such as `xml:lang`) are kept. Setting
+    /// the item at `index == count` appends a new item.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace URI or prefix
+    /// * `path` - The array property path
+    /// * `index` - The index of the item to replace (0-based)
+    /// * `value` - The new value
+    pub fn set_array_item(
+        &mut self,
+        namespace: &str,
+        path: &str,
+        index: usize,
+        value: XmpValue,
+    ) -> XmpResult<()> {
+        let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+        let (ns_uri, path) = resolve_alias_name(ns_uri, path, false);
+
+        let full_path = format!("{}:{}", ns_uri, path);
+        let item_node = value_to_node(value)?;
+        validate::check_custom_property(
+            &ns_uri,
+            &path,
+            None,
+            item_node.as_simple().map(|node| node.value.as_str()),
+        )?;
+        let mut root = root_write!(self.root);
+
+        let array = root
             .get_field_mut(&full_path)
-            .and_then(|node| node.as_array_mut());
+            .and_then(|node| node.as_array_mut())
+            .ok_or_else(|| {
+                XmpError::BadValue(format!(
+                    "Property '{}:{}' does not exist or is not an array",
+                    ns_uri, path
+                ))
+            })?;
 
-        if let Some(array) = array_node {
-            let item_node = value_to_node(value)?;
-            array.append(item_node);
-        } else {
-            // Create new array (default to Ordered)
-            let mut array =
-                crate::core::node::ArrayNode::new(crate::core::node::ArrayType::Ordered);
-            let item_node = value_to_node(value)?;
+        if index == array.len() {
             array.append(item_node);
-            root.set_field(full_path, Node::Array(array));
+            return Ok(());
         }
 
+        let len = array.len();
+        let item = array.get_mut(index).ok_or_else(|| {
+            XmpError::BadParam(format!(
+                "Index {} out of bounds for array of length {}",
+                index, len
+            ))
+        })?;
+
+        match (item, item_node) {
+            (Node::Simple(existing), Node::Simple(new)) => existing.value = new.value,
+            (item, new) => *item = new,
+        }
         Ok(())
     }
 
+    /// Count the items in an array property
+    ///
+    /// Returns 0 if the property doesn't exist or is not an array.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace URI or prefix
+    /// * `path` - The array property path
+    pub fn count_array_items(&self, namespace: &str, path: &str) -> usize {
+        self.get_array_size(namespace, path).unwrap_or(0)
+    }
+
     /// Insert an item into an array property at a specific index
     ///
     /// # Arguments
@@ -307,6 +892,7 @@ impl XmpMeta {
         value: XmpValue,
     ) -> XmpResult<()> {
         let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+        let (ns_uri, path) = resolve_alias_name(ns_uri, path, false);
 
         let full_path = format!("{}:{}", ns_uri, path);
         let mut root = root_write!(self.root);
@@ -322,6 +908,12 @@ impl XmpMeta {
             })?;
 
         let item_node = value_to_node(value)?;
+        validate::check_custom_property(
+            &ns_uri,
+            &path,
+            None,
+            item_node.as_simple().map(|node| node.value.as_str()),
+        )?;
         array.insert(index, item_node)
     }
 
@@ -339,6 +931,7 @@ impl XmpMeta {
         index: usize,
     ) -> XmpResult<()> {
         let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+        let (ns_uri, path) = resolve_alias_name(ns_uri, path, false);
 
         let full_path = format!("{}:{}", ns_uri, path);
         let mut root = root_write!(self.root);
@@ -356,66 +949,175 @@ impl XmpMeta {
         array.remove(index).map(|_| ())
     }
 
+    /// Resolve a structure path into the chain of node keys leading to it
+    ///
+    /// The first component is a property name in `ns_uri` (or a
+    /// `prefix:name`); nested components are separated by `/` and use the
+    /// `prefix:name` form, e.g. `"Outer/ns:Inner"`. Unprefixed nested
+    /// components are taken to be in `ns_uri`.
+    fn struct_path_keys(&self, ns_uri: &str, struct_path: &str) -> XmpResult<Vec<String>> {
+        if struct_path.is_empty() {
+            return Err(XmpError::BadXPath("Empty structure path".to_string()));
+        }
+
+        let mut keys = struct_path
+            .split('/')
+            .map(|component| match component.split_once(':') {
+                _ if component.is_empty() => Err(XmpError::BadXPath(format!(
+                    "Empty component in structure path '{}'",
+                    struct_path
+                ))),
+                Some((prefix, name)) => {
+                    let uri = self.resolve_namespace_uri_or_error(prefix)?;
+                    Ok(format!("{}:{}", uri, name))
+                }
+                None => Ok(format!("{}:{}", ns_uri, component)),
+            })
+            .collect::<XmpResult<Vec<_>>>()?;
+
+        // The top-level property may be an alias
+        if let Some(alias) = keys[0]
+            .rsplit_once(':')
+            .and_then(|(ns_uri, name)| alias::lookup(ns_uri, name))
+            .filter(|alias| alias.form == ValueForm::Simple)
+        {
+            keys[0] = alias.key();
+        }
+        Ok(keys)
+    }
+
+    /// Expand an XMP path relative to a namespace into path steps
+    ///
+    /// An alias as the top-level property is replaced by the steps of the
+    /// actual node it stands for.
+    fn expand_path(&self, ns_uri: &str, path: &str) -> XmpResult<Vec<PathStep>> {
+        let mut steps = path::expand_path(ns_uri, path, |prefix| {
+            self.resolve_namespace_uri_or_error(prefix)
+        })?;
+        if let Some(PathStep::Field(key)) = steps.first() {
+            if let Some(alias) = key
+                .rsplit_once(':')
+                .and_then(|(ns_uri, name)| alias::lookup(ns_uri, name))
+            {
+                steps.splice(0..1, alias.steps());
+            }
+        }
+        Ok(steps)
+    }
+
     /// Get a structure field value
     ///
     /// # Arguments
     ///
-    /// * `namespace` - The namespace URI or prefix
-    /// * `struct_path` - The structure property path
+    /// * `namespace` - The namespace URI or prefix of the structure
+    /// * `struct_path` - The structure property path; nested structures are
+    ///   separated by `/` (e.g., "CreatorContactInfo" or "Outer/ns:Inner")
+    /// * `field_namespace` - The namespace URI or prefix of the field
     /// * `field_name` - The field name within the structure
-    pub fn get_struct_field(
-        &self,
-        namespace: &str,
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{ns, XmpMeta, XmpValue};
+    ///
+    /// let mut meta = XmpMeta::new();
+    /// meta.set_struct_field(ns::EXIF, "Flash", ns::EXIF, "Fired", XmpValue::Boolean(true))
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     meta.get_struct_field(ns::EXIF, "Flash", ns::EXIF, "Fired"),
+    ///     Some(XmpValue::String("True".to_string()))
+    /// );
+    /// ```
+    pub fn get_struct_field(
+        &self,
+        namespace: &str,
         struct_path: &str,
+        field_namespace: &str,
         field_name: &str,
     ) -> Option<XmpValue> {
         let ns_uri = self.resolve_namespace_uri(namespace)?;
+        let field_ns_uri = self.resolve_namespace_uri(field_namespace)?;
+        let keys = self.struct_path_keys(&ns_uri, struct_path).ok()?;
+        let field_key = format!("{}:{}", field_ns_uri, field_name);
 
-        let struct_full_path = format!("{}:{}", ns_uri, struct_path);
         let root = root_read_opt!(self.root);
-        root.get_field(&struct_full_path)
-            .and_then(|node| node.as_structure())
-            .and_then(|structure| structure.get_field(field_name))
+        let mut structure: &StructureNode = &root;
+        for key in &keys {
+            structure = structure.get_field(key)?.as_structure()?;
+        }
+        structure
+            .get_field(&field_key)
             .and_then(|field_node| field_node.as_simple())
-            .map(|n| XmpValue::String(n.value.clone()))
+            .map(simple_value)
     }
 
     /// Set a structure field value
     ///
+    /// The structure, and any intermediate structures named in
+    /// `struct_path`, are created if they don't exist.
+    ///
     /// # Arguments
     ///
-    /// * `namespace` - The namespace URI or prefix
-    /// * `struct_path` - The structure property path
+    /// * `namespace` - The namespace URI or prefix of the structure
+    /// * `struct_path` - The structure property path; nested structures are
+    ///   separated by `/` (e.g., "CreatorContactInfo" or "Outer/ns:Inner")
+    /// * `field_namespace` - The namespace URI or prefix of the field
     /// * `field_name` - The field name within the structure
     /// * `value` - The value to set
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{ns, XmpMeta, XmpValue};
+    ///
+    /// let mut meta = XmpMeta::new();
+    /// meta.set_struct_field(
+    ///     ns::IPTC_CORE,
+    ///     "CreatorContactInfo",
+    ///     ns::IPTC_CORE,
+    ///     "CiEmailWork",
+    ///     XmpValue::String("jane@example.com".to_string()),
+    /// )
+    /// .unwrap();
+    /// ```
     pub fn set_struct_field(
         &mut self,
         namespace: &str,
         struct_path: &str,
+        field_namespace: &str,
         field_name: &str,
         value: XmpValue,
     ) -> XmpResult<()> {
         let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+        let field_ns_uri = self.resolve_namespace_uri_or_error(field_namespace)?;
+        let keys = self.struct_path_keys(&ns_uri, struct_path)?;
+        let field_node = value_to_node(value)?;
+        validate::check_custom_property(&ns_uri, struct_path, Some(ValueForm::Struct), None)?;
 
-        let struct_full_path = format!("{}:{}", ns_uri, struct_path);
         let mut root = root_write!(self.root);
-
-        // Get or create structure node
-        let structure_node = root
-            .get_field_mut(&struct_full_path)
-            .and_then(|node| node.as_structure_mut());
-
-        if let Some(structure) = structure_node {
-            let field_node = value_to_node(value)?;
-            structure.set_field(field_name.to_string(), field_node);
-        } else {
-            // Create new structure
-            let mut structure = crate::core::node::StructureNode::new();
-            let field_node = value_to_node(value)?;
-            structure.set_field(field_name.to_string(), field_node);
-            root.set_field(struct_full_path, Node::Structure(structure));
+        let mut structure: &mut StructureNode = &mut root;
+        for key in keys {
+            // Get or create each structure along the path
+            structure = match structure.fields.entry(key) {
+                std::collections::hash_map::Entry::Occupied(entry) => entry
+                    .into_mut()
+                    .as_structure_mut()
+                    .ok_or_else(|| {
+                        XmpError::BadValue(format!(
+                            "Property '{}:{}' exists but is not a structure. Use get_property() or get_array_item() instead.",
+                            ns_uri, struct_path
+                        ))
+                    })?,
+                std::collections::hash_map::Entry::Vacant(entry) => entry
+                    .insert(Node::structure())
+                    .as_structure_mut()
+                    .ok_or_else(|| {
+                        XmpError::InternalError("Failed to create structure".to_string())
+                    })?,
+            };
         }
 
+        structure.set_field(format!("{}:{}", field_ns_uri, field_name), field_node);
         Ok(())
     }
 
@@ -423,31 +1125,334 @@ impl XmpMeta {
     ///
     /// # Arguments
     ///
-    /// * `namespace` - The namespace URI or prefix
+    /// * `namespace` - The namespace URI or prefix of the structure
     /// * `struct_path` - The structure property path
+    /// * `field_namespace` - The namespace URI or prefix of the field
     /// * `field_name` - The field name to delete
     pub fn delete_struct_field(
         &mut self,
         namespace: &str,
         struct_path: &str,
+        field_namespace: &str,
         field_name: &str,
     ) -> XmpResult<()> {
         let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+        let field_ns_uri = self.resolve_namespace_uri_or_error(field_namespace)?;
+        let keys = self.struct_path_keys(&ns_uri, struct_path)?;
 
-        let struct_full_path = format!("{}:{}", ns_uri, struct_path);
         let mut root = root_write!(self.root);
+        let mut structure: Option<&mut StructureNode> = Some(&mut root);
+        for key in &keys {
+            structure = structure
+                .and_then(|s| s.get_field_mut(key))
+                .and_then(|node| node.as_structure_mut());
+        }
 
-        let structure = root
-            .get_field_mut(&struct_full_path)
-            .and_then(|node| node.as_structure_mut())
-            .ok_or_else(|| {
-                XmpError::BadValue(format!(
-                    "Property '{}:{}' exists but is not a structure. Use get_property() or get_array_item() instead.",
-                    ns_uri, struct_path
-                ))
-            })?;
+        let structure = structure.ok_or_else(|| {
+            XmpError::BadValue(format!(
+                "Property '{}:{}' exists but is not a structure. Use get_property() or get_array_item() instead.",
+                ns_uri, struct_path
+            ))
+        })?;
+
+        structure.remove_field(&format!("{}:{}", field_ns_uri, field_name));
+        Ok(())
+    }
+
+    /// Get a property value by XMP path
+    ///
+    /// Unlike [`get_property`](Self::get_property), `path` may address any
+    /// node below a top-level property, using the XMP path syntax (see
+    /// [`core::path`](crate::core::path)). Array indices in the path are
+    /// 1-based.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace URI or prefix of the top-level property
+    /// * `path` - The XMP path (e.g., "History\[3\]/stEvt:when")
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{ns, XmpMeta, XmpValue};
+    ///
+    /// let mut meta = XmpMeta::new();
+    /// meta.set_property_by_path(ns::XMP_MM, "History[1]/stEvt:action", "created".into())
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     meta.get_property_by_path(ns::XMP_MM, "History[1]/stEvt:action"),
+    ///     Some(XmpValue::String("created".to_string()))
+    /// );
+    /// ```
+    pub fn get_property_by_path(&self, namespace: &str, path: &str) -> Option<XmpValue> {
+        let ns_uri = self.resolve_namespace_uri(namespace)?;
+        let steps = self.expand_path(&ns_uri, path).ok()?;
+
+        let root = root_read_opt!(self.root);
+        if let Some((PathStep::Qualifier { namespace, name }, node_steps)) = steps.split_last() {
+            return find_node(&root, node_steps)?
+                .qualifiers()
+                .iter()
+                .find(|q| q.namespace == *namespace && q.name == *name)
+                .map(|q| XmpValue::String(q.value.clone()));
+        }
+
+        match find_node(&root, &steps)? {
+            Node::Simple(simple) => Some(simple_value(simple)),
+            // Structures have no value of their own (see get_property())
+            Node::Structure(_) => Some(XmpValue::String(String::new())),
+            Node::Array(_) => None,
+        }
+    }
+
+    /// Get a property by XMP path with its items and fields
+    ///
+    /// Unlike [`get_property_by_path`](Self::get_property_by_path), arrays
+    /// are returned as `XmpValue::Array` and structures as
+    /// `XmpValue::Structure`, with fields keyed by "prefix:name".
+    /// Qualifiers are left out.
+    #[cfg(feature = "wasm")]
+    pub(crate) fn get_property_tree(&self, namespace: &str, path: &str) -> Option<XmpValue> {
+        let ns_uri = self.resolve_namespace_uri(namespace)?;
+        let steps = self.expand_path(&ns_uri, path).ok()?;
+
+        let root = root_read_opt!(self.root);
+        find_node(&root, &steps).map(|node| self.node_value(node))
+    }
+
+    #[cfg(feature = "wasm")]
+    fn node_value(&self, node: &Node) -> XmpValue {
+        match node {
+            Node::Simple(simple) => simple_value(simple),
+            Node::Array(array) => XmpValue::Array(
+                array
+                    .items
+                    .iter()
+                    .map(|item| self.node_value(item))
+                    .collect(),
+            ),
+            Node::Structure(structure) => XmpValue::Structure(
+                structure
+                    .field_names()
+                    .filter_map(|key| {
+                        let field = structure.get_field(key)?;
+                        Some((self.prefixed_key(key), self.node_value(field)))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Set a property value by XMP path
+    ///
+    /// Missing nodes along the path are created: structures for field steps,
+    /// ordered arrays for index steps and `rdf:Alt` arrays for language
+    /// selectors. An array index may be one past the last item to append a
+    /// new item. Existing qualifiers of the target node are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace URI or prefix of the top-level property
+    /// * `path` - The XMP path (e.g., "History\[3\]/stEvt:when")
+    /// * `value` - The value to set; must be a simple value
+    pub fn set_property_by_path(
+        &mut self,
+        namespace: &str,
+        path: &str,
+        value: XmpValue,
+    ) -> XmpResult<()> {
+        let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+        let steps = self.expand_path(&ns_uri, path)?;
+        let Node::Simple(value) = value_to_node(value)? else {
+            return Err(XmpError::NotSupported(
+                "Complex types not yet supported".to_string(),
+            ));
+        };
+
+        let (qualifier, node_steps) = match steps.split_last() {
+            Some((PathStep::Qualifier { namespace, name }, rest)) => {
+                (Some((namespace, name)), rest)
+            }
+            _ => (None, &steps[..]),
+        };
+        let Some((PathStep::Field(top_key), child_steps)) = node_steps.split_first() else {
+            return Err(XmpError::BadXPath(format!("Invalid path '{}'", path)));
+        };
+
+        let mut root = root_write!(self.root);
+
+        // Work on a copy of the top-level property so a failure leaves it untouched
+        let mut top = root
+            .get_field(top_key)
+            .cloned()
+            .unwrap_or_else(|| implicit_top_node(top_key, child_steps.first()));
+        let node = find_or_create_node(&mut top, child_steps, path)?;
+
+        match qualifier {
+            Some((qual_ns, qual_name)) => {
+                let qualifiers = node.qualifiers_mut();
+                match qualifiers
+                    .iter_mut()
+                    .find(|q| q.namespace == *qual_ns && q.name == *qual_name)
+                {
+                    Some(existing) => existing.value = value.value,
+                    None => qualifiers.push(Qualifier::new(qual_ns, qual_name, value.value)),
+                }
+            }
+            None => match node {
+                Node::Simple(simple) => simple.value = value.value,
+                _ => {
+                    return Err(XmpError::BadXPath(format!(
+                        "Path '{}' addresses an array or structure, which can't have a value",
+                        path
+                    )))
+                }
+            },
+        }
+
+        root.set_field(top_key.clone(), top);
+        Ok(())
+    }
+
+    /// Get a qualifier value
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace URI or prefix of the property
+    /// * `path` - The property path (e.g., "title" or "creator\[2\]")
+    /// * `qual_namespace` - The namespace URI or prefix of the qualifier
+    /// * `qual_name` - The qualifier name
+    ///
+    /// Array indices in `path` are 1-based, following the XMP path syntax.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{ns, XmpMeta, XmpValue};
+    ///
+    /// let mut meta = XmpMeta::new();
+    /// meta.append_array_item(ns::DC, "contributor", "Jane".into()).unwrap();
+    /// meta.set_qualifier(ns::DC, "contributor[1]", ns::XMP, "role", "editor".into())
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     meta.get_qualifier(ns::DC, "contributor[1]", ns::XMP, "role"),
+    ///     Some(XmpValue::String("editor".to_string()))
+    /// );
+    /// ```
+    pub fn get_qualifier(
+        &self,
+        namespace: &str,
+        path: &str,
+        qual_namespace: &str,
+        qual_name: &str,
+    ) -> Option<XmpValue> {
+        let ns_uri = self.resolve_namespace_uri(namespace)?;
+        let qual_ns_uri = self.resolve_namespace_uri(qual_namespace)?;
+        let steps = self.expand_path(&ns_uri, path).ok()?;
+
+        let root = root_read_opt!(self.root);
+        find_node(&root, &steps)?
+            .qualifiers()
+            .iter()
+            .find(|q| q.namespace == qual_ns_uri && q.name == qual_name)
+            .map(|q| XmpValue::String(q.value.clone()))
+    }
+
+    /// Get all qualifiers of a property
+    ///
+    /// Returns an empty list if the property doesn't exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace URI or prefix of the property
+    /// * `path` - The property path (e.g., "title" or "creator\[2\]")
+    pub fn get_qualifiers(&self, namespace: &str, path: &str) -> Vec<Qualifier> {
+        let Some(ns_uri) = self.resolve_namespace_uri(namespace) else {
+            return Vec::new();
+        };
+        let Ok(steps) = self.expand_path(&ns_uri, path) else {
+            return Vec::new();
+        };
+
+        root_read_with(&self.root, |root| {
+            find_node(root, &steps)
+                .map(|node| node.qualifiers().to_vec())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Set a qualifier value
+    ///
+    /// The property must already exist. An existing qualifier with the same
+    /// name is replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace URI or prefix of the property
+    /// * `path` - The property path (e.g., "title" or "creator\[2\]")
+    /// * `qual_namespace` - The namespace URI or prefix of the qualifier
+    /// * `qual_name` - The qualifier name
+    /// * `value` - The qualifier value; must be a simple value
+    pub fn set_qualifier(
+        &mut self,
+        namespace: &str,
+        path: &str,
+        qual_namespace: &str,
+        qual_name: &str,
+        value: XmpValue,
+    ) -> XmpResult<()> {
+        let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+        let qual_ns_uri = self.resolve_namespace_uri_or_error(qual_namespace)?;
+        let steps = self.expand_path(&ns_uri, path)?;
+
+        let Node::Simple(value) = value_to_node(value)? else {
+            return Err(XmpError::BadValue(
+                "Qualifier values must be simple values".to_string(),
+            ));
+        };
+
+        let mut root = root_write!(self.root);
+        let node = find_node_mut(&mut root, &steps).ok_or_else(|| {
+            XmpError::BadXPath(format!("Property '{}:{}' does not exist", ns_uri, path))
+        })?;
+
+        let qualifiers = node.qualifiers_mut();
+        match qualifiers
+            .iter_mut()
+            .find(|q| q.namespace == qual_ns_uri && q.name == qual_name)
+        {
+            Some(existing) => existing.value = value.value,
+            None => qualifiers.push(Qualifier::new(qual_ns_uri, qual_name, value.value)),
+        }
+        Ok(())
+    }
+
+    /// Delete a qualifier
+    ///
+    /// Deleting a qualifier that doesn't exist is not an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace URI or prefix of the property
+    /// * `path` - The property path (e.g., "title" or "creator\[2\]")
+    /// * `qual_namespace` - The namespace URI or prefix of the qualifier
+    /// * `qual_name` - The qualifier name
+    pub fn delete_qualifier(
+        &mut self,
+        namespace: &str,
+        path: &str,
+        qual_namespace: &str,
+        qual_name: &str,
+    ) -> XmpResult<()> {
+        let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+        let qual_ns_uri = self.resolve_namespace_uri_or_error(qual_namespace)?;
+        let steps = self.expand_path(&ns_uri, path)?;
 
-        structure.remove_field(field_name);
+        let mut root = root_write!(self.root);
+        if let Some(node) = find_node_mut(&mut root, &steps) {
+            node.qualifiers_mut()
+                .retain(|q| !(q.namespace == qual_ns_uri && q.name == qual_name));
+        }
         Ok(())
     }
 
@@ -456,6 +1461,18 @@ impl XmpMeta {
     /// Localized text properties are stored as `rdf:Alt` arrays, where each item
     /// has an `xml:lang` qualifier indicating its language.
     ///
+    /// This follows the Adobe XMP Toolkit semantics, including keeping the
+    /// `x-default` item in sync:
+    /// - Setting `x-default` also updates every item whose value matched the
+    ///   old `x-default` value.
+    /// - Updating an item whose value matched `x-default` also updates
+    ///   `x-default`.
+    /// - When the first language is added, an `x-default` item with the same
+    ///   value is created as well.
+    ///
+    /// The `x-default` item, if any, is always kept first in the array.
+    /// Language tags are matched case-insensitively.
+    ///
     /// # Arguments
     ///
     /// * `namespace` - The namespace URI or prefix
@@ -473,24 +1490,36 @@ impl XmpMeta {
     /// meta.set_localized_text(
     ///     "http://purl.org/dc/elements/1.1/",
     ///     "title",
-    ///     "",
-    ///     "x-default",
-    ///     "Default Title"
+    ///     "en",
+    ///     "en-US",
+    ///     "Title"
     /// ).unwrap();
+    ///
+    /// // The first item also creates x-default
+    /// let (value, lang) = meta
+    ///     .get_localized_text("http://purl.org/dc/elements/1.1/", "title", "", "x-default")
+    ///     .unwrap();
+    /// assert_eq!(value, "Title");
+    /// assert_eq!(lang, "x-default");
     /// ```
     pub fn set_localized_text(
         &mut self,
         namespace: &str,
         property: &str,
-        _generic_lang: &str,
+        generic_lang: &str,
         specific_lang: &str,
         value: &str,
     ) -> XmpResult<()> {
-        use crate::core::namespace::ns;
-        use crate::core::node::{ArrayNode, ArrayType, Node};
-        use crate::types::qualifier::Qualifier;
+        if specific_lang.is_empty() {
+            return Err(XmpError::BadParam(
+                "Specific language must not be empty".to_string(),
+            ));
+        }
 
         let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+        let (ns_uri, property) = resolve_alias_name(ns_uri, property, true);
+
+        validate::check_custom_property(&ns_uri, &property, Some(ValueForm::LangAlt), Some(value))?;
 
         let full_path = format!("{}:{}", ns_uri, property);
         let mut root = root_write!(self.root);
@@ -509,6 +1538,11 @@ impl XmpMeta {
                 )));
             }
             array
+        } else if root.has_field(&full_path) {
+            return Err(XmpError::BadValue(format!(
+                "Property '{}:{}' exists but is not a localized text array (rdf:Alt). Expected array type: Alternative",
+                ns_uri, property
+            )));
         } else {
             // Create new Alt array
             let new_array = ArrayNode::new(ArrayType::Alternative);
@@ -520,31 +1554,83 @@ impl XmpMeta {
                 })?
         };
 
-        // Find existing item with matching specific_lang
-        let mut found = false;
-        for item in &mut array.items {
-            let Some(simple) = item.as_simple_mut() else {
-                continue;
-            };
-            let Some(lang_qual) = simple.get_qualifier(ns::XML, "lang") else {
-                continue;
-            };
-            if lang_qual.value == specific_lang {
-                // Update existing item
-                simple.value = value.to_string();
-                found = true;
-                break;
+        // Make sure the x-default item, if any, is first
+        let have_x_default = match array
+            .items
+            .iter()
+            .position(|item| item_lang(item).is_some_and(|l| lang_eq(l, X_DEFAULT)))
+        {
+            Some(index) => {
+                if index != 0 {
+                    let item = array.items.remove(index);
+                    array.items.insert(0, item);
+                }
+                true
             }
-        }
+            None => false,
+        };
+
+        let x_default_value = |array: &ArrayNode| -> Option<String> {
+            if have_x_default {
+                array
+                    .items
+                    .first()
+                    .and_then(|n| n.as_simple())
+                    .map(|n| n.value.clone())
+            } else {
+                None
+            }
+        };
 
-        // If not found, create new item
-        if !found {
-            let mut simple_node = Node::simple(value.to_string());
-            if let Node::Simple(ref mut sn) = simple_node {
-                let lang_qualifier = Qualifier::new(ns::XML, "lang", specific_lang.to_string());
-                sn.add_qualifier(lang_qualifier);
+        let specific_is_x_default = lang_eq(specific_lang, X_DEFAULT);
+        let (lang_match, index) = choose_localized_item(array, generic_lang, specific_lang);
+
+        match lang_match {
+            LangMatch::Specific if specific_is_x_default => {
+                // Update all items whose value matches the old x-default value
+                let old = x_default_value(array);
+                for item in array.items.iter_mut().skip(1) {
+                    if let Node::Simple(simple) = item {
+                        if Some(&simple.value) == old.as_ref() {
+                            simple.value = value.to_string();
+                        }
+                    }
+                }
+                set_item_value(array, 0, value);
+            }
+            LangMatch::Specific | LangMatch::SingleGeneric => {
+                // Update the matched item, and x-default if it had the same value
+                let index = index.unwrap_or(0);
+                let old = array
+                    .items
+                    .get(index)
+                    .and_then(|n| n.as_simple())
+                    .map(|n| n.value.clone());
+                if index != 0 && old.is_some() && x_default_value(array) == old {
+                    set_item_value(array, 0, value);
+                }
+                set_item_value(array, index, value);
+            }
+            LangMatch::XDefault => {
+                // Create the specific language, update x-default if it was the only item
+                if array.len() == 1 {
+                    set_item_value(array, 0, value);
+                }
+                array.append(lang_item(specific_lang, value));
             }
-            array.append(simple_node);
+            LangMatch::MultipleGeneric | LangMatch::FirstItem | LangMatch::NoValues => {
+                // Create the specific language
+                if specific_is_x_default {
+                    array.items.insert(0, lang_item(specific_lang, value));
+                } else {
+                    array.append(lang_item(specific_lang, value));
+                }
+            }
+        }
+
+        // Add an x-default at the front if this is the first item
+        if !have_x_default && !specific_is_x_default && array.len() == 1 {
+            array.items.insert(0, lang_item(X_DEFAULT, value));
         }
 
         Ok(())
@@ -555,8 +1641,11 @@ impl XmpMeta {
     /// This method searches for a localized text value matching the specified
     /// language codes. It follows XMP language matching rules:
     /// 1. Exact match for specific_lang
-    /// 2. Match for generic_lang if specific_lang not found
+    /// 2. Match for generic_lang (e.g., "en" matches "en-GB") if specific_lang not found
     /// 3. Fallback to "x-default" if neither found
+    /// 4. Fallback to the first item
+    ///
+    /// Language tags are matched case-insensitively.
     ///
     /// # Arguments
     ///
@@ -571,7 +1660,7 @@ impl XmpMeta {
     /// - `value` is the text value
     /// - `actual_lang` is the actual language code used (may differ from requested)
     ///
-    /// Returns `None` if the property doesn't exist or no matching language found.
+    /// Returns `None` if the property doesn't exist or has no items.
     ///
     /// # Example
     ///
@@ -603,9 +1692,8 @@ impl XmpMeta {
         generic_lang: &str,
         specific_lang: &str,
     ) -> Option<(String, String)> {
-        use crate::core::namespace::ns;
-
         let ns_uri = self.resolve_namespace_uri(namespace)?;
+        let (ns_uri, property) = resolve_alias_name(ns_uri, property, true);
 
         let full_path = format!("{}:{}", ns_uri, property);
         let root = root_read_opt!(self.root);
@@ -615,58 +1703,14 @@ impl XmpMeta {
             .and_then(|node| node.as_array())?;
 
         // Ensure it's an Alt array
-        if array.array_type != crate::core::node::ArrayType::Alternative {
+        if array.array_type != ArrayType::Alternative {
             return None;
         }
 
-        // Try exact match for specific_lang
-        for item in &array.items {
-            let Some(simple) = item.as_simple() else {
-                continue;
-            };
-            let Some(lang_qual) = simple.get_qualifier(ns::XML, "lang") else {
-                continue;
-            };
-            if lang_qual.value == specific_lang {
-                return Some((simple.value.clone(), lang_qual.value.clone()));
-            }
-        }
-
-        // Try match for generic_lang (if provided)
-        if !generic_lang.is_empty() {
-            for item in &array.items {
-                let Some(simple) = item.as_simple() else {
-                    continue;
-                };
-                let Some(lang_qual) = simple.get_qualifier(ns::XML, "lang") else {
-                    continue;
-                };
-                if lang_qual.value.starts_with(generic_lang) {
-                    return Some((simple.value.clone(), lang_qual.value.clone()));
-                }
-            }
-        }
-
-        // Fallback to x-default
-        for item in &array.items {
-            let Some(simple) = item.as_simple() else {
-                continue;
-            };
-            let Some(lang_qual) = simple.get_qualifier(ns::XML, "lang") else {
-                continue;
-            };
-            if lang_qual.value == "x-default" {
-                return Some((simple.value.clone(), lang_qual.value.clone()));
-            }
-        }
-
-        // If no x-default, return first item
-        let first_item = array.items.first()?;
-        let simple = first_item.as_simple()?;
-        let lang = simple
-            .get_qualifier(ns::XML, "lang")
-            .map(|q| q.value.clone())
-            .unwrap_or_else(|| "".to_string());
+        let (_, index) = choose_localized_item(array, generic_lang, specific_lang);
+        let item = array.get(index?)?;
+        let simple = item.as_simple()?;
+        let lang = item_lang(item).unwrap_or_default().to_string();
         Some((simple.value.clone(), lang))
     }
 
@@ -708,8 +1752,7 @@ impl XmpMeta {
         dt: &crate::utils::datetime::XmpDateTime,
     ) -> XmpResult<()> {
         dt.validate()?;
-        let formatted = dt.format();
-        self.set_property(namespace, path, XmpValue::DateTime(formatted))
+        self.set_property(namespace, path, XmpValue::DateTime(dt.clone()))
     }
 
     /// Get a date/time property
@@ -736,7 +1779,7 @@ impl XmpMeta {
     /// meta.set_property(
     ///     "http://ns.adobe.com/xap/1.0/",
     ///     "ModifyDate",
-    ///     XmpValue::DateTime("2023-12-25T10:30:00Z".to_string())
+    ///     XmpValue::DateTime(XmpDateTime::parse("2023-12-25T10:30:00Z").unwrap())
     /// ).unwrap();
     ///
     /// let dt = meta.get_date_time("http://ns.adobe.com/xap/1.0/", "ModifyDate").unwrap();
@@ -749,13 +1792,436 @@ impl XmpMeta {
         namespace: &str,
         path: &str,
     ) -> Option<crate::utils::datetime::XmpDateTime> {
-        self.get_property(namespace, path)
-            .and_then(|v| match v {
-                XmpValue::DateTime(s) => Some(s),
-                XmpValue::String(s) => Some(s),
-                _ => None,
+        match self.get_property(namespace, path)? {
+            XmpValue::DateTime(dt) => Some(dt),
+            XmpValue::String(s) => s.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Get a property value as a boolean
+    ///
+    /// The value is converted using XMP rules: `"True"`, `"t"` and `"1"` are
+    /// `true`; `"False"`, `"f"` and `"0"` are `false` (case-insensitive).
+    ///
+    /// Returns `None` if the property doesn't exist or can't be converted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::XmpMeta;
+    ///
+    /// let mut meta = XmpMeta::new();
+    /// meta.set_property_bool("http://ns.adobe.com/xap/1.0/rights/", "Marked", true).unwrap();
+    /// assert_eq!(
+    ///     meta.get_property_bool("http://ns.adobe.com/xap/1.0/rights/", "Marked"),
+    ///     Some(true)
+    /// );
+    /// ```
+    pub fn get_property_bool(&self, namespace: &str, path: &str) -> Option<bool> {
+        match self.get_property(namespace, path)? {
+            XmpValue::Boolean(b) => Some(b),
+            XmpValue::String(s) => convert::convert_to_bool(&s).ok(),
+            _ => None,
+        }
+    }
+
+    /// Get a property value as a 64-bit integer
+    ///
+    /// Decimal and `0x`-prefixed hexadecimal values are accepted.
+    ///
+    /// Returns `None` if the property doesn't exist or can't be converted.
+    pub fn get_property_i64(&self, namespace: &str, path: &str) -> Option<i64> {
+        match self.get_property(namespace, path)? {
+            XmpValue::Integer(i) => Some(i),
+            XmpValue::String(s) => convert::convert_to_int64(&s).ok(),
+            _ => None,
+        }
+    }
+
+    /// Get a property value as a floating point number
+    ///
+    /// Decimal and exponent notation (e.g. `"1.5E-3"`) are accepted.
+    ///
+    /// Returns `None` if the property doesn't exist or can't be converted.
+    pub fn get_property_f64(&self, namespace: &str, path: &str) -> Option<f64> {
+        match self.get_property(namespace, path)? {
+            XmpValue::Integer(i) => Some(i as f64),
+            XmpValue::String(s) => convert::convert_to_float(&s).ok(),
+            _ => None,
+        }
+    }
+
+    /// Get a property value as a date/time
+    ///
+    /// This is equivalent to [`get_date_time`](Self::get_date_time).
+    pub fn get_property_date(
+        &self,
+        namespace: &str,
+        path: &str,
+    ) -> Option<crate::utils::datetime::XmpDateTime> {
+        self.get_date_time(namespace, path)
+    }
+
+    /// Set a boolean property value
+    ///
+    /// The value is stored as `"True"` or `"False"`.
+    pub fn set_property_bool(&mut self, namespace: &str, path: &str, value: bool) -> XmpResult<()> {
+        self.set_property(namespace, path, XmpValue::Boolean(value))
+    }
+
+    /// Set a 64-bit integer property value
+    pub fn set_property_i64(&mut self, namespace: &str, path: &str, value: i64) -> XmpResult<()> {
+        self.set_property(namespace, path, XmpValue::Integer(value))
+    }
+
+    /// Set a floating point property value
+    ///
+    /// Returns an error if the value is NaN or infinite, since these have no
+    /// XMP representation.
+    pub fn set_property_f64(&mut self, namespace: &str, path: &str, value: f64) -> XmpResult<()> {
+        let s = convert::convert_from_float(value)?;
+        self.set_property(namespace, path, XmpValue::String(s))
+    }
+
+    /// Set a date/time property value
+    ///
+    /// This is equivalent to [`set_date_time`](Self::set_date_time).
+    pub fn set_property_date(
+        &mut self,
+        namespace: &str,
+        path: &str,
+        dt: &crate::utils::datetime::XmpDateTime,
+    ) -> XmpResult<()> {
+        self.set_date_time(namespace, path, dt)
+    }
+}
+
+/// Find the node addressed by expanded path steps
+///
+/// Returns `None` if the node doesn't exist or the path ends in a qualifier.
+fn find_node<'a>(root: &'a StructureNode, steps: &[PathStep]) -> Option<&'a Node> {
+    let (PathStep::Field(key), rest) = steps.split_first()? else {
+        return None;
+    };
+
+    let mut node = root.get_field(key)?;
+    for step in rest {
+        node = match step {
+            PathStep::Field(key) => node.as_structure()?.get_field(key)?,
+            _ => {
+                let array = node.as_array()?;
+                array.get(select_array_item(array, step)?)?
+            }
+        };
+    }
+    Some(node)
+}
+
+/// Find the node addressed by expanded path steps for modification
+fn find_node_mut<'a>(root: &'a mut StructureNode, steps: &[PathStep]) -> Option<&'a mut Node> {
+    let (PathStep::Field(key), rest) = steps.split_first()? else {
+        return None;
+    };
+
+    let mut node = root.get_field_mut(key)?;
+    for step in rest {
+        node = match step {
+            PathStep::Field(key) => node.as_structure_mut()?.get_field_mut(key)?,
+            _ => {
+                let array = node.as_array_mut()?;
+                let index = select_array_item(array, step)?;
+                array.get_mut(index)?
+            }
+        };
+    }
+    Some(node)
+}
+
+/// Match a name against a pattern where `*` matches any sequence and `?` any character
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    // Position to resume from after the last `*`: (pattern index, name index)
+    let mut backtrack = None;
+    let (mut p, mut n) = (0, 0);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_n)) => {
+                    backtrack = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Find the node addressed by path steps below `node`, creating missing nodes
+fn find_or_create_node<'a>(
+    mut node: &'a mut Node,
+    steps: &[PathStep],
+    path: &str,
+) -> XmpResult<&'a mut Node> {
+    let not_found = || XmpError::BadXPath(format!("No node matches path '{}'", path));
+
+    for (i, step) in steps.iter().enumerate() {
+        let next = steps.get(i + 1);
+        node = match step {
+            PathStep::Field(key) => node
+                .as_structure_mut()
+                .ok_or_else(|| {
+                    XmpError::BadXPath(format!("Path '{}' uses a field of a non-structure", path))
+                })?
+                .fields
+                .entry(key.clone())
+                .or_insert_with(|| implicit_node(next)),
+            PathStep::Qualifier { .. } => return Err(not_found()),
+            _ => {
+                let array = node.as_array_mut().ok_or_else(|| {
+                    XmpError::BadXPath(format!("Path '{}' indexes a non-array", path))
+                })?;
+
+                let index = match (select_array_item(array, step), step) {
+                    (Some(index), _) => index,
+                    // Appending one past the end creates a new item
+                    (None, PathStep::Index(index)) if *index == array.len() => {
+                        array.append(implicit_node(next));
+                        *index
+                    }
+                    // A missing language is added to the localized text array
+                    (
+                        None,
+                        PathStep::QualifierSelector {
+                            namespace,
+                            name,
+                            value,
+                        },
+                    ) if next.is_none()
+                        && namespace == crate::core::namespace::ns::XML
+                        && name == "lang" =>
+                    {
+                        array.append(lang_item(value, ""));
+                        array.len() - 1
+                    }
+                    _ => return Err(not_found()),
+                };
+                array.get_mut(index).ok_or_else(not_found)?
+            }
+        };
+    }
+    Ok(node)
+}
+
+/// Resolve a top-level property name that is an alias of a whole property
+///
+/// With `lang_alt`, aliases of the `x-default` item of a language
+/// alternative resolve to the whole array too. Other names are returned as
+/// they are.
+fn resolve_alias_name(ns_uri: String, name: &str, lang_alt: bool) -> (String, Cow<'_, str>) {
+    match alias::lookup(&ns_uri, name) {
+        Some(alias)
+            if alias.form == ValueForm::Simple
+                || (lang_alt && alias.form == ValueForm::LangAlt) =>
+        {
+            (alias.namespace, Cow::Owned(alias.property))
+        }
+        _ => (ns_uri, Cow::Borrowed(name)),
+    }
+}
+
+/// Create the top-level property implied by the step that will be applied
+/// to it, using the array form known from schemas and aliases
+fn implicit_top_node(key: &str, next: Option<&PathStep>) -> Node {
+    let array_type = key.rsplit_once(':').and_then(|(ns_uri, name)| {
+        validate::custom_array_type(ns_uri, name).or_else(|| alias::actual_array_type(ns_uri, name))
+    });
+    match (array_type, next) {
+        (None, _) | (_, None | Some(PathStep::Field(_) | PathStep::Qualifier { .. })) => {
+            implicit_node(next)
+        }
+        (Some(array_type), _) => Node::array(array_type),
+    }
+}
+
+/// Create the node implied by the step that will be applied to it
+fn implicit_node(next: Option<&PathStep>) -> Node {
+    use crate::core::namespace::ns;
+
+    match next {
+        None | Some(PathStep::Qualifier { .. }) => Node::simple(""),
+        Some(PathStep::Field(_)) => Node::structure(),
+        Some(PathStep::QualifierSelector {
+            namespace, name, ..
+        }) if namespace == ns::XML && name == "lang" => Node::array(ArrayType::Alternative),
+        Some(_) => Node::array(ArrayType::Ordered),
+    }
+}
+
+/// Get the index of the array item selected by an index or selector step
+fn select_array_item(array: &ArrayNode, step: &PathStep) -> Option<usize> {
+    use crate::core::namespace::ns;
+
+    match step {
+        PathStep::Index(index) => (*index < array.len()).then_some(*index),
+        PathStep::Last => array.len().checked_sub(1),
+        PathStep::QualifierSelector {
+            namespace,
+            name,
+            value,
+        } => array.items.iter().position(|item| {
+            item.qualifiers().iter().any(|q| {
+                q.namespace == *namespace
+                    && q.name == *name
+                    && if namespace == ns::XML && name == "lang" {
+                        lang_eq(&q.value, value)
+                    } else {
+                        q.value == *value
+                    }
             })
-            .and_then(|s| crate::utils::datetime::XmpDateTime::parse(&s).ok())
+        }),
+        PathStep::FieldSelector { field, value } => array.items.iter().position(|item| {
+            item.as_structure()
+                .and_then(|s| s.get_field(field))
+                .and_then(|f| f.as_simple())
+                .is_some_and(|f| f.value == *value)
+        }),
+        PathStep::Field(_) | PathStep::Qualifier { .. } => None,
+    }
+}
+
+/// Language tag of the default item in a localized text array
+const X_DEFAULT: &str = "x-default";
+
+/// How a localized text item was selected (Adobe `XMP_CLTMatch`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LangMatch {
+    /// An item with the specific language was found
+    Specific,
+    /// Exactly one item with the generic language was found
+    SingleGeneric,
+    /// Several items with the generic language were found; the first is used
+    MultipleGeneric,
+    /// The x-default item was used
+    XDefault,
+    /// The first item was used
+    FirstItem,
+    /// The array has no items
+    NoValues,
+}
+
+/// Choose the item of a localized text array for the given languages
+///
+/// Returns the kind of match and the index of the chosen item.
+fn choose_localized_item(
+    array: &ArrayNode,
+    generic_lang: &str,
+    specific_lang: &str,
+) -> (LangMatch, Option<usize>) {
+    if array.is_empty() {
+        return (LangMatch::NoValues, None);
+    }
+
+    let find = |lang: &str| {
+        array
+            .items
+            .iter()
+            .position(|item| item_lang(item).is_some_and(|l| lang_eq(l, lang)))
+    };
+
+    if let Some(index) = find(specific_lang) {
+        return (LangMatch::Specific, Some(index));
+    }
+
+    if !generic_lang.is_empty() {
+        let mut generic_matches =
+            array.items.iter().enumerate().filter(|(_, item)| {
+                item_lang(item).is_some_and(|l| is_generic_match(l, generic_lang))
+            });
+        if let Some((index, _)) = generic_matches.next() {
+            let kind = if generic_matches.next().is_some() {
+                LangMatch::MultipleGeneric
+            } else {
+                LangMatch::SingleGeneric
+            };
+            return (kind, Some(index));
+        }
+    }
+
+    if let Some(index) = find(X_DEFAULT) {
+        return (LangMatch::XDefault, Some(index));
+    }
+
+    (LangMatch::FirstItem, Some(0))
+}
+
+/// Get the `xml:lang` qualifier value of a node
+fn item_lang(item: &Node) -> Option<&str> {
+    use crate::core::namespace::ns;
+
+    item.qualifiers()
+        .iter()
+        .find(|q| q.namespace == ns::XML && q.name == "lang")
+        .map(|q| q.value.as_str())
+}
+
+/// Compare language tags (case-insensitive, per RFC 3066)
+fn lang_eq(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b)
+}
+
+/// Check if `lang` is `generic` or a sub-tag of it (e.g., "en-GB" for "en")
+fn is_generic_match(lang: &str, generic: &str) -> bool {
+    lang.len() >= generic.len()
+        && lang.is_char_boundary(generic.len())
+        && lang_eq(&lang[..generic.len()], generic)
+        && (lang.len() == generic.len() || lang.as_bytes()[generic.len()] == b'-')
+}
+
+/// Create a localized text item with an `xml:lang` qualifier
+fn lang_item(lang: &str, value: &str) -> Node {
+    use crate::core::namespace::ns;
+
+    let mut node = Node::simple(value);
+    node.qualifiers_mut()
+        .push(Qualifier::new(ns::XML, "lang", lang));
+    node
+}
+
+/// Set the value of a simple item in an array
+fn set_item_value(array: &mut ArrayNode, index: usize, value: &str) {
+    if let Some(Node::Simple(simple)) = array.get_mut(index) {
+        simple.value = value.to_string();
+    }
+}
+
+/// Get the value of a simple node, keeping its URI flag
+fn simple_value(simple: &SimpleNode) -> XmpValue {
+    if simple.is_uri {
+        XmpValue::Uri(simple.value.clone())
+    } else {
+        XmpValue::String(simple.value.clone())
+    }
+}
+
+/// Get the schema form of an array form
+fn array_form(array_type: ArrayType) -> ValueForm {
+    match array_type {
+        ArrayType::Unordered => ValueForm::Bag,
+        ArrayType::Ordered => ValueForm::Seq,
+        ArrayType::Alternative => ValueForm::Alt,
     }
 }
 
@@ -764,8 +2230,9 @@ fn value_to_node(value: XmpValue) -> XmpResult<Node> {
     match value {
         XmpValue::String(s) => Ok(Node::simple(s)),
         XmpValue::Integer(i) => Ok(Node::simple(i.to_string())),
-        XmpValue::Boolean(b) => Ok(Node::simple(if b { "True" } else { "False" })),
-        XmpValue::DateTime(dt) => Ok(Node::simple(dt)),
+        XmpValue::Boolean(b) => Ok(Node::simple(convert::convert_from_bool(b))),
+        XmpValue::DateTime(dt) => Ok(Node::simple(dt.format())),
+        XmpValue::Uri(uri) => Ok(Node::uri(uri)),
         _ => Err(XmpError::NotSupported(
             "Complex types not yet supported".to_string(),
         )),
@@ -828,6 +2295,38 @@ mod tests {
         assert_eq!(value, Some(XmpValue::String("TestApp".to_string())));
     }
 
+    #[test]
+    fn test_uri_property() {
+        use crate::core::namespace::ns;
+
+        let xml = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+                              xmlns:xmpRights="http://ns.adobe.com/xap/1.0/rights/">
+          <rdf:Description rdf:about="">
+            <xmpRights:WebStatement rdf:resource="https://example.com/license"/>
+          </rdf:Description>
+        </rdf:RDF>"#;
+        let mut meta = XmpMeta::parse(xml).unwrap();
+        let uri = XmpValue::Uri("https://example.com/license".to_string());
+        assert_eq!(meta.get_property(ns::XMP_RIGHTS, "WebStatement"), Some(uri));
+
+        meta.set_property(
+            ns::XMP_RIGHTS,
+            "Certificate",
+            XmpValue::Uri("https://example.com/c?a=1&b=2".into()),
+        )
+        .unwrap();
+        let packet = meta.serialize_packet().unwrap();
+        assert!(packet
+            .contains(r#"<xmpRights:WebStatement rdf:resource="https://example.com/license"/>"#));
+        assert!(packet.contains(r#"rdf:resource="https://example.com/c?a=1&amp;b=2""#));
+
+        let reparsed = XmpMeta::parse(&packet).unwrap();
+        assert_eq!(
+            reparsed.get_property(ns::XMP_RIGHTS, "Certificate"),
+            Some(XmpValue::Uri("https://example.com/c?a=1&b=2".to_string()))
+        );
+    }
+
     #[test]
     fn test_serialize() {
         let mut meta = XmpMeta::new();
@@ -927,33 +2426,390 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_array_set_and_count() {
+        let mut meta = XmpMeta::new();
+        let ns = "http://purl.org/dc/elements/1.1/";
+
+        assert_eq!(meta.count_array_items(ns, "subject"), 0);
+        assert!(meta
+            .set_array_item(ns, "subject", 0, "missing".into())
+            .is_err());
+
+        meta.append_array_item_with(ns, "subject", ArrayType::Unordered, "a".into())
+            .unwrap();
+        meta.append_array_item(ns, "subject", "b".into()).unwrap();
+        assert_eq!(meta.count_array_items(ns, "subject"), 2);
+
+        meta.set_array_item(ns, "subject", 1, "B".into()).unwrap();
+        assert_eq!(
+            meta.get_array_item(ns, "subject", 1),
+            Some(XmpValue::String("B".to_string()))
+        );
+
+        // Setting at the end appends
+        meta.set_array_item(ns, "subject", 2, "c".into()).unwrap();
+        assert_eq!(meta.count_array_items(ns, "subject"), 3);
+        assert!(meta.set_array_item(ns, "subject", 5, "x".into()).is_err());
+
+        // Array form is kept and mismatches are rejected
+        let serialized = meta.serialize().unwrap();
+        assert!(serialized.contains("rdf:Bag"));
+        assert!(meta
+            .append_array_item_with(ns, "subject", ArrayType::Ordered, "d".into())
+            .is_err());
+
+        // Appending to a non-array property is an error
+        meta.set_property(ns, "format", "image/jpeg".into())
+            .unwrap();
+        assert!(meta.append_array_item(ns, "format", "x".into()).is_err());
+    }
+
     #[test]
     fn test_struct_operations() {
+        let exif = "http://ns.adobe.com/exif/1.0/";
+
         let mut meta = XmpMeta::new();
 
         // Set struct field
-        meta.set_struct_field(
-            "http://ns.adobe.com/exif/1.0/",
-            "Flash",
-            "Fired",
-            XmpValue::Boolean(true),
-        )
-        .unwrap();
+        meta.set_struct_field(exif, "Flash", exif, "Fired", XmpValue::Boolean(true))
+            .unwrap();
 
         // Get struct field
         assert_eq!(
-            meta.get_struct_field("http://ns.adobe.com/exif/1.0/", "Flash", "Fired"),
+            meta.get_struct_field(exif, "Flash", exif, "Fired"),
             Some(XmpValue::String("True".to_string()))
         );
 
         // Delete struct field
-        meta.delete_struct_field("http://ns.adobe.com/exif/1.0/", "Flash", "Fired")
+        meta.delete_struct_field(exif, "Flash", exif, "Fired")
+            .unwrap();
+
+        assert_eq!(meta.get_struct_field(exif, "Flash", exif, "Fired"), None);
+    }
+
+    #[test]
+    fn test_nested_struct_fields() {
+        use crate::core::namespace::ns;
+
+        let mut meta = XmpMeta::new();
+
+        // Intermediate structures are created on demand
+        meta.set_struct_field(
+            ns::IPTC_CORE,
+            "CreatorContactInfo",
+            ns::IPTC_CORE,
+            "CiEmailWork",
+            XmpValue::String("jane@example.com".to_string()),
+        )
+        .unwrap();
+        meta.set_struct_field(
+            ns::XMP_MM,
+            "ManageTo/xmpMM:Source",
+            "xmpMM",
+            "DocumentID",
+            XmpValue::String("xmp.did:1234".to_string()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            meta.get_struct_field(
+                ns::XMP_MM,
+                "ManageTo/xmpMM:Source",
+                ns::XMP_MM,
+                "DocumentID"
+            ),
+            Some(XmpValue::String("xmp.did:1234".to_string()))
+        );
+
+        // Fields are namespace-qualified
+        assert_eq!(
+            meta.get_struct_field(ns::IPTC_CORE, "CreatorContactInfo", ns::DC, "CiEmailWork"),
+            None
+        );
+
+        // Round-trip through RDF
+        let packet = meta.serialize_packet().unwrap();
+        assert!(packet.contains("Iptc4xmpCore:CiEmailWork"));
+        let meta2 = XmpMeta::parse(&packet).unwrap();
+        assert_eq!(
+            meta2.get_struct_field(
+                ns::IPTC_CORE,
+                "CreatorContactInfo",
+                ns::IPTC_CORE,
+                "CiEmailWork"
+            ),
+            Some(XmpValue::String("jane@example.com".to_string()))
+        );
+        assert_eq!(
+            meta2.get_struct_field(ns::XMP_MM, "ManageTo/xmpMM:Source", "xmpMM", "DocumentID"),
+            Some(XmpValue::String("xmp.did:1234".to_string()))
+        );
+
+        // A simple property can't be used as a structure
+        meta.set_property(ns::DC, "format", XmpValue::String("image/png".to_string()))
+            .unwrap();
+        assert!(meta
+            .set_struct_field(ns::DC, "format", ns::DC, "x", XmpValue::from("y"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_property_by_path() {
+        use crate::core::namespace::ns;
+        use crate::core::path::{compose_array_item_path, compose_struct_field_path};
+
+        let mut meta = XmpMeta::new();
+
+        // Build xmpMM:History[n]/stEvt:* from nothing
+        for (i, action) in ["created", "saved", "converted"].iter().enumerate() {
+            let item = compose_array_item_path("History", i);
+            let path = compose_struct_field_path(&item, ns::ST_EVT, "action").unwrap();
+            meta.set_property_by_path(ns::XMP_MM, &path, (*action).into())
+                .unwrap();
+        }
+        meta.set_property_by_path(ns::XMP_MM, "History[3]/stEvt:when", "2024-01-01".into())
+            .unwrap();
+
+        assert_eq!(meta.get_array_size(ns::XMP_MM, "History"), Some(3));
+        assert_eq!(
+            meta.get_property_by_path(ns::XMP_MM, "xmpMM:History[3]/stEvt:when"),
+            Some(XmpValue::String("2024-01-01".to_string()))
+        );
+        assert_eq!(
+            meta.get_property_by_path(ns::XMP_MM, "History[stEvt:action='saved']/stEvt:action"),
+            Some(XmpValue::String("saved".to_string()))
+        );
+        assert_eq!(
+            meta.get_property_by_path(ns::XMP_MM, "History[last()]/stEvt:action"),
+            Some(XmpValue::String("converted".to_string()))
+        );
+        assert_eq!(
+            meta.get_property_by_path(ns::XMP_MM, "History[4]/stEvt:action"),
+            None
+        );
+
+        // Index must be at most one past the end
+        assert!(meta
+            .set_property_by_path(ns::XMP_MM, "History[9]/stEvt:action", "x".into())
+            .is_err());
+        assert_eq!(meta.get_array_size(ns::XMP_MM, "History"), Some(3));
+
+        // Structures and arrays can't take a value
+        assert!(meta
+            .set_property_by_path(ns::XMP_MM, "History[1]", "x".into())
+            .is_err());
+
+        // Language selectors create localized text items
+        meta.set_property_by_path(ns::DC, "title[?xml:lang='x-default']", "Title".into())
+            .unwrap();
+        assert_eq!(
+            meta.get_localized_text(ns::DC, "title", "", "x-default")
+                .unwrap()
+                .0,
+            "Title"
+        );
+
+        // Qualifier paths
+        meta.set_property_by_path(ns::DC, "title[1]/?xmp:note", "n".into())
+            .unwrap();
+        assert_eq!(
+            meta.get_property_by_path(ns::DC, "title[1]/?xmp:note"),
+            Some(XmpValue::String("n".to_string()))
+        );
+        assert_eq!(
+            meta.get_property_by_path(ns::DC, "title[1]/?xml:lang"),
+            Some(XmpValue::String("x-default".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_delete_by_path() {
+        use crate::core::namespace::ns;
+
+        let mut meta = XmpMeta::new();
+        for subject in ["a", "b", "c"] {
+            meta.append_array_item(ns::DC, "subject", subject.into())
+                .unwrap();
+        }
+        meta.set_struct_field(ns::EXIF, "Flash", ns::EXIF, "Fired", "True".into())
+            .unwrap();
+        meta.set_qualifier(ns::DC, "subject[1]", ns::XMP, "note", "n".into())
+            .unwrap();
+
+        assert!(meta.does_property_exist(ns::DC, "subject[3]"));
+        assert!(meta.does_property_exist(ns::DC, "subject[1]/?xmp:note"));
+        assert!(meta.does_property_exist(ns::EXIF, "Flash/exif:Fired"));
+        assert!(!meta.does_property_exist(ns::DC, "subject[4]"));
+        assert!(!meta.does_property_exist(ns::DC, "subject["));
+
+        meta.delete_property(ns::DC, "subject[1]/?xmp:note")
+            .unwrap();
+        assert!(!meta.does_property_exist(ns::DC, "subject[1]/?xmp:note"));
+
+        meta.delete_property(ns::DC, "subject[2]").unwrap();
+        assert_eq!(meta.count_array_items(ns::DC, "subject"), 2);
+        assert_eq!(
+            meta.get_array_item(ns::DC, "subject", 1),
+            Some(XmpValue::String("c".to_string()))
+        );
+
+        meta.delete_property(ns::EXIF, "Flash/exif:Fired").unwrap();
+        assert!(meta.has_property(ns::EXIF, "Flash"));
+        assert!(!meta.has_property(ns::EXIF, "Flash/exif:Fired"));
+
+        // Missing nodes are ignored, invalid paths are not
+        meta.delete_property(ns::DC, "subject[9]").unwrap();
+        assert!(meta.delete_property(ns::DC, "subject[0]").is_err());
+    }
+
+    #[test]
+    fn test_delete_properties_matching() {
+        use crate::core::namespace::ns;
+
+        let mut meta = XmpMeta::new();
+        for name in ["City", "Country", "State", "Credit"] {
+            meta.set_property(ns::PHOTOSHOP, name, XmpValue::String(name.into()))
+                .unwrap();
+        }
+        meta.set_property(ns::XMP, "CreatorTool", XmpValue::String("t".into()))
+            .unwrap();
+
+        assert_eq!(
+            meta.delete_properties_matching("photoshop", "C?*y")
+                .unwrap(),
+            2
+        );
+        assert!(meta.has_property(ns::PHOTOSHOP, "Credit"));
+        assert_eq!(
+            meta.delete_properties_matching(ns::PHOTOSHOP, "*").unwrap(),
+            2
+        );
+        assert!(meta.has_property(ns::XMP, "CreatorTool"));
+        assert_eq!(
+            meta.delete_properties_matching(ns::PHOTOSHOP, "*").unwrap(),
+            0
+        );
+
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXbYbc"));
+        assert!(!glob_match("a*b", "aXbY"));
+        assert!(!glob_match("?", ""));
+    }
+
+    #[test]
+    fn test_serialize_with_options_round_trip() {
+        use crate::core::namespace::ns;
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "CreatorTool", XmpValue::String("a\r\nb".into()))
+            .unwrap();
+        meta.set_localized_text(ns::DC, "title", "", "x-default", "Title")
+            .unwrap();
+
+        for options in [
+            SerializeOptions::default(),
+            SerializeOptions::default()
+                .use_compact_format()
+                .indent("\t")
+                .newline("\r\n"),
+        ] {
+            let packet = meta.serialize_with_options(&options).unwrap();
+            let parsed = XmpMeta::parse(&packet).unwrap();
+            assert_eq!(
+                parsed.get_property(ns::XMP, "CreatorTool"),
+                Some(XmpValue::String("a\r\nb".to_string()))
+            );
+            assert_eq!(
+                parsed
+                    .get_localized_text(ns::DC, "title", "", "x-default")
+                    .unwrap()
+                    .0,
+                "Title"
+            );
+        }
+    }
+
+    #[test]
+    fn test_qualifiers() {
+        use crate::core::namespace::ns;
+
+        let mut meta = XmpMeta::new();
+        meta.append_array_item(ns::DC, "contributor", "Jane".into())
+            .unwrap();
+        meta.append_array_item(ns::DC, "contributor", "John".into())
             .unwrap();
+        meta.set_property(ns::XMP, "Label", "Red".into()).unwrap();
 
+        // Qualifiers on array items and top-level properties
+        meta.set_qualifier(ns::DC, "contributor[2]", ns::XMP, "role", "editor".into())
+            .unwrap();
+        meta.set_qualifier(ns::XMP, "Label", "xmp", "source", "camera".into())
+            .unwrap();
+        assert_eq!(
+            meta.get_qualifier(ns::DC, "contributor[2]", ns::XMP, "role"),
+            Some(XmpValue::String("editor".to_string()))
+        );
         assert_eq!(
-            meta.get_struct_field("http://ns.adobe.com/exif/1.0/", "Flash", "Fired"),
+            meta.get_qualifier(ns::DC, "contributor[1]", ns::XMP, "role"),
             None
         );
+
+        // Replacing keeps a single qualifier
+        meta.set_qualifier(
+            ns::DC,
+            "contributor[last()]",
+            ns::XMP,
+            "role",
+            "author".into(),
+        )
+        .unwrap();
+        let qualifiers = meta.get_qualifiers(ns::DC, "contributor[2]");
+        assert_eq!(qualifiers.len(), 1);
+        assert_eq!(qualifiers[0].value, "author");
+
+        // The property must exist
+        assert!(meta
+            .set_qualifier(ns::DC, "contributor[3]", ns::XMP, "role", "x".into())
+            .is_err());
+
+        // Round-trip through RDF using the rdf:value form
+        let packet = meta.serialize_packet().unwrap();
+        assert!(packet.contains("rdf:value"));
+        let meta2 = XmpMeta::parse(&packet).unwrap();
+        assert_eq!(
+            meta2.get_array_item(ns::DC, "contributor", 1),
+            Some(XmpValue::String("John".to_string()))
+        );
+        assert_eq!(
+            meta2.get_qualifier(ns::DC, "contributor[2]", ns::XMP, "role"),
+            Some(XmpValue::String("author".to_string()))
+        );
+        assert_eq!(
+            meta2.get_property(ns::XMP, "Label"),
+            Some(XmpValue::String("Red".to_string()))
+        );
+        assert_eq!(
+            meta2.get_qualifier(ns::XMP, "Label", ns::XMP, "source"),
+            Some(XmpValue::String("camera".to_string()))
+        );
+
+        // xml:lang is a qualifier too
+        meta.set_localized_text(ns::DC, "title", "", "x-default", "Title")
+            .unwrap();
+        assert_eq!(
+            meta.get_qualifier(ns::DC, "title[1]", ns::XML, "lang"),
+            Some(XmpValue::String("x-default".to_string()))
+        );
+        assert_eq!(
+            meta.get_qualifiers(ns::DC, "title[?xml:lang='x-default']")
+                .len(),
+            1
+        );
+
+        meta.delete_qualifier(ns::DC, "contributor[2]", ns::XMP, "role")
+            .unwrap();
+        assert!(meta.get_qualifiers(ns::DC, "contributor[2]").is_empty());
     }
 
     #[test]
@@ -995,11 +2851,12 @@ mod tests {
         assert_eq!(value, "中文标题");
         assert_eq!(lang, "zh-CN");
 
-        // Test fallback to x-default when specific language not found
+        // Test fallback to x-default when specific language not found.
+        // x-default was updated when en-US was added while it was the only item.
         let (value, lang) = meta
             .get_localized_text(ns, property, "fr", "fr-FR")
             .unwrap();
-        assert_eq!(value, "Default Title");
+        assert_eq!(value, "English Title");
         assert_eq!(lang, "x-default");
     }
 
@@ -1024,6 +2881,101 @@ mod tests {
         assert_eq!(value, "Updated Title");
     }
 
+    #[test]
+    fn test_localized_text_x_default_sync() {
+        let mut meta = XmpMeta::new();
+        let ns = "http://purl.org/dc/elements/1.1/";
+
+        // First specific language also creates x-default, placed first
+        meta.set_localized_text(ns, "title", "en", "en-US", "Hello")
+            .unwrap();
+        assert_eq!(meta.get_array_size(ns, "title"), Some(2));
+        assert_eq!(
+            meta.get_localized_text(ns, "title", "", "x-default"),
+            Some(("Hello".to_string(), "x-default".to_string()))
+        );
+        assert_eq!(
+            meta.get_array_item(ns, "title", 0),
+            Some(XmpValue::String("Hello".to_string()))
+        );
+
+        // Updating the item that matches x-default updates x-default too
+        meta.set_localized_text(ns, "title", "en", "en-US", "Hi")
+            .unwrap();
+        assert_eq!(
+            meta.get_localized_text(ns, "title", "", "x-default")
+                .unwrap()
+                .0,
+            "Hi"
+        );
+
+        // Adding another language leaves x-default alone
+        meta.set_localized_text(ns, "title", "fr", "fr-FR", "Salut")
+            .unwrap();
+        assert_eq!(meta.get_array_size(ns, "title"), Some(3));
+        assert_eq!(
+            meta.get_localized_text(ns, "title", "", "x-default")
+                .unwrap()
+                .0,
+            "Hi"
+        );
+
+        // Setting x-default updates items that shared its old value
+        meta.set_localized_text(ns, "title", "", "x-default", "Greetings")
+            .unwrap();
+        assert_eq!(
+            meta.get_localized_text(ns, "title", "en", "en-US")
+                .unwrap()
+                .0,
+            "Greetings"
+        );
+        assert_eq!(
+            meta.get_localized_text(ns, "title", "fr", "fr-FR")
+                .unwrap()
+                .0,
+            "Salut"
+        );
+    }
+
+    #[test]
+    fn test_localized_text_fallback() {
+        let mut meta = XmpMeta::new();
+        let ns = "http://purl.org/dc/elements/1.1/";
+
+        meta.set_localized_text(ns, "title", "", "x-default", "Default")
+            .unwrap();
+        meta.set_localized_text(ns, "title", "en", "en-GB", "Colour")
+            .unwrap();
+        meta.set_localized_text(ns, "title", "de", "de-DE", "Farbe")
+            .unwrap();
+
+        // Case-insensitive specific match
+        assert_eq!(
+            meta.get_localized_text(ns, "title", "", "DE-de"),
+            Some(("Farbe".to_string(), "de-DE".to_string()))
+        );
+        // Generic match
+        assert_eq!(
+            meta.get_localized_text(ns, "title", "en", "en-US"),
+            Some(("Colour".to_string(), "en-GB".to_string()))
+        );
+        // Generic must match a whole sub-tag
+        assert_eq!(
+            meta.get_localized_text(ns, "title", "e", "e-X").unwrap().1,
+            "x-default"
+        );
+        // x-default fallback
+        assert_eq!(
+            meta.get_localized_text(ns, "title", "fr", "fr-FR")
+                .unwrap()
+                .1,
+            "x-default"
+        );
+
+        // Empty specific language is rejected
+        assert!(meta.set_localized_text(ns, "title", "", "", "x").is_err());
+    }
+
     #[test]
     fn test_localized_text_serialize_round_trip() {
         let mut meta = XmpMeta::new();
@@ -1046,7 +2998,8 @@ mod tests {
         let (value1, lang1) = meta2
             .get_localized_text(ns, property, "", "x-default")
             .expect("Failed to get localized text for x-default");
-        assert_eq!(value1, "Default Title");
+        // x-default is synced when en-US is added while it is the only item
+        assert_eq!(value1, "English Title");
         assert_eq!(lang1, "x-default");
 
         let (value2, lang2) = meta2
@@ -1088,7 +3041,7 @@ mod tests {
         assert_eq!(retrieved_dt.hour, 10);
         assert_eq!(retrieved_dt.minute, 30);
         assert_eq!(retrieved_dt.second, 0);
-        assert_eq!(retrieved_dt.has_timezone, true);
+        assert!(retrieved_dt.has_timezone);
         assert_eq!(retrieved_dt.tz_sign, 0);
     }
 
@@ -1098,11 +3051,13 @@ mod tests {
         let ns = "http://ns.adobe.com/xap/1.0/";
         let property = "ModifyDate";
 
-        // Set date/time via string
+        // Set date/time value
         meta.set_property(
             ns,
             property,
-            XmpValue::DateTime("2023-12-25T10:30:00Z".to_string()),
+            XmpValue::DateTime(
+                crate::utils::datetime::XmpDateTime::parse("2023-12-25T10:30:00Z").unwrap(),
+            ),
         )
         .unwrap();
 
@@ -1122,6 +3077,47 @@ mod tests {
         assert_eq!(dt.second, 0);
     }
 
+    #[test]
+    fn test_typed_property_accessors() {
+        let mut meta = XmpMeta::new();
+        let ns = "http://ns.adobe.com/xap/1.0/";
+
+        meta.set_property_bool(ns, "Flag", false).unwrap();
+        assert_eq!(
+            meta.get_property(ns, "Flag"),
+            Some(XmpValue::String("False".to_string()))
+        );
+        assert_eq!(meta.get_property_bool(ns, "Flag"), Some(false));
+
+        meta.set_property_i64(ns, "Rating", -1).unwrap();
+        assert_eq!(meta.get_property_i64(ns, "Rating"), Some(-1));
+        assert_eq!(meta.get_property_f64(ns, "Rating"), Some(-1.0));
+        assert_eq!(meta.get_property_bool(ns, "Rating"), None);
+
+        meta.set_property_f64(ns, "Ratio", 0.25).unwrap();
+        assert_eq!(
+            meta.get_property(ns, "Ratio"),
+            Some(XmpValue::String("0.25".to_string()))
+        );
+        assert_eq!(meta.get_property_f64(ns, "Ratio"), Some(0.25));
+        assert_eq!(meta.get_property_i64(ns, "Ratio"), None);
+        assert!(meta.set_property_f64(ns, "Ratio", f64::NAN).is_err());
+
+        meta.set_property(ns, "Exp", XmpValue::String("1.5E2".to_string()))
+            .unwrap();
+        assert_eq!(meta.get_property_f64(ns, "Exp"), Some(150.0));
+
+        meta.set_property(ns, "Hex", XmpValue::String("0x10".to_string()))
+            .unwrap();
+        assert_eq!(meta.get_property_i64(ns, "Hex"), Some(16));
+
+        let dt = crate::utils::datetime::XmpDateTime::parse("2024-03-01T08:00:00Z").unwrap();
+        meta.set_property_date(ns, "ModifyDate", &dt).unwrap();
+        assert_eq!(meta.get_property_date(ns, "ModifyDate").unwrap().day, 1);
+
+        assert_eq!(meta.get_property_bool(ns, "Missing"), None);
+    }
+
     #[test]
     fn test_date_time_partial_dates() {
         use crate::utils::datetime::XmpDateTime;
@@ -1151,4 +3147,94 @@ mod tests {
         assert_eq!(retrieved.month, 12);
         assert_eq!(retrieved.day, 0);
     }
+
+    #[test]
+    fn test_aliases() {
+        use crate::core::namespace::ns;
+
+        let xml = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""
+      xmlns:pdf="http://ns.adobe.com/pdf/1.3/"
+      xmlns:tiff="http://ns.adobe.com/tiff/1.0/"
+      xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+      xmlns:photoshop="http://ns.adobe.com/photoshop/1.0/"
+      pdf:Title="Report" tiff:Artist="Jane" pdf:Creator="Old" xmp:CreatorTool="New">
+    <photoshop:Keywords><rdf:Bag><rdf:li>sky</rdf:li></rdf:Bag></photoshop:Keywords>
+  </rdf:Description>
+</rdf:RDF>"#;
+        let mut meta = XmpMeta::parse(xml).unwrap();
+
+        // pdf:Creator is an alias of xmp:CreatorTool
+        let options = ParseOptions::default().strict_aliasing();
+        assert!(XmpMeta::parse_with_options(xml, &options).is_err());
+        let consistent = xml.replace("\"Old\"", "\"New\"");
+        assert!(XmpMeta::parse_with_options(&consistent, &options).is_ok());
+
+        // Aliases are moved to their actual properties on parse
+        assert_eq!(
+            meta.get_localized_text(ns::DC, "title", "", "x-default"),
+            Some(("Report".to_string(), "x-default".to_string()))
+        );
+        assert_eq!(
+            meta.get_array_item(ns::DC, "creator", 0),
+            Some(XmpValue::String("Jane".into()))
+        );
+        assert_eq!(
+            meta.get_array_item(ns::DC, "subject", 0),
+            Some(XmpValue::String("sky".into()))
+        );
+        assert_eq!(
+            meta.get_property(ns::XMP, "CreatorTool"),
+            Some(XmpValue::String("New".into()))
+        );
+        let serialized = meta.serialize().unwrap();
+        assert!(!serialized.contains("pdf:") && !serialized.contains("tiff:"));
+
+        // Getters and setters resolve aliases
+        assert_eq!(
+            meta.get_property(ns::PDF, "Creator"),
+            Some(XmpValue::String("New".into()))
+        );
+        assert_eq!(
+            meta.get_property(ns::PHOTOSHOP, "Author"),
+            Some(XmpValue::String("Jane".into()))
+        );
+        meta.set_property(ns::TIFF, "Artist", "John".into())
+            .unwrap();
+        meta.append_array_item(ns::XMP, "Keywords", "sea".into())
+            .unwrap();
+        meta.set_localized_text(ns::PHOTOSHOP, "Caption", "", "x-default", "Caption")
+            .unwrap();
+        assert_eq!(
+            meta.get_array_item(ns::DC, "creator", 0),
+            Some(XmpValue::String("John".into()))
+        );
+        assert_eq!(meta.get_array_size(ns::DC, "subject"), Some(2));
+        assert_eq!(
+            meta.get_property_by_path(ns::PDF, "Subject"),
+            Some(XmpValue::String("Caption".into()))
+        );
+        meta.delete_property(ns::PDF, "Title").unwrap();
+        assert_eq!(meta.get_array_size(ns::DC, "title"), Some(0));
+
+        // Aliases can be registered with a custom schema
+        const NS: &str = "http://example.com/ns/alias-test/1.0/";
+        validate::register_schema(validate::XmpSchema::new(NS, "atest2").alias(
+            "Byline",
+            ns::DC,
+            "creator",
+            ValueForm::Seq,
+        ))
+        .unwrap();
+        assert_eq!(
+            meta.get_property(NS, "Byline"),
+            Some(XmpValue::String("John".into()))
+        );
+        let mut meta = XmpMeta::new();
+        meta.set_property(NS, "Byline", "Ann".into()).unwrap();
+        assert_eq!(
+            meta.get_array_item(ns::DC, "creator", 0),
+            Some(XmpValue::String("Ann".into()))
+        );
+    }
 }
@@ -0,0 +1,228 @@
+//! Namespace prefixes of XMP metadata
+//!
+//! Each [`XmpMeta`] has a namespace map of its own, which starts with the
+//! built-in namespaces. Parsing adds the namespaces of the document that are
+//! not registered, so that their properties can be serialized again, and
+//! [`XmpMeta::set_namespace_prefix`] overrides the prefix of a namespace for
+//! one object. Serializing with
+//! [`SerializeOptions::canonical_prefixes`](crate::SerializeOptions::canonical_prefixes)
+//! ignores the overrides, so that every packet uses the same prefixes.
+
+use super::XmpMeta;
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::namespace::{
+    get_global_namespace_prefix, get_global_namespace_uri, is_namespace_registered, ns,
+    NamespaceMap,
+};
+use crate::core::serializer::XmpSerializer;
+
+impl XmpMeta {
+    /// Get the prefix written for a namespace URI
+    ///
+    /// This is the prefix set with
+    /// [`set_namespace_prefix`](Self::set_namespace_prefix) or found when
+    /// parsing, or else the registered one.
+    pub fn namespace_prefix(&self, uri: &str) -> Option<String> {
+        match self.namespaces.get_prefix(uri) {
+            Some(prefix) => Some(prefix.to_string()),
+            None => get_global_namespace_prefix(uri),
+        }
+    }
+
+    /// Set the prefix written for a namespace URI by this object
+    ///
+    /// The prefix can also be used instead of the URI in the other
+    /// methods. The prefixes of `rdf` and `xml` can't be changed.
+    ///
+    /// Fails with `XmpError::BadParam` if the prefix is not a valid XML name
+    /// or is already used for another namespace. The prefixes of the
+    /// built-in namespaces are reserved for them, since parsers don't
+    /// always honor their declarations.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{ns, SerializeOptions, XmpMeta};
+    ///
+    /// let mut meta = XmpMeta::new();
+    /// meta.set_property(ns::XMP, "Label", "Red".into()).unwrap();
+    /// meta.set_namespace_prefix(ns::XMP, "xap").unwrap();
+    /// assert!(meta.serialize().unwrap().contains("xap:Label"));
+    ///
+    /// let options = SerializeOptions::default().canonical_prefixes();
+    /// assert!(meta.serialize_with_options(&options).unwrap().contains("xmp:Label"));
+    /// ```
+    pub fn set_namespace_prefix(&mut self, uri: &str, prefix: &str) -> XmpResult<()> {
+        if uri.is_empty() {
+            return Err(XmpError::BadParam("URI cannot be empty".to_string()));
+        }
+        if uri == ns::RDF || uri == ns::XML {
+            return Err(XmpError::BadParam(format!(
+                "The prefix of '{}' can't be changed",
+                uri
+            )));
+        }
+        if !is_prefix(prefix) {
+            return Err(XmpError::BadParam(format!("Invalid prefix '{}'", prefix)));
+        }
+        if let Some(existing_uri) = self.prefix_uri(prefix) {
+            if existing_uri != uri {
+                return Err(XmpError::BadParam(format!(
+                    "Prefix '{}' is already used for '{}'",
+                    prefix, existing_uri
+                )));
+            }
+        }
+        self.namespaces.set_prefix(uri, prefix);
+        Ok(())
+    }
+
+    /// Get the URI of the namespace that a prefix is written for
+    fn prefix_uri(&self, prefix: &str) -> Option<String> {
+        if let Some(uri) = NamespaceMap::new().get_uri(prefix) {
+            return Some(uri.to_string());
+        }
+        if let Some(uri) = self.namespaces.get_uri(prefix) {
+            return Some(uri.to_string());
+        }
+        // A registered prefix is free if this object writes another one
+        // for its namespace
+        get_global_namespace_uri(prefix).filter(|uri| !self.namespaces.has_uri(uri))
+    }
+
+    /// Convert an internal "ns_uri:name" key to "prefix:name"
+    #[cfg(feature = "wasm")]
+    pub(crate) fn prefixed_key(&self, key: &str) -> String {
+        match key.rsplit_once(':') {
+            Some((uri, name)) => match self.namespace_prefix(uri) {
+                Some(prefix) => format!("{}:{}", prefix, name),
+                None => key.to_string(),
+            },
+            None => key.to_string(),
+        }
+    }
+
+    /// Create a serializer writing the name and the prefixes of this
+    /// object, or the registered prefixes if `canonical`
+    pub(crate) fn serializer(&self, canonical: bool) -> XmpSerializer {
+        let namespaces = if canonical {
+            let mut namespaces = NamespaceMap::new();
+            for (uri, prefix) in self.namespaces.get_all_namespaces() {
+                if !is_namespace_registered(&uri) {
+                    let _ = namespaces.register(&uri, &prefix);
+                }
+            }
+            namespaces
+        } else {
+            self.namespaces.clone()
+        };
+        XmpSerializer::with_namespaces(namespaces).with_about(self.name())
+    }
+}
+
+/// Create the namespace map of metadata parsed with the namespaces of
+/// `parsed`
+///
+/// Only the namespaces that are not registered are kept, so that known
+/// namespaces are written with their usual prefixes. A prefix that is
+/// already used for another namespace gets a `_1_`, `_2_`... suffix, as
+/// with the Adobe XMP Toolkit.
+pub(super) fn document_namespaces(parsed: &NamespaceMap) -> NamespaceMap {
+    let mut namespaces = NamespaceMap::new();
+    let mut declared = parsed.get_all_namespaces();
+    declared.sort();
+    for (uri, prefix) in declared {
+        if namespaces.has_uri(&uri) || is_namespace_registered(&uri) {
+            continue;
+        }
+        let mut unique = prefix.clone();
+        let mut n = 1;
+        while namespaces.has_prefix(&unique) || get_global_namespace_uri(&unique).is_some() {
+            unique = format!("{}_{}_", prefix, n);
+            n += 1;
+        }
+        let _ = namespaces.register(&uri, &unique);
+    }
+    namespaces
+}
+
+/// Check that a prefix is a valid XML name without a colon
+fn is_prefix(prefix: &str) -> bool {
+    let mut chars = prefix.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        && !prefix.to_ascii_lowercase().starts_with("xml")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::namespace::{ns, register_namespace};
+    use crate::core::serializer::SerializeOptions;
+    use crate::XmpMeta;
+
+    #[test]
+    fn test_namespace_prefixes() {
+        let xml = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+            <rdf:Description rdf:about=""
+                xmlns:xap="http://ns.adobe.com/xap/1.0/"
+                xmlns:foo="http://example.com/prefixes/foo/"
+                xap:Label="Red" foo:Bar="Baz"/>
+        </rdf:RDF>"#;
+        let mut meta = XmpMeta::parse(xml).unwrap();
+
+        // Known namespaces are normalized, unknown ones are kept
+        let rdf = meta.serialize().unwrap();
+        assert!(rdf.contains("xmp:Label=\"Red\""));
+        assert!(rdf.contains("xmlns:foo=\"http://example.com/prefixes/foo/\""));
+        assert!(rdf.contains("foo:Bar=\"Baz\""));
+        assert_eq!(
+            meta.namespace_prefix("http://example.com/prefixes/foo/")
+                .as_deref(),
+            Some("foo")
+        );
+
+        meta.set_namespace_prefix(ns::XMP, "xap").unwrap();
+        assert!(meta.set_namespace_prefix(ns::DC, "xap").is_err());
+        assert!(meta.set_namespace_prefix(ns::DC, "foo").is_err());
+        assert!(meta.set_namespace_prefix(ns::RDF, "r").is_err());
+        assert!(meta.set_namespace_prefix(ns::DC, "1dc").is_err());
+        assert!(meta.set_namespace_prefix(ns::DC, "xmp").is_err());
+        meta.set_namespace_prefix(ns::DC, "dcElements").unwrap();
+        meta.set_property("dcElements", "title", "Title".into())
+            .unwrap();
+        assert!(meta.has_property(ns::DC, "title"));
+
+        let packet = meta.serialize_packet().unwrap();
+        assert!(packet.contains("xap:Label=\"Red\""));
+        assert!(packet.contains("dcElements:title"));
+        let reparsed = XmpMeta::parse(&packet).unwrap();
+        assert!(reparsed.has_property(ns::XMP, "Label"));
+        assert!(reparsed.has_property(ns::DC, "title"));
+
+        let options = SerializeOptions::default().canonical_prefixes();
+        let canonical = meta.serialize_with_options(&options).unwrap();
+        assert!(canonical.contains("<xmp:Label>Red</xmp:Label>"));
+        assert!(canonical.contains("<dc:title>"));
+        assert!(canonical.contains("<foo:Bar>Baz</foo:Bar>"));
+        assert!(!canonical.contains("xap:"));
+    }
+
+    #[test]
+    fn test_document_prefix_conflicts() {
+        register_namespace("http://example.com/prefixes/registered/", "prefixTest").unwrap();
+        let xml = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+            <rdf:Description rdf:about=""
+                xmlns:prefixTest="http://example.com/prefixes/other/"
+                prefixTest:One="1"/>
+        </rdf:RDF>"#;
+        let meta = XmpMeta::parse(xml).unwrap();
+        assert_eq!(
+            meta.namespace_prefix("http://example.com/prefixes/other/")
+                .as_deref(),
+            Some("prefixTest_1_")
+        );
+
+        let reparsed = XmpMeta::parse(&meta.serialize().unwrap()).unwrap();
+        assert!(reparsed.has_property("http://example.com/prefixes/other/", "One"));
+    }
+}
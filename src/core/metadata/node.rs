@@ -2,6 +2,11 @@
 //!
 //! This module provides type aliases and helper methods that abstract over
 //! single-threaded (Rc<RefCell<>>) and multi-threaded (Arc<RwLock<>>) implementations.
+//!
+//! The root also keeps a copy of the tree as it was before the first write,
+//! which [`XmpMeta::is_dirty`](super::XmpMeta::is_dirty) compares the tree
+//! with. The copy is only made when the tree is first written to, so
+//! metadata that is only read costs nothing more.
 
 use crate::core::node::StructureNode;
 
@@ -11,12 +16,22 @@ mod impl_ {
     use std::cell::{Ref, RefCell, RefMut};
     use std::rc::Rc;
 
+    /// The tree and its copy from before the first write
+    #[derive(Debug)]
+    pub struct RootCell {
+        node: RefCell<StructureNode>,
+        baseline: RefCell<Option<StructureNode>>,
+    }
+
     /// Single-threaded root node type (zero-cost abstraction)
-    pub type RootNode = Rc<RefCell<StructureNode>>;
+    pub type RootNode = Rc<RootCell>;
 
     /// Create a new root node
     pub fn new_root_node(node: StructureNode) -> RootNode {
-        Rc::new(RefCell::new(node))
+        Rc::new(RootCell {
+            node: RefCell::new(node),
+            baseline: RefCell::new(None),
+        })
     }
 
     /// Read guard for root node
@@ -27,12 +42,27 @@ mod impl_ {
 
     /// Read access to the root node (shared borrow)
     pub fn root_read(root: &RootNode) -> RootReadGuard<'_> {
-        root.borrow()
+        root.node.borrow()
     }
 
     /// Write access to the root node (mutable borrow)
     pub fn root_write(root: &RootNode) -> RootWriteGuard<'_> {
-        root.borrow_mut()
+        let node = root.node.borrow_mut();
+        root.baseline
+            .borrow_mut()
+            .get_or_insert_with(|| node.clone());
+        node
+    }
+
+    /// Get a copy of the tree from before the first write, if it was
+    /// written to
+    pub fn root_baseline(root: &RootNode) -> Option<StructureNode> {
+        root.baseline.borrow().clone()
+    }
+
+    /// Forget the copy of the tree, making the current tree the baseline
+    pub fn root_reset_baseline(root: &RootNode) {
+        root.baseline.borrow_mut().take();
     }
 
     /// Execute a closure with read access to the root node
@@ -49,14 +79,24 @@ mod impl_ {
 #[cfg(feature = "mutli-thread")]
 mod impl_ {
     use super::StructureNode;
-    use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+    use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+    /// The tree and its copy from before the first write
+    #[derive(Debug)]
+    pub struct RootCell {
+        node: RwLock<StructureNode>,
+        baseline: Mutex<Option<StructureNode>>,
+    }
 
     /// Multi-threaded root node type (thread-safe)
-    pub type RootNode = Arc<RwLock<StructureNode>>;
+    pub type RootNode = Arc<RootCell>;
 
     /// Create a new root node
     pub fn new_root_node(node: StructureNode) -> RootNode {
-        Arc::new(RwLock::new(node))
+        Arc::new(RootCell {
+            node: RwLock::new(node),
+            baseline: Mutex::new(None),
+        })
     }
 
     /// Read guard for root node
@@ -69,7 +109,7 @@ mod impl_ {
     pub fn root_read(
         root: &RootNode,
     ) -> Result<RootReadGuard<'_>, std::sync::PoisonError<RwLockReadGuard<'_, StructureNode>>> {
-        root.read()
+        root.node.read()
     }
 
     /// Write access to the root node (exclusive lock)
@@ -77,7 +117,31 @@ mod impl_ {
         root: &RootNode,
     ) -> Result<RootWriteGuard<'_>, std::sync::PoisonError<RwLockWriteGuard<'_, StructureNode>>>
     {
-        root.write()
+        let node = root.node.write()?;
+        // The baseline is only written with the tree locked, so a poisoned
+        // baseline still holds a complete copy
+        root.baseline
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_or_insert_with(|| node.clone());
+        Ok(node)
+    }
+
+    /// Get a copy of the tree from before the first write, if it was
+    /// written to
+    pub fn root_baseline(root: &RootNode) -> Option<StructureNode> {
+        root.baseline
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Forget the copy of the tree, making the current tree the baseline
+    pub fn root_reset_baseline(root: &RootNode) {
+        root.baseline
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
     }
 
     /// Execute a closure with read access to the root node
@@ -94,4 +158,7 @@ mod impl_ {
     }
 }
 
-pub use impl_::{new_root_node, root_read, root_read_with, root_write, RootNode};
+pub use impl_::{
+    new_root_node, root_baseline, root_read, root_read_with, root_reset_baseline, root_write,
+    RootNode,
+};
@@ -0,0 +1,157 @@
+//! Ratings and labels (`xmp:Rating`, `xmp:Label`)
+//!
+//! `xmp:Rating` is a number of stars from 1 to 5, with 0 for unrated and
+//! -1 for rejected. Windows also writes `MicrosoftPhoto:Rating`, a
+//! percentage (1, 25, 50, 75 or 99 for 1 to 5 stars), and some files only
+//! have that one. [`XmpMeta::get_rating`] reads either, and
+//! [`XmpMeta::set_rating`] keeps an existing percentage in step with the
+//! stars.
+//!
+//! # Example
+//!
+//! ```rust
+//! use xmpkit::{ns, XmpMeta};
+//!
+//! let mut meta = XmpMeta::new();
+//! meta.set_property(ns::MICROSOFT_PHOTO, "Rating", "75".into()).unwrap();
+//! assert_eq!(meta.get_rating(), Some(4));
+//!
+//! meta.set_rating(5).unwrap();
+//! meta.set_label("Red").unwrap();
+//! assert_eq!(meta.get_property_i64(ns::XMP, "Rating"), Some(5));
+//! assert_eq!(meta.get_property_i64(ns::MICROSOFT_PHOTO, "Rating"), Some(99));
+//! assert_eq!(meta.get_label().as_deref(), Some("Red"));
+//! ```
+
+use super::XmpMeta;
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::namespace::ns;
+use crate::types::value::XmpValue;
+
+/// Percentages of `MicrosoftPhoto:Rating` written for 1 to 5 stars
+const STAR_PERCENTS: [i64; 5] = [1, 25, 50, 75, 99];
+
+impl XmpMeta {
+    /// Get the rating: -1 for rejected, 0 for unrated, or 1 to 5 stars
+    ///
+    /// `xmp:Rating` is rounded to whole stars. Without it, the
+    /// `MicrosoftPhoto:Rating` percentage is converted to stars. Returns
+    /// `None` if neither is set or valid.
+    pub fn get_rating(&self) -> Option<i8> {
+        if let Some(rating) = self.get_property_f64(ns::XMP, "Rating") {
+            let rating = rating.round();
+            return (-1.0..=5.0).contains(&rating).then_some(rating as i8);
+        }
+        match self.get_property_i64(ns::MICROSOFT_PHOTO, "Rating")? {
+            0 => Some(0),
+            1..=12 => Some(1),
+            13..=37 => Some(2),
+            38..=62 => Some(3),
+            63..=87 => Some(4),
+            88..=100 => Some(5),
+            _ => None,
+        }
+    }
+
+    /// Set `xmp:Rating`: -1 for rejected, 0 for unrated, or 1 to 5 stars
+    ///
+    /// An existing `MicrosoftPhoto:Rating` is updated to the matching
+    /// percentage, or removed for a rejected rating, which it can't
+    /// express.
+    ///
+    /// Fails with `XmpError::BadValue` if the rating is out of range.
+    pub fn set_rating(&mut self, rating: i8) -> XmpResult<()> {
+        if !(-1..=5).contains(&rating) {
+            return Err(XmpError::BadValue(format!(
+                "Rating {} is not between -1 and 5",
+                rating
+            )));
+        }
+        self.set_property(ns::XMP, "Rating", XmpValue::Integer(rating.into()))?;
+        if self.does_property_exist(ns::MICROSOFT_PHOTO, "Rating") {
+            match rating {
+                -1 => self.delete_property(ns::MICROSOFT_PHOTO, "Rating")?,
+                0 => self.set_property(ns::MICROSOFT_PHOTO, "Rating", XmpValue::Integer(0))?,
+                stars => self.set_property(
+                    ns::MICROSOFT_PHOTO,
+                    "Rating",
+                    XmpValue::Integer(STAR_PERCENTS[stars as usize - 1]),
+                )?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Get `xmp:Label`, such as "Red" or "Approved"
+    pub fn get_label(&self) -> Option<String> {
+        match self.get_property(ns::XMP, "Label")? {
+            XmpValue::String(label) if !label.is_empty() => Some(label),
+            _ => None,
+        }
+    }
+
+    /// Set `xmp:Label`, or remove it if `label` is empty
+    pub fn set_label(&mut self, label: &str) -> XmpResult<()> {
+        if label.is_empty() {
+            self.delete_property(ns::XMP, "Label")
+        } else {
+            self.set_property(ns::XMP, "Label", label.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rating_bridging() {
+        let mut meta = XmpMeta::new();
+        assert_eq!(meta.get_rating(), None);
+
+        // Without an existing percentage, only xmp:Rating is written
+        meta.set_rating(3).unwrap();
+        assert_eq!(meta.get_rating(), Some(3));
+        assert!(!meta.does_property_exist(ns::MICROSOFT_PHOTO, "Rating"));
+        assert!(meta.set_rating(6).is_err());
+        assert!(meta.set_rating(-2).is_err());
+
+        meta.set_property(ns::XMP, "Rating", "4.6".into()).unwrap();
+        assert_eq!(meta.get_rating(), Some(5));
+        meta.set_property(ns::XMP, "Rating", "7".into()).unwrap();
+        assert_eq!(meta.get_rating(), None);
+
+        // Percentages are read when xmp:Rating is missing
+        meta.delete_property(ns::XMP, "Rating").unwrap();
+        for (percent, stars) in [(0, 0), (1, 1), (25, 2), (50, 3), (63, 4), (100, 5)] {
+            meta.set_property(ns::MICROSOFT_PHOTO, "Rating", XmpValue::Integer(percent))
+                .unwrap();
+            assert_eq!(meta.get_rating(), Some(stars), "{}", percent);
+        }
+        meta.set_property(ns::MICROSOFT_PHOTO, "Rating", "150".into())
+            .unwrap();
+        assert_eq!(meta.get_rating(), None);
+
+        meta.set_rating(2).unwrap();
+        assert_eq!(
+            meta.get_property_i64(ns::MICROSOFT_PHOTO, "Rating"),
+            Some(25)
+        );
+        meta.set_rating(-1).unwrap();
+        assert_eq!(meta.get_rating(), Some(-1));
+        assert!(!meta.does_property_exist(ns::MICROSOFT_PHOTO, "Rating"));
+
+        let packet = meta.serialize_packet().unwrap();
+        assert_eq!(XmpMeta::parse(&packet).unwrap().get_rating(), Some(-1));
+    }
+
+    #[test]
+    fn test_label() {
+        let mut meta = XmpMeta::new();
+        assert_eq!(meta.get_label(), None);
+        meta.set_label("Approved").unwrap();
+        assert_eq!(meta.get_label().as_deref(), Some("Approved"));
+        meta.set_label("").unwrap();
+        assert!(!meta.does_property_exist(ns::XMP, "Label"));
+    }
+}
@@ -0,0 +1,428 @@
+//! Image regions (`mwg-rs:Regions`), such as tagged faces
+//!
+//! The Metadata Working Group regions schema describes areas of an image:
+//! `mwg-rs:Regions` is a structure with the `mwg-rs:AppliedToDimensions` of
+//! the image the regions were set on, and a `mwg-rs:RegionList` bag of
+//! regions. Each region has an `mwg-rs:Area`, an `stArea` structure giving
+//! the center, width and height of the area as fractions of the image
+//! size, and optionally a `mwg-rs:Type` ("Face", "Pet", "Focus" or
+//! "BarCode"), a `mwg-rs:Name` and a `mwg-rs:Description`.
+//!
+//! Windows writes the people it tags in `MP:RegionInfo` instead, with
+//! rectangles given by their top left corner.
+//! [`XmpMeta::microsoft_regions`] converts them to MWG regions.
+//!
+//! # Example
+//!
+//! ```rust
+//! use xmpkit::core::{Region, RegionArea, RegionInfo, RegionType};
+//! use xmpkit::XmpMeta;
+//!
+//! let mut meta = XmpMeta::new();
+//! let face = Region {
+//!     area: RegionArea::from_top_left(0.25, 0.5, 0.5, 0.25),
+//!     region_type: Some(RegionType::Face),
+//!     name: Some("Alice".to_string()),
+//!     description: None,
+//! };
+//! meta.set_regions(&RegionInfo::new(vec![face.clone()])).unwrap();
+//!
+//! let regions = meta.regions().unwrap();
+//! assert_eq!(regions.regions, [face]);
+//! assert_eq!(regions.regions[0].area.x, 0.5);
+//! assert_eq!(regions.regions[0].area.left(), 0.25);
+//! ```
+
+use super::XmpMeta;
+use crate::core::error::XmpResult;
+use crate::core::namespace::ns;
+use crate::core::node::{ArrayNode, ArrayType, Node, StructureNode};
+use crate::types::value::XmpValue;
+use crate::utils::convert::{convert_from_float, convert_to_float};
+
+/// Unit of the areas of MWG regions
+const NORMALIZED: &str = "normalized";
+
+/// The regions of an image (`mwg-rs:Regions`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RegionInfo {
+    /// Size of the image the regions were set on
+    pub applied_to_dimensions: Option<Dimensions>,
+    /// The regions
+    pub regions: Vec<Region>,
+}
+
+impl RegionInfo {
+    /// Create regions without the size of the image
+    pub fn new(regions: Vec<Region>) -> Self {
+        Self {
+            applied_to_dimensions: None,
+            regions,
+        }
+    }
+}
+
+/// Size of an image (`stDim:Dimensions`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dimensions {
+    /// Width of the image
+    pub width: f64,
+    /// Height of the image
+    pub height: f64,
+    /// Unit of the size, such as "pixel"
+    pub unit: String,
+}
+
+impl Dimensions {
+    /// Create a size in pixels
+    pub fn pixels(width: u32, height: u32) -> Self {
+        Self {
+            width: width.into(),
+            height: height.into(),
+            unit: "pixel".to_string(),
+        }
+    }
+}
+
+/// A region of an image (an item of `mwg-rs:RegionList`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    /// Area of the region
+    pub area: RegionArea,
+    /// What the region shows
+    pub region_type: Option<RegionType>,
+    /// Name of the region, such as the name of a person
+    pub name: Option<String>,
+    /// Description of the region
+    pub description: Option<String>,
+}
+
+/// Area of a region (`stArea:Area`)
+///
+/// The position is the center of the area. With the "normalized" unit of
+/// MWG regions, all values are fractions of the image size, from the top
+/// left corner of the image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionArea {
+    /// Horizontal position of the center
+    pub x: f64,
+    /// Vertical position of the center
+    pub y: f64,
+    /// Width of the area, 0 for a point
+    pub width: f64,
+    /// Height of the area, 0 for a point
+    pub height: f64,
+    /// Unit of the values, "normalized" for MWG regions
+    pub unit: String,
+}
+
+impl RegionArea {
+    /// Create a normalized area from its top left corner and size
+    pub fn from_top_left(left: f64, top: f64, width: f64, height: f64) -> Self {
+        Self {
+            x: left + width / 2.0,
+            y: top + height / 2.0,
+            width,
+            height,
+            unit: NORMALIZED.to_string(),
+        }
+    }
+
+    /// Horizontal position of the left edge
+    pub fn left(&self) -> f64 {
+        self.x - self.width / 2.0
+    }
+
+    /// Vertical position of the top edge
+    pub fn top(&self) -> f64 {
+        self.y - self.height / 2.0
+    }
+}
+
+/// What a region shows (`mwg-rs:Type`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegionType {
+    /// A human face
+    Face,
+    /// An animal face
+    Pet,
+    /// The area a camera focused on
+    Focus,
+    /// A one or two dimensional barcode
+    BarCode,
+    /// Another type
+    Other(String),
+}
+
+impl RegionType {
+    /// Get the `mwg-rs:Type` value of the type
+    pub fn as_str(&self) -> &str {
+        match self {
+            RegionType::Face => "Face",
+            RegionType::Pet => "Pet",
+            RegionType::Focus => "Focus",
+            RegionType::BarCode => "BarCode",
+            RegionType::Other(other) => other,
+        }
+    }
+
+    /// Get the type of a `mwg-rs:Type` value
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "Face" => RegionType::Face,
+            "Pet" => RegionType::Pet,
+            "Focus" => RegionType::Focus,
+            "BarCode" => RegionType::BarCode,
+            other => RegionType::Other(other.to_string()),
+        }
+    }
+}
+
+impl XmpMeta {
+    /// Get the MWG regions of `mwg-rs:Regions`
+    ///
+    /// Regions without a valid area are skipped. Returns `None` if there is
+    /// no `mwg-rs:Regions`.
+    pub fn regions(&self) -> Option<RegionInfo> {
+        let text = |path: &str| match self.get_property_by_path(ns::MWG_RS, path)? {
+            XmpValue::String(s) => Some(s),
+            _ => None,
+        };
+        let number = |path: &str| convert_to_float(&text(path)?).ok();
+        if !self.does_property_exist(ns::MWG_RS, "Regions") {
+            return None;
+        }
+
+        let dimensions = "Regions/mwg-rs:AppliedToDimensions/stDim:";
+        let applied_to_dimensions = match (
+            number(&format!("{}w", dimensions)),
+            number(&format!("{}h", dimensions)),
+        ) {
+            (Some(width), Some(height)) => Some(Dimensions {
+                width,
+                height,
+                unit: text(&format!("{}unit", dimensions)).unwrap_or_default(),
+            }),
+            _ => None,
+        };
+
+        let mut regions = Vec::new();
+        for i in 1.. {
+            let item = format!("Regions/mwg-rs:RegionList[{}]", i);
+            if text(&item).is_none() {
+                break;
+            }
+            let area = |name: &str| number(&format!("{}/mwg-rs:Area/stArea:{}", item, name));
+            let (Some(x), Some(y)) = (area("x"), area("y")) else {
+                continue;
+            };
+            let field = |name: &str| text(&format!("{}/mwg-rs:{}", item, name));
+            regions.push(Region {
+                area: RegionArea {
+                    x,
+                    y,
+                    width: area("w").unwrap_or(0.0),
+                    height: area("h").unwrap_or(0.0),
+                    unit: text(&format!("{}/mwg-rs:Area/stArea:unit", item))
+                        .unwrap_or_else(|| NORMALIZED.to_string()),
+                },
+                region_type: field("Type").map(|t| RegionType::parse(&t)),
+                name: field("Name"),
+                description: field("Description"),
+            });
+        }
+        Some(RegionInfo {
+            applied_to_dimensions,
+            regions,
+        })
+    }
+
+    /// Replace `mwg-rs:Regions`
+    ///
+    /// Fails with `XmpError::BadValue`, without changing anything, if a
+    /// number is not finite.
+    pub fn set_regions(&mut self, info: &RegionInfo) -> XmpResult<()> {
+        let mut regions = StructureNode::new();
+        if let Some(dimensions) = &info.applied_to_dimensions {
+            let mut node = StructureNode::new();
+            set_field(
+                &mut node,
+                ns::ST_DIM,
+                "w",
+                convert_from_float(dimensions.width)?,
+            );
+            set_field(
+                &mut node,
+                ns::ST_DIM,
+                "h",
+                convert_from_float(dimensions.height)?,
+            );
+            set_field(&mut node, ns::ST_DIM, "unit", dimensions.unit.clone());
+            regions.set_field(
+                format!("{}:AppliedToDimensions", ns::MWG_RS),
+                Node::Structure(node),
+            );
+        }
+
+        let mut list = ArrayNode::new(ArrayType::Unordered);
+        for region in &info.regions {
+            let mut area = StructureNode::new();
+            let values = [
+                ("x", region.area.x),
+                ("y", region.area.y),
+                ("w", region.area.width),
+                ("h", region.area.height),
+            ];
+            for (name, value) in values {
+                set_field(&mut area, ns::ST_AREA, name, convert_from_float(value)?);
+            }
+            set_field(&mut area, ns::ST_AREA, "unit", region.area.unit.clone());
+
+            let mut node = StructureNode::new();
+            node.set_field(format!("{}:Area", ns::MWG_RS), Node::Structure(area));
+            if let Some(region_type) = &region.region_type {
+                set_field(
+                    &mut node,
+                    ns::MWG_RS,
+                    "Type",
+                    region_type.as_str().to_string(),
+                );
+            }
+            if let Some(name) = &region.name {
+                set_field(&mut node, ns::MWG_RS, "Name", name.clone());
+            }
+            if let Some(description) = &region.description {
+                set_field(&mut node, ns::MWG_RS, "Description", description.clone());
+            }
+            list.append(Node::Structure(node));
+        }
+        regions.set_field(format!("{}:RegionList", ns::MWG_RS), Node::Array(list));
+
+        root_write!(self.root)
+            .set_field(format!("{}:Regions", ns::MWG_RS), Node::Structure(regions));
+        Ok(())
+    }
+
+    /// Get the Microsoft People Tags of `MP:RegionInfo` as MWG regions
+    ///
+    /// The rectangles of `MPReg:Rectangle` ("left, top, width, height")
+    /// become normalized areas, and `MPReg:PersonDisplayName` the names of
+    /// face regions. Regions with a malformed rectangle are skipped.
+    /// Returns `None` if there is no `MP:RegionInfo`.
+    pub fn microsoft_regions(&self) -> Option<RegionInfo> {
+        if !self.does_property_exist(ns::MP, "RegionInfo") {
+            return None;
+        }
+        let text = |path: &str| match self.get_property_by_path(ns::MP, path)? {
+            XmpValue::String(s) => Some(s),
+            _ => None,
+        };
+
+        let mut regions = Vec::new();
+        for i in 1.. {
+            let item = format!("RegionInfo/MPRI:Regions[{}]", i);
+            if text(&item).is_none() {
+                break;
+            }
+            let Some(rectangle) = text(&format!("{}/MPReg:Rectangle", item)) else {
+                continue;
+            };
+            let values: Vec<f64> = rectangle
+                .split(',')
+                .filter_map(|value| convert_to_float(value).ok())
+                .collect();
+            let &[left, top, width, height] = values.as_slice() else {
+                continue;
+            };
+            regions.push(Region {
+                area: RegionArea::from_top_left(left, top, width, height),
+                region_type: Some(RegionType::Face),
+                name: text(&format!("{}/MPReg:PersonDisplayName", item)),
+                description: None,
+            });
+        }
+        Some(RegionInfo::new(regions))
+    }
+}
+
+/// Set a simple field of a structure
+fn set_field(node: &mut StructureNode, namespace: &str, name: &str, value: String) {
+    node.set_field(format!("{}:{}", namespace, name), Node::simple(value));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regions_round_trip() {
+        let mut meta = XmpMeta::new();
+        assert_eq!(meta.regions(), None);
+
+        let info = RegionInfo {
+            applied_to_dimensions: Some(Dimensions::pixels(4000, 3000)),
+            regions: vec![
+                Region {
+                    area: RegionArea::from_top_left(0.1, 0.2, 0.3, 0.4),
+                    region_type: Some(RegionType::Face),
+                    name: Some("Bob".to_string()),
+                    description: Some("On the left".to_string()),
+                },
+                Region {
+                    area: RegionArea::from_top_left(0.5, 0.5, 0.0, 0.0),
+                    region_type: Some(RegionType::Other("Sign".to_string())),
+                    name: None,
+                    description: None,
+                },
+            ],
+        };
+        meta.set_regions(&info).unwrap();
+
+        let packet = meta.serialize_packet().unwrap();
+        assert!(packet.contains("<mwg-rs:RegionList>"));
+        assert!(packet.contains("<rdf:Bag>"));
+        assert!(packet.contains("stArea:unit"));
+        let parsed = XmpMeta::parse(&packet).unwrap();
+        assert_eq!(parsed.regions(), Some(info));
+
+        let mut bad = RegionInfo::new(vec![]);
+        bad.applied_to_dimensions = Some(Dimensions {
+            width: f64::NAN,
+            height: 1.0,
+            unit: "pixel".to_string(),
+        });
+        assert!(meta.set_regions(&bad).is_err());
+        assert!(meta.regions().unwrap().applied_to_dimensions.is_some());
+    }
+
+    #[test]
+    fn test_microsoft_regions() {
+        let xml = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+            <rdf:Description rdf:about=""
+                xmlns:MP="http://ns.microsoft.com/photo/1.2/"
+                xmlns:MPRI="http://ns.microsoft.com/photo/1.2/t/RegionInfo#"
+                xmlns:MPReg="http://ns.microsoft.com/photo/1.2/t/Region#">
+                <MP:RegionInfo rdf:parseType="Resource">
+                    <MPRI:Regions>
+                        <rdf:Bag>
+                            <rdf:li MPReg:Rectangle="0.25, 0.5, 0.5, 0.25"
+                                MPReg:PersonDisplayName="Alice"/>
+                            <rdf:li MPReg:Rectangle="bad"/>
+                        </rdf:Bag>
+                    </MPRI:Regions>
+                </MP:RegionInfo>
+            </rdf:Description>
+        </rdf:RDF>"#;
+        let meta = XmpMeta::parse(xml).unwrap();
+        assert_eq!(meta.regions(), None);
+
+        let info = meta.microsoft_regions().unwrap();
+        assert_eq!(info.regions.len(), 1);
+        let region = &info.regions[0];
+        assert_eq!((region.area.x, region.area.y), (0.5, 0.625));
+        assert_eq!((region.area.left(), region.area.top()), (0.25, 0.5));
+        assert_eq!(region.area.unit, "normalized");
+        assert_eq!(region.region_type, Some(RegionType::Face));
+        assert_eq!(region.name.as_deref(), Some("Alice"));
+    }
+}
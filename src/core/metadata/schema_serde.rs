@@ -0,0 +1,964 @@
+//! Mapping between Rust types and the properties of a schema (serde)
+//!
+//! [`XmpMeta::serialize_schema`] writes a value implementing `Serialize` as
+//! the properties of a namespace, and [`XmpMeta::deserialize_schema`] reads
+//! them back into a type implementing `Deserialize`:
+//! - the fields of a struct (or the entries of a map) are properties, and
+//!   the fields of nested structs and maps are structure fields, all in the
+//!   given namespace
+//! - strings, numbers, booleans (`True`/`False`) and unit enum variants are
+//!   simple values
+//! - sequences (`Vec`, slices, tuples) are arrays: `rdf:Seq` unless the
+//!   property is defined as a Bag or an Alt by a built-in or registered
+//!   schema (see [`validate`]) or the value is wrapped in [`Bag`], [`Seq`]
+//!   or [`Alt`]
+//! - strings of language alternatives (such as `dc:title`) are their
+//!   `x-default` item
+//! - `None` values are skipped, so the properties are left unchanged
+//!
+//! Properties of registered custom schemas are checked before anything is
+//! written, as with the other setters of [`XmpMeta`].
+
+use super::{array_form, item_lang, lang_item, XmpMeta};
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::node::{ArrayNode, ArrayType, Node, StructureNode};
+use crate::core::validate::{self, ValueForm};
+use crate::utils::convert;
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use serde::ser::{self, Impossible, Serialize};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// Newtype names marking the array form of a sequence
+const BAG: &str = "$xmpkit::Bag";
+const SEQ: &str = "$xmpkit::Seq";
+const ALT: &str = "$xmpkit::Alt";
+
+macro_rules! array_wrapper {
+    ($name:ident, $marker:ident, $form:literal) => {
+        #[doc = concat!("A sequence serialized as an `rdf:", $form, "` array")]
+        ///
+        /// Any array form is accepted when deserializing.
+        #[derive(Debug, Clone, Default, PartialEq, Eq)]
+        pub struct $name<T>(pub Vec<T>);
+
+        impl<T> Deref for $name<T> {
+            type Target = Vec<T>;
+
+            fn deref(&self) -> &Vec<T> {
+                &self.0
+            }
+        }
+
+        impl<T> DerefMut for $name<T> {
+            fn deref_mut(&mut self) -> &mut Vec<T> {
+                &mut self.0
+            }
+        }
+
+        impl<T> From<Vec<T>> for $name<T> {
+            fn from(items: Vec<T>) -> Self {
+                Self(items)
+            }
+        }
+
+        impl<T: Serialize> Serialize for $name<T> {
+            fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_newtype_struct($marker, &self.0)
+            }
+        }
+
+        impl<'de, T: de::Deserialize<'de>> de::Deserialize<'de> for $name<T> {
+            fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                Vec::deserialize(deserializer).map(Self)
+            }
+        }
+    };
+}
+
+array_wrapper!(Bag, BAG, "Bag");
+array_wrapper!(Seq, SEQ, "Seq");
+array_wrapper!(Alt, ALT, "Alt");
+
+impl ser::Error for XmpError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        XmpError::SerializationError(msg.to_string())
+    }
+}
+
+impl de::Error for XmpError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        XmpError::BadValue(msg.to_string())
+    }
+}
+
+impl XmpMeta {
+    /// Set the properties of a namespace from a serializable value
+    ///
+    /// `value` must serialize as a struct or a map; see the
+    /// [module documentation](self) for how values are mapped. Existing
+    /// properties that `value` doesn't set are kept.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde::{Deserialize, Serialize};
+    /// use xmpkit::{ns, XmpMeta};
+    ///
+    /// #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    /// struct Dc {
+    ///     title: String,
+    ///     creator: Vec<String>,
+    ///     subject: Vec<String>,
+    /// }
+    ///
+    /// let dc = Dc {
+    ///     title: "Sunset".to_string(),
+    ///     creator: vec!["Jane".to_string()],
+    ///     subject: vec!["sky".to_string(), "sea".to_string()],
+    /// };
+    /// let mut meta = XmpMeta::new();
+    /// meta.serialize_schema(&dc, ns::DC).unwrap();
+    /// assert_eq!(meta.count_array_items(ns::DC, "subject"), 2);
+    /// assert_eq!(meta.deserialize_schema::<Dc>(ns::DC).unwrap(), dc);
+    /// ```
+    pub fn serialize_schema<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+        namespace: &str,
+    ) -> XmpResult<()> {
+        let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+        let node = value.serialize(NodeSerializer {
+            ns_uri: &ns_uri,
+            form: None,
+            top_level: true,
+        })?;
+        let Some(Node::Structure(structure)) = node else {
+            return Err(XmpError::BadParam(
+                "Value must serialize as a struct or a map".to_string(),
+            ));
+        };
+
+        let prefix_len = ns_uri.len() + 1;
+        for (key, node) in &structure.fields {
+            check_custom_node(&ns_uri, &key[prefix_len..], node)?;
+        }
+        let mut root = root_write!(self.root);
+        for (key, node) in structure.fields {
+            root.set_field(key, node);
+        }
+        Ok(())
+    }
+
+    /// Read the properties of a namespace into a deserializable value
+    ///
+    /// See the [module documentation](self) for how values are mapped.
+    /// Missing properties are missing fields, so they must be `Option` or
+    /// have a default. Fails with `XmpError::BadValue` if a property can't
+    /// be converted to the type of its field.
+    pub fn deserialize_schema<T: DeserializeOwned>(&self, namespace: &str) -> XmpResult<T> {
+        let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+        let root = root_read!(self.root);
+        T::deserialize(StructureDeserializer {
+            structure: &root,
+            ns_uri: &ns_uri,
+        })
+    }
+}
+
+/// Check a top-level node against the custom schema of its namespace
+fn check_custom_node(ns_uri: &str, name: &str, node: &Node) -> XmpResult<()> {
+    match node {
+        Node::Simple(simple) => validate::check_custom_property(
+            ns_uri,
+            name,
+            Some(ValueForm::Simple),
+            Some(&simple.value),
+        ),
+        Node::Array(array) => {
+            let form = array_form(array.array_type);
+            validate::check_custom_property(ns_uri, name, Some(form), None)?;
+            for item in &array.items {
+                let value = item.as_simple().map(|simple| simple.value.as_str());
+                validate::check_custom_property(ns_uri, name, None, value)?;
+            }
+            Ok(())
+        }
+        Node::Structure(_) => {
+            validate::check_custom_property(ns_uri, name, Some(ValueForm::Struct), None)
+        }
+    }
+}
+
+fn unsupported(what: &str) -> XmpError {
+    XmpError::NotSupported(format!("Can't map {} to XMP", what))
+}
+
+/// Serializer of a value into a node (`None` for values that are skipped)
+struct NodeSerializer<'a> {
+    ns_uri: &'a str,
+    /// Form of the property, if defined by its schema or a wrapper
+    form: Option<ValueForm>,
+    /// Whether the value is the whole schema
+    top_level: bool,
+}
+
+impl<'a> NodeSerializer<'a> {
+    fn simple(value: impl ToString) -> XmpResult<Option<Node>> {
+        Ok(Some(Node::simple(value.to_string())))
+    }
+
+    fn structure(self) -> StructSerializer<'a> {
+        StructSerializer {
+            ns_uri: self.ns_uri,
+            top_level: self.top_level,
+            structure: StructureNode::new(),
+            key: None,
+        }
+    }
+}
+
+impl<'a> ser::Serializer for NodeSerializer<'a> {
+    type Ok = Option<Node>;
+    type Error = XmpError;
+    type SerializeSeq = SeqSerializer<'a>;
+    type SerializeTuple = SeqSerializer<'a>;
+    type SerializeTupleStruct = SeqSerializer<'a>;
+    type SerializeTupleVariant = Impossible<Option<Node>, XmpError>;
+    type SerializeMap = StructSerializer<'a>;
+    type SerializeStruct = StructSerializer<'a>;
+    type SerializeStructVariant = Impossible<Option<Node>, XmpError>;
+
+    fn serialize_bool(self, v: bool) -> XmpResult<Option<Node>> {
+        Self::simple(convert::convert_from_bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> XmpResult<Option<Node>> {
+        Self::simple(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> XmpResult<Option<Node>> {
+        Self::simple(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> XmpResult<Option<Node>> {
+        Self::simple(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> XmpResult<Option<Node>> {
+        Self::simple(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> XmpResult<Option<Node>> {
+        Self::simple(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> XmpResult<Option<Node>> {
+        Self::simple(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> XmpResult<Option<Node>> {
+        Self::simple(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> XmpResult<Option<Node>> {
+        Self::simple(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> XmpResult<Option<Node>> {
+        Self::simple(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> XmpResult<Option<Node>> {
+        Self::simple(v)
+    }
+
+    fn serialize_char(self, v: char) -> XmpResult<Option<Node>> {
+        Self::simple(v)
+    }
+
+    fn serialize_str(self, v: &str) -> XmpResult<Option<Node>> {
+        Self::simple(v)
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> XmpResult<Option<Node>> {
+        Err(unsupported("bytes"))
+    }
+
+    fn serialize_none(self) -> XmpResult<Option<Node>> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> XmpResult<Option<Node>> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> XmpResult<Option<Node>> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> XmpResult<Option<Node>> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> XmpResult<Option<Node>> {
+        Self::simple(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> XmpResult<Option<Node>> {
+        let form = match name {
+            BAG => Some(ValueForm::Bag),
+            SEQ => Some(ValueForm::Seq),
+            ALT => Some(ValueForm::Alt),
+            _ => return value.serialize(self),
+        };
+        value.serialize(NodeSerializer { form, ..self })
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> XmpResult<Option<Node>> {
+        Err(unsupported("enum variants with data"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> XmpResult<SeqSerializer<'a>> {
+        let array_type = match self.form {
+            Some(ValueForm::Bag) => ArrayType::Unordered,
+            Some(ValueForm::Alt | ValueForm::LangAlt) => ArrayType::Alternative,
+            _ => ArrayType::Ordered,
+        };
+        Ok(SeqSerializer {
+            ns_uri: self.ns_uri,
+            array: ArrayNode::new(array_type),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> XmpResult<SeqSerializer<'a>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> XmpResult<SeqSerializer<'a>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> XmpResult<Self::SerializeTupleVariant> {
+        Err(unsupported("enum variants with data"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> XmpResult<StructSerializer<'a>> {
+        Ok(self.structure())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> XmpResult<StructSerializer<'a>> {
+        Ok(self.structure())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> XmpResult<Self::SerializeStructVariant> {
+        Err(unsupported("enum variants with data"))
+    }
+}
+
+/// Serializer of sequences into arrays
+struct SeqSerializer<'a> {
+    ns_uri: &'a str,
+    array: ArrayNode,
+}
+
+impl SeqSerializer<'_> {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> XmpResult<()> {
+        let item = value.serialize(NodeSerializer {
+            ns_uri: self.ns_uri,
+            form: None,
+            top_level: false,
+        })?;
+        if let Some(item) = item {
+            self.array.append(item);
+        }
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer<'_> {
+    type Ok = Option<Node>;
+    type Error = XmpError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> XmpResult<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> XmpResult<Option<Node>> {
+        Ok(Some(Node::Array(self.array)))
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer<'_> {
+    type Ok = Option<Node>;
+    type Error = XmpError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> XmpResult<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> XmpResult<Option<Node>> {
+        Ok(Some(Node::Array(self.array)))
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer<'_> {
+    type Ok = Option<Node>;
+    type Error = XmpError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> XmpResult<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> XmpResult<Option<Node>> {
+        Ok(Some(Node::Array(self.array)))
+    }
+}
+
+/// Serializer of structs and maps into structures
+struct StructSerializer<'a> {
+    ns_uri: &'a str,
+    top_level: bool,
+    structure: StructureNode,
+    /// Pending map key
+    key: Option<String>,
+}
+
+impl StructSerializer<'_> {
+    fn field<T: Serialize + ?Sized>(&mut self, name: &str, value: &T) -> XmpResult<()> {
+        let form = if self.top_level {
+            validate::known_form(self.ns_uri, name)
+        } else {
+            None
+        };
+        let serializer = NodeSerializer {
+            ns_uri: self.ns_uri,
+            form,
+            top_level: false,
+        };
+        let node = match (form, value.serialize(serializer)?) {
+            (_, None) => return Ok(()),
+            (Some(ValueForm::LangAlt), Some(Node::Simple(simple))) => {
+                let mut array = ArrayNode::new(ArrayType::Alternative);
+                array.append(lang_item("x-default", &simple.value));
+                Node::Array(array)
+            }
+            (_, Some(node)) => node,
+        };
+        self.structure
+            .set_field(format!("{}:{}", self.ns_uri, name), node);
+        Ok(())
+    }
+
+    fn end(self) -> XmpResult<Option<Node>> {
+        Ok(Some(Node::Structure(self.structure)))
+    }
+}
+
+impl ser::SerializeMap for StructSerializer<'_> {
+    type Ok = Option<Node>;
+    type Error = XmpError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> XmpResult<()> {
+        let key = key.serialize(NodeSerializer {
+            ns_uri: self.ns_uri,
+            form: None,
+            top_level: false,
+        })?;
+        match key {
+            Some(Node::Simple(simple)) => {
+                self.key = Some(simple.value);
+                Ok(())
+            }
+            _ => Err(XmpError::BadParam(
+                "Map keys must be simple values".to_string(),
+            )),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> XmpResult<()> {
+        let key = self.key.take().ok_or_else(|| {
+            XmpError::InternalError("Map value serialized before its key".to_string())
+        })?;
+        self.field(&key, value)
+    }
+
+    fn end(self) -> XmpResult<Option<Node>> {
+        StructSerializer::end(self)
+    }
+}
+
+impl ser::SerializeStruct for StructSerializer<'_> {
+    type Ok = Option<Node>;
+    type Error = XmpError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> XmpResult<()> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> XmpResult<Option<Node>> {
+        StructSerializer::end(self)
+    }
+}
+
+/// Deserializer of the fields of a structure (or of the root) in a namespace
+struct StructureDeserializer<'a> {
+    structure: &'a StructureNode,
+    ns_uri: &'a str,
+}
+
+impl<'de> de::Deserializer<'de> for StructureDeserializer<'de> {
+    type Error = XmpError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> XmpResult<V::Value> {
+        visitor.visit_map(FieldAccess::new(self.structure, self.ns_uri))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Access to the fields of a structure in a namespace, sorted by name
+struct FieldAccess<'a> {
+    fields: std::vec::IntoIter<(&'a str, &'a Node)>,
+    ns_uri: &'a str,
+    value: Option<&'a Node>,
+}
+
+impl<'a> FieldAccess<'a> {
+    fn new(structure: &'a StructureNode, ns_uri: &'a str) -> Self {
+        let mut fields: Vec<(&str, &Node)> = structure
+            .fields
+            .iter()
+            .filter_map(|(key, node)| {
+                let name = key.strip_prefix(ns_uri)?.strip_prefix(':')?;
+                Some((name, node))
+            })
+            .collect();
+        fields.sort_by_key(|(name, _)| *name);
+        Self {
+            fields: fields.into_iter(),
+            ns_uri,
+            value: None,
+        }
+    }
+}
+
+impl<'de> de::MapAccess<'de> for FieldAccess<'de> {
+    type Error = XmpError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> XmpResult<Option<K::Value>> {
+        let Some((name, node)) = self.fields.next() else {
+            return Ok(None);
+        };
+        self.value = Some(node);
+        seed.deserialize(de::value::BorrowedStrDeserializer::new(name))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> XmpResult<V::Value> {
+        let node = self.value.take().ok_or_else(|| {
+            XmpError::InternalError("Field value deserialized before its name".to_string())
+        })?;
+        seed.deserialize(NodeDeserializer {
+            node,
+            ns_uri: self.ns_uri,
+        })
+    }
+}
+
+/// Access to the items of an array
+struct ItemAccess<'a> {
+    items: std::slice::Iter<'a, Node>,
+    ns_uri: &'a str,
+}
+
+impl<'de> de::SeqAccess<'de> for ItemAccess<'de> {
+    type Error = XmpError;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> XmpResult<Option<T::Value>> {
+        self.items
+            .next()
+            .map(|node| {
+                seed.deserialize(NodeDeserializer {
+                    node,
+                    ns_uri: self.ns_uri,
+                })
+            })
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+/// Deserializer of a node
+struct NodeDeserializer<'a> {
+    node: &'a Node,
+    ns_uri: &'a str,
+}
+
+impl<'a> NodeDeserializer<'a> {
+    /// Get the text of a simple value, or of the default item of an Alt
+    fn text(&self) -> XmpResult<&'a str> {
+        let node = match self.node {
+            Node::Array(array) if array.array_type == ArrayType::Alternative => array
+                .items
+                .iter()
+                .find(|item| item_lang(item) == Some("x-default"))
+                .or_else(|| array.items.first())
+                .unwrap_or(self.node),
+            node => node,
+        };
+        node.as_simple()
+            .map(|simple| simple.value.as_str())
+            .ok_or_else(|| XmpError::BadValue("Expected a simple value".to_string()))
+    }
+
+    fn parse<T: std::str::FromStr>(&self, what: &str) -> XmpResult<T> {
+        let text = self.text()?;
+        text.trim()
+            .parse()
+            .map_err(|_| XmpError::BadValue(format!("Invalid {} {:?}", what, text)))
+    }
+
+    fn items(&self) -> XmpResult<ItemAccess<'a>> {
+        let items = match self.node {
+            Node::Array(array) => array.items.iter(),
+            // A simple value is a single item
+            Node::Simple(_) => std::slice::from_ref(self.node).iter(),
+            Node::Structure(_) => {
+                return Err(XmpError::BadValue(
+                    "Expected an array, found a structure".to_string(),
+                ))
+            }
+        };
+        Ok(ItemAccess {
+            items,
+            ns_uri: self.ns_uri,
+        })
+    }
+
+    fn fields(&self) -> XmpResult<FieldAccess<'a>> {
+        match self.node {
+            Node::Structure(structure) => Ok(FieldAccess::new(structure, self.ns_uri)),
+            _ => Err(XmpError::BadValue("Expected a structure".to_string())),
+        }
+    }
+}
+
+macro_rules! deserialize_number {
+    ($($method:ident => $visit:ident, $what:literal;)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> XmpResult<V::Value> {
+                visitor.$visit(self.parse($what)?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for NodeDeserializer<'de> {
+    type Error = XmpError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> XmpResult<V::Value> {
+        match self.node {
+            Node::Simple(simple) => visitor.visit_borrowed_str(&simple.value),
+            Node::Array(_) => visitor.visit_seq(self.items()?),
+            Node::Structure(_) => visitor.visit_map(self.fields()?),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> XmpResult<V::Value> {
+        visitor.visit_bool(convert::convert_to_bool(self.text()?)?)
+    }
+
+    deserialize_number! {
+        deserialize_i8 => visit_i8, "integer";
+        deserialize_i16 => visit_i16, "integer";
+        deserialize_i32 => visit_i32, "integer";
+        deserialize_i64 => visit_i64, "integer";
+        deserialize_u8 => visit_u8, "integer";
+        deserialize_u16 => visit_u16, "integer";
+        deserialize_u32 => visit_u32, "integer";
+        deserialize_u64 => visit_u64, "integer";
+        deserialize_f32 => visit_f32, "real";
+        deserialize_f64 => visit_f64, "real";
+        deserialize_char => visit_char, "character";
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> XmpResult<V::Value> {
+        visitor.visit_borrowed_str(self.text()?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> XmpResult<V::Value> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, _visitor: V) -> XmpResult<V::Value> {
+        Err(unsupported("bytes"))
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, _visitor: V) -> XmpResult<V::Value> {
+        Err(unsupported("bytes"))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> XmpResult<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> XmpResult<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> XmpResult<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> XmpResult<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> XmpResult<V::Value> {
+        visitor.visit_seq(self.items()?)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> XmpResult<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> XmpResult<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> XmpResult<V::Value> {
+        visitor.visit_map(self.fields()?)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> XmpResult<V::Value> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> XmpResult<V::Value> {
+        visitor.visit_enum(self.text()?.into_deserializer())
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> XmpResult<V::Value> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> XmpResult<V::Value> {
+        visitor.visit_unit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "PascalCase")]
+    struct Asset {
+        width: u32,
+        ratio: f64,
+        approved: bool,
+        status: Status,
+        tags: Bag<String>,
+        versions: Vec<u32>,
+        owner: Owner,
+        extra: BTreeMap<String, String>,
+        note: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Status {
+        Draft,
+        Final,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "PascalCase")]
+    struct Owner {
+        name: String,
+        emails: Vec<String>,
+    }
+
+    const NS: &str = "http://example.com/ns/serde-test/1.0/";
+
+    #[test]
+    fn test_round_trip() {
+        crate::core::namespace::register_namespace(NS, "sertest").unwrap();
+        let asset = Asset {
+            width: 1920,
+            ratio: 1.5,
+            approved: true,
+            status: Status::Final,
+            tags: vec!["a".to_string(), "b".to_string()].into(),
+            versions: vec![1, 2],
+            owner: Owner {
+                name: "Jane".to_string(),
+                emails: vec!["jane@example.com".to_string()],
+            },
+            extra: BTreeMap::from([("Key".to_string(), "Value".to_string())]),
+            note: None,
+        };
+        let mut meta = XmpMeta::new();
+        meta.set_property(NS, "Note", "kept".into()).unwrap();
+        meta.serialize_schema(&asset, NS).unwrap();
+
+        assert_eq!(meta.get_property(NS, "Width"), Some("1920".into()));
+        assert_eq!(meta.get_property(NS, "Approved"), Some("True".into()));
+        assert_eq!(meta.get_property(NS, "Status"), Some("Final".into()));
+        assert_eq!(meta.get_property(NS, "Note"), Some("kept".into()));
+        assert_eq!(
+            meta.get_struct_field(NS, "Owner", NS, "Name"),
+            Some("Jane".into())
+        );
+        let packet = meta.serialize().unwrap();
+        assert!(packet.contains("<rdf:Bag>"));
+        assert!(packet.contains("<rdf:Seq>"));
+
+        let read: Asset = meta.deserialize_schema(NS).unwrap();
+        assert_eq!(
+            read,
+            Asset {
+                note: Some("kept".to_string()),
+                ..asset
+            }
+        );
+
+        // Round trip through a packet
+        let parsed = XmpMeta::parse(&packet).unwrap();
+        assert_eq!(parsed.deserialize_schema::<Asset>(NS).unwrap(), read);
+    }
+
+    #[test]
+    fn test_known_schemas() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Dc {
+            title: String,
+            subject: Vec<String>,
+            creator: Vec<String>,
+        }
+
+        let mut meta = XmpMeta::new();
+        meta.serialize_schema(
+            &Dc {
+                title: "Title".to_string(),
+                subject: vec!["beach".to_string()],
+                creator: vec!["Jane".to_string()],
+            },
+            "dc",
+        )
+        .unwrap();
+        // Forms of the built-in schema
+        assert_eq!(
+            meta.get_localized_text(ns::DC, "title", "", "x-default"),
+            Some(("Title".to_string(), "x-default".to_string()))
+        );
+        let packet = meta.serialize().unwrap();
+        assert!(packet.contains("<rdf:Bag>"));
+        assert!(crate::core::validate::XmpValidator::new().is_valid(&meta));
+
+        // Type errors
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Wrong {
+            title: u32,
+        }
+        assert!(matches!(
+            meta.deserialize_schema::<Wrong>(ns::DC),
+            Err(XmpError::BadValue(_))
+        ));
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Missing {
+            rights: String,
+        }
+        assert!(meta.deserialize_schema::<Missing>(ns::DC).is_err());
+        assert!(matches!(
+            meta.serialize_schema(&"text", ns::DC),
+            Err(XmpError::BadParam(_))
+        ));
+
+        meta.set_property(ns::XMP, "Rating", XmpValue::Integer(3))
+            .unwrap();
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Basic {
+            rating: i32,
+        }
+        assert_eq!(meta.deserialize_schema::<Basic>(ns::XMP).unwrap().rating, 3);
+    }
+}
@@ -0,0 +1,112 @@
+//! Snapshots of XMP properties for undo and redo
+//!
+//! [`XmpMeta::snapshot`] copies the properties into an immutable
+//! [`XmpSnapshot`], and [`XmpMeta::restore`] puts them back. Snapshots are
+//! reference counted, so keeping them on undo and redo stacks doesn't copy
+//! them again.
+
+use std::sync::Arc;
+
+use super::node::root_read_with;
+use super::XmpMeta;
+use crate::core::error::XmpResult;
+use crate::core::node::StructureNode;
+
+/// The properties of an [`XmpMeta`] at one point in time
+///
+/// Created by [`XmpMeta::snapshot`]. Cloning a snapshot is cheap.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::{ns, XmpMeta, XmpValue};
+///
+/// let mut meta = XmpMeta::new();
+/// meta.set_property(ns::XMP, "Rating", XmpValue::Integer(3)).unwrap();
+///
+/// let mut undo = vec![meta.snapshot().unwrap()];
+/// meta.set_property(ns::XMP, "Rating", XmpValue::Integer(5)).unwrap();
+///
+/// let redo = meta.snapshot().unwrap();
+/// meta.restore(&undo.pop().unwrap()).unwrap();
+/// assert_eq!(meta.get_property_i64(ns::XMP, "Rating"), Some(3));
+///
+/// meta.restore(&redo).unwrap();
+/// assert_eq!(meta.get_property_i64(ns::XMP, "Rating"), Some(5));
+/// ```
+#[derive(Debug, Clone)]
+pub struct XmpSnapshot {
+    root: Arc<StructureNode>,
+    about_uri: Option<String>,
+}
+
+impl XmpSnapshot {
+    /// Check if the snapshot has the same properties as `meta`
+    pub fn matches(&self, meta: &XmpMeta) -> bool {
+        root_read_with(&meta.root, |root| *root == *self.root) && self.about_uri == meta.about_uri
+    }
+}
+
+impl XmpMeta {
+    /// Take a snapshot of the properties and the about URI
+    ///
+    /// The properties are copied once; see [`XmpSnapshot`] for undo and
+    /// redo.
+    pub fn snapshot(&self) -> XmpResult<XmpSnapshot> {
+        Ok(XmpSnapshot {
+            root: Arc::new(root_read!(self.root).clone()),
+            about_uri: self.about_uri.clone(),
+        })
+    }
+
+    /// Replace the properties and the about URI with those of a snapshot
+    ///
+    /// The snapshot may come from another `XmpMeta`. Clones share their
+    /// properties, so they are restored too. Restoring counts as a change
+    /// for [`XmpMeta::is_dirty`] unless it brings back the parsed
+    /// properties.
+    pub fn restore(&mut self, snapshot: &XmpSnapshot) -> XmpResult<()> {
+        *root_write!(self.root) = (*snapshot.root).clone();
+        self.about_uri = snapshot.about_uri.clone();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+
+    #[test]
+    fn test_snapshot_restore() {
+        let packet = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+            <rdf:Description rdf:about="" xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+                xmp:Label="Red"/>
+        </rdf:RDF>"#;
+        let mut meta = XmpMeta::parse(packet).unwrap();
+        let parsed = meta.snapshot().unwrap();
+        assert!(parsed.matches(&meta));
+
+        let copy = meta.clone();
+        meta.delete_property(ns::XMP, "Label").unwrap();
+        meta.set_about_uri("uuid:1");
+        assert!(!parsed.matches(&meta));
+        assert!(meta.is_dirty());
+
+        // Clones share the restored properties
+        meta.restore(&parsed).unwrap();
+        assert_eq!(
+            copy.get_property(ns::XMP, "Label"),
+            Some(XmpValue::String("Red".into()))
+        );
+        assert_eq!(meta.about_uri(), None);
+        assert!(parsed.matches(&meta));
+        assert!(!meta.is_dirty());
+
+        // A snapshot of another object
+        let mut other = XmpMeta::new();
+        other.restore(&parsed).unwrap();
+        assert!(other.has_property(ns::XMP, "Label"));
+    }
+}
@@ -0,0 +1,370 @@
+//! Removing properties and copying subtrees between XMP objects
+//!
+//! This is a port of `RemoveProperties` and `DuplicateSubtree` from the
+//! Adobe XMP Toolkit. Like [`XmpMeta::append_from`], removal leaves the
+//! internal properties maintained by applications and file handlers alone
+//! unless [`RemoveOptions::all_properties`] is set.
+
+use super::append::is_internal_property;
+use super::{find_node, find_or_create_node, implicit_top_node, XmpMeta};
+use crate::core::alias;
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::node::Node;
+use crate::core::path::PathStep;
+
+/// Options to control [`XmpMeta::remove_properties`]
+#[derive(Default, Clone, Copy, Debug)]
+pub struct RemoveOptions {
+    /// Remove internal properties as well
+    pub(crate) all_properties: bool,
+    /// Remove the actual properties of the aliases of the namespace too
+    pub(crate) include_aliases: bool,
+}
+
+impl RemoveOptions {
+    /// Remove internal properties too.
+    ///
+    /// By default only external properties, which users edit, are removed.
+    pub fn all_properties(mut self) -> Self {
+        self.all_properties = true;
+        self
+    }
+
+    /// When removing the properties of a namespace, also remove the nodes
+    /// that its aliases stand for (see [`crate::core::alias`]).
+    pub fn include_aliases(mut self) -> Self {
+        self.include_aliases = true;
+        self
+    }
+}
+
+impl XmpMeta {
+    /// Remove properties
+    ///
+    /// - With a `property`, only that property (or the node addressed by
+    ///   the XMP path) is removed.
+    /// - With a `namespace` and no `property`, all the properties of the
+    ///   namespace are removed, and with [`RemoveOptions::include_aliases`]
+    ///   the nodes its aliases stand for.
+    /// - With neither, all the properties are removed.
+    ///
+    /// Internal properties are kept unless [`RemoveOptions::all_properties`]
+    /// is set.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::core::RemoveOptions;
+    /// use xmpkit::{ns, XmpMeta, XmpValue};
+    ///
+    /// let mut meta = XmpMeta::new();
+    /// meta.set_property(ns::DC, "source", XmpValue::String("Scan".into())).unwrap();
+    /// meta.set_property(ns::XMP, "ModifyDate", XmpValue::String("2024-05-06".into()))
+    ///     .unwrap();
+    ///
+    /// meta.remove_properties("", "", RemoveOptions::default()).unwrap();
+    /// assert!(!meta.has_property(ns::DC, "source"));
+    /// // xmp:ModifyDate is maintained by applications
+    /// assert!(meta.has_property(ns::XMP, "ModifyDate"));
+    /// ```
+    pub fn remove_properties(
+        &mut self,
+        namespace: &str,
+        property: &str,
+        options: RemoveOptions,
+    ) -> XmpResult<()> {
+        if !property.is_empty() {
+            if namespace.is_empty() {
+                return Err(XmpError::BadParam(
+                    "Property name requires schema namespace".to_string(),
+                ));
+            }
+            let ns_uri = self.resolve_namespace_uri_or_error(namespace)?;
+            let steps = self.expand_path(&ns_uri, property)?;
+            let Some(PathStep::Field(top_key)) = steps.first() else {
+                return Ok(());
+            };
+            let internal = top_key
+                .rsplit_once(':')
+                .is_some_and(|(ns_uri, name)| is_internal_property(ns_uri, name));
+            if options.all_properties || !internal {
+                self.delete_property(&ns_uri, property)?;
+            }
+            return Ok(());
+        }
+
+        let ns_uri = if namespace.is_empty() {
+            None
+        } else {
+            Some(self.resolve_namespace_uri_or_error(namespace)?)
+        };
+        if let (Some(ns_uri), true) = (&ns_uri, options.include_aliases) {
+            for (_, alias) in alias::aliases_of(ns_uri) {
+                if options.all_properties
+                    || !is_internal_property(&alias.namespace, &alias.property)
+                {
+                    self.delete_property(&alias.namespace, &alias.path())?;
+                }
+            }
+        }
+
+        let mut root = root_write!(self.root);
+        root.fields.retain(|key, _| {
+            let Some((key_ns, name)) = key.rsplit_once(':') else {
+                return true;
+            };
+            let selected = ns_uri.as_deref().is_none_or(|ns_uri| ns_uri == key_ns);
+            !(selected && (options.all_properties || !is_internal_property(key_ns, name)))
+        });
+        Ok(())
+    }
+
+    /// Copy a subtree from one XMP object to another
+    ///
+    /// - With a `source_root`, the node at `source_ns`:`source_root` (any
+    ///   XMP path) is copied to `dest_ns`:`dest_root`, creating the
+    ///   parents of the destination as needed. `dest_ns` and `dest_root`
+    ///   default to the source ones.
+    /// - With a `source_ns` and no `source_root`, all the properties of the
+    ///   namespace are copied, into `dest_ns` if given.
+    /// - With neither, all the properties are copied.
+    ///
+    /// Existing destination nodes are replaced. To copy within one object,
+    /// pass a clone of it as `source`. Namespaces registered in `source` are
+    /// registered in `dest` too.
+    ///
+    /// Fails with `XmpError::BadXPath` if the source subtree doesn't exist.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{ns, XmpMeta};
+    ///
+    /// let mut template = XmpMeta::new();
+    /// template
+    ///     .set_struct_field(ns::IPTC_CORE, "CreatorContactInfo", ns::IPTC_CORE, "CiEmailWork", "jane@example.com".into())
+    ///     .unwrap();
+    ///
+    /// let mut meta = XmpMeta::new();
+    /// XmpMeta::duplicate_subtree(&template, &mut meta, ns::IPTC_CORE, "CreatorContactInfo", "", "")
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     meta.get_struct_field(ns::IPTC_CORE, "CreatorContactInfo", ns::IPTC_CORE, "CiEmailWork"),
+    ///     Some("jane@example.com".into())
+    /// );
+    /// ```
+    pub fn duplicate_subtree(
+        source: &XmpMeta,
+        dest: &mut XmpMeta,
+        source_ns: &str,
+        source_root: &str,
+        dest_ns: &str,
+        dest_root: &str,
+    ) -> XmpResult<()> {
+        if source_root.is_empty() && !dest_root.is_empty() {
+            return Err(XmpError::BadParam(
+                "Destination root requires a source root".to_string(),
+            ));
+        }
+        if source_ns.is_empty() && (!source_root.is_empty() || !dest_ns.is_empty()) {
+            return Err(XmpError::BadParam(
+                "Source namespace is required to copy a subtree or a schema".to_string(),
+            ));
+        }
+
+        // Source prefixes must resolve in `dest` too
+        dest.register_namespaces_of(source);
+
+        // Copy the source first: `source` may share its root with `dest`
+        let copies: Vec<(String, Node)> = if source_ns.is_empty() {
+            root_read!(source.root)
+                .fields
+                .iter()
+                .map(|(key, node)| (key.clone(), node.clone()))
+                .collect()
+        } else {
+            let source_uri = source.resolve_namespace_uri_or_error(source_ns)?;
+            let dest_uri = if dest_ns.is_empty() {
+                source_uri.clone()
+            } else {
+                dest.resolve_namespace_uri_or_error(dest_ns)?
+            };
+
+            if source_root.is_empty() {
+                let prefix = format!("{}:", source_uri);
+                root_read!(source.root)
+                    .fields
+                    .iter()
+                    .filter_map(|(key, node)| {
+                        let name = key.strip_prefix(&prefix)?;
+                        Some((format!("{}:{}", dest_uri, name), node.clone()))
+                    })
+                    .collect()
+            } else {
+                let steps = source.expand_path(&source_uri, source_root)?;
+                let source_tree = root_read!(source.root);
+                let node = find_node(&source_tree, &steps).cloned().ok_or_else(|| {
+                    XmpError::BadXPath(format!("Can't find source subtree '{}'", source_root))
+                })?;
+                drop(source_tree);
+                let dest_root = if dest_root.is_empty() {
+                    source_root
+                } else {
+                    dest_root
+                };
+                let dest_steps = dest.expand_path(&dest_uri, dest_root)?;
+                return dest.set_subtree(&dest_steps, dest_root, node);
+            }
+        };
+
+        let mut root = root_write!(dest.root);
+        for (key, node) in copies {
+            root.set_field(key, node);
+        }
+        Ok(())
+    }
+
+    /// Replace the node at `steps` with `node`, creating its parents
+    fn set_subtree(&mut self, steps: &[PathStep], path: &str, node: Node) -> XmpResult<()> {
+        let Some((PathStep::Field(top_key), child_steps)) = steps.split_first() else {
+            return Err(XmpError::BadXPath(format!("Invalid path '{}'", path)));
+        };
+        if matches!(steps.last(), Some(PathStep::Qualifier { .. })) {
+            return Err(XmpError::BadXPath(format!(
+                "Destination '{}' can't be a qualifier",
+                path
+            )));
+        }
+
+        let mut root = root_write!(self.root);
+        if child_steps.is_empty() {
+            root.set_field(top_key.clone(), node);
+            return Ok(());
+        }
+
+        // Work on a copy of the top-level property so a failure leaves it untouched
+        let mut top = root
+            .get_field(top_key)
+            .cloned()
+            .unwrap_or_else(|| implicit_top_node(top_key, child_steps.first()));
+        *find_or_create_node(&mut top, child_steps, path)? = node;
+        root.set_field(top_key.clone(), top);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+
+    fn sample() -> XmpMeta {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "source", XmpValue::String("Scan".into()))
+            .unwrap();
+        meta.set_property(ns::DC, "format", XmpValue::String("image/jpeg".into()))
+            .unwrap();
+        meta.set_property(ns::XMP, "Label", XmpValue::String("Red".into()))
+            .unwrap();
+        meta.set_property(ns::XMP, "CreatorTool", XmpValue::String("App".into()))
+            .unwrap();
+        meta
+    }
+
+    #[test]
+    fn test_remove_properties() {
+        let mut meta = sample();
+        meta.remove_properties(ns::DC, "", RemoveOptions::default())
+            .unwrap();
+        assert!(!meta.has_property(ns::DC, "source"));
+        assert!(meta.has_property(ns::DC, "format"));
+        assert!(meta.has_property(ns::XMP, "Label"));
+
+        meta.remove_properties(ns::XMP, "CreatorTool", RemoveOptions::default())
+            .unwrap();
+        assert!(meta.has_property(ns::XMP, "CreatorTool"));
+        meta.remove_properties(
+            ns::XMP,
+            "CreatorTool",
+            RemoveOptions::default().all_properties(),
+        )
+        .unwrap();
+        assert!(!meta.has_property(ns::XMP, "CreatorTool"));
+
+        meta.remove_properties("", "", RemoveOptions::default().all_properties())
+            .unwrap();
+        assert!(!meta.has_property(ns::DC, "format"));
+        assert!(!meta.has_property(ns::XMP, "Label"));
+
+        assert!(matches!(
+            meta.remove_properties("", "Label", RemoveOptions::default()),
+            Err(XmpError::BadParam(_))
+        ));
+
+        // pdf:Title stands for the x-default item of dc:title, pdf:Creator for
+        // the internal xmp:CreatorTool
+        let mut meta = sample();
+        meta.set_localized_text(ns::DC, "title", "", "x-default", "Title")
+            .unwrap();
+        meta.remove_properties(ns::PDF, "", RemoveOptions::default().include_aliases())
+            .unwrap();
+        assert_eq!(meta.get_array_size(ns::DC, "title"), Some(0));
+        assert!(meta.has_property(ns::XMP, "CreatorTool"));
+    }
+
+    #[test]
+    fn test_duplicate_subtree() {
+        let mut source = sample();
+        source
+            .set_struct_field(
+                ns::IPTC_CORE,
+                "CreatorContactInfo",
+                ns::IPTC_CORE,
+                "CiAdrCity",
+                "Oslo".into(),
+            )
+            .unwrap();
+
+        // A whole schema, renamed
+        let mut dest = XmpMeta::new();
+        XmpMeta::duplicate_subtree(&source, &mut dest, ns::DC, "", ns::XMP, "").unwrap();
+        assert_eq!(
+            dest.get_property(ns::XMP, "source"),
+            Some(XmpValue::String("Scan".into()))
+        );
+        assert!(!dest.has_property(ns::DC, "source"));
+
+        // A structure into a new array item
+        XmpMeta::duplicate_subtree(
+            &source,
+            &mut dest,
+            ns::IPTC_CORE,
+            "CreatorContactInfo",
+            ns::XMP,
+            "Contacts[1]",
+        )
+        .unwrap();
+        assert_eq!(
+            dest.get_property_by_path(ns::XMP, "Contacts[1]/Iptc4xmpCore:CiAdrCity"),
+            Some(XmpValue::String("Oslo".into()))
+        );
+
+        // Within one object
+        let copy = dest.clone();
+        XmpMeta::duplicate_subtree(&copy, &mut dest, ns::XMP, "source", "", "Label").unwrap();
+        assert_eq!(
+            dest.get_property(ns::XMP, "Label"),
+            Some(XmpValue::String("Scan".into()))
+        );
+
+        assert!(matches!(
+            XmpMeta::duplicate_subtree(&source, &mut dest, ns::XMP, "Missing", "", ""),
+            Err(XmpError::BadXPath(_))
+        ));
+        assert!(matches!(
+            XmpMeta::duplicate_subtree(&source, &mut dest, "", "", "", "Label"),
+            Err(XmpError::BadParam(_))
+        ));
+    }
+}
@@ -0,0 +1,158 @@
+//! Thumbnails (`xmp:Thumbnails`)
+//!
+//! `xmp:Thumbnails` is an `rdf:Alt` array of `xmpGImg` structures, one per
+//! thumbnail size. Each structure has the `width` and `height` of the image
+//! in pixels, its `format` ("JPEG" is the only format XMP defines) and the
+//! `image` data encoded as Base64.
+//!
+//! # Example
+//!
+//! ```rust
+//! use xmpkit::XmpMeta;
+//!
+//! let mut meta = XmpMeta::new();
+//! meta.set_thumbnail(160, 120, &[0xFF, 0xD8, 0xFF, 0xD9]).unwrap();
+//!
+//! let thumbnails = meta.thumbnails();
+//! assert_eq!(thumbnails.len(), 1);
+//! assert_eq!((thumbnails[0].width, thumbnails[0].height), (160, 120));
+//! assert_eq!(thumbnails[0].format, "JPEG");
+//! assert_eq!(thumbnails[0].image, [0xFF, 0xD8, 0xFF, 0xD9]);
+//! ```
+
+use super::XmpMeta;
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::namespace::ns;
+use crate::core::node::{ArrayType, Node};
+use crate::types::value::XmpValue;
+use crate::utils::base64;
+
+/// A thumbnail image of `xmp:Thumbnails`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Thumbnail {
+    /// Width of the image in pixels
+    pub width: u32,
+    /// Height of the image in pixels
+    pub height: u32,
+    /// Format of the image, such as "JPEG"
+    pub format: String,
+    /// The image data
+    pub image: Vec<u8>,
+}
+
+impl Thumbnail {
+    /// Create a JPEG thumbnail
+    pub fn jpeg(width: u32, height: u32, image: Vec<u8>) -> Self {
+        Self {
+            width,
+            height,
+            format: "JPEG".to_string(),
+            image,
+        }
+    }
+}
+
+impl XmpMeta {
+    /// Get the thumbnails of `xmp:Thumbnails`
+    ///
+    /// Items without a valid size or Base64 image are skipped.
+    pub fn thumbnails(&self) -> Vec<Thumbnail> {
+        let count = self.get_array_size(ns::XMP, "Thumbnails").unwrap_or(0);
+        (1..=count)
+            .filter_map(|i| {
+                let field = |name: &str| {
+                    let path = format!("Thumbnails[{}]/xmpGImg:{}", i, name);
+                    match self.get_property_by_path(ns::XMP, &path)? {
+                        XmpValue::String(s) => Some(s),
+                        _ => None,
+                    }
+                };
+                Some(Thumbnail {
+                    width: field("width")?.trim().parse().ok()?,
+                    height: field("height")?.trim().parse().ok()?,
+                    format: field("format").unwrap_or_default(),
+                    image: base64::decode(&field("image")?).ok()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Add a thumbnail to `xmp:Thumbnails`
+    ///
+    /// The array is created if needed.
+    pub fn add_thumbnail(&mut self, thumbnail: &Thumbnail) -> XmpResult<()> {
+        let count = match self.get_array_size(ns::XMP, "Thumbnails") {
+            Some(count) => count,
+            None if !self.does_property_exist(ns::XMP, "Thumbnails") => {
+                root_write!(self.root).set_field(
+                    format!("{}:Thumbnails", ns::XMP),
+                    Node::array(ArrayType::Alternative),
+                );
+                0
+            }
+            None => {
+                return Err(XmpError::BadXPath(
+                    "xmp:Thumbnails is not an array".to_string(),
+                ))
+            }
+        };
+        let fields = [
+            ("width", thumbnail.width.to_string()),
+            ("height", thumbnail.height.to_string()),
+            ("format", thumbnail.format.clone()),
+            ("image", base64::encode(&thumbnail.image)),
+        ];
+        for (name, value) in fields {
+            let path = format!("Thumbnails[{}]/xmpGImg:{}", count + 1, name);
+            self.set_property_by_path(ns::XMP, &path, value.into())?;
+        }
+        Ok(())
+    }
+
+    /// Replace `xmp:Thumbnails` with a JPEG thumbnail
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Width of the thumbnail in pixels
+    /// * `height` - Height of the thumbnail in pixels
+    /// * `jpeg` - The JPEG data of the thumbnail
+    pub fn set_thumbnail(&mut self, width: u32, height: u32, jpeg: &[u8]) -> XmpResult<()> {
+        self.delete_property(ns::XMP, "Thumbnails")?;
+        self.add_thumbnail(&Thumbnail::jpeg(width, height, jpeg.to_vec()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnails_round_trip() {
+        let mut meta = XmpMeta::new();
+        meta.set_thumbnail(4, 3, b"old").unwrap();
+        meta.set_thumbnail(160, 120, b"jpeg data").unwrap();
+        meta.add_thumbnail(&Thumbnail {
+            width: 32,
+            height: 24,
+            format: "PNG".to_string(),
+            image: vec![0, 1, 2],
+        })
+        .unwrap();
+
+        let packet = meta.serialize_packet().unwrap();
+        assert!(packet.contains("<rdf:Alt>"));
+        let parsed = XmpMeta::parse(&packet).unwrap();
+        let thumbnails = parsed.thumbnails();
+        assert_eq!(thumbnails.len(), 2);
+        assert_eq!(
+            thumbnails[0],
+            Thumbnail::jpeg(160, 120, b"jpeg data".to_vec())
+        );
+        assert_eq!(thumbnails[1].format, "PNG");
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "Thumbnails", "none".into())
+            .unwrap();
+        assert!(meta.add_thumbnail(&thumbnails[0]).is_err());
+    }
+}
@@ -0,0 +1,287 @@
+//! Media management (`xmpMM`) helpers
+//!
+//! The XMP Media Management schema tracks the provenance of a document:
+//! - `xmpMM:DocumentID` identifies the document and all its versions, and
+//!   `xmpMM:OriginalDocumentID` the document it was originally created as
+//! - `xmpMM:InstanceID` identifies one saved version, and changes each time
+//!   the document is saved
+//! - `xmpMM:DerivedFrom` references the document a new one was derived from
+//! - `xmpMM:History` lists the actions performed on the document
+//!
+//! The identifiers are generated with [`new_xmp_id`], like Adobe
+//! applications do. [`SaveOptions`] can update them
+//! automatically when a file is saved.
+//!
+//! # Example
+//!
+//! ```rust
+//! use xmpkit::{XmpDateTime, XmpMeta};
+//!
+//! let mut meta = XmpMeta::new();
+//! meta.record_save("MyApp 1.0", None).unwrap();
+//! let first = meta.instance_id().unwrap();
+//! meta.record_save("MyApp 1.0", XmpDateTime::parse("2024-05-06T10:00:00Z").ok().as_ref())
+//!     .unwrap();
+//!
+//! assert_ne!(meta.instance_id().unwrap(), first);
+//! assert!(meta.document_id().unwrap().starts_with("xmp.did:"));
+//! assert_eq!(meta.original_document_id(), meta.document_id());
+//!
+//! let history = meta.history();
+//! assert_eq!(history.len(), 2);
+//! assert_eq!(history[1].action, "saved");
+//! assert_eq!(history[1].instance_id, meta.instance_id());
+//! ```
+//!
+//! [`SaveOptions`]: crate::files::SaveOptions
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::core::namespace::ns;
+use crate::types::value::XmpValue;
+use crate::utils::datetime::{current_datetime, XmpDateTime};
+use crate::utils::guid::{new_xmp_id, XmpIdKind};
+
+/// An event of `xmpMM:History` (an `stEvt:ResourceEvent`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// The action performed, such as "created", "saved" or "converted"
+    pub action: String,
+    /// The instance ID of the document after the action
+    pub instance_id: Option<String>,
+    /// When the action was performed
+    pub when: Option<XmpDateTime>,
+    /// The software that performed the action
+    pub software_agent: Option<String>,
+    /// The parts of the document that changed, separated by semicolons
+    pub changed: Option<String>,
+    /// Additional description of the action
+    pub parameters: Option<String>,
+}
+
+impl XmpMeta {
+    /// Get `xmpMM:DocumentID`
+    pub fn document_id(&self) -> Option<String> {
+        self.mm_property("DocumentID")
+    }
+
+    /// Get `xmpMM:OriginalDocumentID`
+    pub fn original_document_id(&self) -> Option<String> {
+        self.mm_property("OriginalDocumentID")
+    }
+
+    /// Get `xmpMM:InstanceID`
+    pub fn instance_id(&self) -> Option<String> {
+        self.mm_property("InstanceID")
+    }
+
+    fn mm_property(&self, name: &str) -> Option<String> {
+        match self.get_property(ns::XMP_MM, name)? {
+            XmpValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Add an event to `xmpMM:History`
+    ///
+    /// The event records the current `xmpMM:InstanceID`, if any. Empty
+    /// `software` and `params` are omitted.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action performed, such as "created" or "saved"
+    /// * `software` - The software that performed the action
+    /// * `when` - When the action was performed
+    /// * `params` - Additional description of the action
+    pub fn add_history_entry(
+        &mut self,
+        action: &str,
+        software: &str,
+        when: Option<&XmpDateTime>,
+        params: &str,
+    ) -> XmpResult<()> {
+        if action.is_empty() {
+            return Err(XmpError::BadParam(
+                "History action cannot be empty".to_string(),
+            ));
+        }
+        let item = format!(
+            "History[{}]",
+            self.get_array_size(ns::XMP_MM, "History").unwrap_or(0) + 1
+        );
+        let when = when.map(XmpDateTime::format);
+        let fields = [
+            ("action", Some(action.to_string())),
+            ("instanceID", self.instance_id()),
+            ("when", when),
+            ("softwareAgent", Some(software.to_string())),
+            ("parameters", Some(params.to_string())),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value.filter(|v| !v.is_empty()) {
+                let path = format!("{}/stEvt:{}", item, name);
+                self.set_property_by_path(ns::XMP_MM, &path, XmpValue::String(value))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the events of `xmpMM:History`, oldest first
+    pub fn history(&self) -> Vec<HistoryEntry> {
+        let count = self.get_array_size(ns::XMP_MM, "History").unwrap_or(0);
+        (1..=count)
+            .map(|i| {
+                let field = |name: &str| {
+                    let path = format!("History[{}]/stEvt:{}", i, name);
+                    match self.get_property_by_path(ns::XMP_MM, &path)? {
+                        XmpValue::String(s) => Some(s),
+                        _ => None,
+                    }
+                };
+                HistoryEntry {
+                    action: field("action").unwrap_or_default(),
+                    instance_id: field("instanceID"),
+                    when: field("when").and_then(|s| XmpDateTime::parse(&s).ok()),
+                    software_agent: field("softwareAgent"),
+                    changed: field("changed"),
+                    parameters: field("parameters"),
+                }
+            })
+            .collect()
+    }
+
+    /// Set a new `xmpMM:InstanceID`
+    ///
+    /// Returns the new instance ID.
+    pub fn new_instance_id(&mut self) -> XmpResult<String> {
+        let id = new_xmp_id(XmpIdKind::Instance);
+        self.set_property(ns::XMP_MM, "InstanceID", id.clone().into())?;
+        Ok(id)
+    }
+
+    /// Initialize the identifiers of the document
+    ///
+    /// A missing `xmpMM:DocumentID` or `xmpMM:InstanceID` is generated, and
+    /// a missing `xmpMM:OriginalDocumentID` is set to the document ID.
+    /// Existing identifiers are kept.
+    pub fn init_document_ids(&mut self) -> XmpResult<()> {
+        let document_id = match self.document_id() {
+            Some(id) => id,
+            None => {
+                let id = new_xmp_id(XmpIdKind::Document);
+                self.set_property(ns::XMP_MM, "DocumentID", id.clone().into())?;
+                id
+            }
+        };
+        if self.original_document_id().is_none() {
+            self.set_property(ns::XMP_MM, "OriginalDocumentID", document_id.into())?;
+        }
+        if self.instance_id().is_none() {
+            self.new_instance_id()?;
+        }
+        Ok(())
+    }
+
+    /// Make this metadata describe a new document derived from the current
+    /// one
+    ///
+    /// `xmpMM:DerivedFrom` is set to reference the current instance, and new
+    /// document and instance IDs are generated. `xmpMM:OriginalDocumentID`
+    /// is kept.
+    pub fn derive_document(&mut self) -> XmpResult<()> {
+        self.init_document_ids()?;
+        let fields = [
+            ("instanceID", self.instance_id()),
+            ("documentID", self.document_id()),
+            ("originalDocumentID", self.original_document_id()),
+        ];
+        self.delete_property(ns::XMP_MM, "DerivedFrom")?;
+        for (name, value) in fields {
+            if let Some(value) = value {
+                self.set_struct_field(ns::XMP_MM, "DerivedFrom", ns::ST_REF, name, value.into())?;
+            }
+        }
+
+        self.set_property(
+            ns::XMP_MM,
+            "DocumentID",
+            new_xmp_id(XmpIdKind::Document).into(),
+        )?;
+        self.new_instance_id()?;
+        Ok(())
+    }
+
+    /// Record that the document is being saved
+    ///
+    /// The document IDs are initialized if needed, a new instance ID is
+    /// generated, and a "saved" event is added to `xmpMM:History`. `when`
+    /// defaults to the current time.
+    pub fn record_save(&mut self, software: &str, when: Option<&XmpDateTime>) -> XmpResult<()> {
+        let had_ids = self.instance_id().is_some();
+        self.init_document_ids()?;
+        if had_ids {
+            self.new_instance_id()?;
+        }
+        let now = current_datetime().ok();
+        self.add_history_entry("saved", software, when.or(now.as_ref()), "")
+    }
+}
+
+/// Get the kind of identifier of an `xmpMM` ID property
+pub(crate) fn id_kind(ns_uri: &str, name: &str) -> Option<XmpIdKind> {
+    match (ns_uri, name) {
+        (ns::XMP_MM, "DocumentID" | "OriginalDocumentID") => Some(XmpIdKind::Document),
+        (ns::XMP_MM, "InstanceID") => Some(XmpIdKind::Instance),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_document() {
+        let mut meta = XmpMeta::new();
+        meta.init_document_ids().unwrap();
+        let (document_id, instance_id) = (meta.document_id(), meta.instance_id());
+        meta.add_history_entry("created", "MyApp", None, "from scratch")
+            .unwrap();
+
+        meta.derive_document().unwrap();
+        assert_ne!(meta.document_id(), document_id);
+        assert_ne!(meta.instance_id(), instance_id);
+        assert_eq!(meta.original_document_id(), document_id);
+        assert_eq!(
+            meta.get_struct_field(ns::XMP_MM, "DerivedFrom", ns::ST_REF, "instanceID"),
+            instance_id.map(XmpValue::String)
+        );
+
+        let history = meta.history();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].software_agent.as_deref(), Some("MyApp"));
+        assert_eq!(history[0].parameters.as_deref(), Some("from scratch"));
+        assert_eq!(history[0].when, None);
+        assert!(meta.add_history_entry("", "", None, "").is_err());
+    }
+
+    #[test]
+    fn test_auto_ids() {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP_MM, "DocumentID", "".into())
+            .unwrap();
+        assert_eq!(meta.document_id().as_deref(), Some(""));
+
+        meta.set_auto_ids(true);
+        meta.set_property(ns::XMP_MM, "OriginalDocumentID", "".into())
+            .unwrap();
+        meta.set_property(ns::XMP, "Label", "".into()).unwrap();
+        assert!(meta
+            .original_document_id()
+            .is_some_and(|id| id.starts_with("xmp.did:")));
+        assert_eq!(
+            meta.get_property(ns::XMP, "Label"),
+            Some(XmpValue::String(String::new()))
+        );
+    }
+}
@@ -3,21 +3,43 @@
 //! This module contains the core functionality for XMP metadata processing,
 //! including parsing, manipulation, and serialization.
 
+pub mod alias;
+pub mod borrowed;
+pub mod conformance;
 pub mod error;
 pub mod metadata;
+pub mod mm;
 pub mod namespace;
 pub mod node;
 pub mod parser;
+pub mod path;
 pub mod serializer;
+pub mod validate;
 pub mod xpath;
 
+pub use alias::{register_alias, resolve_alias, XmpAlias};
+pub use borrowed::XmpMetaRef;
+pub use conformance::{add_pdfa_extension_schemas, check_pdfa, PdfaIssue};
 pub use error::{XmpError, XmpResult};
-pub use metadata::XmpMeta;
+#[cfg(feature = "serde_json")]
+pub use metadata::JsonOptions;
+#[cfg(feature = "serde")]
+pub use metadata::{Alt, Bag, Seq};
+pub use metadata::{
+    AppendOptions, DiffEntry, Dimensions, GpsPosition, IterOptions, PropertyOptions, Region,
+    RegionArea, RegionInfo, RegionType, RemoveOptions, SeparateOptions, TemplateOptions, Thumbnail,
+    XmpDiff, XmpIterItem, XmpIterator, XmpMeta, XmpSnapshot,
+};
+pub use mm::HistoryEntry;
 pub use namespace::{
     get_all_registered_namespaces, get_builtin_namespace_uris, get_global_namespace_prefix,
     get_global_namespace_uri, register_namespace, NamespaceMap,
 };
 pub use node::{ArrayNode, ArrayType, Node, SimpleNode, StructureNode};
-pub use parser::XmpParser;
-pub use serializer::XmpSerializer;
+pub use parser::{DescriptionMerge, ParseOptions, ParseWarning, ParseWarningKind, XmpParser};
+pub use serializer::{SerializeOptions, StructForm, XmpSerializer};
+pub use validate::{
+    get_registered_schema, register_schema, SchemaProperty, ValueForm, ValueType, Violation,
+    ViolationKind, XmpSchema, XmpValidator,
+};
 pub use xpath::{build_path, parse_path, PathComponent, PathComponents};
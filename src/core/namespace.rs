@@ -42,6 +42,16 @@ pub mod ns {
     pub const PDFX: &str = "http://ns.adobe.com/pdfx/1.3/";
     /// PDF/A namespace
     pub const PDFA: &str = "http://www.aiim.org/pdfa/ns/id/";
+    /// PDF/A extension schema container namespace
+    pub const PDFA_EXTENSION: &str = "http://www.aiim.org/pdfa/ns/extension/";
+    /// PDF/A extension schema description namespace
+    pub const PDFA_SCHEMA: &str = "http://www.aiim.org/pdfa/ns/schema#";
+    /// PDF/A extension property description namespace
+    pub const PDFA_PROPERTY: &str = "http://www.aiim.org/pdfa/ns/property#";
+    /// PDF/A extension value type description namespace
+    pub const PDFA_TYPE: &str = "http://www.aiim.org/pdfa/ns/type#";
+    /// PDF/A extension structure field description namespace
+    pub const PDFA_FIELD: &str = "http://www.aiim.org/pdfa/ns/field#";
     /// XMP Dynamic Media namespace
     pub const XMP_DM: &str = "http://ns.adobe.com/xmp/1.0/DynamicMedia/";
     /// XMP PagedText namespace
@@ -50,6 +60,32 @@ pub mod ns {
     pub const XMP_GRAPHICS: &str = "http://ns.adobe.com/xap/1.0/g/";
     /// XMP Image namespace
     pub const XMP_IMAGE: &str = "http://ns.adobe.com/xap/1.0/g/img/";
+    /// Resource Event structure namespace
+    pub const ST_EVT: &str = "http://ns.adobe.com/xap/1.0/sType/ResourceEvent#";
+    /// Resource Reference structure namespace
+    pub const ST_REF: &str = "http://ns.adobe.com/xap/1.0/sType/ResourceRef#";
+    /// Broadcast Wave Format `bext` chunk namespace
+    pub const BEXT: &str = "http://ns.adobe.com/bwf/bext/1.0/";
+    /// XMP Note namespace (used for Extended XMP in JPEG files)
+    pub const XMP_NOTE: &str = "http://ns.adobe.com/xmp/note/";
+    /// MWG Regions namespace
+    pub const MWG_RS: &str = "http://www.metadataworkinggroup.com/schemas/regions/";
+    /// Area structure namespace (used by MWG Regions)
+    pub const ST_AREA: &str = "http://ns.adobe.com/xmp/sType/Area#";
+    /// Dimensions structure namespace
+    pub const ST_DIM: &str = "http://ns.adobe.com/xap/1.0/sType/Dimensions#";
+    /// Microsoft Photo 1.2 namespace (People Tags)
+    pub const MP: &str = "http://ns.microsoft.com/photo/1.2/";
+    /// Microsoft Photo region info structure namespace
+    pub const MP_RI: &str = "http://ns.microsoft.com/photo/1.2/t/RegionInfo#";
+    /// Microsoft Photo region structure namespace
+    pub const MP_REG: &str = "http://ns.microsoft.com/photo/1.2/t/Region#";
+    /// Lightroom namespace
+    pub const LIGHTROOM: &str = "http://ns.adobe.com/lightroom/1.0/";
+    /// Microsoft Photo namespace (written by Windows)
+    pub const MICROSOFT_PHOTO: &str = "http://ns.microsoft.com/photo/1.0/";
+    /// PLUS (Picture Licensing Universal System) namespace
+    pub const PLUS: &str = "http://ns.useplus.org/ldf/xmp/1.0/";
     /// RDF namespace
     pub const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
     /// XML namespace (for xml:lang, etc.)
@@ -88,6 +124,16 @@ pub mod ns {
     pub const PDFX_PREFIX: &str = "pdfx";
     /// PDF/A prefix
     pub const PDFA_PREFIX: &str = "pdfaid";
+    /// PDF/A extension schema container prefix
+    pub const PDFA_EXTENSION_PREFIX: &str = "pdfaExtension";
+    /// PDF/A extension schema description prefix
+    pub const PDFA_SCHEMA_PREFIX: &str = "pdfaSchema";
+    /// PDF/A extension property description prefix
+    pub const PDFA_PROPERTY_PREFIX: &str = "pdfaProperty";
+    /// PDF/A extension value type description prefix
+    pub const PDFA_TYPE_PREFIX: &str = "pdfaType";
+    /// PDF/A extension structure field description prefix
+    pub const PDFA_FIELD_PREFIX: &str = "pdfaField";
     /// XMP Dynamic Media prefix
     pub const XMP_DM_PREFIX: &str = "xmpDM";
     /// XMP PagedText prefix
@@ -96,6 +142,32 @@ pub mod ns {
     pub const XMP_GRAPHICS_PREFIX: &str = "xmpG";
     /// XMP Image prefix
     pub const XMP_IMAGE_PREFIX: &str = "xmpGImg";
+    /// Resource Event structure namespace prefix
+    pub const ST_EVT_PREFIX: &str = "stEvt";
+    /// Resource Reference structure namespace prefix
+    pub const ST_REF_PREFIX: &str = "stRef";
+    /// Broadcast Wave Format `bext` chunk prefix
+    pub const BEXT_PREFIX: &str = "bext";
+    /// XMP Note prefix
+    pub const XMP_NOTE_PREFIX: &str = "xmpNote";
+    /// MWG Regions prefix
+    pub const MWG_RS_PREFIX: &str = "mwg-rs";
+    /// Area structure prefix
+    pub const ST_AREA_PREFIX: &str = "stArea";
+    /// Dimensions structure prefix
+    pub const ST_DIM_PREFIX: &str = "stDim";
+    /// Microsoft Photo 1.2 prefix
+    pub const MP_PREFIX: &str = "MP";
+    /// Microsoft Photo region info structure prefix
+    pub const MP_RI_PREFIX: &str = "MPRI";
+    /// Microsoft Photo region structure prefix
+    pub const MP_REG_PREFIX: &str = "MPReg";
+    /// Lightroom prefix
+    pub const LIGHTROOM_PREFIX: &str = "lr";
+    /// Microsoft Photo prefix
+    pub const MICROSOFT_PHOTO_PREFIX: &str = "MicrosoftPhoto";
+    /// PLUS prefix
+    pub const PLUS_PREFIX: &str = "plus";
 }
 
 /// Map of namespace URI to prefix
@@ -143,6 +215,25 @@ impl NamespaceMap {
         Ok(())
     }
 
+    /// Bind a namespace URI to another prefix, replacing its current one
+    ///
+    /// The caller checks that the prefix isn't bound to a different URI.
+    pub(crate) fn set_prefix(&mut self, uri: &str, prefix: &str) {
+        if let Some(old) = self.uri_to_prefix.remove(uri) {
+            if self
+                .prefix_to_uri
+                .get(&old)
+                .is_some_and(|old_uri| old_uri == uri)
+            {
+                self.prefix_to_uri.remove(&old);
+            }
+        }
+        self.uri_to_prefix
+            .insert(uri.to_string(), prefix.to_string());
+        self.prefix_to_uri
+            .insert(prefix.to_string(), uri.to_string());
+    }
+
     /// Get the prefix for a namespace URI
     pub fn get_prefix(&self, uri: &str) -> Option<&str> {
         self.uri_to_prefix.get(uri).map(|s| s.as_str())
@@ -194,11 +285,34 @@ impl NamespaceMap {
         self.register(ns::PDF, ns::PDF_PREFIX).unwrap();
         self.register(ns::PDFX, ns::PDFX_PREFIX).unwrap();
         self.register(ns::PDFA, ns::PDFA_PREFIX).unwrap();
+        self.register(ns::PDFA_EXTENSION, ns::PDFA_EXTENSION_PREFIX)
+            .unwrap();
+        self.register(ns::PDFA_SCHEMA, ns::PDFA_SCHEMA_PREFIX)
+            .unwrap();
+        self.register(ns::PDFA_PROPERTY, ns::PDFA_PROPERTY_PREFIX)
+            .unwrap();
+        self.register(ns::PDFA_TYPE, ns::PDFA_TYPE_PREFIX).unwrap();
+        self.register(ns::PDFA_FIELD, ns::PDFA_FIELD_PREFIX)
+            .unwrap();
         self.register(ns::XMP_DM, ns::XMP_DM_PREFIX).unwrap();
         self.register(ns::XMP_PAGED, ns::XMP_PAGED_PREFIX).unwrap();
         self.register(ns::XMP_GRAPHICS, ns::XMP_GRAPHICS_PREFIX)
             .unwrap();
         self.register(ns::XMP_IMAGE, ns::XMP_IMAGE_PREFIX).unwrap();
+        self.register(ns::ST_EVT, ns::ST_EVT_PREFIX).unwrap();
+        self.register(ns::ST_REF, ns::ST_REF_PREFIX).unwrap();
+        self.register(ns::BEXT, ns::BEXT_PREFIX).unwrap();
+        self.register(ns::XMP_NOTE, ns::XMP_NOTE_PREFIX).unwrap();
+        self.register(ns::MWG_RS, ns::MWG_RS_PREFIX).unwrap();
+        self.register(ns::ST_AREA, ns::ST_AREA_PREFIX).unwrap();
+        self.register(ns::ST_DIM, ns::ST_DIM_PREFIX).unwrap();
+        self.register(ns::MP, ns::MP_PREFIX).unwrap();
+        self.register(ns::MP_RI, ns::MP_RI_PREFIX).unwrap();
+        self.register(ns::MP_REG, ns::MP_REG_PREFIX).unwrap();
+        self.register(ns::LIGHTROOM, ns::LIGHTROOM_PREFIX).unwrap();
+        self.register(ns::MICROSOFT_PHOTO, ns::MICROSOFT_PHOTO_PREFIX)
+            .unwrap();
+        self.register(ns::PLUS, ns::PLUS_PREFIX).unwrap();
     }
 }
 
@@ -278,10 +392,28 @@ pub fn get_builtin_namespace_uris() -> Vec<String> {
         ns::PDF.to_string(),
         ns::PDFX.to_string(),
         ns::PDFA.to_string(),
+        ns::PDFA_EXTENSION.to_string(),
+        ns::PDFA_SCHEMA.to_string(),
+        ns::PDFA_PROPERTY.to_string(),
+        ns::PDFA_TYPE.to_string(),
+        ns::PDFA_FIELD.to_string(),
         ns::XMP_DM.to_string(),
         ns::XMP_PAGED.to_string(),
         ns::XMP_GRAPHICS.to_string(),
         ns::XMP_IMAGE.to_string(),
+        ns::ST_EVT.to_string(),
+        ns::ST_REF.to_string(),
+        ns::BEXT.to_string(),
+        ns::XMP_NOTE.to_string(),
+        ns::MWG_RS.to_string(),
+        ns::ST_AREA.to_string(),
+        ns::ST_DIM.to_string(),
+        ns::MP.to_string(),
+        ns::MP_RI.to_string(),
+        ns::MP_REG.to_string(),
+        ns::LIGHTROOM.to_string(),
+        ns::MICROSOFT_PHOTO.to_string(),
+        ns::PLUS.to_string(),
         ns::RDF.to_string(),
         ns::XML.to_string(),
     ]
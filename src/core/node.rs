@@ -32,12 +32,14 @@ impl ArrayType {
 }
 
 /// A simple value node
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SimpleNode {
     /// The value of the node
     pub value: String,
     /// Qualifiers attached to this node
     pub qualifiers: Vec<Qualifier>,
+    /// The value is a URI, written as an `rdf:resource` attribute
+    pub is_uri: bool,
 }
 
 impl SimpleNode {
@@ -46,6 +48,15 @@ impl SimpleNode {
         Self {
             value: value.into(),
             qualifiers: Vec::new(),
+            is_uri: false,
+        }
+    }
+
+    /// Create a new simple node whose value is a URI
+    pub fn uri(value: impl Into<String>) -> Self {
+        Self {
+            is_uri: true,
+            ..Self::new(value)
         }
     }
 
@@ -71,7 +82,7 @@ impl SimpleNode {
 }
 
 /// An array node containing multiple child nodes
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ArrayNode {
     /// The items in the array
     pub items: Vec<Node>,
@@ -155,7 +166,7 @@ impl ArrayNode {
 }
 
 /// A structure node containing named fields
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct StructureNode {
     /// The fields in the structure
     pub fields: HashMap<String, Node>,
@@ -222,7 +233,7 @@ impl Default for StructureNode {
 }
 
 /// A node in the XMP data model
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Node {
     /// A simple value node
     Simple(SimpleNode),
@@ -238,6 +249,11 @@ impl Node {
         Node::Simple(SimpleNode::new(value))
     }
 
+    /// Create a new simple node whose value is a URI
+    pub fn uri(value: impl Into<String>) -> Self {
+        Node::Simple(SimpleNode::uri(value))
+    }
+
     /// Create a new array node
     pub fn array(array_type: ArrayType) -> Self {
         Node::Array(ArrayNode::new(array_type))
@@ -263,6 +279,24 @@ impl Node {
         matches!(self, Node::Structure(_))
     }
 
+    /// Get the qualifiers attached to this node
+    pub fn qualifiers(&self) -> &[Qualifier] {
+        match self {
+            Node::Simple(node) => &node.qualifiers,
+            Node::Array(node) => &node.qualifiers,
+            Node::Structure(node) => &node.qualifiers,
+        }
+    }
+
+    /// Get a mutable reference to the qualifiers attached to this node
+    pub fn qualifiers_mut(&mut self) -> &mut Vec<Qualifier> {
+        match self {
+            Node::Simple(node) => &mut node.qualifiers,
+            Node::Array(node) => &mut node.qualifiers,
+            Node::Structure(node) => &mut node.qualifiers,
+        }
+    }
+
     /// Get the simple node, if this is a simple node
     pub fn as_simple(&self) -> Option<&SimpleNode> {
         match self {
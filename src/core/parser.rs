@@ -1,18 +1,260 @@
 //! XMP XML/RDF parser
 //!
 //! This module provides functionality for parsing XMP Packets from XML/RDF format.
+//!
+//! The parser builds the node tree directly from the XML events, without an
+//! intermediate document tree, and can read the XML incrementally from any
+//! [`BufRead`] (see [`XmpParser::parse_reader`]).
+//!
+//! A large fraction of the XMP found in files is slightly broken. In lenient
+//! mode (see [`XmpParser::set_lenient`] and [`XmpMeta::parse_lenient`]), the
+//! parser recovers from common breakage and collects [`ParseWarning`]s
+//! instead of failing:
+//! - control characters, which XML does not allow, are removed
+//! - unescaped `&` and unknown entity references are kept as text
+//! - unclosed elements are closed by the end tag of an enclosing element,
+//!   and unmatched end tags are ignored
+//! - malformed XML ends the parsing, keeping the properties read so far
+//! - `rdf:Description` elements with different `rdf:about` values (with
+//!   [`DescriptionMerge::Merge`]) and properties defined twice are merged,
+//!   the last value winning
+//! - variants of the URIs of built-in namespaces (such as a missing
+//!   trailing `/`) are replaced by the URIs, and undeclared prefixes of
+//!   registered namespaces are resolved
+//!
+//! [`XmpMeta::parse_lenient`]: crate::core::metadata::XmpMeta::parse_lenient
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt;
+use std::io::BufRead;
 
 use crate::core::error::{XmpError, XmpResult};
-use crate::core::namespace::{ns, NamespaceMap};
-use crate::core::node::{Node, StructureNode};
+use crate::core::namespace::{
+    get_builtin_namespace_uris, get_global_namespace_uri, ns, NamespaceMap,
+};
+use crate::core::node::{ArrayNode, ArrayType, Node, StructureNode};
 use crate::types::qualifier::Qualifier;
-use quick_xml::escape::unescape;
-use quick_xml::events::Event;
+use quick_xml::escape::{resolve_predefined_entity, unescape};
+use quick_xml::events::{BytesEnd, BytesStart, Event};
 use quick_xml::Reader;
 
+/// Default maximum nesting depth of XML elements (see
+/// [`ParseOptions::max_depth`])
+///
+/// Real packets nest a few tens of levels at most; the limit keeps the
+/// recursive parsers well within the stack of a thread.
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+/// Options to control XMP parsing
+///
+/// Equivalent to the parsing options of the Adobe XMP Toolkit
+/// (`kXMP_RequireXMPMeta` and `kXMP_StrictAliasing`), plus limits that
+/// protect against hostile input. Exceeding a limit fails the parsing with
+/// `XmpError::ParseError`, even in lenient mode. By default the nesting
+/// depth is limited to [`DEFAULT_MAX_DEPTH`] levels, and the size and the
+/// number of nodes are not limited.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::{ParseOptions, XmpMeta};
+///
+/// let options = ParseOptions::default()
+///     .max_packet_size(1 << 20)
+///     .max_node_count(10_000)
+///     .max_depth(32);
+/// let xml = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"/>"#;
+/// assert!(XmpMeta::parse_with_options(xml, &options).is_ok());
+///
+/// let options = options.require_xmpmeta_element();
+/// assert!(XmpMeta::parse_with_options(xml, &options).is_err());
+/// ```
+#[derive(Default, Clone, Copy, Debug)]
+pub struct ParseOptions {
+    /// Require the `x:xmpmeta` element around `rdf:RDF`
+    pub(crate) require_xmpmeta_element: bool,
+    /// Fail when an alias and its actual property have different values
+    pub(crate) strict_aliasing: bool,
+    /// Maximum size of the packet, in bytes
+    pub(crate) max_packet_size: Option<usize>,
+    /// Maximum number of properties and array items
+    pub(crate) max_node_count: Option<usize>,
+    /// Maximum nesting depth of XML elements
+    pub(crate) max_depth: Option<usize>,
+    /// What to do with descriptions of different resources
+    pub(crate) description_merge: DescriptionMerge,
+}
+
+impl ParseOptions {
+    /// Require the `x:xmpmeta` element around `rdf:RDF`.
+    ///
+    /// By default a bare `rdf:RDF` element is accepted.
+    pub fn require_xmpmeta_element(mut self) -> Self {
+        self.require_xmpmeta_element = true;
+        self
+    }
+
+    /// Fail when an alias and its actual property are both present with
+    /// different values.
+    ///
+    /// By default the alias is dropped and the actual property is kept.
+    pub fn strict_aliasing(mut self) -> Self {
+        self.strict_aliasing = true;
+        self
+    }
+
+    /// Set the maximum size of the packet, in bytes.
+    pub fn max_packet_size(mut self, size: usize) -> Self {
+        self.max_packet_size = Some(size);
+        self
+    }
+
+    /// Set the maximum number of nodes (properties, structure fields and
+    /// array items).
+    pub fn max_node_count(mut self, count: usize) -> Self {
+        self.max_node_count = Some(count);
+        self
+    }
+
+    /// Set the maximum nesting depth of XML elements, counting the
+    /// `x:xmpmeta` and `rdf:RDF` wrappers (default: [`DEFAULT_MAX_DEPTH`]).
+    ///
+    /// The parser is recursive: a much higher limit lets deeply nested
+    /// input overflow the stack, which aborts the process.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Get the maximum nesting depth of XML elements
+    pub(crate) fn depth_limit(&self) -> usize {
+        self.max_depth.unwrap_or(DEFAULT_MAX_DEPTH)
+    }
+
+    /// Set what to do with `rdf:Description` elements that have different
+    /// `rdf:about` values (default: [`DescriptionMerge::Merge`]).
+    pub fn description_merge(mut self, merge: DescriptionMerge) -> Self {
+        self.description_merge = merge;
+        self
+    }
+}
+
+/// What to do with `rdf:Description` elements that have different
+/// `rdf:about` values
+///
+/// Such elements describe different resources, which XMP doesn't allow.
+/// Elements with an empty `rdf:about` or none always describe the first
+/// resource.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::{DescriptionMerge, ParseOptions, XmpMeta};
+///
+/// let xml = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+///                       xmlns:xmp="http://ns.adobe.com/xap/1.0/">
+///   <rdf:Description rdf:about="uuid:a" xmp:Label="Red"/>
+///   <rdf:Description rdf:about="uuid:b" xmp:Label="Blue"/>
+/// </rdf:RDF>"#;
+///
+/// let options = ParseOptions::default().description_merge(DescriptionMerge::Reject);
+/// assert!(XmpMeta::parse_with_options(xml, &options).is_err());
+///
+/// let options = ParseOptions::default().description_merge(DescriptionMerge::Separate);
+/// let resources = XmpMeta::parse_resources(xml, &options).unwrap();
+/// assert_eq!(resources.len(), 2);
+/// assert_eq!(resources[1].name(), "uuid:b");
+/// ```
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DescriptionMerge {
+    /// Merge the properties of all the elements, the last value of a
+    /// property winning
+    #[default]
+    Merge,
+    /// Keep the properties of each resource apart
+    ///
+    /// [`XmpMeta::parse_with_options`] only keeps the first resource; use
+    /// [`XmpMeta::parse_resources`] to get all of them.
+    ///
+    /// [`XmpMeta::parse_with_options`]: crate::core::metadata::XmpMeta::parse_with_options
+    /// [`XmpMeta::parse_resources`]: crate::core::metadata::XmpMeta::parse_resources
+    Separate,
+    /// Fail with `XmpError::ParseError`, even in lenient mode
+    Reject,
+}
+
+/// What a lenient parse recovered from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParseWarningKind {
+    /// Characters that XML does not allow were removed
+    InvalidCharacter,
+    /// An unescaped `&` or an unknown entity reference was kept as text
+    BadEntity,
+    /// An element was not closed
+    UnclosedElement,
+    /// An end tag without a start tag was ignored
+    UnmatchedEndTag,
+    /// The XML is malformed, so the rest of it was skipped
+    MalformedXml,
+    /// `rdf:Description` elements have different `rdf:about` values
+    MismatchedAbout,
+    /// A property is defined more than once
+    DuplicateProperty,
+    /// A namespace prefix is used without being declared
+    UndeclaredPrefix,
+    /// A namespace URI is a variant of the URI of a built-in namespace
+    NamespaceUri,
+}
+
+/// A problem found by a lenient parse
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParseWarning {
+    /// What was recovered from
+    pub kind: ParseWarningKind,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
 /// Parser for XMP Packets
 pub struct XmpParser {
     namespaces: NamespaceMap,
+    /// Options of the current parse
+    options: ParseOptions,
+    /// Number of nodes parsed
+    node_count: usize,
+    /// An `x:xmpmeta` element was found
+    found_xmpmeta: bool,
+    /// Recover from malformed XML instead of failing
+    lenient: bool,
+    /// Problems recovered from in lenient mode
+    warnings: Vec<ParseWarning>,
+    /// Prefixes declared in the document
+    declared: HashSet<String>,
+    /// Names of the elements being parsed, outermost first
+    ///
+    /// The names are only kept when needed, in lenient mode or to require
+    /// `x:xmpmeta`; otherwise the stack only tracks the depth.
+    open: Vec<String>,
+    /// Event buffers to reuse, one for each level of nesting
+    buffers: Vec<Vec<u8>>,
+    /// End tag read for an enclosing element, to be handled by its parser
+    pending: Option<BytesEnd<'static>>,
+    /// The XML is malformed, so the parsing stops
+    failed: bool,
+    /// `rdf:about` of the first `rdf:Description`
+    about: Option<String>,
+    /// The resources other than the first, with
+    /// [`DescriptionMerge::Separate`]
+    resources: Vec<(String, StructureNode)>,
 }
 
 impl XmpParser {
@@ -20,23 +262,161 @@ impl XmpParser {
     pub fn new() -> Self {
         Self {
             namespaces: NamespaceMap::new(),
+            options: ParseOptions::default(),
+            node_count: 0,
+            found_xmpmeta: false,
+            lenient: false,
+            warnings: Vec::new(),
+            declared: HashSet::new(),
+            open: Vec::new(),
+            buffers: Vec::new(),
+            pending: None,
+            failed: false,
+            about: None,
+            resources: Vec::new(),
         }
     }
 
+    /// Recover from malformed XML instead of failing
+    ///
+    /// See the [module documentation](self) for the problems recovered
+    /// from. They are reported by [`warnings`](Self::warnings).
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+
+    /// Get the problems recovered from in lenient mode
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+
+    /// Get the namespaces known to the parser, with those declared in the
+    /// document
+    pub(crate) fn namespaces(&self) -> &NamespaceMap {
+        &self.namespaces
+    }
+
+    /// Take the problems recovered from in lenient mode
+    pub fn take_warnings(&mut self) -> Vec<ParseWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Get the `rdf:about` value of the first resource of the last parse
+    pub fn about(&self) -> Option<&str> {
+        self.about.as_deref()
+    }
+
+    /// Take the `rdf:about` values and properties of the resources other
+    /// than the first, parsed with [`DescriptionMerge::Separate`]
+    pub fn take_resources(&mut self) -> Vec<(String, StructureNode)> {
+        std::mem::take(&mut self.resources)
+    }
+
     /// Parse an XMP Packet from a string
     ///
     /// This function extracts the XMP Packet from the `<?xpacket>` wrapper
     /// and parses the RDF/XML content.
     pub fn parse_packet(&mut self, xml: &str) -> XmpResult<StructureNode> {
+        self.parse_packet_with(xml, &ParseOptions::default())
+    }
+
+    /// Parse an XMP Packet from a string with options
+    ///
+    /// See [`ParseOptions`] for the available options. Alias handling is
+    /// done by [`XmpMeta::parse_with_options`], so
+    /// [`ParseOptions::strict_aliasing`] doesn't apply here.
+    ///
+    /// [`XmpMeta::parse_with_options`]: crate::core::metadata::XmpMeta::parse_with_options
+    pub fn parse_packet_with(
+        &mut self,
+        xml: &str,
+        options: &ParseOptions,
+    ) -> XmpResult<StructureNode> {
+        if let Some(max) = options.max_packet_size {
+            if xml.len() > max {
+                return Err(XmpError::ParseError(format!(
+                    "Packet size of {} bytes exceeds the limit of {} bytes",
+                    xml.len(),
+                    max
+                )));
+            }
+        }
+        self.options = *options;
+        self.node_count = 0;
+        self.found_xmpmeta = false;
+        self.about = None;
+        self.resources.clear();
+
         // Extract XMP Packet content (remove <?xpacket> wrapper)
         let packet_content = self.extract_packet_content(xml)?;
+        let packet_content = if self.lenient {
+            self.remove_invalid_characters(packet_content)
+        } else {
+            Cow::Borrowed(packet_content)
+        };
 
         // Parse RDF/XML
         self.parse_rdf(&packet_content)
     }
 
+    /// Parse an XMP Packet from a reader
+    ///
+    /// The XML is parsed as it is read, so the packet is never held in
+    /// memory as a whole, and reading stops at the `<?xpacket end?>`
+    /// trailer. The packet must be UTF-8. In lenient mode, control
+    /// characters end the parsing instead of being removed.
+    ///
+    /// [`ParseOptions::max_packet_size`] limits the number of bytes read.
+    pub fn parse_reader<R: BufRead>(
+        &mut self,
+        reader: R,
+        options: &ParseOptions,
+    ) -> XmpResult<StructureNode> {
+        self.options = *options;
+        self.node_count = 0;
+        self.found_xmpmeta = false;
+        self.about = None;
+        self.resources.clear();
+
+        // Read one byte more than allowed, to detect oversized packets
+        let limit = options
+            .max_packet_size
+            .map_or(u64::MAX, |max| (max as u64).saturating_add(1));
+        let mut reader = Reader::from_reader(reader.take(limit));
+        // Skip the whitespace before the content, so an empty document is
+        // detected
+        reader.config_mut().trim_text_start = true;
+        let mut buf = Vec::new();
+        match self.next_event(&mut reader, &mut buf)? {
+            Event::Eof => Err(XmpError::ParseError("Invalid XML content".to_string())),
+            Event::Text(_) => Err(XmpError::ParseError("Invalid XML content".to_string())),
+            event => {
+                reader.config_mut().trim_text_start = false;
+                let first = event.into_owned();
+                self.parse_events(reader, Some(first))
+            }
+        }
+    }
+
+    /// Remove the characters that XML 1.0 does not allow
+    fn remove_invalid_characters<'a>(&mut self, content: &'a str) -> Cow<'a, str> {
+        let is_invalid = |c: char| {
+            (c < '\u{20}' && !matches!(c, '\t' | '\n' | '\r'))
+                || matches!(c, '\u{FFFE}' | '\u{FFFF}')
+        };
+        let count = content.chars().filter(|&c| is_invalid(c)).count();
+        if count == 0 {
+            return Cow::Borrowed(content);
+        }
+        self.warn(
+            ParseWarningKind::InvalidCharacter,
+            format!("Removed {} invalid control character(s)", count),
+        );
+        Cow::Owned(content.chars().filter(|&c| !is_invalid(c)).collect())
+    }
+
     /// Extract the XMP Packet content from the `<?xpacket>` wrapper
-    fn extract_packet_content(&self, xml: &str) -> XmpResult<String> {
+    fn extract_packet_content<'a>(&self, xml: &'a str) -> XmpResult<&'a str> {
         // Look for <?xpacket start
         let Some(start_pos) = xml.find("<?xpacket") else {
             return self.validate_and_return_xml(xml);
@@ -51,169 +431,677 @@ impl XmpParser {
             return self.validate_and_return_xml(xml);
         };
 
-        let content = xml[pi_end..pi_end + close_pos].trim().to_string();
-        Ok(content)
+        Ok(xml[pi_end..pi_end + close_pos].trim())
     }
 
     /// Validate XML content and return it if valid
-    fn validate_and_return_xml(&self, xml: &str) -> XmpResult<String> {
+    fn validate_and_return_xml<'a>(&self, xml: &'a str) -> XmpResult<&'a str> {
         let trimmed = xml.trim();
         if trimmed.is_empty() || (!trimmed.starts_with('<') && !trimmed.starts_with("<?xml")) {
             return Err(XmpError::ParseError("Invalid XML content".to_string()));
         }
-        Ok(trimmed.to_string())
+        Ok(trimmed)
     }
 
     /// Parse RDF/XML content into a StructureNode
+    ///
+    /// Every `rdf:Description` found in the document contributes its
+    /// properties to the returned root node. Wrapper elements such as
+    /// `x:xmpmeta` and `rdf:RDF` are descended into.
     fn parse_rdf(&mut self, xml: &str) -> XmpResult<StructureNode> {
-        let mut reader = Reader::from_str(xml);
-        reader.config_mut().trim_text(true);
+        self.parse_events(Reader::from_str(xml), None)
+    }
 
-        let mut buf = Vec::new();
+    /// Parse the XML events of a reader into a StructureNode
+    ///
+    /// `first` is an event already read from the reader. Parsing stops at
+    /// the end of the document or at the `<?xpacket end?>` trailer.
+    fn parse_events<R: BufRead>(
+        &mut self,
+        mut reader: Reader<R>,
+        first: Option<Event<'static>>,
+    ) -> XmpResult<StructureNode> {
+        if self.lenient {
+            let config = reader.config_mut();
+            config.allow_dangling_amp = true;
+            config.allow_unmatched_ends = true;
+            config.check_end_names = false;
+        }
         let mut root = StructureNode::new();
-        let mut stack: Vec<StructureNode> = Vec::new();
-        let mut current_path: Vec<String> = Vec::new();
-        let mut current_qualifiers: Vec<Qualifier> = Vec::new();
 
+        let mut first = first;
+        let mut buf = Vec::new();
         loop {
-            match reader.read_event_into(&mut buf) {
-                Ok(Event::Start(e)) => {
-                    let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+            buf.clear();
+            let event = match first.take() {
+                Some(event) => event,
+                None => self.next_event(&mut reader, &mut buf)?,
+            };
+            match event {
+                Event::Start(e) => {
+                    let name = element_name(&e);
                     let attrs = Self::collect_attributes(&e);
-                    self.process_attributes(&attrs, &mut current_qualifiers);
+                    self.register_namespaces(&attrs);
 
-                    // Handle RDF Description
                     if self.is_description_element(&name) {
-                        self.handle_description_attributes(&attrs, &mut root, &current_qualifiers)?;
+                        self.check_xmpmeta()?;
+                        let resource = self.check_about(&attrs)?;
+                        let mut target = self.take_resource(resource, &mut root);
+                        self.add_description_attributes(&attrs, &mut target)?;
+                        self.parse_property_elements(&mut reader, &name, &mut target)?;
+                        self.put_resource(resource, &mut root, target);
+                    } else {
+                        if is_xmpmeta_element(&name) {
+                            self.found_xmpmeta = true;
+                        }
+                        self.enter(&name)?;
+                    }
+                }
+                Event::Empty(e) => {
+                    let name = element_name(&e);
+                    let attrs = Self::collect_attributes(&e);
+                    self.register_namespaces(&attrs);
+
+                    if self.is_description_element(&name) {
+                        self.check_xmpmeta()?;
+                        let resource = self.check_about(&attrs)?;
+                        let mut target = self.take_resource(resource, &mut root);
+                        self.add_description_attributes(&attrs, &mut target)?;
+                        self.put_resource(resource, &mut root, target);
+                    } else if is_xmpmeta_element(&name) {
+                        self.found_xmpmeta = true;
+                    }
+                }
+                Event::End(_) => {
+                    self.open.pop();
+                }
+                Event::PI(pi) if pi.starts_with(b"xpacket end") => break,
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        if self.options.require_xmpmeta_element && !self.found_xmpmeta {
+            return Err(XmpError::ParseError(
+                "Missing x:xmpmeta element".to_string(),
+            ));
+        }
+        Ok(root)
+    }
+
+    /// Check that an `rdf:Description` is inside `x:xmpmeta`, if required
+    fn check_xmpmeta(&self) -> XmpResult<()> {
+        if self.options.require_xmpmeta_element
+            && !self.open.iter().any(|name| is_xmpmeta_element(name))
+        {
+            return Err(XmpError::ParseError(
+                "rdf:Description outside of an x:xmpmeta element".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Start parsing the content of the element `name`
+    fn enter(&mut self, name: &str) -> XmpResult<()> {
+        let max = self.options.depth_limit();
+        if self.open.len() >= max {
+            return Err(XmpError::ParseError(format!(
+                "Element nesting exceeds the limit of {} levels",
+                max
+            )));
+        }
+        if self.lenient || self.options.require_xmpmeta_element {
+            self.open.push(name.to_string());
+        } else {
+            self.open.push(String::new());
+        }
+        Ok(())
+    }
+
+    /// Get an empty buffer for reading events
+    fn take_buffer(&mut self) -> Vec<u8> {
+        self.buffers.pop().unwrap_or_default()
+    }
+
+    /// Return a buffer taken with [`take_buffer`](Self::take_buffer)
+    fn return_buffer(&mut self, mut buf: Vec<u8>) {
+        buf.clear();
+        self.buffers.push(buf);
+    }
+
+    /// Count a parsed node against the node limit
+    fn count_node(&mut self) -> XmpResult<()> {
+        self.node_count += 1;
+        if let Some(max) = self.options.max_node_count {
+            if self.node_count > max {
+                return Err(XmpError::ParseError(format!(
+                    "Node count exceeds the limit of {} nodes",
+                    max
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the next XML event
+    ///
+    /// In lenient mode, malformed XML is reported as the end of the
+    /// document.
+    fn next_event<'b, R: BufRead>(
+        &mut self,
+        reader: &mut Reader<R>,
+        buf: &'b mut Vec<u8>,
+    ) -> XmpResult<Event<'b>> {
+        if let Some(end) = self.pending.take() {
+            return Ok(Event::End(end));
+        }
+        if self.failed {
+            return Ok(Event::Eof);
+        }
+        let event = reader.read_event_into(buf);
+        self.check_packet_size(reader)?;
+        match event {
+            Ok(event) => Ok(event),
+            Err(e) if self.lenient => {
+                self.failed = true;
+                self.warn(
+                    ParseWarningKind::MalformedXml,
+                    format!(
+                        "XML parsing error at byte {}: {}",
+                        reader.error_position(),
+                        e
+                    ),
+                );
+                Ok(Event::Eof)
+            }
+            Err(e) => Err(xml_error(e)),
+        }
+    }
+
+    /// Check the number of bytes read against the packet size limit
+    fn check_packet_size<R>(&self, reader: &Reader<R>) -> XmpResult<()> {
+        match self.options.max_packet_size {
+            Some(max) if reader.buffer_position() > max as u64 => Err(XmpError::ParseError(
+                format!("Packet size exceeds the limit of {} bytes", max),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Skip an element and its content
+    fn skip_element<R: BufRead>(
+        &mut self,
+        reader: &mut Reader<R>,
+        start: &BytesStart<'_>,
+    ) -> XmpResult<()> {
+        let mut buf = self.take_buffer();
+        let result = reader.read_to_end_into(start.name(), &mut buf);
+        self.return_buffer(buf);
+        self.check_packet_size(reader)?;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) if self.lenient => {
+                self.failed = true;
+                self.warn(
+                    ParseWarningKind::MalformedXml,
+                    format!(
+                        "XML parsing error at byte {}: {}",
+                        reader.error_position(),
+                        e
+                    ),
+                );
+                Ok(())
+            }
+            Err(e) => Err(xml_error(e)),
+        }
+    }
+
+    /// Check if an end tag closes the element `name`
+    ///
+    /// In lenient mode, the end tag of an enclosing element also closes it
+    /// (and is handled again by the parser of the enclosing element), and
+    /// other end tags are ignored.
+    fn closes(&mut self, end: &BytesEnd<'_>, name: &str) -> bool {
+        if !self.lenient || end.name().as_ref() == name.as_bytes() {
+            return true;
+        }
+        let end_name = String::from_utf8_lossy(end.name().as_ref()).to_string();
+        if self.open.contains(&end_name) {
+            self.warn(
+                ParseWarningKind::UnclosedElement,
+                format!("Element <{}> is not closed", name),
+            );
+            self.pending = Some(end.clone().into_owned());
+            true
+        } else {
+            self.warn(
+                ParseWarningKind::UnmatchedEndTag,
+                format!("Ignored unmatched end tag </{}>", end_name),
+            );
+            false
+        }
+    }
+
+    /// Handle the end of the document inside the element `name`
+    ///
+    /// Returns the error to fail with, or `None` in lenient mode.
+    fn unexpected_eof(&mut self, name: &str) -> Option<XmpError> {
+        if !self.lenient {
+            return Some(XmpError::ParseError(
+                "Unexpected end of RDF content".to_string(),
+            ));
+        }
+        if !self.failed {
+            self.warn(
+                ParseWarningKind::UnclosedElement,
+                format!("Element <{}> is not closed", name),
+            );
+        }
+        None
+    }
+
+    /// Record a problem recovered from
+    fn warn(&mut self, kind: ParseWarningKind, message: String) {
+        self.warnings.push(ParseWarning { kind, message });
+    }
+
+    /// Check that all `rdf:Description` elements describe the same resource
+    ///
+    /// Returns the index in `resources` of the resource described by the
+    /// element, or `None` for the first one.
+    fn check_about(&mut self, attrs: &[(String, String)]) -> XmpResult<Option<usize>> {
+        let Some((_, about)) = attrs
+            .iter()
+            .find(|(name, _)| name == "rdf:about" || name == "about")
+        else {
+            return Ok(None);
+        };
+        let first = match &self.about {
+            Some(first) if !first.is_empty() => first,
+            _ => {
+                self.about = Some(about.clone());
+                return Ok(None);
+            }
+        };
+        if about.is_empty() || first == about {
+            return Ok(None);
+        }
+        let message = format!(
+            "rdf:Description elements have different rdf:about values ({:?} and {:?})",
+            first, about
+        );
+        match self.options.description_merge {
+            DescriptionMerge::Merge => {
+                if self.lenient {
+                    self.warn(ParseWarningKind::MismatchedAbout, message);
+                }
+                Ok(None)
+            }
+            DescriptionMerge::Separate => {
+                let index = match self.resources.iter().position(|(uri, _)| uri == about) {
+                    Some(index) => index,
+                    None => {
+                        self.resources.push((about.clone(), StructureNode::new()));
+                        self.resources.len() - 1
                     }
-                    // Handle RDF containers (Seq, Bag, Alt)
-                    else if self.is_array_container(&name) {
-                        self.handle_array_container(&name, &mut root, &mut current_path)?;
+                };
+                Ok(Some(index))
+            }
+            DescriptionMerge::Reject => Err(XmpError::ParseError(message)),
+        }
+    }
+
+    /// Take the properties of a resource returned by
+    /// [`check_about`](Self::check_about), to add to them
+    fn take_resource(
+        &mut self,
+        resource: Option<usize>,
+        root: &mut StructureNode,
+    ) -> StructureNode {
+        match resource {
+            Some(index) => std::mem::take(&mut self.resources[index].1),
+            None => std::mem::take(root),
+        }
+    }
+
+    /// Put back the properties taken by [`take_resource`](Self::take_resource)
+    fn put_resource(
+        &mut self,
+        resource: Option<usize>,
+        root: &mut StructureNode,
+        target: StructureNode,
+    ) {
+        match resource {
+            Some(index) => self.resources[index].1 = target,
+            None => *root = target,
+        }
+    }
+
+    /// Set a field, reporting duplicate properties in lenient mode
+    fn set_field(&mut self, target: &mut StructureNode, key: String, node: Node) {
+        if self.lenient && target.has_field(&key) {
+            self.warn(
+                ParseWarningKind::DuplicateProperty,
+                format!("Property {} is defined more than once", key),
+            );
+        }
+        target.set_field(key, node);
+    }
+
+    /// Parse property elements until the end of the enclosing element `name`
+    ///
+    /// Each child element becomes a field of `target`, keyed by its
+    /// resolved `namespace_uri:name`.
+    fn parse_property_elements<R: BufRead>(
+        &mut self,
+        reader: &mut Reader<R>,
+        name: &str,
+        target: &mut StructureNode,
+    ) -> XmpResult<()> {
+        self.enter(name)?;
+        let mut buf = self.take_buffer();
+        loop {
+            buf.clear();
+            match self.next_event(reader, &mut buf)? {
+                Event::Start(e) => {
+                    let name = element_name(&e);
+                    match self.resolve_name(&name) {
+                        Some(key) => {
+                            let node = self.parse_property_element(reader, &e, false)?;
+                            self.set_field(target, key, node);
+                        }
+                        None => self.skip_element(reader, &e)?,
                     }
-                    // Handle li (list item) - add to current array
-                    // Note: li elements don't push to current_path, they add items to the current array
-                    else if self.is_li_element(&name) {
-                        // Extract qualifiers (xml:lang) for the li element
-                        // These will be used when we encounter the text content
-                        // Don't push to current_path - we're already in an array context
-                    } else if !self.is_rdf_element(&name) {
-                        self.push_element_to_path(&name, &mut current_path);
+                }
+                Event::Empty(e) => {
+                    let name = element_name(&e);
+                    if let Some(key) = self.resolve_name(&name) {
+                        let node = self.parse_property_element(reader, &e, true)?;
+                        self.set_field(target, key, node);
                     }
                 }
-                Ok(Event::Text(e)) => {
-                    // Decode XML entities (e.g., &quot; -> ")
-                    let raw_text = String::from_utf8_lossy(e.as_ref());
-                    let text = match unescape(&raw_text) {
-                        Ok(unescaped) => unescaped.to_string(),
-                        Err(_) => raw_text.to_string(),
-                    };
-
-                    let trimmed_text = text.trim();
-                    if trimmed_text.is_empty() {
-                        continue;
+                Event::End(e) if self.closes(&e, name) => break,
+                Event::Eof => {
+                    if let Some(error) = self.unexpected_eof(name) {
+                        return Err(error);
                     }
+                    break;
+                }
+                _ => {}
+            }
+        }
+        self.open.pop();
+        self.return_buffer(buf);
+        Ok(())
+    }
 
-                    // Check if we're inside an array (current_path ends with "__array__")
-                    let Some(last_path) = current_path.last() else {
-                        continue;
-                    };
-
-                    if last_path == "__array__" {
-                        // We're in an array, add item to the array
-                        self.handle_array_text_item(
-                            &mut root,
-                            &current_path,
-                            trimmed_text,
-                            &current_qualifiers,
-                        )?;
+    /// Parse a single property element (or `rdf:li` array item) into a node
+    ///
+    /// Handles the RDF forms used by XMP:
+    /// - literal text content (simple value)
+    /// - `rdf:resource` attribute (simple value)
+    /// - nested `rdf:Seq`/`rdf:Bag`/`rdf:Alt` (array)
+    /// - nested `rdf:Description` or `rdf:parseType="Resource"` (structure)
+    /// - property attributes on the element itself (structure shorthand)
+    /// - any of the structure forms with an `rdf:value` field, as an element
+    ///   or an attribute (qualified value)
+    /// - `rdf:resource` with property attributes (qualified value)
+    fn parse_property_element<R: BufRead>(
+        &mut self,
+        reader: &mut Reader<R>,
+        start: &BytesStart<'_>,
+        is_empty: bool,
+    ) -> XmpResult<Node> {
+        self.count_node()?;
+        let attrs = Self::collect_attributes(start);
+        self.register_namespaces(&attrs);
+
+        let mut qualifiers = Vec::new();
+        let mut parse_resource = false;
+        let mut resource = None;
+        let mut fields = StructureNode::new();
+
+        for (attr_name, attr_value) in &attrs {
+            if attr_name == "xmlns" || attr_name.starts_with("xmlns:") {
+                continue;
+            }
+            if self.is_lang_attribute(attr_name) {
+                qualifiers.push(Qualifier::new(ns::XML, "lang", attr_value.clone()));
+            } else if attr_name == "rdf:parseType" {
+                parse_resource = attr_value == "Resource";
+            } else if attr_name == "rdf:resource" {
+                resource = Some(attr_value.clone());
+            } else if attr_name == "rdf:value" {
+                fields.set_field(rdf_value_key(), Node::simple(attr_value.clone()));
+            } else if attr_name.starts_with("rdf:") {
+                // rdf:about, rdf:ID, rdf:nodeID, rdf:datatype, ...
+                continue;
+            } else if let Some(key) = self.resolve_name(attr_name) {
+                self.count_node()?;
+                fields.set_field(key, Node::simple(attr_value.clone()));
+            }
+        }
+
+        let name = element_name(start);
+        let mut node = if is_empty {
+            if let Some(uri) = resource {
+                // Property attributes are qualifiers of the resource
+                let mut resource = Node::uri(uri);
+                if !fields.fields.is_empty() {
+                    resource = qualified_value(resource, fields);
+                }
+                resource
+            } else if parse_resource || !fields.fields.is_empty() {
+                Node::Structure(fields)
+            } else {
+                Node::simple("")
+            }
+        } else if parse_resource {
+            self.parse_property_elements(reader, &name, &mut fields)?;
+            Node::Structure(fields)
+        } else {
+            self.parse_element_content(reader, &name, fields)?
+        };
+
+        // A structure with an rdf:value field is a qualified value
+        if let Node::Structure(structure) = &mut node {
+            if let Some(value) = structure.remove_field(&rdf_value_key()) {
+                node = qualified_value(value, std::mem::take(structure));
+            }
+        }
+
+        node.qualifiers_mut().extend(qualifiers);
+        Ok(node)
+    }
+
+    /// Parse the content of the non-empty property element `name`
+    fn parse_element_content<R: BufRead>(
+        &mut self,
+        reader: &mut Reader<R>,
+        name: &str,
+        mut fields: StructureNode,
+    ) -> XmpResult<Node> {
+        let mut text = String::new();
+        let mut child: Option<Node> = None;
+
+        self.enter(name)?;
+        let mut buf = self.take_buffer();
+        loop {
+            buf.clear();
+            match self.next_event(reader, &mut buf)? {
+                Event::Text(e) => {
+                    let content = e.xml_content().map_err(xml_error)?;
+                    if self.lenient && content.contains('&') {
+                        self.warn(
+                            ParseWarningKind::BadEntity,
+                            format!("Unescaped '&' in element <{}>", name),
+                        );
+                    }
+                    text.push_str(&content);
+                }
+                Event::CData(e) => {
+                    text.push_str(&e.xml_content().map_err(xml_error)?);
+                }
+                Event::GeneralRef(e) => {
+                    let entity = e.decode().map_err(xml_error)?;
+                    if let Some(ch) = e.resolve_char_ref().map_err(xml_error)? {
+                        text.push(ch);
+                    } else if let Some(resolved) = resolve_predefined_entity(&entity) {
+                        text.push_str(resolved);
+                    } else if self.lenient {
+                        self.warn(
+                            ParseWarningKind::BadEntity,
+                            format!("Unknown entity reference '&{};' kept as text", entity),
+                        );
+                        text.push_str(&format!("&{};", entity));
                     } else {
-                        // Not in array, set as field
-                        self.handle_simple_text_item(
-                            &mut root,
-                            &mut stack,
-                            last_path,
-                            trimmed_text,
-                            &current_qualifiers,
-                        )?;
+                        return Err(XmpError::ParseError(format!(
+                            "Unknown entity reference '&{};'",
+                            entity
+                        )));
                     }
                 }
-                Ok(Event::End(e)) => {
-                    let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
-
-                    if name == "Seq"
-                        || name == "Bag"
-                        || name == "Alt"
-                        || name.ends_with(":Seq")
-                        || name.ends_with(":Bag")
-                        || name.ends_with(":Alt")
-                    {
-                        // End of array container, pop "__array__" marker
-                        if current_path.last() == Some(&"__array__".to_string()) {
-                            current_path.pop();
-                        }
-                    } else if name != "Description"
-                        && !name.ends_with(":Description")
-                        && name != "RDF"
-                        && !name.ends_with(":RDF")
-                        && name != "li"
-                        && !name.ends_with(":li")
-                    {
-                        current_path.pop();
+                Event::Start(e) => {
+                    let child_name = element_name(&e);
+                    if let Some(array_type) = self.array_type(&child_name) {
+                        let mut array = ArrayNode::new(array_type);
+                        self.parse_array_items(reader, &child_name, &mut array)?;
+                        child = Some(Node::Array(array));
+                    } else if self.is_description_element(&child_name) {
+                        let attrs = Self::collect_attributes(&e);
+                        self.register_namespaces(&attrs);
+                        let mut structure = StructureNode::new();
+                        self.add_description_attributes(&attrs, &mut structure)?;
+                        add_rdf_value_attribute(&attrs, &mut structure);
+                        self.parse_property_elements(reader, &child_name, &mut structure)?;
+                        child = Some(Node::Structure(structure));
+                    } else if let Some(key) = self.resolve_name(&child_name) {
+                        // Field element without an enclosing rdf:Description
+                        let node = self.parse_property_element(reader, &e, false)?;
+                        self.set_field(&mut fields, key, node);
+                    } else {
+                        self.skip_element(reader, &e)?;
                     }
                 }
-                Ok(Event::Eof) => break,
-                Err(e) => {
-                    return Err(XmpError::ParseError(format!("XML parsing error: {}", e)));
+                Event::Empty(e) => {
+                    let child_name = element_name(&e);
+                    if let Some(array_type) = self.array_type(&child_name) {
+                        child = Some(Node::array(array_type));
+                    } else if self.is_description_element(&child_name) {
+                        let attrs = Self::collect_attributes(&e);
+                        self.register_namespaces(&attrs);
+                        let mut structure = StructureNode::new();
+                        self.add_description_attributes(&attrs, &mut structure)?;
+                        add_rdf_value_attribute(&attrs, &mut structure);
+                        child = Some(Node::Structure(structure));
+                    } else if let Some(key) = self.resolve_name(&child_name) {
+                        let node = self.parse_property_element(reader, &e, true)?;
+                        self.set_field(&mut fields, key, node);
+                    }
                 }
-                Ok(Event::Empty(e)) => {
-                    // Handle empty/self-closing elements the same way as Start events
-                    let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
-                    let attrs = Self::collect_attributes_empty(&e);
-                    self.process_attributes(&attrs, &mut current_qualifiers);
-
-                    // Handle RDF Description
-                    if self.is_description_element(&name) {
-                        self.handle_description_attributes(&attrs, &mut root, &current_qualifiers)?;
+                Event::End(e) if self.closes(&e, name) => break,
+                Event::Eof => {
+                    if let Some(error) = self.unexpected_eof(name) {
+                        return Err(error);
                     }
+                    break;
                 }
                 _ => {}
             }
-            buf.clear();
         }
+        self.open.pop();
+        self.return_buffer(buf);
 
-        Ok(root)
+        if let Some(node) = child {
+            Ok(node)
+        } else if !fields.fields.is_empty() {
+            Ok(Node::Structure(fields))
+        } else {
+            Ok(Node::simple(text))
+        }
     }
 
-    /// Process collected attributes: extract namespaces and qualifiers
-    fn process_attributes(
+    /// Parse `rdf:li` items until the end of the array container `name`
+    fn parse_array_items<R: BufRead>(
         &mut self,
-        attrs: &[(String, String)],
-        current_qualifiers: &mut Vec<Qualifier>,
-    ) {
-        // Extract namespace declarations from attributes (on any element)
-        for (attr_name, attr_value) in attrs {
-            if attr_name == "xmlns" {
-                // Default namespace - For XMP, we typically don't use default namespace
-                continue;
-            }
-            if let Some(prefix) = attr_name.strip_prefix("xmlns:") {
-                // Namespace prefix declaration: xmlns:prefix="uri"
-                let _ = self.namespaces.register(attr_value, prefix);
+        reader: &mut Reader<R>,
+        name: &str,
+        array: &mut ArrayNode,
+    ) -> XmpResult<()> {
+        self.enter(name)?;
+        let mut buf = self.take_buffer();
+        loop {
+            buf.clear();
+            match self.next_event(reader, &mut buf)? {
+                Event::Start(e) => {
+                    if self.is_li_element(&element_name(&e)) {
+                        let item = self.parse_property_element(reader, &e, false)?;
+                        array.append(item);
+                    } else {
+                        self.skip_element(reader, &e)?;
+                    }
+                }
+                Event::Empty(e) if self.is_li_element(&element_name(&e)) => {
+                    let item = self.parse_property_element(reader, &e, true)?;
+                    array.append(item);
+                }
+                Event::End(e) if self.closes(&e, name) => break,
+                Event::Eof => {
+                    if let Some(error) = self.unexpected_eof(name) {
+                        return Err(error);
+                    }
+                    break;
+                }
+                _ => {}
             }
         }
+        self.open.pop();
+        self.return_buffer(buf);
+        Ok(())
+    }
 
-        // Extract qualifiers from attributes (e.g., xml:lang)
-        current_qualifiers.clear();
+    /// Register namespace declarations found in an element's attributes
+    ///
+    /// In lenient mode, variants of the URIs of built-in namespaces are
+    /// replaced by the URIs, and a prefix declared again with another URI
+    /// takes the new URI.
+    fn register_namespaces(&mut self, attrs: &[(String, String)]) {
         for (attr_name, attr_value) in attrs {
-            if self.is_lang_attribute(attr_name) {
-                let qualifier = Qualifier::new(ns::XML, "lang", attr_value.clone());
-                current_qualifiers.push(qualifier);
+            // Default namespace - For XMP, we typically don't use default namespace
+            if let Some(prefix) = attr_name.strip_prefix("xmlns:") {
+                // Namespace prefix declaration: xmlns:prefix="uri"
+                self.declared.insert(prefix.to_string());
+                if !self.lenient {
+                    let _ = self.namespaces.register(attr_value, prefix);
+                    continue;
+                }
+
+                let uri = match builtin_uri_variant(attr_value) {
+                    Some(builtin) => {
+                        self.warn(
+                            ParseWarningKind::NamespaceUri,
+                            format!("Namespace URI {:?} replaced by {:?}", attr_value, builtin),
+                        );
+                        builtin
+                    }
+                    None => attr_value.clone(),
+                };
+                if self.namespaces.register(&uri, prefix).is_err() {
+                    let mut namespaces = NamespaceMap::default();
+                    for (existing_uri, existing_prefix) in self.namespaces.get_all_namespaces() {
+                        if existing_prefix != prefix {
+                            let _ = namespaces.register(&existing_uri, &existing_prefix);
+                        }
+                    }
+                    let _ = namespaces.register(&uri, prefix);
+                    self.namespaces = namespaces;
+                }
             }
         }
     }
 
     /// Collect attributes from XML element
-    fn collect_attributes(e: &quick_xml::events::BytesStart<'_>) -> Vec<(String, String)> {
+    fn collect_attributes(e: &BytesStart<'_>) -> Vec<(String, String)> {
         e.attributes()
             .flatten()
             .map(|attr| {
@@ -229,11 +1117,6 @@ impl XmpParser {
             .collect()
     }
 
-    /// Collect attributes from empty XML element (same as Start)
-    fn collect_attributes_empty(e: &quick_xml::events::BytesStart<'_>) -> Vec<(String, String)> {
-        Self::collect_attributes(e)
-    }
-
     /// Check if attribute name is a language qualifier
     fn is_lang_attribute(&self, attr_name: &str) -> bool {
         attr_name == "lang" || attr_name == "xml:lang" || attr_name.ends_with(":lang")
@@ -244,14 +1127,15 @@ impl XmpParser {
         name == "Description" || name.ends_with(":Description")
     }
 
-    /// Check if element name is an array container (Seq, Bag, Alt)
-    fn is_array_container(&self, name: &str) -> bool {
-        name == "Seq"
-            || name == "Bag"
-            || name == "Alt"
-            || name.ends_with(":Seq")
-            || name.ends_with(":Bag")
-            || name.ends_with(":Alt")
+    /// Get the array type for an array container element (Seq, Bag, Alt)
+    fn array_type(&self, name: &str) -> Option<ArrayType> {
+        let local = name.rsplit(':').next().unwrap_or(name);
+        match local {
+            "Seq" => Some(ArrayType::Ordered),
+            "Bag" => Some(ArrayType::Unordered),
+            "Alt" => Some(ArrayType::Alternative),
+            _ => None,
+        }
     }
 
     /// Check if element name is a li element
@@ -259,60 +1143,35 @@ impl XmpParser {
         name == "li" || name.ends_with(":li")
     }
 
-    /// Check if element name is an RDF element
-    fn is_rdf_element(&self, name: &str) -> bool {
-        name == "RDF" || name.ends_with(":RDF")
-    }
-
-    /// Handle Description element attributes
-    fn handle_description_attributes(
-        &self,
+    /// Add Description element attributes as simple properties of `target`
+    fn add_description_attributes(
+        &mut self,
         attrs: &[(String, String)],
-        root: &mut StructureNode,
-        qualifiers: &[Qualifier],
+        target: &mut StructureNode,
     ) -> XmpResult<()> {
+        // xml:lang on the Description applies to its attribute properties
+        let qualifiers: Vec<Qualifier> = attrs
+            .iter()
+            .filter(|(name, _)| self.is_lang_attribute(name))
+            .map(|(_, value)| Qualifier::new(ns::XML, "lang", value.clone()))
+            .collect();
+
         for (attr_name, attr_value) in attrs {
             // Skip xmlns declarations, rdf:about, and qualifiers
             if self.should_skip_attribute(attr_name) {
                 continue;
             }
 
-            // Parse namespace:property format
-            let Some(colon_pos) = attr_name.find(':') else {
-                continue;
-            };
-
-            let ns_prefix = &attr_name[..colon_pos];
-            let prop_name = &attr_name[colon_pos + 1..];
-
-            // Try to get namespace URI for the prefix
-            // Handle case where prefix in attribute name doesn't match declared prefix
-            // (e.g., TC260:AIGC but xmlns:C260="...")
-            let ns_uri = self.namespaces.get_uri(ns_prefix).or_else(|| {
-                // If prefix not found, try common variations
-                // For TC260, try C260
-                if ns_prefix == "TC260" {
-                    self.namespaces.get_uri("C260")
-                } else if ns_prefix == "C260" {
-                    self.namespaces.get_uri("TC260")
-                } else {
-                    None
-                }
-            });
-
-            let Some(ns_uri) = ns_uri else {
+            let Some(full_path) = self.resolve_name(attr_name) else {
                 continue;
             };
 
-            let full_path = format!("{}:{}", ns_uri, prop_name);
+            self.count_node()?;
             let mut simple_node = Node::simple(attr_value.clone());
-            // Add qualifiers to the node
-            if let Node::Simple(ref mut sn) = simple_node {
-                for qual in qualifiers {
-                    sn.add_qualifier(qual.clone());
-                }
-            }
-            root.set_field(full_path.clone(), simple_node);
+            simple_node
+                .qualifiers_mut()
+                .extend(qualifiers.iter().cloned());
+            self.set_field(target, full_path, simple_node);
         }
         Ok(())
     }
@@ -321,160 +1180,116 @@ impl XmpParser {
     fn should_skip_attribute(&self, attr_name: &str) -> bool {
         attr_name == "xmlns"
             || attr_name.starts_with("xmlns:")
+            || attr_name.starts_with("rdf:")
             || attr_name == "about"
             || attr_name.ends_with(":about")
             || self.is_lang_attribute(attr_name)
     }
 
-    /// Handle array container (Seq, Bag, Alt)
-    fn handle_array_container(
-        &self,
-        name: &str,
-        root: &mut StructureNode,
-        current_path: &mut Vec<String>,
-    ) -> XmpResult<()> {
-        use crate::core::node::{ArrayNode, ArrayType};
-
-        let array_type = if name.contains("Seq") {
-            ArrayType::Ordered
-        } else if name.contains("Bag") {
-            ArrayType::Unordered
-        } else {
-            ArrayType::Alternative
-        };
-
-        let array_node = ArrayNode::new(array_type);
-        let array_node_wrapper = Node::Array(array_node);
-
-        // Set array to the current path (property name)
-        let Some(last_path) = current_path.last() else {
-            return Ok(());
-        };
-
-        let full_path = self.resolve_path_to_full_format(last_path);
-        root.set_field(full_path.clone(), array_node_wrapper);
-
-        // Mark that we're in an array for adding items
-        // Store the full path so we can reference it later
-        current_path.push("__array__".to_string());
-        Ok(())
-    }
-
-    /// Push element name to current path, resolving namespace if needed
-    fn push_element_to_path(&self, name: &str, current_path: &mut Vec<String>) {
-        let Some(colon_pos) = name.find(':') else {
-            current_path.push(name.to_string());
-            return;
+    /// Resolve a qualified XML name (prefix:name) to the internal
+    /// "namespace_uri:name" format
+    ///
+    /// Returns `None` for unprefixed names and unknown prefixes. In lenient
+    /// mode, undeclared prefixes of built-in or registered namespaces are
+    /// resolved too, with a warning.
+    fn resolve_name(&mut self, name: &str) -> Option<String> {
+        let (ns_prefix, prop_name) = name.split_once(':')?;
+
+        // Try to get namespace URI for the prefix
+        // Handle case where prefix in attribute name doesn't match declared prefix
+        // (e.g., TC260:AIGC but xmlns:C260="...")
+        let ns_uri = self.namespaces.get_uri(ns_prefix).or_else(|| {
+            // If prefix not found, try common variations
+            // For TC260, try C260
+            if ns_prefix == "TC260" {
+                self.namespaces.get_uri("C260")
+            } else if ns_prefix == "C260" {
+                self.namespaces.get_uri("TC260")
+            } else {
+                None
+            }
+        });
+        let ns_uri = match ns_uri {
+            Some(uri) => uri.to_string(),
+            None if self.lenient => get_global_namespace_uri(ns_prefix)?,
+            None => return None,
         };
 
-        let ns_prefix = &name[..colon_pos];
-        let prop_name = &name[colon_pos + 1..];
-
-        if let Some(ns_uri) = self.namespaces.get_uri(ns_prefix) {
-            let full_path = format!("{}:{}", ns_uri, prop_name);
-            current_path.push(full_path);
-        } else {
-            current_path.push(name.to_string());
+        if self.lenient && ns_prefix != "xml" && self.declared.insert(ns_prefix.to_string()) {
+            self.warn(
+                ParseWarningKind::UndeclaredPrefix,
+                format!(
+                    "Namespace prefix '{}' is not declared, using {:?}",
+                    ns_prefix, ns_uri
+                ),
+            );
         }
+
+        Some(format!("{}:{}", ns_uri, prop_name))
     }
+}
 
-    /// Resolve path to full format (namespace URI:property)
-    fn resolve_path_to_full_format(&self, path: &str) -> String {
-        if path.starts_with("http://") {
-            return path.to_string();
-        }
+/// Check if an element name is `x:xmpmeta` (or the older `x:xapmeta`)
+fn is_xmpmeta_element(name: &str) -> bool {
+    let local = name.rsplit(':').next().unwrap_or(name);
+    local == "xmpmeta" || local == "xapmeta"
+}
 
-        let Some(colon_pos) = path.find(':') else {
-            return path.to_string();
-        };
+/// Get the built-in namespace URI that `uri` is a variant of
+///
+/// Variants differ in case, surrounding whitespace or the trailing `/` or
+/// `#`. Returns `None` for the built-in URIs themselves and other URIs.
+fn builtin_uri_variant(uri: &str) -> Option<String> {
+    let normalize = |uri: &str| uri.trim().trim_end_matches(['/', '#']).to_ascii_lowercase();
+    let normalized = normalize(uri);
+    get_builtin_namespace_uris()
+        .into_iter()
+        .find(|builtin| builtin != uri && normalize(builtin) == normalized)
+}
 
-        let ns_prefix = &path[..colon_pos];
-        let prop_name = &path[colon_pos + 1..];
+/// Internal key of the `rdf:value` field of a qualified value
+fn rdf_value_key() -> String {
+    format!("{}:value", ns::RDF)
+}
 
-        self.namespaces
-            .get_uri(ns_prefix)
-            .map(|ns_uri| format!("{}:{}", ns_uri, prop_name))
-            .unwrap_or_else(|| path.to_string())
+/// Add the `rdf:value` attribute of a nested `rdf:Description`, the value
+/// of a qualified property, to its fields
+fn add_rdf_value_attribute(attrs: &[(String, String)], target: &mut StructureNode) {
+    if let Some((_, value)) = attrs.iter().find(|(name, _)| name == "rdf:value") {
+        target.set_field(rdf_value_key(), Node::simple(value.clone()));
     }
+}
 
-    /// Handle text item in an array context
-    fn handle_array_text_item(
-        &self,
-        root: &mut StructureNode,
-        current_path: &[String],
-        text: &str,
-        qualifiers: &[Qualifier],
-    ) -> XmpResult<()> {
-        // Get the property path (the element before "__array__")
-        if current_path.len() < 2 {
-            return Ok(());
-        }
-
-        let prop_path = &current_path[current_path.len() - 2];
-        let full_path = prop_path.clone();
+/// Build a qualified value node from the `rdf:value` node and the
+/// remaining fields, which become its qualifiers
+fn qualified_value(mut value: Node, others: StructureNode) -> Node {
+    let mut keys: Vec<_> = others.fields.keys().cloned().collect();
+    keys.sort();
 
-        let Some(Node::Array(ref mut arr)) = root.get_field_mut(&full_path) else {
-            return Ok(());
+    let mut fields = others.fields;
+    for key in keys {
+        let Some(Node::Simple(simple)) = fields.remove(&key) else {
+            continue;
         };
-
-        let mut simple_node = Node::simple(text);
-        // Add qualifiers to the node
-        if let Node::Simple(ref mut sn) = simple_node {
-            for qual in qualifiers {
-                sn.add_qualifier(qual.clone());
-            }
-        }
-        arr.append(simple_node);
-        Ok(())
-    }
-
-    /// Handle simple text item (not in array)
-    fn handle_simple_text_item(
-        &self,
-        root: &mut StructureNode,
-        stack: &mut [StructureNode],
-        last_path: &str,
-        text: &str,
-        qualifiers: &[Qualifier],
-    ) -> XmpResult<()> {
-        // Resolve path to full format
-        let path_to_check = if last_path.starts_with("http://") {
-            last_path.to_string()
-        } else if let Some(colon_pos) = last_path.find(':') {
-            let ns_prefix = &last_path[..colon_pos];
-            let prop_name = &last_path[colon_pos + 1..];
-            self.namespaces
-                .get_uri(ns_prefix)
-                .map(|ns_uri| format!("{}:{}", ns_uri, prop_name))
-                .unwrap_or_else(|| last_path.to_string())
-        } else {
-            last_path.to_string()
+        let Some((namespace, name)) = key.rsplit_once(':') else {
+            continue;
         };
+        value
+            .qualifiers_mut()
+            .push(Qualifier::new(namespace, name, simple.value));
+    }
+    value.qualifiers_mut().extend(others.qualifiers);
+    value
+}
 
-        // Only set as simple node if there's no existing array
-        let has_array = root
-            .get_field(&path_to_check)
-            .map(|n| n.is_array())
-            .unwrap_or(false);
-        if has_array {
-            return Ok(());
-        }
-
-        let mut simple_node = Node::simple(text);
-        // Add qualifiers to the node
-        if let Node::Simple(ref mut sn) = simple_node {
-            for qual in qualifiers {
-                sn.add_qualifier(qual.clone());
-            }
-        }
+/// Get the qualified name of an element as a string
+fn element_name<'a>(e: &'a BytesStart<'_>) -> Cow<'a, str> {
+    String::from_utf8_lossy(e.name().into_inner())
+}
 
-        if let Some(parent) = stack.last_mut() {
-            parent.set_field(path_to_check.clone(), simple_node);
-        } else {
-            root.set_field(path_to_check.clone(), simple_node);
-        }
-        Ok(())
-    }
+/// Convert an XML reader error to a parse error
+fn xml_error(e: impl std::fmt::Display) -> XmpError {
+    XmpError::ParseError(format!("XML parsing error: {}", e))
 }
 
 impl Default for XmpParser {
@@ -522,4 +1337,309 @@ mod tests {
 
         assert!(root.has_field("http://ns.adobe.com/xap/1.0/:CreatorTool"));
     }
+
+    #[test]
+    fn test_parse_structures() {
+        let mut parser = XmpParser::new();
+        let xml = r#"
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+         xmlns:exif="http://ns.adobe.com/exif/1.0/"
+         xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <rdf:Description rdf:about="">
+    <exif:Flash rdf:parseType="Resource">
+      <exif:Fired>True</exif:Fired>
+      <exif:Mode>2</exif:Mode>
+    </exif:Flash>
+    <dc:source>
+      <rdf:Description exif:Attr="A">
+        <exif:Elem>B &amp; C</exif:Elem>
+      </rdf:Description>
+    </dc:source>
+    <dc:relation exif:Short="S"/>
+  </rdf:Description>
+</rdf:RDF>"#;
+
+        let root = parser.parse_rdf(xml).unwrap();
+
+        let flash = root
+            .get_field("http://ns.adobe.com/exif/1.0/:Flash")
+            .and_then(|n| n.as_structure())
+            .unwrap();
+        assert_eq!(
+            flash
+                .get_field("http://ns.adobe.com/exif/1.0/:Fired")
+                .and_then(|n| n.as_simple())
+                .map(|n| n.value.as_str()),
+            Some("True")
+        );
+        assert_eq!(flash.fields.len(), 2);
+
+        let source = root
+            .get_field("http://purl.org/dc/elements/1.1/:source")
+            .and_then(|n| n.as_structure())
+            .unwrap();
+        assert_eq!(
+            source
+                .get_field("http://ns.adobe.com/exif/1.0/:Elem")
+                .and_then(|n| n.as_simple())
+                .map(|n| n.value.as_str()),
+            Some("B & C")
+        );
+        assert!(source.has_field("http://ns.adobe.com/exif/1.0/:Attr"));
+
+        let relation = root
+            .get_field("http://purl.org/dc/elements/1.1/:relation")
+            .and_then(|n| n.as_structure())
+            .unwrap();
+        assert!(relation.has_field("http://ns.adobe.com/exif/1.0/:Short"));
+
+        // Struct fields must not leak into the top level
+        assert!(!root.has_field("http://ns.adobe.com/exif/1.0/:Fired"));
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        let xml = r#"
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+         xmlns:xmp="http://ns.adobe.com/xap/1.0">
+  <rdf:Description rdf:about="a" xmp:Label="first"/>
+  <rdf:Description rdf:about="b" xmp:Label="second">
+    <dc:title>
+      <rdf:Alt>
+        <rdf:li xml:lang="x-default">&copy; 2024 &amp; more
+      </rdf:Alt>
+    </dc:title>
+    </xmp:Stray>
+    <xmp:Nickname>unclosed
+  </rdf:Description>
+  <rdf:Description rdf:about="b">
+    <xmp:Rating>5</xmp:Rating> <xmp:Bad"#;
+
+        let mut parser = XmpParser::new();
+        assert!(parser.parse_packet(xml).is_err());
+
+        let mut parser = XmpParser::new();
+        parser.set_lenient(true);
+        let root = parser.parse_packet(xml).unwrap();
+        let value = |key: &str| match root.get_field(key) {
+            Some(Node::Simple(simple)) => Some(simple.value.clone()),
+            _ => None,
+        };
+        assert_eq!(
+            value("http://ns.adobe.com/xap/1.0/:Label").as_deref(),
+            Some("second")
+        );
+        assert_eq!(
+            value("http://ns.adobe.com/xap/1.0/:Rating").as_deref(),
+            Some("5")
+        );
+        assert!(value("http://ns.adobe.com/xap/1.0/:Nickname").is_some());
+        let Some(Node::Array(title)) = root.get_field("http://purl.org/dc/elements/1.1/:title")
+        else {
+            panic!("dc:title is not an array");
+        };
+        let Some(Node::Simple(item)) = title.get(0) else {
+            panic!("dc:title has no item");
+        };
+        assert_eq!(item.value.trim(), "&copy; 2024 & more");
+
+        let kinds: Vec<_> = parser.warnings().iter().map(|w| w.kind).collect();
+        for kind in [
+            ParseWarningKind::NamespaceUri,
+            ParseWarningKind::MismatchedAbout,
+            ParseWarningKind::DuplicateProperty,
+            ParseWarningKind::UndeclaredPrefix,
+            ParseWarningKind::BadEntity,
+            ParseWarningKind::UnclosedElement,
+            ParseWarningKind::UnmatchedEndTag,
+            ParseWarningKind::MalformedXml,
+        ] {
+            assert!(kinds.contains(&kind), "missing {:?} in {:?}", kind, kinds);
+        }
+        assert_eq!(parser.take_warnings().len(), kinds.len());
+        assert!(parser.warnings().is_empty());
+    }
+
+    #[test]
+    fn test_rdf_shorthands() {
+        let parse = |property: &str| {
+            let xml = format!(
+                r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+                    xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+                    xmlns:ex="http://ns.example.com/shorthand/">
+                  <rdf:Description rdf:about="">{}</rdf:Description>
+                </rdf:RDF>"#,
+                property
+            );
+            let mut root = XmpParser::new().parse_packet(&xml).unwrap();
+            root.remove_field("http://ns.adobe.com/xap/1.0/:Label")
+                .unwrap()
+        };
+
+        // A qualified value
+        let qualified = parse(
+            r#"<xmp:Label rdf:parseType="Resource">
+                 <rdf:value>Red</rdf:value><ex:by>me</ex:by>
+               </xmp:Label>"#,
+        );
+        assert_eq!(qualified.qualifiers().len(), 1);
+        for form in [
+            r#"<xmp:Label rdf:value="Red" ex:by="me"/>"#,
+            r#"<xmp:Label><rdf:Description><rdf:value>Red</rdf:value><ex:by>me</ex:by></rdf:Description></xmp:Label>"#,
+            r#"<xmp:Label><rdf:Description rdf:value="Red" ex:by="me"/></xmp:Label>"#,
+            r#"<xmp:Label><rdf:Description rdf:value="Red"><ex:by>me</ex:by></rdf:Description></xmp:Label>"#,
+        ] {
+            assert_eq!(parse(form), qualified, "{}", form);
+        }
+
+        // A URI with a qualifier
+        let Node::Simple(uri) =
+            parse(r#"<xmp:Label rdf:resource="http://example.com/" ex:by="me"/>"#)
+        else {
+            panic!("not a simple value");
+        };
+        assert_eq!(uri.value, "http://example.com/");
+        assert_eq!(uri.qualifiers.len(), 1);
+
+        // A structure
+        let structure = parse(
+            r#"<xmp:Label rdf:parseType="Resource"><ex:a>1</ex:a><ex:b>2</ex:b></xmp:Label>"#,
+        );
+        for form in [
+            r#"<xmp:Label ex:a="1" ex:b="2"/>"#,
+            r#"<xmp:Label><rdf:Description ex:a="1" ex:b="2"/></xmp:Label>"#,
+            r#"<xmp:Label><rdf:Description ex:a="1"><ex:b>2</ex:b></rdf:Description></xmp:Label>"#,
+            r#"<xmp:Label><ex:a>1</ex:a><ex:b>2</ex:b></xmp:Label>"#,
+        ] {
+            assert_eq!(parse(form), structure, "{}", form);
+        }
+    }
+
+    #[test]
+    fn test_description_merge() {
+        let xml = r#"
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+         xmlns:xmp="http://ns.adobe.com/xap/1.0/">
+  <rdf:Description rdf:about="" xmp:Rating="1"/>
+  <rdf:Description rdf:about="a" xmp:Label="first"/>
+  <rdf:Description rdf:about="b"><xmp:Label>second</xmp:Label></rdf:Description>
+  <rdf:Description rdf:about="b" xmp:Nickname="b"/>
+  <rdf:Description xmp:CreatorTool="tool"/>
+</rdf:RDF>"#;
+        let label = "http://ns.adobe.com/xap/1.0/:Label";
+        let value = |root: &StructureNode, key: &str| match root.get_field(key) {
+            Some(Node::Simple(simple)) => Some(simple.value.clone()),
+            _ => None,
+        };
+
+        let mut parser = XmpParser::new();
+        let root = parser.parse_packet(xml).unwrap();
+        assert_eq!(value(&root, label).as_deref(), Some("second"));
+        assert_eq!(parser.about(), Some("a"));
+        assert!(parser.take_resources().is_empty());
+
+        let options = ParseOptions::default().description_merge(DescriptionMerge::Separate);
+        let root = parser.parse_packet_with(xml, &options).unwrap();
+        assert_eq!(value(&root, label).as_deref(), Some("first"));
+        assert_eq!(root.fields.len(), 3);
+        let resources = parser.take_resources();
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0].0, "b");
+        assert_eq!(value(&resources[0].1, label).as_deref(), Some("second"));
+        assert_eq!(resources[0].1.fields.len(), 2);
+
+        let options = ParseOptions::default().description_merge(DescriptionMerge::Reject);
+        parser.set_lenient(true);
+        assert!(parser.parse_packet_with(xml, &options).is_err());
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let xml = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+         xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <rdf:Description rdf:about="" dc:format="image/jpeg">
+    <dc:subject><rdf:Bag><rdf:li>a</rdf:li><rdf:li>b</rdf:li></rdf:Bag></dc:subject>
+  </rdf:Description>
+</rdf:RDF>
+</x:xmpmeta>"#;
+        let parse = |options: ParseOptions| XmpParser::new().parse_packet_with(xml, &options);
+
+        let options = ParseOptions::default().require_xmpmeta_element();
+        assert!(parse(options).is_ok());
+        let bare = &xml[xml.find("<rdf:RDF").unwrap()..xml.find("</x:xmpmeta>").unwrap()];
+        assert!(XmpParser::new().parse_packet_with(bare, &options).is_err());
+        assert!(XmpParser::new().parse_packet(bare).is_ok());
+
+        assert!(parse(ParseOptions::default().max_packet_size(xml.len())).is_ok());
+        assert!(parse(ParseOptions::default().max_packet_size(xml.len() - 1)).is_err());
+        // dc:format, dc:subject and its two items
+        assert!(parse(ParseOptions::default().max_node_count(4)).is_ok());
+        assert!(parse(ParseOptions::default().max_node_count(3)).is_err());
+        // x:xmpmeta, rdf:RDF, rdf:Description, dc:subject, rdf:Bag and rdf:li
+        assert!(parse(ParseOptions::default().max_depth(6)).is_ok());
+        assert!(parse(ParseOptions::default().max_depth(5)).is_err());
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        // Nested structures, as a hostile file could hold
+        let nested = |levels: usize| {
+            format!(
+                r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+         xmlns:ns="http://example.com/ns/">
+  <rdf:Description rdf:about="">{}x{}</rdf:Description>
+</rdf:RDF>"#,
+                r#"<ns:a rdf:parseType="Resource">"#.repeat(levels),
+                "</ns:a>".repeat(levels)
+            )
+        };
+
+        // The default limit fails the parsing instead of overflowing the stack
+        let result = XmpParser::new().parse_packet(&nested(10_000));
+        assert!(matches!(result, Err(XmpError::ParseError(_))));
+        assert!(crate::XmpMeta::parse(&nested(10_000)).is_err());
+        assert!(XmpParser::new()
+            .parse_packet(&nested(DEFAULT_MAX_DEPTH - 3))
+            .is_ok());
+        assert!(XmpParser::new()
+            .parse_packet(&nested(DEFAULT_MAX_DEPTH - 1))
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_reader() {
+        let xml = r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+         xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <rdf:Description rdf:about="" dc:format="image/jpeg">
+    <dc:subject><rdf:Bag><rdf:li>a &amp; b</rdf:li></rdf:Bag></dc:subject>
+  </rdf:Description>
+</rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#;
+        let expected = XmpParser::new().parse_packet(xml).unwrap();
+
+        // Data after the trailer is not read
+        let data = format!("{}\0\0 not XML <", xml);
+        let options = ParseOptions::default();
+        let parsed = XmpParser::new()
+            .parse_reader(
+                std::io::BufReader::with_capacity(8, data.as_bytes()),
+                &options,
+            )
+            .unwrap();
+        assert_eq!(parsed, expected);
+
+        let options = ParseOptions::default().max_packet_size(100);
+        assert!(XmpParser::new()
+            .parse_reader(data.as_bytes(), &options)
+            .is_err());
+        for invalid in ["", "  \n", "text"] {
+            assert!(XmpParser::new()
+                .parse_reader(invalid.as_bytes(), &ParseOptions::default())
+                .is_err());
+        }
+    }
 }
@@ -0,0 +1,412 @@
+//! XMP path expressions
+//!
+//! This module handles the XMP path syntax used to address nodes below a
+//! top-level property, as defined by the XMP specification and the Adobe
+//! XMP Toolkit:
+//!
+//! - `prefix:name` - a top-level property
+//! - `prop/prefix:field` - a structure field
+//! - `prop[1]` - an array item (indices are 1-based)
+//! - `prop[last()]` - the last array item
+//! - `prop[?xml:lang="en-US"]` - the item of a localized text array with the given language
+//! - `prop[prefix:field="value"]` - the array item whose field has the given value
+//! - `prop/?prefix:qual` - a qualifier
+//!
+//! The `compose_*` functions build such paths, mirroring `XMPPathComposeUtils`
+//! of the Adobe XMP Toolkit. The resulting paths can be passed to
+//! [`XmpMeta::get_property_by_path`](crate::XmpMeta::get_property_by_path) and
+//! [`XmpMeta::set_property_by_path`](crate::XmpMeta::set_property_by_path).
+//!
+//! # Example
+//!
+//! ```rust
+//! use xmpkit::core::path::{compose_array_item_path, compose_struct_field_path};
+//! use xmpkit::ns;
+//!
+//! let item = compose_array_item_path("History", 2);
+//! let path = compose_struct_field_path(&item, ns::ST_EVT, "when").unwrap();
+//! assert_eq!(path, "History[3]/stEvt:when");
+//! ```
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::namespace::{get_global_namespace_prefix, get_global_namespace_uri};
+
+/// Index value addressing the last item of an array
+///
+/// Passing this to [`compose_array_item_path`] produces `[last()]`.
+pub const LAST_ITEM: usize = usize::MAX;
+
+/// Compose the path to an array item
+///
+/// `index` is 0-based, like the array methods of `XmpMeta`; the path uses
+/// the 1-based XMP syntax. Pass [`LAST_ITEM`] to address the last item.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::core::path::{compose_array_item_path, LAST_ITEM};
+///
+/// assert_eq!(compose_array_item_path("dc:creator", 0), "dc:creator[1]");
+/// assert_eq!(compose_array_item_path("dc:creator", LAST_ITEM), "dc:creator[last()]");
+/// ```
+pub fn compose_array_item_path(array_name: &str, index: usize) -> String {
+    if index == LAST_ITEM {
+        format!("{}[last()]", array_name)
+    } else {
+        format!("{}[{}]", array_name, index + 1)
+    }
+}
+
+/// Compose the path to a structure field
+///
+/// `field_namespace` may be a namespace URI or a registered prefix.
+pub fn compose_struct_field_path(
+    struct_name: &str,
+    field_namespace: &str,
+    field_name: &str,
+) -> XmpResult<String> {
+    let prefix = namespace_prefix(field_namespace)?;
+    check_name(field_name)?;
+    Ok(format!("{}/{}:{}", struct_name, prefix, field_name))
+}
+
+/// Compose the path to a qualifier
+///
+/// `qual_namespace` may be a namespace URI or a registered prefix.
+pub fn compose_qualifier_path(
+    prop_name: &str,
+    qual_namespace: &str,
+    qual_name: &str,
+) -> XmpResult<String> {
+    let prefix = namespace_prefix(qual_namespace)?;
+    check_name(qual_name)?;
+    Ok(format!("{}/?{}:{}", prop_name, prefix, qual_name))
+}
+
+/// Compose the path to the item of a localized text array with a given language
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::core::path::compose_lang_selector;
+///
+/// assert_eq!(compose_lang_selector("dc:title", "en-US"), "dc:title[?xml:lang=\"en-US\"]");
+/// ```
+pub fn compose_lang_selector(array_name: &str, lang: &str) -> String {
+    format!("{}[?xml:lang={}]", array_name, quote(lang))
+}
+
+/// Compose the path to the array item whose field has a given value
+///
+/// `field_namespace` may be a namespace URI or a registered prefix.
+pub fn compose_field_selector(
+    array_name: &str,
+    field_namespace: &str,
+    field_name: &str,
+    field_value: &str,
+) -> XmpResult<String> {
+    let prefix = namespace_prefix(field_namespace)?;
+    check_name(field_name)?;
+    Ok(format!(
+        "{}[{}:{}={}]",
+        array_name,
+        prefix,
+        field_name,
+        quote(field_value)
+    ))
+}
+
+/// Get the registered prefix for a namespace URI or prefix
+fn namespace_prefix(namespace: &str) -> XmpResult<String> {
+    if let Some(prefix) = get_global_namespace_prefix(namespace) {
+        return Ok(prefix);
+    }
+    if get_global_namespace_uri(namespace).is_some() {
+        return Ok(namespace.to_string());
+    }
+    Err(XmpError::BadSchema(format!(
+        "Unregistered schema namespace '{}'. Register the namespace first using register_namespace().",
+        namespace
+    )))
+}
+
+/// Check that a field or qualifier name is a simple XML name
+fn check_name(name: &str) -> XmpResult<()> {
+    if name.is_empty() || name.contains([':', '/', '[', ']', '?', '@']) {
+        return Err(XmpError::BadXPath(format!("Invalid name '{}'", name)));
+    }
+    Ok(())
+}
+
+/// Quote a selector value, doubling any embedded quotes
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+/// A single step of an expanded XMP path
+///
+/// Names are stored in the internal "namespace_uri:name" format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PathStep {
+    /// A top-level property or structure field
+    Field(String),
+    /// An array item (0-based)
+    Index(usize),
+    /// The last array item
+    Last,
+    /// An array item selected by one of its qualifiers (e.g., `xml:lang`)
+    QualifierSelector {
+        namespace: String,
+        name: String,
+        value: String,
+    },
+    /// An array item selected by the value of one of its fields
+    FieldSelector { field: String, value: String },
+    /// A qualifier of the preceding node
+    Qualifier { namespace: String, name: String },
+}
+
+/// Expand an XMP path into steps
+///
+/// `ns_uri` is the namespace of the top-level property when its name is
+/// unprefixed. `resolve_prefix` maps a namespace prefix to its URI.
+pub(crate) fn expand_path<F>(
+    ns_uri: &str,
+    path: &str,
+    resolve_prefix: F,
+) -> XmpResult<Vec<PathStep>>
+where
+    F: Fn(&str) -> XmpResult<String>,
+{
+    let bad_path = |msg: &str| XmpError::BadXPath(format!("{} in path '{}'", msg, path));
+
+    let qualified_name = |name: &str, default_ns: Option<&str>| -> XmpResult<(String, String)> {
+        if name.is_empty() {
+            return Err(bad_path("Empty name"));
+        }
+        match name.split_once(':') {
+            Some((prefix, local)) if !prefix.is_empty() && !local.is_empty() => {
+                Ok((resolve_prefix(prefix)?, local.to_string()))
+            }
+            Some(_) => Err(bad_path("Malformed qualified name")),
+            None => match default_ns {
+                Some(ns) => Ok((ns.to_string(), name.to_string())),
+                None => Err(bad_path("Unqualified name")),
+            },
+        }
+    };
+
+    let mut steps = Vec::new();
+    let mut rest = path;
+
+    // Top-level property name
+    let name_end = rest.find(['/', '[']).unwrap_or(rest.len());
+    let (ns, name) = qualified_name(&rest[..name_end], Some(ns_uri))?;
+    steps.push(PathStep::Field(format!("{}:{}", ns, name)));
+    rest = &rest[name_end..];
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('/') {
+            let end = after.find(['/', '[']).unwrap_or(after.len());
+            let segment = &after[..end];
+            rest = &after[end..];
+
+            if let Some(qual) = segment
+                .strip_prefix('?')
+                .or_else(|| segment.strip_prefix('@'))
+            {
+                let (namespace, name) = qualified_name(qual, None)?;
+                steps.push(PathStep::Qualifier { namespace, name });
+            } else {
+                // Unprefixed fields inherit the namespace of the top-level property
+                let (ns, name) = qualified_name(segment, Some(ns_uri))?;
+                steps.push(PathStep::Field(format!("{}:{}", ns, name)));
+            }
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = find_closing_bracket(after).ok_or_else(|| bad_path("Unclosed bracket"))?;
+            let selector = after[..end].trim();
+            rest = &after[end + 1..];
+
+            if selector.eq_ignore_ascii_case("last()") {
+                steps.push(PathStep::Last);
+            } else if !selector.is_empty() && selector.bytes().all(|b| b.is_ascii_digit()) {
+                let index: usize = selector
+                    .parse()
+                    .map_err(|_| bad_path("Invalid array index"))?;
+                if index == 0 {
+                    return Err(bad_path("Array index must be 1 or greater"));
+                }
+                steps.push(PathStep::Index(index - 1));
+            } else {
+                let (lhs, value) = selector
+                    .split_once('=')
+                    .ok_or_else(|| bad_path("Invalid array selector"))?;
+                let value =
+                    unquote(value.trim()).ok_or_else(|| bad_path("Unquoted selector value"))?;
+                let lhs = lhs.trim();
+
+                if let Some(qual) = lhs.strip_prefix('?').or_else(|| lhs.strip_prefix('@')) {
+                    let (namespace, name) = qualified_name(qual, None)?;
+                    steps.push(PathStep::QualifierSelector {
+                        namespace,
+                        name,
+                        value,
+                    });
+                } else {
+                    let (ns, name) = qualified_name(lhs, None)?;
+                    steps.push(PathStep::FieldSelector {
+                        field: format!("{}:{}", ns, name),
+                        value,
+                    });
+                }
+            }
+        } else {
+            return Err(bad_path("Unexpected character"));
+        }
+    }
+
+    // Qualifiers are leaf values; nothing can follow them
+    if steps[..steps.len() - 1]
+        .iter()
+        .any(|s| matches!(s, PathStep::Qualifier { .. }))
+    {
+        return Err(bad_path("Qualifier must be the last path component"));
+    }
+
+    Ok(steps)
+}
+
+/// Find the `]` closing a selector, skipping over quoted values
+fn find_closing_bracket(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, ch) in s.char_indices() {
+        match (quote, ch) {
+            (None, '"' | '\'') => quote = Some(ch),
+            (Some(q), c) if c == q => quote = None,
+            (None, ']') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Remove matching single or double quotes around a selector value
+///
+/// A doubled quote character inside the value stands for one quote.
+fn unquote(s: &str) -> Option<String> {
+    let quote = s.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let inner = s.strip_prefix(quote)?.strip_suffix(quote)?;
+    let doubled: String = [quote, quote].iter().collect();
+    Some(inner.replace(&doubled, &quote.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+
+    const DC: &str = "http://purl.org/dc/elements/1.1/";
+    const XML: &str = "http://www.w3.org/XML/1998/namespace";
+
+    fn resolve(prefix: &str) -> XmpResult<String> {
+        match prefix {
+            "dc" => Ok(DC.to_string()),
+            "xml" => Ok(XML.to_string()),
+            _ => Err(XmpError::BadSchema(prefix.to_string())),
+        }
+    }
+
+    #[test]
+    fn test_expand_simple_and_indexed() {
+        assert_eq!(
+            expand_path(DC, "creator", resolve).unwrap(),
+            vec![PathStep::Field(format!("{}:creator", DC))]
+        );
+        assert_eq!(
+            expand_path(DC, "dc:creator[2]", resolve).unwrap(),
+            vec![
+                PathStep::Field(format!("{}:creator", DC)),
+                PathStep::Index(1)
+            ]
+        );
+        assert_eq!(
+            expand_path(DC, "creator[last()]", resolve).unwrap()[1],
+            PathStep::Last
+        );
+        assert!(expand_path(DC, "creator[0]", resolve).is_err());
+        assert!(expand_path(DC, "creator[1", resolve).is_err());
+    }
+
+    #[test]
+    fn test_expand_fields_selectors_and_qualifiers() {
+        let steps = expand_path(DC, "title[?xml:lang='en-US']/?xml:lang", resolve).unwrap();
+        assert_eq!(
+            steps[1],
+            PathStep::QualifierSelector {
+                namespace: XML.to_string(),
+                name: "lang".to_string(),
+                value: "en-US".to_string(),
+            }
+        );
+        assert_eq!(
+            steps[2],
+            PathStep::Qualifier {
+                namespace: XML.to_string(),
+                name: "lang".to_string(),
+            }
+        );
+
+        let steps = expand_path(DC, "source[dc:type=\"a]b\"]/dc:name", resolve).unwrap();
+        assert_eq!(
+            steps[1],
+            PathStep::FieldSelector {
+                field: format!("{}:type", DC),
+                value: "a]b".to_string(),
+            }
+        );
+        assert_eq!(steps[2], PathStep::Field(format!("{}:name", DC)));
+
+        assert!(expand_path(DC, "a/?xml:lang/dc:b", resolve).is_err());
+        assert!(expand_path(DC, "a/unknown:b", resolve).is_err());
+    }
+
+    #[test]
+    fn test_compose_paths() {
+        assert_eq!(compose_array_item_path("dc:subject", 2), "dc:subject[3]");
+        assert_eq!(
+            compose_struct_field_path("Flash", ns::EXIF, "Fired").unwrap(),
+            "Flash/exif:Fired"
+        );
+        assert_eq!(
+            compose_struct_field_path("Flash", "exif", "Fired").unwrap(),
+            "Flash/exif:Fired"
+        );
+        assert_eq!(
+            compose_qualifier_path("creator[1]", ns::XMP, "role").unwrap(),
+            "creator[1]/?xmp:role"
+        );
+        assert_eq!(
+            compose_field_selector("History", ns::ST_EVT, "action", "say \"hi\"").unwrap(),
+            "History[stEvt:action=\"say \"\"hi\"\"\"]"
+        );
+        assert!(compose_struct_field_path("a", "http://unknown.example/", "b").is_err());
+        assert!(compose_struct_field_path("a", ns::DC, "x:y").is_err());
+    }
+
+    #[test]
+    fn test_composed_paths_expand() {
+        let path = compose_field_selector("title", ns::DC, "type", "a\"b").unwrap();
+        let steps = expand_path(DC, &path, resolve).unwrap();
+        assert_eq!(
+            steps[1],
+            PathStep::FieldSelector {
+                field: format!("{}:type", DC),
+                value: "a\"b".to_string(),
+            }
+        );
+
+        let path = compose_lang_selector("title", "en-US");
+        let steps = expand_path(DC, &path, resolve).unwrap();
+        assert!(matches!(steps[1], PathStep::QualifierSelector { .. }));
+    }
+}
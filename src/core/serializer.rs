@@ -4,14 +4,255 @@
 
 use crate::core::error::{XmpError, XmpResult};
 use crate::core::namespace::{ns, NamespaceMap};
-use crate::core::node::{ArrayNode, ArrayType, Node, StructureNode};
+use crate::core::node::{ArrayType, Node, StructureNode};
+use crate::types::qualifier::Qualifier;
+use crate::utils::encoding::PacketEncoding;
+use quick_xml::events::attributes::Attribute;
 use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
 use quick_xml::Writer;
 use std::io::Cursor;
 
+/// Default amount of whitespace padding in a packet, in bytes
+const DEFAULT_PADDING: usize = 2048;
+
+/// Padding allowance for a thumbnail when `xmp:Thumbnails` is missing, in bytes
+const THUMBNAIL_PADDING: usize = 10000;
+
+/// Options to control XMP serialization
+///
+/// Equivalent to the serialization options of the Adobe XMP Toolkit
+/// (`kXMP_OmitPacketWrapper`, `kXMP_ReadOnlyPacket`, `kXMP_UseCompactFormat`,
+/// `kXMP_IncludeThumbnailPad`, `kXMP_ExactPacketLength`, and the padding,
+/// newline, indent and base indent parameters).
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::{SerializeOptions, XmpMeta};
+///
+/// let meta = XmpMeta::new();
+/// let options = SerializeOptions::default().use_compact_format().exact_packet_length(4096);
+/// let packet = meta.serialize_with_options(&options).unwrap();
+/// assert_eq!(packet.len(), 4096);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SerializeOptions {
+    /// Do not write the `<?xpacket?>` wrapper
+    pub(crate) omit_packet_wrapper: bool,
+    /// Mark the packet as read-only (`end="r"`)
+    pub(crate) read_only_packet: bool,
+    /// Write simple properties as attributes
+    pub(crate) use_compact_format: bool,
+    /// Add padding for a thumbnail that may be added later
+    pub(crate) include_thumbnail_pad: bool,
+    /// Exact length of the whole packet, in bytes
+    pub(crate) exact_packet_length: Option<usize>,
+    /// Amount of padding, in bytes
+    pub(crate) padding: usize,
+    /// String used for one level of indentation
+    pub(crate) indent: String,
+    /// String used for newlines
+    pub(crate) newline: String,
+    /// Number of indentation levels applied to every line
+    pub(crate) base_indent: usize,
+    /// Character encoding of the packet
+    pub(crate) encoding: PacketEncoding,
+    /// Use the registered prefixes instead of those of the metadata
+    pub(crate) canonical_prefixes: bool,
+    /// RDF form of structures
+    pub(crate) struct_form: StructForm,
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        Self {
+            omit_packet_wrapper: false,
+            read_only_packet: false,
+            use_compact_format: false,
+            include_thumbnail_pad: false,
+            exact_packet_length: None,
+            padding: DEFAULT_PADDING,
+            indent: "  ".to_string(),
+            newline: "\n".to_string(),
+            base_indent: 0,
+            encoding: PacketEncoding::Utf8,
+            canonical_prefixes: false,
+            struct_form: StructForm::ParseTypeResource,
+        }
+    }
+}
+
+impl SerializeOptions {
+    /// Do not write the `<?xpacket?>` wrapper or any padding.
+    ///
+    /// Can't be combined with [`read_only_packet`](Self::read_only_packet),
+    /// [`include_thumbnail_pad`](Self::include_thumbnail_pad) or
+    /// [`exact_packet_length`](Self::exact_packet_length).
+    pub fn omit_packet_wrapper(mut self) -> Self {
+        self.omit_packet_wrapper = true;
+        self
+    }
+
+    /// Mark the packet as read-only.
+    ///
+    /// Read-only packets have no padding, since they are not meant to be
+    /// updated in place.
+    pub fn read_only_packet(mut self) -> Self {
+        self.read_only_packet = true;
+        self
+    }
+
+    /// Write simple unqualified properties as attributes of `rdf:Description`.
+    ///
+    /// By default every property is written as an element.
+    pub fn use_compact_format(mut self) -> Self {
+        self.use_compact_format = true;
+        self
+    }
+
+    /// Add padding for a thumbnail if the metadata has no `xmp:Thumbnails` yet.
+    pub fn include_thumbnail_pad(mut self) -> Self {
+        self.include_thumbnail_pad = true;
+        self
+    }
+
+    /// Pad the packet to exactly `length` bytes.
+    ///
+    /// Serialization fails if the packet doesn't fit. Overrides
+    /// [`padding`](Self::padding).
+    pub fn exact_packet_length(mut self, length: usize) -> Self {
+        self.exact_packet_length = Some(length);
+        self
+    }
+
+    /// Set the amount of whitespace padding, in bytes (default: 2048).
+    pub fn padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Set the string used for one level of indentation (default: two spaces).
+    pub fn indent(mut self, indent: impl Into<String>) -> Self {
+        self.indent = indent.into();
+        self
+    }
+
+    /// Set the string used for newlines (default: `"\n"`).
+    pub fn newline(mut self, newline: impl Into<String>) -> Self {
+        self.newline = newline.into();
+        self
+    }
+
+    /// Set the number of indentation levels applied to every line (default: 0).
+    pub fn base_indent(mut self, base_indent: usize) -> Self {
+        self.base_indent = base_indent;
+        self
+    }
+
+    /// Set the character encoding of the packet (default: UTF-8).
+    ///
+    /// Other encodings declare themselves with a byte order mark in the
+    /// `begin` attribute. Lengths and padding are counted in bytes of the
+    /// encoded packet, so use
+    /// [`XmpMeta::serialize_to_bytes`](crate::XmpMeta::serialize_to_bytes) to
+    /// get it.
+    pub fn encoding(mut self, encoding: PacketEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Write the [registered](crate::register_namespace) prefix of every
+    /// namespace that has one.
+    ///
+    /// By default, the prefixes set with
+    /// [`XmpMeta::set_namespace_prefix`](crate::XmpMeta::set_namespace_prefix)
+    /// are written instead. Namespaces that are not registered keep the
+    /// prefix of the metadata either way.
+    pub fn canonical_prefixes(mut self) -> Self {
+        self.canonical_prefixes = true;
+        self
+    }
+
+    /// Set the RDF form of structures (default:
+    /// [`StructForm::ParseTypeResource`]).
+    pub fn struct_form(mut self, form: StructForm) -> Self {
+        self.struct_form = form;
+        self
+    }
+
+    /// Options matching [`XmpSerializer::serialize_packet`]
+    pub(crate) fn compact_unpadded() -> Self {
+        Self::default().use_compact_format().padding(0)
+    }
+}
+
+/// RDF form of structures
+///
+/// All the forms are read back by [`XmpParser`](crate::core::parser::XmpParser). Empty
+/// structures are always written as `<ns:s rdf:parseType="Resource"/>`.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::{ns, SerializeOptions, StructForm, XmpMeta};
+///
+/// let mut meta = XmpMeta::new();
+/// meta.set_struct_field(ns::EXIF, "Flash", ns::EXIF, "Fired", "True".into())
+///     .unwrap();
+///
+/// let options = SerializeOptions::default().struct_form(StructForm::Attributes);
+/// let packet = meta.serialize_with_options(&options).unwrap();
+/// assert!(packet.contains(r#"<exif:Flash exif:Fired="True"/>"#));
+///
+/// let parsed = XmpMeta::parse(&packet).unwrap();
+/// assert!(parsed.get_struct_field(ns::EXIF, "Flash", ns::EXIF, "Fired").is_some());
+/// ```
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StructForm {
+    /// Fields as elements of a `rdf:parseType="Resource"` property element
+    ///
+    /// ```xml
+    /// <ns:s rdf:parseType="Resource">
+    ///   <f:a>1</f:a>
+    /// </ns:s>
+    /// ```
+    #[default]
+    ParseTypeResource,
+    /// Fields as elements of a nested `rdf:Description`
+    ///
+    /// ```xml
+    /// <ns:s>
+    ///   <rdf:Description>
+    ///     <f:a>1</f:a>
+    ///   </rdf:Description>
+    /// </ns:s>
+    /// ```
+    Description,
+    /// Simple fields without qualifiers as attributes, like the compact
+    /// format of the Adobe XMP Toolkit
+    ///
+    /// The attributes are on the property element if all the fields are
+    /// attributes, and on a nested `rdf:Description` otherwise. A structure
+    /// without such fields uses `rdf:parseType="Resource"`.
+    ///
+    /// ```xml
+    /// <ns:s f:a="1"/>
+    /// <ns:t>
+    ///   <rdf:Description f:a="1">
+    ///     <f:b>
+    ///       <rdf:Seq>...</rdf:Seq>
+    ///     </f:b>
+    ///   </rdf:Description>
+    /// </ns:t>
+    /// ```
+    Attributes,
+}
+
 /// Serializer for XMP Packets
 pub struct XmpSerializer {
     namespaces: NamespaceMap,
+    /// `rdf:about` value of the `rdf:Description` element
+    about: String,
 }
 
 impl XmpSerializer {
@@ -19,12 +260,48 @@ impl XmpSerializer {
     pub fn new() -> Self {
         Self {
             namespaces: NamespaceMap::new(),
+            about: String::new(),
+        }
+    }
+
+    /// Create a serializer with a namespace map of its own
+    ///
+    /// The prefixes of `namespaces` take precedence over the registered
+    /// ones.
+    pub(crate) fn with_namespaces(namespaces: NamespaceMap) -> Self {
+        Self {
+            namespaces,
+            about: String::new(),
         }
     }
 
+    /// Set the `rdf:about` value of the `rdf:Description` element
+    /// (default: empty)
+    pub fn with_about(mut self, about: impl Into<String>) -> Self {
+        self.about = about.into();
+        self
+    }
+
     /// Serialize a StructureNode to RDF/XML
+    ///
+    /// Simple properties are written as attributes (compact format).
     pub fn serialize_rdf(&self, root: &StructureNode) -> XmpResult<String> {
-        let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2);
+        self.serialize_rdf_with(root, &SerializeOptions::compact_unpadded())
+    }
+
+    /// Serialize a StructureNode to RDF/XML with options
+    ///
+    /// Only the format, indentation and newline options apply; the packet
+    /// options are used by [`serialize_packet_with`](Self::serialize_packet_with).
+    pub fn serialize_rdf_with(
+        &self,
+        root: &StructureNode,
+        options: &SerializeOptions,
+    ) -> XmpResult<String> {
+        // Indent by one space per level, then substitute the requested
+        // indentation. Newlines in values are escaped, so every line break
+        // in the output is formatting.
+        let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 1);
 
         // Collect namespaces used in the metadata
         let mut used_namespaces = std::collections::HashMap::new();
@@ -34,12 +311,11 @@ impl XmpSerializer {
         let mut complex_nodes = Vec::new();
 
         for (key, node) in &root.fields {
-            if self.should_serialize_as_element(key, node) {
-                complex_nodes.push((key.clone(), node.clone()));
-            } else if let Some((prefix, prop_name, ns_uri)) = self.parse_path_with_namespace(key) {
-                // Record namespace usage
-                used_namespaces.insert(ns_uri.clone(), prefix.clone());
+            self.collect_namespaces(key, node, &mut used_namespaces);
 
+            if !options.use_compact_format || self.should_serialize_as_element(key, node) {
+                complex_nodes.push((key.clone(), node.clone()));
+            } else if let Some((prefix, prop_name)) = self.parse_path(key) {
                 if let Node::Simple(simple) = node {
                     simple_attrs.push((format!("{}:{}", prefix, prop_name), simple.value.clone()));
                 } else {
@@ -51,9 +327,11 @@ impl XmpSerializer {
         // Write RDF root element with namespaces
         let mut rdf_start = BytesStart::new("rdf:RDF");
         rdf_start.push_attribute(("xmlns:rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#"));
-        rdf_start.push_attribute(("xmlns:xmp", "http://ns.adobe.com/xap/1.0/"));
-        rdf_start.push_attribute(("xmlns:dc", "http://purl.org/dc/elements/1.1/"));
-        rdf_start.push_attribute(("xmlns:exif", "http://ns.adobe.com/exif/1.0/"));
+        for ns_uri in [ns::XMP, ns::DC, ns::EXIF] {
+            if let Some(prefix) = self.prefix_of(ns_uri) {
+                rdf_start.push_attribute((format!("xmlns:{}", prefix).as_str(), ns_uri));
+            }
+        }
         rdf_start.push_attribute(("xmlns:xml", ns::XML));
 
         // Add dynamically discovered namespaces
@@ -76,11 +354,11 @@ impl XmpSerializer {
 
         // Write Description element with attributes and nested elements
         let mut desc_start = BytesStart::new("rdf:Description");
-        desc_start.push_attribute(("rdf:about", ""));
+        desc_start.push_attribute(("rdf:about", self.about.as_str()));
 
         // Add simple attributes to Description
         for (attr_name, attr_value) in &simple_attrs {
-            desc_start.push_attribute((attr_name.as_str(), attr_value.as_str()));
+            push_attribute(&mut desc_start, attr_name, attr_value);
         }
 
         // If there are no complex nodes, use Empty (self-closing) tag
@@ -92,7 +370,7 @@ impl XmpSerializer {
 
             // Serialize complex nodes as nested elements
             for (key, node) in &complex_nodes {
-                self.serialize_node(&mut writer, key, node)?;
+                self.serialize_node(&mut writer, key, node, options.struct_form)?;
             }
 
             writer.write_event(Event::End(BytesEnd::new("rdf:Description")))?;
@@ -100,8 +378,9 @@ impl XmpSerializer {
         writer.write_event(Event::End(BytesEnd::new("rdf:RDF")))?;
 
         let result = writer.into_inner().into_inner();
-        String::from_utf8(result)
-            .map_err(|e| XmpError::SerializationError(format!("UTF-8 encoding error: {}", e)))
+        let rdf = String::from_utf8(result)
+            .map_err(|e| XmpError::SerializationError(format!("UTF-8 encoding error: {}", e)))?;
+        Ok(reindent(&rdf, options))
     }
 
     /// Parse a path in format "namespace_uri:property_name" into (prefix, property_name, namespace_uri)
@@ -117,24 +396,51 @@ impl XmpSerializer {
         let ns_uri = &path[..colon_pos];
         let prop_name = &path[colon_pos + 1..];
 
-        // Try to get prefix from instance namespace map first
+        // Namespace not registered - return None (following C++ SDK behavior)
+        // In C++ SDK, unregistered namespaces would cause an error during serialization
+        let prefix = self.prefix_of(ns_uri)?;
+        Some((prefix, prop_name.to_string(), ns_uri.to_string()))
+    }
+
+    /// Get the prefix of a namespace URI, from the instance namespace map
+    /// first and then from the global namespace registry
+    fn prefix_of(&self, ns_uri: &str) -> Option<String> {
         if let Some(prefix) = self.namespaces.get_prefix(ns_uri) {
-            return Some((
-                prefix.to_string(),
-                prop_name.to_string(),
-                ns_uri.to_string(),
-            ));
+            return Some(prefix.to_string());
         }
-
-        // Fallback: check global namespace registry
         use crate::core::namespace::get_global_namespace_prefix;
-        if let Some(prefix) = get_global_namespace_prefix(ns_uri) {
-            return Some((prefix, prop_name.to_string(), ns_uri.to_string()));
-        }
+        get_global_namespace_prefix(ns_uri)
+    }
 
-        // Namespace not registered - return None (following C++ SDK behavior)
-        // In C++ SDK, unregistered namespaces would cause an error during serialization
-        None
+    /// Record the namespaces used by a node, its fields, items and qualifiers
+    fn collect_namespaces(
+        &self,
+        key: &str,
+        node: &Node,
+        used_namespaces: &mut std::collections::HashMap<String, String>,
+    ) {
+        if let Some((prefix, _, ns_uri)) = self.parse_path_with_namespace(key) {
+            used_namespaces.insert(ns_uri, prefix);
+        }
+        for qualifier in node.qualifiers() {
+            if let Some((prefix, _, ns_uri)) = self.parse_path_with_namespace(&qualifier.path()) {
+                used_namespaces.insert(ns_uri, prefix);
+            }
+        }
+        match node {
+            Node::Simple(_) => {}
+            Node::Array(array) => {
+                for item in &array.items {
+                    // Array items have no name of their own
+                    self.collect_namespaces("", item, used_namespaces);
+                }
+            }
+            Node::Structure(structure) => {
+                for (field_key, field) in &structure.fields {
+                    self.collect_namespaces(field_key, field, used_namespaces);
+                }
+            }
+        }
     }
 
     /// Parse a path in format "namespace_uri:property_name" into (prefix, property_name)
@@ -144,118 +450,178 @@ impl XmpSerializer {
             .map(|(prefix, prop_name, _)| (prefix, prop_name))
     }
 
-    /// Serialize a node
+    /// Serialize a node as a property element
     fn serialize_node(
         &self,
         writer: &mut Writer<Cursor<Vec<u8>>>,
         path: &str,
         node: &Node,
+        form: StructForm,
     ) -> XmpResult<()> {
-        match node {
-            Node::Simple(simple) => {
-                self.serialize_simple_node(writer, path, simple)?;
-            }
-            Node::Array(array) => {
-                self.serialize_array_node(writer, path, array)?;
-            }
-            Node::Structure(structure) => {
-                self.serialize_structure_node(writer, path, structure)?;
-            }
-        }
-        Ok(())
+        let elem_name = self.qualified_name(path)?;
+        self.write_element(writer, &elem_name, node, form)
     }
 
-    /// Serialize a simple node
-    fn serialize_simple_node(
-        &self,
-        writer: &mut Writer<Cursor<Vec<u8>>>,
-        path: &str,
-        node: &crate::core::node::SimpleNode,
-    ) -> XmpResult<()> {
+    /// Get the `prefix:name` of a path in "namespace_uri:property_name"
+    /// format
+    fn qualified_name(&self, path: &str) -> XmpResult<String> {
         let (prefix, prop_name) = self
             .parse_path(path)
             .ok_or_else(|| XmpError::BadXPath(format!("Invalid path format: {}", path)))?;
-
-        let elem_name = format!("{}:{}", prefix, prop_name);
-        let mut elem_start = BytesStart::new(&elem_name);
-
-        // Add qualifiers as attributes (e.g., xml:lang)
-        self.add_lang_qualifier_attributes(&Node::Simple(node.clone()), &mut elem_start);
-
-        writer.write_event(Event::Start(elem_start))?;
-        writer.write_event(Event::Text(BytesText::new(&node.value)))?;
-        writer.write_event(Event::End(BytesEnd::new(&elem_name)))?;
-
-        Ok(())
+        Ok(format!("{}:{}", prefix, prop_name))
     }
 
-    /// Serialize an array node
-    fn serialize_array_node(
+    /// Write an element holding a node and its qualifiers
+    ///
+    /// `xml:lang` is written as an attribute. Other qualifiers use the
+    /// `rdf:value` form:
+    ///
+    /// ```xml
+    /// <ns:prop rdf:parseType="Resource">
+    ///   <rdf:value>value</rdf:value>
+    ///   <q:qualifier>qualifier value</q:qualifier>
+    /// </ns:prop>
+    /// ```
+    fn write_element(
         &self,
         writer: &mut Writer<Cursor<Vec<u8>>>,
-        path: &str,
-        node: &ArrayNode,
+        elem_name: &str,
+        node: &Node,
+        form: StructForm,
     ) -> XmpResult<()> {
-        let (prefix, prop_name) = self
-            .parse_path(path)
-            .ok_or_else(|| XmpError::BadXPath(format!("Invalid path format: {}", path)))?;
-
-        let container_name = match node.array_type {
-            ArrayType::Ordered => "rdf:Seq",
-            ArrayType::Unordered => "rdf:Bag",
-            ArrayType::Alternative => "rdf:Alt",
-        };
+        let mut elem_start = BytesStart::new(elem_name);
 
-        // Write property element containing the container
-        let prop_elem = format!("{}:{}", prefix, prop_name);
-        writer.write_event(Event::Start(BytesStart::new(&prop_elem)))?;
+        // Add qualifiers as attributes (e.g., xml:lang)
+        self.add_lang_qualifier_attributes(node, &mut elem_start);
 
-        // Write container element
-        writer.write_event(Event::Start(BytesStart::new(container_name)))?;
+        let qualifiers: Vec<_> = node
+            .qualifiers()
+            .iter()
+            .filter(|q| !is_lang_qualifier(q))
+            .collect();
 
-        // Write list items
-        for item in &node.items {
-            let mut li_start = BytesStart::new("rdf:li");
-            self.add_lang_qualifier_attributes(item, &mut li_start);
-            writer.write_event(Event::Start(li_start))?;
+        if qualifiers.is_empty() {
+            return self.write_value(writer, elem_start, elem_name, node, form);
+        }
 
-            self.serialize_array_item(writer, item)?;
+        elem_start.push_attribute(("rdf:parseType", "Resource"));
+        writer.write_event(Event::Start(elem_start))?;
 
-            writer.write_event(Event::End(BytesEnd::new("rdf:li")))?;
+        self.write_value(
+            writer,
+            BytesStart::new("rdf:value"),
+            "rdf:value",
+            node,
+            form,
+        )?;
+
+        for qualifier in qualifiers {
+            let (prefix, name) = self.parse_path(&qualifier.path()).ok_or_else(|| {
+                XmpError::BadXPath(format!("Invalid qualifier: {}", qualifier.path()))
+            })?;
+            let qual_name = format!("{}:{}", prefix, name);
+            writer.write_event(Event::Start(BytesStart::new(&qual_name)))?;
+            writer.write_event(Event::Text(BytesText::from_escaped(escape_text(
+                &qualifier.value,
+            ))))?;
+            writer.write_event(Event::End(BytesEnd::new(&qual_name)))?;
         }
 
-        writer.write_event(Event::End(BytesEnd::new(container_name)))?;
-        writer.write_event(Event::End(BytesEnd::new(&prop_elem)))?;
+        writer.write_event(Event::End(BytesEnd::new(elem_name)))?;
         Ok(())
     }
 
-    /// Serialize a structure node
-    fn serialize_structure_node(
+    /// Write an element whose content is the value of a node
+    fn write_value(
         &self,
         writer: &mut Writer<Cursor<Vec<u8>>>,
-        path: &str,
-        node: &StructureNode,
+        mut elem_start: BytesStart<'_>,
+        elem_name: &str,
+        node: &Node,
+        form: StructForm,
     ) -> XmpResult<()> {
-        let (prefix, prop_name) = self
-            .parse_path(path)
-            .ok_or_else(|| XmpError::BadXPath(format!("Invalid path format: {}", path)))?;
-
-        // Write property element containing the structure
-        let prop_elem = format!("{}:{}", prefix, prop_name);
-        writer.write_event(Event::Start(BytesStart::new(&prop_elem)))?;
-
-        // Write structure as nested Description with rdf:parseType="Resource"
-        let mut desc_start = BytesStart::new("rdf:Description");
-        desc_start.push_attribute(("rdf:parseType", "Resource"));
-        writer.write_event(Event::Start(desc_start))?;
-
-        // Write fields
-        for (key, value) in &node.fields {
-            self.serialize_node(writer, key, value)?;
+        match node {
+            Node::Simple(simple) if simple.is_uri => {
+                push_attribute(&mut elem_start, "rdf:resource", &simple.value);
+                writer.write_event(Event::Empty(elem_start))?;
+            }
+            Node::Simple(simple) => {
+                writer.write_event(Event::Start(elem_start))?;
+                writer.write_event(Event::Text(BytesText::from_escaped(escape_text(
+                    &simple.value,
+                ))))?;
+                writer.write_event(Event::End(BytesEnd::new(elem_name)))?;
+            }
+            Node::Array(array) => {
+                let container_name = match array.array_type {
+                    ArrayType::Ordered => "rdf:Seq",
+                    ArrayType::Unordered => "rdf:Bag",
+                    ArrayType::Alternative => "rdf:Alt",
+                };
+
+                writer.write_event(Event::Start(elem_start))?;
+                writer.write_event(Event::Start(BytesStart::new(container_name)))?;
+                for item in &array.items {
+                    self.write_element(writer, "rdf:li", item, form)?;
+                }
+                writer.write_event(Event::End(BytesEnd::new(container_name)))?;
+                writer.write_event(Event::End(BytesEnd::new(elem_name)))?;
+            }
+            Node::Structure(structure) => {
+                let (attrs, elems): (Vec<_>, Vec<_>) =
+                    structure.fields.iter().partition(|(_, field)| {
+                        form == StructForm::Attributes
+                            && matches!(field, Node::Simple(simple) if simple.qualifiers.is_empty() && !simple.is_uri)
+                    });
+
+                if !attrs.is_empty() && elems.is_empty() {
+                    // All fields as attributes of the property element
+                    for (key, field) in attrs {
+                        if let Node::Simple(simple) = field {
+                            push_attribute(
+                                &mut elem_start,
+                                &self.qualified_name(key)?,
+                                &simple.value,
+                            );
+                        }
+                    }
+                    writer.write_event(Event::Empty(elem_start))?;
+                } else if structure.fields.is_empty()
+                    || (attrs.is_empty() && form != StructForm::Description)
+                {
+                    // Write structure fields directly inside the element using rdf:parseType="Resource"
+                    elem_start.push_attribute(("rdf:parseType", "Resource"));
+                    if structure.fields.is_empty() {
+                        writer.write_event(Event::Empty(elem_start))?;
+                    } else {
+                        writer.write_event(Event::Start(elem_start))?;
+                        for (key, value) in elems {
+                            self.serialize_node(writer, key, value, form)?;
+                        }
+                        writer.write_event(Event::End(BytesEnd::new(elem_name)))?;
+                    }
+                } else {
+                    // Fields inside a nested rdf:Description
+                    let mut desc_start = BytesStart::new("rdf:Description");
+                    for (key, field) in attrs {
+                        if let Node::Simple(simple) = field {
+                            push_attribute(
+                                &mut desc_start,
+                                &self.qualified_name(key)?,
+                                &simple.value,
+                            );
+                        }
+                    }
+                    writer.write_event(Event::Start(elem_start))?;
+                    writer.write_event(Event::Start(desc_start))?;
+                    for (key, value) in elems {
+                        self.serialize_node(writer, key, value, form)?;
+                    }
+                    writer.write_event(Event::End(BytesEnd::new("rdf:Description")))?;
+                    writer.write_event(Event::End(BytesEnd::new(elem_name)))?;
+                }
+            }
         }
-
-        writer.write_event(Event::End(BytesEnd::new("rdf:Description")))?;
-        writer.write_event(Event::End(BytesEnd::new(&prop_elem)))?;
         Ok(())
     }
 
@@ -266,68 +632,149 @@ impl XmpSerializer {
             return true;
         };
 
-        // Simple nodes with xml:lang qualifier must be elements
-        simple
-            .qualifiers
-            .iter()
-            .any(|q| q.namespace == ns::XML && q.name == "lang")
+        // Simple nodes with qualifiers (e.g., xml:lang) and URIs must be
+        // elements
+        !simple.qualifiers.is_empty() || simple.is_uri
     }
 
     /// Add language qualifier attributes to an element
     fn add_lang_qualifier_attributes(&self, node: &Node, elem_start: &mut BytesStart) {
-        let Node::Simple(simple) = node else {
-            return;
-        };
-
-        for qualifier in &simple.qualifiers {
-            if qualifier.namespace == ns::XML && qualifier.name == "lang" {
-                elem_start.push_attribute(("xml:lang", qualifier.value.as_str()));
+        for qualifier in node.qualifiers() {
+            if is_lang_qualifier(qualifier) {
+                push_attribute(elem_start, "xml:lang", &qualifier.value);
             }
         }
     }
 
-    /// Serialize an array item
-    fn serialize_array_item(
+    /// Serialize to XMP Packet format
+    ///
+    /// Uses the compact format without padding.
+    pub fn serialize_packet(&self, root: &StructureNode) -> XmpResult<String> {
+        self.serialize_packet_with(root, &SerializeOptions::compact_unpadded())
+    }
+
+    /// Serialize to XMP Packet format with options
+    pub fn serialize_packet_with(
         &self,
-        writer: &mut Writer<Cursor<Vec<u8>>>,
-        item: &Node,
-    ) -> XmpResult<()> {
-        match item {
-            Node::Simple(simple) => {
-                writer.write_event(Event::Text(BytesText::new(&simple.value)))?;
-            }
-            Node::Structure(structure) => {
-                writer.write_event(Event::Start(BytesStart::new("rdf:Description")))?;
-                for (key, value) in &structure.fields {
-                    self.serialize_node(writer, key, value)?;
-                }
-                writer.write_event(Event::End(BytesEnd::new("rdf:Description")))?;
-            }
-            Node::Array(_) => {
-                return Err(XmpError::NotSupported(
-                    "Nested arrays not yet supported".to_string(),
-                ));
-            }
+        root: &StructureNode,
+        options: &SerializeOptions,
+    ) -> XmpResult<String> {
+        let exact_or_thumbnail =
+            options.exact_packet_length.is_some() || options.include_thumbnail_pad;
+        if options.omit_packet_wrapper && (options.read_only_packet || exact_or_thumbnail) {
+            return Err(XmpError::BadParam(
+                "Inconsistent options for omitting the packet wrapper".to_string(),
+            ));
+        }
+        if options.read_only_packet && exact_or_thumbnail {
+            return Err(XmpError::BadParam(
+                "Inconsistent options for a read-only packet".to_string(),
+            ));
         }
-        Ok(())
-    }
 
-    /// Serialize to XMP Packet format
-    pub fn serialize_packet(&self, root: &StructureNode) -> XmpResult<String> {
-        let rdf_content = self.serialize_rdf(root)?;
-
-        // Wrap in xpacket
-        let packet = format!(
-            r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
-{}
-<?xpacket end="w"?>"#,
-            rdf_content
+        let rdf_content = self.serialize_rdf_with(root, options)?;
+        if options.omit_packet_wrapper {
+            return Ok(rdf_content);
+        }
+
+        let base_indent = options.indent.repeat(options.base_indent);
+        let bom = match options.encoding {
+            PacketEncoding::Utf8 => "",
+            _ => "\u{FEFF}",
+        };
+        let header = format!(
+            r#"{}<?xpacket begin="{}" id="W5M0MpCehiHzreSzNTczkc9d"?>{}"#,
+            base_indent, bom, options.newline
+        );
+        let trailer = format!(
+            r#"{}<?xpacket end="{}"?>"#,
+            base_indent,
+            if options.read_only_packet { "r" } else { "w" }
         );
+        let encoded_len = |text: &str| options.encoding.encoded_len(text);
+        let unpadded_len = encoded_len(&header)
+            + encoded_len(&rdf_content)
+            + encoded_len(&options.newline)
+            + encoded_len(&trailer);
+
+        let padding = if let Some(length) = options.exact_packet_length {
+            length.checked_sub(unpadded_len).ok_or_else(|| {
+                XmpError::SerializationError(format!(
+                    "Can't fit into specified packet size: {} bytes needed, {} allowed",
+                    unpadded_len, length
+                ))
+            })?
+        } else if options.read_only_packet {
+            0
+        } else {
+            let thumbnail = options.include_thumbnail_pad
+                && !root.has_field(&format!("{}:Thumbnails", ns::XMP));
+            options.padding + if thumbnail { THUMBNAIL_PADDING } else { 0 }
+        };
+
+        // Padding is ASCII, one code unit per character
+        let unit_size = options.encoding.unit_size();
+        if !padding.is_multiple_of(unit_size) {
+            return Err(XmpError::SerializationError(format!(
+                "Padding of {} bytes is not a whole number of {} characters",
+                padding,
+                options.encoding.name()
+            )));
+        }
 
+        let mut packet = header;
+        packet.push_str(&rdf_content);
+        packet.push_str(&options.newline);
+        push_padding(&mut packet, padding / unit_size, &options.newline);
+        packet.push_str(&trailer);
         Ok(packet)
     }
 }
 
+/// Append `len` characters of whitespace, broken into lines of 100 spaces
+fn push_padding(packet: &mut String, len: usize, newline: &str) {
+    let end = packet.len() + len;
+    while packet.len() < end {
+        let remaining = end - packet.len();
+        if !newline.is_empty() && remaining > 100 + newline.len() {
+            packet.push_str(&" ".repeat(100));
+            packet.push_str(newline);
+        } else {
+            packet.push_str(&" ".repeat(remaining));
+        }
+    }
+}
+
+/// Replace the one-space-per-level indentation written by quick-xml
+fn reindent(xml: &str, options: &SerializeOptions) -> String {
+    xml.split('\n')
+        .map(|line| {
+            let content = line.trim_start_matches(' ');
+            let depth = options.base_indent + line.len() - content.len();
+            format!("{}{}", options.indent.repeat(depth), content)
+        })
+        .collect::<Vec<_>>()
+        .join(&options.newline)
+}
+
+/// Escape text content, including line breaks
+fn escape_text(value: &str) -> String {
+    quick_xml::escape::escape(value)
+        .replace('\n', "&#xA;")
+        .replace('\r', "&#xD;")
+}
+
+/// Add an attribute, escaping line breaks and tabs so they survive parsing
+fn push_attribute(elem: &mut BytesStart<'_>, name: &str, value: &str) {
+    let escaped = escape_text(value).replace('\t', "&#x9;");
+    elem.push_attribute(Attribute::from((name.as_bytes(), escaped.as_bytes())));
+}
+
+/// Check if a qualifier is `xml:lang`
+fn is_lang_qualifier(qualifier: &Qualifier) -> bool {
+    qualifier.namespace == ns::XML && qualifier.name == "lang"
+}
+
 impl Default for XmpSerializer {
     fn default() -> Self {
         Self::new()
@@ -362,4 +809,118 @@ mod tests {
         assert!(packet.contains("rdf:RDF"));
         assert!(packet.contains("xmp:CreatorTool"));
     }
+
+    #[test]
+    fn test_serialize_options() {
+        let serializer = XmpSerializer::new();
+        let mut root = StructureNode::new();
+        root.set_field(
+            "http://ns.adobe.com/xap/1.0/:CreatorTool".to_string(),
+            Node::simple("Line 1\nLine 2".to_string()),
+        );
+
+        // Canonical format writes simple properties as elements
+        let packet = serializer
+            .serialize_packet_with(&root, &SerializeOptions::default())
+            .unwrap();
+        assert!(packet.contains("<xmp:CreatorTool>Line 1&#xA;Line 2</xmp:CreatorTool>"));
+        assert!(packet.ends_with("<?xpacket end=\"w\"?>"));
+        assert!(packet.len() > DEFAULT_PADDING);
+
+        let compact = serializer
+            .serialize_packet_with(&root, &SerializeOptions::default().use_compact_format())
+            .unwrap();
+        assert!(compact.contains("xmp:CreatorTool=\"Line 1&#xA;Line 2\""));
+
+        let exact = serializer
+            .serialize_packet_with(
+                &root,
+                &SerializeOptions::default().exact_packet_length(3000),
+            )
+            .unwrap();
+        assert_eq!(exact.len(), 3000);
+        assert!(serializer
+            .serialize_packet_with(&root, &SerializeOptions::default().exact_packet_length(10))
+            .is_err());
+
+        let read_only = serializer
+            .serialize_packet_with(&root, &SerializeOptions::default().read_only_packet())
+            .unwrap();
+        assert!(read_only.ends_with("<?xpacket end=\"r\"?>"));
+        assert!(read_only.len() < DEFAULT_PADDING);
+
+        let thumbnail = serializer
+            .serialize_packet_with(&root, &SerializeOptions::default().include_thumbnail_pad())
+            .unwrap();
+        assert!(thumbnail.len() > DEFAULT_PADDING + THUMBNAIL_PADDING);
+
+        let bare = serializer
+            .serialize_packet_with(&root, &SerializeOptions::default().omit_packet_wrapper())
+            .unwrap();
+        assert!(bare.starts_with("<rdf:RDF"));
+        assert!(serializer
+            .serialize_packet_with(
+                &root,
+                &SerializeOptions::default()
+                    .omit_packet_wrapper()
+                    .read_only_packet()
+            )
+            .is_err());
+
+        let formatted = serializer
+            .serialize_packet_with(
+                &root,
+                &SerializeOptions::default()
+                    .padding(0)
+                    .indent("\t")
+                    .newline("\r\n")
+                    .base_indent(1),
+            )
+            .unwrap();
+        assert!(formatted.starts_with("\t<?xpacket begin"));
+        assert!(formatted.contains("\r\n\t\t<rdf:Description"));
+        assert!(!formatted.replace("\r\n", "").contains('\n'));
+    }
+
+    #[test]
+    fn test_struct_forms() {
+        use crate::core::node::ArrayNode;
+        use crate::core::parser::XmpParser;
+
+        let serializer = XmpSerializer::new();
+        let mut flash = StructureNode::new();
+        flash.set_field(format!("{}:Fired", ns::EXIF), Node::simple("True"));
+        let mut mixed = flash.clone();
+        mixed.set_field(
+            format!("{}:Mode", ns::EXIF),
+            Node::Array(ArrayNode::new(ArrayType::Ordered)),
+        );
+        let mut root = StructureNode::new();
+        root.set_field(format!("{}:Flash", ns::EXIF), Node::Structure(flash));
+        root.set_field(format!("{}:Mixed", ns::EXIF), Node::Structure(mixed));
+        root.set_field(
+            format!("{}:Empty", ns::EXIF),
+            Node::Structure(StructureNode::new()),
+        );
+
+        for (form, expected) in [
+            (
+                StructForm::ParseTypeResource,
+                "<exif:Flash rdf:parseType=\"Resource\">",
+            ),
+            (StructForm::Description, "<exif:Flash>"),
+            (StructForm::Attributes, "<exif:Flash exif:Fired=\"True\"/>"),
+        ] {
+            let options = SerializeOptions::default().struct_form(form);
+            let packet = serializer.serialize_packet_with(&root, &options).unwrap();
+            assert!(packet.contains(expected), "{:?}: {}", form, packet);
+            assert!(packet.contains("<exif:Empty rdf:parseType=\"Resource\"/>"));
+            if form == StructForm::Attributes {
+                assert!(packet.contains("<rdf:Description exif:Fired=\"True\">"));
+            }
+
+            let parsed = XmpParser::new().parse_packet(&packet).unwrap();
+            assert_eq!(parsed, root, "{:?}", form);
+        }
+    }
 }
@@ -0,0 +1,1045 @@
+//! Schema-aware validation of XMP metadata
+//!
+//! [`XmpValidator`] checks the properties of an [`XmpMeta`] against the
+//! definitions of the built-in schemas (`dc`, `xmp`, `xmpMM`, `exif`,
+//! `Iptc4xmpCore`, `Iptc4xmpExt`, `photoshop` and `plus`), following the
+//! XMP Specification, the IPTC Photo Metadata Standard and the PLUS License
+//! Data Format:
+//! - the form of each property (simple value, Bag, Seq, Alt, language
+//!   alternative or structure)
+//! - the type of simple values and array items (integer, real, boolean,
+//!   rational, URI or date)
+//! - the values of closed choices
+//!
+//! Custom schemas can be described with [`XmpSchema`] and registered with
+//! [`register_schema`]. The validator checks their properties as well, and
+//! [`XmpMeta`] rejects values that don't conform to them when they are set.
+//! Properties of other namespaces are not checked.
+
+use crate::core::alias::{self, XmpAlias};
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::{IterOptions, XmpMeta};
+use crate::core::namespace::{self, ns};
+use crate::core::node::ArrayType;
+use crate::utils::datetime::XmpDateTime;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{OnceLock, RwLock};
+
+/// Form of a property
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ValueForm {
+    /// A simple value
+    Simple,
+    /// An unordered array (rdf:Bag)
+    Bag,
+    /// An ordered array (rdf:Seq)
+    Seq,
+    /// An alternative array (rdf:Alt)
+    Alt,
+    /// An alternative array of `xml:lang` qualified text
+    LangAlt,
+    /// A structure
+    Struct,
+}
+
+impl fmt::Display for ValueForm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ValueForm::Simple => "simple value",
+            ValueForm::Bag => "Bag",
+            ValueForm::Seq => "Seq",
+            ValueForm::Alt => "Alt",
+            ValueForm::LangAlt => "language alternative",
+            ValueForm::Struct => "structure",
+        })
+    }
+}
+
+/// Type of simple values (and of array items)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ValueType {
+    /// Any text
+    Text,
+    /// A decimal integer
+    Integer,
+    /// A decimal number
+    Real,
+    /// `True` or `False`
+    Boolean,
+    /// A rational number (`numerator/denominator`)
+    Rational,
+    /// A URI (text without spaces)
+    Uri,
+    /// An XMP date (ISO 8601)
+    Date,
+    /// A structure
+    Struct,
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ValueType::Text => "text",
+            ValueType::Integer => "integer",
+            ValueType::Real => "real",
+            ValueType::Boolean => "boolean",
+            ValueType::Rational => "rational",
+            ValueType::Uri => "URI",
+            ValueType::Date => "date",
+            ValueType::Struct => "structure",
+        })
+    }
+}
+
+/// Definition of a schema property
+#[derive(Debug, Clone, Copy)]
+struct PropertyDef {
+    name: &'static str,
+    form: ValueForm,
+    value_type: ValueType,
+    /// Allowed values of closed choices (empty for open values)
+    choices: &'static [&'static str],
+}
+
+/// Shorthand for property definitions
+const fn prop(name: &'static str, form: ValueForm, value_type: ValueType) -> PropertyDef {
+    PropertyDef {
+        name,
+        form,
+        value_type,
+        choices: &[],
+    }
+}
+
+/// Shorthand for closed choices of integers
+const fn choice(name: &'static str, choices: &'static [&'static str]) -> PropertyDef {
+    PropertyDef {
+        name,
+        form: ValueForm::Simple,
+        value_type: ValueType::Integer,
+        choices,
+    }
+}
+
+/// Shorthand for closed choices of URIs
+const fn uri_choice(name: &'static str, choices: &'static [&'static str]) -> PropertyDef {
+    PropertyDef {
+        name,
+        form: ValueForm::Simple,
+        value_type: ValueType::Uri,
+        choices,
+    }
+}
+
+use ValueForm::{Alt, Bag, LangAlt, Seq, Simple};
+use ValueType::{Boolean, Date, Integer, Rational, Real, Text, Uri};
+
+/// Dublin Core properties
+const DC: &[PropertyDef] = &[
+    prop("contributor", Bag, Text),
+    prop("coverage", Simple, Text),
+    prop("creator", Seq, Text),
+    prop("date", Seq, Date),
+    prop("description", LangAlt, Text),
+    prop("format", Simple, Text),
+    prop("identifier", Simple, Text),
+    prop("language", Bag, Text),
+    prop("publisher", Bag, Text),
+    prop("relation", Bag, Text),
+    prop("rights", LangAlt, Text),
+    prop("source", Simple, Text),
+    prop("subject", Bag, Text),
+    prop("title", LangAlt, Text),
+    prop("type", Bag, Text),
+];
+
+/// XMP basic properties
+const XMP: &[PropertyDef] = &[
+    prop("Advisory", Bag, Text),
+    prop("BaseURL", Simple, Uri),
+    prop("CreateDate", Simple, Date),
+    prop("CreatorTool", Simple, Text),
+    prop("Identifier", Bag, Text),
+    prop("Label", Simple, Text),
+    prop("MetadataDate", Simple, Date),
+    prop("ModifyDate", Simple, Date),
+    prop("Nickname", Simple, Text),
+    prop("Rating", Simple, Real),
+    prop("Thumbnails", Alt, ValueType::Struct),
+];
+
+/// XMP Media Management properties
+const XMP_MM: &[PropertyDef] = &[
+    prop("DerivedFrom", ValueForm::Struct, ValueType::Struct),
+    prop("DocumentID", Simple, Uri),
+    prop("History", Seq, ValueType::Struct),
+    prop("Ingredients", Bag, ValueType::Struct),
+    prop("InstanceID", Simple, Uri),
+    prop("ManagedFrom", ValueForm::Struct, ValueType::Struct),
+    prop("Manager", Simple, Text),
+    prop("ManageTo", Simple, Uri),
+    prop("ManageUI", Simple, Uri),
+    prop("ManagerVariant", Simple, Text),
+    prop("OriginalDocumentID", Simple, Text),
+    prop("RenditionClass", Simple, Text),
+    prop("RenditionParams", Simple, Text),
+    prop("VersionID", Simple, Text),
+    prop("Versions", Seq, ValueType::Struct),
+];
+
+/// EXIF properties
+const EXIF: &[PropertyDef] = &[
+    prop("ApertureValue", Simple, Rational),
+    prop("BrightnessValue", Simple, Rational),
+    choice("ColorSpace", &["1", "65535"]),
+    prop("DateTimeDigitized", Simple, Date),
+    prop("DateTimeOriginal", Simple, Date),
+    prop("ExifVersion", Simple, Text),
+    prop("ExposureBiasValue", Simple, Rational),
+    choice("ExposureMode", &["0", "1", "2"]),
+    choice(
+        "ExposureProgram",
+        &["0", "1", "2", "3", "4", "5", "6", "7", "8"],
+    ),
+    prop("ExposureTime", Simple, Rational),
+    prop("FNumber", Simple, Rational),
+    prop("Flash", ValueForm::Struct, ValueType::Struct),
+    prop("FocalLength", Simple, Rational),
+    prop("FocalLengthIn35mmFilm", Simple, Integer),
+    prop("GPSAltitude", Simple, Rational),
+    choice("GPSAltitudeRef", &["0", "1"]),
+    prop("GPSLatitude", Simple, Text),
+    prop("GPSLongitude", Simple, Text),
+    prop("GPSTimeStamp", Simple, Date),
+    prop("GPSVersionID", Simple, Text),
+    prop("ISOSpeedRatings", Seq, Integer),
+    prop("LightSource", Simple, Integer),
+    prop("MaxApertureValue", Simple, Rational),
+    choice("MeteringMode", &["0", "1", "2", "3", "4", "5", "6", "255"]),
+    prop("PixelXDimension", Simple, Integer),
+    prop("PixelYDimension", Simple, Integer),
+    choice("SceneCaptureType", &["0", "1", "2", "3"]),
+    prop("ShutterSpeedValue", Simple, Rational),
+    prop("SubjectDistance", Simple, Rational),
+    prop("UserComment", LangAlt, Text),
+    choice("WhiteBalance", &["0", "1"]),
+];
+
+/// IPTC Core properties
+const IPTC_CORE: &[PropertyDef] = &[
+    prop("AltTextAccessibility", LangAlt, Text),
+    prop("CountryCode", Simple, Text),
+    prop("CreatorContactInfo", ValueForm::Struct, ValueType::Struct),
+    prop("ExtDescrAccessibility", LangAlt, Text),
+    prop("IntellectualGenre", Simple, Text),
+    prop("Location", Simple, Text),
+    prop("Scene", Bag, Text),
+    prop("SubjectCode", Bag, Text),
+];
+
+/// IPTC Extension properties
+const IPTC_EXT: &[PropertyDef] = &[
+    prop("AddlModelInfo", Simple, Text),
+    prop("ArtworkOrObject", Bag, ValueType::Struct),
+    prop("DigImageGUID", Simple, Text),
+    prop("DigitalSourceType", Simple, Uri),
+    prop("Event", LangAlt, Text),
+    prop("Genre", Bag, ValueType::Struct),
+    prop("LocationCreated", Bag, ValueType::Struct),
+    prop("LocationShown", Bag, ValueType::Struct),
+    prop("MaxAvailHeight", Simple, Integer),
+    prop("MaxAvailWidth", Simple, Integer),
+    prop("ModelAge", Bag, Integer),
+    prop("OrganisationInImageCode", Bag, Text),
+    prop("OrganisationInImageName", Bag, Text),
+    prop("PersonInImage", Bag, Text),
+    prop("PersonInImageWDetails", Bag, ValueType::Struct),
+    prop("ProductInImage", Bag, ValueType::Struct),
+    prop("RegistryId", Bag, ValueType::Struct),
+];
+
+/// Photoshop properties
+const PHOTOSHOP: &[PropertyDef] = &[
+    prop("AuthorsPosition", Simple, Text),
+    prop("CaptionWriter", Simple, Text),
+    prop("Category", Simple, Text),
+    prop("City", Simple, Text),
+    choice("ColorMode", &["0", "1", "2", "3", "4", "7", "8", "9"]),
+    prop("Country", Simple, Text),
+    prop("Credit", Simple, Text),
+    prop("DateCreated", Simple, Date),
+    prop("DocumentAncestors", Bag, Text),
+    prop("Headline", Simple, Text),
+    prop("History", Simple, Text),
+    prop("ICCProfile", Simple, Text),
+    prop("Instructions", Simple, Text),
+    prop("Source", Simple, Text),
+    prop("State", Simple, Text),
+    prop("SupplementalCategories", Bag, Text),
+    prop("TextLayers", Seq, ValueType::Struct),
+    prop("TransmissionReference", Simple, Text),
+    choice("Urgency", &["0", "1", "2", "3", "4", "5", "6", "7", "8"]),
+];
+
+/// PLUS properties
+const PLUS: &[PropertyDef] = &[
+    prop("CopyrightOwner", Seq, ValueType::Struct),
+    prop("ImageCreator", Seq, ValueType::Struct),
+    prop("ImageSupplier", Seq, ValueType::Struct),
+    prop("Licensee", Seq, ValueType::Struct),
+    prop("Licensor", Seq, ValueType::Struct),
+    uri_choice(
+        "MinorModelAgeDisclosure",
+        &[
+            "http://ns.useplus.org/ldf/vocab/AG-UNK",
+            "http://ns.useplus.org/ldf/vocab/AG-A25",
+            "http://ns.useplus.org/ldf/vocab/AG-A24",
+            "http://ns.useplus.org/ldf/vocab/AG-A23",
+            "http://ns.useplus.org/ldf/vocab/AG-A22",
+            "http://ns.useplus.org/ldf/vocab/AG-A21",
+            "http://ns.useplus.org/ldf/vocab/AG-A20",
+            "http://ns.useplus.org/ldf/vocab/AG-A19",
+            "http://ns.useplus.org/ldf/vocab/AG-A18",
+            "http://ns.useplus.org/ldf/vocab/AG-A17",
+            "http://ns.useplus.org/ldf/vocab/AG-A16",
+            "http://ns.useplus.org/ldf/vocab/AG-A15",
+            "http://ns.useplus.org/ldf/vocab/AG-U14",
+        ],
+    ),
+    prop("ModelReleaseID", Bag, Text),
+    uri_choice(
+        "ModelReleaseStatus",
+        &[
+            "http://ns.useplus.org/ldf/vocab/MR-NON",
+            "http://ns.useplus.org/ldf/vocab/MR-NAP",
+            "http://ns.useplus.org/ldf/vocab/MR-UMR",
+            "http://ns.useplus.org/ldf/vocab/MR-LMR",
+        ],
+    ),
+    prop("PropertyReleaseID", Bag, Text),
+    uri_choice(
+        "PropertyReleaseStatus",
+        &[
+            "http://ns.useplus.org/ldf/vocab/PR-NON",
+            "http://ns.useplus.org/ldf/vocab/PR-NAP",
+            "http://ns.useplus.org/ldf/vocab/PR-UPR",
+            "http://ns.useplus.org/ldf/vocab/PR-LPR",
+        ],
+    ),
+    prop("Version", Simple, Text),
+];
+
+/// Built-in schemas: namespace URI and property definitions
+const SCHEMAS: &[(&str, &[PropertyDef])] = &[
+    (ns::DC, DC),
+    (ns::XMP, XMP),
+    (ns::XMP_MM, XMP_MM),
+    (ns::EXIF, EXIF),
+    (ns::IPTC_CORE, IPTC_CORE),
+    (ns::IPTC_EXT, IPTC_EXT),
+    (ns::PHOTOSHOP, PHOTOSHOP),
+    (ns::PLUS, PLUS),
+];
+
+/// Definition of a property of a custom schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaProperty {
+    pub(crate) name: String,
+    pub(crate) form: ValueForm,
+    pub(crate) value_type: ValueType,
+    pub(crate) choices: Vec<String>,
+}
+
+impl SchemaProperty {
+    /// Name of the property (without prefix)
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Form of the property
+    pub fn form(&self) -> ValueForm {
+        self.form
+    }
+
+    /// Type of the value (or of the array items)
+    pub fn value_type(&self) -> ValueType {
+        self.value_type
+    }
+
+    /// Allowed values of a closed choice (empty for open values)
+    pub fn choices(&self) -> &[String] {
+        &self.choices
+    }
+}
+
+impl From<&PropertyDef> for SchemaProperty {
+    fn from(def: &PropertyDef) -> Self {
+        Self {
+            name: def.name.to_string(),
+            form: def.form,
+            value_type: def.value_type,
+            choices: def.choices.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+}
+
+/// Description of a custom schema
+///
+/// Once registered with [`register_schema`], the schema is used by
+/// [`XmpValidator`], and the setters of [`XmpMeta`] fail with
+/// `XmpError::BadValue` for values of the wrong type or form. Arrays
+/// created by [`XmpMeta::append_array_item`] get the form of the schema.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::core::validate::{register_schema, ValueForm, ValueType, XmpSchema};
+/// use xmpkit::XmpMeta;
+///
+/// const NS: &str = "http://example.com/ns/asset/1.0/";
+///
+/// register_schema(
+///     XmpSchema::new(NS, "asset")
+///         .property("Width", ValueForm::Simple, ValueType::Integer)
+///         .property("Tags", ValueForm::Bag, ValueType::Text)
+///         .choice("Status", ValueForm::Simple, &["draft", "final"]),
+/// )
+/// .unwrap();
+///
+/// let mut meta = XmpMeta::new();
+/// meta.set_property(NS, "Width", 1920.into()).unwrap();
+/// meta.set_property(NS, "Status", "final".into()).unwrap();
+/// assert!(meta.set_property(NS, "Width", "wide".into()).is_err());
+/// assert!(meta.set_property(NS, "Status", "lost".into()).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmpSchema {
+    pub(crate) uri: String,
+    pub(crate) prefix: String,
+    pub(crate) properties: Vec<SchemaProperty>,
+    pub(crate) aliases: Vec<(String, XmpAlias)>,
+}
+
+impl XmpSchema {
+    /// Create an empty schema for a namespace
+    pub fn new(uri: &str, prefix: &str) -> Self {
+        Self {
+            uri: uri.to_string(),
+            prefix: prefix.to_string(),
+            properties: Vec::new(),
+            aliases: Vec::new(),
+        }
+    }
+
+    /// Define a property, replacing any previous definition
+    pub fn property(mut self, name: &str, form: ValueForm, value_type: ValueType) -> Self {
+        self.define(SchemaProperty {
+            name: name.to_string(),
+            form,
+            value_type,
+            choices: Vec::new(),
+        });
+        self
+    }
+
+    /// Define a property whose values are a closed choice of text values
+    ///
+    /// With an array `form`, the choice applies to the items.
+    pub fn choice(mut self, name: &str, form: ValueForm, choices: &[&str]) -> Self {
+        self.define(SchemaProperty {
+            name: name.to_string(),
+            form,
+            value_type: ValueType::Text,
+            choices: choices.iter().map(|c| c.to_string()).collect(),
+        });
+        self
+    }
+
+    /// Define an alias for a property of another schema
+    ///
+    /// The alias is registered with the schema; see
+    /// [`register_alias`](crate::core::alias::register_alias) for `form`.
+    pub fn alias(
+        mut self,
+        name: &str,
+        actual_ns: &str,
+        actual_prop: &str,
+        form: ValueForm,
+    ) -> Self {
+        self.aliases.retain(|(alias, _)| alias != name);
+        self.aliases.push((
+            name.to_string(),
+            XmpAlias {
+                namespace: actual_ns.to_string(),
+                property: actual_prop.to_string(),
+                form,
+            },
+        ));
+        self
+    }
+
+    fn define(&mut self, property: SchemaProperty) {
+        self.properties.retain(|p| p.name != property.name);
+        self.properties.push(property);
+    }
+
+    /// Namespace URI of the schema
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Preferred prefix of the schema
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Get the definition of a property
+    pub fn get_property(&self, name: &str) -> Option<&SchemaProperty> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    /// The property definitions, in definition order
+    pub fn properties(&self) -> &[SchemaProperty] {
+        &self.properties
+    }
+}
+
+/// Global registry of custom schemas, keyed by namespace URI
+static GLOBAL_SCHEMAS: OnceLock<RwLock<HashMap<String, XmpSchema>>> = OnceLock::new();
+
+fn get_global_schemas() -> &'static RwLock<HashMap<String, XmpSchema>> {
+    GLOBAL_SCHEMAS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Register a custom schema
+///
+/// The namespace is registered with the preferred prefix of the schema (see
+/// [`register_namespace`](crate::core::namespace::register_namespace)), so
+/// the serializer uses it, and its aliases are registered. Registering a
+/// schema again replaces the previous definition, but aliases can't be
+/// redefined. The built-in schemas can't be redefined.
+pub fn register_schema(schema: XmpSchema) -> XmpResult<()> {
+    if SCHEMAS.iter().any(|(uri, _)| *uri == schema.uri) {
+        return Err(XmpError::BadParam(format!(
+            "'{}' is a built-in schema",
+            schema.uri
+        )));
+    }
+    namespace::register_namespace(&schema.uri, &schema.prefix)?;
+    for (name, actual) in &schema.aliases {
+        alias::register_alias(
+            &schema.uri,
+            name,
+            &actual.namespace,
+            &actual.property,
+            actual.form,
+        )?;
+    }
+
+    // RwLock::write() only fails if the lock is poisoned
+    let mut guard = get_global_schemas()
+        .write()
+        .expect("Schema registry lock poisoned");
+    guard.insert(schema.uri.clone(), schema);
+    Ok(())
+}
+
+/// Get a registered custom schema
+pub fn get_registered_schema(uri: &str) -> Option<XmpSchema> {
+    let guard = get_global_schemas()
+        .read()
+        .expect("Schema registry lock poisoned");
+    guard.get(uri).cloned()
+}
+
+/// Get the form of a property of a built-in or registered schema
+#[cfg(feature = "serde")]
+pub(crate) fn known_form(ns_uri: &str, name: &str) -> Option<ValueForm> {
+    if let Some((_, properties)) = SCHEMAS.iter().find(|(uri, _)| *uri == ns_uri) {
+        return properties
+            .iter()
+            .find(|def| def.name == name)
+            .map(|def| def.form);
+    }
+    let guard = get_global_schemas()
+        .read()
+        .expect("Schema registry lock poisoned");
+    Some(guard.get(ns_uri)?.get_property(name)?.form)
+}
+
+/// Get the array form of a property of a custom schema
+pub(crate) fn custom_array_type(ns_uri: &str, name: &str) -> Option<ArrayType> {
+    let guard = get_global_schemas()
+        .read()
+        .expect("Schema registry lock poisoned");
+    match guard.get(ns_uri)?.get_property(name)?.form {
+        Bag => Some(ArrayType::Unordered),
+        Seq => Some(ArrayType::Ordered),
+        Alt | LangAlt => Some(ArrayType::Alternative),
+        Simple | ValueForm::Struct => None,
+    }
+}
+
+/// Check a value being set against the custom schema of its namespace
+///
+/// `name` is the top-level property, `form` the form it is written with
+/// (`None` to skip the check) and `value` the simple value or array item
+/// (`None` if there is none). Paths below the top level are not checked.
+pub(crate) fn check_custom_property(
+    ns_uri: &str,
+    name: &str,
+    form: Option<ValueForm>,
+    value: Option<&str>,
+) -> XmpResult<()> {
+    if name.contains(['/', '[']) {
+        return Ok(());
+    }
+    let guard = get_global_schemas()
+        .read()
+        .expect("Schema registry lock poisoned");
+    let Some(def) = guard
+        .get(ns_uri)
+        .and_then(|schema| schema.get_property(name))
+    else {
+        return Ok(());
+    };
+    let prefix = &guard[ns_uri].prefix;
+
+    if let Some(found) = form {
+        let compatible = found == def.form
+            || (matches!(def.form, Alt | LangAlt) && matches!(found, Alt | LangAlt));
+        if !compatible {
+            return Err(XmpError::BadValue(format!(
+                "{}:{}: expected {}, found {}",
+                prefix, name, def.form, found
+            )));
+        }
+    }
+    if let Some(value) = value {
+        if def.value_type == ValueType::Struct {
+            return Err(XmpError::BadValue(format!(
+                "{}:{}: expected structure, found simple value",
+                prefix, name
+            )));
+        }
+        if let Some(kind) = check_value(def, value) {
+            let violation = Violation {
+                schema_ns: ns_uri.to_string(),
+                path: format!("{}:{}", prefix, name),
+                kind,
+            };
+            return Err(XmpError::BadValue(violation.to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// What is wrong with a property
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ViolationKind {
+    /// The property doesn't have the form required by its schema
+    WrongForm {
+        /// Form defined by the schema
+        expected: ValueForm,
+        /// Form of the property
+        found: ValueForm,
+    },
+    /// A value doesn't have the type defined by the schema
+    InvalidValue {
+        /// Type defined by the schema
+        expected: ValueType,
+        /// The invalid value
+        value: String,
+    },
+    /// A value is not one of the allowed values of a closed choice
+    NotInChoice {
+        /// The invalid value
+        value: String,
+        /// The allowed values
+        allowed: Vec<String>,
+    },
+    /// The property is not defined by its schema
+    UnknownProperty,
+}
+
+/// A property that doesn't conform to its schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Violation {
+    /// Namespace URI of the property
+    pub schema_ns: String,
+    /// XMP path of the invalid node (e.g. `dc:date[2]`)
+    pub path: String,
+    /// What is wrong
+    pub kind: ViolationKind,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ViolationKind::WrongForm { expected, found } => {
+                write!(f, "{}: expected {}, found {}", self.path, expected, found)
+            }
+            ViolationKind::InvalidValue { expected, value } => {
+                write!(f, "{}: invalid {} {:?}", self.path, expected, value)
+            }
+            ViolationKind::NotInChoice { value, allowed } => write!(
+                f,
+                "{}: {:?} is not one of {}",
+                self.path,
+                value,
+                allowed.join(", ")
+            ),
+            ViolationKind::UnknownProperty => write!(f, "{}: unknown property", self.path),
+        }
+    }
+}
+
+/// Validator of XMP metadata against the built-in and registered schema
+/// definitions
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::{ns, XmpMeta, XmpValue};
+/// use xmpkit::core::validate::{ViolationKind, XmpValidator};
+///
+/// let mut meta = XmpMeta::new();
+/// meta.set_property(ns::XMP, "CreateDate", XmpValue::String("yesterday".into()))
+///     .unwrap();
+///
+/// let violations = XmpValidator::new().validate(&meta);
+/// assert_eq!(violations.len(), 1);
+/// assert_eq!(violations[0].path, "xmp:CreateDate");
+/// assert!(matches!(violations[0].kind, ViolationKind::InvalidValue { .. }));
+/// ```
+#[derive(Default, Clone, Copy, Debug)]
+pub struct XmpValidator {
+    /// Report properties of the known schemas that they don't define
+    pub(crate) reject_unknown_properties: bool,
+}
+
+impl XmpValidator {
+    /// Create a validator for the built-in and registered schemas
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report the properties of the known schemas that they don't
+    /// define (such as misspelled names).
+    pub fn reject_unknown_properties(mut self) -> Self {
+        self.reject_unknown_properties = true;
+        self
+    }
+
+    /// Check if the metadata conforms to the schemas
+    pub fn is_valid(&self, meta: &XmpMeta) -> bool {
+        self.validate(meta).is_empty()
+    }
+
+    /// Check the metadata against the schemas
+    ///
+    /// Returns the violations in document order (see [`XmpMeta::iter`]);
+    /// an empty list if the metadata is valid.
+    pub fn validate(&self, meta: &XmpMeta) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        // Definition of the top-level property being visited
+        let mut current: Option<(String, Option<SchemaProperty>)> = None;
+        let custom = get_global_schemas()
+            .read()
+            .expect("Schema registry lock poisoned");
+
+        for item in meta.iter_with(IterOptions::default().omit_qualifiers()) {
+            let builtin = SCHEMAS.iter().find(|(uri, _)| *uri == item.schema_ns);
+            let schema = custom.get(&item.schema_ns);
+            if builtin.is_none() && schema.is_none() {
+                continue;
+            }
+            let violation = |kind| Violation {
+                schema_ns: item.schema_ns.clone(),
+                path: item.path.clone(),
+                kind,
+            };
+
+            // Top-level property
+            if !item.path.contains(['/', '[']) {
+                let name = item.path.rsplit(':').next().unwrap_or_default();
+                let def = match (builtin, schema) {
+                    (Some((_, properties)), _) => properties
+                        .iter()
+                        .find(|def| def.name == name)
+                        .map(SchemaProperty::from),
+                    (None, Some(schema)) => schema.get_property(name).cloned(),
+                    (None, None) => None,
+                };
+                current = Some((item.path.clone(), def.clone()));
+                let Some(def) = def else {
+                    if self.reject_unknown_properties {
+                        violations.push(violation(ViolationKind::UnknownProperty));
+                    }
+                    continue;
+                };
+
+                let found = form_of(meta, &item.path, &item.options);
+                let compatible = found == def.form || (def.form == Alt && found == LangAlt);
+                if !compatible {
+                    violations.push(violation(ViolationKind::WrongForm {
+                        expected: def.form,
+                        found,
+                    }));
+                } else if let (Simple, Some(value)) = (found, &item.value) {
+                    violations.extend(check_value(&def, value).map(violation));
+                }
+                continue;
+            }
+
+            // Direct items of arrays
+            let Some((top, Some(def))) = &current else {
+                continue;
+            };
+            let is_item = item
+                .path
+                .strip_prefix(top.as_str())
+                .and_then(|rest| rest.strip_prefix('['))
+                .is_some_and(|index| index.ends_with(']') && !index.contains(['/', '[']));
+            if !is_item || !matches!(def.form, Bag | Seq | Alt) {
+                continue;
+            }
+            if def.value_type == ValueType::Struct {
+                if item.options.is_simple() {
+                    violations.push(violation(ViolationKind::WrongForm {
+                        expected: ValueForm::Struct,
+                        found: Simple,
+                    }));
+                }
+            } else if let Some(value) = &item.value {
+                violations.extend(check_value(def, value).map(violation));
+            }
+        }
+        violations
+    }
+}
+
+/// Get the form of a top-level property
+fn form_of(
+    meta: &XmpMeta,
+    path: &str,
+    options: &crate::core::metadata::PropertyOptions,
+) -> ValueForm {
+    match options.array_type {
+        Some(ArrayType::Unordered) => Bag,
+        Some(ArrayType::Ordered) => Seq,
+        Some(ArrayType::Alternative) => {
+            // Language alternatives have `xml:lang` on every item
+            let items: Vec<_> = meta
+                .iter_with(IterOptions::default())
+                .filter(|item| {
+                    item.path
+                        .strip_prefix(path)
+                        .and_then(|rest| rest.strip_prefix('['))
+                        .is_some_and(|index| index.ends_with(']') && !index.contains(['/', '[']))
+                })
+                .collect();
+            if !items.is_empty() && items.iter().all(|item| item.options.has_lang) {
+                LangAlt
+            } else {
+                Alt
+            }
+        }
+        None if options.is_struct => ValueForm::Struct,
+        None => Simple,
+    }
+}
+
+/// Check a simple value against its definition
+fn check_value(def: &SchemaProperty, value: &str) -> Option<ViolationKind> {
+    if !def.choices.is_empty() {
+        if def.choices.iter().any(|c| c == value.trim()) {
+            return None;
+        }
+        return Some(ViolationKind::NotInChoice {
+            value: value.to_string(),
+            allowed: def.choices.clone(),
+        });
+    }
+
+    let trimmed = value.trim();
+    let valid = match def.value_type {
+        Text | ValueType::Struct => true,
+        Integer => trimmed
+            .strip_prefix('+')
+            .unwrap_or(trimmed)
+            .parse::<i64>()
+            .is_ok(),
+        Real => trimmed.parse::<f64>().is_ok_and(f64::is_finite),
+        Boolean => matches!(trimmed, "True" | "False"),
+        Rational => trimmed
+            .split_once('/')
+            .is_some_and(|(num, den)| num.parse::<i64>().is_ok() && den.parse::<u64>().is_ok()),
+        Uri => !value.is_empty() && !value.contains(char::is_whitespace),
+        Date => XmpDateTime::parse(trimmed).is_ok_and(|dt| dt.validate().is_ok()),
+    };
+    (!valid).then(|| ViolationKind::InvalidValue {
+        expected: def.value_type,
+        value: value.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::value::XmpValue;
+
+    fn violation_paths(violations: &[Violation]) -> Vec<&str> {
+        violations.iter().map(|v| v.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_valid_metadata() {
+        let mut meta = XmpMeta::new();
+        meta.set_localized_text(ns::DC, "title", "", "x-default", "Title")
+            .unwrap();
+        meta.append_array_item(ns::DC, "creator", "Author".into())
+            .unwrap();
+        meta.append_array_item(ns::DC, "date", "2024-05-06".into())
+            .unwrap();
+        meta.set_property(ns::XMP, "CreateDate", "2024-05-06T07:08:09Z".into())
+            .unwrap();
+        meta.set_property(ns::XMP, "Rating", "4".into()).unwrap();
+        meta.set_property(ns::EXIF, "FNumber", "28/10".into())
+            .unwrap();
+        meta.set_property(ns::EXIF, "ColorSpace", XmpValue::Integer(1))
+            .unwrap();
+        meta.set_property(ns::TIFF, "Anything", "goes".into())
+            .unwrap();
+        assert!(XmpValidator::new().is_valid(&meta));
+    }
+
+    #[test]
+    fn test_violations() {
+        let mut meta = XmpMeta::new();
+        // Simple value instead of a language alternative
+        meta.set_property(ns::DC, "title", "Title".into()).unwrap();
+        // Seq instead of Bag
+        meta.append_array_item(ns::DC, "subject", "beach".into())
+            .unwrap();
+        meta.append_array_item(ns::DC, "date", "2024-05-06".into())
+            .unwrap();
+        meta.append_array_item(ns::DC, "date", "May 2024".into())
+            .unwrap();
+        meta.set_property(ns::XMP_MM, "InstanceID", "xmp.iid: 1".into())
+            .unwrap();
+        meta.set_property(ns::EXIF, "FNumber", "2.8".into())
+            .unwrap();
+        meta.set_property(ns::PHOTOSHOP, "Urgency", "9".into())
+            .unwrap();
+        meta.set_property(ns::XMP, "Ratng", "5".into()).unwrap();
+
+        let violations = XmpValidator::new().validate(&meta);
+        assert_eq!(
+            violation_paths(&violations),
+            vec![
+                "exif:FNumber",
+                "photoshop:Urgency",
+                "xmpMM:InstanceID",
+                "dc:date[2]",
+                "dc:subject",
+                "dc:title",
+            ]
+        );
+        assert_eq!(
+            violations[4].kind,
+            ViolationKind::WrongForm {
+                expected: Bag,
+                found: Seq
+            }
+        );
+        assert_eq!(
+            violations[3].to_string(),
+            "dc:date[2]: invalid date \"May 2024\""
+        );
+        assert!(matches!(
+            violations[1].kind,
+            ViolationKind::NotInChoice { .. }
+        ));
+
+        let violations = XmpValidator::new()
+            .reject_unknown_properties()
+            .validate(&meta);
+        assert!(violations
+            .iter()
+            .any(|v| v.path == "xmp:Ratng" && v.kind == ViolationKind::UnknownProperty));
+    }
+
+    #[test]
+    fn test_custom_schema() {
+        const NS: &str = "http://example.com/ns/validate-test/1.0/";
+        register_schema(
+            XmpSchema::new(NS, "vtest")
+                .property("Count", Simple, Integer)
+                .property("Reviewed", Simple, Boolean)
+                .property("Keywords", Bag, Text)
+                .property("Caption", LangAlt, Text)
+                .property("Owner", ValueForm::Struct, ValueType::Struct)
+                .choice("Status", Simple, &["draft", "final"]),
+        )
+        .unwrap();
+        assert_eq!(get_registered_schema(NS).unwrap().prefix(), "vtest");
+        assert!(matches!(
+            register_schema(XmpSchema::new(ns::DC, "dc")),
+            Err(XmpError::BadParam(_))
+        ));
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(NS, "Count", XmpValue::Integer(3))
+            .unwrap();
+        meta.set_property(NS, "Reviewed", XmpValue::Boolean(true))
+            .unwrap();
+        meta.set_property(NS, "Status", "draft".into()).unwrap();
+        meta.set_localized_text(NS, "Caption", "", "x-default", "Hello")
+            .unwrap();
+        meta.set_struct_field(NS, "Owner", NS, "Name", "Jane".into())
+            .unwrap();
+        // Arrays get the form of the schema
+        meta.append_array_item(NS, "Keywords", "a".into()).unwrap();
+        assert!(meta.serialize().unwrap().contains("rdf:Bag"));
+        assert!(meta.insert_array_item(NS, "Keywords", 0, "".into()).is_ok());
+        meta.set_property(NS, "Undefined", "anything".into())
+            .unwrap();
+        assert!(XmpValidator::new().is_valid(&meta));
+
+        // Type errors are reported at set time
+        let err = meta.set_property(NS, "Count", "many".into()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Bad value: vtest:Count: invalid integer \"many\""
+        );
+        assert!(meta.set_property(NS, "Status", "lost".into()).is_err());
+        assert!(meta.set_property(NS, "Keywords", "a".into()).is_err());
+        assert!(meta.set_property(NS, "Owner", "Jane".into()).is_err());
+        assert!(meta
+            .append_array_item_with(NS, "Keywords", ArrayType::Ordered, "b".into())
+            .is_err());
+        assert!(meta
+            .set_localized_text(NS, "Count", "", "x-default", "3")
+            .is_err());
+        assert_eq!(meta.get_property(NS, "Count"), Some("3".into()));
+
+        // Values from parsed packets are caught by the validator
+        let parsed = XmpMeta::parse(&format!(
+            r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+<rdf:Description rdf:about="" xmlns:vtest="{}" vtest:Count="many"/>
+</rdf:RDF>
+</x:xmpmeta>"#,
+            NS
+        ))
+        .unwrap();
+        let violations = XmpValidator::new().validate(&parsed);
+        assert_eq!(violation_paths(&violations), vec!["vtest:Count"]);
+    }
+}
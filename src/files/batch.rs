@@ -0,0 +1,473 @@
+//! Batch processing of the files of a directory
+//!
+//! [`process_dir`] walks a directory, opens the files whose names match the
+//! filters of [`BatchOptions`] on a pool of threads, applies a closure to
+//! the metadata of each file, and saves the files the closure changed. The
+//! outcome of each file is collected in a [`BatchReport`], so that one
+//! broken file does not stop the batch.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use xmpkit::files::batch::{process_dir, BatchOptions};
+//! use xmpkit::{ns, XmpValue};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let options = BatchOptions::default()
+//!     .recursive()
+//!     .include("*.jpg")
+//!     .include("*.tif");
+//! let report = process_dir("photos", &options, |meta| {
+//!     meta.set_property(ns::XMP, "Label", XmpValue::String("Reviewed".into()))?;
+//!     Ok(true)
+//! })?;
+//! for (path, error) in report.failures() {
+//!     eprintln!("{}: {}", path.display(), error);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::files::file::{ReadOptions, SaveOptions, SaveOutcome, XmpFile};
+use crate::files::progress::{ProgressCallback, ProgressTracker};
+
+/// Options for [`process_dir`]
+///
+/// By default, all the files directly in the directory are processed, with
+/// one thread per available CPU.
+#[derive(Default, Clone, Debug)]
+pub struct BatchOptions {
+    /// Process the files of subdirectories too
+    pub(crate) recursive: bool,
+    /// Patterns of the file names to process (all files if empty)
+    pub(crate) include: Vec<String>,
+    /// Patterns of the file names to skip
+    pub(crate) exclude: Vec<String>,
+    /// Number of threads (0 means one per available CPU)
+    pub(crate) threads: usize,
+    /// Options for opening the files
+    pub(crate) read_options: ReadOptions,
+    /// Options for saving the changed files
+    pub(crate) save_options: SaveOptions,
+    /// Callback reporting the number of files processed
+    pub(crate) progress: Option<ProgressCallback>,
+}
+
+impl BatchOptions {
+    /// Process the files of subdirectories too.
+    pub fn recursive(mut self) -> Self {
+        self.recursive = true;
+        self
+    }
+
+    /// Only process the files whose names match `pattern`.
+    ///
+    /// Patterns match the file name, ignoring ASCII case: `*` matches any
+    /// characters and `?` one character, e.g. `*.jpg`. Files matching any
+    /// of the included patterns are processed.
+    pub fn include(mut self, pattern: impl Into<String>) -> Self {
+        self.include.push(pattern.into());
+        self
+    }
+
+    /// Skip the files whose names match `pattern`.
+    ///
+    /// See [`include`](Self::include) for the patterns. Exclusion wins over
+    /// inclusion.
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.exclude.push(pattern.into());
+        self
+    }
+
+    /// Set the number of threads (default: one per available CPU).
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Set the options for opening the files.
+    pub fn read_options(mut self, options: ReadOptions) -> Self {
+        self.read_options = options;
+        self
+    }
+
+    /// Set the options for saving the changed files.
+    pub fn save_options(mut self, options: SaveOptions) -> Self {
+        self.save_options = options;
+        self
+    }
+
+    /// Report the number of files processed to `callback`.
+    ///
+    /// When the callback cancels the batch, the files that were not
+    /// started yet are recorded as `XmpError::Cancelled` failures.
+    pub fn progress(mut self, callback: ProgressCallback) -> Self {
+        self.progress = Some(callback);
+        self
+    }
+
+    /// Check if a file name passes the filters
+    fn matches(&self, file_name: &str) -> bool {
+        let matching = |patterns: &[String]| patterns.iter().any(|p| glob_match(p, file_name));
+        (self.include.is_empty() || matching(&self.include)) && !matching(&self.exclude)
+    }
+}
+
+/// What happened to a file processed by [`process_dir`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchStatus {
+    /// The closure did not change the metadata, so the file was not saved
+    Unchanged,
+    /// The file was saved
+    Saved(SaveOutcome),
+}
+
+/// The outcome of each file processed by [`process_dir`]
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    /// The files and their outcomes, sorted by path
+    pub entries: Vec<(PathBuf, XmpResult<BatchStatus>)>,
+}
+
+impl BatchReport {
+    /// Get the files that were processed successfully
+    pub fn successes(&self) -> impl Iterator<Item = (&Path, BatchStatus)> {
+        self.entries
+            .iter()
+            .filter_map(|(path, result)| Some((path.as_path(), *result.as_ref().ok()?)))
+    }
+
+    /// Get the files (or directories) that failed, and their errors
+    pub fn failures(&self) -> impl Iterator<Item = (&Path, &XmpError)> {
+        self.entries
+            .iter()
+            .filter_map(|(path, result)| Some((path.as_path(), result.as_ref().err()?)))
+    }
+
+    /// Check if all the files were processed successfully
+    pub fn is_success(&self) -> bool {
+        self.entries.iter().all(|(_, result)| result.is_ok())
+    }
+}
+
+/// Process the XMP metadata of the files of a directory in parallel
+///
+/// Each file is opened with the read options of `options`, and `process`
+/// is called with its metadata (empty if the file has none). When
+/// `process` returns `Ok(true)`, the metadata is saved back to the file;
+/// `Ok(false)` leaves the file untouched. Errors of `process`, and of
+/// opening or saving a file, are recorded in the report. Subdirectories
+/// that can't be read are recorded as failures too.
+///
+/// With [`BatchOptions::progress`], the callback is called as the files
+/// are done, and can cancel the rest of the batch.
+///
+/// Each thread opens its own files, so the metadata never moves between
+/// threads; `process` is shared by the threads.
+///
+/// Returns an error if `dir` can't be read.
+pub fn process_dir<P, F>(dir: P, options: &BatchOptions, process: F) -> XmpResult<BatchReport>
+where
+    P: AsRef<Path>,
+    F: Fn(&mut XmpMeta) -> XmpResult<bool> + Sync,
+{
+    let mut files = Vec::new();
+    let mut entries = Vec::new();
+    collect_files(dir.as_ref(), options, &mut files, &mut entries)?;
+
+    let threads = match options.threads {
+        0 => std::thread::available_parallelism().map_or(1, usize::from),
+        threads => threads,
+    }
+    .min(files.len())
+    .max(1);
+    let next = AtomicUsize::new(0);
+    let tracker = options
+        .progress
+        .as_ref()
+        .map(|callback| ProgressTracker::start(callback, files.len() as u64));
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    while let Some(path) = files.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        if tracker.as_ref().is_some_and(ProgressTracker::is_cancelled) {
+                            results.push((path.clone(), Err(XmpError::Cancelled)));
+                            continue;
+                        }
+                        results.push((path.clone(), process_file(path, options, &process)));
+                        if let Some(tracker) = &tracker {
+                            tracker.add(1);
+                        }
+                    }
+                    results
+                })
+            })
+            .collect();
+        for worker in workers {
+            match worker.join() {
+                Ok(results) => entries.extend(results),
+                Err(panic) => std::panic::resume_unwind(panic),
+            }
+        }
+    });
+
+    // A cancellation is recorded in the report instead
+    if let Some(tracker) = tracker {
+        let _ = tracker.finish(Ok(()));
+    }
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(BatchReport { entries })
+}
+
+/// List the files of a directory that [`process_dir`] would process
+///
+/// The files are sorted by path. Subdirectories that can't be read are
+/// skipped; returns an error if `dir` can't be read.
+pub fn list_files<P: AsRef<Path>>(dir: P, options: &BatchOptions) -> XmpResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect_files(dir.as_ref(), options, &mut files, &mut Vec::new())?;
+    files.sort();
+    Ok(files)
+}
+
+/// Open, process and save a single file
+fn process_file<F>(path: &Path, options: &BatchOptions, process: &F) -> XmpResult<BatchStatus>
+where
+    F: Fn(&mut XmpMeta) -> XmpResult<bool>,
+{
+    let mut file = XmpFile::new();
+    file.open_with(path, options.read_options)?;
+    let mut meta = file.get_xmp().cloned().unwrap_or_default();
+    if !process(&mut meta)? {
+        return Ok(BatchStatus::Unchanged);
+    }
+    file.put_xmp(meta);
+    let outcome = file.save_with(path, options.save_options)?;
+    Ok(BatchStatus::Saved(outcome))
+}
+
+/// Collect the files of `dir` that pass the filters
+///
+/// Errors reading subdirectories are added to `failures`; errors reading
+/// the top directory are returned. A file reached through several paths,
+/// such as symbolic links, is collected once, so that no two workers write
+/// it at the same time.
+fn collect_files(
+    dir: &Path,
+    options: &BatchOptions,
+    files: &mut Vec<PathBuf>,
+    failures: &mut Vec<(PathBuf, XmpResult<BatchStatus>)>,
+) -> XmpResult<()> {
+    walk_dir(dir, options, files, failures, &mut HashSet::new(), true)?;
+    let mut seen = HashSet::new();
+    files.retain(|path| seen.insert(std::fs::canonicalize(path).unwrap_or_else(|_| path.clone())));
+    Ok(())
+}
+
+/// Add the files of `dir` that pass the filters to `files`
+///
+/// `visited` holds the canonical paths of the directories already walked,
+/// so that symbolic links looping back to a parent are not followed again.
+fn walk_dir(
+    dir: &Path,
+    options: &BatchOptions,
+    files: &mut Vec<PathBuf>,
+    failures: &mut Vec<(PathBuf, XmpResult<BatchStatus>)>,
+    visited: &mut HashSet<PathBuf>,
+    top: bool,
+) -> XmpResult<()> {
+    if let Ok(canonical) = std::fs::canonicalize(dir) {
+        if !visited.insert(canonical) {
+            return Ok(());
+        }
+    }
+    let read_dir = match std::fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) if top => return Err(e.into()),
+        Err(e) => {
+            failures.push((dir.to_path_buf(), Err(e.into())));
+            return Ok(());
+        }
+    };
+    for entry in read_dir {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                failures.push((dir.to_path_buf(), Err(e.into())));
+                continue;
+            }
+        };
+        let path = entry.path();
+        // Follow symbolic links, like opening the file does
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            if options.recursive {
+                walk_dir(&path, options, files, failures, visited, false)?;
+            }
+        } else if options.matches(&entry.file_name().to_string_lossy()) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Match a file name against a pattern with `*` and `?` wildcards,
+/// ignoring ASCII case
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // Position after the last `*`, and the name position it matched up to
+    let mut star = None;
+    let (mut p, mut n) = (0, 0);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c.eq_ignore_ascii_case(&name[n]) => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.jpg", "Photo.JPG"));
+        assert!(glob_match("img_????.*", "img_0001.tif"));
+        assert!(glob_match("*a*b", "xaybzb"));
+        assert!(!glob_match("*.jpg", "photo.jpeg"));
+        assert!(!glob_match("img_?", "img_"));
+        assert!(glob_match("*", ""));
+    }
+
+    #[test]
+    fn test_process_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let packet = |label: &str| {
+            let mut meta = XmpMeta::new();
+            meta.set_property(ns::XMP, "Label", XmpValue::String(label.into()))
+                .unwrap();
+            meta.serialize_packet().unwrap()
+        };
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("a.xmp"), packet("a")).unwrap();
+        std::fs::write(dir.path().join("sub/b.xmp"), packet("b")).unwrap();
+        std::fs::write(dir.path().join("sub/skip.xmp"), packet("skip")).unwrap();
+        std::fs::write(dir.path().join("c.txt"), "not metadata").unwrap();
+
+        let options = BatchOptions::default()
+            .recursive()
+            .include("*.XMP")
+            .exclude("skip*")
+            .threads(2);
+        let report = process_dir(dir.path(), &options, |meta| {
+            let label = meta.get_property(ns::XMP, "Label");
+            if label == Some(XmpValue::String("b".into())) {
+                return Err(XmpError::BadValue("b is not allowed".into()));
+            }
+            meta.set_property(ns::XMP, "Label", XmpValue::String("done".into()))?;
+            Ok(true)
+        })
+        .unwrap();
+
+        assert_eq!(report.entries.len(), 2);
+        assert!(!report.is_success());
+        let successes: Vec<_> = report.successes().collect();
+        assert_eq!(successes.len(), 1);
+        assert!(successes[0].0.ends_with("a.xmp"));
+        let failures: Vec<_> = report.failures().collect();
+        assert!(failures[0].0.ends_with("sub/b.xmp"));
+
+        let mut file = XmpFile::new();
+        file.open(dir.path().join("a.xmp")).unwrap();
+        assert_eq!(
+            file.get_xmp().unwrap().get_property(ns::XMP, "Label"),
+            Some(XmpValue::String("done".into()))
+        );
+        assert!(process_dir(dir.path().join("missing"), &options, |_| Ok(false)).is_err());
+        let files = list_files(dir.path(), &options).unwrap();
+        assert_eq!(
+            files,
+            [dir.path().join("a.xmp"), dir.path().join("sub/b.xmp")]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_loops() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        for name in ["a.xmp", "sub/b.xmp"] {
+            std::fs::write(
+                dir.path().join(name),
+                XmpMeta::new().serialize_packet().unwrap(),
+            )
+            .unwrap();
+        }
+        // A loop back to the top directory, and a second path to a file
+        std::os::unix::fs::symlink("..", dir.path().join("sub/up")).unwrap();
+        std::os::unix::fs::symlink("a.xmp", dir.path().join("link.xmp")).unwrap();
+
+        let calls = AtomicUsize::new(0);
+        let options = BatchOptions::default().recursive().threads(4);
+        let report = process_dir(dir.path(), &options, |meta| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            meta.set_property(ns::XMP, "Label", XmpValue::String("done".into()))?;
+            Ok(true)
+        })
+        .unwrap();
+
+        assert_eq!(calls.into_inner(), 2);
+        assert_eq!(report.entries.len(), 2);
+        assert!(report.is_success());
+    }
+
+    #[test]
+    fn test_cancel_batch() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a.xmp", "b.xmp", "c.xmp"] {
+            std::fs::write(
+                dir.path().join(name),
+                XmpMeta::new().serialize_packet().unwrap(),
+            )
+            .unwrap();
+        }
+        let callback = ProgressCallback::new(|progress| progress.processed < 1)
+            .interval(std::time::Duration::ZERO);
+        let options = BatchOptions::default().threads(1).progress(callback);
+        let report = process_dir(dir.path(), &options, |_| Ok(false)).unwrap();
+
+        assert_eq!(report.successes().count(), 1);
+        let failures: Vec<_> = report.failures().collect();
+        assert_eq!(failures.len(), 2);
+        assert!(matches!(failures[0].1, XmpError::Cancelled));
+    }
+}
@@ -5,14 +5,28 @@
 
 use crate::core::error::{XmpError, XmpResult};
 use crate::core::metadata::XmpMeta;
-use crate::files::handler::FileHandler;
-use crate::files::registry::default_registry;
+use crate::core::namespace::ns;
+use crate::core::serializer::SerializeOptions;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::files::format::{FileFormat, FormatCapabilities};
+use crate::files::formats::sidecar::SidecarHandler;
+use crate::files::handler::{FileHandler, ReadSeek};
+use crate::files::io::{self, XmpIo};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::files::progress::{ProgressCallback, ProgressTracker};
+use crate::files::registry::{default_registry, Handler, HandlerRegistry};
+use crate::types::value::XmpValue;
+use crate::utils::datetime::current_datetime;
+use crate::utils::encoding::PacketEncoding;
 use std::io::{Cursor, Read, Seek, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
 
 /// Options for reading XMP metadata from files or memory.
 ///
 /// Use the builder pattern to configure options. These options apply to both
 /// file operations (`open_with`) and memory operations (`from_bytes_with`, `from_reader_with`).
+/// They mirror the `kXMPFiles_Open*` flags of the Adobe XMP Toolkit.
 ///
 /// # Example
 ///
@@ -31,7 +45,7 @@ use std::io::{Cursor, Read, Seek, Write};
 pub struct ReadOptions {
     /// Open for reading and writing (default: read-only)
     pub(crate) for_update: bool,
-    /// Only the XMP is wanted (allows optimizations)
+    /// Only the XMP is wanted (do not import legacy metadata)
     pub(crate) only_xmp: bool,
     /// Force use of the given handler (format)
     pub(crate) force_given_handler: bool,
@@ -43,10 +57,22 @@ pub struct ReadOptions {
     pub(crate) use_packet_scanning: bool,
     /// Only packet scan files "known" to need scanning
     pub(crate) limited_scanning: bool,
+    /// Read the file in chunks instead of loading it into memory
+    pub(crate) streaming: bool,
+    /// Chunk size for streaming mode (0 means the default)
+    pub(crate) chunk_size: usize,
+    /// Import the thumbnail of the native metadata into `xmp:Thumbnails`
+    pub(crate) import_thumbnail: bool,
+    /// Format to use instead of the detected one
+    pub(crate) format: Option<FileFormat>,
+    /// Recover the XMP of damaged files
+    pub(crate) repair_file: bool,
 }
 
 impl ReadOptions {
     /// Open for read-only access (default).
+    ///
+    /// Closing the file never writes to it, nor to any temporary file.
     pub fn for_read(mut self) -> Self {
         self.for_update = false;
         self
@@ -62,15 +88,28 @@ impl ReadOptions {
 
     /// Only the XMP is wanted.
     ///
-    /// This allows space/time optimizations by skipping other metadata.
+    /// By default, legacy metadata (such as EXIF in JPEG and TIFF files,
+    /// IPTC in JPEG files, ID3 in MP3 files, iTunes items in MP4 files and
+    /// text chunks in PNG files) is imported into the XMP properties that
+    /// don't exist yet. This skips reading it.
     pub fn only_xmp(mut self) -> Self {
         self.only_xmp = true;
         self
     }
 
+    /// Import the thumbnail of the native metadata into `xmp:Thumbnails`.
+    ///
+    /// The EXIF thumbnail of JPEG files is added when the XMP has no
+    /// thumbnails yet. Nothing is imported with [`only_xmp`](Self::only_xmp).
+    pub fn import_thumbnail(mut self) -> Self {
+        self.import_thumbnail = true;
+        self
+    }
+
     /// Force use of the given handler (format).
     ///
-    /// Do not even verify the format.
+    /// The handler of [`format`](Self::format) is used without even
+    /// verifying the format.
     pub fn force_given_handler(mut self) -> Self {
         self.force_given_handler = true;
         self
@@ -78,7 +117,8 @@ impl ReadOptions {
 
     /// Be strict about only attempting to use the designated file handler.
     ///
-    /// Do not fall back to other handlers.
+    /// Do not fall back to other handlers: fail if the file is not in the
+    /// given [`format`](Self::format), or if no handler supports it.
     pub fn strict(mut self) -> Self {
         self.strict = true;
         self
@@ -105,6 +145,454 @@ impl ReadOptions {
         self.limited_scanning = true;
         self
     }
+
+    /// Read the file in chunks instead of loading it into memory.
+    ///
+    /// Format handlers and packet scanning work directly on the file, so
+    /// only the segments holding XMP are buffered. When writing, the
+    /// original file is read again from its path; files opened from a
+    /// reader in streaming mode can only be read.
+    pub fn streaming(mut self) -> Self {
+        self.streaming = true;
+        self
+    }
+
+    /// Set the chunk size used in streaming mode (default: 64 KiB).
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Recover the XMP of damaged files.
+    ///
+    /// When the format handler fails to read a file, such as a truncated
+    /// file or one with invalid segment lengths, the whole file is scanned
+    /// for an XMP packet instead, and malformed XML in the packet is
+    /// recovered from (see [`XmpMeta::parse_lenient`]). The native metadata
+    /// of such files is not imported. Saving rewrites the file with its
+    /// format handler, which may still fail if the damage is not around the
+    /// XMP.
+    ///
+    /// [`XmpMeta::parse_lenient`]: crate::core::metadata::XmpMeta::parse_lenient
+    pub fn repair_file(mut self) -> Self {
+        self.repair_file = true;
+        self
+    }
+
+    /// Use the handler of a format instead of detecting the format.
+    ///
+    /// The format is still verified, and detected when the file is not in
+    /// that format, unless [`strict`](Self::strict) or
+    /// [`force_given_handler`](Self::force_given_handler) is set.
+    pub fn format(mut self, format: FileFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Find the handler of a file, honoring the given format
+    pub(crate) fn find_handler<'r, R: Read + Seek>(
+        &self,
+        registry: &'r HandlerRegistry,
+        reader: &mut R,
+    ) -> XmpResult<Option<&'r Handler>> {
+        let Some(format) = self.format else {
+            return registry.find_by_detection(reader);
+        };
+        match registry.find_by_format(format) {
+            Some(handler) if self.force_given_handler => return Ok(Some(handler)),
+            Some(handler) => {
+                let pos = reader.stream_position()?;
+                let handled = handler.can_handle(reader)?;
+                reader.seek(std::io::SeekFrom::Start(pos))?;
+                if handled {
+                    return Ok(Some(handler));
+                }
+                if self.strict {
+                    return Err(XmpError::BadValue(format!("Not a {} file", format.name())));
+                }
+            }
+            None if self.strict || self.force_given_handler => {
+                return Err(XmpError::NotSupported(format!(
+                    "No handler available for {} files",
+                    format.name()
+                )));
+            }
+            None => {}
+        }
+        registry.find_by_detection(reader)
+    }
+
+    /// Get the chunk size used in streaming mode
+    pub(crate) fn effective_chunk_size(&self) -> usize {
+        if self.chunk_size == 0 {
+            DEFAULT_CHUNK_SIZE
+        } else {
+            self.chunk_size
+        }
+    }
+}
+
+/// Default chunk size for streaming mode
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The data of an opened file, kept for writing
+enum FileData {
+    /// The data read into memory
+    Buffered(Vec<u8>),
+    /// The file mapped into memory
+    #[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+    Mapped(memmap2::Mmap),
+}
+
+impl FileData {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            FileData::Buffered(data) => data,
+            #[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+            FileData::Mapped(map) => map,
+        }
+    }
+}
+
+/// Options for writing XMP metadata to files or memory.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use xmpkit::{SaveOptions, SaveOutcome, XmpFile};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut file = XmpFile::new();
+/// file.open("video.mp4")?;
+/// // ... modify metadata ...
+/// let outcome = file.save_with("video.mp4", SaveOptions::default().prefer_in_place())?;
+/// if outcome == SaveOutcome::InPlace {
+///     println!("Only the XMP packet was rewritten");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default, Clone, Copy, Debug)]
+pub struct SaveOptions {
+    /// Overwrite the existing packet when the new one fits in it
+    pub(crate) prefer_in_place: bool,
+    /// Write XMP values back to the native EXIF metadata
+    pub(crate) reconcile_exif: bool,
+    /// Write XMP values back to the native IPTC-IIM metadata
+    pub(crate) reconcile_iptc: bool,
+    /// Write XMP values back to the native ID3 tags
+    pub(crate) reconcile_id3: bool,
+    /// Write XMP values back to the native QuickTime metadata
+    pub(crate) reconcile_quicktime: bool,
+    /// Write XMP values back to the native PNG text chunks
+    pub(crate) reconcile_png_text: bool,
+    /// Record the save in the `xmpMM` properties
+    pub(crate) record_history: bool,
+    /// Save as a new document derived from the opened one
+    pub(crate) derive_document: bool,
+    /// Stamp `xmp:MetadataDate` and `xmp:ModifyDate` with the save time
+    pub(crate) update_metadata_date: bool,
+    /// Time zone of the save time, as an offset from UTC in minutes
+    pub(crate) timezone: Option<i32>,
+    /// Write a temporary file and rename it over the target
+    pub(crate) atomic: bool,
+    /// Keep the modification time of the target
+    pub(crate) keep_modification_time: bool,
+    /// Remove the XMP and native metadata of the file
+    pub(crate) strip_all_metadata: bool,
+}
+
+impl SaveOptions {
+    /// Overwrite the existing XMP packet in place when possible.
+    ///
+    /// The existing packet must be writable (`end="w"`) and the new packet
+    /// must fit in its padding. Formats that checksum the packet (such as
+    /// PNG) are always rewritten.
+    pub fn prefer_in_place(mut self) -> Self {
+        self.prefer_in_place = true;
+        self
+    }
+
+    /// Write XMP values back to the native EXIF metadata.
+    ///
+    /// The orientation, description, artist, copyright and dates of the XMP
+    /// are written to the EXIF of JPEG and TIFF files, so that viewers that
+    /// only read EXIF see the updates. EXIF tags without a corresponding XMP
+    /// property are left unchanged. The file is always rewritten (never
+    /// updated in place).
+    pub fn reconcile_exif(mut self) -> Self {
+        self.reconcile_exif = true;
+        self
+    }
+
+    /// Write XMP values back to the native IPTC-IIM metadata.
+    ///
+    /// The `dc:`, `photoshop:` and `Iptc4xmpCore:` properties mapped by the
+    /// IPTC Photo Metadata Standard (title, description, keywords, creator,
+    /// location, credits, ...) are written to the IPTC records of JPEG
+    /// files, along with the digest that marks them as in sync with the
+    /// XMP. Datasets without a corresponding XMP property are left
+    /// unchanged. The file is always rewritten (never updated in place).
+    pub fn reconcile_iptc(mut self) -> Self {
+        self.reconcile_iptc = true;
+        self
+    }
+
+    /// Write XMP values back to the native ID3 tags.
+    ///
+    /// The title, artist, album, date, comment, genre, copyright, track
+    /// number and composer of the XMP (`dc:`, `xmp:CreateDate` and `xmpDM:`
+    /// properties) are written to the ID3v2 frames of MP3 files, which
+    /// players display. Frames without a corresponding XMP property are
+    /// left unchanged. The file is always rewritten (never updated in
+    /// place).
+    pub fn reconcile_id3(mut self) -> Self {
+        self.reconcile_id3 = true;
+        self
+    }
+
+    /// Write XMP values back to the native QuickTime metadata.
+    ///
+    /// The title, artist, date and keywords of the XMP (`dc:title`,
+    /// `xmpDM:artist`, `xmp:CreateDate` and `dc:subject`) are written to
+    /// the iTunes metadata items of MP4 and M4A files, and the date to the
+    /// creation time of their movie and media headers. Items without a
+    /// corresponding XMP property are left unchanged. The file is always
+    /// rewritten (never updated in place).
+    pub fn reconcile_quicktime(mut self) -> Self {
+        self.reconcile_quicktime = true;
+        self
+    }
+
+    /// Write XMP values back to the native PNG text chunks.
+    ///
+    /// The title, creators, description, rights and creation date of the
+    /// XMP (`dc:` properties and `xmp:CreateDate`) are written to the
+    /// Title, Author, Description, Copyright and Creation Time text chunks
+    /// of PNG files. Chunks without a corresponding XMP property are left
+    /// unchanged. The file is always rewritten (never updated in place).
+    pub fn reconcile_png_text(mut self) -> Self {
+        self.reconcile_png_text = true;
+        self
+    }
+
+    /// Record the save in the media management (`xmpMM`) properties.
+    ///
+    /// A new `xmpMM:InstanceID` is generated, the document IDs are
+    /// initialized if needed, and a "saved" event with the current time and
+    /// the `xmp:CreatorTool` as software is added to `xmpMM:History` (see
+    /// [`XmpMeta::record_save`]). The metadata of the [`XmpFile`] is updated
+    /// as well.
+    pub fn record_history(mut self) -> Self {
+        self.record_history = true;
+        self
+    }
+
+    /// Save as a new document derived from the opened one.
+    ///
+    /// `xmpMM:DerivedFrom` references the opened document, and new document
+    /// and instance IDs are generated (see [`XmpMeta::derive_document`]).
+    /// The metadata of the [`XmpFile`] is updated as well.
+    pub fn derive_document(mut self) -> Self {
+        self.derive_document = true;
+        self
+    }
+
+    /// Stamp `xmp:MetadataDate` and `xmp:ModifyDate` with the current time.
+    ///
+    /// The dates are in UTC unless a time zone is selected with
+    /// [`timezone`](Self::timezone). Nothing is stamped on platforms
+    /// without a clock. The metadata of the [`XmpFile`] is updated as well.
+    pub fn update_metadata_date(mut self) -> Self {
+        self.update_metadata_date = true;
+        self
+    }
+
+    /// Select the time zone of the dates written when saving.
+    ///
+    /// The offset from UTC is in minutes, e.g. `-300` for New York in
+    /// winter. It applies to the dates of
+    /// [`update_metadata_date`](Self::update_metadata_date) and to the
+    /// event of [`record_history`](Self::record_history).
+    pub fn timezone(mut self, offset_minutes: i32) -> Self {
+        self.timezone = Some(offset_minutes);
+        self
+    }
+
+    /// Save atomically, so that a crash never leaves a partly written file.
+    ///
+    /// The file is written to a temporary file in the same directory,
+    /// flushed to disk and renamed over the target. The permissions of the
+    /// target are kept, and on Unix its owner and group when the process is
+    /// allowed to set them. With [`prefer_in_place`](Self::prefer_in_place),
+    /// the packet is still replaced in place, but in the temporary copy.
+    pub fn atomic(mut self) -> Self {
+        self.atomic = true;
+        self
+    }
+
+    /// Keep the modification time of the file being replaced.
+    ///
+    /// Useful for tools that sort files by date, since only the metadata
+    /// changes.
+    pub fn keep_modification_time(mut self) -> Self {
+        self.keep_modification_time = true;
+        self
+    }
+
+    /// Remove all the metadata of the file.
+    ///
+    /// The XMP packet and the native metadata (such as EXIF, IPTC-IIM,
+    /// thumbnails and PNG text chunks) are removed, whatever the metadata
+    /// of the [`XmpFile`]. Metadata that affects how the file is rendered,
+    /// such as ICC profiles, is kept. Formats whose handler can't remove
+    /// metadata return `XmpError::NotSupported`.
+    pub fn strip_all_metadata(mut self) -> Self {
+        self.strip_all_metadata = true;
+        self
+    }
+
+    /// Check if the existing packet may be overwritten in place
+    fn allows_in_place(&self) -> bool {
+        self.prefer_in_place && !self.exports_legacy() && !self.strip_all_metadata
+    }
+
+    /// Check if native metadata is written along with the XMP
+    fn exports_legacy(&self) -> bool {
+        self.reconcile_exif
+            || self.reconcile_iptc
+            || self.reconcile_id3
+            || self.reconcile_quicktime
+            || self.reconcile_png_text
+    }
+}
+
+/// How XMP metadata was written by [`XmpFile::save_with`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveOutcome {
+    /// Nothing was written, as the opened file had no changes to save
+    /// (see [`XmpFile::is_dirty`])
+    Unchanged,
+    /// Only the bytes of the existing XMP packet were replaced
+    InPlace,
+    /// The file was rewritten by its format handler
+    Rewritten,
+}
+
+/// Location and encoding of the XMP packet of a file
+///
+/// Returned by [`XmpFile::packet_info`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PacketInfo {
+    /// Byte offset of the packet (`<?xpacket begin`) in the file
+    pub offset: u64,
+    /// Length of the packet in bytes, up to the end of `<?xpacket end?>`
+    pub length: usize,
+    /// Length of the whitespace padding before `<?xpacket end`, in bytes
+    pub padding: usize,
+    /// Character encoding of the packet
+    pub encoding: PacketEncoding,
+    /// Whether the packet is marked writable (`end="w"`)
+    pub writable: bool,
+}
+
+/// The XMP packet of a file, as located by [`XmpFile::packet_info`]
+struct RawPacket {
+    info: PacketInfo,
+    bytes: Vec<u8>,
+}
+
+/// Location of an XMP packet in file data
+pub(crate) struct PacketLocation {
+    /// Byte offset of `<?xpacket begin`
+    pub(crate) start: usize,
+    /// Byte offset just after the closing `?>` of `<?xpacket end`
+    pub(crate) end: usize,
+    /// Whether the packet is marked writable (`end="w"`)
+    writable: bool,
+    /// Character encoding of the packet
+    encoding: PacketEncoding,
+}
+
+/// Incremental search for the first XMP packet that parses
+///
+/// Data is pushed in chunks, and only the data from the start of a
+/// candidate packet onwards is kept in memory.
+pub(crate) struct PacketScanner {
+    markers: Vec<Vec<u8>>,
+    end_markers: Vec<Vec<u8>>,
+    marker_len: usize,
+    buffer: Vec<u8>,
+    /// Offset in the data of buffer[0]
+    base: usize,
+}
+
+impl PacketScanner {
+    pub(crate) fn new() -> Self {
+        let markers: Vec<_> = PacketEncoding::ALL
+            .iter()
+            .map(|encoding| encoding.encode("<?xpacket"))
+            .collect();
+        let end_markers = PacketEncoding::ALL
+            .iter()
+            .map(|encoding| encoding.encode("<?xpacket end"))
+            .collect();
+        let marker_len = markers.iter().map(Vec::len).max().unwrap_or(0);
+        Self {
+            markers,
+            end_markers,
+            marker_len,
+            buffer: Vec::new(),
+            base: 0,
+        }
+    }
+
+    /// Scan the next chunk of data
+    ///
+    /// Returns the packet once its trailer has been pushed.
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> Option<(PacketLocation, XmpMeta)> {
+        self.buffer.extend_from_slice(chunk);
+
+        // Only try parsing once a packet trailer has been read
+        let has_trailer = self.end_markers.iter().any(|end_marker| {
+            self.buffer
+                .windows(end_marker.len())
+                .any(|window| window == end_marker.as_slice())
+        });
+        if has_trailer {
+            if let Some(found) = self.parse() {
+                return Some(found);
+            }
+        }
+
+        // Keep everything from the first packet start, or just enough
+        // bytes to find a marker split across chunks
+        let keep_from = self
+            .markers
+            .iter()
+            .filter_map(|marker| {
+                self.buffer
+                    .windows(marker.len())
+                    .position(|window| window == marker.as_slice())
+            })
+            .min()
+            .unwrap_or(self.buffer.len().saturating_sub(self.marker_len - 1));
+        self.buffer.drain(..keep_from);
+        self.base += keep_from;
+        None
+    }
+
+    /// Scan the data left at the end of the input
+    pub(crate) fn finish(&mut self) -> Option<(PacketLocation, XmpMeta)> {
+        self.parse()
+    }
+
+    fn parse(&self) -> Option<(PacketLocation, XmpMeta)> {
+        let (mut location, meta) = XmpFile::find_xmp_packet(&self.buffer, false)?;
+        location.start += self.base;
+        location.end += self.base;
+        Some((location, meta))
+    }
 }
 
 /// High-level API for working with XMP metadata in files
@@ -153,13 +641,29 @@ pub struct XmpFile {
     file_path: Option<std::path::PathBuf>,
     /// Original file data (for in-memory operations)
     #[allow(dead_code)] // Used in native code paths (open_with, try_close)
-    file_data: Option<Vec<u8>>,
+    file_data: Option<FileData>,
     /// Handler used to read/write the file
     #[allow(dead_code)] // Used in native code paths (open_with, try_close)
     handler: Option<crate::files::registry::Handler>,
     /// Open options
     #[allow(dead_code)] // Used in native code paths (open_with, try_close)
     options: ReadOptions,
+    /// Sidecar file associated with the file by `open_with_sidecar`
+    #[cfg(not(target_arch = "wasm32"))]
+    sidecar_path: Option<std::path::PathBuf>,
+    /// Whether the metadata was read from the sidecar file
+    #[cfg(not(target_arch = "wasm32"))]
+    from_sidecar: bool,
+    /// Whether the XMP packet is removed when saving
+    xmp_removed: bool,
+    /// Whether `put_xmp` replaced the metadata with other properties since
+    /// opening or saving
+    meta_replaced: AtomicBool,
+    /// The packet of the opened data, located on first use
+    raw_packet: OnceLock<Option<RawPacket>>,
+    /// Callback reporting the progress of opening and saving
+    #[cfg(not(target_arch = "wasm32"))]
+    progress: Option<ProgressCallback>,
     /// Whether the file is open
     is_open: bool,
 }
@@ -176,12 +680,38 @@ impl XmpFile {
             file_data: None,
             handler: None,
             options: ReadOptions::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            sidecar_path: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            from_sidecar: false,
+            xmp_removed: false,
+            meta_replaced: AtomicBool::new(false),
+            raw_packet: OnceLock::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            progress: None,
             is_open: false,
         }
     }
 
+    /// Set the callback reporting the progress of opening and saving files
+    /// (native platforms only)
+    ///
+    /// The callback is called while the file data is read when opening,
+    /// and while it is written when saving, and can cancel the operation;
+    /// see [`progress`](crate::files::progress). It is kept for the files
+    /// opened later: pass `None` to remove it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_progress_callback(&mut self, callback: Option<ProgressCallback>) {
+        self.progress = callback;
+    }
+
     /// Open a file from a path with options (native platforms only)
     ///
+    /// With the `mmap` feature, the file is mapped into memory instead of
+    /// being read, unless in streaming mode: only the parts that the format
+    /// handler or packet scanning look at are read from disk. Files that
+    /// can't be mapped are read normally.
+    ///
     /// # Platform Support
     ///
     /// - Native platforms (iOS, Android, macOS, Windows)
@@ -206,10 +736,52 @@ impl XmpFile {
     ) -> XmpResult<()> {
         use std::fs;
         let path = path.as_ref();
+        Self::check_limited_scanning(path, &options)?;
+
+        // Read file and use from_reader_with
+        let file = fs::File::open(path)?;
+        #[cfg(feature = "mmap")]
+        if !options.streaming {
+            if let Some(map) = Self::map_file(&file) {
+                self.reset(options);
+                let handler = self.track_reading(Cursor::new(&map[..]), |file, source| {
+                    file.read_source(source, options)
+                })?;
+                self.finish_open(Some(FileData::Mapped(map)), handler);
+                self.file_path = Some(path.to_path_buf());
+                return Ok(());
+            }
+        }
+        self.from_reader_with(
+            std::io::BufReader::with_capacity(options.effective_chunk_size(), file),
+            options,
+        )?;
+        self.file_path = Some(path.to_path_buf());
+        Ok(())
+    }
 
-        // Check limited_scanning: only scan known file types
-        // This check needs to happen before reading the file, so we do it here
-        // rather than in from_reader_with_options
+    /// Map a file into memory, or `None` to fall back to reading it
+    ///
+    /// Only regular files are mapped: pipes and devices are read normally.
+    #[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+    fn map_file(file: &std::fs::File) -> Option<memmap2::Mmap> {
+        let metadata = file.metadata().ok()?;
+        if !metadata.is_file() || metadata.len() == 0 {
+            return None;
+        }
+        // SAFETY: the mapping is only read. Like with any memory map, if
+        // another process truncates the file while it is open, reading the
+        // missing pages fails; saving over the source replaces the file
+        // instead of truncating it.
+        unsafe { memmap2::Mmap::map(file) }.ok()
+    }
+
+    /// Check `limited_scanning`: only scan known file types
+    ///
+    /// This check needs to happen before reading the file, so it is done when
+    /// opening a path rather than in `from_reader_with`.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn check_limited_scanning(path: &std::path::Path, options: &ReadOptions) -> XmpResult<()> {
         if options.use_packet_scanning && options.limited_scanning {
             let file_ext = path
                 .extension()
@@ -225,11 +797,51 @@ impl XmpFile {
                 )));
             }
         }
+        Ok(())
+    }
 
-        // Read file and use from_reader_with
-        let file = fs::File::open(path)?;
-        self.file_path = Some(path.to_path_buf());
-        self.from_reader_with(file, options)
+    /// Detect the format of file data from its content
+    ///
+    /// The signatures of the formats whose feature is enabled are checked,
+    /// so files with a wrong extension (or none) are recognized. Returns
+    /// `FileFormat::Unknown` for other data.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{FileFormat, XmpFile};
+    ///
+    /// let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+    /// assert_eq!(XmpFile::detect_format(png), FileFormat::Png);
+    /// assert_eq!(XmpFile::detect_format(b"plain text"), FileFormat::Unknown);
+    /// ```
+    pub fn detect_format(data: &[u8]) -> FileFormat {
+        let registry = default_registry();
+        match registry.find_by_detection(&mut Cursor::new(data)) {
+            Ok(Some(handler)) => handler.format(),
+            _ => FileFormat::Unknown,
+        }
+    }
+
+    /// Get what xmpkit can do with the files of a format
+    ///
+    /// Formats whose feature is not enabled have no capabilities.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::{FileFormat, XmpFile};
+    ///
+    /// let info = XmpFile::format_info(FileFormat::Jpeg);
+    /// assert!(info.can_write && info.supports_in_place);
+    /// assert!(!XmpFile::format_info(FileFormat::Indd).can_write);
+    /// ```
+    pub fn format_info(format: FileFormat) -> FormatCapabilities {
+        let registry = default_registry();
+        registry
+            .find_by_format(format)
+            .map(|handler| handler.capabilities())
+            .unwrap_or_default()
     }
 
     /// Scan file content for XMP packet (packet scanning mode)
@@ -237,16 +849,61 @@ impl XmpFile {
     /// This method searches for XMP packets in file content by looking for
     /// the `<?xpacket` marker. Used when packet scanning is requested.
     pub fn scan_for_xmp_packet(file_data: &[u8]) -> XmpResult<Option<XmpMeta>> {
+        Ok(Self::find_xmp_packet(file_data, false).map(|(_, meta)| meta))
+    }
+
+    /// Find the first XMP packet that parses, reading `reader` in chunks
+    ///
+    /// Only the data from the start of a candidate packet onwards is kept in
+    /// memory.
+    fn find_xmp_packet_in<R: Read + Seek + ?Sized>(
+        reader: &mut R,
+        chunk_size: usize,
+    ) -> XmpResult<Option<(PacketLocation, XmpMeta)>> {
+        let mut scanner = PacketScanner::new();
+        reader.seek(std::io::SeekFrom::Start(0))?;
+        let mut chunk = vec![0u8; chunk_size.max(scanner.marker_len)];
+
+        loop {
+            let read = reader.read(&mut chunk)?;
+            if read == 0 {
+                return Ok(scanner.finish());
+            }
+            if let Some(found) = scanner.push(&chunk[..read]) {
+                return Ok(Some(found));
+            }
+        }
+    }
+
+    /// Find the first XMP packet that parses, with its location
+    ///
+    /// Packets in any [`PacketEncoding`] are found; the first in the data
+    /// wins. With `lenient`, malformed XML in the packets is recovered from.
+    fn find_xmp_packet(file_data: &[u8], lenient: bool) -> Option<(PacketLocation, XmpMeta)> {
+        PacketEncoding::ALL
+            .iter()
+            .filter_map(|&encoding| Self::find_encoded_xmp_packet(file_data, encoding, lenient))
+            .min_by_key(|(location, _)| location.start)
+    }
+
+    /// Find the first XMP packet in one encoding that parses
+    fn find_encoded_xmp_packet(
+        file_data: &[u8],
+        encoding: PacketEncoding,
+        lenient: bool,
+    ) -> Option<(PacketLocation, XmpMeta)> {
         // Use byte search to find XMP packet (files may contain binary data)
         // Look for "<?xpacket" pattern
-        let xpacket_start = b"<?xpacket";
+        let xpacket_start = encoding.encode("<?xpacket");
+        let xpacket_end_marker = encoding.encode("<?xpacket end");
+        let pi_end = encoding.encode("?>");
         let mut search_pos = 0;
 
         while search_pos + xpacket_start.len() <= file_data.len() {
             // Find next occurrence of "<?xpacket"
             let Some(pos) = file_data[search_pos..]
                 .windows(xpacket_start.len())
-                .position(|window| window == xpacket_start)
+                .position(|window| window == xpacket_start.as_slice())
             else {
                 break;
             };
@@ -254,10 +911,9 @@ impl XmpFile {
             let start_pos = search_pos + pos;
 
             // Find the end of the packet ("<?xpacket end")
-            let xpacket_end_marker = b"<?xpacket end";
             let Some(packet_end_offset) = file_data[start_pos..]
                 .windows(xpacket_end_marker.len())
-                .position(|window| window.starts_with(xpacket_end_marker))
+                .position(|window| window == xpacket_end_marker.as_slice())
             else {
                 search_pos = start_pos + 1;
                 continue;
@@ -266,52 +922,53 @@ impl XmpFile {
             // Find the actual end: "<?xpacket end=\"w\"?>" or "<?xpacket end=\"r\"?>"
             // Search for "?>" after the end marker (should be close after "end=")
             let end_marker_start = start_pos + packet_end_offset;
-            // Look for "?>" after "<?xpacket end" - it should be within a reasonable distance
-            // (typically "<?xpacket end=\"w\"?>" or "<?xpacket end=\"r\"?>")
-            let Some(close_pos) = file_data[end_marker_start..]
-                .iter()
-                .enumerate()
-                .find(|(_, &b)| b == b'?')
-                .and_then(|(q_pos, _)| {
-                    if end_marker_start + q_pos + 1 < file_data.len()
-                        && file_data[end_marker_start + q_pos + 1] == b'>'
-                    {
-                        // Verify this is actually the end of <?xpacket end (not just any ?>)
-                        // Check that we have "end=" before the ?>
-                        let before_close = &file_data[end_marker_start..end_marker_start + q_pos];
-                        if before_close.ends_with(b"\"w\"") || before_close.ends_with(b"\"r\"") {
-                            Some(q_pos + 2)
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
+            let attrs_start = end_marker_start + xpacket_end_marker.len();
+            // Check that we have end="w" or end="r" before the ?>
+            let end_attrs = |q_pos: usize| {
+                encoding
+                    .decode(&file_data[attrs_start..attrs_start + q_pos])
+                    .ok()
+                    .map(|attrs| attrs.trim_end().to_string())
+            };
+            let Some((close_pos, attrs)) = file_data[attrs_start..]
+                .windows(pi_end.len())
+                .position(|window| window == pi_end.as_slice())
+                .and_then(|q_pos| Some((q_pos, end_attrs(q_pos)?)))
+                .filter(|(_, attrs)| {
+                    ["\"w\"", "\"r\"", "'w'", "'r'"]
+                        .iter()
+                        .any(|value| attrs.ends_with(value))
                 })
+                .map(|(q_pos, attrs)| (attrs_start + q_pos + pi_end.len(), attrs))
             else {
                 search_pos = start_pos + 1;
                 continue;
             };
 
-            let packet_end_pos = end_marker_start + close_pos;
-
-            // Extract packet as string (XMP content should be valid UTF-8)
-            if let Ok(packet_str) = std::str::from_utf8(&file_data[start_pos..packet_end_pos]) {
-                // Try to parse the packet
-                match XmpMeta::parse(packet_str) {
-                    Ok(meta) => return Ok(Some(meta)),
-                    Err(_) => {
-                        // If parsing fails, continue searching for another packet
-                        search_pos = start_pos + 1;
-                        continue;
+            // Decode the packet and try to parse it
+            let parsed = encoding
+                .decode(&file_data[start_pos..close_pos])
+                .and_then(|packet_str| {
+                    if lenient {
+                        XmpMeta::parse_lenient(&packet_str).map(|(meta, _)| meta)
+                    } else {
+                        XmpMeta::parse(&packet_str)
                     }
-                }
+                });
+            if let Ok(meta) = parsed {
+                let location = PacketLocation {
+                    start: start_pos,
+                    end: close_pos,
+                    writable: attrs.ends_with("\"w\"") || attrs.ends_with("'w'"),
+                    encoding,
+                };
+                return Some((location, meta));
             }
-
+            // If parsing fails, continue searching for another packet
             search_pos = start_pos + 1;
         }
 
-        Ok(None)
+        None
     }
 
     /// Open a file from a path (native platforms only)
@@ -334,9 +991,93 @@ impl XmpFile {
     /// ```
     #[cfg(not(target_arch = "wasm32"))]
     pub fn open<P: AsRef<std::path::Path>>(&mut self, path: P) -> XmpResult<()> {
-        use std::fs::File;
-        let file = File::open(path)?;
-        self.from_reader(file)
+        self.open_with(path, ReadOptions::default())
+    }
+
+    /// Open a file, preferring the metadata of its XMP sidecar file (native platforms only)
+    ///
+    /// If a sidecar file exists next to the file (see
+    /// [`XmpFile::sidecar_path_for`]), its metadata replaces the metadata
+    /// embedded in the file, as Lightroom does. Otherwise the embedded
+    /// metadata is used.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use xmpkit::XmpFile;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut file = XmpFile::new();
+    /// file.open_with_sidecar("photo.cr2")?;
+    /// if let Some(sidecar) = file.sidecar_path() {
+    ///     println!("Metadata read from {}", sidecar.display());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open_with_sidecar<P: AsRef<std::path::Path>>(&mut self, path: P) -> XmpResult<()> {
+        self.open_with_sidecar_with(path, ReadOptions::default())
+    }
+
+    /// Open a file with options, preferring the metadata of its XMP sidecar file (native platforms only)
+    ///
+    /// See [`XmpFile::open_with_sidecar`]. When the file is opened with
+    /// [`ReadOptions::for_update`], closing it writes the changes to the
+    /// sidecar file if the metadata was read from it. Otherwise they are
+    /// written to the file itself, or to a new sidecar file if the format
+    /// can't be written (such as proprietary camera RAW files).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open_with_sidecar_with<P: AsRef<std::path::Path>>(
+        &mut self,
+        path: P,
+        options: ReadOptions,
+    ) -> XmpResult<()> {
+        let path = path.as_ref();
+        self.open_with(path, options)?;
+
+        let sidecar_path = Self::sidecar_path_for(path);
+        if sidecar_path.is_file() {
+            let file = std::fs::File::open(&sidecar_path)?;
+            if let Some(meta) = SidecarHandler::read_xmp(std::io::BufReader::new(file))? {
+                self.meta = Some(meta);
+                self.from_sidecar = true;
+            }
+        }
+        self.sidecar_path = Some(sidecar_path);
+        Ok(())
+    }
+
+    /// Get the path of the XMP sidecar file of a file
+    ///
+    /// Returns the first existing file among `name.xmp` and `name.ext.xmp`
+    /// (the conventions of Lightroom and darktable), or `name.xmp` if
+    /// neither exists.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn sidecar_path_for<P: AsRef<std::path::Path>>(path: P) -> std::path::PathBuf {
+        let path = path.as_ref();
+        let replaced = path.with_extension("xmp");
+        if replaced.is_file() || path.extension().is_none() {
+            return replaced;
+        }
+
+        let mut appended = path.as_os_str().to_owned();
+        appended.push(".xmp");
+        let appended = std::path::PathBuf::from(appended);
+        if appended.is_file() {
+            appended
+        } else {
+            replaced
+        }
+    }
+
+    /// Get the path of the sidecar file the metadata was read from
+    ///
+    /// Returns `None` unless the file was opened with
+    /// [`XmpFile::open_with_sidecar`] and a sidecar file was found.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn sidecar_path(&self) -> Option<&std::path::Path> {
+        self.sidecar_path.as_deref().filter(|_| self.from_sidecar)
     }
 
     /// Open a file from bytes (all platforms, including Wasm)
@@ -412,105 +1153,190 @@ impl XmpFile {
     /// ```
     pub fn from_reader_with<R: Read + Seek>(
         &mut self,
-        mut reader: R,
+        reader: R,
         options: ReadOptions,
     ) -> XmpResult<()> {
-        // Reset state before opening (in case of retry)
+        self.track_reading(reader, |file, reader| file.read_from(reader, options))
+    }
+
+    /// Open a file stored in an [`XmpIo`] (all platforms, including Wasm)
+    ///
+    /// The data is read from the start of `io` into memory, even in
+    /// streaming mode. Save the changes with [`XmpFile::save_io`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::files::io::MemoryIo;
+    /// use xmpkit::{ReadOptions, XmpFile};
+    ///
+    /// let mut io = MemoryIo::new(b"no metadata here".to_vec());
+    /// let mut file = XmpFile::new();
+    /// file.open_io(&mut io, ReadOptions::default()).unwrap();
+    /// assert!(file.get_xmp().is_none());
+    /// ```
+    pub fn open_io(&mut self, io: &mut dyn XmpIo, mut options: ReadOptions) -> XmpResult<()> {
+        options.streaming = false;
+        io.seek(std::io::SeekFrom::Start(0))?;
+        self.from_reader_with(io, options)
+    }
+
+    /// Run `read` on `source`, reporting the progress of reading it to the
+    /// progress callback
+    fn track_reading<S: Read + Seek, T>(
+        &mut self,
+        mut source: S,
+        read: impl FnOnce(&mut Self, &mut dyn ReadSeek) -> XmpResult<T>,
+    ) -> XmpResult<T> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(callback) = self.progress.clone() {
+            let position = source.stream_position()?;
+            let total = source.seek(std::io::SeekFrom::End(0))?;
+            source.seek(std::io::SeekFrom::Start(position))?;
+            let tracker = ProgressTracker::start(&callback, total);
+            let result = read(self, &mut tracker.reader(source, position));
+            return tracker.finish(result);
+        }
+        read(self, &mut source)
+    }
+
+    /// Open a file from a reader
+    fn read_from<R: Read + Seek>(&mut self, mut reader: R, options: ReadOptions) -> XmpResult<()> {
+        self.reset(options);
+
+        // In streaming mode the handlers work directly on the reader.
+        // Otherwise, read file data for packet scanning or handler operations
+        // and store it for writing (needed on all platforms including Wasm).
+        if options.streaming {
+            let handler = self.read_source(&mut reader, options)?;
+            self.finish_open(None, handler);
+        } else {
+            let mut file_data = Vec::new();
+            reader.read_to_end(&mut file_data)?;
+            let handler = self.read_source(&mut Cursor::new(file_data.as_slice()), options)?;
+            self.finish_open(Some(FileData::Buffered(file_data)), handler);
+        }
+        Ok(())
+    }
+
+    /// Reset the state before opening (in case of retry)
+    fn reset(&mut self, options: ReadOptions) {
         self.meta = None;
+        self.file_data = None;
+        self.xmp_removed = false;
+        self.meta_replaced = AtomicBool::new(false);
+        self.raw_packet = OnceLock::new();
         #[cfg(not(target_arch = "wasm32"))]
         {
+            self.file_path = None;
             self.handler = None;
+            self.sidecar_path = None;
+            self.from_sidecar = false;
             self.is_open = false;
         }
         self.options = options;
+    }
 
-        // Read file data for potential packet scanning or handler operations
-        // Store file_data for writing (needed on all platforms including Wasm)
-        let mut file_data = Vec::new();
-        reader.read_to_end(&mut file_data)?;
-        self.file_data = Some(file_data.clone());
-
-        // If packet scanning is requested, search for XMP packet in file content
-        // Note: limited_scanning check is done in open_with (for file paths) before calling this
-        if options.use_packet_scanning {
-            self.meta = Self::scan_for_xmp_packet(&file_data)?;
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                self.is_open = true;
-            }
-            return Ok(());
+    /// Keep the state of an opened file
+    fn finish_open(
+        &mut self,
+        file_data: Option<FileData>,
+        handler: Option<crate::files::registry::Handler>,
+    ) {
+        self.file_data = file_data;
+        // Legacy metadata imported while reading is not a change to save
+        if let Some(meta) = &self.meta {
+            meta.clear_changes();
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.handler = handler;
+            self.is_open = true;
         }
+        #[cfg(target_arch = "wasm32")]
+        let _ = handler;
+    }
 
-        // Detect handler
-        let registry = default_registry();
-        let mut reader_cursor = Cursor::new(&file_data);
-
-        // Handle force_given_handler: skip format detection, use handler directly
-        // Note: This requires a handler to be specified, which we don't currently support
-        // in from_reader_with_options. For now, we'll just proceed with normal detection.
-        let handler = if options.force_given_handler {
-            // Force given handler: try all handlers without format check
-            // This is a simplified version - in full implementation, handler would be specified
-            registry.find_by_detection(&mut reader_cursor)?
+    /// Read the metadata of a file, returning the handler of its format
+    fn read_source(
+        &mut self,
+        source: &mut dyn ReadSeek,
+        options: ReadOptions,
+    ) -> XmpResult<Option<crate::files::registry::Handler>> {
+        let mut source = source;
+        let chunk_size = options.effective_chunk_size();
+        let handler = if options.use_packet_scanning {
+            // If packet scanning is requested, search for XMP packet in file content
+            // Note: limited_scanning check is done in open_with (for file paths) before calling this
+            self.meta = Self::find_xmp_packet_in(source, chunk_size)?.map(|(_, meta)| meta);
+            None
         } else {
-            registry.find_by_detection(&mut reader_cursor)?
-        };
+            // Detect handler
+            let registry = default_registry();
+            source.seek(std::io::SeekFrom::Start(0))?;
 
-        // Handle use_smart_handler: if set and no handler found, return error
-        if options.use_smart_handler {
-            let handler = handler.ok_or_else(|| {
-                XmpError::NotSupported("No smart file handler available to handle file".to_string())
-            })?;
-            // Read XMP (only_xmp flag is passed implicitly - handlers already only read XMP)
-            reader_cursor.set_position(0);
-            self.meta = handler.read_xmp(&mut reader_cursor)?;
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                self.handler = Some(handler.clone());
-                self.is_open = true;
+            let handler = options.find_handler(&registry, &mut source)?;
+
+            // Handle use_smart_handler: if set and no handler found, return error
+            if options.use_smart_handler && handler.is_none() {
+                return Err(XmpError::NotSupported(
+                    "No smart file handler available to handle file".to_string(),
+                ));
             }
-            return Ok(());
-        }
 
-        // Handle strict: if set and no handler found, return error (don't fall back)
-        if options.strict {
-            let handler = handler.ok_or_else(|| {
-                XmpError::NotSupported("No handler available for file format".to_string())
-            })?;
-            // Read XMP (only_xmp flag is passed implicitly)
-            reader_cursor.set_position(0);
-            self.meta = handler.read_xmp(&mut reader_cursor)?;
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                self.handler = Some(handler.clone());
-                self.is_open = true;
+            // Handle strict: if set and no handler found, return error (don't fall back)
+            if options.strict && handler.is_none() {
+                return Err(XmpError::NotSupported(
+                    "No handler available for file format".to_string(),
+                ));
             }
-            return Ok(());
-        }
 
-        // Normal case: try to find handler, fall back to packet scanning if not found
-        if let Some(handler) = handler {
-            // Read XMP
-            // Note: only_xmp flag is currently not used in our handlers as they already
-            // only read XMP metadata. This flag is kept for API compatibility and future
-            // optimizations where handlers might skip reading other metadata (Exif, IPTC, etc.)
-            reader_cursor.set_position(0);
-            self.meta = handler.read_xmp(&mut reader_cursor)?;
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                self.handler = Some(handler.clone());
-                self.is_open = true;
+            if let Some(handler) = handler {
+                self.meta = match Self::read_with_handler(handler, source, &options) {
+                    Ok(meta) => meta,
+                    // Scan the damaged file for its packet
+                    Err(_) if options.repair_file => {
+                        source.seek(std::io::SeekFrom::Start(0))?;
+                        let mut data = Vec::new();
+                        source.read_to_end(&mut data)?;
+                        Self::find_xmp_packet(&data, true).map(|(_, meta)| meta)
+                    }
+                    Err(e) => return Err(e),
+                };
+                Some(handler.clone())
+            } else {
+                // No handler found, try packet scanning as fallback
+                self.meta = Self::find_xmp_packet_in(source, chunk_size)?.map(|(_, meta)| meta);
+                None
             }
-            Ok(())
-        } else {
-            // No handler found, try packet scanning as fallback
-            self.meta = Self::scan_for_xmp_packet(&file_data)?;
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                self.is_open = true;
+        };
+        Ok(handler)
+    }
+
+    /// Read the XMP with a format handler, then import legacy metadata
+    /// (Exif, etc.) unless only XMP is wanted
+    fn read_with_handler(
+        handler: &crate::files::registry::Handler,
+        mut source: &mut dyn ReadSeek,
+        options: &ReadOptions,
+    ) -> XmpResult<Option<XmpMeta>> {
+        source.seek(std::io::SeekFrom::Start(0))?;
+        let xmp = handler.read_xmp(&mut source)?;
+        if options.only_xmp {
+            return Ok(xmp);
+        }
+        source.seek(std::io::SeekFrom::Start(0))?;
+        let had_xmp = xmp.is_some();
+        let mut meta = xmp.unwrap_or_default();
+        let mut imported = handler.import_legacy(&mut source, &mut meta)?;
+        if options.import_thumbnail && !meta.does_property_exist(ns::XMP, "Thumbnails") {
+            source.seek(std::io::SeekFrom::Start(0))?;
+            if let Some(thumbnail) = handler.read_thumbnail(&mut source)? {
+                meta.add_thumbnail(&thumbnail)?;
+                imported = true;
             }
-            Ok(())
         }
+        Ok((imported || had_xmp).then_some(meta))
     }
 
     /// Get the XMP metadata
@@ -527,6 +1353,49 @@ impl XmpFile {
         self.meta.as_mut()
     }
 
+    /// Get the location and encoding of the XMP packet of the file
+    ///
+    /// The packet is located in the data that was opened, by scanning it
+    /// for `<?xpacket` the first time. Returns `None` if the data has no
+    /// packet that the format handler reads, such as compressed packets or
+    /// packets without the `<?xpacket` wrapper, or if it can't be read.
+    /// Saving the file doesn't update the location; open the file again to
+    /// locate the new packet.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use xmpkit::XmpFile;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut file = XmpFile::new();
+    /// file.open("image.jpg")?;
+    /// if let Some(info) = file.packet_info() {
+    ///     println!("{} bytes at {} ({} of padding)", info.length, info.offset, info.padding);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn packet_info(&self) -> Option<PacketInfo> {
+        self.raw_packet().map(|packet| packet.info)
+    }
+
+    /// Get the bytes of the XMP packet of the file, as they are in the file
+    ///
+    /// The bytes are not decoded nor re-serialized, so they can be hashed
+    /// or archived. See [`XmpFile::packet_info`] for how the packet is
+    /// located.
+    pub fn raw_xmp(&self) -> Option<&[u8]> {
+        self.raw_packet().map(|packet| packet.bytes.as_slice())
+    }
+
+    /// Get the packet of the opened data, locating it on first use
+    fn raw_packet(&self) -> Option<&RawPacket> {
+        self.raw_packet
+            .get_or_init(|| self.read_raw_packet().ok().flatten())
+            .as_ref()
+    }
+
     /// Put XMP metadata
     ///
     /// Replaces any existing metadata.
@@ -561,10 +1430,82 @@ impl XmpFile {
     /// # }
     /// ```
     pub fn put_xmp(&mut self, meta: XmpMeta) {
+        // A copy of the current metadata shares its properties and changes
+        if !self.meta.as_ref().is_some_and(|old| old.shares_root(&meta)) {
+            *self.meta_replaced.get_mut() = true;
+        }
         self.meta = Some(meta);
+        self.xmp_removed = false;
         // Note: Changes are written to disk when close() or try_close() is called
     }
 
+    /// Remove the XMP metadata
+    ///
+    /// The XMP packet is removed from the file when it is saved, or when it
+    /// is closed if it was opened for update; the native metadata is kept
+    /// (see [`SaveOptions::strip_all_metadata`] to remove it too). A sidecar
+    /// file associated by [`XmpFile::open_with_sidecar`] is deleted when
+    /// closing. Calling [`XmpFile::put_xmp`] cancels the removal.
+    ///
+    /// Formats whose handler can't remove the packet return
+    /// `XmpError::NotSupported` when saving.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use xmpkit::{XmpFile, ReadOptions};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut file = XmpFile::new();
+    /// file.open_with("image.jpg", ReadOptions::default().for_update())?;
+    /// file.remove_xmp();
+    /// file.try_close()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn remove_xmp(&mut self) {
+        self.meta = None;
+        self.xmp_removed = true;
+    }
+
+    /// Check if there are changes to save
+    ///
+    /// The file has changes once its metadata changed (see
+    /// [`XmpMeta::is_dirty`]), was replaced by [`XmpFile::put_xmp`] or
+    /// removed by [`XmpFile::remove_xmp`]. Saving to the opened file, and
+    /// closing a file opened for update, do nothing without changes.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use xmpkit::{ns, ReadOptions, XmpFile, XmpValue};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut file = XmpFile::new();
+    /// file.open_with("image.jpg", ReadOptions::default().for_update())?;
+    /// assert!(!file.is_dirty());
+    ///
+    /// if let Some(meta) = file.get_xmp_mut() {
+    ///     meta.set_property(ns::XMP, "Rating", XmpValue::Integer(5))?;
+    /// }
+    /// assert_eq!(file.is_dirty(), file.get_xmp().is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn is_dirty(&self) -> bool {
+        self.xmp_removed
+            || self.meta_replaced.load(Ordering::Relaxed)
+            || self.meta.as_ref().is_some_and(XmpMeta::is_dirty)
+    }
+
+    /// Forget the changes, once they are saved to the opened file
+    fn clear_changes(&self) {
+        self.meta_replaced.store(false, Ordering::Relaxed);
+        if let Some(meta) = &self.meta {
+            meta.clear_changes();
+        }
+    }
+
     /// Explicitly closes an opened file.
     ///
     /// Performs any necessary output to the file and closes it. Files that are
@@ -607,7 +1548,8 @@ impl XmpFile {
     /// Explicitly closes an opened file with error handling.
     ///
     /// Performs any necessary output to the file and closes it. Files that are
-    /// opened for update are written to only when closing.
+    /// opened for update are written to only when closing, and only if they
+    /// have changes (see [`XmpFile::is_dirty`]).
     ///
     /// If the file is opened for read-only access (using
     /// [`ReadOptions::for_read`]), the disk file is closed
@@ -645,52 +1587,33 @@ impl XmpFile {
         // On native, if opened for update, write changes to disk
         #[cfg(not(target_arch = "wasm32"))]
         {
-            if self.options.for_update {
-                if let Some(ref path) = self.file_path {
-                    if let Some(ref meta) = self.meta {
-                        use std::fs::File;
-                        use std::io::BufWriter;
-
-                        // If handler is None (e.g., packet scanning mode), detect handler from file data
-                        let handler = if let Some(ref h) = self.handler {
-                            h.clone()
-                        } else {
-                            // Use file_data to detect handler (avoid re-opening file)
-                            let registry = default_registry();
-                            let mut reader =
-                                Cursor::new(self.file_data.as_ref().ok_or_else(|| {
-                                    XmpError::BadValue(
-                                        "File data not available for handler detection".to_string(),
-                                    )
-                                })?);
-                            registry
-                                .find_by_detection(&mut reader)?
-                                .ok_or_else(|| {
-                                    XmpError::NotSupported(
-                                        "Unsupported file format for writing".to_string(),
-                                    )
-                                })?
-                                .clone()
-                        };
-
-                        // Read original file content first (before creating new file)
-                        let file_data = self
-                            .file_data
-                            .as_ref()
-                            .ok_or_else(|| {
-                                XmpError::BadValue(
-                                    "File data not available for writing".to_string(),
-                                )
-                            })?
-                            .clone();
-                        let mut reader = Cursor::new(&file_data);
-
-                        // Write to same file (or create new one)
-                        let mut writer = BufWriter::new(File::create(path)?);
-
-                        // Write XMP
-                        handler.write_xmp(&mut reader, &mut writer, meta)?;
-                        writer.flush()?;
+            if self.options.for_update && self.is_dirty() {
+                if let Some(path) = self.file_path.clone() {
+                    match self.sidecar_path.clone() {
+                        Some(sidecar) if self.xmp_removed => {
+                            match self.write_to_bytes() {
+                                Ok(data) => std::fs::write(path, data)?,
+                                // The sidecar holds the only packet
+                                Err(XmpError::NotSupported(_)) => {}
+                                Err(e) => return Err(e),
+                            }
+                            if sidecar.exists() {
+                                std::fs::remove_file(sidecar)?;
+                            }
+                        }
+                        Some(sidecar) if self.from_sidecar => self.save_sidecar(sidecar)?,
+                        Some(sidecar) => {
+                            // Write to memory first, so a file that can't be
+                            // written is left untouched
+                            match self.write_to_bytes() {
+                                Ok(data) => std::fs::write(path, data)?,
+                                Err(XmpError::NotSupported(_)) => self.save_sidecar(sidecar)?,
+                                Err(e) => return Err(e),
+                            }
+                        }
+                        None => {
+                            self.save_with(path, SaveOptions::default())?;
+                        }
                     }
                 }
             }
@@ -728,9 +1651,207 @@ impl XmpFile {
     /// ```
     #[cfg(not(target_arch = "wasm32"))]
     pub fn save<P: AsRef<std::path::Path>>(&self, path: P) -> XmpResult<()> {
-        use std::fs::File;
+        self.save_with(path, SaveOptions::default()).map(|_| ())
+    }
+
+    /// Write XMP metadata to a file path with options (native platforms only)
+    ///
+    /// With [`SaveOptions::prefer_in_place`], saving to the file that was
+    /// opened only overwrites the bytes of the existing XMP packet, which
+    /// avoids copying the rest of the file. Saving to another path writes a
+    /// copy of the original data with the packet replaced. With
+    /// [`SaveOptions::atomic`], the target is replaced by a temporary file
+    /// instead of being overwritten, so that a crash can't leave it partly
+    /// written.
+    ///
+    /// Returns how the metadata was written.
+    ///
+    /// # Platform Support
+    ///
+    /// - Native platforms (iOS, Android, macOS, Windows)
+    /// - Wasm: Not supported (use `write_to_writer_with()` instead)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_with<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        options: SaveOptions,
+    ) -> XmpResult<SaveOutcome> {
+        use std::fs::{self, OpenOptions};
+        let path = path.as_ref();
+
+        let is_source = match (&self.file_path, fs::canonicalize(path)) {
+            (Some(source), Ok(target)) => fs::canonicalize(source).is_ok_and(|s| s == target),
+            _ => false,
+        };
+        if is_source && !self.is_dirty() && !options.strip_all_metadata {
+            return Ok(SaveOutcome::Unchanged);
+        }
+        self.update_provenance(&options)?;
+
+        let original = fs::metadata(path).ok();
+        let outcome = self.save_to_path(path, options, is_source)?;
+        if is_source {
+            self.clear_changes();
+        }
+        if let Some(modified) = original
+            .filter(|_| options.keep_modification_time)
+            .and_then(|metadata| metadata.modified().ok())
+        {
+            let times = fs::FileTimes::new().set_modified(modified);
+            OpenOptions::new()
+                .write(true)
+                .open(path)?
+                .set_times(times)?;
+        }
+        Ok(outcome)
+    }
+
+    /// Write the file to `path`, which is the source file if `is_source`
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_to_path(
+        &self,
+        path: &std::path::Path,
+        options: SaveOptions,
+        is_source: bool,
+    ) -> XmpResult<SaveOutcome> {
+        use std::fs::{File, OpenOptions};
+        use std::io::SeekFrom;
+
+        if options.allows_in_place() && is_source && !options.atomic {
+            if let Some((location, packet)) = self.in_place_packet()? {
+                let mut file = OpenOptions::new().write(true).open(path)?;
+                file.seek(SeekFrom::Start(location.start as u64))?;
+                file.write_all(&packet)?;
+                file.flush()?;
+                return Ok(SaveOutcome::InPlace);
+            }
+        }
+
+        // A streamed or mapped source is read while writing, so it can't be
+        // truncated first: write a temporary file next to it and replace it
+        if options.atomic || (is_source && !matches!(self.file_data, Some(FileData::Buffered(_)))) {
+            return self.save_by_rename(path, options);
+        }
+
         let file = File::create(path)?;
-        self.write_to_writer(file)
+        self.write_to(file, options)
+    }
+
+    /// Write a temporary file next to `path` and rename it over `path`
+    /// (or over the target of `path`, if it is a symbolic link)
+    ///
+    /// The temporary file is flushed to disk first, and gets the
+    /// permissions (and on Unix, when allowed, the owner) of the file it
+    /// replaces.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_by_rename(
+        &self,
+        path: &std::path::Path,
+        options: SaveOptions,
+    ) -> XmpResult<SaveOutcome> {
+        use std::fs::{self, File};
+
+        // Replace the target of a symbolic link, not the link
+        let path = &io::resolve_path(path);
+        let temp_path = io::temp_path_for(path);
+        let original = fs::metadata(path).ok();
+        let write = || -> XmpResult<SaveOutcome> {
+            let mut file = File::create(&temp_path)?;
+            let outcome = self.write_to(&mut file, options)?;
+            io::replace_with_temp(&file, &temp_path, path, original.as_ref())?;
+            Ok(outcome)
+        };
+        write().inspect_err(|_| {
+            let _ = fs::remove_file(&temp_path);
+        })
+    }
+
+    /// Save a copy of the file with the updated XMP to another path (native platforms only)
+    ///
+    /// The original data is copied to `path` with the new packet injected,
+    /// reading the original once. Afterwards the `XmpFile` refers to the
+    /// copy: later saves and [`XmpFile::try_close`] write to the copy, so
+    /// the original file is never modified.
+    ///
+    /// Returns `XmpError::BadParam` if `path` is the opened file.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use xmpkit::{ReadOptions, XmpFile};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut file = XmpFile::new();
+    /// file.open_with("original.jpg", ReadOptions::default().for_update())?;
+    /// // ... modify metadata ...
+    /// file.save_as("export/photo.jpg")?;
+    /// file.try_close()?; // Writes to export/photo.jpg, if anything changed since
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_as<P: AsRef<std::path::Path>>(&mut self, path: P) -> XmpResult<SaveOutcome> {
+        self.save_as_with(path, SaveOptions::default())
+    }
+
+    /// Save a copy of the file to another path with options (native platforms only)
+    ///
+    /// See [`XmpFile::save_as`] and [`SaveOptions`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_as_with<P: AsRef<std::path::Path>>(
+        &mut self,
+        path: P,
+        options: SaveOptions,
+    ) -> XmpResult<SaveOutcome> {
+        let path = path.as_ref();
+        if let (Some(source), Ok(target)) = (&self.file_path, std::fs::canonicalize(path)) {
+            if std::fs::canonicalize(source).is_ok_and(|source| source == target) {
+                return Err(XmpError::BadParam(format!(
+                    "Cannot save a copy over the opened file {}",
+                    path.display()
+                )));
+            }
+        }
+        let outcome = self.save_with(path, options)?;
+
+        // The copy becomes the file: its layout may differ from the
+        // original's, so the original data is not used anymore
+        self.file_path = Some(path.to_path_buf());
+        self.file_data = None;
+        self.raw_packet = OnceLock::new();
+        self.sidecar_path = None;
+        self.from_sidecar = false;
+        self.clear_changes();
+        Ok(outcome)
+    }
+
+    /// Write XMP metadata to a standalone XMP sidecar file (native platforms only)
+    ///
+    /// The sidecar file is created or replaced; the original file is not
+    /// modified. Use [`XmpFile::sidecar_path_for`] to get the conventional
+    /// sidecar path of a file. This is the only way to save edits for
+    /// read-only formats such as proprietary camera RAW files.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use xmpkit::XmpFile;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut file = XmpFile::new();
+    /// file.open("photo.nef")?;
+    /// // ... modify metadata ...
+    /// file.save_sidecar(XmpFile::sidecar_path_for("photo.nef"))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_sidecar<P: AsRef<std::path::Path>>(&self, path: P) -> XmpResult<()> {
+        let meta = self.meta.as_ref().ok_or_else(|| {
+            XmpError::BadValue("No XMP metadata available for writing".to_string())
+        })?;
+        std::fs::write(path, SidecarHandler::serialize(meta)?)?;
+        Ok(())
     }
 
     /// Write XMP metadata to bytes (all platforms, including Wasm)
@@ -775,64 +1896,1369 @@ impl XmpFile {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn write_to_writer<W: Write + Seek>(&self, mut writer: W) -> XmpResult<()> {
+    pub fn write_to_writer<W: Write + Seek>(&self, writer: W) -> XmpResult<()> {
+        self.write_to(writer, SaveOptions::default()).map(|_| ())
+    }
+
+    /// Rewrite the file with its format handler, exporting native metadata
+    /// as requested by the options
+    fn rewrite<W: Write + Seek>(&self, mut writer: W, options: &SaveOptions) -> XmpResult<()> {
+        if self.xmp_removed || options.strip_all_metadata {
+            return self.remove_metadata(writer, options.strip_all_metadata);
+        }
+
         // Get XMP metadata
         let meta = self.meta.as_ref().ok_or_else(|| {
             XmpError::BadValue("No XMP metadata available for writing".to_string())
         })?;
 
         // Get original file data
-        let file_data = self.file_data.as_ref().ok_or_else(|| {
-            XmpError::BadValue("Original file data not available for writing".to_string())
-        })?;
+        let mut reader = self.open_source()?;
 
         // Detect handler from file data
         let registry = default_registry();
-        let mut reader = Cursor::new(file_data);
-        let handler = registry.find_by_detection(&mut reader)?.ok_or_else(|| {
-            XmpError::NotSupported("Unsupported file format for writing".to_string())
-        })?;
+        let handler = self
+            .options
+            .find_handler(&registry, &mut reader)?
+            .ok_or_else(|| {
+                XmpError::NotSupported("Unsupported file format for writing".to_string())
+            })?;
 
         // Reset reader position
-        reader.set_position(0);
+        reader.seek(std::io::SeekFrom::Start(0))?;
 
-        // Write XMP using handler
-        handler.write_xmp(&mut reader, &mut writer, meta)?;
+        // Write native metadata first, then XMP using handler
+        let mut exported = Cursor::new(Vec::new());
+        if options.exports_legacy()
+            && handler.export_legacy(&mut reader, &mut exported, meta, options)?
+        {
+            exported.seek(std::io::SeekFrom::Start(0))?;
+            handler.write_xmp(&mut exported, &mut writer, meta)?;
+        } else {
+            reader.seek(std::io::SeekFrom::Start(0))?;
+            handler.write_xmp(&mut reader, &mut writer, meta)?;
+        }
         writer.flush()?;
 
         Ok(())
     }
-}
 
-impl Default for XmpFile {
-    fn default() -> Self {
-        Self::new()
+    /// Rewrite the file without its XMP packet, and with `all`, without its
+    /// native metadata
+    fn remove_metadata<W: Write + Seek>(&self, mut writer: W, all: bool) -> XmpResult<()> {
+        let mut reader = self.open_source()?;
+        let registry = default_registry();
+        let handler = self
+            .options
+            .find_handler(&registry, &mut reader)?
+            .ok_or_else(|| {
+                XmpError::NotSupported("Unsupported file format for writing".to_string())
+            })?;
+        reader.seek(std::io::SeekFrom::Start(0))?;
+        handler.remove_metadata(&mut reader, &mut writer, all)?;
+        writer.flush()?;
+        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Write XMP metadata to a writer with options (all platforms, including Wasm)
+    ///
+    /// With [`SaveOptions::prefer_in_place`], the original data is written
+    /// with only the XMP packet replaced, if the new packet fits.
+    ///
+    /// Returns how the metadata was written.
+    pub fn write_to_writer_with<W: Write + Seek>(
+        &self,
+        writer: W,
+        options: SaveOptions,
+    ) -> XmpResult<SaveOutcome> {
+        self.update_provenance(&options)?;
+        self.write_to(writer, options)
+    }
 
-    #[test]
-    fn test_new() {
-        let file = XmpFile::new();
-        assert!(file.get_xmp().is_none());
+    /// Write XMP metadata to an [`XmpIo`] (all platforms, including Wasm)
+    ///
+    /// With [`SaveOptions::prefer_in_place`], only the bytes of the XMP
+    /// packet are overwritten when the new packet fits, so `io` must hold
+    /// the data the file was opened from. Otherwise, the file is written to
+    /// a temporary derived from `io`, which then replaces its content; the
+    /// temporary is deleted if writing fails, leaving `io` unchanged.
+    ///
+    /// Returns how the metadata was written.
+    pub fn save_io(&self, io: &mut dyn XmpIo, options: SaveOptions) -> XmpResult<SaveOutcome> {
+        use std::io::SeekFrom;
+
+        self.update_provenance(&options)?;
+        if options.allows_in_place() && !options.atomic {
+            if let Some((location, packet)) = self.in_place_packet()? {
+                io.seek(SeekFrom::Start(location.start as u64))?;
+                io.write_all(&packet)?;
+                io.flush()?;
+                return Ok(SaveOutcome::InPlace);
+            }
+        }
+
+        let written = io
+            .derive_temp()
+            .and_then(|temp| self.write_to(temp, options));
+        match written {
+            Ok(outcome) => {
+                io.absorb_temp()?;
+                io.seek(SeekFrom::Start(0))?;
+                Ok(outcome)
+            }
+            Err(e) => {
+                let _ = io.delete_temp();
+                Err(e)
+            }
+        }
     }
 
-    #[test]
-    fn test_from_bytes_empty() {
-        let mut file = XmpFile::new();
-        // Empty data should fail format detection
-        let result = file.from_bytes(&[]);
-        assert!(result.is_err());
+    /// Update the media management properties as requested by the options
+    ///
+    /// Clones of the metadata share its properties, so this updates the
+    /// metadata of the file.
+    fn update_provenance(&self, options: &SaveOptions) -> XmpResult<()> {
+        let Some(mut meta) = self.meta.clone().filter(|_| !options.strip_all_metadata) else {
+            return Ok(());
+        };
+        let now = current_datetime().ok().map(|mut now| {
+            if let Some(offset) = options.timezone {
+                now.convert_to_local(offset);
+            }
+            now
+        });
+        if options.derive_document {
+            meta.derive_document()?;
+        }
+        if options.record_history {
+            let software = match meta.get_property(ns::XMP, "CreatorTool") {
+                Some(XmpValue::String(s)) => s,
+                _ => String::new(),
+            };
+            meta.record_save(&software, now.as_ref())?;
+        }
+        if let Some(now) = now.filter(|_| options.update_metadata_date) {
+            meta.set_date_time(ns::XMP, "MetadataDate", &now)?;
+            meta.set_date_time(ns::XMP, "ModifyDate", &now)?;
+        }
+        Ok(())
     }
 
-    #[test]
-    fn test_put_and_get_xmp() {
-        let mut file = XmpFile::new();
-        let meta = XmpMeta::new();
-        file.put_xmp(meta);
-        assert!(file.get_xmp().is_some());
+    /// Write XMP metadata to a writer, reporting the progress to the
+    /// progress callback
+    fn write_to<W: Write + Seek>(&self, writer: W, options: SaveOptions) -> XmpResult<SaveOutcome> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(callback) = &self.progress {
+            let tracker = ProgressTracker::start(callback, self.source_len());
+            let result = self.write_file(tracker.writer(writer), options);
+            return tracker.finish(result);
+        }
+        self.write_file(writer, options)
+    }
+
+    /// Get the size of the original file data, or 0 if it is unknown
+    #[cfg(not(target_arch = "wasm32"))]
+    fn source_len(&self) -> u64 {
+        match (&self.file_data, &self.file_path) {
+            (Some(file_data), _) => file_data.as_slice().len() as u64,
+            (None, Some(path)) => std::fs::metadata(path).map_or(0, |metadata| metadata.len()),
+            (None, None) => 0,
+        }
+    }
+
+    /// Write XMP metadata to a writer, in place if requested and possible
+    fn write_file<W: Write + Seek>(
+        &self,
+        mut writer: W,
+        options: SaveOptions,
+    ) -> XmpResult<SaveOutcome> {
+        if options.allows_in_place() {
+            if let Some((location, packet)) = self.in_place_packet()? {
+                let mut reader = self.open_source()?;
+                std::io::copy(&mut (&mut reader).take(location.start as u64), &mut writer)?;
+                writer.write_all(&packet)?;
+                reader.seek(std::io::SeekFrom::Start(location.end as u64))?;
+                std::io::copy(&mut reader, &mut writer)?;
+                writer.flush()?;
+                return Ok(SaveOutcome::InPlace);
+            }
+        }
+
+        self.rewrite(writer, &options)?;
+        Ok(SaveOutcome::Rewritten)
+    }
+
+    /// Open the original file data for reading
+    ///
+    /// Uses the data kept in memory, or re-opens the file in streaming mode.
+    fn open_source(&self) -> XmpResult<Box<dyn ReadSeek + '_>> {
+        if let Some(file_data) = &self.file_data {
+            return Ok(Box::new(Cursor::new(file_data.as_slice())));
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = &self.file_path {
+            let file = std::fs::File::open(path)?;
+            let capacity = self.options.effective_chunk_size();
+            return Ok(Box::new(std::io::BufReader::with_capacity(capacity, file)));
+        }
+
+        Err(XmpError::BadValue(
+            "Original file data not available for writing".to_string(),
+        ))
+    }
+
+    /// Find the packet used by the format handler in the original data
+    ///
+    /// The first packet found by scanning must be the one that the handler
+    /// reads (and not, e.g., a packet embedded in a thumbnail). Without a
+    /// handler, the scanned packet is used.
+    fn locate_packet(
+        &self,
+        mut reader: &mut dyn ReadSeek,
+        handler: Option<&Handler>,
+    ) -> XmpResult<Option<PacketLocation>> {
+        let chunk_size = self.options.effective_chunk_size();
+        let Some((location, scanned)) = Self::find_xmp_packet_in(reader, chunk_size)? else {
+            return Ok(None);
+        };
+        if let Some(handler) = handler {
+            reader.seek(std::io::SeekFrom::Start(0))?;
+            match handler.read_xmp(&mut reader)? {
+                Some(main) if main.diff(&scanned).is_empty() => {}
+                _ => return Ok(None),
+            }
+        }
+        Ok(Some(location))
+    }
+
+    /// Locate the packet of the opened data and copy its bytes
+    fn read_raw_packet(&self) -> XmpResult<Option<RawPacket>> {
+        let mut reader = self.open_source()?;
+        let registry = default_registry();
+        let handler = if self.options.use_packet_scanning {
+            None
+        } else {
+            self.options.find_handler(&registry, &mut reader)?
+        };
+        let Some(location) = self.locate_packet(&mut *reader, handler)? else {
+            return Ok(None);
+        };
+
+        let mut bytes = vec![0u8; location.end - location.start];
+        reader.seek(std::io::SeekFrom::Start(location.start as u64))?;
+        reader.read_exact(&mut bytes)?;
+        // Whitespace before the trailer, in code units of the encoding
+        let text = location.encoding.decode(&bytes)?;
+        let body = text
+            .rfind("<?xpacket")
+            .map_or(text.as_str(), |end| &text[..end]);
+        let padding = body.len() - body.trim_end().len();
+        let info = PacketInfo {
+            offset: location.start as u64,
+            length: bytes.len(),
+            padding: padding * location.encoding.unit_size(),
+            encoding: location.encoding,
+            writable: location.writable,
+        };
+        Ok(Some(RawPacket { info, bytes }))
+    }
+
+    /// Serialize the metadata to replace the existing packet, if possible
+    ///
+    /// Returns the location of the existing packet and a new packet of
+    /// exactly the same length, or `None` if the file has to be rewritten.
+    fn in_place_packet(&self) -> XmpResult<Option<(PacketLocation, Vec<u8>)>> {
+        let Some(meta) = &self.meta else {
+            return Ok(None);
+        };
+        let mut reader = self.open_source()?;
+
+        let registry = default_registry();
+        let Some(handler) = self.options.find_handler(&registry, &mut reader)? else {
+            return Ok(None);
+        };
+        if !handler.supports_in_place_update() {
+            return Ok(None);
+        }
+        let Some(location) = self
+            .locate_packet(&mut *reader, Some(handler))?
+            .filter(|location| location.writable)
+        else {
+            return Ok(None);
+        };
+
+        let serialize_options = SerializeOptions::default()
+            .use_compact_format()
+            .exact_packet_length(location.end - location.start)
+            .encoding(location.encoding);
+        match meta.serialize_to_bytes(&serialize_options) {
+            Ok(packet) => Ok(Some((location, packet))),
+            // The new packet doesn't fit in the old one
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// Async file API (requires the `tokio` feature)
+///
+/// Format handlers work on in-memory data, so these methods read the whole
+/// input asynchronously, then process it without further I/O. The streaming
+/// read option is ignored.
+#[cfg(feature = "tokio")]
+impl XmpFile {
+    /// Open a file from a path asynchronously (native platforms only)
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use xmpkit::XmpFile;
+    ///
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut file = XmpFile::new();
+    /// file.open_async("image.jpg").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn open_async<P: AsRef<std::path::Path>>(&mut self, path: P) -> XmpResult<()> {
+        self.open_async_with(path, ReadOptions::default()).await
+    }
+
+    /// Open a file from a path asynchronously with options (native platforms only)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn open_async_with<P: AsRef<std::path::Path>>(
+        &mut self,
+        path: P,
+        mut options: ReadOptions,
+    ) -> XmpResult<()> {
+        let path = path.as_ref();
+        Self::check_limited_scanning(path, &options)?;
+
+        let data = tokio::fs::read(path).await?;
+        options.streaming = false;
+        self.from_bytes_with(&data, options)?;
+        self.file_path = Some(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Open a file from an async reader
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use xmpkit::XmpFile;
+    ///
+    /// # async fn run(upload: tokio::fs::File) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut file = XmpFile::new();
+    /// file.from_async_reader(upload).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn from_async_reader<R: tokio::io::AsyncRead + Unpin>(
+        &mut self,
+        reader: R,
+    ) -> XmpResult<()> {
+        self.from_async_reader_with(reader, ReadOptions::default())
+            .await
+    }
+
+    /// Open a file from an async reader with options
+    pub async fn from_async_reader_with<R: tokio::io::AsyncRead + Unpin>(
+        &mut self,
+        mut reader: R,
+        mut options: ReadOptions,
+    ) -> XmpResult<()> {
+        use tokio::io::AsyncReadExt;
+
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).await?;
+        options.streaming = false;
+        self.from_bytes_with(&data, options)
+    }
+
+    /// Save the file to a path asynchronously (native platforms only)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn save_async<P: AsRef<std::path::Path>>(&self, path: P) -> XmpResult<()> {
+        let data = self.write_to_bytes()?;
+        tokio::fs::write(path, data).await?;
+        Ok(())
+    }
+
+    /// Write the file to an async writer
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use xmpkit::XmpFile;
+    ///
+    /// # async fn run(file: &XmpFile) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut output = Vec::new();
+    /// file.write_to_async_writer(&mut output).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn write_to_async_writer<W: tokio::io::AsyncWrite + Unpin>(
+        &self,
+        mut writer: W,
+    ) -> XmpResult<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let data = self.write_to_bytes()?;
+        writer.write_all(&data).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+}
+
+impl Default for XmpFile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+
+    #[test]
+    fn test_new() {
+        let file = XmpFile::new();
+        assert!(file.get_xmp().is_none());
+    }
+
+    #[test]
+    fn test_from_bytes_empty() {
+        let mut file = XmpFile::new();
+        // Empty data should fail format detection
+        let result = file.from_bytes(&[]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_scan_for_xmp_packet() {
+        let mut meta = XmpMeta::new();
+        meta.set_property(
+            ns::XMP,
+            "CreatorTool",
+            crate::types::value::XmpValue::String("Scanner".into()),
+        )
+        .unwrap();
+        let mut data = b"binary prefix <?xpacket".to_vec();
+        data.extend_from_slice(meta.serialize_packet().unwrap().as_bytes());
+        data.extend_from_slice(b"binary suffix");
+
+        let scanned = XmpFile::scan_for_xmp_packet(&data).unwrap().unwrap();
+        assert!(scanned.has_property(ns::XMP, "CreatorTool"));
+        assert!(XmpFile::scan_for_xmp_packet(b"no packet")
+            .unwrap()
+            .is_none());
+    }
+
+    /// Build a JPEG whose APP1 XMP packet has `padding` bytes of padding
+    #[cfg(feature = "jpeg")]
+    fn jpeg_with_packet(meta: &XmpMeta, padding: usize) -> Vec<u8> {
+        let options = SerializeOptions::default()
+            .use_compact_format()
+            .padding(padding);
+        let packet = meta.serialize_with_options(&options).unwrap();
+        let signature = b"http://ns.adobe.com/xap/1.0/\0";
+        let len = (2 + signature.len() + packet.len()) as u16;
+
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        data.extend_from_slice(&len.to_be_bytes());
+        data.extend_from_slice(signature);
+        data.extend_from_slice(packet.as_bytes());
+        data.extend_from_slice(&[0xFF, 0xD9]);
+        data
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_packet_info() {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "CreatorTool", XmpValue::String("MyApp".into()))
+            .unwrap();
+        let data = jpeg_with_packet(&meta, 100);
+
+        let mut file = XmpFile::new();
+        file.from_bytes(&data).unwrap();
+        let info = file.packet_info().unwrap();
+        assert_eq!(info.offset, 35);
+        assert_eq!(info.length, data.len() - 37);
+        assert!(info.padding >= 100);
+        assert_eq!(info.encoding, PacketEncoding::Utf8);
+        assert!(info.writable);
+        let raw = file.raw_xmp().unwrap();
+        assert_eq!(raw, &data[35..data.len() - 2]);
+        assert!(raw.starts_with(b"<?xpacket begin"));
+
+        file.from_bytes(&[0xFF, 0xD8, 0xFF, 0xD9]).unwrap();
+        assert_eq!(file.packet_info(), None);
+        assert_eq!(file.raw_xmp(), None);
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_progress_callback() {
+        use crate::files::progress::ProgressCallback;
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        let data = jpeg_with_packet(&XmpMeta::new(), 100);
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let recorded = reports.clone();
+        let callback = ProgressCallback::new(move |progress| {
+            recorded
+                .lock()
+                .unwrap()
+                .push((progress.processed, progress.total));
+            true
+        })
+        .interval(Duration::ZERO)
+        .send_start_stop();
+
+        let mut file = XmpFile::new();
+        file.set_progress_callback(Some(callback));
+        file.from_bytes(&data).unwrap();
+        let total = data.len() as u64;
+        {
+            let reports = reports.lock().unwrap();
+            assert_eq!(reports.first(), Some(&(0, total)));
+            assert_eq!(reports.last(), Some(&(total, total)));
+        }
+        reports.lock().unwrap().clear();
+        file.write_to_bytes().unwrap();
+        assert!(reports.lock().unwrap().len() > 2);
+
+        let cancel =
+            ProgressCallback::new(|progress| progress.processed == 0).interval(Duration::ZERO);
+        file.set_progress_callback(Some(cancel));
+        assert!(matches!(file.write_to_bytes(), Err(XmpError::Cancelled)));
+        assert!(matches!(file.from_bytes(&data), Err(XmpError::Cancelled)));
+        file.set_progress_callback(None);
+        file.from_bytes(&data).unwrap();
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_save_records_history() {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "CreatorTool", XmpValue::String("MyApp".into()))
+            .unwrap();
+        meta.init_document_ids().unwrap();
+        let (document_id, instance_id) = (meta.document_id(), meta.instance_id());
+        let data = jpeg_with_packet(&meta, 100);
+
+        let mut file = XmpFile::new();
+        file.from_bytes(&data).unwrap();
+        let mut output = Vec::new();
+        file.write_to_writer_with(
+            Cursor::new(&mut output),
+            SaveOptions::default().record_history().derive_document(),
+        )
+        .unwrap();
+
+        let mut saved = XmpFile::new();
+        saved.from_bytes(&output).unwrap();
+        let saved = saved.get_xmp().unwrap();
+        assert_eq!(saved.original_document_id(), document_id);
+        assert_ne!(saved.document_id(), document_id);
+        assert_eq!(
+            saved.get_struct_field(ns::XMP_MM, "DerivedFrom", ns::ST_REF, "instanceID"),
+            instance_id.map(XmpValue::String)
+        );
+        let history = saved.history();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].software_agent.as_deref(), Some("MyApp"));
+        assert_eq!(history[0].instance_id, saved.instance_id());
+        assert!(history[0].when.is_some());
+        // The metadata of the file is updated too
+        assert_eq!(file.get_xmp().unwrap().instance_id(), saved.instance_id());
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_save_updates_metadata_date() {
+        let data = jpeg_with_packet(&XmpMeta::new(), 100);
+        let mut file = XmpFile::new();
+        file.from_bytes(&data).unwrap();
+        let mut output = Vec::new();
+        file.write_to_writer_with(
+            Cursor::new(&mut output),
+            SaveOptions::default()
+                .update_metadata_date()
+                .record_history()
+                .timezone(120),
+        )
+        .unwrap();
+
+        let mut saved = XmpFile::new();
+        saved.from_bytes(&output).unwrap();
+        let saved = saved.get_xmp().unwrap();
+        let metadata_date = saved.get_date_time(ns::XMP, "MetadataDate").unwrap();
+        assert_eq!(metadata_date.timezone_offset(), Some(120));
+        assert_eq!(
+            saved.get_date_time(ns::XMP, "ModifyDate"),
+            Some(metadata_date.clone())
+        );
+        assert_eq!(saved.history()[0].when, Some(metadata_date));
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_write_in_place() {
+        use crate::types::value::XmpValue;
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "CreatorTool", XmpValue::String("Old".into()))
+            .unwrap();
+        let data = jpeg_with_packet(&meta, 500);
+
+        let mut file = XmpFile::new();
+        file.from_bytes(&data).unwrap();
+        let mut meta = file.get_xmp().cloned().unwrap();
+        meta.set_property(ns::XMP, "CreatorTool", XmpValue::String("New tool".into()))
+            .unwrap();
+        file.put_xmp(meta.clone());
+
+        let mut output = Vec::new();
+        let outcome = file
+            .write_to_writer_with(
+                Cursor::new(&mut output),
+                SaveOptions::default().prefer_in_place(),
+            )
+            .unwrap();
+        assert_eq!(outcome, SaveOutcome::InPlace);
+        assert_eq!(output.len(), data.len());
+
+        let mut reread = XmpFile::new();
+        reread.from_bytes(&output).unwrap();
+        assert_eq!(
+            reread
+                .get_xmp()
+                .unwrap()
+                .get_property(ns::XMP, "CreatorTool"),
+            Some(XmpValue::String("New tool".to_string()))
+        );
+
+        // Without padding the new packet doesn't fit
+        let data = jpeg_with_packet(&meta, 0);
+        file.from_bytes(&data).unwrap();
+        meta.set_property(
+            ns::XMP,
+            "CreatorTool",
+            XmpValue::String("Longer tool".into()),
+        )
+        .unwrap();
+        file.put_xmp(meta);
+        let outcome = file
+            .write_to_writer_with(
+                Cursor::new(&mut Vec::new()),
+                SaveOptions::default().prefer_in_place(),
+            )
+            .unwrap();
+        assert_eq!(outcome, SaveOutcome::Rewritten);
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_import_exif() {
+        use crate::types::value::XmpValue;
+
+        // JPEG with an EXIF APP1 segment (Make and Orientation) and no XMP
+        let mut tiff = b"II*\0\x08\0\0\0\x02\0".to_vec();
+        tiff.extend_from_slice(&[0x0F, 0x01, 2, 0, 6, 0, 0, 0, 38, 0, 0, 0]);
+        tiff.extend_from_slice(&[0x12, 0x01, 3, 0, 1, 0, 0, 0, 6, 0, 0, 0]);
+        tiff.extend_from_slice(&[0; 4]);
+        tiff.extend_from_slice(b"Nikon\0");
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        data.extend_from_slice(&((2 + 6 + tiff.len()) as u16).to_be_bytes());
+        data.extend_from_slice(b"Exif\0\0");
+        data.extend(tiff);
+        data.extend_from_slice(&[0xFF, 0xD9]);
+
+        let mut file = XmpFile::new();
+        file.from_bytes(&data).unwrap();
+        let meta = file.get_xmp().unwrap();
+        assert_eq!(
+            meta.get_property(ns::TIFF, "Make"),
+            Some(XmpValue::String("Nikon".to_string()))
+        );
+        assert_eq!(meta.get_property_i64(ns::TIFF, "Orientation"), Some(6));
+
+        file.from_bytes_with(&data, ReadOptions::default().only_xmp())
+            .unwrap();
+        assert!(file.get_xmp().is_none());
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_reconcile_exif() {
+        use crate::files::formats::exif::{ExifData, ExifIfd};
+        use crate::files::formats::jpeg::JpegHandler;
+        use crate::types::value::XmpValue;
+
+        let mut file = XmpFile::new();
+        file.from_bytes(&[0xFF, 0xD8, 0xFF, 0xD9]).unwrap();
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::TIFF, "Orientation", XmpValue::Integer(8))
+            .unwrap();
+        meta.set_property(
+            ns::XMP,
+            "ModifyDate",
+            XmpValue::String("2024-05-06T07:08:09+02:00".to_string()),
+        )
+        .unwrap();
+        file.put_xmp(meta);
+
+        // EXIF is only written when requested
+        let mut output = Cursor::new(Vec::new());
+        file.write_to_writer_with(&mut output, SaveOptions::default().prefer_in_place())
+            .unwrap();
+        assert!(JpegHandler::read_exif(Cursor::new(output.into_inner()))
+            .unwrap()
+            .is_none());
+
+        let mut output = Cursor::new(Vec::new());
+        let outcome = file
+            .write_to_writer_with(
+                &mut output,
+                SaveOptions::default().prefer_in_place().reconcile_exif(),
+            )
+            .unwrap();
+        assert_eq!(outcome, SaveOutcome::Rewritten);
+        let data = output.into_inner();
+        let exif: ExifData = JpegHandler::read_exif(Cursor::new(&data)).unwrap().unwrap();
+        assert_eq!(
+            exif.get(ExifIfd::Primary, 0x0112).and_then(|v| v.as_u32()),
+            Some(8)
+        );
+        assert_eq!(
+            exif.get(ExifIfd::Primary, 0x0132).and_then(|v| v.as_str()),
+            Some("2024:05:06 07:08:09")
+        );
+        assert_eq!(
+            exif.get(ExifIfd::Exif, 0x9010).and_then(|v| v.as_str()),
+            Some("+02:00")
+        );
+
+        // The XMP is still written
+        let mut reread = XmpFile::new();
+        reread
+            .from_bytes_with(&data, ReadOptions::default().only_xmp())
+            .unwrap();
+        assert_eq!(
+            reread
+                .get_xmp()
+                .unwrap()
+                .get_property_i64(ns::TIFF, "Orientation"),
+            Some(8)
+        );
+    }
+
+    #[test]
+    fn test_find_xmp_packet_in_chunks() {
+        let meta = XmpMeta::new();
+        let mut data = vec![0u8; 100];
+        data.extend_from_slice(meta.serialize_packet().unwrap().as_bytes());
+        data.extend_from_slice(&[0u8; 100]);
+
+        // Small chunks split the markers across reads
+        for chunk_size in [1, 7, 16, 4096] {
+            let (location, _) = XmpFile::find_xmp_packet_in(&mut Cursor::new(&data), chunk_size)
+                .unwrap()
+                .unwrap();
+            assert_eq!(location.start, 100);
+            assert_eq!(location.end, data.len() - 100);
+        }
+    }
+
+    #[test]
+    fn test_packet_scanner_stops_at_trailer() {
+        let meta = XmpMeta::new();
+        let packet = meta.serialize_packet().unwrap();
+        let mut scanner = PacketScanner::new();
+
+        assert!(scanner.push(&[0u8; 50]).is_none());
+        let (head, tail) = packet.as_bytes().split_at(packet.len() / 2);
+        assert!(scanner.push(head).is_none());
+        let (location, _) = scanner.push(tail).unwrap();
+        assert_eq!((location.start, location.end), (50, 50 + packet.len()));
+
+        // A packet without its trailer is only tried at the end
+        let mut scanner = PacketScanner::new();
+        assert!(scanner.push(head).is_none());
+        assert!(scanner.finish().is_none());
+    }
+
+    #[test]
+    fn test_find_utf16_and_utf32_packets() {
+        use crate::types::value::XmpValue;
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "CreatorTool", XmpValue::String("Très".into()))
+            .unwrap();
+        for encoding in [PacketEncoding::Utf16Le, PacketEncoding::Utf32Be] {
+            let options = SerializeOptions::default().encoding(encoding);
+            let packet = meta.serialize_to_bytes(&options).unwrap();
+            let mut data = b"binary".to_vec();
+            data.extend_from_slice(&packet);
+            data.extend_from_slice(b"data");
+
+            for chunk_size in [3, 4096] {
+                let (location, found) =
+                    XmpFile::find_xmp_packet_in(&mut Cursor::new(&data), chunk_size)
+                        .unwrap()
+                        .unwrap();
+                assert_eq!((location.start, location.end), (6, 6 + packet.len()));
+                assert_eq!(location.encoding, encoding);
+                assert!(location.writable);
+                assert!(found.diff(&meta).is_empty());
+            }
+
+            // A replacement packet of the same length in the same encoding
+            let replacement = meta
+                .serialize_to_bytes(&options.exact_packet_length(packet.len()))
+                .unwrap();
+            assert_eq!(replacement.len(), packet.len());
+            assert!(XmpMeta::parse_bytes(&replacement)
+                .unwrap()
+                .diff(&meta)
+                .is_empty());
+        }
+    }
+
+    #[cfg(all(feature = "jpeg", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_streaming_open_and_save() {
+        use crate::types::value::XmpValue;
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "CreatorTool", XmpValue::String("Old".into()))
+            .unwrap();
+        let path =
+            std::env::temp_dir().join(format!("xmpkit-streaming-{}.jpg", std::process::id()));
+        std::fs::write(&path, jpeg_with_packet(&meta, 0)).unwrap();
+
+        let mut file = XmpFile::new();
+        file.open_with(&path, ReadOptions::default().streaming().chunk_size(16))
+            .unwrap();
+        assert!(file.file_data.is_none());
+        let mut meta = file.get_xmp().cloned().unwrap();
+        meta.set_property(ns::XMP, "CreatorTool", XmpValue::String("New tool".into()))
+            .unwrap();
+        file.put_xmp(meta);
+
+        // Saving over the source goes through a temporary file
+        file.save(&path).unwrap();
+
+        let mut reread = XmpFile::new();
+        reread.open(&path).unwrap();
+        assert_eq!(
+            reread
+                .get_xmp()
+                .unwrap()
+                .get_property(ns::XMP, "CreatorTool"),
+            Some(XmpValue::String("New tool".to_string()))
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_open_and_save_io() {
+        use crate::files::io::MemoryIo;
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "CreatorTool", XmpValue::String("Old".into()))
+            .unwrap();
+        let data = jpeg_with_packet(&meta, 100);
+        let mut io = MemoryIo::new(data.clone());
+
+        let mut file = XmpFile::new();
+        file.open_io(&mut io, ReadOptions::default()).unwrap();
+        let mut meta = file.get_xmp().cloned().unwrap();
+        meta.set_property(ns::XMP, "CreatorTool", XmpValue::String("New".into()))
+            .unwrap();
+        file.put_xmp(meta);
+        let outcome = file
+            .save_io(&mut io, SaveOptions::default().prefer_in_place())
+            .unwrap();
+        assert_eq!(outcome, SaveOutcome::InPlace);
+        assert_eq!(io.as_bytes().len(), data.len());
+
+        let outcome = file.save_io(&mut io, SaveOptions::default()).unwrap();
+        assert_eq!(outcome, SaveOutcome::Rewritten);
+        let mut reread = XmpFile::new();
+        reread.open_io(&mut io, ReadOptions::default()).unwrap();
+        assert_eq!(
+            reread
+                .get_xmp()
+                .unwrap()
+                .get_property(ns::XMP, "CreatorTool"),
+            Some(XmpValue::String("New".to_string()))
+        );
+    }
+
+    #[cfg(all(feature = "jpeg", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_atomic_save() {
+        use crate::types::value::XmpValue;
+        use std::time::{Duration, SystemTime};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("photo.jpg");
+        std::fs::write(&path, jpeg_with_packet(&XmpMeta::new(), 0)).unwrap();
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(modified).unwrap();
+        let mut permissions = file.metadata().unwrap().permissions();
+        permissions.set_readonly(true);
+        drop(file);
+        std::fs::set_permissions(&path, permissions.clone()).unwrap();
+
+        let mut file = XmpFile::new();
+        file.open(&path).unwrap();
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "Label", XmpValue::String("Atomic".into()))
+            .unwrap();
+        file.put_xmp(meta);
+        let options = SaveOptions::default().atomic().keep_modification_time();
+        assert_eq!(
+            file.save_with(&path, options).unwrap(),
+            SaveOutcome::Rewritten
+        );
+
+        let metadata = std::fs::metadata(&path).unwrap();
+        assert_eq!(metadata.modified().unwrap(), modified);
+        assert_eq!(metadata.permissions(), permissions);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+        let mut reread = XmpFile::new();
+        reread.open(&path).unwrap();
+        assert_eq!(
+            reread.get_xmp().unwrap().get_property(ns::XMP, "Label"),
+            Some(XmpValue::String("Atomic".to_string()))
+        );
+    }
+
+    #[cfg(all(feature = "jpeg", unix))]
+    #[test]
+    fn test_save_through_symlink() {
+        use crate::types::value::XmpValue;
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("library")).unwrap();
+        let target = dir.path().join("library").join("photo.jpg");
+        let link = dir.path().join("photo.jpg");
+        std::fs::write(&target, jpeg_with_packet(&XmpMeta::new(), 0)).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        for (label, options) in [
+            ("Atomic", SaveOptions::default().atomic()),
+            // Mapped sources are also saved by renaming a temporary file
+            ("Default", SaveOptions::default()),
+        ] {
+            let mut file = XmpFile::new();
+            file.open_with(&link, ReadOptions::default().for_update())
+                .unwrap();
+            let mut meta = XmpMeta::new();
+            meta.set_property(ns::XMP, "Label", XmpValue::String(label.into()))
+                .unwrap();
+            file.put_xmp(meta);
+            file.save_with(&link, options).unwrap();
+
+            // The link still points to the target, which has the new packet
+            assert!(std::fs::symlink_metadata(&link)
+                .unwrap()
+                .file_type()
+                .is_symlink());
+            let mut reread = XmpFile::new();
+            reread.open(&target).unwrap();
+            assert_eq!(
+                reread.get_xmp().unwrap().get_property(ns::XMP, "Label"),
+                Some(XmpValue::String(label.to_string()))
+            );
+        }
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+        assert_eq!(
+            std::fs::read_dir(dir.path().join("library"))
+                .unwrap()
+                .count(),
+            1
+        );
+    }
+
+    #[cfg(all(feature = "jpeg", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_save_as() {
+        use crate::types::value::XmpValue;
+
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("original.jpg");
+        let copy = dir.path().join("copy.jpg");
+        let original = jpeg_with_packet(&XmpMeta::new(), 100);
+        std::fs::write(&source, &original).unwrap();
+
+        let mut file = XmpFile::new();
+        file.open_with(&source, ReadOptions::default().for_update())
+            .unwrap();
+        assert!(file.save_as(&source).is_err());
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "Label", XmpValue::String("Copy".into()))
+            .unwrap();
+        file.put_xmp(meta.clone());
+        file.save_as(&copy).unwrap();
+        meta.set_property(ns::XMP, "Rating", XmpValue::Integer(3))
+            .unwrap();
+        file.put_xmp(meta);
+        file.try_close().unwrap();
+
+        assert_eq!(std::fs::read(&source).unwrap(), original);
+        let mut reread = XmpFile::new();
+        reread.open(&copy).unwrap();
+        let meta = reread.get_xmp().unwrap();
+        assert_eq!(
+            meta.get_property(ns::XMP, "Label"),
+            Some(XmpValue::String("Copy".to_string()))
+        );
+        assert_eq!(
+            meta.get_property(ns::XMP, "Rating"),
+            Some(XmpValue::String("3".to_string()))
+        );
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_save_unchanged() {
+        use crate::types::value::XmpValue;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("photo.jpg");
+        let original = jpeg_with_packet(&XmpMeta::new(), 100);
+        std::fs::write(&path, &original).unwrap();
+
+        let mut file = XmpFile::new();
+        file.open_with(&path, ReadOptions::default().for_update())
+            .unwrap();
+        assert!(!file.is_dirty());
+        let options = SaveOptions::default().update_metadata_date();
+        assert_eq!(
+            file.save_with(&path, options).unwrap(),
+            SaveOutcome::Unchanged
+        );
+        assert_eq!(std::fs::read(&path).unwrap(), original);
+
+        // A copy of the metadata shares its changes
+        let mut meta = file.get_xmp().cloned().unwrap();
+        file.put_xmp(meta.clone());
+        assert!(!file.is_dirty());
+        meta.set_property(ns::XMP, "Label", XmpValue::String("Red".into()))
+            .unwrap();
+        assert!(file.is_dirty());
+        assert_ne!(
+            file.save_with(&path, options).unwrap(),
+            SaveOutcome::Unchanged
+        );
+        assert!(!file.is_dirty());
+        assert_eq!(
+            file.save_with(&path, options).unwrap(),
+            SaveOutcome::Unchanged
+        );
+
+        // Other metadata is a change, even if never modified
+        file.put_xmp(XmpMeta::new());
+        assert!(file.is_dirty());
+        file.try_close().unwrap();
+        assert!(XmpFile::scan_for_xmp_packet(&std::fs::read(&path).unwrap())
+            .unwrap()
+            .is_some_and(|meta| !meta.has_property(ns::XMP, "Label")));
+    }
+
+    #[cfg(all(feature = "jpeg", feature = "png"))]
+    #[test]
+    fn test_detect_format() {
+        use crate::types::value::XmpValue;
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "Label", XmpValue::String("Red".into()))
+            .unwrap();
+        let jpeg = jpeg_with_packet(&meta, 0);
+        assert_eq!(XmpFile::detect_format(&jpeg), FileFormat::Jpeg);
+        assert_eq!(XmpFile::detect_format(&[]), FileFormat::Unknown);
+
+        // A wrong format is verified and replaced by the detected one
+        let mut file = XmpFile::new();
+        file.from_bytes_with(&jpeg, ReadOptions::default().format(FileFormat::Png))
+            .unwrap();
+        assert!(file.get_xmp().is_some());
+        let strict = ReadOptions::default().format(FileFormat::Png).strict();
+        assert!(file.from_bytes_with(&jpeg, strict).is_err());
+        let forced = ReadOptions::default()
+            .format(FileFormat::Png)
+            .force_given_handler();
+        assert!(file.from_bytes_with(&jpeg, forced).is_err());
+        file.from_bytes_with(&jpeg, ReadOptions::default().format(FileFormat::Jpeg))
+            .unwrap();
+        assert!(file.get_xmp().is_some());
+    }
+
+    #[cfg(all(feature = "jpeg", feature = "png", feature = "eps", feature = "flac"))]
+    #[test]
+    fn test_format_info() {
+        let jpeg = XmpFile::format_info(FileFormat::Jpeg);
+        assert!(jpeg.can_read && jpeg.can_write && jpeg.can_inject);
+        assert!(jpeg.supports_in_place && jpeg.supports_reconciliation);
+        let png = XmpFile::format_info(FileFormat::Png);
+        assert!(png.can_write && !png.supports_in_place);
+        let eps = XmpFile::format_info(FileFormat::Eps);
+        assert!(eps.can_write && !eps.can_inject);
+        assert_eq!(
+            XmpFile::format_info(FileFormat::Flac).max_packet_size,
+            Some(0xFF_FFFF - 4)
+        );
+        assert_eq!(
+            XmpFile::format_info(FileFormat::Unknown),
+            FormatCapabilities::default()
+        );
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_repair_file() {
+        use crate::types::value::XmpValue;
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "Label", XmpValue::String("Kept".into()))
+            .unwrap();
+        // The APP1 segment claims to be longer than the file
+        let mut data = jpeg_with_packet(&meta, 0);
+        data[4..6].copy_from_slice(&u16::MAX.to_be_bytes());
+
+        let mut file = XmpFile::new();
+        assert!(file.from_bytes(&data).is_err());
+        file.from_bytes_with(&data, ReadOptions::default().repair_file())
+            .unwrap();
+        assert_eq!(
+            file.get_xmp().unwrap().get_property(ns::XMP, "Label"),
+            Some(XmpValue::String("Kept".into()))
+        );
+    }
+
+    #[cfg(all(feature = "jpeg", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_remove_xmp() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("image.jpg");
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "Label", "Private".into())
+            .unwrap();
+        std::fs::write(&path, jpeg_with_packet(&meta, 100)).unwrap();
+
+        let mut file = XmpFile::new();
+        file.open_with(&path, ReadOptions::default().for_update())
+            .unwrap();
+        file.remove_xmp();
+        assert!(file.get_xmp().is_none());
+        file.try_close().unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), [0xFF, 0xD8, 0xFF, 0xD9]);
+
+        // Stripping ignores the metadata of the file, even in place
+        std::fs::write(&path, jpeg_with_packet(&meta, 100)).unwrap();
+        let mut file = XmpFile::new();
+        file.open(&path).unwrap();
+        let options = SaveOptions::default()
+            .prefer_in_place()
+            .strip_all_metadata();
+        let outcome = file.save_with(&path, options).unwrap();
+        assert_eq!(outcome, SaveOutcome::Rewritten);
+        assert_eq!(std::fs::read(&path).unwrap(), [0xFF, 0xD8, 0xFF, 0xD9]);
+
+        let mut file = XmpFile::new();
+        file.from_bytes(b"plain text").unwrap();
+        file.remove_xmp();
+        assert!(file.write_to_bytes().is_err());
+    }
+
+    #[cfg(all(feature = "mmap", feature = "jpeg"))]
+    #[test]
+    fn test_mapped_open_and_save() {
+        use crate::types::value::XmpValue;
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "CreatorTool", XmpValue::String("Old".into()))
+            .unwrap();
+        let path = std::env::temp_dir().join(format!("xmpkit-mmap-{}.jpg", std::process::id()));
+        std::fs::write(&path, jpeg_with_packet(&meta, 0)).unwrap();
+
+        let mut file = XmpFile::new();
+        file.open_with(&path, ReadOptions::default().for_update())
+            .unwrap();
+        assert!(matches!(file.file_data, Some(FileData::Mapped(_))));
+        let mut meta = file.get_xmp().cloned().unwrap();
+        meta.set_property(ns::XMP, "CreatorTool", XmpValue::String("New tool".into()))
+            .unwrap();
+        file.put_xmp(meta);
+        file.try_close().unwrap();
+
+        let mut reread = XmpFile::new();
+        reread.open(&path).unwrap();
+        assert_eq!(
+            reread
+                .get_xmp()
+                .unwrap()
+                .get_property(ns::XMP, "CreatorTool"),
+            Some(XmpValue::String("New tool".to_string()))
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        // Empty files are read instead
+        std::fs::write(&path, b"").unwrap();
+        assert!(XmpFile::map_file(&std::fs::File::open(&path).unwrap()).is_none());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_put_and_get_xmp() {
+        let mut file = XmpFile::new();
+        let meta = XmpMeta::new();
+        file.put_xmp(meta);
+        assert!(file.get_xmp().is_some());
+    }
+
+    fn title_meta(title: &str) -> XmpMeta {
+        let mut meta = XmpMeta::new();
+        meta.set_property(
+            ns::DC,
+            "title",
+            crate::types::value::XmpValue::String(title.to_string()),
+        )
+        .unwrap();
+        meta
+    }
+
+    fn title(meta: Option<&XmpMeta>) -> Option<crate::types::value::XmpValue> {
+        meta.and_then(|meta| meta.get_property(ns::DC, "title"))
+    }
+
+    #[test]
+    fn test_sidecar_path_for() {
+        let dir = std::env::temp_dir().join(format!("xmpkit-sidecar-path-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let photo = dir.join("photo.cr2");
+        assert_eq!(XmpFile::sidecar_path_for(&photo), dir.join("photo.xmp"));
+
+        std::fs::write(dir.join("photo.cr2.xmp"), "").unwrap();
+        assert_eq!(XmpFile::sidecar_path_for(&photo), dir.join("photo.cr2.xmp"));
+        std::fs::write(dir.join("photo.xmp"), "").unwrap();
+        assert_eq!(XmpFile::sidecar_path_for(&photo), dir.join("photo.xmp"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_open_and_save_sidecar_file() {
+        let path = std::env::temp_dir().join(format!("xmpkit-sidecar-{}.xmp", std::process::id()));
+        let mut file = XmpFile::new();
+        file.put_xmp(title_meta("First"));
+        file.save_sidecar(&path).unwrap();
+
+        let mut file = XmpFile::new();
+        file.open_with(&path, ReadOptions::default().for_update())
+            .unwrap();
+        assert_eq!(
+            title(file.get_xmp()),
+            Some(crate::types::value::XmpValue::String("First".to_string()))
+        );
+        file.put_xmp(title_meta("Second"));
+        file.try_close().unwrap();
+
+        let mut reread = XmpFile::new();
+        reread.open(&path).unwrap();
+        assert_eq!(
+            title(reread.get_xmp()),
+            Some(crate::types::value::XmpValue::String("Second".to_string()))
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_open_with_sidecar() {
+        use crate::types::value::XmpValue;
+
+        let dir = std::env::temp_dir().join(format!("xmpkit-with-sidecar-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let photo = dir.join("photo.jpg");
+        let jpeg = jpeg_with_packet(&title_meta("Embedded"), 100);
+        std::fs::write(&photo, &jpeg).unwrap();
+
+        // Without a sidecar, the embedded metadata is used
+        let mut file = XmpFile::new();
+        file.open_with_sidecar(&photo).unwrap();
+        assert!(file.sidecar_path().is_none());
+        assert_eq!(
+            title(file.get_xmp()),
+            Some(XmpValue::String("Embedded".to_string()))
+        );
+
+        // The sidecar is preferred, and updates go to it
+        let sidecar = dir.join("photo.xmp");
+        std::fs::write(
+            &sidecar,
+            SidecarHandler::serialize(&title_meta("Sidecar")).unwrap(),
+        )
+        .unwrap();
+        let mut file = XmpFile::new();
+        file.open_with_sidecar_with(&photo, ReadOptions::default().for_update())
+            .unwrap();
+        assert_eq!(file.sidecar_path(), Some(sidecar.as_path()));
+        assert_eq!(
+            title(file.get_xmp()),
+            Some(XmpValue::String("Sidecar".to_string()))
+        );
+        file.put_xmp(title_meta("Edited"));
+        file.try_close().unwrap();
+
+        assert_eq!(std::fs::read(&photo).unwrap(), jpeg);
+        let mut reread = XmpFile::new();
+        reread.open(&sidecar).unwrap();
+        assert_eq!(
+            title(reread.get_xmp()),
+            Some(XmpValue::String("Edited".to_string()))
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "tiff")]
+    #[test]
+    fn test_read_only_raw_saved_to_sidecar() {
+        use crate::types::value::XmpValue;
+
+        let dir = std::env::temp_dir().join(format!("xmpkit-raw-sidecar-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let photo = dir.join("photo.orf");
+        // Minimal Olympus ORF file: header + empty IFD0
+        let orf = b"IIRO\x08\0\0\0\0\0\0\0\0\0".to_vec();
+        std::fs::write(&photo, &orf).unwrap();
+
+        let mut file = XmpFile::new();
+        file.open_with_sidecar_with(&photo, ReadOptions::default().for_update())
+            .unwrap();
+        assert!(file.get_xmp().is_none());
+        file.put_xmp(title_meta("Raw edit"));
+        file.try_close().unwrap();
+
+        // The RAW file is untouched and the edits are in the sidecar
+        assert_eq!(std::fs::read(&photo).unwrap(), orf);
+        let mut file = XmpFile::new();
+        file.open_with_sidecar(&photo).unwrap();
+        assert_eq!(file.sidecar_path(), Some(dir.join("photo.xmp").as_path()));
+        assert_eq!(
+            title(file.get_xmp()),
+            Some(XmpValue::String("Raw edit".to_string()))
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
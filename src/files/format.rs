@@ -0,0 +1,139 @@
+//! File formats
+//!
+//! [`FileFormat`] names the formats that xmpkit has handlers for. The format
+//! of a file is detected from its content (see [`XmpFile::detect_format`]),
+//! not from its extension, which may be wrong or missing; the detection can
+//! be overridden with [`ReadOptions::format`].
+//!
+//! [`XmpFile::detect_format`]: crate::files::XmpFile::detect_format
+//! [`ReadOptions::format`]: crate::files::ReadOptions::format
+//!
+//! [`FormatCapabilities`] describes what a handler can do with the files of
+//! its format.
+
+/// What a file handler can do with the files of its format
+///
+/// Returned by [`XmpFile::format_info`], so that applications can tell
+/// up front whether to offer editing the metadata of a file. Some files
+/// of a format may still fail, such as proprietary RAW files, which are
+/// TIFF files that can only be read. The default has no capabilities.
+///
+/// [`XmpFile::format_info`]: crate::files::XmpFile::format_info
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FormatCapabilities {
+    /// The XMP can be read
+    pub can_read: bool,
+    /// The XMP can be written
+    pub can_write: bool,
+    /// XMP can be added to files that have no packet yet
+    pub can_inject: bool,
+    /// The XMP packet can be overwritten in place
+    pub supports_in_place: bool,
+    /// XMP values can be written back to the native metadata (see
+    /// [`SaveOptions`](crate::files::SaveOptions))
+    pub supports_reconciliation: bool,
+    /// Largest packet the format can hold, in bytes, if it is limited below
+    /// the size of a file
+    pub max_packet_size: Option<usize>,
+}
+
+/// File format with an XMP handler
+///
+/// All the formats are listed whatever the enabled features, but only the
+/// formats whose feature is enabled are detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FileFormat {
+    /// AIFF audio
+    Aiff,
+    /// ASF (WMA, WMV) media
+    Asf,
+    /// AVI video
+    Avi,
+    /// AVIF image
+    Avif,
+    /// Encapsulated PostScript
+    Eps,
+    /// FLAC audio
+    Flac,
+    /// GIF image
+    Gif,
+    /// HEIF (HEIC) image
+    Heif,
+    /// InDesign document
+    Indd,
+    /// JPEG 2000 image
+    Jp2,
+    /// JPEG image
+    Jpeg,
+    /// JPEG XL image
+    Jxl,
+    /// Matroska (MKV, WebM) media
+    Mkv,
+    /// QuickTime movie
+    Mov,
+    /// MP3 audio
+    Mp3,
+    /// MPEG-4 media
+    Mp4,
+    /// Ogg media
+    Ogg,
+    /// PDF document
+    Pdf,
+    /// PNG image
+    Png,
+    /// Photoshop document
+    Psd,
+    /// SVG image
+    Svg,
+    /// TIFF image (including TIFF-based camera RAW files)
+    Tiff,
+    /// WAV audio
+    Wav,
+    /// WebP image
+    Webp,
+    /// Standalone XMP (sidecar) file
+    Xmp,
+    /// Format of a registered [`CustomHandler`], by name
+    ///
+    /// [`CustomHandler`]: crate::files::handler::CustomHandler
+    Custom(&'static str),
+    /// Not a supported format
+    Unknown,
+}
+
+impl FileFormat {
+    /// Get the name of the format, as returned by
+    /// [`FileHandler::format_name`](crate::files::handler::FileHandler::format_name)
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileFormat::Aiff => "AIFF",
+            FileFormat::Asf => "ASF",
+            FileFormat::Avi => "AVI",
+            FileFormat::Avif => "AVIF",
+            FileFormat::Eps => "EPS",
+            FileFormat::Flac => "FLAC",
+            FileFormat::Gif => "GIF",
+            FileFormat::Heif => "HEIF",
+            FileFormat::Indd => "InDesign",
+            FileFormat::Jp2 => "JPEG 2000",
+            FileFormat::Jpeg => "JPEG",
+            FileFormat::Jxl => "JPEG XL",
+            FileFormat::Mkv => "Matroska",
+            FileFormat::Mov => "MOV",
+            FileFormat::Mp3 => "MP3",
+            FileFormat::Mp4 => "MP4",
+            FileFormat::Ogg => "Ogg",
+            FileFormat::Pdf => "PDF",
+            FileFormat::Png => "PNG",
+            FileFormat::Psd => "PSD",
+            FileFormat::Svg => "SVG",
+            FileFormat::Tiff => "TIFF",
+            FileFormat::Wav => "WAV",
+            FileFormat::Webp => "WebP",
+            FileFormat::Xmp => "XMP",
+            FileFormat::Custom(name) => name,
+            FileFormat::Unknown => "Unknown",
+        }
+    }
+}
@@ -0,0 +1,375 @@
+//! AIFF file format handler
+//!
+//! This module provides functionality for reading and writing XMP metadata
+//! in AIFF and AIFF-C files. The implementation is pure Rust and
+//! cross-platform compatible.
+//!
+//! AIFF File Structure:
+//! - "FORM" + size (4 bytes, big-endian) + "AIFF" or "AIFC" + chunks
+//! - Chunk: ID (4 bytes) + size (4 bytes, big-endian) + data
+//! - Chunks with an odd size are followed by a padding byte (not counted
+//!   in the chunk size)
+//!
+//! AIFF XMP Storage:
+//! - XMP is stored in an application specific ("APPL") chunk whose data
+//!   starts with the "XMP " application signature, followed by the packet
+//! - Other chunks (including other "APPL" chunks) are copied unchanged,
+//!   without loading the audio data in memory
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::files::handler::FileHandler;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// IFF container signature
+const FORM_SIGNATURE: &[u8; 4] = b"FORM";
+
+/// AIFF form types
+const AIFF_FORM_TYPE: &[u8; 4] = b"AIFF";
+const AIFC_FORM_TYPE: &[u8; 4] = b"AIFC";
+
+/// Application specific chunk ID
+const CHUNK_APPL: &[u8; 4] = b"APPL";
+
+/// Application signature of the XMP "APPL" chunk
+const XMP_SIGNATURE: &[u8; 4] = b"XMP ";
+
+/// AIFF file handler for XMP metadata
+#[derive(Debug, Clone, Copy)]
+pub struct AiffHandler;
+
+impl FileHandler for AiffHandler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        let pos = reader.stream_position()?;
+        let mut header = [0u8; 12];
+        let result = reader.read_exact(&mut header);
+        reader.seek(SeekFrom::Start(pos))?;
+        Ok(result.is_ok() && Self::is_aiff_header(&header))
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn format_name(&self) -> &'static str {
+        "AIFF"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["aif", "aiff", "aifc"]
+    }
+}
+
+/// The location of a chunk in the file
+#[derive(Debug, Clone, Copy)]
+struct AiffChunk {
+    id: [u8; 4],
+    /// Offset of the chunk header
+    offset: u64,
+    /// Size of the chunk data, without padding
+    size: u64,
+}
+
+impl AiffHandler {
+    /// Read XMP metadata from an AIFF file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(XmpMeta))` if XMP metadata is found
+    /// * `Ok(None)` if no XMP metadata is found
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_xmp<R: Read + Seek>(mut reader: R) -> XmpResult<Option<XmpMeta>> {
+        let (_, form_end) = Self::read_header(&mut reader)?;
+        let chunks = Self::read_chunks(&mut reader, form_end)?;
+
+        for chunk in &chunks {
+            if Self::is_xmp_chunk(&mut reader, chunk)? {
+                reader.seek(SeekFrom::Start(chunk.offset + 12))?;
+                let mut data = vec![0u8; (chunk.size - 4) as usize];
+                reader.read_exact(&mut data)?;
+                let xmp_str = String::from_utf8(data)
+                    .map_err(|e| XmpError::ParseError(format!("Invalid UTF-8 in XMP: {}", e)))?;
+                return XmpMeta::parse(xmp_str.trim_end_matches('\0')).map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Write XMP metadata to an AIFF file
+    ///
+    /// The XMP "APPL" chunk is replaced, or appended if the file has none.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    pub fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        let xmp_packet = meta.serialize_packet()?;
+        let mut xmp_data = XMP_SIGNATURE.to_vec();
+        xmp_data.extend_from_slice(xmp_packet.as_bytes());
+
+        let (form_type, form_end) = Self::read_header(&mut reader)?;
+        let chunks = Self::read_chunks(&mut reader, form_end)?;
+
+        let mut output: Vec<OutputChunk> = Vec::with_capacity(chunks.len() + 1);
+        let mut has_xmp = false;
+        for chunk in &chunks {
+            if !Self::is_xmp_chunk(&mut reader, chunk)? {
+                output.push(OutputChunk::Copy(*chunk));
+            } else if !has_xmp {
+                output.push(OutputChunk::Xmp);
+                has_xmp = true;
+            }
+        }
+        if !has_xmp {
+            output.push(OutputChunk::Xmp);
+        }
+
+        let data_size = |chunk: &OutputChunk| match chunk {
+            OutputChunk::Copy(chunk) => chunk.size,
+            OutputChunk::Xmp => xmp_data.len() as u64,
+        };
+        let form_size: u64 = 4 + output
+            .iter()
+            .map(|chunk| {
+                let size = data_size(chunk);
+                8 + size + (size & 1)
+            })
+            .sum::<u64>();
+        let form_size = u32::try_from(form_size)
+            .map_err(|_| XmpError::BadValue("AIFF file too large".to_string()))?;
+
+        writer.write_all(FORM_SIGNATURE)?;
+        writer.write_all(&form_size.to_be_bytes())?;
+        writer.write_all(&form_type)?;
+        for chunk in &output {
+            match chunk {
+                OutputChunk::Copy(chunk) => {
+                    reader.seek(SeekFrom::Start(chunk.offset))?;
+                    let copied =
+                        std::io::copy(&mut reader.by_ref().take(8 + chunk.size), &mut writer)?;
+                    if copied != 8 + chunk.size {
+                        return Err(XmpError::BadValue("Truncated AIFF chunk".to_string()));
+                    }
+                }
+                OutputChunk::Xmp => {
+                    writer.write_all(CHUNK_APPL)?;
+                    writer.write_all(&(xmp_data.len() as u32).to_be_bytes())?;
+                    writer.write_all(&xmp_data)?;
+                }
+            }
+            // The padding byte is written even if the source file omitted it
+            if data_size(chunk) & 1 == 1 {
+                writer.write_all(&[0])?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_aiff_header(header: &[u8; 12]) -> bool {
+        &header[0..4] == FORM_SIGNATURE
+            && (&header[8..12] == AIFF_FORM_TYPE || &header[8..12] == AIFC_FORM_TYPE)
+    }
+
+    /// Read and check the FORM header, returning the form type and the end
+    /// offset of the FORM data
+    fn read_header<R: Read + Seek>(reader: &mut R) -> XmpResult<([u8; 4], u64)> {
+        let mut header = [0u8; 12];
+        reader.rewind()?;
+        reader
+            .read_exact(&mut header)
+            .map_err(|_| XmpError::BadValue("Not a valid AIFF file".to_string()))?;
+        if !Self::is_aiff_header(&header) {
+            return Err(XmpError::BadValue("Not a valid AIFF file".to_string()));
+        }
+
+        // Don't trust the FORM size beyond the end of the file
+        let form_size = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as u64;
+        let file_end = reader.seek(SeekFrom::End(0))?;
+        Ok((
+            [header[8], header[9], header[10], header[11]],
+            (8 + form_size).min(file_end),
+        ))
+    }
+
+    /// Read the chunk headers up to `form_end`
+    fn read_chunks<R: Read + Seek>(reader: &mut R, form_end: u64) -> XmpResult<Vec<AiffChunk>> {
+        let mut chunks = Vec::new();
+        let mut pos = 12u64;
+        while pos + 8 <= form_end {
+            reader.seek(SeekFrom::Start(pos))?;
+            let mut header = [0u8; 8];
+            reader.read_exact(&mut header)?;
+            let size = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as u64;
+            if pos + 8 + size > form_end {
+                return Err(XmpError::BadValue("Truncated AIFF chunk".to_string()));
+            }
+
+            chunks.push(AiffChunk {
+                id: [header[0], header[1], header[2], header[3]],
+                offset: pos,
+                size,
+            });
+            pos += 8 + size + (size & 1);
+        }
+        Ok(chunks)
+    }
+
+    /// Check whether a chunk is the XMP "APPL" chunk
+    fn is_xmp_chunk<R: Read + Seek>(reader: &mut R, chunk: &AiffChunk) -> XmpResult<bool> {
+        if chunk.id != *CHUNK_APPL || chunk.size < 4 {
+            return Ok(false);
+        }
+        reader.seek(SeekFrom::Start(chunk.offset + 8))?;
+        let mut signature = [0u8; 4];
+        reader.read_exact(&mut signature)?;
+        Ok(signature == *XMP_SIGNATURE)
+    }
+}
+
+/// A chunk of the output file
+enum OutputChunk {
+    /// Copied unchanged from the source file
+    Copy(AiffChunk),
+    /// The new XMP chunk
+    Xmp,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+    use std::io::Cursor;
+
+    /// Build an AIFF file from chunks
+    fn build_aiff(form_type: &[u8; 4], chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+        let mut body = form_type.to_vec();
+        for (id, data) in chunks {
+            body.extend_from_slice(*id);
+            body.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            body.extend_from_slice(data);
+            if data.len() & 1 == 1 {
+                body.push(0);
+            }
+        }
+        let mut file = FORM_SIGNATURE.to_vec();
+        file.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        file.extend_from_slice(&body);
+        file
+    }
+
+    fn minimal_aiff(form_type: &[u8; 4]) -> Vec<u8> {
+        build_aiff(
+            form_type,
+            &[
+                (
+                    b"COMM",
+                    &[
+                        0, 1, 0, 0, 0, 1, 0, 16, 0x40, 0x0E, 0xAC, 0x44, 0, 0, 0, 0, 0, 0,
+                    ],
+                ),
+                (CHUNK_APPL, b"pdosdata"),
+                (b"SSND", &[0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3]),
+            ],
+        )
+    }
+
+    fn chunk_ids(data: &[u8]) -> Vec<[u8; 4]> {
+        let mut reader = Cursor::new(data);
+        let (_, form_end) = AiffHandler::read_header(&mut reader).unwrap();
+        assert_eq!(form_end, data.len() as u64);
+        AiffHandler::read_chunks(&mut reader, form_end)
+            .unwrap()
+            .iter()
+            .map(|chunk| chunk.id)
+            .collect()
+    }
+
+    #[test]
+    fn test_can_handle() {
+        let handler = AiffHandler;
+        assert!(handler
+            .can_handle(&mut Cursor::new(minimal_aiff(AIFF_FORM_TYPE)))
+            .unwrap());
+        assert!(handler
+            .can_handle(&mut Cursor::new(minimal_aiff(AIFC_FORM_TYPE)))
+            .unwrap());
+        assert!(!handler
+            .can_handle(&mut Cursor::new(b"FORM\0\0\0\x04ILBM".to_vec()))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_read_xmp_no_xmp() {
+        let aiff = minimal_aiff(AIFF_FORM_TYPE);
+        assert!(AiffHandler::read_xmp(Cursor::new(aiff)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_write_xmp() {
+        let aiff = minimal_aiff(AIFC_FORM_TYPE);
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String("First".to_string()))
+            .unwrap();
+        let mut writer = Cursor::new(Vec::new());
+        AiffHandler::write_xmp(Cursor::new(&aiff), &mut writer, &meta).unwrap();
+        let first = writer.into_inner();
+        assert_eq!(&first[8..aiff.len()], &aiff[8..]);
+        assert_eq!(
+            chunk_ids(&first),
+            [*b"COMM", *CHUNK_APPL, *b"SSND", *CHUNK_APPL]
+        );
+
+        // The XMP chunk is replaced, keeping the other APPL chunk
+        meta.set_property(ns::DC, "title", XmpValue::String("Second".to_string()))
+            .unwrap();
+        let mut writer = Cursor::new(Vec::new());
+        AiffHandler::write_xmp(Cursor::new(&first), &mut writer, &meta).unwrap();
+        let second = writer.into_inner();
+        assert_eq!(
+            chunk_ids(&second),
+            [*b"COMM", *CHUNK_APPL, *b"SSND", *CHUNK_APPL]
+        );
+
+        let read = AiffHandler::read_xmp(Cursor::new(&second))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            read.get_property(ns::DC, "title"),
+            Some(XmpValue::String("Second".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_truncated_chunk() {
+        let mut aiff = minimal_aiff(AIFF_FORM_TYPE);
+        let len = aiff.len();
+        aiff.truncate(len - 4);
+        assert!(AiffHandler::read_xmp(Cursor::new(aiff)).is_err());
+    }
+}
@@ -0,0 +1,366 @@
+//! ASF file format handler
+//!
+//! This module provides functionality for reading and writing XMP metadata
+//! in ASF (Windows Media: WMV, WMA) files. The implementation is pure Rust
+//! and cross-platform compatible.
+//!
+//! ASF File Structure:
+//! - A sequence of top-level objects: GUID (16 bytes) + size (8 bytes,
+//!   little-endian, including the object header) + data
+//! - The Header object comes first and holds the header objects (File
+//!   Properties, Stream Properties, ...), followed by the Data object and
+//!   the optional Index objects
+//!
+//! ASF XMP Storage:
+//! - XMP Packet is stored in a top-level XMP object
+//! - GUID: BE7ACFCB-97A9-42E8-9C71-999491E3AFAC
+//! - Like Adobe's ASF handler, a new XMP object is placed at the end of the
+//!   file so the media data does not move; the file size of the File
+//!   Properties object is updated accordingly
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::files::handler::FileHandler;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// Header object GUID (75B22630-668E-11CF-A6D9-00AA0062CE6C)
+const GUID_HEADER: [u8; 16] = [
+    0x30, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, 0xA6, 0xD9, 0x00, 0xAA, 0x00, 0x62, 0xCE, 0x6C,
+];
+
+/// File Properties object GUID (8CABDCA1-A947-11CF-8EE4-00C00C205365)
+const GUID_FILE_PROPERTIES: [u8; 16] = [
+    0xA1, 0xDC, 0xAB, 0x8C, 0x47, 0xA9, 0xCF, 0x11, 0x8E, 0xE4, 0x00, 0xC0, 0x0C, 0x20, 0x53, 0x65,
+];
+
+/// XMP object GUID (BE7ACFCB-97A9-42E8-9C71-999491E3AFAC)
+const GUID_XMP: [u8; 16] = [
+    0xCB, 0xCF, 0x7A, 0xBE, 0xA9, 0x97, 0xE8, 0x42, 0x9C, 0x71, 0x99, 0x94, 0x91, 0xE3, 0xAF, 0xAC,
+];
+
+/// Size of an object header (GUID + size)
+const OBJECT_HEADER_SIZE: u64 = 24;
+
+/// Size of the Header object fields before its header objects
+/// (object header + object count + 2 reserved bytes)
+const HEADER_OBJECT_PREFIX: u64 = 30;
+
+/// Offset of the file size in the File Properties object data
+const FILE_SIZE_OFFSET: usize = 16;
+
+/// Offset of the flags in the File Properties object data
+const FLAGS_OFFSET: usize = 64;
+
+/// Broadcast flag: the file size is not valid
+const FLAG_BROADCAST: u32 = 0x01;
+
+/// ASF file handler for XMP metadata
+#[derive(Debug, Clone, Copy)]
+pub struct AsfHandler;
+
+impl FileHandler for AsfHandler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        let pos = reader.stream_position()?;
+        let mut guid = [0u8; 16];
+        let result = reader.read_exact(&mut guid);
+        reader.seek(SeekFrom::Start(pos))?;
+        Ok(result.is_ok() && guid == GUID_HEADER)
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn format_name(&self) -> &'static str {
+        "ASF"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["asf", "wmv", "wma"]
+    }
+}
+
+/// The location of an ASF object
+#[derive(Debug, Clone, Copy)]
+struct AsfObject {
+    guid: [u8; 16],
+    offset: u64,
+    /// Size of the object, including its header
+    size: u64,
+}
+
+impl AsfObject {
+    fn data_offset(&self) -> u64 {
+        self.offset + OBJECT_HEADER_SIZE
+    }
+
+    fn data_size(&self) -> u64 {
+        self.size - OBJECT_HEADER_SIZE
+    }
+}
+
+impl AsfHandler {
+    /// Read XMP metadata from an ASF file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(XmpMeta))` if XMP metadata is found
+    /// * `Ok(None)` if no XMP metadata is found
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_xmp<R: Read + Seek>(mut reader: R) -> XmpResult<Option<XmpMeta>> {
+        let objects = Self::read_objects(&mut reader)?;
+        let Some(xmp_object) = objects.iter().find(|object| object.guid == GUID_XMP) else {
+            return Ok(None);
+        };
+
+        let data = Self::read_object_data(&mut reader, xmp_object)?;
+        let end = data.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+        let xmp_str = String::from_utf8(data[..end].to_vec())
+            .map_err(|e| XmpError::ParseError(format!("Invalid UTF-8 in XMP: {}", e)))?;
+        XmpMeta::parse(&xmp_str).map(Some)
+    }
+
+    /// Write XMP metadata to an ASF file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    pub fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        let xmp_packet = meta.serialize_packet()?;
+        let xmp_bytes = xmp_packet.as_bytes();
+
+        let objects = Self::read_objects(&mut reader)?;
+        let kept: Vec<_> = objects
+            .iter()
+            .filter(|object| object.guid != GUID_XMP)
+            .collect();
+        let xmp_object_size = OBJECT_HEADER_SIZE + xmp_bytes.len() as u64;
+        let file_size = kept.iter().map(|object| object.size).sum::<u64>() + xmp_object_size;
+
+        // Header object with the new file size
+        let mut header = vec![0u8; objects[0].size as usize];
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(&mut header)?;
+        Self::update_file_size(&mut header, file_size)?;
+        writer.write_all(&header)?;
+
+        for object in &kept[1..] {
+            reader.seek(SeekFrom::Start(object.offset))?;
+            let copied = std::io::copy(&mut reader.by_ref().take(object.size), &mut writer)?;
+            if copied != object.size {
+                return Err(XmpError::BadValue("Truncated ASF object".to_string()));
+            }
+        }
+
+        writer.write_all(&GUID_XMP)?;
+        writer.write_all(&xmp_object_size.to_le_bytes())?;
+        writer.write_all(xmp_bytes)?;
+
+        Ok(())
+    }
+
+    /// Read the top-level objects, checking that the first is the Header
+    fn read_objects<R: Read + Seek>(reader: &mut R) -> XmpResult<Vec<AsfObject>> {
+        let file_end = reader.seek(SeekFrom::End(0))?;
+        let mut objects = Vec::new();
+        let mut pos = 0u64;
+        while pos + OBJECT_HEADER_SIZE <= file_end {
+            reader.seek(SeekFrom::Start(pos))?;
+            let mut header = [0u8; 24];
+            reader.read_exact(&mut header)?;
+            let mut guid = [0u8; 16];
+            guid.copy_from_slice(&header[..16]);
+            let mut size = [0u8; 8];
+            size.copy_from_slice(&header[16..]);
+            let size = u64::from_le_bytes(size);
+            if size < OBJECT_HEADER_SIZE || size > file_end - pos {
+                return Err(XmpError::BadValue("Invalid ASF object size".to_string()));
+            }
+
+            objects.push(AsfObject {
+                guid,
+                offset: pos,
+                size,
+            });
+            pos += size;
+        }
+
+        if objects
+            .first()
+            .is_none_or(|object| object.guid != GUID_HEADER || object.size < HEADER_OBJECT_PREFIX)
+        {
+            return Err(XmpError::BadValue("Not a valid ASF file".to_string()));
+        }
+        Ok(objects)
+    }
+
+    /// Read the data of an object
+    fn read_object_data<R: Read + Seek>(reader: &mut R, object: &AsfObject) -> XmpResult<Vec<u8>> {
+        reader.seek(SeekFrom::Start(object.data_offset()))?;
+        let mut data = vec![0u8; object.data_size() as usize];
+        reader.read_exact(&mut data)?;
+        Ok(data)
+    }
+
+    /// Update the file size of the File Properties object in the Header
+    fn update_file_size(header: &mut [u8], file_size: u64) -> XmpResult<()> {
+        let mut pos = HEADER_OBJECT_PREFIX as usize;
+        while pos + OBJECT_HEADER_SIZE as usize <= header.len() {
+            let mut size = [0u8; 8];
+            size.copy_from_slice(&header[pos + 16..pos + 24]);
+            let size = u64::from_le_bytes(size) as usize;
+            if size < OBJECT_HEADER_SIZE as usize || size > header.len() - pos {
+                return Err(XmpError::BadValue("Invalid ASF header object".to_string()));
+            }
+
+            if header[pos..pos + 16] == GUID_FILE_PROPERTIES {
+                let data = &mut header[pos + OBJECT_HEADER_SIZE as usize..pos + size];
+                if data.len() < FLAGS_OFFSET + 4 {
+                    return Err(XmpError::BadValue(
+                        "Invalid ASF File Properties object".to_string(),
+                    ));
+                }
+                let flags = u32::from_le_bytes([
+                    data[FLAGS_OFFSET],
+                    data[FLAGS_OFFSET + 1],
+                    data[FLAGS_OFFSET + 2],
+                    data[FLAGS_OFFSET + 3],
+                ]);
+                if flags & FLAG_BROADCAST == 0 {
+                    data[FILE_SIZE_OFFSET..FILE_SIZE_OFFSET + 8]
+                        .copy_from_slice(&file_size.to_le_bytes());
+                }
+                return Ok(());
+            }
+            pos += size;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+    use std::io::Cursor;
+
+    /// Data object GUID (75B22636-668E-11CF-A6D9-00AA0062CE6C)
+    const GUID_DATA: [u8; 16] = [
+        0x36, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, 0xA6, 0xD9, 0x00, 0xAA, 0x00, 0x62, 0xCE,
+        0x6C,
+    ];
+
+    fn object(guid: &[u8; 16], data: &[u8]) -> Vec<u8> {
+        let mut out = guid.to_vec();
+        out.extend_from_slice(&(OBJECT_HEADER_SIZE + data.len() as u64).to_le_bytes());
+        out.extend_from_slice(data);
+        out
+    }
+
+    fn build_asf(extra: &[u8]) -> Vec<u8> {
+        let file_properties = object(&GUID_FILE_PROPERTIES, &[0; 80]);
+        let mut header_data = 1u32.to_le_bytes().to_vec();
+        header_data.extend_from_slice(&[0x01, 0x02]);
+        header_data.extend(file_properties);
+
+        let mut asf = object(&GUID_HEADER, &header_data);
+        asf.extend(object(&GUID_DATA, b"packets"));
+        asf.extend_from_slice(extra);
+        let file_size = asf.len() as u64;
+        AsfHandler::update_file_size(&mut asf, file_size).unwrap();
+        asf
+    }
+
+    fn file_size(asf: &[u8]) -> u64 {
+        let offset = HEADER_OBJECT_PREFIX as usize + OBJECT_HEADER_SIZE as usize;
+        let mut size = [0u8; 8];
+        size.copy_from_slice(&asf[offset + FILE_SIZE_OFFSET..offset + FILE_SIZE_OFFSET + 8]);
+        u64::from_le_bytes(size)
+    }
+
+    fn test_meta(title: &str) -> XmpMeta {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String(title.to_string()))
+            .unwrap();
+        meta
+    }
+
+    #[test]
+    fn test_can_handle() {
+        let handler = AsfHandler;
+        assert!(handler
+            .can_handle(&mut Cursor::new(build_asf(&[])))
+            .unwrap());
+        assert!(!handler
+            .can_handle(&mut Cursor::new(b"RIFF\0\0\0\0AVI ".to_vec()))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_write_xmp() {
+        let asf = build_asf(&[]);
+        assert_eq!(file_size(&asf), asf.len() as u64);
+        assert!(AsfHandler::read_xmp(Cursor::new(&asf)).unwrap().is_none());
+
+        let mut writer = Cursor::new(Vec::new());
+        AsfHandler::write_xmp(Cursor::new(&asf), &mut writer, &test_meta("Archive")).unwrap();
+        let data = writer.into_inner();
+        // The Data object has not moved
+        let data_object = asf.len() - 31..asf.len();
+        assert_eq!(&data[data_object.clone()], &asf[data_object]);
+        assert_eq!(file_size(&data), data.len() as u64);
+
+        let meta = AsfHandler::read_xmp(Cursor::new(&data)).unwrap().unwrap();
+        assert_eq!(
+            meta.get_property(ns::DC, "title"),
+            Some(XmpValue::String("Archive".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_write_xmp_replaces_existing() {
+        let old = test_meta("Old").serialize_packet().unwrap();
+        let mut asf = build_asf(&object(&GUID_XMP, old.as_bytes()));
+        asf.extend(object(&[0x11; 16], b"index"));
+
+        let mut writer = Cursor::new(Vec::new());
+        AsfHandler::write_xmp(Cursor::new(&asf), &mut writer, &test_meta("New")).unwrap();
+        let data = writer.into_inner();
+
+        let objects = AsfHandler::read_objects(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(objects.len(), 4);
+        assert_eq!(objects.last().unwrap().guid, GUID_XMP);
+        assert_eq!(objects[2].guid, [0x11; 16]);
+        assert_eq!(file_size(&data), data.len() as u64);
+
+        let meta = AsfHandler::read_xmp(Cursor::new(&data)).unwrap().unwrap();
+        assert_eq!(
+            meta.get_property(ns::DC, "title"),
+            Some(XmpValue::String("New".to_string()))
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! AVI file format handler
+//!
+//! This module provides functionality for reading and writing XMP metadata
+//! in AVI files. The implementation is pure Rust and cross-platform
+//! compatible.
+//!
+//! AVI XMP Storage:
+//! - AVI is a RIFF container: "RIFF" + size (little-endian) + "AVI " + chunks
+//! - Chunks are grouped in "LIST" chunks: "LIST" + size + list type + chunks
+//!   (e.g. "hdrl" headers, "movi" media data)
+//! - XMP Packet is stored in a "_PMX" chunk of the first RIFF chunk
+//! - Files larger than 1 GB (OpenDML) have more "RIFF" "AVIX" chunks
+//!
+//! An existing "_PMX" chunk is rewritten in place when the new packet fits,
+//! padding with a "JUNK" chunk. Otherwise it is turned into a "JUNK" chunk
+//! and a new "_PMX" chunk is appended to the first RIFF chunk, so the media
+//! data never moves.
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::files::formats::riff::{self, ChunkHeader};
+use crate::files::handler::FileHandler;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// AVI form type
+const AVI_FORM_TYPE: &[u8; 4] = b"AVI ";
+
+/// XMP chunk ID
+const CHUNK_XMP: &[u8; 4] = b"_PMX";
+
+/// List chunk ID
+const CHUNK_LIST: &[u8; 4] = b"LIST";
+
+/// Filler chunk ID
+const CHUNK_JUNK: &[u8; 4] = b"JUNK";
+
+/// List type of the media data, which is not searched for XMP
+const LIST_MOVI: &[u8; 4] = b"movi";
+
+/// AVI file handler for XMP metadata
+#[derive(Debug, Clone, Copy)]
+pub struct AviHandler;
+
+impl FileHandler for AviHandler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        riff::is_riff(reader, AVI_FORM_TYPE)
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn format_name(&self) -> &'static str {
+        "AVI"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["avi"]
+    }
+}
+
+impl AviHandler {
+    /// Read XMP metadata from an AVI file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(XmpMeta))` if XMP metadata is found
+    /// * `Ok(None)` if no XMP metadata is found
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_xmp<R: Read + Seek>(mut reader: R) -> XmpResult<Option<XmpMeta>> {
+        let riff_end = riff::read_header(&mut reader, AVI_FORM_TYPE, "AVI")?;
+        let Some(header) = Self::find_xmp_chunk(&mut reader, 12, riff_end)? else {
+            return Ok(None);
+        };
+
+        let data = riff::read_chunk_data(&mut reader, &header)?;
+        let end = data.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+        let xmp_str = String::from_utf8(data[..end].to_vec())
+            .map_err(|e| XmpError::ParseError(format!("Invalid UTF-8 in XMP: {}", e)))?;
+        XmpMeta::parse(&xmp_str).map(Some)
+    }
+
+    /// Write XMP metadata to an AVI file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    pub fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        let xmp_packet = meta.serialize_packet()?;
+        let xmp_bytes = xmp_packet.as_bytes();
+
+        let riff_end = riff::read_header(&mut reader, AVI_FORM_TYPE, "AVI")?;
+        let file_end = reader.seek(SeekFrom::End(0))?;
+        let existing = Self::find_xmp_chunk(&mut reader, 12, riff_end)?;
+
+        // Rewrite in place, filling the rest of the old chunk with JUNK
+        let mut new_chunk = Vec::new();
+        riff::write_chunk(&mut new_chunk, CHUNK_XMP, xmp_bytes)?;
+        if let Some(header) = existing {
+            let space = riff::chunk_size(header.size);
+            let remaining = space.checked_sub(new_chunk.len() as u64);
+            if let Some(remaining) = remaining.filter(|&r| r == 0 || r >= 8) {
+                if remaining > 0 {
+                    riff::write_chunk(
+                        &mut new_chunk,
+                        CHUNK_JUNK,
+                        &vec![0; remaining as usize - 8],
+                    )?;
+                }
+                let patch_end = header.offset + space;
+                Self::copy_range(&mut reader, &mut writer, 0, header.offset)?;
+                writer.write_all(&new_chunk)?;
+                return Self::copy_range(&mut reader, &mut writer, patch_end, file_end);
+            }
+        }
+
+        // Appending to the first RIFF chunk would move the OpenDML chunks
+        if file_end > riff_end {
+            return Err(XmpError::NotSupported(
+                "Growing the XMP of multi-RIFF AVI files".to_string(),
+            ));
+        }
+
+        // Keep the RIFF data even before appending
+        let pad = riff_end & 1;
+        let new_riff_size = u32::try_from(riff_end - 8 + pad + new_chunk.len() as u64)
+            .map_err(|_| XmpError::BadValue("AVI file too large".to_string()))?;
+        writer.write_all(riff::RIFF_SIGNATURE)?;
+        writer.write_all(&new_riff_size.to_le_bytes())?;
+        let mut pos = 8;
+
+        // The old chunk becomes a JUNK chunk
+        if let Some(header) = existing {
+            Self::copy_range(&mut reader, &mut writer, pos, header.offset)?;
+            writer.write_all(CHUNK_JUNK)?;
+            pos = header.offset + 4;
+        }
+        Self::copy_range(&mut reader, &mut writer, pos, riff_end)?;
+        if pad == 1 {
+            writer.write_all(&[0])?;
+        }
+        writer.write_all(&new_chunk)?;
+
+        Ok(())
+    }
+
+    /// Find the "_PMX" chunk between `start` and `end`, searching LIST chunks
+    fn find_xmp_chunk<R: Read + Seek>(
+        reader: &mut R,
+        start: u64,
+        end: u64,
+    ) -> XmpResult<Option<ChunkHeader>> {
+        for header in riff::read_chunk_headers(reader, start, end, "AVI")? {
+            if header.id == *CHUNK_XMP {
+                return Ok(Some(header));
+            }
+            if header.id == *CHUNK_LIST && header.size >= 4 {
+                reader.seek(SeekFrom::Start(header.data_offset()))?;
+                let mut list_type = [0u8; 4];
+                reader.read_exact(&mut list_type)?;
+                if list_type == *LIST_MOVI {
+                    continue;
+                }
+                let list_end = header.data_offset() + header.size;
+                if let Some(found) =
+                    Self::find_xmp_chunk(reader, header.data_offset() + 4, list_end)?
+                {
+                    return Ok(Some(found));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Copy the bytes between `start` and `end` from the reader
+    fn copy_range<R: Read + Seek, W: Write>(
+        reader: &mut R,
+        writer: &mut W,
+        start: u64,
+        end: u64,
+    ) -> XmpResult<()> {
+        reader.seek(SeekFrom::Start(start))?;
+        let copied = std::io::copy(&mut reader.by_ref().take(end - start), writer)?;
+        if copied != end - start {
+            return Err(XmpError::BadValue("Truncated AVI file".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+    use std::io::Cursor;
+
+    fn chunk(id: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        riff::write_chunk(&mut out, id, data).unwrap();
+        out
+    }
+
+    /// Build an AVI file: LIST hdrl, optional extra chunks, LIST movi, idx1
+    fn build_avi(extra: &[u8]) -> Vec<u8> {
+        let mut hdrl = b"hdrl".to_vec();
+        hdrl.extend(chunk(b"avih", &[0; 56]));
+        let mut movi = b"movi".to_vec();
+        movi.extend(chunk(b"00dc", b"frame"));
+
+        let mut chunks = chunk(CHUNK_LIST, &hdrl);
+        chunks.extend_from_slice(extra);
+        chunks.extend(chunk(CHUNK_LIST, &movi));
+        chunks.extend(chunk(b"idx1", &[0; 16]));
+
+        let mut avi = Vec::new();
+        riff::write_header(&mut avi, AVI_FORM_TYPE, chunks.len() as u64, "AVI").unwrap();
+        avi.extend(chunks);
+        avi
+    }
+
+    fn test_meta(title: &str) -> XmpMeta {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String(title.to_string()))
+            .unwrap();
+        meta
+    }
+
+    fn write(data: &[u8], meta: &XmpMeta) -> Vec<u8> {
+        let mut writer = Cursor::new(Vec::new());
+        AviHandler::write_xmp(Cursor::new(data), &mut writer, meta).unwrap();
+        writer.into_inner()
+    }
+
+    fn title(data: &[u8]) -> Option<XmpValue> {
+        AviHandler::read_xmp(Cursor::new(data))
+            .unwrap()
+            .and_then(|meta| meta.get_property(ns::DC, "title"))
+    }
+
+    fn frame_offset(data: &[u8]) -> usize {
+        data.windows(5).position(|w| w == b"frame").unwrap()
+    }
+
+    #[test]
+    fn test_can_handle() {
+        let handler = AviHandler;
+        assert!(handler
+            .can_handle(&mut Cursor::new(build_avi(&[])))
+            .unwrap());
+        assert!(!handler
+            .can_handle(&mut Cursor::new(b"RIFF\0\0\0\0WAVE".to_vec()))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_write_xmp_appends() {
+        let avi = build_avi(&[]);
+        assert_eq!(title(&avi), None);
+
+        let data = write(&avi, &test_meta("Clip"));
+        assert_eq!(title(&data), Some(XmpValue::String("Clip".to_string())));
+        assert_eq!(frame_offset(&data), frame_offset(&avi));
+
+        let riff_size = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
+        assert_eq!(riff_size + 8, data.len());
+    }
+
+    #[test]
+    fn test_write_xmp_in_place_and_junk() {
+        // XMP in a LIST chunk before the media data
+        let mut info = b"INFO".to_vec();
+        info.extend(chunk(
+            CHUNK_XMP,
+            test_meta("Old title with some extra words")
+                .serialize_packet()
+                .unwrap()
+                .as_bytes(),
+        ));
+        let avi = build_avi(&chunk(CHUNK_LIST, &info));
+        assert_eq!(
+            title(&avi),
+            Some(XmpValue::String(
+                "Old title with some extra words".to_string()
+            ))
+        );
+
+        // A smaller packet fits in place
+        let data = write(&avi, &test_meta("Old"));
+        assert_eq!(data.len(), avi.len());
+        assert_eq!(title(&data), Some(XmpValue::String("Old".to_string())));
+        assert!(data.windows(4).any(|w| w == CHUNK_JUNK));
+
+        // A larger packet is appended and the old chunk becomes JUNK
+        let data = write(&avi, &test_meta(&"Longer title".repeat(20)));
+        assert_eq!(frame_offset(&data), frame_offset(&avi));
+        assert_eq!(data.windows(4).filter(|w| w == CHUNK_XMP).count(), 1);
+        assert_eq!(
+            title(&data),
+            Some(XmpValue::String("Longer title".repeat(20)))
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! AVIF file format handler
+//!
+//! This module provides functionality for reading and writing XMP metadata
+//! in AVIF files. The implementation is pure Rust and cross-platform compatible.
+//!
+//! AVIF XMP Storage:
+//! - AVIF uses the HEIF container with AV1 image items, identified by the
+//!   `avif` (still image) or `avis` (image sequence) brand
+//! - XMP is stored the same way as in HEIF: a `mime` item with content type
+//!   `application/rdf+xml`, so reading and writing is done by [`HeifHandler`]
+
+use crate::core::error::XmpResult;
+use crate::core::metadata::XmpMeta;
+use crate::files::formats::heif::{HeifHandler, OTHER_BRANDS};
+use crate::files::handler::FileHandler;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// AVIF file handler for XMP metadata
+#[derive(Debug, Clone, Copy)]
+pub struct AvifHandler;
+
+impl FileHandler for AvifHandler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        let pos = reader.stream_position()?;
+        let result = HeifHandler::read_brands(reader);
+        reader.seek(SeekFrom::Start(pos))?;
+
+        let Ok(Some((major, compatible))) = result else {
+            return Ok(false);
+        };
+        Ok(OTHER_BRANDS.contains(&&major)
+            || compatible.iter().any(|brand| OTHER_BRANDS.contains(&brand)))
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        HeifHandler::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        HeifHandler::write_xmp(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn format_name(&self) -> &'static str {
+        "AVIF"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["avif"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::files::formats::heif::tests::create_minimal_file;
+    use crate::types::value::XmpValue;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_can_handle() {
+        let avif = create_minimal_file(b"avif", b"mif1miafavif", b"av01");
+        assert!(AvifHandler.can_handle(&mut Cursor::new(avif)).unwrap());
+        assert!(!HeifHandler
+            .can_handle(&mut Cursor::new(create_minimal_file(
+                b"avif", b"mif1", b"av01"
+            )))
+            .unwrap());
+
+        let heic = create_minimal_file(b"heic", b"mif1heic", b"hvc1");
+        assert!(!AvifHandler.can_handle(&mut Cursor::new(heic)).unwrap());
+    }
+
+    #[test]
+    fn test_write_xmp() {
+        let avif = create_minimal_file(b"avif", b"mif1miafavif", b"av01");
+        assert!(AvifHandler
+            .read_xmp(&mut Cursor::new(&avif))
+            .unwrap()
+            .is_none());
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String("AVIF Image".to_string()))
+            .unwrap();
+        let mut writer = Cursor::new(Vec::new());
+        AvifHandler
+            .write_xmp(&mut Cursor::new(avif), &mut writer, &meta)
+            .unwrap();
+
+        writer.set_position(0);
+        let meta = AvifHandler.read_xmp(&mut writer).unwrap().unwrap();
+        assert_eq!(
+            meta.get_property(ns::DC, "title"),
+            Some(XmpValue::String("AVIF Image".to_string()))
+        );
+    }
+}
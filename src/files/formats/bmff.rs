@@ -0,0 +1,137 @@
+//! ISO base media box helpers
+//!
+//! Shared by the handlers of box-based image formats (JPEG 2000, JPEG XL).
+//!
+//! Box Structure:
+//! - Size (4 bytes, big-endian, including the header) + type (4 bytes) + data
+//! - A size of 1 means a 64-bit size follows the type; a size of 0 means the
+//!   box extends to the end of the file
+
+use crate::core::error::{XmpError, XmpResult};
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// UUID of XMP boxes (BE7ACFCB-97A9-42E8-9C71-999491E3AFAC)
+#[cfg_attr(not(feature = "jp2"), allow(dead_code))]
+pub(crate) const XMP_UUID: &[u8; 16] = &[
+    0xBE, 0x7A, 0xCF, 0xCB, 0x97, 0xA9, 0x42, 0xE8, 0x9C, 0x71, 0x99, 0x94, 0x91, 0xE3, 0xAF, 0xAC,
+];
+
+/// The location of a box in the file
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BoxHeader {
+    pub(crate) box_type: [u8; 4],
+    /// Offset of the box header
+    pub(crate) offset: u64,
+    /// Size of the box header (8 or 16 bytes)
+    pub(crate) header_size: u64,
+    /// Size of the box, including its header
+    pub(crate) size: u64,
+    /// Whether the size field is 0 (up to the end of the file)
+    pub(crate) to_end: bool,
+}
+
+impl BoxHeader {
+    /// Offset of the box data
+    pub(crate) fn data_offset(&self) -> u64 {
+        self.offset + self.header_size
+    }
+
+    /// Size of the box data
+    pub(crate) fn data_size(&self) -> u64 {
+        self.size - self.header_size
+    }
+}
+
+/// Read the top-level box headers between `start` and the end of the file
+pub(crate) fn read_boxes<R: Read + Seek>(
+    reader: &mut R,
+    start: u64,
+    format: &str,
+) -> XmpResult<Vec<BoxHeader>> {
+    let file_end = reader.seek(SeekFrom::End(0))?;
+    let mut boxes = Vec::new();
+    let mut pos = start;
+    while pos + 8 <= file_end {
+        reader.seek(SeekFrom::Start(pos))?;
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header)?;
+        let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as u64;
+        let (header_size, size) = match size {
+            0 => (8, file_end - pos),
+            1 => {
+                let mut ext = [0u8; 8];
+                reader
+                    .read_exact(&mut ext)
+                    .map_err(|_| XmpError::BadValue(format!("Truncated {} box", format)))?;
+                (16, u64::from_be_bytes(ext))
+            }
+            size => (8, size),
+        };
+        if size < header_size || size > file_end - pos {
+            return Err(XmpError::BadValue(format!("Invalid {} box size", format)));
+        }
+
+        boxes.push(BoxHeader {
+            box_type: [header[4], header[5], header[6], header[7]],
+            offset: pos,
+            header_size,
+            size,
+            to_end: header[..4] == [0; 4],
+        });
+        pos += size;
+    }
+    Ok(boxes)
+}
+
+/// Read the data of a box
+pub(crate) fn read_box_data<R: Read + Seek>(
+    reader: &mut R,
+    header: &BoxHeader,
+) -> XmpResult<Vec<u8>> {
+    reader.seek(SeekFrom::Start(header.data_offset()))?;
+    let mut data = vec![0u8; header.data_size() as usize];
+    reader.read_exact(&mut data)?;
+    Ok(data)
+}
+
+/// Write a box header for data of the given size
+pub(crate) fn write_box_header<W: Write>(
+    writer: &mut W,
+    box_type: &[u8; 4],
+    data_size: u64,
+) -> XmpResult<()> {
+    let size = 8 + data_size;
+    if size <= u32::MAX as u64 {
+        writer.write_all(&(size as u32).to_be_bytes())?;
+        writer.write_all(box_type)?;
+    } else {
+        writer.write_all(&1u32.to_be_bytes())?;
+        writer.write_all(box_type)?;
+        writer.write_all(&(size + 8).to_be_bytes())?;
+    }
+    Ok(())
+}
+
+/// Copy a box from the reader
+///
+/// A box extending to the end of the file gets an explicit size, so other
+/// boxes can follow it.
+pub(crate) fn copy_box<R: Read + Seek, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    header: &BoxHeader,
+    format: &str,
+) -> XmpResult<()> {
+    let (start, len) = if header.to_end {
+        write_box_header(writer, &header.box_type, header.data_size())?;
+        (header.data_offset(), header.data_size())
+    } else {
+        (header.offset, header.size)
+    };
+    reader.seek(SeekFrom::Start(start))?;
+    let copied = std::io::copy(&mut reader.by_ref().take(len), writer)?;
+    if copied != len {
+        return Err(XmpError::BadValue(format!("Truncated {} box", format)));
+    }
+    Ok(())
+}
@@ -0,0 +1,384 @@
+//! EPS / PostScript file format handler
+//!
+//! This module provides functionality for reading and writing XMP metadata
+//! in EPS and PostScript files. The implementation is pure Rust and
+//! cross-platform compatible.
+//!
+//! PostScript File Structure:
+//! - Plain files start with `%!PS-Adobe-`; DOS EPS files start with a binary
+//!   header (0xC5D0D3C6) giving the offset and length of the PostScript
+//!   section, followed by optional WMF and TIFF previews
+//! - DSC header comments (`%%...`) come first, up to `%%EndComments`
+//!
+//! PostScript XMP Storage:
+//! - XMP Packets are embedded in the PostScript code, and are found by
+//!   packet scanning
+//! - The `%ADO_ContainsXMP:` header comment tells which packet is the main
+//!   one: `MainFirst` (the first packet), `MainLast` (the last packet) or
+//!   `NoMain` (packets only belong to placed content); without the comment
+//!   the first packet is used
+//! - Writing only replaces the main packet in place (the new packet is
+//!   padded to the same length), since the PostScript code around it can't
+//!   be safely rewritten
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::core::serializer::SerializeOptions;
+use crate::files::format::FormatCapabilities;
+use crate::files::handler::FileHandler;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+
+/// PostScript file signature
+const PS_SIGNATURE: &[u8] = b"%!PS-Adobe-";
+
+/// DOS EPS binary header signature
+const DOS_EPS_SIGNATURE: &[u8] = &[0xC5, 0xD0, 0xD3, 0xC6];
+
+/// Size of the DOS EPS binary header
+const DOS_EPS_HEADER_SIZE: usize = 30;
+
+/// DSC comment giving the location of the main XMP packet
+const CONTAINS_XMP_COMMENT: &[u8] = b"%ADO_ContainsXMP:";
+
+/// Markers of an XMP packet
+const PACKET_START: &[u8] = b"<?xpacket begin";
+const PACKET_END: &[u8] = b"<?xpacket end";
+
+/// EPS / PostScript file handler for XMP metadata
+#[derive(Debug, Clone, Copy)]
+pub struct EpsHandler;
+
+impl FileHandler for EpsHandler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        let pos = reader.stream_position()?;
+        let mut header = [0u8; 11];
+        let result = reader.read_exact(&mut header);
+        reader.seek(SeekFrom::Start(pos))?;
+        Ok(result.is_ok() && (header == *PS_SIGNATURE || header[..4] == *DOS_EPS_SIGNATURE))
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> FormatCapabilities {
+        // Only an existing writable packet can be replaced
+        FormatCapabilities {
+            can_read: true,
+            can_write: true,
+            supports_in_place: true,
+            ..Default::default()
+        }
+    }
+
+    fn format_name(&self) -> &'static str {
+        "EPS"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["eps", "epsf", "ps"]
+    }
+}
+
+/// Location of the main XMP packet, from the `%ADO_ContainsXMP:` comment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MainPacket {
+    First,
+    Last,
+    None,
+}
+
+/// The PostScript section of a file
+struct PostScript {
+    /// File offset of the section
+    offset: u64,
+    data: Vec<u8>,
+}
+
+impl PostScript {
+    fn read<R: Read + Seek>(reader: &mut R) -> XmpResult<Self> {
+        let file_end = reader.seek(SeekFrom::End(0))?;
+        reader.rewind()?;
+        let mut header = [0u8; DOS_EPS_HEADER_SIZE];
+        let len = file_end.min(DOS_EPS_HEADER_SIZE as u64) as usize;
+        reader.read_exact(&mut header[..len])?;
+
+        let (offset, size) = if header[..4] == *DOS_EPS_SIGNATURE {
+            if len < DOS_EPS_HEADER_SIZE {
+                return Err(XmpError::BadValue("Truncated DOS EPS header".to_string()));
+            }
+            let offset = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as u64;
+            let size = u32::from_le_bytes([header[8], header[9], header[10], header[11]]) as u64;
+            if offset.checked_add(size).is_none_or(|end| end > file_end) {
+                return Err(XmpError::BadValue(
+                    "Invalid DOS EPS PostScript section".to_string(),
+                ));
+            }
+            (offset, size)
+        } else if header.starts_with(PS_SIGNATURE) {
+            (0, file_end)
+        } else {
+            return Err(XmpError::BadValue(
+                "Not a valid PostScript file".to_string(),
+            ));
+        };
+
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut data = vec![0u8; size as usize];
+        reader.read_exact(&mut data)?;
+        Ok(Self { offset, data })
+    }
+
+    /// Get the main packet setting from the DSC header comments
+    fn main_packet(&self) -> MainPacket {
+        for line in self.data.split(|&b| b == b'\n' || b == b'\r') {
+            if line.is_empty() {
+                continue;
+            }
+            if !line.starts_with(b"%") || line.starts_with(b"%%EndComments") {
+                break;
+            }
+            if let Some(value) = line.strip_prefix(CONTAINS_XMP_COMMENT) {
+                let value = value.trim_ascii();
+                if value.starts_with(b"NoMain") {
+                    return MainPacket::None;
+                }
+                if value.starts_with(b"MainLast") {
+                    return MainPacket::Last;
+                }
+                return MainPacket::First;
+            }
+        }
+        MainPacket::First
+    }
+
+    /// Find all complete XMP packets, in file order
+    fn packets(&self) -> Vec<Range<usize>> {
+        let data = &self.data;
+        let mut packets = Vec::new();
+        let mut pos = 0;
+        while let Some(start) = find(&data[pos..], PACKET_START).map(|p| pos + p) {
+            let Some(end_marker) = find(&data[start..], PACKET_END).map(|p| start + p) else {
+                break;
+            };
+            let Some(end) = find(&data[end_marker..], b"?>").map(|p| end_marker + p + 2) else {
+                break;
+            };
+            packets.push(start..end);
+            pos = end;
+        }
+        packets
+    }
+
+    /// Find the main XMP packet
+    fn main_packet_range(&self) -> Option<Range<usize>> {
+        let packets = self.packets();
+        match self.main_packet() {
+            MainPacket::First => packets.first().cloned(),
+            MainPacket::Last => packets.last().cloned(),
+            MainPacket::None => None,
+        }
+    }
+}
+
+/// Find the first occurrence of `needle` in `data`
+fn find(data: &[u8], needle: &[u8]) -> Option<usize> {
+    data.windows(needle.len())
+        .position(|window| window == needle)
+}
+
+impl EpsHandler {
+    /// Read XMP metadata from an EPS or PostScript file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(XmpMeta))` if XMP metadata is found
+    /// * `Ok(None)` if no XMP metadata is found
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_xmp<R: Read + Seek>(mut reader: R) -> XmpResult<Option<XmpMeta>> {
+        let postscript = PostScript::read(&mut reader)?;
+        let Some(range) = postscript.main_packet_range() else {
+            return Ok(None);
+        };
+
+        let xmp_str = std::str::from_utf8(&postscript.data[range])
+            .map_err(|e| XmpError::ParseError(format!("Invalid UTF-8 in XMP: {}", e)))?;
+        XmpMeta::parse(xmp_str).map(Some)
+    }
+
+    /// Write XMP metadata to an EPS or PostScript file
+    ///
+    /// The main XMP packet is replaced in place. Returns
+    /// `XmpError::NotSupported` if the file has no writable main packet, or
+    /// if the new packet doesn't fit in it.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    pub fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        let postscript = PostScript::read(&mut reader)?;
+        let range = postscript.main_packet_range().ok_or_else(|| {
+            XmpError::NotSupported("Adding XMP to PostScript files without a packet".to_string())
+        })?;
+        let trailer = postscript.data[..range.end - 2].trim_ascii_end();
+        if !(trailer.ends_with(b"\"w\"") || trailer.ends_with(b"'w'")) {
+            return Err(XmpError::NotSupported(
+                "Updating a read-only PostScript XMP packet".to_string(),
+            ));
+        }
+
+        let options = SerializeOptions::default()
+            .use_compact_format()
+            .exact_packet_length(range.len());
+        let packet = meta.serialize_with_options(&options).map_err(|_| {
+            XmpError::NotSupported(
+                "XMP packet larger than the existing PostScript packet".to_string(),
+            )
+        })?;
+
+        reader.rewind()?;
+        std::io::copy(&mut reader, &mut writer)?;
+        writer.seek(SeekFrom::Start(postscript.offset + range.start as u64))?;
+        writer.write_all(packet.as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+    use std::io::Cursor;
+
+    fn packet(title: &str, padding: usize) -> String {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String(title.to_string()))
+            .unwrap();
+        let options = SerializeOptions::default()
+            .use_compact_format()
+            .padding(padding);
+        meta.serialize_with_options(&options).unwrap()
+    }
+
+    fn build_eps(contains_xmp: Option<&str>, packets: &[String]) -> Vec<u8> {
+        let mut eps = "%!PS-Adobe-3.0 EPSF-3.0\n%%BoundingBox: 0 0 100 100\n".to_string();
+        if let Some(value) = contains_xmp {
+            eps.push_str(&format!("%ADO_ContainsXMP: {}\n", value));
+        }
+        eps.push_str("%%EndComments\n");
+        for packet in packets {
+            eps.push_str("%begin_xml_packet\n");
+            eps.push_str(packet);
+            eps.push_str("\n% &&end XMP packet marker&&\n");
+        }
+        eps.push_str("showpage\n%%EOF\n");
+        eps.into_bytes()
+    }
+
+    fn title(data: &[u8]) -> Option<XmpValue> {
+        EpsHandler::read_xmp(Cursor::new(data))
+            .unwrap()
+            .and_then(|meta| meta.get_property(ns::DC, "title"))
+    }
+
+    #[test]
+    fn test_can_handle() {
+        let handler = EpsHandler;
+        assert!(handler
+            .can_handle(&mut Cursor::new(build_eps(None, &[])))
+            .unwrap());
+        assert!(!handler
+            .can_handle(&mut Cursor::new(b"%PDF-1.7\n%%EOF\n".to_vec()))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_main_packet_selection() {
+        let packets = [packet("Placed", 0), packet("Main", 0)];
+        assert_eq!(
+            title(&build_eps(Some("MainLast"), &packets)),
+            Some(XmpValue::String("Main".to_string()))
+        );
+        assert_eq!(
+            title(&build_eps(Some("MainFirst"), &packets)),
+            Some(XmpValue::String("Placed".to_string()))
+        );
+        assert_eq!(
+            title(&build_eps(None, &packets)),
+            Some(XmpValue::String("Placed".to_string()))
+        );
+        assert_eq!(title(&build_eps(Some("NoMain"), &packets)), None);
+    }
+
+    #[test]
+    fn test_write_in_place() {
+        let eps = build_eps(Some("MainFirst"), &[packet("Old", 512)]);
+        let mut writer = Cursor::new(Vec::new());
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String("New".to_string()))
+            .unwrap();
+        EpsHandler::write_xmp(Cursor::new(&eps), &mut writer, &meta).unwrap();
+
+        let data = writer.into_inner();
+        assert_eq!(data.len(), eps.len());
+        assert!(data.ends_with(b"showpage\n%%EOF\n"));
+        assert_eq!(title(&data), Some(XmpValue::String("New".to_string())));
+
+        // A packet that doesn't fit is not supported
+        meta.set_property(ns::DC, "title", XmpValue::String("x".repeat(2048)))
+            .unwrap();
+        let result = EpsHandler::write_xmp(Cursor::new(&eps), Cursor::new(Vec::new()), &meta);
+        assert!(matches!(result, Err(XmpError::NotSupported(_))));
+    }
+
+    #[test]
+    fn test_dos_eps() {
+        let postscript = build_eps(None, &[packet("Binary header", 256)]);
+        let mut eps = DOS_EPS_SIGNATURE.to_vec();
+        eps.extend_from_slice(&(DOS_EPS_HEADER_SIZE as u32).to_le_bytes());
+        eps.extend_from_slice(&(postscript.len() as u32).to_le_bytes());
+        eps.resize(DOS_EPS_HEADER_SIZE, 0);
+        eps.extend_from_slice(&postscript);
+        eps.extend_from_slice(b"TIFF preview");
+
+        assert!(EpsHandler.can_handle(&mut Cursor::new(&eps)).unwrap());
+        assert_eq!(
+            title(&eps),
+            Some(XmpValue::String("Binary header".to_string()))
+        );
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String("Updated".to_string()))
+            .unwrap();
+        let mut writer = Cursor::new(Vec::new());
+        EpsHandler::write_xmp(Cursor::new(&eps), &mut writer, &meta).unwrap();
+        let data = writer.into_inner();
+        assert!(data.ends_with(b"TIFF preview"));
+        assert_eq!(title(&data), Some(XmpValue::String("Updated".to_string())));
+    }
+}
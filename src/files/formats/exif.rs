@@ -0,0 +1,1526 @@
+//! EXIF metadata reader
+//!
+//! This module reads the native EXIF metadata of TIFF-structured data, such
+//! as TIFF files, TIFF-based camera RAW files and the APP1 segment of JPEG
+//! files, and imports it into XMP. The implementation is pure Rust and
+//! cross-platform compatible.
+//!
+//! EXIF Structure:
+//! - A TIFF header: byte order ("II" or "MM") + magic (2 bytes) + offset of
+//!   the 0th IFD (4 bytes); all offsets are relative to the header
+//! - The 0th IFD holds the primary image tags (Make, Model, Orientation, ...)
+//! - The EXIF IFD (pointer tag 0x8769) holds the capture settings
+//! - The GPS IFD (pointer tag 0x8825) holds the location
+//! - The 1st IFD, linked from the end of the 0th IFD, describes the
+//!   thumbnail; JPEG thumbnails are stored at the offset of tag 0x0201
+//!   with the length of tag 0x0202
+//! - Each IFD entry is tag (2 bytes) + type (2 bytes) + count (4 bytes) +
+//!   value or offset (4 bytes); values of up to 4 bytes are stored inline
+//!
+//! XMP Reconciliation:
+//! - Following the Adobe XMP Toolkit, EXIF values are mapped to the `tiff:`
+//!   and `exif:` schemas (and a few `xmp:` and `dc:` properties, such as
+//!   `xmp:ModifyDate` and `dc:creator`)
+//! - A value is only imported when the XMP has no corresponding property,
+//!   since the XMP is expected to be more recent
+//! - When saving, a few values (orientation, description, artist, copyright
+//!   and dates) can be exported back; tags are updated in place or appended,
+//!   so offsets used by other data (maker notes, thumbnails) stay valid
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::core::namespace::ns;
+use crate::types::value::XmpValue;
+use crate::utils::datetime::XmpDateTime;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+/// Pointer tag of the EXIF IFD
+const TAG_EXIF_IFD: u16 = 0x8769;
+
+/// Pointer tag of the GPS IFD
+const TAG_GPS_IFD: u16 = 0x8825;
+
+/// Offset of the JPEG thumbnail (1st IFD)
+const TAG_JPEG_INTERCHANGE_FORMAT: u16 = 0x0201;
+
+/// Length of the JPEG thumbnail (1st IFD)
+const TAG_JPEG_INTERCHANGE_FORMAT_LENGTH: u16 = 0x0202;
+
+/// Largest value read from an IFD entry (larger values are skipped)
+const MAX_VALUE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// How an EXIF value is converted to XMP
+#[derive(Debug, Clone, Copy)]
+enum Conversion {
+    /// ASCII text
+    Text,
+    /// ASCII text, as the `x-default` item of a language alternative
+    LangAlt,
+    /// The first integer
+    Integer,
+    /// All integers, as an ordered array
+    IntegerSeq,
+    /// The first rational, as `numerator/denominator`
+    Rational,
+    /// A version stored as 4 ASCII digits (such as "0231")
+    Version,
+    /// A date, with the EXIF IFD tags holding its sub-seconds and offset
+    Date(u16, u16),
+}
+
+/// EXIF tags mapped to XMP properties: IFD, tag, namespace, property and
+/// conversion
+const MAPPINGS: &[(ExifIfd, u16, &str, &str, Conversion)] = {
+    use Conversion::*;
+    use ExifIfd::{Exif, Gps, Primary};
+    &[
+        (Primary, 0x0100, ns::TIFF, "ImageWidth", Integer),
+        (Primary, 0x0101, ns::TIFF, "ImageLength", Integer),
+        (Primary, 0x0102, ns::TIFF, "BitsPerSample", IntegerSeq),
+        (Primary, 0x0103, ns::TIFF, "Compression", Integer),
+        (
+            Primary,
+            0x0106,
+            ns::TIFF,
+            "PhotometricInterpretation",
+            Integer,
+        ),
+        (Primary, 0x010E, ns::DC, "description", LangAlt),
+        (Primary, 0x010F, ns::TIFF, "Make", Text),
+        (Primary, 0x0110, ns::TIFF, "Model", Text),
+        (Primary, 0x0112, ns::TIFF, "Orientation", Integer),
+        (Primary, 0x0115, ns::TIFF, "SamplesPerPixel", Integer),
+        (Primary, 0x011A, ns::TIFF, "XResolution", Rational),
+        (Primary, 0x011B, ns::TIFF, "YResolution", Rational),
+        (Primary, 0x011C, ns::TIFF, "PlanarConfiguration", Integer),
+        (Primary, 0x0128, ns::TIFF, "ResolutionUnit", Integer),
+        (Primary, 0x0131, ns::TIFF, "Software", Text),
+        (Primary, 0x0132, ns::XMP, "ModifyDate", Date(0x9290, 0x9010)),
+        (Primary, 0x0213, ns::TIFF, "YCbCrPositioning", Integer),
+        (Primary, 0x8298, ns::DC, "rights", LangAlt),
+        (Exif, 0x829A, ns::EXIF, "ExposureTime", Rational),
+        (Exif, 0x829D, ns::EXIF, "FNumber", Rational),
+        (Exif, 0x8822, ns::EXIF, "ExposureProgram", Integer),
+        (Exif, 0x8824, ns::EXIF, "SpectralSensitivity", Text),
+        (Exif, 0x8827, ns::EXIF, "ISOSpeedRatings", IntegerSeq),
+        (Exif, 0x9000, ns::EXIF, "ExifVersion", Version),
+        (
+            Exif,
+            TAG_DATE_TIME_ORIGINAL,
+            ns::EXIF,
+            "DateTimeOriginal",
+            Date(0x9291, 0x9011),
+        ),
+        (
+            Exif,
+            0x9004,
+            ns::EXIF,
+            "DateTimeDigitized",
+            Date(0x9292, 0x9012),
+        ),
+        (Exif, 0x9102, ns::EXIF, "CompressedBitsPerPixel", Rational),
+        (Exif, 0x9201, ns::EXIF, "ShutterSpeedValue", Rational),
+        (Exif, 0x9202, ns::EXIF, "ApertureValue", Rational),
+        (Exif, 0x9203, ns::EXIF, "BrightnessValue", Rational),
+        (Exif, 0x9204, ns::EXIF, "ExposureBiasValue", Rational),
+        (Exif, 0x9205, ns::EXIF, "MaxApertureValue", Rational),
+        (Exif, 0x9206, ns::EXIF, "SubjectDistance", Rational),
+        (Exif, 0x9207, ns::EXIF, "MeteringMode", Integer),
+        (Exif, 0x9208, ns::EXIF, "LightSource", Integer),
+        (Exif, 0x920A, ns::EXIF, "FocalLength", Rational),
+        (Exif, 0x9214, ns::EXIF, "SubjectArea", IntegerSeq),
+        (Exif, 0xA000, ns::EXIF, "FlashpixVersion", Version),
+        (Exif, 0xA001, ns::EXIF, "ColorSpace", Integer),
+        (Exif, 0xA002, ns::EXIF, "PixelXDimension", Integer),
+        (Exif, 0xA003, ns::EXIF, "PixelYDimension", Integer),
+        (Exif, 0xA004, ns::EXIF, "RelatedSoundFile", Text),
+        (Exif, 0xA20B, ns::EXIF, "FlashEnergy", Rational),
+        (Exif, 0xA20E, ns::EXIF, "FocalPlaneXResolution", Rational),
+        (Exif, 0xA20F, ns::EXIF, "FocalPlaneYResolution", Rational),
+        (Exif, 0xA210, ns::EXIF, "FocalPlaneResolutionUnit", Integer),
+        (Exif, 0xA214, ns::EXIF, "SubjectLocation", IntegerSeq),
+        (Exif, 0xA215, ns::EXIF, "ExposureIndex", Rational),
+        (Exif, 0xA217, ns::EXIF, "SensingMethod", Integer),
+        (Exif, 0xA401, ns::EXIF, "CustomRendered", Integer),
+        (Exif, 0xA402, ns::EXIF, "ExposureMode", Integer),
+        (Exif, 0xA403, ns::EXIF, "WhiteBalance", Integer),
+        (Exif, 0xA404, ns::EXIF, "DigitalZoomRatio", Rational),
+        (Exif, 0xA405, ns::EXIF, "FocalLengthIn35mmFilm", Integer),
+        (Exif, 0xA406, ns::EXIF, "SceneCaptureType", Integer),
+        (Exif, 0xA407, ns::EXIF, "GainControl", Integer),
+        (Exif, 0xA408, ns::EXIF, "Contrast", Integer),
+        (Exif, 0xA409, ns::EXIF, "Saturation", Integer),
+        (Exif, 0xA40A, ns::EXIF, "Sharpness", Integer),
+        (Exif, 0xA40C, ns::EXIF, "SubjectDistanceRange", Integer),
+        (Exif, 0xA420, ns::EXIF, "ImageUniqueID", Text),
+        (Gps, 0x0005, ns::EXIF, "GPSAltitudeRef", Integer),
+        (Gps, 0x0006, ns::EXIF, "GPSAltitude", Rational),
+        (Gps, 0x0008, ns::EXIF, "GPSSatellites", Text),
+        (Gps, 0x0009, ns::EXIF, "GPSStatus", Text),
+        (Gps, 0x000A, ns::EXIF, "GPSMeasureMode", Text),
+        (Gps, 0x000B, ns::EXIF, "GPSDOP", Rational),
+        (Gps, 0x000C, ns::EXIF, "GPSSpeedRef", Text),
+        (Gps, 0x000D, ns::EXIF, "GPSSpeed", Rational),
+        (Gps, 0x000E, ns::EXIF, "GPSTrackRef", Text),
+        (Gps, 0x000F, ns::EXIF, "GPSTrack", Rational),
+        (Gps, 0x0010, ns::EXIF, "GPSImgDirectionRef", Text),
+        (Gps, 0x0011, ns::EXIF, "GPSImgDirection", Rational),
+        (Gps, 0x0012, ns::EXIF, "GPSMapDatum", Text),
+        (Gps, 0x0017, ns::EXIF, "GPSDestBearingRef", Text),
+        (Gps, 0x0018, ns::EXIF, "GPSDestBearing", Rational),
+        (Gps, 0x0019, ns::EXIF, "GPSDestDistanceRef", Text),
+        (Gps, 0x001A, ns::EXIF, "GPSDestDistance", Rational),
+        (Gps, 0x001E, ns::EXIF, "GPSDifferential", Integer),
+    ]
+};
+
+/// GPS coordinates: value tag, reference tag and property
+const GPS_COORDINATES: &[(u16, u16, &str)] = &[
+    (0x0002, 0x0001, "GPSLatitude"),
+    (0x0004, 0x0003, "GPSLongitude"),
+    (0x0014, 0x0013, "GPSDestLatitude"),
+    (0x0016, 0x0015, "GPSDestLongitude"),
+];
+
+/// Artist tag (0th IFD), mapped to `dc:creator`
+const TAG_ARTIST: u16 = 0x013B;
+
+/// Flash tag (EXIF IFD), mapped to the `exif:Flash` structure
+const TAG_FLASH: u16 = 0x9209;
+
+/// UserComment tag (EXIF IFD)
+const TAG_USER_COMMENT: u16 = 0x9286;
+
+/// DateTimeOriginal tag (EXIF IFD)
+const TAG_DATE_TIME_ORIGINAL: u16 = 0x9003;
+
+/// GPSVersionID tag (GPS IFD)
+const TAG_GPS_VERSION_ID: u16 = 0x0000;
+
+/// GPSTimeStamp tag (GPS IFD), combined with GPSDateStamp
+const TAG_GPS_TIME_STAMP: u16 = 0x0007;
+
+/// GPSDateStamp tag (GPS IFD)
+const TAG_GPS_DATE_STAMP: u16 = 0x001D;
+
+/// Tags exported from XMP: orientation, description, copyright and dates
+/// (with their sub-seconds and offsets); the artist is exported too
+const EXPORTED_TAGS: &[u16] = &[
+    0x0112,
+    0x010E,
+    0x8298,
+    0x0132,
+    TAG_DATE_TIME_ORIGINAL,
+    0x9004,
+];
+
+/// Empty little-endian TIFF structure, for files without EXIF
+#[cfg_attr(not(feature = "jpeg"), allow(dead_code))]
+pub(crate) const EMPTY_TIFF: &[u8] = b"II*\0\x08\0\0\0\0\0\0\0\0\0";
+
+/// The IFD an EXIF entry was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExifIfd {
+    /// The 0th IFD (primary image)
+    Primary,
+    /// The EXIF IFD
+    Exif,
+    /// The GPS IFD
+    Gps,
+    /// The 1st IFD (thumbnail image)
+    Thumbnail,
+}
+
+/// The value of an EXIF entry
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExifValue {
+    /// BYTE values
+    Byte(Vec<u8>),
+    /// ASCII string (without the trailing NUL)
+    Ascii(String),
+    /// SHORT values
+    Short(Vec<u16>),
+    /// LONG values
+    Long(Vec<u32>),
+    /// RATIONAL values (numerator, denominator)
+    Rational(Vec<(u32, u32)>),
+    /// SBYTE values
+    SByte(Vec<i8>),
+    /// UNDEFINED bytes
+    Undefined(Vec<u8>),
+    /// SSHORT values
+    SShort(Vec<i16>),
+    /// SLONG values
+    SLong(Vec<i32>),
+    /// SRATIONAL values (numerator, denominator)
+    SRational(Vec<(i32, i32)>),
+    /// FLOAT values
+    Float(Vec<f32>),
+    /// DOUBLE values
+    Double(Vec<f64>),
+}
+
+impl ExifValue {
+    /// Get the value as a string (ASCII values only)
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ExifValue::Ascii(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get the first value as an unsigned integer (BYTE, SHORT, LONG)
+    pub fn as_u32(&self) -> Option<u32> {
+        match self {
+            ExifValue::Byte(v) => v.first().map(|&v| v as u32),
+            ExifValue::Short(v) => v.first().map(|&v| v as u32),
+            ExifValue::Long(v) => v.first().copied(),
+            _ => None,
+        }
+    }
+
+    /// Get the first value as a floating point number
+    ///
+    /// Rationals with a zero denominator have no value.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ExifValue::Rational(v) => v
+                .first()
+                .filter(|(_, d)| *d != 0)
+                .map(|&(n, d)| n as f64 / d as f64),
+            ExifValue::SRational(v) => v
+                .first()
+                .filter(|(_, d)| *d != 0)
+                .map(|&(n, d)| n as f64 / d as f64),
+            ExifValue::SByte(v) => v.first().map(|&v| v as f64),
+            ExifValue::SShort(v) => v.first().map(|&v| v as f64),
+            ExifValue::SLong(v) => v.first().map(|&v| v as f64),
+            ExifValue::Float(v) => v.first().map(|&v| v as f64),
+            ExifValue::Double(v) => v.first().copied(),
+            _ => self.as_u32().map(|v| v as f64),
+        }
+    }
+}
+
+/// An EXIF entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExifEntry {
+    /// The IFD containing the entry
+    pub ifd: ExifIfd,
+    /// The tag ID
+    pub tag: u16,
+    /// The value
+    pub value: ExifValue,
+}
+
+/// EXIF metadata read from TIFF-structured data
+///
+/// Holds the entries of the 0th IFD, the EXIF IFD, the GPS IFD and the 1st
+/// IFD, and the JPEG thumbnail; the IFD pointer tags themselves are not
+/// included.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExifData {
+    entries: Vec<ExifEntry>,
+    thumbnail: Option<Vec<u8>>,
+}
+
+impl ExifData {
+    /// Read EXIF metadata from TIFF-structured data
+    ///
+    /// The reader must be positioned at the TIFF header; IFD offsets are
+    /// relative to it. Entries that cannot be read (unknown types, offsets
+    /// outside the data) are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    pub fn read<R: Read + Seek>(mut reader: R) -> XmpResult<Self> {
+        let base = reader.stream_position()?;
+        let mut header = [0u8; 8];
+        reader
+            .read_exact(&mut header)
+            .map_err(|_| XmpError::BadValue("Truncated TIFF header".to_string()))?;
+        let big_endian = match &header[..2] {
+            b"II" => false,
+            b"MM" => true,
+            _ => return Err(XmpError::BadValue("Invalid TIFF byte order".to_string())),
+        };
+
+        let mut parser = IfdParser {
+            reader,
+            base,
+            big_endian,
+        };
+        let ifd0_offset = parser.u32(&header[4..8]);
+        let mut data = ExifData::default();
+        let pointers = parser.read_ifd(ifd0_offset, ExifIfd::Primary, &mut data.entries)?;
+        for (ifd, offset) in pointers {
+            // Broken sub-IFDs do not invalidate the primary entries
+            let _ = parser.read_ifd(offset, ifd, &mut data.entries);
+        }
+
+        let thumbnail_tag = |tag| {
+            data.get(ExifIfd::Thumbnail, tag)
+                .and_then(ExifValue::as_u32)
+        };
+        if let (Some(offset), Some(length)) = (
+            thumbnail_tag(TAG_JPEG_INTERCHANGE_FORMAT),
+            thumbnail_tag(TAG_JPEG_INTERCHANGE_FORMAT_LENGTH),
+        ) {
+            data.thumbnail = parser.read_bytes(offset, length);
+        }
+        Ok(data)
+    }
+
+    /// Get the JPEG thumbnail, if any
+    pub fn thumbnail(&self) -> Option<&[u8]> {
+        self.thumbnail.as_deref()
+    }
+
+    /// Get the value of a tag
+    pub fn get(&self, ifd: ExifIfd, tag: u16) -> Option<&ExifValue> {
+        self.entries
+            .iter()
+            .find(|entry| entry.ifd == ifd && entry.tag == tag)
+            .map(|entry| &entry.value)
+    }
+
+    /// Get all entries, in file order
+    pub fn entries(&self) -> &[ExifEntry] {
+        &self.entries
+    }
+
+    /// Check if there are no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Import the EXIF values into XMP
+    ///
+    /// Values are mapped to the `tiff:` and `exif:` schemas, `xmp:ModifyDate`,
+    /// `dc:description`, `dc:rights` and `dc:creator`. A value is only
+    /// imported when the XMP has no corresponding property.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` if any property was imported
+    /// * `Ok(false)` otherwise
+    /// * `Err(XmpError)` if an error occurs
+    pub fn import_into(&self, meta: &mut XmpMeta) -> XmpResult<bool> {
+        let mut imported = false;
+
+        for &(ifd, tag, namespace, name, conversion) in MAPPINGS {
+            if meta.does_property_exist(namespace, name) {
+                continue;
+            }
+            let Some(value) = self.get(ifd, tag) else {
+                continue;
+            };
+            imported |= self.import_value(meta, value, namespace, name, conversion)?;
+        }
+
+        if let Some(artist) = self.text(ExifIfd::Primary, TAG_ARTIST) {
+            if !meta.does_property_exist(ns::DC, "creator") {
+                for creator in artist.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+                    meta.append_array_item(ns::DC, "creator", creator.into())?;
+                    imported = true;
+                }
+            }
+        }
+
+        if let Some(flash) = self.get(ExifIfd::Exif, TAG_FLASH).and_then(|v| v.as_u32()) {
+            if !meta.does_property_exist(ns::EXIF, "Flash") {
+                let fields = [
+                    ("Fired", XmpValue::Boolean(flash & 0x01 != 0)),
+                    ("Return", XmpValue::Integer(((flash >> 1) & 0x03) as i64)),
+                    ("Mode", XmpValue::Integer(((flash >> 3) & 0x03) as i64)),
+                    ("Function", XmpValue::Boolean(flash & 0x20 != 0)),
+                    ("RedEyeMode", XmpValue::Boolean(flash & 0x40 != 0)),
+                ];
+                for (field, value) in fields {
+                    meta.set_struct_field(ns::EXIF, "Flash", ns::EXIF, field, value)?;
+                }
+                imported = true;
+            }
+        }
+
+        if let Some(ExifValue::Undefined(comment)) = self.get(ExifIfd::Exif, TAG_USER_COMMENT) {
+            if !meta.does_property_exist(ns::EXIF, "UserComment") {
+                if let Some(comment) = decode_user_comment(comment) {
+                    meta.set_localized_text(ns::EXIF, "UserComment", "", "x-default", &comment)?;
+                    imported = true;
+                }
+            }
+        }
+
+        if let Some(ExifValue::Byte(version)) = self.get(ExifIfd::Gps, TAG_GPS_VERSION_ID) {
+            if !meta.does_property_exist(ns::EXIF, "GPSVersionID") && !version.is_empty() {
+                let version: Vec<String> = version.iter().map(u8::to_string).collect();
+                meta.set_property(ns::EXIF, "GPSVersionID", version.join(".").into())?;
+                imported = true;
+            }
+        }
+
+        for &(tag, ref_tag, name) in GPS_COORDINATES {
+            if meta.does_property_exist(ns::EXIF, name) {
+                continue;
+            }
+            let coordinate = match (
+                self.get(ExifIfd::Gps, tag),
+                self.text(ExifIfd::Gps, ref_tag),
+            ) {
+                (Some(ExifValue::Rational(parts)), Some(reference)) => {
+                    format_gps_coordinate(parts, reference)
+                }
+                _ => None,
+            };
+            if let Some(coordinate) = coordinate {
+                meta.set_property(ns::EXIF, name, coordinate.into())?;
+                imported = true;
+            }
+        }
+
+        if !meta.does_property_exist(ns::EXIF, "GPSTimeStamp") {
+            if let Some(timestamp) = self.gps_timestamp() {
+                meta.set_property(ns::EXIF, "GPSTimeStamp", XmpValue::DateTime(timestamp))?;
+                imported = true;
+            }
+        }
+
+        Ok(imported)
+    }
+
+    /// Import a value with a table conversion
+    fn import_value(
+        &self,
+        meta: &mut XmpMeta,
+        value: &ExifValue,
+        namespace: &str,
+        name: &str,
+        conversion: Conversion,
+    ) -> XmpResult<bool> {
+        let value = match conversion {
+            Conversion::Text => trimmed_text(value).map(XmpValue::from),
+            Conversion::LangAlt => {
+                let Some(text) = trimmed_text(value) else {
+                    return Ok(false);
+                };
+                meta.set_localized_text(namespace, name, "", "x-default", text)?;
+                return Ok(true);
+            }
+            Conversion::Integer => value.as_u32().map(|v| XmpValue::Integer(v as i64)),
+            Conversion::IntegerSeq => {
+                let integers = integers(value);
+                for &integer in &integers {
+                    meta.append_array_item(namespace, name, XmpValue::Integer(integer as i64))?;
+                }
+                return Ok(!integers.is_empty());
+            }
+            Conversion::Rational => format_rational(value).map(XmpValue::String),
+            Conversion::Version => match value {
+                ExifValue::Undefined(bytes) | ExifValue::Byte(bytes) if bytes.len() == 4 => Some(
+                    XmpValue::String(String::from_utf8_lossy(bytes).into_owned()),
+                ),
+                _ => None,
+            },
+            Conversion::Date(sub_sec_tag, offset_tag) => value
+                .as_str()
+                .and_then(|date| {
+                    format_date(
+                        date,
+                        self.text(ExifIfd::Exif, sub_sec_tag),
+                        self.text(ExifIfd::Exif, offset_tag),
+                    )
+                })
+                .and_then(|date| date.parse().ok())
+                .map(XmpValue::DateTime),
+        };
+
+        match value {
+            Some(value) => {
+                meta.set_property(namespace, name, value)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Get a trimmed, non-empty ASCII value
+    fn text(&self, ifd: ExifIfd, tag: u16) -> Option<&str> {
+        self.get(ifd, tag).and_then(trimmed_text)
+    }
+
+    /// Combine GPSDateStamp (or the date of DateTimeOriginal) and
+    /// GPSTimeStamp into a UTC date
+    fn gps_timestamp(&self) -> Option<XmpDateTime> {
+        let Some(ExifValue::Rational(time)) = self.get(ExifIfd::Gps, TAG_GPS_TIME_STAMP) else {
+            return None;
+        };
+        let date = self
+            .text(ExifIfd::Gps, TAG_GPS_DATE_STAMP)
+            .or_else(|| self.text(ExifIfd::Exif, TAG_DATE_TIME_ORIGINAL))?;
+        let date = parse_exif_date(date.get(..10)?)?;
+
+        let part = |index: usize| -> Option<f64> {
+            let &(n, d) = time.get(index)?;
+            (d != 0).then(|| n as f64 / d as f64)
+        };
+        let (hours, minutes, seconds) = (part(0)?, part(1)?, part(2)?);
+        let seconds = if seconds.fract() == 0.0 {
+            format!("{:02}", seconds as u32)
+        } else {
+            let formatted = format!("{:06.3}", seconds);
+            formatted.trim_end_matches('0').to_string()
+        };
+        format!(
+            "{}T{:02}:{:02}:{}Z",
+            date, hours as u32, minutes as u32, seconds
+        )
+        .parse()
+        .ok()
+    }
+}
+
+/// Get an ASCII value with surrounding spaces removed, if not empty
+fn trimmed_text(value: &ExifValue) -> Option<&str> {
+    value.as_str().map(str::trim).filter(|s| !s.is_empty())
+}
+
+/// Get all unsigned integers of a value
+fn integers(value: &ExifValue) -> Vec<u32> {
+    match value {
+        ExifValue::Byte(v) => v.iter().map(|&v| v as u32).collect(),
+        ExifValue::Short(v) => v.iter().map(|&v| v as u32).collect(),
+        ExifValue::Long(v) => v.clone(),
+        _ => Vec::new(),
+    }
+}
+
+/// Format the first rational of a value as `numerator/denominator`
+fn format_rational(value: &ExifValue) -> Option<String> {
+    match value {
+        ExifValue::Rational(v) => v.first().map(|(n, d)| format!("{}/{}", n, d)),
+        ExifValue::SRational(v) => v.first().map(|(n, d)| format!("{}/{}", n, d)),
+        _ => None,
+    }
+}
+
+/// Parse the date part of an EXIF date ("YYYY:MM:DD") as "YYYY-MM-DD"
+///
+/// Returns `None` for malformed or unknown (all zero) dates.
+fn parse_exif_date(date: &str) -> Option<String> {
+    let bytes = date.as_bytes();
+    let valid = bytes.len() == 10
+        && (bytes[4] == b':' || bytes[4] == b'-')
+        && bytes[7] == bytes[4]
+        && [0..4, 5..7, 8..10]
+            .into_iter()
+            .all(|range| bytes[range].iter().all(u8::is_ascii_digit));
+    if !valid || &date[..4] == "0000" {
+        return None;
+    }
+    Some(format!("{}-{}-{}", &date[..4], &date[5..7], &date[8..10]))
+}
+
+/// Format an EXIF date ("YYYY:MM:DD HH:MM:SS") as an XMP date, with optional
+/// sub-seconds and time offset ("+HH:MM")
+fn format_date(date: &str, sub_sec: Option<&str>, offset: Option<&str>) -> Option<String> {
+    let day = parse_exif_date(date.get(..10)?)?;
+    let time = date.get(11..19)?;
+    let time_bytes = time.as_bytes();
+    let valid_time = time_bytes[2] == b':'
+        && time_bytes[5] == b':'
+        && [0..2, 3..5, 6..8]
+            .into_iter()
+            .all(|range| time_bytes[range].iter().all(u8::is_ascii_digit));
+    if !valid_time {
+        return Some(day);
+    }
+
+    let mut result = format!("{}T{}", day, time);
+    if let Some(sub_sec) = sub_sec.filter(|s| s.bytes().all(|b| b.is_ascii_digit())) {
+        result.push('.');
+        result.push_str(sub_sec);
+    }
+    if let Some(offset) = offset {
+        let bytes = offset.as_bytes();
+        let valid_offset = bytes.len() == 6
+            && (bytes[0] == b'+' || bytes[0] == b'-')
+            && bytes[3] == b':'
+            && [1..3, 4..6]
+                .into_iter()
+                .all(|range| bytes[range].iter().all(u8::is_ascii_digit));
+        if valid_offset {
+            result.push_str(offset);
+        }
+    }
+    Some(result)
+}
+
+/// Format a GPS coordinate (degrees, minutes and seconds) as an XMP GPS
+/// coordinate: "DDD,MM,SSk" for whole values, "DDD,MM.mmk" otherwise
+fn format_gps_coordinate(parts: &[(u32, u32)], reference: &str) -> Option<String> {
+    let reference = reference.chars().next().filter(|c| "NSEW".contains(*c))?;
+    let &(deg_num, deg_den) = parts.first()?;
+    if deg_den == 0 {
+        return None;
+    }
+    let part = |index: usize| parts.get(index).copied().unwrap_or((0, 1));
+    let (min_num, min_den) = part(1);
+    let (sec_num, sec_den) = part(2);
+
+    let whole = |n: u32, d: u32| d != 0 && n.is_multiple_of(d);
+    if whole(deg_num, deg_den) && whole(min_num, min_den) && whole(sec_num, sec_den) {
+        return Some(format!(
+            "{},{},{}{}",
+            deg_num / deg_den,
+            min_num / min_den,
+            sec_num / sec_den,
+            reference
+        ));
+    }
+
+    let ratio = |n: u32, d: u32| if d == 0 { 0.0 } else { n as f64 / d as f64 };
+    let degrees = ratio(deg_num, deg_den);
+    let minutes =
+        (degrees.fract() * 60.0) + ratio(min_num, min_den) + ratio(sec_num, sec_den) / 60.0;
+    let minutes = format!("{:.8}", minutes);
+    let minutes = minutes.trim_end_matches('0').trim_end_matches('.');
+    Some(format!(
+        "{},{}{}",
+        degrees.trunc() as u32,
+        minutes,
+        reference
+    ))
+}
+
+/// Decode a UserComment: 8 bytes of character code followed by the text
+fn decode_user_comment(comment: &[u8]) -> Option<String> {
+    if comment.len() < 8 {
+        return None;
+    }
+    let (code, text) = comment.split_at(8);
+    let text = match code {
+        b"UNICODE\0" => {
+            // UCS-2, usually in the byte order of the EXIF data: guess it
+            // from the position of the zero bytes
+            let zeros_first = text.iter().step_by(2).filter(|&&b| b == 0).count();
+            let zeros_second = text.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+            let units = text.chunks_exact(2).map(|b| {
+                if zeros_first >= zeros_second {
+                    u16::from_be_bytes([b[0], b[1]])
+                } else {
+                    u16::from_le_bytes([b[0], b[1]])
+                }
+            });
+            String::from_utf16_lossy(&units.collect::<Vec<_>>())
+        }
+        // ASCII, or undefined (often UTF-8 in practice)
+        _ => String::from_utf8_lossy(text).into_owned(),
+    };
+    let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Export XMP values to the EXIF tags of TIFF-structured data
+///
+/// Updates the tags mapped from `tiff:Orientation`, `dc:description`,
+/// `dc:rights`, `dc:creator`, `xmp:ModifyDate`, `exif:DateTimeOriginal` and
+/// `exif:DateTimeDigitized` that differ from the XMP. Tags without a
+/// corresponding XMP property are left unchanged.
+///
+/// Returns `true` if `tiff` was modified.
+pub(crate) fn export_exif(tiff: &mut Vec<u8>, meta: &XmpMeta) -> XmpResult<bool> {
+    let exif = ExifData::read(Cursor::new(tiff.as_slice()))?;
+    let updates: Vec<_> = exif_updates(meta)
+        .into_iter()
+        .filter(|(ifd, tag, value)| exif.get(*ifd, *tag) != Some(value))
+        .collect();
+    if updates.is_empty() {
+        return Ok(false);
+    }
+    TiffEditor::new(tiff)?.update(&updates)?;
+    Ok(true)
+}
+
+/// Get the EXIF values of the exported XMP properties
+fn exif_updates(meta: &XmpMeta) -> Vec<(ExifIfd, u16, ExifValue)> {
+    let mut updates = Vec::new();
+    for &(ifd, tag, namespace, name, conversion) in MAPPINGS {
+        if !EXPORTED_TAGS.contains(&tag) {
+            continue;
+        }
+        match conversion {
+            Conversion::Integer => {
+                let value = meta.get_property_i64(namespace, name);
+                if let Some(value) = value.and_then(|v| u16::try_from(v).ok()) {
+                    updates.push((ifd, tag, ExifValue::Short(vec![value])));
+                }
+            }
+            Conversion::LangAlt => {
+                if let Some((text, _)) = meta.get_localized_text(namespace, name, "", "x-default") {
+                    updates.push((ifd, tag, ExifValue::Ascii(text)));
+                }
+            }
+            Conversion::Date(sub_sec_tag, offset_tag) => {
+                let date = match meta.get_property(namespace, name) {
+                    Some(XmpValue::String(date)) => date,
+                    Some(XmpValue::DateTime(date)) => date.format(),
+                    _ => continue,
+                };
+                let Some((date, sub_sec, offset)) = parse_xmp_date(&date) else {
+                    continue;
+                };
+                updates.push((ifd, tag, ExifValue::Ascii(date)));
+                if let Some(sub_sec) = sub_sec {
+                    updates.push((ExifIfd::Exif, sub_sec_tag, ExifValue::Ascii(sub_sec)));
+                }
+                if let Some(offset) = offset {
+                    updates.push((ExifIfd::Exif, offset_tag, ExifValue::Ascii(offset)));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let creators: Vec<String> = (0..meta.count_array_items(ns::DC, "creator"))
+        .filter_map(|index| meta.get_array_item(ns::DC, "creator", index))
+        .filter_map(|value| value.as_str().map(str::to_string))
+        .collect();
+    if !creators.is_empty() {
+        updates.push((
+            ExifIfd::Primary,
+            TAG_ARTIST,
+            ExifValue::Ascii(creators.join("; ")),
+        ));
+    }
+    updates
+}
+
+/// Parse an XMP date as an EXIF date ("YYYY:MM:DD HH:MM:SS"), sub-seconds
+/// and time offset ("+HH:MM")
+///
+/// Returns `None` for dates without a day; a missing time is written as
+/// midnight.
+fn parse_xmp_date(date: &str) -> Option<(String, Option<String>, Option<String>)> {
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let (day, time) = date.split_once('T').unwrap_or((date, ""));
+    let mut parts = day.splitn(3, '-');
+    let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+    if year.len() != 4
+        || month.len() != 2
+        || day.len() != 2
+        || ![year, month, day].into_iter().all(digits)
+    {
+        return None;
+    }
+
+    // Split the time zone designator off the time
+    let (time, offset) = match time.find(['Z', '+', '-']) {
+        Some(pos) if &time[pos..] == "Z" => (&time[..pos], Some("+00:00".to_string())),
+        Some(pos) => (&time[..pos], Some(time[pos..].to_string())),
+        None => (time, None),
+    };
+    let offset = offset.filter(|offset| {
+        offset.len() == 6
+            && offset.as_bytes()[3] == b':'
+            && digits(&offset[1..3])
+            && digits(&offset[4..6])
+    });
+
+    let (time, sub_sec) = match time.split_once('.') {
+        Some((time, fraction)) => (time, Some(fraction.to_string()).filter(|f| digits(f))),
+        None => (time, None),
+    };
+    let mut fields = time.split(':');
+    let mut field = || {
+        fields
+            .next()
+            .filter(|f| f.len() == 2 && digits(f))
+            .unwrap_or("00")
+    };
+    let (hour, minute, second) = (field(), field(), field());
+
+    Some((
+        format!("{}:{}:{} {}:{}:{}", year, month, day, hour, minute, second),
+        sub_sec,
+        offset,
+    ))
+}
+
+/// Size of one value of an IFD entry type, or `None` for unknown types
+fn type_size(type_: u16) -> Option<u64> {
+    match type_ {
+        1 | 2 | 6 | 7 => Some(1),
+        3 | 8 => Some(2),
+        4 | 9 | 11 => Some(4),
+        5 | 10 | 12 => Some(8),
+        _ => None,
+    }
+}
+
+/// Edits the IFDs of TIFF-structured data in memory
+///
+/// Values that fit in their current space are overwritten in place. Larger
+/// values, and IFDs that gain entries, are appended at the end of the data
+/// and the offsets pointing to them are updated; nothing else moves.
+struct TiffEditor<'a> {
+    data: &'a mut Vec<u8>,
+    big_endian: bool,
+}
+
+impl<'a> TiffEditor<'a> {
+    fn new(data: &'a mut Vec<u8>) -> XmpResult<Self> {
+        let big_endian = match data.get(..2) {
+            Some(b"II") => false,
+            Some(b"MM") => true,
+            _ => return Err(XmpError::BadValue("Invalid TIFF byte order".to_string())),
+        };
+        if data.len() < 8 {
+            return Err(XmpError::BadValue("Truncated TIFF header".to_string()));
+        }
+        Ok(Self { data, big_endian })
+    }
+
+    /// Update the tags of the 0th and EXIF IFDs (GPS tags are not
+    /// supported), creating the EXIF IFD if needed
+    fn update(&mut self, updates: &[(ExifIfd, u16, ExifValue)]) -> XmpResult<()> {
+        let select = |wanted: ExifIfd| -> Vec<(u16, ExifValue)> {
+            updates
+                .iter()
+                .filter(|(ifd, _, _)| *ifd == wanted)
+                .map(|(_, tag, value)| (*tag, value.clone()))
+                .collect()
+        };
+        let exif_updates = select(ExifIfd::Exif);
+        let mut primary_updates = select(ExifIfd::Primary);
+        let ifd0 = self.u32_at(4);
+
+        if !exif_updates.is_empty() {
+            let pointer = self
+                .find_entry(ifd0, TAG_EXIF_IFD)?
+                .map(|pos| self.u32_at(pos + 8));
+            let exif_ifd = self.update_ifd(pointer, &exif_updates)?;
+            if pointer != Some(exif_ifd) {
+                primary_updates.push((TAG_EXIF_IFD, ExifValue::Long(vec![exif_ifd])));
+            }
+        }
+        if !primary_updates.is_empty() {
+            let new_ifd0 = self.update_ifd(Some(ifd0), &primary_updates)?;
+            let bytes = self.u32_bytes(new_ifd0);
+            self.data[4..8].copy_from_slice(&bytes);
+        }
+        Ok(())
+    }
+
+    /// Get the position of the entries of an IFD, and their count
+    fn ifd_entries(&self, offset: u32) -> XmpResult<(usize, usize)> {
+        let offset = offset as usize;
+        if offset + 2 > self.data.len() {
+            return Err(XmpError::BadValue("Invalid TIFF IFD offset".to_string()));
+        }
+        let count = self.u16_at(offset) as usize;
+        if offset + 2 + count * 12 + 4 > self.data.len() {
+            return Err(XmpError::BadValue("Truncated TIFF IFD".to_string()));
+        }
+        Ok((offset + 2, count))
+    }
+
+    /// Find the position of the entry of a tag in an IFD
+    fn find_entry(&self, ifd: u32, tag: u16) -> XmpResult<Option<usize>> {
+        let (start, count) = self.ifd_entries(ifd)?;
+        Ok((0..count)
+            .map(|index| start + index * 12)
+            .find(|&pos| self.u16_at(pos) == tag))
+    }
+
+    /// Update the entries of an IFD, or create it if `offset` is `None`
+    ///
+    /// Returns the offset of the IFD, which changes when entries are added.
+    fn update_ifd(&mut self, offset: Option<u32>, updates: &[(u16, ExifValue)]) -> XmpResult<u32> {
+        let (start, count, next) = match offset {
+            Some(offset) => {
+                let (start, count) = self.ifd_entries(offset)?;
+                (start, count, self.u32_at(start + count * 12))
+            }
+            None => (0, 0, 0),
+        };
+
+        let mut added = Vec::new();
+        for (tag, value) in updates {
+            let (type_, value_count, bytes) = self.encode(value);
+            let existing = (0..count)
+                .map(|index| start + index * 12)
+                .find(|&pos| self.u16_at(pos) == *tag);
+
+            let mut entry = [0u8; 12];
+            entry[0..2].copy_from_slice(&self.u16_bytes(*tag));
+            entry[2..4].copy_from_slice(&self.u16_bytes(type_));
+            entry[4..8].copy_from_slice(&self.u32_bytes(value_count));
+            if bytes.len() <= 4 {
+                entry[8..8 + bytes.len()].copy_from_slice(&bytes);
+            } else {
+                // Reuse the space of the old value if the new one fits
+                let reusable = existing.and_then(|pos| {
+                    let old_size = type_size(self.u16_at(pos + 2))? * self.u32_at(pos + 4) as u64;
+                    let old_offset = self.u32_at(pos + 8);
+                    (old_size > 4
+                        && old_size >= bytes.len() as u64
+                        && old_offset as u64 + old_size <= self.data.len() as u64)
+                        .then_some(old_offset)
+                });
+                let value_offset = match reusable {
+                    Some(value_offset) => {
+                        let pos = value_offset as usize;
+                        self.data[pos..pos + bytes.len()].copy_from_slice(&bytes);
+                        value_offset
+                    }
+                    None => self.append(&bytes)?,
+                };
+                entry[8..12].copy_from_slice(&self.u32_bytes(value_offset));
+            }
+
+            match existing {
+                Some(pos) => self.data[pos..pos + 12].copy_from_slice(&entry),
+                None => added.push(entry),
+            }
+        }
+
+        if let (Some(offset), true) = (offset, added.is_empty()) {
+            return Ok(offset);
+        }
+
+        // Write a new IFD with the added entries, sorted by tag
+        let mut entries: Vec<[u8; 12]> = (0..count)
+            .map(|index| {
+                let pos = start + index * 12;
+                let mut entry = [0u8; 12];
+                entry.copy_from_slice(&self.data[pos..pos + 12]);
+                entry
+            })
+            .collect();
+        entries.extend(added);
+        entries.sort_by_key(|entry| self.u16_at_slice(&entry[0..2]));
+
+        let mut ifd = self.u16_bytes(entries.len() as u16).to_vec();
+        for entry in &entries {
+            ifd.extend_from_slice(entry);
+        }
+        ifd.extend_from_slice(&self.u32_bytes(next));
+        self.append(&ifd)
+    }
+
+    /// Append bytes at a word boundary, returning their offset
+    fn append(&mut self, bytes: &[u8]) -> XmpResult<u32> {
+        if !self.data.len().is_multiple_of(2) {
+            self.data.push(0);
+        }
+        let offset = u32::try_from(self.data.len())
+            .ok()
+            .filter(|offset| offset.checked_add(bytes.len() as u32).is_some())
+            .ok_or_else(|| XmpError::NotSupported("TIFF data larger than 4 GB".to_string()))?;
+        self.data.extend_from_slice(bytes);
+        Ok(offset)
+    }
+
+    /// Encode a value as an entry type, count and bytes
+    fn encode(&self, value: &ExifValue) -> (u16, u32, Vec<u8>) {
+        let mut bytes = Vec::new();
+        let (type_, count) = match value {
+            ExifValue::Byte(v) => {
+                bytes.extend_from_slice(v);
+                (1, v.len())
+            }
+            ExifValue::Ascii(s) => {
+                bytes.extend_from_slice(s.as_bytes());
+                bytes.push(0);
+                (2, bytes.len())
+            }
+            ExifValue::Short(v) => {
+                v.iter().for_each(|&v| bytes.extend(self.u16_bytes(v)));
+                (3, v.len())
+            }
+            ExifValue::Long(v) => {
+                v.iter().for_each(|&v| bytes.extend(self.u32_bytes(v)));
+                (4, v.len())
+            }
+            ExifValue::Rational(v) => {
+                for &(n, d) in v {
+                    bytes.extend(self.u32_bytes(n));
+                    bytes.extend(self.u32_bytes(d));
+                }
+                (5, v.len())
+            }
+            ExifValue::SByte(v) => {
+                bytes.extend(v.iter().map(|&v| v as u8));
+                (6, v.len())
+            }
+            ExifValue::Undefined(v) => {
+                bytes.extend_from_slice(v);
+                (7, v.len())
+            }
+            ExifValue::SShort(v) => {
+                v.iter()
+                    .for_each(|&v| bytes.extend(self.u16_bytes(v as u16)));
+                (8, v.len())
+            }
+            ExifValue::SLong(v) => {
+                v.iter()
+                    .for_each(|&v| bytes.extend(self.u32_bytes(v as u32)));
+                (9, v.len())
+            }
+            ExifValue::SRational(v) => {
+                for &(n, d) in v {
+                    bytes.extend(self.u32_bytes(n as u32));
+                    bytes.extend(self.u32_bytes(d as u32));
+                }
+                (10, v.len())
+            }
+            ExifValue::Float(v) => {
+                v.iter()
+                    .for_each(|&v| bytes.extend(self.u32_bytes(v.to_bits())));
+                (11, v.len())
+            }
+            ExifValue::Double(v) => {
+                for &v in v {
+                    let bits = v.to_bits();
+                    let (high, low) = ((bits >> 32) as u32, bits as u32);
+                    if self.big_endian {
+                        bytes.extend(self.u32_bytes(high));
+                        bytes.extend(self.u32_bytes(low));
+                    } else {
+                        bytes.extend(self.u32_bytes(low));
+                        bytes.extend(self.u32_bytes(high));
+                    }
+                }
+                (12, v.len())
+            }
+        };
+        (type_, count as u32, bytes)
+    }
+
+    fn u16_at(&self, pos: usize) -> u16 {
+        self.u16_at_slice(&self.data[pos..pos + 2])
+    }
+
+    fn u16_at_slice(&self, bytes: &[u8]) -> u16 {
+        let bytes = [bytes[0], bytes[1]];
+        if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        }
+    }
+
+    fn u32_at(&self, pos: usize) -> u32 {
+        let bytes = [
+            self.data[pos],
+            self.data[pos + 1],
+            self.data[pos + 2],
+            self.data[pos + 3],
+        ];
+        if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    }
+
+    fn u16_bytes(&self, value: u16) -> [u8; 2] {
+        if self.big_endian {
+            value.to_be_bytes()
+        } else {
+            value.to_le_bytes()
+        }
+    }
+
+    fn u32_bytes(&self, value: u32) -> [u8; 4] {
+        if self.big_endian {
+            value.to_be_bytes()
+        } else {
+            value.to_le_bytes()
+        }
+    }
+}
+
+/// Reads IFDs relative to a TIFF header
+struct IfdParser<R> {
+    reader: R,
+    base: u64,
+    big_endian: bool,
+}
+
+impl<R: Read + Seek> IfdParser<R> {
+    /// Read the entries of an IFD, returning the sub-IFD pointers it contains
+    ///
+    /// The 0th IFD also points to the 1st IFD that follows it.
+    fn read_ifd(
+        &mut self,
+        offset: u32,
+        ifd: ExifIfd,
+        entries: &mut Vec<ExifEntry>,
+    ) -> XmpResult<Vec<(ExifIfd, u32)>> {
+        self.reader
+            .seek(SeekFrom::Start(self.base + offset as u64))?;
+        let mut count = [0u8; 2];
+        self.reader.read_exact(&mut count)?;
+        let mut raw = vec![0u8; self.u16(&count) as usize * 12];
+        self.reader.read_exact(&mut raw)?;
+
+        let mut pointers = Vec::new();
+        for entry in raw.chunks_exact(12) {
+            let tag = self.u16(&entry[0..2]);
+            let type_ = self.u16(&entry[2..4]);
+            let count = self.u32(&entry[4..8]);
+            match (ifd, tag) {
+                (ExifIfd::Primary, TAG_EXIF_IFD) => {
+                    pointers.push((ExifIfd::Exif, self.u32(&entry[8..12])));
+                }
+                (ExifIfd::Primary, TAG_GPS_IFD) => {
+                    pointers.push((ExifIfd::Gps, self.u32(&entry[8..12])));
+                }
+                _ => {
+                    if let Some(value) = self.read_value(type_, count, &entry[8..12])? {
+                        entries.push(ExifEntry { ifd, tag, value });
+                    }
+                }
+            }
+        }
+        if ifd == ExifIfd::Primary {
+            let mut next = [0u8; 4];
+            if self.reader.read_exact(&mut next).is_ok() && self.u32(&next) != 0 {
+                pointers.push((ExifIfd::Thumbnail, self.u32(&next)));
+            }
+        }
+        Ok(pointers)
+    }
+
+    /// Read `length` bytes at `offset`, or `None` if they cannot be read
+    fn read_bytes(&mut self, offset: u32, length: u32) -> Option<Vec<u8>> {
+        if length as u64 > MAX_VALUE_SIZE {
+            return None;
+        }
+        self.reader
+            .seek(SeekFrom::Start(self.base + offset as u64))
+            .ok()?;
+        let mut bytes = vec![0u8; length as usize];
+        self.reader.read_exact(&mut bytes).ok()?;
+        Some(bytes)
+    }
+
+    /// Read the value of an entry, or `None` if it cannot be read
+    fn read_value(
+        &mut self,
+        type_: u16,
+        count: u32,
+        inline: &[u8],
+    ) -> XmpResult<Option<ExifValue>> {
+        let Some(unit) = type_size(type_) else {
+            return Ok(None);
+        };
+        let size = count as u64 * unit;
+        if size > MAX_VALUE_SIZE {
+            return Ok(None);
+        }
+
+        let bytes = if size <= 4 {
+            inline[..size as usize].to_vec()
+        } else {
+            let offset = self.u32(inline) as u64;
+            self.reader.seek(SeekFrom::Start(self.base + offset))?;
+            let mut bytes = vec![0u8; size as usize];
+            if self.reader.read_exact(&mut bytes).is_err() {
+                return Ok(None);
+            }
+            bytes
+        };
+
+        let value = match type_ {
+            1 => ExifValue::Byte(bytes),
+            2 => {
+                let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+                ExifValue::Ascii(String::from_utf8_lossy(&bytes[..end]).into_owned())
+            }
+            3 => ExifValue::Short(bytes.chunks_exact(2).map(|b| self.u16(b)).collect()),
+            4 => ExifValue::Long(bytes.chunks_exact(4).map(|b| self.u32(b)).collect()),
+            5 => ExifValue::Rational(
+                bytes
+                    .chunks_exact(8)
+                    .map(|b| (self.u32(&b[..4]), self.u32(&b[4..])))
+                    .collect(),
+            ),
+            6 => ExifValue::SByte(bytes.iter().map(|&b| b as i8).collect()),
+            8 => ExifValue::SShort(bytes.chunks_exact(2).map(|b| self.u16(b) as i16).collect()),
+            9 => ExifValue::SLong(bytes.chunks_exact(4).map(|b| self.u32(b) as i32).collect()),
+            10 => ExifValue::SRational(
+                bytes
+                    .chunks_exact(8)
+                    .map(|b| (self.u32(&b[..4]) as i32, self.u32(&b[4..]) as i32))
+                    .collect(),
+            ),
+            11 => ExifValue::Float(
+                bytes
+                    .chunks_exact(4)
+                    .map(|b| f32::from_bits(self.u32(b)))
+                    .collect(),
+            ),
+            12 => ExifValue::Double(
+                bytes
+                    .chunks_exact(8)
+                    .map(|b| f64::from_bits(self.u64(b)))
+                    .collect(),
+            ),
+            _ => ExifValue::Undefined(bytes),
+        };
+        Ok(Some(value))
+    }
+
+    fn u16(&self, bytes: &[u8]) -> u16 {
+        let bytes = [bytes[0], bytes[1]];
+        if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        }
+    }
+
+    fn u32(&self, bytes: &[u8]) -> u32 {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    }
+
+    fn u64(&self, bytes: &[u8]) -> u64 {
+        let mut array = [0u8; 8];
+        array.copy_from_slice(&bytes[..8]);
+        if self.big_endian {
+            u64::from_be_bytes(array)
+        } else {
+            u64::from_le_bytes(array)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Big-endian TIFF with Make, Orientation, an EXIF IFD and a GPS IFD
+    fn build_exif() -> Vec<u8> {
+        let mut data = b"MM\x00\x2a\x00\x00\x00\x08".to_vec();
+        // IFD0 at 8: 4 entries
+        data.extend_from_slice(&4u16.to_be_bytes());
+        let entry = |tag: u16, type_: u16, count: u32, value: u32| {
+            let mut e = tag.to_be_bytes().to_vec();
+            e.extend_from_slice(&type_.to_be_bytes());
+            e.extend_from_slice(&count.to_be_bytes());
+            e.extend_from_slice(&value.to_be_bytes());
+            e
+        };
+        // IFD0 ends at 8 + 2 + 48 + 4 = 62
+        data.extend(entry(0x010F, 2, 6, 62)); // Make -> "Canon\0"
+        data.extend(entry(0x0112, 3, 1, 6 << 16)); // Orientation (inline SHORT)
+        data.extend(entry(TAG_EXIF_IFD, 4, 1, 68));
+        data.extend(entry(TAG_GPS_IFD, 4, 1, 100));
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(b"Canon\0");
+        // EXIF IFD at 68: ExposureTime (RATIONAL) -> 86
+        data.extend_from_slice(&1u16.to_be_bytes());
+        data.extend(entry(0x829A, 5, 1, 86));
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&1u32.to_be_bytes());
+        data.extend_from_slice(&250u32.to_be_bytes());
+        // Padding up to 100
+        data.resize(100, 0);
+        // GPS IFD at 100: GPSLatitudeRef "N"
+        data.extend_from_slice(&1u16.to_be_bytes());
+        data.extend(entry(0x0001, 2, 2, u32::from_be_bytes(*b"N\0\0\0")));
+        data.extend_from_slice(&[0; 4]);
+        data
+    }
+
+    #[test]
+    fn test_read_exif() {
+        let exif = ExifData::read(Cursor::new(build_exif())).unwrap();
+        assert_eq!(exif.entries().len(), 4);
+        assert_eq!(
+            exif.get(ExifIfd::Primary, 0x010F).and_then(|v| v.as_str()),
+            Some("Canon")
+        );
+        assert_eq!(
+            exif.get(ExifIfd::Primary, 0x0112).and_then(|v| v.as_u32()),
+            Some(6)
+        );
+        assert_eq!(
+            exif.get(ExifIfd::Exif, 0x829A).and_then(|v| v.as_f64()),
+            Some(0.004)
+        );
+        assert_eq!(
+            exif.get(ExifIfd::Gps, 0x0001).and_then(|v| v.as_str()),
+            Some("N")
+        );
+        assert!(exif.get(ExifIfd::Primary, TAG_EXIF_IFD).is_none());
+    }
+
+    #[test]
+    fn test_read_exif_relative_to_header() {
+        let mut data = b"Exif\0\0".to_vec();
+        data.extend(build_exif());
+        let mut reader = Cursor::new(data);
+        reader.set_position(6);
+        let exif = ExifData::read(reader).unwrap();
+        assert_eq!(
+            exif.get(ExifIfd::Primary, 0x010F).and_then(|v| v.as_str()),
+            Some("Canon")
+        );
+    }
+
+    #[test]
+    fn test_skip_broken_entries() {
+        let mut data = build_exif();
+        // Point the Make value past the end of the data
+        data[18..22].copy_from_slice(&0xFFFFu32.to_be_bytes());
+        let exif = ExifData::read(Cursor::new(data)).unwrap();
+        assert!(exif.get(ExifIfd::Primary, 0x010F).is_none());
+        assert!(exif.get(ExifIfd::Primary, 0x0112).is_some());
+
+        assert!(ExifData::read(Cursor::new(b"XX\x00\x2a".to_vec())).is_err());
+    }
+
+    #[test]
+    fn test_import_into() {
+        let exif = ExifData::read(Cursor::new(build_exif())).unwrap();
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::TIFF, "Make", XmpValue::String("Edited".to_string()))
+            .unwrap();
+        assert!(exif.import_into(&mut meta).unwrap());
+
+        // Existing XMP takes precedence
+        assert_eq!(
+            meta.get_property(ns::TIFF, "Make"),
+            Some(XmpValue::String("Edited".to_string()))
+        );
+        assert_eq!(meta.get_property_i64(ns::TIFF, "Orientation"), Some(6));
+        assert_eq!(
+            meta.get_property(ns::EXIF, "ExposureTime"),
+            Some(XmpValue::String("1/250".to_string()))
+        );
+        // A reference without coordinates is not imported
+        assert!(!meta.does_property_exist(ns::EXIF, "GPSLatitude"));
+
+        // Nothing left to import
+        assert!(!exif.import_into(&mut meta).unwrap());
+    }
+
+    #[test]
+    fn test_export_exif() {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::TIFF, "Orientation", XmpValue::Integer(3))
+            .unwrap();
+        meta.set_localized_text(ns::DC, "description", "", "x-default", "A long description")
+            .unwrap();
+        meta.set_property(
+            ns::EXIF,
+            "DateTimeOriginal",
+            XmpValue::String("2024-05-06T07:08:09.25Z".to_string()),
+        )
+        .unwrap();
+
+        let mut data = build_exif();
+        let original_len = data.len();
+        assert!(export_exif(&mut data, &meta).unwrap());
+        assert!(data.len() > original_len);
+
+        let exif = ExifData::read(Cursor::new(data.clone())).unwrap();
+        assert_eq!(
+            exif.get(ExifIfd::Primary, 0x0112).and_then(|v| v.as_u32()),
+            Some(3)
+        );
+        assert_eq!(
+            exif.get(ExifIfd::Primary, 0x010E).and_then(|v| v.as_str()),
+            Some("A long description")
+        );
+        assert_eq!(
+            exif.get(ExifIfd::Exif, TAG_DATE_TIME_ORIGINAL)
+                .and_then(|v| v.as_str()),
+            Some("2024:05:06 07:08:09")
+        );
+        assert_eq!(
+            exif.get(ExifIfd::Exif, 0x9291).and_then(|v| v.as_str()),
+            Some("25")
+        );
+        assert_eq!(
+            exif.get(ExifIfd::Exif, 0x9011).and_then(|v| v.as_str()),
+            Some("+00:00")
+        );
+        // Other tags are kept
+        assert_eq!(
+            exif.get(ExifIfd::Primary, 0x010F).and_then(|v| v.as_str()),
+            Some("Canon")
+        );
+        assert!(exif.get(ExifIfd::Exif, 0x829A).is_some());
+        assert!(exif.get(ExifIfd::Gps, 0x0001).is_some());
+
+        // Up to date
+        assert!(!export_exif(&mut data, &meta).unwrap());
+
+        // An EXIF IFD is created when missing
+        let mut data = EMPTY_TIFF.to_vec();
+        assert!(export_exif(&mut data, &meta).unwrap());
+        let exif = ExifData::read(Cursor::new(data)).unwrap();
+        assert!(exif.get(ExifIfd::Exif, TAG_DATE_TIME_ORIGINAL).is_some());
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(
+            parse_xmp_date("2024-05-06T07:08:09.25-05:00"),
+            Some((
+                "2024:05:06 07:08:09".to_string(),
+                Some("25".to_string()),
+                Some("-05:00".to_string())
+            ))
+        );
+        assert_eq!(
+            parse_xmp_date("2024-05-06T07:08"),
+            Some(("2024:05:06 07:08:00".to_string(), None, None))
+        );
+        assert_eq!(
+            parse_xmp_date("2024-05-06"),
+            Some(("2024:05:06 00:00:00".to_string(), None, None))
+        );
+        assert_eq!(parse_xmp_date("2024-05"), None);
+        assert_eq!(
+            format_date("2024:05:06 07:08:09", Some("25"), Some("+02:00")).as_deref(),
+            Some("2024-05-06T07:08:09.25+02:00")
+        );
+        assert_eq!(
+            format_date("2024:05:06 07:08:09", None, None).as_deref(),
+            Some("2024-05-06T07:08:09")
+        );
+        assert_eq!(format_date("0000:00:00 00:00:00", None, None), None);
+        assert_eq!(format_date("    :  :     :  :  ", None, None), None);
+
+        assert_eq!(
+            format_gps_coordinate(&[(48, 1), (51, 1), (30, 1)], "N").as_deref(),
+            Some("48,51,30N")
+        );
+        assert_eq!(
+            format_gps_coordinate(&[(2, 1), (2118, 100), (0, 1)], "E").as_deref(),
+            Some("2,21.18E")
+        );
+        assert_eq!(format_gps_coordinate(&[(2, 1)], "X"), None);
+
+        let mut comment = b"ASCII\0\0\0".to_vec();
+        comment.extend_from_slice(b"Hello  ");
+        assert_eq!(decode_user_comment(&comment).as_deref(), Some("Hello"));
+        let mut comment = b"UNICODE\0".to_vec();
+        comment.extend_from_slice(&[0, b'H', 0, b'i']);
+        assert_eq!(decode_user_comment(&comment).as_deref(), Some("Hi"));
+        assert_eq!(decode_user_comment(&[0; 12]), None);
+    }
+}
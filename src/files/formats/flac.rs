@@ -0,0 +1,413 @@
+//! FLAC file format handler
+//!
+//! This module provides functionality for reading and writing XMP metadata
+//! in FLAC files. The implementation is pure Rust and cross-platform compatible.
+//!
+//! FLAC File Structure:
+//! - Optional ID3v2 tag (copied unchanged)
+//! - "fLaC" signature
+//! - Metadata blocks: header (1 byte last-block flag + 7 bits type, 3 bytes
+//!   big-endian length) + data. STREAMINFO is always the first block
+//! - Audio frames
+//!
+//! FLAC XMP Storage:
+//! - XMP is stored in an APPLICATION metadata block (type 2) whose data
+//!   starts with the "XMP " application ID, followed by the packet
+//! - When the metadata size changes, an existing PADDING block is resized
+//!   to absorb the difference, if possible, so the audio frames don't move
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::files::format::FormatCapabilities;
+use crate::files::handler::FileHandler;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// FLAC stream signature
+const FLAC_SIGNATURE: &[u8; 4] = b"fLaC";
+
+/// ID3v2 tag header size
+const ID3_HEADER_SIZE: usize = 10;
+
+/// Metadata block types
+const BLOCK_STREAMINFO: u8 = 0;
+const BLOCK_PADDING: u8 = 1;
+const BLOCK_APPLICATION: u8 = 2;
+
+/// Flag set in the header of the last metadata block
+const LAST_BLOCK_FLAG: u8 = 0x80;
+
+/// Maximum length of a metadata block (24-bit length field)
+const MAX_BLOCK_LENGTH: usize = 0xFF_FFFF;
+
+/// Application ID of the XMP APPLICATION block
+const XMP_APPLICATION_ID: &[u8; 4] = b"XMP ";
+
+/// FLAC file handler for XMP metadata
+#[derive(Debug, Clone, Copy)]
+pub struct FlacHandler;
+
+impl FileHandler for FlacHandler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        let pos = reader.stream_position()?;
+        let result = Self::find_signature(reader);
+        reader.seek(SeekFrom::Start(pos))?;
+        Ok(matches!(result, Ok(Some(_))))
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> FormatCapabilities {
+        FormatCapabilities {
+            can_read: true,
+            can_write: true,
+            can_inject: true,
+            supports_in_place: true,
+            supports_reconciliation: false,
+            max_packet_size: Some(MAX_BLOCK_LENGTH - XMP_APPLICATION_ID.len()),
+        }
+    }
+
+    fn format_name(&self) -> &'static str {
+        "FLAC"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["flac"]
+    }
+}
+
+/// A metadata block
+#[derive(Debug, Clone)]
+struct MetadataBlock {
+    block_type: u8,
+    data: Vec<u8>,
+}
+
+impl MetadataBlock {
+    fn is_xmp(&self) -> bool {
+        self.block_type == BLOCK_APPLICATION && self.data.starts_with(XMP_APPLICATION_ID)
+    }
+}
+
+impl FlacHandler {
+    /// Read XMP metadata from a FLAC file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(XmpMeta))` if XMP metadata is found
+    /// * `Ok(None)` if no XMP metadata is found
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_xmp<R: Read + Seek>(mut reader: R) -> XmpResult<Option<XmpMeta>> {
+        let (_, blocks) = Self::read_metadata(&mut reader)?;
+
+        let Some(block) = blocks.iter().find(|block| block.is_xmp()) else {
+            return Ok(None);
+        };
+        let xmp_str = String::from_utf8(block.data[XMP_APPLICATION_ID.len()..].to_vec())
+            .map_err(|e| XmpError::ParseError(format!("Invalid UTF-8 in XMP: {}", e)))?;
+        XmpMeta::parse(xmp_str.trim_end_matches('\0')).map(Some)
+    }
+
+    /// Write XMP metadata to a FLAC file
+    ///
+    /// The XMP APPLICATION block replaces the existing one, or is inserted
+    /// before the padding (or at the end of the metadata). The last-block
+    /// flags are rewritten for the new block order.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    pub fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        let xmp_packet = meta.serialize_packet()?;
+        let mut xmp_data = XMP_APPLICATION_ID.to_vec();
+        xmp_data.extend_from_slice(xmp_packet.as_bytes());
+        if xmp_data.len() > MAX_BLOCK_LENGTH {
+            return Err(XmpError::BadValue(
+                "XMP packet too large for a FLAC metadata block".to_string(),
+            ));
+        }
+
+        let (signature_offset, mut blocks) = Self::read_metadata(&mut reader)?;
+        let audio_offset = reader.stream_position()?;
+        let old_size = Self::metadata_size(&blocks);
+
+        let xmp_block = MetadataBlock {
+            block_type: BLOCK_APPLICATION,
+            data: xmp_data,
+        };
+        match blocks.iter().position(MetadataBlock::is_xmp) {
+            Some(index) => {
+                blocks[index] = xmp_block;
+                let mut i = index + 1;
+                while i < blocks.len() {
+                    if blocks[i].is_xmp() {
+                        blocks.remove(i);
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+            None => {
+                let index = blocks
+                    .iter()
+                    .position(|block| block.block_type == BLOCK_PADDING)
+                    .unwrap_or(blocks.len());
+                blocks.insert(index, xmp_block);
+            }
+        }
+
+        // Keep the audio frames in place using the padding, if possible
+        let new_size = Self::metadata_size(&blocks);
+        if let Some(padding) = blocks
+            .iter_mut()
+            .find(|block| block.block_type == BLOCK_PADDING)
+        {
+            let padded_len = padding.data.len() as i64 + old_size as i64 - new_size as i64;
+            if (0..=MAX_BLOCK_LENGTH as i64).contains(&padded_len) {
+                padding.data.resize(padded_len as usize, 0);
+            }
+        }
+
+        // Copy the ID3v2 tag before the signature
+        reader.rewind()?;
+        std::io::copy(&mut reader.by_ref().take(signature_offset), &mut writer)?;
+        writer.write_all(FLAC_SIGNATURE)?;
+        for (i, block) in blocks.iter().enumerate() {
+            let mut header = (block.data.len() as u32).to_be_bytes();
+            header[0] = block.block_type;
+            if i == blocks.len() - 1 {
+                header[0] |= LAST_BLOCK_FLAG;
+            }
+            writer.write_all(&header)?;
+            writer.write_all(&block.data)?;
+        }
+
+        // Copy the audio frames
+        reader.seek(SeekFrom::Start(audio_offset))?;
+        std::io::copy(&mut reader, &mut writer)?;
+
+        Ok(())
+    }
+
+    /// Find the offset of the "fLaC" signature, skipping an ID3v2 tag
+    fn find_signature<R: Read + Seek>(reader: &mut R) -> XmpResult<Option<u64>> {
+        let start = reader.stream_position()?;
+        let mut header = [0u8; ID3_HEADER_SIZE];
+        reader.read_exact(&mut header[..4])?;
+        if &header[..4] == FLAC_SIGNATURE {
+            return Ok(Some(start));
+        }
+        if &header[..3] != b"ID3" {
+            return Ok(None);
+        }
+
+        reader.read_exact(&mut header[4..])?;
+        // Syncsafe tag size, plus the footer if present
+        let size = header[6..10]
+            .iter()
+            .fold(0u64, |size, &byte| (size << 7) | (byte & 0x7F) as u64);
+        let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+        let offset = start + ID3_HEADER_SIZE as u64 + size + footer;
+
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut signature = [0u8; 4];
+        reader.read_exact(&mut signature)?;
+        Ok((signature == *FLAC_SIGNATURE).then_some(offset))
+    }
+
+    /// Read all metadata blocks, returning the offset of the signature
+    ///
+    /// Leaves the reader at the first audio frame.
+    fn read_metadata<R: Read + Seek>(reader: &mut R) -> XmpResult<(u64, Vec<MetadataBlock>)> {
+        reader.rewind()?;
+        let signature_offset = Self::find_signature(reader)
+            .ok()
+            .flatten()
+            .ok_or_else(|| XmpError::BadValue("Not a valid FLAC file".to_string()))?;
+        reader.seek(SeekFrom::Start(signature_offset + 4))?;
+
+        let mut blocks = Vec::new();
+        loop {
+            let mut header = [0u8; 4];
+            reader
+                .read_exact(&mut header)
+                .map_err(|_| XmpError::BadValue("Truncated FLAC metadata".to_string()))?;
+            let length = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+
+            let mut data = vec![0u8; length];
+            reader
+                .read_exact(&mut data)
+                .map_err(|_| XmpError::BadValue("Truncated FLAC metadata block".to_string()))?;
+            blocks.push(MetadataBlock {
+                block_type: header[0] & !LAST_BLOCK_FLAG,
+                data,
+            });
+
+            if header[0] & LAST_BLOCK_FLAG != 0 {
+                break;
+            }
+        }
+
+        if blocks[0].block_type != BLOCK_STREAMINFO {
+            return Err(XmpError::BadValue(
+                "Invalid FLAC file: missing STREAMINFO block".to_string(),
+            ));
+        }
+        Ok((signature_offset, blocks))
+    }
+
+    /// Total size of the metadata blocks, including their headers
+    fn metadata_size(blocks: &[MetadataBlock]) -> usize {
+        blocks.iter().map(|block| 4 + block.data.len()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+    use std::io::Cursor;
+
+    const AUDIO: &[u8] = &[0xFF, 0xF8, 0x69, 0x08, 0x00, 0x00];
+
+    /// Build a FLAC file from metadata blocks (type, data)
+    fn build_flac(prefix: &[u8], blocks: &[(u8, &[u8])]) -> Vec<u8> {
+        let mut data = prefix.to_vec();
+        data.extend_from_slice(FLAC_SIGNATURE);
+        for (i, (block_type, block)) in blocks.iter().enumerate() {
+            let mut header = (block.len() as u32).to_be_bytes();
+            header[0] = *block_type;
+            if i == blocks.len() - 1 {
+                header[0] |= LAST_BLOCK_FLAG;
+            }
+            data.extend_from_slice(&header);
+            data.extend_from_slice(block);
+        }
+        data.extend_from_slice(AUDIO);
+        data
+    }
+
+    fn test_meta(title: &str) -> XmpMeta {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String(title.to_string()))
+            .unwrap();
+        meta
+    }
+
+    fn block_types(data: &[u8]) -> Vec<u8> {
+        let (_, blocks) = FlacHandler::read_metadata(&mut Cursor::new(data)).unwrap();
+        blocks.iter().map(|block| block.block_type).collect()
+    }
+
+    #[test]
+    fn test_can_handle() {
+        let handler = FlacHandler;
+        let flac = build_flac(&[], &[(BLOCK_STREAMINFO, &[0; 34])]);
+        assert!(handler.can_handle(&mut Cursor::new(flac)).unwrap());
+
+        let id3 = b"ID3\x04\x00\x00\x00\x00\x00\x02\x00\x00";
+        let flac = build_flac(id3, &[(BLOCK_STREAMINFO, &[0; 34])]);
+        assert!(handler.can_handle(&mut Cursor::new(flac)).unwrap());
+
+        let mp3 = b"ID3\x04\x00\x00\x00\x00\x00\x00\xFF\xFB\x90\x00".to_vec();
+        assert!(!handler.can_handle(&mut Cursor::new(mp3)).unwrap());
+    }
+
+    #[test]
+    fn test_write_xmp_last_block_flag() {
+        let flac = build_flac(&[], &[(BLOCK_STREAMINFO, &[0; 34])]);
+        assert!(FlacHandler::read_xmp(Cursor::new(&flac)).unwrap().is_none());
+
+        let mut writer = Cursor::new(Vec::new());
+        FlacHandler::write_xmp(Cursor::new(&flac), &mut writer, &test_meta("Song")).unwrap();
+        let data = writer.into_inner();
+
+        // STREAMINFO is no longer the last block
+        assert_eq!(data[4] & LAST_BLOCK_FLAG, 0);
+        assert_eq!(block_types(&data), [BLOCK_STREAMINFO, BLOCK_APPLICATION]);
+        assert!(data.ends_with(AUDIO));
+
+        let meta = FlacHandler::read_xmp(Cursor::new(&data)).unwrap().unwrap();
+        assert_eq!(
+            meta.get_property(ns::DC, "title"),
+            Some(XmpValue::String("Song".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_write_xmp_uses_padding() {
+        let id3 = b"ID3\x04\x00\x00\x00\x00\x00\x02\x00\x00";
+        let flac = build_flac(
+            id3,
+            &[
+                (BLOCK_STREAMINFO, &[0; 34]),
+                (BLOCK_APPLICATION, b"abcdother"),
+                (BLOCK_PADDING, &[0; 8192]),
+            ],
+        );
+
+        let mut writer = Cursor::new(Vec::new());
+        FlacHandler::write_xmp(Cursor::new(&flac), &mut writer, &test_meta("First")).unwrap();
+        let first = writer.into_inner();
+        assert!(first.starts_with(id3));
+        assert_eq!(
+            block_types(&first),
+            [
+                BLOCK_STREAMINFO,
+                BLOCK_APPLICATION,
+                BLOCK_APPLICATION,
+                BLOCK_PADDING
+            ]
+        );
+        // The padding absorbed the new block
+        assert_eq!(first.len(), flac.len());
+
+        let mut writer = Cursor::new(Vec::new());
+        FlacHandler::write_xmp(Cursor::new(&first), &mut writer, &test_meta("Second")).unwrap();
+        let second = writer.into_inner();
+        assert_eq!(second.len(), flac.len());
+        assert_eq!(block_types(&second).len(), 4);
+        assert!(second.ends_with(AUDIO));
+
+        let meta = FlacHandler::read_xmp(Cursor::new(&second))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            meta.get_property(ns::DC, "title"),
+            Some(XmpValue::String("Second".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_invalid_flac() {
+        assert!(FlacHandler::read_xmp(Cursor::new(b"fLaC".to_vec())).is_err());
+        let flac = build_flac(&[], &[(BLOCK_PADDING, &[0; 4])]);
+        assert!(FlacHandler::read_xmp(Cursor::new(flac)).is_err());
+    }
+}
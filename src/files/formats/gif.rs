@@ -64,6 +64,10 @@ impl FileHandler for GifHandler {
         Self::write_xmp(reader, writer, meta)
     }
 
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
     fn format_name(&self) -> &'static str {
         "GIF"
     }
@@ -0,0 +1,1097 @@
+//! HEIF file format handler
+//!
+//! This module provides functionality for reading and writing XMP metadata
+//! in HEIF/HEIC files. The implementation is pure Rust and cross-platform compatible.
+//!
+//! HEIF XMP Storage:
+//! - HEIF is an ISO Base Media File Format (ISO BMFF) file with image items
+//! - Items are declared in the `meta` box: `iinf` holds the item infos
+//!   (`infe`), `iloc` holds the location of each item's data
+//! - XMP is stored as a `mime` item with content type `application/rdf+xml`,
+//!   usually linked to the primary image with a `cdsc` reference in `iref`
+//! - Item data is usually in an `mdat` box, addressed by absolute file offset
+//!
+//! When writing, the XMP packet is stored in its own `mdat` box following
+//! the `meta` box and the offsets of all other items are relocated.
+//!
+//! The same container is used by AVIF, see the `avif` module.
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::files::handler::FileHandler;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// File type box
+const BOX_FTYP: &[u8; 4] = b"ftyp";
+/// Metadata box
+const BOX_META: &[u8; 4] = b"meta";
+/// Media data box
+const BOX_MDAT: &[u8; 4] = b"mdat";
+/// Movie box (image sequences)
+const BOX_MOOV: &[u8; 4] = b"moov";
+/// Item information box
+const BOX_IINF: &[u8; 4] = b"iinf";
+/// Item information entry
+const BOX_INFE: &[u8; 4] = b"infe";
+/// Item location box
+const BOX_ILOC: &[u8; 4] = b"iloc";
+/// Item reference box
+const BOX_IREF: &[u8; 4] = b"iref";
+/// Primary item box
+const BOX_PITM: &[u8; 4] = b"pitm";
+/// Item data box
+const BOX_IDAT: &[u8; 4] = b"idat";
+
+/// Item type for MIME items
+const ITEM_TYPE_MIME: &[u8; 4] = b"mime";
+/// Content type of XMP items
+const XMP_CONTENT_TYPE: &str = "application/rdf+xml";
+/// Reference type linking metadata to the item it describes
+const REF_TYPE_CDSC: &[u8; 4] = b"cdsc";
+
+/// Brands identifying HEIF files
+const HEIF_BRANDS: &[&[u8; 4]] = &[
+    b"heic", b"heix", b"heim", b"heis", b"hevc", b"hevx", b"hevm", b"hevs", b"mif1", b"msf1",
+];
+
+/// Brands of other formats that also list the generic HEIF brands
+pub(crate) const OTHER_BRANDS: &[&[u8; 4]] = &[b"avif", b"avis"];
+
+/// HEIF file handler for XMP metadata
+#[derive(Debug, Clone, Copy)]
+pub struct HeifHandler;
+
+impl FileHandler for HeifHandler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        let pos = reader.stream_position()?;
+        let result = Self::read_brands(reader);
+        reader.seek(SeekFrom::Start(pos))?;
+
+        let Ok(Some((major, compatible))) = result else {
+            return Ok(false);
+        };
+        if OTHER_BRANDS.contains(&&major) {
+            return Ok(false);
+        }
+        Ok(HEIF_BRANDS.contains(&&major)
+            || compatible.iter().any(|brand| HEIF_BRANDS.contains(&brand)))
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn format_name(&self) -> &'static str {
+        "HEIF"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["heic", "heif", "hif"]
+    }
+}
+
+/// Major brand and compatible brands of a file
+pub(crate) type Brands = ([u8; 4], Vec<[u8; 4]>);
+
+/// A top-level box in the file
+#[derive(Debug, Clone)]
+struct BmffBox {
+    box_type: [u8; 4],
+    /// Offset of the box header
+    offset: u64,
+    /// Size of the box header
+    header_size: u64,
+    /// Total size of the box, including its header
+    size: u64,
+}
+
+impl BmffBox {
+    fn end(&self) -> u64 {
+        self.offset + self.size
+    }
+}
+
+/// A box inside an in-memory parent box
+#[derive(Debug, Clone)]
+struct ChildBox {
+    box_type: [u8; 4],
+    /// Range of the whole box in the parent data
+    start: usize,
+    end: usize,
+    /// Start of the payload in the parent data
+    payload_start: usize,
+}
+
+/// An item information entry
+#[derive(Debug, Clone)]
+struct ItemInfo {
+    id: u32,
+    item_type: [u8; 4],
+    content_type: Option<String>,
+}
+
+/// Parsed item information box
+#[derive(Debug, Clone)]
+struct ItemInfoBox {
+    version: u8,
+    flags: u32,
+    /// Raw `infe` boxes
+    entries: Vec<Vec<u8>>,
+    items: Vec<ItemInfo>,
+}
+
+/// A single extent of an item
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Extent {
+    index: u64,
+    offset: u64,
+    length: u64,
+}
+
+/// The location of an item
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ItemLocation {
+    id: u32,
+    construction_method: u16,
+    data_reference_index: u16,
+    base_offset: u64,
+    extents: Vec<Extent>,
+}
+
+/// Parsed item location box
+#[derive(Debug, Clone)]
+struct ItemLocationBox {
+    version: u8,
+    flags: u32,
+    offset_size: u8,
+    length_size: u8,
+    base_offset_size: u8,
+    index_size: u8,
+    items: Vec<ItemLocation>,
+}
+
+/// Big-endian reader over in-memory box data
+struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> XmpResult<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| XmpError::BadValue("Truncated HEIF box".to_string()))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> XmpResult<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> XmpResult<u16> {
+        self.sized(2).map(|value| value as u16)
+    }
+
+    fn u32(&mut self) -> XmpResult<u32> {
+        self.sized(4).map(|value| value as u32)
+    }
+
+    fn fourcc(&mut self) -> XmpResult<[u8; 4]> {
+        let bytes = self.bytes(4)?;
+        Ok([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    /// Read a big-endian integer of `size` bytes (0 reads nothing)
+    fn sized(&mut self, size: u8) -> XmpResult<u64> {
+        Ok(self
+            .bytes(size as usize)?
+            .iter()
+            .fold(0u64, |value, &byte| (value << 8) | byte as u64))
+    }
+
+    /// Read a null-terminated string (the terminator may be missing at the end)
+    fn cstring(&mut self) -> String {
+        let rest = &self.data[self.pos..];
+        let len = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+        self.pos += (len + 1).min(rest.len());
+        String::from_utf8_lossy(&rest[..len]).into_owned()
+    }
+
+    /// Read the version and flags of a full box
+    fn full_box_header(&mut self) -> XmpResult<(u8, u32)> {
+        let value = self.u32()?;
+        Ok(((value >> 24) as u8, value & 0x00FF_FFFF))
+    }
+
+    fn remaining(&self) -> &'a [u8] {
+        &self.data[self.pos..]
+    }
+}
+
+/// Append a big-endian integer of `size` bytes
+fn push_sized(out: &mut Vec<u8>, value: u64, size: u8) -> XmpResult<()> {
+    if size < 8 && value >> (size as u32 * 8) != 0 {
+        return Err(XmpError::BadValue(
+            "HEIF item location does not fit its field".to_string(),
+        ));
+    }
+    out.extend_from_slice(&value.to_be_bytes()[8 - size as usize..]);
+    Ok(())
+}
+
+/// Build a box from its type and payload
+fn make_box(box_type: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(payload.len() + 16);
+    match u32::try_from(payload.len() + 8) {
+        Ok(size) => {
+            out.extend_from_slice(&size.to_be_bytes());
+            out.extend_from_slice(box_type);
+        }
+        Err(_) => {
+            out.extend_from_slice(&1u32.to_be_bytes());
+            out.extend_from_slice(box_type);
+            out.extend_from_slice(&(payload.len() as u64 + 16).to_be_bytes());
+        }
+    }
+    out.extend_from_slice(payload);
+    out
+}
+
+/// Build a full box from its type, version, flags and payload
+fn make_full_box(box_type: &[u8; 4], version: u8, flags: u32, payload: &[u8]) -> Vec<u8> {
+    let mut data = ((version as u32) << 24 | (flags & 0x00FF_FFFF))
+        .to_be_bytes()
+        .to_vec();
+    data.extend_from_slice(payload);
+    make_box(box_type, &data)
+}
+
+impl HeifHandler {
+    /// Read XMP metadata from a HEIF file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(XmpMeta))` if XMP metadata is found
+    /// * `Ok(None)` if no XMP metadata is found
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_xmp<R: Read + Seek>(mut reader: R) -> XmpResult<Option<XmpMeta>> {
+        let boxes = Self::read_top_level_boxes(&mut reader)?;
+        let Some(meta_box) = boxes.iter().find(|b| b.box_type == *BOX_META) else {
+            return Ok(None);
+        };
+        let meta_payload = Self::read_payload(&mut reader, meta_box)?;
+        let children = Self::parse_children(meta_payload.get(4..).unwrap_or_default())?;
+        let meta_children = &meta_payload[4.min(meta_payload.len())..];
+
+        let Some(iinf) = Self::find_child(meta_children, &children, BOX_IINF) else {
+            return Ok(None);
+        };
+        let Some(xmp_id) = Self::find_xmp_item(&Self::parse_iinf(iinf)?) else {
+            return Ok(None);
+        };
+        let iloc = Self::find_child(meta_children, &children, BOX_ILOC)
+            .ok_or_else(|| XmpError::BadValue("HEIF file has no item locations".to_string()))?;
+        let iloc = Self::parse_iloc(iloc)?;
+        let location = iloc
+            .items
+            .iter()
+            .find(|item| item.id == xmp_id)
+            .ok_or_else(|| XmpError::BadValue("HEIF XMP item has no location".to_string()))?;
+
+        let mut data = Vec::new();
+        match location.construction_method {
+            0 => {
+                if location.data_reference_index != 0 {
+                    return Err(XmpError::NotSupported(
+                        "HEIF XMP item in an external file".to_string(),
+                    ));
+                }
+                for extent in &location.extents {
+                    reader.seek(SeekFrom::Start(location.base_offset + extent.offset))?;
+                    let read = reader.by_ref().take(extent.length).read_to_end(&mut data)?;
+                    if (read as u64) < extent.length {
+                        return Err(XmpError::BadValue("Truncated HEIF XMP item".to_string()));
+                    }
+                }
+            }
+            1 => {
+                let idat = Self::find_child(meta_children, &children, BOX_IDAT).unwrap_or_default();
+                for extent in &location.extents {
+                    let start = (location.base_offset + extent.offset) as usize;
+                    let bytes = start
+                        .checked_add(extent.length as usize)
+                        .and_then(|end| idat.get(start..end))
+                        .ok_or_else(|| XmpError::BadValue("Truncated HEIF XMP item".to_string()))?;
+                    data.extend_from_slice(bytes);
+                }
+            }
+            method => {
+                return Err(XmpError::NotSupported(format!(
+                    "HEIF item construction method {}",
+                    method
+                )));
+            }
+        }
+
+        let xmp_str = String::from_utf8(data)
+            .map_err(|e| XmpError::ParseError(format!("Invalid UTF-8 in XMP: {}", e)))?;
+        XmpMeta::parse(xmp_str.trim_end_matches('\0')).map(Some)
+    }
+
+    /// Write XMP metadata to a HEIF file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    pub fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        let xmp_packet = meta.serialize_packet()?;
+        let xmp_bytes = xmp_packet.as_bytes();
+
+        let boxes = Self::read_top_level_boxes(&mut reader)?;
+        if boxes.first().is_none_or(|b| b.box_type != *BOX_FTYP) {
+            return Err(XmpError::BadValue("Not a valid HEIF file".to_string()));
+        }
+        if boxes.iter().any(|b| b.box_type == *BOX_MOOV) {
+            return Err(XmpError::NotSupported(
+                "Writing XMP to HEIF image sequences".to_string(),
+            ));
+        }
+        let meta_index = boxes
+            .iter()
+            .position(|b| b.box_type == *BOX_META)
+            .ok_or_else(|| XmpError::BadValue("HEIF file has no meta box".to_string()))?;
+
+        let meta_payload = Self::read_payload(&mut reader, &boxes[meta_index])?;
+        if meta_payload.len() < 4 {
+            return Err(XmpError::BadValue("Truncated HEIF box".to_string()));
+        }
+        let (meta_header, meta_children) = meta_payload.split_at(4);
+        let children = Self::parse_children(meta_children)?;
+
+        let mut iinf = match Self::find_child(meta_children, &children, BOX_IINF) {
+            Some(data) => Self::parse_iinf(data)?,
+            None => ItemInfoBox {
+                version: 0,
+                flags: 0,
+                entries: Vec::new(),
+                items: Vec::new(),
+            },
+        };
+        let mut iloc = Self::parse_iloc(
+            Self::find_child(meta_children, &children, BOX_ILOC)
+                .ok_or_else(|| XmpError::BadValue("HEIF file has no item locations".to_string()))?,
+        )?;
+
+        // Find or create the XMP item
+        let existing_id = Self::find_xmp_item(&iinf);
+        let xmp_id = match existing_id {
+            Some(id) => id,
+            None => {
+                let id = iinf
+                    .items
+                    .iter()
+                    .map(|item| item.id)
+                    .chain(iloc.items.iter().map(|item| item.id))
+                    .max()
+                    .unwrap_or(0)
+                    + 1;
+                iinf.entries.push(Self::make_xmp_infe(id));
+                iinf.items.push(ItemInfo {
+                    id,
+                    item_type: *ITEM_TYPE_MIME,
+                    content_type: Some(XMP_CONTENT_TYPE.to_string()),
+                });
+                id
+            }
+        };
+
+        // Link a new XMP item to the primary image
+        let mut new_iref = None;
+        if existing_id.is_none() {
+            if let Some(primary) = Self::find_child(meta_children, &children, BOX_PITM)
+                .map(Self::parse_pitm)
+                .transpose()?
+            {
+                let iref = Self::find_child(meta_children, &children, BOX_IREF);
+                new_iref = Some(Self::add_cdsc_reference(iref, xmp_id, primary)?);
+            }
+        }
+
+        // An mdat holding only the previous XMP packet is dropped
+        let old_location = iloc.items.iter().find(|item| item.id == xmp_id).cloned();
+        let dropped = old_location.as_ref().and_then(|location| {
+            let [extent] = location.extents.as_slice() else {
+                return None;
+            };
+            let start = location.base_offset + extent.offset;
+            boxes.iter().position(|b| {
+                location.construction_method == 0
+                    && b.box_type == *BOX_MDAT
+                    && b.offset + b.header_size == start
+                    && b.end() == start + extent.length
+            })
+        });
+        iloc.items.retain(|item| item.id != xmp_id);
+
+        // Make sure the new offsets fit the item location fields
+        let file_end = boxes.last().map_or(0, BmffBox::end);
+        if iloc.version < 2 && xmp_id > 0xFFFF {
+            iloc.version = 2;
+        }
+        iloc.offset_size = iloc.offset_size.max(4);
+        iloc.length_size = iloc.length_size.max(4);
+        if file_end + xmp_bytes.len() as u64 + (1 << 20) > u32::MAX as u64 {
+            iloc.offset_size = 8;
+        }
+
+        // Relocate all items stored in the file
+        let mut relocated = iloc.clone();
+        let build_meta = |iloc: &ItemLocationBox| -> XmpResult<Vec<u8>> {
+            let mut payload = meta_header.to_vec();
+            for child in &children {
+                match &child.box_type {
+                    BOX_IINF => payload.extend_from_slice(&Self::encode_iinf(&iinf)),
+                    BOX_ILOC => payload.extend_from_slice(&Self::encode_iloc(iloc)?),
+                    BOX_IREF if new_iref.is_some() => {}
+                    _ => payload.extend_from_slice(&meta_children[child.start..child.end]),
+                }
+                if child.box_type == *BOX_IINF {
+                    if let Some(iref) = &new_iref {
+                        payload.extend_from_slice(iref);
+                    }
+                }
+            }
+            if !children.iter().any(|child| child.box_type == *BOX_IINF) {
+                payload.extend_from_slice(&Self::encode_iinf(&iinf));
+                if let Some(iref) = &new_iref {
+                    payload.extend_from_slice(iref);
+                }
+            }
+            Ok(make_box(BOX_META, &payload))
+        };
+
+        // Locations have a fixed size, so the layout can be computed from a
+        // first build of the meta box
+        relocated.items.push(ItemLocation {
+            id: xmp_id,
+            construction_method: 0,
+            data_reference_index: 0,
+            base_offset: 0,
+            extents: vec![Extent {
+                index: 0,
+                offset: 0,
+                length: xmp_bytes.len() as u64,
+            }],
+        });
+        let meta_size = build_meta(&relocated)?.len() as u64;
+        let xmp_box = make_box(BOX_MDAT, xmp_bytes);
+
+        // New offset of each kept box
+        let mut new_offsets = vec![None; boxes.len()];
+        let mut pos = 0u64;
+        let mut xmp_offset = 0;
+        for (i, b) in boxes.iter().enumerate() {
+            if Some(i) == dropped {
+                continue;
+            }
+            new_offsets[i] = Some(pos);
+            if i == meta_index {
+                pos += meta_size;
+                xmp_offset = pos + (xmp_box.len() - xmp_bytes.len()) as u64;
+                pos += xmp_box.len() as u64;
+            } else {
+                pos += b.size;
+            }
+        }
+        let relocate = |offset: u64| -> u64 {
+            boxes
+                .iter()
+                .zip(&new_offsets)
+                .find(|(b, _)| b.offset <= offset && offset < b.end())
+                .and_then(|(b, new_offset)| new_offset.map(|new| new + (offset - b.offset)))
+                .unwrap_or(offset)
+        };
+
+        for item in relocated.items.iter_mut() {
+            if item.id == xmp_id {
+                item.extents[0].offset = xmp_offset;
+            } else if item.construction_method == 0 && item.data_reference_index == 0 {
+                let base = item.base_offset;
+                for extent in item.extents.iter_mut() {
+                    extent.offset = relocate(base + extent.offset);
+                }
+                item.base_offset = 0;
+            }
+        }
+        let meta_box = build_meta(&relocated)?;
+        debug_assert_eq!(meta_box.len() as u64, meta_size);
+
+        // Write the new file
+        for (i, b) in boxes.iter().enumerate() {
+            if Some(i) == dropped {
+                continue;
+            }
+            if i == meta_index {
+                writer.write_all(&meta_box)?;
+                writer.write_all(&xmp_box)?;
+            } else {
+                reader.seek(SeekFrom::Start(b.offset))?;
+                let copied = std::io::copy(&mut reader.by_ref().take(b.size), &mut writer)?;
+                if copied < b.size {
+                    return Err(XmpError::BadValue("Truncated HEIF box".to_string()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read the major and compatible brands from the `ftyp` box
+    pub(crate) fn read_brands<R: Read + Seek>(reader: &mut R) -> XmpResult<Option<Brands>> {
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header)?;
+        if header[4..8] != *BOX_FTYP {
+            return Ok(None);
+        }
+        let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        if !(16..=4096).contains(&size) {
+            return Ok(None);
+        }
+        let mut data = vec![0u8; size - 8];
+        reader.read_exact(&mut data)?;
+
+        let major = [data[0], data[1], data[2], data[3]];
+        let compatible = data[8..]
+            .chunks_exact(4)
+            .map(|brand| [brand[0], brand[1], brand[2], brand[3]])
+            .collect();
+        Ok(Some((major, compatible)))
+    }
+
+    /// Read the headers of all top-level boxes
+    fn read_top_level_boxes<R: Read + Seek>(reader: &mut R) -> XmpResult<Vec<BmffBox>> {
+        let file_end = reader.seek(SeekFrom::End(0))?;
+        let invalid = || XmpError::BadValue("Not a valid HEIF file".to_string());
+
+        let mut boxes = Vec::new();
+        let mut pos = 0u64;
+        while pos + 8 <= file_end {
+            reader.seek(SeekFrom::Start(pos))?;
+            let mut header = [0u8; 8];
+            reader.read_exact(&mut header)?;
+            let box_type = [header[4], header[5], header[6], header[7]];
+            let (size, header_size) =
+                match u32::from_be_bytes([header[0], header[1], header[2], header[3]]) {
+                    0 => (file_end - pos, 8),
+                    1 => {
+                        let mut large = [0u8; 8];
+                        reader.read_exact(&mut large)?;
+                        (u64::from_be_bytes(large), 16)
+                    }
+                    size => (size as u64, 8),
+                };
+            if size < header_size || pos + size > file_end {
+                return Err(invalid());
+            }
+            if boxes.is_empty() && box_type != *BOX_FTYP {
+                return Err(invalid());
+            }
+
+            boxes.push(BmffBox {
+                box_type,
+                offset: pos,
+                header_size,
+                size,
+            });
+            pos += size;
+        }
+
+        if boxes.is_empty() {
+            return Err(invalid());
+        }
+        Ok(boxes)
+    }
+
+    /// Read the payload of a top-level box
+    fn read_payload<R: Read + Seek>(reader: &mut R, b: &BmffBox) -> XmpResult<Vec<u8>> {
+        reader.seek(SeekFrom::Start(b.offset + b.header_size))?;
+        let mut data = vec![0u8; (b.size - b.header_size) as usize];
+        reader.read_exact(&mut data)?;
+        Ok(data)
+    }
+
+    /// Parse the boxes contained in `data`
+    fn parse_children(data: &[u8]) -> XmpResult<Vec<ChildBox>> {
+        let mut children = Vec::new();
+        let mut reader = ByteReader::new(data);
+        while reader.remaining().len() >= 8 {
+            let start = reader.pos;
+            let size = reader.u32()? as u64;
+            let box_type = reader.fourcc()?;
+            let size = match size {
+                0 => (data.len() - start) as u64,
+                1 => reader.sized(8)?,
+                size => size,
+            };
+            let payload_start = reader.pos;
+            let end = usize::try_from(size)
+                .ok()
+                .and_then(|size| start.checked_add(size))
+                .filter(|&end| end >= payload_start && end <= data.len())
+                .ok_or_else(|| XmpError::BadValue("Truncated HEIF box".to_string()))?;
+
+            children.push(ChildBox {
+                box_type,
+                start,
+                end,
+                payload_start,
+            });
+            reader.pos = end;
+        }
+        Ok(children)
+    }
+
+    /// Get the payload of the first child box of a type
+    fn find_child<'a>(
+        data: &'a [u8],
+        children: &[ChildBox],
+        box_type: &[u8; 4],
+    ) -> Option<&'a [u8]> {
+        children
+            .iter()
+            .find(|child| child.box_type == *box_type)
+            .map(|child| &data[child.payload_start..child.end])
+    }
+
+    /// Find the ID of the XMP item
+    fn find_xmp_item(iinf: &ItemInfoBox) -> Option<u32> {
+        iinf.items
+            .iter()
+            .find(|item| {
+                item.item_type == *ITEM_TYPE_MIME
+                    && item.content_type.as_deref().is_some_and(|content_type| {
+                        content_type
+                            .split(';')
+                            .next()
+                            .is_some_and(|mime| mime.trim() == XMP_CONTENT_TYPE)
+                    })
+            })
+            .map(|item| item.id)
+    }
+
+    /// Parse an item information box payload
+    fn parse_iinf(data: &[u8]) -> XmpResult<ItemInfoBox> {
+        let mut reader = ByteReader::new(data);
+        let (version, flags) = reader.full_box_header()?;
+        let _entry_count = if version == 0 {
+            reader.u16()? as u32
+        } else {
+            reader.u32()?
+        };
+
+        let rest = reader.remaining();
+        let mut entries = Vec::new();
+        let mut items = Vec::new();
+        for child in Self::parse_children(rest)? {
+            entries.push(rest[child.start..child.end].to_vec());
+            if child.box_type == *BOX_INFE {
+                items.push(Self::parse_infe(&rest[child.payload_start..child.end])?);
+            }
+        }
+
+        Ok(ItemInfoBox {
+            version,
+            flags,
+            entries,
+            items,
+        })
+    }
+
+    /// Parse an item information entry payload
+    fn parse_infe(data: &[u8]) -> XmpResult<ItemInfo> {
+        let mut reader = ByteReader::new(data);
+        let (version, _flags) = reader.full_box_header()?;
+
+        if version < 2 {
+            // Versions 0 and 1 only describe MIME items
+            let id = reader.u16()? as u32;
+            let _protection_index = reader.u16()?;
+            let _name = reader.cstring();
+            let content_type = reader.cstring();
+            return Ok(ItemInfo {
+                id,
+                item_type: *ITEM_TYPE_MIME,
+                content_type: Some(content_type),
+            });
+        }
+
+        let id = if version == 2 {
+            reader.u16()? as u32
+        } else {
+            reader.u32()?
+        };
+        let _protection_index = reader.u16()?;
+        let item_type = reader.fourcc()?;
+        let _name = reader.cstring();
+        let content_type = (item_type == *ITEM_TYPE_MIME).then(|| reader.cstring());
+
+        Ok(ItemInfo {
+            id,
+            item_type,
+            content_type,
+        })
+    }
+
+    /// Encode an item information box
+    fn encode_iinf(iinf: &ItemInfoBox) -> Vec<u8> {
+        let version = if iinf.version == 0 && iinf.entries.len() > 0xFFFF {
+            1
+        } else {
+            iinf.version
+        };
+
+        let mut payload = Vec::new();
+        if version == 0 {
+            payload.extend_from_slice(&(iinf.entries.len() as u16).to_be_bytes());
+        } else {
+            payload.extend_from_slice(&(iinf.entries.len() as u32).to_be_bytes());
+        }
+        for entry in &iinf.entries {
+            payload.extend_from_slice(entry);
+        }
+        make_full_box(BOX_IINF, version, iinf.flags, &payload)
+    }
+
+    /// Create the item information entry of a new XMP item
+    fn make_xmp_infe(id: u32) -> Vec<u8> {
+        let mut payload = Vec::new();
+        let version = if id > 0xFFFF {
+            payload.extend_from_slice(&id.to_be_bytes());
+            3
+        } else {
+            payload.extend_from_slice(&(id as u16).to_be_bytes());
+            2
+        };
+        payload.extend_from_slice(&0u16.to_be_bytes()); // item_protection_index
+        payload.extend_from_slice(ITEM_TYPE_MIME);
+        payload.extend_from_slice(b"XMP\0"); // item_name
+        payload.extend_from_slice(XMP_CONTENT_TYPE.as_bytes());
+        payload.push(0);
+        make_full_box(BOX_INFE, version, 0, &payload)
+    }
+
+    /// Parse an item location box payload
+    fn parse_iloc(data: &[u8]) -> XmpResult<ItemLocationBox> {
+        let mut reader = ByteReader::new(data);
+        let (version, flags) = reader.full_box_header()?;
+        if version > 2 {
+            return Err(XmpError::NotSupported(format!(
+                "HEIF item location box version {}",
+                version
+            )));
+        }
+
+        let sizes = reader.u8()?;
+        let (offset_size, length_size) = (sizes >> 4, sizes & 0x0F);
+        let sizes = reader.u8()?;
+        let base_offset_size = sizes >> 4;
+        let index_size = if version > 0 { sizes & 0x0F } else { 0 };
+        for size in [offset_size, length_size, base_offset_size, index_size] {
+            if !matches!(size, 0 | 4 | 8) {
+                return Err(XmpError::BadValue(
+                    "Invalid HEIF item location field size".to_string(),
+                ));
+            }
+        }
+
+        let item_count = if version < 2 {
+            reader.u16()? as u32
+        } else {
+            reader.u32()?
+        };
+        let mut items = Vec::new();
+        for _ in 0..item_count {
+            let id = if version < 2 {
+                reader.u16()? as u32
+            } else {
+                reader.u32()?
+            };
+            let construction_method = if version > 0 {
+                reader.u16()? & 0x000F
+            } else {
+                0
+            };
+            let data_reference_index = reader.u16()?;
+            let base_offset = reader.sized(base_offset_size)?;
+            let extent_count = reader.u16()?;
+            let mut extents = Vec::new();
+            for _ in 0..extent_count {
+                extents.push(Extent {
+                    index: reader.sized(index_size)?,
+                    offset: reader.sized(offset_size)?,
+                    length: reader.sized(length_size)?,
+                });
+            }
+            items.push(ItemLocation {
+                id,
+                construction_method,
+                data_reference_index,
+                base_offset,
+                extents,
+            });
+        }
+
+        Ok(ItemLocationBox {
+            version,
+            flags,
+            offset_size,
+            length_size,
+            base_offset_size,
+            index_size,
+            items,
+        })
+    }
+
+    /// Encode an item location box
+    fn encode_iloc(iloc: &ItemLocationBox) -> XmpResult<Vec<u8>> {
+        let version = iloc.version;
+        let mut payload = vec![
+            iloc.offset_size << 4 | iloc.length_size,
+            iloc.base_offset_size << 4 | if version > 0 { iloc.index_size } else { 0 },
+        ];
+        if version < 2 {
+            payload.extend_from_slice(&(iloc.items.len() as u16).to_be_bytes());
+        } else {
+            payload.extend_from_slice(&(iloc.items.len() as u32).to_be_bytes());
+        }
+
+        for item in &iloc.items {
+            push_sized(
+                &mut payload,
+                item.id as u64,
+                if version < 2 { 2 } else { 4 },
+            )?;
+            if version > 0 {
+                payload.extend_from_slice(&item.construction_method.to_be_bytes());
+            }
+            payload.extend_from_slice(&item.data_reference_index.to_be_bytes());
+            push_sized(&mut payload, item.base_offset, iloc.base_offset_size)?;
+            payload.extend_from_slice(&(item.extents.len() as u16).to_be_bytes());
+            for extent in &item.extents {
+                if version > 0 {
+                    push_sized(&mut payload, extent.index, iloc.index_size)?;
+                }
+                push_sized(&mut payload, extent.offset, iloc.offset_size)?;
+                push_sized(&mut payload, extent.length, iloc.length_size)?;
+            }
+        }
+
+        Ok(make_full_box(BOX_ILOC, version, iloc.flags, &payload))
+    }
+
+    /// Parse a primary item box payload
+    fn parse_pitm(data: &[u8]) -> XmpResult<u32> {
+        let mut reader = ByteReader::new(data);
+        let (version, _flags) = reader.full_box_header()?;
+        if version == 0 {
+            Ok(reader.u16()? as u32)
+        } else {
+            reader.u32()
+        }
+    }
+
+    /// Build an item reference box with a new `cdsc` reference
+    fn add_cdsc_reference(iref: Option<&[u8]>, from: u32, to: u32) -> XmpResult<Vec<u8>> {
+        let (version, flags, mut payload) = match iref {
+            Some(data) => {
+                let mut reader = ByteReader::new(data);
+                let (version, flags) = reader.full_box_header()?;
+                (version, flags, reader.remaining().to_vec())
+            }
+            None => (u8::from(from > 0xFFFF || to > 0xFFFF), 0, Vec::new()),
+        };
+
+        let id_size = if version == 0 { 2 } else { 4 };
+        let mut reference = Vec::new();
+        push_sized(&mut reference, from as u64, id_size)?;
+        reference.extend_from_slice(&1u16.to_be_bytes());
+        push_sized(&mut reference, to as u64, id_size)?;
+        payload.extend_from_slice(&make_box(REF_TYPE_CDSC, &reference));
+
+        Ok(make_full_box(BOX_IREF, version, flags, &payload))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+    use std::io::Cursor;
+
+    const IMAGE_DATA: &[u8] = b"fake hevc image data";
+
+    /// Build a minimal HEIC file with one image item stored in mdat
+    fn create_minimal_heic() -> Vec<u8> {
+        create_minimal_file(b"heic", b"mif1heic", b"hvc1")
+    }
+
+    /// Build a minimal image file with the given brands and image item type
+    pub(crate) fn create_minimal_file(
+        major_brand: &[u8; 4],
+        compatible_brands: &[u8],
+        item_type: &[u8; 4],
+    ) -> Vec<u8> {
+        let mut ftyp = major_brand.to_vec();
+        ftyp.extend_from_slice(&0u32.to_be_bytes());
+        ftyp.extend_from_slice(compatible_brands);
+        let ftyp = make_box(BOX_FTYP, &ftyp);
+
+        let mut hdlr = vec![0u8; 4];
+        hdlr.extend_from_slice(b"pict");
+        hdlr.extend_from_slice(&[0u8; 13]);
+        let hdlr = make_full_box(b"hdlr", 0, 0, &hdlr);
+        let pitm = make_full_box(BOX_PITM, 0, 0, &1u16.to_be_bytes());
+
+        let mut infe = 1u16.to_be_bytes().to_vec();
+        infe.extend_from_slice(&0u16.to_be_bytes());
+        infe.extend_from_slice(item_type);
+        infe.push(0);
+        let infe = make_full_box(BOX_INFE, 2, 0, &infe);
+        let mut iinf = 1u16.to_be_bytes().to_vec();
+        iinf.extend_from_slice(&infe);
+        let iinf = make_full_box(BOX_IINF, 0, 0, &iinf);
+
+        let build = |image_offset: u32| {
+            let mut iloc = vec![0x44, 0x00];
+            iloc.extend_from_slice(&1u16.to_be_bytes()); // item_count
+            iloc.extend_from_slice(&1u16.to_be_bytes()); // item_ID
+            iloc.extend_from_slice(&0u16.to_be_bytes()); // data_reference_index
+            iloc.extend_from_slice(&1u16.to_be_bytes()); // extent_count
+            iloc.extend_from_slice(&image_offset.to_be_bytes());
+            iloc.extend_from_slice(&(IMAGE_DATA.len() as u32).to_be_bytes());
+            let iloc = make_full_box(BOX_ILOC, 0, 0, &iloc);
+
+            let meta = [hdlr.clone(), pitm.clone(), iloc, iinf.clone()].concat();
+            let meta = make_full_box(BOX_META, 0, 0, &meta);
+            [ftyp.clone(), meta, make_box(BOX_MDAT, IMAGE_DATA)].concat()
+        };
+        let size = build(0).len();
+        build((size - IMAGE_DATA.len()) as u32)
+    }
+
+    /// Read the data of an item through the item location box
+    fn read_item(data: &[u8], id: u32) -> Vec<u8> {
+        let mut reader = Cursor::new(data);
+        let boxes = HeifHandler::read_top_level_boxes(&mut reader).unwrap();
+        let meta = boxes.iter().find(|b| b.box_type == *BOX_META).unwrap();
+        let payload = HeifHandler::read_payload(&mut reader, meta).unwrap();
+        let children = HeifHandler::parse_children(&payload[4..]).unwrap();
+        let iloc = HeifHandler::find_child(&payload[4..], &children, BOX_ILOC).unwrap();
+        let iloc = HeifHandler::parse_iloc(iloc).unwrap();
+        let item = iloc.items.iter().find(|item| item.id == id).unwrap();
+        let start = (item.base_offset + item.extents[0].offset) as usize;
+        data[start..start + item.extents[0].length as usize].to_vec()
+    }
+
+    fn write_title(data: &[u8], title: &str) -> Vec<u8> {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String(title.to_string()))
+            .unwrap();
+        let mut writer = Cursor::new(Vec::new());
+        HeifHandler::write_xmp(Cursor::new(data), &mut writer, &meta).unwrap();
+        writer.into_inner()
+    }
+
+    #[test]
+    fn test_can_handle() {
+        let handler = HeifHandler;
+        assert!(handler
+            .can_handle(&mut Cursor::new(create_minimal_heic()))
+            .unwrap());
+
+        let mut avif = b"avif".to_vec();
+        avif.extend_from_slice(&0u32.to_be_bytes());
+        avif.extend_from_slice(b"mif1avif");
+        let avif = make_box(BOX_FTYP, &avif);
+        assert!(!handler.can_handle(&mut Cursor::new(avif)).unwrap());
+    }
+
+    #[test]
+    fn test_read_xmp_no_xmp() {
+        let result = HeifHandler::read_xmp(Cursor::new(create_minimal_heic())).unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_invalid_heif() {
+        let result = HeifHandler::read_xmp(Cursor::new(vec![0x00, 0x01, 0x02]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_write_xmp() {
+        let data = write_title(&create_minimal_heic(), "HEIF Image");
+
+        // The image item is relocated
+        assert_eq!(read_item(&data, 1), IMAGE_DATA);
+
+        let meta = HeifHandler::read_xmp(Cursor::new(&data)).unwrap().unwrap();
+        assert_eq!(
+            meta.get_property(ns::DC, "title"),
+            Some(XmpValue::String("HEIF Image".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_write_xmp_replaces_existing() {
+        let first = write_title(&create_minimal_heic(), "First");
+        let second = write_title(&first, "Second title");
+
+        // The previous XMP box is dropped, not accumulated
+        assert_eq!(
+            second.len() - first.len(),
+            "Second title".len() - "First".len()
+        );
+        assert_eq!(read_item(&second, 1), IMAGE_DATA);
+
+        let meta = HeifHandler::read_xmp(Cursor::new(&second))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            meta.get_property(ns::DC, "title"),
+            Some(XmpValue::String("Second title".to_string()))
+        );
+    }
+}
@@ -0,0 +1,794 @@
+//! ID3v2 tags
+//!
+//! This module reads and writes the frames of ID3v2 tags, such as the tag
+//! at the start of MP3 files, and reconciles them with XMP. The
+//! implementation is pure Rust and cross-platform compatible.
+//!
+//! ID3v2 Structure:
+//! - Tag header: "ID3" + version (2 bytes) + flags (1 byte) + tag size
+//!   (4 bytes, synchsafe)
+//! - Frames: ID (3 bytes in v2.2, 4 bytes otherwise) + size (3 bytes in
+//!   v2.2, 4 bytes in v2.3, 4 bytes synchsafe in v2.4) + flags (2 bytes,
+//!   v2.3 and v2.4 only) + content
+//! - Text frames start with an encoding byte: Latin-1 (0), UTF-16 with BOM
+//!   (1), UTF-16BE (2) or UTF-8 (3)
+//!
+//! XMP Reconciliation:
+//! - Following the MP3 handler of the Adobe XMP Toolkit, the title, artist,
+//!   album, year, comment, genre, copyright, track number and composer
+//!   frames are mapped to `dc:`, `xmp:` and `xmpDM:` properties
+//! - A frame is only imported when the XMP has no corresponding property
+//! - When saving, the mapped frames are rewritten from the XMP; frames
+//!   without an XMP property are kept. Pictures (APIC) are never changed
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::core::namespace::ns;
+use crate::types::value::XmpValue;
+
+/// ID3v2 tag header size
+pub(crate) const TAG_HEADER_SIZE: usize = 10;
+
+/// Frame identifiers for ID3v2.2 and ID3v2.3/2.4
+const FRAME_COMMENT: (&str, &str) = ("COM", "COMM");
+const FRAME_PICTURE: (&str, &str) = ("PIC", "APIC");
+const FRAME_YEAR: (&str, &str) = ("TYE", "TYER");
+
+/// ID3v2.4 recording time frame (replaces TYER)
+const FRAME_RECORDING_TIME: &str = "TDRC";
+
+/// Language of new comment frames
+const COMMENT_LANGUAGE: &[u8; 3] = b"eng";
+
+/// How a frame is converted to XMP
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Conversion {
+    /// Text, as a simple property
+    Text,
+    /// Text, as the `x-default` item of a language alternative
+    LangAlt,
+    /// Track number ("3" or "3/12"), as an integer
+    Track,
+    /// Genre, resolving ID3v1 genre numbers ("(17)" or "17")
+    Genre,
+    /// Comment frame, as a simple property
+    Comment,
+    /// Year (v2.2 and v2.3) or recording time (v2.4), as a date
+    Date,
+}
+
+/// Mapping of frames (ID3v2.2 ID, ID3v2.3/2.4 ID) to XMP properties
+const MAPPINGS: &[(&str, &str, &str, &str, Conversion)] = &[
+    ("TT2", "TIT2", ns::DC, "title", Conversion::LangAlt),
+    ("TP1", "TPE1", ns::XMP_DM, "artist", Conversion::Text),
+    ("TAL", "TALB", ns::XMP_DM, "album", Conversion::Text),
+    ("TYE", "TYER", ns::XMP, "CreateDate", Conversion::Date),
+    ("COM", "COMM", ns::XMP_DM, "logComment", Conversion::Comment),
+    ("TCO", "TCON", ns::XMP_DM, "genre", Conversion::Genre),
+    ("TCR", "TCOP", ns::DC, "rights", Conversion::LangAlt),
+    ("TRK", "TRCK", ns::XMP_DM, "trackNumber", Conversion::Track),
+    ("TCM", "TCOM", ns::XMP_DM, "composer", Conversion::Text),
+];
+
+/// ID3v1 genres, referenced by number in genre frames
+const GENRES: &[&str] = &[
+    "Blues",
+    "Classic Rock",
+    "Country",
+    "Dance",
+    "Disco",
+    "Funk",
+    "Grunge",
+    "Hip-Hop",
+    "Jazz",
+    "Metal",
+    "New Age",
+    "Oldies",
+    "Other",
+    "Pop",
+    "R&B",
+    "Rap",
+    "Reggae",
+    "Rock",
+    "Techno",
+    "Industrial",
+    "Alternative",
+    "Ska",
+    "Death Metal",
+    "Pranks",
+    "Soundtrack",
+    "Euro-Techno",
+    "Ambient",
+    "Trip-Hop",
+    "Vocal",
+    "Jazz+Funk",
+    "Fusion",
+    "Trance",
+    "Classical",
+    "Instrumental",
+    "Acid",
+    "House",
+    "Game",
+    "Sound Clip",
+    "Gospel",
+    "Noise",
+    "AlternRock",
+    "Bass",
+    "Soul",
+    "Punk",
+    "Space",
+    "Meditative",
+    "Instrumental Pop",
+    "Instrumental Rock",
+    "Ethnic",
+    "Gothic",
+    "Darkwave",
+    "Techno-Industrial",
+    "Electronic",
+    "Pop-Folk",
+    "Eurodance",
+    "Dream",
+    "Southern Rock",
+    "Comedy",
+    "Cult",
+    "Gangsta",
+    "Top 40",
+    "Christian Rap",
+    "Pop/Funk",
+    "Jungle",
+    "Native American",
+    "Cabaret",
+    "New Wave",
+    "Psychadelic",
+    "Rave",
+    "Showtunes",
+    "Trailer",
+    "Lo-Fi",
+    "Tribal",
+    "Acid Punk",
+    "Acid Jazz",
+    "Polka",
+    "Retro",
+    "Musical",
+    "Rock & Roll",
+    "Hard Rock",
+];
+
+/// An ID3v2 frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Id3Frame {
+    /// The frame ID ("TIT2", or "TT2" in ID3v2.2)
+    pub id: String,
+    /// The frame flags (always 0 in ID3v2.2)
+    pub flags: u16,
+    /// The raw frame content
+    pub data: Vec<u8>,
+}
+
+impl Id3Frame {
+    /// Get the content of the frame, without the prefixes added by its
+    /// flags
+    ///
+    /// Returns `None` for compressed, encrypted and unsynchronized frames.
+    fn content(&self, major_version: u8) -> Option<&[u8]> {
+        let (unsupported, grouping, data_length) = match major_version {
+            3 => (0x00C0, 0x0020, 0x0000),
+            4 => (0x000E, 0x0040, 0x0001),
+            _ => (0, 0, 0),
+        };
+        if self.flags & unsupported != 0 {
+            return None;
+        }
+        let mut content = self.data.as_slice();
+        if self.flags & grouping != 0 {
+            content = content.get(1..)?;
+        }
+        if self.flags & data_length != 0 {
+            content = content.get(4..)?;
+        }
+        Some(content)
+    }
+}
+
+/// An ID3v2 tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Id3Tag {
+    major_version: u8,
+    frames: Vec<Id3Frame>,
+}
+
+impl Id3Tag {
+    /// Parse an ID3v2 tag
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The tag, starting with its header
+    pub fn parse(data: &[u8]) -> XmpResult<Self> {
+        if data.len() < TAG_HEADER_SIZE || &data[..3] != b"ID3" {
+            return Err(XmpError::BadValue("Not an ID3v2 tag".to_string()));
+        }
+        let (major_version, flags) = (data[3], data[5]);
+        if !(2..=4).contains(&major_version) {
+            return Err(XmpError::BadValue(format!(
+                "Unsupported ID3v2 version: {}",
+                major_version
+            )));
+        }
+        if flags & 0x80 != 0 {
+            return Err(XmpError::NotSupported(
+                "Unsynchronized ID3v2 tags not supported".to_string(),
+            ));
+        }
+        let tag_end = (TAG_HEADER_SIZE + synchsafe(&data[6..10])? as usize).min(data.len());
+
+        let mut pos = TAG_HEADER_SIZE;
+        if flags & 0x40 != 0 && major_version > 2 {
+            // The v2.3 size excludes itself, the v2.4 size includes itself
+            let size = data
+                .get(pos..pos + 4)
+                .ok_or_else(|| XmpError::BadValue("Truncated ID3v2 tag".to_string()))?;
+            pos += if major_version == 3 {
+                4 + u32::from_be_bytes([size[0], size[1], size[2], size[3]]) as usize
+            } else {
+                synchsafe(size)? as usize
+            };
+        }
+
+        let header_size = if major_version == 2 { 6 } else { 10 };
+        let mut frames = Vec::new();
+        while pos + header_size <= tag_end {
+            let header = &data[pos..pos + header_size];
+            // Padding
+            if header[0] == 0 {
+                break;
+            }
+            let (id, size, flags) = match major_version {
+                2 => (
+                    &header[..3],
+                    u32::from_be_bytes([0, header[3], header[4], header[5]]),
+                    0,
+                ),
+                3 => (
+                    &header[..4],
+                    u32::from_be_bytes([header[4], header[5], header[6], header[7]]),
+                    u16::from_be_bytes([header[8], header[9]]),
+                ),
+                _ => (
+                    &header[..4],
+                    synchsafe(&header[4..8])?,
+                    u16::from_be_bytes([header[8], header[9]]),
+                ),
+            };
+            let start = pos + header_size;
+            let end = start + size as usize;
+            if end > tag_end {
+                return Err(XmpError::BadValue("Truncated ID3v2 frame".to_string()));
+            }
+            frames.push(Id3Frame {
+                id: String::from_utf8_lossy(id).into_owned(),
+                flags,
+                data: data[start..end].to_vec(),
+            });
+            pos = end;
+        }
+
+        Ok(Self {
+            major_version,
+            frames,
+        })
+    }
+
+    /// Serialize the tag, with its header and without padding
+    ///
+    /// The extended header (if any) is not written.
+    pub fn to_bytes(&self) -> XmpResult<Vec<u8>> {
+        let mut body = Vec::new();
+        for frame in &self.frames {
+            let size = u32::try_from(frame.data.len())
+                .ok()
+                .filter(|&size| size <= 0x0FFF_FFFF)
+                .ok_or_else(|| XmpError::BadValue("ID3v2 frame too large".to_string()))?;
+            body.extend_from_slice(frame.id.as_bytes());
+            match self.major_version {
+                2 => body.extend_from_slice(&size.to_be_bytes()[1..]),
+                3 => body.extend_from_slice(&size.to_be_bytes()),
+                _ => body.extend_from_slice(&to_synchsafe(size)),
+            }
+            if self.major_version > 2 {
+                body.extend_from_slice(&frame.flags.to_be_bytes());
+            }
+            body.extend_from_slice(&frame.data);
+        }
+        let size = u32::try_from(body.len())
+            .ok()
+            .filter(|&size| size <= 0x0FFF_FFFF)
+            .ok_or_else(|| XmpError::BadValue("ID3v2 tag too large".to_string()))?;
+
+        let mut tag = b"ID3".to_vec();
+        tag.extend_from_slice(&[self.major_version, 0, 0]);
+        tag.extend_from_slice(&to_synchsafe(size));
+        tag.extend(body);
+        Ok(tag)
+    }
+
+    /// Get the major version (2, 3 or 4)
+    pub fn version(&self) -> u8 {
+        self.major_version
+    }
+
+    /// Get all frames, in file order
+    pub fn frames(&self) -> &[Id3Frame] {
+        &self.frames
+    }
+
+    /// Get the first frame with an ID
+    pub fn frame(&self, id: &str) -> Option<&Id3Frame> {
+        self.frames.iter().find(|frame| frame.id == id)
+    }
+
+    /// Get the value of a text frame (the first one, if it holds several)
+    pub fn text(&self, id: &str) -> Option<String> {
+        let content = self.frame(id)?.content(self.major_version)?;
+        let (&encoding, text) = content.split_first()?;
+        let (value, _) = split_terminated(text, encoding);
+        Some(decode(value, encoding)?.trim().to_string()).filter(|text| !text.is_empty())
+    }
+
+    /// Get the text of the comment frame
+    ///
+    /// The comment without a description is preferred.
+    pub fn comment(&self) -> Option<String> {
+        let id = self.id(FRAME_COMMENT);
+        let comments: Vec<(String, String)> = self
+            .frames
+            .iter()
+            .filter(|frame| frame.id == id)
+            .filter_map(|frame| parse_comment(frame.content(self.major_version)?))
+            .collect();
+        comments
+            .iter()
+            .find(|(description, _)| description.is_empty())
+            .or(comments.first())
+            .map(|(_, text)| text.clone())
+            .filter(|text| !text.is_empty())
+    }
+
+    /// Check if the tag has an attached picture (such as cover art)
+    pub fn has_picture(&self) -> bool {
+        self.frame(self.id(FRAME_PICTURE)).is_some()
+    }
+
+    /// Import the frames into XMP
+    ///
+    /// Frames are mapped to `dc:title`, `dc:rights`, `xmp:CreateDate` and
+    /// `xmpDM:` properties. A frame is only imported when the XMP has no
+    /// corresponding property.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` if any property was imported
+    /// * `Ok(false)` otherwise
+    /// * `Err(XmpError)` if an error occurs
+    pub fn import_into(&self, meta: &mut XmpMeta) -> XmpResult<bool> {
+        let mut imported = false;
+        for &(v22, v23, namespace, name, conversion) in MAPPINGS {
+            if meta.does_property_exist(namespace, name) {
+                continue;
+            }
+            let Some(value) = self.value(v22, v23, conversion) else {
+                continue;
+            };
+            match conversion {
+                Conversion::LangAlt => {
+                    meta.set_localized_text(namespace, name, "", "x-default", &value)?;
+                }
+                Conversion::Track => {
+                    let Some(track) = parse_track(&value) else {
+                        continue;
+                    };
+                    meta.set_property(namespace, name, XmpValue::Integer(track))?;
+                }
+                _ => meta.set_property(namespace, name, value.into())?,
+            }
+            imported = true;
+        }
+        Ok(imported)
+    }
+
+    /// Update the mapped frames from XMP
+    ///
+    /// Returns `true` if any frame was changed.
+    pub(crate) fn update_from(&mut self, meta: &XmpMeta) -> bool {
+        let mut updated = false;
+        for &(v22, v23, namespace, name, conversion) in MAPPINGS {
+            let Some(value) = export_value(meta, namespace, name, conversion) else {
+                continue;
+            };
+            let current = self.value(v22, v23, conversion);
+            let value = match conversion {
+                Conversion::Date => match self.major_version {
+                    4 => value,
+                    _ => value.chars().take(4).collect(),
+                },
+                // Keep the track count
+                Conversion::Track => match current.as_deref().and_then(|c| c.split_once('/')) {
+                    Some((_, count)) => format!("{}/{}", value, count),
+                    None => value,
+                },
+                _ => value,
+            };
+            let unchanged = match conversion {
+                Conversion::Track => {
+                    current.as_deref().and_then(parse_track) == parse_track(&value)
+                }
+                _ => current.as_deref() == Some(value.as_str()),
+            };
+            if unchanged || value.is_empty() {
+                continue;
+            }
+
+            let id = match (conversion, self.major_version) {
+                (Conversion::Date, 4) => FRAME_RECORDING_TIME,
+                _ => self.id((v22, v23)),
+            };
+            let data = match conversion {
+                Conversion::Comment => self.encode_comment(&value),
+                _ => self.encode_text(&value),
+            };
+            self.set_frame(id, data);
+            updated = true;
+        }
+        updated
+    }
+
+    /// Get the value of a mapped frame, as an XMP value
+    fn value(&self, v22: &str, v23: &str, conversion: Conversion) -> Option<String> {
+        match conversion {
+            Conversion::Comment => self.comment(),
+            Conversion::Date if self.major_version == 4 => self
+                .text(FRAME_RECORDING_TIME)
+                .or_else(|| self.text(self.id(FRAME_YEAR))),
+            Conversion::Genre => self
+                .text(self.id((v22, v23)))
+                .map(|genre| resolve_genre(&genre)),
+            _ => self.text(self.id((v22, v23))),
+        }
+    }
+
+    /// Get the frame ID used by the tag version
+    fn id<'a>(&self, (v22, v23): (&'a str, &'a str)) -> &'a str {
+        if self.major_version == 2 {
+            v22
+        } else {
+            v23
+        }
+    }
+
+    /// Replace the frames with an ID by a new frame
+    fn set_frame(&mut self, id: &str, data: Vec<u8>) {
+        let frame = Id3Frame {
+            id: id.to_string(),
+            flags: 0,
+            data,
+        };
+        match self.frames.iter().position(|f| f.id == id) {
+            Some(index) => {
+                self.frames[index] = frame;
+                let mut seen = false;
+                self.frames.retain(|f| {
+                    let duplicate = f.id == id && seen;
+                    seen |= f.id == id;
+                    !duplicate
+                });
+            }
+            None => self.frames.push(frame),
+        }
+    }
+
+    /// Encode text frame content: UTF-8 in ID3v2.4, otherwise Latin-1 or
+    /// UTF-16 with BOM
+    fn encode_text(&self, text: &str) -> Vec<u8> {
+        if self.major_version == 4 {
+            let mut data = vec![3];
+            data.extend_from_slice(text.as_bytes());
+            return data;
+        }
+        if text.chars().all(|c| (c as u32) < 0x100) {
+            let mut data = vec![0];
+            data.extend(text.chars().map(|c| c as u8));
+            return data;
+        }
+        let mut data = vec![1, 0xFF, 0xFE];
+        data.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        data
+    }
+
+    /// Encode comment frame content, without a description
+    fn encode_comment(&self, text: &str) -> Vec<u8> {
+        let encoded = self.encode_text(text);
+        let (encoding, text) = (encoded[0], &encoded[1..]);
+        let mut data = vec![encoding];
+        data.extend_from_slice(COMMENT_LANGUAGE);
+        // Empty description: a BOM for UTF-16, then the terminator
+        if encoding == 1 {
+            data.extend_from_slice(&[0xFF, 0xFE, 0, 0]);
+        } else {
+            data.push(0);
+        }
+        data.extend_from_slice(text);
+        data
+    }
+}
+
+/// Get the value of a mapped XMP property, or `None` if it doesn't exist
+fn export_value(
+    meta: &XmpMeta,
+    namespace: &str,
+    name: &str,
+    conversion: Conversion,
+) -> Option<String> {
+    if conversion == Conversion::LangAlt {
+        return meta
+            .get_localized_text(namespace, name, "", "x-default")
+            .map(|(text, _)| text);
+    }
+    let value = match meta.get_property(namespace, name)? {
+        XmpValue::String(s) | XmpValue::Uri(s) => s,
+        XmpValue::DateTime(dt) => dt.format(),
+        XmpValue::Integer(i) => i.to_string(),
+        _ => return None,
+    };
+    match conversion {
+        // ID3 timestamps have no fractions or time zone
+        Conversion::Date => {
+            let end = value
+                .char_indices()
+                .skip(10)
+                .find(|&(_, c)| matches!(c, '.' | 'Z' | '+' | '-'))
+                .map_or(value.len(), |(pos, _)| pos);
+            Some(value[..end].to_string())
+        }
+        Conversion::Track => parse_track(&value).map(|track| track.to_string()),
+        _ => Some(value),
+    }
+}
+
+/// Parse a track number ("3" or "3/12")
+fn parse_track(value: &str) -> Option<i64> {
+    value.split('/').next()?.trim().parse().ok()
+}
+
+/// Resolve ID3v1 genre references ("(17)", "(17)Rock" or "17")
+fn resolve_genre(genre: &str) -> String {
+    let by_number = |number: &str| {
+        number
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| GENRES.get(index))
+            .map(|genre| genre.to_string())
+    };
+    if let Some(rest) = genre.strip_prefix('(') {
+        if let Some((number, refinement)) = rest.split_once(')') {
+            if !refinement.is_empty() {
+                return refinement.to_string();
+            }
+            if let Some(genre) = by_number(number) {
+                return genre;
+            }
+        }
+    }
+    by_number(genre).unwrap_or_else(|| genre.to_string())
+}
+
+/// Parse comment frame content as its description and text
+fn parse_comment(content: &[u8]) -> Option<(String, String)> {
+    let (&encoding, rest) = content.split_first()?;
+    let rest = rest.get(3..)?;
+    let (description, text) = split_terminated(rest, encoding);
+    let (text, _) = split_terminated(text, encoding);
+    Some((
+        decode(description, encoding)?,
+        decode(text, encoding)?.trim().to_string(),
+    ))
+}
+
+/// Split encoded text at its terminator (NUL, or two aligned NUL bytes for
+/// UTF-16)
+fn split_terminated(data: &[u8], encoding: u8) -> (&[u8], &[u8]) {
+    if encoding == 1 || encoding == 2 {
+        let end = (0..data.len() / 2).find(|&i| data[2 * i] == 0 && data[2 * i + 1] == 0);
+        match end {
+            Some(i) => (&data[..2 * i], &data[2 * i + 2..]),
+            None => (data, &[]),
+        }
+    } else {
+        match data.iter().position(|&b| b == 0) {
+            Some(i) => (&data[..i], &data[i + 1..]),
+            None => (data, &[]),
+        }
+    }
+}
+
+/// Decode text with a frame encoding
+fn decode(data: &[u8], encoding: u8) -> Option<String> {
+    match encoding {
+        0 => Some(data.iter().map(|&b| b as char).collect()),
+        1 | 2 => {
+            let (big_endian, data) = match data {
+                [0xFE, 0xFF, rest @ ..] => (true, rest),
+                [0xFF, 0xFE, rest @ ..] => (false, rest),
+                _ => (encoding == 2, data),
+            };
+            let units: Vec<u16> = data
+                .chunks_exact(2)
+                .map(|pair| {
+                    if big_endian {
+                        u16::from_be_bytes([pair[0], pair[1]])
+                    } else {
+                        u16::from_le_bytes([pair[0], pair[1]])
+                    }
+                })
+                .collect();
+            Some(String::from_utf16_lossy(&units))
+        }
+        3 => Some(String::from_utf8_lossy(data).into_owned()),
+        _ => None,
+    }
+}
+
+/// Decode a synchsafe integer (7 bits per byte, big-endian)
+fn synchsafe(bytes: &[u8]) -> XmpResult<u32> {
+    if bytes.iter().any(|&b| b & 0x80 != 0) {
+        return Err(XmpError::BadValue("Invalid synchsafe integer".to_string()));
+    }
+    Ok(bytes.iter().fold(0, |value, &b| (value << 7) | b as u32))
+}
+
+/// Encode a synchsafe integer (the value must fit in 28 bits)
+fn to_synchsafe(value: u32) -> [u8; 4] {
+    [
+        ((value >> 21) & 0x7F) as u8,
+        ((value >> 14) & 0x7F) as u8,
+        ((value >> 7) & 0x7F) as u8,
+        (value & 0x7F) as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_frame(id: &str, text: &[u8]) -> Id3Frame {
+        Id3Frame {
+            id: id.to_string(),
+            flags: 0,
+            data: text.to_vec(),
+        }
+    }
+
+    /// ID3v2.3 tag with a Latin-1 title, a UTF-16 artist, a genre number,
+    /// a track with count, a comment and a picture
+    fn build_tag() -> Vec<u8> {
+        let mut comment = b"\0eng".to_vec();
+        comment.extend_from_slice(b"desc\0Described\0");
+        let tag = Id3Tag {
+            major_version: 3,
+            frames: vec![
+                text_frame("TIT2", b"\0Caf\xE9"),
+                text_frame("TPE1", b"\x01\xFF\xFEB\0a\0n\0d\0\0\0"),
+                text_frame("TCON", b"\0(17)"),
+                text_frame("TRCK", b"\x003/12"),
+                text_frame("TYER", b"\x002021"),
+                text_frame("COMM", &comment),
+                text_frame("COMM", b"\0eng\0Plain"),
+                text_frame("APIC", b"\0image/png\0\x03\0data"),
+            ],
+        };
+        tag.to_bytes().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let data = build_tag();
+        let tag = Id3Tag::parse(&data).unwrap();
+        assert_eq!(tag.version(), 3);
+        assert_eq!(tag.frames().len(), 8);
+        assert_eq!(tag.text("TIT2").as_deref(), Some("Café"));
+        assert_eq!(tag.text("TPE1").as_deref(), Some("Band"));
+        assert_eq!(tag.comment().as_deref(), Some("Plain"));
+        assert!(tag.has_picture());
+        assert_eq!(tag.to_bytes().unwrap(), data);
+
+        // Padding after the frames
+        let mut padded = data.clone();
+        padded.extend_from_slice(&[0; 16]);
+        padded[6..10].copy_from_slice(&to_synchsafe((data.len() - 10 + 16) as u32));
+        assert_eq!(Id3Tag::parse(&padded).unwrap(), tag);
+
+        assert!(Id3Tag::parse(b"ID3\x09\0\0\0\0\0\0").is_err());
+    }
+
+    #[test]
+    fn test_import_into() {
+        let tag = Id3Tag::parse(&build_tag()).unwrap();
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP_DM, "album", "Edited".into())
+            .unwrap();
+        assert!(tag.import_into(&mut meta).unwrap());
+
+        assert_eq!(
+            meta.get_localized_text(ns::DC, "title", "", "x-default")
+                .map(|(text, _)| text),
+            Some("Café".to_string())
+        );
+        assert_eq!(
+            meta.get_property(ns::XMP_DM, "artist"),
+            Some(XmpValue::String("Band".to_string()))
+        );
+        assert_eq!(
+            meta.get_property(ns::XMP_DM, "album"),
+            Some(XmpValue::String("Edited".to_string()))
+        );
+        assert_eq!(
+            meta.get_property(ns::XMP_DM, "genre"),
+            Some(XmpValue::String("Rock".to_string()))
+        );
+        assert_eq!(meta.get_property_i64(ns::XMP_DM, "trackNumber"), Some(3));
+        assert_eq!(
+            meta.get_property(ns::XMP, "CreateDate"),
+            Some(XmpValue::String("2021".to_string()))
+        );
+        assert_eq!(
+            meta.get_property(ns::XMP_DM, "logComment"),
+            Some(XmpValue::String("Plain".to_string()))
+        );
+        assert!(!tag.import_into(&mut meta).unwrap());
+    }
+
+    #[test]
+    fn test_update_from() {
+        let mut tag = Id3Tag::parse(&build_tag()).unwrap();
+        let mut meta = XmpMeta::new();
+        tag.import_into(&mut meta).unwrap();
+        assert!(!tag.update_from(&meta));
+
+        meta.set_localized_text(ns::DC, "title", "", "x-default", "Ωmega")
+            .unwrap();
+        meta.set_property(ns::XMP_DM, "trackNumber", XmpValue::Integer(4))
+            .unwrap();
+        meta.set_property(ns::XMP_DM, "logComment", "New".into())
+            .unwrap();
+        meta.set_property(ns::XMP, "CreateDate", "2024-05-06T07:08:09Z".into())
+            .unwrap();
+        assert!(tag.update_from(&meta));
+
+        let tag = Id3Tag::parse(&tag.to_bytes().unwrap()).unwrap();
+        assert_eq!(tag.text("TIT2").as_deref(), Some("Ωmega"));
+        assert_eq!(tag.text("TRCK").as_deref(), Some("4/12"));
+        assert_eq!(tag.text("TYER").as_deref(), Some("2024"));
+        assert_eq!(tag.comment().as_deref(), Some("New"));
+        assert_eq!(tag.frames().iter().filter(|f| f.id == "COMM").count(), 1);
+        // Other frames are kept
+        assert_eq!(tag.text("TPE1").as_deref(), Some("Band"));
+        assert!(tag.has_picture());
+
+        // ID3v2.4 uses UTF-8 and the recording time frame
+        let mut tag = Id3Tag {
+            major_version: 4,
+            frames: Vec::new(),
+        };
+        assert!(tag.update_from(&meta));
+        let tag = Id3Tag::parse(&tag.to_bytes().unwrap()).unwrap();
+        assert_eq!(tag.frame("TIT2").unwrap().data[0], 3);
+        assert_eq!(tag.text("TDRC").as_deref(), Some("2024-05-06T07:08:09"));
+    }
+
+    #[test]
+    fn test_resolve_genre() {
+        assert_eq!(resolve_genre("(17)"), "Rock");
+        assert_eq!(resolve_genre("17"), "Rock");
+        assert_eq!(resolve_genre("(17)Indie Rock"), "Indie Rock");
+        assert_eq!(resolve_genre("Jazz"), "Jazz");
+        assert_eq!(resolve_genre("(999)"), "(999)");
+    }
+}
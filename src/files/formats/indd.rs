@@ -0,0 +1,307 @@
+//! InDesign file format handler
+//!
+//! This module provides functionality for reading XMP metadata in Adobe
+//! InDesign files (read-only). The implementation is pure Rust and
+//! cross-platform compatible.
+//!
+//! InDesign File Structure:
+//! - Two master pages of 4096 bytes, starting with a GUID (16 bytes) and
+//!   "DOCUMENT" (8 bytes); each holds a sequence number (8 bytes at offset
+//!   264) and the file page count (4 bytes at offset 280), little-endian.
+//!   The page with the highest sequence number is the current one
+//! - Contiguous objects follow the database pages (at `page count * 4096`):
+//!   header (GUID, object UID, class ID, stream length, checksum; 32 bytes,
+//!   little-endian) + stream + trailer (32 bytes)
+//!
+//! InDesign XMP Storage:
+//! - XMP Packet is stored in a contiguous object whose stream is a 4-byte
+//!   length followed by the packet
+//! - Writing is not supported: the packet is referenced by the document
+//!   database, which can't be safely updated
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::files::format::FormatCapabilities;
+use crate::files::handler::FileHandler;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// GUID at the start of the master pages
+const MASTER_PAGE_GUID: [u8; 16] = [
+    0x06, 0x06, 0xED, 0xF5, 0xD8, 0x1D, 0x46, 0xE5, 0xBD, 0x31, 0xEF, 0xE7, 0xFE, 0x74, 0xB7, 0x1D,
+];
+
+/// Magic bytes following the master page GUID
+const MASTER_PAGE_MAGIC: &[u8; 8] = b"DOCUMENT";
+
+/// GUID at the start of contiguous object headers
+const CONTIG_OBJ_HEADER_GUID: [u8; 16] = [
+    0xDE, 0x39, 0x39, 0x79, 0x51, 0x88, 0x4B, 0x6C, 0x8E, 0x63, 0xEE, 0xF8, 0xEE, 0xE0, 0xDF, 0x1D,
+];
+
+/// Size of a database page (and of each master page)
+const PAGE_SIZE: u64 = 4096;
+
+/// Size of contiguous object headers and trailers
+const CONTIG_OBJ_MARKER_SIZE: u64 = 32;
+
+/// Start of an XMP packet
+const PACKET_START: &[u8] = b"<?xpacket begin=";
+
+/// InDesign file handler for XMP metadata (read-only)
+#[derive(Debug, Clone, Copy)]
+pub struct InddHandler;
+
+impl FileHandler for InddHandler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        let pos = reader.stream_position()?;
+        let mut header = [0u8; 24];
+        let result = reader.read_exact(&mut header);
+        reader.seek(SeekFrom::Start(pos))?;
+        Ok(result.is_ok() && Self::is_master_page(&header))
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp(reader, writer, meta)
+    }
+
+    fn capabilities(&self) -> FormatCapabilities {
+        FormatCapabilities {
+            can_read: true,
+            ..Default::default()
+        }
+    }
+
+    fn format_name(&self) -> &'static str {
+        "InDesign"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["indd", "indt"]
+    }
+}
+
+impl InddHandler {
+    /// Read XMP metadata from an InDesign file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(XmpMeta))` if XMP metadata is found
+    /// * `Ok(None)` if no XMP metadata is found
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_xmp<R: Read + Seek>(mut reader: R) -> XmpResult<Option<XmpMeta>> {
+        let file_end = reader.seek(SeekFrom::End(0))?;
+        let mut pos = Self::contiguous_objects_offset(&mut reader)?;
+
+        while pos + 2 * CONTIG_OBJ_MARKER_SIZE <= file_end {
+            reader.seek(SeekFrom::Start(pos))?;
+            let mut header = [0u8; CONTIG_OBJ_MARKER_SIZE as usize];
+            reader.read_exact(&mut header)?;
+            if header[..16] != CONTIG_OBJ_HEADER_GUID {
+                break;
+            }
+            let stream_length =
+                u32::from_le_bytes([header[24], header[25], header[26], header[27]]) as u64;
+            let stream_start = pos + CONTIG_OBJ_MARKER_SIZE;
+            if stream_length > file_end - stream_start {
+                return Err(XmpError::BadValue(
+                    "Invalid InDesign contiguous object length".to_string(),
+                ));
+            }
+
+            if let Some(packet) = Self::read_packet(&mut reader, stream_length)? {
+                let xmp_str = String::from_utf8(packet)
+                    .map_err(|e| XmpError::ParseError(format!("Invalid UTF-8 in XMP: {}", e)))?;
+                return XmpMeta::parse(&xmp_str).map(Some);
+            }
+            pos = stream_start + stream_length + CONTIG_OBJ_MARKER_SIZE;
+        }
+
+        Ok(None)
+    }
+
+    /// Write XMP metadata to an InDesign file
+    ///
+    /// InDesign files are read-only: this always returns
+    /// `XmpError::NotSupported`.
+    pub fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        _reader: R,
+        _writer: W,
+        _meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Err(XmpError::NotSupported(
+            "Writing XMP to InDesign files (read-only format)".to_string(),
+        ))
+    }
+
+    /// Check the GUID and magic bytes of a master page
+    fn is_master_page(page: &[u8]) -> bool {
+        page.len() >= 24 && page[..16] == MASTER_PAGE_GUID && page[16..24] == *MASTER_PAGE_MAGIC
+    }
+
+    /// Get the offset of the first contiguous object from the current
+    /// master page
+    fn contiguous_objects_offset<R: Read + Seek>(reader: &mut R) -> XmpResult<u64> {
+        let mut current: Option<(u64, u32)> = None;
+        for index in 0..2 {
+            reader.seek(SeekFrom::Start(index * PAGE_SIZE))?;
+            let mut page = [0u8; 284];
+            reader
+                .read_exact(&mut page)
+                .map_err(|_| XmpError::BadValue("Truncated InDesign master page".to_string()))?;
+            if !Self::is_master_page(&page) {
+                return Err(XmpError::BadValue("Not a valid InDesign file".to_string()));
+            }
+            let mut sequence = [0u8; 8];
+            sequence.copy_from_slice(&page[264..272]);
+            let sequence = u64::from_le_bytes(sequence);
+            let pages = u32::from_le_bytes([page[280], page[281], page[282], page[283]]);
+            if current.is_none_or(|(best, _)| sequence > best) {
+                current = Some((sequence, pages));
+            }
+        }
+
+        let (_, pages) = current.unwrap_or_default();
+        Ok(pages as u64 * PAGE_SIZE)
+    }
+
+    /// Read the XMP packet of a contiguous object stream, if it holds one
+    ///
+    /// The reader must be positioned at the start of the stream.
+    fn read_packet<R: Read + Seek>(
+        reader: &mut R,
+        stream_length: u64,
+    ) -> XmpResult<Option<Vec<u8>>> {
+        let prefix_len = 4 + PACKET_START.len() as u64;
+        if stream_length < prefix_len {
+            return Ok(None);
+        }
+        let mut prefix = vec![0u8; prefix_len as usize];
+        reader.read_exact(&mut prefix)?;
+        if prefix[4..] != *PACKET_START {
+            return Ok(None);
+        }
+
+        // The length word uses the object stream byte order
+        let length_bytes = [prefix[0], prefix[1], prefix[2], prefix[3]];
+        let length = [
+            u32::from_le_bytes(length_bytes),
+            u32::from_be_bytes(length_bytes),
+        ]
+        .into_iter()
+        .map(u64::from)
+        .find(|&length| length >= PACKET_START.len() as u64 && length <= stream_length - 4)
+        .ok_or_else(|| XmpError::BadValue("Invalid InDesign XMP length".to_string()))?;
+
+        let mut packet = prefix[4..].to_vec();
+        packet.resize(length as usize, 0);
+        reader.read_exact(&mut packet[PACKET_START.len()..])?;
+        Ok(Some(packet))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+    use std::io::Cursor;
+
+    fn master_page(sequence: u64, pages: u32) -> Vec<u8> {
+        let mut page = MASTER_PAGE_GUID.to_vec();
+        page.extend_from_slice(MASTER_PAGE_MAGIC);
+        page.push(1);
+        page.resize(264, 0);
+        page.extend_from_slice(&sequence.to_le_bytes());
+        page.resize(280, 0);
+        page.extend_from_slice(&pages.to_le_bytes());
+        page.resize(PAGE_SIZE as usize, 0);
+        page
+    }
+
+    fn contiguous_object(stream: &[u8]) -> Vec<u8> {
+        let mut object = CONTIG_OBJ_HEADER_GUID.to_vec();
+        object.extend_from_slice(&[0; 8]);
+        object.extend_from_slice(&(stream.len() as u32).to_le_bytes());
+        object.extend_from_slice(&[0; 4]);
+        object.extend_from_slice(stream);
+        object.extend_from_slice(&[0; CONTIG_OBJ_MARKER_SIZE as usize]);
+        object
+    }
+
+    /// InDesign file whose current master page (the second one) puts the
+    /// contiguous objects after 3 pages
+    fn build_indd(streams: &[Vec<u8>]) -> Vec<u8> {
+        let mut indd = master_page(1, 2);
+        indd.extend(master_page(2, 3));
+        indd.resize(3 * PAGE_SIZE as usize, 0);
+        for stream in streams {
+            indd.extend(contiguous_object(stream));
+        }
+        indd
+    }
+
+    fn xmp_stream(title: &str, big_endian: bool) -> Vec<u8> {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String(title.to_string()))
+            .unwrap();
+        let packet = meta.serialize_packet().unwrap();
+        let len = packet.len() as u32;
+        let mut stream = if big_endian {
+            len.to_be_bytes().to_vec()
+        } else {
+            len.to_le_bytes().to_vec()
+        };
+        stream.extend_from_slice(packet.as_bytes());
+        stream
+    }
+
+    #[test]
+    fn test_can_handle() {
+        let handler = InddHandler;
+        assert!(handler
+            .can_handle(&mut Cursor::new(build_indd(&[])))
+            .unwrap());
+        assert!(!handler.can_handle(&mut Cursor::new(vec![0u8; 64])).unwrap());
+    }
+
+    #[test]
+    fn test_read_xmp() {
+        let indd = build_indd(&[b"other object data".to_vec(), xmp_stream("Layout", false)]);
+        let meta = InddHandler::read_xmp(Cursor::new(indd)).unwrap().unwrap();
+        assert_eq!(
+            meta.get_property(ns::DC, "title"),
+            Some(XmpValue::String("Layout".to_string()))
+        );
+
+        let indd = build_indd(&[xmp_stream("Big endian", true)]);
+        let meta = InddHandler::read_xmp(Cursor::new(indd)).unwrap().unwrap();
+        assert_eq!(
+            meta.get_property(ns::DC, "title"),
+            Some(XmpValue::String("Big endian".to_string()))
+        );
+
+        let indd = build_indd(&[b"no metadata".to_vec()]);
+        assert!(InddHandler::read_xmp(Cursor::new(indd)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_write_not_supported() {
+        let indd = build_indd(&[xmp_stream("Layout", false)]);
+        let result =
+            InddHandler::write_xmp(Cursor::new(indd), Cursor::new(Vec::new()), &XmpMeta::new());
+        assert!(matches!(result, Err(XmpError::NotSupported(_))));
+    }
+}
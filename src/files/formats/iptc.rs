@@ -0,0 +1,879 @@
+//! IPTC-IIM metadata
+//!
+//! This module reads and writes the IPTC Information Interchange Model
+//! (IIM) records stored in Photoshop image resources, such as the APP13
+//! segment of JPEG files, and reconciles them with XMP. The implementation
+//! is pure Rust and cross-platform compatible.
+//!
+//! Photoshop Image Resources:
+//! - A sequence of blocks: signature (4 bytes, usually `8BIM`) + ID
+//!   (2 bytes) + Pascal name padded to an even size + data length
+//!   (4 bytes) + data padded to an even size, big-endian
+//! - Resource 0x0404 holds the IIM records; resource 0x0425 holds the MD5
+//!   digest of the IIM records, written by applications that keep the XMP
+//!   in sync with them
+//!
+//! IIM Structure:
+//! - A sequence of datasets: tag marker 0x1C + record (1 byte) + dataset
+//!   (1 byte) + length (2 bytes, big-endian) + data
+//! - Dataset 1:90 declares the character set (`ESC % G` for UTF-8);
+//!   record 2 holds the application datasets (caption, keywords, ...)
+//!
+//! XMP Reconciliation:
+//! - Following the IPTC Photo Metadata Standard, record 2 datasets are
+//!   mapped to `dc:`, `photoshop:` and `Iptc4xmpCore:` properties
+//! - If the digest matches the IIM, the XMP is up to date and nothing is
+//!   imported. If it doesn't match, the IIM was edited by an application
+//!   unaware of XMP, and its values replace the XMP. Without a digest,
+//!   values are only imported when the XMP has no corresponding property
+//! - When saving, the mapped datasets are rewritten from the XMP (as UTF-8)
+//!   and the digest is updated; datasets without an XMP property are kept
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::core::namespace::ns;
+use crate::core::node::ArrayType;
+use crate::types::value::XmpValue;
+use crate::utils::md5;
+
+/// Signature of Photoshop image resource blocks
+const RESOURCE_SIGNATURE: &[u8; 4] = b"8BIM";
+
+/// Image resource holding the IIM records
+const RESOURCE_IPTC: u16 = 0x0404;
+
+/// Image resource holding the MD5 digest of the IIM records
+const RESOURCE_IPTC_DIGEST: u16 = 0x0425;
+
+/// Marker starting each IIM dataset
+const TAG_MARKER: u8 = 0x1C;
+
+/// Record of the envelope datasets
+const RECORD_ENVELOPE: u8 = 1;
+
+/// Record of the application datasets
+const RECORD_APPLICATION: u8 = 2;
+
+/// Envelope dataset declaring the coded character set
+const DATASET_CHARSET: u8 = 90;
+
+/// Coded character set of UTF-8
+const CHARSET_UTF8: &[u8] = b"\x1B%G";
+
+/// Application dataset holding the record version
+const DATASET_RECORD_VERSION: u8 = 0;
+
+/// Application datasets holding the creation date and time
+const DATASET_DATE_CREATED: u8 = 55;
+const DATASET_TIME_CREATED: u8 = 60;
+
+/// Record version written when creating the application record
+const RECORD_VERSION: &[u8] = &[0, 4];
+
+/// Largest dataset written (larger values need an extended length)
+const MAX_DATASET_SIZE: usize = 0x7FFF;
+
+/// How an IIM dataset is converted to XMP
+#[derive(Debug, Clone, Copy)]
+enum Conversion {
+    /// Text, as a simple property
+    Text,
+    /// Text, as the `x-default` item of a language alternative
+    LangAlt,
+    /// Repeatable text, as an unordered array
+    Bag,
+    /// Repeatable text, as an ordered array
+    Seq,
+    /// Object attribute ("number:name"), as the name
+    Genre,
+    /// Repeatable subject reference ("IPTC:number:..."), as an unordered
+    /// array of subject numbers
+    SubjectCode,
+}
+
+/// Mapping of application datasets to XMP properties
+const MAPPINGS: &[(u8, &str, &str, Conversion)] = &[
+    (4, ns::IPTC_CORE, "IntellectualGenre", Conversion::Genre),
+    (5, ns::DC, "title", Conversion::LangAlt),
+    (10, ns::PHOTOSHOP, "Urgency", Conversion::Text),
+    (12, ns::IPTC_CORE, "SubjectCode", Conversion::SubjectCode),
+    (15, ns::PHOTOSHOP, "Category", Conversion::Text),
+    (20, ns::PHOTOSHOP, "SupplementalCategories", Conversion::Bag),
+    (25, ns::DC, "subject", Conversion::Bag),
+    (40, ns::PHOTOSHOP, "Instructions", Conversion::Text),
+    (80, ns::DC, "creator", Conversion::Seq),
+    (85, ns::PHOTOSHOP, "AuthorsPosition", Conversion::Text),
+    (90, ns::PHOTOSHOP, "City", Conversion::Text),
+    (92, ns::IPTC_CORE, "Location", Conversion::Text),
+    (95, ns::PHOTOSHOP, "State", Conversion::Text),
+    (100, ns::IPTC_CORE, "CountryCode", Conversion::Text),
+    (101, ns::PHOTOSHOP, "Country", Conversion::Text),
+    (
+        103,
+        ns::PHOTOSHOP,
+        "TransmissionReference",
+        Conversion::Text,
+    ),
+    (105, ns::PHOTOSHOP, "Headline", Conversion::Text),
+    (110, ns::PHOTOSHOP, "Credit", Conversion::Text),
+    (115, ns::PHOTOSHOP, "Source", Conversion::Text),
+    (116, ns::DC, "rights", Conversion::LangAlt),
+    (120, ns::DC, "description", Conversion::LangAlt),
+    (122, ns::PHOTOSHOP, "CaptionWriter", Conversion::Text),
+];
+
+/// An IIM dataset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IptcDataSet {
+    /// The record number (1 for the envelope, 2 for the application)
+    pub record: u8,
+    /// The dataset number
+    pub dataset: u8,
+    /// The raw data
+    pub data: Vec<u8>,
+}
+
+/// IPTC-IIM metadata
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IptcData {
+    datasets: Vec<IptcDataSet>,
+}
+
+impl IptcData {
+    /// Parse IIM records
+    ///
+    /// Parsing stops at the first byte that doesn't start a dataset (such
+    /// as padding).
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The IIM records
+    pub fn parse(data: &[u8]) -> XmpResult<Self> {
+        let mut datasets = Vec::new();
+        let mut pos = 0;
+        while pos + 5 <= data.len() && data[pos] == TAG_MARKER {
+            let (record, dataset) = (data[pos + 1], data[pos + 2]);
+            let mut length = u16::from_be_bytes([data[pos + 3], data[pos + 4]]) as usize;
+            pos += 5;
+
+            // Extended datasets store the size of their length in the
+            // lower 15 bits
+            if length & 0x8000 != 0 {
+                let size = length & 0x7FFF;
+                if size > 4 || pos + size > data.len() {
+                    return Err(XmpError::BadValue(
+                        "Invalid IIM extended dataset length".to_string(),
+                    ));
+                }
+                length = data[pos..pos + size]
+                    .iter()
+                    .fold(0, |length, &b| (length << 8) | b as usize);
+                pos += size;
+            }
+            if pos + length > data.len() {
+                return Err(XmpError::BadValue("Truncated IIM dataset".to_string()));
+            }
+
+            datasets.push(IptcDataSet {
+                record,
+                dataset,
+                data: data[pos..pos + length].to_vec(),
+            });
+            pos += length;
+        }
+        Ok(Self { datasets })
+    }
+
+    /// Serialize as IIM records
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for dataset in &self.datasets {
+            let data = &dataset.data[..dataset.data.len().min(MAX_DATASET_SIZE)];
+            bytes.extend_from_slice(&[TAG_MARKER, dataset.record, dataset.dataset]);
+            bytes.extend_from_slice(&(data.len() as u16).to_be_bytes());
+            bytes.extend_from_slice(data);
+        }
+        bytes
+    }
+
+    /// Get the data of a dataset (the first one, if repeated)
+    pub fn get(&self, record: u8, dataset: u8) -> Option<&[u8]> {
+        self.get_all(record, dataset).next()
+    }
+
+    /// Get the data of all occurrences of a dataset
+    pub fn get_all(&self, record: u8, dataset: u8) -> impl Iterator<Item = &[u8]> {
+        self.datasets
+            .iter()
+            .filter(move |d| d.record == record && d.dataset == dataset)
+            .map(|d| d.data.as_slice())
+    }
+
+    /// Get all datasets, in file order
+    pub fn datasets(&self) -> &[IptcDataSet] {
+        &self.datasets
+    }
+
+    /// Check if there are no datasets
+    pub fn is_empty(&self) -> bool {
+        self.datasets.is_empty()
+    }
+
+    /// Get all occurrences of an application dataset as text
+    ///
+    /// Text is decoded as UTF-8 when declared by dataset 1:90 or valid, and
+    /// as Latin-1 otherwise. Empty values are skipped.
+    pub fn texts(&self, dataset: u8) -> Vec<String> {
+        self.get_all(RECORD_APPLICATION, dataset)
+            .map(|data| self.decode(data))
+            .map(|text| text.trim_end_matches(['\0', ' ']).to_string())
+            .filter(|text| !text.is_empty())
+            .collect()
+    }
+
+    /// Import the IIM values into XMP
+    ///
+    /// Values are mapped to `dc:`, `photoshop:` and `Iptc4xmpCore:`
+    /// properties. A value is only imported when the XMP has no
+    /// corresponding property.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` if any property was imported
+    /// * `Ok(false)` otherwise
+    /// * `Err(XmpError)` if an error occurs
+    pub fn import_into(&self, meta: &mut XmpMeta) -> XmpResult<bool> {
+        self.import(meta, false)
+    }
+
+    /// Import the IIM values, replacing existing properties if `replace`
+    fn import(&self, meta: &mut XmpMeta, replace: bool) -> XmpResult<bool> {
+        let mut imported = false;
+
+        for &(dataset, namespace, name, conversion) in MAPPINGS {
+            let texts = self.texts(dataset);
+            if texts.is_empty() || (!replace && meta.does_property_exist(namespace, name)) {
+                continue;
+            }
+            if replace && import_matches(meta, namespace, name, conversion, &texts) {
+                continue;
+            }
+            meta.delete_property(namespace, name)?;
+
+            match conversion {
+                Conversion::Text => {
+                    meta.set_property(namespace, name, texts[0].clone().into())?;
+                }
+                Conversion::LangAlt => {
+                    meta.set_localized_text(namespace, name, "", "x-default", &texts[0])?;
+                }
+                Conversion::Genre => {
+                    let genre = texts[0].split_once(':').map_or(&*texts[0], |(_, g)| g);
+                    meta.set_property(namespace, name, genre.into())?;
+                }
+                Conversion::Bag | Conversion::Seq | Conversion::SubjectCode => {
+                    let array_type = match conversion {
+                        Conversion::Seq => ArrayType::Ordered,
+                        _ => ArrayType::Unordered,
+                    };
+                    for item in array_items(conversion, &texts) {
+                        meta.append_array_item_with(namespace, name, array_type, item.into())?;
+                    }
+                }
+            }
+            imported = true;
+        }
+
+        if let Some(date) = self.date_created() {
+            let exists = meta.does_property_exist(ns::PHOTOSHOP, "DateCreated");
+            let current = meta.get_property(ns::PHOTOSHOP, "DateCreated");
+            let same = matches!(
+                &current,
+                Some(XmpValue::String(d)) if *d == date
+            );
+            if (replace && !same) || !exists {
+                meta.set_property(ns::PHOTOSHOP, "DateCreated", date.into())?;
+                imported = true;
+            }
+        }
+
+        Ok(imported)
+    }
+
+    /// Get the creation date and time (datasets 2:55 and 2:60) as an XMP date
+    fn date_created(&self) -> Option<String> {
+        let date = self.texts(DATASET_DATE_CREATED).into_iter().next()?;
+        let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if date.len() != 8 || !digits(&date) {
+            return None;
+        }
+        let mut xmp = format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..8]);
+
+        // HHMMSS±HHMM
+        if let Some(time) = self.texts(DATASET_TIME_CREATED).into_iter().next() {
+            if time.is_ascii() && time.len() >= 6 && digits(&time[..6]) {
+                xmp.push_str(&format!("T{}:{}:{}", &time[..2], &time[2..4], &time[4..6]));
+                let zone = &time[6..];
+                if zone.len() == 5 && zone.starts_with(['+', '-']) && digits(&zone[1..]) {
+                    xmp.push_str(&format!("{}:{}", &zone[..3], &zone[3..]));
+                }
+            }
+        }
+        Some(xmp)
+    }
+
+    /// Update the application datasets from XMP
+    ///
+    /// Mapped datasets whose XMP property exists are replaced, the record
+    /// version is added if missing and the character set is set to UTF-8.
+    fn update_from(&mut self, meta: &XmpMeta) {
+        let mut updates: Vec<(u8, Vec<String>)> = Vec::new();
+        for &(dataset, namespace, name, conversion) in MAPPINGS {
+            if let Some(values) = export_values(meta, namespace, name, conversion) {
+                updates.push((dataset, values));
+            }
+        }
+        if let Some(date) = meta.get_property(ns::PHOTOSHOP, "DateCreated") {
+            if let Some((date, time)) = date.as_str().and_then(iim_date) {
+                updates.push((DATASET_DATE_CREATED, vec![date]));
+                updates.push((DATASET_TIME_CREATED, time.into_iter().collect()));
+            }
+        }
+        if updates.is_empty() && self.is_empty() {
+            return;
+        }
+
+        // The existing text is re-encoded as UTF-8 with the new character set
+        let mut datasets: Vec<IptcDataSet> = Vec::new();
+        for dataset in &self.datasets {
+            let updated = dataset.record == RECORD_APPLICATION
+                && updates.iter().any(|(number, _)| *number == dataset.dataset);
+            if updated || (dataset.record == RECORD_ENVELOPE && dataset.dataset == DATASET_CHARSET)
+            {
+                continue;
+            }
+            let data = if dataset.record == RECORD_APPLICATION
+                && dataset.dataset != DATASET_RECORD_VERSION
+            {
+                self.decode(&dataset.data).into_bytes()
+            } else {
+                dataset.data.clone()
+            };
+            datasets.push(IptcDataSet {
+                record: dataset.record,
+                dataset: dataset.dataset,
+                data,
+            });
+        }
+        datasets.push(IptcDataSet {
+            record: RECORD_ENVELOPE,
+            dataset: DATASET_CHARSET,
+            data: CHARSET_UTF8.to_vec(),
+        });
+        if !datasets
+            .iter()
+            .any(|d| d.record == RECORD_APPLICATION && d.dataset == DATASET_RECORD_VERSION)
+        {
+            datasets.push(IptcDataSet {
+                record: RECORD_APPLICATION,
+                dataset: DATASET_RECORD_VERSION,
+                data: RECORD_VERSION.to_vec(),
+            });
+        }
+        for (dataset, values) in updates {
+            for value in values {
+                datasets.push(IptcDataSet {
+                    record: RECORD_APPLICATION,
+                    dataset,
+                    data: truncate(&value).as_bytes().to_vec(),
+                });
+            }
+        }
+
+        // Records and datasets are stored in ascending order
+        datasets.sort_by_key(|d| (d.record, d.dataset));
+        self.datasets = datasets;
+    }
+
+    /// Decode text data with the character set of the records
+    fn decode(&self, data: &[u8]) -> String {
+        let utf8 = self.get(RECORD_ENVELOPE, DATASET_CHARSET) == Some(CHARSET_UTF8);
+        match std::str::from_utf8(data) {
+            Ok(text) => text.to_string(),
+            Err(_) if utf8 => String::from_utf8_lossy(data).into_owned(),
+            Err(_) => data.iter().map(|&b| b as char).collect(),
+        }
+    }
+}
+
+/// Check if a property already has the imported values
+fn import_matches(
+    meta: &XmpMeta,
+    namespace: &str,
+    name: &str,
+    conversion: Conversion,
+    texts: &[String],
+) -> bool {
+    match export_values(meta, namespace, name, conversion) {
+        Some(values) => match conversion {
+            Conversion::Bag | Conversion::Seq => values == texts,
+            Conversion::SubjectCode => values == array_items(conversion, texts),
+            _ => values.first().map(|v| truncate(v)) == Some(texts[0].as_str()),
+        },
+        None => false,
+    }
+}
+
+/// Get the XMP array items of repeatable datasets
+fn array_items(conversion: Conversion, texts: &[String]) -> Vec<String> {
+    match conversion {
+        Conversion::SubjectCode => texts
+            .iter()
+            .filter_map(|text| text.split(':').nth(1))
+            .filter(|code| !code.is_empty())
+            .map(str::to_string)
+            .collect(),
+        _ => texts.to_vec(),
+    }
+}
+
+/// Get the dataset values of an XMP property, or `None` if it doesn't exist
+fn export_values(
+    meta: &XmpMeta,
+    namespace: &str,
+    name: &str,
+    conversion: Conversion,
+) -> Option<Vec<String>> {
+    if !meta.does_property_exist(namespace, name) {
+        return None;
+    }
+    let simple = || match meta.get_property(namespace, name)? {
+        XmpValue::String(s) | XmpValue::Uri(s) => Some(s),
+        XmpValue::DateTime(dt) => Some(dt.format()),
+        XmpValue::Integer(i) => Some(i.to_string()),
+        XmpValue::Boolean(b) => Some(if b { "True" } else { "False" }.to_string()),
+        _ => None,
+    };
+    let items = || -> Vec<String> {
+        (0..meta.count_array_items(namespace, name))
+            .filter_map(|index| meta.get_array_item(namespace, name, index))
+            .filter_map(|value| value.as_str().map(str::to_string))
+            .collect()
+    };
+
+    let values = match conversion {
+        Conversion::Text => simple().into_iter().collect(),
+        Conversion::LangAlt => meta
+            .get_localized_text(namespace, name, "", "x-default")
+            .map(|(text, _)| text)
+            .into_iter()
+            .collect(),
+        Conversion::Genre => simple()
+            .map(|genre| format!("000:{}", genre))
+            .into_iter()
+            .collect(),
+        Conversion::Bag | Conversion::Seq => items(),
+        Conversion::SubjectCode => items()
+            .into_iter()
+            .map(|code| format!("IPTC:{}:::", code))
+            .collect(),
+    };
+    Some(values)
+}
+
+/// Convert an XMP date to IIM date ("CCYYMMDD") and time ("HHMMSS±HHMM")
+///
+/// Returns `None` for dates without a day. Times without a time zone are
+/// written as UTC.
+fn iim_date(date: &str) -> Option<(String, Option<String>)> {
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let (day, time) = date.split_once('T').unwrap_or((date, ""));
+    let parts: Vec<&str> = day.split('-').collect();
+    if parts.len() != 3 || [4, 2, 2] != [parts[0].len(), parts[1].len(), parts[2].len()] {
+        return None;
+    }
+    if !parts.iter().all(|part| digits(part)) {
+        return None;
+    }
+    let iim_date = parts.concat();
+    if time.is_empty() {
+        return Some((iim_date, None));
+    }
+
+    let (time, zone) = match time.find(['Z', '+', '-']) {
+        Some(pos) => (&time[..pos], &time[pos..]),
+        None => (time, "Z"),
+    };
+    let zone = if zone == "Z" {
+        "+0000".to_string()
+    } else {
+        zone.replace(':', "")
+    };
+    let time = time.split('.').next().unwrap_or_default();
+    let mut fields = time.split(':');
+    let mut field = || {
+        fields
+            .next()
+            .filter(|f| f.len() == 2 && digits(f))
+            .unwrap_or("00")
+    };
+    let (hour, minute, second) = (field(), field(), field());
+    if zone.len() != 5 || !digits(&zone[1..]) {
+        return Some((iim_date, None));
+    }
+    Some((
+        iim_date,
+        Some(format!("{}{}{}{}", hour, minute, second, zone)),
+    ))
+}
+
+/// Truncate text to the largest dataset size, on a character boundary
+fn truncate(text: &str) -> &str {
+    let mut end = text.len().min(MAX_DATASET_SIZE);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// A Photoshop image resource block
+struct ImageResource {
+    signature: [u8; 4],
+    id: u16,
+    /// Pascal name, without its length byte and padding
+    name: Vec<u8>,
+    data: Vec<u8>,
+}
+
+/// Parse Photoshop image resource blocks
+///
+/// Returns the blocks and any trailing data that isn't a block.
+fn parse_resources(data: &[u8]) -> XmpResult<(Vec<ImageResource>, &[u8])> {
+    let truncated = || XmpError::BadValue("Truncated Photoshop image resource".to_string());
+    let mut resources = Vec::new();
+    let mut pos = 0;
+    while pos + 12 <= data.len() && data[pos..pos + 4] == *RESOURCE_SIGNATURE {
+        let mut signature = [0u8; 4];
+        signature.copy_from_slice(&data[pos..pos + 4]);
+        let id = u16::from_be_bytes([data[pos + 4], data[pos + 5]]);
+
+        // Pascal name, padded to an even size (including the length byte)
+        let name_len = data[pos + 6] as usize;
+        let name_start = pos + 7;
+        let name_end = name_start + name_len;
+        let size_pos = pos + 6 + ((name_len + 2) & !1);
+        if size_pos + 4 > data.len() {
+            return Err(truncated());
+        }
+        let size = u32::from_be_bytes([
+            data[size_pos],
+            data[size_pos + 1],
+            data[size_pos + 2],
+            data[size_pos + 3],
+        ]) as usize;
+        let data_start = size_pos + 4;
+        if size > data.len() - data_start {
+            return Err(truncated());
+        }
+
+        resources.push(ImageResource {
+            signature,
+            id,
+            name: data[name_start..name_end].to_vec(),
+            data: data[data_start..data_start + size].to_vec(),
+        });
+        pos = (data_start + size + (size & 1)).min(data.len());
+    }
+    Ok((resources, &data[pos..]))
+}
+
+/// Serialize Photoshop image resource blocks
+fn write_resources(resources: &[ImageResource], trailing: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for resource in resources {
+        bytes.extend_from_slice(&resource.signature);
+        bytes.extend_from_slice(&resource.id.to_be_bytes());
+        bytes.push(resource.name.len() as u8);
+        bytes.extend_from_slice(&resource.name);
+        if resource.name.len() % 2 == 0 {
+            bytes.push(0);
+        }
+        bytes.extend_from_slice(&(resource.data.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&resource.data);
+        if resource.data.len() % 2 != 0 {
+            bytes.push(0);
+        }
+    }
+    bytes.extend_from_slice(trailing);
+    bytes
+}
+
+/// State of the digest of IIM records (image resource 0x0425)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IptcDigest {
+    /// The digest matches the IIM: the XMP is in sync with it
+    Matching,
+    /// The digest doesn't match the IIM: it was edited by an application
+    /// unaware of XMP
+    Mismatched,
+    /// There is no digest
+    #[default]
+    Missing,
+}
+
+/// Read the IIM records of Photoshop image resources
+///
+/// Returns `None` if there is no IPTC resource.
+pub(crate) fn read_resources(data: &[u8]) -> XmpResult<Option<IptcData>> {
+    Ok(read_resources_with_digest(data)?.map(|(records, _)| records))
+}
+
+/// Read the IIM records of Photoshop image resources and the state of
+/// their digest
+///
+/// Returns `None` if there is no IPTC resource.
+pub(crate) fn read_resources_with_digest(data: &[u8]) -> XmpResult<Option<(IptcData, IptcDigest)>> {
+    let (resources, _) = parse_resources(data)?;
+    let Some(iptc) = resources.iter().find(|r| r.id == RESOURCE_IPTC) else {
+        return Ok(None);
+    };
+    let digest = match resources.iter().find(|r| r.id == RESOURCE_IPTC_DIGEST) {
+        Some(digest) if digest.data == md5::digest(&iptc.data) => IptcDigest::Matching,
+        Some(_) => IptcDigest::Mismatched,
+        None => IptcDigest::Missing,
+    };
+    Ok(Some((IptcData::parse(&iptc.data)?, digest)))
+}
+
+/// Import the IIM records of Photoshop image resources into XMP
+///
+/// See the [module documentation](self) for how the digest resource
+/// decides which values are imported.
+pub(crate) fn import_resources(data: &[u8], meta: &mut XmpMeta) -> XmpResult<bool> {
+    match read_resources_with_digest(data)? {
+        None | Some((_, IptcDigest::Matching)) => Ok(false),
+        Some((records, IptcDigest::Mismatched)) => records.import(meta, true),
+        Some((records, IptcDigest::Missing)) => records.import(meta, false),
+    }
+}
+
+/// Export XMP values to the IIM records of Photoshop image resources
+///
+/// The IPTC and digest resources are created if needed. Returns `true` if
+/// `data` was modified.
+pub(crate) fn export_resources(data: &mut Vec<u8>, meta: &XmpMeta) -> XmpResult<bool> {
+    let (mut resources, trailing) = parse_resources(data)?;
+    let trailing = trailing.to_vec();
+    let index = resources.iter().position(|r| r.id == RESOURCE_IPTC);
+    let mut records = match index {
+        Some(index) => IptcData::parse(&resources[index].data)?,
+        None => IptcData::default(),
+    };
+    records.update_from(meta);
+    if records.is_empty() {
+        return Ok(false);
+    }
+
+    let iim = records.to_bytes();
+    let digest = md5::digest(&iim).to_vec();
+    let old_digest = resources
+        .iter()
+        .find(|r| r.id == RESOURCE_IPTC_DIGEST)
+        .map(|r| &r.data);
+    let old_iim = index.map(|index| &resources[index].data);
+    if old_iim == Some(&iim) && old_digest == Some(&digest) {
+        return Ok(false);
+    }
+
+    for (id, value) in [(RESOURCE_IPTC, iim), (RESOURCE_IPTC_DIGEST, digest)] {
+        match resources.iter_mut().find(|r| r.id == id) {
+            Some(resource) => resource.data = value,
+            None => resources.push(ImageResource {
+                signature: *RESOURCE_SIGNATURE,
+                id,
+                name: Vec::new(),
+                data: value,
+            }),
+        }
+    }
+    *data = write_resources(&resources, &trailing);
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dataset(record: u8, dataset: u8, data: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![TAG_MARKER, record, dataset];
+        bytes.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    /// IIM records with a Latin-1 caption, two keywords, a by-line and a
+    /// creation date
+    fn build_iim() -> Vec<u8> {
+        let mut iim = dataset(2, 0, &[0, 4]);
+        iim.extend(dataset(2, 5, b"Harbour"));
+        iim.extend(dataset(2, 25, b"boats"));
+        iim.extend(dataset(2, 25, b"sea"));
+        iim.extend(dataset(2, 55, b"20240506"));
+        iim.extend(dataset(2, 60, b"070809+0200"));
+        iim.extend(dataset(2, 80, b"Jane Doe"));
+        iim.extend(dataset(2, 120, b"Caf\xE9 by the sea"));
+        iim.extend(dataset(2, 200, b"custom"));
+        iim
+    }
+
+    fn resource(id: u16, data: &[u8]) -> Vec<u8> {
+        write_resources(
+            &[ImageResource {
+                signature: *RESOURCE_SIGNATURE,
+                id,
+                name: Vec::new(),
+                data: data.to_vec(),
+            }],
+            &[],
+        )
+    }
+
+    #[test]
+    fn test_parse() {
+        let iptc = IptcData::parse(&build_iim()).unwrap();
+        assert_eq!(iptc.datasets().len(), 9);
+        assert_eq!(iptc.get(2, 5), Some(&b"Harbour"[..]));
+        assert_eq!(iptc.texts(25), vec!["boats", "sea"]);
+        assert_eq!(iptc.texts(120), vec!["Café by the sea"]);
+        assert_eq!(
+            iptc.date_created().as_deref(),
+            Some("2024-05-06T07:08:09+02:00")
+        );
+        assert_eq!(iptc.to_bytes(), build_iim());
+
+        // Extended length
+        let data = [TAG_MARKER, 2, 5, 0x80, 0x02, 0x00, 0x02, b'h', b'i'];
+        assert_eq!(IptcData::parse(&data).unwrap().get(2, 5), Some(&b"hi"[..]));
+        assert!(IptcData::parse(&[TAG_MARKER, 2, 5, 0, 10, b'x']).is_err());
+    }
+
+    #[test]
+    fn test_date_created_non_ascii_time() {
+        let mut iim = dataset(2, 55, b"20240506");
+        iim.extend(dataset(2, 60, b"07080\xFF+0200"));
+        let iptc = IptcData::parse(&iim).unwrap();
+        assert_eq!(iptc.texts(60), vec!["07080\u{ff}+0200"]);
+        // The bad time is left out
+        assert_eq!(iptc.date_created().as_deref(), Some("2024-05-06"));
+    }
+
+    #[test]
+    fn test_import_into() {
+        let iptc = IptcData::parse(&build_iim()).unwrap();
+        let mut meta = XmpMeta::new();
+        meta.set_localized_text(ns::DC, "title", "", "x-default", "Edited")
+            .unwrap();
+        assert!(iptc.import_into(&mut meta).unwrap());
+
+        // Existing XMP takes precedence
+        assert_eq!(
+            meta.get_localized_text(ns::DC, "title", "", "x-default")
+                .map(|(text, _)| text),
+            Some("Edited".to_string())
+        );
+        assert_eq!(meta.count_array_items(ns::DC, "subject"), 2);
+        assert_eq!(
+            meta.get_array_item(ns::DC, "creator", 0),
+            Some(XmpValue::String("Jane Doe".to_string()))
+        );
+        assert_eq!(
+            meta.get_localized_text(ns::DC, "description", "", "x-default")
+                .map(|(text, _)| text),
+            Some("Café by the sea".to_string())
+        );
+        assert_eq!(
+            meta.get_property(ns::PHOTOSHOP, "DateCreated"),
+            Some(XmpValue::String("2024-05-06T07:08:09+02:00".to_string()))
+        );
+        assert!(!iptc.import_into(&mut meta).unwrap());
+    }
+
+    #[test]
+    fn test_digest() {
+        let iim = build_iim();
+        let mut meta = XmpMeta::new();
+        meta.set_localized_text(ns::DC, "title", "", "x-default", "Edited")
+            .unwrap();
+
+        // Matching digest: the XMP is up to date
+        let mut data = resource(RESOURCE_IPTC, &iim);
+        data.extend(resource(RESOURCE_IPTC_DIGEST, &md5::digest(&iim)));
+        assert!(!import_resources(&data, &mut meta).unwrap());
+
+        // Other digest: the IIM was edited and replaces the XMP
+        let mut data = resource(RESOURCE_IPTC, &iim);
+        data.extend(resource(RESOURCE_IPTC_DIGEST, &[0; 16]));
+        assert!(import_resources(&data, &mut meta).unwrap());
+        assert_eq!(
+            meta.get_localized_text(ns::DC, "title", "", "x-default")
+                .map(|(text, _)| text),
+            Some("Harbour".to_string())
+        );
+        assert!(!import_resources(&data, &mut meta).unwrap());
+    }
+
+    #[test]
+    fn test_export_resources() {
+        let mut data = resource(0x03ED, &[1, 2, 3]);
+        data.extend(resource(RESOURCE_IPTC, &build_iim()));
+
+        let mut meta = XmpMeta::new();
+        meta.set_localized_text(ns::DC, "title", "", "x-default", "Quay")
+            .unwrap();
+        meta.append_array_item(ns::DC, "subject", "harbour".into())
+            .unwrap();
+        meta.set_property(ns::PHOTOSHOP, "City", "Brest".into())
+            .unwrap();
+        meta.set_property(ns::PHOTOSHOP, "DateCreated", "2024-05-06".into())
+            .unwrap();
+        assert!(export_resources(&mut data, &meta).unwrap());
+
+        let (resources, _) = parse_resources(&data).unwrap();
+        assert_eq!(resources[0].id, 0x03ED);
+        assert_eq!(resources[0].data, vec![1, 2, 3]);
+        let iptc = read_resources(&data).unwrap().unwrap();
+        assert_eq!(iptc.get(1, DATASET_CHARSET), Some(CHARSET_UTF8));
+        assert_eq!(iptc.texts(5), vec!["Quay"]);
+        assert_eq!(iptc.texts(25), vec!["harbour"]);
+        assert_eq!(iptc.texts(90), vec!["Brest"]);
+        assert_eq!(iptc.texts(55), vec!["20240506"]);
+        assert!(iptc.get(2, 60).is_none());
+        // Datasets without XMP are kept and re-encoded
+        assert_eq!(iptc.texts(80), vec!["Jane Doe"]);
+        assert_eq!(iptc.get(2, 120), Some("Café by the sea".as_bytes()));
+        assert_eq!(iptc.get(2, 200), Some(&b"custom"[..]));
+
+        // The digest is written, so the XMP is considered up to date
+        let mut reread = XmpMeta::new();
+        assert!(!import_resources(&data, &mut reread).unwrap());
+        assert!(!export_resources(&mut data, &meta).unwrap());
+
+        // Nothing to write without IIM or mapped properties
+        let mut empty = Vec::new();
+        assert!(!export_resources(&mut empty, &XmpMeta::new()).unwrap());
+    }
+
+    #[test]
+    fn test_iim_date() {
+        assert_eq!(
+            iim_date("2024-05-06T07:08:09.5-05:00"),
+            Some(("20240506".to_string(), Some("070809-0500".to_string())))
+        );
+        assert_eq!(
+            iim_date("2024-05-06T07:08Z"),
+            Some(("20240506".to_string(), Some("070800+0000".to_string())))
+        );
+        assert_eq!(iim_date("2024-05-06"), Some(("20240506".to_string(), None)));
+        assert_eq!(iim_date("2024-05"), None);
+    }
+}
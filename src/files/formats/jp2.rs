@@ -0,0 +1,312 @@
+//! JPEG 2000 file format handler
+//!
+//! This module provides functionality for reading and writing XMP metadata
+//! in JPEG 2000 (JP2, JPX) files. The implementation is pure Rust and
+//! cross-platform compatible.
+//!
+//! JPEG 2000 File Structure:
+//! - A sequence of boxes, starting with the "jP  " signature box and "ftyp"
+//! - The "jp2h" header box is followed by the "jp2c" codestream box, which
+//!   often extends to the end of the file
+//!
+//! JPEG 2000 XMP Storage:
+//! - XMP Packet is stored in a "uuid" box
+//! - UUID: BE7ACFCB-97A9-42E8-9C71-999491E3AFAC
+//! - A new box is inserted after the "jp2h" box, before the codestream
+//! - JPX files with a fragment table ("ftbl") reference the codestream by
+//!   file offset, so the XMP box is appended at the end of the file instead,
+//!   and an old XMP box is turned into a "free" box
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::files::formats::bmff::{self, BoxHeader, XMP_UUID};
+use crate::files::handler::FileHandler;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// JPEG 2000 signature box
+const JP2_SIGNATURE: &[u8; 12] = b"\x00\x00\x00\x0cjP  \x0d\x0a\x87\x0a";
+
+/// Box types
+const BOX_UUID: &[u8; 4] = b"uuid";
+const BOX_HEADER: &[u8; 4] = b"jp2h";
+const BOX_CODESTREAM: &[u8; 4] = b"jp2c";
+const BOX_FRAGMENT_TABLE: &[u8; 4] = b"ftbl";
+const BOX_FREE: &[u8; 4] = b"free";
+
+/// JPEG 2000 file handler for XMP metadata
+#[derive(Debug, Clone, Copy)]
+pub struct Jp2Handler;
+
+impl FileHandler for Jp2Handler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        let pos = reader.stream_position()?;
+        let mut header = [0u8; 12];
+        let result = reader.read_exact(&mut header);
+        reader.seek(SeekFrom::Start(pos))?;
+        Ok(result.is_ok() && header == *JP2_SIGNATURE)
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn format_name(&self) -> &'static str {
+        "JPEG 2000"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["jp2", "jpx", "jpf"]
+    }
+}
+
+impl Jp2Handler {
+    /// Read XMP metadata from a JPEG 2000 file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(XmpMeta))` if XMP metadata is found
+    /// * `Ok(None)` if no XMP metadata is found
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_xmp<R: Read + Seek>(mut reader: R) -> XmpResult<Option<XmpMeta>> {
+        let boxes = Self::read_boxes(&mut reader)?;
+        let Some(index) = Self::find_xmp_box(&mut reader, &boxes)? else {
+            return Ok(None);
+        };
+
+        let data = bmff::read_box_data(&mut reader, &boxes[index])?;
+        let xmp_str = String::from_utf8(data[XMP_UUID.len()..].to_vec())
+            .map_err(|e| XmpError::ParseError(format!("Invalid UTF-8 in XMP: {}", e)))?;
+        XmpMeta::parse(xmp_str.trim_end_matches('\0')).map(Some)
+    }
+
+    /// Write XMP metadata to a JPEG 2000 file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    pub fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        let xmp_packet = meta.serialize_packet()?;
+        let xmp_bytes = xmp_packet.as_bytes();
+
+        let boxes = Self::read_boxes(&mut reader)?;
+        let existing = Self::find_xmp_box(&mut reader, &boxes)?;
+        let has_fragment_table = boxes.iter().any(|b| b.box_type == *BOX_FRAGMENT_TABLE);
+
+        if has_fragment_table {
+            // Keep every box at its offset
+            let file_end = reader.seek(SeekFrom::End(0))?;
+            let end = match existing {
+                Some(index) if index == boxes.len() - 1 => boxes[index].offset,
+                _ => file_end,
+            };
+            reader.rewind()?;
+            let copied = std::io::copy(&mut reader.by_ref().take(end), &mut writer)?;
+            if copied != end {
+                return Err(XmpError::BadValue("Truncated JPEG 2000 file".to_string()));
+            }
+            if let Some(index) = existing.filter(|&index| index != boxes.len() - 1) {
+                let old = &boxes[index];
+                let type_offset = old.offset + 4;
+                writer.seek(SeekFrom::Start(type_offset))?;
+                writer.write_all(BOX_FREE)?;
+                writer.seek(SeekFrom::Start(end))?;
+            }
+            // The last box may extend to the end of the file
+            if let Some(last) = boxes.last().filter(|b| b.to_end && end == file_end) {
+                let size = u32::try_from(last.size).map_err(|_| {
+                    XmpError::NotSupported("Appending to a large JPEG 2000 box".to_string())
+                })?;
+                writer.seek(SeekFrom::Start(last.offset))?;
+                writer.write_all(&size.to_be_bytes())?;
+                writer.seek(SeekFrom::Start(end))?;
+            }
+            return Self::write_xmp_box(&mut writer, xmp_bytes);
+        }
+
+        let insert_at = existing
+            .or_else(|| {
+                boxes
+                    .iter()
+                    .position(|b| b.box_type == *BOX_HEADER)
+                    .map(|index| index + 1)
+            })
+            .or_else(|| boxes.iter().position(|b| b.box_type == *BOX_CODESTREAM))
+            .unwrap_or(boxes.len());
+
+        for (index, jp2_box) in boxes.iter().enumerate() {
+            if index == insert_at {
+                Self::write_xmp_box(&mut writer, xmp_bytes)?;
+            }
+            if Some(index) != existing {
+                bmff::copy_box(&mut reader, &mut writer, jp2_box, "JPEG 2000")?;
+            }
+        }
+        if insert_at == boxes.len() {
+            Self::write_xmp_box(&mut writer, xmp_bytes)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read the boxes, checking the signature box
+    fn read_boxes<R: Read + Seek>(reader: &mut R) -> XmpResult<Vec<BoxHeader>> {
+        reader.rewind()?;
+        let mut signature = [0u8; 12];
+        if reader.read_exact(&mut signature).is_err() || signature != *JP2_SIGNATURE {
+            return Err(XmpError::BadValue("Not a valid JPEG 2000 file".to_string()));
+        }
+        bmff::read_boxes(reader, 0, "JPEG 2000")
+    }
+
+    /// Find the index of the XMP "uuid" box
+    fn find_xmp_box<R: Read + Seek>(
+        reader: &mut R,
+        boxes: &[BoxHeader],
+    ) -> XmpResult<Option<usize>> {
+        for (index, jp2_box) in boxes.iter().enumerate() {
+            if jp2_box.box_type != *BOX_UUID || jp2_box.data_size() < XMP_UUID.len() as u64 {
+                continue;
+            }
+            reader.seek(SeekFrom::Start(jp2_box.data_offset()))?;
+            let mut uuid = [0u8; 16];
+            reader.read_exact(&mut uuid)?;
+            if uuid == *XMP_UUID {
+                return Ok(Some(index));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Write the XMP "uuid" box
+    fn write_xmp_box<W: Write>(writer: &mut W, xmp: &[u8]) -> XmpResult<()> {
+        bmff::write_box_header(writer, BOX_UUID, (XMP_UUID.len() + xmp.len()) as u64)?;
+        writer.write_all(XMP_UUID)?;
+        writer.write_all(xmp)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+    use std::io::Cursor;
+
+    fn jp2_box(box_type: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        bmff::write_box_header(&mut out, box_type, data.len() as u64).unwrap();
+        out.extend_from_slice(data);
+        out
+    }
+
+    /// Build a JP2 file whose codestream box extends to the end of the file
+    fn build_jp2(extra: &[u8]) -> Vec<u8> {
+        let mut jp2 = JP2_SIGNATURE.to_vec();
+        jp2.extend(jp2_box(b"ftyp", b"jp2 \0\0\0\0jp2 "));
+        jp2.extend(jp2_box(BOX_HEADER, &jp2_box(b"ihdr", &[0; 14])));
+        jp2.extend_from_slice(extra);
+        jp2.extend_from_slice(b"\0\0\0\0jp2c\xff\x4f\xff\x51codestream");
+        jp2
+    }
+
+    fn test_meta(title: &str) -> XmpMeta {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String(title.to_string()))
+            .unwrap();
+        meta
+    }
+
+    fn write(data: &[u8], meta: &XmpMeta) -> Vec<u8> {
+        let mut writer = Cursor::new(Vec::new());
+        Jp2Handler::write_xmp(Cursor::new(data), &mut writer, meta).unwrap();
+        writer.into_inner()
+    }
+
+    fn title(data: &[u8]) -> Option<XmpValue> {
+        Jp2Handler::read_xmp(Cursor::new(data))
+            .unwrap()
+            .and_then(|meta| meta.get_property(ns::DC, "title"))
+    }
+
+    fn box_types(data: &[u8]) -> Vec<[u8; 4]> {
+        Jp2Handler::read_boxes(&mut Cursor::new(data))
+            .unwrap()
+            .iter()
+            .map(|b| b.box_type)
+            .collect()
+    }
+
+    #[test]
+    fn test_can_handle() {
+        let handler = Jp2Handler;
+        assert!(handler
+            .can_handle(&mut Cursor::new(build_jp2(&[])))
+            .unwrap());
+        assert!(!handler
+            .can_handle(&mut Cursor::new(b"\0\0\0\x0cJXL \x0d\x0a\x87\x0a".to_vec()))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_write_xmp() {
+        let jp2 = build_jp2(&[]);
+        assert_eq!(title(&jp2), None);
+
+        let data = write(&jp2, &test_meta("Scan"));
+        let data = write(&data, &test_meta("Scan 2"));
+        assert_eq!(title(&data), Some(XmpValue::String("Scan 2".to_string())));
+        assert_eq!(
+            box_types(&data),
+            vec![*b"jP  ", *b"ftyp", *BOX_HEADER, *BOX_UUID, *BOX_CODESTREAM]
+        );
+        assert!(data.ends_with(b"\xff\x4f\xff\x51codestream"));
+    }
+
+    #[test]
+    fn test_write_xmp_with_fragment_table() {
+        let ftbl = jp2_box(BOX_FRAGMENT_TABLE, &jp2_box(b"flst", &[0; 16]));
+        let jpx = build_jp2(&ftbl);
+
+        // Appended after the codestream, whose offset is kept
+        let data = write(&jpx, &test_meta("Fragment"));
+        let codestream = jpx.len() - 14..jpx.len();
+        assert_eq!(data[..codestream.start - 8], jpx[..codestream.start - 8]);
+        assert_eq!(data[codestream.clone()], jpx[codestream]);
+        assert_eq!(title(&data), Some(XmpValue::String("Fragment".to_string())));
+
+        // A last XMP box is replaced
+        let data = write(&data, &test_meta("Fragment 2"));
+        assert_eq!(
+            box_types(&data).iter().filter(|t| *t == BOX_UUID).count(),
+            1
+        );
+        assert_eq!(
+            title(&data),
+            Some(XmpValue::String("Fragment 2".to_string()))
+        );
+    }
+}
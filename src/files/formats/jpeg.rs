@@ -5,20 +5,44 @@
 //!
 //! JPEG XMP Storage:
 //! - XMP Packet is stored in APP1 segment with identifier `<http://ns.adobe.com/xap/1.0/>\0`
-//! - Extended XMP (if needed) uses GUID-based chunking in additional APP1 segments
 //! - Standard APP1 segment size limit: 64KB (65535 bytes including header)
+//! - Extended XMP: packets that don't fit are split. The standard packet
+//!   holds `xmpNote:HasExtendedXMP`, the GUID (MD5 digest) of an extended
+//!   serialization stored in additional APP1 segments with identifier
+//!   `<http://ns.adobe.com/xap/1.0/ext/>\0`: GUID (32 bytes) + full length
+//!   (4 bytes) + chunk offset (4 bytes) + chunk, big-endian
+//! - When reading, the extension is merged back into the standard XMP
+//!
+//! JPEG EXIF Storage:
+//! - EXIF is stored in APP1 segment with identifier `Exif\0\0`, followed by
+//!   TIFF-structured data (see [`ExifData`]), which can be imported into XMP
+//! - The EXIF may hold a JPEG thumbnail, which can be imported into
+//!   `xmp:Thumbnails`
+//!
+//! JPEG IPTC Storage:
+//! - IPTC-IIM records are stored in the Photoshop image resources of the
+//!   APP13 segment with identifier `Photoshop 3.0\0` (see [`IptcData`]),
+//!   which can be imported into XMP
 
 use crate::core::error::{XmpError, XmpResult};
-use crate::core::metadata::XmpMeta;
+use crate::core::metadata::{AppendOptions, Thumbnail, XmpMeta, HAS_EXTENDED_XMP};
+use crate::core::namespace::ns;
+use crate::files::file::SaveOptions;
+use crate::files::format::FormatCapabilities;
+use crate::files::formats::exif::{self, ExifData};
+use crate::files::formats::iptc::{self, IptcData};
 use crate::files::handler::FileHandler;
-use std::io::{Read, Seek, SeekFrom, Write};
+use crate::types::value::XmpValue;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 
 /// JPEG segment markers
 const MARKER_SOI: u8 = 0xD8; // Start of Image
 const MARKER_APP0: u8 = 0xE0;
 const MARKER_APP1: u8 = 0xE1;
+const MARKER_APP13: u8 = 0xED;
 const MARKER_APP15: u8 = 0xEF;
 const MARKER_SOS: u8 = 0xDA; // Start of Scan
+const MARKER_COM: u8 = 0xFE; // Comment
 const MARKER_EOI: u8 = 0xD9; // End of Image
 
 /// XMP namespace identifier in APP1 segment
@@ -32,9 +56,38 @@ const EXIF_SIGNATURE: &[u8] = b"Exif\0\x00";
 const EXIF_SIGNATURE_ALT: &[u8] = b"Exif\0\xFF";
 const EXIF_SIGNATURE_LENGTH: usize = 6;
 
+// Photoshop image resources signature in APP13
+const PHOTOSHOP_SIGNATURE: &[u8] = b"Photoshop 3.0\0";
+
 /// Maximum size of a standard APP1 segment (64KB - 2 bytes for length)
 const MAX_APP1_SIZE: usize = 65533;
 
+/// Maximum size of the standard XMP packet
+const MAX_STANDARD_XMP_SIZE: usize = MAX_APP1_SIZE - XMP_NAMESPACE.len();
+
+/// Length of the Extended XMP GUID (32 hex digits)
+const EXTENDED_XMP_GUID_LENGTH: usize = 32;
+
+/// Size of the Extended XMP segment header (namespace, GUID, full length and
+/// offset)
+const EXTENDED_XMP_HEADER_SIZE: usize = EXTENDED_XMP_NAMESPACE.len() + EXTENDED_XMP_GUID_LENGTH + 8;
+
+/// Maximum size of an Extended XMP chunk
+const MAX_EXTENDED_XMP_CHUNK_SIZE: usize = MAX_APP1_SIZE - EXTENDED_XMP_HEADER_SIZE;
+
+/// A chunk of Extended XMP
+#[derive(Debug, Clone)]
+struct ExtendedXmpChunk {
+    /// GUID of the full extended serialization
+    guid: [u8; EXTENDED_XMP_GUID_LENGTH],
+    /// Length of the full extended serialization
+    full_length: u32,
+    /// Offset of this chunk in the full extended serialization
+    offset: u32,
+    /// Chunk data
+    data: Vec<u8>,
+}
+
 /// JPEG file handler for XMP metadata
 #[derive(Debug, Clone, Copy)]
 pub struct JpegHandler;
@@ -60,6 +113,67 @@ impl FileHandler for JpegHandler {
         Self::write_xmp(reader, writer, meta)
     }
 
+    fn import_legacy<R: Read + Seek>(&self, reader: &mut R, meta: &mut XmpMeta) -> XmpResult<bool> {
+        let mut imported = match Self::read_exif(&mut *reader)? {
+            Some(exif) => exif.import_into(meta)?,
+            None => false,
+        };
+        if let Some(resources) = Self::read_photoshop_resources(reader)? {
+            // Unreadable IPTC is ignored, like unreadable EXIF
+            imported |= iptc::import_resources(&resources, meta).unwrap_or(false);
+        }
+        Ok(imported)
+    }
+
+    fn read_thumbnail<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<Thumbnail>> {
+        Self::read_thumbnail(reader)
+    }
+
+    fn export_legacy<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+        options: &SaveOptions,
+    ) -> XmpResult<bool> {
+        let mut data = Self::read_to_vec(reader)?;
+        let mut updated = false;
+        if options.reconcile_exif {
+            updated |= Self::update_exif(&mut data, meta)?;
+        }
+        if options.reconcile_iptc {
+            updated |= Self::update_iptc(&mut data, meta)?;
+        }
+        if updated {
+            writer.write_all(&data)?;
+        }
+        Ok(updated)
+    }
+
+    fn remove_metadata<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        all: bool,
+    ) -> XmpResult<()> {
+        Self::remove_metadata(reader, writer, all)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> FormatCapabilities {
+        FormatCapabilities {
+            can_read: true,
+            can_write: true,
+            can_inject: true,
+            supports_in_place: self.supports_in_place_update(),
+            supports_reconciliation: true,
+            max_packet_size: None,
+        }
+    }
+
     fn format_name(&self) -> &'static str {
         "JPEG"
     }
@@ -97,7 +211,7 @@ impl JpegHandler {
 
         // Search for APP1 segments containing XMP
         let mut xmp_data = Vec::new();
-        let mut extended_xmp_parts: Vec<(u32, Vec<u8>)> = Vec::new();
+        let mut extended_xmp_chunks = Vec::new();
 
         loop {
             // Find next marker
@@ -112,7 +226,7 @@ impl JpegHandler {
                     &mut reader,
                     marker,
                     &mut xmp_data,
-                    &mut extended_xmp_parts,
+                    &mut extended_xmp_chunks,
                 )?;
             } else {
                 // Skip other segments
@@ -121,11 +235,6 @@ impl JpegHandler {
             }
         }
 
-        // Reconstruct Extended XMP if present
-        if !extended_xmp_parts.is_empty() {
-            xmp_data = Self::reconstruct_extended_xmp(extended_xmp_parts)?;
-        }
-
         if xmp_data.is_empty() {
             return Ok(None);
         }
@@ -133,8 +242,352 @@ impl JpegHandler {
         // Parse XMP Packet
         let xmp_str = String::from_utf8(xmp_data)
             .map_err(|e| XmpError::ParseError(format!("Invalid UTF-8 in XMP: {}", e)))?;
+        let mut meta = XmpMeta::parse(&xmp_str)?;
+
+        // Merge the Extended XMP linked from the standard XMP, if complete
+        if let Some(XmpValue::String(guid)) = meta.get_property(ns::XMP_NOTE, HAS_EXTENDED_XMP) {
+            if let Some(extended) = Self::reconstruct_extended_xmp(&guid, &extended_xmp_chunks) {
+                let extended_str = String::from_utf8(extended).map_err(|e| {
+                    XmpError::ParseError(format!("Invalid UTF-8 in Extended XMP: {}", e))
+                })?;
+                let extended_meta = XmpMeta::parse(&extended_str)?;
+                meta.append_from(
+                    &extended_meta,
+                    AppendOptions::default()
+                        .replace_old_values()
+                        .include_internal(),
+                )?;
+                meta.delete_property(ns::XMP_NOTE, HAS_EXTENDED_XMP)?;
+            }
+        }
+
+        Ok(Some(meta))
+    }
+
+    /// Read EXIF metadata from a JPEG file
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(ExifData))` if an EXIF segment with tags is found
+    /// * `Ok(None)` if the file has no (readable) EXIF segment
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_exif<R: Read + Seek>(mut reader: R) -> XmpResult<Option<ExifData>> {
+        reader.rewind()?;
+        let mut header = [0u8; 2];
+        reader.read_exact(&mut header)?;
+        if header[0] != 0xFF || header[1] != MARKER_SOI {
+            return Err(XmpError::BadValue("Not a valid JPEG file".to_string()));
+        }
+
+        loop {
+            let marker = Self::find_marker(&mut reader)?;
+            if marker == MARKER_EOI || marker == MARKER_SOS {
+                return Ok(None);
+            }
+
+            let Some(segment_data) = Self::read_app_segment(&mut reader, marker)? else {
+                continue;
+            };
+            if marker == MARKER_APP1 && Self::is_exif_segment(&segment_data) {
+                // Unreadable EXIF is ignored, like a missing segment
+                let exif = ExifData::read(Cursor::new(&segment_data[EXIF_SIGNATURE_LENGTH..]));
+                return Ok(exif.ok().filter(|exif| !exif.is_empty()));
+            }
+        }
+    }
+
+    /// Read the EXIF thumbnail of a JPEG file
+    ///
+    /// The thumbnail is the JPEG image referenced by the 1st IFD of the
+    /// EXIF; its size is read from its frame header.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(Thumbnail))` if the EXIF has a readable JPEG thumbnail
+    /// * `Ok(None)` otherwise
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_thumbnail<R: Read + Seek>(reader: R) -> XmpResult<Option<Thumbnail>> {
+        let Some(exif) = Self::read_exif(reader)? else {
+            return Ok(None);
+        };
+        let Some(image) = exif.thumbnail() else {
+            return Ok(None);
+        };
+        Ok(Self::frame_size(image)
+            .map(|(width, height)| Thumbnail::jpeg(width, height, image.to_vec())))
+    }
+
+    /// Get the width and height of a JPEG image from its frame header (SOFn)
+    fn frame_size(image: &[u8]) -> Option<(u32, u32)> {
+        let mut reader = Cursor::new(image);
+        let mut header = [0u8; 2];
+        reader.read_exact(&mut header).ok()?;
+        if header != [0xFF, MARKER_SOI] {
+            return None;
+        }
+
+        loop {
+            match Self::find_marker(&mut reader).ok()? {
+                MARKER_EOI | MARKER_SOS => return None,
+                // SOFn, except DHT (0xC4), JPG (0xC8) and DAC (0xCC)
+                marker @ 0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                    // Length (2 bytes) + precision (1 byte) + height + width
+                    let mut frame = [0u8; 7];
+                    reader.read_exact(&mut frame).ok()?;
+                    let height = u16::from_be_bytes([frame[3], frame[4]]);
+                    let width = u16::from_be_bytes([frame[5], frame[6]]);
+                    return Some((width.into(), height.into()));
+                }
+                // Markers without a segment (TEM, RSTn)
+                0x01 | 0xD0..=0xD7 => {}
+                marker => {
+                    Self::read_app_segment(&mut reader, marker).ok()?;
+                }
+            }
+        }
+    }
+
+    /// Read IPTC-IIM metadata from a JPEG file
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(IptcData))` if an APP13 segment with IPTC records is found
+    /// * `Ok(None)` if the file has no (readable) IPTC records
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_iptc<R: Read + Seek>(reader: R) -> XmpResult<Option<IptcData>> {
+        let Some(resources) = Self::read_photoshop_resources(reader)? else {
+            return Ok(None);
+        };
+        let iptc = iptc::read_resources(&resources);
+        Ok(iptc.ok().flatten().filter(|iptc| !iptc.is_empty()))
+    }
+
+    /// Read the Photoshop image resources of the APP13 segment
+    pub(crate) fn read_photoshop_resources<R: Read + Seek>(
+        mut reader: R,
+    ) -> XmpResult<Option<Vec<u8>>> {
+        reader.rewind()?;
+        let mut header = [0u8; 2];
+        reader.read_exact(&mut header)?;
+        if header[0] != 0xFF || header[1] != MARKER_SOI {
+            return Err(XmpError::BadValue("Not a valid JPEG file".to_string()));
+        }
+
+        loop {
+            let marker = Self::find_marker(&mut reader)?;
+            if marker == MARKER_EOI || marker == MARKER_SOS {
+                return Ok(None);
+            }
+
+            let Some(segment_data) = Self::read_app_segment(&mut reader, marker)? else {
+                continue;
+            };
+            if marker == MARKER_APP13 && segment_data.starts_with(PHOTOSHOP_SIGNATURE) {
+                return Ok(Some(segment_data[PHOTOSHOP_SIGNATURE.len()..].to_vec()));
+            }
+        }
+    }
+
+    /// Write XMP values to the EXIF segment of a JPEG file
+    ///
+    /// See [`SaveOptions::reconcile_exif`] for the exported values. An EXIF
+    /// segment is added (after the JFIF segment, if any) when the file has
+    /// none.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` if a copy of the file with updated EXIF was written
+    /// * `Ok(false)` if the EXIF is up to date (nothing is written)
+    /// * `Err(XmpError)` if an error occurs
+    pub fn write_exif<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<bool> {
+        let mut data = Self::read_to_vec(&mut reader)?;
+        let updated = Self::update_exif(&mut data, meta)?;
+        if updated {
+            writer.write_all(&data)?;
+        }
+        Ok(updated)
+    }
+
+    /// Write XMP values to the IPTC-IIM records of a JPEG file
+    ///
+    /// See [`SaveOptions::reconcile_iptc`] for the exported values. An APP13
+    /// segment is added (after the other APPn segments) when the file has
+    /// none and the XMP has values to export.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` if a copy of the file with updated IPTC was written
+    /// * `Ok(false)` if the IPTC is up to date (nothing is written)
+    /// * `Err(XmpError)` if an error occurs
+    pub fn write_iptc<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<bool> {
+        let mut data = Self::read_to_vec(&mut reader)?;
+        let updated = Self::update_iptc(&mut data, meta)?;
+        if updated {
+            writer.write_all(&data)?;
+        }
+        Ok(updated)
+    }
+
+    /// Remove the metadata segments of a JPEG file
+    ///
+    /// The standard and Extended XMP segments are removed. With `all`, the
+    /// EXIF segment (with its thumbnail), the APP13 segment (with the IPTC
+    /// records) and the comments are removed too. Other segments, such as
+    /// ICC profiles, are kept, since they affect how the image looks.
+    pub fn remove_metadata<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        all: bool,
+    ) -> XmpResult<()> {
+        let data = Self::read_to_vec(&mut reader)?;
+        let mut output = Vec::with_capacity(data.len());
+        output.extend_from_slice(&data[..2]);
+        let mut pos = 2;
+        while pos + 4 <= data.len() && data[pos] == 0xFF && data[pos + 1] != MARKER_SOS {
+            let marker = data[pos + 1];
+            let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+            let end = pos + 2 + length;
+            if length < 2 || end > data.len() {
+                return Err(XmpError::BadValue(
+                    "Invalid JPEG segment length".to_string(),
+                ));
+            }
+            let segment = &data[pos + 4..end];
+            let remove = match marker {
+                MARKER_APP1 => {
+                    Self::is_xmp_segment(segment)
+                        || Self::is_extended_xmp_segment(segment)
+                        || (all && Self::is_exif_segment(segment))
+                }
+                MARKER_APP13 => all && segment.starts_with(PHOTOSHOP_SIGNATURE),
+                MARKER_COM => all,
+                _ => false,
+            };
+            if !remove {
+                output.extend_from_slice(&data[pos..end]);
+            }
+            pos = end;
+        }
+        output.extend_from_slice(&data[pos..]);
+        writer.write_all(&output)?;
+        Ok(())
+    }
+
+    /// Read a whole JPEG file into memory
+    fn read_to_vec<R: Read + Seek>(mut reader: R) -> XmpResult<Vec<u8>> {
+        reader.rewind()?;
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        if data.len() < 2 || data[0] != 0xFF || data[1] != MARKER_SOI {
+            return Err(XmpError::BadValue("Not a valid JPEG file".to_string()));
+        }
+        Ok(data)
+    }
+
+    /// Update the EXIF segment of JPEG data from XMP
+    fn update_exif(data: &mut Vec<u8>, meta: &XmpMeta) -> XmpResult<bool> {
+        let (existing, insert_at) =
+            Self::find_app_segment(data, MARKER_APP1, Self::is_exif_segment)?;
+        let (mut tiff, range) = match existing {
+            Some((start, end)) => (
+                data[start + 4 + EXIF_SIGNATURE_LENGTH..end].to_vec(),
+                start..end,
+            ),
+            None => (exif::EMPTY_TIFF.to_vec(), insert_at..insert_at),
+        };
+        if !exif::export_exif(&mut tiff, meta)? {
+            return Ok(false);
+        }
+        let mut payload = EXIF_SIGNATURE.to_vec();
+        payload.extend(tiff);
+        Self::replace_app_segment(data, range, MARKER_APP1, &payload)?;
+        Ok(true)
+    }
 
-        XmpMeta::parse(&xmp_str).map(Some)
+    /// Update the IPTC records of the APP13 segment of JPEG data from XMP
+    fn update_iptc(data: &mut Vec<u8>, meta: &XmpMeta) -> XmpResult<bool> {
+        let (existing, insert_at) = Self::find_app_segment(data, MARKER_APP13, |segment| {
+            segment.starts_with(PHOTOSHOP_SIGNATURE)
+        })?;
+        let (mut resources, range) = match existing {
+            Some((start, end)) => (
+                data[start + 4 + PHOTOSHOP_SIGNATURE.len()..end].to_vec(),
+                start..end,
+            ),
+            None => (Vec::new(), insert_at..insert_at),
+        };
+        if !iptc::export_resources(&mut resources, meta)? {
+            return Ok(false);
+        }
+        let mut payload = PHOTOSHOP_SIGNATURE.to_vec();
+        payload.extend(resources);
+        Self::replace_app_segment(data, range, MARKER_APP13, &payload)?;
+        Ok(true)
+    }
+
+    /// Find an APPn segment in JPEG data
+    ///
+    /// Returns the range of the first `marker` segment whose data matches
+    /// (including the marker and length), and where to insert a new one:
+    /// after the leading APPn segments with a lower marker.
+    fn find_app_segment(
+        data: &[u8],
+        marker: u8,
+        matches: impl Fn(&[u8]) -> bool,
+    ) -> XmpResult<(Option<(usize, usize)>, usize)> {
+        let mut pos = 2;
+        let mut insert_at = 2;
+        let mut leading = true;
+        while pos + 4 <= data.len() && data[pos] == 0xFF {
+            let segment_marker = data[pos + 1];
+            if !(MARKER_APP0..=MARKER_APP15).contains(&segment_marker) {
+                break;
+            }
+            let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+            let end = pos + 2 + length;
+            if length < 2 || end > data.len() {
+                return Err(XmpError::BadValue(
+                    "Invalid JPEG segment length".to_string(),
+                ));
+            }
+            if segment_marker == marker && matches(&data[pos + 4..end]) {
+                return Ok((Some((pos, end)), insert_at));
+            }
+            leading &= segment_marker < marker;
+            if leading {
+                insert_at = end;
+            }
+            pos = end;
+        }
+        Ok((None, insert_at))
+    }
+
+    /// Replace a range of JPEG data with an APPn segment
+    fn replace_app_segment(
+        data: &mut Vec<u8>,
+        range: std::ops::Range<usize>,
+        marker: u8,
+        payload: &[u8],
+    ) -> XmpResult<()> {
+        if payload.len() > MAX_APP1_SIZE {
+            return Err(XmpError::BadValue(format!(
+                "Segment data too large for JPEG ({} bytes)",
+                payload.len()
+            )));
+        }
+        let mut segment = vec![0xFF, marker];
+        segment.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+        segment.extend_from_slice(payload);
+        data.splice(range, segment);
+        Ok(())
     }
 
     /// Write XMP metadata to a JPEG file
@@ -145,6 +598,9 @@ impl JpegHandler {
     /// * `writer` - A writer implementing `Write + Seek` for the output file
     /// * `meta` - The XMP metadata to write
     ///
+    /// Packets larger than an APP1 segment are split into standard and
+    /// Extended XMP (see the [module documentation](self)).
+    ///
     /// # Platform Compatibility
     ///
     /// This function uses only standard Rust I/O traits (`Read`, `Seek`, `Write`),
@@ -154,16 +610,8 @@ impl JpegHandler {
         mut writer: W,
         meta: &XmpMeta,
     ) -> XmpResult<()> {
-        // Serialize XMP metadata
-        let xmp_packet = meta.serialize_packet()?;
-        let xmp_bytes = xmp_packet.as_bytes();
-
-        // Check if we need Extended XMP
-        if xmp_bytes.len() > MAX_APP1_SIZE {
-            return Err(XmpError::NotSupported(
-                "Extended XMP not yet implemented".to_string(),
-            ));
-        }
+        // Serialize XMP metadata, splitting off Extended XMP if needed
+        let packet = meta.split_packet(MAX_STANDARD_XMP_SIZE)?;
 
         // Read source file header
         let mut header = [0u8; 2];
@@ -193,8 +641,11 @@ impl JpegHandler {
             writer.write_all(&buffer)?;
         }
 
-        // Write XMP APP1 segment
-        Self::write_app1_xmp_segment(&mut writer, xmp_bytes)?;
+        // Write XMP APP1 segment, followed by the Extended XMP segments
+        Self::write_app1_xmp_segment(&mut writer, packet.standard.as_bytes())?;
+        if let Some((extended, guid)) = &packet.extended {
+            Self::write_app1_extended_xmp_segments(&mut writer, guid, extended.as_bytes())?;
+        }
 
         // Copy remaining segments, skipping old XMP segments, until SOS or EOI
         // The APP0 copy loop already read the next marker and backed up, so we're at the start of the next segment
@@ -263,7 +714,7 @@ impl JpegHandler {
         reader: &mut R,
         marker: u8,
         xmp_data: &mut Vec<u8>,
-        extended_xmp_parts: &mut Vec<(u32, Vec<u8>)>,
+        extended_xmp_chunks: &mut Vec<ExtendedXmpChunk>,
     ) -> XmpResult<()> {
         let Some(segment_data) = Self::read_app_segment(reader, marker)? else {
             return Ok(());
@@ -272,8 +723,8 @@ impl JpegHandler {
         if Self::is_xmp_segment(&segment_data) {
             *xmp_data = Self::extract_xmp_data(&segment_data)?;
         } else if Self::is_extended_xmp_segment(&segment_data) {
-            if let Some((guid, data)) = Self::extract_extended_xmp_data(&segment_data)? {
-                extended_xmp_parts.push((guid, data));
+            if let Some(chunk) = Self::extract_extended_xmp_data(&segment_data) {
+                extended_xmp_chunks.push(chunk);
             }
         }
 
@@ -392,6 +843,13 @@ impl JpegHandler {
             && segment_data[..XMP_NAMESPACE.len()] == *XMP_NAMESPACE
     }
 
+    /// Check if a segment is an EXIF segment
+    fn is_exif_segment(segment_data: &[u8]) -> bool {
+        segment_data.len() >= EXIF_SIGNATURE_LENGTH
+            && (segment_data[..EXIF_SIGNATURE_LENGTH] == *EXIF_SIGNATURE
+                || segment_data[..EXIF_SIGNATURE_LENGTH] == *EXIF_SIGNATURE_ALT)
+    }
+
     /// Check if a segment is an Extended XMP segment
     fn is_extended_xmp_segment(segment_data: &[u8]) -> bool {
         segment_data.len() >= EXTENDED_XMP_NAMESPACE.len()
@@ -407,59 +865,60 @@ impl JpegHandler {
         Ok(segment_data[XMP_NAMESPACE.len()..].to_vec())
     }
 
-    /// Extract Extended XMP data from APP1 segment
-    fn extract_extended_xmp_data(segment_data: &[u8]) -> XmpResult<Option<(u32, Vec<u8>)>> {
-        if segment_data.len() < EXTENDED_XMP_NAMESPACE.len() + 36 {
-            return Ok(None);
+    /// Extract an Extended XMP chunk from APP1 segment
+    fn extract_extended_xmp_data(segment_data: &[u8]) -> Option<ExtendedXmpChunk> {
+        if segment_data.len() < EXTENDED_XMP_HEADER_SIZE {
+            return None;
         }
 
-        // GUID is 32 bytes (128 bits) after namespace
+        // GUID (32 bytes), full length and chunk offset follow the namespace
         let guid_start = EXTENDED_XMP_NAMESPACE.len();
-        let _guid_bytes = &segment_data[guid_start..guid_start + 32];
-
-        // Read chunk info (offset and total size)
-        let offset_start = guid_start + 32;
-        if segment_data.len() < offset_start + 8 {
-            return Ok(None);
-        }
-
-        let offset = u32::from_be_bytes([
-            segment_data[offset_start],
-            segment_data[offset_start + 1],
-            segment_data[offset_start + 2],
-            segment_data[offset_start + 3],
-        ]);
-
-        let _total_size = u32::from_be_bytes([
-            segment_data[offset_start + 4],
-            segment_data[offset_start + 5],
-            segment_data[offset_start + 6],
-            segment_data[offset_start + 7],
-        ]);
-
-        // Extract chunk data
-        let data_start = offset_start + 8;
-        if segment_data.len() < data_start {
-            return Ok(None);
-        }
-
-        let data = segment_data[data_start..].to_vec();
-        Ok(Some((offset, data)))
+        let mut guid = [0u8; EXTENDED_XMP_GUID_LENGTH];
+        guid.copy_from_slice(&segment_data[guid_start..guid_start + EXTENDED_XMP_GUID_LENGTH]);
+
+        let info = &segment_data[guid_start + EXTENDED_XMP_GUID_LENGTH..EXTENDED_XMP_HEADER_SIZE];
+        let full_length = u32::from_be_bytes([info[0], info[1], info[2], info[3]]);
+        let offset = u32::from_be_bytes([info[4], info[5], info[6], info[7]]);
+
+        Some(ExtendedXmpChunk {
+            guid,
+            full_length,
+            offset,
+            data: segment_data[EXTENDED_XMP_HEADER_SIZE..].to_vec(),
+        })
     }
 
-    /// Reconstruct Extended XMP from chunks
-    fn reconstruct_extended_xmp(chunks: Vec<(u32, Vec<u8>)>) -> XmpResult<Vec<u8>> {
-        // Sort chunks by offset
-        let mut sorted_chunks = chunks;
-        sorted_chunks.sort_by_key(|(offset, _)| *offset);
+    /// Reconstruct the Extended XMP with the given GUID from chunks
+    ///
+    /// Returns `None` if chunks are missing or inconsistent.
+    fn reconstruct_extended_xmp(guid: &str, chunks: &[ExtendedXmpChunk]) -> Option<Vec<u8>> {
+        let mut chunks: Vec<&ExtendedXmpChunk> = chunks
+            .iter()
+            .filter(|chunk| chunk.guid == guid.as_bytes())
+            .collect();
+        let full_length = chunks.first()?.full_length as usize;
+        if chunks
+            .iter()
+            .any(|chunk| chunk.full_length as usize != full_length)
+        {
+            return None;
+        }
 
-        // Concatenate chunks
+        // Concatenate chunks by offset, ignoring duplicates
+        chunks.sort_by_key(|chunk| chunk.offset);
         let mut result = Vec::new();
-        for (_, data) in sorted_chunks {
-            result.extend_from_slice(&data);
+        for chunk in chunks {
+            let offset = chunk.offset as usize;
+            if offset < result.len() {
+                continue;
+            }
+            if offset > result.len() || offset + chunk.data.len() > full_length {
+                return None;
+            }
+            result.extend_from_slice(&chunk.data);
         }
 
-        Ok(result)
+        (result.len() == full_length).then_some(result)
     }
 
     /// Write APP1 XMP segment
@@ -479,6 +938,31 @@ impl JpegHandler {
 
         Ok(())
     }
+
+    /// Write the APP1 segments of an Extended XMP serialization
+    fn write_app1_extended_xmp_segments<W: Write>(
+        writer: &mut W,
+        guid: &str,
+        extended: &[u8],
+    ) -> XmpResult<()> {
+        let full_length = u32::try_from(extended.len())
+            .map_err(|_| XmpError::BadValue("Extended XMP is too large".to_string()))?;
+
+        for (index, chunk) in extended.chunks(MAX_EXTENDED_XMP_CHUNK_SIZE).enumerate() {
+            let offset = (index * MAX_EXTENDED_XMP_CHUNK_SIZE) as u32;
+            let segment_length = (EXTENDED_XMP_HEADER_SIZE + chunk.len() + 2) as u16;
+
+            writer.write_all(&[0xFF, MARKER_APP1])?;
+            writer.write_all(&segment_length.to_be_bytes())?;
+            writer.write_all(EXTENDED_XMP_NAMESPACE)?;
+            writer.write_all(guid.as_bytes())?;
+            writer.write_all(&full_length.to_be_bytes())?;
+            writer.write_all(&offset.to_be_bytes())?;
+            writer.write_all(chunk)?;
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -559,4 +1043,265 @@ mod tests {
         let extracted = JpegHandler::extract_xmp_data(&segment).unwrap();
         assert_eq!(extracted, xmp_content);
     }
+
+    /// Split the marker segments of a JPEG file, up to SOS or EOI
+    fn read_segments(jpeg: &[u8]) -> Vec<(u8, Vec<u8>)> {
+        let mut segments = Vec::new();
+        let mut pos = 2;
+        while pos + 4 <= jpeg.len() && jpeg[pos] == 0xFF {
+            let marker = jpeg[pos + 1];
+            if marker == MARKER_SOS || marker == MARKER_EOI {
+                break;
+            }
+            let length = u16::from_be_bytes([jpeg[pos + 2], jpeg[pos + 3]]) as usize;
+            segments.push((marker, jpeg[pos + 4..pos + 2 + length].to_vec()));
+            pos += 2 + length;
+        }
+        segments
+    }
+
+    fn large_meta() -> XmpMeta {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String("Large".to_string()))
+            .unwrap();
+        meta.set_property(
+            ns::CAMERA_RAW,
+            "ToneCurveName",
+            XmpValue::String("x".repeat(150_000)),
+        )
+        .unwrap();
+        meta
+    }
+
+    #[test]
+    fn test_write_extended_xmp() {
+        let meta = large_meta();
+        let mut writer = Cursor::new(Vec::new());
+        JpegHandler::write_xmp(Cursor::new(create_minimal_jpeg()), &mut writer, &meta).unwrap();
+        let jpeg = writer.into_inner();
+
+        let segments = read_segments(&jpeg);
+        let standard: Vec<_> = segments
+            .iter()
+            .filter(|(_, data)| JpegHandler::is_xmp_segment(data))
+            .collect();
+        let extended: Vec<_> = segments
+            .iter()
+            .filter(|(_, data)| JpegHandler::is_extended_xmp_segment(data))
+            .collect();
+        assert_eq!(standard.len(), 1);
+        assert_eq!(extended.len(), 3);
+        assert!(segments.iter().all(|(_, data)| data.len() <= MAX_APP1_SIZE));
+
+        // The standard packet keeps the title and links to the extension
+        let standard = String::from_utf8_lossy(&standard[0].1);
+        assert!(standard.contains("Large"));
+        assert!(!standard.contains("ToneCurveName"));
+        let guid = &extended[0].1[EXTENDED_XMP_NAMESPACE.len()..][..EXTENDED_XMP_GUID_LENGTH];
+        assert!(standard.contains(&format!(
+            "xmpNote:HasExtendedXMP=\"{}\"",
+            String::from_utf8_lossy(guid)
+        )));
+
+        let read_meta = JpegHandler::read_xmp(Cursor::new(jpeg)).unwrap().unwrap();
+        assert_eq!(
+            read_meta.get_property(ns::DC, "title"),
+            Some(XmpValue::String("Large".to_string()))
+        );
+        assert_eq!(
+            read_meta.get_property(ns::CAMERA_RAW, "ToneCurveName"),
+            meta.get_property(ns::CAMERA_RAW, "ToneCurveName")
+        );
+        assert!(read_meta
+            .get_property(ns::XMP_NOTE, HAS_EXTENDED_XMP)
+            .is_none());
+
+        // Rewriting a small packet drops the old extension
+        let mut small = read_meta.clone();
+        small
+            .delete_property(ns::CAMERA_RAW, "ToneCurveName")
+            .unwrap();
+        let mut writer = Cursor::new(Vec::new());
+        JpegHandler::write_xmp(Cursor::new(jpeg_with(&read_meta)), &mut writer, &small).unwrap();
+        let segments = read_segments(&writer.into_inner());
+        assert_eq!(segments.len(), 1);
+        assert!(!String::from_utf8_lossy(&segments[0].1).contains("HasExtendedXMP"));
+    }
+
+    fn jpeg_with(meta: &XmpMeta) -> Vec<u8> {
+        let mut writer = Cursor::new(Vec::new());
+        JpegHandler::write_xmp(Cursor::new(create_minimal_jpeg()), &mut writer, meta).unwrap();
+        writer.into_inner()
+    }
+
+    #[test]
+    fn test_read_extended_xmp_mismatch() {
+        let mut jpeg = jpeg_with(&large_meta());
+
+        // Corrupt the GUID of the last extended chunk
+        let namespace_pos = jpeg
+            .windows(EXTENDED_XMP_NAMESPACE.len())
+            .rposition(|window| window == EXTENDED_XMP_NAMESPACE)
+            .unwrap();
+        jpeg[namespace_pos + EXTENDED_XMP_NAMESPACE.len()] = b'-';
+
+        // The incomplete extension is ignored
+        let meta = JpegHandler::read_xmp(Cursor::new(jpeg)).unwrap().unwrap();
+        assert_eq!(
+            meta.get_property(ns::DC, "title"),
+            Some(XmpValue::String("Large".to_string()))
+        );
+        assert!(meta.get_property(ns::CAMERA_RAW, "ToneCurveName").is_none());
+        assert!(meta.get_property(ns::XMP_NOTE, HAS_EXTENDED_XMP).is_some());
+    }
+
+    #[test]
+    fn test_reconstruct_extended_xmp() {
+        let chunk = |guid: &[u8; 32], offset: u32, data: &[u8]| ExtendedXmpChunk {
+            guid: *guid,
+            full_length: 6,
+            offset,
+            data: data.to_vec(),
+        };
+        let guid = [b'A'; 32];
+        let other = [b'B'; 32];
+        let guid_str = "A".repeat(32);
+
+        let chunks = vec![
+            chunk(&guid, 3, b"def"),
+            chunk(&other, 0, b"xyz"),
+            chunk(&guid, 0, b"abc"),
+            chunk(&guid, 0, b"abc"),
+        ];
+        assert_eq!(
+            JpegHandler::reconstruct_extended_xmp(&guid_str, &chunks),
+            Some(b"abcdef".to_vec())
+        );
+        assert_eq!(
+            JpegHandler::reconstruct_extended_xmp(&guid_str, &chunks[..2]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_write_iptc() {
+        // JFIF and EXIF segments come before the new APP13 segment
+        let mut jpeg = vec![0xFF, MARKER_SOI, 0xFF, MARKER_APP0, 0, 4, 0, 0];
+        jpeg.extend_from_slice(&[0xFF, MARKER_APP1, 0, 8]);
+        jpeg.extend_from_slice(EXIF_SIGNATURE);
+        jpeg.extend_from_slice(&[0xFF, MARKER_EOI]);
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::PHOTOSHOP, "Headline", "Storm".into())
+            .unwrap();
+        meta.append_array_item(ns::DC, "creator", "Jane Doe".into())
+            .unwrap();
+        let mut writer = Cursor::new(Vec::new());
+        assert!(JpegHandler::write_iptc(Cursor::new(&jpeg), &mut writer, &meta).unwrap());
+        let data = writer.into_inner();
+        assert_eq!(data[..18], jpeg[..18]);
+        assert_eq!(data[18..20], [0xFF, MARKER_APP13]);
+
+        let iptc = JpegHandler::read_iptc(Cursor::new(&data)).unwrap().unwrap();
+        assert_eq!(iptc.texts(105), vec!["Storm"]);
+        assert_eq!(iptc.texts(80), vec!["Jane Doe"]);
+
+        // Up to date
+        let mut writer = Cursor::new(Vec::new());
+        assert!(!JpegHandler::write_iptc(Cursor::new(&data), &mut writer, &meta).unwrap());
+        assert!(writer.into_inner().is_empty());
+
+        // IIM edited without the XMP replaces it when importing
+        let mut edited = XmpMeta::new();
+        edited
+            .set_property(ns::PHOTOSHOP, "Headline", "Flood".into())
+            .unwrap();
+        let mut writer = Cursor::new(Vec::new());
+        JpegHandler::write_iptc(Cursor::new(&data), &mut writer, &edited).unwrap();
+        let mut data = writer.into_inner();
+        let digest_pos = data.len() - 2 - 16;
+        data[digest_pos] ^= 0xFF;
+        assert!(JpegHandler
+            .import_legacy(&mut Cursor::new(&data), &mut meta)
+            .unwrap());
+        assert_eq!(
+            meta.get_property(ns::PHOTOSHOP, "Headline"),
+            Some(XmpValue::String("Flood".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_remove_metadata() {
+        // JFIF, EXIF, APP13 and comment segments
+        let mut jpeg = vec![0xFF, MARKER_SOI, 0xFF, MARKER_APP0, 0, 4, 0, 0];
+        jpeg.extend_from_slice(&[0xFF, MARKER_APP1, 0, 8]);
+        jpeg.extend_from_slice(EXIF_SIGNATURE);
+        jpeg.extend_from_slice(&[0xFF, MARKER_APP13, 0, 16]);
+        jpeg.extend_from_slice(PHOTOSHOP_SIGNATURE);
+        jpeg.extend_from_slice(&[0xFF, MARKER_COM, 0, 4, b'h', b'i']);
+        jpeg.extend_from_slice(&[0xFF, MARKER_SOS, 0, 2, 0xFF, MARKER_EOI]);
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "format", "image/jpeg".into())
+            .unwrap();
+        let mut writer = Cursor::new(Vec::new());
+        JpegHandler::write_xmp(Cursor::new(&jpeg), &mut writer, &meta).unwrap();
+        let with_xmp = writer.into_inner();
+
+        let mut writer = Cursor::new(Vec::new());
+        JpegHandler::remove_metadata(Cursor::new(&with_xmp), &mut writer, false).unwrap();
+        assert_eq!(writer.into_inner(), jpeg);
+
+        let mut writer = Cursor::new(Vec::new());
+        JpegHandler::remove_metadata(Cursor::new(&with_xmp), &mut writer, true).unwrap();
+        let mut stripped = jpeg[..8].to_vec();
+        stripped.extend_from_slice(&[0xFF, MARKER_SOS, 0, 2, 0xFF, MARKER_EOI]);
+        assert_eq!(writer.into_inner(), stripped);
+    }
+
+    #[test]
+    fn test_read_thumbnail() {
+        // A 64x48 baseline frame header, one component
+        let thumbnail = [
+            0xFF, MARKER_SOI, 0xFF, 0xC0, 0, 11, 8, 0, 48, 0, 64, 1, 1, 0x11, 0, 0xFF, MARKER_EOI,
+        ];
+        let entry = |tag: u16, type_: u16, value: u32| {
+            let mut entry = tag.to_le_bytes().to_vec();
+            entry.extend_from_slice(&type_.to_le_bytes());
+            entry.extend_from_slice(&1u32.to_le_bytes());
+            entry.extend_from_slice(&value.to_le_bytes());
+            entry
+        };
+        // 0th IFD at 8 (Orientation), 1st IFD at 26, thumbnail at 56
+        let mut tiff = b"II*\0\x08\0\0\0".to_vec();
+        tiff.extend_from_slice(&1u16.to_le_bytes());
+        tiff.extend(entry(0x0112, 3, 1));
+        tiff.extend_from_slice(&26u32.to_le_bytes());
+        tiff.extend_from_slice(&2u16.to_le_bytes());
+        tiff.extend(entry(0x0201, 4, 56));
+        tiff.extend(entry(0x0202, 4, thumbnail.len() as u32));
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        tiff.extend_from_slice(&thumbnail);
+
+        let mut jpeg = vec![0xFF, MARKER_SOI, 0xFF, MARKER_APP1];
+        jpeg.extend_from_slice(&((2 + EXIF_SIGNATURE.len() + tiff.len()) as u16).to_be_bytes());
+        jpeg.extend_from_slice(EXIF_SIGNATURE);
+        jpeg.extend_from_slice(&tiff);
+        jpeg.extend_from_slice(&[0xFF, MARKER_EOI]);
+
+        let read = JpegHandler::read_thumbnail(Cursor::new(&jpeg))
+            .unwrap()
+            .unwrap();
+        assert_eq!(read, Thumbnail::jpeg(64, 48, thumbnail.to_vec()));
+
+        let mut file = crate::files::XmpFile::new();
+        file.from_bytes(&jpeg).unwrap();
+        assert!(file.get_xmp().unwrap().thumbnails().is_empty());
+        file.from_bytes_with(
+            &jpeg,
+            crate::files::ReadOptions::default().import_thumbnail(),
+        )
+        .unwrap();
+        assert_eq!(file.get_xmp().unwrap().thumbnails(), vec![read]);
+    }
 }
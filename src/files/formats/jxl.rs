@@ -0,0 +1,274 @@
+//! JPEG XL file format handler
+//!
+//! This module provides functionality for reading and writing XMP metadata
+//! in JPEG XL files. The implementation is pure Rust and cross-platform
+//! compatible.
+//!
+//! JPEG XL File Structure:
+//! - Container files are a sequence of ISO BMFF-style boxes: size (4 bytes,
+//!   big-endian; 1 means a 64-bit size follows, 0 means up to the end of the
+//!   file) + type (4 bytes) + data
+//! - The container starts with the "JXL " signature box, followed by "ftyp";
+//!   the codestream is in a "jxlc" box or split in "jxlp" boxes
+//! - Raw codestreams (starting with 0xFF 0x0A) cannot hold metadata
+//!
+//! JPEG XL XMP Storage:
+//! - XMP Packet is stored in an "xml " box
+//! - A new box is inserted before the codestream
+//! - Brotli-compressed ("brob") XMP boxes are not supported
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::files::formats::bmff::{self, BoxHeader};
+use crate::files::handler::FileHandler;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// JPEG XL container signature box
+const CONTAINER_SIGNATURE: &[u8; 12] = b"\x00\x00\x00\x0cJXL \x0d\x0a\x87\x0a";
+
+/// JPEG XL raw codestream signature
+const CODESTREAM_SIGNATURE: &[u8; 2] = b"\xff\x0a";
+
+/// Box types
+const BOX_XML: &[u8; 4] = b"xml ";
+const BOX_BROTLI: &[u8; 4] = b"brob";
+const BOX_CODESTREAM: &[u8; 4] = b"jxlc";
+const BOX_PARTIAL_CODESTREAM: &[u8; 4] = b"jxlp";
+
+/// JPEG XL file handler for XMP metadata
+#[derive(Debug, Clone, Copy)]
+pub struct JxlHandler;
+
+impl FileHandler for JxlHandler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        let pos = reader.stream_position()?;
+        let mut header = [0u8; 12];
+        let result = reader.read_exact(&mut header);
+        reader.seek(SeekFrom::Start(pos))?;
+        Ok(result.is_ok()
+            && (header == *CONTAINER_SIGNATURE || header[..2] == *CODESTREAM_SIGNATURE))
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn format_name(&self) -> &'static str {
+        "JPEG XL"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["jxl"]
+    }
+}
+
+impl JxlHandler {
+    /// Read XMP metadata from a JPEG XL file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(XmpMeta))` if XMP metadata is found
+    /// * `Ok(None)` if no XMP metadata is found
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_xmp<R: Read + Seek>(mut reader: R) -> XmpResult<Option<XmpMeta>> {
+        let boxes = Self::read_boxes(&mut reader)?;
+        let Some(xml_box) = boxes.iter().find(|b| b.box_type == *BOX_XML) else {
+            if Self::has_compressed_xmp(&mut reader, &boxes)? {
+                return Err(XmpError::NotSupported(
+                    "Brotli-compressed JPEG XL XMP box".to_string(),
+                ));
+            }
+            return Ok(None);
+        };
+
+        let data = bmff::read_box_data(&mut reader, xml_box)?;
+        let xmp_str = String::from_utf8(data)
+            .map_err(|e| XmpError::ParseError(format!("Invalid UTF-8 in XMP: {}", e)))?;
+        XmpMeta::parse(xmp_str.trim_end_matches('\0')).map(Some)
+    }
+
+    /// Write XMP metadata to a JPEG XL file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    pub fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        let xmp_packet = meta.serialize_packet()?;
+        let boxes = Self::read_boxes(&mut reader)?;
+        if Self::has_compressed_xmp(&mut reader, &boxes)? {
+            return Err(XmpError::NotSupported(
+                "Brotli-compressed JPEG XL XMP box".to_string(),
+            ));
+        }
+
+        let existing = boxes.iter().position(|b| b.box_type == *BOX_XML);
+        let insert_at = existing.or_else(|| {
+            boxes.iter().position(|b| {
+                b.box_type == *BOX_CODESTREAM || b.box_type == *BOX_PARTIAL_CODESTREAM
+            })
+        });
+        let insert_at = insert_at.unwrap_or(boxes.len());
+
+        for (index, jxl_box) in boxes.iter().enumerate() {
+            if index == insert_at {
+                Self::write_xml_box(&mut writer, xmp_packet.as_bytes())?;
+            }
+            if jxl_box.box_type == *BOX_XML {
+                continue;
+            }
+            bmff::copy_box(&mut reader, &mut writer, jxl_box, "JPEG XL")?;
+        }
+        if insert_at == boxes.len() {
+            Self::write_xml_box(&mut writer, xmp_packet.as_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Read the boxes of a container file
+    fn read_boxes<R: Read + Seek>(reader: &mut R) -> XmpResult<Vec<BoxHeader>> {
+        let file_end = reader.seek(SeekFrom::End(0))?;
+        reader.rewind()?;
+        let mut signature = [0u8; 12];
+        let len = file_end.min(12) as usize;
+        reader.read_exact(&mut signature[..len])?;
+        if signature[..2] == *CODESTREAM_SIGNATURE {
+            return Err(XmpError::NotSupported(
+                "XMP in raw JPEG XL codestreams (no container)".to_string(),
+            ));
+        }
+        if signature != *CONTAINER_SIGNATURE {
+            return Err(XmpError::BadValue("Not a valid JPEG XL file".to_string()));
+        }
+
+        bmff::read_boxes(reader, 0, "JPEG XL")
+    }
+
+    /// Check for a Brotli-compressed "xml " box
+    fn has_compressed_xmp<R: Read + Seek>(reader: &mut R, boxes: &[BoxHeader]) -> XmpResult<bool> {
+        for jxl_box in boxes.iter().filter(|b| b.box_type == *BOX_BROTLI) {
+            if jxl_box.data_size() < 4 {
+                continue;
+            }
+            // The data starts with the type of the compressed box
+            reader.seek(SeekFrom::Start(jxl_box.data_offset()))?;
+            let mut box_type = [0u8; 4];
+            reader.read_exact(&mut box_type)?;
+            if box_type == *BOX_XML {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Write an "xml " box
+    fn write_xml_box<W: Write>(writer: &mut W, xmp: &[u8]) -> XmpResult<()> {
+        bmff::write_box_header(writer, BOX_XML, xmp.len() as u64)?;
+        writer.write_all(xmp)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+    use std::io::Cursor;
+
+    fn jxl_box(box_type: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut out = ((8 + data.len()) as u32).to_be_bytes().to_vec();
+        out.extend_from_slice(box_type);
+        out.extend_from_slice(data);
+        out
+    }
+
+    fn build_jxl() -> Vec<u8> {
+        let mut jxl = CONTAINER_SIGNATURE.to_vec();
+        jxl.extend(jxl_box(b"ftyp", b"jxl \0\0\0\0jxl "));
+        jxl.extend(jxl_box(BOX_CODESTREAM, b"\xff\x0acodestream"));
+        jxl
+    }
+
+    fn test_meta(title: &str) -> XmpMeta {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String(title.to_string()))
+            .unwrap();
+        meta
+    }
+
+    fn write(data: &[u8], meta: &XmpMeta) -> Vec<u8> {
+        let mut writer = Cursor::new(Vec::new());
+        JxlHandler::write_xmp(Cursor::new(data), &mut writer, meta).unwrap();
+        writer.into_inner()
+    }
+
+    #[test]
+    fn test_can_handle() {
+        let handler = JxlHandler;
+        assert!(handler.can_handle(&mut Cursor::new(build_jxl())).unwrap());
+        assert!(handler
+            .can_handle(&mut Cursor::new(b"\xff\x0a raw codestream".to_vec()))
+            .unwrap());
+        assert!(!handler
+            .can_handle(&mut Cursor::new(b"\x89PNG\r\n\x1a\n\0\0\0\0".to_vec()))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_raw_codestream_not_supported() {
+        let raw = b"\xff\x0a raw codestream".to_vec();
+        assert!(matches!(
+            JxlHandler::read_xmp(Cursor::new(&raw)),
+            Err(XmpError::NotSupported(_))
+        ));
+        let mut writer = Cursor::new(Vec::new());
+        assert!(matches!(
+            JxlHandler::write_xmp(Cursor::new(&raw), &mut writer, &test_meta("Raw")),
+            Err(XmpError::NotSupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_xmp() {
+        let jxl = build_jxl();
+        assert!(JxlHandler::read_xmp(Cursor::new(&jxl)).unwrap().is_none());
+
+        let data = write(&jxl, &test_meta("Archive"));
+        let data = write(&data, &test_meta("Archive 2"));
+
+        // The "xml " box is before the codestream
+        let boxes = JxlHandler::read_boxes(&mut Cursor::new(&data)).unwrap();
+        let types: Vec<_> = boxes.iter().map(|b| &b.box_type).collect();
+        assert_eq!(types, vec![b"JXL ", b"ftyp", BOX_XML, BOX_CODESTREAM]);
+
+        let meta = JxlHandler::read_xmp(Cursor::new(&data)).unwrap().unwrap();
+        assert_eq!(
+            meta.get_property(ns::DC, "title"),
+            Some(XmpValue::String("Archive 2".to_string()))
+        );
+    }
+}
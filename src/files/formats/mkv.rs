@@ -0,0 +1,917 @@
+//! Matroska / WebM file format handler
+//!
+//! This module provides functionality for reading and writing XMP metadata
+//! in Matroska (MKV, MKA) and WebM files. The implementation is pure Rust and
+//! cross-platform compatible.
+//!
+//! Matroska File Structure:
+//! - EBML elements: ID (variable-length, 1-4 bytes) + size (variable-length
+//!   integer, 1-8 bytes; all ones means unknown) + data
+//! - An EBML header (with the "matroska" or "webm" DocType) followed by a
+//!   Segment holding the top-level elements (SeekHead, Info, Tracks,
+//!   Clusters, Cues, Attachments, Tags, ...)
+//! - Positions in the SeekHead and Cues are relative to the Segment data
+//!
+//! Matroska XMP Storage:
+//! - There is no Adobe convention for Matroska. The XMP Packet is stored
+//!   either as an attachment (`AttachedFile` with the "application/rdf+xml"
+//!   MIME type, the default) or as a `SimpleTag` named "XMP" (see
+//!   [`MkvOptions`])
+//! - To avoid moving the media data, the element holding the XMP is turned
+//!   into a Void element and its new version is appended to the Segment.
+//!   The SeekHead is updated when it has room for the change.
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::files::handler::FileHandler;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// Element IDs
+const ID_EBML: u32 = 0x1A45_DFA3;
+const ID_DOC_TYPE: u32 = 0x4282;
+const ID_SEGMENT: u32 = 0x1853_8067;
+const ID_SEEK_HEAD: u32 = 0x114D_9B74;
+const ID_SEEK: u32 = 0x4DBB;
+const ID_SEEK_ID: u32 = 0x53AB;
+const ID_SEEK_POSITION: u32 = 0x53AC;
+const ID_ATTACHMENTS: u32 = 0x1941_A469;
+const ID_ATTACHED_FILE: u32 = 0x61A7;
+const ID_FILE_NAME: u32 = 0x466E;
+const ID_FILE_MIME_TYPE: u32 = 0x4660;
+const ID_FILE_DATA: u32 = 0x465C;
+const ID_FILE_UID: u32 = 0x46AE;
+const ID_TAGS: u32 = 0x1254_C367;
+const ID_TAG: u32 = 0x7373;
+const ID_TARGETS: u32 = 0x63C0;
+const ID_SIMPLE_TAG: u32 = 0x67C8;
+const ID_TAG_NAME: u32 = 0x45A3;
+const ID_TAG_STRING: u32 = 0x4487;
+const ID_VOID: u32 = 0xEC;
+
+/// Supported DocTypes
+const DOC_TYPES: &[&[u8]] = &[b"matroska", b"webm"];
+
+/// MIME type and file name of the XMP attachment
+const XMP_MIME_TYPE: &str = "application/rdf+xml";
+const XMP_FILE_NAME: &str = "metadata.xmp";
+
+/// Name of the XMP simple tag
+const XMP_TAG_NAME: &str = "XMP";
+
+/// Matroska / WebM file handler for XMP metadata
+#[derive(Debug, Clone, Copy)]
+pub struct MkvHandler;
+
+/// Options for writing Matroska / WebM files
+#[derive(Default, Clone, Copy, Debug)]
+pub struct MkvOptions {
+    pub(crate) store_in_tags: bool,
+}
+
+impl MkvOptions {
+    /// Store the XMP Packet as a `SimpleTag` named "XMP" instead of an
+    /// attachment
+    ///
+    /// An XMP attachment left from a previous write is removed.
+    pub fn store_in_tags(mut self) -> Self {
+        self.store_in_tags = true;
+        self
+    }
+}
+
+impl FileHandler for MkvHandler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        let pos = reader.stream_position()?;
+        let mut header = [0u8; 64];
+        let mut len = 0;
+        while len < header.len() {
+            match reader.read(&mut header[len..])? {
+                0 => break,
+                n => len += n,
+            }
+        }
+        reader.seek(SeekFrom::Start(pos))?;
+
+        Ok(parse_element(&header[..len], 0, len as u64)
+            .filter(|element| element.id == ID_EBML)
+            .and_then(|element| element.data(&header[..len]))
+            .and_then(doc_type)
+            .is_some_and(|doc_type| DOC_TYPES.contains(&doc_type)))
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn format_name(&self) -> &'static str {
+        "Matroska"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["mkv", "mka", "webm"]
+    }
+}
+
+/// The location of an EBML element
+#[derive(Debug, Clone, Copy)]
+struct Element {
+    id: u32,
+    /// Offset of the element ID
+    offset: u64,
+    /// Size of the ID and size fields
+    header_size: u64,
+    /// Size of the data, `None` if unknown
+    size: Option<u64>,
+}
+
+impl Element {
+    fn data_offset(&self) -> u64 {
+        self.offset + self.header_size
+    }
+
+    /// Total size of the element (known sizes only)
+    fn total_size(&self) -> u64 {
+        self.header_size + self.size.unwrap_or(0)
+    }
+
+    fn end(&self) -> u64 {
+        self.offset + self.total_size()
+    }
+
+    /// The element data within the buffer it was parsed from
+    fn data<'a>(&self, buffer: &'a [u8]) -> Option<&'a [u8]> {
+        buffer.get(self.data_offset() as usize..self.end() as usize)
+    }
+
+    /// The whole element within the buffer it was parsed from
+    fn bytes<'a>(&self, buffer: &'a [u8]) -> &'a [u8] {
+        &buffer[self.offset as usize..self.end() as usize]
+    }
+}
+
+/// Parse an element header at `offset` in `bytes`
+///
+/// `available` is the number of bytes up to the end of the parent; an
+/// element with a known size must fit in it.
+fn parse_element(bytes: &[u8], offset: u64, available: u64) -> Option<Element> {
+    let first = *bytes.first()?;
+    let id_len = first.leading_zeros() as usize + 1;
+    if id_len > 4 {
+        return None;
+    }
+    let id = bytes
+        .get(..id_len)?
+        .iter()
+        .fold(0u32, |acc, &b| (acc << 8) | b as u32);
+
+    let size_first = *bytes.get(id_len)?;
+    let size_len = size_first.leading_zeros() as usize + 1;
+    if size_len > 8 {
+        return None;
+    }
+    let size_bytes = bytes.get(id_len..id_len + size_len)?;
+    let mask = (0xFFu16 >> size_len) as u8;
+    let value = size_bytes[1..]
+        .iter()
+        .fold((size_first & mask) as u64, |acc, &b| (acc << 8) | b as u64);
+    let unknown = value == (1u64 << (7 * size_len)) - 1;
+
+    let header_size = (id_len + size_len) as u64;
+    let size = (!unknown).then_some(value);
+    if header_size + size.unwrap_or(0) > available {
+        return None;
+    }
+    Some(Element {
+        id,
+        offset,
+        header_size,
+        size,
+    })
+}
+
+/// Parse the child elements of an in-memory element body
+fn parse_children(data: &[u8]) -> Vec<Element> {
+    let mut children = Vec::new();
+    let mut pos = 0u64;
+    while (pos as usize) < data.len() {
+        let available = data.len() as u64 - pos;
+        match parse_element(&data[pos as usize..], pos, available) {
+            Some(element) if element.size.is_some() => {
+                pos = element.end();
+                children.push(element);
+            }
+            _ => break,
+        }
+    }
+    children
+}
+
+/// Find the data of the first child with the given ID
+fn child_data(data: &[u8], id: u32) -> Option<&[u8]> {
+    parse_children(data)
+        .into_iter()
+        .find(|child| child.id == id)
+        .and_then(|child| child.data(data))
+}
+
+/// The DocType of an EBML header body
+fn doc_type(ebml_data: &[u8]) -> Option<&[u8]> {
+    child_data(ebml_data, ID_DOC_TYPE).map(|doc_type| {
+        let end = doc_type
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(doc_type.len());
+        &doc_type[..end]
+    })
+}
+
+/// Encode an element ID
+fn encode_id(id: u32) -> Vec<u8> {
+    let bytes = id.to_be_bytes();
+    let skip = (id.leading_zeros() / 8) as usize;
+    bytes[skip.min(3)..].to_vec()
+}
+
+/// Encode a size on exactly `len` bytes, if it fits
+fn encode_size_fixed(size: u64, len: usize) -> Option<Vec<u8>> {
+    // All ones is reserved for unknown sizes
+    if !(1..=8).contains(&len) || size >= (1u64 << (7 * len)) - 1 {
+        return None;
+    }
+    let value = size | (1u64 << (7 * len));
+    Some(value.to_be_bytes()[8 - len..].to_vec())
+}
+
+/// Encode a size on the minimal number of bytes
+fn encode_size(size: u64) -> Vec<u8> {
+    (1..=8)
+        .find_map(|len| encode_size_fixed(size, len))
+        .expect("EBML sizes are below 2^56 - 1")
+}
+
+/// Build an element from its ID and data
+fn element(id: u32, data: &[u8]) -> Vec<u8> {
+    let mut out = encode_id(id);
+    out.extend_from_slice(&encode_size(data.len() as u64));
+    out.extend_from_slice(data);
+    out
+}
+
+/// Build an unsigned integer element
+fn uint_element(id: u32, value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = ((value.leading_zeros() / 8) as usize).min(7);
+    element(id, &bytes[skip..])
+}
+
+/// Build a Void element of exactly `len` bytes (at least 2)
+fn void_element(len: u64) -> Vec<u8> {
+    let size_len = (len - 1).min(8);
+    let data_len = len - 1 - size_len;
+    let mut out = vec![ID_VOID as u8];
+    out.extend_from_slice(
+        &encode_size_fixed(data_len, size_len as usize).expect("Void size fits its field"),
+    );
+    out.resize(len as usize, 0);
+    out
+}
+
+/// Read an unsigned integer element body
+fn read_uint(data: &[u8]) -> Option<u64> {
+    (data.len() <= 8).then(|| data.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64))
+}
+
+/// Check whether an element body is a string equal to `value`
+fn is_string(data: Option<&[u8]>, value: &str) -> bool {
+    data.is_some_and(|data| {
+        let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+        data[..end].eq_ignore_ascii_case(value.as_bytes())
+    })
+}
+
+/// Where the XMP Packet is stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Storage {
+    Attachments,
+    Tags,
+}
+
+impl Storage {
+    fn id(self) -> u32 {
+        match self {
+            Storage::Attachments => ID_ATTACHMENTS,
+            Storage::Tags => ID_TAGS,
+        }
+    }
+
+    /// Find the XMP Packet in an Attachments or Tags body
+    fn find_xmp(self, data: &[u8]) -> Option<&[u8]> {
+        parse_children(data)
+            .into_iter()
+            .filter_map(|child| child.data(data))
+            .find_map(|child| self.entry_xmp(child))
+    }
+
+    /// The XMP Packet of an AttachedFile or Tag body
+    fn entry_xmp(self, entry: &[u8]) -> Option<&[u8]> {
+        match self {
+            Storage::Attachments => {
+                let is_xmp = is_string(child_data(entry, ID_FILE_MIME_TYPE), XMP_MIME_TYPE)
+                    || child_data(entry, ID_FILE_NAME).is_some_and(|name| {
+                        name.len() >= 4 && name[name.len() - 4..].eq_ignore_ascii_case(b".xmp")
+                    });
+                is_xmp.then(|| child_data(entry, ID_FILE_DATA)).flatten()
+            }
+            Storage::Tags => parse_children(entry)
+                .into_iter()
+                .filter(|child| child.id == ID_SIMPLE_TAG)
+                .filter_map(|child| child.data(entry))
+                .find(|simple_tag| is_string(child_data(simple_tag, ID_TAG_NAME), XMP_TAG_NAME))
+                .and_then(|simple_tag| child_data(simple_tag, ID_TAG_STRING)),
+        }
+    }
+
+    /// Rebuild an Attachments or Tags body without the XMP Packet
+    ///
+    /// Returns `None` if the body has no XMP Packet.
+    fn remove_xmp(self, data: &[u8]) -> Option<Vec<u8>> {
+        let mut found = false;
+        let mut body = Vec::with_capacity(data.len());
+        for child in parse_children(data) {
+            let Some(entry) = child.data(data) else {
+                continue;
+            };
+            if self.entry_xmp(entry).is_none() {
+                body.extend_from_slice(child.bytes(data));
+                continue;
+            }
+            found = true;
+
+            // Keep the other simple tags of a Tag
+            if self == Storage::Tags {
+                let mut tag = Vec::new();
+                let mut has_simple_tags = false;
+                for tag_child in parse_children(entry) {
+                    let is_xmp = tag_child.id == ID_SIMPLE_TAG
+                        && tag_child.data(entry).is_some_and(|simple_tag| {
+                            is_string(child_data(simple_tag, ID_TAG_NAME), XMP_TAG_NAME)
+                        });
+                    if !is_xmp {
+                        has_simple_tags |= tag_child.id == ID_SIMPLE_TAG;
+                        tag.extend_from_slice(tag_child.bytes(entry));
+                    }
+                }
+                if has_simple_tags {
+                    body.extend_from_slice(&element(ID_TAG, &tag));
+                }
+            }
+        }
+        found.then_some(body)
+    }
+
+    /// Build the AttachedFile or Tag element holding the XMP Packet
+    fn xmp_entry(self, xmp: &[u8]) -> Vec<u8> {
+        match self {
+            Storage::Attachments => {
+                let mut entry = element(ID_FILE_NAME, XMP_FILE_NAME.as_bytes());
+                entry.extend(element(ID_FILE_MIME_TYPE, XMP_MIME_TYPE.as_bytes()));
+                entry.extend(element(ID_FILE_DATA, xmp));
+                entry.extend(uint_element(ID_FILE_UID, file_uid(xmp)));
+                element(ID_ATTACHED_FILE, &entry)
+            }
+            Storage::Tags => {
+                let mut simple_tag = element(ID_TAG_NAME, XMP_TAG_NAME.as_bytes());
+                simple_tag.extend(element(ID_TAG_STRING, xmp));
+                let mut tag = element(ID_TARGETS, &[]);
+                tag.extend(element(ID_SIMPLE_TAG, &simple_tag));
+                element(ID_TAG, &tag)
+            }
+        }
+    }
+}
+
+/// A non-zero attachment UID derived from its data (FNV-1a)
+fn file_uid(data: &[u8]) -> u64 {
+    let hash = data.iter().fold(0xCBF2_9CE4_8422_2325u64, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01B3)
+    });
+    hash.max(1)
+}
+
+/// The layout of the Segment
+struct SegmentLayout {
+    segment: Element,
+    /// End of the Segment data
+    end: u64,
+    /// Top-level elements of the Segment
+    children: Vec<Element>,
+}
+
+impl SegmentLayout {
+    fn read<R: Read + Seek>(reader: &mut R) -> XmpResult<Self> {
+        let file_end = reader.seek(SeekFrom::End(0))?;
+        let ebml = read_element_header(reader, 0, file_end)?
+            .filter(|element| element.id == ID_EBML && element.size.is_some())
+            .ok_or_else(|| XmpError::BadValue("Not a valid Matroska file".to_string()))?;
+        let ebml_data = read_element_data(reader, &ebml)?;
+        if !doc_type(&ebml_data).is_some_and(|doc_type| DOC_TYPES.contains(&doc_type)) {
+            return Err(XmpError::NotSupported("EBML DocType".to_string()));
+        }
+
+        let segment = read_element_header(reader, ebml.end(), file_end)?
+            .filter(|element| element.id == ID_SEGMENT)
+            .ok_or_else(|| XmpError::BadValue("Matroska file has no Segment".to_string()))?;
+        let end = match segment.size {
+            Some(_) => segment.end(),
+            None => file_end,
+        };
+
+        let mut children = Vec::new();
+        let mut pos = segment.data_offset();
+        while pos < end {
+            let Some(child) = read_element_header(reader, pos, end)? else {
+                return Err(XmpError::BadValue("Invalid Matroska element".to_string()));
+            };
+            if child.size.is_none() {
+                return Err(XmpError::NotSupported(
+                    "Matroska element with unknown size".to_string(),
+                ));
+            }
+            pos = child.end();
+            children.push(child);
+        }
+
+        Ok(Self {
+            segment,
+            end,
+            children,
+        })
+    }
+
+    /// Position of an element relative to the Segment data
+    fn relative(&self, offset: u64) -> u64 {
+        offset - self.segment.data_offset()
+    }
+}
+
+/// Read an element header at `pos`, with `end` the end of the parent
+fn read_element_header<R: Read + Seek>(
+    reader: &mut R,
+    pos: u64,
+    end: u64,
+) -> XmpResult<Option<Element>> {
+    let mut header = [0u8; 12];
+    let len = (end.saturating_sub(pos)).min(12) as usize;
+    reader.seek(SeekFrom::Start(pos))?;
+    reader.read_exact(&mut header[..len])?;
+    Ok(parse_element(&header[..len], pos, end - pos))
+}
+
+/// Read the data of an element
+fn read_element_data<R: Read + Seek>(reader: &mut R, element: &Element) -> XmpResult<Vec<u8>> {
+    reader.seek(SeekFrom::Start(element.data_offset()))?;
+    let mut data = vec![0u8; element.size.unwrap_or(0) as usize];
+    reader.read_exact(&mut data)?;
+    Ok(data)
+}
+
+impl MkvHandler {
+    /// Read XMP metadata from a Matroska / WebM file
+    ///
+    /// Both the attachment and the tag storage are read.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(XmpMeta))` if XMP metadata is found
+    /// * `Ok(None)` if no XMP metadata is found
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_xmp<R: Read + Seek>(mut reader: R) -> XmpResult<Option<XmpMeta>> {
+        let layout = SegmentLayout::read(&mut reader)?;
+
+        for child in &layout.children {
+            let storage = match child.id {
+                ID_ATTACHMENTS => Storage::Attachments,
+                ID_TAGS => Storage::Tags,
+                _ => continue,
+            };
+            let data = read_element_data(&mut reader, child)?;
+            if let Some(xmp) = storage.find_xmp(&data) {
+                let xmp_str = String::from_utf8(xmp.to_vec())
+                    .map_err(|e| XmpError::ParseError(format!("Invalid UTF-8 in XMP: {}", e)))?;
+                return XmpMeta::parse(&xmp_str).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Write XMP metadata to a Matroska / WebM file as an attachment
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    pub fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        reader: R,
+        writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp_with(reader, writer, meta, MkvOptions::default())
+    }
+
+    /// Write XMP metadata to a Matroska / WebM file with options
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    /// * `options` - Options, e.g. to store the XMP Packet in the tags
+    pub fn write_xmp_with<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+        options: MkvOptions,
+    ) -> XmpResult<()> {
+        let xmp_packet = meta.serialize_packet()?;
+        let target = if options.store_in_tags {
+            Storage::Tags
+        } else {
+            Storage::Attachments
+        };
+
+        let layout = SegmentLayout::read(&mut reader)?;
+        let mut patches: Vec<(u64, Vec<u8>)> = Vec::new();
+        let mut appended = Vec::new();
+        // (old relative position, new relative position) of moved elements
+        let mut moved: Vec<(u64, Option<u64>)> = Vec::new();
+        let mut added = Vec::new();
+        let append_start = layout.relative(layout.end);
+
+        for storage in [Storage::Attachments, Storage::Tags] {
+            let mut target_written = false;
+            for child in layout.children.iter().filter(|c| c.id == storage.id()) {
+                let data = read_element_data(&mut reader, child)?;
+                let removed = storage.remove_xmp(&data);
+                let is_target = storage == target && !target_written;
+                if removed.is_none() && !is_target {
+                    continue;
+                }
+
+                let mut body = removed.unwrap_or(data);
+                if is_target {
+                    body.extend(storage.xmp_entry(xmp_packet.as_bytes()));
+                    target_written = true;
+                }
+                patches.push((child.offset, void_element(child.total_size())));
+
+                let new_position = (!body.is_empty()).then(|| append_start + appended.len() as u64);
+                if !body.is_empty() {
+                    appended.extend(element(storage.id(), &body));
+                }
+                moved.push((layout.relative(child.offset), new_position));
+            }
+
+            if storage == target && !target_written {
+                added.push((storage.id(), append_start + appended.len() as u64));
+                appended.extend(element(
+                    storage.id(),
+                    &storage.xmp_entry(xmp_packet.as_bytes()),
+                ));
+            }
+        }
+
+        patches.extend(Self::seek_head_patches(
+            &mut reader,
+            &layout,
+            &moved,
+            &added,
+        )?);
+
+        // Segment size
+        if let Some(size) = layout.segment.size {
+            let id_len = encode_id(ID_SEGMENT).len() as u64;
+            let size_len = (layout.segment.header_size - id_len) as usize;
+            let new_size = encode_size_fixed(size + appended.len() as u64, size_len)
+                .ok_or_else(|| XmpError::NotSupported("Matroska Segment too large".to_string()))?;
+            patches.push((layout.segment.offset + id_len, new_size));
+        }
+
+        // Copy the file, applying the patches and appending to the Segment
+        patches.sort_by_key(|(offset, _)| *offset);
+        let file_end = reader.seek(SeekFrom::End(0))?;
+        let mut pos = 0u64;
+        for (offset, bytes) in &patches {
+            Self::copy_range(&mut reader, &mut writer, pos, *offset)?;
+            writer.write_all(bytes)?;
+            pos = offset + bytes.len() as u64;
+        }
+        Self::copy_range(&mut reader, &mut writer, pos, layout.end)?;
+        writer.write_all(&appended)?;
+        Self::copy_range(&mut reader, &mut writer, layout.end, file_end)?;
+
+        Ok(())
+    }
+
+    /// Copy the bytes between `start` and `end` from the reader
+    fn copy_range<R: Read + Seek, W: Write>(
+        reader: &mut R,
+        writer: &mut W,
+        start: u64,
+        end: u64,
+    ) -> XmpResult<()> {
+        reader.seek(SeekFrom::Start(start))?;
+        let copied = std::io::copy(&mut reader.by_ref().take(end - start), writer)?;
+        if copied != end - start {
+            return Err(XmpError::BadValue("Truncated Matroska file".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Update the first SeekHead for moved and added elements
+    ///
+    /// The SeekHead is rebuilt if it fits with the Void element following
+    /// it. Otherwise, the entries of moved elements are voided.
+    fn seek_head_patches<R: Read + Seek>(
+        reader: &mut R,
+        layout: &SegmentLayout,
+        moved: &[(u64, Option<u64>)],
+        added: &[(u32, u64)],
+    ) -> XmpResult<Vec<(u64, Vec<u8>)>> {
+        let Some(index) = layout
+            .children
+            .iter()
+            .position(|child| child.id == ID_SEEK_HEAD)
+        else {
+            return Ok(Vec::new());
+        };
+        let seek_head = layout.children[index];
+        let data = read_element_data(reader, &seek_head)?;
+
+        let mut patches = Vec::new();
+        let mut body = Vec::with_capacity(data.len());
+        for seek in parse_children(&data) {
+            let position = seek
+                .data(&data)
+                .and_then(|seek_data| child_data(seek_data, ID_SEEK_POSITION))
+                .and_then(read_uint);
+            let old_position = moved
+                .iter()
+                .find(|(old, _)| Some(*old) == position)
+                .map(|(_, new)| *new);
+            match old_position {
+                None => body.extend_from_slice(seek.bytes(&data)),
+                Some(new_position) => {
+                    let seek_id = seek
+                        .data(&data)
+                        .and_then(|seek_data| child_data(seek_data, ID_SEEK_ID))
+                        .unwrap_or_default();
+                    if let Some(new_position) = new_position {
+                        body.extend(Self::seek_entry_with_id(seek_id, new_position));
+                    }
+                    patches.push((
+                        seek_head.data_offset() + seek.offset,
+                        void_element(seek.total_size()),
+                    ));
+                }
+            }
+        }
+        for &(id, position) in added {
+            body.extend(Self::seek_entry_with_id(&encode_id(id), position));
+        }
+
+        // Rebuild the SeekHead in its space and the following Void
+        let mut space = seek_head.total_size();
+        if let Some(void) = layout
+            .children
+            .get(index + 1)
+            .filter(|child| child.id == ID_VOID)
+        {
+            space += void.total_size();
+        }
+        let mut new_seek_head = element(ID_SEEK_HEAD, &body);
+        let remaining = space.checked_sub(new_seek_head.len() as u64);
+        match remaining {
+            Some(0) => Ok(vec![(seek_head.offset, new_seek_head)]),
+            Some(remaining) if remaining >= 2 => {
+                new_seek_head.extend(void_element(remaining));
+                Ok(vec![(seek_head.offset, new_seek_head)])
+            }
+            _ => Ok(patches),
+        }
+    }
+
+    /// Build a Seek element
+    fn seek_entry_with_id(seek_id: &[u8], position: u64) -> Vec<u8> {
+        let mut seek = element(ID_SEEK_ID, seek_id);
+        seek.extend(uint_element(ID_SEEK_POSITION, position));
+        element(ID_SEEK, &seek)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+    use std::io::Cursor;
+
+    const CLUSTER_DATA: &[u8] = b"cluster payload";
+
+    /// Build a WebM file: SeekHead + Void + Info + Cluster + extra elements
+    fn build_webm(extra: &[u8], void_size: u64) -> Vec<u8> {
+        let mut ebml = element(0x4286, &[1]);
+        ebml.extend(element(ID_DOC_TYPE, b"webm"));
+        let mut file = element(ID_EBML, &ebml);
+
+        let info = element(0x1549_A966, &uint_element(0x2AD7B1, 1_000_000));
+        let cluster = element(0x1F43_B675, CLUSTER_DATA);
+        let seek_head_len =
+            element(ID_SEEK_HEAD, &MkvHandler::seek_entry_with_id(&[0; 4], 0)).len() as u64;
+        let info_position = seek_head_len + if void_size > 0 { void_size } else { 0 };
+        let seek_head = element(
+            ID_SEEK_HEAD,
+            &MkvHandler::seek_entry_with_id(&encode_id(0x1549_A966), info_position),
+        );
+        assert_eq!(seek_head.len() as u64, seek_head_len);
+
+        let mut segment = seek_head;
+        if void_size > 0 {
+            segment.extend(void_element(void_size));
+        }
+        segment.extend(info);
+        segment.extend(cluster);
+        segment.extend_from_slice(extra);
+
+        file.extend(encode_id(ID_SEGMENT));
+        file.extend(encode_size_fixed(segment.len() as u64, 8).unwrap());
+        file.extend(segment);
+        file
+    }
+
+    fn test_meta(title: &str) -> XmpMeta {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String(title.to_string()))
+            .unwrap();
+        meta
+    }
+
+    fn write(data: &[u8], meta: &XmpMeta, options: MkvOptions) -> Vec<u8> {
+        let mut writer = Cursor::new(Vec::new());
+        MkvHandler::write_xmp_with(Cursor::new(data), &mut writer, meta, options).unwrap();
+        writer.into_inner()
+    }
+
+    fn title(data: &[u8]) -> Option<XmpValue> {
+        MkvHandler::read_xmp(Cursor::new(data))
+            .unwrap()
+            .and_then(|meta| meta.get_property(ns::DC, "title"))
+    }
+
+    /// Element IDs with their positions in the Segment
+    type Positions = Vec<(u32, u64)>;
+
+    /// Top-level element positions and the positions listed in the SeekHead
+    fn structure(data: &[u8]) -> (Positions, Positions) {
+        let layout = SegmentLayout::read(&mut Cursor::new(data)).unwrap();
+        let children: Vec<_> = layout
+            .children
+            .iter()
+            .map(|child| (child.id, layout.relative(child.offset)))
+            .collect();
+        let seek_head = layout.children[0];
+        let seek_data = seek_head.data(data).unwrap();
+        let seeks = parse_children(seek_data)
+            .iter()
+            .filter(|seek| seek.id == ID_SEEK)
+            .map(|seek| {
+                let seek = seek.data(seek_data).unwrap();
+                let id = child_data(seek, ID_SEEK_ID).unwrap();
+                let position = read_uint(child_data(seek, ID_SEEK_POSITION).unwrap()).unwrap();
+                (id.iter().fold(0, |acc, &b| (acc << 8) | b as u32), position)
+            })
+            .collect();
+        (children, seeks)
+    }
+
+    #[test]
+    fn test_ebml_encoding() {
+        assert_eq!(encode_id(ID_SEGMENT), vec![0x18, 0x53, 0x80, 0x67]);
+        assert_eq!(encode_id(ID_VOID), vec![0xEC]);
+        assert_eq!(encode_size(5), vec![0x85]);
+        assert_eq!(encode_size(127), vec![0x40, 0x7F]);
+        for len in 2..20 {
+            let void = void_element(len);
+            assert_eq!(void.len() as u64, len);
+            let parsed = parse_element(&void, 0, len).unwrap();
+            assert_eq!(parsed.id, ID_VOID);
+            assert_eq!(parsed.total_size(), len);
+        }
+    }
+
+    #[test]
+    fn test_can_handle() {
+        let handler = MkvHandler;
+        assert!(handler
+            .can_handle(&mut Cursor::new(build_webm(&[], 0)))
+            .unwrap());
+        assert!(!handler
+            .can_handle(&mut Cursor::new(b"\x1A\x45\xDF\xA3 not really".to_vec()))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_write_attachment() {
+        let webm = build_webm(&[], 100);
+        assert_eq!(title(&webm), None);
+
+        let data = write(&webm, &test_meta("Clip"), MkvOptions::default());
+        assert_eq!(title(&data), Some(XmpValue::String("Clip".to_string())));
+
+        // The media data has not moved
+        let offset = |d: &[u8]| {
+            d.windows(CLUSTER_DATA.len())
+                .position(|w| w == CLUSTER_DATA)
+        };
+        assert_eq!(offset(&data), offset(&webm));
+
+        // The SeekHead points to the new Attachments element
+        let (children, seeks) = structure(&data);
+        let attachments = children
+            .iter()
+            .find(|(id, _)| *id == ID_ATTACHMENTS)
+            .unwrap();
+        assert!(seeks.contains(attachments));
+        let info = children.iter().find(|(id, _)| *id == 0x1549_A966).unwrap();
+        assert!(seeks.contains(info));
+
+        // Rewriting replaces the attachment
+        let data = write(&data, &test_meta("Clip 2"), MkvOptions::default());
+        assert_eq!(title(&data), Some(XmpValue::String("Clip 2".to_string())));
+        let (children, seeks) = structure(&data);
+        let attachments: Vec<_> = children
+            .iter()
+            .filter(|(id, _)| *id == ID_ATTACHMENTS)
+            .collect();
+        assert_eq!(attachments.len(), 1);
+        assert!(seeks.contains(attachments[0]));
+    }
+
+    #[test]
+    fn test_write_tags_keeps_other_entries() {
+        // Attachments with a font, and Tags with a title
+        let mut font = element(ID_FILE_NAME, b"font.ttf");
+        font.extend(element(ID_FILE_MIME_TYPE, b"font/ttf"));
+        font.extend(element(ID_FILE_DATA, b"glyphs"));
+        let mut extra = element(ID_ATTACHMENTS, &element(ID_ATTACHED_FILE, &font));
+        let mut simple_tag = element(ID_TAG_NAME, b"TITLE");
+        simple_tag.extend(element(ID_TAG_STRING, b"My clip"));
+        let mut tag = element(ID_TARGETS, &[]);
+        tag.extend(element(ID_SIMPLE_TAG, &simple_tag));
+        extra.extend(element(ID_TAGS, &element(ID_TAG, &tag)));
+        // No room in the SeekHead
+        let webm = build_webm(&extra, 0);
+
+        let data = write(&webm, &test_meta("Tagged"), MkvOptions::default());
+        let data = write(
+            &data,
+            &test_meta("Tagged"),
+            MkvOptions::default().store_in_tags(),
+        );
+        assert_eq!(title(&data), Some(XmpValue::String("Tagged".to_string())));
+
+        let layout = SegmentLayout::read(&mut Cursor::new(&data)).unwrap();
+        let attachments: Vec<_> = layout
+            .children
+            .iter()
+            .filter(|c| c.id == ID_ATTACHMENTS)
+            .collect();
+        assert_eq!(attachments.len(), 1);
+        let attachments_data = attachments[0].data(&data).unwrap();
+        assert!(Storage::Attachments.find_xmp(attachments_data).is_none());
+        assert!(attachments_data.windows(6).any(|w| w == b"glyphs"));
+
+        let tags: Vec<_> = layout.children.iter().filter(|c| c.id == ID_TAGS).collect();
+        assert_eq!(tags.len(), 1);
+        let tags_data = tags[0].data(&data).unwrap();
+        assert!(Storage::Tags.find_xmp(tags_data).is_some());
+        assert!(tags_data.windows(7).any(|w| w == b"My clip"));
+    }
+}
@@ -4,15 +4,66 @@
 //! and extracting XMP metadata. All handlers are pure Rust implementations
 //! that work across all platforms.
 
+#[cfg(feature = "aiff")]
+pub mod aiff;
+#[cfg(feature = "asf")]
+pub mod asf;
+#[cfg(feature = "avi")]
+pub mod avi;
+#[cfg(feature = "avif")]
+pub mod avif;
+#[cfg(feature = "eps")]
+pub mod eps;
+#[cfg(any(feature = "jpeg", feature = "tiff"))]
+pub mod exif;
+#[cfg(feature = "flac")]
+pub mod flac;
 #[cfg(feature = "gif")]
 pub mod gif;
+#[cfg(feature = "heif")]
+pub mod heif;
+#[cfg(feature = "mp3")]
+pub mod id3;
+#[cfg(feature = "indd")]
+pub mod indd;
+#[cfg(feature = "jpeg")]
+pub mod iptc;
+#[cfg(feature = "jp2")]
+pub mod jp2;
 #[cfg(feature = "jpeg")]
 pub mod jpeg;
+#[cfg(feature = "jxl")]
+pub mod jxl;
+#[cfg(feature = "mkv")]
+pub mod mkv;
+#[cfg(feature = "mov")]
+pub mod mov;
 #[cfg(feature = "mp3")]
 pub mod mp3;
 #[cfg(feature = "mp4")]
 pub mod mp4;
+#[cfg(feature = "ogg")]
+pub mod ogg;
+#[cfg(feature = "pdf")]
+pub mod pdf;
 #[cfg(feature = "png")]
 pub mod png;
+#[cfg(feature = "psd")]
+pub mod psd;
+#[cfg(feature = "mp4")]
+pub mod quicktime;
+pub mod sidecar;
+#[cfg(feature = "svg")]
+pub mod svg;
 #[cfg(feature = "tiff")]
 pub mod tiff;
+#[cfg(feature = "wav")]
+pub mod wav;
+#[cfg(feature = "webp")]
+pub mod webp;
+
+#[cfg(any(feature = "avi", feature = "wav", feature = "webp"))]
+pub(crate) mod riff;
+
+#[cfg(any(feature = "jp2", feature = "jxl"))]
+pub(crate) mod bmff;
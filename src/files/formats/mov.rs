@@ -0,0 +1,583 @@
+//! QuickTime MOV file format handler
+//!
+//! This module provides functionality for reading and writing XMP metadata
+//! in QuickTime movie files. The implementation is pure Rust and
+//! cross-platform compatible.
+//!
+//! QuickTime File Structure:
+//! - A sequence of atoms: size (4 bytes, big-endian) + type (4 bytes) + data
+//! - A size of 1 means a 64-bit size follows the type; a size of 0 means the
+//!   atom extends to the end of the file
+//! - Files start with an `ftyp` atom with the "qt  " brand, or directly with
+//!   `moov`, `mdat`, `wide`, `free`, `skip` or `pnot` for older files
+//!
+//! QuickTime XMP Storage:
+//! - XMP Packet is stored in the `moov/udta/XMP_` atom (Adobe convention)
+//! - A top-level `uuid` atom with the XMP UUID (ISO Base Media convention)
+//!   is also read
+//! - When the `moov` atom grows or shrinks, the `stco`/`co64` chunk offsets
+//!   of the media data after it are updated
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::files::handler::FileHandler;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// QuickTime brand of the `ftyp` atom
+const QT_BRAND: &[u8; 4] = b"qt  ";
+
+/// Atom types that can start a QuickTime file without `ftyp`
+const LEGACY_FIRST_ATOMS: &[&[u8; 4]] = &[b"moov", b"mdat", b"wide", b"free", b"skip", b"pnot"];
+
+/// XMP UUID (same as for MP4 files)
+const XMP_UUID: &[u8; 16] = &[
+    0xBE, 0x7A, 0xCF, 0xCB, 0x97, 0xA9, 0x42, 0xE8, 0x9C, 0x71, 0x99, 0x94, 0x91, 0xE3, 0xAF, 0xAC,
+];
+
+/// Atom types
+const ATOM_MOOV: &[u8; 4] = b"moov";
+const ATOM_UDTA: &[u8; 4] = b"udta";
+const ATOM_XMP: &[u8; 4] = b"XMP_";
+const ATOM_UUID: &[u8; 4] = b"uuid";
+
+/// Container atoms on the path to the chunk offset tables
+const SAMPLE_TABLE_PATH: &[&[u8; 4]] = &[b"trak", b"mdia", b"minf", b"stbl"];
+
+/// QuickTime MOV file handler for XMP metadata
+#[derive(Debug, Clone, Copy)]
+pub struct MovHandler;
+
+impl FileHandler for MovHandler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        let pos = reader.stream_position()?;
+        let mut header = [0u8; 12];
+        let result = reader.read_exact(&mut header);
+        reader.seek(SeekFrom::Start(pos))?;
+        if result.is_err() {
+            return Ok(false);
+        }
+
+        let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+        let atom_type = &header[4..8];
+        if atom_type == b"ftyp" {
+            return Ok(&header[8..12] == QT_BRAND);
+        }
+        Ok((size == 1 || size >= 8) && LEGACY_FIRST_ATOMS.iter().any(|t| t[..] == *atom_type))
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn format_name(&self) -> &'static str {
+        "MOV"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["mov", "qt"]
+    }
+}
+
+/// The location of an atom
+#[derive(Debug, Clone, Copy)]
+struct Atom {
+    atom_type: [u8; 4],
+    /// Offset of the atom header
+    offset: u64,
+    /// Size of the atom header (8 or 16 bytes)
+    header_size: u64,
+    /// Size of the atom, including its header
+    size: u64,
+}
+
+impl Atom {
+    fn data_offset(&self) -> u64 {
+        self.offset + self.header_size
+    }
+
+    fn end(&self) -> u64 {
+        self.offset + self.size
+    }
+}
+
+/// Parse an atom header at `offset` from its first bytes
+///
+/// `available` is the number of bytes up to the end of the parent, used
+/// for atoms with a size of 0.
+fn parse_atom_header(header: &[u8], offset: u64, available: u64) -> Option<Atom> {
+    if header.len() < 8 {
+        return None;
+    }
+    let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as u64;
+    let atom_type = [header[4], header[5], header[6], header[7]];
+    let (header_size, size) = match size {
+        0 => (8, available),
+        1 => {
+            let ext = header.get(8..16)?;
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(ext);
+            (16, u64::from_be_bytes(bytes))
+        }
+        size => (8, size),
+    };
+    (size >= header_size && size <= available).then_some(Atom {
+        atom_type,
+        offset,
+        header_size,
+        size,
+    })
+}
+
+/// Parse the atoms of an in-memory container body
+///
+/// Returns the atoms and the offset where parsing stopped. Old QuickTime
+/// files may end a `udta` atom with a 32-bit zero terminator, which is
+/// left as trailing data.
+fn parse_atoms(data: &[u8]) -> (Vec<Atom>, usize) {
+    let mut atoms = Vec::new();
+    let mut pos = 0usize;
+    while pos + 8 <= data.len() {
+        let available = (data.len() - pos) as u64;
+        if data[pos..pos + 4] == [0; 4] && available < 16 {
+            break;
+        }
+        match parse_atom_header(&data[pos..], pos as u64, available) {
+            Some(atom) => {
+                pos += atom.size as usize;
+                atoms.push(atom);
+            }
+            None => break,
+        }
+    }
+    (atoms, pos)
+}
+
+/// Write an atom with the given type and data
+fn write_atom(out: &mut Vec<u8>, atom_type: &[u8; 4], data: &[u8]) {
+    let size = 8 + data.len() as u64;
+    if size <= u32::MAX as u64 {
+        out.extend_from_slice(&(size as u32).to_be_bytes());
+        out.extend_from_slice(atom_type);
+    } else {
+        out.extend_from_slice(&1u32.to_be_bytes());
+        out.extend_from_slice(atom_type);
+        out.extend_from_slice(&(size + 8).to_be_bytes());
+    }
+    out.extend_from_slice(data);
+}
+
+/// Check whether an in-memory atom is an XMP `uuid` atom
+fn is_xmp_uuid(data: &[u8], atom: &Atom) -> bool {
+    let start = atom.data_offset() as usize;
+    atom.atom_type == *ATOM_UUID && data.get(start..start + 16) == Some(&XMP_UUID[..])
+}
+
+impl MovHandler {
+    /// Read XMP metadata from a QuickTime MOV file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(XmpMeta))` if XMP metadata is found
+    /// * `Ok(None)` if no XMP metadata is found
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_xmp<R: Read + Seek>(mut reader: R) -> XmpResult<Option<XmpMeta>> {
+        let atoms = Self::read_top_level_atoms(&mut reader)?;
+
+        let mut xmp_data = None;
+        if let Some(moov) = atoms.iter().find(|atom| atom.atom_type == *ATOM_MOOV) {
+            let moov_data = Self::read_atom_data(&mut reader, moov)?;
+            xmp_data = Self::find_udta_xmp(&moov_data);
+        }
+        if xmp_data.is_none() {
+            for atom in atoms.iter().filter(|atom| atom.atom_type == *ATOM_UUID) {
+                let data = Self::read_atom_data(&mut reader, atom)?;
+                if data.starts_with(XMP_UUID) {
+                    xmp_data = Some(data[16..].to_vec());
+                    break;
+                }
+            }
+        }
+
+        let Some(xmp_data) = xmp_data else {
+            return Ok(None);
+        };
+        let xmp_str = String::from_utf8(xmp_data)
+            .map_err(|e| XmpError::ParseError(format!("Invalid UTF-8 in XMP: {}", e)))?;
+        XmpMeta::parse(xmp_str.trim_end_matches('\0')).map(Some)
+    }
+
+    /// Write XMP metadata to a QuickTime MOV file
+    ///
+    /// The XMP Packet is written to `moov/udta/XMP_`, creating the `udta`
+    /// atom if needed. If the media data follows the `moov` atom, its chunk
+    /// offsets (`stco` and `co64`) are adjusted by the change in size.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    pub fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        let xmp_packet = meta.serialize_packet()?;
+
+        let atoms = Self::read_top_level_atoms(&mut reader)?;
+        let moov = *atoms
+            .iter()
+            .find(|atom| atom.atom_type == *ATOM_MOOV)
+            .ok_or_else(|| XmpError::BadValue("MOV file has no moov atom".to_string()))?;
+
+        let moov_data = Self::read_atom_data(&mut reader, &moov)?;
+        let mut new_moov_data = Self::moov_with_xmp(&moov_data, xmp_packet.as_bytes());
+        let delta = (8 + new_moov_data.len() as i64) - moov.size as i64;
+        if delta != 0 {
+            Self::update_chunk_offsets(&mut new_moov_data, moov.end(), delta)?;
+        }
+
+        for atom in &atoms {
+            if atom.offset == moov.offset {
+                let mut new_moov = Vec::new();
+                write_atom(&mut new_moov, ATOM_MOOV, &new_moov_data);
+                writer.write_all(&new_moov)?;
+            } else {
+                reader.seek(SeekFrom::Start(atom.offset))?;
+                let copied = std::io::copy(&mut reader.by_ref().take(atom.size), &mut writer)?;
+                if copied != atom.size {
+                    return Err(XmpError::BadValue("Truncated MOV atom".to_string()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read the top-level atoms of the file
+    fn read_top_level_atoms<R: Read + Seek>(reader: &mut R) -> XmpResult<Vec<Atom>> {
+        let file_end = reader.seek(SeekFrom::End(0))?;
+        let mut atoms = Vec::new();
+        let mut pos = 0u64;
+        while pos + 8 <= file_end {
+            reader.seek(SeekFrom::Start(pos))?;
+            let mut header = [0u8; 16];
+            let len = (file_end - pos).min(16) as usize;
+            reader.read_exact(&mut header[..len])?;
+            let atom = parse_atom_header(&header[..len], pos, file_end - pos)
+                .ok_or_else(|| XmpError::BadValue("Invalid MOV atom".to_string()))?;
+            pos = atom.end();
+            atoms.push(atom);
+        }
+
+        if atoms.is_empty() {
+            return Err(XmpError::BadValue("Not a valid MOV file".to_string()));
+        }
+        Ok(atoms)
+    }
+
+    /// Read the data of an atom, without its header
+    fn read_atom_data<R: Read + Seek>(reader: &mut R, atom: &Atom) -> XmpResult<Vec<u8>> {
+        reader.seek(SeekFrom::Start(atom.data_offset()))?;
+        let mut data = vec![0u8; (atom.size - atom.header_size) as usize];
+        reader.read_exact(&mut data)?;
+        Ok(data)
+    }
+
+    /// Find the XMP data in the `udta` atom of a `moov` body
+    fn find_udta_xmp(moov_data: &[u8]) -> Option<Vec<u8>> {
+        let (moov_atoms, _) = parse_atoms(moov_data);
+        let udta = moov_atoms
+            .iter()
+            .find(|atom| atom.atom_type == *ATOM_UDTA)?;
+        let udta_data = &moov_data[udta.data_offset() as usize..udta.end() as usize];
+
+        let (udta_atoms, _) = parse_atoms(udta_data);
+        udta_atoms.iter().find_map(|atom| {
+            let data = &udta_data[atom.data_offset() as usize..atom.end() as usize];
+            if atom.atom_type == *ATOM_XMP {
+                Some(data.to_vec())
+            } else if is_xmp_uuid(udta_data, atom) {
+                Some(data[16..].to_vec())
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Build a new `moov` body with the XMP packet in its `udta` atom
+    fn moov_with_xmp(moov_data: &[u8], xmp: &[u8]) -> Vec<u8> {
+        let (moov_atoms, moov_end) = parse_atoms(moov_data);
+        let mut new_moov = Vec::with_capacity(moov_data.len() + xmp.len() + 16);
+        let mut udta_found = false;
+
+        for atom in &moov_atoms {
+            let atom_bytes = &moov_data[atom.offset as usize..atom.end() as usize];
+            if atom.atom_type != *ATOM_UDTA || udta_found {
+                new_moov.extend_from_slice(atom_bytes);
+                continue;
+            }
+            udta_found = true;
+
+            // Replace any XMP in place, keeping the other user data
+            let udta_data = &moov_data[atom.data_offset() as usize..atom.end() as usize];
+            let (udta_atoms, udta_end) = parse_atoms(udta_data);
+            let mut new_udta = Vec::with_capacity(udta_data.len() + xmp.len() + 8);
+            let mut xmp_written = false;
+            for child in &udta_atoms {
+                if child.atom_type == *ATOM_XMP || is_xmp_uuid(udta_data, child) {
+                    if !xmp_written {
+                        write_atom(&mut new_udta, ATOM_XMP, xmp);
+                        xmp_written = true;
+                    }
+                } else {
+                    new_udta
+                        .extend_from_slice(&udta_data[child.offset as usize..child.end() as usize]);
+                }
+            }
+            if !xmp_written {
+                write_atom(&mut new_udta, ATOM_XMP, xmp);
+            }
+            new_udta.extend_from_slice(&udta_data[udta_end..]);
+            write_atom(&mut new_moov, ATOM_UDTA, &new_udta);
+        }
+
+        if !udta_found {
+            let mut new_udta = Vec::new();
+            write_atom(&mut new_udta, ATOM_XMP, xmp);
+            write_atom(&mut new_moov, ATOM_UDTA, &new_udta);
+        }
+        new_moov.extend_from_slice(&moov_data[moov_end..]);
+        new_moov
+    }
+
+    /// Shift the chunk offsets at or after `threshold` by `delta`
+    ///
+    /// `data` is a `moov` body; the `stco` and `co64` tables are found
+    /// through the `trak/mdia/minf/stbl` containers.
+    fn update_chunk_offsets(data: &mut [u8], threshold: u64, delta: i64) -> XmpResult<()> {
+        Self::update_chunk_offsets_in(data, 0, threshold, delta)
+    }
+
+    fn update_chunk_offsets_in(
+        data: &mut [u8],
+        depth: usize,
+        threshold: u64,
+        delta: i64,
+    ) -> XmpResult<()> {
+        let (atoms, _) = parse_atoms(data);
+        for atom in atoms {
+            let body = &mut data[atom.data_offset() as usize..atom.end() as usize];
+            if depth < SAMPLE_TABLE_PATH.len() {
+                if atom.atom_type == *SAMPLE_TABLE_PATH[depth] {
+                    Self::update_chunk_offsets_in(body, depth + 1, threshold, delta)?;
+                }
+                continue;
+            }
+
+            let entry_size = match &atom.atom_type {
+                b"stco" => 4,
+                b"co64" => 8,
+                _ => continue,
+            };
+            // Version and flags (4 bytes) + entry count (4 bytes) + entries
+            if body.len() < 8 {
+                return Err(XmpError::BadValue("Invalid chunk offset table".to_string()));
+            }
+            let count = u32::from_be_bytes([body[4], body[5], body[6], body[7]]) as usize;
+            let entries = body
+                .get_mut(8..8 + count * entry_size)
+                .ok_or_else(|| XmpError::BadValue("Invalid chunk offset table".to_string()))?;
+
+            for entry in entries.chunks_exact_mut(entry_size) {
+                let offset = if entry_size == 4 {
+                    u32::from_be_bytes([entry[0], entry[1], entry[2], entry[3]]) as u64
+                } else {
+                    let mut bytes = [0u8; 8];
+                    bytes.copy_from_slice(entry);
+                    u64::from_be_bytes(bytes)
+                };
+                if offset < threshold {
+                    continue;
+                }
+
+                let new_offset = offset
+                    .checked_add_signed(delta)
+                    .ok_or_else(|| XmpError::BadValue("Chunk offset out of range".to_string()))?;
+                if entry_size == 4 {
+                    let new_offset = u32::try_from(new_offset).map_err(|_| {
+                        XmpError::NotSupported(
+                            "Chunk offset too large for an stco table".to_string(),
+                        )
+                    })?;
+                    entry.copy_from_slice(&new_offset.to_be_bytes());
+                } else {
+                    entry.copy_from_slice(&new_offset.to_be_bytes());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+    use std::io::Cursor;
+
+    fn atom(atom_type: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_atom(&mut out, atom_type, data);
+        out
+    }
+
+    /// Build a MOV file with one track whose chunks are in the `mdat` atom
+    /// following `moov`. The `stco` table is used, or `co64` if `co64`.
+    fn build_mov(udta: Option<&[u8]>, co64: bool) -> Vec<u8> {
+        let ftyp = atom(b"ftyp", b"qt  \0\0\x02\0qt  ");
+        let build = |mdat_offset: u64| {
+            let mut table = vec![0, 0, 0, 0, 0, 0, 0, 2];
+            for chunk in 0..2u64 {
+                let offset = mdat_offset + 8 + chunk * 4;
+                if co64 {
+                    table.extend_from_slice(&offset.to_be_bytes());
+                } else {
+                    table.extend_from_slice(&(offset as u32).to_be_bytes());
+                }
+            }
+            let stbl = atom(b"stbl", &atom(if co64 { b"co64" } else { b"stco" }, &table));
+            let minf = atom(b"minf", &stbl);
+            let trak = atom(b"trak", &atom(b"mdia", &minf));
+            let mut moov = atom(b"mvhd", &[0; 100]);
+            moov.extend_from_slice(&trak);
+            if let Some(udta) = udta {
+                moov.extend_from_slice(&atom(b"udta", udta));
+            }
+            atom(b"moov", &moov)
+        };
+
+        let moov_size = build(0).len() as u64;
+        let mut mov = ftyp.clone();
+        mov.extend_from_slice(&build(ftyp.len() as u64 + moov_size));
+        mov.extend_from_slice(&atom(b"mdat", b"AAAABBBB"));
+        mov
+    }
+
+    /// Read the chunk data of the first track through its offset table
+    fn read_chunks(mov: &[u8]) -> Vec<Vec<u8>> {
+        let pos = mov
+            .windows(4)
+            .position(|w| w == b"stco" || w == b"co64")
+            .unwrap();
+        let entry_size = if &mov[pos..pos + 4] == b"co64" { 8 } else { 4 };
+        let table = &mov[pos + 12..];
+        (0..2)
+            .map(|i| {
+                let entry = &table[i * entry_size..(i + 1) * entry_size];
+                let offset = entry.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64) as usize;
+                mov[offset..offset + 4].to_vec()
+            })
+            .collect()
+    }
+
+    fn test_meta(title: &str) -> XmpMeta {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String(title.to_string()))
+            .unwrap();
+        meta
+    }
+
+    #[test]
+    fn test_can_handle() {
+        let handler = MovHandler;
+        assert!(handler
+            .can_handle(&mut Cursor::new(build_mov(None, false)))
+            .unwrap());
+        assert!(handler
+            .can_handle(&mut Cursor::new(atom(b"moov", &[0; 8])))
+            .unwrap());
+
+        let mut mp4 = atom(b"ftyp", b"isom\0\0\0\0isom");
+        mp4.extend_from_slice(&atom(b"moov", &[]));
+        assert!(!handler.can_handle(&mut Cursor::new(mp4)).unwrap());
+    }
+
+    #[test]
+    fn test_write_xmp_updates_chunk_offsets() {
+        for co64 in [false, true] {
+            let mov = build_mov(None, co64);
+            assert_eq!(read_chunks(&mov), vec![b"AAAA".to_vec(), b"BBBB".to_vec()]);
+            assert!(MovHandler::read_xmp(Cursor::new(&mov)).unwrap().is_none());
+
+            let mut writer = Cursor::new(Vec::new());
+            MovHandler::write_xmp(Cursor::new(&mov), &mut writer, &test_meta("Movie")).unwrap();
+            let data = writer.into_inner();
+            assert!(data.len() > mov.len());
+            assert_eq!(read_chunks(&data), vec![b"AAAA".to_vec(), b"BBBB".to_vec()]);
+
+            let meta = MovHandler::read_xmp(Cursor::new(&data)).unwrap().unwrap();
+            assert_eq!(
+                meta.get_property(ns::DC, "title"),
+                Some(XmpValue::String("Movie".to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_xmp_replaces_existing() {
+        // udta with another atom, an old XMP_ atom and a zero terminator
+        let mut udta = atom(b"\xa9nam", b"name");
+        udta.extend_from_slice(&atom(ATOM_XMP, b"old"));
+        udta.extend_from_slice(&[0; 4]);
+        let mov = build_mov(Some(&udta), false);
+
+        let mut writer = Cursor::new(Vec::new());
+        MovHandler::write_xmp(Cursor::new(&mov), &mut writer, &test_meta("First")).unwrap();
+        let first = writer.into_inner();
+        let mut writer = Cursor::new(Vec::new());
+        MovHandler::write_xmp(Cursor::new(&first), &mut writer, &test_meta("Second")).unwrap();
+        let data = writer.into_inner();
+
+        assert_eq!(read_chunks(&data), vec![b"AAAA".to_vec(), b"BBBB".to_vec()]);
+        assert_eq!(data.windows(4).filter(|w| w == ATOM_XMP).count(), 1);
+        assert!(data.windows(4).any(|w| w == b"name"));
+        let meta = MovHandler::read_xmp(Cursor::new(&data)).unwrap().unwrap();
+        assert_eq!(
+            meta.get_property(ns::DC, "title"),
+            Some(XmpValue::String("Second".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_read_top_level_uuid() {
+        let mut mov = build_mov(None, false);
+        let mut uuid = XMP_UUID.to_vec();
+        uuid.extend_from_slice(test_meta("UUID").serialize_packet().unwrap().as_bytes());
+        mov.extend_from_slice(&atom(ATOM_UUID, &uuid));
+
+        let meta = MovHandler::read_xmp(Cursor::new(&mov)).unwrap().unwrap();
+        assert_eq!(
+            meta.get_property(ns::DC, "title"),
+            Some(XmpValue::String("UUID".to_string()))
+        );
+    }
+}
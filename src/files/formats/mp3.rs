@@ -7,9 +7,14 @@
 //! - XMP Packet is stored in ID3v2 PRIV frame (ID3v2.3/2.4) or PRV frame (ID3v2.2)
 //! - Frame content format: "XMP\0" + XMP Packet
 //! - ID3v2 tag header is 10 bytes at the start of the file
+//! - The other ID3v2 frames (title, artist, ...) can be imported into XMP
+//!   and rewritten from it (see [`Id3Tag`])
 
 use crate::core::error::{XmpError, XmpResult};
 use crate::core::metadata::XmpMeta;
+use crate::files::file::SaveOptions;
+use crate::files::format::FormatCapabilities;
+use crate::files::formats::id3::{self, Id3Tag};
 use crate::files::handler::FileHandler;
 use std::io::{Read, Seek, SeekFrom, Write};
 
@@ -56,6 +61,43 @@ impl FileHandler for Mp3Handler {
         Self::write_xmp(reader, writer, meta)
     }
 
+    fn import_legacy<R: Read + Seek>(&self, reader: &mut R, meta: &mut XmpMeta) -> XmpResult<bool> {
+        // Unreadable tags are ignored, like a missing tag
+        match Self::read_id3(reader) {
+            Ok(Some(tag)) => tag.import_into(meta),
+            _ => Ok(false),
+        }
+    }
+
+    fn export_legacy<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+        options: &SaveOptions,
+    ) -> XmpResult<bool> {
+        if !options.reconcile_id3 {
+            return Ok(false);
+        }
+        Self::write_id3(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> FormatCapabilities {
+        FormatCapabilities {
+            can_read: true,
+            can_write: true,
+            can_inject: true,
+            supports_in_place: true,
+            supports_reconciliation: true,
+            // The size of an ID3v2 tag is a 28-bit number
+            max_packet_size: Some(0x0FFF_FFFF - ID3V23_FRAME_HEADER_SIZE - XMP_PREFIX.len()),
+        }
+    }
+
     fn format_name(&self) -> &'static str {
         "MP3"
     }
@@ -173,6 +215,66 @@ impl Mp3Handler {
         Ok(None)
     }
 
+    /// Read the ID3v2 tag of an MP3 file
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(Id3Tag))` if the file starts with an ID3v2 tag
+    /// * `Ok(None)` if there is no ID3v2 tag
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_id3<R: Read + Seek>(mut reader: R) -> XmpResult<Option<Id3Tag>> {
+        Ok(match Self::read_id3_data(&mut reader)? {
+            Some((data, _)) => Some(Id3Tag::parse(&data)?),
+            None => None,
+        })
+    }
+
+    /// Write XMP values to the ID3v2 frames of an MP3 file
+    ///
+    /// See [`SaveOptions::reconcile_id3`] for the exported values. The tag
+    /// keeps its version; its padding and extended header are not kept.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` if a copy of the file with updated frames was written
+    /// * `Ok(false)` if the frames are up to date (nothing is written)
+    /// * `Err(XmpError)` if an error occurs
+    pub fn write_id3<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<bool> {
+        let Some((data, tag_end)) = Self::read_id3_data(&mut reader)? else {
+            return Ok(false);
+        };
+        let mut tag = Id3Tag::parse(&data)?;
+        if !tag.update_from(meta) {
+            return Ok(false);
+        }
+        writer.write_all(&tag.to_bytes()?)?;
+        reader.seek(SeekFrom::Start(tag_end))?;
+        std::io::copy(&mut reader, &mut writer)?;
+        Ok(true)
+    }
+
+    /// Read the whole ID3v2 tag (with its header), and the offset of the
+    /// data that follows it (after the footer, if any)
+    fn read_id3_data<R: Read + Seek>(reader: &mut R) -> XmpResult<Option<(Vec<u8>, u64)>> {
+        reader.rewind()?;
+        let mut header = [0u8; id3::TAG_HEADER_SIZE];
+        reader.read_exact(&mut header)?;
+        if &header[0..3] != b"ID3" {
+            return Ok(None);
+        }
+        let tag_size = Self::read_synchsafe_u32(&header[6..10])? as usize;
+        let mut data = header.to_vec();
+        data.resize(id3::TAG_HEADER_SIZE + tag_size, 0);
+        reader.read_exact(&mut data[id3::TAG_HEADER_SIZE..])?;
+        let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+        let tag_end = (data.len() + footer) as u64;
+        Ok(Some((data, tag_end)))
+    }
+
     /// Write XMP metadata to an MP3 file
     ///
     /// # Arguments
@@ -579,4 +681,39 @@ mod tests {
             assert_eq!(value, decoded);
         }
     }
+
+    #[test]
+    fn test_reconcile_id3() {
+        use crate::files::{SaveOptions, XmpFile};
+
+        // ID3v2.3 tag with a title, followed by audio data
+        let mut mp3 = b"ID3\x03\0\0\0\0\0\x0FTIT2\0\0\0\x05\0\0\0Song".to_vec();
+        mp3.extend_from_slice(&[0xFF, 0xFB, 0x90, 0x00]);
+
+        let mut file = XmpFile::new();
+        file.from_bytes(&mp3).unwrap();
+        let meta = file.get_xmp_mut().unwrap();
+        assert_eq!(
+            meta.get_localized_text(ns::DC, "title", "", "x-default")
+                .map(|(text, _)| text),
+            Some("Song".to_string())
+        );
+        meta.set_property(ns::XMP_DM, "artist", "Band".into())
+            .unwrap();
+
+        let mut output = Cursor::new(Vec::new());
+        file.write_to_writer_with(&mut output, SaveOptions::default().reconcile_id3())
+            .unwrap();
+        let data = output.into_inner();
+        assert!(data.ends_with(&[0xFF, 0xFB, 0x90, 0x00]));
+
+        let tag = Mp3Handler::read_id3(Cursor::new(&data)).unwrap().unwrap();
+        assert_eq!(tag.text("TIT2").as_deref(), Some("Song"));
+        assert_eq!(tag.text("TPE1").as_deref(), Some("Band"));
+        let meta = Mp3Handler::read_xmp(Cursor::new(&data)).unwrap().unwrap();
+        assert_eq!(
+            meta.get_property(ns::XMP_DM, "artist"),
+            Some(XmpValue::String("Band".to_string()))
+        );
+    }
 }
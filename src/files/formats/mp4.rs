@@ -7,9 +7,17 @@
 //! - XMP Packet is stored in a UUID box (user data box)
 //! - UUID: BE7ACFCB-97A9-42E8-9C71-999FBE5EFFDB
 //! - The XMP data is stored directly in the UUID box data
+//!
+//! Legacy Metadata:
+//! - iTunes metadata items (`moov/udta/meta/ilst`) and the creation time of
+//!   the movie and media headers are reconciled with XMP (see
+//!   [`quicktime`](crate::files::formats::quicktime))
 
 use crate::core::error::{XmpError, XmpResult};
 use crate::core::metadata::XmpMeta;
+use crate::files::file::SaveOptions;
+use crate::files::format::FormatCapabilities;
+use crate::files::formats::quicktime::QuickTimeMetadata;
 use crate::files::handler::FileHandler;
 use std::io::{Read, Seek, SeekFrom, Write};
 
@@ -74,6 +82,42 @@ impl FileHandler for Mp4Handler {
         Self::write_xmp(reader, writer, meta)
     }
 
+    fn import_legacy<R: Read + Seek>(&self, reader: &mut R, meta: &mut XmpMeta) -> XmpResult<bool> {
+        // Unreadable metadata is ignored, like missing metadata
+        match Self::read_quicktime(reader) {
+            Ok(Some(metadata)) => metadata.import_into(meta),
+            _ => Ok(false),
+        }
+    }
+
+    fn export_legacy<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+        options: &SaveOptions,
+    ) -> XmpResult<bool> {
+        if !options.reconcile_quicktime {
+            return Ok(false);
+        }
+        Self::write_quicktime(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> FormatCapabilities {
+        FormatCapabilities {
+            can_read: true,
+            can_write: true,
+            can_inject: true,
+            supports_in_place: self.supports_in_place_update(),
+            supports_reconciliation: true,
+            max_packet_size: None,
+        }
+    }
+
     fn format_name(&self) -> &'static str {
         "MP4"
     }
@@ -91,6 +135,20 @@ struct Mp4Box {
     data_offset: u64,
 }
 
+/// A top-level movie box, read for legacy metadata
+#[derive(Debug)]
+struct MoovBox {
+    /// Offset of the box
+    start: u64,
+    /// Size of the box, with its header
+    size: u64,
+    /// Content of the box, without its header
+    content: Vec<u8>,
+    /// Whether media data follows the box (its chunk offsets then move
+    /// with the end of the box)
+    media_follows: bool,
+}
+
 /// Box layout information for optimize-file-layout mode (matches Adobe C++ LayoutInfo)
 #[cfg(feature = "optimize-file-layout")]
 #[derive(Debug, Clone)]
@@ -155,6 +213,112 @@ impl Mp4Handler {
         }
     }
 
+    /// Read the QuickTime metadata (iTunes items and creation time) of an
+    /// MP4 file
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(QuickTimeMetadata))` if the file has a movie box
+    /// * `Ok(None)` if the file has no movie box
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_quicktime<R: Read + Seek>(mut reader: R) -> XmpResult<Option<QuickTimeMetadata>> {
+        Ok(match Self::read_moov(&mut reader)? {
+            Some(moov) => Some(QuickTimeMetadata::parse(&moov.content)?),
+            None => None,
+        })
+    }
+
+    /// Write XMP values to the QuickTime metadata of an MP4 file
+    ///
+    /// See [`SaveOptions::reconcile_quicktime`] for the exported values.
+    /// Chunk offsets are updated when the movie box precedes the media
+    /// data.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` if a copy of the file with updated metadata was written
+    /// * `Ok(false)` if the metadata is up to date (nothing is written)
+    /// * `Err(XmpError)` if an error occurs
+    pub fn write_quicktime<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<bool> {
+        let Some(moov) = Self::read_moov(&mut reader)? else {
+            return Ok(false);
+        };
+        let mut metadata = QuickTimeMetadata::parse(&moov.content)?;
+        if !metadata.update_from(meta) {
+            return Ok(false);
+        }
+
+        let content = metadata.write_moov(&moov.content)?;
+        let mut moov_box = if content.len() as u64 + 8 <= u32::MAX as u64 {
+            let mut header = ((content.len() + 8) as u32).to_be_bytes().to_vec();
+            header.extend_from_slice(b"moov");
+            header
+        } else {
+            let mut header = 1u32.to_be_bytes().to_vec();
+            header.extend_from_slice(b"moov");
+            header.extend_from_slice(&(content.len() as u64 + 16).to_be_bytes());
+            header
+        };
+        moov_box.extend_from_slice(&content);
+        if moov.media_follows {
+            let delta = moov_box.len() as i64 - moov.size as i64;
+            Self::update_chunk_offsets_in_buffer(&mut moov_box, delta)?;
+        }
+
+        reader.rewind()?;
+        std::io::copy(&mut (&mut reader).take(moov.start), &mut writer)?;
+        writer.write_all(&moov_box)?;
+        reader.seek(SeekFrom::Start(moov.start + moov.size))?;
+        std::io::copy(&mut reader, &mut writer)?;
+        Ok(true)
+    }
+
+    /// Read the first top-level movie box
+    fn read_moov<R: Read + Seek>(reader: &mut R) -> XmpResult<Option<MoovBox>> {
+        reader.rewind()?;
+        let ftyp_box = Self::read_box(reader)?;
+        if ftyp_box.box_type != *MP4_SIGNATURE {
+            return Err(XmpError::BadValue("Not a valid MP4 file".to_string()));
+        }
+        let file_end = reader.seek(SeekFrom::End(0))?;
+
+        let mut moov: Option<MoovBox> = None;
+        let mut box_start = ftyp_box.size;
+        while box_start + 8 <= file_end {
+            reader.seek(SeekFrom::Start(box_start))?;
+            let box_info = Self::read_box(reader)?;
+            let size = match box_info.size {
+                0 => file_end - box_start,
+                size => size,
+            };
+            if size < 8 || box_start + size > file_end {
+                return Err(XmpError::BadValue("Invalid MP4 box size".to_string()));
+            }
+
+            if box_info.box_type == *b"moov" && moov.is_none() {
+                let header_len = reader.stream_position()? - box_start;
+                let mut content = vec![0u8; (size - header_len) as usize];
+                reader.read_exact(&mut content)?;
+                moov = Some(MoovBox {
+                    start: box_start,
+                    size,
+                    content,
+                    media_follows: false,
+                });
+            } else if box_info.box_type == *b"mdat" {
+                if let Some(moov) = moov.as_mut() {
+                    moov.media_follows = true;
+                }
+            }
+            box_start += size;
+        }
+        Ok(moov)
+    }
+
     /// Search for udta box and XMP UUID box within a parent box
     fn search_udta_for_xmp<R: Read + Seek>(
         reader: &mut R,
@@ -1249,15 +1413,16 @@ impl Mp4Handler {
 
             // Update stco box
             if box_type == b"stco" {
-                if pos + header_size + 12 <= pos + actual_size {
+                // version/flags(4) + entry_count(4) + offsets
+                if pos + header_size + 8 <= pos + actual_size {
                     let entry_count = u32::from_be_bytes([
-                        buffer[pos + header_size + 8],
-                        buffer[pos + header_size + 9],
-                        buffer[pos + header_size + 10],
-                        buffer[pos + header_size + 11],
+                        buffer[pos + header_size + 4],
+                        buffer[pos + header_size + 5],
+                        buffer[pos + header_size + 6],
+                        buffer[pos + header_size + 7],
                     ]) as usize;
 
-                    let table_start = pos + header_size + 12;
+                    let table_start = pos + header_size + 8;
                     if table_start + entry_count * 4 <= pos + actual_size {
                         for i in 0..entry_count {
                             let offset_pos = table_start + i * 4;
@@ -1279,15 +1444,16 @@ impl Mp4Handler {
             }
             // Update co64 box
             else if box_type == b"co64" {
-                if pos + header_size + 12 <= pos + actual_size {
+                // version/flags(4) + entry_count(4) + offsets
+                if pos + header_size + 8 <= pos + actual_size {
                     let entry_count = u32::from_be_bytes([
-                        buffer[pos + header_size + 8],
-                        buffer[pos + header_size + 9],
-                        buffer[pos + header_size + 10],
-                        buffer[pos + header_size + 11],
+                        buffer[pos + header_size + 4],
+                        buffer[pos + header_size + 5],
+                        buffer[pos + header_size + 6],
+                        buffer[pos + header_size + 7],
                     ]) as usize;
 
-                    let table_start = pos + header_size + 12;
+                    let table_start = pos + header_size + 8;
                     if table_start + entry_count * 8 <= pos + actual_size {
                         for i in 0..entry_count {
                             let offset_pos = table_start + i * 8;
@@ -1402,4 +1568,99 @@ mod tests {
             panic!("Expected string value");
         }
     }
+
+    fn mp4_box(kind: &[u8], content: &[u8]) -> Vec<u8> {
+        let mut data = ((content.len() + 8) as u32).to_be_bytes().to_vec();
+        data.extend_from_slice(kind);
+        data.extend_from_slice(content);
+        data
+    }
+
+    /// MP4 file with a title item, a track (whose chunk offset points to
+    /// the media data) and media data after the movie box
+    fn create_mp4_with_track() -> Vec<u8> {
+        let ftyp = &create_minimal_mp4()[8..];
+        let mut title = 1u32.to_be_bytes().to_vec();
+        title.extend_from_slice(&[0; 4]);
+        title.extend_from_slice(b"Clip");
+        let ilst = mp4_box(b"\xA9nam", &mp4_box(b"data", &title));
+        let mut meta = vec![0; 4];
+        meta.extend(mp4_box(b"hdlr", &[0; 25]));
+        meta.extend(mp4_box(b"ilst", &ilst));
+
+        let mut mdhd = vec![0; 4];
+        mdhd.extend_from_slice(&3_786_912_000u32.to_be_bytes());
+        mdhd.extend_from_slice(&[0; 16]);
+        let build = |chunk_offset: u32| {
+            let mut stco = vec![0, 0, 0, 0, 0, 0, 0, 1];
+            stco.extend_from_slice(&chunk_offset.to_be_bytes());
+            let minf = mp4_box(b"minf", &mp4_box(b"stbl", &mp4_box(b"stco", &stco)));
+            let mut mdia = mp4_box(b"mdhd", &mdhd);
+            mdia.extend(minf);
+            let mut moov = mp4_box(b"mvhd", &[0; 100]);
+            moov.extend(mp4_box(b"trak", &mp4_box(b"mdia", &mdia)));
+            moov.extend(mp4_box(b"udta", &mp4_box(b"meta", &meta)));
+            let mut mp4 = mp4_box(b"ftyp", ftyp);
+            mp4.extend(mp4_box(b"moov", &moov));
+            mp4.extend(mp4_box(b"mdat", b"media"));
+            mp4
+        };
+        let mp4 = build(0);
+        build((mp4.len() - 5) as u32)
+    }
+
+    /// Get the first chunk offset of the first track
+    fn first_chunk_offset(mp4: &[u8]) -> usize {
+        let pos = mp4.windows(4).position(|w| w == b"stco").unwrap();
+        u32::from_be_bytes(mp4[pos + 12..pos + 16].try_into().unwrap()) as usize
+    }
+
+    #[test]
+    fn test_reconcile_quicktime() {
+        use crate::files::{SaveOptions, XmpFile};
+
+        let mp4 = create_mp4_with_track();
+        assert_eq!(&mp4[first_chunk_offset(&mp4)..], b"media");
+
+        let mut file = XmpFile::new();
+        file.from_bytes(&mp4).unwrap();
+        let meta = file.get_xmp_mut().unwrap();
+        assert_eq!(
+            meta.get_localized_text(ns::DC, "title", "", "x-default")
+                .map(|(text, _)| text),
+            Some("Clip".to_string())
+        );
+        assert_eq!(
+            meta.get_property(ns::XMP, "CreateDate")
+                .as_ref()
+                .and_then(XmpValue::as_str),
+            Some("2024-01-01T00:00:00Z")
+        );
+        meta.set_property(ns::XMP_DM, "artist", "Crew".into())
+            .unwrap();
+        meta.set_property(ns::XMP, "CreateDate", "2024-05-06T07:08:09Z".into())
+            .unwrap();
+
+        let mut output = Cursor::new(Vec::new());
+        file.write_to_writer_with(&mut output, SaveOptions::default().reconcile_quicktime())
+            .unwrap();
+        let data = output.into_inner();
+        // The chunk offsets follow the media data
+        assert_eq!(&data[first_chunk_offset(&data)..][..5], b"media");
+
+        let metadata = Mp4Handler::read_quicktime(Cursor::new(&data))
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata.text(b"\xA9nam").as_deref(), Some("Clip"));
+        assert_eq!(metadata.text(b"\xA9ART").as_deref(), Some("Crew"));
+        assert_eq!(
+            metadata.creation_time().as_deref(),
+            Some("2024-05-06T07:08:09Z")
+        );
+        let meta = Mp4Handler::read_xmp(Cursor::new(&data)).unwrap().unwrap();
+        assert_eq!(
+            meta.get_property(ns::XMP_DM, "artist"),
+            Some(XmpValue::String("Crew".to_string()))
+        );
+    }
 }
@@ -0,0 +1,645 @@
+//! Ogg file format handler
+//!
+//! This module provides functionality for reading and writing XMP metadata
+//! in Ogg Vorbis and Ogg Opus files. The implementation is pure Rust and
+//! cross-platform compatible.
+//!
+//! Ogg File Structure:
+//! - A sequence of pages: "OggS" header (with stream serial number, page
+//!   sequence number and CRC) + segment table + data
+//! - Pages carry the packets of one or more logical streams; a packet can
+//!   span several pages
+//! - Vorbis streams start with identification, comment and setup header
+//!   packets; Opus streams with "OpusHead" and "OpusTags" packets
+//!
+//! Ogg XMP Storage:
+//! - There is no Adobe convention for Ogg. Like `METADATA_BLOCK_PICTURE`,
+//!   the XMP Packet is stored as a custom field of the comment header
+//!   (Vorbis comment or "OpusTags"): `XMP=<packet>`
+//! - When the comment header is rewritten, the header pages of the first
+//!   logical stream are rebuilt and the sequence numbers (and CRCs) of its
+//!   later pages are updated. Other streams are copied unchanged.
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::files::handler::FileHandler;
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+
+/// Ogg page signature
+const PAGE_SIGNATURE: &[u8; 4] = b"OggS";
+
+/// Size of the fixed part of a page header
+const PAGE_HEADER_SIZE: usize = 27;
+
+/// Page header flags
+const FLAG_CONTINUED: u8 = 0x01;
+const FLAG_BOS: u8 = 0x02;
+
+/// Granule position of pages on which no packet ends
+const NO_GRANULE: u64 = u64::MAX;
+
+/// Maximum number of segments in a page
+const MAX_SEGMENTS: usize = 255;
+
+/// Comment field name of the XMP packet
+const XMP_FIELD: &str = "XMP";
+
+/// Ogg file handler for XMP metadata
+#[derive(Debug, Clone, Copy)]
+pub struct OggHandler;
+
+impl FileHandler for OggHandler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        let pos = reader.stream_position()?;
+        let result = read_page(reader);
+        reader.seek(SeekFrom::Start(pos))?;
+
+        let Ok(Some(page)) = result else {
+            return Ok(false);
+        };
+        Ok(page.header_type & FLAG_BOS != 0 && Codec::detect(&page.data).is_some())
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp(reader, writer, meta)
+    }
+
+    fn format_name(&self) -> &'static str {
+        "Ogg"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["ogg", "oga", "opus"]
+    }
+}
+
+/// Codec of the first logical stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Codec {
+    Vorbis,
+    Opus,
+}
+
+impl Codec {
+    /// Detect the codec from the identification header packet
+    fn detect(packet: &[u8]) -> Option<Self> {
+        if packet.starts_with(b"\x01vorbis") {
+            Some(Codec::Vorbis)
+        } else if packet.starts_with(b"OpusHead") {
+            Some(Codec::Opus)
+        } else {
+            None
+        }
+    }
+
+    /// Number of header packets, including the identification header
+    fn header_count(self) -> usize {
+        match self {
+            Codec::Vorbis => 3,
+            Codec::Opus => 2,
+        }
+    }
+
+    /// Magic bytes of the comment header packet
+    fn comment_magic(self) -> &'static [u8] {
+        match self {
+            Codec::Vorbis => b"\x03vorbis",
+            Codec::Opus => b"OpusTags",
+        }
+    }
+}
+
+/// An Ogg page
+#[derive(Debug, Clone)]
+struct OggPage {
+    header_type: u8,
+    granule: u64,
+    serial: u32,
+    sequence: u32,
+    /// Segment table (lacing values)
+    segments: Vec<u8>,
+    data: Vec<u8>,
+}
+
+impl OggPage {
+    /// Write the page, computing its CRC
+    fn write_to<W: Write>(&self, writer: &mut W) -> XmpResult<()> {
+        let mut page = Vec::with_capacity(PAGE_HEADER_SIZE + self.segments.len() + self.data.len());
+        page.extend_from_slice(PAGE_SIGNATURE);
+        page.push(0); // Version
+        page.push(self.header_type);
+        page.extend_from_slice(&self.granule.to_le_bytes());
+        page.extend_from_slice(&self.serial.to_le_bytes());
+        page.extend_from_slice(&self.sequence.to_le_bytes());
+        page.extend_from_slice(&[0; 4]); // CRC
+        page.push(self.segments.len() as u8);
+        page.extend_from_slice(&self.segments);
+        page.extend_from_slice(&self.data);
+
+        let crc = crc32(&page);
+        page[22..26].copy_from_slice(&crc.to_le_bytes());
+        writer.write_all(&page)?;
+        Ok(())
+    }
+
+    /// Split the page data into packet pieces, with whether each one ends
+    /// on this page
+    fn pieces(&self) -> Vec<(&[u8], bool)> {
+        let mut pieces = Vec::new();
+        let mut start = 0;
+        let mut len = 0;
+        for &lacing in &self.segments {
+            len += lacing as usize;
+            if lacing < 255 {
+                pieces.push((&self.data[start..start + len], true));
+                start += len;
+                len = 0;
+            }
+        }
+        if len > 0 || self.segments.last() == Some(&255) {
+            pieces.push((&self.data[start..start + len], false));
+        }
+        pieces
+    }
+}
+
+/// Read a page, returning `None` at the end of the file
+fn read_page<R: Read>(reader: &mut R) -> XmpResult<Option<OggPage>> {
+    let mut header = [0u8; PAGE_HEADER_SIZE];
+    match reader.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    if &header[0..4] != PAGE_SIGNATURE || header[4] != 0 {
+        return Err(XmpError::BadValue("Invalid Ogg page".to_string()));
+    }
+
+    let mut segments = vec![0u8; header[26] as usize];
+    reader.read_exact(&mut segments)?;
+    let data_len: usize = segments.iter().map(|&lacing| lacing as usize).sum();
+    let mut data = vec![0u8; data_len];
+    reader
+        .read_exact(&mut data)
+        .map_err(|_| XmpError::BadValue("Truncated Ogg page".to_string()))?;
+
+    let le_u32 =
+        |i: usize| u32::from_le_bytes([header[i], header[i + 1], header[i + 2], header[i + 3]]);
+    let mut granule = [0u8; 8];
+    granule.copy_from_slice(&header[6..14]);
+    Ok(Some(OggPage {
+        header_type: header[5],
+        granule: u64::from_le_bytes(granule),
+        serial: le_u32(14),
+        sequence: le_u32(18),
+        segments,
+        data,
+    }))
+}
+
+/// CRC-32 used by Ogg (polynomial 0x04C11DB7, not reflected)
+fn crc32(data: &[u8]) -> u32 {
+    data.iter().fold(0u32, |crc, &byte| {
+        let mut crc = crc ^ ((byte as u32) << 24);
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+/// The header pages of the first logical stream
+struct StreamHeaders {
+    codec: Codec,
+    serial: u32,
+    /// Header packets, including the identification header
+    packets: Vec<Vec<u8>>,
+    /// Number of pages holding the header packets
+    page_count: u32,
+    /// Pages of other streams found before the end of the headers
+    other_pages: Vec<OggPage>,
+}
+
+impl StreamHeaders {
+    /// Read pages until all header packets of the first stream are complete
+    fn read<R: Read>(reader: &mut R) -> XmpResult<Self> {
+        let first = read_page(reader)?
+            .filter(|page| page.header_type & FLAG_BOS != 0)
+            .ok_or_else(|| XmpError::BadValue("Not a valid Ogg file".to_string()))?;
+        let codec = Codec::detect(&first.data)
+            .ok_or_else(|| XmpError::NotSupported("Ogg codec".to_string()))?;
+
+        let mut headers = Self {
+            codec,
+            serial: first.serial,
+            packets: Vec::new(),
+            page_count: 0,
+            other_pages: Vec::new(),
+        };
+        let mut partial: Option<Vec<u8>> = None;
+        let mut page = Some(first);
+        while let Some(current) = page {
+            if current.serial != headers.serial {
+                headers.other_pages.push(current);
+            } else {
+                headers.page_count += 1;
+                for (piece, complete) in current.pieces() {
+                    let mut packet = partial.take().unwrap_or_default();
+                    packet.extend_from_slice(piece);
+                    if !complete {
+                        partial = Some(packet);
+                    } else if headers.packets.len() < codec.header_count() {
+                        headers.packets.push(packet);
+                    } else {
+                        return Err(XmpError::NotSupported(
+                            "Ogg audio data on a header page".to_string(),
+                        ));
+                    }
+                }
+                if headers.packets.len() == codec.header_count() {
+                    if partial.is_some() {
+                        return Err(XmpError::NotSupported(
+                            "Ogg audio data on a header page".to_string(),
+                        ));
+                    }
+                    return Ok(headers);
+                }
+            }
+            page = read_page(reader)?;
+        }
+
+        Err(XmpError::BadValue("Truncated Ogg headers".to_string()))
+    }
+
+    fn comment_packet(&self) -> XmpResult<CommentHeader> {
+        CommentHeader::parse(&self.packets[1], self.codec)
+    }
+}
+
+/// A parsed comment header packet
+struct CommentHeader {
+    vendor: Vec<u8>,
+    comments: Vec<Vec<u8>>,
+    /// Data after the comments (Vorbis framing bit, Opus binary data)
+    trailer: Vec<u8>,
+}
+
+impl CommentHeader {
+    fn parse(packet: &[u8], codec: Codec) -> XmpResult<Self> {
+        let invalid = || XmpError::BadValue("Invalid Ogg comment header".to_string());
+        let magic = codec.comment_magic();
+        if !packet.starts_with(magic) {
+            return Err(invalid());
+        }
+
+        let mut pos = magic.len();
+        let read_bytes = |pos: &mut usize| -> XmpResult<Vec<u8>> {
+            let len_bytes = packet.get(*pos..*pos + 4).ok_or_else(invalid)?;
+            let len = u32::from_le_bytes([len_bytes[0], len_bytes[1], len_bytes[2], len_bytes[3]])
+                as usize;
+            let bytes = packet
+                .get(*pos + 4..(*pos + 4).checked_add(len).ok_or_else(invalid)?)
+                .ok_or_else(invalid)?;
+            *pos += 4 + len;
+            Ok(bytes.to_vec())
+        };
+
+        let vendor = read_bytes(&mut pos)?;
+        let count_bytes = packet.get(pos..pos + 4).ok_or_else(invalid)?;
+        let count = u32::from_le_bytes([
+            count_bytes[0],
+            count_bytes[1],
+            count_bytes[2],
+            count_bytes[3],
+        ]);
+        pos += 4;
+        let mut comments = Vec::new();
+        for _ in 0..count {
+            comments.push(read_bytes(&mut pos)?);
+        }
+
+        Ok(Self {
+            vendor,
+            comments,
+            trailer: packet[pos..].to_vec(),
+        })
+    }
+
+    fn to_packet(&self, codec: Codec) -> Vec<u8> {
+        let mut packet = codec.comment_magic().to_vec();
+        packet.extend_from_slice(&(self.vendor.len() as u32).to_le_bytes());
+        packet.extend_from_slice(&self.vendor);
+        packet.extend_from_slice(&(self.comments.len() as u32).to_le_bytes());
+        for comment in &self.comments {
+            packet.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+            packet.extend_from_slice(comment);
+        }
+        packet.extend_from_slice(&self.trailer);
+        packet
+    }
+
+    /// Check whether a comment is the XMP field (names are case-insensitive)
+    fn is_xmp_comment(comment: &[u8]) -> bool {
+        comment.len() > XMP_FIELD.len()
+            && comment[..XMP_FIELD.len()].eq_ignore_ascii_case(XMP_FIELD.as_bytes())
+            && comment[XMP_FIELD.len()] == b'='
+    }
+
+    fn xmp(&self) -> Option<&[u8]> {
+        self.comments
+            .iter()
+            .find(|comment| Self::is_xmp_comment(comment))
+            .map(|comment| &comment[XMP_FIELD.len() + 1..])
+    }
+}
+
+impl OggHandler {
+    /// Read XMP metadata from an Ogg file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(XmpMeta))` if XMP metadata is found
+    /// * `Ok(None)` if no XMP metadata is found
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_xmp<R: Read + Seek>(mut reader: R) -> XmpResult<Option<XmpMeta>> {
+        reader.rewind()?;
+        let headers = StreamHeaders::read(&mut reader)?;
+        let comments = headers.comment_packet()?;
+
+        let Some(xmp_data) = comments.xmp() else {
+            return Ok(None);
+        };
+        let xmp_str = String::from_utf8(xmp_data.to_vec())
+            .map_err(|e| XmpError::ParseError(format!("Invalid UTF-8 in XMP: {}", e)))?;
+        XmpMeta::parse(&xmp_str).map(Some)
+    }
+
+    /// Write XMP metadata to an Ogg file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    pub fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        let xmp_packet = meta.serialize_packet()?;
+
+        reader.rewind()?;
+        let mut headers = StreamHeaders::read(&mut reader)?;
+        let mut comments = headers.comment_packet()?;
+        comments
+            .comments
+            .retain(|comment| !CommentHeader::is_xmp_comment(comment));
+        let mut field = format!("{}=", XMP_FIELD).into_bytes();
+        field.extend_from_slice(xmp_packet.as_bytes());
+        comments.comments.push(field);
+        headers.packets[1] = comments.to_packet(headers.codec);
+
+        // The identification header has its own page
+        let mut pages = Self::paginate(headers.serial, &headers.packets[..1], 0);
+        pages[0].header_type |= FLAG_BOS;
+        pages.extend(Self::paginate(
+            headers.serial,
+            &headers.packets[1..],
+            pages.len() as u32,
+        ));
+        let new_page_count = pages.len() as u32;
+
+        for page in headers.other_pages.iter().chain(&pages) {
+            page.write_to(&mut writer)?;
+        }
+
+        // Renumber the later pages of the stream
+        while let Some(mut page) = read_page(&mut reader)? {
+            if page.serial == headers.serial {
+                page.sequence = page
+                    .sequence
+                    .wrapping_add(new_page_count)
+                    .wrapping_sub(headers.page_count);
+            }
+            page.write_to(&mut writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Lay out packets on pages, starting with sequence number `sequence`
+    fn paginate(serial: u32, packets: &[Vec<u8>], sequence: u32) -> Vec<OggPage> {
+        let mut pages = Vec::new();
+        let mut page = OggPage {
+            header_type: 0,
+            granule: NO_GRANULE,
+            serial,
+            sequence,
+            segments: Vec::new(),
+            data: Vec::new(),
+        };
+
+        for packet in packets {
+            // A packet of N bytes needs N / 255 + 1 lacing values
+            let mut remaining = packet.as_slice();
+            let mut continued = false;
+            loop {
+                if page.segments.len() == MAX_SEGMENTS {
+                    let next = OggPage {
+                        header_type: if continued { FLAG_CONTINUED } else { 0 },
+                        granule: NO_GRANULE,
+                        serial,
+                        sequence: page.sequence + 1,
+                        segments: Vec::new(),
+                        data: Vec::new(),
+                    };
+                    pages.push(std::mem::replace(&mut page, next));
+                }
+                let len = remaining.len().min(255);
+                page.segments.push(len as u8);
+                page.data.extend_from_slice(&remaining[..len]);
+                remaining = &remaining[len..];
+                if len < 255 {
+                    // Header packets have a granule position of 0
+                    page.granule = 0;
+                    break;
+                }
+                continued = true;
+            }
+        }
+
+        // Headers end their last page
+        if !page.segments.is_empty() {
+            pages.push(page);
+        }
+        pages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+    use std::io::Cursor;
+
+    fn vorbis_comment(comments: &[&str]) -> Vec<u8> {
+        CommentHeader {
+            vendor: b"xmpkit test".to_vec(),
+            comments: comments.iter().map(|c| c.as_bytes().to_vec()).collect(),
+            trailer: vec![1],
+        }
+        .to_packet(Codec::Vorbis)
+    }
+
+    /// Build an Ogg Vorbis file: header pages, then audio pages
+    fn build_vorbis(comments: &[&str], audio_pages: u32) -> Vec<u8> {
+        let mut ident = b"\x01vorbis".to_vec();
+        ident.extend_from_slice(&[0; 23]);
+        let packets = [
+            ident,
+            vorbis_comment(comments),
+            b"\x05vorbis setup".to_vec(),
+        ];
+
+        let mut pages = OggHandler::paginate(7, &packets[..1], 0);
+        pages[0].header_type |= FLAG_BOS;
+        pages.extend(OggHandler::paginate(7, &packets[1..], 1));
+        for i in 0..audio_pages {
+            pages.push(OggPage {
+                header_type: 0,
+                granule: 1024 * (i as u64 + 1),
+                serial: 7,
+                sequence: pages.len() as u32,
+                segments: vec![3],
+                data: vec![i as u8; 3],
+            });
+        }
+
+        let mut data = Vec::new();
+        for page in &pages {
+            page.write_to(&mut data).unwrap();
+        }
+        data
+    }
+
+    fn read_pages(data: &[u8]) -> Vec<OggPage> {
+        let mut reader = Cursor::new(data);
+        let mut pages = Vec::new();
+        while let Some(page) = read_page(&mut reader).unwrap() {
+            pages.push(page);
+        }
+        pages
+    }
+
+    fn test_meta(title: &str) -> XmpMeta {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String(title.to_string()))
+            .unwrap();
+        meta
+    }
+
+    #[test]
+    fn test_crc() {
+        // Checksum of a page is stored in its header
+        let data = build_vorbis(&[], 0);
+        let stored = u32::from_le_bytes([data[22], data[23], data[24], data[25]]);
+        let mut page = data[..PAGE_HEADER_SIZE + 1 + 30].to_vec();
+        page[22..26].fill(0);
+        assert_eq!(crc32(&page), stored);
+        assert_eq!(crc32(b"123456789"), 0x89A1_897F);
+    }
+
+    #[test]
+    fn test_can_handle() {
+        let handler = OggHandler;
+        assert!(handler
+            .can_handle(&mut Cursor::new(build_vorbis(&[], 1)))
+            .unwrap());
+        assert!(!handler
+            .can_handle(&mut Cursor::new(b"OggS but not really".to_vec()))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_write_xmp_renumbers_pages() {
+        let ogg = build_vorbis(&["TITLE=Episode 1", "xmp=old"], 3);
+
+        // A large packet spans several pages
+        let mut writer = Cursor::new(Vec::new());
+        OggHandler::write_xmp(
+            Cursor::new(&ogg),
+            &mut writer,
+            &test_meta(&"Long title ".repeat(7000)),
+        )
+        .unwrap();
+        let data = writer.into_inner();
+
+        let pages = read_pages(&data);
+        assert!(pages.len() > read_pages(&ogg).len());
+        for (i, page) in pages.iter().enumerate() {
+            assert_eq!(page.sequence, i as u32);
+        }
+        assert_eq!(pages.last().unwrap().data, vec![2; 3]);
+        assert_eq!(pages.last().unwrap().granule, 3072);
+
+        let meta = OggHandler::read_xmp(Cursor::new(&data)).unwrap().unwrap();
+        assert_eq!(
+            meta.get_property(ns::DC, "title"),
+            Some(XmpValue::String("Long title ".repeat(7000)))
+        );
+
+        // Other comments are kept
+        let mut reader = Cursor::new(&data);
+        let headers = StreamHeaders::read(&mut reader).unwrap();
+        let comments = headers.comment_packet().unwrap();
+        assert_eq!(comments.comments.len(), 2);
+        assert_eq!(comments.comments[0], b"TITLE=Episode 1");
+        assert_eq!(comments.trailer, vec![1]);
+        assert_eq!(headers.packets[2], b"\x05vorbis setup");
+    }
+
+    #[test]
+    fn test_opus_tags() {
+        let mut head = b"OpusHead".to_vec();
+        head.extend_from_slice(&[1, 2, 0, 0, 0x80, 0xBB, 0, 0, 0, 0, 0]);
+        let tags = CommentHeader {
+            vendor: b"opus".to_vec(),
+            comments: Vec::new(),
+            trailer: Vec::new(),
+        }
+        .to_packet(Codec::Opus);
+        let mut pages = OggHandler::paginate(1, &[head], 0);
+        pages[0].header_type |= FLAG_BOS;
+        pages.extend(OggHandler::paginate(1, &[tags], 1));
+        let mut ogg = Vec::new();
+        for page in &pages {
+            page.write_to(&mut ogg).unwrap();
+        }
+        assert!(OggHandler::read_xmp(Cursor::new(&ogg)).unwrap().is_none());
+
+        let mut writer = Cursor::new(Vec::new());
+        OggHandler::write_xmp(Cursor::new(&ogg), &mut writer, &test_meta("Opus")).unwrap();
+        let meta = OggHandler::read_xmp(Cursor::new(writer.into_inner()))
+            .unwrap()
+            .unwrap();
+        assert!(meta.get_property(ns::DC, "title").is_some());
+    }
+}
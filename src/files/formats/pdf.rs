@@ -0,0 +1,1215 @@
+//! PDF file format handler
+//!
+//! This module provides functionality for reading and writing XMP metadata
+//! in PDF files. The implementation is pure Rust and cross-platform compatible.
+//!
+//! PDF XMP Storage:
+//! - The document XMP Packet is stored in a metadata stream referenced by the
+//!   `/Metadata` entry of the document catalog (the trailer's `/Root`)
+//! - Pages (and other objects) can have their own `/Metadata` stream
+//! - Metadata streams have `/Type /Metadata /Subtype /XML` and are usually
+//!   not compressed
+//!
+//! Writing uses an incremental update: the new metadata stream (and the
+//! catalog or page, if it had no metadata yet) are appended to the file,
+//! followed by a cross-reference section and trailer pointing back to the
+//! previous one. The original bytes are left untouched.
+//!
+//! Objects are located through the cross-reference tables, or by scanning
+//! for object headers when the file uses (compressed) cross-reference
+//! streams. Objects stored in compressed object streams and encrypted files
+//! are not supported.
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::files::handler::FileHandler;
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// PDF file signature
+const PDF_SIGNATURE: &[u8] = b"%PDF-";
+
+/// Maximum offset of the signature from the start of the file
+const SIGNATURE_SEARCH_LIMIT: usize = 1024;
+
+/// PDF file handler for XMP metadata
+#[derive(Debug, Clone, Copy)]
+pub struct PdfHandler;
+
+impl FileHandler for PdfHandler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        let pos = reader.stream_position()?;
+        let mut header = Vec::new();
+        reader
+            .by_ref()
+            .take(SIGNATURE_SEARCH_LIMIT as u64)
+            .read_to_end(&mut header)?;
+        reader.seek(SeekFrom::Start(pos))?;
+        Ok(find(&header, PDF_SIGNATURE, 0).is_some())
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn format_name(&self) -> &'static str {
+        "PDF"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["pdf"]
+    }
+}
+
+/// Which object's metadata stream to access
+#[derive(Debug, Clone, Copy)]
+enum Target {
+    /// The document catalog
+    Document,
+    /// A page, by 0-based index
+    Page(usize),
+}
+
+/// A PDF object (only what is needed to follow references)
+#[derive(Debug, Clone, PartialEq)]
+enum PdfObject {
+    Null,
+    Bool(bool),
+    /// Number, kept as written to avoid reformatting
+    Number(String),
+    Name(String),
+    String(Vec<u8>),
+    Array(Vec<PdfObject>),
+    Dict(PdfDict),
+    Ref(u32, u16),
+}
+
+impl PdfObject {
+    fn as_dict(&self) -> Option<&PdfDict> {
+        match self {
+            PdfObject::Dict(dict) => Some(dict),
+            _ => None,
+        }
+    }
+
+    fn as_ref(&self) -> Option<(u32, u16)> {
+        match self {
+            PdfObject::Ref(num, generation) => Some((*num, *generation)),
+            _ => None,
+        }
+    }
+
+    fn as_name(&self) -> Option<&str> {
+        match self {
+            PdfObject::Name(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn as_u64(&self) -> Option<u64> {
+        match self {
+            PdfObject::Number(number) => number.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Serialize the object (strings are written in hexadecimal)
+    fn write_to(&self, out: &mut Vec<u8>) {
+        match self {
+            PdfObject::Null => out.extend_from_slice(b"null"),
+            PdfObject::Bool(value) => {
+                out.extend_from_slice(if *value { b"true" } else { b"false" })
+            }
+            PdfObject::Number(number) => out.extend_from_slice(number.as_bytes()),
+            PdfObject::Name(name) => write_name(out, name),
+            PdfObject::String(bytes) => {
+                out.push(b'<');
+                for byte in bytes {
+                    out.extend_from_slice(format!("{:02X}", byte).as_bytes());
+                }
+                out.push(b'>');
+            }
+            PdfObject::Array(items) => {
+                out.push(b'[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(b' ');
+                    }
+                    item.write_to(out);
+                }
+                out.push(b']');
+            }
+            PdfObject::Dict(dict) => dict.write_to(out),
+            PdfObject::Ref(num, generation) => {
+                out.extend_from_slice(format!("{} {} R", num, generation).as_bytes())
+            }
+        }
+    }
+}
+
+/// A PDF dictionary, keeping the order of its entries
+#[derive(Debug, Clone, Default, PartialEq)]
+struct PdfDict(Vec<(String, PdfObject)>);
+
+impl PdfDict {
+    fn get(&self, key: &str) -> Option<&PdfObject> {
+        self.0.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    fn set(&mut self, key: &str, value: PdfObject) {
+        match self.0.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value,
+            None => self.0.push((key.to_string(), value)),
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.0.retain(|(k, _)| k != key);
+    }
+
+    fn write_to(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(b"<<");
+        for (key, value) in &self.0 {
+            out.push(b' ');
+            write_name(out, key);
+            out.push(b' ');
+            value.write_to(out);
+        }
+        out.extend_from_slice(b" >>");
+    }
+}
+
+fn write_name(out: &mut Vec<u8>, name: &str) {
+    out.push(b'/');
+    for &byte in name.as_bytes() {
+        if byte.is_ascii_graphic() && byte != b'#' && !is_delimiter(byte) {
+            out.push(byte);
+        } else {
+            out.extend_from_slice(format!("#{:02X}", byte).as_bytes());
+        }
+    }
+}
+
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b'\0' | b'\t' | b'\n' | b'\x0C' | b'\r' | b' ')
+}
+
+fn is_delimiter(byte: u8) -> bool {
+    matches!(
+        byte,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+/// Find `needle` in `data` from `start`
+fn find(data: &[u8], needle: &[u8], start: usize) -> Option<usize> {
+    data.get(start..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|pos| pos + start)
+}
+
+/// Find the last occurrence of `needle` in `data`
+fn rfind(data: &[u8], needle: &[u8]) -> Option<usize> {
+    data.windows(needle.len())
+        .rposition(|window| window == needle)
+}
+
+fn invalid(message: &str) -> XmpError {
+    XmpError::BadValue(format!("Invalid PDF file: {}", message))
+}
+
+/// Tokenizer and object parser over the file data
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Self { data, pos }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(byte) = self.peek() {
+            if is_whitespace(byte) {
+                self.pos += 1;
+            } else if byte == b'%' {
+                while self.peek().is_some_and(|b| b != b'\n' && b != b'\r') {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Read a run of regular characters
+    fn regular(&mut self) -> &'a [u8] {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|b| !is_whitespace(b) && !is_delimiter(b))
+        {
+            self.pos += 1;
+        }
+        self.data.get(start..self.pos).unwrap_or_default()
+    }
+
+    /// Read a keyword or number token
+    fn keyword(&mut self) -> &'a [u8] {
+        self.skip_whitespace();
+        self.regular()
+    }
+
+    fn expect_keyword(&mut self, keyword: &[u8]) -> XmpResult<()> {
+        if self.keyword() == keyword {
+            Ok(())
+        } else {
+            Err(invalid(&format!(
+                "expected '{}'",
+                String::from_utf8_lossy(keyword)
+            )))
+        }
+    }
+
+    fn integer<T: std::str::FromStr>(&mut self) -> XmpResult<T> {
+        std::str::from_utf8(self.keyword())
+            .ok()
+            .and_then(|token| token.parse().ok())
+            .ok_or_else(|| invalid("expected an integer"))
+    }
+
+    /// Parse an object (references included)
+    fn object(&mut self) -> XmpResult<PdfObject> {
+        self.object_at_depth(0)
+    }
+
+    fn object_at_depth(&mut self, depth: usize) -> XmpResult<PdfObject> {
+        if depth > 64 {
+            return Err(invalid("objects nested too deeply"));
+        }
+        self.skip_whitespace();
+        match self
+            .peek()
+            .ok_or_else(|| invalid("unexpected end of file"))?
+        {
+            b'/' => {
+                self.pos += 1;
+                Ok(PdfObject::Name(self.name()))
+            }
+            b'(' => self.literal_string().map(PdfObject::String),
+            b'<' if self.data.get(self.pos + 1) == Some(&b'<') => {
+                self.pos += 2;
+                let mut dict = PdfDict::default();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b'>') => {
+                            self.pos += 2;
+                            return Ok(PdfObject::Dict(dict));
+                        }
+                        Some(b'/') => {
+                            self.pos += 1;
+                            let key = self.name();
+                            let value = self.object_at_depth(depth + 1)?;
+                            dict.0.push((key, value));
+                        }
+                        _ => return Err(invalid("bad dictionary")),
+                    }
+                }
+            }
+            b'<' => self.hex_string().map(PdfObject::String),
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.peek() == Some(b']') {
+                        self.pos += 1;
+                        return Ok(PdfObject::Array(items));
+                    }
+                    items.push(self.object_at_depth(depth + 1)?);
+                }
+            }
+            _ => {
+                let token = self.regular();
+                match token {
+                    b"true" => Ok(PdfObject::Bool(true)),
+                    b"false" => Ok(PdfObject::Bool(false)),
+                    b"null" => Ok(PdfObject::Null),
+                    _ if !token.is_empty()
+                        && token
+                            .iter()
+                            .all(|b| b.is_ascii_digit() || matches!(b, b'+' | b'-' | b'.')) =>
+                    {
+                        let number = String::from_utf8_lossy(token).into_owned();
+                        Ok(self.reference(&number).unwrap_or(PdfObject::Number(number)))
+                    }
+                    _ => Err(invalid("unexpected token")),
+                }
+            }
+        }
+    }
+
+    /// Try to read the rest of a `num gen R` reference
+    fn reference(&mut self, number: &str) -> Option<PdfObject> {
+        let saved = self.pos;
+        let result = (|| {
+            let num = number.parse().ok()?;
+            let generation = std::str::from_utf8(self.keyword()).ok()?.parse().ok()?;
+            (self.keyword() == b"R").then_some(PdfObject::Ref(num, generation))
+        })();
+        if result.is_none() {
+            self.pos = saved;
+        }
+        result
+    }
+
+    fn name(&mut self) -> String {
+        let raw = self.regular();
+        let mut bytes = Vec::with_capacity(raw.len());
+        let mut i = 0;
+        while i < raw.len() {
+            let decoded = (raw[i] == b'#')
+                .then(|| raw.get(i + 1..i + 3))
+                .flatten()
+                .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+            match decoded {
+                Some(byte) => {
+                    bytes.push(byte);
+                    i += 3;
+                }
+                None => {
+                    bytes.push(raw[i]);
+                    i += 1;
+                }
+            }
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    fn literal_string(&mut self) -> XmpResult<Vec<u8>> {
+        self.pos += 1;
+        let mut bytes = Vec::new();
+        let mut depth = 1;
+        loop {
+            let byte = self.peek().ok_or_else(|| invalid("unterminated string"))?;
+            self.pos += 1;
+            match byte {
+                b'(' => depth += 1,
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(bytes);
+                    }
+                }
+                b'\\' => {
+                    let escaped = self.peek().ok_or_else(|| invalid("unterminated string"))?;
+                    self.pos += 1;
+                    let value = match escaped {
+                        b'n' => b'\n',
+                        b'r' => b'\r',
+                        b't' => b'\t',
+                        b'b' => 0x08,
+                        b'f' => 0x0C,
+                        b'0'..=b'7' => {
+                            let mut value = (escaped - b'0') as u32;
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(digit @ b'0'..=b'7') => {
+                                        value = value * 8 + (digit - b'0') as u32;
+                                        self.pos += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            value as u8
+                        }
+                        b'\r' => {
+                            if self.peek() == Some(b'\n') {
+                                self.pos += 1;
+                            }
+                            continue;
+                        }
+                        b'\n' => continue,
+                        other => other,
+                    };
+                    bytes.push(value);
+                    continue;
+                }
+                _ => {}
+            }
+            bytes.push(byte);
+        }
+    }
+
+    fn hex_string(&mut self) -> XmpResult<Vec<u8>> {
+        self.pos += 1;
+        let mut digits = Vec::new();
+        loop {
+            let byte = self.peek().ok_or_else(|| invalid("unterminated string"))?;
+            self.pos += 1;
+            match byte {
+                b'>' => break,
+                b if b.is_ascii_hexdigit() => digits.push(b),
+                b if is_whitespace(b) => {}
+                _ => return Err(invalid("bad hexadecimal string")),
+            }
+        }
+        if digits.len() % 2 == 1 {
+            digits.push(b'0');
+        }
+        Ok(digits
+            .chunks(2)
+            .map(|pair| {
+                u8::from_str_radix(std::str::from_utf8(pair).unwrap_or("00"), 16).unwrap_or(0)
+            })
+            .collect())
+    }
+}
+
+/// Kind of the last cross-reference section
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum XrefKind {
+    Table,
+    Stream,
+}
+
+/// Parsed document structure
+struct PdfDocument<'a> {
+    data: &'a [u8],
+    /// Offset of the last cross-reference section
+    startxref: usize,
+    xref_kind: XrefKind,
+    trailer: PdfDict,
+    /// Offsets of objects from the cross-reference tables
+    offsets: HashMap<u32, usize>,
+    /// Offsets of objects found by scanning (latest definition wins)
+    scanned: HashMap<u32, usize>,
+}
+
+impl<'a> PdfDocument<'a> {
+    fn parse(data: &'a [u8]) -> XmpResult<Self> {
+        if find(
+            &data[..data.len().min(SIGNATURE_SEARCH_LIMIT)],
+            PDF_SIGNATURE,
+            0,
+        )
+        .is_none()
+        {
+            return Err(XmpError::BadValue("Not a valid PDF file".to_string()));
+        }
+
+        let startxref_pos = rfind(data, b"startxref").ok_or_else(|| invalid("no startxref"))?;
+        let mut parser = Parser::new(data, startxref_pos + b"startxref".len());
+        let startxref: usize = parser.integer()?;
+
+        let mut document = Self {
+            data,
+            startxref,
+            xref_kind: XrefKind::Table,
+            trailer: PdfDict::default(),
+            offsets: HashMap::new(),
+            scanned: Self::scan_objects(data),
+        };
+
+        // Read the cross-reference tables, following /Prev
+        let mut visited = HashSet::new();
+        let mut offset = Some(startxref);
+        while let Some(xref) = offset.filter(|&o| o < data.len() && visited.insert(o)) {
+            let mut parser = Parser::new(data, xref);
+            let (kind, trailer) = if parser.keyword() == b"xref" {
+                (XrefKind::Table, document.read_xref_table(&mut parser)?)
+            } else {
+                let (_, object) = Parser::new(data, xref).indirect_object()?;
+                let dict = object
+                    .as_dict()
+                    .cloned()
+                    .ok_or_else(|| invalid("bad cross-reference stream"))?;
+                (XrefKind::Stream, dict)
+            };
+
+            if visited.len() == 1 {
+                document.xref_kind = kind;
+                document.trailer = trailer.clone();
+            }
+            offset = trailer
+                .get("Prev")
+                .and_then(PdfObject::as_u64)
+                .map(|prev| prev as usize);
+        }
+
+        if document.trailer.get("Encrypt").is_some() {
+            return Err(XmpError::NotSupported("Encrypted PDF files".to_string()));
+        }
+        Ok(document)
+    }
+
+    /// Read a cross-reference table (after the `xref` keyword) and its trailer
+    fn read_xref_table(&mut self, parser: &mut Parser) -> XmpResult<PdfDict> {
+        loop {
+            let token = parser.keyword();
+            if token == b"trailer" {
+                return parser
+                    .object()?
+                    .as_dict()
+                    .cloned()
+                    .ok_or_else(|| invalid("bad trailer"));
+            }
+            let first: u32 = std::str::from_utf8(token)
+                .ok()
+                .and_then(|t| t.parse().ok())
+                .ok_or_else(|| invalid("bad cross-reference table"))?;
+            let count: u32 = parser.integer()?;
+            for num in first..first.saturating_add(count) {
+                let offset: usize = parser.integer()?;
+                let _generation: u32 = parser.integer()?;
+                let in_use = parser.keyword() == b"n";
+                // Newer sections are read first; entries past the end of
+                // the file are left to the scanned objects
+                if in_use && num != 0 && offset < self.data.len() {
+                    self.offsets.entry(num).or_insert(offset);
+                }
+            }
+        }
+    }
+
+    /// Find all `num gen obj` headers in the file
+    fn scan_objects(data: &[u8]) -> HashMap<u32, usize> {
+        let mut objects = HashMap::new();
+        let mut search = 0;
+        while let Some(pos) = find(data, b"obj", search) {
+            search = pos + 3;
+            if data
+                .get(pos + 3)
+                .is_some_and(|&b| !is_whitespace(b) && !is_delimiter(b))
+            {
+                continue;
+            }
+
+            // Walk back over "num gen "
+            let mut start = pos;
+            let mut numbers = 0;
+            while numbers < 2 {
+                while start > 0 && is_whitespace(data[start - 1]) {
+                    start -= 1;
+                }
+                let end = start;
+                while start > 0 && data[start - 1].is_ascii_digit() {
+                    start -= 1;
+                }
+                if start == end {
+                    break;
+                }
+                numbers += 1;
+            }
+            if numbers < 2
+                || (start > 0 && !is_whitespace(data[start - 1]) && !is_delimiter(data[start - 1]))
+            {
+                continue;
+            }
+            if let Ok((num, _)) = Parser::new(data, start).object_header() {
+                objects.insert(num, start);
+            }
+        }
+        objects
+    }
+
+    /// Load an object by reference
+    fn load(&self, num: u32) -> XmpResult<(usize, PdfObject)> {
+        let candidates = [self.offsets.get(&num), self.scanned.get(&num)];
+        for offset in candidates.into_iter().flatten() {
+            let mut parser = Parser::new(self.data, *offset);
+            if let Ok(((found, _), object)) = parser.indirect_object() {
+                if found == num {
+                    return Ok((parser.pos, object));
+                }
+            }
+        }
+        Err(XmpError::BadValue(format!("PDF object {} not found", num)))
+    }
+
+    /// Resolve a possibly indirect object
+    fn resolve(&self, object: &PdfObject) -> XmpResult<PdfObject> {
+        match object.as_ref() {
+            Some((num, _)) => self.load(num).map(|(_, object)| object),
+            None => Ok(object.clone()),
+        }
+    }
+
+    /// Get the reference and dictionary of the target object
+    fn target(&self, target: Target) -> XmpResult<((u32, u16), PdfDict)> {
+        let root = self
+            .trailer
+            .get("Root")
+            .and_then(PdfObject::as_ref)
+            .ok_or_else(|| invalid("no document catalog"))?;
+        let catalog = self.dict(root.0)?;
+
+        match target {
+            Target::Document => Ok((root, catalog)),
+            Target::Page(index) => {
+                let pages = catalog
+                    .get("Pages")
+                    .and_then(PdfObject::as_ref)
+                    .ok_or_else(|| invalid("no page tree"))?;
+                let mut found = Vec::new();
+                self.collect_pages(pages, &mut found, &mut HashSet::new())?;
+                let page = *found.get(index).ok_or_else(|| {
+                    XmpError::BadValue(format!("PDF page {} does not exist", index))
+                })?;
+                Ok((page, self.dict(page.0)?))
+            }
+        }
+    }
+
+    fn dict(&self, num: u32) -> XmpResult<PdfDict> {
+        self.load(num)?
+            .1
+            .as_dict()
+            .cloned()
+            .ok_or_else(|| invalid("expected a dictionary"))
+    }
+
+    /// Collect the page references of a page tree node, in order
+    fn collect_pages(
+        &self,
+        node: (u32, u16),
+        pages: &mut Vec<(u32, u16)>,
+        visited: &mut HashSet<u32>,
+    ) -> XmpResult<()> {
+        if !visited.insert(node.0) {
+            return Ok(());
+        }
+        let dict = self.dict(node.0)?;
+        match dict.get("Kids") {
+            Some(kids) if dict.get("Type").and_then(PdfObject::as_name) != Some("Page") => {
+                if let PdfObject::Array(kids) = self.resolve(kids)? {
+                    for kid in kids.iter().filter_map(PdfObject::as_ref) {
+                        self.collect_pages(kid, pages, visited)?;
+                    }
+                }
+            }
+            _ => pages.push(node),
+        }
+        Ok(())
+    }
+
+    /// Read the data of a metadata stream
+    fn read_metadata(&self, num: u32) -> XmpResult<Vec<u8>> {
+        let (pos, object) = self.load(num)?;
+        let dict = object
+            .as_dict()
+            .ok_or_else(|| invalid("metadata is not a stream"))?;
+        if let Some(filter) = dict.get("Filter") {
+            if !matches!(filter, PdfObject::Array(items) if items.is_empty()) {
+                return Err(XmpError::NotSupported(
+                    "Compressed PDF metadata streams".to_string(),
+                ));
+            }
+        }
+
+        let mut parser = Parser::new(self.data, pos);
+        parser.expect_keyword(b"stream")?;
+        let mut start = parser.pos;
+        if self.data.get(start) == Some(&b'\r') {
+            start += 1;
+        }
+        if self.data.get(start) == Some(&b'\n') {
+            start += 1;
+        }
+
+        let length = dict
+            .get("Length")
+            .map(|length| self.resolve(length))
+            .transpose()?
+            .and_then(|length| length.as_u64())
+            .map(|length| length as usize);
+        let end = match length {
+            Some(length)
+                if start + length <= self.data.len()
+                    && find(&self.data[start + length..], b"endstream", 0).is_some_and(|p| {
+                        self.data[start + length..start + length + p]
+                            .iter()
+                            .all(|&b| is_whitespace(b))
+                    }) =>
+            {
+                start + length
+            }
+            // Bad length: look for the end of the stream
+            _ => find(self.data, b"endstream", start)
+                .ok_or_else(|| invalid("unterminated stream"))?,
+        };
+        Ok(self.data[start..end].to_vec())
+    }
+}
+
+impl Parser<'_> {
+    /// Parse `num gen obj`
+    fn object_header(&mut self) -> XmpResult<(u32, u16)> {
+        let num = self.integer()?;
+        let generation = self.integer()?;
+        self.expect_keyword(b"obj")?;
+        Ok((num, generation))
+    }
+
+    /// Parse `num gen obj <object>`, leaving the parser after the object
+    fn indirect_object(&mut self) -> XmpResult<((u32, u16), PdfObject)> {
+        let header = self.object_header()?;
+        Ok((header, self.object()?))
+    }
+}
+
+impl PdfHandler {
+    /// Read the document XMP metadata from a PDF file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(XmpMeta))` if XMP metadata is found
+    /// * `Ok(None)` if no XMP metadata is found
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_xmp<R: Read + Seek>(reader: R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_target_xmp(reader, Target::Document)
+    }
+
+    /// Read the XMP metadata of a page from a PDF file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    /// * `page_index` - 0-based index of the page
+    pub fn read_page_xmp<R: Read + Seek>(
+        reader: R,
+        page_index: usize,
+    ) -> XmpResult<Option<XmpMeta>> {
+        Self::read_target_xmp(reader, Target::Page(page_index))
+    }
+
+    /// Write the document XMP metadata to a PDF file using an incremental update
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    pub fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        reader: R,
+        writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_target_xmp(reader, writer, meta, Target::Document)
+    }
+
+    /// Write the XMP metadata of a page to a PDF file using an incremental update
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `page_index` - 0-based index of the page
+    /// * `meta` - The XMP metadata to write
+    pub fn write_page_xmp<R: Read + Seek, W: Write + Seek>(
+        reader: R,
+        writer: W,
+        page_index: usize,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_target_xmp(reader, writer, meta, Target::Page(page_index))
+    }
+
+    fn read_target_xmp<R: Read + Seek>(
+        mut reader: R,
+        target: Target,
+    ) -> XmpResult<Option<XmpMeta>> {
+        let mut data = Vec::new();
+        reader.rewind()?;
+        reader.read_to_end(&mut data)?;
+        let document = PdfDocument::parse(&data)?;
+
+        let (_, dict) = document.target(target)?;
+        let Some((num, _)) = dict.get("Metadata").and_then(PdfObject::as_ref) else {
+            return Ok(None);
+        };
+        let xmp_data = document.read_metadata(num)?;
+        let xmp_str = String::from_utf8(xmp_data)
+            .map_err(|e| XmpError::ParseError(format!("Invalid UTF-8 in XMP: {}", e)))?;
+        XmpMeta::parse(&xmp_str).map(Some)
+    }
+
+    fn write_target_xmp<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+        target: Target,
+    ) -> XmpResult<()> {
+        let xmp_packet = meta.serialize_packet()?;
+
+        let mut data = Vec::new();
+        reader.rewind()?;
+        reader.read_to_end(&mut data)?;
+        let document = PdfDocument::parse(&data)?;
+
+        let mut size = document
+            .trailer
+            .get("Size")
+            .and_then(PdfObject::as_u64)
+            .ok_or_else(|| invalid("no /Size in trailer"))? as u32;
+        let mut allocate = || {
+            size += 1;
+            size - 1
+        };
+
+        // Objects to append: (num, gen, serialized object)
+        let mut objects: Vec<(u32, u16, Vec<u8>)> = Vec::new();
+        let (target_ref, mut target_dict) = document.target(target)?;
+        let metadata_ref = match target_dict.get("Metadata").and_then(PdfObject::as_ref) {
+            Some(metadata_ref) => metadata_ref,
+            None => {
+                let metadata_ref = (allocate(), 0);
+                target_dict.set("Metadata", PdfObject::Ref(metadata_ref.0, metadata_ref.1));
+                let mut body = Vec::new();
+                target_dict.write_to(&mut body);
+                objects.push((target_ref.0, target_ref.1, body));
+                metadata_ref
+            }
+        };
+
+        let mut stream = format!(
+            "<< /Type /Metadata /Subtype /XML /Length {} >>\nstream\n",
+            xmp_packet.len()
+        )
+        .into_bytes();
+        stream.extend_from_slice(xmp_packet.as_bytes());
+        stream.extend_from_slice(b"\nendstream");
+        objects.insert(0, (metadata_ref.0, metadata_ref.1, stream));
+
+        // Copy the original file, then append the update
+        writer.write_all(&data)?;
+        let mut pos = data.len();
+        if !data.ends_with(b"\n") && !data.ends_with(b"\r") {
+            writer.write_all(b"\n")?;
+            pos += 1;
+        }
+
+        let mut entries = Vec::new();
+        for (num, generation, body) in &objects {
+            let header = format!("{} {} obj\n", num, generation);
+            writer.write_all(header.as_bytes())?;
+            writer.write_all(body)?;
+            writer.write_all(b"\nendobj\n")?;
+            entries.push((*num, *generation, pos));
+            pos += header.len() + body.len() + b"\nendobj\n".len();
+        }
+
+        let mut trailer = document.trailer.clone();
+        for key in [
+            "Prev",
+            "XRefStm",
+            "Type",
+            "W",
+            "Index",
+            "Length",
+            "Filter",
+            "DecodeParms",
+        ] {
+            trailer.remove(key);
+        }
+        trailer.set("Prev", PdfObject::Number(document.startxref.to_string()));
+
+        let xref_offset = pos;
+        match document.xref_kind {
+            XrefKind::Table => {
+                trailer.set("Size", PdfObject::Number(size.to_string()));
+                let mut xref = b"xref\n".to_vec();
+                entries.sort();
+                for (num, generation, offset) in &entries {
+                    xref.extend_from_slice(
+                        format!("{} 1\n{:010} {:05} n\r\n", num, offset, generation).as_bytes(),
+                    );
+                }
+                xref.extend_from_slice(b"trailer\n");
+                trailer.write_to(&mut xref);
+                writer.write_all(&xref)?;
+            }
+            XrefKind::Stream => {
+                // Uncompressed cross-reference stream, listing itself too
+                let xref_num = allocate();
+                entries.push((xref_num, 0, xref_offset));
+                entries.sort();
+                let offset_width = if xref_offset > u32::MAX as usize {
+                    8
+                } else {
+                    4
+                };
+
+                let mut rows = Vec::new();
+                let mut index = Vec::new();
+                for (num, generation, offset) in &entries {
+                    index.push(PdfObject::Number(num.to_string()));
+                    index.push(PdfObject::Number("1".to_string()));
+                    rows.push(1);
+                    rows.extend_from_slice(&(*offset as u64).to_be_bytes()[8 - offset_width..]);
+                    rows.extend_from_slice(&generation.to_be_bytes());
+                }
+
+                trailer.set("Type", PdfObject::Name("XRef".to_string()));
+                trailer.set("Size", PdfObject::Number(size.to_string()));
+                trailer.set("Index", PdfObject::Array(index));
+                trailer.set(
+                    "W",
+                    PdfObject::Array(
+                        [1, offset_width, 2]
+                            .iter()
+                            .map(|w| PdfObject::Number(w.to_string()))
+                            .collect(),
+                    ),
+                );
+                trailer.set("Length", PdfObject::Number(rows.len().to_string()));
+
+                let mut xref = format!("{} 0 obj\n", xref_num).into_bytes();
+                trailer.write_to(&mut xref);
+                xref.extend_from_slice(b"\nstream\n");
+                xref.extend_from_slice(&rows);
+                xref.extend_from_slice(b"\nendstream\nendobj\n");
+                writer.write_all(&xref)?;
+            }
+        }
+        writer.write_all(format!("\nstartxref\n{}\n%%EOF\n", xref_offset).as_bytes())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+    use std::io::Cursor;
+
+    /// Build a PDF file from object bodies (numbered from 1), with a
+    /// cross-reference table or an uncompressed cross-reference stream
+    fn build_pdf(objects: &[&str], xref_stream: bool) -> Vec<u8> {
+        let mut data = b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, body) in objects.iter().enumerate() {
+            offsets.push(data.len());
+            data.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, body).as_bytes());
+        }
+
+        let xref_offset = data.len();
+        let size = objects.len() + 1;
+        if xref_stream {
+            let mut rows = vec![0, 0, 0, 0, 0, 0xFF, 0xFF];
+            for offset in offsets.iter().chain([&xref_offset]) {
+                rows.push(1);
+                rows.extend_from_slice(&(*offset as u32).to_be_bytes());
+                rows.extend_from_slice(&[0, 0]);
+            }
+            data.extend_from_slice(
+                format!(
+                    "{} 0 obj\n<< /Type /XRef /Size {} /W [1 4 2] /Root 1 0 R /Length {} >>\nstream\n",
+                    size,
+                    size + 1,
+                    rows.len()
+                )
+                .as_bytes(),
+            );
+            data.extend_from_slice(&rows);
+            data.extend_from_slice(b"\nendstream\nendobj\n");
+        } else {
+            data.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f\r\n", size).as_bytes());
+            for offset in &offsets {
+                data.extend_from_slice(format!("{:010} 00000 n\r\n", offset).as_bytes());
+            }
+            data.extend_from_slice(
+                format!(
+                    "trailer\n<< /Size {} /Root 1 0 R /ID [<0102> <0304>] >>\n",
+                    size
+                )
+                .as_bytes(),
+            );
+        }
+        data.extend_from_slice(format!("startxref\n{}\n%%EOF\n", xref_offset).as_bytes());
+        data
+    }
+
+    fn minimal_pdf(xref_stream: bool) -> Vec<u8> {
+        build_pdf(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R >>",
+                "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>",
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] >>",
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] >>",
+            ],
+            xref_stream,
+        )
+    }
+
+    fn test_meta(title: &str) -> XmpMeta {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String(title.to_string()))
+            .unwrap();
+        meta
+    }
+
+    fn title(meta: Option<XmpMeta>) -> Option<XmpValue> {
+        meta.unwrap().get_property(ns::DC, "title")
+    }
+
+    #[test]
+    fn test_can_handle() {
+        assert!(PdfHandler
+            .can_handle(&mut Cursor::new(minimal_pdf(false)))
+            .unwrap());
+        assert!(!PdfHandler
+            .can_handle(&mut Cursor::new(b"not a pdf".to_vec()))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_read_xmp_no_xmp() {
+        let result = PdfHandler::read_xmp(Cursor::new(minimal_pdf(false))).unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_invalid_pdf() {
+        let result = PdfHandler::read_xmp(Cursor::new(b"%PDF-1.4\ngarbage".to_vec()));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_xref_offset_past_eof() {
+        let mut writer = Cursor::new(Vec::new());
+        PdfHandler::write_xmp(
+            Cursor::new(minimal_pdf(false)),
+            &mut writer,
+            &test_meta("Title"),
+        )
+        .unwrap();
+        let mut data = writer.into_inner();
+
+        // Point every cross-reference entry past the end of the file
+        let entry = b" 00000 n\r\n";
+        let mut search = 0;
+        while let Some(pos) = find(&data, entry, search) {
+            data[pos - 10..pos].copy_from_slice(b"9999999999");
+            search = pos + entry.len();
+        }
+
+        // The objects are found by scanning instead
+        assert_eq!(
+            title(PdfHandler::read_xmp(Cursor::new(&data)).unwrap()),
+            Some(XmpValue::String("Title".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_write_xmp_incremental() {
+        for xref_stream in [false, true] {
+            let original = minimal_pdf(xref_stream);
+            let mut writer = Cursor::new(Vec::new());
+            PdfHandler::write_xmp(Cursor::new(&original), &mut writer, &test_meta("First"))
+                .unwrap();
+            let first = writer.into_inner();
+
+            // The original bytes are untouched
+            assert_eq!(&first[..original.len()], original.as_slice());
+            assert_eq!(
+                title(PdfHandler::read_xmp(Cursor::new(&first)).unwrap()),
+                Some(XmpValue::String("First".to_string()))
+            );
+
+            // A second update replaces the metadata stream object
+            let mut writer = Cursor::new(Vec::new());
+            PdfHandler::write_xmp(Cursor::new(&first), &mut writer, &test_meta("Second")).unwrap();
+            let second = writer.into_inner();
+            assert_eq!(
+                title(PdfHandler::read_xmp(Cursor::new(&second)).unwrap()),
+                Some(XmpValue::String("Second".to_string()))
+            );
+
+            let document = PdfDocument::parse(&second).unwrap();
+            let (_, catalog) = document.target(Target::Document).unwrap();
+            // The original cross-reference stream is object 5
+            let metadata_num = if xref_stream { 6 } else { 5 };
+            assert_eq!(
+                catalog.get("Metadata"),
+                Some(&PdfObject::Ref(metadata_num, 0))
+            );
+            assert_eq!(catalog.get("Pages"), Some(&PdfObject::Ref(2, 0)));
+        }
+    }
+
+    #[test]
+    fn test_page_xmp() {
+        let original = minimal_pdf(false);
+        assert!(PdfHandler::read_page_xmp(Cursor::new(&original), 1)
+            .unwrap()
+            .is_none());
+        assert!(PdfHandler::read_page_xmp(Cursor::new(&original), 2).is_err());
+
+        let mut writer = Cursor::new(Vec::new());
+        PdfHandler::write_page_xmp(Cursor::new(&original), &mut writer, 1, &test_meta("Page 2"))
+            .unwrap();
+        let data = writer.into_inner();
+
+        assert_eq!(
+            title(PdfHandler::read_page_xmp(Cursor::new(&data), 1).unwrap()),
+            Some(XmpValue::String("Page 2".to_string()))
+        );
+        assert!(PdfHandler::read_page_xmp(Cursor::new(&data), 0)
+            .unwrap()
+            .is_none());
+        assert!(PdfHandler::read_xmp(Cursor::new(&data)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_parse_objects() {
+        let data =
+            b"<< /Name /A#20B /Str (a\\(b\\)\\101) /Hex <48 69> /Arr [1 -2.5 3 0 R true null] >>";
+        let object = Parser::new(data, 0).object().unwrap();
+        let dict = object.as_dict().unwrap();
+        assert_eq!(dict.get("Name"), Some(&PdfObject::Name("A B".to_string())));
+        assert_eq!(dict.get("Str"), Some(&PdfObject::String(b"a(b)A".to_vec())));
+        assert_eq!(dict.get("Hex"), Some(&PdfObject::String(b"Hi".to_vec())));
+        assert_eq!(
+            dict.get("Arr"),
+            Some(&PdfObject::Array(vec![
+                PdfObject::Number("1".to_string()),
+                PdfObject::Number("-2.5".to_string()),
+                PdfObject::Ref(3, 0),
+                PdfObject::Bool(true),
+                PdfObject::Null,
+            ]))
+        );
+
+        // Serialized objects parse back to the same value
+        let mut out = Vec::new();
+        object.write_to(&mut out);
+        assert_eq!(Parser::new(&out, 0).object().unwrap(), object);
+    }
+}
@@ -7,10 +7,23 @@
 //! - XMP Packet is stored in iTXt chunk with keyword "XML:com.adobe.xmp"
 //! - iTXt chunk format: keyword (null-terminated) + compression flag + compression method + language tag + translated keyword + text
 //! - For XMP, compression flag is 0 (uncompressed)
+//!
+//! Legacy Metadata:
+//! - The Title, Author, Description, Copyright and Creation Time text
+//!   chunks (tEXt, or uncompressed iTXt) are mapped to `dc:title`,
+//!   `dc:creator`, `dc:description`, `dc:rights` and `xmp:CreateDate`
+//! - A chunk is only imported when the XMP has no corresponding property
+//! - When saving, the mapped chunks are rewritten from the XMP; chunks
+//!   without an XMP property are kept
 
 use crate::core::error::{XmpError, XmpResult};
 use crate::core::metadata::XmpMeta;
+use crate::core::namespace::ns;
+use crate::files::file::SaveOptions;
+use crate::files::format::FormatCapabilities;
 use crate::files::handler::FileHandler;
+use crate::types::value::XmpValue;
+use crate::utils::datetime::XmpDateTime;
 use std::io::{Read, Seek, Write};
 
 /// PNG file signature
@@ -22,9 +35,35 @@ const XMP_KEYWORD: &[u8] = b"XML:com.adobe.xmp\0";
 /// PNG chunk type for iTXt
 const CHUNK_TYPE_ITXT: &[u8] = b"iTXt";
 
+/// PNG chunk type for tEXt
+const CHUNK_TYPE_TEXT: &[u8] = b"tEXt";
+
 /// PNG chunk type for IEND (end of file)
 const CHUNK_TYPE_IEND: &[u8] = b"IEND";
 
+/// Chunk types of the native metadata: text, EXIF and modification time
+const METADATA_CHUNK_TYPES: &[&[u8]] = &[b"tEXt", b"zTXt", b"iTXt", b"eXIf", b"tIME"];
+
+/// How a text chunk is converted to XMP
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Conversion {
+    /// Text, as the `x-default` item of a language alternative
+    LangAlt,
+    /// Text, as the only item of an ordered array
+    Seq,
+    /// ISO 8601 or RFC 1123 date, as a date
+    Date,
+}
+
+/// Mapping of text chunk keywords to XMP properties
+const TEXT_MAPPINGS: &[(&str, &str, &str, Conversion)] = &[
+    ("Title", ns::DC, "title", Conversion::LangAlt),
+    ("Author", ns::DC, "creator", Conversion::Seq),
+    ("Description", ns::DC, "description", Conversion::LangAlt),
+    ("Copyright", ns::DC, "rights", Conversion::LangAlt),
+    ("Creation Time", ns::XMP, "CreateDate", Conversion::Date),
+];
+
 /// PNG file handler for XMP metadata
 #[derive(Debug, Clone, Copy)]
 pub struct PngHandler;
@@ -50,6 +89,47 @@ impl FileHandler for PngHandler {
         Self::write_xmp(reader, writer, meta)
     }
 
+    fn import_legacy<R: Read + Seek>(&self, reader: &mut R, meta: &mut XmpMeta) -> XmpResult<bool> {
+        // Unreadable chunks are ignored, like missing chunks
+        match Self::read_text(reader) {
+            Ok(texts) => Self::import_text(&texts, meta),
+            Err(_) => Ok(false),
+        }
+    }
+
+    fn export_legacy<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+        options: &SaveOptions,
+    ) -> XmpResult<bool> {
+        if !options.reconcile_png_text {
+            return Ok(false);
+        }
+        Self::write_text(reader, writer, meta)
+    }
+
+    fn remove_metadata<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        all: bool,
+    ) -> XmpResult<()> {
+        Self::remove_metadata(reader, writer, all)
+    }
+
+    fn capabilities(&self) -> FormatCapabilities {
+        FormatCapabilities {
+            can_read: true,
+            can_write: true,
+            can_inject: true,
+            supports_in_place: self.supports_in_place_update(),
+            supports_reconciliation: true,
+            max_packet_size: None,
+        }
+    }
+
     fn format_name(&self) -> &'static str {
         "PNG"
     }
@@ -198,6 +278,241 @@ impl PngHandler {
         Ok(())
     }
 
+    /// Read the text chunks (tEXt and uncompressed iTXt) of a PNG file
+    ///
+    /// The XMP chunk and compressed chunks are skipped.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<(String, String)>)` - the keyword and text of each chunk,
+    ///   in file order
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_text<R: Read + Seek>(mut reader: R) -> XmpResult<Vec<(String, String)>> {
+        reader.rewind()?;
+        let mut signature = [0u8; 8];
+        reader.read_exact(&mut signature)?;
+        if signature != PNG_SIGNATURE {
+            return Err(XmpError::BadValue("Not a valid PNG file".to_string()));
+        }
+
+        let mut texts = Vec::new();
+        loop {
+            let chunk = Self::read_chunk(&mut reader)?;
+            if chunk.chunk_type == *CHUNK_TYPE_IEND {
+                break;
+            }
+            if let Some(text) = Self::parse_text_chunk(&chunk) {
+                texts.push(text);
+            }
+        }
+        Ok(texts)
+    }
+
+    /// Write XMP values to the text chunks of a PNG file
+    ///
+    /// See [`SaveOptions::reconcile_png_text`] for the exported values.
+    /// Updated chunks replace the first chunk with their keyword; new
+    /// chunks are added before IEND.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` if a copy of the file with updated chunks was written
+    /// * `Ok(false)` if the chunks are up to date (nothing is written)
+    /// * `Err(XmpError)` if an error occurs
+    pub fn write_text<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<bool> {
+        let texts = Self::read_text(&mut reader)?;
+        let mut updates: Vec<(&str, String, bool)> = Vec::new();
+        for &(keyword, namespace, name, conversion) in TEXT_MAPPINGS {
+            let value = match conversion {
+                Conversion::LangAlt => meta
+                    .get_localized_text(namespace, name, "", "x-default")
+                    .map(|(text, _)| text),
+                Conversion::Seq => {
+                    let items: Vec<String> = (0..meta.count_array_items(namespace, name))
+                        .filter_map(|index| meta.get_array_item(namespace, name, index))
+                        .filter_map(|value| value.as_str().map(str::to_string))
+                        .collect();
+                    Some(items.join("; "))
+                }
+                Conversion::Date => match meta.get_property(namespace, name) {
+                    Some(XmpValue::String(s)) => Some(s),
+                    Some(XmpValue::DateTime(dt)) => Some(dt.format()),
+                    _ => None,
+                },
+            };
+            let Some(value) = value.filter(|value| !value.is_empty()) else {
+                continue;
+            };
+            let current = texts.iter().find(|(k, _)| k == keyword).map(|(_, t)| t);
+            if current != Some(&value) {
+                updates.push((keyword, value, false));
+            }
+        }
+        if updates.is_empty() {
+            return Ok(false);
+        }
+
+        reader.rewind()?;
+        let mut signature = [0u8; 8];
+        reader.read_exact(&mut signature)?;
+        writer.write_all(&signature)?;
+        loop {
+            let chunk = Self::read_chunk(&mut reader)?;
+            if chunk.chunk_type == *CHUNK_TYPE_IEND {
+                for (keyword, text, written) in updates.iter_mut().filter(|u| !u.2) {
+                    Self::write_text_chunk(&mut writer, keyword, text)?;
+                    *written = true;
+                }
+            } else if let Some((keyword, _)) = Self::parse_text_chunk(&chunk) {
+                if let Some((_, text, written)) = updates.iter_mut().find(|(k, _, _)| *k == keyword)
+                {
+                    // Replace the first chunk with the keyword, drop the others
+                    if !*written {
+                        Self::write_text_chunk(&mut writer, &keyword, text)?;
+                        *written = true;
+                    }
+                    continue;
+                }
+            }
+
+            writer.write_all(&chunk.length.to_be_bytes())?;
+            writer.write_all(&chunk.chunk_type)?;
+            writer.write_all(&chunk.data)?;
+            writer.write_all(&chunk.crc.to_be_bytes())?;
+            if chunk.chunk_type == *CHUNK_TYPE_IEND {
+                break;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Remove the metadata chunks of a PNG file
+    ///
+    /// The XMP iTXt chunk is removed. With `all`, the text chunks (tEXt,
+    /// zTXt and iTXt), the EXIF chunk and the modification time are removed
+    /// too. Chunks that affect how the image looks, such as ICC profiles,
+    /// are kept.
+    pub fn remove_metadata<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        all: bool,
+    ) -> XmpResult<()> {
+        reader.rewind()?;
+        let mut signature = [0u8; 8];
+        reader.read_exact(&mut signature)?;
+        if signature != PNG_SIGNATURE {
+            return Err(XmpError::BadValue("Not a valid PNG file".to_string()));
+        }
+        writer.write_all(&signature)?;
+        loop {
+            let chunk = Self::read_chunk(&mut reader)?;
+            let remove = if all {
+                METADATA_CHUNK_TYPES.contains(&&chunk.chunk_type[..])
+            } else {
+                chunk.chunk_type == *CHUNK_TYPE_ITXT && Self::is_xmp_itxt(&chunk.data)
+            };
+            if remove {
+                continue;
+            }
+
+            writer.write_all(&chunk.length.to_be_bytes())?;
+            writer.write_all(&chunk.chunk_type)?;
+            writer.write_all(&chunk.data)?;
+            writer.write_all(&chunk.crc.to_be_bytes())?;
+            if chunk.chunk_type == *CHUNK_TYPE_IEND {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Import text chunks into XMP
+    ///
+    /// A chunk is only imported when the XMP has no corresponding property.
+    fn import_text(texts: &[(String, String)], meta: &mut XmpMeta) -> XmpResult<bool> {
+        let mut imported = false;
+        for &(keyword, namespace, name, conversion) in TEXT_MAPPINGS {
+            if meta.does_property_exist(namespace, name) {
+                continue;
+            }
+            let Some(text) = texts
+                .iter()
+                .find(|(k, text)| k == keyword && !text.is_empty())
+                .map(|(_, text)| text.as_str())
+            else {
+                continue;
+            };
+            match conversion {
+                Conversion::LangAlt => {
+                    meta.set_localized_text(namespace, name, "", "x-default", text)?;
+                }
+                Conversion::Seq => meta.append_array_item(namespace, name, text.into())?,
+                Conversion::Date => {
+                    let Some(date) = parse_png_date(text).and_then(|date| date.parse().ok()) else {
+                        continue;
+                    };
+                    meta.set_property(namespace, name, XmpValue::DateTime(date))?;
+                }
+            }
+            imported = true;
+        }
+        Ok(imported)
+    }
+
+    /// Get the keyword and text of a tEXt or uncompressed iTXt chunk
+    ///
+    /// Returns `None` for other chunks, including the XMP chunk.
+    fn parse_text_chunk(chunk: &PngChunk) -> Option<(String, String)> {
+        let (keyword, rest) = chunk
+            .data
+            .split_at(chunk.data.iter().position(|&b| b == 0)?);
+        let rest = &rest[1..];
+        let keyword = latin1(keyword);
+        if chunk.chunk_type == *CHUNK_TYPE_TEXT {
+            return Some((keyword, latin1(rest)));
+        }
+        if chunk.chunk_type != *CHUNK_TYPE_ITXT || Self::is_xmp_itxt(&chunk.data) {
+            return None;
+        }
+
+        // Compression flag and method, language tag, translated keyword
+        if *rest.first()? != 0 {
+            return None;
+        }
+        let mut text = rest.get(2..)?;
+        for _ in 0..2 {
+            text = &text[text.iter().position(|&b| b == 0)? + 1..];
+        }
+        Some((keyword, String::from_utf8(text.to_vec()).ok()?))
+    }
+
+    /// Write a text chunk: tEXt if the text is Latin-1, otherwise
+    /// uncompressed iTXt
+    fn write_text_chunk<W: Write>(writer: &mut W, keyword: &str, text: &str) -> XmpResult<()> {
+        let mut chunk_data = keyword.as_bytes().to_vec();
+        chunk_data.push(0);
+        let chunk_type = if text.chars().all(|c| c != '\0' && (c as u32) < 0x100) {
+            chunk_data.extend(text.chars().map(|c| c as u8));
+            CHUNK_TYPE_TEXT
+        } else {
+            // Uncompressed, no language tag or translated keyword
+            chunk_data.extend_from_slice(&[0, 0, 0, 0]);
+            chunk_data.extend_from_slice(text.as_bytes());
+            CHUNK_TYPE_ITXT
+        };
+
+        let crc = Self::calculate_crc(&[chunk_type, &chunk_data].concat());
+        writer.write_all(&(chunk_data.len() as u32).to_be_bytes())?;
+        writer.write_all(chunk_type)?;
+        writer.write_all(&chunk_data)?;
+        writer.write_all(&crc.to_be_bytes())?;
+        Ok(())
+    }
+
     /// Read a PNG chunk
     fn read_chunk<R: Read>(reader: &mut R) -> XmpResult<PngChunk> {
         // Read chunk length (4 bytes, big-endian)
@@ -346,6 +661,50 @@ impl PngHandler {
     }
 }
 
+/// Decode Latin-1 text
+fn latin1(data: &[u8]) -> String {
+    data.iter().map(|&b| b as char).collect()
+}
+
+/// Parse a Creation Time as an XMP date
+///
+/// PNG suggests RFC 1123 dates ("Sat, 06 May 2024 07:08:09 +0200"); XMP
+/// (ISO 8601) dates are also accepted.
+fn parse_png_date(text: &str) -> Option<String> {
+    let text = text.trim();
+    if XmpDateTime::parse(text).is_ok_and(|dt| dt.has_date) {
+        return Some(text.to_string());
+    }
+
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    // The day of the week is optional
+    let text = text.split_once(',').map_or(text, |(_, rest)| rest);
+    let parts: Vec<&str> = text.split_whitespace().collect();
+    let (day, month, year, time) = (parts.first()?, parts.get(1)?, parts.get(2)?, parts.get(3));
+    let month = MONTHS.iter().position(|m| m.eq_ignore_ascii_case(month))? + 1;
+    let day: u8 = day.parse().ok()?;
+    let year: u16 = year.parse().ok()?;
+    let mut date = format!("{:04}-{:02}-{:02}", year, month, day);
+    if let Some(time) = time {
+        let time = match time.len() {
+            5 => format!("{}:00", time),
+            _ => time.to_string(),
+        };
+        let zone = match parts.get(4).copied() {
+            None | Some("GMT") | Some("UT") | Some("UTC") | Some("Z") => "Z".to_string(),
+            Some(zone) if zone.is_ascii() && zone.len() == 5 && zone.starts_with(['+', '-']) => {
+                format!("{}:{}", &zone[..3], &zone[3..])
+            }
+            Some(_) => return None,
+        };
+        date = format!("{}T{}{}", date, time, zone);
+    }
+    XmpDateTime::parse(&date).ok()?.validate().ok()?;
+    Some(date)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -459,4 +818,141 @@ mod tests {
         // Just verify it doesn't panic and returns a value
         assert!(crc != 0 || data.is_empty());
     }
+
+    fn text_chunk(chunk_type: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(chunk_type);
+        chunk.extend_from_slice(data);
+        let crc = PngHandler::calculate_crc(&[chunk_type, data].concat());
+        chunk.extend_from_slice(&crc.to_be_bytes());
+        chunk
+    }
+
+    /// Minimal PNG with a tEXt title, an iTXt author and an RFC 1123
+    /// creation time, before IEND
+    fn create_png_with_text() -> Vec<u8> {
+        let mut png = create_minimal_png();
+        let iend = png.split_off(png.len() - 12);
+        png.extend(text_chunk(CHUNK_TYPE_TEXT, b"Title\0Caf\xE9"));
+        png.extend(text_chunk(
+            CHUNK_TYPE_ITXT,
+            "Author\0\0\0en\0\0Ωmega".as_bytes(),
+        ));
+        png.extend(text_chunk(
+            CHUNK_TYPE_TEXT,
+            b"Creation Time\0Mon, 06 May 2024 07:08:09 +0200",
+        ));
+        png.extend(iend);
+        png
+    }
+
+    #[test]
+    fn test_read_text() {
+        let texts = PngHandler::read_text(Cursor::new(create_png_with_text())).unwrap();
+        assert_eq!(
+            texts,
+            vec![
+                ("Title".to_string(), "Café".to_string()),
+                ("Author".to_string(), "Ωmega".to_string()),
+                (
+                    "Creation Time".to_string(),
+                    "Mon, 06 May 2024 07:08:09 +0200".to_string()
+                ),
+            ]
+        );
+
+        let mut meta = XmpMeta::new();
+        meta.set_localized_text(ns::DC, "title", "", "x-default", "Edited")
+            .unwrap();
+        assert!(PngHandler::import_text(&texts, &mut meta).unwrap());
+        assert_eq!(
+            meta.get_localized_text(ns::DC, "title", "", "x-default")
+                .map(|(text, _)| text),
+            Some("Edited".to_string())
+        );
+        assert_eq!(
+            meta.get_array_item(ns::DC, "creator", 0),
+            Some(XmpValue::String("Ωmega".to_string()))
+        );
+        assert_eq!(
+            meta.get_property(ns::XMP, "CreateDate")
+                .as_ref()
+                .and_then(XmpValue::as_str),
+            Some("2024-05-06T07:08:09+02:00")
+        );
+    }
+
+    #[test]
+    fn test_remove_metadata() {
+        let png = create_png_with_text();
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "format", "image/png".into())
+            .unwrap();
+        let mut writer = Cursor::new(Vec::new());
+        PngHandler::write_xmp(Cursor::new(&png), &mut writer, &meta).unwrap();
+        let with_xmp = writer.into_inner();
+
+        let mut writer = Cursor::new(Vec::new());
+        PngHandler::remove_metadata(Cursor::new(&with_xmp), &mut writer, false).unwrap();
+        assert_eq!(writer.into_inner(), png);
+
+        let mut writer = Cursor::new(Vec::new());
+        PngHandler::remove_metadata(Cursor::new(&with_xmp), &mut writer, true).unwrap();
+        assert_eq!(writer.into_inner(), create_minimal_png());
+    }
+
+    #[test]
+    fn test_parse_png_date() {
+        assert_eq!(
+            parse_png_date("6 May 2024 07:08 GMT").as_deref(),
+            Some("2024-05-06T07:08:00Z")
+        );
+        assert_eq!(parse_png_date("06 May 2024").as_deref(), Some("2024-05-06"));
+        assert_eq!(
+            parse_png_date("2024-05-06T07:08:09Z").as_deref(),
+            Some("2024-05-06T07:08:09Z")
+        );
+        assert_eq!(parse_png_date("Yesterday"), None);
+        assert_eq!(parse_png_date("06 Foo 2024"), None);
+        assert_eq!(parse_png_date("6 May 2024 07:08 +0\u{fa}0"), None);
+    }
+
+    #[test]
+    fn test_reconcile_png_text() {
+        use crate::files::{SaveOptions, XmpFile};
+
+        let mut file = XmpFile::new();
+        file.from_bytes(&create_png_with_text()).unwrap();
+        let meta = file.get_xmp_mut().unwrap();
+        meta.set_localized_text(ns::DC, "title", "", "x-default", "Ωmega title")
+            .unwrap();
+        meta.set_localized_text(ns::DC, "rights", "", "x-default", "CC0")
+            .unwrap();
+
+        let mut output = Cursor::new(Vec::new());
+        file.write_to_writer_with(&mut output, SaveOptions::default().reconcile_png_text())
+            .unwrap();
+        let data = output.into_inner();
+
+        let texts = PngHandler::read_text(Cursor::new(&data)).unwrap();
+        assert_eq!(texts[0], ("Title".to_string(), "Ωmega title".to_string()));
+        assert_eq!(texts[1], ("Author".to_string(), "Ωmega".to_string()));
+        assert_eq!(
+            texts[2],
+            (
+                "Creation Time".to_string(),
+                "2024-05-06T07:08:09+02:00".to_string()
+            )
+        );
+        assert_eq!(texts[3], ("Copyright".to_string(), "CC0".to_string()));
+        assert!(data.ends_with(&create_minimal_png()[33..]));
+
+        // The XMP is still written
+        let meta = PngHandler::read_xmp(Cursor::new(&data)).unwrap().unwrap();
+        assert_eq!(
+            meta.get_localized_text(ns::DC, "rights", "", "x-default")
+                .map(|(text, _)| text),
+            Some("CC0".to_string())
+        );
+    }
 }
@@ -0,0 +1,373 @@
+//! PSD file format handler
+//!
+//! This module provides functionality for reading and writing XMP metadata
+//! in Photoshop PSD and PSB files. The implementation is pure Rust and
+//! cross-platform compatible.
+//!
+//! PSD File Structure:
+//! - File header (26 bytes): `8BPS` signature, version (1 = PSD, 2 = PSB)
+//! - Color mode data section (4-byte length + data)
+//! - Image resources section (4-byte length + resource blocks)
+//! - Layer and mask information section
+//! - Image data section
+//!
+//! Image resource block:
+//! - Signature (4 bytes, usually `8BIM`)
+//! - Resource ID (2 bytes)
+//! - Name (Pascal string, padded to an even length)
+//! - Data size (4 bytes) and data (padded to an even length)
+//!
+//! PSD XMP Storage:
+//! - XMP is stored in the image resource with ID 1060 (0x0424)
+//! - Other resources and sections are preserved byte-for-byte
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::files::handler::FileHandler;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// PSD file signature
+const PSD_SIGNATURE: &[u8; 4] = b"8BPS";
+
+/// Size of the file header
+const HEADER_SIZE: usize = 26;
+
+/// Photoshop image resource signature
+const RESOURCE_SIGNATURE: &[u8; 4] = b"8BIM";
+
+/// Image resource ID for XMP metadata
+const XMP_RESOURCE_ID: u16 = 0x0424;
+
+/// PSD file handler for XMP metadata
+#[derive(Debug, Clone, Copy)]
+pub struct PsdHandler;
+
+impl FileHandler for PsdHandler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        let pos = reader.stream_position()?;
+        let mut header = [0u8; 6];
+        let result = reader.read_exact(&mut header);
+        reader.seek(SeekFrom::Start(pos))?;
+        if result.is_err() {
+            return Ok(false);
+        }
+
+        let version = u16::from_be_bytes([header[4], header[5]]);
+        Ok(&header[..4] == PSD_SIGNATURE && (version == 1 || version == 2))
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn format_name(&self) -> &'static str {
+        "PSD"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["psd", "psb"]
+    }
+}
+
+/// A resource block from the image resources section
+#[derive(Debug, Clone)]
+struct ResourceBlock {
+    /// Resource ID
+    id: u16,
+    /// Offset of the resource data within the section
+    data_offset: usize,
+    /// Size of the resource data (without padding)
+    data_size: usize,
+    /// The complete block as stored in the file (including padding)
+    raw: Vec<u8>,
+}
+
+/// The sections of a PSD file, split around the image resources
+struct PsdLayout {
+    /// Header and color mode data section
+    prefix: Vec<u8>,
+    /// Contents of the image resources section (without its length)
+    resources: Vec<u8>,
+}
+
+impl PsdHandler {
+    /// Read XMP metadata from a PSD or PSB file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(XmpMeta))` if XMP metadata is found
+    /// * `Ok(None)` if no XMP metadata is found
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_xmp<R: Read + Seek>(mut reader: R) -> XmpResult<Option<XmpMeta>> {
+        let layout = Self::read_layout(&mut reader)?;
+        let blocks = Self::parse_resources(&layout.resources)?;
+
+        let Some(block) = blocks.iter().find(|block| block.id == XMP_RESOURCE_ID) else {
+            return Ok(None);
+        };
+        let xmp_data = &layout.resources[block.data_offset..block.data_offset + block.data_size];
+        // Some writers pad the packet with trailing NUL bytes, which
+        // parse_bytes ignores
+        if xmp_data.iter().all(|&b| b == 0) {
+            return Ok(None);
+        }
+        XmpMeta::parse_bytes(xmp_data).map(Some)
+    }
+
+    /// Write XMP metadata to a PSD or PSB file
+    ///
+    /// The XMP resource is replaced in place, or appended to the image
+    /// resources if the file has none. All other data is copied unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    pub fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        let xmp_packet = meta.serialize_packet()?;
+        let layout = Self::read_layout(&mut reader)?;
+        let blocks = Self::parse_resources(&layout.resources)?;
+
+        let xmp_block = Self::create_xmp_block(xmp_packet.as_bytes())?;
+        let mut resources = Vec::with_capacity(layout.resources.len() + xmp_block.len());
+        let mut written = false;
+        for block in &blocks {
+            if block.id == XMP_RESOURCE_ID {
+                if !written {
+                    resources.extend_from_slice(&xmp_block);
+                    written = true;
+                }
+            } else {
+                resources.extend_from_slice(&block.raw);
+            }
+        }
+        if !written {
+            resources.extend_from_slice(&xmp_block);
+        }
+
+        let resources_len = u32::try_from(resources.len())
+            .map_err(|_| XmpError::BadValue("PSD image resources too large".to_string()))?;
+        writer.write_all(&layout.prefix)?;
+        writer.write_all(&resources_len.to_be_bytes())?;
+        writer.write_all(&resources)?;
+
+        // Copy the layer and mask information and image data sections
+        std::io::copy(&mut reader, &mut writer)?;
+
+        Ok(())
+    }
+
+    /// Read the header, color mode data and image resources sections
+    ///
+    /// Leaves the reader at the start of the layer and mask information section.
+    fn read_layout<R: Read + Seek>(reader: &mut R) -> XmpResult<PsdLayout> {
+        reader.rewind()?;
+        let mut prefix = vec![0u8; HEADER_SIZE];
+        reader
+            .read_exact(&mut prefix)
+            .map_err(|_| XmpError::BadValue("Invalid PSD file: truncated header".to_string()))?;
+
+        let version = u16::from_be_bytes([prefix[4], prefix[5]]);
+        if &prefix[..4] != PSD_SIGNATURE || !(version == 1 || version == 2) {
+            return Err(XmpError::BadValue("Not a valid PSD file".to_string()));
+        }
+
+        let color_mode = Self::read_section(reader)?;
+        prefix.extend_from_slice(&(color_mode.len() as u32).to_be_bytes());
+        prefix.extend_from_slice(&color_mode);
+
+        let resources = Self::read_section(reader)?;
+        Ok(PsdLayout { prefix, resources })
+    }
+
+    /// Read a section with a 4-byte length prefix
+    fn read_section<R: Read>(reader: &mut R) -> XmpResult<Vec<u8>> {
+        let mut len_bytes = [0u8; 4];
+        reader.read_exact(&mut len_bytes)?;
+        let len = u32::from_be_bytes(len_bytes) as u64;
+
+        let mut data = Vec::new();
+        reader.take(len).read_to_end(&mut data)?;
+        if data.len() as u64 != len {
+            return Err(XmpError::BadValue(
+                "Invalid PSD file: truncated section".to_string(),
+            ));
+        }
+        Ok(data)
+    }
+
+    /// Parse the resource blocks of the image resources section
+    fn parse_resources(data: &[u8]) -> XmpResult<Vec<ResourceBlock>> {
+        let invalid = || XmpError::BadValue("Invalid PSD image resource block".to_string());
+        let mut blocks = Vec::new();
+        let mut pos = 0;
+
+        while pos < data.len() {
+            let start = pos;
+            // Signature (8BIM, or another Photoshop tag) and ID
+            if data.len() - pos < 7 {
+                return Err(invalid());
+            }
+            let id = u16::from_be_bytes([data[pos + 4], data[pos + 5]]);
+            pos += 6;
+
+            // Pascal string name, padded to an even total length
+            let name_len = data[pos] as usize + 1;
+            pos += name_len + (name_len % 2);
+
+            let size_bytes = data.get(pos..pos + 4).ok_or_else(invalid)?;
+            let data_size =
+                u32::from_be_bytes([size_bytes[0], size_bytes[1], size_bytes[2], size_bytes[3]])
+                    as usize;
+            pos += 4;
+            let data_offset = pos;
+            if data_size > data.len() - pos {
+                return Err(invalid());
+            }
+            pos = (pos + data_size + (data_size % 2)).min(data.len());
+
+            blocks.push(ResourceBlock {
+                id,
+                data_offset,
+                data_size,
+                raw: data[start..pos].to_vec(),
+            });
+        }
+
+        Ok(blocks)
+    }
+
+    /// Create an XMP resource block
+    fn create_xmp_block(xmp: &[u8]) -> XmpResult<Vec<u8>> {
+        let size = u32::try_from(xmp.len())
+            .map_err(|_| XmpError::BadValue("XMP packet too large".to_string()))?;
+
+        let mut block = Vec::with_capacity(12 + xmp.len() + 1);
+        block.extend_from_slice(RESOURCE_SIGNATURE);
+        block.extend_from_slice(&XMP_RESOURCE_ID.to_be_bytes());
+        // Empty name, padded to 2 bytes
+        block.extend_from_slice(&[0, 0]);
+        block.extend_from_slice(&size.to_be_bytes());
+        block.extend_from_slice(xmp);
+        if xmp.len() % 2 == 1 {
+            block.push(0);
+        }
+        Ok(block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+    use std::io::Cursor;
+
+    /// Create a minimal PSD (or PSB) file with the given resource blocks
+    fn create_minimal_psd(version: u16, resources: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(PSD_SIGNATURE);
+        data.extend_from_slice(&version.to_be_bytes());
+        data.extend_from_slice(&[0; 6]); // Reserved
+        data.extend_from_slice(&3u16.to_be_bytes()); // Channels
+        data.extend_from_slice(&1u32.to_be_bytes()); // Height
+        data.extend_from_slice(&1u32.to_be_bytes()); // Width
+        data.extend_from_slice(&8u16.to_be_bytes()); // Depth
+        data.extend_from_slice(&3u16.to_be_bytes()); // RGB mode
+        data.extend_from_slice(&0u32.to_be_bytes()); // Color mode data
+        data.extend_from_slice(&(resources.len() as u32).to_be_bytes());
+        data.extend_from_slice(resources);
+        data.extend_from_slice(&[0, 0, 0, 0]); // Layer and mask information
+        data.extend_from_slice(&[0, 0, 0xFF, 0xFF, 0xFF]); // Image data
+        data
+    }
+
+    /// A resource block with a name
+    fn named_block() -> Vec<u8> {
+        let mut block = b"8BIM\x03\xED".to_vec();
+        block.extend_from_slice(b"\x03abc");
+        block.extend_from_slice(&3u32.to_be_bytes());
+        block.extend_from_slice(b"xyz\0");
+        block
+    }
+
+    #[test]
+    fn test_can_handle() {
+        let handler = PsdHandler;
+        let psd = create_minimal_psd(1, &[]);
+        assert!(handler.can_handle(&mut Cursor::new(psd)).unwrap());
+        let psb = create_minimal_psd(2, &[]);
+        assert!(handler.can_handle(&mut Cursor::new(psb)).unwrap());
+        let other = create_minimal_psd(3, &[]);
+        assert!(!handler.can_handle(&mut Cursor::new(other)).unwrap());
+        assert!(!handler.can_handle(&mut Cursor::new(b"8BP")).unwrap());
+    }
+
+    #[test]
+    fn test_read_xmp_no_xmp() {
+        let psd = create_minimal_psd(1, &named_block());
+        assert!(PsdHandler::read_xmp(Cursor::new(psd)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_write_xmp_preserves_resources() {
+        let resources = named_block();
+        let psd = create_minimal_psd(2, &resources);
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String("First".to_string()))
+            .unwrap();
+        let mut writer = Cursor::new(Vec::new());
+        PsdHandler::write_xmp(Cursor::new(&psd), &mut writer, &meta).unwrap();
+        let first = writer.into_inner();
+
+        meta.set_property(ns::DC, "title", XmpValue::String("Second".to_string()))
+            .unwrap();
+        let mut writer = Cursor::new(Vec::new());
+        PsdHandler::write_xmp(Cursor::new(&first), &mut writer, &meta).unwrap();
+        let second = writer.into_inner();
+
+        let read = PsdHandler::read_xmp(Cursor::new(&second)).unwrap().unwrap();
+        assert_eq!(
+            read.get_property(ns::DC, "title"),
+            Some(XmpValue::String("Second".to_string()))
+        );
+
+        // Other resources and sections are unchanged, with a single XMP block
+        let layout = PsdHandler::read_layout(&mut Cursor::new(&second)).unwrap();
+        let blocks = PsdHandler::parse_resources(&layout.resources).unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].raw, resources);
+        assert_eq!(blocks[1].id, XMP_RESOURCE_ID);
+        assert_eq!(&second[..HEADER_SIZE], &psd[..HEADER_SIZE]);
+        assert!(second.ends_with(&[0, 0, 0, 0, 0, 0, 0xFF, 0xFF, 0xFF]));
+    }
+
+    #[test]
+    fn test_invalid_resources() {
+        let psd = create_minimal_psd(1, b"8BIM\x04\x24\0\0\0\0\x01\0");
+        assert!(PsdHandler::read_xmp(Cursor::new(psd)).is_err());
+    }
+}
@@ -0,0 +1,676 @@
+//! QuickTime and iTunes metadata
+//!
+//! This module reads and writes the iTunes metadata items of MP4 and M4A
+//! files and the creation time of their movie and media headers, and
+//! reconciles them with XMP. The implementation is pure Rust and
+//! cross-platform compatible.
+//!
+//! Metadata Structure:
+//! - Items are stored in `moov/udta/meta/ilst`; `meta` is a full box
+//!   (version and flags, 4 bytes) holding a handler box (`hdlr`, handler
+//!   type `mdir`) and the item list
+//! - Each item is a box named after its key (`©nam`, `©ART`, `©day`,
+//!   `keyw`, ...) holding a `data` box: type indicator (4 bytes, 1 for
+//!   UTF-8 text) + locale (4 bytes) + value
+//! - The movie header (`moov/mvhd`) and media headers
+//!   (`moov/trak/mdia/mdhd`) start with version and flags (4 bytes), then
+//!   the creation time in seconds since 1904-01-01 UTC (4 bytes in version
+//!   0, 8 bytes in version 1)
+//!
+//! XMP Reconciliation:
+//! - The title (`©nam`), artist (`©ART`), date (`©day`) and keywords
+//!   (`keyw`, comma-separated) items are mapped to `dc:title`,
+//!   `xmpDM:artist`, `xmp:CreateDate` and `dc:subject`
+//! - The creation time of the first media header (or of the movie header)
+//!   is mapped to `xmp:CreateDate`, and takes precedence over `©day`
+//! - A value is only imported when the XMP has no corresponding property
+//! - When saving, the mapped items and the creation times are rewritten
+//!   from the XMP; items without an XMP property are kept
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::core::namespace::ns;
+use crate::core::node::ArrayType;
+use crate::types::value::XmpValue;
+use crate::utils::datetime::XmpDateTime;
+
+/// Item keys
+const ITEM_TITLE: [u8; 4] = *b"\xA9nam";
+const ITEM_ARTIST: [u8; 4] = *b"\xA9ART";
+const ITEM_DATE: [u8; 4] = *b"\xA9day";
+const ITEM_KEYWORDS: [u8; 4] = *b"keyw";
+
+/// Type indicators of `data` boxes
+const DATA_TYPE_UTF8: u32 = 1;
+const DATA_TYPE_UTF16: u32 = 2;
+
+/// Seconds between 1904-01-01 (the QuickTime epoch) and 1970-01-01
+const EPOCH_OFFSET: i64 = 2_082_844_800;
+
+/// How an item is converted to XMP
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Conversion {
+    /// Text, as a simple property
+    Text,
+    /// Text, as the `x-default` item of a language alternative
+    LangAlt,
+    /// Comma-separated text, as an unordered array
+    Bag,
+}
+
+/// Mapping of items to XMP properties
+const MAPPINGS: &[([u8; 4], &str, &str, Conversion)] = &[
+    (ITEM_TITLE, ns::DC, "title", Conversion::LangAlt),
+    (ITEM_ARTIST, ns::XMP_DM, "artist", Conversion::Text),
+    (ITEM_DATE, ns::XMP, "CreateDate", Conversion::Text),
+    (ITEM_KEYWORDS, ns::DC, "subject", Conversion::Bag),
+];
+
+/// An iTunes metadata item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuickTimeItem {
+    /// The item key (`©nam`, `keyw`, ...)
+    pub key: [u8; 4],
+    /// The raw item content (its `data` boxes)
+    pub data: Vec<u8>,
+}
+
+impl QuickTimeItem {
+    /// Create a UTF-8 text item
+    fn text(key: [u8; 4], text: &str) -> Self {
+        let mut payload = DATA_TYPE_UTF8.to_be_bytes().to_vec();
+        payload.extend_from_slice(&[0; 4]);
+        payload.extend_from_slice(text.as_bytes());
+        Self {
+            key,
+            data: encode_box(b"data", &payload),
+        }
+    }
+
+    /// Get the value of the first text `data` box
+    pub fn as_text(&self) -> Option<String> {
+        let boxes = child_boxes(&self.data).ok()?;
+        let (_, payload, _) = boxes.iter().find(|(kind, _, _)| kind == b"data")?;
+        let data_type = u32::from_be_bytes(payload.get(..4)?.try_into().ok()?);
+        let value = payload.get(8..)?;
+        match data_type {
+            DATA_TYPE_UTF8 => String::from_utf8(value.to_vec()).ok(),
+            DATA_TYPE_UTF16 => {
+                let units: Vec<u16> = value
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                    .collect();
+                String::from_utf16(&units).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The QuickTime metadata of an MP4 file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuickTimeMetadata {
+    items: Vec<QuickTimeItem>,
+    creation_time: Option<u64>,
+    creation_time_updated: bool,
+}
+
+impl QuickTimeMetadata {
+    /// Parse the metadata of a movie box
+    ///
+    /// # Arguments
+    ///
+    /// * `moov` - The content of the `moov` box, without its header
+    pub fn parse(moov: &[u8]) -> XmpResult<Self> {
+        let mut metadata = Self::default();
+        let mut movie_time = None;
+        for (kind, payload, _) in child_boxes(moov)? {
+            match &kind {
+                b"mvhd" => movie_time = header_time(payload),
+                b"trak" if metadata.creation_time.is_none() => {
+                    metadata.creation_time =
+                        find_box(payload, &[b"mdia", b"mdhd"])?.and_then(header_time);
+                }
+                b"udta" => {
+                    if let Some(meta) = find_box(payload, &[b"meta"])? {
+                        if let Some(ilst) = find_box(&meta[meta_header_len(meta)..], &[b"ilst"])? {
+                            for (key, data, _) in child_boxes(ilst)? {
+                                metadata.items.push(QuickTimeItem {
+                                    key,
+                                    data: data.to_vec(),
+                                });
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        metadata.creation_time = metadata.creation_time.or(movie_time);
+        Ok(metadata)
+    }
+
+    /// Get the metadata items, in file order
+    pub fn items(&self) -> &[QuickTimeItem] {
+        &self.items
+    }
+
+    /// Get the first item with a key
+    pub fn item(&self, key: &[u8; 4]) -> Option<&QuickTimeItem> {
+        self.items.iter().find(|item| item.key == *key)
+    }
+
+    /// Get the text of an item
+    pub fn text(&self, key: &[u8; 4]) -> Option<String> {
+        self.item(key).and_then(QuickTimeItem::as_text)
+    }
+
+    /// Get the creation time of the media (or movie) header, as an XMP
+    /// date in UTC
+    ///
+    /// Returns `None` if the headers have no creation time (0).
+    pub fn creation_time(&self) -> Option<String> {
+        self.creation_time.map(format_time)
+    }
+
+    /// Import the items and the creation time into XMP
+    ///
+    /// Items are mapped to `dc:title`, `dc:subject`, `xmp:CreateDate` and
+    /// `xmpDM:artist`. A value is only imported when the XMP has no
+    /// corresponding property.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` if any property was imported
+    /// * `Ok(false)` otherwise
+    /// * `Err(XmpError)` if an error occurs
+    pub fn import_into(&self, meta: &mut XmpMeta) -> XmpResult<bool> {
+        let mut imported = false;
+        if let Some(time) = self.creation_time().and_then(|time| time.parse().ok()) {
+            if !meta.does_property_exist(ns::XMP, "CreateDate") {
+                meta.set_property(ns::XMP, "CreateDate", XmpValue::DateTime(time))?;
+                imported = true;
+            }
+        }
+        for &(key, namespace, name, conversion) in MAPPINGS {
+            if meta.does_property_exist(namespace, name) {
+                continue;
+            }
+            let Some(value) = self.text(&key).filter(|value| !value.is_empty()) else {
+                continue;
+            };
+            match conversion {
+                Conversion::Text => meta.set_property(namespace, name, value.into())?,
+                Conversion::LangAlt => {
+                    meta.set_localized_text(namespace, name, "", "x-default", &value)?;
+                }
+                Conversion::Bag => {
+                    for keyword in split_keywords(&value) {
+                        meta.append_array_item_with(
+                            namespace,
+                            name,
+                            ArrayType::Unordered,
+                            keyword.into(),
+                        )?;
+                    }
+                }
+            }
+            imported = true;
+        }
+        Ok(imported)
+    }
+
+    /// Update the mapped items and the creation time from XMP
+    ///
+    /// Returns `true` if anything was changed.
+    pub(crate) fn update_from(&mut self, meta: &XmpMeta) -> bool {
+        let mut updated = false;
+        for &(key, namespace, name, conversion) in MAPPINGS {
+            let value = match conversion {
+                Conversion::LangAlt => meta
+                    .get_localized_text(namespace, name, "", "x-default")
+                    .map(|(text, _)| text),
+                Conversion::Bag => {
+                    let keywords: Vec<String> = (0..meta.count_array_items(namespace, name))
+                        .filter_map(|index| meta.get_array_item(namespace, name, index))
+                        .filter_map(|value| value.as_str().map(str::to_string))
+                        .collect();
+                    Some(keywords.join(", "))
+                }
+                Conversion::Text => match meta.get_property(namespace, name) {
+                    Some(XmpValue::String(s)) => Some(s),
+                    Some(XmpValue::DateTime(dt)) => Some(dt.format()),
+                    _ => None,
+                },
+            };
+            let Some(value) = value.filter(|value| !value.is_empty()) else {
+                continue;
+            };
+            let current = self.text(&key);
+            let unchanged = match conversion {
+                Conversion::Bag => current
+                    .as_deref()
+                    .is_some_and(|current| split_keywords(current) == split_keywords(&value)),
+                _ => current.as_deref() == Some(value.as_str()),
+            };
+            if !unchanged {
+                self.set_item(QuickTimeItem::text(key, &value));
+                updated = true;
+            }
+        }
+
+        let time = match meta.get_property(ns::XMP, "CreateDate") {
+            Some(XmpValue::String(s)) => parse_time(&s),
+            Some(XmpValue::DateTime(dt)) => parse_time(&dt.format()),
+            _ => None,
+        };
+        if time.is_some() && time != self.creation_time {
+            self.creation_time = time;
+            self.creation_time_updated = true;
+            updated = true;
+        }
+        updated
+    }
+
+    /// Write the items and the creation time to a movie box
+    ///
+    /// Returns the new content of the `moov` box. The item list, and the
+    /// `udta` and `meta` boxes holding it, are added if needed; other boxes
+    /// are kept.
+    pub(crate) fn write_moov(&self, moov: &[u8]) -> XmpResult<Vec<u8>> {
+        let time = self.creation_time.filter(|_| self.creation_time_updated);
+        let mut content = Vec::new();
+        let mut has_udta = false;
+        for (kind, payload, whole) in child_boxes(moov)? {
+            match (&kind, time) {
+                (b"mvhd", Some(time)) => {
+                    content.extend(encode_box(&kind, &set_header_time(payload, time)));
+                }
+                (b"trak", Some(time)) => {
+                    let trak = map_boxes(payload, b"mdia", |mdia| {
+                        map_boxes(mdia, b"mdhd", |mdhd| Ok(set_header_time(mdhd, time)))
+                    })?;
+                    content.extend(encode_box(&kind, &trak));
+                }
+                (b"udta", _) => {
+                    has_udta = true;
+                    content.extend(encode_box(&kind, &self.write_udta(payload)?));
+                }
+                _ => content.extend_from_slice(whole),
+            }
+        }
+        if !has_udta && !self.items.is_empty() {
+            content.extend(encode_box(b"udta", &self.write_udta(&[])?));
+        }
+        Ok(content)
+    }
+
+    /// Write the item list to the content of a `udta` box
+    fn write_udta(&self, udta: &[u8]) -> XmpResult<Vec<u8>> {
+        let mut has_meta = false;
+        let mut content = map_boxes(udta, b"meta", |meta| {
+            has_meta = true;
+            let header_len = meta_header_len(meta);
+            let mut has_ilst = false;
+            let mut content = meta[..header_len].to_vec();
+            content.extend(map_boxes(&meta[header_len..], b"ilst", |_| {
+                has_ilst = true;
+                Ok(self.write_ilst())
+            })?);
+            if !has_ilst {
+                content.extend(encode_box(b"ilst", &self.write_ilst()));
+            }
+            Ok(content)
+        })?;
+        if !has_meta && !self.items.is_empty() {
+            let mut meta = vec![0; 4];
+            // Handler: version and flags, pre-defined, type, reserved, name
+            let mut hdlr = vec![0; 8];
+            hdlr.extend_from_slice(b"mdirappl");
+            hdlr.extend_from_slice(&[0; 9]);
+            meta.extend(encode_box(b"hdlr", &hdlr));
+            meta.extend(encode_box(b"ilst", &self.write_ilst()));
+            content.extend(encode_box(b"meta", &meta));
+        }
+        Ok(content)
+    }
+
+    /// Encode the content of the `ilst` box
+    fn write_ilst(&self) -> Vec<u8> {
+        self.items
+            .iter()
+            .flat_map(|item| encode_box(&item.key, &item.data))
+            .collect()
+    }
+
+    /// Replace the items with a key by a new item
+    fn set_item(&mut self, item: QuickTimeItem) {
+        let key = item.key;
+        match self.items.iter().position(|i| i.key == key) {
+            Some(index) => {
+                self.items[index] = item;
+                let mut seen = false;
+                self.items.retain(|i| {
+                    let duplicate = i.key == key && seen;
+                    seen |= i.key == key;
+                    !duplicate
+                });
+            }
+            None => self.items.push(item),
+        }
+    }
+}
+
+/// A child box: type, content and whole box
+type ChildBox<'a> = ([u8; 4], &'a [u8], &'a [u8]);
+
+/// Split box content into its child boxes
+fn child_boxes(mut data: &[u8]) -> XmpResult<Vec<ChildBox<'_>>> {
+    let mut boxes = Vec::new();
+    while data.len() >= 8 {
+        let size = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as u64;
+        let kind = [data[4], data[5], data[6], data[7]];
+        let (size, header_len) = match size {
+            0 => (data.len() as u64, 8),
+            1 => {
+                let ext = data
+                    .get(8..16)
+                    .ok_or_else(|| XmpError::BadValue("Truncated MP4 box".to_string()))?;
+                (u64::from_be_bytes(ext.try_into().unwrap()), 16)
+            }
+            size => (size, 8),
+        };
+        if size < header_len || size > data.len() as u64 {
+            return Err(XmpError::BadValue("Invalid MP4 box size".to_string()));
+        }
+        let (whole, rest) = data.split_at(size as usize);
+        boxes.push((kind, &whole[header_len as usize..], whole));
+        data = rest;
+    }
+    Ok(boxes)
+}
+
+/// Find the content of a box by its path of types
+fn find_box<'a>(data: &'a [u8], path: &[&[u8; 4]]) -> XmpResult<Option<&'a [u8]>> {
+    let Some((first, rest)) = path.split_first() else {
+        return Ok(Some(data));
+    };
+    for (kind, payload, _) in child_boxes(data)? {
+        if kind == **first {
+            return find_box(payload, rest);
+        }
+    }
+    Ok(None)
+}
+
+/// Rewrite the content of the child boxes with a type, keeping other boxes
+fn map_boxes(
+    data: &[u8],
+    kind: &[u8; 4],
+    mut f: impl FnMut(&[u8]) -> XmpResult<Vec<u8>>,
+) -> XmpResult<Vec<u8>> {
+    let mut content = Vec::new();
+    for (child, payload, whole) in child_boxes(data)? {
+        if child == *kind {
+            content.extend(encode_box(&child, &f(payload)?));
+        } else {
+            content.extend_from_slice(whole);
+        }
+    }
+    Ok(content)
+}
+
+/// Encode a box, with an extended size if needed
+fn encode_box(kind: &[u8; 4], content: &[u8]) -> Vec<u8> {
+    let size = 8 + content.len() as u64;
+    let mut data = Vec::with_capacity(size as usize + 8);
+    if size <= u32::MAX as u64 {
+        data.extend_from_slice(&(size as u32).to_be_bytes());
+        data.extend_from_slice(kind);
+    } else {
+        data.extend_from_slice(&1u32.to_be_bytes());
+        data.extend_from_slice(kind);
+        data.extend_from_slice(&(size + 8).to_be_bytes());
+    }
+    data.extend_from_slice(content);
+    data
+}
+
+/// Get the length of the version and flags of a `meta` box
+///
+/// `meta` is a full box in MP4 files, but not in QuickTime files, where
+/// the handler box comes first.
+fn meta_header_len(meta: &[u8]) -> usize {
+    if meta.get(4..8) == Some(b"hdlr") {
+        0
+    } else {
+        4.min(meta.len())
+    }
+}
+
+/// Get the creation time of a movie or media header, if set
+fn header_time(header: &[u8]) -> Option<u64> {
+    let time = match header.first()? {
+        0 => u32::from_be_bytes(header.get(4..8)?.try_into().ok()?) as u64,
+        1 => u64::from_be_bytes(header.get(4..12)?.try_into().ok()?),
+        _ => return None,
+    };
+    (time != 0).then_some(time)
+}
+
+/// Set the creation time of a movie or media header
+///
+/// Headers are unchanged if the time doesn't fit their version.
+fn set_header_time(header: &[u8], time: u64) -> Vec<u8> {
+    let mut header = header.to_vec();
+    match header.first() {
+        Some(0) if header.len() >= 8 => {
+            if let Ok(time) = u32::try_from(time) {
+                header[4..8].copy_from_slice(&time.to_be_bytes());
+            }
+        }
+        Some(1) if header.len() >= 12 => header[4..12].copy_from_slice(&time.to_be_bytes()),
+        _ => {}
+    }
+    header
+}
+
+/// Split comma-separated keywords
+fn split_keywords(value: &str) -> Vec<&str> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|keyword| !keyword.is_empty())
+        .collect()
+}
+
+/// Format seconds since 1904 as an XMP date in UTC
+fn format_time(time: u64) -> String {
+    let seconds = time as i64 - EPOCH_OFFSET;
+    let (days, secs) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+
+    // Civil date from days since 1970-01-01 (proleptic Gregorian calendar)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// Parse an XMP date as seconds since 1904
+///
+/// Returns `None` for dates without a day, or before 1904. Dates without
+/// a time zone are taken as UTC.
+fn parse_time(date: &str) -> Option<u64> {
+    let dt = XmpDateTime::parse(date).ok()?;
+    if !dt.has_date || dt.day == 0 {
+        return None;
+    }
+
+    let (month, day) = (dt.month as i64, dt.day as i64);
+    let year = dt.year as i64 - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    let offset = match dt.tz_sign {
+        0 => 0,
+        sign => sign as i64 * (dt.tz_hour as i64 * 3600 + dt.tz_minute as i64 * 60),
+    };
+    let seconds = days * 86400 + dt.hour as i64 * 3600 + dt.minute as i64 * 60 + dt.second as i64
+        - offset
+        + EPOCH_OFFSET;
+    u64::try_from(seconds).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Media header (version 0) with a creation time
+    fn mdhd(time: u32) -> Vec<u8> {
+        let mut content = vec![0; 4];
+        content.extend_from_slice(&time.to_be_bytes());
+        content.extend_from_slice(&[0; 16]);
+        encode_box(b"mdhd", &content)
+    }
+
+    /// Movie box content with a track and an item list
+    fn build_moov() -> Vec<u8> {
+        let mut ilst = QuickTimeItem::text(ITEM_TITLE, "Clip").data;
+        ilst = encode_box(&ITEM_TITLE, &ilst);
+        ilst.extend(encode_box(
+            &ITEM_KEYWORDS,
+            &QuickTimeItem::text(ITEM_KEYWORDS, "beach, sunset").data,
+        ));
+        ilst.extend(encode_box(
+            b"cpil",
+            &encode_box(b"data", &[0, 0, 0, 21, 0, 0, 0, 0, 1]),
+        ));
+        let mut meta = vec![0; 4];
+        meta.extend(encode_box(b"hdlr", &[0; 25]));
+        meta.extend(encode_box(b"ilst", &ilst));
+
+        let mut moov = encode_box(b"mvhd", &[0; 100]);
+        moov.extend(encode_box(
+            b"trak",
+            &encode_box(b"mdia", &mdhd(3_786_912_000)),
+        ));
+        moov.extend(encode_box(b"udta", &encode_box(b"meta", &meta)));
+        moov
+    }
+
+    #[test]
+    fn test_parse() {
+        let metadata = QuickTimeMetadata::parse(&build_moov()).unwrap();
+        assert_eq!(metadata.items().len(), 3);
+        assert_eq!(metadata.text(&ITEM_TITLE).as_deref(), Some("Clip"));
+        assert_eq!(
+            metadata.text(&ITEM_KEYWORDS).as_deref(),
+            Some("beach, sunset")
+        );
+        assert_eq!(metadata.text(b"cpil"), None);
+        assert_eq!(
+            metadata.creation_time().as_deref(),
+            Some("2024-01-01T00:00:00Z")
+        );
+
+        assert!(QuickTimeMetadata::parse(b"\0\0\0\x20mvhd").is_err());
+    }
+
+    #[test]
+    fn test_import_into() {
+        let metadata = QuickTimeMetadata::parse(&build_moov()).unwrap();
+        let mut meta = XmpMeta::new();
+        assert!(metadata.import_into(&mut meta).unwrap());
+        assert_eq!(
+            meta.get_localized_text(ns::DC, "title", "", "x-default")
+                .map(|(text, _)| text),
+            Some("Clip".to_string())
+        );
+        assert_eq!(meta.count_array_items(ns::DC, "subject"), 2);
+        assert_eq!(
+            meta.get_array_item(ns::DC, "subject", 1),
+            Some(XmpValue::String("sunset".to_string()))
+        );
+        assert_eq!(
+            meta.get_property(ns::XMP, "CreateDate")
+                .as_ref()
+                .and_then(XmpValue::as_str),
+            Some("2024-01-01T00:00:00Z")
+        );
+        assert!(!metadata.import_into(&mut meta).unwrap());
+    }
+
+    #[test]
+    fn test_update_from() {
+        let moov = build_moov();
+        let mut metadata = QuickTimeMetadata::parse(&moov).unwrap();
+        let mut meta = XmpMeta::new();
+        metadata.import_into(&mut meta).unwrap();
+        // The date item is added, then the items are up to date
+        assert!(metadata.update_from(&meta));
+        assert!(!metadata.update_from(&meta));
+
+        meta.set_property(ns::XMP_DM, "artist", "Crew".into())
+            .unwrap();
+        meta.set_property(ns::XMP, "CreateDate", "2024-05-06T09:08:09+02:00".into())
+            .unwrap();
+        assert!(metadata.update_from(&meta));
+
+        let updated = QuickTimeMetadata::parse(&metadata.write_moov(&moov).unwrap()).unwrap();
+        assert_eq!(updated.text(&ITEM_ARTIST).as_deref(), Some("Crew"));
+        assert_eq!(
+            updated.text(&ITEM_DATE).as_deref(),
+            Some("2024-05-06T09:08:09+02:00")
+        );
+        assert_eq!(
+            updated.creation_time().as_deref(),
+            Some("2024-05-06T07:08:09Z")
+        );
+        // Other items are kept
+        assert_eq!(updated.text(&ITEM_TITLE).as_deref(), Some("Clip"));
+        assert!(updated.item(b"cpil").is_some());
+
+        // The item list is added to movies without one
+        let moov = encode_box(b"mvhd", &[0; 100]);
+        let mut metadata = QuickTimeMetadata::parse(&moov).unwrap();
+        assert!(metadata.update_from(&meta));
+        let updated = QuickTimeMetadata::parse(&metadata.write_moov(&moov).unwrap()).unwrap();
+        assert_eq!(updated.text(&ITEM_TITLE).as_deref(), Some("Clip"));
+        assert_eq!(
+            updated.creation_time().as_deref(),
+            Some("2024-05-06T07:08:09Z")
+        );
+    }
+
+    #[test]
+    fn test_times() {
+        assert_eq!(format_time(0), "1904-01-01T00:00:00Z");
+        assert_eq!(format_time(EPOCH_OFFSET as u64), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            parse_time("1970-01-01T00:00:00Z"),
+            Some(EPOCH_OFFSET as u64)
+        );
+        assert_eq!(parse_time("2000-02-29"), Some(3_034_627_200));
+        assert_eq!(format_time(3_034_627_200), "2000-02-29T00:00:00Z");
+        assert_eq!(parse_time("2024"), None);
+        assert_eq!(parse_time("1900-01-01"), None);
+    }
+}
@@ -0,0 +1,183 @@
+//! RIFF container helpers
+//!
+//! Shared by the handlers of RIFF-based formats (AVI, WebP, WAV).
+//!
+//! RIFF Structure:
+//! - "RIFF" + size (4 bytes, little-endian) + form type (4 bytes) + chunks
+//! - Chunk: ID (4 bytes) + size (4 bytes, little-endian) + data
+//! - Chunks with an odd size are followed by a padding byte
+
+// Not every helper is used when only some of the RIFF formats are enabled
+#![cfg_attr(
+    not(all(feature = "avi", feature = "wav", feature = "webp")),
+    allow(dead_code)
+)]
+
+use crate::core::error::{XmpError, XmpResult};
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// RIFF container signature
+pub(crate) const RIFF_SIGNATURE: &[u8; 4] = b"RIFF";
+
+/// A chunk loaded in memory
+#[derive(Debug, Clone)]
+pub(crate) struct RiffChunk {
+    pub(crate) id: [u8; 4],
+    pub(crate) data: Vec<u8>,
+}
+
+/// The location of a chunk in the file
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ChunkHeader {
+    pub(crate) id: [u8; 4],
+    /// Offset of the chunk header
+    pub(crate) offset: u64,
+    /// Size of the chunk data, without padding
+    pub(crate) size: u64,
+}
+
+impl ChunkHeader {
+    /// Offset of the chunk data
+    pub(crate) fn data_offset(&self) -> u64 {
+        self.offset + 8
+    }
+}
+
+/// Size of a chunk in the file, including its header and padding
+pub(crate) fn chunk_size(data_size: u64) -> u64 {
+    8 + data_size + (data_size & 1)
+}
+
+/// Check the RIFF signature and form type without consuming the reader
+pub(crate) fn is_riff<R: Read + Seek>(reader: &mut R, form_type: &[u8; 4]) -> XmpResult<bool> {
+    let pos = reader.stream_position()?;
+    let mut header = [0u8; 12];
+    let result = reader.read_exact(&mut header);
+    reader.seek(SeekFrom::Start(pos))?;
+    Ok(result.is_ok() && &header[0..4] == RIFF_SIGNATURE && &header[8..12] == form_type)
+}
+
+/// Read and check the RIFF header, returning the end offset of the RIFF data
+///
+/// Leaves the reader at the first chunk.
+pub(crate) fn read_header<R: Read + Seek>(
+    reader: &mut R,
+    form_type: &[u8; 4],
+    format: &str,
+) -> XmpResult<u64> {
+    let mut header = [0u8; 12];
+    reader.rewind()?;
+    reader
+        .read_exact(&mut header)
+        .map_err(|_| XmpError::BadValue(format!("Not a valid {} file", format)))?;
+    if &header[0..4] != RIFF_SIGNATURE || &header[8..12] != form_type {
+        return Err(XmpError::BadValue(format!("Not a valid {} file", format)));
+    }
+
+    // Don't trust the RIFF size beyond the end of the file
+    let riff_size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as u64;
+    let file_end = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(12))?;
+    Ok((8 + riff_size).min(file_end))
+}
+
+/// Read the chunk headers between `start` and `end`, without loading the data
+pub(crate) fn read_chunk_headers<R: Read + Seek>(
+    reader: &mut R,
+    start: u64,
+    end: u64,
+    format: &str,
+) -> XmpResult<Vec<ChunkHeader>> {
+    let mut headers = Vec::new();
+    let mut pos = start;
+    while pos + 8 <= end {
+        reader.seek(SeekFrom::Start(pos))?;
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header)?;
+        let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as u64;
+        if pos + 8 + size > end {
+            return Err(XmpError::BadValue(format!("Truncated {} chunk", format)));
+        }
+
+        headers.push(ChunkHeader {
+            id: [header[0], header[1], header[2], header[3]],
+            offset: pos,
+            size,
+        });
+        pos += chunk_size(size);
+    }
+    Ok(headers)
+}
+
+/// Read the data of a chunk
+pub(crate) fn read_chunk_data<R: Read + Seek>(
+    reader: &mut R,
+    header: &ChunkHeader,
+) -> XmpResult<Vec<u8>> {
+    reader.seek(SeekFrom::Start(header.data_offset()))?;
+    let mut data = vec![0u8; header.size as usize];
+    reader.read_exact(&mut data)?;
+    Ok(data)
+}
+
+/// Read all chunks between the first chunk and `riff_end`
+pub(crate) fn read_chunks<R: Read + Seek>(
+    reader: &mut R,
+    riff_end: u64,
+    format: &str,
+) -> XmpResult<Vec<RiffChunk>> {
+    read_chunk_headers(reader, 12, riff_end, format)?
+        .iter()
+        .map(|header| {
+            Ok(RiffChunk {
+                id: header.id,
+                data: read_chunk_data(reader, header)?,
+            })
+        })
+        .collect()
+}
+
+/// Write a chunk with its padding byte
+pub(crate) fn write_chunk<W: Write>(writer: &mut W, id: &[u8; 4], data: &[u8]) -> XmpResult<()> {
+    let size = u32::try_from(data.len())
+        .map_err(|_| XmpError::BadValue("RIFF chunk too large".to_string()))?;
+    writer.write_all(id)?;
+    writer.write_all(&size.to_le_bytes())?;
+    writer.write_all(data)?;
+    if data.len() & 1 == 1 {
+        writer.write_all(&[0])?;
+    }
+    Ok(())
+}
+
+/// Copy a chunk from the reader, adding its padding byte if missing
+pub(crate) fn copy_chunk<R: Read + Seek, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    header: &ChunkHeader,
+) -> XmpResult<()> {
+    reader.seek(SeekFrom::Start(header.offset))?;
+    let copied = std::io::copy(&mut reader.by_ref().take(8 + header.size), writer)?;
+    if copied != 8 + header.size {
+        return Err(XmpError::BadValue("Truncated RIFF chunk".to_string()));
+    }
+    if header.size & 1 == 1 {
+        writer.write_all(&[0])?;
+    }
+    Ok(())
+}
+
+/// Write the RIFF header for chunks of the given total size
+pub(crate) fn write_header<W: Write>(
+    writer: &mut W,
+    form_type: &[u8; 4],
+    chunks_size: u64,
+    format: &str,
+) -> XmpResult<()> {
+    let riff_size = u32::try_from(4 + chunks_size)
+        .map_err(|_| XmpError::BadValue(format!("{} file too large", format)))?;
+    writer.write_all(RIFF_SIGNATURE)?;
+    writer.write_all(&riff_size.to_le_bytes())?;
+    writer.write_all(form_type)?;
+    Ok(())
+}
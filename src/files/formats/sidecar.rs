@@ -0,0 +1,222 @@
+//! XMP sidecar file handler
+//!
+//! This module provides functionality for reading and writing standalone XMP
+//! documents (`.xmp` sidecar files), which hold the metadata of another file
+//! stored next to it. The implementation is pure Rust and cross-platform
+//! compatible.
+//!
+//! Sidecar File Structure:
+//! - An `x:xmpmeta` element containing `rdf:RDF`, optionally preceded by an
+//!   XML declaration and wrapped in `<?xpacket?>` processing instructions
+//! - Writing replaces the whole document with an `x:xmpmeta` element (no
+//!   packet wrapper or padding)
+
+use crate::core::error::XmpResult;
+use crate::core::metadata::XmpMeta;
+use crate::core::serializer::SerializeOptions;
+use crate::files::handler::FileHandler;
+use crate::utils::encoding::PacketEncoding;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// Number of bytes read when detecting the format
+const DETECTION_SIZE: u64 = 4096;
+
+/// Markup that can start an XMP document
+const ROOT_MARKERS: &[&str] = &["<?xpacket", "<x:xmpmeta", "<x:xapmeta", "<rdf:RDF"];
+
+/// XMP sidecar file handler
+#[derive(Debug, Clone, Copy)]
+pub struct SidecarHandler;
+
+impl FileHandler for SidecarHandler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        let pos = reader.stream_position()?;
+        let mut header = Vec::new();
+        reader
+            .by_ref()
+            .take(DETECTION_SIZE)
+            .read_to_end(&mut header)?;
+        reader.seek(SeekFrom::Start(pos))?;
+
+        // The header may end in the middle of a character
+        let encoding = PacketEncoding::detect(&header);
+        let len = header.len() / encoding.unit_size() * encoding.unit_size();
+        let text = encoding
+            .decode(&header[..len])
+            .unwrap_or_else(|_| String::from_utf8_lossy(&header).into_owned());
+        Ok(Self::is_xmp_document(&text))
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn format_name(&self) -> &'static str {
+        "XMP"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["xmp"]
+    }
+}
+
+impl SidecarHandler {
+    /// Read XMP metadata from a sidecar file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(XmpMeta))` if XMP metadata is found
+    /// * `Ok(None)` if the file is empty
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_xmp<R: Read + Seek>(mut reader: R) -> XmpResult<Option<XmpMeta>> {
+        reader.rewind()?;
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let text = PacketEncoding::decode_detected(&data)?;
+        if text.trim().is_empty() {
+            return Ok(None);
+        }
+        XmpMeta::parse(&text).map(Some)
+    }
+
+    /// Write XMP metadata to a sidecar file
+    ///
+    /// The previous content of the sidecar is replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The source sidecar file (unused, since the whole document
+    ///   is rewritten)
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    pub fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        _reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        writer.write_all(Self::serialize(meta)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Serialize metadata as a sidecar document
+    pub fn serialize(meta: &XmpMeta) -> XmpResult<String> {
+        let options = SerializeOptions::default()
+            .use_compact_format()
+            .omit_packet_wrapper()
+            .base_indent(1);
+        let rdf = meta.serialize_with_options(&options)?;
+        Ok(format!(
+            "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n{}\n</x:xmpmeta>\n",
+            rdf.trim_end()
+        ))
+    }
+
+    /// Check if the text starts with an XMP document root, after an optional
+    /// XML declaration and comments
+    fn is_xmp_document(text: &str) -> bool {
+        let mut rest = text.trim_start();
+        loop {
+            if rest.starts_with("<?xml") {
+                match rest.find("?>") {
+                    Some(end) => rest = rest[end + 2..].trim_start(),
+                    None => return false,
+                }
+            } else if rest.starts_with("<!--") {
+                match rest.find("-->") {
+                    Some(end) => rest = rest[end + 3..].trim_start(),
+                    None => return false,
+                }
+            } else {
+                return ROOT_MARKERS.iter().any(|marker| rest.starts_with(marker));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+    use std::io::Cursor;
+
+    const LIGHTROOM_SIDECAR: &str = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/" x:xmptk="Adobe XMP Core 5.6-c140">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""
+    xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+   xmp:Rating="3"/>
+ </rdf:RDF>
+</x:xmpmeta>
+"#;
+
+    #[test]
+    fn test_can_handle() {
+        let handler = SidecarHandler;
+        let detect = |text: &str| {
+            handler
+                .can_handle(&mut Cursor::new(text.as_bytes().to_vec()))
+                .unwrap()
+        };
+        assert!(detect(LIGHTROOM_SIDECAR));
+        assert!(detect(&format!(
+            "\u{FEFF}<?xml version=\"1.0\"?>\n<!-- sidecar -->\n{}",
+            LIGHTROOM_SIDECAR
+        )));
+        assert!(detect("<?xpacket begin=\"\"?><x:xmpmeta/>"));
+        assert!(!detect("<?xml version=\"1.0\"?><svg></svg>"));
+        assert!(!detect("plain text"));
+    }
+
+    #[test]
+    fn test_read_xmp() {
+        let meta = SidecarHandler::read_xmp(Cursor::new(LIGHTROOM_SIDECAR))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            meta.get_property(ns::XMP, "Rating"),
+            Some(XmpValue::String("3".to_string()))
+        );
+        assert!(SidecarHandler::read_xmp(Cursor::new("  \n"))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_write_xmp() {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String("Sidecar".to_string()))
+            .unwrap();
+        let mut writer = Cursor::new(Vec::new());
+        SidecarHandler::write_xmp(Cursor::new(LIGHTROOM_SIDECAR), &mut writer, &meta).unwrap();
+
+        let data = writer.into_inner();
+        let text = String::from_utf8(data.clone()).unwrap();
+        assert!(text.starts_with("<x:xmpmeta"));
+        assert!(!text.contains("xmp:Rating"));
+        assert!(SidecarHandler.can_handle(&mut Cursor::new(&data)).unwrap());
+        let reread = SidecarHandler::read_xmp(Cursor::new(data))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            reread.get_property(ns::DC, "title"),
+            Some(XmpValue::String("Sidecar".to_string()))
+        );
+    }
+}
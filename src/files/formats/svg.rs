@@ -0,0 +1,477 @@
+//! SVG file format handler
+//!
+//! This module provides functionality for reading and writing XMP metadata
+//! in SVG files. The implementation is pure Rust and cross-platform compatible.
+//!
+//! SVG XMP Storage:
+//! - XMP is stored as an `x:xmpmeta` element (optionally wrapped in
+//!   `<?xpacket?>` processing instructions) inside the `<metadata>` element
+//!   that is a child of the root `<svg>` element
+//! - Other content of `<metadata>` (e.g. Creative Commons RDF) is kept
+//!
+//! Writing edits the document text directly, so the rest of the document's
+//! formatting is preserved. Only UTF-8 (and ASCII) encoded files are supported.
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::files::handler::FileHandler;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+
+/// Number of bytes read when detecting the format
+const DETECTION_SIZE: u64 = 4096;
+
+/// UTF-8 byte order mark
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// SVG file handler for XMP metadata
+#[derive(Debug, Clone, Copy)]
+pub struct SvgHandler;
+
+impl FileHandler for SvgHandler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        let pos = reader.stream_position()?;
+        let mut header = Vec::new();
+        reader
+            .by_ref()
+            .take(DETECTION_SIZE)
+            .read_to_end(&mut header)?;
+        reader.seek(SeekFrom::Start(pos))?;
+
+        let header = header.strip_prefix(UTF8_BOM).unwrap_or(&header);
+        let text = String::from_utf8_lossy(header);
+        // The first element must be the root `svg` element
+        Ok(Tags::new(&text)
+            .find(|tag| matches!(tag.kind, TagKind::Start | TagKind::Empty | TagKind::End))
+            .is_some_and(|tag| tag.kind != TagKind::End && local_name(tag.name) == "svg"))
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn format_name(&self) -> &'static str {
+        "SVG"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["svg"]
+    }
+}
+
+/// Kind of markup found by [`Tags`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TagKind {
+    /// `<name ...>`
+    Start,
+    /// `<name ... />`
+    Empty,
+    /// `</name>`
+    End,
+    /// `<?target ...?>`
+    ProcessingInstruction,
+    /// Comments, CDATA sections and declarations
+    Other,
+}
+
+/// A piece of markup in the document
+#[derive(Debug, Clone, Copy)]
+struct Tag<'a> {
+    kind: TagKind,
+    /// Element name or processing instruction target
+    name: &'a str,
+    /// Byte range of the markup in the document
+    start: usize,
+    end: usize,
+}
+
+/// Iterator over the markup of an XML document
+struct Tags<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Tags<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    /// Find the end of `delimiter` from `from`, or the end of the text
+    fn skip_past(&self, from: usize, delimiter: &str) -> usize {
+        self.text[from..]
+            .find(delimiter)
+            .map_or(self.text.len(), |i| from + i + delimiter.len())
+    }
+}
+
+impl<'a> Iterator for Tags<'a> {
+    type Item = Tag<'a>;
+
+    fn next(&mut self) -> Option<Tag<'a>> {
+        let start = self.pos + self.text[self.pos..].find('<')?;
+        let rest = &self.text[start..];
+
+        let (kind, end) = if rest.starts_with("<!--") {
+            (TagKind::Other, self.skip_past(start + 4, "-->"))
+        } else if rest.starts_with("<![CDATA[") {
+            (TagKind::Other, self.skip_past(start + 9, "]]>"))
+        } else if rest.starts_with("<?") {
+            (
+                TagKind::ProcessingInstruction,
+                self.skip_past(start + 2, "?>"),
+            )
+        } else if rest.starts_with("<!") {
+            // DOCTYPE, possibly with an internal subset
+            let end = match (rest.find('['), rest.find('>')) {
+                (Some(bracket), Some(close)) if bracket < close => {
+                    self.skip_past(self.skip_past(start + bracket, "]"), ">")
+                }
+                _ => self.skip_past(start, ">"),
+            };
+            (TagKind::Other, end)
+        } else {
+            // Element tag, honoring quoted attribute values
+            let mut quote = None;
+            let mut end = self.text.len();
+            for (i, c) in rest.char_indices().skip(1) {
+                match (quote, c) {
+                    (None, '"' | '\'') => quote = Some(c),
+                    (Some(q), _) if q == c => quote = None,
+                    (None, '>') => {
+                        end = start + i + 1;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            let kind = if rest.starts_with("</") {
+                TagKind::End
+            } else if self.text[..end].ends_with("/>") {
+                TagKind::Empty
+            } else {
+                TagKind::Start
+            };
+            (kind, end)
+        };
+        self.pos = end;
+
+        let name_start = match kind {
+            TagKind::End | TagKind::ProcessingInstruction => start + 2,
+            _ => start + 1,
+        };
+        let name_len = self.text[name_start..end]
+            .find(|c: char| c.is_whitespace() || matches!(c, '>' | '/' | '?'))
+            .unwrap_or(end - name_start);
+        Some(Tag {
+            kind,
+            name: &self.text[name_start..name_start + name_len],
+            start,
+            end,
+        })
+    }
+}
+
+/// Strip the namespace prefix of a name
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+/// Location of the document metadata
+#[derive(Debug, Default)]
+struct SvgLayout {
+    /// The root `svg` start tag, and its name
+    root: Option<(Range<usize>, String, TagKind)>,
+    /// The `metadata` start tag, and whether it is an empty element
+    metadata: Option<(Range<usize>, TagKind)>,
+    /// Start of the `metadata` end tag
+    metadata_end: Option<usize>,
+    /// The XMP block (including `<?xpacket?>` wrappers)
+    xmp: Option<Range<usize>>,
+}
+
+impl SvgLayout {
+    fn parse(text: &str) -> Self {
+        let mut layout = Self::default();
+        let mut depth = 0usize;
+        // Start of an open `<?xpacket begin?>` or `x:xmpmeta` element
+        let mut packet_start = None;
+        let mut xmpmeta_start = None;
+
+        for tag in Tags::new(text) {
+            // Markup directly inside the metadata element, before any XMP
+            let in_metadata = depth == 2 && layout.metadata.is_some() && layout.xmp.is_none();
+            match tag.kind {
+                TagKind::Start | TagKind::Empty => {
+                    if layout.root.is_none() {
+                        if local_name(tag.name) != "svg" {
+                            break;
+                        }
+                        layout.root = Some((tag.start..tag.end, tag.name.to_string(), tag.kind));
+                    } else if depth == 1
+                        && layout.metadata.is_none()
+                        && local_name(tag.name) == "metadata"
+                    {
+                        layout.metadata = Some((tag.start..tag.end, tag.kind));
+                        if tag.kind == TagKind::Empty {
+                            break;
+                        }
+                    } else if in_metadata
+                        && packet_start.is_none()
+                        && local_name(tag.name) == "xmpmeta"
+                    {
+                        if tag.kind == TagKind::Empty {
+                            layout.xmp = Some(tag.start..tag.end);
+                        } else {
+                            xmpmeta_start = Some(tag.start);
+                        }
+                    }
+                    if tag.kind == TagKind::Start {
+                        depth += 1;
+                    }
+                }
+                TagKind::End => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 2 && local_name(tag.name) == "xmpmeta" {
+                        if let Some(start) = xmpmeta_start.take() {
+                            layout.xmp = Some(start..tag.end);
+                        }
+                    } else if depth == 1 && layout.metadata.is_some() {
+                        layout.metadata_end = Some(tag.start);
+                        // Nothing else is needed from here on
+                        break;
+                    } else if depth == 0 {
+                        break;
+                    }
+                }
+                TagKind::ProcessingInstruction
+                    if in_metadata && xmpmeta_start.is_none() && tag.name == "xpacket" =>
+                {
+                    if text[tag.start..tag.end].contains("begin") {
+                        packet_start = Some(tag.start);
+                    } else if let Some(start) = packet_start.take() {
+                        layout.xmp = Some(start..tag.end);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        layout
+    }
+}
+
+impl SvgHandler {
+    /// Read XMP metadata from an SVG file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(XmpMeta))` if XMP metadata is found
+    /// * `Ok(None)` if no XMP metadata is found
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_xmp<R: Read + Seek>(reader: R) -> XmpResult<Option<XmpMeta>> {
+        let text = Self::read_text(reader)?;
+        let layout = SvgLayout::parse(&text);
+        if layout.root.is_none() {
+            return Err(XmpError::BadValue("Not a valid SVG file".to_string()));
+        }
+
+        match layout.xmp {
+            Some(range) => XmpMeta::parse(&text[range]).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Write XMP metadata to an SVG file
+    ///
+    /// An existing XMP block is replaced. Otherwise the packet is added to
+    /// the `<metadata>` element, which is created if needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    pub fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        let xmp_packet = meta.serialize_packet()?;
+        let text = Self::read_text(reader)?;
+        let layout = SvgLayout::parse(&text);
+        let Some((root, root_name, root_kind)) = layout.root else {
+            return Err(XmpError::BadValue("Not a valid SVG file".to_string()));
+        };
+
+        // Use the same prefix for the metadata element as for the root
+        let metadata_name = match root_name.rsplit_once(':') {
+            Some((prefix, _)) => format!("{}:metadata", prefix),
+            None => "metadata".to_string(),
+        };
+
+        let (range, replacement) = match (layout.xmp, layout.metadata, layout.metadata_end) {
+            (Some(xmp), _, _) => (xmp, xmp_packet),
+            (None, Some((_, TagKind::Start)), Some(end)) => (end..end, xmp_packet),
+            (None, Some((tag, _)), _) => {
+                // `<metadata/>`: expand it, keeping its attributes
+                let start_tag = text[tag.start..tag.end - 2].trim_end();
+                (
+                    tag,
+                    format!("{}>{}</{}>", start_tag, xmp_packet, metadata_name),
+                )
+            }
+            (None, None, _) if root_kind == TagKind::Empty => {
+                let start_tag = text[root.start..root.end - 2].trim_end();
+                (
+                    root,
+                    format!(
+                        "{}><{}>{}</{}></{}>",
+                        start_tag, metadata_name, xmp_packet, metadata_name, root_name
+                    ),
+                )
+            }
+            (None, None, _) => (
+                root.end..root.end,
+                format!("<{}>{}</{}>", metadata_name, xmp_packet, metadata_name),
+            ),
+        };
+
+        writer.write_all(&text.as_bytes()[..range.start])?;
+        writer.write_all(replacement.as_bytes())?;
+        writer.write_all(&text.as_bytes()[range.end..])?;
+
+        Ok(())
+    }
+
+    /// Read the whole document as UTF-8 text
+    fn read_text<R: Read + Seek>(mut reader: R) -> XmpResult<String> {
+        reader.rewind()?;
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        String::from_utf8(data)
+            .map_err(|_| XmpError::NotSupported("SVG files not encoded in UTF-8".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+    use std::io::Cursor;
+
+    const SVG: &str = "<?xml version=\"1.0\"?>\n<!-- <svg> in a comment -->\n<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"10\" height=\"10\">\n  <rect width=\"10\" height=\"10\" title=\"a > b\"/>\n</svg>\n";
+
+    fn write(svg: &str, title: &str) -> String {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String(title.to_string()))
+            .unwrap();
+        let mut writer = Cursor::new(Vec::new());
+        SvgHandler::write_xmp(Cursor::new(svg.as_bytes()), &mut writer, &meta).unwrap();
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    fn read_title(svg: &str) -> Option<XmpValue> {
+        SvgHandler::read_xmp(Cursor::new(svg.as_bytes()))
+            .unwrap()?
+            .get_property(ns::DC, "title")
+    }
+
+    #[test]
+    fn test_can_handle() {
+        let handler = SvgHandler;
+        assert!(handler.can_handle(&mut Cursor::new(SVG)).unwrap());
+        assert!(handler
+            .can_handle(&mut Cursor::new(
+                "<svg:svg xmlns:svg=\"http://www.w3.org/2000/svg\"/>"
+            ))
+            .unwrap());
+        assert!(!handler
+            .can_handle(&mut Cursor::new(
+                "<?xml version=\"1.0\"?><html><svg/></html>"
+            ))
+            .unwrap());
+        assert!(!handler.can_handle(&mut Cursor::new(b"\x89PNG")).unwrap());
+    }
+
+    #[test]
+    fn test_write_xmp_creates_metadata() {
+        assert_eq!(read_title(SVG), None);
+
+        let first = write(SVG, "First");
+        assert_eq!(
+            read_title(&first),
+            Some(XmpValue::String("First".to_string()))
+        );
+        assert!(first.contains("<metadata><?xpacket begin="));
+        // The rest of the document is unchanged
+        assert!(first.starts_with(&SVG[..SVG.find("<rect").unwrap() - 3]));
+        assert!(first.ends_with(&SVG[SVG.find("\n  <rect").unwrap()..]));
+
+        // Writing again replaces the packet
+        let second = write(&first, "Second");
+        assert_eq!(
+            read_title(&second),
+            Some(XmpValue::String("Second".to_string()))
+        );
+        assert_eq!(second.matches("<rdf:RDF").count(), 1);
+        assert_eq!(second.matches("<?xpacket").count(), 2);
+    }
+
+    #[test]
+    fn test_write_xmp_existing_metadata() {
+        let svg = "<svg xmlns=\"http://www.w3.org/2000/svg\">\n<metadata id=\"m\">\n<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\"/>\n</metadata>\n</svg>";
+        let output = write(svg, "Kept");
+        assert!(output.starts_with(&svg[..svg.find("</metadata>").unwrap()]));
+        assert!(output.ends_with("</metadata>\n</svg>"));
+        assert_eq!(
+            read_title(&output),
+            Some(XmpValue::String("Kept".to_string()))
+        );
+
+        let empty = "<svg:svg xmlns:svg=\"http://www.w3.org/2000/svg\"><svg:metadata /></svg:svg>";
+        let output = write(empty, "Empty");
+        assert!(output.starts_with(
+            "<svg:svg xmlns:svg=\"http://www.w3.org/2000/svg\"><svg:metadata><?xpacket"
+        ));
+        assert!(output.ends_with("</svg:metadata></svg:svg>"));
+        assert_eq!(
+            read_title(&output),
+            Some(XmpValue::String("Empty".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_write_xmp_empty_root() {
+        let output = write("<svg xmlns=\"http://www.w3.org/2000/svg\" />", "Root");
+        assert!(output.ends_with("</metadata></svg>"));
+        assert_eq!(
+            read_title(&output),
+            Some(XmpValue::String("Root".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_read_xmp_ignores_nested_metadata() {
+        let svg = "<svg><g><metadata><x:xmpmeta xmlns:x=\"adobe:ns:meta/\"/></metadata></g></svg>";
+        assert!(SvgHandler::read_xmp(Cursor::new(svg)).unwrap().is_none());
+        assert!(SvgHandler::read_xmp(Cursor::new("<html/>")).is_err());
+    }
+}
@@ -7,9 +7,19 @@
 //! - XMP Packet is stored in Tag 700 (kTIFF_XMP) in the Primary IFD (0th IFD)
 //! - Tag type is typically BYTE (1) or UNDEFINED (7)
 //! - Value is stored inline if <= 4 bytes, otherwise as an offset to the data
+//!
+//! TIFF-based RAW Files:
+//! - DNG, CR2, NEF and ARW files use a standard TIFF header; ORF and RW2
+//!   files use their own magic numbers but the same IFD structure
+//! - XMP and EXIF can be read from all of them
+//! - Only DNG files can be written; proprietary RAW files are read-only,
+//!   since their undocumented data may reference absolute file offsets
 
 use crate::core::error::{XmpError, XmpResult};
 use crate::core::metadata::XmpMeta;
+use crate::files::file::SaveOptions;
+use crate::files::format::FormatCapabilities;
+use crate::files::formats::exif::{self, ExifData};
 use crate::files::handler::FileHandler;
 use std::io::{Read, Seek, SeekFrom, Write};
 
@@ -17,8 +27,25 @@ use std::io::{Read, Seek, SeekFrom, Write};
 const TIFF_SIGNATURE_LE: &[u8] = &[0x49, 0x49, 0x2A, 0x00]; // II/42 (little-endian)
 const TIFF_SIGNATURE_BE: &[u8] = &[0x4D, 0x4D, 0x00, 0x2A]; // MM/42 (big-endian)
 
+/// Olympus ORF header signatures ("IIRO", "IIRS", "MMOR")
+const ORF_SIGNATURES: [&[u8]; 3] = [
+    &[0x49, 0x49, 0x52, 0x4F],
+    &[0x49, 0x49, 0x52, 0x53],
+    &[0x4D, 0x4D, 0x4F, 0x52],
+];
+
+/// Panasonic RW2 header signature ("IIU\0")
+const RW2_SIGNATURE: &[u8] = &[0x49, 0x49, 0x55, 0x00];
+
+/// Canon CR2 marker following the TIFF header ("CR" + major version 2)
+const CR2_MARKER: &[u8; 3] = b"CR\x02";
+
 /// TIFF Tag IDs
+const TAG_MAKE: u16 = 271;
+const TAG_SUB_IFDS: u16 = 330;
 const TAG_XMP: u16 = 700;
+const TAG_DNG_VERSION: u16 = 50706;
+const TAG_SR2_PRIVATE: u16 = 50740;
 
 /// TIFF Data Types
 const TYPE_BYTE: u16 = 1;
@@ -37,7 +64,7 @@ impl FileHandler for TiffHandler {
         let mut header = [0u8; 4];
         reader.read_exact(&mut header)?;
         reader.rewind()?;
-        Ok(header[0..4] == *TIFF_SIGNATURE_LE || header[0..4] == *TIFF_SIGNATURE_BE)
+        Ok(Self::byte_order(&header).is_ok())
     }
 
     fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
@@ -53,12 +80,83 @@ impl FileHandler for TiffHandler {
         Self::write_xmp(reader, writer, meta)
     }
 
+    fn import_legacy<R: Read + Seek>(&self, reader: &mut R, meta: &mut XmpMeta) -> XmpResult<bool> {
+        match Self::read_exif(reader)? {
+            Some(exif) => exif.import_into(meta),
+            None => Ok(false),
+        }
+    }
+
+    fn export_legacy<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+        options: &SaveOptions,
+    ) -> XmpResult<bool> {
+        if !options.reconcile_exif {
+            return Ok(false);
+        }
+        Self::write_exif(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> FormatCapabilities {
+        FormatCapabilities {
+            can_read: true,
+            can_write: true,
+            can_inject: true,
+            supports_in_place: self.supports_in_place_update(),
+            supports_reconciliation: true,
+            max_packet_size: None,
+        }
+    }
+
     fn format_name(&self) -> &'static str {
         "TIFF"
     }
 
     fn extensions(&self) -> &'static [&'static str] {
-        &["tif", "tiff"]
+        &["tif", "tiff", "dng", "cr2", "nef", "arw", "orf", "rw2"]
+    }
+}
+
+/// TIFF-based RAW formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawFormat {
+    /// Adobe Digital Negative
+    Dng,
+    /// Canon RAW 2
+    Cr2,
+    /// Nikon Electronic Format
+    Nef,
+    /// Sony Alpha RAW
+    Arw,
+    /// Olympus RAW
+    Orf,
+    /// Panasonic RAW 2
+    Rw2,
+}
+
+impl RawFormat {
+    /// Get the name of the format
+    pub fn name(self) -> &'static str {
+        match self {
+            RawFormat::Dng => "DNG",
+            RawFormat::Cr2 => "CR2",
+            RawFormat::Nef => "NEF",
+            RawFormat::Arw => "ARW",
+            RawFormat::Orf => "ORF",
+            RawFormat::Rw2 => "RW2",
+        }
+    }
+
+    /// Check if XMP can be written safely (DNG only)
+    pub fn is_writable(self) -> bool {
+        self == RawFormat::Dng
     }
 }
 
@@ -99,13 +197,7 @@ impl TiffHandler {
         let mut header = [0u8; 8];
         reader.read_exact(&mut header)?;
 
-        let byte_order = if header[0..4] == *TIFF_SIGNATURE_LE {
-            ByteOrder::LittleEndian
-        } else if header[0..4] == *TIFF_SIGNATURE_BE {
-            ByteOrder::BigEndian
-        } else {
-            return Err(XmpError::BadValue("Not a valid TIFF file".to_string()));
-        };
+        let byte_order = Self::byte_order(&header)?;
 
         // Read first IFD offset (bytes 4-7)
         let first_ifd_offset = Self::read_u32(&header[4..8], byte_order)?;
@@ -120,15 +212,50 @@ impl TiffHandler {
             return Ok(None);
         }
 
-        // Parse XMP Packet
-        let xmp_str = String::from_utf8(xmp_data)
-            .map_err(|e| XmpError::ParseError(format!("Invalid UTF-8 in XMP: {}", e)))?;
+        // Parse XMP Packet (some Windows applications write UTF-16)
+        XmpMeta::parse_bytes(&xmp_data).map(Some)
+    }
+
+    /// Write XMP values to the EXIF tags of a TIFF file
+    ///
+    /// See [`SaveOptions::reconcile_exif`] for the exported values. As when
+    /// writing XMP, existing data is never moved. Proprietary RAW files are
+    /// left unchanged.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` if a copy of the file with updated EXIF was written
+    /// * `Ok(false)` if nothing needs to be written
+    /// * `Err(XmpError)` if an error occurs
+    pub fn write_exif<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<bool> {
+        if Self::raw_format(&mut reader)?.is_some_and(|format| !format.is_writable()) {
+            return Ok(false);
+        }
 
-        XmpMeta::parse(&xmp_str).map(Some)
+        reader.rewind()?;
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        if !exif::export_exif(&mut data, meta)? {
+            return Ok(false);
+        }
+        writer.write_all(&data)?;
+        Ok(true)
     }
 
     /// Write XMP metadata to a TIFF file
     ///
+    /// The existing file data is never moved, so offsets stored in other tags
+    /// (including proprietary ones) stay valid: a packet that fits in the
+    /// existing XMP tag value is written in place, otherwise the packet and
+    /// an updated copy of the Primary IFD are appended to the file.
+    ///
+    /// Proprietary RAW files are read-only; writing to them returns
+    /// `XmpError::NotSupported`. DNG files can be written.
+    ///
     /// # Arguments
     ///
     /// * `reader` - A reader implementing `Read + Seek` for the source file
@@ -148,81 +275,201 @@ impl TiffHandler {
         let xmp_packet = meta.serialize_packet()?;
         let xmp_bytes = xmp_packet.as_bytes();
 
-        // Read TIFF header
+        if let Some(raw_format) = Self::raw_format(&mut reader)? {
+            if !raw_format.is_writable() {
+                return Err(XmpError::NotSupported(format!(
+                    "Writing XMP to {} files (read-only RAW format)",
+                    raw_format.name()
+                )));
+            }
+        }
+
+        // Read TIFF header and Primary IFD
+        reader.rewind()?;
         let mut header = [0u8; 8];
         reader.read_exact(&mut header)?;
+        let byte_order = Self::byte_order(&header)?;
+        let first_ifd_offset = Self::read_u32(&header[4..8], byte_order)?;
+        reader.seek(SeekFrom::Start(first_ifd_offset as u64))?;
+        let (mut entries, next_ifd_offset) = Self::read_ifd(&mut reader, byte_order)?;
 
-        let byte_order = if header[0..4] == *TIFF_SIGNATURE_LE {
-            ByteOrder::LittleEndian
-        } else if header[0..4] == *TIFF_SIGNATURE_BE {
-            ByteOrder::BigEndian
-        } else {
-            return Err(XmpError::BadValue("Not a valid TIFF file".to_string()));
+        // Copy the whole file unchanged
+        reader.rewind()?;
+        let file_size = std::io::copy(&mut reader, &mut writer)?;
+
+        let xmp_index = entries.iter().position(|entry| entry.tag == TAG_XMP);
+        if let Some(index) = xmp_index {
+            let entry = &entries[index];
+            let old_size = entry.count as u64;
+            if (entry.type_ == TYPE_BYTE || entry.type_ == TYPE_UNDEFINED)
+                && old_size > 4
+                && xmp_bytes.len() as u64 <= old_size
+            {
+                // Overwrite the existing value and update the entry count
+                writer.seek(SeekFrom::Start(entry.value_or_offset as u64))?;
+                writer.write_all(xmp_bytes)?;
+                let count_offset = first_ifd_offset as u64 + 2 + index as u64 * 12 + 4;
+                writer.seek(SeekFrom::Start(count_offset))?;
+                writer.write_all(&Self::write_u32(xmp_bytes.len() as u32, byte_order))?;
+                return Ok(());
+            }
+        }
+
+        // Append the packet and the new Primary IFD, both word-aligned
+        let xmp_offset = file_size + file_size % 2;
+        let xmp_len = xmp_bytes.len() as u64;
+        let ifd_offset = xmp_offset + xmp_len + xmp_len % 2;
+        let entry_count = entries.len() + usize::from(xmp_index.is_none());
+        let ifd_end = ifd_offset + 2 + entry_count as u64 * IFD_ENTRY_SIZE as u64 + 4;
+        if ifd_end > u32::MAX as u64 {
+            return Err(XmpError::NotSupported(
+                "TIFF files larger than 4 GB".to_string(),
+            ));
+        }
+        let entry_count = u16::try_from(entry_count)
+            .map_err(|_| XmpError::BadValue("Too many TIFF IFD entries".to_string()))?;
+
+        let xmp_entry = IfdEntry {
+            tag: TAG_XMP,
+            type_: TYPE_BYTE,
+            count: xmp_bytes.len() as u32,
+            value_or_offset: xmp_offset as u32,
         };
+        match xmp_index {
+            Some(index) => entries[index] = xmp_entry,
+            None => {
+                // Entries are sorted by tag
+                let index = entries.partition_point(|entry| entry.tag < TAG_XMP);
+                entries.insert(index, xmp_entry);
+            }
+        }
 
-        // Write header
-        writer.write_all(&header)?;
+        writer.seek(SeekFrom::Start(file_size))?;
+        if file_size % 2 == 1 {
+            writer.write_all(&[0])?;
+        }
+        writer.write_all(xmp_bytes)?;
+        if xmp_len % 2 == 1 {
+            writer.write_all(&[0])?;
+        }
+        writer.write_all(&Self::write_u16(entry_count, byte_order))?;
+        for entry in &entries {
+            Self::write_ifd_entry(&mut writer, entry, byte_order)?;
+        }
+        writer.write_all(&Self::write_u32(next_ifd_offset, byte_order))?;
 
-        // Read first IFD offset
-        let first_ifd_offset = Self::read_u32(&header[4..8], byte_order)?;
+        // Point the header to the new Primary IFD
+        writer.seek(SeekFrom::Start(4))?;
+        writer.write_all(&Self::write_u32(ifd_offset as u32, byte_order))?;
+
+        Ok(())
+    }
+
+    /// Read EXIF metadata from a TIFF or TIFF-based RAW file
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(ExifData))` if EXIF tags are found
+    /// * `Ok(None)` if the file has no EXIF tags
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_exif<R: Read + Seek>(mut reader: R) -> XmpResult<Option<ExifData>> {
+        reader.rewind()?;
+        let exif = ExifData::read(reader)?;
+        Ok(if exif.is_empty() { None } else { Some(exif) })
+    }
+
+    /// Detect the TIFF-based RAW format of a file
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(RawFormat))` for RAW files
+    /// * `Ok(None)` for plain TIFF files
+    /// * `Err(XmpError)` if the file is not TIFF-based
+    pub fn raw_format<R: Read + Seek>(mut reader: R) -> XmpResult<Option<RawFormat>> {
+        reader.rewind()?;
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header)?;
+        let byte_order = Self::byte_order(&header)?;
+        if ORF_SIGNATURES.contains(&&header[0..4]) {
+            return Ok(Some(RawFormat::Orf));
+        }
+        if header[0..4] == *RW2_SIGNATURE {
+            return Ok(Some(RawFormat::Rw2));
+        }
 
-        // Copy file up to first IFD
-        reader.seek(SeekFrom::Start(8))?;
-        let mut buffer = vec![0u8; (first_ifd_offset as usize).saturating_sub(8)];
-        if !buffer.is_empty() {
-            reader.read_exact(&mut buffer)?;
-            writer.write_all(&buffer)?;
+        // CR2 marker follows the header
+        let mut marker = [0u8; 3];
+        if reader.read_exact(&mut marker).is_ok() && marker == *CR2_MARKER {
+            return Ok(Some(RawFormat::Cr2));
         }
 
-        // Read and modify Primary IFD
+        let first_ifd_offset = Self::read_u32(&header[4..8], byte_order)?;
         reader.seek(SeekFrom::Start(first_ifd_offset as u64))?;
-        Self::write_ifd_with_xmp(&mut reader, &mut writer, byte_order, xmp_bytes)?;
-
-        // Copy rest of file
-        let current_pos = reader.stream_position()?;
-        reader.seek(SeekFrom::End(0))?;
-        let file_size = reader.stream_position()?;
-        reader.seek(SeekFrom::Start(current_pos))?;
-
-        let mut buffer = vec![0u8; 8192];
-        let remaining = file_size - current_pos;
-        let mut copied = 0u64;
-
-        while copied < remaining {
-            let to_read = ((remaining - copied) as usize).min(buffer.len());
-            reader.read_exact(&mut buffer[..to_read])?;
-            writer.write_all(&buffer[..to_read])?;
-            copied += to_read as u64;
+        let (entries, _) = Self::read_ifd(&mut reader, byte_order)?;
+        let has_tag = |tag: u16| entries.iter().any(|entry| entry.tag == tag);
+        if has_tag(TAG_DNG_VERSION) {
+            return Ok(Some(RawFormat::Dng));
         }
 
-        Ok(())
+        let make = match entries.iter().find(|entry| entry.tag == TAG_MAKE) {
+            Some(entry) if entry.type_ == TYPE_ASCII => {
+                Self::read_tag_value(&mut reader, entry, byte_order)?
+            }
+            _ => Vec::new(),
+        };
+        let make = String::from_utf8_lossy(&make).to_uppercase();
+        if make.starts_with("NIKON") && has_tag(TAG_SUB_IFDS) {
+            return Ok(Some(RawFormat::Nef));
+        }
+        if make.starts_with("SONY") && has_tag(TAG_SR2_PRIVATE) {
+            return Ok(Some(RawFormat::Arw));
+        }
+        Ok(None)
     }
 
-    /// Read IFD and extract XMP tag (Tag 700)
-    fn read_ifd_for_xmp<R: Read + Seek>(
-        reader: &mut R,
-        byte_order: ByteOrder,
-    ) -> XmpResult<Vec<u8>> {
-        // Read entry count
+    /// Get the byte order from a TIFF (or TIFF-based RAW) header
+    fn byte_order(header: &[u8]) -> XmpResult<ByteOrder> {
+        let signature = &header[0..4];
+        if signature == TIFF_SIGNATURE_LE
+            || signature == RW2_SIGNATURE
+            || signature == ORF_SIGNATURES[0]
+            || signature == ORF_SIGNATURES[1]
+        {
+            Ok(ByteOrder::LittleEndian)
+        } else if signature == TIFF_SIGNATURE_BE || signature == ORF_SIGNATURES[2] {
+            Ok(ByteOrder::BigEndian)
+        } else {
+            Err(XmpError::BadValue("Not a valid TIFF file".to_string()))
+        }
+    }
+
+    /// Read the entries and the next IFD offset of an IFD
+    fn read_ifd<R: Read>(reader: &mut R, byte_order: ByteOrder) -> XmpResult<(Vec<IfdEntry>, u32)> {
         let mut count_bytes = [0u8; 2];
         reader.read_exact(&mut count_bytes)?;
         let entry_count = Self::read_u16(&count_bytes, byte_order)?;
 
-        // Read all entries
+        let mut entries = Vec::with_capacity(entry_count as usize);
         for _ in 0..entry_count {
-            let entry = Self::read_ifd_entry(reader, byte_order)?;
-
-            if entry.tag == TAG_XMP {
-                // Found XMP tag
-                return Self::read_tag_value(reader, &entry, byte_order);
-            }
+            entries.push(Self::read_ifd_entry(reader, byte_order)?);
         }
 
-        // Read next IFD offset (we don't need it for XMP in Primary IFD)
-        let mut _next_ifd = [0u8; 4];
-        reader.read_exact(&mut _next_ifd)?;
+        let mut next_ifd_bytes = [0u8; 4];
+        reader.read_exact(&mut next_ifd_bytes)?;
+        let next_ifd_offset = Self::read_u32(&next_ifd_bytes, byte_order)?;
+        Ok((entries, next_ifd_offset))
+    }
 
-        Ok(Vec::new())
+    /// Read IFD and extract XMP tag (Tag 700)
+    fn read_ifd_for_xmp<R: Read + Seek>(
+        reader: &mut R,
+        byte_order: ByteOrder,
+    ) -> XmpResult<Vec<u8>> {
+        let (entries, _) = Self::read_ifd(reader, byte_order)?;
+        match entries.iter().find(|entry| entry.tag == TAG_XMP) {
+            Some(entry) => Self::read_tag_value(reader, entry, byte_order),
+            None => Ok(Vec::new()),
+        }
     }
 
     /// Read an IFD entry
@@ -273,112 +520,6 @@ impl TiffHandler {
         }
     }
 
-    /// Write IFD with XMP tag
-    fn write_ifd_with_xmp<R: Read + Seek, W: Write + Seek>(
-        reader: &mut R,
-        writer: &mut W,
-        byte_order: ByteOrder,
-        xmp_bytes: &[u8],
-    ) -> XmpResult<()> {
-        // Save IFD position (where we'll write the IFD later)
-        let ifd_start = writer.stream_position()?;
-
-        // Read entry count
-        let mut count_bytes = [0u8; 2];
-        reader.read_exact(&mut count_bytes)?;
-        let entry_count = Self::read_u16(&count_bytes, byte_order)?;
-
-        // Read all entries
-        let mut entries = Vec::new();
-        let mut xmp_found = false;
-        let mut xmp_entry_index = None;
-
-        for i in 0..entry_count {
-            let entry = Self::read_ifd_entry(reader, byte_order)?;
-            if entry.tag == TAG_XMP {
-                xmp_found = true;
-                xmp_entry_index = Some(i as usize);
-            }
-            entries.push(entry);
-        }
-
-        // Read next IFD offset
-        let mut next_ifd_bytes = [0u8; 4];
-        reader.read_exact(&mut next_ifd_bytes)?;
-        let next_ifd_offset = Self::read_u32(&next_ifd_bytes, byte_order)?;
-
-        // Calculate IFD size: 2 (entry count) + entries.len() * 12 + 4 (next IFD offset)
-        // Note: We need to account for the new XMP entry if not found
-        let entries_count = if xmp_found {
-            entries.len()
-        } else {
-            entries.len() + 1
-        };
-        let ifd_size = 2 + (entries_count as u32) * 12 + 4;
-
-        // Determine where to write XMP data
-        let xmp_data_offset = if xmp_found {
-            // Replace existing XMP - use same offset if possible
-            entries[xmp_entry_index.unwrap()].value_or_offset
-        } else {
-            // Append new XMP entry after IFD
-            (ifd_start + ifd_size as u64) as u32
-        };
-
-        // Update or add XMP entry
-        if xmp_found {
-            let entry = &mut entries[xmp_entry_index.unwrap()];
-            entry.count = xmp_bytes.len() as u32;
-            entry.type_ = TYPE_BYTE;
-            if xmp_bytes.len() <= 4 {
-                // Store inline
-                let mut value_bytes = [0u8; 4];
-                value_bytes[..xmp_bytes.len()].copy_from_slice(xmp_bytes);
-                entry.value_or_offset = Self::read_u32(&value_bytes, byte_order)?;
-            } else {
-                entry.value_or_offset = xmp_data_offset;
-            }
-        } else {
-            // Add new XMP entry
-            let new_entry = IfdEntry {
-                tag: TAG_XMP,
-                type_: TYPE_BYTE,
-                count: xmp_bytes.len() as u32,
-                value_or_offset: if xmp_bytes.len() <= 4 {
-                    let mut value_bytes = [0u8; 4];
-                    value_bytes[..xmp_bytes.len()].copy_from_slice(xmp_bytes);
-                    Self::read_u32(&value_bytes, byte_order)?
-                } else {
-                    xmp_data_offset
-                },
-            };
-            entries.push(new_entry);
-        }
-
-        // Write updated IFD at the saved position first
-        writer.seek(SeekFrom::Start(ifd_start))?;
-
-        // Write entry count
-        let count = entries.len() as u16;
-        writer.write_all(&Self::write_u16(count, byte_order))?;
-
-        // Write entries
-        for entry in &entries {
-            Self::write_ifd_entry(writer, entry, byte_order)?;
-        }
-
-        // Write next IFD offset
-        writer.write_all(&Self::write_u32(next_ifd_offset, byte_order))?;
-
-        // Write XMP data if needed (after IFD is written)
-        if !xmp_found || xmp_bytes.len() > 4 {
-            writer.seek(SeekFrom::Start(xmp_data_offset as u64))?;
-            writer.write_all(xmp_bytes)?;
-        }
-
-        Ok(())
-    }
-
     /// Write an IFD entry
     fn write_ifd_entry<W: Write>(
         writer: &mut W,
@@ -521,4 +662,162 @@ mod tests {
             panic!("Expected string value");
         }
     }
+
+    /// Little-endian TIFF with the given header signature and IFD0 entries,
+    /// followed by `extra` data (at offset `extra_offset(entries.len())`)
+    fn build_tiff(signature: &[u8], entries: &[(u16, u16, u32, u32)], extra: &[u8]) -> Vec<u8> {
+        let mut tiff = signature.to_vec();
+        tiff.extend_from_slice(&8u32.to_le_bytes());
+        tiff.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        for &(tag, type_, count, value) in entries {
+            tiff.extend_from_slice(&tag.to_le_bytes());
+            tiff.extend_from_slice(&type_.to_le_bytes());
+            tiff.extend_from_slice(&count.to_le_bytes());
+            tiff.extend_from_slice(&value.to_le_bytes());
+        }
+        tiff.extend_from_slice(&[0, 0, 0, 0]);
+        tiff.extend_from_slice(extra);
+        tiff
+    }
+
+    fn extra_offset(entry_count: usize) -> u32 {
+        (8 + 2 + entry_count * IFD_ENTRY_SIZE + 4) as u32
+    }
+
+    fn test_meta(title: &str) -> XmpMeta {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String(title.to_string()))
+            .unwrap();
+        meta
+    }
+
+    fn write(data: &[u8], meta: &XmpMeta) -> XmpResult<Vec<u8>> {
+        let mut writer = Cursor::new(Vec::new());
+        TiffHandler::write_xmp(Cursor::new(data), &mut writer, meta)?;
+        Ok(writer.into_inner())
+    }
+
+    fn title(data: &[u8]) -> Option<XmpValue> {
+        TiffHandler::read_xmp(Cursor::new(data))
+            .unwrap()
+            .and_then(|meta| meta.get_property(ns::DC, "title"))
+    }
+
+    #[test]
+    fn test_raw_format() {
+        let detect = |data: Vec<u8>| TiffHandler::raw_format(Cursor::new(data)).unwrap();
+        assert_eq!(detect(create_minimal_tiff_le()), None);
+        assert_eq!(
+            detect(build_tiff(
+                TIFF_SIGNATURE_LE,
+                &[(TAG_DNG_VERSION, 1, 4, 0x0104)],
+                &[]
+            )),
+            Some(RawFormat::Dng)
+        );
+        let mut cr2 = create_minimal_tiff_le();
+        cr2[4] = 0x10;
+        cr2.splice(8..8, *b"CR\x02\x00\0\0\0\0");
+        assert_eq!(detect(cr2), Some(RawFormat::Cr2));
+        assert_eq!(detect(build_tiff(b"IIRO", &[], &[])), Some(RawFormat::Orf));
+        assert_eq!(
+            detect(build_tiff(RW2_SIGNATURE, &[], &[])),
+            Some(RawFormat::Rw2)
+        );
+
+        let make = |make: &[u8], tag: u16| {
+            build_tiff(
+                TIFF_SIGNATURE_LE,
+                &[
+                    (TAG_MAKE, TYPE_ASCII, make.len() as u32, extra_offset(2)),
+                    (tag, 4, 1, 0),
+                ],
+                make,
+            )
+        };
+        assert_eq!(
+            detect(make(b"NIKON CORPORATION\0", TAG_SUB_IFDS)),
+            Some(RawFormat::Nef)
+        );
+        assert_eq!(
+            detect(make(b"SONY\0", TAG_SR2_PRIVATE)),
+            Some(RawFormat::Arw)
+        );
+        // A Nikon scan saved as plain TIFF
+        assert_eq!(detect(make(b"NIKON CORPORATION\0", 0x0131)), None);
+    }
+
+    #[test]
+    fn test_write_dng() {
+        let image_data = b"raw image data";
+        let dng = build_tiff(
+            TIFF_SIGNATURE_LE,
+            &[
+                (0x0111, 4, 1, extra_offset(2)), // StripOffsets
+                (TAG_DNG_VERSION, 1, 4, 0x0104),
+            ],
+            image_data,
+        );
+        let data = write(&dng, &test_meta("Negative")).unwrap();
+        assert_eq!(title(&data), Some(XmpValue::String("Negative".to_string())));
+
+        // The original data is unchanged apart from the IFD0 offset
+        assert_eq!(data[8..dng.len()], dng[8..]);
+        let ifd_offset = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
+        assert_eq!(ifd_offset % 2, 0);
+        let (entries, _) = TiffHandler::read_ifd(
+            &mut Cursor::new(&data[ifd_offset..]),
+            ByteOrder::LittleEndian,
+        )
+        .unwrap();
+        let tags: Vec<u16> = entries.iter().map(|entry| entry.tag).collect();
+        assert_eq!(tags, vec![0x0111, TAG_XMP, TAG_DNG_VERSION]);
+        assert_eq!(
+            TiffHandler::raw_format(Cursor::new(&data)).unwrap(),
+            Some(RawFormat::Dng)
+        );
+
+        // A smaller packet is written in place
+        let data = write(&data, &test_meta(&"Long title ".repeat(20))).unwrap();
+        let rewritten = write(&data, &test_meta("Short")).unwrap();
+        assert_eq!(rewritten.len(), data.len());
+        assert_eq!(
+            title(&rewritten),
+            Some(XmpValue::String("Short".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_proprietary_raw_read_only() {
+        let meta = test_meta("Camera");
+        let tiff = build_tiff(TIFF_SIGNATURE_LE, &[(0x0100, 3, 1, 100)], &[]);
+        let with_xmp = write(&tiff, &meta).unwrap();
+
+        // Turn the file into an ORF file
+        let mut orf = with_xmp.clone();
+        orf[..4].copy_from_slice(b"IIRO");
+        assert!(TiffHandler.can_handle(&mut Cursor::new(&orf)).unwrap());
+        assert_eq!(title(&orf), Some(XmpValue::String("Camera".to_string())));
+        assert!(matches!(write(&orf, &meta), Err(XmpError::NotSupported(_))));
+    }
+
+    #[test]
+    fn test_read_exif() {
+        let tiff = build_tiff(
+            TIFF_SIGNATURE_LE,
+            &[(TAG_MAKE, TYPE_ASCII, 6, extra_offset(1))],
+            b"Canon\0",
+        );
+        let exif = TiffHandler::read_exif(Cursor::new(tiff)).unwrap().unwrap();
+        assert_eq!(
+            exif.get(crate::files::formats::exif::ExifIfd::Primary, TAG_MAKE)
+                .and_then(|value| value.as_str()),
+            Some("Canon")
+        );
+        assert!(
+            TiffHandler::read_exif(Cursor::new(create_minimal_tiff_le()))
+                .unwrap()
+                .is_none()
+        );
+    }
 }
@@ -0,0 +1,483 @@
+//! WAV file format handler
+//!
+//! This module provides functionality for reading and writing XMP metadata
+//! in WAV (and Broadcast Wave) files. The implementation is pure Rust and
+//! cross-platform compatible.
+//!
+//! WAV XMP Storage:
+//! - WAV is a RIFF container: "RIFF" + size (little-endian) + "WAVE" + chunks
+//! - XMP Packet is stored in a "_PMX" chunk
+//! - Broadcast Wave files have a "bext" chunk with fixed-size description,
+//!   originator and origination fields, which can optionally be reconciled
+//!   with the `bext:` XMP properties (see [`WavOptions`])
+//!
+//! Chunks other than "_PMX" (and "bext" when reconciling) are copied
+//! unchanged, without loading the audio data in memory.
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::core::namespace::ns;
+use crate::files::formats::riff::{self, ChunkHeader};
+use crate::files::handler::FileHandler;
+use crate::types::value::XmpValue;
+use std::io::{Read, Seek, Write};
+
+/// WAV form type
+const WAVE_FORM_TYPE: &[u8; 4] = b"WAVE";
+
+/// XMP chunk ID
+const CHUNK_XMP: &[u8; 4] = b"_PMX";
+
+/// Broadcast audio extension chunk ID
+const CHUNK_BEXT: &[u8; 4] = b"bext";
+
+/// Audio data chunk ID
+const CHUNK_DATA: &[u8; 4] = b"data";
+
+/// Size of the fixed part of the bext chunk (before the coding history)
+const BEXT_FIXED_SIZE: usize = 602;
+
+/// Text fields of the bext chunk: XMP property name, offset and length
+const BEXT_TEXT_FIELDS: &[(&str, usize, usize)] = &[
+    ("description", 0, 256),
+    ("originator", 256, 32),
+    ("originatorReference", 288, 32),
+    ("originationDate", 320, 10),
+    ("originationTime", 330, 8),
+];
+
+/// Offset of the 64-bit sample count since midnight
+const BEXT_TIME_REFERENCE: usize = 338;
+
+/// WAV file handler for XMP metadata
+#[derive(Debug, Clone, Copy)]
+pub struct WavHandler;
+
+/// Options for reading and writing WAV files
+#[derive(Default, Clone, Copy, Debug)]
+pub struct WavOptions {
+    pub(crate) reconcile_bext: bool,
+}
+
+impl WavOptions {
+    /// Reconcile the `bext` chunk with the `bext:` XMP properties
+    ///
+    /// When reading, `bext` fields missing from the XMP are imported. When
+    /// writing, the `bext:` properties are exported to the `bext` chunk,
+    /// which is created if needed.
+    pub fn reconcile_bext(mut self) -> Self {
+        self.reconcile_bext = true;
+        self
+    }
+}
+
+impl FileHandler for WavHandler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        riff::is_riff(reader, WAVE_FORM_TYPE)
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn format_name(&self) -> &'static str {
+        "WAV"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["wav", "bwf"]
+    }
+}
+
+impl WavHandler {
+    /// Read XMP metadata from a WAV file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(XmpMeta))` if XMP metadata is found
+    /// * `Ok(None)` if no XMP metadata is found
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_xmp<R: Read + Seek>(reader: R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp_with(reader, WavOptions::default())
+    }
+
+    /// Read XMP metadata from a WAV file with options
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    /// * `options` - Options, e.g. to import the `bext` chunk fields
+    pub fn read_xmp_with<R: Read + Seek>(
+        mut reader: R,
+        options: WavOptions,
+    ) -> XmpResult<Option<XmpMeta>> {
+        let riff_end = riff::read_header(&mut reader, WAVE_FORM_TYPE, "WAV")?;
+        let headers = riff::read_chunk_headers(&mut reader, 12, riff_end, "WAV")?;
+
+        let mut meta = match headers.iter().find(|header| header.id == *CHUNK_XMP) {
+            Some(header) => {
+                let data = riff::read_chunk_data(&mut reader, header)?;
+                let xmp_str = String::from_utf8(data)
+                    .map_err(|e| XmpError::ParseError(format!("Invalid UTF-8 in XMP: {}", e)))?;
+                Some(XmpMeta::parse(xmp_str.trim_end_matches('\0'))?)
+            }
+            None => None,
+        };
+
+        if options.reconcile_bext {
+            if let Some(header) = headers.iter().find(|header| header.id == *CHUNK_BEXT) {
+                let bext = riff::read_chunk_data(&mut reader, header)?;
+                Self::import_bext(meta.get_or_insert_with(XmpMeta::new), &bext)?;
+            }
+        }
+
+        Ok(meta)
+    }
+
+    /// Write XMP metadata to a WAV file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    pub fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        reader: R,
+        writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp_with(reader, writer, meta, WavOptions::default())
+    }
+
+    /// Write XMP metadata to a WAV file with options
+    ///
+    /// The "_PMX" chunk is replaced, or appended if the file has none.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    /// * `options` - Options, e.g. to export the `bext` chunk fields
+    pub fn write_xmp_with<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+        options: WavOptions,
+    ) -> XmpResult<()> {
+        let xmp_packet = meta.serialize_packet()?;
+        let riff_end = riff::read_header(&mut reader, WAVE_FORM_TYPE, "WAV")?;
+        let headers = riff::read_chunk_headers(&mut reader, 12, riff_end, "WAV")?;
+
+        let mut chunks: Vec<OutputChunk> = Vec::with_capacity(headers.len() + 2);
+        let mut has_xmp = false;
+        let mut has_bext = false;
+        for header in &headers {
+            if header.id == *CHUNK_XMP {
+                if !has_xmp {
+                    chunks.push(OutputChunk::New(*CHUNK_XMP, xmp_packet.as_bytes().to_vec()));
+                    has_xmp = true;
+                }
+            } else if header.id == *CHUNK_BEXT && options.reconcile_bext && !has_bext {
+                let mut bext = riff::read_chunk_data(&mut reader, header)?;
+                Self::export_bext(meta, &mut bext)?;
+                chunks.push(OutputChunk::New(*CHUNK_BEXT, bext));
+                has_bext = true;
+            } else {
+                chunks.push(OutputChunk::Copy(*header));
+            }
+        }
+
+        if options.reconcile_bext && !has_bext && Self::has_bext_properties(meta) {
+            let mut bext = vec![0u8; BEXT_FIXED_SIZE];
+            Self::export_bext(meta, &mut bext)?;
+            // The bext chunk should come before the audio data
+            let insert_at = chunks
+                .iter()
+                .position(|chunk| matches!(chunk, OutputChunk::Copy(h) if h.id == *CHUNK_DATA))
+                .unwrap_or(chunks.len());
+            chunks.insert(insert_at, OutputChunk::New(*CHUNK_BEXT, bext));
+        }
+        if !has_xmp {
+            chunks.push(OutputChunk::New(*CHUNK_XMP, xmp_packet.into_bytes()));
+        }
+
+        let chunks_size = chunks
+            .iter()
+            .map(|chunk| match chunk {
+                OutputChunk::Copy(header) => riff::chunk_size(header.size),
+                OutputChunk::New(_, data) => riff::chunk_size(data.len() as u64),
+            })
+            .sum();
+        riff::write_header(&mut writer, WAVE_FORM_TYPE, chunks_size, "WAV")?;
+        for chunk in &chunks {
+            match chunk {
+                OutputChunk::Copy(header) => riff::copy_chunk(&mut reader, &mut writer, header)?,
+                OutputChunk::New(id, data) => riff::write_chunk(&mut writer, id, data)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Import the bext fields that are missing from the XMP
+    fn import_bext(meta: &mut XmpMeta, bext: &[u8]) -> XmpResult<()> {
+        if bext.len() < BEXT_FIXED_SIZE {
+            return Err(XmpError::BadValue("Truncated WAV bext chunk".to_string()));
+        }
+
+        let mut fields: Vec<(&str, String)> = BEXT_TEXT_FIELDS
+            .iter()
+            .map(|&(name, offset, len)| (name, Self::read_text(&bext[offset..offset + len])))
+            .collect();
+        let mut time_reference = [0u8; 8];
+        time_reference.copy_from_slice(&bext[BEXT_TIME_REFERENCE..BEXT_TIME_REFERENCE + 8]);
+        let time_reference = u64::from_le_bytes(time_reference);
+        if time_reference != 0 {
+            fields.push(("timeReference", time_reference.to_string()));
+        }
+        fields.push(("codingHistory", Self::read_text(&bext[BEXT_FIXED_SIZE..])));
+
+        for (name, value) in fields {
+            if !value.is_empty() && meta.get_property(ns::BEXT, name).is_none() {
+                meta.set_property(ns::BEXT, name, XmpValue::String(value))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Export the `bext:` XMP properties to the bext chunk data
+    fn export_bext(meta: &XmpMeta, bext: &mut Vec<u8>) -> XmpResult<()> {
+        if bext.len() < BEXT_FIXED_SIZE {
+            bext.resize(BEXT_FIXED_SIZE, 0);
+        }
+
+        for &(name, offset, len) in BEXT_TEXT_FIELDS {
+            if let Some(XmpValue::String(value)) = meta.get_property(ns::BEXT, name) {
+                let field = &mut bext[offset..offset + len];
+                field.fill(0);
+                let value = Self::truncate(&value, len);
+                field[..value.len()].copy_from_slice(value.as_bytes());
+            }
+        }
+        if let Some(XmpValue::String(value)) = meta.get_property(ns::BEXT, "timeReference") {
+            let time_reference: u64 = value.trim().parse().map_err(|_| {
+                XmpError::BadValue(format!("Invalid bext:timeReference value: {}", value))
+            })?;
+            bext[BEXT_TIME_REFERENCE..BEXT_TIME_REFERENCE + 8]
+                .copy_from_slice(&time_reference.to_le_bytes());
+        }
+        if let Some(XmpValue::String(value)) = meta.get_property(ns::BEXT, "codingHistory") {
+            bext.truncate(BEXT_FIXED_SIZE);
+            bext.extend_from_slice(value.as_bytes());
+        }
+        Ok(())
+    }
+
+    fn has_bext_properties(meta: &XmpMeta) -> bool {
+        BEXT_TEXT_FIELDS
+            .iter()
+            .map(|&(name, _, _)| name)
+            .chain(["timeReference", "codingHistory"])
+            .any(|name| meta.get_property(ns::BEXT, name).is_some())
+    }
+
+    /// Read a NUL-padded text field
+    fn read_text(field: &[u8]) -> String {
+        let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+        String::from_utf8_lossy(&field[..end])
+            .trim_end()
+            .to_string()
+    }
+
+    /// Truncate a string to at most `len` bytes, on a character boundary
+    fn truncate(value: &str, len: usize) -> &str {
+        let mut end = value.len().min(len);
+        while !value.is_char_boundary(end) {
+            end -= 1;
+        }
+        &value[..end]
+    }
+}
+
+/// A chunk of the output file
+enum OutputChunk {
+    /// Copied unchanged from the source file
+    Copy(ChunkHeader),
+    /// New chunk data
+    New([u8; 4], Vec<u8>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Create a minimal WAV file with the given extra chunks before the data
+    fn create_minimal_wav(extra: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+        let mut chunks = Vec::new();
+        let fmt = [1, 0, 1, 0, 0x44, 0xAC, 0, 0, 0x88, 0x58, 1, 0, 2, 0, 16, 0];
+        riff::write_chunk(&mut chunks, b"fmt ", &fmt).unwrap();
+        for (id, data) in extra {
+            riff::write_chunk(&mut chunks, id, data).unwrap();
+        }
+        riff::write_chunk(&mut chunks, CHUNK_DATA, &[1, 2, 3]).unwrap();
+
+        let mut data = Vec::new();
+        riff::write_header(&mut data, WAVE_FORM_TYPE, chunks.len() as u64, "WAV").unwrap();
+        data.extend_from_slice(&chunks);
+        data
+    }
+
+    fn chunk_ids(data: &[u8]) -> Vec<[u8; 4]> {
+        let mut reader = Cursor::new(data);
+        let riff_end = riff::read_header(&mut reader, WAVE_FORM_TYPE, "WAV").unwrap();
+        assert_eq!(riff_end, data.len() as u64);
+        riff::read_chunk_headers(&mut reader, 12, riff_end, "WAV")
+            .unwrap()
+            .iter()
+            .map(|header| header.id)
+            .collect()
+    }
+
+    fn bext_chunk(description: &str, originator: &str) -> Vec<u8> {
+        let mut bext = vec![0u8; BEXT_FIXED_SIZE];
+        bext[..description.len()].copy_from_slice(description.as_bytes());
+        bext[256..256 + originator.len()].copy_from_slice(originator.as_bytes());
+        bext[BEXT_TIME_REFERENCE] = 42;
+        bext.extend_from_slice(b"A=PCM,F=44100\r\n");
+        bext
+    }
+
+    #[test]
+    fn test_can_handle() {
+        let handler = WavHandler;
+        assert!(handler
+            .can_handle(&mut Cursor::new(create_minimal_wav(&[])))
+            .unwrap());
+        assert!(!handler
+            .can_handle(&mut Cursor::new(b"RIFF\0\0\0\0WEBP".to_vec()))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_write_xmp() {
+        let wav = create_minimal_wav(&[]);
+        assert!(WavHandler::read_xmp(Cursor::new(&wav)).unwrap().is_none());
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String("Take 1".to_string()))
+            .unwrap();
+        let mut writer = Cursor::new(Vec::new());
+        WavHandler::write_xmp(Cursor::new(&wav), &mut writer, &meta).unwrap();
+        let first = writer.into_inner();
+        assert_eq!(chunk_ids(&first), [*b"fmt ", *CHUNK_DATA, *CHUNK_XMP]);
+        // The original chunks are copied unchanged
+        assert_eq!(&first[12..wav.len()], &wav[12..]);
+
+        meta.set_property(ns::DC, "title", XmpValue::String("Take 2".to_string()))
+            .unwrap();
+        let mut writer = Cursor::new(Vec::new());
+        WavHandler::write_xmp(Cursor::new(&first), &mut writer, &meta).unwrap();
+        let second = writer.into_inner();
+        assert_eq!(chunk_ids(&second), [*b"fmt ", *CHUNK_DATA, *CHUNK_XMP]);
+
+        let read = WavHandler::read_xmp(Cursor::new(&second)).unwrap().unwrap();
+        assert_eq!(
+            read.get_property(ns::DC, "title"),
+            Some(XmpValue::String("Take 2".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_read_bext_reconciliation() {
+        let wav = create_minimal_wav(&[(CHUNK_BEXT, &bext_chunk("Interview", "Recorder"))]);
+        assert!(WavHandler::read_xmp(Cursor::new(&wav)).unwrap().is_none());
+
+        let options = WavOptions::default().reconcile_bext();
+        let meta = WavHandler::read_xmp_with(Cursor::new(&wav), options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            meta.get_property(ns::BEXT, "description"),
+            Some(XmpValue::String("Interview".to_string()))
+        );
+        assert_eq!(
+            meta.get_property(ns::BEXT, "originator"),
+            Some(XmpValue::String("Recorder".to_string()))
+        );
+        assert_eq!(
+            meta.get_property(ns::BEXT, "timeReference"),
+            Some(XmpValue::String("42".to_string()))
+        );
+        assert_eq!(
+            meta.get_property(ns::BEXT, "codingHistory"),
+            Some(XmpValue::String("A=PCM,F=44100".to_string()))
+        );
+        assert!(meta.get_property(ns::BEXT, "originationDate").is_none());
+    }
+
+    #[test]
+    fn test_write_bext_reconciliation() {
+        let options = WavOptions::default().reconcile_bext();
+        let mut meta = XmpMeta::new();
+        meta.set_property(
+            ns::BEXT,
+            "description",
+            XmpValue::String("Updated".to_string()),
+        )
+        .unwrap();
+
+        // The existing chunk is updated, keeping the other fields
+        let wav = create_minimal_wav(&[(CHUNK_BEXT, &bext_chunk("Interview", "Recorder"))]);
+        let mut writer = Cursor::new(Vec::new());
+        WavHandler::write_xmp_with(Cursor::new(&wav), &mut writer, &meta, options).unwrap();
+        let data = writer.into_inner();
+        assert_eq!(
+            chunk_ids(&data),
+            [*b"fmt ", *CHUNK_BEXT, *CHUNK_DATA, *CHUNK_XMP]
+        );
+
+        let mut reader = Cursor::new(&data);
+        let headers = riff::read_chunk_headers(&mut reader, 12, data.len() as u64, "WAV").unwrap();
+        let bext = riff::read_chunk_data(&mut reader, &headers[1]).unwrap();
+        assert_eq!(WavHandler::read_text(&bext[..256]), "Updated");
+        assert_eq!(WavHandler::read_text(&bext[256..288]), "Recorder");
+        assert_eq!(bext[BEXT_TIME_REFERENCE], 42);
+
+        // A missing chunk is created before the audio data
+        let wav = create_minimal_wav(&[]);
+        let mut writer = Cursor::new(Vec::new());
+        WavHandler::write_xmp_with(Cursor::new(&wav), &mut writer, &meta, options).unwrap();
+        let data = writer.into_inner();
+        assert_eq!(
+            chunk_ids(&data),
+            [*b"fmt ", *CHUNK_BEXT, *CHUNK_DATA, *CHUNK_XMP]
+        );
+
+        // Without the option, the bext chunk is left alone
+        let mut writer = Cursor::new(Vec::new());
+        WavHandler::write_xmp(Cursor::new(&wav), &mut writer, &meta).unwrap();
+        assert_eq!(
+            chunk_ids(&writer.into_inner()),
+            [*b"fmt ", *CHUNK_DATA, *CHUNK_XMP]
+        );
+    }
+}
@@ -0,0 +1,386 @@
+//! WebP file format handler
+//!
+//! This module provides functionality for reading and writing XMP metadata
+//! in WebP files. The implementation is pure Rust and cross-platform compatible.
+//!
+//! WebP XMP Storage:
+//! - WebP is a RIFF container: "RIFF" + size (little-endian) + "WEBP" + chunks
+//! - XMP Packet is stored in an "XMP " chunk, EXIF in an "EXIF" chunk
+//! - Metadata chunks require the extended format: a "VP8X" chunk must come
+//!   first, with the XMP (0x04) and EXIF (0x08) flags set accordingly
+//! - Chunks with an odd size are followed by a padding byte
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::files::formats::riff::{self, RiffChunk};
+use crate::files::handler::FileHandler;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// WebP form type
+const WEBP_FORM_TYPE: &[u8; 4] = b"WEBP";
+
+/// Chunk FourCCs
+const CHUNK_VP8X: &[u8; 4] = b"VP8X";
+const CHUNK_VP8: &[u8; 4] = b"VP8 ";
+const CHUNK_VP8L: &[u8; 4] = b"VP8L";
+const CHUNK_ALPH: &[u8; 4] = b"ALPH";
+const CHUNK_ANIM: &[u8; 4] = b"ANIM";
+const CHUNK_ICCP: &[u8; 4] = b"ICCP";
+const CHUNK_EXIF: &[u8; 4] = b"EXIF";
+const CHUNK_XMP: &[u8; 4] = b"XMP ";
+
+/// VP8X feature flags
+const FLAG_ICC: u8 = 0x20;
+const FLAG_ALPHA: u8 = 0x10;
+const FLAG_EXIF: u8 = 0x08;
+const FLAG_XMP: u8 = 0x04;
+const FLAG_ANIMATION: u8 = 0x02;
+
+/// Size of the VP8X chunk payload
+const VP8X_SIZE: usize = 10;
+
+/// WebP file handler for XMP metadata
+#[derive(Debug, Clone, Copy)]
+pub struct WebpHandler;
+
+impl FileHandler for WebpHandler {
+    fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
+        riff::is_riff(reader, WEBP_FORM_TYPE)
+    }
+
+    fn read_xmp<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<Option<XmpMeta>> {
+        Self::read_xmp(reader)
+    }
+
+    fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        Self::write_xmp(reader, writer, meta)
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        true
+    }
+
+    fn format_name(&self) -> &'static str {
+        "WebP"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["webp"]
+    }
+}
+
+impl WebpHandler {
+    /// Read XMP metadata from a WebP file
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(XmpMeta))` if XMP metadata is found
+    /// * `Ok(None)` if no XMP metadata is found
+    /// * `Err(XmpError)` if an error occurs
+    pub fn read_xmp<R: Read + Seek>(mut reader: R) -> XmpResult<Option<XmpMeta>> {
+        let riff_end = riff::read_header(&mut reader, WEBP_FORM_TYPE, "WebP")?;
+
+        // Look for the XMP chunk without loading the other chunks
+        let mut pos = 12u64;
+        while pos + 8 <= riff_end {
+            reader.seek(SeekFrom::Start(pos))?;
+            let mut header = [0u8; 8];
+            if reader.read_exact(&mut header).is_err() {
+                break;
+            }
+            let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as u64;
+
+            if header[0..4] == *CHUNK_XMP {
+                let mut data = Vec::new();
+                reader.by_ref().take(size).read_to_end(&mut data)?;
+                if (data.len() as u64) < size {
+                    return Err(XmpError::BadValue("Truncated WebP XMP chunk".to_string()));
+                }
+                let xmp_str = String::from_utf8(data)
+                    .map_err(|e| XmpError::ParseError(format!("Invalid UTF-8 in XMP: {}", e)))?;
+                return XmpMeta::parse(xmp_str.trim_end_matches('\0')).map(Some);
+            }
+
+            pos += 8 + size + (size & 1);
+        }
+
+        Ok(None)
+    }
+
+    /// Write XMP metadata to a WebP file
+    ///
+    /// Existing XMP chunks are replaced by a single new chunk. Simple (lossy
+    /// or lossless) files are converted to the extended format, as required
+    /// for metadata chunks.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek` for the source file
+    /// * `writer` - A writer implementing `Write + Seek` for the output file
+    /// * `meta` - The XMP metadata to write
+    pub fn write_xmp<R: Read + Seek, W: Write + Seek>(
+        mut reader: R,
+        mut writer: W,
+        meta: &XmpMeta,
+    ) -> XmpResult<()> {
+        let xmp_packet = meta.serialize_packet()?;
+
+        let riff_end = riff::read_header(&mut reader, WEBP_FORM_TYPE, "WebP")?;
+        let mut chunks = riff::read_chunks(&mut reader, riff_end, "WebP")?;
+        chunks.retain(|chunk| chunk.id != *CHUNK_XMP);
+
+        match chunks.first() {
+            Some(chunk) if chunk.id == *CHUNK_VP8X => {
+                if chunk.data.len() < VP8X_SIZE {
+                    return Err(XmpError::BadValue("Truncated WebP VP8X chunk".to_string()));
+                }
+            }
+            _ => {
+                let vp8x = Self::create_vp8x(&chunks)?;
+                chunks.insert(0, vp8x);
+            }
+        }
+
+        // The XMP chunk comes after the image data and EXIF chunks
+        let insert_at = chunks
+            .iter()
+            .rposition(|chunk| Self::is_image_chunk(&chunk.id) || chunk.id == *CHUNK_EXIF)
+            .map_or(chunks.len(), |index| index + 1);
+        chunks.insert(
+            insert_at,
+            RiffChunk {
+                id: *CHUNK_XMP,
+                data: xmp_packet.into_bytes(),
+            },
+        );
+
+        // Update VP8X flags from the chunks present
+        let has_exif = chunks.iter().any(|chunk| chunk.id == *CHUNK_EXIF);
+        let flags = &mut chunks[0].data[0];
+        *flags |= FLAG_XMP;
+        if has_exif {
+            *flags |= FLAG_EXIF;
+        } else {
+            *flags &= !FLAG_EXIF;
+        }
+
+        let chunks_size = chunks
+            .iter()
+            .map(|chunk| riff::chunk_size(chunk.data.len() as u64))
+            .sum();
+        riff::write_header(&mut writer, WEBP_FORM_TYPE, chunks_size, "WebP")?;
+        for chunk in &chunks {
+            riff::write_chunk(&mut writer, &chunk.id, &chunk.data)?;
+        }
+
+        Ok(())
+    }
+
+    fn is_image_chunk(id: &[u8; 4]) -> bool {
+        id == CHUNK_VP8 || id == CHUNK_VP8L || id == CHUNK_ALPH || id == b"ANMF"
+    }
+
+    /// Create a VP8X chunk for a simple format file
+    fn create_vp8x(chunks: &[RiffChunk]) -> XmpResult<RiffChunk> {
+        let image = chunks
+            .iter()
+            .find(|chunk| chunk.id == *CHUNK_VP8 || chunk.id == *CHUNK_VP8L)
+            .ok_or_else(|| XmpError::BadValue("WebP file has no image data".to_string()))?;
+        let (width, height, has_alpha) = Self::image_size(image)?;
+
+        let mut flags = 0;
+        if has_alpha || chunks.iter().any(|chunk| chunk.id == *CHUNK_ALPH) {
+            flags |= FLAG_ALPHA;
+        }
+        if chunks.iter().any(|chunk| chunk.id == *CHUNK_ICCP) {
+            flags |= FLAG_ICC;
+        }
+        if chunks.iter().any(|chunk| chunk.id == *CHUNK_ANIM) {
+            flags |= FLAG_ANIMATION;
+        }
+
+        let mut data = vec![0u8; VP8X_SIZE];
+        data[0] = flags;
+        data[4..7].copy_from_slice(&(width - 1).to_le_bytes()[..3]);
+        data[7..10].copy_from_slice(&(height - 1).to_le_bytes()[..3]);
+        Ok(RiffChunk {
+            id: *CHUNK_VP8X,
+            data,
+        })
+    }
+
+    /// Get the canvas size (and alpha usage) from a VP8 or VP8L bitstream
+    fn image_size(chunk: &RiffChunk) -> XmpResult<(u32, u32, bool)> {
+        let data = &chunk.data;
+        let invalid = || XmpError::BadValue("Invalid WebP image data".to_string());
+
+        if chunk.id == *CHUNK_VP8 {
+            // Frame tag (3 bytes), start code (9D 01 2A), then 14-bit sizes
+            if data.len() < 10 || data[3..6] != [0x9D, 0x01, 0x2A] {
+                return Err(invalid());
+            }
+            let width = u16::from_le_bytes([data[6], data[7]]) & 0x3FFF;
+            let height = u16::from_le_bytes([data[8], data[9]]) & 0x3FFF;
+            if width == 0 || height == 0 {
+                return Err(invalid());
+            }
+            Ok((width as u32, height as u32, false))
+        } else {
+            // Signature (0x2F), then 14-bit sizes minus one and the alpha bit
+            if data.len() < 5 || data[0] != 0x2F {
+                return Err(invalid());
+            }
+            let bits = u32::from_le_bytes([data[1], data[2], data[3], data[4]]);
+            let width = (bits & 0x3FFF) + 1;
+            let height = ((bits >> 14) & 0x3FFF) + 1;
+            let has_alpha = (bits >> 28) & 1 == 1;
+            Ok((width, height, has_alpha))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::types::value::XmpValue;
+    use std::io::Cursor;
+
+    /// Build a WebP file from chunks
+    fn build_webp(chunks: &[RiffChunk]) -> Vec<u8> {
+        let mut body = Vec::new();
+        for chunk in chunks {
+            riff::write_chunk(&mut body, &chunk.id, &chunk.data).unwrap();
+        }
+        let mut data = riff::RIFF_SIGNATURE.to_vec();
+        data.extend_from_slice(&(4 + body.len() as u32).to_le_bytes());
+        data.extend_from_slice(WEBP_FORM_TYPE);
+        data.extend_from_slice(&body);
+        data
+    }
+
+    // Minimal lossless WebP: 3x2 with alpha (odd-sized payload)
+    fn minimal_lossless() -> Vec<u8> {
+        let bits: u32 = 2 | (1 << 14) | (1 << 28);
+        let mut data = vec![0x2F];
+        data.extend_from_slice(&bits.to_le_bytes());
+        build_webp(&[RiffChunk {
+            id: *CHUNK_VP8L,
+            data,
+        }])
+    }
+
+    fn test_meta() -> XmpMeta {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::DC, "title", XmpValue::String("WebP Image".to_string()))
+            .unwrap();
+        meta
+    }
+
+    #[test]
+    fn test_can_handle() {
+        let handler = WebpHandler;
+        assert!(handler
+            .can_handle(&mut Cursor::new(minimal_lossless()))
+            .unwrap());
+        assert!(!handler
+            .can_handle(&mut Cursor::new(b"RIFF\0\0\0\0WAVE".to_vec()))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_read_xmp_no_xmp() {
+        let result = WebpHandler::read_xmp(Cursor::new(minimal_lossless())).unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_invalid_webp() {
+        let result = WebpHandler::read_xmp(Cursor::new(vec![0x00, 0x01, 0x02]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_write_xmp_simple_format() {
+        let mut writer = Cursor::new(Vec::new());
+        WebpHandler::write_xmp(Cursor::new(minimal_lossless()), &mut writer, &test_meta()).unwrap();
+
+        let data = writer.into_inner();
+        let riff_end = data.len() as u64;
+        let mut reader = Cursor::new(&data);
+        assert_eq!(
+            riff::read_header(&mut reader, WEBP_FORM_TYPE, "WebP").unwrap(),
+            riff_end
+        );
+        let chunks = riff::read_chunks(&mut reader, riff_end, "WebP").unwrap();
+
+        // VP8X created with the canvas size and flags
+        assert_eq!(chunks[0].id, *CHUNK_VP8X);
+        assert_eq!(chunks[0].data[0], FLAG_ALPHA | FLAG_XMP);
+        assert_eq!(&chunks[0].data[4..10], &[2, 0, 0, 1, 0, 0]);
+        assert_eq!(chunks[1].id, *CHUNK_VP8L);
+        assert_eq!(chunks[2].id, *CHUNK_XMP);
+
+        let meta = WebpHandler::read_xmp(Cursor::new(&data)).unwrap().unwrap();
+        assert_eq!(
+            meta.get_property(ns::DC, "title"),
+            Some(XmpValue::String("WebP Image".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_write_xmp_truncated_vp8x() {
+        let original = build_webp(&[RiffChunk {
+            id: *CHUNK_VP8X,
+            data: Vec::new(),
+        }]);
+        let mut writer = Cursor::new(Vec::new());
+        let result = WebpHandler::write_xmp(Cursor::new(original), &mut writer, &test_meta());
+        assert!(matches!(result, Err(XmpError::BadValue(_))));
+    }
+
+    #[test]
+    fn test_write_xmp_replaces_existing() {
+        let mut vp8x = vec![0u8; VP8X_SIZE];
+        vp8x[0] = FLAG_XMP;
+        let data = build_webp(&[
+            RiffChunk {
+                id: *CHUNK_VP8X,
+                data: vp8x,
+            },
+            RiffChunk {
+                id: *CHUNK_VP8L,
+                data: vec![0x2F, 0, 0, 0, 0],
+            },
+            RiffChunk {
+                id: *CHUNK_XMP,
+                data: b"old".to_vec(),
+            },
+            RiffChunk {
+                id: *CHUNK_EXIF,
+                data: b"II*\0".to_vec(),
+            },
+        ]);
+
+        let mut writer = Cursor::new(Vec::new());
+        WebpHandler::write_xmp(Cursor::new(data), &mut writer, &test_meta()).unwrap();
+
+        let data = writer.into_inner();
+        let mut reader = Cursor::new(&data);
+        let riff_end = riff::read_header(&mut reader, WEBP_FORM_TYPE, "WebP").unwrap();
+        let chunks = riff::read_chunks(&mut reader, riff_end, "WebP").unwrap();
+        let ids: Vec<&[u8; 4]> = chunks.iter().map(|chunk| &chunk.id).collect();
+        assert_eq!(ids, [CHUNK_VP8X, CHUNK_VP8L, CHUNK_EXIF, CHUNK_XMP]);
+        assert_eq!(chunks[0].data[0], FLAG_XMP | FLAG_EXIF);
+        assert!(WebpHandler::read_xmp(Cursor::new(&data)).unwrap().is_some());
+    }
+}
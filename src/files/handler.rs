@@ -2,11 +2,27 @@
 //!
 //! This module defines the trait that all file format handlers must implement.
 //! This allows for a unified interface across different file formats.
+//!
+//! Handlers defined outside xmpkit implement [`CustomHandler`] instead, which
+//! can be used as a trait object, and are added with
+//! [`register_handler`](crate::files::registry::register_handler).
 
-use crate::core::error::XmpResult;
-use crate::core::metadata::XmpMeta;
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::{Thumbnail, XmpMeta};
+use crate::files::file::SaveOptions;
+use crate::files::format::FormatCapabilities;
 use std::io::{Read, Seek, Write};
 
+/// Object-safe combination of `Read` and `Seek`
+pub trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek + ?Sized> ReadSeek for T {}
+
+/// Object-safe combination of `Write` and `Seek`
+pub trait WriteSeek: Write + Seek {}
+
+impl<T: Write + Seek + ?Sized> WriteSeek for T {}
+
 /// Trait for file format handlers
 ///
 /// All file format handlers (JPEG, PNG, TIFF, etc.) must implement this trait
@@ -59,6 +75,104 @@ pub trait FileHandler: Send + Sync {
         meta: &XmpMeta,
     ) -> XmpResult<()>;
 
+    /// Import legacy metadata of the file into XMP
+    ///
+    /// Formats with native metadata (such as EXIF in JPEG and TIFF files)
+    /// map it to the corresponding XMP properties, without replacing the
+    /// properties that already exist. The default imports nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader implementing `Read + Seek`
+    /// * `meta` - The XMP metadata read from the file (possibly empty)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` if any property was imported
+    /// * `Ok(false)` otherwise
+    /// * `Err(XmpError)` if an error occurs
+    fn import_legacy<R: Read + Seek>(
+        &self,
+        _reader: &mut R,
+        _meta: &mut XmpMeta,
+    ) -> XmpResult<bool> {
+        Ok(false)
+    }
+
+    /// Read the thumbnail embedded in the native metadata of the file
+    ///
+    /// Used when reading with [`ReadOptions::import_thumbnail`]. The default
+    /// reads nothing.
+    ///
+    /// [`ReadOptions::import_thumbnail`]: crate::files::ReadOptions::import_thumbnail
+    fn read_thumbnail<R: Read + Seek>(&self, _reader: &mut R) -> XmpResult<Option<Thumbnail>> {
+        Ok(None)
+    }
+
+    /// Export XMP values to the native (legacy) metadata of the file
+    ///
+    /// Writes a copy of the file whose native metadata (such as EXIF) is
+    /// updated from `meta`, as selected by `options`. The XMP packet itself
+    /// is written afterwards by [`FileHandler::write_xmp`].
+    ///
+    /// Returns `true` if the copy was written, or `false` (writing nothing)
+    /// if the native metadata doesn't need changes. The default
+    /// implementation exports nothing.
+    fn export_legacy<R: Read + Seek, W: Write + Seek>(
+        &self,
+        _reader: &mut R,
+        _writer: &mut W,
+        _meta: &XmpMeta,
+        _options: &SaveOptions,
+    ) -> XmpResult<bool> {
+        Ok(false)
+    }
+
+    /// Remove the metadata of a file
+    ///
+    /// Writes a copy of the file without its XMP packet. With `all`, the
+    /// native metadata (such as EXIF, IPTC-IIM and thumbnails) is removed
+    /// too. Used by [`XmpFile::remove_xmp`] and
+    /// [`SaveOptions::strip_all_metadata`]. The default returns
+    /// `XmpError::NotSupported`.
+    ///
+    /// [`XmpFile::remove_xmp`]: crate::files::XmpFile::remove_xmp
+    fn remove_metadata<R: Read + Seek, W: Write + Seek>(
+        &self,
+        _reader: &mut R,
+        _writer: &mut W,
+        _all: bool,
+    ) -> XmpResult<()> {
+        Err(XmpError::NotSupported(format!(
+            "Removing metadata from {} files",
+            self.format_name()
+        )))
+    }
+
+    /// Check if the XMP packet can be overwritten in place
+    ///
+    /// Returns `true` if replacing the packet bytes with a packet of the same
+    /// length keeps the file valid. Formats that checksum the packet or
+    /// transform it when writing must return `false` (the default).
+    fn supports_in_place_update(&self) -> bool {
+        false
+    }
+
+    /// Get what the handler can do with files of its format
+    ///
+    /// The default reports a handler that can read the XMP, write it and
+    /// add it to files without a packet, and overwrite it in place if
+    /// [`FileHandler::supports_in_place_update`] says so.
+    fn capabilities(&self) -> FormatCapabilities {
+        FormatCapabilities {
+            can_read: true,
+            can_write: true,
+            can_inject: true,
+            supports_in_place: self.supports_in_place_update(),
+            ..Default::default()
+        }
+    }
+
     /// Get the name of the file format this handler supports
     ///
     /// # Returns
@@ -73,3 +187,103 @@ pub trait FileHandler: Send + Sync {
     /// A slice of file extensions (e.g., &["jpg", "jpeg"] for JPEG)
     fn extensions(&self) -> &'static [&'static str];
 }
+
+/// Trait for file format handlers defined outside xmpkit
+///
+/// Unlike [`FileHandler`], this trait can be used as a trait object, so that
+/// applications can add support for formats that xmpkit doesn't know, such
+/// as proprietary containers, with
+/// [`register_handler`](crate::files::registry::register_handler). The
+/// methods have the same meaning as those of [`FileHandler`].
+///
+/// # Example
+///
+/// ```rust
+/// use std::io::{Read, Seek, SeekFrom};
+/// use xmpkit::files::handler::{CustomHandler, ReadSeek, WriteSeek};
+/// use xmpkit::{XmpMeta, XmpResult};
+///
+/// /// A container holding the XMP packet after an 8-byte signature
+/// struct VendorHandler;
+///
+/// impl CustomHandler for VendorHandler {
+///     fn can_handle(&self, reader: &mut dyn ReadSeek) -> XmpResult<bool> {
+///         let mut signature = [0u8; 8];
+///         Ok(reader.read_exact(&mut signature).is_ok() && &signature == b"VNDRXMP\0")
+///     }
+///
+///     fn read_xmp(&self, reader: &mut dyn ReadSeek) -> XmpResult<Option<XmpMeta>> {
+///         let mut packet = String::new();
+///         reader.seek(SeekFrom::Start(8))?;
+///         reader.read_to_string(&mut packet)?;
+///         Ok(Some(XmpMeta::parse(&packet)?))
+///     }
+///
+///     fn write_xmp(
+///         &self,
+///         _reader: &mut dyn ReadSeek,
+///         writer: &mut dyn WriteSeek,
+///         meta: &XmpMeta,
+///     ) -> XmpResult<()> {
+///         writer.write_all(b"VNDRXMP\0")?;
+///         writer.write_all(meta.serialize_packet()?.as_bytes())?;
+///         Ok(())
+///     }
+///
+///     fn format_name(&self) -> &'static str {
+///         "Vendor"
+///     }
+/// }
+///
+/// xmpkit::files::register_handler(Box::new(VendorHandler));
+/// ```
+pub trait CustomHandler: Send + Sync {
+    /// Check if this handler can handle the given file
+    ///
+    /// The reader is at the start of the file, and its position doesn't
+    /// need to be restored.
+    fn can_handle(&self, reader: &mut dyn ReadSeek) -> XmpResult<bool>;
+
+    /// Read XMP metadata from a file
+    fn read_xmp(&self, reader: &mut dyn ReadSeek) -> XmpResult<Option<XmpMeta>>;
+
+    /// Write a copy of a file with its XMP metadata replaced by `meta`
+    fn write_xmp(
+        &self,
+        reader: &mut dyn ReadSeek,
+        writer: &mut dyn WriteSeek,
+        meta: &XmpMeta,
+    ) -> XmpResult<()>;
+
+    /// Get what the handler can do with files of its format
+    ///
+    /// The default reports a handler that can read the XMP, write it and
+    /// add it to files without a packet, but not update it in place.
+    fn capabilities(&self) -> FormatCapabilities {
+        FormatCapabilities {
+            can_read: true,
+            can_write: true,
+            can_inject: true,
+            ..Default::default()
+        }
+    }
+
+    /// Get the name of the file format this handler supports
+    ///
+    /// The name identifies the handler: registering another handler with
+    /// the same name replaces it.
+    fn format_name(&self) -> &'static str;
+
+    /// Get the file extensions this handler supports (none by default)
+    fn extensions(&self) -> &'static [&'static str] {
+        &[]
+    }
+}
+
+impl std::fmt::Debug for dyn CustomHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("CustomHandler")
+            .field(&self.format_name())
+            .finish()
+    }
+}
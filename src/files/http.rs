@@ -0,0 +1,514 @@
+//! Reading remote files with HTTP range requests
+//!
+//! [`RangeReader`] implements `Read` and `Seek` on top of a [`RangeFetch`]
+//! source, fetching the file in blocks as they are read and keeping the
+//! most recent blocks. Opened in streaming mode, the format handlers only
+//! read the parts of the file they need, such as the APP1 segments of a
+//! JPEG or the `moov` box of an MP4, instead of downloading all of it.
+//!
+//! [`HttpFetch`] is a minimal HTTP/1.1 client for `http://` URLs. It has no
+//! TLS support: for HTTPS and signed S3 requests, implement [`RangeFetch`]
+//! with the HTTP client of the application.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use xmpkit::files::http::RangeReader;
+//! use xmpkit::{ReadOptions, XmpFile};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let reader = RangeReader::open_url("http://example.com/photos/image.jpg")?;
+//! let mut file = XmpFile::new();
+//! file.from_reader_with(reader, ReadOptions::default().streaming().only_xmp())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::net::TcpStream;
+use std::ops::Range;
+use std::time::Duration;
+
+use crate::core::error::{XmpError, XmpResult};
+
+/// Default size of the blocks fetched by a [`RangeReader`]
+const DEFAULT_BLOCK_SIZE: u64 = 64 * 1024;
+
+/// Number of blocks kept by a [`RangeReader`]
+const MAX_CACHED_BLOCKS: usize = 16;
+
+/// Source of byte ranges of a remote file
+pub trait RangeFetch {
+    /// Get the length of the file, in bytes
+    fn length(&mut self) -> XmpResult<u64>;
+
+    /// Fetch the bytes of `range`, which is within the file
+    fn fetch(&mut self, range: Range<u64>) -> XmpResult<Vec<u8>>;
+}
+
+/// Reader fetching a remote file in blocks, as they are read
+///
+/// A read of several blocks that are not cached yet fetches them with a
+/// single request.
+#[derive(Debug)]
+pub struct RangeReader<F> {
+    fetch: F,
+    length: u64,
+    position: u64,
+    block_size: u64,
+    /// Cached blocks by index, the most recently used last
+    blocks: VecDeque<(u64, Vec<u8>)>,
+    fetched: u64,
+}
+
+impl<F: RangeFetch> RangeReader<F> {
+    /// Create a reader, getting the length of the file from `fetch`
+    pub fn new(mut fetch: F) -> XmpResult<Self> {
+        let length = fetch.length()?;
+        Ok(Self {
+            fetch,
+            length,
+            position: 0,
+            block_size: DEFAULT_BLOCK_SIZE,
+            blocks: VecDeque::new(),
+            fetched: 0,
+        })
+    }
+
+    /// Set the size of the blocks to fetch (default: 64 KiB).
+    pub fn block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size.max(1) as u64;
+        self.blocks.clear();
+        self
+    }
+
+    /// Get the length of the file
+    pub fn len(&self) -> u64 {
+        self.length
+    }
+
+    /// Check if the file is empty
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Get the number of bytes fetched so far
+    pub fn bytes_fetched(&self) -> u64 {
+        self.fetched
+    }
+
+    /// Get the source of the byte ranges
+    pub fn into_inner(self) -> F {
+        self.fetch
+    }
+
+    /// Get a cached block, marking it as the most recently used
+    fn cached_block(&mut self, index: u64) -> Option<&[u8]> {
+        let i = self
+            .blocks
+            .iter()
+            .position(|(cached, _)| *cached == index)?;
+        let block = self.blocks.remove(i)?;
+        self.blocks.push_back(block);
+        self.blocks.back().map(|(_, data)| data.as_slice())
+    }
+
+    /// Fetch the blocks from `index` that a read into `buf` spans, copy the
+    /// data at the position to `buf`, and cache the blocks
+    fn fetch_blocks(&mut self, index: u64, buf: &mut [u8]) -> XmpResult<usize> {
+        let end = (self.position + buf.len() as u64).min(self.length);
+        let start = index * self.block_size;
+        let stop = ((end - 1) / self.block_size + 1) * self.block_size;
+        let range = start..stop.min(self.length);
+        let data = self.fetch.fetch(range.clone())?;
+        if data.len() as u64 != range.end - range.start {
+            return Err(XmpError::IoError(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("Fetched {} bytes of range {:?}", data.len(), range),
+            )));
+        }
+        self.fetched += data.len() as u64;
+
+        let offset = (self.position - start) as usize;
+        let read = buf.len().min(data.len() - offset);
+        buf[..read].copy_from_slice(&data[offset..offset + read]);
+        for (i, block) in data.chunks(self.block_size as usize).enumerate() {
+            self.blocks.push_back((index + i as u64, block.to_vec()));
+            if self.blocks.len() > MAX_CACHED_BLOCKS {
+                self.blocks.pop_front();
+            }
+        }
+        Ok(read)
+    }
+}
+
+impl RangeReader<HttpFetch> {
+    /// Create a reader for an `http://` URL
+    pub fn open_url(url: &str) -> XmpResult<Self> {
+        Self::new(HttpFetch::new(url)?)
+    }
+}
+
+impl<F: RangeFetch> Read for RangeReader<F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.position >= self.length {
+            return Ok(0);
+        }
+        let index = self.position / self.block_size;
+        let offset = (self.position % self.block_size) as usize;
+        let read = match self.cached_block(index) {
+            Some(block) => {
+                let read = buf.len().min(block.len() - offset);
+                buf[..read].copy_from_slice(&block[offset..offset + read]);
+                read
+            }
+            None => self.fetch_blocks(index, buf).map_err(|e| match e {
+                XmpError::IoError(e) => e,
+                e => io::Error::other(e),
+            })?,
+        };
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<F> Seek for RangeReader<F> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.length.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        self.position = position
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Seek before the start"))?;
+        Ok(self.position)
+    }
+}
+
+/// [`RangeFetch`] for `http://` URLs, with one connection per request
+#[derive(Debug, Clone)]
+pub struct HttpFetch {
+    url: String,
+    /// Host and port to connect to
+    address: String,
+    /// Value of the `Host` header
+    host: String,
+    /// Path and query of the request
+    path: String,
+    timeout: Duration,
+}
+
+/// Response to an HTTP request
+struct HttpResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Get a header by (lowercase) name
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Get the total length from the `Content-Range` header
+    fn range_total(&self) -> Option<u64> {
+        let (_, total) = self.header("content-range")?.rsplit_once('/')?;
+        total.trim().parse().ok()
+    }
+}
+
+impl HttpFetch {
+    /// Create a source for an `http://` URL
+    ///
+    /// Returns `XmpError::NotSupported` for `https://` URLs, and
+    /// `XmpError::BadParam` for other invalid URLs.
+    pub fn new(url: &str) -> XmpResult<Self> {
+        let bad_url = || XmpError::BadParam(format!("Invalid HTTP URL: {}", url));
+        let Some((scheme, rest)) = url.split_once("://") else {
+            return Err(bad_url());
+        };
+        if scheme.eq_ignore_ascii_case("https") {
+            return Err(XmpError::NotSupported(
+                "HTTPS URLs need a RangeFetch implementation with TLS".to_string(),
+            ));
+        }
+        if !scheme.eq_ignore_ascii_case("http") {
+            return Err(bad_url());
+        }
+        let split = rest.find(['/', '?']).unwrap_or(rest.len());
+        let (host, path) = rest.split_at(split);
+        let path = match path {
+            "" => "/".to_string(),
+            path if path.starts_with('?') => format!("/{}", path),
+            path => path.to_string(),
+        };
+        if host.is_empty() || host.contains('@') {
+            return Err(bad_url());
+        }
+        // The port follows the last colon, unless it is inside an IPv6 address
+        let has_port = host
+            .rfind(':')
+            .is_some_and(|colon| !host[colon..].contains(']'));
+        let address = if has_port {
+            host.to_string()
+        } else {
+            format!("{}:80", host)
+        };
+        Ok(Self {
+            url: url.to_string(),
+            address,
+            host: host.to_string(),
+            path,
+            timeout: Duration::from_secs(30),
+        })
+    }
+
+    /// Set the timeout for connecting, sending and receiving (default: 30
+    /// seconds).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Send a GET request for a byte range, and read the response
+    fn request(&self, range: &str) -> XmpResult<HttpResponse> {
+        let address = std::net::ToSocketAddrs::to_socket_addrs(&self.address)?
+            .next()
+            .ok_or_else(|| XmpError::NotFound(format!("Host of {}", self.url)))?;
+        let mut stream = TcpStream::connect_timeout(&address, self.timeout)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: {}\r\nRange: bytes={}\r\nUser-Agent: xmpkit\r\nConnection: close\r\n\r\n",
+            self.path, self.host, range
+        )?;
+        stream.flush()?;
+        let mut data = Vec::new();
+        stream.read_to_end(&mut data)?;
+        let response = parse_response(&data).ok_or_else(|| {
+            XmpError::ParseError(format!("Invalid HTTP response from {}", self.url))
+        })?;
+        match response.status {
+            200..=299 | 416 => Ok(response),
+            404 | 410 => Err(XmpError::NotFound(self.url.clone())),
+            status => Err(XmpError::IoError(io::Error::other(format!(
+                "HTTP status {} for {}",
+                status, self.url
+            )))),
+        }
+    }
+}
+
+impl RangeFetch for HttpFetch {
+    fn length(&mut self) -> XmpResult<u64> {
+        let response = self.request("0-0")?;
+        match response.status {
+            // The server ignored the range
+            200 => Ok(response.body.len() as u64),
+            _ => response.range_total().ok_or_else(|| {
+                XmpError::ParseError(format!("No file length in the response for {}", self.url))
+            }),
+        }
+    }
+
+    fn fetch(&mut self, range: Range<u64>) -> XmpResult<Vec<u8>> {
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+        let response = self.request(&format!("{}-{}", range.start, range.end - 1))?;
+        match response.status {
+            206 => Ok(response.body),
+            // The server ignored the range and sent the whole file
+            200 => {
+                let start = (range.start as usize).min(response.body.len());
+                let end = (range.end as usize).min(response.body.len());
+                Ok(response.body[start..end].to_vec())
+            }
+            status => Err(XmpError::IoError(io::Error::other(format!(
+                "HTTP status {} for a range of {}",
+                status, self.url
+            )))),
+        }
+    }
+}
+
+/// Parse an HTTP/1.1 response read until the connection closed
+fn parse_response(data: &[u8]) -> Option<HttpResponse> {
+    let head_end = data.windows(4).position(|window| window == b"\r\n\r\n")?;
+    let head = std::str::from_utf8(&data[..head_end]).ok()?;
+    let mut lines = head.split("\r\n");
+    let status = lines.next()?.split(' ').nth(1)?.parse().ok()?;
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            Some((name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        })
+        .collect();
+    let mut response = HttpResponse {
+        status,
+        headers,
+        body: data[head_end + 4..].to_vec(),
+    };
+    if response
+        .header("transfer-encoding")
+        .is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked"))
+    {
+        response.body = decode_chunked(&response.body)?;
+    } else if let Some(length) = response.header("content-length") {
+        let length = length.parse().ok()?;
+        if response.body.len() < length {
+            return None;
+        }
+        response.body.truncate(length);
+    }
+    Some(response)
+}
+
+/// Decode a body with the chunked transfer encoding
+fn decode_chunked(mut data: &[u8]) -> Option<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = data.windows(2).position(|window| window == b"\r\n")?;
+        let line = std::str::from_utf8(&data[..line_end]).ok()?;
+        let size = line.split(';').next()?.trim();
+        let size = usize::from_str_radix(size, 16).ok()?;
+        data = &data[line_end + 2..];
+        if size == 0 {
+            return Some(body);
+        }
+        body.extend_from_slice(data.get(..size)?);
+        data = data.get(size + 2..)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    /// Serve `data` with range requests on a local port, returning its URL
+    fn serve(data: Vec<u8>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/file.jpg?v=1", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = Vec::new();
+                let mut byte = [0u8; 1];
+                while !request.ends_with(b"\r\n\r\n") {
+                    stream.read_exact(&mut byte).unwrap();
+                    request.push(byte[0]);
+                }
+                let request = String::from_utf8(request).unwrap();
+                assert!(request.starts_with("GET /file.jpg?v=1 HTTP/1.1\r\n"));
+                let range = request
+                    .lines()
+                    .find_map(|line| line.strip_prefix("Range: bytes="))
+                    .unwrap();
+                let (start, end) = range.split_once('-').unwrap();
+                let (start, end): (usize, usize) = (start.parse().unwrap(), end.parse().unwrap());
+                let body = &data[start..=end.min(data.len() - 1)];
+                write!(
+                    stream,
+                    "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}-{}/{}\r\nContent-Length: {}\r\n\r\n",
+                    start, end, data.len(), body.len()
+                )
+                .unwrap();
+                stream.write_all(body).unwrap();
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn test_range_reader() {
+        let data: Vec<u8> = (0..100_000u32).map(|i| i as u8).collect();
+        let url = serve(data.clone());
+        let mut reader = RangeReader::open_url(&url).unwrap().block_size(1000);
+        assert_eq!(reader.len(), 100_000);
+
+        let mut buf = vec![0u8; 2500];
+        reader.seek(SeekFrom::Start(50_500)).unwrap();
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, &data[50_500..53_000]);
+        assert_eq!(reader.bytes_fetched(), 3000);
+
+        // Cached blocks are not fetched again
+        reader.seek(SeekFrom::Current(-1000)).unwrap();
+        reader.read_exact(&mut buf[..500]).unwrap();
+        assert_eq!(&buf[..500], &data[52_000..52_500]);
+        assert_eq!(reader.bytes_fetched(), 3000);
+
+        reader.seek(SeekFrom::End(-10)).unwrap();
+        let mut tail = Vec::new();
+        reader.read_to_end(&mut tail).unwrap();
+        assert_eq!(tail, &data[99_990..]);
+        assert!(reader.seek(SeekFrom::Current(-200_000)).is_err());
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_read_remote_jpeg() {
+        use crate::core::metadata::XmpMeta;
+        use crate::core::namespace::ns;
+        use crate::files::file::{ReadOptions, XmpFile};
+        use crate::types::value::XmpValue;
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "CreatorTool", XmpValue::String("Remote".into()))
+            .unwrap();
+        let packet = meta.serialize_packet().unwrap();
+        let signature = b"http://ns.adobe.com/xap/1.0/\0";
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        data.extend_from_slice(&((2 + signature.len() + packet.len()) as u16).to_be_bytes());
+        data.extend_from_slice(signature);
+        data.extend_from_slice(packet.as_bytes());
+        data.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02]);
+        data.resize(data.len() + 1_000_000, 0x55);
+        data.extend_from_slice(&[0xFF, 0xD9]);
+
+        let mut reader = RangeReader::open_url(&serve(data)).unwrap();
+        let mut file = XmpFile::new();
+        file.from_reader_with(&mut reader, ReadOptions::default().streaming().only_xmp())
+            .unwrap();
+        assert_eq!(
+            file.get_xmp().unwrap().get_property(ns::XMP, "CreatorTool"),
+            Some(XmpValue::String("Remote".into()))
+        );
+        // Only the first block is downloaded
+        assert_eq!(reader.bytes_fetched(), DEFAULT_BLOCK_SIZE);
+    }
+
+    #[test]
+    fn test_http_fetch_urls() {
+        let fetch = HttpFetch::new("http://example.com?q=1").unwrap();
+        assert_eq!(
+            (fetch.address.as_str(), fetch.path.as_str()),
+            ("example.com:80", "/?q=1")
+        );
+        let fetch = HttpFetch::new("HTTP://[::1]:8080/a/b.jpg").unwrap();
+        assert_eq!(
+            (fetch.address.as_str(), fetch.host.as_str()),
+            ("[::1]:8080", "[::1]:8080")
+        );
+        assert!(matches!(
+            HttpFetch::new("https://example.com/a.jpg"),
+            Err(XmpError::NotSupported(_))
+        ));
+        assert!(HttpFetch::new("ftp://example.com/a.jpg").is_err());
+        assert!(HttpFetch::new("example.com/a.jpg").is_err());
+
+        let response = parse_response(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nabcd\r\n2;x\r\nef\r\n0\r\n\r\n")
+            .unwrap();
+        assert_eq!(
+            (response.status, response.body.as_slice()),
+            (200, &b"abcdef"[..])
+        );
+    }
+}
@@ -0,0 +1,408 @@
+//! Pluggable storage for file data
+//!
+//! [`XmpIo`] is the equivalent of `XMP_IO` in the Adobe XMP Toolkit: a
+//! readable, writable and seekable byte store that can also be truncated,
+//! and that can derive a temporary store to write a new version of the
+//! file into, then absorb it in place of its content. [`XmpFile::open_io`]
+//! and [`XmpFile::save_io`] work with any implementation, on all platforms,
+//! so that files can live in cloud objects or encrypted storage.
+//!
+//! [`FileIo`] stores the data in a file (native platforms only), and
+//! [`MemoryIo`] in memory.
+//!
+//! [`XmpFile::open_io`]: crate::files::XmpFile::open_io
+//! [`XmpFile::save_io`]: crate::files::XmpFile::save_io
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use xmpkit::files::io::MemoryIo;
+//! use xmpkit::{ns, ReadOptions, SaveOptions, XmpFile, XmpValue};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut io = MemoryIo::new(std::fs::read("image.jpg")?);
+//! let mut file = XmpFile::new();
+//! file.open_io(&mut io, ReadOptions::default())?;
+//! let mut meta = file.get_xmp().cloned().unwrap_or_default();
+//! meta.set_property(ns::XMP, "Label", XmpValue::String("Reviewed".into()))?;
+//! file.put_xmp(meta);
+//! file.save_io(&mut io, SaveOptions::default())?;
+//! let data = io.into_inner();
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+
+use crate::core::error::{XmpError, XmpResult};
+
+/// Byte store holding the data of a file
+///
+/// At most one temporary exists at a time: it is created by
+/// [`derive_temp`](Self::derive_temp), and must then be either absorbed or
+/// deleted.
+pub trait XmpIo: Read + Write + Seek + Send {
+    /// Get the length of the data, in bytes
+    ///
+    /// The default implementation seeks to the end and back.
+    fn length(&mut self) -> XmpResult<u64> {
+        let position = self.stream_position()?;
+        let length = self.seek(SeekFrom::End(0))?;
+        self.seek(SeekFrom::Start(position))?;
+        Ok(length)
+    }
+
+    /// Truncate the data to `length` bytes, or extend it with zeros
+    fn truncate(&mut self, length: u64) -> XmpResult<()>;
+
+    /// Create an empty temporary store, to write a new version of the data
+    /// into
+    ///
+    /// Returns `XmpError::BadParam` if a temporary already exists.
+    fn derive_temp(&mut self) -> XmpResult<&mut dyn XmpIo>;
+
+    /// Replace the data with the content of the temporary, and drop the
+    /// temporary
+    ///
+    /// Returns `XmpError::BadParam` if there is no temporary.
+    fn absorb_temp(&mut self) -> XmpResult<()>;
+
+    /// Drop the temporary, if there is one, keeping the data unchanged
+    fn delete_temp(&mut self) -> XmpResult<()>;
+}
+
+/// Error for absorbing a temporary that doesn't exist
+fn no_temp() -> XmpError {
+    XmpError::BadParam("No temporary to absorb".to_string())
+}
+
+/// Error for deriving a second temporary
+fn temp_exists() -> XmpError {
+    XmpError::BadParam("A temporary already exists".to_string())
+}
+
+/// [`XmpIo`] storing the data in memory
+#[derive(Debug, Clone, Default)]
+pub struct MemoryIo {
+    data: Cursor<Vec<u8>>,
+    temp: Option<Box<MemoryIo>>,
+}
+
+impl MemoryIo {
+    /// Create a store holding `data`, positioned at its start
+    pub fn new(data: Vec<u8>) -> Self {
+        Self {
+            data: Cursor::new(data),
+            temp: None,
+        }
+    }
+
+    /// Get the data
+    pub fn as_bytes(&self) -> &[u8] {
+        self.data.get_ref()
+    }
+
+    /// Get the data, consuming the store
+    pub fn into_inner(self) -> Vec<u8> {
+        self.data.into_inner()
+    }
+}
+
+impl Read for MemoryIo {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.data.read(buf)
+    }
+}
+
+impl Write for MemoryIo {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.data.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for MemoryIo {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.data.seek(pos)
+    }
+}
+
+impl XmpIo for MemoryIo {
+    fn length(&mut self) -> XmpResult<u64> {
+        Ok(self.data.get_ref().len() as u64)
+    }
+
+    fn truncate(&mut self, length: u64) -> XmpResult<()> {
+        let length = usize::try_from(length)
+            .map_err(|_| XmpError::BadParam(format!("Length {} is too large", length)))?;
+        self.data.get_mut().resize(length, 0);
+        Ok(())
+    }
+
+    fn derive_temp(&mut self) -> XmpResult<&mut dyn XmpIo> {
+        if self.temp.is_some() {
+            return Err(temp_exists());
+        }
+        Ok(self.temp.insert(Box::default()).as_mut())
+    }
+
+    fn absorb_temp(&mut self) -> XmpResult<()> {
+        let temp = self.temp.take().ok_or_else(no_temp)?;
+        self.data = Cursor::new(temp.data.into_inner());
+        Ok(())
+    }
+
+    fn delete_temp(&mut self) -> XmpResult<()> {
+        self.temp = None;
+        Ok(())
+    }
+}
+
+/// [`XmpIo`] storing the data in a file (native platforms only)
+///
+/// The temporary is a file next to it, which replaces it when absorbed, so
+/// that a crash can't leave the file partly written.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct FileIo {
+    file: std::fs::File,
+    path: std::path::PathBuf,
+    temp: Option<Box<FileIo>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileIo {
+    /// Open a file for reading and writing
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> XmpResult<Self> {
+        let path = path.as_ref();
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)?;
+        Ok(Self::with_file(file, path))
+    }
+
+    /// Open a file for reading only
+    ///
+    /// Writing returns an I/O error, but a temporary can still be derived
+    /// and absorbed, which replaces the file if its directory is writable.
+    pub fn open_read_only<P: AsRef<std::path::Path>>(path: P) -> XmpResult<Self> {
+        let path = path.as_ref();
+        Ok(Self::with_file(std::fs::File::open(path)?, path))
+    }
+
+    fn with_file(file: std::fs::File, path: &std::path::Path) -> Self {
+        Self {
+            file,
+            path: path.to_path_buf(),
+            temp: None,
+        }
+    }
+
+    /// Get the path of the file
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Read for FileIo {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.file.read(buf)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Write for FileIo {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Seek for FileIo {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.file.seek(pos)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl XmpIo for FileIo {
+    fn length(&mut self) -> XmpResult<u64> {
+        Ok(self.file.metadata()?.len())
+    }
+
+    fn truncate(&mut self, length: u64) -> XmpResult<()> {
+        self.file.set_len(length)?;
+        Ok(())
+    }
+
+    fn derive_temp(&mut self) -> XmpResult<&mut dyn XmpIo> {
+        if self.temp.is_some() {
+            return Err(temp_exists());
+        }
+        let path = temp_path_for(&resolve_path(&self.path));
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        Ok(self
+            .temp
+            .insert(Box::new(Self::with_file(file, &path)))
+            .as_mut())
+    }
+
+    fn absorb_temp(&mut self) -> XmpResult<()> {
+        let temp = self.temp.take().ok_or_else(no_temp)?;
+        let target = resolve_path(&self.path);
+        let original = std::fs::metadata(&target).ok();
+        if let Err(e) = replace_with_temp(&temp.file, &temp.path, &target, original.as_ref()) {
+            let _ = std::fs::remove_file(&temp.path);
+            return Err(e);
+        }
+        self.file = temp.file;
+        self.file.seek(SeekFrom::Start(0))?;
+        Ok(())
+    }
+
+    fn delete_temp(&mut self) -> XmpResult<()> {
+        if let Some(temp) = self.temp.take() {
+            drop(temp.file);
+            std::fs::remove_file(&temp.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Get the path of the file that `path` refers to, following symbolic
+/// links, so that replacing it replaces the target instead of the link
+///
+/// A path that can't be resolved, such as a file that doesn't exist yet,
+/// is returned as it is.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn resolve_path(path: &std::path::Path) -> std::path::PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Get the path of the temporary file written next to `path`
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn temp_path_for(path: &std::path::Path) -> std::path::PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.xmpkit-tmp", file_name))
+}
+
+/// Rename the temporary file `temp` over `path`
+///
+/// The temporary file is flushed to disk first, and gets the permissions
+/// (and on Unix, when allowed, the owner) of the `original` file it
+/// replaces.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn replace_with_temp(
+    temp: &std::fs::File,
+    temp_path: &std::path::Path,
+    path: &std::path::Path,
+    original: Option<&std::fs::Metadata>,
+) -> XmpResult<()> {
+    temp.sync_all()?;
+    if let Some(original) = original {
+        temp.set_permissions(original.permissions())?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            // Only a privileged process can give a file to another user, so
+            // the owner is kept when possible
+            let _ = std::os::unix::fs::fchown(temp, Some(original.uid()), Some(original.gid()));
+        }
+    }
+    std::fs::rename(temp_path, path)?;
+    // Make the rename itself durable
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        let dir = if dir.as_os_str().is_empty() {
+            std::path::Path::new(".")
+        } else {
+            dir
+        };
+        if let Ok(dir) = std::fs::File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write `data` to a temporary of `io` and absorb it
+    fn replace(io: &mut dyn XmpIo, data: &[u8]) {
+        let temp = io.derive_temp().unwrap();
+        temp.write_all(data).unwrap();
+        assert!(io.derive_temp().is_err());
+        io.absorb_temp().unwrap();
+        assert!(io.absorb_temp().is_err());
+    }
+
+    #[test]
+    fn test_memory_io() {
+        let mut io = MemoryIo::new(b"original".to_vec());
+        assert_eq!(io.length().unwrap(), 8);
+        io.truncate(4).unwrap();
+        assert_eq!(io.as_bytes(), b"orig");
+
+        io.derive_temp().unwrap().write_all(b"dropped").unwrap();
+        io.delete_temp().unwrap();
+        assert_eq!(io.as_bytes(), b"orig");
+        replace(&mut io, b"new data");
+        assert_eq!(io.into_inner(), b"new data");
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_file_io() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        std::fs::write(&path, b"original").unwrap();
+
+        let mut io = FileIo::open(&path).unwrap();
+        replace(&mut io, b"new data");
+        assert!(!temp_path_for(&path).exists());
+        assert_eq!(io.length().unwrap(), 8);
+        let mut data = Vec::new();
+        io.read_to_end(&mut data).unwrap();
+        assert_eq!(data, b"new data");
+        io.truncate(3).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"new");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_io_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("data.bin");
+        let link = dir.path().join("link.bin");
+        std::fs::write(&target, b"original").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        // The target is replaced, and the link kept
+        let mut io = FileIo::open(&link).unwrap();
+        replace(&mut io, b"new data");
+        assert!(std::fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(std::fs::read(&target).unwrap(), b"new data");
+    }
+}
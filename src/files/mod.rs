@@ -4,23 +4,84 @@
 //! in various file formats. All implementations are pure Rust and cross-platform
 //! compatible (iOS, Android, HarmonyOS, macOS, Windows, Wasm).
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod batch;
 pub mod file;
+pub mod format;
 pub mod formats;
 pub mod handler;
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+pub mod http;
+pub mod io;
+#[cfg(feature = "jpeg")]
+pub mod mwg;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod progress;
 pub mod registry;
 
-pub use file::{ReadOptions, XmpFile};
+pub use file::{PacketInfo, ReadOptions, SaveOptions, SaveOutcome, XmpFile};
+pub use format::{FileFormat, FormatCapabilities};
+#[cfg(feature = "aiff")]
+pub use formats::aiff::AiffHandler;
+#[cfg(feature = "asf")]
+pub use formats::asf::AsfHandler;
+#[cfg(feature = "avi")]
+pub use formats::avi::AviHandler;
+#[cfg(feature = "avif")]
+pub use formats::avif::AvifHandler;
+#[cfg(feature = "eps")]
+pub use formats::eps::EpsHandler;
+#[cfg(any(feature = "jpeg", feature = "tiff"))]
+pub use formats::exif::{ExifData, ExifEntry, ExifIfd, ExifValue};
+#[cfg(feature = "flac")]
+pub use formats::flac::FlacHandler;
 #[cfg(feature = "gif")]
 pub use formats::gif::GifHandler;
+#[cfg(feature = "heif")]
+pub use formats::heif::HeifHandler;
+#[cfg(feature = "mp3")]
+pub use formats::id3::{Id3Frame, Id3Tag};
+#[cfg(feature = "indd")]
+pub use formats::indd::InddHandler;
+#[cfg(feature = "jpeg")]
+pub use formats::iptc::{IptcData, IptcDataSet, IptcDigest};
+#[cfg(feature = "jp2")]
+pub use formats::jp2::Jp2Handler;
 #[cfg(feature = "jpeg")]
 pub use formats::jpeg::JpegHandler;
+#[cfg(feature = "jxl")]
+pub use formats::jxl::JxlHandler;
+#[cfg(feature = "mkv")]
+pub use formats::mkv::{MkvHandler, MkvOptions};
+#[cfg(feature = "mov")]
+pub use formats::mov::MovHandler;
 #[cfg(feature = "mp3")]
 pub use formats::mp3::Mp3Handler;
 #[cfg(feature = "mp4")]
 pub use formats::mp4::Mp4Handler;
+#[cfg(feature = "ogg")]
+pub use formats::ogg::OggHandler;
+#[cfg(feature = "pdf")]
+pub use formats::pdf::PdfHandler;
 #[cfg(feature = "png")]
 pub use formats::png::PngHandler;
+#[cfg(feature = "psd")]
+pub use formats::psd::PsdHandler;
+#[cfg(feature = "mp4")]
+pub use formats::quicktime::{QuickTimeItem, QuickTimeMetadata};
+pub use formats::sidecar::SidecarHandler;
+#[cfg(feature = "svg")]
+pub use formats::svg::SvgHandler;
 #[cfg(feature = "tiff")]
-pub use formats::tiff::TiffHandler;
+pub use formats::tiff::{RawFormat, TiffHandler};
+#[cfg(feature = "wav")]
+pub use formats::wav::{WavHandler, WavOptions};
+#[cfg(feature = "webp")]
+pub use formats::webp::WebpHandler;
+pub use handler::CustomHandler;
 pub use handler::FileHandler;
+#[cfg(not(target_arch = "wasm32"))]
+pub use io::FileIo;
+pub use io::{MemoryIo, XmpIo};
+pub use registry::register_handler;
 pub use registry::{default_registry, Handler, HandlerRegistry};
@@ -0,0 +1,337 @@
+//! Consolidated metadata following the Metadata Working Group guidelines
+//!
+//! Photos often carry the same values in three containers: EXIF,
+//! IPTC-IIM and XMP, which applications update more or less consistently.
+//! The MWG guidelines define which one to trust when reading:
+//! - An EXIF value is preferred, since applications unaware of XMP update
+//!   it
+//! - IPTC values are preferred over XMP if the IPTC digest doesn't match,
+//!   since the IIM was then edited by an application unaware of XMP
+//! - Otherwise XMP is preferred, and IPTC is only used for values the XMP
+//!   doesn't have
+//!
+//! [`MwgMetadata`] applies these rules to the date, description, creators,
+//! copyright, keywords, orientation and rating of a photo, and records
+//! where each value came from.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::fs::File;
+//! use xmpkit::files::mwg::MwgMetadata;
+//!
+//! let metadata = MwgMetadata::read_jpeg(File::open("photo.jpg")?)?;
+//! if let Some(date) = &metadata.date {
+//!     println!("Taken on {} (from {:?})", date.value, date.source);
+//! }
+//! # Ok::<(), xmpkit::XmpError>(())
+//! ```
+
+use std::io::{Read, Seek};
+
+use crate::core::error::XmpResult;
+use crate::core::metadata::XmpMeta;
+use crate::core::namespace::ns;
+use crate::files::formats::exif::ExifData;
+use crate::files::formats::iptc::{self, IptcData, IptcDigest};
+use crate::files::formats::jpeg::JpegHandler;
+use crate::types::value::XmpValue;
+use crate::utils::datetime::XmpDateTime;
+
+/// Container a consolidated value was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MwgSource {
+    /// Native EXIF
+    Exif,
+    /// Native IPTC-IIM
+    Iptc,
+    /// XMP
+    Xmp,
+}
+
+/// A consolidated value and the container it was read from
+#[derive(Debug, Clone, PartialEq)]
+pub struct MwgValue<T> {
+    /// The value
+    pub value: T,
+    /// The container the value was read from
+    pub source: MwgSource,
+}
+
+/// The metadata containers of a file
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::files::mwg::{MwgMetadata, MwgSource, MwgSources};
+/// use xmpkit::XmpMeta;
+///
+/// let mut xmp = XmpMeta::new();
+/// xmp.set_rating(4).unwrap();
+/// let metadata = MwgMetadata::consolidate(&MwgSources::new().xmp(&xmp)).unwrap();
+/// let rating = metadata.rating.unwrap();
+/// assert_eq!((rating.value, rating.source), (4, MwgSource::Xmp));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MwgSources<'a> {
+    pub(crate) xmp: Option<&'a XmpMeta>,
+    pub(crate) exif: Option<&'a ExifData>,
+    pub(crate) iptc: Option<&'a IptcData>,
+    pub(crate) iptc_digest: IptcDigest,
+}
+
+impl<'a> MwgSources<'a> {
+    /// Create sources without any container
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the XMP
+    pub fn xmp(mut self, xmp: &'a XmpMeta) -> Self {
+        self.xmp = Some(xmp);
+        self
+    }
+
+    /// Set the native EXIF
+    pub fn exif(mut self, exif: &'a ExifData) -> Self {
+        self.exif = Some(exif);
+        self
+    }
+
+    /// Set the native IPTC-IIM and the state of its digest
+    pub fn iptc(mut self, iptc: &'a IptcData, digest: IptcDigest) -> Self {
+        self.iptc = Some(iptc);
+        self.iptc_digest = digest;
+        self
+    }
+}
+
+/// The values of a photo consolidated from its EXIF, IPTC-IIM and XMP
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MwgMetadata {
+    /// When the photo was taken (EXIF `DateTimeOriginal`, IPTC 2:55 and
+    /// 2:60, `photoshop:DateCreated`)
+    pub date: Option<MwgValue<XmpDateTime>>,
+    /// Description (EXIF `ImageDescription`, IPTC 2:120,
+    /// `dc:description`)
+    pub description: Option<MwgValue<String>>,
+    /// Creators (EXIF `Artist`, IPTC 2:80, `dc:creator`)
+    pub creators: Option<MwgValue<Vec<String>>>,
+    /// Copyright notice (EXIF `Copyright`, IPTC 2:116, `dc:rights`)
+    pub copyright: Option<MwgValue<String>>,
+    /// Keywords (IPTC 2:25, `dc:subject`)
+    pub keywords: Option<MwgValue<Vec<String>>>,
+    /// EXIF orientation, from 1 to 8 (EXIF `Orientation`,
+    /// `tiff:Orientation`)
+    pub orientation: Option<MwgValue<u16>>,
+    /// Rating, from -1 to 5 (`xmp:Rating`)
+    pub rating: Option<MwgValue<i8>>,
+}
+
+impl MwgMetadata {
+    /// Consolidate the values of metadata containers
+    pub fn consolidate(sources: &MwgSources) -> XmpResult<Self> {
+        let mut exif = XmpMeta::new();
+        if let Some(data) = sources.exif {
+            data.import_into(&mut exif)?;
+        }
+        let mut iptc = XmpMeta::new();
+        if let Some(data) = sources.iptc {
+            data.import_into(&mut iptc)?;
+        }
+        let empty = XmpMeta::new();
+        let xmp = sources.xmp.unwrap_or(&empty);
+
+        // Candidates of values with an EXIF counterpart, in order
+        let mut all = vec![(MwgSource::Exif, &exif)];
+        if sources.iptc_digest == IptcDigest::Mismatched {
+            all.extend([(MwgSource::Iptc, &iptc), (MwgSource::Xmp, xmp)]);
+        } else {
+            all.extend([(MwgSource::Xmp, xmp), (MwgSource::Iptc, &iptc)]);
+        }
+        let without_exif = &all[1..];
+
+        Ok(Self {
+            date: pick(&all, |meta| {
+                meta.get_date_time(ns::PHOTOSHOP, "DateCreated")
+                    .or_else(|| meta.get_date_time(ns::EXIF, "DateTimeOriginal"))
+            }),
+            description: pick(&all, |meta| default_text(meta, "description")),
+            creators: pick(&all, |meta| {
+                let count = meta.count_array_items(ns::DC, "creator");
+                let creators: Vec<String> = (0..count)
+                    .filter_map(|i| match meta.get_array_item(ns::DC, "creator", i)? {
+                        XmpValue::String(s) if !s.is_empty() => Some(s),
+                        _ => None,
+                    })
+                    .collect();
+                (!creators.is_empty()).then_some(creators)
+            }),
+            copyright: pick(&all, |meta| default_text(meta, "rights")),
+            keywords: pick(without_exif, |meta| {
+                let keywords = meta.keywords();
+                (!keywords.is_empty()).then_some(keywords)
+            }),
+            orientation: pick(&all, |meta| {
+                let orientation = meta.get_property_i64(ns::TIFF, "Orientation")?;
+                (1..=8).contains(&orientation).then_some(orientation as u16)
+            }),
+            rating: pick(&[(MwgSource::Xmp, xmp)], XmpMeta::get_rating),
+        })
+    }
+
+    /// Read and consolidate the metadata of a JPEG file
+    ///
+    /// Unreadable EXIF or IPTC is ignored, like missing containers.
+    pub fn read_jpeg<R: Read + Seek>(mut reader: R) -> XmpResult<Self> {
+        reader.rewind()?;
+        let xmp = JpegHandler::read_xmp(&mut reader)?;
+        let exif = JpegHandler::read_exif(&mut reader)?;
+        let iptc = JpegHandler::read_photoshop_resources(&mut reader)?
+            .and_then(|resources| iptc::read_resources_with_digest(&resources).ok().flatten());
+
+        let mut sources = MwgSources::new();
+        if let Some(xmp) = &xmp {
+            sources = sources.xmp(xmp);
+        }
+        if let Some(exif) = &exif {
+            sources = sources.exif(exif);
+        }
+        if let Some((iptc, digest)) = &iptc {
+            sources = sources.iptc(iptc, *digest);
+        }
+        Self::consolidate(&sources)
+    }
+}
+
+/// Get the first value found in the candidates, with its source
+fn pick<T>(
+    candidates: &[(MwgSource, &XmpMeta)],
+    get: impl Fn(&XmpMeta) -> Option<T>,
+) -> Option<MwgValue<T>> {
+    candidates
+        .iter()
+        .find_map(|&(source, meta)| get(meta).map(|value| MwgValue { value, source }))
+}
+
+/// Get the non-empty default text of a `dc:` localized text property
+fn default_text(meta: &XmpMeta, name: &str) -> Option<String> {
+    meta.get_localized_text(ns::DC, name, "", "x-default")
+        .map(|(text, _)| text)
+        .filter(|text| !text.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Build little-endian TIFF data with ASCII and SHORT entries in the 0th
+    /// IFD
+    fn tiff(ascii: &[(u16, &str)], shorts: &[(u16, u16)]) -> Vec<u8> {
+        let count = ascii.len() + shorts.len();
+        let mut data = b"II*\0\x08\0\0\0".to_vec();
+        data.extend((count as u16).to_le_bytes());
+        let mut values_offset = 8 + 2 + count * 12 + 4;
+        let mut values = Vec::new();
+        for (tag, text) in ascii {
+            let mut bytes = text.as_bytes().to_vec();
+            bytes.push(0);
+            data.extend(tag.to_le_bytes());
+            data.extend(2u16.to_le_bytes());
+            data.extend((bytes.len() as u32).to_le_bytes());
+            data.extend((values_offset as u32).to_le_bytes());
+            values_offset += bytes.len();
+            values.extend(bytes);
+        }
+        for (tag, value) in shorts {
+            data.extend(tag.to_le_bytes());
+            data.extend(3u16.to_le_bytes());
+            data.extend(1u32.to_le_bytes());
+            data.extend(value.to_le_bytes());
+            data.extend([0, 0]);
+        }
+        data.extend([0; 4]);
+        data.extend(values);
+        data
+    }
+
+    fn iim(datasets: &[(u8, &str)]) -> IptcData {
+        let mut data = Vec::new();
+        for (dataset, text) in datasets {
+            data.extend([0x1C, 2, *dataset]);
+            data.extend((text.len() as u16).to_be_bytes());
+            data.extend(text.as_bytes());
+        }
+        IptcData::parse(&data).unwrap()
+    }
+
+    #[test]
+    fn test_consolidate() {
+        let exif = ExifData::read(Cursor::new(tiff(
+            &[(0x010E, "EXIF description"), (0x013B, "Ann; Bob")],
+            &[(0x0112, 6)],
+        )))
+        .unwrap();
+        let iptc = iim(&[
+            (120, "IPTC description"),
+            (116, "(c) IPTC"),
+            (25, "harbour"),
+            (55, "20240506"),
+        ]);
+        let mut xmp = XmpMeta::new();
+        xmp.set_localized_text(ns::DC, "rights", "", "x-default", "(c) XMP")
+            .unwrap();
+        xmp.set_property(ns::PHOTOSHOP, "DateCreated", "2024-05-07".into())
+            .unwrap();
+        xmp.set_rating(3).unwrap();
+
+        // EXIF first, then XMP, then IPTC
+        let sources = MwgSources::new()
+            .xmp(&xmp)
+            .exif(&exif)
+            .iptc(&iptc, IptcDigest::Matching);
+        let metadata = MwgMetadata::consolidate(&sources).unwrap();
+        let description = metadata.description.unwrap();
+        assert_eq!(description.value, "EXIF description");
+        assert_eq!(description.source, MwgSource::Exif);
+        let creators = metadata.creators.unwrap();
+        assert_eq!(creators.value, ["Ann", "Bob"]);
+        assert_eq!(creators.source, MwgSource::Exif);
+        assert_eq!(metadata.copyright.unwrap().source, MwgSource::Xmp);
+        let date = metadata.date.unwrap();
+        assert_eq!(
+            (date.value.to_string(), date.source),
+            ("2024-05-07".to_string(), MwgSource::Xmp)
+        );
+        let keywords = metadata.keywords.unwrap();
+        assert_eq!(
+            (keywords.value, keywords.source),
+            (vec!["harbour".to_string()], MwgSource::Iptc)
+        );
+        assert_eq!(metadata.orientation.unwrap().value, 6);
+        assert_eq!(metadata.rating.unwrap().value, 3);
+
+        // An edited IIM is preferred over the XMP
+        let sources = MwgSources::new()
+            .xmp(&xmp)
+            .iptc(&iptc, IptcDigest::Mismatched);
+        let metadata = MwgMetadata::consolidate(&sources).unwrap();
+        let copyright = metadata.copyright.unwrap();
+        assert_eq!(
+            (copyright.value.as_str(), copyright.source),
+            ("(c) IPTC", MwgSource::Iptc)
+        );
+        assert_eq!(metadata.date.unwrap().value.to_string(), "2024-05-06");
+        assert_eq!(metadata.orientation, None);
+
+        assert_eq!(
+            MwgMetadata::consolidate(&MwgSources::new()).unwrap(),
+            MwgMetadata::default()
+        );
+        assert_eq!(
+            MwgMetadata::read_jpeg(Cursor::new(b"\xFF\xD8\xFF\xD9")).unwrap(),
+            MwgMetadata::default()
+        );
+    }
+}
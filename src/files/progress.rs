@@ -0,0 +1,351 @@
+//! Progress reporting and cancellation of long file operations
+//!
+//! Opening and saving large files, such as multi-gigabyte videos that have
+//! to be rewritten, can take a while. A [`ProgressCallback`] set with
+//! [`XmpFile::set_progress_callback`] is called periodically while the file
+//! data is read or written, and can cancel the operation by returning
+//! `false`, like the callback of `SetProgressCallback` in the Adobe XMP
+//! Toolkit. A cancelled operation returns `XmpError::Cancelled`; a
+//! cancelled save leaves an atomically saved or renamed target untouched.
+//!
+//! [`BatchOptions::progress`](crate::files::batch::BatchOptions::progress)
+//! reports the progress of a batch in files instead of bytes.
+//!
+//! [`XmpFile::set_progress_callback`]: crate::files::XmpFile::set_progress_callback
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::sync::atomic::{AtomicBool, Ordering};
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//! use xmpkit::files::progress::ProgressCallback;
+//! use xmpkit::XmpFile;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let cancel = Arc::new(AtomicBool::new(false));
+//! let flag = cancel.clone();
+//! let callback = ProgressCallback::new(move |progress| {
+//!     println!("{:.0}%", progress.fraction_done() * 100.0);
+//!     !flag.load(Ordering::Relaxed)
+//! })
+//! .interval(Duration::from_millis(250));
+//!
+//! let mut file = XmpFile::new();
+//! file.set_progress_callback(Some(callback));
+//! file.open("video.mp4")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::core::error::{XmpError, XmpResult};
+
+/// Progress of an operation, passed to a [`ProgressCallback`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Time since the operation started
+    pub elapsed: Duration,
+    /// Amount of work done: bytes for files, files for batches
+    pub processed: u64,
+    /// Expected amount of work, in the unit of `processed`
+    ///
+    /// When saving, this is the size of the original file, which the new
+    /// file can exceed a little.
+    pub total: u64,
+}
+
+impl Progress {
+    /// Get the fraction of the work done, between 0 and 1
+    pub fn fraction_done(&self) -> f32 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        (self.processed as f64 / self.total as f64).min(1.0) as f32
+    }
+
+    /// Estimate the time left from the rate so far
+    ///
+    /// Returns `None` until some work is done.
+    pub fn remaining(&self) -> Option<Duration> {
+        let fraction = self.fraction_done() as f64;
+        if fraction <= 0.0 {
+            return None;
+        }
+        Some(self.elapsed.mul_f64((1.0 - fraction) / fraction))
+    }
+}
+
+/// Callback reporting the progress of an operation
+///
+/// The function is called at most once per interval (one second by
+/// default), and returns `false` to cancel the operation. It is shared by
+/// clones of the callback, and may be called from several threads by a
+/// batch.
+#[derive(Clone)]
+pub struct ProgressCallback {
+    /// The function to call
+    report: Arc<dyn Fn(&Progress) -> bool + Send + Sync>,
+    /// Minimum time between two calls
+    interval: Duration,
+    /// Whether to also call the function when the operation starts and ends
+    send_start_stop: bool,
+}
+
+impl ProgressCallback {
+    /// Create a callback calling `report`.
+    pub fn new<F>(report: F) -> Self
+    where
+        F: Fn(&Progress) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            report: Arc::new(report),
+            interval: Duration::from_secs(1),
+            send_start_stop: false,
+        }
+    }
+
+    /// Set the minimum time between two calls (default: one second).
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Also call the function when the operation starts and when it
+    /// completes.
+    pub fn send_start_stop(mut self) -> Self {
+        self.send_start_stop = true;
+        self
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressCallback")
+            .field("interval", &self.interval)
+            .field("send_start_stop", &self.send_start_stop)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Progress of a running operation, shared by the threads of a batch
+pub(crate) struct ProgressTracker<'a> {
+    callback: &'a ProgressCallback,
+    start: Instant,
+    total: u64,
+    state: Mutex<TrackerState>,
+}
+
+/// Mutable state of a [`ProgressTracker`]
+struct TrackerState {
+    processed: u64,
+    last_report: Instant,
+    cancelled: bool,
+}
+
+impl<'a> ProgressTracker<'a> {
+    /// Start tracking an operation of `total` units of work
+    pub(crate) fn start(callback: &'a ProgressCallback, total: u64) -> Self {
+        let start = Instant::now();
+        let tracker = Self {
+            callback,
+            start,
+            total,
+            state: Mutex::new(TrackerState {
+                processed: 0,
+                last_report: start,
+                cancelled: false,
+            }),
+        };
+        if callback.send_start_stop {
+            tracker.report(0);
+        }
+        tracker
+    }
+
+    /// Record that `processed` units of work are done, if more than before
+    ///
+    /// Returns `false` once the operation is cancelled.
+    pub(crate) fn advance_to(&self, processed: u64) -> bool {
+        self.update(|done| (*done).max(processed))
+    }
+
+    /// Record that `amount` more units of work are done
+    ///
+    /// Returns `false` once the operation is cancelled.
+    pub(crate) fn add(&self, amount: u64) -> bool {
+        self.update(|done| done.saturating_add(amount))
+    }
+
+    /// Check if the operation was cancelled
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.lock().cancelled
+    }
+
+    /// Finish the operation, turning the error of a cancelled operation
+    /// into `XmpError::Cancelled`
+    pub(crate) fn finish<T>(self, result: XmpResult<T>) -> XmpResult<T> {
+        if self.is_cancelled() {
+            return Err(XmpError::Cancelled);
+        }
+        if result.is_ok() && self.callback.send_start_stop {
+            let processed = self.lock().processed.max(self.total);
+            self.report(processed);
+        }
+        result
+    }
+
+    /// Wrap a reader at `position`, advancing to its position
+    pub(crate) fn reader<R>(&self, inner: R, position: u64) -> ProgressReader<'_, R> {
+        ProgressReader {
+            inner,
+            tracker: self,
+            position,
+        }
+    }
+
+    /// Wrap a writer, advancing by the bytes written
+    pub(crate) fn writer<W>(&self, inner: W) -> ProgressWriter<'_, W> {
+        ProgressWriter {
+            inner,
+            tracker: self,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TrackerState> {
+        self.state.lock().expect("progress lock poisoned")
+    }
+
+    /// Update the work done, and call the callback if the interval elapsed
+    fn update(&self, processed: impl FnOnce(&u64) -> u64) -> bool {
+        let processed = {
+            let mut state = self.lock();
+            if state.cancelled {
+                return false;
+            }
+            state.processed = processed(&state.processed);
+            let now = Instant::now();
+            if now.duration_since(state.last_report) < self.callback.interval {
+                return true;
+            }
+            state.last_report = now;
+            state.processed
+        };
+        // Called without the lock, so that the function can take its time
+        self.report(processed)
+    }
+
+    /// Call the callback, recording a cancellation
+    fn report(&self, processed: u64) -> bool {
+        let progress = Progress {
+            elapsed: self.start.elapsed(),
+            processed,
+            total: self.total,
+        };
+        if (self.callback.report)(&progress) {
+            return true;
+        }
+        self.lock().cancelled = true;
+        false
+    }
+}
+
+/// Error returned by the wrappers of a cancelled operation
+fn cancelled() -> io::Error {
+    io::Error::other("Cancelled by the progress callback")
+}
+
+/// Reader reporting its position to a [`ProgressTracker`]
+pub(crate) struct ProgressReader<'t, R> {
+    inner: R,
+    tracker: &'t ProgressTracker<'t>,
+    position: u64,
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.position += read as u64;
+        if !self.tracker.advance_to(self.position) {
+            return Err(cancelled());
+        }
+        Ok(read)
+    }
+}
+
+impl<R: Seek> Seek for ProgressReader<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        if self.tracker.is_cancelled() {
+            return Err(cancelled());
+        }
+        self.position = self.inner.seek(pos)?;
+        Ok(self.position)
+    }
+}
+
+/// Writer reporting the bytes written to a [`ProgressTracker`]
+pub(crate) struct ProgressWriter<'t, W> {
+    inner: W,
+    tracker: &'t ProgressTracker<'t>,
+}
+
+impl<W: Write> Write for ProgressWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        if !self.tracker.add(written as u64) {
+            return Err(cancelled());
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Seek> Seek for ProgressWriter<'_, W> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_tracker_reports_and_cancels() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let callback = ProgressCallback::new(move |progress| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            progress.processed < 6
+        })
+        .interval(Duration::ZERO)
+        .send_start_stop();
+
+        let tracker = ProgressTracker::start(&callback, 10);
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+        let mut reader = tracker.reader(io::Cursor::new(vec![0u8; 10]), 0);
+        let mut buf = [0u8; 4];
+        reader.read_exact(&mut buf).unwrap();
+        assert!(reader.read_exact(&mut buf).is_err());
+        assert!(tracker.is_cancelled());
+        assert!(!tracker.add(1));
+        assert!(matches!(tracker.finish(Ok(())), Err(XmpError::Cancelled)));
+        assert_eq!(calls.load(Ordering::Relaxed), 3);
+
+        let progress = Progress {
+            elapsed: Duration::from_secs(3),
+            processed: 25,
+            total: 100,
+        };
+        assert_eq!(progress.fraction_done(), 0.25);
+        assert_eq!(progress.remaining(), Some(Duration::from_secs(9)));
+    }
+}
@@ -3,43 +3,210 @@
 //! This module provides a registry system for managing file format handlers.
 //! Handlers can be registered and looked up by file extension or format detection.
 
-use crate::core::error::XmpResult;
-use crate::files::handler::FileHandler;
+use crate::core::error::{XmpError, XmpResult};
+use crate::files::format::{FileFormat, FormatCapabilities};
+use crate::files::handler::{CustomHandler, FileHandler};
 use std::io::{Read, Seek, Write};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Global list of the handlers registered with [`register_handler`]
+static CUSTOM_HANDLERS: OnceLock<RwLock<Vec<Arc<dyn CustomHandler>>>> = OnceLock::new();
+
+fn get_custom_handlers() -> &'static RwLock<Vec<Arc<dyn CustomHandler>>> {
+    CUSTOM_HANDLERS.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// Register a handler for a file format that xmpkit doesn't support
+///
+/// Registered handlers are used by all [`XmpFile`](crate::files::XmpFile)s
+/// and [`HandlerRegistry`]s created afterwards. They are tried before the
+/// built-in handlers, so they can also take over files that a built-in
+/// handler would recognize, such as TIFF-based containers. Registering a
+/// handler with the [`format_name`](CustomHandler::format_name) of a
+/// registered handler replaces it.
+pub fn register_handler(handler: Box<dyn CustomHandler>) {
+    // RwLock::write() only fails if the lock is poisoned
+    let mut handlers = get_custom_handlers()
+        .write()
+        .expect("Handler registry lock poisoned");
+    let handler: Arc<dyn CustomHandler> = Arc::from(handler);
+    match handlers
+        .iter_mut()
+        .find(|h| h.format_name() == handler.format_name())
+    {
+        Some(existing) => *existing = handler,
+        None => handlers.push(handler),
+    }
+}
 
 /// Enum of supported file handlers
 #[derive(Debug, Clone)]
 #[non_exhaustive]
 pub enum Handler {
+    #[cfg(feature = "aiff")]
+    Aiff(crate::files::formats::aiff::AiffHandler),
+    #[cfg(feature = "asf")]
+    Asf(crate::files::formats::asf::AsfHandler),
+    #[cfg(feature = "avi")]
+    Avi(crate::files::formats::avi::AviHandler),
+    #[cfg(feature = "avif")]
+    Avif(crate::files::formats::avif::AvifHandler),
+    #[cfg(feature = "eps")]
+    Eps(crate::files::formats::eps::EpsHandler),
+    #[cfg(feature = "flac")]
+    Flac(crate::files::formats::flac::FlacHandler),
     #[cfg(feature = "gif")]
     Gif(crate::files::formats::gif::GifHandler),
+    #[cfg(feature = "heif")]
+    Heif(crate::files::formats::heif::HeifHandler),
+    #[cfg(feature = "indd")]
+    Indd(crate::files::formats::indd::InddHandler),
+    #[cfg(feature = "jp2")]
+    Jp2(crate::files::formats::jp2::Jp2Handler),
     #[cfg(feature = "jpeg")]
     Jpeg(crate::files::formats::jpeg::JpegHandler),
+    #[cfg(feature = "jxl")]
+    Jxl(crate::files::formats::jxl::JxlHandler),
+    #[cfg(feature = "mkv")]
+    Mkv(crate::files::formats::mkv::MkvHandler),
+    #[cfg(feature = "mov")]
+    Mov(crate::files::formats::mov::MovHandler),
     #[cfg(feature = "mp3")]
     Mp3(crate::files::formats::mp3::Mp3Handler),
     #[cfg(feature = "mp4")]
     Mp4(crate::files::formats::mp4::Mp4Handler),
+    #[cfg(feature = "ogg")]
+    Ogg(crate::files::formats::ogg::OggHandler),
+    #[cfg(feature = "pdf")]
+    Pdf(crate::files::formats::pdf::PdfHandler),
     #[cfg(feature = "png")]
     Png(crate::files::formats::png::PngHandler),
+    #[cfg(feature = "psd")]
+    Psd(crate::files::formats::psd::PsdHandler),
+    Sidecar(crate::files::formats::sidecar::SidecarHandler),
+    /// A handler registered with [`register_handler`]
+    Custom(Arc<dyn CustomHandler>),
+    #[cfg(feature = "svg")]
+    Svg(crate::files::formats::svg::SvgHandler),
     #[cfg(feature = "tiff")]
     Tiff(crate::files::formats::tiff::TiffHandler),
+    #[cfg(feature = "wav")]
+    Wav(crate::files::formats::wav::WavHandler),
+    #[cfg(feature = "webp")]
+    Webp(crate::files::formats::webp::WebpHandler),
+}
+
+impl Handler {
+    /// Get the file format of the handler
+    pub fn format(&self) -> FileFormat {
+        match self {
+            #[cfg(feature = "aiff")]
+            Handler::Aiff(_) => FileFormat::Aiff,
+            #[cfg(feature = "asf")]
+            Handler::Asf(_) => FileFormat::Asf,
+            #[cfg(feature = "avi")]
+            Handler::Avi(_) => FileFormat::Avi,
+            #[cfg(feature = "avif")]
+            Handler::Avif(_) => FileFormat::Avif,
+            #[cfg(feature = "eps")]
+            Handler::Eps(_) => FileFormat::Eps,
+            #[cfg(feature = "flac")]
+            Handler::Flac(_) => FileFormat::Flac,
+            #[cfg(feature = "gif")]
+            Handler::Gif(_) => FileFormat::Gif,
+            #[cfg(feature = "heif")]
+            Handler::Heif(_) => FileFormat::Heif,
+            #[cfg(feature = "indd")]
+            Handler::Indd(_) => FileFormat::Indd,
+            #[cfg(feature = "jp2")]
+            Handler::Jp2(_) => FileFormat::Jp2,
+            #[cfg(feature = "jpeg")]
+            Handler::Jpeg(_) => FileFormat::Jpeg,
+            #[cfg(feature = "jxl")]
+            Handler::Jxl(_) => FileFormat::Jxl,
+            #[cfg(feature = "mkv")]
+            Handler::Mkv(_) => FileFormat::Mkv,
+            #[cfg(feature = "mov")]
+            Handler::Mov(_) => FileFormat::Mov,
+            #[cfg(feature = "mp3")]
+            Handler::Mp3(_) => FileFormat::Mp3,
+            #[cfg(feature = "mp4")]
+            Handler::Mp4(_) => FileFormat::Mp4,
+            #[cfg(feature = "ogg")]
+            Handler::Ogg(_) => FileFormat::Ogg,
+            #[cfg(feature = "pdf")]
+            Handler::Pdf(_) => FileFormat::Pdf,
+            #[cfg(feature = "png")]
+            Handler::Png(_) => FileFormat::Png,
+            #[cfg(feature = "psd")]
+            Handler::Psd(_) => FileFormat::Psd,
+            Handler::Sidecar(_) => FileFormat::Xmp,
+            Handler::Custom(h) => FileFormat::Custom(h.format_name()),
+            #[cfg(feature = "svg")]
+            Handler::Svg(_) => FileFormat::Svg,
+            #[cfg(feature = "tiff")]
+            Handler::Tiff(_) => FileFormat::Tiff,
+            #[cfg(feature = "wav")]
+            Handler::Wav(_) => FileFormat::Wav,
+            #[cfg(feature = "webp")]
+            Handler::Webp(_) => FileFormat::Webp,
+        }
+    }
 }
 
 impl FileHandler for Handler {
     fn can_handle<R: Read + Seek>(&self, reader: &mut R) -> XmpResult<bool> {
         match self {
+            #[cfg(feature = "aiff")]
+            Handler::Aiff(h) => h.can_handle(reader),
+            #[cfg(feature = "asf")]
+            Handler::Asf(h) => h.can_handle(reader),
+            #[cfg(feature = "avi")]
+            Handler::Avi(h) => h.can_handle(reader),
+            #[cfg(feature = "avif")]
+            Handler::Avif(h) => h.can_handle(reader),
+            #[cfg(feature = "eps")]
+            Handler::Eps(h) => h.can_handle(reader),
+            #[cfg(feature = "flac")]
+            Handler::Flac(h) => h.can_handle(reader),
             #[cfg(feature = "gif")]
             Handler::Gif(h) => h.can_handle(reader),
+            #[cfg(feature = "heif")]
+            Handler::Heif(h) => h.can_handle(reader),
+            #[cfg(feature = "indd")]
+            Handler::Indd(h) => h.can_handle(reader),
+            #[cfg(feature = "jp2")]
+            Handler::Jp2(h) => h.can_handle(reader),
             #[cfg(feature = "jpeg")]
             Handler::Jpeg(h) => h.can_handle(reader),
+            #[cfg(feature = "jxl")]
+            Handler::Jxl(h) => h.can_handle(reader),
+            #[cfg(feature = "mkv")]
+            Handler::Mkv(h) => h.can_handle(reader),
+            #[cfg(feature = "mov")]
+            Handler::Mov(h) => h.can_handle(reader),
             #[cfg(feature = "mp3")]
             Handler::Mp3(h) => h.can_handle(reader),
             #[cfg(feature = "mp4")]
             Handler::Mp4(h) => h.can_handle(reader),
+            #[cfg(feature = "ogg")]
+            Handler::Ogg(h) => h.can_handle(reader),
+            #[cfg(feature = "pdf")]
+            Handler::Pdf(h) => h.can_handle(reader),
             #[cfg(feature = "png")]
             Handler::Png(h) => h.can_handle(reader),
+            #[cfg(feature = "psd")]
+            Handler::Psd(h) => h.can_handle(reader),
+            Handler::Sidecar(h) => h.can_handle(reader),
+            Handler::Custom(h) => h.can_handle(reader),
+            #[cfg(feature = "svg")]
+            Handler::Svg(h) => h.can_handle(reader),
             #[cfg(feature = "tiff")]
             Handler::Tiff(h) => h.can_handle(reader),
+            #[cfg(feature = "wav")]
+            Handler::Wav(h) => h.can_handle(reader),
+            #[cfg(feature = "webp")]
+            Handler::Webp(h) => h.can_handle(reader),
         }
     }
 
@@ -48,18 +215,56 @@ impl FileHandler for Handler {
         reader: &mut R,
     ) -> XmpResult<Option<crate::core::metadata::XmpMeta>> {
         match self {
+            #[cfg(feature = "aiff")]
+            Handler::Aiff(h) => h.read_xmp(reader),
+            #[cfg(feature = "asf")]
+            Handler::Asf(h) => h.read_xmp(reader),
+            #[cfg(feature = "avi")]
+            Handler::Avi(h) => h.read_xmp(reader),
+            #[cfg(feature = "avif")]
+            Handler::Avif(h) => h.read_xmp(reader),
+            #[cfg(feature = "eps")]
+            Handler::Eps(h) => h.read_xmp(reader),
+            #[cfg(feature = "flac")]
+            Handler::Flac(h) => h.read_xmp(reader),
             #[cfg(feature = "gif")]
             Handler::Gif(h) => h.read_xmp(reader),
+            #[cfg(feature = "heif")]
+            Handler::Heif(h) => h.read_xmp(reader),
+            #[cfg(feature = "indd")]
+            Handler::Indd(h) => h.read_xmp(reader),
+            #[cfg(feature = "jp2")]
+            Handler::Jp2(h) => h.read_xmp(reader),
             #[cfg(feature = "jpeg")]
             Handler::Jpeg(h) => h.read_xmp(reader),
+            #[cfg(feature = "jxl")]
+            Handler::Jxl(h) => h.read_xmp(reader),
+            #[cfg(feature = "mkv")]
+            Handler::Mkv(h) => h.read_xmp(reader),
+            #[cfg(feature = "mov")]
+            Handler::Mov(h) => h.read_xmp(reader),
             #[cfg(feature = "mp3")]
             Handler::Mp3(h) => h.read_xmp(reader),
             #[cfg(feature = "mp4")]
             Handler::Mp4(h) => h.read_xmp(reader),
+            #[cfg(feature = "ogg")]
+            Handler::Ogg(h) => h.read_xmp(reader),
+            #[cfg(feature = "pdf")]
+            Handler::Pdf(h) => h.read_xmp(reader),
             #[cfg(feature = "png")]
             Handler::Png(h) => h.read_xmp(reader),
+            #[cfg(feature = "psd")]
+            Handler::Psd(h) => h.read_xmp(reader),
+            Handler::Sidecar(h) => h.read_xmp(reader),
+            Handler::Custom(h) => h.read_xmp(reader),
+            #[cfg(feature = "svg")]
+            Handler::Svg(h) => h.read_xmp(reader),
             #[cfg(feature = "tiff")]
             Handler::Tiff(h) => h.read_xmp(reader),
+            #[cfg(feature = "wav")]
+            Handler::Wav(h) => h.read_xmp(reader),
+            #[cfg(feature = "webp")]
+            Handler::Webp(h) => h.read_xmp(reader),
         }
     }
 
@@ -70,52 +275,520 @@ impl FileHandler for Handler {
         meta: &crate::core::metadata::XmpMeta,
     ) -> XmpResult<()> {
         match self {
+            #[cfg(feature = "aiff")]
+            Handler::Aiff(h) => h.write_xmp(reader, writer, meta),
+            #[cfg(feature = "asf")]
+            Handler::Asf(h) => h.write_xmp(reader, writer, meta),
+            #[cfg(feature = "avi")]
+            Handler::Avi(h) => h.write_xmp(reader, writer, meta),
+            #[cfg(feature = "avif")]
+            Handler::Avif(h) => h.write_xmp(reader, writer, meta),
+            #[cfg(feature = "eps")]
+            Handler::Eps(h) => h.write_xmp(reader, writer, meta),
+            #[cfg(feature = "flac")]
+            Handler::Flac(h) => h.write_xmp(reader, writer, meta),
             #[cfg(feature = "gif")]
             Handler::Gif(h) => h.write_xmp(reader, writer, meta),
+            #[cfg(feature = "heif")]
+            Handler::Heif(h) => h.write_xmp(reader, writer, meta),
+            #[cfg(feature = "indd")]
+            Handler::Indd(h) => h.write_xmp(reader, writer, meta),
+            #[cfg(feature = "jp2")]
+            Handler::Jp2(h) => h.write_xmp(reader, writer, meta),
             #[cfg(feature = "jpeg")]
             Handler::Jpeg(h) => h.write_xmp(reader, writer, meta),
+            #[cfg(feature = "jxl")]
+            Handler::Jxl(h) => h.write_xmp(reader, writer, meta),
+            #[cfg(feature = "mkv")]
+            Handler::Mkv(h) => h.write_xmp(reader, writer, meta),
+            #[cfg(feature = "mov")]
+            Handler::Mov(h) => h.write_xmp(reader, writer, meta),
             #[cfg(feature = "mp3")]
             Handler::Mp3(h) => h.write_xmp(reader, writer, meta),
             #[cfg(feature = "mp4")]
             Handler::Mp4(h) => h.write_xmp(reader, writer, meta),
+            #[cfg(feature = "ogg")]
+            Handler::Ogg(h) => h.write_xmp(reader, writer, meta),
+            #[cfg(feature = "pdf")]
+            Handler::Pdf(h) => h.write_xmp(reader, writer, meta),
             #[cfg(feature = "png")]
             Handler::Png(h) => h.write_xmp(reader, writer, meta),
+            #[cfg(feature = "psd")]
+            Handler::Psd(h) => h.write_xmp(reader, writer, meta),
+            Handler::Sidecar(h) => h.write_xmp(reader, writer, meta),
+            Handler::Custom(h) if !h.capabilities().can_write => Err(XmpError::NotSupported(
+                format!("Writing {} files", h.format_name()),
+            )),
+            Handler::Custom(h) => h.write_xmp(reader, writer, meta),
+            #[cfg(feature = "svg")]
+            Handler::Svg(h) => h.write_xmp(reader, writer, meta),
             #[cfg(feature = "tiff")]
             Handler::Tiff(h) => h.write_xmp(reader, writer, meta),
+            #[cfg(feature = "wav")]
+            Handler::Wav(h) => h.write_xmp(reader, writer, meta),
+            #[cfg(feature = "webp")]
+            Handler::Webp(h) => h.write_xmp(reader, writer, meta),
+        }
+    }
+
+    fn import_legacy<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        meta: &mut crate::core::metadata::XmpMeta,
+    ) -> XmpResult<bool> {
+        match self {
+            #[cfg(feature = "aiff")]
+            Handler::Aiff(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "asf")]
+            Handler::Asf(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "avi")]
+            Handler::Avi(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "avif")]
+            Handler::Avif(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "eps")]
+            Handler::Eps(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "flac")]
+            Handler::Flac(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "gif")]
+            Handler::Gif(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "heif")]
+            Handler::Heif(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "indd")]
+            Handler::Indd(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "jp2")]
+            Handler::Jp2(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "jpeg")]
+            Handler::Jpeg(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "jxl")]
+            Handler::Jxl(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "mkv")]
+            Handler::Mkv(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "mov")]
+            Handler::Mov(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "mp3")]
+            Handler::Mp3(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "mp4")]
+            Handler::Mp4(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "ogg")]
+            Handler::Ogg(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "pdf")]
+            Handler::Pdf(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "png")]
+            Handler::Png(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "psd")]
+            Handler::Psd(h) => h.import_legacy(reader, meta),
+            Handler::Sidecar(h) => h.import_legacy(reader, meta),
+            Handler::Custom(_) => Ok(false),
+            #[cfg(feature = "svg")]
+            Handler::Svg(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "tiff")]
+            Handler::Tiff(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "wav")]
+            Handler::Wav(h) => h.import_legacy(reader, meta),
+            #[cfg(feature = "webp")]
+            Handler::Webp(h) => h.import_legacy(reader, meta),
+        }
+    }
+
+    fn read_thumbnail<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> XmpResult<Option<crate::core::metadata::Thumbnail>> {
+        match self {
+            #[cfg(feature = "aiff")]
+            Handler::Aiff(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "asf")]
+            Handler::Asf(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "avi")]
+            Handler::Avi(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "avif")]
+            Handler::Avif(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "eps")]
+            Handler::Eps(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "flac")]
+            Handler::Flac(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "gif")]
+            Handler::Gif(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "heif")]
+            Handler::Heif(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "indd")]
+            Handler::Indd(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "jp2")]
+            Handler::Jp2(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "jpeg")]
+            Handler::Jpeg(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "jxl")]
+            Handler::Jxl(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "mkv")]
+            Handler::Mkv(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "mov")]
+            Handler::Mov(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "mp3")]
+            Handler::Mp3(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "mp4")]
+            Handler::Mp4(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "ogg")]
+            Handler::Ogg(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "pdf")]
+            Handler::Pdf(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "png")]
+            Handler::Png(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "psd")]
+            Handler::Psd(h) => h.read_thumbnail(reader),
+            Handler::Sidecar(h) => h.read_thumbnail(reader),
+            Handler::Custom(_) => Ok(None),
+            #[cfg(feature = "svg")]
+            Handler::Svg(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "tiff")]
+            Handler::Tiff(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "wav")]
+            Handler::Wav(h) => h.read_thumbnail(reader),
+            #[cfg(feature = "webp")]
+            Handler::Webp(h) => h.read_thumbnail(reader),
+        }
+    }
+
+    fn export_legacy<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        meta: &crate::core::metadata::XmpMeta,
+        options: &crate::files::file::SaveOptions,
+    ) -> XmpResult<bool> {
+        match self {
+            #[cfg(feature = "aiff")]
+            Handler::Aiff(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "asf")]
+            Handler::Asf(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "avi")]
+            Handler::Avi(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "avif")]
+            Handler::Avif(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "eps")]
+            Handler::Eps(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "flac")]
+            Handler::Flac(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "gif")]
+            Handler::Gif(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "heif")]
+            Handler::Heif(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "indd")]
+            Handler::Indd(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "jp2")]
+            Handler::Jp2(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "jpeg")]
+            Handler::Jpeg(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "jxl")]
+            Handler::Jxl(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "mkv")]
+            Handler::Mkv(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "mov")]
+            Handler::Mov(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "mp3")]
+            Handler::Mp3(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "mp4")]
+            Handler::Mp4(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "ogg")]
+            Handler::Ogg(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "pdf")]
+            Handler::Pdf(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "png")]
+            Handler::Png(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "psd")]
+            Handler::Psd(h) => h.export_legacy(reader, writer, meta, options),
+            Handler::Sidecar(h) => h.export_legacy(reader, writer, meta, options),
+            Handler::Custom(_) => Ok(false),
+            #[cfg(feature = "svg")]
+            Handler::Svg(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "tiff")]
+            Handler::Tiff(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "wav")]
+            Handler::Wav(h) => h.export_legacy(reader, writer, meta, options),
+            #[cfg(feature = "webp")]
+            Handler::Webp(h) => h.export_legacy(reader, writer, meta, options),
+        }
+    }
+
+    fn remove_metadata<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        all: bool,
+    ) -> XmpResult<()> {
+        match self {
+            #[cfg(feature = "aiff")]
+            Handler::Aiff(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "asf")]
+            Handler::Asf(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "avi")]
+            Handler::Avi(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "avif")]
+            Handler::Avif(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "eps")]
+            Handler::Eps(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "flac")]
+            Handler::Flac(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "gif")]
+            Handler::Gif(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "heif")]
+            Handler::Heif(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "indd")]
+            Handler::Indd(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "jp2")]
+            Handler::Jp2(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "jpeg")]
+            Handler::Jpeg(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "jxl")]
+            Handler::Jxl(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "mkv")]
+            Handler::Mkv(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "mov")]
+            Handler::Mov(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "mp3")]
+            Handler::Mp3(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "mp4")]
+            Handler::Mp4(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "ogg")]
+            Handler::Ogg(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "pdf")]
+            Handler::Pdf(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "png")]
+            Handler::Png(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "psd")]
+            Handler::Psd(h) => h.remove_metadata(reader, writer, all),
+            Handler::Sidecar(h) => h.remove_metadata(reader, writer, all),
+            Handler::Custom(h) => Err(XmpError::NotSupported(format!(
+                "Removing metadata from {} files",
+                h.format_name()
+            ))),
+            #[cfg(feature = "svg")]
+            Handler::Svg(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "tiff")]
+            Handler::Tiff(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "wav")]
+            Handler::Wav(h) => h.remove_metadata(reader, writer, all),
+            #[cfg(feature = "webp")]
+            Handler::Webp(h) => h.remove_metadata(reader, writer, all),
+        }
+    }
+
+    fn supports_in_place_update(&self) -> bool {
+        match self {
+            #[cfg(feature = "aiff")]
+            Handler::Aiff(h) => h.supports_in_place_update(),
+            #[cfg(feature = "asf")]
+            Handler::Asf(h) => h.supports_in_place_update(),
+            #[cfg(feature = "avi")]
+            Handler::Avi(h) => h.supports_in_place_update(),
+            #[cfg(feature = "avif")]
+            Handler::Avif(h) => h.supports_in_place_update(),
+            #[cfg(feature = "eps")]
+            Handler::Eps(h) => h.supports_in_place_update(),
+            #[cfg(feature = "flac")]
+            Handler::Flac(h) => h.supports_in_place_update(),
+            #[cfg(feature = "gif")]
+            Handler::Gif(h) => h.supports_in_place_update(),
+            #[cfg(feature = "heif")]
+            Handler::Heif(h) => h.supports_in_place_update(),
+            #[cfg(feature = "indd")]
+            Handler::Indd(h) => h.supports_in_place_update(),
+            #[cfg(feature = "jp2")]
+            Handler::Jp2(h) => h.supports_in_place_update(),
+            #[cfg(feature = "jpeg")]
+            Handler::Jpeg(h) => h.supports_in_place_update(),
+            #[cfg(feature = "jxl")]
+            Handler::Jxl(h) => h.supports_in_place_update(),
+            #[cfg(feature = "mkv")]
+            Handler::Mkv(h) => h.supports_in_place_update(),
+            #[cfg(feature = "mov")]
+            Handler::Mov(h) => h.supports_in_place_update(),
+            #[cfg(feature = "mp3")]
+            Handler::Mp3(h) => h.supports_in_place_update(),
+            #[cfg(feature = "mp4")]
+            Handler::Mp4(h) => h.supports_in_place_update(),
+            #[cfg(feature = "ogg")]
+            Handler::Ogg(h) => h.supports_in_place_update(),
+            #[cfg(feature = "pdf")]
+            Handler::Pdf(h) => h.supports_in_place_update(),
+            #[cfg(feature = "png")]
+            Handler::Png(h) => h.supports_in_place_update(),
+            #[cfg(feature = "psd")]
+            Handler::Psd(h) => h.supports_in_place_update(),
+            Handler::Sidecar(h) => h.supports_in_place_update(),
+            Handler::Custom(h) => h.capabilities().supports_in_place,
+            #[cfg(feature = "svg")]
+            Handler::Svg(h) => h.supports_in_place_update(),
+            #[cfg(feature = "tiff")]
+            Handler::Tiff(h) => h.supports_in_place_update(),
+            #[cfg(feature = "wav")]
+            Handler::Wav(h) => h.supports_in_place_update(),
+            #[cfg(feature = "webp")]
+            Handler::Webp(h) => h.supports_in_place_update(),
+        }
+    }
+
+    fn capabilities(&self) -> FormatCapabilities {
+        match self {
+            #[cfg(feature = "aiff")]
+            Handler::Aiff(h) => h.capabilities(),
+            #[cfg(feature = "asf")]
+            Handler::Asf(h) => h.capabilities(),
+            #[cfg(feature = "avi")]
+            Handler::Avi(h) => h.capabilities(),
+            #[cfg(feature = "avif")]
+            Handler::Avif(h) => h.capabilities(),
+            #[cfg(feature = "eps")]
+            Handler::Eps(h) => h.capabilities(),
+            #[cfg(feature = "flac")]
+            Handler::Flac(h) => h.capabilities(),
+            #[cfg(feature = "gif")]
+            Handler::Gif(h) => h.capabilities(),
+            #[cfg(feature = "heif")]
+            Handler::Heif(h) => h.capabilities(),
+            #[cfg(feature = "indd")]
+            Handler::Indd(h) => h.capabilities(),
+            #[cfg(feature = "jp2")]
+            Handler::Jp2(h) => h.capabilities(),
+            #[cfg(feature = "jpeg")]
+            Handler::Jpeg(h) => h.capabilities(),
+            #[cfg(feature = "jxl")]
+            Handler::Jxl(h) => h.capabilities(),
+            #[cfg(feature = "mkv")]
+            Handler::Mkv(h) => h.capabilities(),
+            #[cfg(feature = "mov")]
+            Handler::Mov(h) => h.capabilities(),
+            #[cfg(feature = "mp3")]
+            Handler::Mp3(h) => h.capabilities(),
+            #[cfg(feature = "mp4")]
+            Handler::Mp4(h) => h.capabilities(),
+            #[cfg(feature = "ogg")]
+            Handler::Ogg(h) => h.capabilities(),
+            #[cfg(feature = "pdf")]
+            Handler::Pdf(h) => h.capabilities(),
+            #[cfg(feature = "png")]
+            Handler::Png(h) => h.capabilities(),
+            #[cfg(feature = "psd")]
+            Handler::Psd(h) => h.capabilities(),
+            Handler::Sidecar(h) => h.capabilities(),
+            Handler::Custom(h) => h.capabilities(),
+            #[cfg(feature = "svg")]
+            Handler::Svg(h) => h.capabilities(),
+            #[cfg(feature = "tiff")]
+            Handler::Tiff(h) => h.capabilities(),
+            #[cfg(feature = "wav")]
+            Handler::Wav(h) => h.capabilities(),
+            #[cfg(feature = "webp")]
+            Handler::Webp(h) => h.capabilities(),
         }
     }
 
     fn format_name(&self) -> &'static str {
         match self {
+            #[cfg(feature = "aiff")]
+            Handler::Aiff(h) => h.format_name(),
+            #[cfg(feature = "asf")]
+            Handler::Asf(h) => h.format_name(),
+            #[cfg(feature = "avi")]
+            Handler::Avi(h) => h.format_name(),
+            #[cfg(feature = "avif")]
+            Handler::Avif(h) => h.format_name(),
+            #[cfg(feature = "eps")]
+            Handler::Eps(h) => h.format_name(),
+            #[cfg(feature = "flac")]
+            Handler::Flac(h) => h.format_name(),
             #[cfg(feature = "gif")]
             Handler::Gif(h) => h.format_name(),
+            #[cfg(feature = "heif")]
+            Handler::Heif(h) => h.format_name(),
+            #[cfg(feature = "indd")]
+            Handler::Indd(h) => h.format_name(),
+            #[cfg(feature = "jp2")]
+            Handler::Jp2(h) => h.format_name(),
             #[cfg(feature = "jpeg")]
             Handler::Jpeg(h) => h.format_name(),
+            #[cfg(feature = "jxl")]
+            Handler::Jxl(h) => h.format_name(),
+            #[cfg(feature = "mkv")]
+            Handler::Mkv(h) => h.format_name(),
+            #[cfg(feature = "mov")]
+            Handler::Mov(h) => h.format_name(),
             #[cfg(feature = "mp3")]
             Handler::Mp3(h) => h.format_name(),
             #[cfg(feature = "mp4")]
             Handler::Mp4(h) => h.format_name(),
+            #[cfg(feature = "ogg")]
+            Handler::Ogg(h) => h.format_name(),
+            #[cfg(feature = "pdf")]
+            Handler::Pdf(h) => h.format_name(),
             #[cfg(feature = "png")]
             Handler::Png(h) => h.format_name(),
+            #[cfg(feature = "psd")]
+            Handler::Psd(h) => h.format_name(),
+            Handler::Sidecar(h) => h.format_name(),
+            Handler::Custom(h) => h.format_name(),
+            #[cfg(feature = "svg")]
+            Handler::Svg(h) => h.format_name(),
             #[cfg(feature = "tiff")]
             Handler::Tiff(h) => h.format_name(),
+            #[cfg(feature = "wav")]
+            Handler::Wav(h) => h.format_name(),
+            #[cfg(feature = "webp")]
+            Handler::Webp(h) => h.format_name(),
         }
     }
 
     fn extensions(&self) -> &'static [&'static str] {
         match self {
+            #[cfg(feature = "aiff")]
+            Handler::Aiff(h) => h.extensions(),
+            #[cfg(feature = "asf")]
+            Handler::Asf(h) => h.extensions(),
+            #[cfg(feature = "avi")]
+            Handler::Avi(h) => h.extensions(),
+            #[cfg(feature = "avif")]
+            Handler::Avif(h) => h.extensions(),
+            #[cfg(feature = "eps")]
+            Handler::Eps(h) => h.extensions(),
+            #[cfg(feature = "flac")]
+            Handler::Flac(h) => h.extensions(),
             #[cfg(feature = "gif")]
             Handler::Gif(h) => h.extensions(),
+            #[cfg(feature = "heif")]
+            Handler::Heif(h) => h.extensions(),
+            #[cfg(feature = "indd")]
+            Handler::Indd(h) => h.extensions(),
+            #[cfg(feature = "jp2")]
+            Handler::Jp2(h) => h.extensions(),
             #[cfg(feature = "jpeg")]
             Handler::Jpeg(h) => h.extensions(),
+            #[cfg(feature = "jxl")]
+            Handler::Jxl(h) => h.extensions(),
+            #[cfg(feature = "mkv")]
+            Handler::Mkv(h) => h.extensions(),
+            #[cfg(feature = "mov")]
+            Handler::Mov(h) => h.extensions(),
             #[cfg(feature = "mp3")]
             Handler::Mp3(h) => h.extensions(),
             #[cfg(feature = "mp4")]
             Handler::Mp4(h) => h.extensions(),
+            #[cfg(feature = "ogg")]
+            Handler::Ogg(h) => h.extensions(),
+            #[cfg(feature = "pdf")]
+            Handler::Pdf(h) => h.extensions(),
             #[cfg(feature = "png")]
             Handler::Png(h) => h.extensions(),
+            #[cfg(feature = "psd")]
+            Handler::Psd(h) => h.extensions(),
+            Handler::Sidecar(h) => h.extensions(),
+            Handler::Custom(h) => h.extensions(),
+            #[cfg(feature = "svg")]
+            Handler::Svg(h) => h.extensions(),
             #[cfg(feature = "tiff")]
             Handler::Tiff(h) => h.extensions(),
+            #[cfg(feature = "wav")]
+            Handler::Wav(h) => h.extensions(),
+            #[cfg(feature = "webp")]
+            Handler::Webp(h) => h.extensions(),
         }
     }
 }
@@ -126,11 +799,19 @@ pub struct HandlerRegistry {
 }
 
 impl HandlerRegistry {
-    /// Create a new handler registry with default handlers registered
+    /// Create a new handler registry with the handlers registered with
+    /// [`register_handler`], followed by the default handlers
     pub fn new() -> Self {
         let mut registry = Self {
             handlers: Vec::new(),
         };
+        let custom = get_custom_handlers()
+            .read()
+            .expect("Handler registry lock poisoned");
+        for handler in custom.iter() {
+            registry.register(Handler::Custom(handler.clone()));
+        }
+        drop(custom);
         registry.register_defaults();
         registry
     }
@@ -140,20 +821,59 @@ impl HandlerRegistry {
         self.handlers.push(handler);
     }
 
-    /// Register default handlers (GIF, JPEG, MP3, MP4, PNG, TIFF)
+    /// Register default handlers (one per enabled format feature)
     fn register_defaults(&mut self) {
+        #[cfg(feature = "aiff")]
+        self.register(Handler::Aiff(crate::files::formats::aiff::AiffHandler));
+        #[cfg(feature = "asf")]
+        self.register(Handler::Asf(crate::files::formats::asf::AsfHandler));
+        #[cfg(feature = "avi")]
+        self.register(Handler::Avi(crate::files::formats::avi::AviHandler));
+        #[cfg(feature = "avif")]
+        self.register(Handler::Avif(crate::files::formats::avif::AvifHandler));
+        #[cfg(feature = "eps")]
+        self.register(Handler::Eps(crate::files::formats::eps::EpsHandler));
+        #[cfg(feature = "flac")]
+        self.register(Handler::Flac(crate::files::formats::flac::FlacHandler));
         #[cfg(feature = "gif")]
         self.register(Handler::Gif(crate::files::formats::gif::GifHandler));
+        #[cfg(feature = "heif")]
+        self.register(Handler::Heif(crate::files::formats::heif::HeifHandler));
+        #[cfg(feature = "indd")]
+        self.register(Handler::Indd(crate::files::formats::indd::InddHandler));
+        #[cfg(feature = "jp2")]
+        self.register(Handler::Jp2(crate::files::formats::jp2::Jp2Handler));
         #[cfg(feature = "jpeg")]
         self.register(Handler::Jpeg(crate::files::formats::jpeg::JpegHandler));
+        #[cfg(feature = "jxl")]
+        self.register(Handler::Jxl(crate::files::formats::jxl::JxlHandler));
+        #[cfg(feature = "mkv")]
+        self.register(Handler::Mkv(crate::files::formats::mkv::MkvHandler));
+        #[cfg(feature = "mov")]
+        self.register(Handler::Mov(crate::files::formats::mov::MovHandler));
         #[cfg(feature = "mp3")]
         self.register(Handler::Mp3(crate::files::formats::mp3::Mp3Handler));
         #[cfg(feature = "mp4")]
         self.register(Handler::Mp4(crate::files::formats::mp4::Mp4Handler));
+        #[cfg(feature = "ogg")]
+        self.register(Handler::Ogg(crate::files::formats::ogg::OggHandler));
+        #[cfg(feature = "pdf")]
+        self.register(Handler::Pdf(crate::files::formats::pdf::PdfHandler));
         #[cfg(feature = "png")]
         self.register(Handler::Png(crate::files::formats::png::PngHandler));
+        #[cfg(feature = "psd")]
+        self.register(Handler::Psd(crate::files::formats::psd::PsdHandler));
+        self.register(Handler::Sidecar(
+            crate::files::formats::sidecar::SidecarHandler,
+        ));
+        #[cfg(feature = "svg")]
+        self.register(Handler::Svg(crate::files::formats::svg::SvgHandler));
         #[cfg(feature = "tiff")]
         self.register(Handler::Tiff(crate::files::formats::tiff::TiffHandler));
+        #[cfg(feature = "wav")]
+        self.register(Handler::Wav(crate::files::formats::wav::WavHandler));
+        #[cfg(feature = "webp")]
+        self.register(Handler::Webp(crate::files::formats::webp::WebpHandler));
     }
 
     /// Find a handler by file extension
@@ -173,6 +893,13 @@ impl HandlerRegistry {
             .find(|h| h.extensions().iter().any(|e| e.to_lowercase() == ext_lower))
     }
 
+    /// Find the handler of a file format
+    ///
+    /// Returns `None` if the feature of the format is not enabled.
+    pub fn find_by_format(&self, format: FileFormat) -> Option<&Handler> {
+        self.handlers.iter().find(|h| h.format() == format)
+    }
+
     /// Find a handler by format detection
     ///
     /// This method tries each registered handler's `can_handle` method
@@ -252,6 +979,97 @@ mod tests {
         assert_eq!(handler.unwrap().format_name(), "JPEG");
     }
 
+    #[test]
+    fn test_find_by_format() {
+        let registry = HandlerRegistry::new();
+        for handler in registry.handlers() {
+            assert_eq!(handler.format().name(), handler.format_name());
+            let found = registry.find_by_format(handler.format()).unwrap();
+            assert_eq!(found.format(), handler.format());
+        }
+        assert!(registry.find_by_format(FileFormat::Unknown).is_none());
+    }
+
+    /// A container holding the XMP packet after a signature
+    struct VendorHandler;
+
+    impl CustomHandler for VendorHandler {
+        fn can_handle(&self, reader: &mut dyn crate::files::handler::ReadSeek) -> XmpResult<bool> {
+            let mut signature = [0u8; 4];
+            Ok(reader.read_exact(&mut signature).is_ok() && &signature == b"VNDR")
+        }
+
+        fn read_xmp(
+            &self,
+            reader: &mut dyn crate::files::handler::ReadSeek,
+        ) -> XmpResult<Option<crate::core::metadata::XmpMeta>> {
+            let mut packet = String::new();
+            reader.seek(std::io::SeekFrom::Start(4))?;
+            reader.read_to_string(&mut packet)?;
+            crate::core::metadata::XmpMeta::parse(&packet).map(Some)
+        }
+
+        fn write_xmp(
+            &self,
+            _reader: &mut dyn crate::files::handler::ReadSeek,
+            writer: &mut dyn crate::files::handler::WriteSeek,
+            meta: &crate::core::metadata::XmpMeta,
+        ) -> XmpResult<()> {
+            writer.write_all(b"VNDR")?;
+            writer.write_all(meta.serialize_packet()?.as_bytes())?;
+            Ok(())
+        }
+
+        fn format_name(&self) -> &'static str {
+            "Vendor"
+        }
+    }
+
+    #[test]
+    fn test_register_handler() {
+        use crate::core::namespace::ns;
+        use crate::files::XmpFile;
+        use crate::types::value::XmpValue;
+
+        register_handler(Box::new(VendorHandler));
+        register_handler(Box::new(VendorHandler));
+        let registry = HandlerRegistry::new();
+        let vendor = FileFormat::Custom("Vendor");
+        assert_eq!(
+            registry
+                .handlers()
+                .iter()
+                .filter(|h| h.format() == vendor)
+                .count(),
+            1
+        );
+        assert!(!registry
+            .find_by_format(vendor)
+            .unwrap()
+            .supports_in_place_update());
+
+        let mut meta = crate::core::metadata::XmpMeta::new();
+        meta.set_property(ns::XMP, "Label", XmpValue::String("Raw".into()))
+            .unwrap();
+        let data = [b"VNDR", meta.serialize_packet().unwrap().as_bytes()].concat();
+        assert_eq!(XmpFile::detect_format(&data), vendor);
+
+        let mut file = XmpFile::new();
+        file.from_bytes(&data).unwrap();
+        let mut meta = file.get_xmp().unwrap().clone();
+        meta.set_property(ns::XMP, "Rating", XmpValue::Integer(5))
+            .unwrap();
+        file.put_xmp(meta);
+        let written = file.write_to_bytes().unwrap();
+        file.from_bytes(&written).unwrap();
+        let meta = file.get_xmp().unwrap();
+        assert_eq!(
+            meta.get_property(ns::XMP, "Label"),
+            Some(XmpValue::String("Raw".into()))
+        );
+        assert!(meta.does_property_exist(ns::XMP, "Rating"));
+    }
+
     #[test]
     fn test_find_by_detection_png() {
         let registry = HandlerRegistry::new();
@@ -0,0 +1,39 @@
+//! Exceptions thrown for XMP errors
+
+use crate::core::error::XmpError;
+
+/// Get the JNI name of the `XmpException` subclass thrown for an error
+///
+/// The subclasses are declared in `XmpException.kt`.
+pub(crate) fn exception_class(error: &XmpError) -> &'static str {
+    match error {
+        XmpError::BadParam(_) => "io/github/rtthw/xmpkit/XmpException$BadParam",
+        XmpError::BadValue(_) => "io/github/rtthw/xmpkit/XmpException$BadValue",
+        XmpError::BadSchema(_) => "io/github/rtthw/xmpkit/XmpException$BadSchema",
+        XmpError::BadXPath(_) => "io/github/rtthw/xmpkit/XmpException$BadXPath",
+        XmpError::ParseError(_) => "io/github/rtthw/xmpkit/XmpException$ParseError",
+        XmpError::SerializationError(_) => "io/github/rtthw/xmpkit/XmpException$SerializationError",
+        XmpError::IoError(_) => "io/github/rtthw/xmpkit/XmpException$IoError",
+        XmpError::InternalError(_) => "io/github/rtthw/xmpkit/XmpException$InternalError",
+        XmpError::NotFound(_) => "io/github/rtthw/xmpkit/XmpException$NotFound",
+        XmpError::NotSupported(_) => "io/github/rtthw/xmpkit/XmpException$NotSupported",
+        XmpError::Cancelled => "io/github/rtthw/xmpkit/XmpException$Cancelled",
+    }
+}
+
+/// Get the message of the exception thrown for an error
+pub(crate) fn exception_message(error: &XmpError) -> String {
+    match error {
+        XmpError::BadParam(msg)
+        | XmpError::BadValue(msg)
+        | XmpError::BadSchema(msg)
+        | XmpError::BadXPath(msg)
+        | XmpError::ParseError(msg)
+        | XmpError::SerializationError(msg)
+        | XmpError::InternalError(msg)
+        | XmpError::NotFound(msg)
+        | XmpError::NotSupported(msg) => msg.clone(),
+        XmpError::IoError(io_err) => io_err.to_string(),
+        XmpError::Cancelled => error.to_string(),
+    }
+}
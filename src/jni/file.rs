@@ -0,0 +1,179 @@
+//! JNI bindings for XMP file operations
+
+use std::ptr;
+
+use super::sys::{Env, JByteArray, JClass, JInt, JLong, RawEnv};
+use super::{free_handle, handle_mut, into_handle, run};
+use crate::core::error::XmpError;
+use crate::{ReadOptions, XmpFile, XmpMeta};
+
+/// A flag of `ReadOptions.kt` and the builder method it calls
+type ReadFlag = (JInt, fn(ReadOptions) -> ReadOptions);
+
+/// Flags of `ReadOptions.kt`
+const READ_FLAGS: [ReadFlag; 6] = [
+    (0x1, ReadOptions::only_xmp),
+    (0x2, ReadOptions::strict),
+    (0x4, ReadOptions::use_smart_handler),
+    (0x8, ReadOptions::use_packet_scanning),
+    (0x10, ReadOptions::limited_scanning),
+    (0x20, ReadOptions::repair_file),
+];
+
+/// Convert the flags of a `ReadOptions.kt` to read options
+fn read_options(flags: JInt) -> ReadOptions {
+    READ_FLAGS
+        .iter()
+        .filter(|(flag, _)| flags & flag != 0)
+        .fold(ReadOptions::default(), |options, (_, builder)| {
+            builder(options)
+        })
+}
+
+/// Native method of `Native.fileNew`
+#[no_mangle]
+pub extern "system" fn Java_io_github_rtthw_xmpkit_Native_fileNew(
+    _env: RawEnv,
+    _class: JClass,
+) -> JLong {
+    into_handle(XmpFile::new())
+}
+
+/// Native method of `Native.fileFree`
+///
+/// # Safety
+///
+/// Called by the JVM with a handle that was not freed yet.
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_github_rtthw_xmpkit_Native_fileFree(
+    _env: RawEnv,
+    _class: JClass,
+    handle: JLong,
+) {
+    free_handle::<XmpFile>(handle);
+}
+
+/// Native method of `Native.fileFromBytes`
+///
+/// # Safety
+///
+/// Called by the JVM with valid arguments.
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_github_rtthw_xmpkit_Native_fileFromBytes(
+    env: RawEnv,
+    _class: JClass,
+    handle: JLong,
+    data: JByteArray,
+    flags: JInt,
+) {
+    let env = Env::new(env);
+    run(env, (), || {
+        let file = handle_mut::<XmpFile>(handle)?;
+        let data = env
+            .get_byte_array(data)
+            .ok_or_else(|| XmpError::BadParam("data is null".to_string()))?;
+        file.from_bytes_with(&data, read_options(flags))
+    })
+}
+
+/// Native method of `Native.fileGetXmp`
+///
+/// Returns a handle of a copy sharing the properties of the metadata, or
+/// 0 if the file has no metadata.
+///
+/// # Safety
+///
+/// Called by the JVM with valid arguments.
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_github_rtthw_xmpkit_Native_fileGetXmp(
+    env: RawEnv,
+    _class: JClass,
+    handle: JLong,
+) -> JLong {
+    let env = Env::new(env);
+    run(env, 0, || {
+        let file = handle_mut::<XmpFile>(handle)?;
+        Ok(file.get_xmp().cloned().map_or(0, into_handle))
+    })
+}
+
+/// Native method of `Native.filePutXmp`
+///
+/// # Safety
+///
+/// Called by the JVM with valid arguments.
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_github_rtthw_xmpkit_Native_filePutXmp(
+    env: RawEnv,
+    _class: JClass,
+    handle: JLong,
+    meta: JLong,
+) {
+    let env = Env::new(env);
+    run(env, (), || {
+        let meta = handle_mut::<XmpMeta>(meta)?.clone();
+        handle_mut::<XmpFile>(handle)?.put_xmp(meta);
+        Ok(())
+    })
+}
+
+/// Native method of `Native.fileRemoveXmp`
+///
+/// # Safety
+///
+/// Called by the JVM with valid arguments.
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_github_rtthw_xmpkit_Native_fileRemoveXmp(
+    env: RawEnv,
+    _class: JClass,
+    handle: JLong,
+) {
+    let env = Env::new(env);
+    run(env, (), || {
+        handle_mut::<XmpFile>(handle)?.remove_xmp();
+        Ok(())
+    })
+}
+
+/// Native method of `Native.fileWriteToBytes`
+///
+/// # Safety
+///
+/// Called by the JVM with valid arguments.
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_github_rtthw_xmpkit_Native_fileWriteToBytes(
+    env: RawEnv,
+    _class: JClass,
+    handle: JLong,
+) -> JByteArray {
+    let env = Env::new(env);
+    run(env, ptr::null_mut(), || {
+        let file = handle_mut::<XmpFile>(handle)?;
+        Ok(env.new_byte_array(&file.write_to_bytes()?))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_flags_match_kotlin() {
+        let kotlin =
+            include_str!("../../android/src/main/kotlin/io/github/rtthw/xmpkit/ReadOptions.kt");
+        let names = [
+            "ONLY_XMP",
+            "STRICT",
+            "USE_SMART_HANDLER",
+            "USE_PACKET_SCANNING",
+            "LIMITED_SCANNING",
+            "REPAIR_FILE",
+        ];
+        for ((flag, _), name) in READ_FLAGS.iter().zip(names) {
+            assert!(kotlin.contains(&format!("const val {} = 0x{:x}", name, flag)));
+        }
+
+        let options = read_options(0x1 | 0x20);
+        assert!(options.only_xmp && options.repair_file && !options.strict);
+    }
+}
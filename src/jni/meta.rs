@@ -0,0 +1,258 @@
+//! JNI bindings for XMP metadata operations
+
+use std::ptr;
+
+use super::sys::{Env, JBoolean, JClass, JLong, JString, RawEnv};
+use super::{free_handle, handle_mut, into_handle, run, string_arg};
+use crate::{XmpMeta, XmpValue};
+
+/// Native method of `Native.metaNew`
+#[no_mangle]
+pub extern "system" fn Java_io_github_rtthw_xmpkit_Native_metaNew(
+    _env: RawEnv,
+    _class: JClass,
+) -> JLong {
+    into_handle(XmpMeta::new())
+}
+
+/// Native method of `Native.metaParse`
+///
+/// # Safety
+///
+/// Called by the JVM with valid arguments.
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_github_rtthw_xmpkit_Native_metaParse(
+    env: RawEnv,
+    _class: JClass,
+    xmp_packet: JString,
+) -> JLong {
+    let env = Env::new(env);
+    run(env, 0, || {
+        let meta = XmpMeta::parse(&string_arg(env, xmp_packet, "xmpPacket")?)?;
+        Ok(into_handle(meta))
+    })
+}
+
+/// Native method of `Native.metaFree`
+///
+/// # Safety
+///
+/// Called by the JVM with a handle that was not freed yet.
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_github_rtthw_xmpkit_Native_metaFree(
+    _env: RawEnv,
+    _class: JClass,
+    handle: JLong,
+) {
+    free_handle::<XmpMeta>(handle);
+}
+
+/// Native method of `Native.metaGetProperty`
+///
+/// Returns null if the property doesn't exist.
+///
+/// # Safety
+///
+/// Called by the JVM with valid arguments.
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_github_rtthw_xmpkit_Native_metaGetProperty(
+    env: RawEnv,
+    _class: JClass,
+    handle: JLong,
+    namespace: JString,
+    path: JString,
+) -> JString {
+    let env = Env::new(env);
+    run(env, ptr::null_mut(), || {
+        let meta = handle_mut::<XmpMeta>(handle)?;
+        let value = meta.get_property(
+            &string_arg(env, namespace, "namespace")?,
+            &string_arg(env, path, "path")?,
+        );
+        Ok(match value {
+            Some(value) => env.new_string(&value_string(value)),
+            None => ptr::null_mut(),
+        })
+    })
+}
+
+/// Native method of `Native.metaSetProperty`
+///
+/// # Safety
+///
+/// Called by the JVM with valid arguments.
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_github_rtthw_xmpkit_Native_metaSetProperty(
+    env: RawEnv,
+    _class: JClass,
+    handle: JLong,
+    namespace: JString,
+    path: JString,
+    value: JString,
+) {
+    let env = Env::new(env);
+    run(env, (), || {
+        let meta = handle_mut::<XmpMeta>(handle)?;
+        meta.set_property(
+            &string_arg(env, namespace, "namespace")?,
+            &string_arg(env, path, "path")?,
+            XmpValue::String(string_arg(env, value, "value")?),
+        )
+    })
+}
+
+/// Native method of `Native.metaHasProperty`
+///
+/// # Safety
+///
+/// Called by the JVM with valid arguments.
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_github_rtthw_xmpkit_Native_metaHasProperty(
+    env: RawEnv,
+    _class: JClass,
+    handle: JLong,
+    namespace: JString,
+    path: JString,
+) -> JBoolean {
+    let env = Env::new(env);
+    run(env, 0, || {
+        let meta = handle_mut::<XmpMeta>(handle)?;
+        let exists = meta.has_property(
+            &string_arg(env, namespace, "namespace")?,
+            &string_arg(env, path, "path")?,
+        );
+        Ok(exists as JBoolean)
+    })
+}
+
+/// Native method of `Native.metaDeleteProperty`
+///
+/// # Safety
+///
+/// Called by the JVM with valid arguments.
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_github_rtthw_xmpkit_Native_metaDeleteProperty(
+    env: RawEnv,
+    _class: JClass,
+    handle: JLong,
+    namespace: JString,
+    path: JString,
+) {
+    let env = Env::new(env);
+    run(env, (), || {
+        let meta = handle_mut::<XmpMeta>(handle)?;
+        meta.delete_property(
+            &string_arg(env, namespace, "namespace")?,
+            &string_arg(env, path, "path")?,
+        )
+    })
+}
+
+/// Native method of `Native.metaGetLocalizedText`
+///
+/// Returns null if the property has no text.
+///
+/// # Safety
+///
+/// Called by the JVM with valid arguments.
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_github_rtthw_xmpkit_Native_metaGetLocalizedText(
+    env: RawEnv,
+    _class: JClass,
+    handle: JLong,
+    namespace: JString,
+    path: JString,
+    generic_lang: JString,
+    specific_lang: JString,
+) -> JString {
+    let env = Env::new(env);
+    run(env, ptr::null_mut(), || {
+        let meta = handle_mut::<XmpMeta>(handle)?;
+        let text = meta.get_localized_text(
+            &string_arg(env, namespace, "namespace")?,
+            &string_arg(env, path, "path")?,
+            &string_arg(env, generic_lang, "genericLang")?,
+            &string_arg(env, specific_lang, "specificLang")?,
+        );
+        Ok(match text {
+            Some((value, _)) => env.new_string(&value),
+            None => ptr::null_mut(),
+        })
+    })
+}
+
+/// Native method of `Native.metaSetLocalizedText`
+///
+/// # Safety
+///
+/// Called by the JVM with valid arguments.
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_github_rtthw_xmpkit_Native_metaSetLocalizedText(
+    env: RawEnv,
+    _class: JClass,
+    handle: JLong,
+    namespace: JString,
+    path: JString,
+    generic_lang: JString,
+    specific_lang: JString,
+    value: JString,
+) {
+    let env = Env::new(env);
+    run(env, (), || {
+        let meta = handle_mut::<XmpMeta>(handle)?;
+        meta.set_localized_text(
+            &string_arg(env, namespace, "namespace")?,
+            &string_arg(env, path, "path")?,
+            &string_arg(env, generic_lang, "genericLang")?,
+            &string_arg(env, specific_lang, "specificLang")?,
+            &string_arg(env, value, "value")?,
+        )
+    })
+}
+
+/// Native method of `Native.metaSerialize`
+///
+/// # Safety
+///
+/// Called by the JVM with valid arguments.
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_github_rtthw_xmpkit_Native_metaSerialize(
+    env: RawEnv,
+    _class: JClass,
+    handle: JLong,
+) -> JString {
+    let env = Env::new(env);
+    run(env, ptr::null_mut(), || {
+        let meta = handle_mut::<XmpMeta>(handle)?;
+        Ok(env.new_string(&meta.serialize()?))
+    })
+}
+
+/// Native method of `Native.metaSerializePacket`
+///
+/// # Safety
+///
+/// Called by the JVM with valid arguments.
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_github_rtthw_xmpkit_Native_metaSerializePacket(
+    env: RawEnv,
+    _class: JClass,
+    handle: JLong,
+) -> JString {
+    let env = Env::new(env);
+    run(env, ptr::null_mut(), || {
+        let meta = handle_mut::<XmpMeta>(handle)?;
+        Ok(env.new_string(&meta.serialize_packet()?))
+    })
+}
+
+/// Convert a property value to the string returned to Java
+fn value_string(value: XmpValue) -> String {
+    match value {
+        XmpValue::String(s) | XmpValue::Uri(s) => s,
+        XmpValue::Integer(i) => i.to_string(),
+        XmpValue::Boolean(b) => b.to_string(),
+        XmpValue::DateTime(d) => d.format(),
+        _ => format!("{:?}", value),
+    }
+}
@@ -0,0 +1,190 @@
+//! JNI bindings for xmpkit on Android
+//!
+//! This module exports the native methods of the Kotlin wrapper in
+//! `android/`, which provides `XmpFile` and `XmpMeta` classes mirroring the
+//! Rust API. Enable the `jni` feature to build them.
+//!
+//! # Usage
+//!
+//! Build the library for the Android targets, for example with
+//! [cargo-ndk](https://github.com/bbqsrc/cargo-ndk):
+//! ```bash
+//! cargo ndk -t arm64-v8a -t armeabi-v7a -t x86_64 -o app/src/main/jniLibs \
+//!     build --release --features jni
+//! ```
+//!
+//! Then add the sources of `android/src/main/kotlin` to the app, and use
+//! them from Kotlin:
+//! ```kotlin
+//! import io.github.rtthw.xmpkit.XmpFile
+//!
+//! XmpFile.fromBytes(bytes).use { file ->
+//!     val meta = file.getXmp()
+//!     val tool = meta?.getProperty("http://ns.adobe.com/xap/1.0/", "CreatorTool")
+//! }
+//! ```
+//!
+//! Files are read from and written to `ByteArray`s, which fits the content
+//! URIs of Android. Errors are thrown as subclasses of `XmpException`, one
+//! per [`XmpError`] variant.
+
+#![allow(non_snake_case)]
+
+mod error;
+mod file;
+mod meta;
+mod sys;
+
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::core::error::{XmpError, XmpResult};
+use sys::{Env, JClass, JLong, JString, RawEnv};
+
+/// Run the body of a native method
+///
+/// Errors and panics are thrown as an `XmpException`, and `fallback` is
+/// returned to Java, which ignores it.
+fn run<T>(env: Env, fallback: T, f: impl FnOnce() -> XmpResult<T>) -> T {
+    let error = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => return value,
+        Ok(Err(error)) => error,
+        Err(_) => XmpError::InternalError("Panic in xmpkit".to_string()),
+    };
+    env.throw_new(
+        error::exception_class(&error),
+        &error::exception_message(&error),
+    );
+    fallback
+}
+
+/// Move a value to Java, as the handle of its wrapper
+fn into_handle<T>(value: T) -> JLong {
+    Box::into_raw(Box::new(value)) as JLong
+}
+
+/// Borrow the value of a handle
+///
+/// # Safety
+///
+/// `handle` must be 0 or a handle of a `T` that was not freed yet.
+unsafe fn handle_mut<'a, T>(handle: JLong) -> XmpResult<&'a mut T> {
+    (handle as *mut T)
+        .as_mut()
+        .ok_or_else(|| XmpError::BadParam("The object is closed".to_string()))
+}
+
+/// Free the value of a handle
+///
+/// # Safety
+///
+/// `handle` must be 0 or a handle of a `T` that was not freed yet.
+unsafe fn free_handle<T>(handle: JLong) {
+    if handle != 0 {
+        drop(Box::from_raw(handle as *mut T));
+    }
+}
+
+/// Copy a string argument
+///
+/// # Safety
+///
+/// `string` must be null or a `java.lang.String`.
+unsafe fn string_arg(env: Env, string: JString, name: &str) -> XmpResult<String> {
+    env.get_string(string)
+        .ok_or_else(|| XmpError::BadParam(format!("{} is null", name)))
+}
+
+/// Native method of `Native.registerNamespace`
+///
+/// # Safety
+///
+/// Called by the JVM with valid arguments.
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_github_rtthw_xmpkit_Native_registerNamespace(
+    env: RawEnv,
+    _class: JClass,
+    uri: JString,
+    prefix: JString,
+) {
+    let env = Env::new(env);
+    run(env, (), || {
+        crate::register_namespace(
+            &string_arg(env, uri, "uri")?,
+            &string_arg(env, prefix, "prefix")?,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exceptions_match_kotlin_classes() {
+        let kotlin =
+            include_str!("../../android/src/main/kotlin/io/github/rtthw/xmpkit/XmpException.kt");
+        let errors = [
+            XmpError::BadParam(String::new()),
+            XmpError::BadValue(String::new()),
+            XmpError::BadSchema(String::new()),
+            XmpError::BadXPath(String::new()),
+            XmpError::ParseError(String::new()),
+            XmpError::SerializationError(String::new()),
+            XmpError::IoError(std::io::Error::other("")),
+            XmpError::InternalError(String::new()),
+            XmpError::NotFound(String::new()),
+            XmpError::NotSupported(String::new()),
+            XmpError::Cancelled,
+        ];
+        for error in &errors {
+            let class = error::exception_class(error);
+            let (_, name) = class.split_once('$').unwrap();
+            assert!(
+                kotlin.contains(&format!("class {}(message: String)", name)),
+                "XmpException.{} is not declared",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_native_methods_match_kotlin() {
+        let kotlin = include_str!("../../android/src/main/kotlin/io/github/rtthw/xmpkit/Native.kt");
+        let sources = [
+            include_str!("mod.rs"),
+            include_str!("meta.rs"),
+            include_str!("file.rs"),
+        ];
+        let methods: Vec<&str> = sources
+            .iter()
+            // Split so that this test is not matched
+            .flat_map(|source| {
+                source
+                    .split(concat!("fn Java_io_github_rtthw_xmpkit", "_Native_"))
+                    .skip(1)
+            })
+            .filter_map(|rest| rest.split('(').next())
+            .collect();
+        assert!(methods.len() > 15);
+        for method in methods {
+            assert!(
+                kotlin.contains(&format!("external fun {}(", method)),
+                "Native.{} is not declared",
+                method
+            );
+        }
+    }
+
+    #[test]
+    fn test_handles() {
+        let handle = into_handle(crate::XmpMeta::new());
+        unsafe {
+            assert!(handle_mut::<crate::XmpMeta>(handle).is_ok());
+            free_handle::<crate::XmpMeta>(handle);
+            assert!(matches!(
+                handle_mut::<crate::XmpMeta>(0),
+                Err(XmpError::BadParam(_))
+            ));
+        }
+    }
+}
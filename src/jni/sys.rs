@@ -0,0 +1,187 @@
+//! The parts of the JNI function table used by the bindings
+//!
+//! Declared here rather than taken from a crate, so that the `jni` feature
+//! has no dependencies. The indices are those of `JNINativeInterface` in
+//! `jni.h`, which are fixed by the JNI specification.
+
+use std::ffi::{c_char, c_void, CString};
+use std::ptr;
+
+pub type JInt = i32;
+pub type JLong = i64;
+pub type JBoolean = u8;
+pub type JObject = *mut c_void;
+pub type JClass = JObject;
+pub type JString = JObject;
+pub type JByteArray = JObject;
+
+/// `JNIEnv`: a pointer to the function table
+pub type RawEnv = *const *const *const c_void;
+
+const FIND_CLASS: usize = 6;
+const THROW_NEW: usize = 14;
+const DELETE_LOCAL_REF: usize = 23;
+const NEW_STRING: usize = 163;
+const GET_STRING_LENGTH: usize = 164;
+const GET_ARRAY_LENGTH: usize = 171;
+const NEW_BYTE_ARRAY: usize = 176;
+const GET_BYTE_ARRAY_REGION: usize = 200;
+const SET_BYTE_ARRAY_REGION: usize = 208;
+const GET_STRING_REGION: usize = 220;
+const EXCEPTION_CHECK: usize = 228;
+
+/// JNI environment of the calling thread
+#[derive(Clone, Copy)]
+pub struct Env(RawEnv);
+
+impl Env {
+    /// Wrap the environment passed to a native method
+    ///
+    /// # Safety
+    ///
+    /// `env` must be the `JNIEnv` of the calling thread.
+    pub unsafe fn new(env: RawEnv) -> Self {
+        Self(env)
+    }
+
+    /// Get the function at `index` of the table
+    ///
+    /// # Safety
+    ///
+    /// `F` must be the type of the function at `index`.
+    unsafe fn function<F: Copy>(&self, index: usize) -> F {
+        let function = *(*self.0).add(index);
+        std::mem::transmute_copy::<*const c_void, F>(&function)
+    }
+
+    /// Check if an exception is pending
+    pub fn exception_check(&self) -> bool {
+        unsafe {
+            let check: unsafe extern "system" fn(RawEnv) -> JBoolean =
+                self.function(EXCEPTION_CHECK);
+            check(self.0) != 0
+        }
+    }
+
+    /// Throw a new exception of class `class`, such as
+    /// `java/lang/IllegalStateException`
+    ///
+    /// Does nothing if an exception is already pending.
+    pub fn throw_new(&self, class: &str, message: &str) {
+        if self.exception_check() {
+            return;
+        }
+        let (Ok(class), Ok(message)) = (
+            CString::new(class),
+            CString::new(message.replace('\0', " ")),
+        ) else {
+            return;
+        };
+        unsafe {
+            let find_class: unsafe extern "system" fn(RawEnv, *const c_char) -> JClass =
+                self.function(FIND_CLASS);
+            let throw_new: unsafe extern "system" fn(RawEnv, JClass, *const c_char) -> JInt =
+                self.function(THROW_NEW);
+            // FindClass throws NoClassDefFoundError itself on failure
+            let class = find_class(self.0, class.as_ptr());
+            if !class.is_null() {
+                throw_new(self.0, class, message.as_ptr());
+                self.delete_local_ref(class);
+            }
+        }
+    }
+
+    /// Release a local reference
+    fn delete_local_ref(&self, object: JObject) {
+        unsafe {
+            let delete: unsafe extern "system" fn(RawEnv, JObject) =
+                self.function(DELETE_LOCAL_REF);
+            delete(self.0, object);
+        }
+    }
+
+    /// Copy a Java string
+    ///
+    /// Returns `None` if `string` is null.
+    ///
+    /// # Safety
+    ///
+    /// `string` must be null or a `java.lang.String`.
+    pub unsafe fn get_string(&self, string: JString) -> Option<String> {
+        if string.is_null() {
+            return None;
+        }
+        let length: unsafe extern "system" fn(RawEnv, JString) -> JInt =
+            self.function(GET_STRING_LENGTH);
+        let region: unsafe extern "system" fn(RawEnv, JString, JInt, JInt, *mut u16) =
+            self.function(GET_STRING_REGION);
+        let len = length(self.0, string);
+        let mut chars = vec![0u16; len.max(0) as usize];
+        region(self.0, string, 0, len, chars.as_mut_ptr());
+        Some(String::from_utf16_lossy(&chars))
+    }
+
+    /// Create a Java string
+    ///
+    /// Returns null, with an exception pending, if it can't be created.
+    pub fn new_string(&self, string: &str) -> JString {
+        let chars: Vec<u16> = string.encode_utf16().collect();
+        let Ok(len) = JInt::try_from(chars.len()) else {
+            self.throw_new(
+                "java/lang/OutOfMemoryError",
+                "The string is too large for a Java string",
+            );
+            return ptr::null_mut();
+        };
+        unsafe {
+            let new_string: unsafe extern "system" fn(RawEnv, *const u16, JInt) -> JString =
+                self.function(NEW_STRING);
+            new_string(self.0, chars.as_ptr(), len)
+        }
+    }
+
+    /// Copy a Java `byte[]`
+    ///
+    /// Returns `None` if `array` is null.
+    ///
+    /// # Safety
+    ///
+    /// `array` must be null or a `byte[]`.
+    pub unsafe fn get_byte_array(&self, array: JByteArray) -> Option<Vec<u8>> {
+        if array.is_null() {
+            return None;
+        }
+        let length: unsafe extern "system" fn(RawEnv, JObject) -> JInt =
+            self.function(GET_ARRAY_LENGTH);
+        let region: unsafe extern "system" fn(RawEnv, JByteArray, JInt, JInt, *mut u8) =
+            self.function(GET_BYTE_ARRAY_REGION);
+        let len = length(self.0, array);
+        let mut bytes = vec![0u8; len.max(0) as usize];
+        region(self.0, array, 0, len, bytes.as_mut_ptr());
+        Some(bytes)
+    }
+
+    /// Create a Java `byte[]`
+    ///
+    /// Returns null, with an exception pending, if it can't be created.
+    pub fn new_byte_array(&self, bytes: &[u8]) -> JByteArray {
+        let Ok(len) = JInt::try_from(bytes.len()) else {
+            self.throw_new(
+                "java/lang/OutOfMemoryError",
+                "The data is too large for a byte array",
+            );
+            return ptr::null_mut();
+        };
+        unsafe {
+            let new_array: unsafe extern "system" fn(RawEnv, JInt) -> JByteArray =
+                self.function(NEW_BYTE_ARRAY);
+            let region: unsafe extern "system" fn(RawEnv, JByteArray, JInt, JInt, *const u8) =
+                self.function(SET_BYTE_ARRAY_REGION);
+            let array = new_array(self.0, len);
+            if !array.is_null() {
+                region(self.0, array, 0, len, bytes.as_ptr());
+            }
+            array
+        }
+    }
+}
@@ -80,7 +80,7 @@
 //! ### Parsing XMP from String
 //!
 //! ```rust
-//! use xmpkit::{XmpMeta, XmpValue};
+//! use xmpkit::{XmpDateTime, XmpMeta, XmpValue};
 //! use xmpkit::core::namespace::ns;
 //!
 //! # fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -104,7 +104,7 @@
 //! meta.set_property(
 //!     ns::XMP,
 //!     "ModifyDate",
-//!     XmpValue::DateTime("2024-01-01T00:00:00Z".to_string()),
+//!     XmpValue::DateTime(XmpDateTime::parse("2024-01-01T00:00:00Z")?),
 //! )?;
 //!
 //! let serialized = meta.serialize_packet()?;
@@ -112,12 +112,17 @@
 //! # }
 //! ```
 //!
+//! To only read properties, [`XmpMeta::parse_borrowed`] returns a read-only
+//! view whose values borrow their text from the packet.
+//!
 //! ## Modules
 //!
 //! - [`core`] - Core XMP functionality (parsing, serialization, metadata API)
 //! - [`files`] - File format handlers for reading/writing XMP from files
+//! - [`schemas`] - Accessors for the properties of standard schemas (Camera
+//!   Raw, Dublin Core, Dynamic Media, IPTC, PLUS, XMP Rights)
 //! - [`types`] - Common types and data structures (XmpValue, Qualifier)
-//! - [`utils`] - Utility functions (date/time handling)
+//! - [`utils`] - Utility functions (date/time handling, XMP identifiers, timecodes)
 //!
 //! ## Platform Support
 //!
@@ -197,11 +202,19 @@
 //!
 //! - `core` - Core XMP functionality (enabled by default)
 //! - `files` - File format support infrastructure (enabled by default)
-//! - `jpeg`, `png`, `tiff`, `mp3`, `gif`, `mp4` - Individual file format handlers
+//! - `jpeg`, `png`, `tiff`, `mp3`, `gif`, `mp4`, `webp`, `heif`, `avif`, `pdf`, `psd`, `svg`, `wav`, `aiff`, `flac`, `ogg`, `mov`, `mkv`, `avi`, `asf`, `jxl`, `jp2`, `eps`, `indd` - Individual file format handlers
 //! - `full-formats` - Enable all file format handlers (enabled by default)
 //! - `mutli-thread` - Multi-threaded runtime support (enabled by default)
+//! - `tokio` - Async file API using tokio (optional)
+//! - `serde` - Mapping between serde types and schema properties, and serde derives for diffs and validation reports (optional)
+//! - `serde_json` - JSON export and import of XMP trees and diffs (optional)
+//! - `jsonld` - JSON-LD serialization and parsing of XMP (optional)
+//! - `mmap` - Memory-map files opened from a path instead of reading them (optional)
+//! - `chrono`, `time` - Conversions between `XmpDateTime` and the date/time types of these crates (optional)
 //! - `wasm` - WebAssembly JavaScript bindings (optional, enables wasm-bindgen integration)
 //! - `ohos` - OpenHarmony/HarmonyOS Node-API bindings (optional, enables napi-ohos integration)
+//! - `jni` - Android JNI bindings for the Kotlin wrapper in `android/` (optional)
+//! - `capi` - C API mirroring the Adobe XMP Toolkit, declared in `include/xmpkit.h` (optional)
 //!
 //! ## Supported File Formats
 //!
@@ -213,32 +226,81 @@
 //! | MP3    | .mp3      | Yes | Yes |
 //! | GIF    | .gif      | Yes | Yes |
 //! | MP4    | .mp4      | Yes | Yes |
+//! | WebP   | .webp     | Yes | Yes |
+//! | HEIF   | .heic, .heif, .hif | Yes | Yes |
+//! | AVIF   | .avif     | Yes | Yes |
+//! | PDF    | .pdf      | Yes | Yes |
+//! | PSD    | .psd, .psb | Yes | Yes |
+//! | SVG    | .svg      | Yes | Yes |
+//! | WAV    | .wav, .bwf | Yes | Yes |
+//! | AIFF   | .aif, .aiff, .aifc | Yes | Yes |
+//! | FLAC   | .flac     | Yes | Yes |
+//! | Ogg    | .ogg, .oga, .opus | Yes | Yes |
+//! | MOV    | .mov, .qt | Yes | Yes |
+//! | Matroska | .mkv, .mka, .webm | Yes | Yes |
+//! | AVI    | .avi      | Yes | Yes |
+//! | ASF    | .asf, .wmv, .wma | Yes | Yes |
+//! | JPEG XL | .jxl      | Yes | Yes |
+//! | JPEG 2000 | .jp2, .jpx, .jpf | Yes | Yes |
+//! | DNG    | .dng      | Yes | Yes |
+//! | Camera RAW | .cr2, .nef, .arw, .orf, .rw2 | Yes | No |
+//! | XMP sidecar | .xmp     | Yes | Yes |
+//! | EPS    | .eps, .epsf, .ps | Yes | Yes |
+//! | InDesign | .indd, .indt | Yes | No |
 
 #[cfg(feature = "core")]
 pub mod core;
 #[cfg(feature = "files")]
 pub mod files;
+#[cfg(feature = "core")]
+pub mod schemas;
 pub mod types;
 pub mod utils;
 
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
-#[cfg(all(feature = "ohos", target_ohos))]
+#[cfg(all(feature = "ohos", any(target_ohos, test)))]
 pub mod ohos;
 
+#[cfg(all(feature = "capi", not(target_arch = "wasm32")))]
+pub mod capi;
+
+#[cfg(all(feature = "jni", not(target_arch = "wasm32")))]
+pub mod jni;
+
 // Re-export commonly used types
 #[cfg(feature = "core")]
+pub use core::alias::{register_alias, resolve_alias, XmpAlias};
+#[cfg(feature = "core")]
+pub use core::borrowed::XmpMetaRef;
+#[cfg(feature = "core")]
 pub use core::error::{XmpError, XmpResult};
+#[cfg(all(feature = "core", feature = "serde_json"))]
+pub use core::metadata::JsonOptions;
 #[cfg(feature = "core")]
-pub use core::metadata::XmpMeta;
+pub use core::metadata::{
+    AppendOptions, DiffEntry, IterOptions, PropertyOptions, TemplateOptions, XmpDiff, XmpIterItem,
+    XmpIterator, XmpMeta, XmpSnapshot,
+};
 #[cfg(feature = "core")]
 pub use core::namespace::{
     get_all_registered_namespaces, get_builtin_namespace_uris, get_global_namespace_prefix,
     get_global_namespace_uri, is_namespace_registered, ns, register_namespace,
 };
+#[cfg(feature = "core")]
+pub use core::parser::{DescriptionMerge, ParseOptions, ParseWarning, ParseWarningKind};
+#[cfg(feature = "core")]
+pub use core::serializer::{SerializeOptions, StructForm};
+#[cfg(feature = "core")]
+pub use core::validate::{
+    register_schema, ValueForm, ValueType, Violation, ViolationKind, XmpSchema, XmpValidator,
+};
 #[cfg(feature = "files")]
-pub use files::{ReadOptions, XmpFile};
+pub use files::{
+    FileFormat, FormatCapabilities, PacketInfo, ReadOptions, SaveOptions, SaveOutcome, XmpFile,
+};
 pub use types::qualifier::Qualifier;
 pub use types::value::XmpValue;
+pub use types::value_ref::{XmpPropertyRef, XmpValueRef};
 pub use utils::datetime::XmpDateTime;
@@ -42,6 +42,8 @@ pub enum XmpErrorKind {
     NotFound,
     /// Not supported error
     NotSupported,
+    /// Cancelled operation
+    Cancelled,
 }
 
 #[napi]
@@ -72,6 +74,7 @@ pub(crate) fn xmp_error_to_ohos_error(err: RustXmpError) -> XmpError {
         RustXmpError::InternalError(msg) => (XmpErrorKind::InternalError, msg.clone()),
         RustXmpError::NotFound(msg) => (XmpErrorKind::NotFound, msg.clone()),
         RustXmpError::NotSupported(msg) => (XmpErrorKind::NotSupported, msg.clone()),
+        RustXmpError::Cancelled => (XmpErrorKind::Cancelled, err.to_string()),
     };
     XmpError { kind, message }
 }
@@ -1,11 +1,21 @@
 //! OpenHarmony bindings for XMP file operations
 
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::core::error::XmpError as RustXmpError;
 use crate::files::file::ReadOptions as RustReadOptions;
 use crate::ohos::error::xmp_error_to_ohos_error;
 use crate::ohos::meta::XmpMeta;
+use crate::ohos::task::{self, FileOperation};
 use crate::XmpFile as RustXmpFile;
 use napi_derive_ohos::napi;
 use napi_ohos::bindgen_prelude::*;
+use napi_ohos::{Env, Task};
+
+/// Convert an XMP error to a Node-API error
+fn napi_error(e: RustXmpError) -> Error {
+    Error::from_reason(format!("{}", xmp_error_to_ohos_error(e)))
+}
 
 /// Options for reading XMP metadata from files or memory (OpenHarmony)
 ///
@@ -53,10 +63,26 @@ impl ReadOptions {
 /// XmpFile for OpenHarmony
 ///
 /// Provides the same API as Rust's `XmpFile`.
+///
+/// The `*Async` methods run on the Node-API worker pool and return a
+/// `Promise`, so that large media files don't block the UI thread. The
+/// other methods of the file throw while an asynchronous operation is
+/// running.
+///
+/// # Example
+///
+/// ```typescript
+/// const file = new XmpFile();
+/// await file.openAsync(path);
+/// const meta = file.getXmp();
+/// meta?.setProperty("http://ns.adobe.com/xap/1.0/", "CreatorTool", "MyApp");
+/// file.putXmp(meta);
+/// await file.saveAsync(path);
+/// ```
 #[derive(Default)]
 #[napi]
 pub struct XmpFile {
-    inner: RustXmpFile,
+    inner: Arc<Mutex<RustXmpFile>>,
 }
 
 #[napi]
@@ -64,42 +90,147 @@ impl XmpFile {
     /// Create a new XmpFile instance
     #[napi(constructor)]
     pub fn new() -> XmpFile {
-        XmpFile {
-            inner: RustXmpFile::new(),
-        }
+        XmpFile::default()
+    }
+
+    /// Open a file
+    pub fn open(&mut self, path: String) -> Result<()> {
+        lock(&self.inner)?.open(path).map_err(napi_error)
+    }
+
+    /// Open a file with options
+    pub fn open_with(&mut self, path: String, options: &ReadOptions) -> Result<()> {
+        lock(&self.inner)?
+            .open_with(path, options.inner)
+            .map_err(napi_error)
+    }
+
+    /// Open a file on the worker pool
+    ///
+    /// Returns a `Promise<void>`.
+    pub fn open_async(&mut self, path: String) -> AsyncTask<FileTask> {
+        self.open_async_with(path, &ReadOptions::default())
+    }
+
+    /// Open a file with options on the worker pool
+    ///
+    /// Returns a `Promise<void>`.
+    pub fn open_async_with(&mut self, path: String, options: &ReadOptions) -> AsyncTask<FileTask> {
+        self.task(task::open(path, options.inner))
     }
 
     /// Load XMP from file bytes
     pub fn from_bytes(&mut self, data: Buffer) -> Result<()> {
-        self.inner
+        lock(&self.inner)?
             .from_bytes(data.as_ref())
-            .map_err(|e| Error::from_reason(format!("{}", xmp_error_to_ohos_error(e))))
+            .map_err(napi_error)
     }
 
     /// Load XMP from file bytes with options
     pub fn from_bytes_with(&mut self, data: Buffer, options: &ReadOptions) -> Result<()> {
-        self.inner
+        lock(&self.inner)?
             .from_bytes_with(data.as_ref(), options.inner)
-            .map_err(|e| Error::from_reason(format!("{}", xmp_error_to_ohos_error(e))))
+            .map_err(napi_error)
+    }
+
+    /// Load XMP from file bytes on the worker pool
+    ///
+    /// The bytes are read in place rather than copied, so `data` must not be
+    /// modified until the returned `Promise<void>` settles.
+    pub fn from_bytes_async(&mut self, data: Uint8Array) -> AsyncTask<FileTask> {
+        self.from_bytes_async_with(data, &ReadOptions::default())
+    }
+
+    /// Load XMP from file bytes with options on the worker pool
+    ///
+    /// The bytes are read in place rather than copied, so `data` must not be
+    /// modified until the returned `Promise<void>` settles.
+    pub fn from_bytes_async_with(
+        &mut self,
+        data: Uint8Array,
+        options: &ReadOptions,
+    ) -> AsyncTask<FileTask> {
+        self.task(task::from_bytes(data, options.inner))
     }
 
     /// Get XMP metadata (returns an XmpMeta instance)
-    pub fn get_xmp(&self) -> Option<XmpMeta> {
-        self.inner.get_xmp().map(|meta| XmpMeta {
+    pub fn get_xmp(&self) -> Result<Option<XmpMeta>> {
+        Ok(lock(&self.inner)?.get_xmp().map(|meta| XmpMeta {
             inner: meta.clone(),
-        })
+        }))
     }
 
     /// Set XMP metadata
-    pub fn put_xmp(&mut self, meta: XmpMeta) {
-        self.inner.put_xmp(meta.inner);
+    pub fn put_xmp(&mut self, meta: XmpMeta) -> Result<()> {
+        lock(&self.inner)?.put_xmp(meta.inner);
+        Ok(())
+    }
+
+    /// Save the metadata to a file
+    pub fn save(&self, path: String) -> Result<()> {
+        lock(&self.inner)?.save(path).map_err(napi_error)
+    }
+
+    /// Save the metadata to a file on the worker pool
+    ///
+    /// Returns a `Promise<void>`.
+    pub fn save_async(&self, path: String) -> AsyncTask<FileTask> {
+        self.task(task::save(path))
     }
 
     /// Write file to bytes
     pub fn write_to_bytes(&mut self) -> Result<Buffer> {
-        self.inner
+        lock(&self.inner)?
             .write_to_bytes()
             .map(Buffer::from)
-            .map_err(|e| Error::from_reason(format!("{}", xmp_error_to_ohos_error(e))))
+            .map_err(napi_error)
+    }
+
+    /// Write file to bytes on the worker pool
+    ///
+    /// Returns a `Promise<Uint8Array>` whose buffer is handed over to ArkTS
+    /// without a copy.
+    pub fn write_to_bytes_async(&mut self) -> AsyncTask<FileTask> {
+        self.task(task::write_to_bytes())
+    }
+}
+
+impl XmpFile {
+    fn task(&self, operation: FileOperation) -> AsyncTask<FileTask> {
+        AsyncTask::new(FileTask {
+            file: Arc::clone(&self.inner),
+            operation: Some(operation),
+        })
+    }
+}
+
+/// Lock a file, failing rather than blocking while an asynchronous
+/// operation is running
+fn lock(file: &Mutex<RustXmpFile>) -> Result<MutexGuard<'_, RustXmpFile>> {
+    file.try_lock().map_err(|_| {
+        napi_error(RustXmpError::BadParam(
+            "The file is busy with an asynchronous operation".to_string(),
+        ))
+    })
+}
+
+/// Asynchronous operation of an `XmpFile`
+///
+/// Resolves to `undefined`, or to a `Uint8Array` for `writeToBytesAsync`.
+pub struct FileTask {
+    file: Arc<Mutex<RustXmpFile>>,
+    operation: Option<FileOperation>,
+}
+
+impl Task for FileTask {
+    type Output = Option<Vec<u8>>;
+    type JsValue = Option<Uint8Array>;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        task::run(&self.file, self.operation.take()).map_err(napi_error)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output.map(Uint8Array::new))
     }
 }
@@ -32,10 +32,10 @@ impl XmpMeta {
         self.inner
             .get_property(&namespace, &property)
             .map(|value| match value {
-                XmpValue::String(s) => s,
+                XmpValue::String(s) | XmpValue::Uri(s) => s,
                 XmpValue::Integer(i) => i.to_string(),
                 XmpValue::Boolean(b) => b.to_string(),
-                XmpValue::DateTime(d) => d,
+                XmpValue::DateTime(d) => d.format(),
                 _ => format!("{:?}", value),
             })
     }
@@ -77,4 +77,18 @@ impl XmpMeta {
             .delete_property(&namespace, &path)
             .map_err(|e| Error::from_reason(format!("{}", xmp_error_to_ohos_error(e))))
     }
+
+    /// Delete all properties of a namespace whose names match a pattern
+    ///
+    /// Returns the number of properties deleted.
+    pub fn delete_properties_matching(
+        &mut self,
+        namespace: String,
+        pattern: String,
+    ) -> Result<u32> {
+        self.inner
+            .delete_properties_matching(&namespace, &pattern)
+            .map(|count| count as u32)
+            .map_err(|e| Error::from_reason(format!("{}", xmp_error_to_ohos_error(e))))
+    }
 }
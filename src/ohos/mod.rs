@@ -29,24 +29,43 @@
 //!
 //! This module provides Node-API bindings that mirror the Rust API.
 //! Use `XmpFile` and `XmpMeta` classes in ArkTS just like in Rust.
+//!
+//! The Node-API bindings are only built for OpenHarmony targets. The
+//! operations of the asynchronous file methods are also built for the tests
+//! on other targets, since they don't use Node-API.
 
+#[cfg(target_ohos)]
 mod datetime;
+#[cfg(target_ohos)]
 mod error;
+#[cfg(target_ohos)]
 mod file;
+#[cfg(target_ohos)]
 mod meta;
+#[cfg(target_ohos)]
 mod namespace;
+#[cfg(target_ohos)]
 mod qualifier;
+mod task;
+#[cfg(target_ohos)]
 mod value;
 
+#[cfg(target_ohos)]
 pub use datetime::XmpDateTime;
+#[cfg(target_ohos)]
 pub use error::{XmpError, XmpErrorKind};
+#[cfg(target_ohos)]
 pub use file::{ReadOptions, XmpFile};
+#[cfg(target_ohos)]
 pub use meta::XmpMeta;
+#[cfg(target_ohos)]
 pub use namespace::{
     get_all_registered_namespaces, get_builtin_namespace_uris, get_namespace_prefix,
     get_namespace_uri, is_namespace_registered, namespace_uri, register_namespace, Namespace,
 };
+#[cfg(target_ohos)]
 pub use qualifier::Qualifier;
+#[cfg(target_ohos)]
 pub use value::{XmpValue, XmpValueKind};
 
 // Module registration is done automatically by napi-ohos runtime
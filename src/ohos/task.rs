@@ -0,0 +1,160 @@
+//! Operations of the asynchronous `XmpFile` methods
+//!
+//! The napi glue in `file.rs` runs these on the Node-API worker pool. They
+//! don't use Node-API, so they are tested without an OpenHarmony runtime.
+
+use std::ops::Deref;
+use std::sync::Mutex;
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::files::file::ReadOptions;
+use crate::XmpFile;
+
+/// An operation on a file, returning the bytes written if any
+pub(crate) type FileOperation = Box<dyn FnOnce(&mut XmpFile) -> XmpResult<Option<Vec<u8>>> + Send>;
+
+/// Open a file
+pub(crate) fn open(path: String, options: ReadOptions) -> FileOperation {
+    Box::new(move |file| {
+        file.open_with(path, options)?;
+        Ok(None)
+    })
+}
+
+/// Load XMP from file bytes
+pub(crate) fn from_bytes<D>(data: D, options: ReadOptions) -> FileOperation
+where
+    D: Deref<Target = [u8]> + Send + 'static,
+{
+    Box::new(move |file| {
+        file.from_bytes_with(&data, options)?;
+        Ok(None)
+    })
+}
+
+/// Save the metadata to a file
+pub(crate) fn save(path: String) -> FileOperation {
+    Box::new(move |file| {
+        file.save(path)?;
+        Ok(None)
+    })
+}
+
+/// Write the file to bytes
+pub(crate) fn write_to_bytes() -> FileOperation {
+    Box::new(|file| file.write_to_bytes().map(Some))
+}
+
+/// Run an operation once, waiting for the other operations on the file
+///
+/// An error rejects the `Promise` of the operation.
+pub(crate) fn run(
+    file: &Mutex<XmpFile>,
+    operation: Option<FileOperation>,
+) -> XmpResult<Option<Vec<u8>>> {
+    let operation =
+        operation.ok_or_else(|| XmpError::InternalError("The task has already run".to_string()))?;
+    // Unlike the synchronous methods, wait for other tasks on the file
+    let mut file = file.lock().map_err(|_| {
+        XmpError::InternalError("An earlier operation on the file panicked".to_string())
+    })?;
+    operation(&mut file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::{XmpMeta, XmpValue};
+    use std::sync::Arc;
+
+    /// A JPEG file with an XMP packet setting xmp:Label
+    fn jpeg(label: &str) -> Vec<u8> {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "Label", XmpValue::String(label.to_string()))
+            .unwrap();
+        let packet = meta.serialize_packet().unwrap();
+        let signature = b"http://ns.adobe.com/xap/1.0/\0";
+        let len = (2 + signature.len() + packet.len()) as u16;
+
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        data.extend_from_slice(&len.to_be_bytes());
+        data.extend_from_slice(signature);
+        data.extend_from_slice(packet.as_bytes());
+        data.extend_from_slice(&[0xFF, 0xD9]);
+        data
+    }
+
+    fn label(file: &Mutex<XmpFile>) -> Option<XmpValue> {
+        file.lock()
+            .unwrap()
+            .get_xmp()
+            .and_then(|meta| meta.get_property(ns::XMP, "Label"))
+    }
+
+    #[test]
+    fn test_open_and_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("photo.jpg");
+        std::fs::write(&path, jpeg("Red")).unwrap();
+        let path = path.to_str().unwrap().to_string();
+
+        let file = Mutex::new(XmpFile::new());
+        let options = ReadOptions::default().for_update();
+        assert_eq!(run(&file, Some(open(path.clone(), options))).unwrap(), None);
+        assert_eq!(label(&file), Some(XmpValue::String("Red".to_string())));
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "Label", XmpValue::String("Blue".to_string()))
+            .unwrap();
+        file.lock().unwrap().put_xmp(meta);
+        assert_eq!(run(&file, Some(save(path.clone()))).unwrap(), None);
+
+        let reread = Mutex::new(XmpFile::new());
+        run(&reread, Some(open(path, ReadOptions::default()))).unwrap();
+        assert_eq!(label(&reread), Some(XmpValue::String("Blue".to_string())));
+    }
+
+    #[test]
+    fn test_bytes() {
+        let file = Mutex::new(XmpFile::new());
+        let read = from_bytes(jpeg("Green"), ReadOptions::default());
+        assert_eq!(run(&file, Some(read)).unwrap(), None);
+        assert_eq!(label(&file), Some(XmpValue::String("Green".to_string())));
+
+        let bytes = run(&file, Some(write_to_bytes())).unwrap().unwrap();
+        let reread = Mutex::new(XmpFile::new());
+        run(&reread, Some(from_bytes(bytes, ReadOptions::default()))).unwrap();
+        assert_eq!(label(&reread), Some(XmpValue::String("Green".to_string())));
+    }
+
+    #[test]
+    fn test_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing").join("photo.jpg");
+        let missing = missing.to_str().unwrap().to_string();
+
+        // Saving a file that was never opened, or to a missing directory
+        let file = Mutex::new(XmpFile::new());
+        assert!(run(&file, Some(save(missing.clone()))).is_err());
+        run(&file, Some(from_bytes(jpeg("Red"), ReadOptions::default()))).unwrap();
+        assert!(run(&file, Some(save(missing.clone()))).is_err());
+        assert!(run(&file, Some(open(missing, ReadOptions::default()))).is_err());
+
+        // A task runs once
+        assert!(matches!(run(&file, None), Err(XmpError::InternalError(_))));
+
+        // A panic in an earlier operation poisons the file
+        let file = Arc::new(Mutex::new(XmpFile::new()));
+        let poisoned = Arc::clone(&file);
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoned.lock().unwrap();
+            panic!("operation panicked");
+        })
+        .join();
+        assert!(matches!(
+            run(&file, Some(write_to_bytes())),
+            Err(XmpError::InternalError(_))
+        ));
+    }
+}
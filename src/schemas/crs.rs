@@ -0,0 +1,341 @@
+//! Camera Raw develop settings (`crs:`)
+//!
+//! Lightroom and Camera Raw don't change the pixels of a RAW: they write
+//! the edits as `crs:` properties, in the XMP of the file or of its `.xmp`
+//! sidecar. [`DevelopSettings`] reads the common ones, so that an asset
+//! browser can show whether a RAW was edited and how it was cropped
+//! without rendering it.
+//!
+//! The crop is written as fractions of the width and height of the image,
+//! before the crop angle and the orientation are applied. Tone curves are
+//! arrays of "input, output" points from 0 to 255; those of process version
+//! 2012 and later (`crs:ToneCurvePV2012`) are applied on top of the
+//! parametric adjustments.
+//!
+//! # Example
+//!
+//! ```rust
+//! use xmpkit::schemas::crs::{DevelopSettings, WhiteBalance};
+//! use xmpkit::XmpMeta;
+//!
+//! let packet = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+//!   <rdf:Description rdf:about=""
+//!       xmlns:crs="http://ns.adobe.com/camera-raw-settings/1.0/"
+//!       crs:WhiteBalance="Custom" crs:Temperature="5200" crs:Exposure2012="+0.35"
+//!       crs:HasCrop="True" crs:CropTop="0.1" crs:CropLeft="0"
+//!       crs:CropBottom="0.9" crs:CropRight="1" crs:CropAngle="0"/>
+//! </rdf:RDF>"#;
+//! let settings = DevelopSettings::from_xmp(&XmpMeta::parse(packet).unwrap());
+//!
+//! assert_eq!(settings.white_balance, Some(WhiteBalance::Custom));
+//! assert_eq!(settings.exposure, Some(0.35));
+//! assert_eq!(settings.crop.unwrap().height(), 0.8);
+//! assert!(settings.has_edits());
+//! ```
+
+use super::{text, text_items};
+use crate::core::metadata::XmpMeta;
+use crate::core::namespace::ns;
+
+/// White balance preset (`crs:WhiteBalance`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WhiteBalance {
+    /// The white balance of the camera ("As Shot")
+    AsShot,
+    /// Estimated from the image ("Auto")
+    Auto,
+    /// Daylight
+    Daylight,
+    /// Cloudy
+    Cloudy,
+    /// Shade
+    Shade,
+    /// Tungsten
+    Tungsten,
+    /// Fluorescent
+    Fluorescent,
+    /// Flash
+    Flash,
+    /// Set from `crs:Temperature` and `crs:Tint` ("Custom")
+    Custom,
+    /// Another preset
+    Other(String),
+}
+
+impl WhiteBalance {
+    /// Get the `crs:WhiteBalance` value of the preset
+    pub fn as_str(&self) -> &str {
+        match self {
+            WhiteBalance::AsShot => "As Shot",
+            WhiteBalance::Auto => "Auto",
+            WhiteBalance::Daylight => "Daylight",
+            WhiteBalance::Cloudy => "Cloudy",
+            WhiteBalance::Shade => "Shade",
+            WhiteBalance::Tungsten => "Tungsten",
+            WhiteBalance::Fluorescent => "Fluorescent",
+            WhiteBalance::Flash => "Flash",
+            WhiteBalance::Custom => "Custom",
+            WhiteBalance::Other(other) => other,
+        }
+    }
+
+    /// Get the preset of a `crs:WhiteBalance` value
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "As Shot" => WhiteBalance::AsShot,
+            "Auto" => WhiteBalance::Auto,
+            "Daylight" => WhiteBalance::Daylight,
+            "Cloudy" => WhiteBalance::Cloudy,
+            "Shade" => WhiteBalance::Shade,
+            "Tungsten" => WhiteBalance::Tungsten,
+            "Fluorescent" => WhiteBalance::Fluorescent,
+            "Flash" => WhiteBalance::Flash,
+            "Custom" => WhiteBalance::Custom,
+            other => WhiteBalance::Other(other.to_string()),
+        }
+    }
+}
+
+/// A crop, in fractions of the width and height of the image
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Crop {
+    /// Top edge (`crs:CropTop`)
+    pub top: f64,
+    /// Left edge (`crs:CropLeft`)
+    pub left: f64,
+    /// Bottom edge (`crs:CropBottom`)
+    pub bottom: f64,
+    /// Right edge (`crs:CropRight`)
+    pub right: f64,
+    /// Rotation in degrees, clockwise (`crs:CropAngle`)
+    pub angle: f64,
+}
+
+impl Crop {
+    /// Get the width of the crop, as a fraction of the width of the image
+    pub fn width(&self) -> f64 {
+        self.right - self.left
+    }
+
+    /// Get the height of the crop, as a fraction of the height of the image
+    pub fn height(&self) -> f64 {
+        self.bottom - self.top
+    }
+
+    /// Check if the crop keeps the whole image, unrotated
+    pub fn is_full_frame(&self) -> bool {
+        self.top <= 0.0
+            && self.left <= 0.0
+            && self.bottom >= 1.0
+            && self.right >= 1.0
+            && self.angle == 0.0
+    }
+}
+
+/// Tone curves (`crs:ToneCurvePV2012` and its color channels)
+///
+/// A point is an input and an output level from 0 to 255; an empty curve
+/// is linear.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToneCurve {
+    /// Name of the curve, such as "Linear" or "Medium Contrast"
+    /// (`crs:ToneCurveName2012`)
+    pub name: Option<String>,
+    /// Points of the luminance curve (`crs:ToneCurvePV2012`)
+    pub luminance: Vec<(u32, u32)>,
+    /// Points of the red curve (`crs:ToneCurvePV2012Red`)
+    pub red: Vec<(u32, u32)>,
+    /// Points of the green curve (`crs:ToneCurvePV2012Green`)
+    pub green: Vec<(u32, u32)>,
+    /// Points of the blue curve (`crs:ToneCurvePV2012Blue`)
+    pub blue: Vec<(u32, u32)>,
+}
+
+impl ToneCurve {
+    /// Check if all the curves leave the levels unchanged
+    pub fn is_linear(&self) -> bool {
+        [&self.luminance, &self.red, &self.green, &self.blue]
+            .into_iter()
+            .flatten()
+            .all(|(input, output)| input == output)
+    }
+}
+
+/// Common develop settings of a RAW
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DevelopSettings {
+    /// Whether the settings were saved by Camera Raw (`crs:HasSettings`)
+    pub has_settings: Option<bool>,
+    /// Process version, such as "11.0" (`crs:ProcessVersion`)
+    pub process_version: Option<String>,
+    /// White balance preset (`crs:WhiteBalance`)
+    pub white_balance: Option<WhiteBalance>,
+    /// Color temperature in kelvins (`crs:Temperature`)
+    pub temperature: Option<i64>,
+    /// Tint, from -150 (green) to 150 (magenta) (`crs:Tint`)
+    pub tint: Option<i64>,
+    /// Exposure in stops, from -5 to 5 (`crs:Exposure2012`)
+    pub exposure: Option<f64>,
+    /// Crop, if one is applied (`crs:HasCrop` and the `crs:Crop*` fields)
+    pub crop: Option<Crop>,
+    /// Tone curves
+    pub tone_curve: ToneCurve,
+}
+
+impl DevelopSettings {
+    /// Read the develop settings of the metadata
+    ///
+    /// The crop is read if `crs:HasCrop` is `True`, or, when it is missing,
+    /// if the crop fields don't keep the whole image. Malformed numbers and
+    /// tone curve points are skipped.
+    pub fn from_xmp(meta: &XmpMeta) -> Self {
+        let number = |name: &str| meta.get_property_f64(ns::CAMERA_RAW, name);
+        let crop = Crop {
+            top: number("CropTop").unwrap_or(0.0),
+            left: number("CropLeft").unwrap_or(0.0),
+            bottom: number("CropBottom").unwrap_or(1.0),
+            right: number("CropRight").unwrap_or(1.0),
+            angle: number("CropAngle").unwrap_or(0.0),
+        };
+        let crop = match meta.get_property_bool(ns::CAMERA_RAW, "HasCrop") {
+            Some(true) => Some(crop),
+            Some(false) => None,
+            None => (!crop.is_full_frame()).then_some(crop),
+        };
+
+        Self {
+            has_settings: meta.get_property_bool(ns::CAMERA_RAW, "HasSettings"),
+            process_version: text(meta, ns::CAMERA_RAW, "ProcessVersion"),
+            white_balance: text(meta, ns::CAMERA_RAW, "WhiteBalance")
+                .map(|value| WhiteBalance::parse(&value)),
+            temperature: meta.get_property_i64(ns::CAMERA_RAW, "Temperature"),
+            tint: meta.get_property_i64(ns::CAMERA_RAW, "Tint"),
+            exposure: number("Exposure2012"),
+            crop,
+            tone_curve: ToneCurve {
+                name: text(meta, ns::CAMERA_RAW, "ToneCurveName2012"),
+                luminance: curve_points(meta, "ToneCurvePV2012"),
+                red: curve_points(meta, "ToneCurvePV2012Red"),
+                green: curve_points(meta, "ToneCurvePV2012Green"),
+                blue: curve_points(meta, "ToneCurvePV2012Blue"),
+            },
+        }
+    }
+
+    /// Check if the settings change the image: a crop, a non-zero exposure,
+    /// a white balance other than the camera's, or a tone curve that isn't
+    /// linear
+    pub fn has_edits(&self) -> bool {
+        self.crop.is_some()
+            || self.exposure.is_some_and(|exposure| exposure != 0.0)
+            || self
+                .white_balance
+                .as_ref()
+                .is_some_and(|white_balance| *white_balance != WhiteBalance::AsShot)
+            || !self.tone_curve.is_linear()
+    }
+}
+
+/// Read the "input, output" points of a tone curve array
+fn curve_points(meta: &XmpMeta, name: &str) -> Vec<(u32, u32)> {
+    text_items(meta, ns::CAMERA_RAW, name)
+        .iter()
+        .filter_map(|point| {
+            let (input, output) = point.split_once(',')?;
+            Some((input.trim().parse().ok()?, output.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_develop_settings() {
+        let packet = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+  <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+    <rdf:Description rdf:about=""
+        xmlns:crs="http://ns.adobe.com/camera-raw-settings/1.0/"
+        crs:ProcessVersion="11.0"
+        crs:WhiteBalance="As Shot"
+        crs:Temperature="5450"
+        crs:Tint="+6"
+        crs:Exposure2012="-0.50"
+        crs:HasSettings="True"
+        crs:CropTop="0.052"
+        crs:CropLeft="0.1"
+        crs:CropBottom="0.948"
+        crs:CropRight="0.9"
+        crs:CropAngle="-1.25"
+        crs:HasCrop="True"
+        crs:ToneCurveName2012="Custom">
+      <crs:ToneCurvePV2012>
+        <rdf:Seq>
+          <rdf:li>0, 0</rdf:li>
+          <rdf:li>64, 56</rdf:li>
+          <rdf:li>not a point</rdf:li>
+          <rdf:li>255, 255</rdf:li>
+        </rdf:Seq>
+      </crs:ToneCurvePV2012>
+      <crs:ToneCurvePV2012Red>
+        <rdf:Seq>
+          <rdf:li>0, 0</rdf:li>
+          <rdf:li>255, 255</rdf:li>
+        </rdf:Seq>
+      </crs:ToneCurvePV2012Red>
+    </rdf:Description>
+  </rdf:RDF>
+</x:xmpmeta>"#;
+        let settings = DevelopSettings::from_xmp(&XmpMeta::parse(packet).unwrap());
+        assert_eq!(settings.has_settings, Some(true));
+        assert_eq!(settings.process_version.as_deref(), Some("11.0"));
+        assert_eq!(settings.white_balance, Some(WhiteBalance::AsShot));
+        assert_eq!(settings.temperature, Some(5450));
+        assert_eq!(settings.tint, Some(6));
+        assert_eq!(settings.exposure, Some(-0.5));
+        let crop = settings.crop.unwrap();
+        assert_eq!(crop.angle, -1.25);
+        assert!((crop.width() - 0.8).abs() < 1e-9);
+        assert_eq!(settings.tone_curve.name.as_deref(), Some("Custom"));
+        assert_eq!(
+            settings.tone_curve.luminance,
+            vec![(0, 0), (64, 56), (255, 255)]
+        );
+        assert_eq!(settings.tone_curve.red, vec![(0, 0), (255, 255)]);
+        assert!(settings.tone_curve.green.is_empty());
+        assert!(!settings.tone_curve.is_linear());
+        assert!(settings.has_edits());
+    }
+
+    #[test]
+    fn test_unedited_settings() {
+        assert_eq!(
+            DevelopSettings::from_xmp(&XmpMeta::new()),
+            DevelopSettings::default()
+        );
+        assert!(!DevelopSettings::default().has_edits());
+
+        // Camera Raw keeps the last crop when it is turned off
+        let packet = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""
+      xmlns:crs="http://ns.adobe.com/camera-raw-settings/1.0/"
+      crs:WhiteBalance="As Shot" crs:Exposure2012="0.00"
+      crs:HasCrop="False" crs:CropTop="0.2" crs:CropBottom="0.8"/>
+</rdf:RDF>"#;
+        let settings = DevelopSettings::from_xmp(&XmpMeta::parse(packet).unwrap());
+        assert_eq!(settings.crop, None);
+        assert!(!settings.has_edits());
+
+        // Without crs:HasCrop, a crop that keeps the whole image is no crop
+        let packet = packet.replace(
+            r#"crs:HasCrop="False" crs:CropTop="0.2""#,
+            "crs:CropTop=\"0\"",
+        );
+        let settings = DevelopSettings::from_xmp(&XmpMeta::parse(&packet).unwrap());
+        assert_eq!(settings.crop.map(|crop| crop.bottom), Some(0.8));
+        let packet = packet.replace(r#"crs:CropBottom="0.8""#, r#"crs:CropBottom="1""#);
+        let settings = DevelopSettings::from_xmp(&XmpMeta::parse(&packet).unwrap());
+        assert_eq!(settings.crop, None);
+    }
+}
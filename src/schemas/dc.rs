@@ -0,0 +1,179 @@
+//! Dublin Core properties (`dc:`)
+//!
+//! `dc:title`, `dc:description` and `dc:rights` are language alternatives
+//! (`rdf:Alt` arrays with an `xml:lang` on each item), and `dc:creator` is an
+//! ordered array (`rdf:Seq`) of names. Writing them as simple values gives
+//! files that other tools read wrongly or not at all. The functions here
+//! always write the right form, and repair a property found in another
+//! form: a simple value becomes the only item (the `x-default` one for a
+//! language alternative), and the items of an array of another form are
+//! kept.
+//!
+//! Languages are RFC 3066 tags such as `"en-US"`, or `"x-default"`. When
+//! getting a text, a missing language falls back to another variant of the
+//! same language, then to `x-default`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use xmpkit::schemas::dc;
+//! use xmpkit::XmpMeta;
+//!
+//! let mut meta = XmpMeta::new();
+//! dc::set_title(&mut meta, "en-US", "Sunset").unwrap();
+//! dc::set_title(&mut meta, "fr-FR", "Coucher de soleil").unwrap();
+//! dc::add_creator(&mut meta, "Ada Lovelace").unwrap();
+//!
+//! assert_eq!(dc::title(&meta, "fr-CA").as_deref(), Some("Coucher de soleil"));
+//! assert_eq!(dc::title(&meta, "de-DE").as_deref(), Some("Sunset"));
+//! assert_eq!(dc::creators(&meta), ["Ada Lovelace"]);
+//! ```
+
+use super::{localized_text, property_values, repair_form, set_localized_text};
+use crate::core::error::XmpResult;
+use crate::core::metadata::XmpMeta;
+use crate::core::namespace::ns;
+use crate::core::node::ArrayType;
+
+/// Get `dc:title` in a language
+pub fn title(meta: &XmpMeta, lang: &str) -> Option<String> {
+    localized_text(meta, ns::DC, "title", lang)
+}
+
+/// Set `dc:title` in a language
+///
+/// The first title also becomes the `x-default` one.
+///
+/// Fails with `XmpError::BadParam` if `lang` is empty, or with
+/// `XmpError::BadValue` if `dc:title` is a structure.
+pub fn set_title(meta: &mut XmpMeta, lang: &str, title: &str) -> XmpResult<()> {
+    set_localized_text(meta, ns::DC, "title", lang, title)
+}
+
+/// Get `dc:description` in a language
+pub fn description(meta: &XmpMeta, lang: &str) -> Option<String> {
+    localized_text(meta, ns::DC, "description", lang)
+}
+
+/// Set `dc:description` in a language
+///
+/// The first description also becomes the `x-default` one.
+///
+/// Fails with `XmpError::BadParam` if `lang` is empty, or with
+/// `XmpError::BadValue` if `dc:description` is a structure.
+pub fn set_description(meta: &mut XmpMeta, lang: &str, description: &str) -> XmpResult<()> {
+    set_localized_text(meta, ns::DC, "description", lang, description)
+}
+
+/// Get the copyright notice of `dc:rights` in a language
+pub fn rights(meta: &XmpMeta, lang: &str) -> Option<String> {
+    localized_text(meta, ns::DC, "rights", lang)
+}
+
+/// Set the copyright notice of `dc:rights` in a language
+///
+/// The first notice also becomes the `x-default` one.
+///
+/// Fails with `XmpError::BadParam` if `lang` is empty, or with
+/// `XmpError::BadValue` if `dc:rights` is a structure.
+pub fn set_rights(meta: &mut XmpMeta, lang: &str, rights: &str) -> XmpResult<()> {
+    set_localized_text(meta, ns::DC, "rights", lang, rights)
+}
+
+/// Get the names of `dc:creator`, in order
+pub fn creators(meta: &XmpMeta) -> Vec<String> {
+    property_values(meta, ns::DC, "creator")
+}
+
+/// Append a name to `dc:creator`
+///
+/// Fails with `XmpError::BadValue` if `dc:creator` is a structure.
+pub fn add_creator(meta: &mut XmpMeta, creator: &str) -> XmpResult<()> {
+    repair_form(meta, ns::DC, "creator", ArrayType::Ordered)?;
+    meta.append_array_item_with(ns::DC, "creator", ArrayType::Ordered, creator.into())
+}
+
+/// Replace the names of `dc:creator`, or remove it if `creators` is empty
+pub fn set_creators(meta: &mut XmpMeta, creators: &[&str]) -> XmpResult<()> {
+    meta.delete_property(ns::DC, "creator")?;
+    for creator in creators {
+        meta.append_array_item_with(ns::DC, "creator", ArrayType::Ordered, (*creator).into())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_localized_properties() {
+        let mut meta = XmpMeta::new();
+        assert_eq!(title(&meta, "en-US"), None);
+        assert!(set_title(&mut meta, "", "Sunset").is_err());
+
+        set_title(&mut meta, "en-US", "Sunset").unwrap();
+        set_title(&mut meta, "de-DE", "Sonnenuntergang").unwrap();
+        set_description(&mut meta, "x-default", "A sunset over the sea").unwrap();
+        set_rights(&mut meta, "en", "© 2024 Ada Lovelace").unwrap();
+        assert_eq!(title(&meta, "de-AT").as_deref(), Some("Sonnenuntergang"));
+        assert_eq!(title(&meta, "fr-FR").as_deref(), Some("Sunset"));
+        assert_eq!(title(&meta, "x-default").as_deref(), Some("Sunset"));
+        assert_eq!(
+            description(&meta, "en-GB").as_deref(),
+            Some("A sunset over the sea")
+        );
+        assert_eq!(rights(&meta, "en").as_deref(), Some("© 2024 Ada Lovelace"));
+
+        let packet = meta.serialize_packet().unwrap();
+        assert!(packet.contains("<dc:title>\n"));
+        assert!(packet.contains("<rdf:Alt>"));
+    }
+
+    #[test]
+    fn test_repair_forms() {
+        let packet = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+  <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+    <rdf:Description rdf:about="" xmlns:dc="http://purl.org/dc/elements/1.1/">
+      <dc:creator>Ada Lovelace</dc:creator>
+      <dc:title>Sunset</dc:title>
+      <dc:rights>
+        <rdf:Bag><rdf:li>© 2024</rdf:li></rdf:Bag>
+      </dc:rights>
+    </rdf:Description>
+  </rdf:RDF>
+</x:xmpmeta>"#;
+        let mut meta = XmpMeta::parse(packet).unwrap();
+        assert_eq!(creators(&meta), ["Ada Lovelace"]);
+        assert_eq!(title(&meta, "en-US").as_deref(), Some("Sunset"));
+        assert_eq!(rights(&meta, "en-US").as_deref(), Some("© 2024"));
+
+        add_creator(&mut meta, "Charles Babbage").unwrap();
+        assert_eq!(creators(&meta), ["Ada Lovelace", "Charles Babbage"]);
+        assert_eq!(
+            crate::schemas::property_options(&meta, ns::DC, "creator")
+                .unwrap()
+                .array_type,
+            Some(ArrayType::Ordered)
+        );
+
+        // The only x-default item follows the first specific language
+        set_title(&mut meta, "fr-FR", "Coucher de soleil").unwrap();
+        assert_eq!(
+            title(&meta, "x-default").as_deref(),
+            Some("Coucher de soleil")
+        );
+        assert_eq!(meta.count_array_items(ns::DC, "title"), 2);
+        assert_eq!(title(&meta, "fr-FR").as_deref(), Some("Coucher de soleil"));
+        set_rights(&mut meta, "x-default", "© 2025").unwrap();
+        assert_eq!(
+            meta.get_localized_text(ns::DC, "rights", "", "x-default"),
+            Some(("© 2025".to_string(), "x-default".to_string()))
+        );
+
+        set_creators(&mut meta, &["Grace Hopper"]).unwrap();
+        assert_eq!(creators(&meta), ["Grace Hopper"]);
+        set_creators(&mut meta, &[]).unwrap();
+        assert!(!meta.does_property_exist(ns::DC, "creator"));
+    }
+}
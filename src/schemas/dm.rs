@@ -0,0 +1,421 @@
+//! XMP Dynamic Media properties (`xmpDM:`) of audio and video
+//!
+//! `xmpDM:duration` is an `xmpDM:Time` structure: a number of units of a
+//! rational scale in seconds, such as milliseconds (`1/1000`) or the frames
+//! of a 29.97 fps video (`1001/30000`). `xmpDM:startTimecode` is an
+//! `xmpDM:Timecode` structure, a SMPTE timecode with its format.
+//!
+//! Markers, such as chapters and cue points, are `xmpDM:Marker` structures
+//! in the `xmpDM:markers` array, or in the `xmpDM:markers` of the
+//! `xmpDM:Tracks` that Premiere writes. Their start times and durations are
+//! counted in frames of the frame rate of their track.
+//!
+//! # Example
+//!
+//! ```rust
+//! use xmpkit::schemas::dm::{self, Marker, MarkerType, MediaTime};
+//! use xmpkit::XmpMeta;
+//!
+//! let mut meta = XmpMeta::new();
+//! dm::set_duration(&mut meta, &MediaTime::new(90_000, 1, 1000)).unwrap();
+//! dm::add_marker(&mut meta, &Marker::chapter("Intro", "0")).unwrap();
+//! dm::add_marker(&mut meta, &Marker::chapter("Interview", "1500")).unwrap();
+//!
+//! assert_eq!(dm::duration(&meta).unwrap().seconds(), 90.0);
+//! let markers = dm::markers(&meta);
+//! assert_eq!(markers[1].name.as_deref(), Some("Interview"));
+//! assert_eq!(markers[1].marker_type, Some(MarkerType::Chapter));
+//! ```
+
+use super::{item_count, text};
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::core::namespace::ns;
+use crate::core::node::{ArrayNode, ArrayType, Node, StructureNode};
+
+pub use crate::utils::timecode::{MediaTime, TimeFormat};
+
+/// A SMPTE timecode (`xmpDM:Timecode`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timecode {
+    /// Format of the timecode (`xmpDM:timeFormat`)
+    pub format: TimeFormat,
+    /// The timecode, such as "01:00:00:00", or "01;00;00;00" for drop frame
+    /// formats (`xmpDM:timeValue`); see [`crate::utils::timecode`]
+    pub value: String,
+}
+
+/// Kind of a marker (`xmpDM:type`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarkerType {
+    /// A chapter
+    Chapter,
+    /// A cue point, for navigation or to trigger an event
+    Cue,
+    /// An index entry
+    Index,
+    /// A segment of speech
+    Speech,
+    /// A track
+    Track,
+    /// Another type
+    Other(String),
+}
+
+impl MarkerType {
+    /// Get the `xmpDM:type` value of the type
+    pub fn as_str(&self) -> &str {
+        match self {
+            MarkerType::Chapter => "Chapter",
+            MarkerType::Cue => "Cue",
+            MarkerType::Index => "Index",
+            MarkerType::Speech => "Speech",
+            MarkerType::Track => "Track",
+            MarkerType::Other(other) => other,
+        }
+    }
+
+    /// Get the type of an `xmpDM:type` value
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "Chapter" => MarkerType::Chapter,
+            "Cue" => MarkerType::Cue,
+            "Index" => MarkerType::Index,
+            "Speech" => MarkerType::Speech,
+            "Track" => MarkerType::Track,
+            other => MarkerType::Other(other.to_string()),
+        }
+    }
+}
+
+/// A marker (`xmpDM:Marker`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Marker {
+    /// Name (`xmpDM:name`)
+    pub name: Option<String>,
+    /// Kind of the marker (`xmpDM:type`)
+    pub marker_type: Option<MarkerType>,
+    /// Start, in frames of the frame rate of the track (`xmpDM:startTime`)
+    pub start_time: Option<String>,
+    /// Duration, in frames of the frame rate of the track
+    /// (`xmpDM:duration`)
+    pub duration: Option<String>,
+    /// Comment (`xmpDM:comment`)
+    pub comment: Option<String>,
+    /// URL the marker links to (`xmpDM:location`)
+    pub location: Option<String>,
+    /// Frame of the page the URL opens in (`xmpDM:target`)
+    pub target: Option<String>,
+    /// Kind of cue point, such as "Event" or "Navigation"
+    /// (`xmpDM:cuePointType`)
+    pub cue_point_type: Option<String>,
+    /// Parameters of the cue point, in order (`xmpDM:cuePointParams`)
+    pub cue_point_params: Vec<(String, String)>,
+}
+
+impl Marker {
+    /// Create a chapter marker starting at a frame
+    pub fn chapter(name: &str, start_time: &str) -> Self {
+        Self {
+            name: Some(name.to_string()),
+            marker_type: Some(MarkerType::Chapter),
+            start_time: Some(start_time.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+/// A track of markers (an item of `xmpDM:Tracks`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Track {
+    /// Name (`xmpDM:trackName`)
+    pub name: Option<String>,
+    /// Kind of the markers, such as "Comment" or "Chapter"
+    /// (`xmpDM:trackType`)
+    pub track_type: Option<String>,
+    /// Frame rate of the times of the markers, such as "f25" or
+    /// "f30000s1001" (`xmpDM:frameRate`)
+    pub frame_rate: Option<String>,
+    /// Markers, in order (`xmpDM:markers`)
+    pub markers: Vec<Marker>,
+}
+
+/// Get `xmpDM:duration`
+///
+/// Returns `None` if it is missing or malformed.
+pub fn duration(meta: &XmpMeta) -> Option<MediaTime> {
+    let value = text(meta, ns::XMP_DM, "duration/xmpDM:value")?;
+    let scale = text(meta, ns::XMP_DM, "duration/xmpDM:scale")?;
+    let (numerator, denominator) = scale.split_once('/')?;
+    let time = MediaTime::new(
+        value.trim().parse().ok()?,
+        numerator.trim().parse().ok()?,
+        denominator.trim().parse().ok()?,
+    );
+    (time.scale_denominator != 0).then_some(time)
+}
+
+/// Set `xmpDM:duration`
+///
+/// Fails with `XmpError::BadValue` if the denominator of the scale is 0.
+pub fn set_duration(meta: &mut XmpMeta, time: &MediaTime) -> XmpResult<()> {
+    if time.scale_denominator == 0 {
+        return Err(XmpError::BadValue(
+            "The scale of a time must not have a zero denominator".to_string(),
+        ));
+    }
+    let mut node = StructureNode::new();
+    set_field(&mut node, "value", time.value.to_string());
+    set_field(
+        &mut node,
+        "scale",
+        format!("{}/{}", time.scale_numerator, time.scale_denominator),
+    );
+    meta.set_property_node(ns::XMP_DM, "duration", Node::Structure(node))
+}
+
+/// Get `xmpDM:startTimecode`
+///
+/// Returns `None` if it is missing or its format is unknown.
+pub fn start_timecode(meta: &XmpMeta) -> Option<Timecode> {
+    let format = text(meta, ns::XMP_DM, "startTimecode/xmpDM:timeFormat")?;
+    Some(Timecode {
+        format: TimeFormat::parse(&format)?,
+        value: text(meta, ns::XMP_DM, "startTimecode/xmpDM:timeValue")?,
+    })
+}
+
+/// Set `xmpDM:startTimecode`
+pub fn set_start_timecode(meta: &mut XmpMeta, timecode: &Timecode) -> XmpResult<()> {
+    let mut node = StructureNode::new();
+    set_field(
+        &mut node,
+        "timeFormat",
+        timecode.format.as_str().to_string(),
+    );
+    set_field(&mut node, "timeValue", timecode.value.clone());
+    meta.set_property_node(ns::XMP_DM, "startTimecode", Node::Structure(node))
+}
+
+/// Get the markers of `xmpDM:markers`
+pub fn markers(meta: &XmpMeta) -> Vec<Marker> {
+    read_markers(meta, "markers")
+}
+
+/// Append a marker to `xmpDM:markers`
+///
+/// The other markers, and fields of them that [`Marker`] doesn't hold, are
+/// kept.
+pub fn add_marker(meta: &mut XmpMeta, marker: &Marker) -> XmpResult<()> {
+    meta.append_array_node(
+        ns::XMP_DM,
+        "markers",
+        Some(ArrayType::Ordered),
+        marker_node(marker),
+    )
+}
+
+/// Get the tracks of `xmpDM:Tracks`
+pub fn tracks(meta: &XmpMeta) -> Vec<Track> {
+    (1..=item_count(meta, ns::XMP_DM, "Tracks"))
+        .map(|i| {
+            let field =
+                |name: &str| text(meta, ns::XMP_DM, &format!("Tracks[{}]/xmpDM:{}", i, name));
+            Track {
+                name: field("trackName"),
+                track_type: field("trackType"),
+                frame_rate: field("frameRate"),
+                markers: read_markers(meta, &format!("Tracks[{}]/xmpDM:markers", i)),
+            }
+        })
+        .collect()
+}
+
+/// Replace `xmpDM:Tracks`, or remove it if there are no tracks
+pub fn set_tracks(meta: &mut XmpMeta, tracks: &[Track]) -> XmpResult<()> {
+    if tracks.is_empty() {
+        return meta.delete_property(ns::XMP_DM, "Tracks");
+    }
+    let mut list = ArrayNode::new(ArrayType::Unordered);
+    for track in tracks {
+        let mut node = StructureNode::new();
+        set_optional_field(&mut node, "trackName", &track.name);
+        set_optional_field(&mut node, "trackType", &track.track_type);
+        set_optional_field(&mut node, "frameRate", &track.frame_rate);
+        if !track.markers.is_empty() {
+            let mut markers = ArrayNode::new(ArrayType::Ordered);
+            for marker in &track.markers {
+                markers.append(marker_node(marker));
+            }
+            node.set_field(format!("{}:markers", ns::XMP_DM), Node::Array(markers));
+        }
+        list.append(Node::Structure(node));
+    }
+    meta.set_property_node(ns::XMP_DM, "Tracks", Node::Array(list))
+}
+
+/// Read the markers of an array property path
+fn read_markers(meta: &XmpMeta, path: &str) -> Vec<Marker> {
+    (1..=item_count(meta, ns::XMP_DM, path))
+        .map(|i| {
+            let item = format!("{}[{}]/xmpDM:", path, i);
+            let field = |name: &str| text(meta, ns::XMP_DM, &format!("{}{}", item, name));
+            let params = format!("{}cuePointParams", item);
+            Marker {
+                name: field("name"),
+                marker_type: field("type").map(|t| MarkerType::parse(&t)),
+                start_time: field("startTime"),
+                duration: field("duration"),
+                comment: field("comment"),
+                location: field("location"),
+                target: field("target"),
+                cue_point_type: field("cuePointType"),
+                cue_point_params: (1..=item_count(meta, ns::XMP_DM, &params))
+                    .filter_map(|j| {
+                        let param = |name: &str| {
+                            text(
+                                meta,
+                                ns::XMP_DM,
+                                &format!("{}[{}]/xmpDM:{}", params, j, name),
+                            )
+                        };
+                        Some((param("key")?, param("value").unwrap_or_default()))
+                    })
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// Create the structure of a marker
+fn marker_node(marker: &Marker) -> Node {
+    let mut node = StructureNode::new();
+    set_optional_field(&mut node, "name", &marker.name);
+    if let Some(marker_type) = &marker.marker_type {
+        set_field(&mut node, "type", marker_type.as_str().to_string());
+    }
+    set_optional_field(&mut node, "startTime", &marker.start_time);
+    set_optional_field(&mut node, "duration", &marker.duration);
+    set_optional_field(&mut node, "comment", &marker.comment);
+    set_optional_field(&mut node, "location", &marker.location);
+    set_optional_field(&mut node, "target", &marker.target);
+    set_optional_field(&mut node, "cuePointType", &marker.cue_point_type);
+    if !marker.cue_point_params.is_empty() {
+        let mut params = ArrayNode::new(ArrayType::Ordered);
+        for (key, value) in &marker.cue_point_params {
+            let mut param = StructureNode::new();
+            set_field(&mut param, "key", key.clone());
+            set_field(&mut param, "value", value.clone());
+            params.append(Node::Structure(param));
+        }
+        node.set_field(
+            format!("{}:cuePointParams", ns::XMP_DM),
+            Node::Array(params),
+        );
+    }
+    Node::Structure(node)
+}
+
+/// Set a simple field of an `xmpDM:` structure
+fn set_field(node: &mut StructureNode, name: &str, value: String) {
+    node.set_field(format!("{}:{}", ns::XMP_DM, name), Node::simple(value));
+}
+
+/// Set a simple field of an `xmpDM:` structure, if there is a value
+fn set_optional_field(node: &mut StructureNode, name: &str, value: &Option<String>) {
+    if let Some(value) = value {
+        set_field(node, name, value.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration_and_timecode() {
+        let mut meta = XmpMeta::new();
+        assert_eq!(duration(&meta), None);
+        assert!(set_duration(&mut meta, &MediaTime::new(10, 1, 0)).is_err());
+
+        let time = MediaTime::new(2997, 1001, 30000);
+        set_duration(&mut meta, &time).unwrap();
+        let timecode = Timecode {
+            format: TimeFormat::Fps2997Drop,
+            value: "01;00;00;00".to_string(),
+        };
+        set_start_timecode(&mut meta, &timecode).unwrap();
+
+        let packet = meta.serialize_packet().unwrap();
+        assert!(packet.contains("1001/30000"));
+        assert!(packet.contains("2997DropTimecode"));
+        let meta = XmpMeta::parse(&packet).unwrap();
+        assert_eq!(duration(&meta), Some(time));
+        assert!((time.seconds() - 99.9999).abs() < 1e-9);
+        assert_eq!(start_timecode(&meta), Some(timecode));
+        assert_eq!(TimeFormat::parse("29.97"), None);
+    }
+
+    #[test]
+    fn test_markers() {
+        let xml = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about="" xmlns:xmpDM="http://ns.adobe.com/xmp/1.0/DynamicMedia/">
+    <xmpDM:markers>
+      <rdf:Seq>
+        <rdf:li xmpDM:name="Opening" xmpDM:startTime="0" xmpDM:speaker="Host"/>
+      </rdf:Seq>
+    </xmpDM:markers>
+  </rdf:Description>
+</rdf:RDF>"#;
+        let mut meta = XmpMeta::parse(xml).unwrap();
+        let cue = Marker {
+            name: Some("Ad break".to_string()),
+            marker_type: Some(MarkerType::Cue),
+            start_time: Some("900".to_string()),
+            cue_point_type: Some("Event".to_string()),
+            cue_point_params: vec![
+                ("slot".to_string(), "midroll".to_string()),
+                ("length".to_string(), "30".to_string()),
+            ],
+            ..Default::default()
+        };
+        add_marker(&mut meta, &Marker::chapter("Part 1", "250")).unwrap();
+        add_marker(&mut meta, &cue).unwrap();
+
+        let packet = meta.serialize_packet().unwrap();
+        let meta = XmpMeta::parse(&packet).unwrap();
+        let markers = markers(&meta);
+        assert_eq!(markers.len(), 3);
+        assert_eq!(markers[0].name.as_deref(), Some("Opening"));
+        assert_eq!(markers[1], Marker::chapter("Part 1", "250"));
+        assert_eq!(markers[2], cue);
+        // Fields that Marker doesn't hold are kept
+        assert!(packet.contains("Host"));
+    }
+
+    #[test]
+    fn test_tracks() {
+        let mut meta = XmpMeta::new();
+        let tracks_in = vec![Track {
+            name: Some("Chapters".to_string()),
+            track_type: Some("Chapter".to_string()),
+            frame_rate: Some("f25".to_string()),
+            markers: vec![
+                Marker::chapter("Intro", "0"),
+                Marker {
+                    duration: Some("125".to_string()),
+                    ..Marker::chapter("Credits", "4500")
+                },
+            ],
+        }];
+        set_tracks(&mut meta, &tracks_in).unwrap();
+
+        let packet = meta.serialize_packet().unwrap();
+        assert!(packet.contains("<xmpDM:Tracks>"));
+        let mut meta = XmpMeta::parse(&packet).unwrap();
+        assert_eq!(tracks(&meta), tracks_in);
+
+        set_tracks(&mut meta, &[]).unwrap();
+        assert!(!meta.does_property_exist(ns::XMP_DM, "Tracks"));
+    }
+}
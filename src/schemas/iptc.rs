@@ -0,0 +1,576 @@
+//! IPTC Photo Metadata (`Iptc4xmpCore:`, `Iptc4xmpExt:`)
+//!
+//! [`IptcCore`] and [`IptcExtension`] hold the structured properties of the
+//! IPTC Core and Extension schemas: the creator's contact details, genre and
+//! scene codes, the locations and people shown, and the artworks pictured.
+//! `from_xmp` reads them, and `to_xmp` writes them with the forms of the
+//! IPTC Photo Metadata Standard, replacing the properties they hold and
+//! removing those left empty.
+//!
+//! `validate` checks the values against the IPTC controlled vocabularies
+//! and formats, and reports the problems as [`Violation`]s, like
+//! [`XmpValidator`](crate::core::validate::XmpValidator) does for the forms
+//! of the properties.
+//!
+//! # Example
+//!
+//! ```rust
+//! use xmpkit::schemas::iptc::{IptcCore, IptcExtension, LocationDetails};
+//! use xmpkit::XmpMeta;
+//!
+//! let mut meta = XmpMeta::new();
+//! let core = IptcCore {
+//!     scene_codes: vec!["011900".to_string()],
+//!     ..Default::default()
+//! };
+//! core.to_xmp(&mut meta).unwrap();
+//! assert!(core.validate().is_empty());
+//!
+//! let ext = IptcExtension {
+//!     location_shown: vec![LocationDetails {
+//!         city: Some("Paris".to_string()),
+//!         country_code: Some("FR".to_string()),
+//!         ..Default::default()
+//!     }],
+//!     ..Default::default()
+//! };
+//! ext.to_xmp(&mut meta).unwrap();
+//! assert_eq!(IptcExtension::from_xmp(&meta), ext);
+//! ```
+
+use super::{
+    default_text, item_count, lang_alt, set_bag, set_text, set_text_field, text, text_array,
+    text_items,
+};
+use crate::core::error::XmpResult;
+use crate::core::metadata::XmpMeta;
+use crate::core::namespace::ns;
+use crate::core::node::{ArrayNode, ArrayType, Node, StructureNode};
+use crate::core::validate::{ValueType, Violation, ViolationKind};
+use crate::utils::datetime::XmpDateTime;
+
+/// Codes of the IPTC Scene NewsCodes, allowed in `Iptc4xmpCore:Scene`
+pub const SCENE_CODES: &[&str] = &[
+    "010100", // headshot
+    "010200", // half-length
+    "010300", // full-length
+    "010400", // profile
+    "010500", // rear view
+    "010600", // single
+    "010700", // couple
+    "010800", // two
+    "010900", // group
+    "011000", // general view
+    "011100", // panoramic view
+    "011200", // aerial view
+    "011300", // under-water
+    "011400", // night scene
+    "011500", // satellite
+    "011600", // exterior view
+    "011700", // interior view
+    "011800", // close-up
+    "011900", // action
+    "012000", // performing
+    "012100", // posing
+    "012200", // symbolic
+    "012300", // off-beat
+    "012400", // movie scene
+];
+
+/// Properties of the IPTC Core schema
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IptcCore {
+    /// Contact details of the creator (`Iptc4xmpCore:CreatorContactInfo`)
+    pub creator_contact_info: Option<CreatorContactInfo>,
+    /// Nature of the content, such as "Actuality" or "Profile"
+    /// (`Iptc4xmpCore:IntellectualGenre`)
+    pub intellectual_genre: Option<String>,
+    /// IPTC Scene NewsCodes of the content (`Iptc4xmpCore:Scene`)
+    pub scene_codes: Vec<String>,
+    /// IPTC Subject NewsCodes of the content (`Iptc4xmpCore:SubjectCode`)
+    pub subject_codes: Vec<String>,
+}
+
+/// Contact details of the creator (`Iptc4xmpCore:CreatorContactInfo`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CreatorContactInfo {
+    /// Street address (`Iptc4xmpCore:CiAdrExtadr`)
+    pub address: Option<String>,
+    /// City (`Iptc4xmpCore:CiAdrCity`)
+    pub city: Option<String>,
+    /// State or province (`Iptc4xmpCore:CiAdrRegion`)
+    pub region: Option<String>,
+    /// Postal code (`Iptc4xmpCore:CiAdrPcode`)
+    pub postal_code: Option<String>,
+    /// Country (`Iptc4xmpCore:CiAdrCtry`)
+    pub country: Option<String>,
+    /// Work phone numbers, separated by commas (`Iptc4xmpCore:CiTelWork`)
+    pub phone: Option<String>,
+    /// Work email addresses, separated by commas (`Iptc4xmpCore:CiEmailWork`)
+    pub email: Option<String>,
+    /// Work web addresses, separated by commas (`Iptc4xmpCore:CiUrlWork`)
+    pub url: Option<String>,
+}
+
+/// Properties of the IPTC Extension schema
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IptcExtension {
+    /// Where the photo was taken (`Iptc4xmpExt:LocationCreated`)
+    pub location_created: Vec<LocationDetails>,
+    /// Locations shown in the photo (`Iptc4xmpExt:LocationShown`)
+    pub location_shown: Vec<LocationDetails>,
+    /// Names of the people shown (`Iptc4xmpExt:PersonInImage`)
+    pub persons_in_image: Vec<String>,
+    /// Artworks and objects shown (`Iptc4xmpExt:ArtworkOrObject`)
+    pub artwork_or_object: Vec<ArtworkOrObject>,
+}
+
+/// A location (`Iptc4xmpExt:LocationDetails`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LocationDetails {
+    /// Default name of the location (`Iptc4xmpExt:LocationName`)
+    pub name: Option<String>,
+    /// Identifiers of the location, such as gazetteer URIs
+    /// (`Iptc4xmpExt:LocationId`)
+    pub location_ids: Vec<String>,
+    /// Part of the city, such as a street or building
+    /// (`Iptc4xmpExt:Sublocation`)
+    pub sublocation: Option<String>,
+    /// City (`Iptc4xmpExt:City`)
+    pub city: Option<String>,
+    /// State or province (`Iptc4xmpExt:ProvinceState`)
+    pub province_state: Option<String>,
+    /// Country (`Iptc4xmpExt:CountryName`)
+    pub country_name: Option<String>,
+    /// ISO 3166 code of the country (`Iptc4xmpExt:CountryCode`)
+    pub country_code: Option<String>,
+    /// Region of the world, such as a continent (`Iptc4xmpExt:WorldRegion`)
+    pub world_region: Option<String>,
+}
+
+/// An artwork or object shown (`Iptc4xmpExt:ArtworkOrObjectDetails`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArtworkOrObject {
+    /// Default title (`Iptc4xmpExt:AOTitle`)
+    pub title: Option<String>,
+    /// Creators, in order (`Iptc4xmpExt:AOCreator`)
+    pub creators: Vec<String>,
+    /// When it was created, as an XMP date (`Iptc4xmpExt:AODateCreated`)
+    pub date_created: Option<String>,
+    /// Organisation or person holding it (`Iptc4xmpExt:AOSource`)
+    pub source: Option<String>,
+    /// Inventory number given by the source (`Iptc4xmpExt:AOSourceInvNo`)
+    pub source_inventory_number: Option<String>,
+    /// Copyright notice (`Iptc4xmpExt:AOCopyrightNotice`)
+    pub copyright_notice: Option<String>,
+}
+
+impl IptcCore {
+    /// Read the IPTC Core properties of the metadata
+    pub fn from_xmp(meta: &XmpMeta) -> Self {
+        let contact = |name: &str| {
+            text(
+                meta,
+                ns::IPTC_CORE,
+                &format!("CreatorContactInfo/Iptc4xmpCore:{}", name),
+            )
+        };
+        let info = CreatorContactInfo {
+            address: contact("CiAdrExtadr"),
+            city: contact("CiAdrCity"),
+            region: contact("CiAdrRegion"),
+            postal_code: contact("CiAdrPcode"),
+            country: contact("CiAdrCtry"),
+            phone: contact("CiTelWork"),
+            email: contact("CiEmailWork"),
+            url: contact("CiUrlWork"),
+        };
+        Self {
+            creator_contact_info: (!info.is_empty()).then_some(info),
+            intellectual_genre: text(meta, ns::IPTC_CORE, "IntellectualGenre"),
+            scene_codes: text_items(meta, ns::IPTC_CORE, "Scene"),
+            subject_codes: text_items(meta, ns::IPTC_CORE, "SubjectCode"),
+        }
+    }
+
+    /// Write the IPTC Core properties to the metadata
+    ///
+    /// Empty properties are removed.
+    pub fn to_xmp(&self, meta: &mut XmpMeta) -> XmpResult<()> {
+        match self.creator_contact_info.as_ref().filter(|i| !i.is_empty()) {
+            Some(info) => {
+                let mut node = StructureNode::new();
+                for (name, value) in info.fields() {
+                    set_text_field(&mut node, ns::IPTC_CORE, name, value);
+                }
+                meta.set_property_node(ns::IPTC_CORE, "CreatorContactInfo", Node::Structure(node))?;
+            }
+            None => meta.delete_property(ns::IPTC_CORE, "CreatorContactInfo")?,
+        }
+        set_text(
+            meta,
+            ns::IPTC_CORE,
+            "IntellectualGenre",
+            &self.intellectual_genre,
+        )?;
+        set_bag(meta, ns::IPTC_CORE, "Scene", &self.scene_codes)?;
+        set_bag(meta, ns::IPTC_CORE, "SubjectCode", &self.subject_codes)
+    }
+
+    /// Check the values against the IPTC controlled vocabularies
+    ///
+    /// Scene codes must be IPTC Scene NewsCodes ([`SCENE_CODES`]), and
+    /// subject codes IPTC Subject NewsCodes (8 digits, from the subjects
+    /// `01000000` to `17000000`).
+    pub fn validate(&self) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for (i, code) in self.scene_codes.iter().enumerate() {
+            if !SCENE_CODES.contains(&code.as_str()) {
+                violations.push(Violation {
+                    schema_ns: ns::IPTC_CORE.to_string(),
+                    path: format!("Iptc4xmpCore:Scene[{}]", i + 1),
+                    kind: ViolationKind::NotInChoice {
+                        value: code.clone(),
+                        allowed: SCENE_CODES.iter().map(|c| c.to_string()).collect(),
+                    },
+                });
+            }
+        }
+        for (i, code) in self.subject_codes.iter().enumerate() {
+            if !is_subject_code(code) {
+                violations.push(Violation {
+                    schema_ns: ns::IPTC_CORE.to_string(),
+                    path: format!("Iptc4xmpCore:SubjectCode[{}]", i + 1),
+                    kind: ViolationKind::InvalidValue {
+                        expected: ValueType::Text,
+                        value: code.clone(),
+                    },
+                });
+            }
+        }
+        violations
+    }
+}
+
+impl CreatorContactInfo {
+    /// Names of the fields and their values
+    fn fields(&self) -> [(&'static str, &Option<String>); 8] {
+        [
+            ("CiAdrExtadr", &self.address),
+            ("CiAdrCity", &self.city),
+            ("CiAdrRegion", &self.region),
+            ("CiAdrPcode", &self.postal_code),
+            ("CiAdrCtry", &self.country),
+            ("CiTelWork", &self.phone),
+            ("CiEmailWork", &self.email),
+            ("CiUrlWork", &self.url),
+        ]
+    }
+
+    /// Check if no field is set
+    fn is_empty(&self) -> bool {
+        self.fields().iter().all(|(_, value)| value.is_none())
+    }
+}
+
+impl IptcExtension {
+    /// Read the IPTC Extension properties of the metadata
+    pub fn from_xmp(meta: &XmpMeta) -> Self {
+        Self {
+            location_created: read_locations(meta, "LocationCreated"),
+            location_shown: read_locations(meta, "LocationShown"),
+            persons_in_image: text_items(meta, ns::IPTC_EXT, "PersonInImage"),
+            artwork_or_object: (1..=item_count(meta, ns::IPTC_EXT, "ArtworkOrObject"))
+                .map(|i| {
+                    let item = format!("ArtworkOrObject[{}]/Iptc4xmpExt:", i);
+                    let field = |name: &str| text(meta, ns::IPTC_EXT, &format!("{}{}", item, name));
+                    ArtworkOrObject {
+                        title: default_text(meta, ns::IPTC_EXT, &format!("{}AOTitle", item)),
+                        creators: text_items(meta, ns::IPTC_EXT, &format!("{}AOCreator", item)),
+                        date_created: field("AODateCreated"),
+                        source: field("AOSource"),
+                        source_inventory_number: field("AOSourceInvNo"),
+                        copyright_notice: field("AOCopyrightNotice"),
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Write the IPTC Extension properties to the metadata
+    ///
+    /// Empty properties are removed.
+    pub fn to_xmp(&self, meta: &mut XmpMeta) -> XmpResult<()> {
+        write_locations(meta, "LocationCreated", &self.location_created)?;
+        write_locations(meta, "LocationShown", &self.location_shown)?;
+        set_bag(meta, ns::IPTC_EXT, "PersonInImage", &self.persons_in_image)?;
+
+        if self.artwork_or_object.is_empty() {
+            return meta.delete_property(ns::IPTC_EXT, "ArtworkOrObject");
+        }
+        let mut list = ArrayNode::new(ArrayType::Unordered);
+        for artwork in &self.artwork_or_object {
+            let mut node = StructureNode::new();
+            if let Some(title) = &artwork.title {
+                node.set_field(format!("{}:AOTitle", ns::IPTC_EXT), lang_alt(title));
+            }
+            if !artwork.creators.is_empty() {
+                node.set_field(
+                    format!("{}:AOCreator", ns::IPTC_EXT),
+                    text_array(ArrayType::Ordered, &artwork.creators),
+                );
+            }
+            set_text_field(
+                &mut node,
+                ns::IPTC_EXT,
+                "AODateCreated",
+                &artwork.date_created,
+            );
+            set_text_field(&mut node, ns::IPTC_EXT, "AOSource", &artwork.source);
+            set_text_field(
+                &mut node,
+                ns::IPTC_EXT,
+                "AOSourceInvNo",
+                &artwork.source_inventory_number,
+            );
+            set_text_field(
+                &mut node,
+                ns::IPTC_EXT,
+                "AOCopyrightNotice",
+                &artwork.copyright_notice,
+            );
+            list.append(Node::Structure(node));
+        }
+        meta.set_property_node(ns::IPTC_EXT, "ArtworkOrObject", Node::Array(list))
+    }
+
+    /// Check the values against the IPTC controlled vocabularies and formats
+    ///
+    /// Country codes must be ISO 3166 codes of 2 or 3 capital letters, and
+    /// the dates of artworks XMP dates.
+    pub fn validate(&self) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let mut push = |path: String, expected: ValueType, value: &str| {
+            violations.push(Violation {
+                schema_ns: ns::IPTC_EXT.to_string(),
+                path,
+                kind: ViolationKind::InvalidValue {
+                    expected,
+                    value: value.to_string(),
+                },
+            })
+        };
+        let locations = [
+            ("LocationCreated", &self.location_created),
+            ("LocationShown", &self.location_shown),
+        ];
+        for (name, locations) in locations {
+            for (i, location) in locations.iter().enumerate() {
+                if let Some(code) = location.country_code.as_deref() {
+                    if !is_country_code(code) {
+                        push(
+                            format!("Iptc4xmpExt:{}[{}]/Iptc4xmpExt:CountryCode", name, i + 1),
+                            ValueType::Text,
+                            code,
+                        );
+                    }
+                }
+            }
+        }
+        for (i, artwork) in self.artwork_or_object.iter().enumerate() {
+            if let Some(date) = artwork.date_created.as_deref() {
+                if XmpDateTime::parse(date).is_err() {
+                    push(
+                        format!(
+                            "Iptc4xmpExt:ArtworkOrObject[{}]/Iptc4xmpExt:AODateCreated",
+                            i + 1
+                        ),
+                        ValueType::Date,
+                        date,
+                    );
+                }
+            }
+        }
+        violations
+    }
+}
+
+impl LocationDetails {
+    /// Names of the simple fields and their values
+    fn fields(&self) -> [(&'static str, &Option<String>); 6] {
+        [
+            ("Sublocation", &self.sublocation),
+            ("City", &self.city),
+            ("ProvinceState", &self.province_state),
+            ("CountryName", &self.country_name),
+            ("CountryCode", &self.country_code),
+            ("WorldRegion", &self.world_region),
+        ]
+    }
+}
+
+/// Read a bag of `Iptc4xmpExt:LocationDetails`
+fn read_locations(meta: &XmpMeta, name: &str) -> Vec<LocationDetails> {
+    (1..=item_count(meta, ns::IPTC_EXT, name))
+        .map(|i| {
+            let item = format!("{}[{}]/Iptc4xmpExt:", name, i);
+            let field = |name: &str| text(meta, ns::IPTC_EXT, &format!("{}{}", item, name));
+            LocationDetails {
+                name: default_text(meta, ns::IPTC_EXT, &format!("{}LocationName", item)),
+                location_ids: text_items(meta, ns::IPTC_EXT, &format!("{}LocationId", item)),
+                sublocation: field("Sublocation"),
+                city: field("City"),
+                province_state: field("ProvinceState"),
+                country_name: field("CountryName"),
+                country_code: field("CountryCode"),
+                world_region: field("WorldRegion"),
+            }
+        })
+        .collect()
+}
+
+/// Replace a bag of `Iptc4xmpExt:LocationDetails`, or remove it if there
+/// are no locations
+fn write_locations(meta: &mut XmpMeta, name: &str, locations: &[LocationDetails]) -> XmpResult<()> {
+    if locations.is_empty() {
+        return meta.delete_property(ns::IPTC_EXT, name);
+    }
+    let mut list = ArrayNode::new(ArrayType::Unordered);
+    for location in locations {
+        let mut node = StructureNode::new();
+        if let Some(name) = &location.name {
+            node.set_field(format!("{}:LocationName", ns::IPTC_EXT), lang_alt(name));
+        }
+        if !location.location_ids.is_empty() {
+            node.set_field(
+                format!("{}:LocationId", ns::IPTC_EXT),
+                text_array(ArrayType::Unordered, &location.location_ids),
+            );
+        }
+        for (name, value) in location.fields() {
+            set_text_field(&mut node, ns::IPTC_EXT, name, value);
+        }
+        list.append(Node::Structure(node));
+    }
+    meta.set_property_node(ns::IPTC_EXT, name, Node::Array(list))
+}
+
+/// Check if a code is an IPTC Subject NewsCode
+fn is_subject_code(code: &str) -> bool {
+    code.len() == 8
+        && code.bytes().all(|b| b.is_ascii_digit())
+        && matches!(code[..2].parse::<u8>(), Ok(1..=17))
+}
+
+/// Check if a code has the form of an ISO 3166 country code
+fn is_country_code(code: &str) -> bool {
+    (2..=3).contains(&code.len()) && code.bytes().all(|b| b.is_ascii_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iptc_core_round_trip() {
+        let mut meta = XmpMeta::new();
+        assert_eq!(IptcCore::from_xmp(&meta), IptcCore::default());
+
+        let core = IptcCore {
+            creator_contact_info: Some(CreatorContactInfo {
+                city: Some("Geneva".to_string()),
+                email: Some("photo@example.com".to_string()),
+                ..Default::default()
+            }),
+            intellectual_genre: Some("Actuality".to_string()),
+            scene_codes: vec!["010900".to_string(), "011900".to_string()],
+            subject_codes: vec!["15054000".to_string()],
+        };
+        core.to_xmp(&mut meta).unwrap();
+        assert!(core.validate().is_empty());
+
+        let packet = meta.serialize_packet().unwrap();
+        assert!(packet.contains("<Iptc4xmpCore:Scene>"));
+        assert!(packet.contains("Iptc4xmpCore:CiEmailWork"));
+        let mut meta = XmpMeta::parse(&packet).unwrap();
+        assert_eq!(IptcCore::from_xmp(&meta), core);
+
+        IptcCore::default().to_xmp(&mut meta).unwrap();
+        assert!(!meta.does_property_exist(ns::IPTC_CORE, "Scene"));
+        assert!(!meta.does_property_exist(ns::IPTC_CORE, "CreatorContactInfo"));
+
+        let invalid = IptcCore {
+            scene_codes: vec!["010900".to_string(), "headshot".to_string()],
+            subject_codes: vec!["99000000".to_string(), "1505400".to_string()],
+            ..Default::default()
+        };
+        let paths: Vec<String> = invalid.validate().into_iter().map(|v| v.path).collect();
+        assert_eq!(
+            paths,
+            [
+                "Iptc4xmpCore:Scene[2]",
+                "Iptc4xmpCore:SubjectCode[1]",
+                "Iptc4xmpCore:SubjectCode[2]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_iptc_extension_round_trip() {
+        let mut meta = XmpMeta::new();
+        let ext = IptcExtension {
+            location_created: vec![LocationDetails {
+                name: Some("Eiffel Tower".to_string()),
+                location_ids: vec!["https://sws.geonames.org/6254976/".to_string()],
+                city: Some("Paris".to_string()),
+                country_code: Some("FRA".to_string()),
+                world_region: Some("Europe".to_string()),
+                ..Default::default()
+            }],
+            location_shown: vec![
+                LocationDetails {
+                    city: Some("Paris".to_string()),
+                    ..Default::default()
+                },
+                LocationDetails {
+                    sublocation: Some("Trocadéro".to_string()),
+                    ..Default::default()
+                },
+            ],
+            persons_in_image: vec!["Ada Lovelace".to_string()],
+            artwork_or_object: vec![ArtworkOrObject {
+                title: Some("Mona Lisa".to_string()),
+                creators: vec!["Leonardo da Vinci".to_string()],
+                date_created: Some("1503".to_string()),
+                source: Some("Musée du Louvre".to_string()),
+                source_inventory_number: Some("INV 779".to_string()),
+                copyright_notice: None,
+            }],
+        };
+        ext.to_xmp(&mut meta).unwrap();
+        assert!(ext.validate().is_empty());
+
+        let packet = meta.serialize_packet().unwrap();
+        assert!(packet.contains("<Iptc4xmpExt:LocationCreated>"));
+        assert!(packet.contains("xml:lang=\"x-default\""));
+        let meta = XmpMeta::parse(&packet).unwrap();
+        assert_eq!(IptcExtension::from_xmp(&meta), ext);
+
+        let mut invalid = ext;
+        invalid.location_shown[0].country_code = Some("fr".to_string());
+        invalid.artwork_or_object[0].date_created = Some("circa 1503".to_string());
+        let violations = invalid.validate();
+        assert_eq!(violations.len(), 2);
+        assert_eq!(
+            violations[0].path,
+            "Iptc4xmpExt:LocationShown[1]/Iptc4xmpExt:CountryCode"
+        );
+        assert!(matches!(
+            violations[1].kind,
+            ViolationKind::InvalidValue {
+                expected: ValueType::Date,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,200 @@
+//! Accessors for standard XMP schemas
+//!
+//! The functions and types of these modules read and write the properties
+//! of a schema with the value forms it requires, such as an `rdf:Seq` for
+//! `dc:creator` or a language alternative for `dc:title`, so that callers
+//! don't have to know them.
+
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::{IterOptions, PropertyOptions, XmpMeta};
+use crate::core::namespace::ns;
+use crate::core::node::{ArrayNode, ArrayType, Node, StructureNode};
+use crate::types::qualifier::Qualifier;
+use crate::types::value::XmpValue;
+
+pub mod crs;
+pub mod dc;
+pub mod dm;
+pub mod iptc;
+pub mod plus;
+pub mod rights;
+
+/// Language of the default item of a language alternative
+const X_DEFAULT: &str = "x-default";
+
+/// Get a language alternative in a language, or the value of a property
+/// written in another form
+fn localized_text(meta: &XmpMeta, namespace: &str, name: &str, lang: &str) -> Option<String> {
+    match meta.get_localized_text(namespace, name, generic_lang(lang), lang) {
+        Some((text, _)) => Some(text),
+        None => property_values(meta, namespace, name).into_iter().next(),
+    }
+}
+
+/// Set a language alternative in a language
+fn set_localized_text(
+    meta: &mut XmpMeta,
+    namespace: &str,
+    name: &str,
+    lang: &str,
+    text: &str,
+) -> XmpResult<()> {
+    if lang.is_empty() {
+        return Err(XmpError::BadParam("Language must not be empty".to_string()));
+    }
+    repair_form(meta, namespace, name, ArrayType::Alternative)?;
+    meta.set_localized_text(namespace, name, generic_lang(lang), lang, text)
+}
+
+/// Get the primary subtag of a language ("en" of "en-US"), if it has others
+fn generic_lang(lang: &str) -> &str {
+    lang.split_once('-')
+        .filter(|_| !lang.eq_ignore_ascii_case(X_DEFAULT))
+        .map_or("", |(generic, _)| generic)
+}
+
+/// Get the text of a simple property, or the text items of an array
+fn property_values(meta: &XmpMeta, namespace: &str, name: &str) -> Vec<String> {
+    let Some(options) = property_options(meta, namespace, name) else {
+        return Vec::new();
+    };
+    if options.is_simple() {
+        return match meta.get_property(namespace, name) {
+            Some(XmpValue::String(text)) if !text.is_empty() => vec![text],
+            _ => Vec::new(),
+        };
+    }
+    let count = meta.get_array_size(namespace, name).unwrap_or(0);
+    (0..count)
+        .filter_map(|i| match meta.get_array_item(namespace, name, i)? {
+            XmpValue::String(text) => Some(text),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Get the kind of a top-level property
+fn property_options(meta: &XmpMeta, namespace: &str, name: &str) -> Option<PropertyOptions> {
+    meta.iter_with(IterOptions::default().namespace(namespace).just_children())
+        .find(|item| item.path.split_once(':').is_some_and(|(_, n)| n == name))
+        .map(|item| item.options)
+}
+
+/// Rewrite a property found as a simple value or an array of another form
+/// as an array of `form`, keeping its text
+fn repair_form(meta: &mut XmpMeta, namespace: &str, name: &str, form: ArrayType) -> XmpResult<()> {
+    let Some(options) = property_options(meta, namespace, name) else {
+        return Ok(());
+    };
+    if options.array_type == Some(form) {
+        return Ok(());
+    }
+    if options.is_struct {
+        return Err(XmpError::BadValue(format!(
+            "Property '{}' of '{}' is a structure, expected an rdf:{} array",
+            name,
+            namespace,
+            form.rdf_type()
+        )));
+    }
+
+    let values = property_values(meta, namespace, name);
+    meta.delete_property(namespace, name)?;
+    for (i, value) in values.into_iter().enumerate() {
+        // Items of other arrays have no language: the first one becomes the
+        // default, the others are kept without a language
+        if form == ArrayType::Alternative && i == 0 {
+            meta.set_localized_text(namespace, name, "", X_DEFAULT, &value)?;
+            continue;
+        }
+        meta.append_array_item_with(namespace, name, form, value.into())?;
+    }
+    Ok(())
+}
+
+/// Get the text of a property path, if it is not empty
+fn text(meta: &XmpMeta, namespace: &str, path: &str) -> Option<String> {
+    match meta.get_property_by_path(namespace, path)? {
+        XmpValue::String(s) | XmpValue::Uri(s) if !s.is_empty() => Some(s),
+        _ => None,
+    }
+}
+
+/// Get the `x-default` item of a language alternative property path, or
+/// its first item
+fn default_text(meta: &XmpMeta, namespace: &str, path: &str) -> Option<String> {
+    text(
+        meta,
+        namespace,
+        &format!("{}[?xml:lang=\"{}\"]", path, X_DEFAULT),
+    )
+    .or_else(|| text(meta, namespace, &format!("{}[1]", path)))
+}
+
+/// Count the items of an array property path
+fn item_count(meta: &XmpMeta, namespace: &str, path: &str) -> usize {
+    (1..)
+        .take_while(|i| {
+            meta.get_property_by_path(namespace, &format!("{}[{}]", path, i))
+                .is_some()
+        })
+        .count()
+}
+
+/// Get the text items of an array property path
+fn text_items(meta: &XmpMeta, namespace: &str, path: &str) -> Vec<String> {
+    (1..=item_count(meta, namespace, path))
+        .filter_map(|i| text(meta, namespace, &format!("{}[{}]", path, i)))
+        .collect()
+}
+
+/// Set a simple property, or remove it if there is no value
+fn set_text(
+    meta: &mut XmpMeta,
+    namespace: &str,
+    name: &str,
+    value: &Option<String>,
+) -> XmpResult<()> {
+    match value {
+        Some(value) => meta.set_property(namespace, name, value.as_str().into()),
+        None => meta.delete_property(namespace, name),
+    }
+}
+
+/// Replace a bag of text, or remove it if there are no items
+fn set_bag(meta: &mut XmpMeta, namespace: &str, name: &str, items: &[String]) -> XmpResult<()> {
+    if items.is_empty() {
+        meta.delete_property(namespace, name)
+    } else {
+        meta.set_property_node(namespace, name, text_array(ArrayType::Unordered, items))
+    }
+}
+
+/// Set a simple field of a structure, if there is a value
+fn set_text_field(node: &mut StructureNode, namespace: &str, name: &str, value: &Option<String>) {
+    if let Some(value) = value {
+        node.set_field(
+            format!("{}:{}", namespace, name),
+            Node::simple(value.as_str()),
+        );
+    }
+}
+
+/// Create an array of text items
+fn text_array(array_type: ArrayType, items: &[String]) -> Node {
+    let mut array = ArrayNode::new(array_type);
+    for item in items {
+        array.append(Node::simple(item.as_str()));
+    }
+    Node::Array(array)
+}
+
+/// Create a language alternative with an `x-default` item
+fn lang_alt(text: &str) -> Node {
+    let mut item = Node::simple(text);
+    item.qualifiers_mut()
+        .push(Qualifier::new(ns::XML, "lang", X_DEFAULT));
+    let mut array = ArrayNode::new(ArrayType::Alternative);
+    array.append(item);
+    Node::Array(array)
+}
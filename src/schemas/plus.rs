@@ -0,0 +1,365 @@
+//! PLUS licensing properties (`plus:`)
+//!
+//! The PLUS License Data Format describes who licenses an image, who owns
+//! its copyright and created it, and whether releases were obtained from the
+//! people and owners of the property it shows. [`Plus`] holds these
+//! properties: `from_xmp` reads them, and `to_xmp` writes them, replacing
+//! the properties it holds and removing those left empty.
+//!
+//! The release statuses are closed choices of PLUS vocabulary URIs, such as
+//! `http://ns.useplus.org/ldf/vocab/MR-NON`. [`ModelReleaseStatus`] and
+//! [`PropertyReleaseStatus`] give them, and
+//! [`XmpValidator`](crate::core::validate::XmpValidator) reports other
+//! values.
+//!
+//! # Example
+//!
+//! ```rust
+//! use xmpkit::schemas::plus::{Licensor, ModelReleaseStatus, Plus};
+//! use xmpkit::XmpMeta;
+//!
+//! let mut meta = XmpMeta::new();
+//! let plus = Plus {
+//!     licensors: vec![Licensor {
+//!         name: Some("Example Images".to_string()),
+//!         url: Some("https://images.example.com".to_string()),
+//!         ..Default::default()
+//!     }],
+//!     model_release_status: Some(ModelReleaseStatus::Unlimited),
+//!     model_release_ids: vec!["MR-2024-001".to_string()],
+//!     ..Default::default()
+//! };
+//! plus.to_xmp(&mut meta).unwrap();
+//! assert_eq!(Plus::from_xmp(&meta), plus);
+//! ```
+
+use super::{item_count, set_bag, set_text, set_text_field, text, text_items};
+use crate::core::error::XmpResult;
+use crate::core::metadata::XmpMeta;
+use crate::core::namespace::ns;
+use crate::core::node::{ArrayNode, ArrayType, Node, StructureNode};
+
+/// Prefix of the URIs of the PLUS vocabularies
+const VOCABULARY: &str = "http://ns.useplus.org/ldf/vocab/";
+
+/// PLUS licensing properties
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Plus {
+    /// Licensors, in order (`plus:Licensor`)
+    pub licensors: Vec<Licensor>,
+    /// Copyright owners, in order (`plus:CopyrightOwner`)
+    pub copyright_owners: Vec<Party>,
+    /// Creators of the image, in order (`plus:ImageCreator`)
+    pub image_creators: Vec<Party>,
+    /// Whether the people shown signed releases (`plus:ModelReleaseStatus`)
+    pub model_release_status: Option<ModelReleaseStatus>,
+    /// Identifiers of the model releases (`plus:ModelReleaseID`)
+    pub model_release_ids: Vec<String>,
+    /// Whether the owners of the property shown signed releases
+    /// (`plus:PropertyReleaseStatus`)
+    pub property_release_status: Option<PropertyReleaseStatus>,
+    /// Identifiers of the property releases (`plus:PropertyReleaseID`)
+    pub property_release_ids: Vec<String>,
+}
+
+/// A licensor of the image (an item of `plus:Licensor`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Licensor {
+    /// Name (`plus:LicensorName`)
+    pub name: Option<String>,
+    /// Identifier given by a registry (`plus:LicensorID`)
+    pub id: Option<String>,
+    /// Email address (`plus:LicensorEmail`)
+    pub email: Option<String>,
+    /// Telephone number (`plus:LicensorTelephone1`)
+    pub telephone: Option<String>,
+    /// Web address (`plus:LicensorURL`)
+    pub url: Option<String>,
+}
+
+/// A copyright owner or image creator (an item of `plus:CopyrightOwner` or
+/// `plus:ImageCreator`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Party {
+    /// Name (`plus:CopyrightOwnerName` or `plus:ImageCreatorName`)
+    pub name: Option<String>,
+    /// Identifier given by a registry (`plus:CopyrightOwnerID` or
+    /// `plus:ImageCreatorID`)
+    pub id: Option<String>,
+}
+
+/// Whether the people shown signed model releases
+/// (`plus:ModelReleaseStatus`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelReleaseStatus {
+    /// No release (`MR-NON`)
+    None,
+    /// No release is needed (`MR-NAP`)
+    NotApplicable,
+    /// Releases without limits (`MR-UMR`)
+    Unlimited,
+    /// Limited or incomplete releases (`MR-LMR`)
+    Limited,
+}
+
+/// Whether the owners of the property shown signed property releases
+/// (`plus:PropertyReleaseStatus`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyReleaseStatus {
+    /// No release (`PR-NON`)
+    None,
+    /// No release is needed (`PR-NAP`)
+    NotApplicable,
+    /// Releases without limits (`PR-UPR`)
+    Unlimited,
+    /// Limited or incomplete releases (`PR-LPR`)
+    Limited,
+}
+
+impl ModelReleaseStatus {
+    /// Get the code of the status, such as "MR-NON"
+    pub fn code(&self) -> &'static str {
+        match self {
+            ModelReleaseStatus::None => "MR-NON",
+            ModelReleaseStatus::NotApplicable => "MR-NAP",
+            ModelReleaseStatus::Unlimited => "MR-UMR",
+            ModelReleaseStatus::Limited => "MR-LMR",
+        }
+    }
+
+    /// Get the status of a vocabulary URI or code
+    pub fn parse(value: &str) -> Option<Self> {
+        [
+            ModelReleaseStatus::None,
+            ModelReleaseStatus::NotApplicable,
+            ModelReleaseStatus::Unlimited,
+            ModelReleaseStatus::Limited,
+        ]
+        .into_iter()
+        .find(|status| status.code() == vocabulary_code(value))
+    }
+}
+
+impl PropertyReleaseStatus {
+    /// Get the code of the status, such as "PR-NON"
+    pub fn code(&self) -> &'static str {
+        match self {
+            PropertyReleaseStatus::None => "PR-NON",
+            PropertyReleaseStatus::NotApplicable => "PR-NAP",
+            PropertyReleaseStatus::Unlimited => "PR-UPR",
+            PropertyReleaseStatus::Limited => "PR-LPR",
+        }
+    }
+
+    /// Get the status of a vocabulary URI or code
+    pub fn parse(value: &str) -> Option<Self> {
+        [
+            PropertyReleaseStatus::None,
+            PropertyReleaseStatus::NotApplicable,
+            PropertyReleaseStatus::Unlimited,
+            PropertyReleaseStatus::Limited,
+        ]
+        .into_iter()
+        .find(|status| status.code() == vocabulary_code(value))
+    }
+}
+
+impl Plus {
+    /// Read the PLUS properties of the metadata
+    ///
+    /// Release statuses that are not in the PLUS vocabularies are read as
+    /// `None`.
+    pub fn from_xmp(meta: &XmpMeta) -> Self {
+        let status = |name: &str| text(meta, ns::PLUS, name);
+        Self {
+            licensors: (1..=item_count(meta, ns::PLUS, "Licensor"))
+                .map(|i| {
+                    let field = |name: &str| {
+                        text(
+                            meta,
+                            ns::PLUS,
+                            &format!("Licensor[{}]/plus:Licensor{}", i, name),
+                        )
+                    };
+                    Licensor {
+                        name: field("Name"),
+                        id: field("ID"),
+                        email: field("Email"),
+                        telephone: field("Telephone1"),
+                        url: field("URL"),
+                    }
+                })
+                .collect(),
+            copyright_owners: read_parties(meta, "CopyrightOwner"),
+            image_creators: read_parties(meta, "ImageCreator"),
+            model_release_status: status("ModelReleaseStatus")
+                .and_then(|s| ModelReleaseStatus::parse(&s)),
+            model_release_ids: text_items(meta, ns::PLUS, "ModelReleaseID"),
+            property_release_status: status("PropertyReleaseStatus")
+                .and_then(|s| PropertyReleaseStatus::parse(&s)),
+            property_release_ids: text_items(meta, ns::PLUS, "PropertyReleaseID"),
+        }
+    }
+
+    /// Write the PLUS properties to the metadata
+    ///
+    /// Release statuses are written as vocabulary URIs. Empty properties are
+    /// removed.
+    pub fn to_xmp(&self, meta: &mut XmpMeta) -> XmpResult<()> {
+        if self.licensors.is_empty() {
+            meta.delete_property(ns::PLUS, "Licensor")?;
+        } else {
+            let mut list = ArrayNode::new(ArrayType::Ordered);
+            for licensor in &self.licensors {
+                let fields = [
+                    ("LicensorName", &licensor.name),
+                    ("LicensorID", &licensor.id),
+                    ("LicensorEmail", &licensor.email),
+                    ("LicensorTelephone1", &licensor.telephone),
+                    ("LicensorURL", &licensor.url),
+                ];
+                let mut node = StructureNode::new();
+                for (name, value) in fields {
+                    set_text_field(&mut node, ns::PLUS, name, value);
+                }
+                list.append(Node::Structure(node));
+            }
+            meta.set_property_node(ns::PLUS, "Licensor", Node::Array(list))?;
+        }
+        write_parties(meta, "CopyrightOwner", &self.copyright_owners)?;
+        write_parties(meta, "ImageCreator", &self.image_creators)?;
+
+        let model_status = self
+            .model_release_status
+            .map(|status| format!("{}{}", VOCABULARY, status.code()));
+        set_text(meta, ns::PLUS, "ModelReleaseStatus", &model_status)?;
+        set_bag(meta, ns::PLUS, "ModelReleaseID", &self.model_release_ids)?;
+        let property_status = self
+            .property_release_status
+            .map(|status| format!("{}{}", VOCABULARY, status.code()));
+        set_text(meta, ns::PLUS, "PropertyReleaseStatus", &property_status)?;
+        set_bag(
+            meta,
+            ns::PLUS,
+            "PropertyReleaseID",
+            &self.property_release_ids,
+        )
+    }
+}
+
+/// Read an array of parties, whose fields are named after the array
+fn read_parties(meta: &XmpMeta, name: &str) -> Vec<Party> {
+    (1..=item_count(meta, ns::PLUS, name))
+        .map(|i| {
+            let field = |suffix: &str| {
+                text(
+                    meta,
+                    ns::PLUS,
+                    &format!("{}[{}]/plus:{}{}", name, i, name, suffix),
+                )
+            };
+            Party {
+                name: field("Name"),
+                id: field("ID"),
+            }
+        })
+        .collect()
+}
+
+/// Replace an array of parties, or remove it if there are none
+fn write_parties(meta: &mut XmpMeta, name: &str, parties: &[Party]) -> XmpResult<()> {
+    if parties.is_empty() {
+        return meta.delete_property(ns::PLUS, name);
+    }
+    let mut list = ArrayNode::new(ArrayType::Ordered);
+    for party in parties {
+        let mut node = StructureNode::new();
+        set_text_field(&mut node, ns::PLUS, &format!("{}Name", name), &party.name);
+        set_text_field(&mut node, ns::PLUS, &format!("{}ID", name), &party.id);
+        list.append(Node::Structure(node));
+    }
+    meta.set_property_node(ns::PLUS, name, Node::Array(list))
+}
+
+/// Get the code of a vocabulary URI, or the value itself if it is not one
+fn vocabulary_code(value: &str) -> &str {
+    let value = value.trim();
+    value.strip_prefix(VOCABULARY).unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::validate::{ViolationKind, XmpValidator};
+
+    #[test]
+    fn test_plus_round_trip() {
+        let mut meta = XmpMeta::new();
+        assert_eq!(Plus::from_xmp(&meta), Plus::default());
+
+        let plus = Plus {
+            licensors: vec![Licensor {
+                name: Some("Example Images".to_string()),
+                id: Some("PLUS-ID-1234".to_string()),
+                email: Some("licensing@example.com".to_string()),
+                telephone: None,
+                url: Some("https://images.example.com".to_string()),
+            }],
+            copyright_owners: vec![Party {
+                name: Some("Ada Lovelace".to_string()),
+                id: None,
+            }],
+            image_creators: vec![
+                Party {
+                    name: Some("Ada Lovelace".to_string()),
+                    id: Some("C-1".to_string()),
+                },
+                Party {
+                    name: Some("Charles Babbage".to_string()),
+                    id: None,
+                },
+            ],
+            model_release_status: Some(ModelReleaseStatus::Limited),
+            model_release_ids: vec!["MR-1".to_string(), "MR-2".to_string()],
+            property_release_status: Some(PropertyReleaseStatus::NotApplicable),
+            property_release_ids: Vec::new(),
+        };
+        plus.to_xmp(&mut meta).unwrap();
+        assert!(XmpValidator::new().validate(&meta).is_empty());
+
+        let packet = meta.serialize_packet().unwrap();
+        assert!(packet.contains("<plus:Licensor>"));
+        assert!(packet.contains("http://ns.useplus.org/ldf/vocab/PR-NAP"));
+        let mut meta = XmpMeta::parse(&packet).unwrap();
+        assert_eq!(Plus::from_xmp(&meta), plus);
+
+        Plus::default().to_xmp(&mut meta).unwrap();
+        assert!(!meta.does_property_exist(ns::PLUS, "Licensor"));
+        assert!(!meta.does_property_exist(ns::PLUS, "ModelReleaseStatus"));
+    }
+
+    #[test]
+    fn test_release_status_choices() {
+        assert_eq!(
+            ModelReleaseStatus::parse("MR-UMR"),
+            Some(ModelReleaseStatus::Unlimited)
+        );
+        assert_eq!(
+            PropertyReleaseStatus::parse("http://ns.useplus.org/ldf/vocab/PR-NON"),
+            Some(PropertyReleaseStatus::None)
+        );
+        assert_eq!(ModelReleaseStatus::parse("PR-NON"), None);
+
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::PLUS, "ModelReleaseStatus", "released".into())
+            .unwrap();
+        assert_eq!(Plus::from_xmp(&meta).model_release_status, None);
+        let violations = XmpValidator::new().validate(&meta);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, "plus:ModelReleaseStatus");
+        assert!(matches!(
+            violations[0].kind,
+            ViolationKind::NotInChoice { .. }
+        ));
+    }
+}
@@ -0,0 +1,208 @@
+//! Copyright and usage rights (`dc:rights`, `xmpRights:`)
+//!
+//! The rights of a work are described by properties of two schemas: the
+//! copyright notice is `dc:rights`, and the XMP Rights Management schema has
+//! the `xmpRights:UsageTerms` telling how the work may be used, the
+//! `xmpRights:WebStatement` URL of a page about its rights, and
+//! `xmpRights:Marked`, `True` for a work under copyright and `False` for one
+//! in the public domain. The notice and the usage terms are language
+//! alternatives.
+//!
+//! [`set_copyright`] writes all four together, so that a notice doesn't stay
+//! next to the usage terms or marking of an earlier copyright.
+//!
+//! # Example
+//!
+//! ```rust
+//! use xmpkit::schemas::rights;
+//! use xmpkit::XmpMeta;
+//!
+//! let mut meta = XmpMeta::new();
+//! rights::set_copyright(
+//!     &mut meta,
+//!     Some("© 2024 Ada Lovelace"),
+//!     Some("Editorial use only"),
+//!     Some("https://example.com/rights"),
+//!     Some(true),
+//! )
+//! .unwrap();
+//! rights::set_usage_terms(&mut meta, "fr-FR", "Usage éditorial uniquement").unwrap();
+//!
+//! assert_eq!(rights::copyright_notice(&meta, "en-US").as_deref(), Some("© 2024 Ada Lovelace"));
+//! assert_eq!(rights::usage_terms(&meta, "fr-FR").as_deref(), Some("Usage éditorial uniquement"));
+//! assert_eq!(rights::marked(&meta), Some(true));
+//! ```
+
+use super::{localized_text, set_localized_text, X_DEFAULT};
+use crate::core::error::{XmpError, XmpResult};
+use crate::core::metadata::XmpMeta;
+use crate::core::namespace::ns;
+use crate::types::value::XmpValue;
+
+/// Get the copyright notice of `dc:rights` in a language
+pub fn copyright_notice(meta: &XmpMeta, lang: &str) -> Option<String> {
+    localized_text(meta, ns::DC, "rights", lang)
+}
+
+/// Get `xmpRights:UsageTerms` in a language
+pub fn usage_terms(meta: &XmpMeta, lang: &str) -> Option<String> {
+    localized_text(meta, ns::XMP_RIGHTS, "UsageTerms", lang)
+}
+
+/// Set `xmpRights:UsageTerms` in a language
+///
+/// The first usage terms also become the `x-default` ones.
+///
+/// Fails with `XmpError::BadParam` if `lang` is empty, or with
+/// `XmpError::BadValue` if `xmpRights:UsageTerms` is a structure.
+pub fn set_usage_terms(meta: &mut XmpMeta, lang: &str, terms: &str) -> XmpResult<()> {
+    set_localized_text(meta, ns::XMP_RIGHTS, "UsageTerms", lang, terms)
+}
+
+/// Get the URL of `xmpRights:WebStatement`
+pub fn web_statement(meta: &XmpMeta) -> Option<String> {
+    match meta.get_property(ns::XMP_RIGHTS, "WebStatement")? {
+        XmpValue::String(url) | XmpValue::Uri(url) if !url.is_empty() => Some(url),
+        _ => None,
+    }
+}
+
+/// Get `xmpRights:Marked`: `true` if the work is under copyright, `false` if
+/// it is in the public domain, `None` if unknown
+pub fn marked(meta: &XmpMeta) -> Option<bool> {
+    meta.get_property_bool(ns::XMP_RIGHTS, "Marked")
+}
+
+/// Set the copyright notice, usage terms, web statement and marking of the
+/// work
+///
+/// The notice and the usage terms replace the language alternatives of
+/// `dc:rights` and `xmpRights:UsageTerms` with a single `x-default` text;
+/// translations can be added afterwards with
+/// [`dc::set_rights`](super::dc::set_rights) and [`set_usage_terms`].
+/// `None` values remove the properties, and `marked` is `None` when it is
+/// not known if the work is under copyright.
+///
+/// Fails with `XmpError::BadValue`, without changing anything, if the web
+/// statement is not a URL (empty or with spaces).
+pub fn set_copyright(
+    meta: &mut XmpMeta,
+    notice: Option<&str>,
+    usage_terms: Option<&str>,
+    web_statement: Option<&str>,
+    marked: Option<bool>,
+) -> XmpResult<()> {
+    if let Some(url) = web_statement {
+        if url.is_empty() || url.contains(char::is_whitespace) {
+            return Err(XmpError::BadValue(format!(
+                "Invalid web statement URL '{}'",
+                url
+            )));
+        }
+    }
+
+    let alternatives = [
+        (ns::DC, "rights", notice),
+        (ns::XMP_RIGHTS, "UsageTerms", usage_terms),
+    ];
+    for (namespace, name, text) in alternatives {
+        meta.delete_property(namespace, name)?;
+        if let Some(text) = text {
+            meta.set_localized_text(namespace, name, "", X_DEFAULT, text)?;
+        }
+    }
+    match web_statement {
+        Some(url) => meta.set_property(ns::XMP_RIGHTS, "WebStatement", url.into())?,
+        None => meta.delete_property(ns::XMP_RIGHTS, "WebStatement")?,
+    }
+    match marked {
+        Some(marked) => meta.set_property_bool(ns::XMP_RIGHTS, "Marked", marked),
+        None => meta.delete_property(ns::XMP_RIGHTS, "Marked"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::dc;
+
+    #[test]
+    fn test_set_copyright() {
+        let mut meta = XmpMeta::new();
+        assert_eq!(copyright_notice(&meta, "en-US"), None);
+        assert_eq!(marked(&meta), None);
+
+        set_copyright(
+            &mut meta,
+            Some("© 2023 Ada Lovelace"),
+            Some("All rights reserved"),
+            Some("https://example.com/rights"),
+            Some(true),
+        )
+        .unwrap();
+        dc::set_rights(
+            &mut meta,
+            "de-DE",
+            "© 2023 Ada Lovelace, Rechte vorbehalten",
+        )
+        .unwrap();
+        set_usage_terms(&mut meta, "de-DE", "Alle Rechte vorbehalten").unwrap();
+        assert_eq!(
+            usage_terms(&meta, "de-AT").as_deref(),
+            Some("Alle Rechte vorbehalten")
+        );
+
+        let packet = meta.serialize_packet().unwrap();
+        assert!(packet.contains("<xmpRights:UsageTerms>"));
+        assert!(packet.contains("xmpRights:Marked"));
+        let mut meta = XmpMeta::parse(&packet).unwrap();
+        assert_eq!(
+            web_statement(&meta).as_deref(),
+            Some("https://example.com/rights")
+        );
+        assert_eq!(marked(&meta), Some(true));
+
+        // A new copyright replaces the translations and the other properties
+        assert!(set_copyright(&mut meta, None, None, Some("not a url"), Some(false)).is_err());
+        assert_eq!(marked(&meta), Some(true));
+        set_copyright(&mut meta, Some("Public domain"), None, None, Some(false)).unwrap();
+        assert_eq!(
+            copyright_notice(&meta, "de-DE").as_deref(),
+            Some("Public domain")
+        );
+        assert_eq!(meta.count_array_items(ns::DC, "rights"), 1);
+        assert!(!meta.does_property_exist(ns::XMP_RIGHTS, "UsageTerms"));
+        assert_eq!(web_statement(&meta), None);
+        assert_eq!(marked(&meta), Some(false));
+
+        set_copyright(&mut meta, None, None, None, None).unwrap();
+        assert!(!meta.does_property_exist(ns::DC, "rights"));
+        assert_eq!(marked(&meta), None);
+    }
+
+    #[test]
+    fn test_usage_terms_simple_value() {
+        // Some writers store the usage terms as a simple value
+        let packet = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+  <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+    <rdf:Description rdf:about=""
+        xmlns:xmpRights="http://ns.adobe.com/xap/1.0/rights/"
+        xmpRights:UsageTerms="Editorial use only"/>
+  </rdf:RDF>
+</x:xmpmeta>"#;
+        let mut meta = XmpMeta::parse(packet).unwrap();
+        assert_eq!(
+            usage_terms(&meta, "en-US").as_deref(),
+            Some("Editorial use only")
+        );
+
+        set_usage_terms(&mut meta, "x-default", "Editorial use only, no archiving").unwrap();
+        assert_eq!(
+            meta.get_localized_text(ns::XMP_RIGHTS, "UsageTerms", "", "x-default"),
+            Some((
+                "Editorial use only, no archiving".to_string(),
+                "x-default".to_string()
+            ))
+        );
+    }
+}
@@ -4,6 +4,8 @@
 
 pub mod qualifier;
 pub mod value;
+pub mod value_ref;
 
 pub use qualifier::Qualifier;
 pub use value::XmpValue;
+pub use value_ref::{XmpPropertyRef, XmpValueRef};
@@ -4,6 +4,8 @@
 
 use std::fmt;
 
+use crate::utils::datetime::XmpDateTime;
+
 /// XMP property value types
 #[derive(Debug, Clone, PartialEq)]
 pub enum XmpValue {
@@ -13,8 +15,10 @@ pub enum XmpValue {
     Integer(i64),
     /// Boolean value
     Boolean(bool),
-    /// Date/time value (ISO 8601 format)
-    DateTime(String),
+    /// Date/time value, serialized in the ISO 8601 format of XMP
+    DateTime(XmpDateTime),
+    /// URI value, serialized as an `rdf:resource` attribute
+    Uri(String),
     /// Array of values
     Array(Vec<XmpValue>),
     /// Structure (key-value pairs)
@@ -45,6 +49,22 @@ impl XmpValue {
             _ => None,
         }
     }
+
+    /// Get the value as a date/time, if it is a date/time type
+    pub fn as_date_time(&self) -> Option<&XmpDateTime> {
+        match self {
+            XmpValue::DateTime(dt) => Some(dt),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a URI, if it is a URI type
+    pub fn as_uri(&self) -> Option<&str> {
+        match self {
+            XmpValue::Uri(uri) => Some(uri),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for XmpValue {
@@ -54,6 +74,7 @@ impl fmt::Display for XmpValue {
             XmpValue::Integer(i) => write!(f, "{}", i),
             XmpValue::Boolean(b) => write!(f, "{}", b),
             XmpValue::DateTime(dt) => write!(f, "{}", dt),
+            XmpValue::Uri(uri) => write!(f, "{}", uri),
             XmpValue::Array(_) => write!(f, "[Array]"),
             XmpValue::Structure(_) => write!(f, "[Structure]"),
         }
@@ -84,6 +105,12 @@ impl From<bool> for XmpValue {
     }
 }
 
+impl From<XmpDateTime> for XmpValue {
+    fn from(dt: XmpDateTime) -> Self {
+        XmpValue::DateTime(dt)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -120,4 +147,20 @@ mod tests {
         let value: XmpValue = true.into();
         assert_eq!(value.as_bool(), Some(true));
     }
+
+    #[test]
+    fn test_xmp_value_date_time() {
+        let dt = XmpDateTime::parse("2023-12-25T10:30:00Z").unwrap();
+        let value: XmpValue = dt.clone().into();
+        assert_eq!(value.as_date_time(), Some(&dt));
+        assert_eq!(value.to_string(), "2023-12-25T10:30:00Z"); // Display trait
+    }
+
+    #[test]
+    fn test_xmp_value_uri() {
+        let value = XmpValue::Uri("https://example.com/".to_string());
+        assert_eq!(value.as_uri(), Some("https://example.com/"));
+        assert_eq!(value.as_str(), None);
+        assert_eq!(value.to_string(), "https://example.com/");
+    }
 }
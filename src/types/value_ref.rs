@@ -0,0 +1,179 @@
+//! Borrowed XMP value types
+//!
+//! [`XmpValueRef`] is the value type of
+//! [`XmpMetaRef`](crate::core::borrowed::XmpMetaRef), the read-only view
+//! created by [`XmpMeta::parse_borrowed`]. Its text borrows from the parsed
+//! packet, and is only copied when it contains entity references or line
+//! breaks that XML normalizes.
+//!
+//! [`XmpMeta::parse_borrowed`]: crate::core::metadata::XmpMeta::parse_borrowed
+
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::utils::datetime::XmpDateTime;
+
+/// XMP property value borrowed from a packet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XmpValueRef<'a> {
+    /// Text value
+    String(Cow<'a, str>),
+    /// Text value with an `xml:lang` qualifier, such as an item of a
+    /// language alternative
+    LangString {
+        /// The language, such as "x-default" or "en-US"
+        lang: Cow<'a, str>,
+        /// The text
+        value: Cow<'a, str>,
+    },
+    /// Array of values
+    Array(Vec<XmpValueRef<'a>>),
+    /// Structure fields, in the order of the packet
+    Structure(Vec<XmpPropertyRef<'a>>),
+}
+
+/// A property or structure field borrowed from a packet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmpPropertyRef<'a> {
+    /// The namespace URI
+    pub namespace: Cow<'a, str>,
+    /// The name, without the namespace prefix
+    pub name: Cow<'a, str>,
+    /// The value
+    pub value: XmpValueRef<'a>,
+}
+
+impl<'a> XmpValueRef<'a> {
+    /// Get the text of a text value
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            XmpValueRef::String(s) | XmpValueRef::LangString { value: s, .. } => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get the `xml:lang` qualifier of a text value
+    pub fn lang(&self) -> Option<&str> {
+        match self {
+            XmpValueRef::LangString { lang, .. } => Some(lang),
+            _ => None,
+        }
+    }
+
+    /// Parse the text as an integer
+    pub fn as_int(&self) -> Option<i64> {
+        self.as_str()?.trim().parse().ok()
+    }
+
+    /// Parse the text as a boolean ("True" or "False", in any case)
+    pub fn as_bool(&self) -> Option<bool> {
+        let text = self.as_str()?.trim();
+        if text.eq_ignore_ascii_case("true") {
+            Some(true)
+        } else if text.eq_ignore_ascii_case("false") {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Parse the text as a date/time
+    pub fn as_date_time(&self) -> Option<XmpDateTime> {
+        XmpDateTime::parse(self.as_str()?.trim()).ok()
+    }
+
+    /// Get the items of an array
+    pub fn as_array(&self) -> Option<&[XmpValueRef<'a>]> {
+        match self {
+            XmpValueRef::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Get the fields of a structure
+    pub fn as_structure(&self) -> Option<&[XmpPropertyRef<'a>]> {
+        match self {
+            XmpValueRef::Structure(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    /// Get a field of a structure by namespace URI and name
+    pub fn field(&self, namespace: &str, name: &str) -> Option<&XmpValueRef<'a>> {
+        self.as_structure()?
+            .iter()
+            .find(|field| field.namespace == namespace && field.name == name)
+            .map(|field| &field.value)
+    }
+
+    /// Copy the borrowed text, so that the value outlives the packet
+    pub fn into_owned(self) -> XmpValueRef<'static> {
+        let owned = |s: Cow<'a, str>| Cow::Owned(s.into_owned());
+        match self {
+            XmpValueRef::String(s) => XmpValueRef::String(owned(s)),
+            XmpValueRef::LangString { lang, value } => XmpValueRef::LangString {
+                lang: owned(lang),
+                value: owned(value),
+            },
+            XmpValueRef::Array(items) => {
+                XmpValueRef::Array(items.into_iter().map(XmpValueRef::into_owned).collect())
+            }
+            XmpValueRef::Structure(fields) => {
+                XmpValueRef::Structure(fields.into_iter().map(XmpPropertyRef::into_owned).collect())
+            }
+        }
+    }
+}
+
+impl XmpPropertyRef<'_> {
+    /// Copy the borrowed text, so that the property outlives the packet
+    pub fn into_owned(self) -> XmpPropertyRef<'static> {
+        XmpPropertyRef {
+            namespace: Cow::Owned(self.namespace.into_owned()),
+            name: Cow::Owned(self.name.into_owned()),
+            value: self.value.into_owned(),
+        }
+    }
+}
+
+impl fmt::Display for XmpValueRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XmpValueRef::String(s) | XmpValueRef::LangString { value: s, .. } => {
+                write!(f, "{}", s)
+            }
+            XmpValueRef::Array(_) => write!(f, "[Array]"),
+            XmpValueRef::Structure(_) => write!(f, "[Structure]"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_ref_conversions() {
+        let value = XmpValueRef::String(Cow::Borrowed(" 42 "));
+        assert_eq!(value.as_int(), Some(42));
+        assert_eq!(value.as_bool(), None);
+        assert_eq!(XmpValueRef::String("True".into()).as_bool(), Some(true));
+        assert_eq!(
+            XmpValueRef::String("2024-05-06".into()).as_date_time(),
+            XmpDateTime::parse("2024-05-06").ok()
+        );
+
+        let item = XmpValueRef::LangString {
+            lang: "x-default".into(),
+            value: "Title".into(),
+        };
+        assert_eq!(
+            (item.as_str(), item.lang()),
+            (Some("Title"), Some("x-default"))
+        );
+        let array = XmpValueRef::Array(vec![item]);
+        assert_eq!(array.to_string(), "[Array]");
+        let owned = array.clone().into_owned();
+        assert_eq!(owned, array);
+    }
+}
@@ -0,0 +1,86 @@
+//! Base64 encoding
+//!
+//! A small implementation of the standard Base64 alphabet (RFC 4648), used
+//! for binary data stored in XMP text, such as the image of
+//! `xmp:Thumbnails`. Decoding skips whitespace, since applications break
+//! long values into lines.
+
+use crate::core::error::{XmpError, XmpResult};
+
+/// The standard Base64 alphabet
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode `data` as padded Base64
+pub fn encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let bits = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Decode Base64, with or without padding
+///
+/// Whitespace is skipped. Returns `XmpError::BadValue` for other characters
+/// outside the alphabet.
+pub fn decode(text: &str) -> XmpResult<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(text.len() / 4 * 3);
+    let mut bits = 0u32;
+    let mut count = 0;
+    for byte in text.bytes().filter(|b| !b.is_ascii_whitespace()) {
+        if byte == b'=' {
+            break;
+        }
+        let value = ALPHABET.iter().position(|&c| c == byte).ok_or_else(|| {
+            XmpError::BadValue(format!("Invalid Base64 character '{}'", byte as char))
+        })?;
+        bits = bits << 6 | value as u32;
+        count += 1;
+        if count == 4 {
+            decoded.extend_from_slice(&bits.to_be_bytes()[1..]);
+            bits = 0;
+            count = 0;
+        }
+    }
+    match count {
+        0 => {}
+        2 => decoded.push((bits >> 4) as u8),
+        3 => decoded.extend_from_slice(&((bits >> 2) as u16).to_be_bytes()),
+        _ => return Err(XmpError::BadValue("Truncated Base64 data".to_string())),
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let cases: [(&[u8], &str); 4] = [
+            (b"", ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foobar", "Zm9vYmFy"),
+        ];
+        for (data, text) in cases {
+            assert_eq!(encode(data), text);
+            assert_eq!(decode(text).unwrap(), data);
+        }
+        assert_eq!(decode("Zm9v\nYmE").unwrap(), b"fooba");
+        assert!(decode("Zm9v!").is_err());
+        assert!(decode("Z").is_err());
+    }
+}
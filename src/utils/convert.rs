@@ -0,0 +1,198 @@
+//! XMP value conversion utilities
+//!
+//! This module provides conversions between XMP string values and common
+//! Rust scalar types. The rules follow the Adobe XMP Toolkit (`XMPUtils`
+//! `ConvertToBool`, `ConvertToInt64`, `ConvertToFloat` and their
+//! `ConvertFrom*` counterparts), so values written by Adobe applications
+//! are read back correctly and values written here are understood by them.
+
+use crate::core::error::{XmpError, XmpResult};
+
+/// Canonical XMP string for `true`
+pub const TRUE_STRING: &str = "True";
+
+/// Canonical XMP string for `false`
+pub const FALSE_STRING: &str = "False";
+
+/// Convert a boolean to its XMP string form
+///
+/// XMP booleans are always written as `"True"` or `"False"`.
+pub fn convert_from_bool(value: bool) -> String {
+    if value { TRUE_STRING } else { FALSE_STRING }.to_string()
+}
+
+/// Convert an XMP string to a boolean
+///
+/// Accepts (case-insensitively) `"true"`, `"t"` and `"1"` as `true`, and
+/// `"false"`, `"f"` and `"0"` as `false`. Surrounding whitespace is ignored.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::utils::convert::convert_to_bool;
+///
+/// assert_eq!(convert_to_bool("True").unwrap(), true);
+/// assert_eq!(convert_to_bool("0").unwrap(), false);
+/// assert!(convert_to_bool("maybe").is_err());
+/// ```
+pub fn convert_to_bool(value: &str) -> XmpResult<bool> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Err(XmpError::BadValue("Empty convert-from string".to_string()));
+    }
+
+    match trimmed.to_ascii_lowercase().as_str() {
+        "true" | "t" | "1" => Ok(true),
+        "false" | "f" | "0" => Ok(false),
+        _ => Err(XmpError::BadValue(format!(
+            "Invalid boolean string '{}'",
+            value
+        ))),
+    }
+}
+
+/// Convert a 64-bit integer to its XMP string form
+pub fn convert_from_int64(value: i64) -> String {
+    value.to_string()
+}
+
+/// Convert an XMP string to a 64-bit integer
+///
+/// Accepts an optional leading sign, decimal digits, or a hexadecimal value
+/// prefixed with `0x`/`0X`. Surrounding whitespace is ignored.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::utils::convert::convert_to_int64;
+///
+/// assert_eq!(convert_to_int64("-42").unwrap(), -42);
+/// assert_eq!(convert_to_int64("0x1F").unwrap(), 31);
+/// ```
+pub fn convert_to_int64(value: &str) -> XmpResult<i64> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Err(XmpError::BadValue("Empty convert-from string".to_string()));
+    }
+
+    let (negative, digits) = match trimmed.as_bytes()[0] {
+        b'-' => (true, &trimmed[1..]),
+        b'+' => (false, &trimmed[1..]),
+        _ => (false, trimmed),
+    };
+
+    let magnitude = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        u64::from_str_radix(hex, 16)
+    } else {
+        digits.parse::<u64>()
+    }
+    .map_err(|_| XmpError::BadValue(format!("Invalid integer string '{}'", value)))?;
+
+    let result = if negative {
+        0i64.checked_sub_unsigned(magnitude)
+    } else {
+        i64::try_from(magnitude).ok()
+    };
+
+    result.ok_or_else(|| XmpError::BadValue(format!("Integer string '{}' is out of range", value)))
+}
+
+/// Convert a floating point number to its XMP string form
+///
+/// Uses the shortest representation that round-trips exactly. Non-finite
+/// values cannot be represented in XMP and are rejected.
+pub fn convert_from_float(value: f64) -> XmpResult<String> {
+    if !value.is_finite() {
+        return Err(XmpError::BadValue(format!(
+            "Cannot convert non-finite float '{}'",
+            value
+        )));
+    }
+    Ok(value.to_string())
+}
+
+/// Convert an XMP string to a floating point number
+///
+/// Accepts decimal notation and exponent notation (e.g. `"1.5e-3"`).
+/// Surrounding whitespace is ignored; infinities and NaN are rejected.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::utils::convert::convert_to_float;
+///
+/// assert_eq!(convert_to_float("2.5").unwrap(), 2.5);
+/// assert_eq!(convert_to_float("1E3").unwrap(), 1000.0);
+/// ```
+pub fn convert_to_float(value: &str) -> XmpResult<f64> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Err(XmpError::BadValue("Empty convert-from string".to_string()));
+    }
+
+    // Rust's parser also accepts "inf"/"nan", which are not valid XMP reals
+    let is_numeric = trimmed
+        .bytes()
+        .all(|b| b.is_ascii_digit() || matches!(b, b'+' | b'-' | b'.' | b'e' | b'E'));
+    if !is_numeric {
+        return Err(XmpError::BadValue(format!(
+            "Invalid float string '{}'",
+            value
+        )));
+    }
+
+    trimmed
+        .parse::<f64>()
+        .map_err(|_| XmpError::BadValue(format!("Invalid float string '{}'", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bool_conversion() {
+        assert_eq!(convert_from_bool(true), "True");
+        assert_eq!(convert_from_bool(false), "False");
+
+        for s in ["True", "true", "TRUE", "t", "1", " True "] {
+            assert!(convert_to_bool(s).unwrap(), "{}", s);
+        }
+        for s in ["False", "false", "f", "F", "0"] {
+            assert!(!convert_to_bool(s).unwrap(), "{}", s);
+        }
+        assert!(convert_to_bool("").is_err());
+        assert!(convert_to_bool("yes").is_err());
+    }
+
+    #[test]
+    fn test_int64_conversion() {
+        assert_eq!(convert_to_int64("42").unwrap(), 42);
+        assert_eq!(convert_to_int64("+42").unwrap(), 42);
+        assert_eq!(convert_to_int64("-42").unwrap(), -42);
+        assert_eq!(convert_to_int64("0xff").unwrap(), 255);
+        assert_eq!(convert_to_int64("-0x10").unwrap(), -16);
+        assert_eq!(convert_to_int64("-9223372036854775808").unwrap(), i64::MIN);
+        assert!(convert_to_int64("9223372036854775808").is_err());
+        assert!(convert_to_int64("12abc").is_err());
+        assert!(convert_to_int64("").is_err());
+        assert_eq!(convert_from_int64(-7), "-7");
+    }
+
+    #[test]
+    fn test_float_conversion() {
+        assert_eq!(convert_to_float("3.25").unwrap(), 3.25);
+        assert_eq!(convert_to_float("-1.5e2").unwrap(), -150.0);
+        assert_eq!(convert_to_float("2E-1").unwrap(), 0.2);
+        assert!(convert_to_float("inf").is_err());
+        assert!(convert_to_float("NaN").is_err());
+        assert!(convert_to_float("1.2.3").is_err());
+
+        assert_eq!(convert_from_float(0.5).unwrap(), "0.5");
+        assert_eq!(convert_from_float(100.0).unwrap(), "100");
+        assert!(convert_from_float(f64::INFINITY).is_err());
+    }
+}
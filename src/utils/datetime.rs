@@ -2,6 +2,22 @@
 //!
 //! This module provides functionality for parsing and formatting XMP date/time values.
 //! XMP uses a specific ISO 8601-like format that supports partial dates and time zones.
+//!
+//! Values can be date-only, time-only, or both, with or without a time zone.
+//! [`XmpDateTime`] converts between time zones and compares values the way
+//! the Adobe XMP Toolkit does.
+//!
+//! With the `chrono` or `time` feature, `XmpDateTime` converts from the
+//! date/time types of these crates with `From`, and to them with `TryFrom`.
+//! Values without a time zone convert to `chrono::NaiveDateTime` or
+//! `time::PrimitiveDateTime`, and values with one to
+//! `chrono::DateTime<FixedOffset>` or `time::OffsetDateTime`. Missing months
+//! and days are taken as 1.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::core::error::{XmpError, XmpResult};
 
@@ -399,18 +415,21 @@ impl XmpDateTime {
 
         // Format date portion
         if self.has_date {
+            // Four digits, plus the sign of BCE years
+            let year = if self.year < 0 {
+                format!("{:05}", self.year)
+            } else {
+                format!("{:04}", self.year)
+            };
             if self.month == 0 {
                 // Year only
-                result.push_str(&format!("{:04}", self.year));
+                result.push_str(&year);
             } else if self.day == 0 {
                 // Year and month
-                result.push_str(&format!("{:04}-{:02}", self.year, self.month));
+                result.push_str(&format!("{}-{:02}", year, self.month));
             } else {
                 // Full date
-                result.push_str(&format!(
-                    "{:04}-{:02}-{:02}",
-                    self.year, self.month, self.day
-                ));
+                result.push_str(&format!("{}-{:02}-{:02}", year, self.month, self.day));
             }
         }
 
@@ -503,12 +522,457 @@ impl XmpDateTime {
     }
 }
 
+impl XmpDateTime {
+    /// Create a date-only value
+    ///
+    /// A `month` or `day` of 0 makes a partial date.
+    pub fn from_date(year: i32, month: u8, day: u8) -> Self {
+        Self {
+            year,
+            month,
+            day,
+            has_date: true,
+            ..Self::new()
+        }
+    }
+
+    /// Create a time-only value
+    pub fn from_time(hour: u8, minute: u8, second: u8) -> Self {
+        Self::new().with_time(hour, minute, second)
+    }
+
+    /// Create a UTC value from a Unix timestamp
+    ///
+    /// # Arguments
+    ///
+    /// * `secs` - Seconds since 1970-01-01T00:00:00Z
+    /// * `nanos` - Nanoseconds within the second (0-999999999)
+    pub fn from_timestamp(secs: i64, nanos: u32) -> Self {
+        let mut dt = Self {
+            has_date: true,
+            has_time: true,
+            has_timezone: true,
+            ..Self::new()
+        };
+        dt.set_local_seconds(secs);
+        dt.nanosecond = nanos.min(999_999_999);
+        dt
+    }
+
+    /// Set the time of the value
+    pub fn with_time(mut self, hour: u8, minute: u8, second: u8) -> Self {
+        self.hour = hour;
+        self.minute = minute;
+        self.second = second;
+        self.has_time = true;
+        self
+    }
+
+    /// Set the fractional seconds of the value
+    pub fn with_nanosecond(mut self, nanosecond: u32) -> Self {
+        self.nanosecond = nanosecond;
+        self
+    }
+
+    /// Set the time zone of the value, as an offset from UTC in minutes
+    pub fn with_timezone(mut self, offset_minutes: i32) -> Self {
+        self.set_timezone_offset(offset_minutes);
+        self
+    }
+
+    /// Whether the value has a date but no time
+    pub fn is_date_only(&self) -> bool {
+        self.has_date && !self.has_time
+    }
+
+    /// Whether the value has a time but no date
+    pub fn is_time_only(&self) -> bool {
+        self.has_time && !self.has_date
+    }
+
+    /// Get the time zone of the value, as an offset from UTC in minutes
+    ///
+    /// Returns `None` for values without a time zone ("naive" values).
+    pub fn timezone_offset(&self) -> Option<i32> {
+        self.has_timezone.then(|| {
+            i32::from(self.tz_sign) * (i32::from(self.tz_hour) * 60 + i32::from(self.tz_minute))
+        })
+    }
+
+    /// Set the time zone of the value, as an offset from UTC in minutes
+    ///
+    /// The local date and time are kept. Offsets are clamped to less than
+    /// 24 hours.
+    pub fn set_timezone_offset(&mut self, offset_minutes: i32) {
+        let minutes = offset_minutes
+            .clamp(-MAX_OFFSET_MINUTES, MAX_OFFSET_MINUTES)
+            .abs();
+        self.has_timezone = true;
+        self.tz_sign = offset_minutes.signum() as i8;
+        self.tz_hour = (minutes / 60) as u8;
+        self.tz_minute = (minutes % 60) as u8;
+    }
+
+    /// Remove the time zone of the value, keeping the local date and time
+    pub fn clear_timezone(&mut self) {
+        self.has_timezone = false;
+        self.tz_sign = 0;
+        self.tz_hour = 0;
+        self.tz_minute = 0;
+    }
+
+    /// Get the Unix timestamp of the value, in whole seconds
+    ///
+    /// Values without a time zone are taken as UTC, missing months and days
+    /// as 1, and time-only values are on 0000-01-01.
+    pub fn timestamp(&self) -> i64 {
+        self.local_seconds() - i64::from(self.timezone_offset().unwrap_or(0)) * 60
+    }
+
+    /// Convert the value to UTC
+    ///
+    /// Values without a time zone are left unchanged, as there is no way to
+    /// know which time zone they are in.
+    pub fn convert_to_utc(&mut self) {
+        self.convert_to_local(0);
+    }
+
+    /// Convert the value to a local time zone
+    ///
+    /// The local time zone is given as its offset from UTC in minutes, since
+    /// the crate has no time zone database. Values without a time zone are
+    /// left unchanged, and taken to be local already.
+    pub fn convert_to_local(&mut self, offset_minutes: i32) {
+        let Some(offset) = self.timezone_offset() else {
+            return;
+        };
+        let offset_minutes = offset_minutes.clamp(-MAX_OFFSET_MINUTES, MAX_OFFSET_MINUTES);
+        let seconds = self.local_seconds() + i64::from(offset_minutes - offset) * 60;
+        self.set_local_seconds(seconds);
+        self.set_timezone_offset(offset_minutes);
+    }
+
+    /// Compare two values in time
+    ///
+    /// Both values are converted to UTC first when they both have a time
+    /// zone; otherwise their fields are compared as they are.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::cmp::Ordering;
+    /// use xmpkit::XmpDateTime;
+    ///
+    /// let paris = XmpDateTime::parse("2024-05-06T12:00:00+02:00").unwrap();
+    /// let london = XmpDateTime::parse("2024-05-06T10:30:00Z").unwrap();
+    /// assert_eq!(paris.compare(&london), Ordering::Less);
+    /// ```
+    pub fn compare(&self, other: &Self) -> Ordering {
+        if self.has_timezone && other.has_timezone {
+            (self.timestamp(), self.nanosecond).cmp(&(other.timestamp(), other.nanosecond))
+        } else {
+            (self.local_seconds(), self.nanosecond).cmp(&(other.local_seconds(), other.nanosecond))
+        }
+    }
+
+    /// Add a number of seconds to the value
+    ///
+    /// The time zone is kept, and partial dates are completed.
+    pub fn add_seconds(&mut self, seconds: i64) {
+        self.set_local_seconds(self.local_seconds() + seconds);
+    }
+
+    /// Add a number of days to the value
+    ///
+    /// The time zone is kept, and partial dates are completed.
+    pub fn add_days(&mut self, days: i64) {
+        self.add_seconds(days * SECONDS_PER_DAY);
+    }
+
+    /// Get the number of whole seconds from `other` to this value
+    ///
+    /// Values are compared in UTC when they both have a time zone, like in
+    /// [`compare`](Self::compare). Fractional seconds are ignored.
+    pub fn seconds_since(&self, other: &Self) -> i64 {
+        if self.has_timezone && other.has_timezone {
+            self.timestamp() - other.timestamp()
+        } else {
+            self.local_seconds() - other.local_seconds()
+        }
+    }
+
+    /// Seconds since 1970-01-01T00:00:00 of the local date and time
+    fn local_seconds(&self) -> i64 {
+        let days = days_from_civil(self.year, self.month.max(1), self.day.max(1));
+        days * SECONDS_PER_DAY
+            + i64::from(self.hour) * 3600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+    }
+
+    /// Set the local date and time from seconds since 1970-01-01T00:00:00
+    fn set_local_seconds(&mut self, seconds: i64) {
+        let (days, secs_of_day) = (
+            seconds.div_euclid(SECONDS_PER_DAY),
+            seconds.rem_euclid(SECONDS_PER_DAY),
+        );
+        (self.year, self.month, self.day) = civil_from_days(days);
+        self.hour = (secs_of_day / 3600) as u8;
+        self.minute = (secs_of_day % 3600 / 60) as u8;
+        self.second = (secs_of_day % 60) as u8;
+    }
+}
+
+const SECONDS_PER_DAY: i64 = 86_400;
+const MAX_OFFSET_MINUTES: i32 = 23 * 60 + 59;
+
+/// Days since 1970-01-01 of a civil date (Howard Hinnant's algorithm)
+fn days_from_civil(year: i32, month: u8, day: u8) -> i64 {
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let month = i64::from(month);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Civil date of days since 1970-01-01 (Howard Hinnant's algorithm)
+fn civil_from_days(days: i64) -> (i32, u8, u8) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = (yoe + era * 400 + i64::from(month <= 2)) as i32;
+    (year, month, day)
+}
+
+/// Get the current date and time, in UTC
+///
+/// Returns an error on platforms without a clock, such as WebAssembly
+/// without the `wasm` feature.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::utils::datetime::current_datetime;
+///
+/// let now = current_datetime().unwrap();
+/// assert_eq!(now.timezone_offset(), Some(0));
+/// ```
+pub fn current_datetime() -> XmpResult<XmpDateTime> {
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    {
+        let millis = js_sys::Date::now() as i64;
+        Ok(XmpDateTime::from_timestamp(
+            millis.div_euclid(1000),
+            (millis.rem_euclid(1000) * 1_000_000) as u32,
+        ))
+    }
+
+    #[cfg(all(target_arch = "wasm32", not(feature = "wasm")))]
+    {
+        Err(XmpError::NotSupported(
+            "No clock is available on this platform".to_string(),
+        ))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        Ok(SystemTime::now().into())
+    }
+}
+
+impl From<SystemTime> for XmpDateTime {
+    fn from(time: SystemTime) -> Self {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(elapsed) => Self::from_timestamp(elapsed.as_secs() as i64, elapsed.subsec_nanos()),
+            Err(err) => {
+                let before = err.duration();
+                let mut dt = Self::from_timestamp(-(before.as_secs() as i64), 0);
+                if before.subsec_nanos() > 0 {
+                    dt.add_seconds(-1);
+                    dt.nanosecond = 1_000_000_000 - before.subsec_nanos();
+                }
+                dt
+            }
+        }
+    }
+}
+
+impl fmt::Display for XmpDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format())
+    }
+}
+
+impl FromStr for XmpDateTime {
+    type Err = XmpError;
+
+    fn from_str(s: &str) -> XmpResult<Self> {
+        Self::parse(s)
+    }
+}
+
 impl Default for XmpDateTime {
     fn default() -> Self {
         Self::new()
     }
 }
 
+// Conversions between XmpDateTime and chrono types
+#[cfg(feature = "chrono")]
+mod chrono_interop {
+    use super::XmpDateTime;
+    use crate::core::error::XmpError;
+    use chrono::{
+        DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone,
+        Timelike,
+    };
+
+    impl From<NaiveDate> for XmpDateTime {
+        fn from(date: NaiveDate) -> Self {
+            Self::from_date(date.year(), date.month() as u8, date.day() as u8)
+        }
+    }
+
+    impl From<NaiveDateTime> for XmpDateTime {
+        fn from(dt: NaiveDateTime) -> Self {
+            let time = dt.time();
+            Self::from(dt.date())
+                .with_time(time.hour() as u8, time.minute() as u8, time.second() as u8)
+                // chrono represents leap seconds with nanoseconds past 1e9
+                .with_nanosecond(time.nanosecond().min(999_999_999))
+        }
+    }
+
+    impl<Tz: TimeZone> From<DateTime<Tz>> for XmpDateTime {
+        fn from(dt: DateTime<Tz>) -> Self {
+            let offset = dt.offset().fix().local_minus_utc() / 60;
+            Self::from(dt.naive_local()).with_timezone(offset)
+        }
+    }
+
+    impl TryFrom<&XmpDateTime> for NaiveDate {
+        type Error = XmpError;
+
+        fn try_from(dt: &XmpDateTime) -> Result<Self, XmpError> {
+            if !dt.has_date {
+                return Err(XmpError::BadValue("Date/time has no date".to_string()));
+            }
+            NaiveDate::from_ymd_opt(
+                dt.year,
+                u32::from(dt.month.max(1)),
+                u32::from(dt.day.max(1)),
+            )
+            .ok_or_else(|| XmpError::BadValue(format!("Invalid date: {}", dt)))
+        }
+    }
+
+    impl TryFrom<&XmpDateTime> for NaiveDateTime {
+        type Error = XmpError;
+
+        fn try_from(dt: &XmpDateTime) -> Result<Self, XmpError> {
+            let date = NaiveDate::try_from(dt)?;
+            let time = NaiveTime::from_hms_nano_opt(
+                u32::from(dt.hour),
+                u32::from(dt.minute),
+                u32::from(dt.second),
+                dt.nanosecond,
+            )
+            .ok_or_else(|| XmpError::BadValue(format!("Invalid time: {}", dt)))?;
+            Ok(date.and_time(time))
+        }
+    }
+
+    impl TryFrom<&XmpDateTime> for DateTime<FixedOffset> {
+        type Error = XmpError;
+
+        fn try_from(dt: &XmpDateTime) -> Result<Self, XmpError> {
+            let offset = dt
+                .timezone_offset()
+                .ok_or_else(|| XmpError::BadValue("Date/time has no time zone".to_string()))?;
+            let offset = FixedOffset::east_opt(offset * 60)
+                .ok_or_else(|| XmpError::BadValue(format!("Invalid time zone: {}", dt)))?;
+            NaiveDateTime::try_from(dt)?
+                .and_local_timezone(offset)
+                .single()
+                .ok_or_else(|| XmpError::BadValue(format!("Invalid date/time: {}", dt)))
+        }
+    }
+}
+
+// Conversions between XmpDateTime and time types
+#[cfg(feature = "time")]
+mod time_interop {
+    use super::XmpDateTime;
+    use crate::core::error::XmpError;
+    use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+    impl From<Date> for XmpDateTime {
+        fn from(date: Date) -> Self {
+            Self::from_date(date.year(), date.month() as u8, date.day())
+        }
+    }
+
+    impl From<PrimitiveDateTime> for XmpDateTime {
+        fn from(dt: PrimitiveDateTime) -> Self {
+            Self::from(dt.date())
+                .with_time(dt.hour(), dt.minute(), dt.second())
+                .with_nanosecond(dt.nanosecond())
+        }
+    }
+
+    impl From<OffsetDateTime> for XmpDateTime {
+        fn from(dt: OffsetDateTime) -> Self {
+            let offset = dt.offset().whole_seconds() / 60;
+            Self::from(PrimitiveDateTime::new(dt.date(), dt.time())).with_timezone(offset)
+        }
+    }
+
+    impl TryFrom<&XmpDateTime> for Date {
+        type Error = XmpError;
+
+        fn try_from(dt: &XmpDateTime) -> Result<Self, XmpError> {
+            if !dt.has_date {
+                return Err(XmpError::BadValue("Date/time has no date".to_string()));
+            }
+            let month = Month::try_from(dt.month.max(1))
+                .map_err(|_| XmpError::BadValue(format!("Invalid date: {}", dt)))?;
+            Date::from_calendar_date(dt.year, month, dt.day.max(1))
+                .map_err(|_| XmpError::BadValue(format!("Invalid date: {}", dt)))
+        }
+    }
+
+    impl TryFrom<&XmpDateTime> for PrimitiveDateTime {
+        type Error = XmpError;
+
+        fn try_from(dt: &XmpDateTime) -> Result<Self, XmpError> {
+            let date = Date::try_from(dt)?;
+            let time = Time::from_hms_nano(dt.hour, dt.minute, dt.second, dt.nanosecond)
+                .map_err(|_| XmpError::BadValue(format!("Invalid time: {}", dt)))?;
+            Ok(PrimitiveDateTime::new(date, time))
+        }
+    }
+
+    impl TryFrom<&XmpDateTime> for OffsetDateTime {
+        type Error = XmpError;
+
+        fn try_from(dt: &XmpDateTime) -> Result<Self, XmpError> {
+            let offset = dt
+                .timezone_offset()
+                .ok_or_else(|| XmpError::BadValue("Date/time has no time zone".to_string()))?;
+            let offset = UtcOffset::from_whole_seconds(offset * 60)
+                .map_err(|_| XmpError::BadValue(format!("Invalid time zone: {}", dt)))?;
+            Ok(PrimitiveDateTime::try_from(dt)?.assume_offset(offset))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -518,8 +982,8 @@ mod tests {
         let dt = XmpDateTime::parse("2023").unwrap();
         assert_eq!(dt.year, 2023);
         assert_eq!(dt.month, 0);
-        assert_eq!(dt.has_date, true);
-        assert_eq!(dt.has_time, false);
+        assert!(dt.has_date);
+        assert!(!dt.has_time);
     }
 
     #[test]
@@ -536,7 +1000,7 @@ mod tests {
         assert_eq!(dt.year, 2023);
         assert_eq!(dt.month, 12);
         assert_eq!(dt.day, 25);
-        assert_eq!(dt.has_time, false);
+        assert!(!dt.has_time);
     }
 
     #[test]
@@ -548,20 +1012,20 @@ mod tests {
         assert_eq!(dt.hour, 10);
         assert_eq!(dt.minute, 30);
         assert_eq!(dt.second, 0);
-        assert_eq!(dt.has_time, true);
+        assert!(dt.has_time);
     }
 
     #[test]
     fn test_parse_with_timezone_utc() {
         let dt = XmpDateTime::parse("2023-12-25T10:30:00Z").unwrap();
-        assert_eq!(dt.has_timezone, true);
+        assert!(dt.has_timezone);
         assert_eq!(dt.tz_sign, 0);
     }
 
     #[test]
     fn test_parse_with_timezone_offset() {
         let dt = XmpDateTime::parse("2023-12-25T10:30:00+08:00").unwrap();
-        assert_eq!(dt.has_timezone, true);
+        assert!(dt.has_timezone);
         assert_eq!(dt.tz_sign, 1);
         assert_eq!(dt.tz_hour, 8);
         assert_eq!(dt.tz_minute, 0);
@@ -647,4 +1111,110 @@ mod tests {
             assert_eq!(dt.second, dt2.second);
         }
     }
+
+    #[test]
+    fn test_convert_time_zones() {
+        let mut dt = XmpDateTime::parse("2023-12-31T22:30:00-05:00").unwrap();
+        dt.convert_to_utc();
+        assert_eq!(dt.format(), "2024-01-01T03:30:00Z");
+        dt.convert_to_local(-(9 * 60 + 30));
+        assert_eq!(dt.format(), "2023-12-31T18:00:00-09:30");
+
+        let mut naive = XmpDateTime::parse("2023-12-25T10:30:00").unwrap();
+        naive.convert_to_utc();
+        assert_eq!(naive.format(), "2023-12-25T10:30:00");
+        assert_eq!(naive.timezone_offset(), None);
+    }
+
+    #[test]
+    fn test_compare_and_arithmetic() {
+        let a = XmpDateTime::parse("2024-03-01T00:00:00+01:00").unwrap();
+        let b = XmpDateTime::parse("2024-02-29T23:00:00Z").unwrap();
+        assert_eq!(a.compare(&b), Ordering::Equal);
+        assert_eq!(a.seconds_since(&b), 0);
+        assert_ne!(a, b);
+
+        let mut date = XmpDateTime::from_date(2024, 2, 28);
+        date.add_days(1);
+        assert_eq!(date.format(), "2024-02-29");
+        date.add_days(-365);
+        assert_eq!(date.format(), "2023-03-01");
+        assert!(date.is_date_only());
+
+        let mut time = XmpDateTime::from_time(23, 59, 30);
+        time.add_seconds(45);
+        assert_eq!(time.format(), "00:00:15");
+        assert!(time.is_time_only());
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let dt = XmpDateTime::from_timestamp(0, 0);
+        assert_eq!(dt.format(), "1970-01-01T00:00:00Z");
+        let dt = XmpDateTime::from_timestamp(1_700_000_000, 500_000_000);
+        assert_eq!(dt.to_string(), "2023-11-14T22:13:20.5Z");
+        assert_eq!(dt.timestamp(), 1_700_000_000);
+
+        let dt: XmpDateTime = "-0044-03-15".parse().unwrap();
+        assert_eq!(dt.year, -44);
+        assert_eq!(dt.format(), "-0044-03-15");
+        assert_eq!(XmpDateTime::from_timestamp(dt.timestamp(), 0).day, 15);
+
+        let before = UNIX_EPOCH - std::time::Duration::from_millis(1500);
+        let dt = XmpDateTime::from(before);
+        assert_eq!(dt.format(), "1969-12-31T23:59:58.5Z");
+    }
+
+    #[test]
+    fn test_current_datetime() {
+        let now = current_datetime().unwrap();
+        assert!(now.year >= 2024);
+        assert!(now.validate().is_ok());
+        assert_eq!(now.timezone_offset(), Some(0));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono_interop() {
+        use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
+
+        let dt = XmpDateTime::parse("2024-05-06T12:30:00.25+02:00").unwrap();
+        let chrono_dt = DateTime::<FixedOffset>::try_from(&dt).unwrap();
+        assert_eq!(chrono_dt.timestamp(), dt.timestamp());
+        assert_eq!(chrono_dt.offset().local_minus_utc(), 7200);
+        assert_eq!(XmpDateTime::from(chrono_dt), dt);
+
+        let naive = XmpDateTime::parse("2024-05-06T12:30").unwrap();
+        assert!(DateTime::<FixedOffset>::try_from(&naive).is_err());
+        let chrono_naive = NaiveDateTime::try_from(&naive).unwrap();
+        assert_eq!(XmpDateTime::from(chrono_naive), naive);
+
+        let month = XmpDateTime::parse("2024-05").unwrap();
+        assert_eq!(
+            NaiveDate::try_from(&month).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()
+        );
+        assert!(NaiveDate::try_from(&XmpDateTime::from_time(10, 0, 0)).is_err());
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time_interop() {
+        use time::{Date, OffsetDateTime, PrimitiveDateTime};
+
+        let dt = XmpDateTime::parse("2024-05-06T12:30:00.5-05:00").unwrap();
+        let time_dt = OffsetDateTime::try_from(&dt).unwrap();
+        assert_eq!(time_dt.unix_timestamp(), dt.timestamp());
+        assert_eq!(time_dt.offset().whole_minutes(), -300);
+        assert_eq!(XmpDateTime::from(time_dt), dt);
+
+        let naive = XmpDateTime::parse("2024-05-06T12:30").unwrap();
+        assert!(OffsetDateTime::try_from(&naive).is_err());
+        let time_naive = PrimitiveDateTime::try_from(&naive).unwrap();
+        assert_eq!(XmpDateTime::from(time_naive), naive);
+
+        let date = Date::try_from(&XmpDateTime::parse("2024").unwrap()).unwrap();
+        assert_eq!(date.to_string(), "2024-01-01");
+        assert!(Date::try_from(&XmpDateTime::from_date(2023, 2, 30)).is_err());
+    }
 }
@@ -0,0 +1,201 @@
+//! Character encodings of XMP packets
+//!
+//! XMP packets are usually UTF-8, but the XMP Specification also allows
+//! UTF-16 and UTF-32, big or little endian. The encoding is declared by the
+//! byte order mark in the `begin` attribute of `<?xpacket?>`, and can be
+//! recognized from the first bytes of the packet: `<` is followed or
+//! preceded by zero bytes in the wider encodings.
+
+use crate::core::error::{XmpError, XmpResult};
+
+/// Character encoding of an XMP packet
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum PacketEncoding {
+    /// UTF-8
+    #[default]
+    Utf8,
+    /// UTF-16, big endian
+    Utf16Be,
+    /// UTF-16, little endian
+    Utf16Le,
+    /// UTF-32, big endian
+    Utf32Be,
+    /// UTF-32, little endian
+    Utf32Le,
+}
+
+impl PacketEncoding {
+    /// All the encodings
+    pub const ALL: [PacketEncoding; 5] = [
+        PacketEncoding::Utf8,
+        PacketEncoding::Utf16Be,
+        PacketEncoding::Utf16Le,
+        PacketEncoding::Utf32Be,
+        PacketEncoding::Utf32Le,
+    ];
+
+    /// Detect the encoding of XML data
+    ///
+    /// Uses the byte order mark if there is one, or else the zero bytes
+    /// around the first (ASCII) character. Defaults to UTF-8.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xmpkit::utils::encoding::PacketEncoding;
+    ///
+    /// let data = PacketEncoding::Utf16Le.encode("<?xpacket begin=\"\u{FEFF}\"?>");
+    /// assert_eq!(PacketEncoding::detect(&data), PacketEncoding::Utf16Le);
+    /// assert_eq!(PacketEncoding::detect(b"<?xpacket"), PacketEncoding::Utf8);
+    /// ```
+    pub fn detect(data: &[u8]) -> Self {
+        // UTF-32 first, since its little endian BOM starts like UTF-16's
+        for encoding in [Self::Utf32Be, Self::Utf32Le, Self::Utf16Be, Self::Utf16Le] {
+            if data.starts_with(encoding.bom()) {
+                return encoding;
+            }
+        }
+        match data {
+            [0, 0, 0, b, ..] if *b != 0 => Self::Utf32Be,
+            [b, 0, 0, 0, ..] if *b != 0 => Self::Utf32Le,
+            [0, b, ..] if *b != 0 => Self::Utf16Be,
+            [b, 0, ..] if *b != 0 => Self::Utf16Le,
+            _ => Self::Utf8,
+        }
+    }
+
+    /// Get the name of the encoding, such as "UTF-16BE"
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::Utf16Be => "UTF-16BE",
+            Self::Utf16Le => "UTF-16LE",
+            Self::Utf32Be => "UTF-32BE",
+            Self::Utf32Le => "UTF-32LE",
+        }
+    }
+
+    /// Get the size of a code unit, in bytes
+    pub fn unit_size(&self) -> usize {
+        match self {
+            Self::Utf8 => 1,
+            Self::Utf16Be | Self::Utf16Le => 2,
+            Self::Utf32Be | Self::Utf32Le => 4,
+        }
+    }
+
+    /// Get the byte order mark of the encoding
+    pub fn bom(&self) -> &'static [u8] {
+        match self {
+            Self::Utf8 => b"\xEF\xBB\xBF",
+            Self::Utf16Be => b"\xFE\xFF",
+            Self::Utf16Le => b"\xFF\xFE",
+            Self::Utf32Be => b"\x00\x00\xFE\xFF",
+            Self::Utf32Le => b"\xFF\xFE\x00\x00",
+        }
+    }
+
+    /// Encode text, without a byte order mark
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        match self {
+            Self::Utf8 => text.as_bytes().to_vec(),
+            Self::Utf16Be => text.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+            Self::Utf16Le => text.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+            Self::Utf32Be => text
+                .chars()
+                .flat_map(|c| (c as u32).to_be_bytes())
+                .collect(),
+            Self::Utf32Le => text
+                .chars()
+                .flat_map(|c| (c as u32).to_le_bytes())
+                .collect(),
+        }
+    }
+
+    /// Get the length of encoded text, in bytes
+    pub fn encoded_len(&self, text: &str) -> usize {
+        match self {
+            Self::Utf8 => text.len(),
+            Self::Utf16Be | Self::Utf16Le => text.encode_utf16().count() * 2,
+            Self::Utf32Be | Self::Utf32Le => text.chars().count() * 4,
+        }
+    }
+
+    /// Decode text
+    ///
+    /// A leading byte order mark is removed. Returns `XmpError::ParseError`
+    /// for invalid data.
+    pub fn decode(&self, data: &[u8]) -> XmpResult<String> {
+        let data = data.strip_prefix(self.bom()).unwrap_or(data);
+        let invalid = || XmpError::ParseError(format!("Invalid {} in XMP", self.name()));
+        if !data.len().is_multiple_of(self.unit_size()) {
+            return Err(invalid());
+        }
+        match self {
+            Self::Utf8 => String::from_utf8(data.to_vec()).map_err(|_| invalid()),
+            Self::Utf16Be | Self::Utf16Le => {
+                let units = data.chunks_exact(2).map(|unit| {
+                    let unit = [unit[0], unit[1]];
+                    if *self == Self::Utf16Be {
+                        u16::from_be_bytes(unit)
+                    } else {
+                        u16::from_le_bytes(unit)
+                    }
+                });
+                char::decode_utf16(units)
+                    .collect::<Result<String, _>>()
+                    .map_err(|_| invalid())
+            }
+            Self::Utf32Be | Self::Utf32Le => data
+                .chunks_exact(4)
+                .map(|unit| {
+                    let unit = [unit[0], unit[1], unit[2], unit[3]];
+                    let code = if *self == Self::Utf32Be {
+                        u32::from_be_bytes(unit)
+                    } else {
+                        u32::from_le_bytes(unit)
+                    };
+                    char::from_u32(code).ok_or_else(invalid)
+                })
+                .collect(),
+        }
+    }
+
+    /// Detect the encoding of XML data and decode it
+    ///
+    /// Trailing zero bytes, which some formats use as padding, are removed.
+    pub fn decode_detected(data: &[u8]) -> XmpResult<String> {
+        let encoding = Self::detect(data);
+        // Keep the zero bytes that belong to the last character
+        let unit = encoding.unit_size();
+        let content = data.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+        let end = content.div_ceil(unit) * unit;
+        encoding.decode(&data[..end.min(data.len())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let text = "<?xpacket begin=\"\u{FEFF}\"?><a>é 𝄞</a>";
+        for encoding in PacketEncoding::ALL {
+            let mut data = encoding.encode(text);
+            assert_eq!(data.len(), encoding.encoded_len(text));
+            assert_eq!(PacketEncoding::detect(&data), encoding);
+            assert_eq!(encoding.decode(&data).unwrap(), text);
+
+            data.extend_from_slice(&[0, 0, 0]);
+            assert_eq!(PacketEncoding::decode_detected(&data).unwrap(), text);
+        }
+
+        let mut data = PacketEncoding::Utf16Be.bom().to_vec();
+        data.extend(PacketEncoding::Utf16Be.encode("<a/>"));
+        assert_eq!(PacketEncoding::detect(&data), PacketEncoding::Utf16Be);
+        assert_eq!(PacketEncoding::Utf16Be.decode(&data).unwrap(), "<a/>");
+        assert!(PacketEncoding::Utf16Le.decode(&[0x3C]).is_err());
+        assert!(PacketEncoding::Utf32Le.decode(&[0, 0, 0x11, 0]).is_err());
+    }
+}
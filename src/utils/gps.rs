@@ -0,0 +1,279 @@
+//! GPS coordinate conversion utilities
+//!
+//! XMP writes the `exif:GPSLatitude` and `exif:GPSLongitude` coordinates
+//! as text: degrees and decimal minutes followed by the direction, as in
+//! `"48,51.5N"`, or degrees, minutes and seconds, as in `"48,51,30N"`. This
+//! module converts between that form and signed decimal degrees, where
+//! south and west are negative. `exif:GPSAltitude` is a rational number of
+//! meters, with `exif:GPSAltitudeRef` telling if it is above ("0") or below
+//! ("1") sea level.
+
+use crate::core::error::{XmpError, XmpResult};
+
+/// Axis of a GPS coordinate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpsAxis {
+    /// A latitude, from -90 (south) to 90 (north) degrees
+    Latitude,
+    /// A longitude, from -180 (west) to 180 (east) degrees
+    Longitude,
+}
+
+impl GpsAxis {
+    /// Largest absolute value of a coordinate in degrees
+    fn max_degrees(&self) -> f64 {
+        match self {
+            GpsAxis::Latitude => 90.0,
+            GpsAxis::Longitude => 180.0,
+        }
+    }
+
+    /// Direction letters of positive and negative coordinates
+    fn directions(&self) -> (char, char) {
+        match self {
+            GpsAxis::Latitude => ('N', 'S'),
+            GpsAxis::Longitude => ('E', 'W'),
+        }
+    }
+}
+
+/// Format decimal degrees as an XMP GPS coordinate ("DDD,MM.mmmmK")
+///
+/// The minutes are written with up to 8 decimals, without trailing zeros.
+///
+/// Fails with `XmpError::BadValue` if `degrees` is not a finite number
+/// within the range of the axis.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::utils::gps::{format_coordinate, GpsAxis};
+///
+/// assert_eq!(format_coordinate(48.8583, GpsAxis::Latitude).unwrap(), "48,51.498N");
+/// assert_eq!(format_coordinate(-2.5, GpsAxis::Longitude).unwrap(), "2,30W");
+/// ```
+pub fn format_coordinate(degrees: f64, axis: GpsAxis) -> XmpResult<String> {
+    if !degrees.is_finite() || degrees.abs() > axis.max_degrees() {
+        return Err(XmpError::BadValue(format!(
+            "Invalid {:?} {}",
+            axis, degrees
+        )));
+    }
+    let (positive, negative) = axis.directions();
+    let direction = if degrees < 0.0 { negative } else { positive };
+
+    // Round the minutes before splitting them, so that they never show as 60
+    let total_minutes = (degrees.abs() * 60.0 * 1e8).round() / 1e8;
+    let whole_degrees = (total_minutes / 60.0).floor();
+    let minutes = format!("{:.8}", total_minutes - whole_degrees * 60.0);
+    let minutes = minutes.trim_end_matches('0').trim_end_matches('.');
+    Ok(format!("{},{}{}", whole_degrees, minutes, direction))
+}
+
+/// Parse an XMP GPS coordinate as decimal degrees and the axis of its
+/// direction
+///
+/// Accepts both "DDD,MM.mmK" and "DDD,MM,SSK", where K is N, S, E or W
+/// (in any case). South and west coordinates are negative.
+///
+/// Fails with `XmpError::BadValue` if the coordinate is malformed or out
+/// of range.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::utils::gps::{parse_coordinate, GpsAxis};
+///
+/// assert_eq!(parse_coordinate("48,51.5N").unwrap(), (48.858333333333334, GpsAxis::Latitude));
+/// assert_eq!(parse_coordinate("2,30,0W").unwrap(), (-2.5, GpsAxis::Longitude));
+/// assert!(parse_coordinate("48.5N,2E").is_err());
+/// ```
+pub fn parse_coordinate(value: &str) -> XmpResult<(f64, GpsAxis)> {
+    let invalid = || XmpError::BadValue(format!("Invalid GPS coordinate '{}'", value));
+    let trimmed = value.trim();
+    let direction = trimmed.chars().last().ok_or_else(invalid)?;
+    let (axis, sign) = match direction.to_ascii_uppercase() {
+        'N' => (GpsAxis::Latitude, 1.0),
+        'S' => (GpsAxis::Latitude, -1.0),
+        'E' => (GpsAxis::Longitude, 1.0),
+        'W' => (GpsAxis::Longitude, -1.0),
+        _ => return Err(invalid()),
+    };
+
+    let number = |part: &str| -> XmpResult<f64> {
+        let part = part.trim();
+        if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            return Err(invalid());
+        }
+        part.parse().map_err(|_| invalid())
+    };
+    let parts: Vec<&str> = trimmed[..trimmed.len() - 1].split(',').collect();
+    let (degrees, minutes) = match parts.as_slice() {
+        [degrees, minutes] => (number(degrees)?, number(minutes)?),
+        [degrees, minutes, seconds] => {
+            let seconds = number(seconds)?;
+            if seconds >= 60.0 {
+                return Err(invalid());
+            }
+            (number(degrees)?, number(minutes)? + seconds / 60.0)
+        }
+        _ => return Err(invalid()),
+    };
+    if minutes >= 60.0 {
+        return Err(invalid());
+    }
+
+    let decimal = degrees + minutes / 60.0;
+    if decimal > axis.max_degrees() {
+        return Err(invalid());
+    }
+    Ok((sign * decimal, axis))
+}
+
+/// Format an altitude in meters as `exif:GPSAltitude` and
+/// `exif:GPSAltitudeRef` values
+///
+/// The altitude is a rational number of meters, rounded to the
+/// millimeter, and the reference is "1" below sea level and "0" otherwise.
+///
+/// Fails with `XmpError::BadValue` if `meters` is not a finite number or
+/// is too large.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::utils::gps::format_altitude;
+///
+/// assert_eq!(format_altitude(35.5).unwrap(), ("71/2".to_string(), "0"));
+/// assert_eq!(format_altitude(-12.0).unwrap(), ("12/1".to_string(), "1"));
+/// ```
+pub fn format_altitude(meters: f64) -> XmpResult<(String, &'static str)> {
+    let millimeters = (meters.abs() * 1000.0).round();
+    if !meters.is_finite() || millimeters > u32::MAX as f64 {
+        return Err(XmpError::BadValue(format!("Invalid altitude {}", meters)));
+    }
+    let numerator = millimeters as u32;
+    let divisor = gcd(numerator, 1000);
+    let reference = if meters < 0.0 && numerator > 0 {
+        "1"
+    } else {
+        "0"
+    };
+    Ok((
+        format!("{}/{}", numerator / divisor, 1000 / divisor),
+        reference,
+    ))
+}
+
+/// Parse `exif:GPSAltitude` and `exif:GPSAltitudeRef` values as an
+/// altitude in meters
+///
+/// The altitude can be a rational ("71/2") or a decimal number. A missing
+/// reference means above sea level.
+///
+/// Fails with `XmpError::BadValue` if a value is malformed.
+pub fn parse_altitude(altitude: &str, reference: Option<&str>) -> XmpResult<f64> {
+    let invalid = || XmpError::BadValue(format!("Invalid altitude '{}'", altitude));
+    let meters = match altitude.trim().split_once('/') {
+        Some((numerator, denominator)) => {
+            let numerator: f64 = numerator.trim().parse().map_err(|_| invalid())?;
+            let denominator: f64 = denominator.trim().parse().map_err(|_| invalid())?;
+            if denominator == 0.0 {
+                return Err(invalid());
+            }
+            numerator / denominator
+        }
+        None => altitude.trim().parse().map_err(|_| invalid())?,
+    };
+    if !meters.is_finite() {
+        return Err(invalid());
+    }
+    match reference.map(str::trim) {
+        None | Some("0") => Ok(meters),
+        Some("1") => Ok(-meters),
+        Some(other) => Err(XmpError::BadValue(format!(
+            "Invalid altitude reference '{}'",
+            other
+        ))),
+    }
+}
+
+/// Greatest common divisor
+fn gcd(mut a: u32, mut b: u32) -> u32 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coordinate_conversion() {
+        assert_eq!(format_coordinate(0.0, GpsAxis::Latitude).unwrap(), "0,0N");
+        assert_eq!(
+            format_coordinate(-33.8688, GpsAxis::Latitude).unwrap(),
+            "33,52.128S"
+        );
+        assert_eq!(
+            format_coordinate(180.0, GpsAxis::Longitude).unwrap(),
+            "180,0E"
+        );
+        // Minutes that round up to 60 carry into the degrees
+        assert_eq!(
+            format_coordinate(10.999999999999, GpsAxis::Longitude).unwrap(),
+            "11,0E"
+        );
+        assert!(format_coordinate(90.5, GpsAxis::Latitude).is_err());
+        assert!(format_coordinate(f64::NAN, GpsAxis::Longitude).is_err());
+
+        for degrees in [48.858_37, -122.419_42, 0.000_1, -179.999_9] {
+            let text = format_coordinate(degrees, GpsAxis::Longitude).unwrap();
+            let (parsed, axis) = parse_coordinate(&text).unwrap();
+            assert!((parsed - degrees).abs() < 1e-9, "{}", text);
+            assert_eq!(axis, GpsAxis::Longitude);
+        }
+
+        assert_eq!(
+            parse_coordinate(" 48,30,36n ").unwrap(),
+            (48.51, GpsAxis::Latitude)
+        );
+        assert_eq!(parse_coordinate("33,52.128S").unwrap().0, -33.8688);
+        for invalid in [
+            "",
+            "N",
+            "48N",
+            "48,60N",
+            "48,30,60N",
+            "91,0N",
+            "181,0E",
+            "48,30X",
+            "-48,30N",
+            "48,,30N",
+            "1,2,3,4N",
+        ] {
+            assert!(parse_coordinate(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_altitude_conversion() {
+        assert_eq!(format_altitude(0.0).unwrap(), ("0/1".to_string(), "0"));
+        assert_eq!(
+            format_altitude(8848.86).unwrap(),
+            ("442443/50".to_string(), "0")
+        );
+        assert_eq!(format_altitude(-0.0001).unwrap(), ("0/1".to_string(), "0"));
+        assert!(format_altitude(f64::INFINITY).is_err());
+        assert!(format_altitude(1e12).is_err());
+
+        assert_eq!(parse_altitude("442443/50", Some("0")).unwrap(), 8848.86);
+        assert_eq!(parse_altitude("12", Some("1")).unwrap(), -12.0);
+        assert_eq!(parse_altitude("71/2", None).unwrap(), 35.5);
+        assert!(parse_altitude("1/0", None).is_err());
+        assert!(parse_altitude("high", None).is_err());
+        assert!(parse_altitude("12/1", Some("2")).is_err());
+    }
+}
@@ -0,0 +1,100 @@
+//! Unique identifiers for XMP documents
+//!
+//! Adobe applications identify documents and their saved versions with
+//! random (version 4) UUIDs, prefixed with `xmp.did:` for the
+//! `xmpMM:DocumentID` and `xmp.iid:` for the `xmpMM:InstanceID`, e.g.
+//! `xmp.iid:2f0cc33b-5b6d-4a4c-a8b7-1f8c0e3e7a3f`.
+//!
+//! The UUIDs are not suitable for security purposes.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Kind of XMP identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XmpIdKind {
+    /// A document ID (`xmp.did:`), for `xmpMM:DocumentID` and
+    /// `xmpMM:OriginalDocumentID`
+    Document,
+    /// An instance ID (`xmp.iid:`), for `xmpMM:InstanceID`
+    Instance,
+}
+
+impl XmpIdKind {
+    /// Prefix of the identifiers of this kind
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            XmpIdKind::Document => "xmp.did:",
+            XmpIdKind::Instance => "xmp.iid:",
+        }
+    }
+}
+
+/// Generate a new XMP identifier
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::utils::guid::{new_xmp_id, XmpIdKind};
+///
+/// let id = new_xmp_id(XmpIdKind::Instance);
+/// assert!(id.starts_with("xmp.iid:"));
+/// assert_eq!(id.len(), "xmp.iid:".len() + 36);
+/// ```
+pub fn new_xmp_id(kind: XmpIdKind) -> String {
+    format!("{}{}", kind.prefix(), new_uuid())
+}
+
+/// Generate a random (version 4) UUID in its hyphenated lowercase form
+pub fn new_uuid() -> String {
+    let hex: String = random_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// Generate 16 random bytes with the UUID version and variant bits set
+///
+/// The standard library seeds `RandomState` randomly for each process; a
+/// counter makes the values differ within a process.
+fn random_bytes() -> [u8; 16] {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut bytes = [0u8; 16];
+    for chunk in bytes.chunks_mut(8) {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Ok(elapsed) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+            hasher.write_u128(elapsed.as_nanos());
+        }
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes());
+    }
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_uuid() {
+        let uuid = new_uuid();
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(&uuid[14..15], "4");
+        assert!("89ab".contains(&uuid[19..20]));
+        assert_ne!(new_uuid(), uuid);
+        assert!(new_xmp_id(XmpIdKind::Document).starts_with("xmp.did:"));
+    }
+}
@@ -2,4 +2,11 @@
 //!
 //! This module contains utility functions used throughout the XMP Toolkit.
 
+pub mod base64;
+pub mod convert;
 pub mod datetime;
+pub mod encoding;
+pub mod gps;
+pub mod guid;
+pub mod md5;
+pub mod timecode;
@@ -0,0 +1,422 @@
+//! SMPTE timecode and media time conversion utilities
+//!
+//! The `xmpDM:` schema writes times in three forms: `xmpDM:Time` rational
+//! times such as `xmpDM:duration`, which count units of a scale in seconds;
+//! `xmpDM:Timecode` SMPTE timecodes ("HH:MM:SS:FF") with their
+//! `xmpDM:timeFormat`; and marker start times and durations, which count
+//! frames of the `xmpDM:frameRate` of their track ("f25", or "f30000s1001"
+//! for 29.97 fps). This module converts between frame counts and the other
+//! forms, the way Premiere interprets them.
+//!
+//! The 29.97 and 59.94 fps formats have drop frame variants, which skip the
+//! first 2 (or 4) frame numbers of every minute except every tenth one, so
+//! that the timecode follows the wall clock. Their timecodes are written
+//! with semicolons ("01;00;00;00"). The 23.976 fps format counts 24 frames
+//! per second without dropping any.
+
+use crate::core::error::{XmpError, XmpResult};
+
+/// A time (`xmpDM:Time`): `value` units of `scale` seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MediaTime {
+    /// Number of units (`xmpDM:value`)
+    pub value: i64,
+    /// Numerator of the length of a unit in seconds (`xmpDM:scale`)
+    pub scale_numerator: u32,
+    /// Denominator of the length of a unit in seconds (`xmpDM:scale`)
+    pub scale_denominator: u32,
+}
+
+impl MediaTime {
+    /// Create a time of `value` units of `scale_numerator/scale_denominator`
+    /// seconds
+    pub fn new(value: i64, scale_numerator: u32, scale_denominator: u32) -> Self {
+        Self {
+            value,
+            scale_numerator,
+            scale_denominator,
+        }
+    }
+
+    /// Get the time in seconds
+    pub fn seconds(&self) -> f64 {
+        self.value as f64 * f64::from(self.scale_numerator) / f64::from(self.scale_denominator)
+    }
+}
+
+/// Format of a timecode (`xmpDM:timeFormat`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// 23.976 frames per second (`23976Timecode`)
+    Fps23976,
+    /// 24 frames per second (`24Timecode`)
+    Fps24,
+    /// 25 frames per second (`25Timecode`)
+    Fps25,
+    /// 29.97 frames per second, drop frame (`2997DropTimecode`)
+    Fps2997Drop,
+    /// 29.97 frames per second, non-drop frame (`2997NonDropTimecode`)
+    Fps2997NonDrop,
+    /// 30 frames per second (`30Timecode`)
+    Fps30,
+    /// 50 frames per second (`50Timecode`)
+    Fps50,
+    /// 59.94 frames per second, drop frame (`5994DropTimecode`)
+    Fps5994Drop,
+    /// 59.94 frames per second, non-drop frame (`5994NonDropTimecode`)
+    Fps5994NonDrop,
+    /// 60 frames per second (`60Timecode`)
+    Fps60,
+}
+
+impl TimeFormat {
+    /// All the formats
+    const ALL: [TimeFormat; 10] = [
+        TimeFormat::Fps23976,
+        TimeFormat::Fps24,
+        TimeFormat::Fps25,
+        TimeFormat::Fps2997Drop,
+        TimeFormat::Fps2997NonDrop,
+        TimeFormat::Fps30,
+        TimeFormat::Fps50,
+        TimeFormat::Fps5994Drop,
+        TimeFormat::Fps5994NonDrop,
+        TimeFormat::Fps60,
+    ];
+
+    /// Get the `xmpDM:timeFormat` value of the format
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeFormat::Fps23976 => "23976Timecode",
+            TimeFormat::Fps24 => "24Timecode",
+            TimeFormat::Fps25 => "25Timecode",
+            TimeFormat::Fps2997Drop => "2997DropTimecode",
+            TimeFormat::Fps2997NonDrop => "2997NonDropTimecode",
+            TimeFormat::Fps30 => "30Timecode",
+            TimeFormat::Fps50 => "50Timecode",
+            TimeFormat::Fps5994Drop => "5994DropTimecode",
+            TimeFormat::Fps5994NonDrop => "5994NonDropTimecode",
+            TimeFormat::Fps60 => "60Timecode",
+        }
+    }
+
+    /// Get the format of an `xmpDM:timeFormat` value
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.as_str() == value)
+    }
+
+    /// Get the frame rate as frames per second, a numerator and a
+    /// denominator, such as 30000/1001 for 29.97 fps
+    pub fn frame_rate(&self) -> (u32, u32) {
+        match self {
+            TimeFormat::Fps23976 => (24000, 1001),
+            TimeFormat::Fps2997Drop | TimeFormat::Fps2997NonDrop => (30000, 1001),
+            TimeFormat::Fps5994Drop | TimeFormat::Fps5994NonDrop => (60000, 1001),
+            _ => (self.frames_per_second(), 1),
+        }
+    }
+
+    /// Get the number of frame numbers of a second of timecode, such as 30
+    /// for 29.97 fps
+    pub fn frames_per_second(&self) -> u32 {
+        match self {
+            TimeFormat::Fps23976 | TimeFormat::Fps24 => 24,
+            TimeFormat::Fps25 => 25,
+            TimeFormat::Fps2997Drop | TimeFormat::Fps2997NonDrop | TimeFormat::Fps30 => 30,
+            TimeFormat::Fps50 => 50,
+            TimeFormat::Fps5994Drop | TimeFormat::Fps5994NonDrop | TimeFormat::Fps60 => 60,
+        }
+    }
+
+    /// Check if the format is drop frame
+    pub fn is_drop_frame(&self) -> bool {
+        self.dropped_frames() != 0
+    }
+
+    /// Number of frame numbers dropped at the start of most minutes
+    fn dropped_frames(&self) -> u64 {
+        match self {
+            TimeFormat::Fps2997Drop => 2,
+            TimeFormat::Fps5994Drop => 4,
+            _ => 0,
+        }
+    }
+}
+
+/// Number of frames in 24 hours of timecode, after which timecodes wrap
+fn frames_per_day(format: TimeFormat) -> u64 {
+    let fps = u64::from(format.frames_per_second());
+    fps * 86_400 - format.dropped_frames() * 24 * 6 * 9
+}
+
+/// Format a frame count as a timecode
+///
+/// Drop frame timecodes are written with semicolons ("HH;MM;SS;FF"), the
+/// others with colons ("HH:MM:SS:FF").
+///
+/// Fails with `XmpError::BadValue` if the frame count is 24 hours or more.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::utils::timecode::{frames_to_timecode, TimeFormat};
+///
+/// assert_eq!(frames_to_timecode(1800, TimeFormat::Fps2997Drop).unwrap(), "00;01;00;02");
+/// assert_eq!(frames_to_timecode(1800, TimeFormat::Fps30).unwrap(), "00:01:00:00");
+/// ```
+pub fn frames_to_timecode(frames: u64, format: TimeFormat) -> XmpResult<String> {
+    if frames >= frames_per_day(format) {
+        return Err(XmpError::BadValue(format!(
+            "Frame {} is beyond 24 hours of {}",
+            frames,
+            format.as_str()
+        )));
+    }
+    let fps = u64::from(format.frames_per_second());
+    let dropped = format.dropped_frames();
+    let mut number = frames;
+    if dropped != 0 {
+        // Add back the frame numbers dropped before the frame
+        let per_ten_minutes = fps * 600 - dropped * 9;
+        let per_minute = fps * 60 - dropped;
+        let (tens, rest) = (frames / per_ten_minutes, frames % per_ten_minutes);
+        number += dropped * 9 * tens;
+        if rest > dropped {
+            number += dropped * ((rest - dropped) / per_minute);
+        }
+    }
+
+    let separator = if format.is_drop_frame() { ';' } else { ':' };
+    Ok(format!(
+        "{:02}{sep}{:02}{sep}{:02}{sep}{:02}",
+        number / (fps * 3600),
+        number / (fps * 60) % 60,
+        number / fps % 60,
+        number % fps,
+        sep = separator
+    ))
+}
+
+/// Parse a timecode as a frame count
+///
+/// Accepts colons and semicolons as separators, and a period or comma
+/// before the frames as some writers do.
+///
+/// Fails with `XmpError::BadValue` if the timecode is malformed, out of
+/// range, or a frame number that a drop frame format skips.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::utils::timecode::{timecode_to_frames, TimeFormat};
+///
+/// assert_eq!(timecode_to_frames("00;01;00;02", TimeFormat::Fps2997Drop).unwrap(), 1800);
+/// assert_eq!(timecode_to_frames("01:00:00:00", TimeFormat::Fps25).unwrap(), 90_000);
+/// assert!(timecode_to_frames("00;01;00;00", TimeFormat::Fps2997Drop).is_err());
+/// ```
+pub fn timecode_to_frames(value: &str, format: TimeFormat) -> XmpResult<u64> {
+    let invalid =
+        || XmpError::BadValue(format!("Invalid {} timecode '{}'", format.as_str(), value));
+    let parts = value
+        .trim()
+        .split([':', ';', '.', ','])
+        .map(|part| {
+            if part.len() == 2 && part.chars().all(|c| c.is_ascii_digit()) {
+                part.parse::<u64>().map_err(|_| invalid())
+            } else {
+                Err(invalid())
+            }
+        })
+        .collect::<XmpResult<Vec<u64>>>()?;
+    let [hours, minutes, seconds, frame] = parts[..] else {
+        return Err(invalid());
+    };
+
+    let fps = u64::from(format.frames_per_second());
+    let dropped = format.dropped_frames();
+    if hours >= 24 || minutes >= 60 || seconds >= 60 || frame >= fps {
+        return Err(invalid());
+    }
+    let total_minutes = hours * 60 + minutes;
+    if dropped != 0 && seconds == 0 && frame < dropped && minutes % 10 != 0 {
+        return Err(invalid());
+    }
+    let number = (total_minutes * 60 + seconds) * fps + frame;
+    Ok(number - dropped * (total_minutes - total_minutes / 10))
+}
+
+/// Convert a frame count to an `xmpDM:Time` in frames of the format
+///
+/// The scale of the time is the length of a frame, such as 1001/30000 for
+/// 29.97 fps.
+pub fn frames_to_media_time(frames: u64, format: TimeFormat) -> XmpResult<MediaTime> {
+    let value = i64::try_from(frames)
+        .map_err(|_| XmpError::BadValue(format!("Frame count {} is too large", frames)))?;
+    let (numerator, denominator) = format.frame_rate();
+    Ok(MediaTime::new(value, denominator, numerator))
+}
+
+/// Convert an `xmpDM:Time` to the nearest frame count of the format
+///
+/// Fails with `XmpError::BadValue` if the time is negative or its scale has
+/// a zero denominator.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::utils::timecode::{media_time_to_frames, MediaTime, TimeFormat};
+///
+/// // 10 seconds in milliseconds
+/// let time = MediaTime::new(10_000, 1, 1000);
+/// assert_eq!(media_time_to_frames(&time, TimeFormat::Fps25).unwrap(), 250);
+/// assert_eq!(media_time_to_frames(&time, TimeFormat::Fps2997NonDrop).unwrap(), 300);
+/// ```
+pub fn media_time_to_frames(time: &MediaTime, format: TimeFormat) -> XmpResult<u64> {
+    if time.value < 0 || time.scale_denominator == 0 {
+        return Err(XmpError::BadValue(format!(
+            "Invalid media time {} x {}/{}",
+            time.value, time.scale_numerator, time.scale_denominator
+        )));
+    }
+    let (fps_numerator, fps_denominator) = format.frame_rate();
+    let numerator =
+        i128::from(time.value) * i128::from(time.scale_numerator) * i128::from(fps_numerator);
+    let denominator = i128::from(time.scale_denominator) * i128::from(fps_denominator);
+    let frames = (numerator * 2 + denominator) / (denominator * 2);
+    u64::try_from(frames).map_err(|_| XmpError::BadValue("Media time is too large".to_string()))
+}
+
+/// Format a frame rate as an `xmpDM:frameRate` ("f25", or "f30000s1001")
+///
+/// Fails with `XmpError::BadValue` if either number is 0.
+pub fn format_frame_rate(numerator: u64, denominator: u64) -> XmpResult<String> {
+    match (numerator, denominator) {
+        (0, _) | (_, 0) => Err(XmpError::BadValue(format!(
+            "Invalid frame rate {}/{}",
+            numerator, denominator
+        ))),
+        (numerator, 1) => Ok(format!("f{}", numerator)),
+        (numerator, denominator) => Ok(format!("f{}s{}", numerator, denominator)),
+    }
+}
+
+/// Parse an `xmpDM:frameRate` ("f25", or "f30000s1001") as frames per
+/// second, a numerator and a denominator
+///
+/// Fails with `XmpError::BadValue` if the frame rate is malformed or 0.
+///
+/// # Example
+///
+/// ```rust
+/// use xmpkit::utils::timecode::parse_frame_rate;
+///
+/// assert_eq!(parse_frame_rate("f30000s1001").unwrap(), (30000, 1001));
+/// assert_eq!(parse_frame_rate("f25").unwrap(), (25, 1));
+/// ```
+pub fn parse_frame_rate(value: &str) -> XmpResult<(u64, u64)> {
+    let invalid = || XmpError::BadValue(format!("Invalid frame rate '{}'", value));
+    let number = |part: &str| -> XmpResult<u64> {
+        if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        match part.parse() {
+            Ok(0) | Err(_) => Err(invalid()),
+            Ok(number) => Ok(number),
+        }
+    };
+    let rate = value.trim().strip_prefix('f').ok_or_else(invalid)?;
+    match rate.split_once('s') {
+        Some((numerator, denominator)) => Ok((number(numerator)?, number(denominator)?)),
+        None => Ok((number(rate)?, 1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timecode_conversion() {
+        let cases = [
+            (0, TimeFormat::Fps2997Drop, "00;00;00;00"),
+            (1799, TimeFormat::Fps2997Drop, "00;00;59;29"),
+            (1800, TimeFormat::Fps2997Drop, "00;01;00;02"),
+            (17982, TimeFormat::Fps2997Drop, "00;10;00;00"),
+            (107_892, TimeFormat::Fps2997Drop, "01;00;00;00"),
+            (3600, TimeFormat::Fps5994Drop, "00;01;00;04"),
+            (215_784, TimeFormat::Fps5994Drop, "01;00;00;00"),
+            (107_892, TimeFormat::Fps2997NonDrop, "00:59:56:12"),
+            (86_400, TimeFormat::Fps23976, "01:00:00:00"),
+            (90_049, TimeFormat::Fps25, "01:00:01:24"),
+        ];
+        for (frames, format, timecode) in cases {
+            assert_eq!(frames_to_timecode(frames, format).unwrap(), timecode);
+            assert_eq!(timecode_to_frames(timecode, format).unwrap(), frames);
+        }
+        // Every frame round-trips across the drop frame minutes
+        for frames in 0..40_000 {
+            let timecode = frames_to_timecode(frames, TimeFormat::Fps2997Drop).unwrap();
+            assert_eq!(
+                timecode_to_frames(&timecode, TimeFormat::Fps2997Drop).unwrap(),
+                frames
+            );
+        }
+        let last = frames_per_day(TimeFormat::Fps2997Drop) - 1;
+        assert_eq!(
+            frames_to_timecode(last, TimeFormat::Fps2997Drop).unwrap(),
+            "23;59;59;29"
+        );
+        assert!(frames_to_timecode(last + 1, TimeFormat::Fps2997Drop).is_err());
+
+        assert_eq!(
+            timecode_to_frames(" 00:01:00.02 ", TimeFormat::Fps2997Drop).unwrap(),
+            1800
+        );
+        for (invalid, format) in [
+            ("", TimeFormat::Fps25),
+            ("00:00:00", TimeFormat::Fps25),
+            ("00:00:00:25", TimeFormat::Fps25),
+            ("00:60:00:00", TimeFormat::Fps25),
+            ("24:00:00:00", TimeFormat::Fps25),
+            ("0:00:00:00", TimeFormat::Fps25),
+            ("00:00:00:+1", TimeFormat::Fps25),
+            ("00;01;00;01", TimeFormat::Fps2997Drop),
+            ("00;02;00;03", TimeFormat::Fps5994Drop),
+        ] {
+            assert!(timecode_to_frames(invalid, format).is_err(), "{}", invalid);
+        }
+        assert!(timecode_to_frames("00:10:00:00", TimeFormat::Fps2997Drop).is_ok());
+    }
+
+    #[test]
+    fn test_media_time_and_frame_rate() {
+        let time = frames_to_media_time(300, TimeFormat::Fps2997Drop).unwrap();
+        assert_eq!(time, MediaTime::new(300, 1001, 30000));
+        assert_eq!(time.seconds(), 10.01);
+        assert_eq!(
+            media_time_to_frames(&time, TimeFormat::Fps2997Drop).unwrap(),
+            300
+        );
+        // 1.5 seconds rounds to the nearest frame
+        let time = MediaTime::new(1500, 1, 1000);
+        assert_eq!(
+            media_time_to_frames(&time, TimeFormat::Fps23976).unwrap(),
+            36
+        );
+        assert!(media_time_to_frames(&MediaTime::new(-1, 1, 25), TimeFormat::Fps25).is_err());
+        assert!(media_time_to_frames(&MediaTime::new(1, 1, 0), TimeFormat::Fps25).is_err());
+
+        assert_eq!(format_frame_rate(25, 1).unwrap(), "f25");
+        assert_eq!(format_frame_rate(30000, 1001).unwrap(), "f30000s1001");
+        assert_eq!(
+            parse_frame_rate("f254016000000").unwrap(),
+            (254_016_000_000, 1)
+        );
+        assert!(format_frame_rate(0, 1).is_err());
+        for invalid in ["", "25", "f", "f0", "f25s", "fs1001", "f25s0", "f2.5"] {
+            assert!(parse_frame_rate(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
@@ -22,6 +22,7 @@ use wasm_bindgen::prelude::*;
 /// }
 /// ```
 #[wasm_bindgen]
+#[derive(Debug)]
 pub struct XmpError {
     kind: XmpErrorKind,
     message: String,
@@ -70,6 +71,8 @@ pub enum XmpErrorKind {
     NotFound,
     /// Not supported error
     NotSupported,
+    /// Cancelled operation
+    Cancelled,
 }
 
 #[wasm_bindgen]
@@ -100,6 +103,7 @@ pub(crate) fn xmp_error_to_wasm_error(err: RustXmpError) -> XmpError {
         RustXmpError::InternalError(msg) => (XmpErrorKind::InternalError, msg.clone()),
         RustXmpError::NotFound(msg) => (XmpErrorKind::NotFound, msg.clone()),
         RustXmpError::NotSupported(msg) => (XmpErrorKind::NotSupported, msg.clone()),
+        RustXmpError::Cancelled => (XmpErrorKind::Cancelled, err.to_string()),
     };
     XmpError { kind, message }
 }
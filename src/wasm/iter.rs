@@ -0,0 +1,151 @@
+//! WebAssembly bindings for iteration over XMP metadata
+
+use crate::{
+    IterOptions as RustIterOptions, XmpIterItem as RustXmpIterItem, XmpIterator as RustXmpIterator,
+};
+use wasm_bindgen::prelude::*;
+
+/// Options to control iteration over XMP metadata (WebAssembly)
+///
+/// # Example
+///
+/// ```javascript
+/// const options = new IterOptions();
+/// options.namespace("http://purl.org/dc/elements/1.1/");
+/// options.just_leaf_nodes();
+/// const iter = meta.iter_with(options);
+/// ```
+#[derive(Default)]
+#[wasm_bindgen]
+pub struct IterOptions {
+    pub(crate) inner: RustIterOptions,
+}
+
+#[wasm_bindgen]
+impl IterOptions {
+    /// Create default options
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> IterOptions {
+        IterOptions::default()
+    }
+
+    /// Only visit the top-level properties, not their items, fields or qualifiers
+    pub fn just_children(&mut self) {
+        self.inner = self.inner.clone().just_children();
+    }
+
+    /// Only visit leaf nodes (simple values and qualifiers)
+    pub fn just_leaf_nodes(&mut self) {
+        self.inner = self.inner.clone().just_leaf_nodes();
+    }
+
+    /// Report only the last component of each path (e.g. `[1]` or `?xml:lang`)
+    pub fn just_leaf_name(&mut self) {
+        self.inner = self.inner.clone().just_leaf_name();
+    }
+
+    /// Do not visit qualifiers
+    pub fn omit_qualifiers(&mut self) {
+        self.inner = self.inner.clone().omit_qualifiers();
+    }
+
+    /// Only visit properties in the given namespace (URI or prefix)
+    pub fn namespace(&mut self, namespace: &str) {
+        self.inner = self.inner.clone().namespace(namespace);
+    }
+}
+
+/// A node visited by `XmpIterator` (WebAssembly)
+#[wasm_bindgen]
+pub struct XmpIterItem {
+    inner: RustXmpIterItem,
+}
+
+#[wasm_bindgen]
+impl XmpIterItem {
+    /// Namespace URI of the top-level property containing the node
+    #[wasm_bindgen(getter)]
+    pub fn schema_ns(&self) -> String {
+        self.inner.schema_ns.clone()
+    }
+
+    /// XMP path of the node (e.g. `dc:title[1]/?xml:lang`)
+    #[wasm_bindgen(getter)]
+    pub fn path(&self) -> String {
+        self.inner.path.clone()
+    }
+
+    /// Value of the node, or undefined for arrays and structures
+    #[wasm_bindgen(getter)]
+    pub fn value(&self) -> Option<String> {
+        self.inner.value.clone()
+    }
+
+    /// The node is a simple value
+    #[wasm_bindgen(getter)]
+    pub fn is_simple(&self) -> bool {
+        self.inner.options.is_simple()
+    }
+
+    /// The node is an array
+    #[wasm_bindgen(getter)]
+    pub fn is_array(&self) -> bool {
+        self.inner.options.is_array()
+    }
+
+    /// The node is a structure
+    #[wasm_bindgen(getter)]
+    pub fn is_struct(&self) -> bool {
+        self.inner.options.is_struct
+    }
+
+    /// The node is a qualifier of its parent
+    #[wasm_bindgen(getter)]
+    pub fn is_qualifier(&self) -> bool {
+        self.inner.options.is_qualifier
+    }
+
+    /// The node has an `xml:lang` qualifier
+    #[wasm_bindgen(getter)]
+    pub fn has_lang(&self) -> bool {
+        self.inner.options.has_lang
+    }
+
+    /// The node is a simple value that is a URI
+    #[wasm_bindgen(getter)]
+    pub fn is_uri(&self) -> bool {
+        self.inner.options.is_uri
+    }
+}
+
+/// Iterator over the nodes of an `XmpMeta` tree (WebAssembly)
+///
+/// Works on a snapshot taken when it is created, so the metadata can be
+/// modified while iterating.
+///
+/// # Example
+///
+/// ```javascript
+/// const iter = meta.iter();
+/// for (let item = iter.next(); item; item = iter.next()) {
+///     console.log(item.path, item.value);
+/// }
+/// ```
+#[wasm_bindgen]
+pub struct XmpIterator {
+    pub(crate) inner: RustXmpIterator,
+}
+
+#[wasm_bindgen]
+impl XmpIterator {
+    /// Get the next node, or undefined at the end
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<XmpIterItem> {
+        self.inner.next().map(|inner| XmpIterItem { inner })
+    }
+
+    /// Skip the children and qualifiers of the node returned last
+    pub fn skip_subtree(&mut self) {
+        self.inner.skip_subtree();
+    }
+}
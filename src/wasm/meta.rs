@@ -1,9 +1,22 @@
 //! WebAssembly bindings for XMP metadata operations
 
 use crate::wasm::error::{xmp_error_to_wasm_error, XmpError};
+use crate::wasm::iter::{IterOptions, XmpIterator};
+use crate::wasm::value::XmpValue as WasmXmpValue;
 use crate::{XmpMeta as RustXmpMeta, XmpValue};
 use wasm_bindgen::prelude::*;
 
+/// A localized text and its language (WebAssembly)
+///
+/// Returned by `XmpMeta.get_localized_text`.
+#[wasm_bindgen(getter_with_clone)]
+pub struct LocalizedText {
+    /// The text
+    pub value: String,
+    /// The `xml:lang` of the text that was found
+    pub language: String,
+}
+
 /// XmpMeta for WebAssembly
 ///
 /// Provides the same API as Rust's `XmpMeta`.
@@ -51,15 +64,49 @@ impl XmpMeta {
     pub fn get_property(&self, namespace: &str, property: &str) -> Option<String> {
         self.inner.get_property(namespace, property).map(|value| {
             match value {
-                XmpValue::String(s) => s,
+                XmpValue::String(s) | XmpValue::Uri(s) => s,
                 XmpValue::Integer(i) => i.to_string(),
                 XmpValue::Boolean(b) => b.to_string(),
-                XmpValue::DateTime(d) => d,
+                XmpValue::DateTime(d) => d.format(),
                 _ => format!("{:?}", value), // Fallback for complex types
             }
         })
     }
 
+    /// Get a property value with its type
+    ///
+    /// Unlike `get_property`, arrays and structures are returned as
+    /// `XmpValue`s of kind `Array` and `Structure`, with fields named
+    /// "prefix:name". Simple values are stored as text, so they are of kind
+    /// `String` (or `Uri`) whatever kind they were set with.
+    pub fn get_property_value(&self, namespace: &str, path: &str) -> Option<WasmXmpValue> {
+        self.inner
+            .get_property_tree(namespace, path)
+            .map(WasmXmpValue::from_rust)
+    }
+
+    /// Set a property to a typed value
+    ///
+    /// Only simple values can be set; arrays and structures fail with
+    /// `NotSupported`. Build arrays with `append_array_item_value`.
+    ///
+    /// # Example
+    ///
+    /// ```javascript
+    /// meta.set_property_value("http://ns.adobe.com/xap/1.0/", "Rating", XmpValue.integer(5));
+    /// ```
+    pub fn set_property_value(
+        &mut self,
+        namespace: &str,
+        path: &str,
+        value: &WasmXmpValue,
+    ) -> Result<(), XmpError> {
+        value
+            .to_rust()
+            .and_then(|value| self.inner.set_property(namespace, path, value))
+            .map_err(xmp_error_to_wasm_error)
+    }
+
     /// Set a property value
     ///
     /// # Arguments
@@ -101,19 +148,47 @@ impl XmpMeta {
             .map_err(xmp_error_to_wasm_error)
     }
 
+    /// Delete all properties of a namespace whose names match a pattern
+    ///
+    /// Returns the number of properties deleted.
+    pub fn delete_properties_matching(
+        &mut self,
+        namespace: &str,
+        pattern: &str,
+    ) -> Result<usize, XmpError> {
+        self.inner
+            .delete_properties_matching(namespace, pattern)
+            .map_err(xmp_error_to_wasm_error)
+    }
+
     /// Get an array item by index
     pub fn get_array_item(&self, namespace: &str, path: &str, index: usize) -> Option<String> {
         self.inner
             .get_array_item(namespace, path, index)
             .map(|value| match value {
-                XmpValue::String(s) => s,
+                XmpValue::String(s) | XmpValue::Uri(s) => s,
                 XmpValue::Integer(i) => i.to_string(),
                 XmpValue::Boolean(b) => b.to_string(),
-                XmpValue::DateTime(d) => d,
+                XmpValue::DateTime(d) => d.format(),
                 _ => format!("{:?}", value),
             })
     }
 
+    /// Get an array item by index (0-based), with its type
+    ///
+    /// Items that are arrays or structures are returned as with
+    /// `get_property_value`.
+    pub fn get_array_item_value(
+        &self,
+        namespace: &str,
+        path: &str,
+        index: usize,
+    ) -> Option<WasmXmpValue> {
+        self.inner
+            .get_property_tree(namespace, &format!("{}[{}]", path, index + 1))
+            .map(WasmXmpValue::from_rust)
+    }
+
     /// Get the size of an array property
     pub fn get_array_size(&self, namespace: &str, path: &str) -> Option<usize> {
         self.inner.get_array_size(namespace, path)
@@ -131,6 +206,19 @@ impl XmpMeta {
             .map_err(xmp_error_to_wasm_error)
     }
 
+    /// Append a typed item to an array property
+    pub fn append_array_item_value(
+        &mut self,
+        namespace: &str,
+        path: &str,
+        value: &WasmXmpValue,
+    ) -> Result<(), XmpError> {
+        value
+            .to_rust()
+            .and_then(|value| self.inner.append_array_item(namespace, path, value))
+            .map_err(xmp_error_to_wasm_error)
+    }
+
     /// Insert an item into an array property at a specific index
     pub fn insert_array_item(
         &mut self,
@@ -161,15 +249,16 @@ impl XmpMeta {
         &self,
         namespace: &str,
         struct_path: &str,
+        field_namespace: &str,
         field: &str,
     ) -> Option<String> {
         self.inner
-            .get_struct_field(namespace, struct_path, field)
+            .get_struct_field(namespace, struct_path, field_namespace, field)
             .map(|value| match value {
-                XmpValue::String(s) => s,
+                XmpValue::String(s) | XmpValue::Uri(s) => s,
                 XmpValue::Integer(i) => i.to_string(),
                 XmpValue::Boolean(b) => b.to_string(),
-                XmpValue::DateTime(d) => d,
+                XmpValue::DateTime(d) => d.format(),
                 _ => format!("{:?}", value),
             })
     }
@@ -179,6 +268,7 @@ impl XmpMeta {
         &mut self,
         namespace: &str,
         struct_path: &str,
+        field_namespace: &str,
         field: &str,
         value: &str,
     ) -> Result<(), XmpError> {
@@ -186,6 +276,7 @@ impl XmpMeta {
             .set_struct_field(
                 namespace,
                 struct_path,
+                field_namespace,
                 field,
                 XmpValue::String(value.to_string()),
             )
@@ -197,10 +288,11 @@ impl XmpMeta {
         &mut self,
         namespace: &str,
         struct_path: &str,
+        field_namespace: &str,
         field: &str,
     ) -> Result<(), XmpError> {
         self.inner
-            .delete_struct_field(namespace, struct_path, field)
+            .delete_struct_field(namespace, struct_path, field_namespace, field)
             .map_err(xmp_error_to_wasm_error)
     }
 
@@ -213,4 +305,172 @@ impl XmpMeta {
     pub fn set_about_uri(&mut self, uri: &str) {
         self.inner.set_about_uri(uri);
     }
+
+    /// Get a localized text
+    ///
+    /// Returns the best match for the languages, or undefined if the
+    /// property has no text.
+    ///
+    /// # Example
+    ///
+    /// ```javascript
+    /// const title = meta.get_localized_text("http://purl.org/dc/elements/1.1/", "title", "", "x-default");
+    /// if (title) console.log(title.value, title.language);
+    /// ```
+    pub fn get_localized_text(
+        &self,
+        namespace: &str,
+        property: &str,
+        generic_lang: &str,
+        specific_lang: &str,
+    ) -> Option<LocalizedText> {
+        self.inner
+            .get_localized_text(namespace, property, generic_lang, specific_lang)
+            .map(|(value, language)| LocalizedText { value, language })
+    }
+
+    /// Set a localized text
+    pub fn set_localized_text(
+        &mut self,
+        namespace: &str,
+        property: &str,
+        generic_lang: &str,
+        specific_lang: &str,
+        value: &str,
+    ) -> Result<(), XmpError> {
+        self.inner
+            .set_localized_text(namespace, property, generic_lang, specific_lang, value)
+            .map_err(xmp_error_to_wasm_error)
+    }
+
+    /// Iterate over every node of the metadata tree
+    pub fn iter(&self) -> XmpIterator {
+        XmpIterator {
+            inner: self.inner.iter(),
+        }
+    }
+
+    /// Iterate over the metadata tree with options
+    pub fn iter_with(&self, options: &IterOptions) -> XmpIterator {
+        XmpIterator {
+            inner: self.inner.iter_with(options.inner.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::namespace::ns;
+    use crate::wasm::value::XmpValueKind;
+
+    #[test]
+    fn test_property_values() {
+        let mut meta = XmpMeta::new();
+        meta.set_property_value(ns::XMP, "Rating", &WasmXmpValue::integer(5))
+            .unwrap();
+        // Simple values are stored as text
+        let rating = meta.get_property_value(ns::XMP, "Rating").unwrap();
+        assert_eq!(rating.kind(), XmpValueKind::String);
+        assert_eq!(rating.as_string().as_deref(), Some("5"));
+        assert_eq!(meta.get_property(ns::XMP, "Rating").as_deref(), Some("5"));
+
+        meta.append_array_item_value(ns::DC, "subject", &WasmXmpValue::string("sky".into()))
+            .unwrap();
+        meta.append_array_item_value(ns::DC, "subject", &WasmXmpValue::string("sea".into()))
+            .unwrap();
+        let subject = meta.get_property_value(ns::DC, "subject").unwrap();
+        assert_eq!(subject.kind(), XmpValueKind::Array);
+        assert_eq!(subject.items().len(), 2);
+        assert_eq!(
+            meta.get_array_item_value(ns::DC, "subject", 1)
+                .and_then(|item| item.as_string())
+                .as_deref(),
+            Some("sea")
+        );
+
+        meta.inner
+            .set_struct_field(ns::EXIF, "Flash", ns::EXIF, "Fired", "False".into())
+            .unwrap();
+        let flash = meta.get_property_value(ns::EXIF, "Flash").unwrap();
+        assert_eq!(flash.kind(), XmpValueKind::Structure);
+        assert_eq!(flash.field_names(), vec!["exif:Fired"]);
+        assert_eq!(
+            flash
+                .field("exif:Fired")
+                .and_then(|field| field.as_string()),
+            Some("False".to_string())
+        );
+
+        let error = meta
+            .set_property_value(
+                ns::XMP,
+                "CreateDate",
+                &WasmXmpValue::date_time("yesterday".into()),
+            )
+            .unwrap_err();
+        assert_eq!(error.kind(), crate::wasm::XmpErrorKind::BadValue);
+        assert!(meta.get_property_value(ns::XMP, "CreateDate").is_none());
+    }
+
+    #[test]
+    fn test_localized_text() {
+        let mut meta = XmpMeta::new();
+        assert!(meta
+            .get_localized_text(ns::DC, "title", "", "x-default")
+            .is_none());
+        meta.set_localized_text(ns::DC, "title", "", "x-default", "Sunset")
+            .unwrap();
+        meta.set_localized_text(ns::DC, "title", "en", "en-US", "Sunset")
+            .unwrap();
+        meta.set_localized_text(ns::DC, "title", "fr", "fr-FR", "Coucher de soleil")
+            .unwrap();
+
+        let text = meta
+            .get_localized_text(ns::DC, "title", "fr", "fr-FR")
+            .unwrap();
+        assert_eq!(text.value, "Coucher de soleil");
+        assert_eq!(text.language, "fr-FR");
+        // The default text is used for other languages
+        let text = meta
+            .get_localized_text(ns::DC, "title", "de", "de-DE")
+            .unwrap();
+        assert_eq!(text.value, "Sunset");
+        assert_eq!(text.language, "x-default");
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut meta = XmpMeta::new();
+        meta.set_property(ns::XMP, "CreatorTool", "App").unwrap();
+        meta.set_localized_text(ns::DC, "title", "", "x-default", "Sunset")
+            .unwrap();
+
+        let mut paths = Vec::new();
+        let mut iter = meta.iter();
+        while let Some(item) = iter.next() {
+            paths.push((item.path(), item.value(), item.is_qualifier()));
+        }
+        assert!(paths.contains(&(
+            "xmp:CreatorTool".to_string(),
+            Some("App".to_string()),
+            false
+        )));
+        assert!(paths.contains(&(
+            "dc:title[1]/?xml:lang".to_string(),
+            Some("x-default".to_string()),
+            true
+        )));
+
+        let mut options = IterOptions::new();
+        options.namespace(ns::DC);
+        options.just_leaf_nodes();
+        options.omit_qualifiers();
+        let mut iter = meta.iter_with(&options);
+        let item = iter.next().unwrap();
+        assert_eq!(item.schema_ns(), ns::DC);
+        assert_eq!(item.path(), "dc:title[1]");
+        assert!(item.is_simple() && item.has_lang());
+        assert!(iter.next().is_none());
+    }
 }
@@ -32,19 +32,23 @@
 mod datetime;
 mod error;
 mod file;
+mod iter;
 mod meta;
 mod namespace;
 mod qualifier;
+mod scanner;
 mod value;
 
 pub use datetime::XmpDateTime;
 pub use error::{XmpError, XmpErrorKind};
 pub use file::{ReadOptions, XmpFile};
-pub use meta::XmpMeta;
+pub use iter::{IterOptions, XmpIterItem, XmpIterator};
+pub use meta::{LocalizedText, XmpMeta};
 pub use namespace::{
     get_all_registered_namespaces, get_builtin_namespace_uris, get_namespace_prefix,
     get_namespace_uri, is_namespace_registered, namespace_prefix, namespace_uri,
     register_namespace, Namespace,
 };
 pub use qualifier::Qualifier;
+pub use scanner::XmpScanner;
 pub use value::{XmpValue, XmpValueKind};
@@ -0,0 +1,105 @@
+//! WebAssembly bindings for incremental XMP packet scanning
+
+use crate::files::file::{PacketLocation, PacketScanner};
+use crate::wasm::meta::XmpMeta;
+use crate::XmpMeta as RustXmpMeta;
+use wasm_bindgen::prelude::*;
+
+/// Incremental XMP packet scanner (WebAssembly)
+///
+/// Finds the first XMP packet of a file fed in chunks, so that large files
+/// (such as videos) never have to be copied into WebAssembly memory at
+/// once. Only the data from the start of a candidate packet onwards is kept.
+///
+/// # Example
+///
+/// ```javascript
+/// import init, { XmpScanner } from './pkg/xmpkit.js';
+/// await init();
+///
+/// const scanner = new XmpScanner();
+/// const reader = blob.stream().getReader();
+/// let meta;
+/// for (;;) {
+///     const { done, value } = await reader.read();
+///     meta = done ? scanner.finish() : scanner.push(value);
+///     if (done || meta) break;
+/// }
+/// await reader.cancel();
+/// if (meta) console.log(scanner.packet_offset, scanner.packet_length);
+/// ```
+#[wasm_bindgen]
+pub struct XmpScanner {
+    inner: PacketScanner,
+    /// Offset and length of the packet, once found
+    packet: Option<(usize, usize)>,
+}
+
+impl Default for XmpScanner {
+    fn default() -> Self {
+        XmpScanner {
+            inner: PacketScanner::new(),
+            packet: None,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl XmpScanner {
+    /// Create a new scanner
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> XmpScanner {
+        XmpScanner::default()
+    }
+
+    /// Scan the next chunk of the file
+    ///
+    /// Returns the metadata once the chunk containing the end of the packet
+    /// has been pushed, or undefined. Chunks pushed after the packet was
+    /// found are ignored.
+    pub fn push(&mut self, chunk: &[u8]) -> Option<XmpMeta> {
+        if self.is_done() {
+            return None;
+        }
+        let found = self.inner.push(chunk);
+        self.found(found)
+    }
+
+    /// Scan the data left at the end of the file
+    ///
+    /// Returns the metadata of a packet found in the remaining data, or
+    /// undefined.
+    pub fn finish(&mut self) -> Option<XmpMeta> {
+        if self.is_done() {
+            return None;
+        }
+        let found = self.inner.finish();
+        self.found(found)
+    }
+
+    /// Whether the packet has been found
+    #[wasm_bindgen(getter)]
+    pub fn is_done(&self) -> bool {
+        self.packet.is_some()
+    }
+
+    /// Byte offset of the packet in the file, once found
+    #[wasm_bindgen(getter)]
+    pub fn packet_offset(&self) -> Option<usize> {
+        self.packet.map(|(offset, _)| offset)
+    }
+
+    /// Length of the packet in bytes, once found
+    #[wasm_bindgen(getter)]
+    pub fn packet_length(&self) -> Option<usize> {
+        self.packet.map(|(_, length)| length)
+    }
+}
+
+impl XmpScanner {
+    fn found(&mut self, found: Option<(PacketLocation, RustXmpMeta)>) -> Option<XmpMeta> {
+        let (location, meta) = found?;
+        self.packet = Some((location.start, location.end - location.start));
+        Some(XmpMeta { inner: meta })
+    }
+}
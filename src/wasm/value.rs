@@ -1,5 +1,8 @@
 //! WebAssembly bindings for XMP value types
 
+use crate::core::error::XmpResult;
+use crate::utils::datetime::XmpDateTime as RustXmpDateTime;
+use crate::XmpValue as RustXmpValue;
 use wasm_bindgen::prelude::*;
 
 /// XMP value type kind
@@ -14,6 +17,12 @@ pub enum XmpValueKind {
     Boolean,
     /// Date/time value
     DateTime,
+    /// URI value
+    Uri,
+    /// Array of values
+    Array,
+    /// Structure (named fields)
+    Structure,
 }
 
 /// XMP property value types
@@ -26,6 +35,8 @@ pub struct XmpValue {
     string_value: Option<String>,
     integer_value: Option<i64>,
     boolean_value: Option<bool>,
+    items: Vec<XmpValue>,
+    fields: Vec<(String, XmpValue)>,
 }
 
 #[wasm_bindgen]
@@ -34,43 +45,58 @@ impl XmpValue {
     #[wasm_bindgen(constructor)]
     pub fn string(s: String) -> XmpValue {
         XmpValue {
-            kind: XmpValueKind::String,
             string_value: Some(s),
-            integer_value: None,
-            boolean_value: None,
+            ..XmpValue::empty(XmpValueKind::String)
         }
     }
 
     /// Create an integer value
     pub fn integer(i: i64) -> XmpValue {
         XmpValue {
-            kind: XmpValueKind::Integer,
-            string_value: None,
             integer_value: Some(i),
-            boolean_value: None,
+            ..XmpValue::empty(XmpValueKind::Integer)
         }
     }
 
     /// Create a boolean value
     pub fn boolean(b: bool) -> XmpValue {
         XmpValue {
-            kind: XmpValueKind::Boolean,
-            string_value: None,
-            integer_value: None,
             boolean_value: Some(b),
+            ..XmpValue::empty(XmpValueKind::Boolean)
         }
     }
 
     /// Create a date/time value
     pub fn date_time(dt: String) -> XmpValue {
         XmpValue {
-            kind: XmpValueKind::DateTime,
             string_value: Some(dt),
-            integer_value: None,
-            boolean_value: None,
+            ..XmpValue::empty(XmpValueKind::DateTime)
+        }
+    }
+
+    /// Create a URI value
+    pub fn uri(uri: String) -> XmpValue {
+        XmpValue {
+            string_value: Some(uri),
+            ..XmpValue::empty(XmpValueKind::Uri)
+        }
+    }
+
+    /// Create an array value
+    pub fn array(items: Vec<XmpValue>) -> XmpValue {
+        XmpValue {
+            items,
+            ..XmpValue::empty(XmpValueKind::Array)
         }
     }
 
+    /// Create an empty structure value
+    ///
+    /// Add fields with `set_field`.
+    pub fn structure() -> XmpValue {
+        XmpValue::empty(XmpValueKind::Structure)
+    }
+
     /// Get the value kind
     #[wasm_bindgen(getter)]
     pub fn kind(&self) -> XmpValueKind {
@@ -100,4 +126,173 @@ impl XmpValue {
             None
         }
     }
+
+    /// Get the value as a URI, if it is a URI type
+    pub fn as_uri(&self) -> Option<String> {
+        if self.kind == XmpValueKind::Uri {
+            self.string_value.clone()
+        } else {
+            None
+        }
+    }
+
+    /// Get the items, if it is an array type
+    pub fn items(&self) -> Vec<XmpValue> {
+        self.items.clone()
+    }
+
+    /// Get the field names, if it is a structure type
+    pub fn field_names(&self) -> Vec<String> {
+        self.fields.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    /// Get a field, if it is a structure type with this field
+    pub fn field(&self, name: &str) -> Option<XmpValue> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.clone())
+    }
+
+    /// Set a field of a structure value, replacing any field with this name
+    pub fn set_field(&mut self, name: String, value: XmpValue) {
+        match self.fields.iter_mut().find(|(field, _)| *field == name) {
+            Some((_, field)) => *field = value,
+            None => self.fields.push((name, value)),
+        }
+    }
+}
+
+impl XmpValue {
+    fn empty(kind: XmpValueKind) -> XmpValue {
+        XmpValue {
+            kind,
+            string_value: None,
+            integer_value: None,
+            boolean_value: None,
+            items: Vec::new(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Convert a Rust value
+    pub(crate) fn from_rust(value: RustXmpValue) -> XmpValue {
+        match value {
+            RustXmpValue::String(s) => XmpValue::string(s),
+            RustXmpValue::Integer(i) => XmpValue::integer(i),
+            RustXmpValue::Boolean(b) => XmpValue::boolean(b),
+            RustXmpValue::DateTime(dt) => XmpValue::date_time(dt.format()),
+            RustXmpValue::Uri(uri) => XmpValue::uri(uri),
+            RustXmpValue::Array(items) => {
+                XmpValue::array(items.into_iter().map(XmpValue::from_rust).collect())
+            }
+            RustXmpValue::Structure(fields) => {
+                let mut fields: Vec<_> = fields
+                    .into_iter()
+                    .map(|(name, value)| (name, XmpValue::from_rust(value)))
+                    .collect();
+                fields.sort_by(|a, b| a.0.cmp(&b.0));
+                XmpValue {
+                    fields,
+                    ..XmpValue::empty(XmpValueKind::Structure)
+                }
+            }
+        }
+    }
+
+    /// Convert to a Rust value
+    ///
+    /// Fails if a date/time value is not a valid XMP date/time string.
+    pub(crate) fn to_rust(&self) -> XmpResult<RustXmpValue> {
+        let value = match self.kind {
+            XmpValueKind::String => {
+                RustXmpValue::String(self.string_value.clone().unwrap_or_default())
+            }
+            XmpValueKind::Integer => RustXmpValue::Integer(self.integer_value.unwrap_or_default()),
+            XmpValueKind::Boolean => RustXmpValue::Boolean(self.boolean_value.unwrap_or_default()),
+            XmpValueKind::DateTime => {
+                let dt = self.string_value.as_deref().unwrap_or_default();
+                RustXmpValue::DateTime(RustXmpDateTime::parse(dt)?)
+            }
+            XmpValueKind::Uri => RustXmpValue::Uri(self.string_value.clone().unwrap_or_default()),
+            XmpValueKind::Array => RustXmpValue::Array(
+                self.items
+                    .iter()
+                    .map(XmpValue::to_rust)
+                    .collect::<XmpResult<_>>()?,
+            ),
+            XmpValueKind::Structure => RustXmpValue::Structure(
+                self.fields
+                    .iter()
+                    .map(|(name, value)| Ok((name.clone(), value.to_rust()?)))
+                    .collect::<XmpResult<_>>()?,
+            ),
+        };
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_from_rust() {
+        let mut fields = HashMap::new();
+        fields.insert("b".to_string(), RustXmpValue::Integer(2));
+        fields.insert("a".to_string(), RustXmpValue::Boolean(true));
+        let value = XmpValue::from_rust(RustXmpValue::Structure(fields));
+        assert_eq!(value.kind(), XmpValueKind::Structure);
+        // Fields are sorted by name
+        assert_eq!(value.field_names(), vec!["a", "b"]);
+        assert_eq!(value.field("a").unwrap().as_boolean(), Some(true));
+        assert_eq!(value.field("b").unwrap().as_integer(), Some(2));
+        assert!(value.field("c").is_none());
+
+        let dt = RustXmpDateTime::parse("2024-05-06T07:08:09Z").unwrap();
+        let value = XmpValue::from_rust(RustXmpValue::Array(vec![
+            RustXmpValue::DateTime(dt),
+            RustXmpValue::Uri("http://example.com/".to_string()),
+        ]));
+        let items = value.items();
+        assert_eq!(value.kind(), XmpValueKind::Array);
+        assert_eq!(
+            items[0].as_date_time().as_deref(),
+            Some("2024-05-06T07:08:09Z")
+        );
+        assert_eq!(items[1].as_uri().as_deref(), Some("http://example.com/"));
+        // Strings are not URIs or dates
+        assert!(XmpValue::string("x".to_string()).as_uri().is_none());
+        assert!(XmpValue::string("x".to_string()).as_date_time().is_none());
+    }
+
+    #[test]
+    fn test_to_rust() {
+        let mut value = XmpValue::structure();
+        value.set_field("count".to_string(), XmpValue::integer(1));
+        value.set_field("count".to_string(), XmpValue::integer(3));
+        value.set_field(
+            "tags".to_string(),
+            XmpValue::array(vec![XmpValue::string("sky".to_string())]),
+        );
+        assert_eq!(value.field_names(), vec!["count", "tags"]);
+
+        let mut fields = HashMap::new();
+        fields.insert("count".to_string(), RustXmpValue::Integer(3));
+        fields.insert(
+            "tags".to_string(),
+            RustXmpValue::Array(vec![RustXmpValue::String("sky".to_string())]),
+        );
+        assert_eq!(value.to_rust().unwrap(), RustXmpValue::Structure(fields));
+
+        let dt = XmpValue::date_time("2024-05-06".to_string()).to_rust();
+        assert_eq!(
+            dt.unwrap(),
+            RustXmpValue::DateTime(RustXmpDateTime::parse("2024-05-06").unwrap())
+        );
+        assert!(XmpValue::date_time("not a date".to_string())
+            .to_rust()
+            .is_err());
+    }
 }
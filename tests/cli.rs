@@ -0,0 +1,57 @@
+//! Tests for the xmpkit command line tool
+
+#![cfg(feature = "cli")]
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn xmpkit(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_xmpkit"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn set_get_and_copy() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("sub")).unwrap();
+    let packet = xmpkit::XmpMeta::new().serialize_packet().unwrap();
+    std::fs::write(dir.path().join("a.xmp"), &packet).unwrap();
+    std::fs::write(dir.path().join("sub/b.xmp"), &packet).unwrap();
+
+    stdout(&xmpkit(
+        dir.path(),
+        &["set", "a.xmp", "dc:title", "Title", "--lang", "x-default"],
+    ));
+    assert_eq!(
+        stdout(&xmpkit(dir.path(), &["get", "a.xmp", "dc:title"])),
+        "Title\n"
+    );
+    stdout(&xmpkit(
+        dir.path(),
+        &["set", "--recursive", ".", "xmp:Rating", "5"],
+    ));
+    assert_eq!(
+        stdout(&xmpkit(dir.path(), &["get", "-r", "*.xmp", "xmp:Rating"])),
+        "./a.xmp:\n  5\n./sub/b.xmp:\n  5\n"
+    );
+
+    stdout(&xmpkit(
+        dir.path(),
+        &["copy", "--from", "a.xmp", "--to", "sub/b.xmp"],
+    ));
+    let json = stdout(&xmpkit(dir.path(), &["dump", "--json", "sub/b.xmp"]));
+    assert!(json.contains("\"Title\""));
+
+    let missing = xmpkit(dir.path(), &["get", "a.xmp", "dc:creator"]);
+    assert!(!missing.status.success());
+    let unknown = xmpkit(dir.path(), &["get", "a.xmp", "nope:title"]);
+    assert!(String::from_utf8_lossy(&unknown.stderr).contains("Unknown namespace prefix"));
+}
@@ -2,6 +2,7 @@
 //!
 //! These tests verify file operations work correctly.
 
+#[allow(clippy::duplicate_mod)]
 #[path = "fixtures/mod.rs"]
 mod fixtures;
 
@@ -29,6 +30,24 @@ mod native_tests {
         }
     }
 
+    #[test]
+    fn open_psd() {
+        if !fixture_exists("Purple Square.psd") {
+            eprintln!("Skipping test: fixture Purple Square.psd not found");
+            return;
+        }
+
+        let mut file = XmpFile::new();
+        file.open(fixture_path("Purple Square.psd")).unwrap();
+        let meta = file.get_xmp().expect("PSD fixture has XMP");
+        assert_eq!(
+            meta.get_property("http://purl.org/dc/elements/1.1/", "format"),
+            Some(xmpkit::XmpValue::String(
+                "application/vnd.adobe.photoshop".to_string()
+            ))
+        );
+    }
+
     #[test]
     fn file_not_found() {
         let mut file = XmpFile::new();
@@ -63,3 +82,68 @@ mod wasm_compatible_tests {
         assert!(file.get_xmp().is_some());
     }
 }
+
+#[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+mod async_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn open_and_save_async() {
+        if !fixture_exists("image2.jpg") {
+            eprintln!("Skipping test: fixture image2.jpg not found");
+            return;
+        }
+
+        let mut file = XmpFile::new();
+        file.open_async(fixture_path("image2.jpg")).await.unwrap();
+
+        let mut meta = file.get_xmp().cloned().unwrap_or_default();
+        meta.set_property(xmpkit::ns::XMP, "CreatorTool", "Async".into())
+            .unwrap();
+        file.put_xmp(meta);
+
+        let tempdir = tempfile::tempdir().unwrap();
+        let output = tempdir.path().join("image2.jpg");
+        file.save_async(&output).await.unwrap();
+
+        let mut reread = XmpFile::new();
+        reread.open(&output).unwrap();
+        assert_eq!(
+            reread
+                .get_xmp()
+                .unwrap()
+                .get_property(xmpkit::ns::XMP, "CreatorTool"),
+            Some("Async".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn async_reader_and_writer() {
+        if !fixture_exists("image2.jpg") {
+            eprintln!("Skipping test: fixture image2.jpg not found");
+            return;
+        }
+
+        let data = std::fs::read(fixture_path("image2.jpg")).unwrap();
+        let mut file = XmpFile::new();
+        file.from_async_reader(data.as_slice()).await.unwrap();
+
+        let mut meta = file.get_xmp().cloned().unwrap_or_default();
+        meta.set_property(xmpkit::ns::XMP, "CreatorTool", "Async".into())
+            .unwrap();
+        file.put_xmp(meta);
+
+        let mut output = Vec::new();
+        file.write_to_async_writer(&mut output).await.unwrap();
+
+        let mut reread = XmpFile::new();
+        reread.from_bytes(&output).unwrap();
+        assert_eq!(
+            reread
+                .get_xmp()
+                .unwrap()
+                .get_property(xmpkit::ns::XMP, "CreatorTool"),
+            Some("Async".into())
+        );
+    }
+}
@@ -0,0 +1,449 @@
+//! Swift and Kotlin bindings for xmpkit, generated with UniFFI
+//!
+//! This crate exports `XmpFile` and `XmpMeta` objects mirroring the Rust
+//! API, from which `uniffi-bindgen` generates Swift and Kotlin code, with
+//! no hand-written bridging. See `README.md` for building an XCFramework
+//! for iOS and macOS.
+//!
+//! ```swift
+//! import XmpKit
+//!
+//! let file = XmpFile()
+//! try file.open(path: url.path, options: ReadOptions())
+//! let meta = file.getXmp() ?? XmpMeta()
+//! try meta.setProperty(namespace: nsXmp(), path: "CreatorTool", value: "MyApp")
+//! file.putXmp(meta: meta)
+//! try file.save(path: url.path, options: SaveOptions(atomic: true))
+//! ```
+//!
+//! Objects are reference counted by the foreign language and can be
+//! shared by threads; their methods lock the Rust value.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use xmpkit::XmpValue;
+
+uniffi::setup_scaffolding!();
+
+/// Error of an XMP operation, one case per `xmpkit::XmpError` variant
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum XmpError {
+    /// An argument is invalid
+    #[error("Bad parameter: {message}")]
+    BadParam { message: String },
+    /// A value is invalid
+    #[error("Bad value: {message}")]
+    BadValue { message: String },
+    /// Unknown namespace or invalid schema
+    #[error("Bad schema: {message}")]
+    BadSchema { message: String },
+    /// Invalid property path
+    #[error("Bad XPath: {message}")]
+    BadXPath { message: String },
+    /// The XMP or the file could not be parsed
+    #[error("Parse error: {message}")]
+    ParseError { message: String },
+    /// The XMP could not be serialized
+    #[error("Serialization error: {message}")]
+    SerializationError { message: String },
+    /// Reading or writing a file failed
+    #[error("IO error: {message}")]
+    IoError { message: String },
+    /// Internal error
+    #[error("Internal error: {message}")]
+    InternalError { message: String },
+    /// The requested data was not found
+    #[error("Resource not found: {message}")]
+    NotFound { message: String },
+    /// The operation is not supported, for example for the file format
+    #[error("Operation not supported: {message}")]
+    NotSupported { message: String },
+    /// The operation was cancelled
+    #[error("Operation cancelled")]
+    Cancelled,
+}
+
+impl From<xmpkit::XmpError> for XmpError {
+    fn from(error: xmpkit::XmpError) -> Self {
+        use xmpkit::XmpError as E;
+        match error {
+            E::BadParam(message) => XmpError::BadParam { message },
+            E::BadValue(message) => XmpError::BadValue { message },
+            E::BadSchema(message) => XmpError::BadSchema { message },
+            E::BadXPath(message) => XmpError::BadXPath { message },
+            E::ParseError(message) => XmpError::ParseError { message },
+            E::SerializationError(message) => XmpError::SerializationError { message },
+            E::IoError(io_err) => XmpError::IoError {
+                message: io_err.to_string(),
+            },
+            E::InternalError(message) => XmpError::InternalError { message },
+            E::NotFound(message) => XmpError::NotFound { message },
+            E::NotSupported(message) => XmpError::NotSupported { message },
+            E::Cancelled => XmpError::Cancelled,
+        }
+    }
+}
+
+/// Result of the exported functions
+type Result<T> = std::result::Result<T, XmpError>;
+
+/// Lock a value, ignoring a panic of a previous holder
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Convert a property value to the string returned to the foreign code
+fn value_string(value: XmpValue) -> String {
+    match value {
+        XmpValue::String(s) | XmpValue::Uri(s) => s,
+        XmpValue::Integer(i) => i.to_string(),
+        XmpValue::Boolean(b) => b.to_string(),
+        XmpValue::DateTime(d) => d.format(),
+        _ => format!("{:?}", value),
+    }
+}
+
+/// Get the Dublin Core namespace URI
+#[uniffi::export]
+pub fn ns_dc() -> String {
+    xmpkit::ns::DC.to_string()
+}
+
+/// Get the XMP basic namespace URI
+#[uniffi::export]
+pub fn ns_xmp() -> String {
+    xmpkit::ns::XMP.to_string()
+}
+
+/// Register a namespace URI with a preferred prefix
+#[uniffi::export]
+pub fn register_namespace(uri: String, prefix: String) -> Result<()> {
+    Ok(xmpkit::register_namespace(&uri, &prefix)?)
+}
+
+/// A localized text and its language
+#[derive(uniffi::Record)]
+pub struct LocalizedText {
+    /// The text
+    pub value: String,
+    /// The language of the text, such as `en-US` or `x-default`
+    pub language: String,
+}
+
+/// XMP metadata
+#[derive(uniffi::Object)]
+pub struct XmpMeta {
+    inner: Mutex<xmpkit::XmpMeta>,
+}
+
+impl XmpMeta {
+    fn wrap(meta: xmpkit::XmpMeta) -> Arc<Self> {
+        Arc::new(Self {
+            inner: Mutex::new(meta),
+        })
+    }
+}
+
+#[uniffi::export]
+impl XmpMeta {
+    /// Create empty metadata
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        Self::wrap(xmpkit::XmpMeta::new())
+    }
+
+    /// Parse an XMP packet or RDF/XML document
+    #[uniffi::constructor]
+    pub fn parse(xmp_packet: String) -> Result<Arc<Self>> {
+        Ok(Self::wrap(xmpkit::XmpMeta::parse(&xmp_packet)?))
+    }
+
+    /// Get the value of a simple property, or nil if it doesn't exist
+    pub fn get_property(&self, namespace: String, path: String) -> Option<String> {
+        lock(&self.inner)
+            .get_property(&namespace, &path)
+            .map(value_string)
+    }
+
+    /// Set the value of a simple property, creating it if needed
+    pub fn set_property(&self, namespace: String, path: String, value: String) -> Result<()> {
+        lock(&self.inner).set_property(&namespace, &path, XmpValue::String(value))?;
+        Ok(())
+    }
+
+    /// Check if a property exists
+    pub fn has_property(&self, namespace: String, path: String) -> bool {
+        lock(&self.inner).has_property(&namespace, &path)
+    }
+
+    /// Delete a property and its children
+    pub fn delete_property(&self, namespace: String, path: String) -> Result<()> {
+        lock(&self.inner).delete_property(&namespace, &path)?;
+        Ok(())
+    }
+
+    /// Count the items of an array property, 0 if it doesn't exist
+    pub fn count_array_items(&self, namespace: String, path: String) -> u32 {
+        lock(&self.inner).count_array_items(&namespace, &path) as u32
+    }
+
+    /// Get the values of the simple items of an array property
+    pub fn get_array_items(&self, namespace: String, path: String) -> Vec<String> {
+        let meta = lock(&self.inner);
+        (0..meta.count_array_items(&namespace, &path))
+            .filter_map(|index| meta.get_array_item(&namespace, &path, index))
+            .map(value_string)
+            .collect()
+    }
+
+    /// Append an item to an array property, creating an unordered array
+    /// if needed
+    pub fn append_array_item(&self, namespace: String, path: String, value: String) -> Result<()> {
+        lock(&self.inner).append_array_item(&namespace, &path, XmpValue::String(value))?;
+        Ok(())
+    }
+
+    /// Delete the array item at `index` (0-based)
+    pub fn delete_array_item(&self, namespace: String, path: String, index: u32) -> Result<()> {
+        lock(&self.inner).delete_array_item(&namespace, &path, index as usize)?;
+        Ok(())
+    }
+
+    /// Get a localized text in the language that best matches
+    /// `specific_lang` (such as `en-US`) or `generic_lang` (such as `en`,
+    /// or an empty string)
+    pub fn get_localized_text(
+        &self,
+        namespace: String,
+        path: String,
+        generic_lang: String,
+        specific_lang: String,
+    ) -> Option<LocalizedText> {
+        lock(&self.inner)
+            .get_localized_text(&namespace, &path, &generic_lang, &specific_lang)
+            .map(|(value, language)| LocalizedText { value, language })
+    }
+
+    /// Set a localized text, creating the language alternative if needed
+    pub fn set_localized_text(
+        &self,
+        namespace: String,
+        path: String,
+        generic_lang: String,
+        specific_lang: String,
+        value: String,
+    ) -> Result<()> {
+        lock(&self.inner).set_localized_text(
+            &namespace,
+            &path,
+            &generic_lang,
+            &specific_lang,
+            &value,
+        )?;
+        Ok(())
+    }
+
+    /// Serialize to RDF/XML
+    pub fn serialize(&self) -> Result<String> {
+        Ok(lock(&self.inner).serialize()?)
+    }
+
+    /// Serialize to an XMP packet
+    pub fn serialize_packet(&self) -> Result<String> {
+        Ok(lock(&self.inner).serialize_packet()?)
+    }
+}
+
+/// Whether an option is set, and the builder method setting it
+type Flag<T> = (bool, fn(T) -> T);
+
+/// Build options from the builder methods of the options that are set
+fn build<T: Default, const N: usize>(flags: [Flag<T>; N]) -> T {
+    flags
+        .into_iter()
+        .filter(|(set, _)| *set)
+        .fold(T::default(), |options, (_, builder)| builder(options))
+}
+
+/// Options for opening files, see `xmpkit::ReadOptions`
+#[derive(uniffi::Record)]
+pub struct ReadOptions {
+    /// Open the file for update, written by `XmpFile.close`
+    #[uniffi(default = false)]
+    pub for_update: bool,
+    /// Only the XMP is wanted (allows optimizations)
+    #[uniffi(default = false)]
+    pub only_xmp: bool,
+    /// Be strict about only attempting to use the designated file handler
+    #[uniffi(default = false)]
+    pub strict: bool,
+    /// Force packet scanning (do not use smart handler)
+    #[uniffi(default = false)]
+    pub use_packet_scanning: bool,
+    /// Attempt to repair a damaged file
+    #[uniffi(default = false)]
+    pub repair_file: bool,
+}
+
+impl From<ReadOptions> for xmpkit::ReadOptions {
+    fn from(options: ReadOptions) -> Self {
+        build([
+            (options.for_update, Self::for_update),
+            (options.only_xmp, Self::only_xmp),
+            (options.strict, Self::strict),
+            (options.use_packet_scanning, Self::use_packet_scanning),
+            (options.repair_file, Self::repair_file),
+        ])
+    }
+}
+
+/// Options for saving files, see `xmpkit::SaveOptions`
+#[derive(uniffi::Record)]
+pub struct SaveOptions {
+    /// Write a new file and rename it over the target, so that the target
+    /// is left untouched if saving fails
+    #[uniffi(default = false)]
+    pub atomic: bool,
+    /// Keep the modification time of the target
+    #[uniffi(default = false)]
+    pub keep_modification_time: bool,
+    /// Also remove the native metadata, such as Exif and IPTC
+    #[uniffi(default = false)]
+    pub strip_all_metadata: bool,
+}
+
+impl From<SaveOptions> for xmpkit::SaveOptions {
+    fn from(options: SaveOptions) -> Self {
+        build([
+            (options.atomic, Self::atomic),
+            (options.keep_modification_time, Self::keep_modification_time),
+            (options.strip_all_metadata, Self::strip_all_metadata),
+        ])
+    }
+}
+
+/// File whose XMP metadata is read and written
+#[derive(uniffi::Object)]
+pub struct XmpFile {
+    inner: Mutex<xmpkit::XmpFile>,
+}
+
+#[uniffi::export]
+impl XmpFile {
+    /// Create a file object, to open with `open` or `from_bytes`
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            inner: Mutex::new(xmpkit::XmpFile::new()),
+        })
+    }
+
+    /// Open a file and read its metadata
+    pub fn open(&self, path: String, options: ReadOptions) -> Result<()> {
+        lock(&self.inner).open_with(path, options.into())?;
+        Ok(())
+    }
+
+    /// Read the metadata of a file in memory
+    pub fn from_bytes(&self, data: Vec<u8>, options: ReadOptions) -> Result<()> {
+        lock(&self.inner).from_bytes_with(&data, options.into())?;
+        Ok(())
+    }
+
+    /// Get the metadata, or nil if the file has none
+    ///
+    /// The metadata shares its properties with the file, so that changes
+    /// to it are saved with the file.
+    pub fn get_xmp(&self) -> Option<Arc<XmpMeta>> {
+        lock(&self.inner).get_xmp().cloned().map(XmpMeta::wrap)
+    }
+
+    /// Replace the metadata
+    pub fn put_xmp(&self, meta: Arc<XmpMeta>) {
+        let meta = lock(&meta.inner).clone();
+        lock(&self.inner).put_xmp(meta);
+    }
+
+    /// Remove the metadata when the file is saved
+    pub fn remove_xmp(&self) {
+        lock(&self.inner).remove_xmp();
+    }
+
+    /// Save the file with its metadata to `path`
+    pub fn save(&self, path: String, options: SaveOptions) -> Result<()> {
+        lock(&self.inner).save_with(path, options.into())?;
+        Ok(())
+    }
+
+    /// Write the file with its metadata to memory
+    pub fn write_to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(lock(&self.inner).write_to_bytes()?)
+    }
+
+    /// Close the file, writing it if it was opened for update
+    pub fn close(&self) -> Result<()> {
+        lock(&self.inner).try_close()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meta_round_trip() {
+        let meta = XmpMeta::new();
+        meta.set_property(ns_xmp(), "CreatorTool".into(), "MyApp".into())
+            .unwrap();
+        meta.set_localized_text(
+            ns_dc(),
+            "title".into(),
+            String::new(),
+            "x-default".into(),
+            "Title".into(),
+        )
+        .unwrap();
+        for keyword in ["one", "two"] {
+            meta.append_array_item(ns_dc(), "subject".into(), keyword.into())
+                .unwrap();
+        }
+
+        let parsed = XmpMeta::parse(meta.serialize_packet().unwrap()).unwrap();
+        assert_eq!(
+            parsed.get_property(ns_xmp(), "CreatorTool".into()),
+            Some("MyApp".to_string())
+        );
+        assert_eq!(
+            parsed.get_array_items(ns_dc(), "subject".into()),
+            vec!["one".to_string(), "two".to_string()]
+        );
+        let title = parsed
+            .get_localized_text(ns_dc(), "title".into(), "en".into(), "en-US".into())
+            .unwrap();
+        assert_eq!(
+            (title.value.as_str(), title.language.as_str()),
+            ("Title", "x-default")
+        );
+        assert!(matches!(
+            XmpMeta::parse("<x:xmpmeta".into()),
+            Err(XmpError::ParseError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_file_errors() {
+        let file = XmpFile::new();
+        let options = ReadOptions {
+            for_update: false,
+            only_xmp: true,
+            strict: false,
+            use_packet_scanning: false,
+            repair_file: false,
+        };
+        assert!(file.from_bytes(vec![1, 2, 3], options).is_err());
+        assert!(file.get_xmp().is_none());
+    }
+}
@@ -0,0 +1,5 @@
+//! Generate the Swift and Kotlin bindings, see README.md
+
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}